@@ -27,13 +27,10 @@ probing tracing 入门
 查看最近 span（另开终端）::
 
     probing -t <pid> query "
-      SELECT s.name, s.phase,
-             round((e.time - s.time) / 1e6, 2) AS ms
-      FROM python.trace_event s
-      JOIN python.trace_event e
-        ON s.span_id = e.span_id AND e.record_type = 'span_end'
-      WHERE s.record_type = 'span_start'
-      ORDER BY s.time DESC LIMIT 12"
+      SELECT name, phase, round(duration / 1e6, 2) AS ms
+      FROM python.trace_event
+      WHERE record_type = 'span'
+      ORDER BY time DESC LIMIT 12"
 """
 
 from __future__ import annotations
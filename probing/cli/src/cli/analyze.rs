@@ -0,0 +1,251 @@
+//! `probing analyze`: move trace archives between processes.
+//!
+//! `--dump FILE` streams `GET /apis/trace/dump` from the target to disk;
+//! `--import FILE` replays an archive into the target under its own catalog
+//! (admin token required, see `PROBING_AUTH_TOKEN`). With `--offline` the archive is only
+//! checked and summarized locally, no target needed.
+//!
+//! `--import` also takes a `probing.trace.autosave` directory: the segments
+//! its manifest lists are decompressed and merged into one archive first.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use probing_proto::prelude::{AutosaveManifest, TraceArchive, TraceImportSummary};
+use probing_proto::protocol::trace_archive::{
+    archive_version, AUTOSAVE_MANIFEST, DEFAULT_REPLAY_NAMESPACE,
+};
+
+use crate::cli::ctrl::{download, request_bytes, ProbeEndpoint};
+
+#[derive(Args, Debug, Clone)]
+#[command(group(clap::ArgGroup::new("action").required(true).args(["dump", "import"])))]
+pub struct AnalyzeCommand {
+    /// Write the target's trace archive (spans, events, metrics) to FILE
+    #[arg(long, value_name = "FILE")]
+    pub dump: Option<String>,
+
+    /// Load the trace archive FILE (or autosave directory) into the target for replay
+    #[arg(long, value_name = "FILE")]
+    pub import: Option<String>,
+
+    /// Catalog imported tables are registered under, e.g. `replay.python.trace_event`
+    #[arg(long, default_value = DEFAULT_REPLAY_NAMESPACE, requires = "import")]
+    pub namespace: String,
+
+    /// Only validate and summarize the archive locally (no target)
+    #[arg(long, requires = "import")]
+    pub offline: bool,
+}
+
+impl AnalyzeCommand {
+    /// `--import --offline` runs without a target.
+    pub fn is_offline(&self) -> bool {
+        self.offline && self.import.is_some()
+    }
+
+    pub fn run_offline(&self) -> Result<()> {
+        let path = self.import.as_deref().unwrap_or_default();
+        let (_, archive) = read_archive(path)?;
+        print!("{}", summarize(&archive));
+        Ok(())
+    }
+
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        if let Some(path) = &self.dump {
+            let written = dump_archive(ctrl, path).await?;
+            eprintln!("wrote {written} bytes to {path}");
+            return Ok(());
+        }
+
+        let path = self.import.as_deref().unwrap_or_default();
+        if self.offline {
+            return self.run_offline();
+        }
+        // Validate locally first so version mismatches are reported before upload.
+        let (bytes, _) = read_archive(path)?;
+        let url = format!("/apis/trace/import?namespace={}", self.namespace);
+        let reply = request_bytes(ctrl, &url, bytes).await?;
+        let summary: TraceImportSummary = serde_json::from_slice(&reply).map_err(|_| {
+            anyhow::anyhow!("trace import failed: {}", String::from_utf8_lossy(&reply))
+        })?;
+        println!(
+            "imported {} rows into namespace `{}`:",
+            summary.rows, summary.namespace
+        );
+        for table in summary.tables {
+            println!("  {table}");
+        }
+        Ok(())
+    }
+}
+
+/// Stream the target's trace archive to `path` and check its header; returns
+/// the bytes written. A reply that is not an archive is deleted again.
+pub async fn dump_archive(ctrl: ProbeEndpoint, path: &str) -> Result<u64> {
+    // Streamed straight to disk; error replies are non-2xx and never reach
+    // the file.
+    let written = download(ctrl, "/apis/trace/dump", path).await?;
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|f| f.take(16).read_to_end(&mut head))
+        .with_context(|| format!("failed to read back {path}"))?;
+    if archive_version(&head).is_err() {
+        let _ = std::fs::remove_file(path);
+        anyhow::bail!("trace dump failed: target did not return a trace archive");
+    }
+    Ok(written)
+}
+
+/// Read an archive file, zstd-compressed or not, or an autosave directory;
+/// returns the encoded archive along with it.
+pub(crate) fn read_archive(path: &str) -> Result<(Vec<u8>, TraceArchive)> {
+    if Path::new(path).is_dir() {
+        let archive = read_autosave_dir(Path::new(path))?;
+        return Ok((archive.encode()?, archive));
+    }
+    let bytes = read_segment(Path::new(path))?;
+    let archive = TraceArchive::decode(&bytes)
+        .with_context(|| format!("{path} is not a usable trace archive"))?;
+    Ok((bytes, archive))
+}
+
+/// Archive bytes of one file, decompressed if it is zstd.
+fn read_segment(path: &Path) -> Result<Vec<u8>> {
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return Ok(bytes);
+    }
+    zstd::decode_all(bytes.as_slice())
+        .with_context(|| format!("{} is not valid zstd data", path.display()))
+}
+
+/// Merge the segments listed in `dir`'s manifest, oldest first.
+fn read_autosave_dir(dir: &Path) -> Result<TraceArchive> {
+    let manifest_path = dir.join(AUTOSAVE_MANIFEST);
+    let manifest: AutosaveManifest = serde_json::from_slice(
+        &std::fs::read(&manifest_path)
+            .with_context(|| format!("{} has no {AUTOSAVE_MANIFEST}", dir.display()))?,
+    )
+    .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let mut parts = Vec::with_capacity(manifest.segments.len());
+    for segment in &manifest.segments {
+        let path = dir.join(&segment.file);
+        let archive = TraceArchive::decode(&read_segment(&path)?)
+            .with_context(|| format!("{} is not a usable trace archive", path.display()))?;
+        parts.push(archive);
+    }
+    if parts.is_empty() {
+        anyhow::bail!("{} lists no segments yet", manifest_path.display());
+    }
+    Ok(TraceArchive::merge(parts))
+}
+
+fn summarize(archive: &TraceArchive) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "clock anchor: {} ({})",
+        archive.clock.wall_ns, archive.clock.time_base
+    );
+    for (key, value) in &archive.resource {
+        let _ = writeln!(out, "resource.{key}: {value}");
+    }
+    for table in &archive.tables {
+        let _ = writeln!(
+            out,
+            "{}: {} rows, {} columns",
+            table.table,
+            table.dataframe.len(),
+            table.dataframe.names.len()
+        );
+        if !table.dropped_columns.is_empty() {
+            let _ = writeln!(out, "  dropped: {}", table.dropped_columns.join(", "));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::{ArchivedTable, DataFrame, Seq};
+
+    #[test]
+    fn summarizes_tables_and_resource() {
+        let archive = TraceArchive {
+            resource: [("rank".to_string(), "0".to_string())].into(),
+            tables: vec![ArchivedTable {
+                table: "python.trace_event".into(),
+                dataframe: DataFrame::new(vec!["time".into()], vec![Seq::SeqI64(vec![1, 2])]),
+                dropped_columns: vec!["attributes".into()],
+            }],
+            ..Default::default()
+        };
+        let text = summarize(&archive);
+        assert!(text.contains("resource.rank: 0"));
+        assert!(text.contains("python.trace_event: 2 rows, 1 columns"));
+        assert!(text.contains("dropped: attributes"));
+    }
+
+    #[test]
+    fn reads_autosave_directories_and_compressed_segments() {
+        use probing_proto::prelude::{AutosaveSegment, Seq};
+
+        let dir = std::env::temp_dir().join(format!("probing-autosave-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut manifest = AutosaveManifest::new();
+        for (seq, times) in [(1, vec![1, 2]), (2, vec![3])] {
+            let archive = TraceArchive {
+                tables: vec![ArchivedTable {
+                    table: "python.trace_event".into(),
+                    dataframe: DataFrame::new(vec!["time".into()], vec![Seq::SeqI64(times)]),
+                    dropped_columns: vec![],
+                }],
+                ..Default::default()
+            };
+            let file = format!("segment-{seq:06}.prbtrace.zst");
+            let bytes = zstd::encode_all(archive.encode().unwrap().as_slice(), 3).unwrap();
+            std::fs::write(dir.join(&file), bytes).unwrap();
+            manifest.segments.push(AutosaveSegment {
+                file,
+                seq,
+                ..Default::default()
+            });
+        }
+        std::fs::write(
+            dir.join(AUTOSAVE_MANIFEST),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+
+        let (_, single) =
+            read_archive(dir.join("segment-000002.prbtrace.zst").to_str().unwrap()).unwrap();
+        assert_eq!(single.row_count(), 1);
+        let (bytes, merged) = read_archive(dir.to_str().unwrap()).unwrap();
+        assert_eq!(TraceArchive::decode(&bytes).unwrap(), merged);
+        assert!(summarize(&merged).contains("python.trace_event: 3 rows, 1 columns"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn offline_import_needs_no_target() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Wrap {
+            #[command(flatten)]
+            cmd: AnalyzeCommand,
+        }
+        let w = Wrap::try_parse_from(["analyze", "--import", "d.bin", "--offline"]).unwrap();
+        assert!(w.cmd.is_offline());
+        assert_eq!(w.cmd.namespace, "replay");
+        assert!(Wrap::try_parse_from(["analyze"]).is_err());
+        assert!(Wrap::try_parse_from(["analyze", "--dump", "d.bin", "--offline"]).is_err());
+    }
+}
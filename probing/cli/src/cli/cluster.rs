@@ -4,7 +4,8 @@ use probing_proto::prelude::NodeListResponse;
 use probing_skills::backend::parse_cluster_query_response;
 
 use crate::cli::ctrl::ProbeEndpoint;
-use crate::table::render_dataframe;
+use crate::cli::error::CliError;
+use crate::table::{render, OutputFormat};
 
 #[derive(clap::Subcommand, Debug, Clone)]
 pub enum ClusterCommand {
@@ -23,10 +24,10 @@ pub enum ClusterCommand {
     Nodes,
 }
 
-pub async fn run(ctrl: ProbeEndpoint, cmd: ClusterCommand) -> Result<()> {
+pub async fn run(ctrl: ProbeEndpoint, cmd: ClusterCommand, format: OutputFormat) -> Result<()> {
     match cmd {
         ClusterCommand::Query { query, local, flat } => {
-            cluster_query(ctrl, &query, !local, !flat).await
+            cluster_query(ctrl, &query, !local, !flat, format).await
         }
         ClusterCommand::Nodes => cluster_nodes(ctrl).await,
     }
@@ -37,6 +38,7 @@ async fn cluster_query(
     expr: &str,
     cluster: bool,
     hierarchical: bool,
+    format: OutputFormat,
 ) -> Result<()> {
     let body = serde_json::json!({
         "expr": expr,
@@ -48,7 +50,7 @@ async fn cluster_query(
         .await?;
     let value: serde_json::Value = serde_json::from_str(&reply)?;
     let (dataframe, cluster_meta) =
-        parse_cluster_query_response(&value).map_err(|e| anyhow::anyhow!(e.0))?;
+        parse_cluster_query_response(&value).map_err(|e| CliError::Query(e.0))?;
     if let Some(meta) = &cluster_meta {
         eprintln!(
             "cluster query: cluster={cluster}, nodes_queried={}, nodes_failed={}",
@@ -70,7 +72,7 @@ async fn cluster_query(
             );
         }
     }
-    render_dataframe(&dataframe);
+    render(&dataframe, format);
     Ok(())
 }
 
@@ -128,6 +128,11 @@ pub enum Commands {
     #[command(visible_aliases = ["in", "i"])]
     Inject(super::inject::InjectCommand),
 
+    /// List Python processes on this host and whether probing is loaded
+    #[cfg(target_os = "linux")]
+    #[command()]
+    Ps(super::ps::PsCommand),
+
     /// List processes that already have probing enabled
     #[command(visible_aliases = ["ls", "l"])]
     List {
@@ -139,8 +144,11 @@ pub enum Commands {
     },
 
     /// Display or modify the configuration
-    #[command(visible_aliases = ["cfg", "c"])]
+    #[command(visible_aliases = ["cfg", "c"], args_conflicts_with_subcommands = true)]
     Config {
+        #[command(subcommand)]
+        action: Option<crate::cli::config::ConfigCommand>,
+
         #[command(flatten)]
         options: Settings,
 
@@ -151,26 +159,51 @@ pub enum Commands {
     #[command(visible_aliases = ["bt", "b"])]
     Backtrace { tid: Option<i32> },
 
+    /// Dump the call stacks of all threads, like `py-spy dump`
+    #[command()]
+    Stacks(super::stacks::StacksCommand),
+
     /// Get RDMA flow of the target process or thread
     #[command(visible_aliases = ["rd"])]
     Rdma { hca_name: Option<String> },
 
     /// Evaluate Python code in the target process
     #[command(visible_aliases = ["e"])]
-    Eval {
-        #[arg()]
-        code: String,
+    Eval(super::eval::EvalCommand),
+
+    /// Switch probing off in the target, leaving the process running
+    #[command()]
+    Detach,
+
+    /// Run Python garbage collection in the target and report what it freed
+    #[command()]
+    Gc {
+        /// Collect only this generation (0-2) instead of all of them
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=2))]
+        generation: Option<u8>,
     },
 
     /// Query data from the target process
     #[command(visible_aliases = ["q"])]
     Query {
-        #[arg()]
-        query: String,
+        #[arg(required_unless_present = "cancel")]
+        query: Option<String>,
 
-        /// Output format for the query result
-        #[arg(short, long, value_enum, default_value_t = crate::table::OutputFormat::Table)]
-        format: crate::table::OutputFormat,
+        /// Re-run the query on this interval and redraw the result, e.g. `2`, `500ms`, `1m`
+        #[arg(long, value_name = "INTERVAL", value_parser = super::watchdog::parse_interval)]
+        watch: Option<std::time::Duration>,
+
+        /// Run the query under this id, so `query --cancel <ID>` can stop it
+        #[arg(long, value_name = "ID", conflicts_with = "watch")]
+        id: Option<String>,
+
+        /// Cancel the query running under ID instead of running one
+        #[arg(long, value_name = "ID", conflicts_with_all = ["query", "watch", "id"])]
+        cancel: Option<String>,
+
+        /// File the `--format arrow` result is written to
+        #[arg(short, long, value_name = "FILE", default_value = "query.arrow")]
+        output: std::path::PathBuf,
     },
 
     /// List queryable tables in the target process
@@ -179,10 +212,6 @@ pub enum Commands {
         /// Show all tables including internal information_schema tables
         #[arg(short, long)]
         all: bool,
-
-        /// Output format for the table list
-        #[arg(short, long, value_enum, default_value_t = crate::table::OutputFormat::Table)]
-        format: crate::table::OutputFormat,
     },
 
     /// Show memory usage (host RSS and GPU memory) of the target process
@@ -191,27 +220,39 @@ pub enum Commands {
         /// Number of recent samples to display
         #[arg(short, long, default_value_t = 10)]
         limit: usize,
-
-        /// Output format for the memory report
-        #[arg(short, long, value_enum, default_value_t = crate::table::OutputFormat::Table)]
-        format: crate::table::OutputFormat,
     },
 
-    /// Fetch a flamegraph (CPU/pprof or PyTorch) from the target process
+    /// Live CPU, memory, I/O and GPU view of the target (`q` quits, `p` pauses)
+    #[command()]
+    Top(super::top::TopCommand),
+
+    /// Fetch a flamegraph (CPU/pprof or PyTorch) as HTML, SVG or folded stacks
     #[command(visible_aliases = ["flame", "fg"])]
-    Flamegraph {
-        /// Flamegraph source: `pprof` (CPU sampling) or `torch` (PyTorch modules)
-        #[arg(value_enum, default_value_t = FlamegraphKind::Pprof)]
-        kind: FlamegraphKind,
+    Flamegraph(super::flamegraph::FlamegraphCommand),
 
-        /// Write output to a file instead of stdout
-        #[arg(short, long)]
-        output: Option<String>,
+    /// Dump a trace archive from the target, or import one for replay
+    #[command()]
+    Analyze(super::analyze::AnalyzeCommand),
 
-        /// Emit raw folded-stack JSON instead of interactive HTML
-        #[arg(short, long)]
-        json: bool,
-    },
+    /// Save the target's trace as chrome tracing JSON (or raw rows) to a file
+    #[command()]
+    DumpTrace(super::dump_trace::DumpTraceCommand),
+
+    /// Snapshot the target periodically, keeping the last N archives for post-mortem
+    #[command()]
+    Watchdog(super::watchdog::WatchdogCommand),
+
+    /// Serve a trace archive as a read-only dashboard (no target needed)
+    #[command()]
+    ServeSnapshot(super::serve_snapshot::ServeSnapshotCommand),
+
+    /// Serve a pprof-compatible HTTP endpoint for `go tool pprof`
+    #[command(subcommand)]
+    Pprof(super::pprof::PprofCommand),
+
+    /// Watch traced-function variable records live
+    #[command(subcommand)]
+    Trace(super::trace::TraceCommand),
 
     /// Interactive Python REPL session
     #[command(visible_aliases = ["r"])]
@@ -250,4 +291,63 @@ pub enum Commands {
     /// MCP endpoint URL and agent config for the target probing server
     #[command(subcommand, visible_aliases = ["agent-mcp"])]
     Mcp(super::mcp::McpCommand),
+
+    /// Print a shell completion script, e.g. `source <(probing completions bash)`
+    #[command()]
+    Completions(super::completions::CompletionsCommand),
+
+    /// Candidate pids for `-t` completion, `pid<TAB>description` per line
+    #[command(hide = true)]
+    CompletePids,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(subcommand)]
+        cmd: Commands,
+    }
+
+    fn parse(args: &[&str]) -> Result<Commands, clap::Error> {
+        Cli::try_parse_from(std::iter::once("probing").chain(args.iter().copied())).map(|c| c.cmd)
+    }
+
+    #[test]
+    fn query_takes_an_id_or_cancels_one() {
+        let Ok(Commands::Query { query, id, .. }) = parse(&["query", "SELECT 1", "--id", "q1"])
+        else {
+            panic!("query with --id");
+        };
+        assert_eq!(
+            (query.as_deref(), id.as_deref()),
+            (Some("SELECT 1"), Some("q1"))
+        );
+        let Ok(Commands::Query { query, cancel, .. }) = parse(&["query", "--cancel", "q1"]) else {
+            panic!("query --cancel");
+        };
+        assert_eq!((query, cancel.as_deref()), (None, Some("q1")));
+
+        assert!(parse(&["query"]).is_err());
+        assert!(parse(&["query", "SELECT 1", "--cancel", "q1"]).is_err());
+        assert!(parse(&["query", "SELECT 1", "--id", "q1", "--watch", "2s"]).is_err());
+    }
+
+    #[test]
+    fn arrow_results_go_to_query_arrow_unless_named() {
+        let Ok(Commands::Query { output, .. }) = parse(&["query", "SELECT 1"]) else {
+            panic!("query");
+        };
+        assert_eq!(output, std::path::PathBuf::from("query.arrow"));
+        let Ok(Commands::Query { output, .. }) =
+            parse(&["query", "SELECT 1", "-o", "/tmp/r.arrow"])
+        else {
+            panic!("query -o");
+        };
+        assert_eq!(output, std::path::PathBuf::from("/tmp/r.arrow"));
+    }
 }
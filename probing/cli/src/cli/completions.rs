@@ -0,0 +1,133 @@
+//! `probing completions <shell>`: tab-completion script on stdout.
+//!
+//! The script is clap's completion for the current command tree. For bash,
+//! zsh and fish it also completes the value of `-t/--target` with local pids
+//! by calling the hidden `probing complete-pids`, which lists Python
+//! processes (probed ones first) one per line as `pid<TAB>description`.
+//!
+//! ```bash
+//! source <(probing completions bash)
+//! probing completions zsh > "${fpath[1]}/_probing"
+//! probing completions fish > ~/.config/fish/completions/probing.fish
+//! ```
+
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use clap_complete::Shell;
+
+use super::Cli;
+
+const BIN: &str = "probing";
+
+#[derive(Args, Debug, Clone)]
+pub struct CompletionsCommand {
+    /// Shell to generate the script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+impl CompletionsCommand {
+    pub fn run(&self) -> Result<()> {
+        let mut out = std::io::stdout().lock();
+        generate(self.shell, &mut out)?;
+        Ok(out.flush()?)
+    }
+}
+
+/// Write the completion script for `shell` to `out`.
+pub fn generate(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::build_command(), BIN, &mut script);
+    let script = String::from_utf8(script)?;
+    let script = match shell {
+        Shell::Bash => format!("{script}{BASH_PIDS}"),
+        Shell::Zsh => zsh_with_pids(&script),
+        Shell::Fish => format!("{script}{FISH_PIDS}"),
+        _ => script,
+    };
+    out.write_all(script.as_bytes())?;
+    Ok(())
+}
+
+/// Wraps clap's `_probing` so `-t`/`--target` values come from
+/// `probing complete-pids`.
+const BASH_PIDS: &str = r#"
+_probing_with_pids() {
+    case "${COMP_WORDS[COMP_CWORD-1]}" in
+        -t|--target)
+            local pids
+            pids=$(probing complete-pids 2>/dev/null | cut -f1)
+            COMPREPLY=($(compgen -W "${pids}" -- "${COMP_WORDS[COMP_CWORD]}"))
+            return 0
+            ;;
+    esac
+    _probing "$@"
+}
+complete -F _probing_with_pids -o bashdefault -o default probing
+"#;
+
+const ZSH_PIDS: &str = r#"
+_probing_pids() {
+    local -a pids
+    pids=(${(f)"$(probing complete-pids 2>/dev/null | tr '\t' ':')"})
+    _describe -t pids 'process' pids
+}
+"#;
+
+/// clap's zsh script with the `--target` value completed by `_probing_pids`,
+/// defined right after the `#compdef` line.
+fn zsh_with_pids(script: &str) -> String {
+    let script = script.replace(":TARGET:_default", ":TARGET:_probing_pids");
+    match script.split_once('\n') {
+        Some((compdef, rest)) => format!("{compdef}\n{ZSH_PIDS}{rest}"),
+        None => script,
+    }
+}
+
+const FISH_PIDS: &str = r#"
+complete -c probing -s t -l target -x -a '(probing complete-pids 2>/dev/null)'
+"#;
+
+/// `probing complete-pids`: candidate `-t` values, one `pid<TAB>description`
+/// per line. Never fails; completion just offers nothing.
+pub fn print_pids() {
+    for (pid, description) in candidate_pids() {
+        println!("{pid}\t{description}");
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn candidate_pids() -> Vec<(i32, String)> {
+    use super::ps::ProbingState;
+
+    let mut processes = super::ps::scan().unwrap_or_default();
+    processes.sort_by_key(|p| (p.probing != ProbingState::Injected, p.pid));
+    processes
+        .into_iter()
+        .map(|p| {
+            let cmdline: String = p.cmdline.chars().take(60).collect();
+            let tag = if p.probing == ProbingState::Injected {
+                " [probing]"
+            } else {
+                ""
+            };
+            (p.pid, format!("{cmdline}{tag}"))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn candidate_pids() -> Vec<(i32, String)> {
+    let mut pids: Vec<i32> = super::ptree::find_probe_sockets()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(pid, _)| pid)
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    pids.into_iter()
+        .map(|pid| (pid, "probing".to_string()))
+        .collect()
+}
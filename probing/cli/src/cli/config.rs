@@ -0,0 +1,287 @@
+//! `probing <endpoint> config list|get|set|watch`: runtime options of the target.
+//!
+//! `list`, `get` and `set` go through SQL: `list` reads `probing.config`
+//! (key, value, type and bounds, help text), `get` the `probing.*` rows of
+//! `information_schema.df_settings`, and `set` runs `SET`, so the extension
+//! owning the key validates the value.
+//!
+//! `watch` subscribes to `/apis/config/watch` and prints one line per change,
+//! `time key old -> new (source)`, until interrupted. The source names the
+//! writer (`token:<fingerprint> req:<request id>`), so people sharing a
+//! target can tell whose settings changed; writes from inside the process
+//! show as `(local)`.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use clap::{Args, Subcommand};
+use probing_proto::prelude::{ConfigChange, DataFrame, Ele, Query, Seq};
+
+use crate::cli::ctrl::{stream_lines, ProbeEndpoint};
+use crate::cli::error::CliError;
+use crate::table::{render, OutputFormat};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// List options with their current value, type and help text
+    List {
+        /// Only keys starting with this prefix (e.g. `torch.`; `probing.` optional)
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Print the current value of one option
+    Get {
+        /// Option name, e.g. `probing.torch.profiling` (`probing.` optional)
+        key: String,
+    },
+    /// Change an option and print its previous value
+    Set {
+        /// Option name, e.g. `probing.torch.profiling` (`probing.` optional)
+        key: String,
+        value: String,
+    },
+    /// Print config changes as they happen, until interrupted
+    Watch(WatchArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// Only keys starting with this prefix (e.g. `trace.`; `probing.` optional)
+    #[arg(long)]
+    pub filter: Option<String>,
+}
+
+pub async fn run(ctrl: ProbeEndpoint, cmd: ConfigCommand, format: OutputFormat) -> Result<()> {
+    match cmd {
+        ConfigCommand::List { filter } => list(ctrl, filter.as_deref(), format).await,
+        ConfigCommand::Get { key } => get(ctrl, &key, format).await,
+        ConfigCommand::Set { key, value } => set(ctrl, &key, &value, format).await,
+        ConfigCommand::Watch(args) => watch(ctrl, args).await,
+    }
+}
+
+/// `key` with the `probing.` prefix; only names can be spliced into SQL.
+fn option_key(key: &str) -> Result<String> {
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        return Err(CliError::Usage(format!("invalid option name `{key}`")).into());
+    }
+    Ok(if key.starts_with("probing.") {
+        key.to_string()
+    } else {
+        format!("probing.{key}")
+    })
+}
+
+async fn list(ctrl: ProbeEndpoint, filter: Option<&str>, format: OutputFormat) -> Result<()> {
+    let prefix = match filter {
+        Some(filter) => option_key(filter)?,
+        None => "probing.".to_string(),
+    };
+    // `probing.config` keys drop the `probing.` prefix.
+    let prefix = prefix.strip_prefix("probing.").unwrap_or(&prefix);
+    let sql = format!(
+        "select 'probing.' || key as key, value, option_type as type, choices, min, max, \
+         help as description from probing.config \
+         where starts_with(key, '{prefix}') order by key"
+    );
+    render(&ctrl.query(Query::new(sql)).await?, format);
+    Ok(())
+}
+
+/// Current value of `key`, `None` when unset; fails for keys no extension has.
+pub(crate) async fn read(ctrl: &ProbeEndpoint, key: &str) -> Result<Option<String>> {
+    let sql = format!("select value from information_schema.df_settings where name = '{key}'");
+    let df = ctrl.query(Query::new(sql)).await?;
+    let Some(col) = df.cols.first().filter(|col| !col.is_empty()) else {
+        return Err(CliError::Usage(format!(
+            "unknown option `{key}` (see `probing config list`)"
+        ))
+        .into());
+    };
+    Ok(match col.get(0) {
+        Ele::Nil => None,
+        Ele::Text(value) => Some(value),
+        other => Some(other.to_string()),
+    })
+}
+
+async fn get(ctrl: ProbeEndpoint, key: &str, format: OutputFormat) -> Result<()> {
+    let key = option_key(key)?;
+    let value = read(&ctrl, &key).await?;
+    match format {
+        // Bare value for `$(probing config get ...)`; unset prints an empty line.
+        OutputFormat::Table => println!("{}", value.unwrap_or_default()),
+        _ => render(&frame(&[("key", Some(key)), ("value", value)]), format),
+    }
+    Ok(())
+}
+
+async fn set(ctrl: ProbeEndpoint, key: &str, value: &str, format: OutputFormat) -> Result<()> {
+    let key = option_key(key)?;
+    let previous = read(&ctrl, &key).await?;
+    write(&ctrl, &key, value).await?;
+    match format {
+        OutputFormat::Table => println!(
+            "{key}: {} -> {value}",
+            previous.as_deref().unwrap_or("(unset)")
+        ),
+        _ => render(
+            &frame(&[
+                ("key", Some(key)),
+                ("previous", previous),
+                ("value", Some(value.to_string())),
+            ]),
+            format,
+        ),
+    }
+    Ok(())
+}
+
+/// `SET key = value`; an empty value clears the option.
+pub(crate) async fn write(ctrl: &ProbeEndpoint, key: &str, value: &str) -> Result<()> {
+    let stmt = format!("set {key}='{}'", value.replace('\'', "''"));
+    ctrl.query(Query::new(stmt))
+        .await
+        .map_err(|err| explain_set_error(err, key, value))?;
+    Ok(())
+}
+
+/// Name the `EngineError` behind a failed `SET`; the server's message is kept
+/// as the cause.
+fn explain_set_error(err: anyhow::Error, key: &str, value: &str) -> anyhow::Error {
+    let msg = err.to_string();
+    let summary = if msg.contains("Unsupported option") {
+        format!("unknown option `{key}` (see `probing config list`)")
+    } else if msg.contains("Invalid option value") {
+        format!("invalid value `{value}` for `{key}`")
+    } else if msg.contains("Read-only option") {
+        format!("`{key}` is read-only")
+    } else {
+        return err;
+    };
+    err.context(CliError::Usage(summary))
+}
+
+/// One-row frame; `None` cells render as `null` / empty.
+fn frame(cells: &[(&str, Option<String>)]) -> DataFrame {
+    DataFrame::new(
+        cells.iter().map(|(name, _)| name.to_string()).collect(),
+        cells
+            .iter()
+            .map(|(_, value)| match value {
+                Some(value) => Seq::SeqText(vec![value.clone()]),
+                None => Seq::Nil,
+            })
+            .collect(),
+    )
+}
+
+async fn watch(ctrl: ProbeEndpoint, args: WatchArgs) -> Result<()> {
+    let url = match &args.filter {
+        Some(prefix) => format!("/apis/config/watch?filter={prefix}"),
+        None => "/apis/config/watch".to_string(),
+    };
+    eprintln!("watching config changes (Ctrl-C to stop)...");
+    stream_lines(ctrl, &url, |line| {
+        match serde_json::from_str::<ConfigChange>(line) {
+            Ok(change) => println!("{}", format_change(&change)),
+            Err(_) => eprintln!("unexpected line: {line}"),
+        }
+    })
+    .await
+}
+
+/// `time key old -> new (source)`; an unset side prints as `-`.
+pub fn format_change(change: &ConfigChange) -> String {
+    let time = DateTime::from_timestamp_millis(change.timestamp_ms as i64)
+        .map(|t| {
+            t.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or_else(|| change.timestamp_ms.to_string());
+    let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+    let source = change
+        .source
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("local");
+    format!(
+        "{time} {} {} -> {} ({source})",
+        change.key,
+        value(&change.old),
+        value(&change.new)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_keys_get_the_probing_prefix() {
+        assert_eq!(
+            option_key("torch.profiling").unwrap(),
+            "probing.torch.profiling"
+        );
+        assert_eq!(
+            option_key(" probing.pprof.sample_freq ").unwrap(),
+            "probing.pprof.sample_freq"
+        );
+        assert!(option_key("").is_err());
+        assert!(option_key("x'; drop table t; --").is_err());
+    }
+
+    #[test]
+    fn set_errors_name_the_cause() {
+        let unsupported =
+            anyhow::anyhow!("error: External error: Unsupported option: probing.nope");
+        let err = explain_set_error(unsupported, "probing.nope", "1");
+        assert_eq!(
+            err.to_string(),
+            "unknown option `probing.nope` (see `probing config list`)"
+        );
+        assert!(format!("{err:#}").contains("Unsupported option: probing.nope"));
+
+        let invalid = anyhow::anyhow!("error: Invalid option value: pprof.sample_freq=abc");
+        assert_eq!(
+            explain_set_error(invalid, "probing.pprof.sample_freq", "abc").to_string(),
+            "invalid value `abc` for `probing.pprof.sample_freq`"
+        );
+        let other = anyhow::anyhow!("connection refused");
+        assert_eq!(
+            explain_set_error(other, "probing.x", "1").to_string(),
+            "connection refused"
+        );
+    }
+
+    #[test]
+    fn formats_one_line_per_change() {
+        let change = ConfigChange {
+            timestamp_ms: 1_700_000_000_123,
+            key: "probing.trace.max_events".into(),
+            old: Some("65536".into()),
+            new: Some("1024".into()),
+            source: Some("token:1a2b3c4d req:r1".into()),
+        };
+        let line = format_change(&change);
+        assert!(line.ends_with(" probing.trace.max_events 65536 -> 1024 (token:1a2b3c4d req:r1)"));
+        assert!(line.contains(".123 "));
+
+        let removed = ConfigChange {
+            key: "x".into(),
+            old: Some("1".into()),
+            ..change
+        };
+        let removed = ConfigChange {
+            new: None,
+            source: None,
+            ..removed
+        };
+        assert!(format_change(&removed).ends_with(" x 1 -> - (local)"));
+    }
+}
@@ -1,14 +1,17 @@
 use crate::cli::fanout::fanout_strict_enabled;
 use anyhow::{Context, Result};
 use std::io::Write;
+use std::time::Duration;
 
 use http_body_util::{BodyExt, Full};
-use hyper::header::{HeaderValue, AUTHORIZATION};
+use hyper::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use hyper_util::rt::TokioIo;
 
 use probing_proto::{prelude::*, protocol::process::CallFrame};
 
-use crate::table::{render, OutputFormat};
+use crate::cli::bench::metrics::human_bytes;
+use crate::cli::error::CliError;
+use crate::table::{render, render_arrow, OutputFormat};
 
 pub async fn query(ctrl: ProbeEndpoint, query: Query) -> Result<()> {
     query_with_format(ctrl, query, OutputFormat::Table).await
@@ -24,6 +27,41 @@ pub async fn query_with_format(
     Ok(())
 }
 
+/// [`query_with_format`] under a caller-chosen id (`/query?id=`), which
+/// `query --cancel <id>` can stop from another shell.
+pub async fn query_as(
+    ctrl: ProbeEndpoint,
+    query: Query,
+    id: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let reply = ctrl.query_at(&format!("/query?id={id}"), query).await?;
+    render(&reply, format);
+    Ok(())
+}
+
+/// Run `query` (under `id`, if given) and write its result as an Arrow IPC
+/// stream to `path`.
+pub async fn query_arrow(
+    ctrl: ProbeEndpoint,
+    query: Query,
+    id: Option<&str>,
+    path: &std::path::Path,
+) -> Result<()> {
+    let url = match id {
+        Some(id) => format!("/query?id={id}"),
+        None => "/query".to_string(),
+    };
+    let bytes = ctrl.query_arrow_at(&url, query).await?;
+    std::fs::write(path, &bytes).with_context(|| format!("failed to write {}", path.display()))?;
+    eprintln!(
+        "wrote {} to {}",
+        human_bytes(bytes.len() as u64),
+        path.display()
+    );
+    Ok(())
+}
+
 #[derive(Clone)]
 pub enum ProbeEndpoint {
     Ptrace { pid: i32 },
@@ -40,8 +78,65 @@ impl TryFrom<&str> for ProbeEndpoint {
             return Ok(Self::Remote { addr: value.into() });
         }
 
-        Ok(Self::Local {
-            pid: value.parse::<i32>()?,
+        let pid = value.parse::<i32>().map_err(|_| {
+            CliError::Usage(format!(
+                "invalid target `{value}`: expected a pid or host:port"
+            ))
+        })?;
+        Ok(Self::Local { pid })
+    }
+}
+
+impl ProbeEndpoint {
+    /// `--endpoint`: `http://host:port` (a trailing `/` is fine) or `host:port`.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let url = url.trim();
+        let usage = |msg: String| -> Result<Self> { Err(CliError::Usage(msg).into()) };
+        if url.starts_with("https://") {
+            return usage(format!(
+                "{url}: the CLI speaks plain HTTP only; reach a TLS endpoint through a tunnel, \
+                 e.g. `ssh -L 8080:localhost:8080 <host>` and `--endpoint http://localhost:8080`"
+            ));
+        }
+        let rest = url.strip_prefix("http://").unwrap_or(url);
+        if rest.contains("://") {
+            return usage(format!("{url}: expected http://host:port"));
+        }
+        let addr = rest.trim_end_matches('/');
+        if addr.is_empty() || addr.contains('/') {
+            return usage(format!("{url}: expected http://host:port without a path"));
+        }
+        // `[::1]` has colons but no port.
+        let has_port = addr
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        let addr = if has_port {
+            addr.to_string()
+        } else {
+            format!("{addr}:80")
+        };
+        Ok(Self::Remote { addr })
+    }
+
+    /// Warning for a token about to travel unencrypted to another host.
+    pub fn cleartext_token_warning(&self) -> Option<String> {
+        let ProbeEndpoint::Remote { addr } = self else {
+            return None;
+        };
+        auth_token()?;
+        let host = addr
+            .rsplit_once(':')
+            .map_or(addr.as_str(), |(host, _)| host)
+            .trim_matches(['[', ']']);
+        let loopback = host == "localhost"
+            || host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback());
+        (!loopback).then(|| {
+            format!(
+                "warning: the auth token is sent to {addr} over plain HTTP and can be read on \
+                 the way; prefer an SSH tunnel (`ssh -L`) to a loopback address"
+            )
         })
     }
 }
@@ -102,81 +197,56 @@ impl ProbeEndpoint {
         Ok(String::from_utf8(reply)?)
     }
 
-    pub async fn eval(&self, code: String) -> Result<()> {
-        let reply_str = self.eval_json(code).await?;
-
-        // Parse JSON response and handle output similar to repl
-        match serde_json::from_str::<serde_json::Value>(&reply_str) {
-            Ok(json) => {
-                // Display output
-                if let Some(output) = json.get("output").and_then(|v| v.as_str()) {
-                    if !output.is_empty() {
-                        print!("{}", output);
-                        // If output doesn't end with newline, add one
-                        if !output.ends_with('\n') {
-                            println!();
-                        }
-                    }
-                }
-
-                // Display error traceback
-                if let Some(traceback) = json.get("traceback").and_then(|v| v.as_array()) {
-                    if !traceback.is_empty() {
-                        for line in traceback {
-                            if let Some(line_str) = line.as_str() {
-                                eprintln!("{}", line_str);
-                            }
-                        }
-                    }
-                }
-
-                // Flush output
-                std::io::stdout().flush().unwrap();
-                std::io::stderr().flush().unwrap();
-            }
-            Err(_) => {
-                // If not JSON, display raw response
-                print!("{}", reply_str);
-                if !reply_str.ends_with('\n') {
-                    println!();
-                }
-                std::io::stdout().flush().unwrap();
-            }
-        }
-
-        Ok(())
+    pub async fn query(&self, q: Query) -> Result<DataFrame> {
+        self.query_at("/query", q).await
     }
 
-    pub async fn query(&self, q: Query) -> Result<DataFrame> {
+    async fn query_at(&self, url: &str, q: Query) -> Result<DataFrame> {
         let request = Message::new(q);
         let q_str = serde_json::to_string(&request)?;
-        let reply_str = self.send_request("/query", &q_str).await?; // Renamed reply variable
-        let msg = serde_json::from_str::<Message<QueryDataFormat>>(&reply_str)?;
-        if let Some(meta) = &msg.meta {
-            if meta
-                .get("fanout")
-                .and_then(|f| f.get("partial"))
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false)
-            {
-                if fanout_strict_enabled() {
-                    return Err(anyhow::anyhow!(
-                        "federated fan-out strict mode: query returned partial data: {meta}"
-                    ));
-                }
-                eprintln!("warning: federated query returned partial data: {meta}");
-            }
+        let reply_str = self.send_request(url, &q_str).await?; // Renamed reply variable
+        decode_query_reply(&reply_str)
+    }
+
+    /// Arrow IPC stream of a query's result, asked for with `Accept`.
+    async fn query_arrow_at(&self, url: &str, q: Query) -> Result<Vec<u8>> {
+        let body = serde_json::to_string(&Message::new(q))?;
+        let (is_arrow, reply) = within(self, url, async {
+            let mut sender = connect(self).await?;
+            let mut request = build_request(url, Some(body.into()))?;
+            request
+                .headers_mut()
+                .insert(ACCEPT, HeaderValue::from_static(ARROW_STREAM_CONTENT_TYPE));
+            let res = sender.send_request(request).await?;
+            check_authorized(self, res.status())?;
+            let is_arrow = res
+                .headers()
+                .get(CONTENT_TYPE)
+                .is_some_and(|v| v.as_bytes() == ARROW_STREAM_CONTENT_TYPE.as_bytes());
+            Ok((is_arrow, res.collect().await?.to_bytes()))
+        })
+        .await?;
+        if is_arrow {
+            return Ok(reply.to_vec());
         }
-        let reply = msg.payload;
-
-        match reply {
-            QueryDataFormat::Error(err) => Err(anyhow::anyhow!("error: {}", err)),
-            QueryDataFormat::Nil => Ok(Default::default()),
-            QueryDataFormat::DataFrame(df) => Ok(df),
-            QueryDataFormat::TimeSeries(_) => {
-                anyhow::bail!("TimeSeries query responses are not supported by the CLI")
-            }
+        // Errors, SET statements and servers without Arrow results answer JSON.
+        let df = decode_query_reply(std::str::from_utf8(&reply)?)?;
+        render_arrow(&df)
+    }
+
+    /// Cancel the query the target runs under `id` (see [`query_as`]).
+    pub async fn cancel_query(&self, id: &str) -> Result<()> {
+        let reply = self
+            .post_json(&format!("/query/cancel?id={id}"), "")
+            .await?;
+        let cancelled = serde_json::from_str::<serde_json::Value>(&reply)
+            .ok()
+            .and_then(|v| v.get("cancelled")?.as_bool())
+            .unwrap_or(false);
+        if !cancelled {
+            return Err(CliError::Query(format!("cannot cancel query {id}: {reply}")).into());
         }
+        Ok(())
     }
 
     /// Fetch a flamegraph (`torch` or `pprof`) and return its raw bytes (HTML or JSON).
@@ -187,7 +257,10 @@ impl ProbeEndpoint {
             ("pprof", true) => "/apis/pprofextension/flamegraph/json",
             ("pprof", false) => "/apis/pprofextension/flamegraph",
             (other, _) => {
-                anyhow::bail!("unknown flamegraph kind: {other} (expected torch or pprof)")
+                return Err(CliError::Usage(format!(
+                    "unknown flamegraph kind: {other} (expected torch or pprof)"
+                ))
+                .into())
             }
         };
         request(self.clone(), url, None).await
@@ -204,12 +277,284 @@ impl ProbeEndpoint {
     }
 }
 
+/// DataFrame of a `/query` reply; partial fan-out results warn, or fail in
+/// strict mode.
+fn decode_query_reply(reply_str: &str) -> Result<DataFrame> {
+    let msg = serde_json::from_str::<Message<QueryDataFormat>>(reply_str)?;
+    if let Some(meta) = &msg.meta {
+        if meta
+            .get("fanout")
+            .and_then(|f| f.get("partial"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            if fanout_strict_enabled() {
+                return Err(anyhow::anyhow!(
+                    "federated fan-out strict mode: query returned partial data: {meta}"
+                ));
+            }
+            eprintln!("warning: federated query returned partial data: {meta}");
+        }
+    }
+    let reply = msg.payload;
+
+    match reply {
+        // The server names the `probing.query.timeout` that was hit.
+        QueryDataFormat::Error(QueryError {
+            code: ErrorCode::TimeoutError,
+            message,
+            ..
+        }) => Err(CliError::Timeout(format!("error: {message}")).into()),
+        QueryDataFormat::Error(err) => Err(CliError::Query(format!("error: {err}")).into()),
+        QueryDataFormat::Nil => Ok(Default::default()),
+        QueryDataFormat::DataFrame(df) => Ok(df),
+        QueryDataFormat::TimeSeries(_) => {
+            anyhow::bail!("TimeSeries query responses are not supported by the CLI")
+        }
+    }
+}
+
 pub async fn request(ctrl: ProbeEndpoint, url: &str, body: Option<String>) -> Result<Vec<u8>> {
-    use hyper::body::Bytes;
+    send(ctrl, url, body.map(hyper::body::Bytes::from)).await
+}
+
+/// POST a binary body (e.g. a trace archive); see [`request`].
+pub async fn request_bytes(ctrl: ProbeEndpoint, url: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+    send(ctrl, url, Some(hyper::body::Bytes::from(body))).await
+}
+
+/// Stream a GET response to `path`, reporting received bytes on stderr as
+/// chunks arrive (large exports are sent with chunked transfer encoding, so
+/// there is no total to show). Returns the number of bytes written.
+pub async fn download(ctrl: ProbeEndpoint, url: &str, path: &str) -> Result<u64> {
+    let mut file =
+        std::fs::File::create(path).with_context(|| format!("failed to create {path}"))?;
+    let mut progress = DownloadProgress::default();
+    stream_chunks(ctrl, url, |data| {
+        file.write_all(data)
+            .with_context(|| format!("failed to write {path}"))?;
+        progress.advance(data.len() as u64);
+        Ok(())
+    })
+    .await?;
+    progress.finish();
+    Ok(progress.bytes)
+}
+
+/// Call `on_chunk` with each body chunk of a GET response as it arrives;
+/// an error from `on_chunk` stops the transfer.
+pub async fn stream_chunks(
+    ctrl: ProbeEndpoint,
+    url: &str,
+    mut on_chunk: impl FnMut(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let mut body = open_ok(ctrl, url).await?.into_body();
+    while let Some(frame) = body.frame().await {
+        if let Some(data) = frame?.data_ref() {
+            on_chunk(data)?;
+        }
+    }
+    Ok(())
+}
+
+/// Call `on_line` for each newline-terminated line of a streamed GET
+/// response (NDJSON endpoints), until the server closes the stream.
+pub async fn stream_lines(
+    ctrl: ProbeEndpoint,
+    url: &str,
+    mut on_line: impl FnMut(&str),
+) -> Result<()> {
+    let mut body = open_ok(ctrl, url).await?.into_body();
+    let mut pending = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Some(data) = frame?.data_ref() {
+            pending.extend_from_slice(data);
+            while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line[..end]);
+                if !line.trim().is_empty() {
+                    on_line(line.trim_end_matches('\r'));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A GET response with a success status; otherwise the body is the error.
+async fn open_ok(ctrl: ProbeEndpoint, url: &str) -> Result<hyper::Response<hyper::body::Incoming>> {
+    // Streams may run for long; only waiting for the headers is bounded.
+    let res = within(&ctrl, url, open(ctrl.clone(), url, None)).await?;
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.collect().await?.to_bytes();
+        anyhow::bail!(
+            "{url} failed ({status}): {}",
+            String::from_utf8_lossy(&body)
+        );
+    }
+    Ok(res)
+}
+
+/// Received-bytes line for [`download`], redrawn at most every
+/// [`DownloadProgress::STEP`] bytes.
+#[derive(Default)]
+struct DownloadProgress {
+    bytes: u64,
+    shown: u64,
+}
+
+impl DownloadProgress {
+    const STEP: u64 = 4 << 20;
+
+    fn advance(&mut self, n: u64) {
+        self.bytes += n;
+        if self.bytes - self.shown >= Self::STEP {
+            self.shown = self.bytes;
+            eprint!("\r{} received", human_bytes(self.bytes));
+            let _ = std::io::stderr().flush();
+        }
+    }
+
+    fn finish(&self) {
+        if self.shown > 0 {
+            eprintln!("\r{} received", human_bytes(self.bytes));
+        }
+    }
+}
+
+/// Whole request under `--timeout`; body-less GETs are retried `--retries`
+/// times with exponential backoff when the target stalls or the connection
+/// fails.
+async fn send(ctrl: ProbeEndpoint, url: &str, body: Option<hyper::body::Bytes>) -> Result<Vec<u8>> {
+    let retries = if body.is_none() { limits().retries } else { 0 };
+    let mut backoff = RETRY_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        let result = within(&ctrl, url, async {
+            let res = open(ctrl.clone(), url, body.clone()).await?;
+            Ok::<_, anyhow::Error>(res.collect().await.map(|x| x.to_bytes().to_vec())?)
+        })
+        .await;
+        match result {
+            Err(err) if attempt < retries && is_transient(&err) => {
+                attempt += 1;
+                eprintln!("{err:#}\nretrying in {backoff:?} ({attempt}/{retries})...");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// First wait of [`send`] before a retry; doubled after each one.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Stalls and connection failures; not HTTP errors such as a 401.
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|e| {
+        matches!(e.downcast_ref::<CliError>(), Some(CliError::Timeout(_)))
+            || e.is::<std::io::Error>()
+            || e.is::<hyper::Error>()
+    })
+}
+
+/// `--timeout` and `--retries`, see [`set_limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// Extra attempts for body-less GET requests.
+    pub retries: u32,
+}
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static LIMITS: std::sync::RwLock<Limits> = std::sync::RwLock::new(Limits {
+    timeout: Some(DEFAULT_TIMEOUT),
+    retries: 0,
+});
+
+/// Apply `limits` to every following request, connection and injection.
+pub fn set_limits(limits: Limits) {
+    *LIMITS.write().unwrap_or_else(|e| e.into_inner()) = limits;
+}
+
+pub(crate) fn limits() -> Limits {
+    *LIMITS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// [`CliError::Timeout`] for `target` not answering `what` within `after`.
+fn stalled(target: String, what: &str, after: Duration) -> CliError {
+    let pid = match target.parse::<i32>() {
+        Ok(pid) => pid.to_string(),
+        Err(_) => "<pid>".to_string(),
+    };
+    CliError::Timeout(format!(
+        "{target} did not answer {what} within {after:?}; the process may be holding the GIL \
+         (a long native call or a deadlock): `py-spy dump --pid {pid}` shows where. \
+         Raise `--timeout` if it is just slow"
+    ))
+}
+
+/// `fut` under `--timeout`, failing with [`CliError::Timeout`] naming `what`.
+pub(crate) async fn within<T>(
+    ctrl: &ProbeEndpoint,
+    what: &str,
+    fut: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(after) = limits().timeout else {
+        return fut.await;
+    };
+    match tokio::time::timeout(after, fut).await {
+        Ok(result) => result,
+        Err(_) => Err(stalled(String::from(ctrl.clone()), what, after).into()),
+    }
+}
+
+type Sender = hyper::client::conn::http1::SendRequest<Full<hyper::body::Bytes>>;
+
+async fn open(
+    ctrl: ProbeEndpoint,
+    url: &str,
+    body: Option<hyper::body::Bytes>,
+) -> Result<hyper::Response<hyper::body::Incoming>> {
+    let mut sender = connect(&ctrl).await?;
+    let res = sender.send_request(build_request(url, body)?).await?;
+    check_authorized(&ctrl, res.status())?;
+    Ok(res)
+}
+
+/// A 401 names the missing or rejected token instead of surfacing the body.
+fn check_authorized(ctrl: &ProbeEndpoint, status: hyper::StatusCode) -> Result<()> {
+    if status != hyper::StatusCode::UNAUTHORIZED {
+        return Ok(());
+    }
+    let target = String::from(ctrl.clone());
+    let msg = if auth_token().is_some() {
+        format!(
+            "{target} rejected the token (401 Unauthorized); it must equal the server's \
+             `server.auth_token`"
+        )
+    } else {
+        format!(
+            "{target} requires authentication (401 Unauthorized); pass `--token <token>` or \
+             set PROBING_TOKEN"
+        )
+    };
+    Err(CliError::Auth(msg).into())
+}
+
+/// HTTP/1 handshake with the target's control socket, under `--timeout`.
+async fn connect(ctrl: &ProbeEndpoint) -> Result<Sender> {
+    within(ctrl, "the connection handshake", handshake(ctrl)).await
+}
+
+async fn handshake(ctrl: &ProbeEndpoint) -> Result<Sender> {
     use hyper::client::conn;
-    use hyper::Request;
 
-    let mut sender = match ctrl {
+    let sender = match ctrl {
         ProbeEndpoint::Ptrace { pid } | ProbeEndpoint::Local { pid } => {
             eprintln!("sending ctrl commands via unix socket...");
             #[cfg(target_os = "linux")]
@@ -220,7 +565,14 @@ pub async fn request(ctrl: ProbeEndpoint, url: &str, body: Option<String>) -> Re
                 let file_path = temp_dir.join(format!("probing-{}.sock", pid));
                 file_path.to_string_lossy().to_string()
             };
-            let stream = tokio::net::UnixStream::connect(path).await?;
+            let stream = tokio::net::UnixStream::connect(path)
+                .await
+                .with_context(|| {
+                    CliError::TargetNotFound(format!(
+                        "cannot reach probing in pid {pid}: is the process running with probing \
+                         enabled? (`probing ps` lists candidates)"
+                    ))
+                })?;
             let io = TokioIo::new(stream);
 
             let (sender, connection) = conn::http1::handshake(io).await?;
@@ -233,7 +585,14 @@ pub async fn request(ctrl: ProbeEndpoint, url: &str, body: Option<String>) -> Re
         }
         ProbeEndpoint::Remote { addr } => {
             eprintln!("sending ctrl commands via tcp socket...");
-            let stream = tokio::net::TcpStream::connect(addr).await?;
+            let stream = tokio::net::TcpStream::connect(addr.as_str())
+                .await
+                .with_context(|| {
+                    CliError::TargetNotFound(format!(
+                        "cannot connect to the probing server at {addr}: is it running and \
+                         listening there (PROBING_PORT), and is the port reachable?"
+                    ))
+                })?;
             let io = TokioIo::new(stream);
 
             let (sender, connection) = conn::http1::handshake(io).await?;
@@ -245,15 +604,33 @@ pub async fn request(ctrl: ProbeEndpoint, url: &str, body: Option<String>) -> Re
             sender
         }
         ProbeEndpoint::Launch { .. } => {
-            anyhow::bail!(
+            return Err(CliError::Usage(
                 "launch endpoint does not support HTTP requests; use `probing launch` instead"
+                    .into(),
             )
+            .into())
         }
     };
+    Ok(sender)
+}
+
+fn build_request(
+    url: &str,
+    body: Option<hyper::body::Bytes>,
+) -> Result<hyper::Request<Full<hyper::body::Bytes>>> {
+    use hyper::body::Bytes;
+    use hyper::Request;
+
     let request = if let Some(body) = body {
-        apply_auth_headers(Request::builder())
+        let mut builder = apply_auth_headers(Request::builder())
             .method("POST")
-            .uri(url)
+            .uri(url);
+        // JSON routes (`/apis/cluster/query`, `/apis/profile/svg`) reject
+        // bodies without the content type; archives are binary.
+        if body.trim_ascii_start().starts_with(b"{") {
+            builder = builder.header(CONTENT_TYPE, "application/json");
+        }
+        builder
             .body(Full::<Bytes>::from(body))
             .context("Failed to build POST request")?
     } else {
@@ -264,19 +641,71 @@ pub async fn request(ctrl: ProbeEndpoint, url: &str, body: Option<String>) -> Re
             .context("Failed to build GET request")?
     };
 
-    let res = sender.send_request(request).await?;
+    Ok(request)
+}
 
-    Ok(res.collect().await.map(|x| x.to_bytes().to_vec())?)
+/// One connection reused across requests, for commands that poll the target
+/// (`query --watch`). A failed request drops the connection; the next one
+/// dials again.
+pub struct Session {
+    ctrl: ProbeEndpoint,
+    sender: Option<Sender>,
 }
 
-fn apply_auth_headers(builder: hyper::http::request::Builder) -> hyper::http::request::Builder {
-    if let Ok(token) = std::env::var("PROBING_AUTH_TOKEN") {
-        let token = token.trim();
-        if !token.is_empty() {
-            if let Ok(value) = HeaderValue::from_str(&format!("Bearer {token}")) {
-                return builder.header(AUTHORIZATION, value);
-            }
+impl Session {
+    pub fn new(ctrl: ProbeEndpoint) -> Self {
+        Self { ctrl, sender: None }
+    }
+
+    pub async fn query(&mut self, q: Query) -> Result<DataFrame> {
+        let body = serde_json::to_string(&Message::new(q))?;
+        let reply = self.send("/query", Some(body.into())).await?;
+        decode_query_reply(&String::from_utf8(reply)?)
+    }
+
+    async fn send(&mut self, url: &str, body: Option<hyper::body::Bytes>) -> Result<Vec<u8>> {
+        let sender = match &mut self.sender {
+            Some(sender) if !sender.is_closed() => sender,
+            slot => slot.insert(connect(&self.ctrl).await?),
+        };
+        let ctrl = &self.ctrl;
+        let result = within(ctrl, url, async {
+            sender.ready().await?;
+            let res = sender.send_request(build_request(url, body)?).await?;
+            check_authorized(ctrl, res.status())?;
+            Ok::<_, anyhow::Error>(res.collect().await?.to_bytes().to_vec())
+        })
+        .await;
+        if result.is_err() {
+            self.sender = None;
         }
+        result
+    }
+}
+
+/// Token from `--token` / `PROBING_TOKEN`, see [`set_auth_token`].
+static AUTH_TOKEN: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// Send `token` as `Authorization: Bearer` on every request; `None` falls
+/// back to `PROBING_AUTH_TOKEN`.
+pub fn set_auth_token(token: Option<&str>) {
+    let token = token.map(str::trim).filter(|t| !t.is_empty());
+    *AUTH_TOKEN.write().unwrap_or_else(|e| e.into_inner()) = token.map(String::from);
+}
+
+pub(crate) fn auth_token() -> Option<String> {
+    let set = AUTH_TOKEN.read().unwrap_or_else(|e| e.into_inner()).clone();
+    set.or_else(|| {
+        std::env::var("PROBING_AUTH_TOKEN")
+            .ok()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+    })
+}
+
+fn apply_auth_headers(builder: hyper::http::request::Builder) -> hyper::http::request::Builder {
+    match auth_token().and_then(|t| HeaderValue::from_str(&format!("Bearer {t}")).ok()) {
+        Some(value) => builder.header(AUTHORIZATION, value),
+        None => builder,
     }
-    builder
 }
@@ -0,0 +1,218 @@
+//! `probing <pid> detach`: switch probing off in the target and leave the
+//! process running.
+//!
+//! `POST /apis/detach` disables the profilers, removes the Python hooks,
+//! stops the samplers and background loops, and closes the target's HTTP
+//! listeners. Each part is reported on its own line. Afterwards the command
+//! checks that the control socket (and the TCP port, if the target had one)
+//! no longer accepts connections. When a part failed or a listener is still
+//! open the command exits with [`EXIT_PARTIAL`]. The endpoint is admin-only:
+//! set `PROBING_AUTH_TOKEN` to the target's `server.auth_token`.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::cli::ctrl::{request, ProbeEndpoint};
+
+/// Process exit code when a part could not be torn down or a listener is
+/// still open.
+pub const EXIT_PARTIAL: i32 = 5;
+
+/// How long the listeners get to close after the reply.
+const CLOSE_WAIT: Duration = Duration::from_secs(3);
+const CLOSE_POLL: Duration = Duration::from_millis(100);
+
+/// How [`run`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetachExit {
+    Detached,
+    Partial,
+}
+
+/// One part of probing torn down by the target.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Component {
+    name: String,
+    ok: bool,
+    #[serde(default)]
+    detail: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Reply of `POST /apis/detach`.
+#[derive(Debug, Deserialize)]
+struct DetachReport {
+    pid: u32,
+    #[serde(default)]
+    address: Option<String>,
+    components: Vec<Component>,
+}
+
+pub async fn run(ctrl: ProbeEndpoint) -> Result<DetachExit> {
+    let reply = request(ctrl.clone(), "/apis/detach", Some(String::new())).await?;
+    let report: DetachReport = serde_json::from_slice(&reply).map_err(|_| {
+        anyhow::anyhow!("detach failed: {}", String::from_utf8_lossy(&reply).trim())
+    })?;
+
+    println!("detached probing from pid {}:", report.pid);
+    for line in component_lines(&report.components) {
+        println!("{line}");
+    }
+
+    let mut listeners: Vec<Listener> = Listener::of(&ctrl).into_iter().collect();
+    if let Some(addr) = report.address.as_deref().and_then(tcp_check_addr) {
+        let tcp = Listener::Tcp(addr);
+        if !listeners.contains(&tcp) {
+            listeners.push(tcp);
+        }
+    }
+    let mut open = false;
+    for listener in &listeners {
+        if listener.wait_closed().await {
+            println!("{listener} closed");
+        } else {
+            println!("{listener} still accepts connections");
+            open = true;
+        }
+    }
+
+    Ok(
+        if open || report.components.iter().any(|component| !component.ok) {
+            DetachExit::Partial
+        } else {
+            DetachExit::Detached
+        },
+    )
+}
+
+/// `  ok      name   detail` rows, names padded to one column.
+fn component_lines(components: &[Component]) -> Vec<String> {
+    let width = components.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    components
+        .iter()
+        .map(|c| {
+            let (status, text) = if c.ok {
+                ("ok", c.detail.as_deref().unwrap_or(""))
+            } else {
+                ("FAILED", c.error.as_deref().unwrap_or("unknown error"))
+            };
+            format!("  {status:<6}  {:<width$}  {text}", c.name)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Where to check a wildcard listen address from this host.
+fn tcp_check_addr(addr: &str) -> Option<String> {
+    let (host, port) = addr.rsplit_once(':')?;
+    Some(match host {
+        "0.0.0.0" => format!("127.0.0.1:{port}"),
+        "[::]" => format!("[::1]:{port}"),
+        _ => addr.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Listener {
+    /// Control socket of a local pid.
+    Unix(i32),
+    Tcp(String),
+}
+
+impl std::fmt::Display for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Listener::Unix(pid) => write!(f, "control socket of pid {pid}"),
+            Listener::Tcp(addr) => write!(f, "listener at {addr}"),
+        }
+    }
+}
+
+impl Listener {
+    fn of(ctrl: &ProbeEndpoint) -> Option<Self> {
+        match ctrl {
+            ProbeEndpoint::Ptrace { pid } | ProbeEndpoint::Local { pid } => {
+                Some(Listener::Unix(*pid))
+            }
+            ProbeEndpoint::Remote { addr } => Some(Listener::Tcp(addr.clone())),
+            ProbeEndpoint::Launch { .. } => None,
+        }
+    }
+
+    async fn accepts(&self) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Listener::Unix(pid) => tokio::net::UnixStream::connect(format!("\0probing-{pid}"))
+                .await
+                .is_ok(),
+            #[cfg(not(target_os = "linux"))]
+            Listener::Unix(pid) => {
+                let path = std::env::temp_dir().join(format!("probing-{pid}.sock"));
+                tokio::net::UnixStream::connect(path).await.is_ok()
+            }
+            Listener::Tcp(addr) => tokio::net::TcpStream::connect(addr.as_str()).await.is_ok(),
+        }
+    }
+
+    /// Whether the listener stopped accepting within [`CLOSE_WAIT`].
+    async fn wait_closed(&self) -> bool {
+        let deadline = tokio::time::Instant::now() + CLOSE_WAIT;
+        while self.accepts().await {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(CLOSE_POLL).await;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_components_with_their_outcome() {
+        let report: DetachReport = serde_json::from_str(
+            r#"{"pid": 42, "address": "0.0.0.0:9700", "components": [
+                {"name": "pprof profiler", "ok": true, "detail": "not active"},
+                {"name": "torch module hooks", "ok": false, "error": "hook still referenced"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            component_lines(&report.components),
+            [
+                "  ok      pprof profiler      not active",
+                "  FAILED  torch module hooks  hook still referenced",
+            ]
+        );
+        assert_eq!(
+            report
+                .address
+                .as_deref()
+                .and_then(tcp_check_addr)
+                .as_deref(),
+            Some("127.0.0.1:9700")
+        );
+        assert_eq!(tcp_check_addr("[::]:80").as_deref(), Some("[::1]:80"));
+        assert_eq!(
+            tcp_check_addr("10.0.0.2:9700").as_deref(),
+            Some("10.0.0.2:9700")
+        );
+    }
+
+    #[tokio::test]
+    async fn closed_listener_is_detected() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let check = Listener::Tcp(addr);
+        assert!(check.accepts().await);
+        drop(listener);
+        assert!(check.wait_closed().await);
+    }
+}
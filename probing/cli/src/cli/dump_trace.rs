@@ -0,0 +1,410 @@
+//! `probing dump-trace`: save the target's span timeline to a file.
+//!
+//! By default the chrome-tracing document of
+//! `/apis/trace/chrome-tracing/download` is written as it streams in, ready
+//! for Perfetto or `chrome://tracing`. `--raw` writes the rows of
+//! `python.trace_event` instead, one JSON object per line, fetched in pages
+//! keyed on `time`.
+//! Either way the output is never held in memory in full; `--gzip`
+//! compresses it locally and `--output -` writes to stdout. The event count
+//! and time range go to stderr.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use clap::Args;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use probing_proto::prelude::{DataFrame, Ele, Query};
+
+use crate::cli::ctrl::{stream_chunks, ProbeEndpoint};
+use crate::table::json_rows;
+
+const RAW_TABLE: &str = "python.trace_event";
+/// Rows fetched per query in `--raw` mode.
+const RAW_PAGE_ROWS: usize = 5000;
+
+#[derive(Args, Debug, Clone)]
+pub struct DumpTraceCommand {
+    /// File to write, `-` for stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: String,
+
+    /// Export at most N events (default: all)
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Write `python.trace_event` rows as JSON lines instead of chrome tracing JSON
+    #[arg(long)]
+    pub raw: bool,
+
+    /// Gzip the output (`.gz` is appended to FILE unless present)
+    #[arg(long)]
+    pub gzip: bool,
+}
+
+/// Where the dump goes: a file or stdout, gzipped or not.
+enum Output {
+    Plain(Box<dyn Write>),
+    Gzip(GzEncoder<Box<dyn Write>>),
+}
+
+impl Output {
+    fn open(path: &str, gzip: bool) -> Result<Self> {
+        let inner: Box<dyn Write> = if path == "-" {
+            Box::new(std::io::stdout().lock())
+        } else {
+            let file = File::create(path).with_context(|| format!("failed to create {path}"))?;
+            Box::new(BufWriter::new(file))
+        };
+        Ok(if gzip {
+            Output::Gzip(GzEncoder::new(inner, Compression::default()))
+        } else {
+            Output::Plain(inner)
+        })
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(mut out) => out.flush(),
+            Output::Gzip(out) => out.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::Plain(out) => out.write(buf),
+            Output::Gzip(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(out) => out.flush(),
+            Output::Gzip(out) => out.flush(),
+        }
+    }
+}
+
+/// `path` as written with `--gzip`.
+fn output_path(path: &str, gzip: bool) -> String {
+    if gzip && path != "-" && !path.ends_with(".gz") {
+        format!("{path}.gz")
+    } else {
+        path.to_string()
+    }
+}
+
+impl DumpTraceCommand {
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        let path = output_path(&self.output, self.gzip);
+        let mut out = Output::open(&path, self.gzip)?;
+        let summary = if self.raw {
+            dump_raw(&ctrl, &mut out, self.limit).await
+        } else {
+            dump_chrome(ctrl, &mut out, self.limit).await
+        };
+        let finished = out.finish();
+        let summary = summary?;
+        finished.with_context(|| format!("failed to write {path}"))?;
+        let dest = if path == "-" { "stdout" } else { path.as_str() };
+        eprintln!("{} to {dest}", summary.describe(self.raw));
+        Ok(())
+    }
+}
+
+async fn dump_chrome(
+    ctrl: ProbeEndpoint,
+    out: &mut Output,
+    limit: Option<usize>,
+) -> Result<Summary> {
+    // `limit=0` exports every event.
+    let url = format!(
+        "/apis/trace/chrome-tracing/download?limit={}",
+        limit.unwrap_or(0)
+    );
+    let mut scan = ChromeScan::default();
+    stream_chunks(ctrl, &url, |chunk| {
+        scan.feed(chunk);
+        Ok(out.write_all(chunk)?)
+    })
+    .await?;
+    Ok(scan.finish())
+}
+
+async fn dump_raw(ctrl: &ProbeEndpoint, out: &mut Output, limit: Option<usize>) -> Result<Summary> {
+    let mut summary = Summary::default();
+    let mut remaining = limit.unwrap_or(usize::MAX);
+    // Keyset pagination on `time`: each page is cut before its last
+    // timestamp, and the rows sharing that timestamp are then fetched in one
+    // query, so ties across pages are neither skipped nor repeated and every
+    // query is an index-free range scan instead of an ever-growing OFFSET.
+    let mut after: Option<i64> = None;
+    while remaining > 0 {
+        let page = RAW_PAGE_ROWS.min(remaining);
+        let range = match after {
+            Some(t) => format!("time > {t}"),
+            None => "time IS NOT NULL".to_string(),
+        };
+        let df = query_raw(ctrl, &range, Some(page)).await?;
+        let times = row_times(&df);
+        let boundary = match times.last() {
+            Some(&Some(t)) if df.len() == page => t,
+            _ => {
+                write_raw(out, &df, &times, |_| true, &mut summary, &mut remaining)?;
+                break;
+            }
+        };
+        write_raw(
+            out,
+            &df,
+            &times,
+            |t| t < boundary,
+            &mut summary,
+            &mut remaining,
+        )?;
+        let tied = query_raw(ctrl, &format!("time = {boundary}"), None).await?;
+        write_raw(
+            out,
+            &tied,
+            &row_times(&tied),
+            |_| true,
+            &mut summary,
+            &mut remaining,
+        )?;
+        after = Some(boundary);
+    }
+    Ok(summary)
+}
+
+/// Recorded rows of `python.trace_event` in `range`, oldest first. The
+/// `span` rows the table synthesizes from start/end pairs are left out: they
+/// duplicate the recorded rows.
+async fn query_raw(ctrl: &ProbeEndpoint, range: &str, limit: Option<usize>) -> Result<DataFrame> {
+    let mut sql = format!(
+        "SELECT * FROM {RAW_TABLE} WHERE record_type <> 'span' AND {range} \
+         ORDER BY time, thread_id, span_id, record_type"
+    );
+    if let Some(limit) = limit {
+        sql.push_str(&format!(" LIMIT {limit}"));
+    }
+    ctrl.query(Query::new(sql)).await
+}
+
+/// Writes the rows of `df` whose time passes `keep`, up to `remaining`.
+fn write_raw(
+    out: &mut Output,
+    df: &DataFrame,
+    times: &[Option<i64>],
+    keep: impl Fn(i64) -> bool,
+    summary: &mut Summary,
+    remaining: &mut usize,
+) -> Result<()> {
+    for (row, time) in json_rows(df).into_iter().zip(times) {
+        if *remaining == 0 {
+            break;
+        }
+        let Some(ns) = *time else { continue };
+        if !keep(ns) {
+            continue;
+        }
+        serde_json::to_writer(&mut *out, &row)?;
+        out.write_all(b"\n")?;
+        summary.observe_ts(ns as f64 / 1000.0);
+        summary.events += 1;
+        *remaining -= 1;
+    }
+    Ok(())
+}
+
+/// The `time` of each row of `df` in nanoseconds.
+fn row_times(df: &DataFrame) -> Vec<Option<i64>> {
+    let Some(col) = df.col_index("time").and_then(|i| df.cols.get(i)) else {
+        return vec![None; df.len()];
+    };
+    (0..df.len()).map(|row| time_ns(col.get(row))).collect()
+}
+
+/// Nanoseconds since the epoch; `DataTime` holds microseconds.
+fn time_ns(value: Ele) -> Option<i64> {
+    match value {
+        Ele::I64(ns) | Ele::DateTime(ns) => Some(ns),
+        Ele::DataTime(us) => i64::try_from(us).ok()?.checked_mul(1000),
+        _ => None,
+    }
+}
+
+/// What was written: events and the range of their timestamps (µs).
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    events: u64,
+    first_us: Option<f64>,
+    last_us: Option<f64>,
+}
+
+impl Summary {
+    fn observe_ts(&mut self, us: f64) {
+        self.first_us = Some(self.first_us.map_or(us, |t| t.min(us)));
+        self.last_us = Some(self.last_us.map_or(us, |t| t.max(us)));
+    }
+
+    /// Raw rows carry wall-clock times; chrome timestamps are relative.
+    fn describe(&self, wall_clock: bool) -> String {
+        let noun = if wall_clock { "rows" } else { "events" };
+        let (Some(first), Some(last)) = (self.first_us, self.last_us) else {
+            return format!("wrote {} {noun}", self.events);
+        };
+        let span = (last - first) / 1e6;
+        if wall_clock {
+            let at = |us: f64| {
+                DateTime::from_timestamp_micros(us as i64)
+                    .map(|t| {
+                        t.with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M:%S%.3f")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| format!("{us}"))
+            };
+            format!(
+                "wrote {} {noun} from {} to {} ({span:.3} s)",
+                self.events,
+                at(first),
+                at(last)
+            )
+        } else {
+            format!("wrote {} {noun} spanning {span:.3} s", self.events)
+        }
+    }
+}
+
+/// Counts events (`"ph"` keys) and tracks `"ts"` values of a chrome-tracing
+/// document fed in arbitrary chunks, without parsing it.
+#[derive(Default)]
+struct ChromeScan {
+    summary: Summary,
+    /// Unscanned tail of the previous chunk: a key may straddle chunks.
+    carry: Vec<u8>,
+}
+
+impl ChromeScan {
+    /// Longest `"ts": <number>` the scanner needs to see whole.
+    const WINDOW: usize = 48;
+
+    fn feed(&mut self, chunk: &[u8]) {
+        let mut buf = std::mem::take(&mut self.carry);
+        buf.extend_from_slice(chunk);
+        let end = buf.len().saturating_sub(Self::WINDOW);
+        self.scan(&buf, end);
+        self.carry = buf[end..].to_vec();
+    }
+
+    fn finish(mut self) -> Summary {
+        let buf = std::mem::take(&mut self.carry);
+        self.scan(&buf, buf.len());
+        self.summary
+    }
+
+    /// Keys starting before `end`.
+    fn scan(&mut self, buf: &[u8], end: usize) {
+        let mut i = 0;
+        while i < end {
+            let Some(pos) = buf[i..end].iter().position(|b| *b == b'"') else {
+                break;
+            };
+            let start = i + pos;
+            i = start + 1;
+            // A quote inside a string value is escaped.
+            if start > 0 && buf[start - 1] == b'\\' {
+                continue;
+            }
+            match key_value(&buf[start..]) {
+                Some((b"ph", _)) => self.summary.events += 1,
+                Some((b"ts", value)) => {
+                    if let Some(ts) = number(value) {
+                        self.summary.observe_ts(ts);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `"key": rest` at the start of `buf`, for short keys.
+fn key_value(buf: &[u8]) -> Option<(&[u8], &[u8])> {
+    let close = buf.get(1..4)?.iter().position(|b| *b == b'"')? + 1;
+    let key = &buf[1..close];
+    let rest = &buf[close + 1..];
+    let colon = rest.iter().position(|b| !b.is_ascii_whitespace())?;
+    (rest[colon] == b':').then(|| (key, &rest[colon + 1..]))
+}
+
+fn number(value: &[u8]) -> Option<f64> {
+    let value = value.trim_ascii_start();
+    let len = value
+        .iter()
+        .position(|b| !(b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'e' | b'E' | b'+')))
+        .unwrap_or(value.len());
+    std::str::from_utf8(&value[..len]).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r#"{"traceEvents": [
+{"name": "step", "ph": "X", "ts": 10.5, "dur": 4, "pid": 1, "tid": 2, "args": {"note": "\"ts\": 999"}},
+{"name": "thread_name", "ph": "M", "pid": 1, "tid": 2, "args": {"name": "main"}},
+{"name": "cpu", "ph": "C", "ts": 2000010.5, "pid": 0, "args": {"rss_kb": 1}}
+]}"#;
+
+    #[test]
+    fn scan_counts_events_across_any_chunking() {
+        for size in [1, 3, 7, 64, DOC.len()] {
+            let mut scan = ChromeScan::default();
+            for chunk in DOC.as_bytes().chunks(size) {
+                scan.feed(chunk);
+            }
+            let summary = scan.finish();
+            assert_eq!(summary.events, 3, "chunk size {size}");
+            assert_eq!(summary.first_us, Some(10.5), "chunk size {size}");
+            assert_eq!(summary.last_us, Some(2000010.5), "chunk size {size}");
+        }
+        assert_eq!(
+            ChromeScan::default().finish().describe(false),
+            "wrote 0 events"
+        );
+    }
+
+    #[test]
+    fn describes_the_covered_range() {
+        let mut summary = Summary {
+            events: 3,
+            ..Default::default()
+        };
+        summary.observe_ts(10.5);
+        summary.observe_ts(2_000_010.5);
+        assert_eq!(summary.describe(false), "wrote 3 events spanning 2.000 s");
+        assert!(summary.describe(true).starts_with("wrote 3 rows from "));
+    }
+
+    #[test]
+    fn raw_times_are_nanoseconds() {
+        assert_eq!(time_ns(Ele::I64(1_500)), Some(1_500));
+        assert_eq!(time_ns(Ele::DateTime(1_500)), Some(1_500));
+        assert_eq!(time_ns(Ele::DataTime(3)), Some(3_000));
+        assert_eq!(time_ns(Ele::Nil), None);
+    }
+
+    #[test]
+    fn gzip_appends_extension() {
+        assert_eq!(output_path("trace.json", true), "trace.json.gz");
+        assert_eq!(output_path("trace.json.gz", true), "trace.json.gz");
+        assert_eq!(output_path("-", true), "-");
+        assert_eq!(output_path("trace.json", false), "trace.json");
+    }
+}
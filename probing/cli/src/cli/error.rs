@@ -0,0 +1,200 @@
+//! Typed CLI failures and the exit codes scripts can match on.
+//!
+//! Errors travel as `anyhow::Error`. Paths that know why they failed raise a
+//! [`CliError`], or attach one as context so the underlying cause stays in
+//! the chain. [`exit_code`] finds it at any depth; any other error exits
+//! with [`EXIT_FAILURE`]. Codes 3 to 5 are left to command outcomes (see
+//! `watchdog`, `flamegraph` and `detach`), so the error codes start at 10.
+//!
+//! `--error-format json` prints the failure on stderr as one JSON object
+//! (see [`render`]) instead of the `Error: ...` text.
+
+use clap::ValueEnum;
+use serde_json::json;
+
+/// Any failure without a more specific code.
+pub const EXIT_FAILURE: i32 = 1;
+/// Bad arguments, including those clap rejects.
+pub const EXIT_USAGE: i32 = 2;
+/// No such process, or probing cannot be reached or attached in it.
+pub const EXIT_TARGET_NOT_FOUND: i32 = 10;
+/// The target requires a token or rejected the one sent.
+pub const EXIT_AUTH: i32 = 11;
+/// The target could not run a query.
+pub const EXIT_QUERY: i32 = 12;
+/// The target did not answer within `--timeout`.
+pub const EXIT_TIMEOUT: i32 = 13;
+
+/// A failure with its own exit code; the message is the whole payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// Arguments clap cannot check, e.g. a malformed target or name.
+    Usage(String),
+    TargetNotFound(String),
+    Auth(String),
+    Query(String),
+    Timeout(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::TargetNotFound(_) => EXIT_TARGET_NOT_FOUND,
+            CliError::Auth(_) => EXIT_AUTH,
+            CliError::Query(_) => EXIT_QUERY,
+            CliError::Timeout(_) => EXIT_TIMEOUT,
+        }
+    }
+
+    /// `kind` of the JSON error object.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::Usage(_) => "usage",
+            CliError::TargetNotFound(_) => "target_not_found",
+            CliError::Auth(_) => "auth",
+            CliError::Query(_) => "query",
+            CliError::Timeout(_) => "timeout",
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(msg)
+            | CliError::TargetNotFound(msg)
+            | CliError::Auth(msg)
+            | CliError::Query(msg)
+            | CliError::Timeout(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// `--error-format`: how a failure is printed on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// `Error: <message>` followed by its causes
+    #[default]
+    Text,
+    /// One JSON object: `kind`, `exit_code`, `message` and `causes`
+    Json,
+}
+
+impl ErrorFormat {
+    /// `--error-format` read from raw arguments, for failures clap reports
+    /// before it has parsed the flag.
+    pub fn from_args(args: &[String]) -> Self {
+        let mut value = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--error-format" {
+                value = args.next().map(String::as_str);
+            } else if let Some(v) = arg.strip_prefix("--error-format=") {
+                value = Some(v);
+            }
+        }
+        value
+            .and_then(|v| <Self as ValueEnum>::from_str(v, true).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// [`CliError::kind`] and exit code of `err`; `("usage", 2)` for clap errors.
+fn classify(err: &anyhow::Error) -> (&'static str, i32) {
+    if let Some(cli) = err.downcast_ref::<CliError>() {
+        (cli.kind(), cli.exit_code())
+    } else if err.downcast_ref::<clap::Error>().is_some() {
+        ("usage", EXIT_USAGE)
+    } else {
+        ("error", EXIT_FAILURE)
+    }
+}
+
+/// Process exit code for `err`.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    classify(err).1
+}
+
+/// `err` as printed on stderr.
+pub fn render(err: &anyhow::Error, format: ErrorFormat) -> String {
+    let (kind, code) = classify(err);
+    match format {
+        // clap's message already reads `error: ...` and ends with the usage.
+        ErrorFormat::Text if err.downcast_ref::<clap::Error>().is_some() => {
+            err.to_string().trim_end().to_string()
+        }
+        ErrorFormat::Text => format!("Error: {err:?}"),
+        ErrorFormat::Json => json!({
+            "kind": kind,
+            "exit_code": code,
+            "message": err.to_string().trim_end(),
+            "causes": err.chain().skip(1).map(|e| e.to_string()).collect::<Vec<_>>(),
+        })
+        .to_string(),
+    }
+}
+
+/// Print `err` on stderr and return the code to exit with.
+pub fn report(err: &anyhow::Error, format: ErrorFormat) -> i32 {
+    eprintln!("{}", render(err, format));
+    exit_code(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn typed_errors_keep_their_code_under_context() {
+        let err = anyhow::Error::from(CliError::Query("error: no table t".into()))
+            .context("unknown option `probing.x`");
+        assert_eq!(exit_code(&err), EXIT_QUERY);
+
+        let io = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        let err = Err::<(), _>(io)
+            .with_context(|| CliError::TargetNotFound("cannot reach pid 7".into()))
+            .unwrap_err();
+        assert_eq!(exit_code(&err), EXIT_TARGET_NOT_FOUND);
+
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn renders_json_objects() {
+        let err = anyhow::Error::from(CliError::Timeout("7 did not answer /query".into()))
+            .context("query failed");
+        let value: serde_json::Value =
+            serde_json::from_str(&render(&err, ErrorFormat::Json)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "kind": "timeout",
+                "exit_code": EXIT_TIMEOUT,
+                "message": "query failed",
+                "causes": ["7 did not answer /query"],
+            })
+        );
+        assert!(render(&err, ErrorFormat::Text).starts_with("Error: query failed"));
+    }
+
+    #[test]
+    fn error_format_is_read_from_raw_args() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            ErrorFormat::from_args(&args(&["probing", "--error-format", "json", "q"])),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            ErrorFormat::from_args(&args(&["probing", "--error-format=JSON"])),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            ErrorFormat::from_args(&args(&["probing", "--error-format"])),
+            ErrorFormat::Text
+        );
+    }
+}
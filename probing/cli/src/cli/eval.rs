@@ -0,0 +1,355 @@
+//! `probing eval`: run Python in the target's interpreter.
+//!
+//! The code comes from the command line, a script (`-f`), or, with `--repl`,
+//! from stdin one statement at a time. Every submission is a
+//! `POST /apis/pythonext/eval` against the process-wide console of the target,
+//! so names bound by one line stay visible to the next. In `--repl` a line
+//! ending in `:` (or `\`, or with open brackets) continues until a blank line,
+//! like the Python prompt, and `<<TAG` starts a block that runs up to a line
+//! reading `TAG` (`<<` alone ends at `EOF`).
+//!
+//! Output is the remote interpreter's; tracebacks go to stderr under a
+//! heading naming the target. When the code raised, the command exits with
+//! [`EXIT_RAISED`].
+
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde::Deserialize;
+
+use super::ctrl::ProbeEndpoint;
+
+/// Process exit code when the remote code raised.
+pub const EXIT_RAISED: i32 = 1;
+
+#[derive(Args, Debug, Clone)]
+pub struct EvalCommand {
+    /// Python code to run
+    #[arg(required_unless_present_any = ["file", "repl"], conflicts_with_all = ["file", "repl"])]
+    code: Option<String>,
+
+    /// Run a script file instead (`-` reads it from stdin)
+    #[arg(short, long, value_name = "FILE", conflicts_with = "repl")]
+    file: Option<PathBuf>,
+
+    /// Read statements from stdin and run each in turn, keeping the remote namespace
+    #[arg(long)]
+    repl: bool,
+}
+
+/// How [`EvalCommand::run`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalExit {
+    Ok,
+    Raised,
+}
+
+/// Body of an eval response.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+struct Reply {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    output: Option<String>,
+    #[serde(default)]
+    traceback: Vec<String>,
+    /// Set when the console itself failed.
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl Reply {
+    /// A body that is not an eval reply is shown as output.
+    fn parse(body: &str) -> Self {
+        serde_json::from_str(body).unwrap_or_else(|_| Reply {
+            output: Some(body.to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn raised(&self) -> bool {
+        self.error.is_some() || matches!(self.status.as_deref(), Some("error" | "incomplete"))
+    }
+
+    fn print(&self, target: &str) -> Result<()> {
+        let mut out = std::io::stdout().lock();
+        if let Some(output) = self.output.as_deref().filter(|o| !o.is_empty()) {
+            write!(out, "{output}")?;
+            if !output.ends_with('\n') {
+                writeln!(out)?;
+            }
+        }
+        out.flush()?;
+        if self.raised() {
+            let mut err = std::io::stderr().lock();
+            writeln!(err, "Python in {target} raised:")?;
+            for line in &self.traceback {
+                writeln!(err, "{line}")?;
+            }
+            if let Some(error) = &self.error {
+                writeln!(err, "{error}")?;
+            }
+            if self.status.as_deref() == Some("incomplete") {
+                writeln!(err, "incomplete input: the code ends inside a statement")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Trailing whitespace dropped and one newline added, which the console
+/// needs to see the end of a compound statement.
+fn submission(code: &str) -> String {
+    format!("{}\n", code.trim_end())
+}
+
+async fn eval(ctrl: &ProbeEndpoint, target: &str, code: &str) -> Result<bool> {
+    let reply = Reply::parse(&ctrl.eval_json(submission(code)).await?);
+    reply.print(target)?;
+    Ok(!reply.raised())
+}
+
+impl EvalCommand {
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<EvalExit> {
+        let target = String::from(ctrl.clone());
+        let code = match (&self.code, &self.file) {
+            (Some(code), _) => code.clone(),
+            (None, Some(path)) if path.as_os_str() == "-" => {
+                std::io::read_to_string(std::io::stdin()).context("failed to read stdin")?
+            }
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?,
+            (None, None) => return repl(&ctrl, &target).await,
+        };
+        if std::io::stdout().is_terminal() {
+            eprintln!("# output of the Python interpreter in {target}");
+        }
+        Ok(if eval(&ctrl, &target, &code).await? {
+            EvalExit::Ok
+        } else {
+            EvalExit::Raised
+        })
+    }
+}
+
+/// One stdin line, read off the async runtime; `None` at end of input.
+async fn read_line() -> Result<Option<String>> {
+    tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+        let n = std::io::stdin().read_line(&mut line)?;
+        Ok::<_, anyhow::Error>((n > 0).then(|| line.trim_end_matches(['\n', '\r']).to_string()))
+    })
+    .await?
+}
+
+async fn repl(ctrl: &ProbeEndpoint, target: &str) -> Result<EvalExit> {
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+        eprintln!(
+            "Python in {target}: names persist between statements. \
+             `<<EOF` starts a block, `exit` or Ctrl-D leaves."
+        );
+    }
+    let mut block = Block::default();
+    let mut raised = false;
+    loop {
+        if interactive {
+            eprint!("[{target}] {}", block.prompt());
+            std::io::stderr().flush()?;
+        }
+        let Some(line) = read_line().await? else {
+            if let Some(code) = block.finish() {
+                raised |= !eval(ctrl, target, &code).await?;
+            }
+            break;
+        };
+        match block.feed(&line) {
+            Feed::More => {}
+            Feed::Exit => break,
+            Feed::Ready(code) => raised |= !eval(ctrl, target, &code).await?,
+        }
+    }
+    Ok(if raised {
+        EvalExit::Raised
+    } else {
+        EvalExit::Ok
+    })
+}
+
+/// What a line of REPL input completed.
+#[derive(Debug, PartialEq, Eq)]
+enum Feed {
+    More,
+    Ready(String),
+    /// `exit` / `quit`: leave the local session, not the remote interpreter.
+    Exit,
+}
+
+/// Lines of one REPL submission.
+#[derive(Debug, Default)]
+struct Block {
+    lines: Vec<String>,
+    /// Terminator of a `<<TAG` block.
+    heredoc: Option<String>,
+    /// A `:` line was seen: only a blank line ends the block.
+    compound: bool,
+}
+
+impl Block {
+    fn prompt(&self) -> &'static str {
+        if self.lines.is_empty() && self.heredoc.is_none() {
+            ">>> "
+        } else {
+            "... "
+        }
+    }
+
+    fn feed(&mut self, line: &str) -> Feed {
+        if let Some(tag) = &self.heredoc {
+            if line.trim() == tag {
+                self.heredoc = None;
+                return self.take();
+            }
+            self.lines.push(line.to_string());
+            return Feed::More;
+        }
+        let trimmed = line.trim();
+        if self.lines.is_empty() {
+            if let Some(tag) = trimmed.strip_prefix("<<") {
+                let tag = tag.trim();
+                self.heredoc = Some(if tag.is_empty() { "EOF" } else { tag }.to_string());
+                return Feed::More;
+            }
+            if matches!(trimmed, "exit" | "quit" | "exit()" | "quit()") {
+                return Feed::Exit;
+            }
+            if trimmed.is_empty() {
+                return Feed::More;
+            }
+        } else if trimmed.is_empty() && self.compound {
+            return self.take();
+        }
+        self.lines.push(line.to_string());
+        let end = line.trim_end();
+        self.compound |= end.ends_with(':');
+        if self.compound || end.ends_with('\\') || bracket_depth(&self.lines) > 0 {
+            Feed::More
+        } else {
+            self.take()
+        }
+    }
+
+    /// Whatever is pending at end of input.
+    fn finish(&mut self) -> Option<String> {
+        match self.take() {
+            Feed::Ready(code) if !code.trim().is_empty() => Some(code),
+            _ => None,
+        }
+    }
+
+    fn take(&mut self) -> Feed {
+        self.compound = false;
+        Feed::Ready(std::mem::take(&mut self.lines).join("\n"))
+    }
+}
+
+/// Open brackets across `lines`, skipping string literals and comments.
+fn bracket_depth(lines: &[String]) -> i32 {
+    let mut depth = 0;
+    for line in lines {
+        let mut quote = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(_), '\\') => {
+                    chars.next();
+                }
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '#') => break,
+                (None, '(' | '[' | '{') => depth += 1,
+                (None, ')' | ']' | '}') => depth -= 1,
+                (None, _) => {}
+            }
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(lines: &[&str]) -> Vec<Feed> {
+        let mut block = Block::default();
+        lines.iter().map(|line| block.feed(line)).collect()
+    }
+
+    #[test]
+    fn simple_lines_run_at_once() {
+        assert_eq!(
+            feed_all(&["x = 1", "", "x"]),
+            [
+                Feed::Ready("x = 1".into()),
+                Feed::More,
+                Feed::Ready("x".into())
+            ]
+        );
+        assert_eq!(feed_all(&["exit"]), [Feed::Exit]);
+    }
+
+    #[test]
+    fn compound_statements_end_at_a_blank_line() {
+        let fed = feed_all(&["for i in range(3):", "    print(i)", ""]);
+        assert_eq!(
+            fed.last().unwrap(),
+            &Feed::Ready("for i in range(3):\n    print(i)".into())
+        );
+        let fed = feed_all(&["d = {", "  'a': ')',", "}"]);
+        assert_eq!(
+            fed.last().unwrap(),
+            &Feed::Ready("d = {\n  'a': ')',\n}".into())
+        );
+    }
+
+    #[test]
+    fn heredoc_runs_up_to_its_tag() {
+        let fed = feed_all(&["<<END", "def f():", "", "    return 1", "END", "f()"]);
+        assert_eq!(fed[4], Feed::Ready("def f():\n\n    return 1".into()));
+        assert_eq!(fed[5], Feed::Ready("f()".into()));
+
+        let mut block = Block::default();
+        assert_eq!(block.feed("<<"), Feed::More);
+        assert_eq!(block.prompt(), "... ");
+        block.feed("y = 2");
+        assert_eq!(block.feed("EOF"), Feed::Ready("y = 2".into()));
+    }
+
+    #[test]
+    fn pending_input_runs_at_end_of_input() {
+        let mut block = Block::default();
+        block.feed("if True:");
+        block.feed("    z = 3");
+        assert_eq!(block.finish().as_deref(), Some("if True:\n    z = 3"));
+        assert_eq!(block.finish(), None);
+    }
+
+    #[test]
+    fn replies_report_exceptions() {
+        let ok = Reply::parse(r#"{"status": "ok", "output": "10", "traceback": []}"#);
+        assert!(!ok.raised());
+        assert_eq!(ok.output.as_deref(), Some("10"));
+        let err = Reply::parse(r#"{"status": "error", "traceback": ["NameError: y"]}"#);
+        assert!(err.raised());
+        assert!(Reply::parse(r#"{"error": "REPL execution panicked"}"#).raised());
+        assert!(!Reply::parse("{}").raised());
+        assert_eq!(Reply::parse("plain").output.as_deref(), Some("plain"));
+        assert_eq!(
+            submission("for i in x:\n  f(i)\n\n  "),
+            "for i in x:\n  f(i)\n"
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! `probing flamegraph`: a CPU (pprof) or PyTorch module flamegraph of the
+//! target as interactive HTML, a standalone SVG, the web UI's JSON, or
+//! folded stack lines (`frame;frame count`) for `flamegraph.pl`, speedscope
+//! and similar tools.
+//!
+//! A disabled profiler has nothing to draw. The command then exits with
+//! [`EXIT_PROFILER_DISABLED`] rather than 1, so scripts can tell it from a
+//! connection error, unless `--enable` is given: the profiler is switched on
+//! for `--duration`, the profile captured, and the previous setting restored.
+
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::commands::FlamegraphKind;
+use super::config;
+use super::ctrl::{request, ProbeEndpoint};
+use super::watchdog::parse_interval;
+
+/// Process exit code when the profiler is disabled and `--enable` was not given.
+pub const EXIT_PROFILER_DISABLED: i32 = 4;
+
+#[derive(Args, Debug, Clone)]
+pub struct FlamegraphCommand {
+    /// Flamegraph source: `pprof` (CPU sampling) or `torch` (PyTorch modules)
+    #[arg(value_enum)]
+    kind: Option<FlamegraphKind>,
+
+    /// Flamegraph source, same as the positional argument
+    #[arg(long, value_enum, conflicts_with = "kind")]
+    profiler: Option<FlamegraphKind>,
+
+    /// Write output to a file instead of stdout (a `.svg` path implies `--svg`)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Emit the web UI's flamegraph JSON instead of interactive HTML
+    #[arg(short, long, conflicts_with_all = ["svg", "folded"])]
+    json: bool,
+
+    /// Emit a standalone SVG instead of interactive HTML
+    #[arg(long, conflicts_with = "folded")]
+    svg: bool,
+
+    /// Emit folded stack lines (`frame;frame count`) instead of interactive HTML
+    #[arg(long)]
+    folded: bool,
+
+    /// If the profiler is disabled, enable it for `--duration`, capture, then restore it
+    #[arg(long)]
+    enable: bool,
+
+    /// How long to profile with `--enable`, e.g. `30s`, `2m` or `30`
+    #[arg(long, default_value = "30s", value_parser = parse_interval, requires = "enable")]
+    duration: Duration,
+}
+
+/// How [`FlamegraphCommand::run`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlamegraphExit {
+    Written,
+    ProfilerDisabled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Html,
+    Json,
+    Svg,
+    Folded,
+}
+
+impl FlamegraphKind {
+    /// Option that switches the profiler on and off.
+    fn option_key(self) -> &'static str {
+        match self {
+            FlamegraphKind::Pprof => "probing.pprof.sample_freq",
+            FlamegraphKind::Torch => "probing.torch.profiling",
+        }
+    }
+
+    /// Value of [`Self::option_key`] set by `--enable`.
+    fn enable_value(self) -> &'static str {
+        match self {
+            FlamegraphKind::Pprof => "100",
+            FlamegraphKind::Torch => "on",
+        }
+    }
+
+    /// Whether `value` of [`Self::option_key`] leaves the profiler running.
+    fn is_enabled_by(self, value: Option<&str>) -> bool {
+        let value = value.map(str::trim).unwrap_or_default();
+        match self {
+            // A frequency below 1 (or none) tears the sampler down.
+            FlamegraphKind::Pprof => value.parse::<i32>().is_ok_and(|freq| freq >= 1),
+            // `off`, `false`, `0`, or a spec with `enabled=off`.
+            FlamegraphKind::Torch => {
+                let off =
+                    |v: &str| matches!(v.to_ascii_lowercase().as_str(), "off" | "false" | "0");
+                let mut tokens = value.split(',').map(str::trim).filter(|t| !t.is_empty());
+                match tokens.next() {
+                    None => false,
+                    Some(first) if off(first) => false,
+                    Some(_) => !value.split(',').any(|t| {
+                        t.split_once('=')
+                            .is_some_and(|(k, v)| k.trim() == "enabled" && off(v.trim()))
+                    }),
+                }
+            }
+        }
+    }
+}
+
+impl FlamegraphCommand {
+    fn kind(&self) -> FlamegraphKind {
+        self.profiler.or(self.kind).unwrap_or(FlamegraphKind::Pprof)
+    }
+
+    fn format(&self) -> Format {
+        let svg_path = self
+            .output
+            .as_deref()
+            .is_some_and(|path| path.to_ascii_lowercase().ends_with(".svg"));
+        if self.folded {
+            Format::Folded
+        } else if self.json {
+            Format::Json
+        } else if self.svg || svg_path {
+            Format::Svg
+        } else {
+            Format::Html
+        }
+    }
+
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<FlamegraphExit> {
+        let kind = self.kind();
+        let key = kind.option_key();
+        let previous = config::read(&ctrl, key).await?;
+        if kind.is_enabled_by(previous.as_deref()) {
+            self.capture(&ctrl, kind).await?;
+            return Ok(FlamegraphExit::Written);
+        }
+        if !self.enable {
+            eprintln!(
+                "the {} profiler is disabled ({key} is {}); rerun with `--enable [--duration 30s]` \
+                 to profile for a while, or set {key} first",
+                kind.as_str(),
+                previous.as_deref().unwrap_or("unset"),
+            );
+            return Ok(FlamegraphExit::ProfilerDisabled);
+        }
+
+        config::write(&ctrl, key, kind.enable_value()).await?;
+        eprintln!(
+            "enabled the {} profiler ({key}={}), profiling for {:?}...",
+            kind.as_str(),
+            kind.enable_value(),
+            self.duration
+        );
+        let captured = tokio::select! {
+            _ = tokio::time::sleep(self.duration) => self.capture(&ctrl, kind).await,
+            _ = tokio::signal::ctrl_c() => Err(anyhow::anyhow!("interrupted before the capture")),
+        };
+        // Restore even when the capture failed; an unset option is cleared.
+        let restored = config::write(&ctrl, key, previous.as_deref().unwrap_or_default()).await;
+        match &restored {
+            Ok(()) => eprintln!(
+                "restored {key} to {}",
+                previous.as_deref().unwrap_or("(unset)")
+            ),
+            Err(err) => eprintln!("failed to restore {key}: {err:#}"),
+        }
+        captured?;
+        restored?;
+        Ok(FlamegraphExit::Written)
+    }
+
+    async fn capture(&self, ctrl: &ProbeEndpoint, kind: FlamegraphKind) -> Result<()> {
+        let format = self.format();
+        let bytes = match format {
+            Format::Html | Format::Json => {
+                ctrl.flamegraph(kind.as_str(), format == Format::Json)
+                    .await?
+            }
+            Format::Folded => {
+                let mut text = folded_lines(ctrl, kind).await?.join("\n");
+                text.push('\n');
+                text.into_bytes()
+            }
+            Format::Svg => {
+                let lines = folded_lines(ctrl, kind).await?;
+                let title = match kind {
+                    FlamegraphKind::Pprof => "CPU sampling",
+                    FlamegraphKind::Torch => "Module performance",
+                };
+                let body = serde_json::json!({ "lines": lines, "title": title });
+                request(ctrl.clone(), "/apis/profile/svg", Some(body.to_string())).await?
+            }
+        };
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &bytes)?;
+                eprintln!("flamegraph ({}) written to {path}", kind.as_str());
+            }
+            None => {
+                std::io::stdout().write_all(&bytes)?;
+                std::io::stdout().flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Folded stacks of the profiler; an error when it has none yet.
+async fn folded_lines(ctrl: &ProbeEndpoint, kind: FlamegraphKind) -> Result<Vec<String>> {
+    #[derive(serde::Deserialize)]
+    struct Folded {
+        lines: Vec<String>,
+    }
+    let url = match kind {
+        FlamegraphKind::Pprof => "/apis/pprofextension/flamegraph/folded/json",
+        FlamegraphKind::Torch => "/apis/torchextension/flamegraph/folded/json",
+    };
+    let body = request(ctrl.clone(), url, None).await?;
+    let lines = serde_json::from_slice::<Folded>(&body)?.lines;
+    if lines.is_empty() {
+        anyhow::bail!(
+            "the {} profiler has no samples yet; let the workload run longer",
+            kind.as_str()
+        );
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        cmd: FlamegraphCommand,
+    }
+
+    fn parse(args: &[&str]) -> FlamegraphCommand {
+        Cli::try_parse_from(std::iter::once("flamegraph").chain(args.iter().copied()))
+            .unwrap()
+            .cmd
+    }
+
+    #[test]
+    fn picks_profiler_and_format() {
+        let cmd = parse(&["--profiler", "torch", "-o", "out.SVG"]);
+        assert_eq!(
+            (cmd.kind(), cmd.format()),
+            (FlamegraphKind::Torch, Format::Svg)
+        );
+        let cmd = parse(&["torch", "--folded", "-o", "out.svg"]);
+        assert_eq!(
+            (cmd.kind(), cmd.format()),
+            (FlamegraphKind::Torch, Format::Folded)
+        );
+        let cmd = parse(&[]);
+        assert_eq!(
+            (cmd.kind(), cmd.format()),
+            (FlamegraphKind::Pprof, Format::Html)
+        );
+        assert_eq!(cmd.duration, Duration::from_secs(30));
+        assert_eq!(parse(&["--json"]).format(), Format::Json);
+
+        let parse_err = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("flamegraph").chain(args.iter().copied())).is_err()
+        };
+        assert!(parse_err(&["torch", "--profiler", "pprof"]));
+        assert!(parse_err(&["--duration", "5s"]));
+        assert!(parse_err(&["--json", "--folded"]));
+        assert_eq!(
+            parse(&["--enable", "--duration", "2m"]).duration,
+            Duration::from_secs(120)
+        );
+    }
+
+    #[test]
+    fn reads_profiler_state_from_its_option() {
+        let pprof = FlamegraphKind::Pprof;
+        assert!(pprof.is_enabled_by(Some("100")));
+        assert!(!pprof.is_enabled_by(Some("0")));
+        assert!(!pprof.is_enabled_by(Some("")));
+        assert!(!pprof.is_enabled_by(None));
+
+        let torch = FlamegraphKind::Torch;
+        assert!(torch.is_enabled_by(Some("on")));
+        assert!(torch.is_enabled_by(Some("random:0.1,tracepy=on")));
+        assert!(!torch.is_enabled_by(Some("off")));
+        assert!(!torch.is_enabled_by(Some("on,enabled=false")));
+        assert!(!torch.is_enabled_by(None));
+    }
+}
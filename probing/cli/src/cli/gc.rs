@@ -0,0 +1,107 @@
+//! `probing <pid> gc [--generation N]`: force a garbage collection in the
+//! target and print what it freed.
+//!
+//! The target runs `gc.collect` (all generations unless `--generation`
+//! picks one) and reports collected and uncollectable object counts, the
+//! time the collection took and RSS before and after. The run is also
+//! recorded in the target's `probe.events`. The endpoint is admin-only: set
+//! `PROBING_AUTH_TOKEN` to the target's `server.auth_token`.
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::cli::bench::metrics::human_bytes;
+use crate::cli::ctrl::{request, ProbeEndpoint};
+
+/// Reply of `/apis/pythonext/gc`.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct GcReport {
+    pub generation: u8,
+    pub collected: u64,
+    pub uncollectable: u64,
+    pub unreachable: u64,
+    #[serde(default)]
+    pub garbage: u64,
+    pub duration_ms: f64,
+    pub rss_before: Option<u64>,
+    pub rss_after: Option<u64>,
+}
+
+impl std::fmt::Display for GcReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "generation     {}", self.generation)?;
+        writeln!(f, "collected      {} objects", self.collected)?;
+        writeln!(f, "uncollectable  {} objects", self.uncollectable)?;
+        if self.garbage > 0 {
+            writeln!(f, "gc.garbage     {} objects", self.garbage)?;
+        }
+        writeln!(f, "duration       {:.2} ms", self.duration_ms)?;
+        match (self.rss_before, self.rss_after) {
+            (Some(before), Some(after)) => {
+                let (sign, delta) = if after >= before {
+                    ("+", after - before)
+                } else {
+                    ("-", before - after)
+                };
+                write!(
+                    f,
+                    "rss            {} -> {} ({sign}{})",
+                    human_bytes(before),
+                    human_bytes(after),
+                    human_bytes(delta)
+                )
+            }
+            _ => write!(f, "rss            unavailable"),
+        }
+    }
+}
+
+pub async fn run(ctrl: ProbeEndpoint, generation: Option<u8>) -> Result<()> {
+    let url = match generation {
+        Some(g) => format!("/apis/pythonext/gc?generation={g}"),
+        None => "/apis/pythonext/gc".to_string(),
+    };
+    let reply = request(ctrl, &url, None).await?;
+    println!("{}", parse_report(&reply)?);
+    Ok(())
+}
+
+fn parse_report(reply: &[u8]) -> Result<GcReport> {
+    let value: serde_json::Value = serde_json::from_slice(reply)
+        .map_err(|_| anyhow::anyhow!("gc failed: {}", String::from_utf8_lossy(reply).trim()))?;
+    if let Some(err) = value.get("error").and_then(|e| e.as_str()) {
+        anyhow::bail!("gc failed: {err}");
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_prints_report() {
+        let report = parse_report(
+            br#"{"generation": 2, "unreachable": 40, "collected": 40, "uncollectable": 0,
+                 "garbage": 0, "duration_ms": 1.5, "rss_before": 2097152, "rss_after": 1048576}"#,
+        )
+        .unwrap();
+        assert_eq!(report.collected, 40);
+        let text = report.to_string();
+        assert!(text.contains("collected      40 objects"), "{text}");
+        assert!(text.contains("2.00 MiB -> 1.00 MiB (-1.00 MiB)"), "{text}");
+        assert!(!text.contains("gc.garbage"), "{text}");
+    }
+
+    #[test]
+    fn surfaces_server_errors() {
+        let err = parse_report(br#"{"error": "generation must be 0, 1 or 2, got 5"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("generation must be"), "{err}");
+        let err = parse_report(b"/pythonext/gc requires the admin token")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("admin token"), "{err}");
+    }
+}
@@ -26,58 +26,84 @@ struct HelpSection {
 const SECTIONS: &[HelpSection] = &[
     HelpSection {
         heading: "Processes",
-        blurb: "Start probing on a process, wrap a new command, or list probed PIDs",
-        commands: &["inject", "launch", "list"],
+        blurb: "Find Python PIDs, start or stop probing, wrap a new command, or list probed PIDs",
+        commands: &["ps", "inject", "launch", "list", "detach"],
     },
     HelpSection {
         heading: "Analyze",
-        blurb: "Run SQL, inspect table catalog, fan out across cluster nodes",
-        commands: &["query", "tables", "cluster"],
+        blurb: "Run SQL, inspect table catalog, fan out across cluster nodes, replay traces",
+        commands: &[
+            "query",
+            "tables",
+            "cluster",
+            "analyze",
+            "dump-trace",
+            "watchdog",
+            "serve-snapshot",
+        ],
     },
     HelpSection {
         heading: "Diagnose",
-        blurb: "Interactive inspection — Python eval, REPL, stack traces",
-        commands: &["eval", "repl", "backtrace"],
+        blurb: "Interactive inspection — Python eval, REPL, stack traces, live variable traces",
+        commands: &["eval", "repl", "backtrace", "stacks", "trace"],
     },
     HelpSection {
         heading: "Runtime",
-        blurb: "Runtime state and profiling — memory, config, flamegraphs, RDMA flows",
-        commands: &["memory", "config", "flamegraph", "rdma"],
+        blurb: "Runtime state and profiling — live top, memory, config, flamegraphs, RDMA flows",
+        commands: &["top", "memory", "config", "flamegraph", "pprof", "rdma"],
     },
     HelpSection {
         heading: "Agent",
         blurb: "Diagnostic skills and MCP — start with `skill run health_overview`",
         commands: &["skill", "mcp"],
     },
+    HelpSection {
+        heading: "Shell",
+        blurb: "Tab completion for subcommands, options and target pids",
+        commands: &["completions"],
+    },
 ];
 
 #[cfg(not(target_os = "linux"))]
 const SECTIONS: &[HelpSection] = &[
     HelpSection {
         heading: "Processes",
-        blurb: "List processes that already have probing enabled",
-        commands: &["list"],
+        blurb: "List processes that already have probing enabled, or stop probing in one",
+        commands: &["list", "detach"],
     },
     HelpSection {
         heading: "Analyze",
-        blurb: "Run SQL, inspect table catalog, fan out across cluster nodes",
-        commands: &["query", "tables", "cluster"],
+        blurb: "Run SQL, inspect table catalog, fan out across cluster nodes, replay traces",
+        commands: &[
+            "query",
+            "tables",
+            "cluster",
+            "analyze",
+            "dump-trace",
+            "watchdog",
+            "serve-snapshot",
+        ],
     },
     HelpSection {
         heading: "Diagnose",
-        blurb: "Interactive inspection — Python eval, REPL, stack traces",
-        commands: &["eval", "repl", "backtrace"],
+        blurb: "Interactive inspection — Python eval, REPL, stack traces, live variable traces",
+        commands: &["eval", "repl", "backtrace", "stacks", "trace"],
     },
     HelpSection {
         heading: "Runtime",
-        blurb: "Runtime state and profiling — memory, config, flamegraphs, RDMA flows",
-        commands: &["memory", "config", "flamegraph", "rdma"],
+        blurb: "Runtime state and profiling — live top, memory, config, flamegraphs, RDMA flows",
+        commands: &["top", "memory", "config", "flamegraph", "pprof", "rdma"],
     },
     HelpSection {
         heading: "Agent",
         blurb: "Diagnostic skills and MCP — start with `skill run health_overview`",
         commands: &["skill", "mcp"],
     },
+    HelpSection {
+        heading: "Shell",
+        blurb: "Tab completion for subcommands, options and target pids",
+        commands: &["completions"],
+    },
 ];
 
 /// Replace the default flat subcommand list with grouped sections in `{after-help}`.
@@ -125,7 +151,7 @@ fn render_grouped_subcommands(root: &Command) -> String {
 
     out.push_str(
         "\nMost commands need `-t PID` or `-t host:port` \
-         (exceptions: list, skill list/install/update).\n\
+         (exceptions: ps, list, completions, skill list/install/update, analyze --offline).\n\
          Run `probing <cmd> --help` for command-specific options.\n",
     );
     out
@@ -148,6 +174,7 @@ mod tests {
             "Diagnose —",
             "Runtime —",
             "Agent —",
+            "Shell —",
         ] {
             assert!(
                 text.contains(heading),
@@ -5,12 +5,48 @@ use probing_proto::prelude::Query;
 
 use super::ctrl;
 use super::ctrl::ProbeEndpoint;
+use super::error::CliError;
+use super::ranks::{self, PidSource, RankReport, Rendezvous, SshRunner};
+use crate::table::render_dataframe;
 
 /// Inject into the target process
 #[derive(Args, Default, Debug)]
 pub struct InjectCommand {
     #[arg(short = 'D', long = "define", num_args = 1..)]
     settings: Vec<String>,
+
+    /// Inject every rank of the job on every host over ssh; `-t` names the
+    /// launcher whose environment (SLURM_JOB_NODELIST, ...) lists the hosts
+    #[arg(long, conflicts_with = "local_ranks")]
+    all_ranks: bool,
+
+    /// Hosts to inject instead of the launcher's host list
+    #[arg(long, value_delimiter = ',', requires = "all_ranks")]
+    hosts: Vec<String>,
+
+    /// How each host finds its rank processes
+    #[arg(long, value_enum, default_value_t = PidSource::Env)]
+    pids_from: PidSource,
+
+    /// Only ranks whose environment has VAR=VALUE (default: the launcher's job id)
+    #[arg(long, value_name = "VAR=VALUE")]
+    job: Option<String>,
+
+    /// Expected number of ranks (default: WORLD_SIZE / SLURM_NTASKS of the launcher)
+    #[arg(long, requires = "all_ranks")]
+    world_size: Option<u32>,
+
+    /// Command used to reach each host
+    #[arg(long, env = "PROBING_SSH", default_value = "ssh -o BatchMode=yes")]
+    ssh: String,
+
+    /// probing CLI to run on each host
+    #[arg(long, default_value = "probing", requires = "all_ranks")]
+    remote_cli: String,
+
+    /// Inject the rank processes on this host and print one report line each
+    #[arg(long, hide = true)]
+    local_ranks: bool,
 }
 
 impl InjectCommand {
@@ -26,11 +62,27 @@ impl InjectCommand {
         }))
     }
 
+    /// Whether `lib_name` is mapped, or `None` when `/proc/<pid>/maps` is
+    /// unreadable (e.g. a container masking it).
+    fn probe_library(&self, pid: i32, lib_name: &str) -> Option<bool> {
+        match self.check_library(pid, lib_name) {
+            Ok(found) => Some(found),
+            Err(e) => {
+                log::warn!("cannot read memory maps of {pid} to look for {lib_name}: {e}");
+                None
+            }
+        }
+    }
+
     fn wait_for_library(&self, pid: i32, lib_name: &str) -> Result<()> {
-        if self.check_library(pid, lib_name).unwrap_or(false) {
-            return Ok(());
+        match self.probe_library(pid, lib_name) {
+            Some(false) => Err(CliError::TargetNotFound(format!(
+                "Library {lib_name} not found in target process"
+            ))
+            .into()),
+            // Unverifiable: let the injection itself report what is missing.
+            Some(true) | None => Ok(()),
         }
-        Err(anyhow!("Library {} not found in target process", lib_name))
     }
 
     fn build_settings(&self) -> Vec<String> {
@@ -46,40 +98,113 @@ impl InjectCommand {
             .collect()
     }
 
-    fn inject(&self, pid: i32) -> Result<()> {
+    /// ptrace injection, under `--timeout`: a stopped or stuck target would
+    /// otherwise block the attach forever.
+    async fn inject(&self, pid: i32) -> Result<()> {
         let soname = std::fs::read_link("/proc/self/exe")?.with_file_name("libprobing.so");
         let settings = self.build_settings();
 
         println!("Injecting {} into {}", soname.display(), pid);
-        Injector::attach(Process::get(pid as u32).map_err(Error::msg)?)
-            .map_err(Error::msg)?
-            .inject(&soname, settings)
-            .map_err(|e| anyhow!("Failed to inject probing: {}\n\t{}", e, e.root_cause()))
+        let attach = tokio::task::spawn_blocking(move || {
+            let not_attachable = |e: Error| CliError::TargetNotFound(format!("{pid}: {e:#}"));
+            Injector::attach(Process::get(pid as u32).map_err(not_attachable)?)
+                .map_err(not_attachable)?
+                .inject(&soname, settings)
+                .map_err(|e| anyhow!("Failed to inject probing: {}\n\t{}", e, e.root_cause()))
+        });
+        ctrl::within(&ProbeEndpoint::Ptrace { pid }, "the injection", async {
+            attach.await?
+        })
+        .await
+    }
+
+    async fn inject_pid(&self, pid: i32) -> Result<()> {
+        // Unreadable maps: assume probing is not loaded yet.
+        if !self.probe_library(pid, "libprobing.so").unwrap_or(false) {
+            self.wait_for_library(pid, "python")?;
+            self.inject(pid).await
+        } else {
+            let settings = self.build_settings();
+            let query: Vec<String> = settings
+                .iter()
+                .map(|setting| format!("set {setting}"))
+                .collect();
+            let query = query.join(";");
+
+            ctrl::query(
+                ProbeEndpoint::Local { pid },
+                Query {
+                    expr: query,
+                    opts: None,
+                },
+            )
+            .await
+        }
+    }
+
+    /// Runs on each host of an `--all-ranks` injection; failures are
+    /// reported per rank rather than aborting the remaining ranks.
+    async fn inject_local_ranks(&self) -> Result<()> {
+        for local in ranks::local_ranks(self.pids_from, self.job.as_deref())? {
+            let result = self.inject_pid(local.pid).await;
+            let report = RankReport {
+                rank: local.rank,
+                pid: local.pid,
+                ok: result.is_ok(),
+                error: result.err().map(|e| format!("{e:#}")),
+            };
+            println!("{}", report.line());
+        }
+        Ok(())
+    }
+
+    async fn inject_all_ranks(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        let launcher = match ctrl {
+            ProbeEndpoint::Ptrace { pid } | ProbeEndpoint::Local { pid } => pid,
+            _ => 0,
+        };
+        let rdzv = Rendezvous::from_env(&ranks::process_env(launcher)?)?;
+        let hosts = if self.hosts.is_empty() {
+            rdzv.hosts
+        } else {
+            self.hosts.clone()
+        };
+        if hosts.is_empty() {
+            return Err(CliError::Usage(
+                "no host list in the launcher environment (SLURM_JOB_NODELIST); pass --hosts"
+                    .into(),
+            )
+            .into());
+        }
+        let job = self.job.clone().or(rdzv.job);
+        let args = ranks::remote_args(
+            &self.remote_cli,
+            self.pids_from,
+            job.as_deref(),
+            &self.settings,
+        );
+        let runner = SshRunner::new(&self.ssh)?;
+        eprintln!(
+            "Injecting all ranks on {} host(s) via `{}`",
+            hosts.len(),
+            self.ssh
+        );
+        let rows = ranks::inject_hosts(&runner, &hosts, &args);
+        let outcome = ranks::summarize(rows, self.world_size.or(rdzv.world_size));
+        render_dataframe(&outcome.to_dataframe());
+        outcome.into_result()
     }
 
     pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        if self.all_ranks {
+            return self.inject_all_ranks(ctrl).await;
+        }
+        if self.local_ranks {
+            return self.inject_local_ranks().await;
+        }
         match ctrl {
             ProbeEndpoint::Ptrace { pid } | ProbeEndpoint::Local { pid } => {
-                if !self.check_library(pid, "libprobing.so")? {
-                    self.wait_for_library(pid, "python")?;
-                    self.inject(pid)
-                } else {
-                    let settings = self.build_settings();
-                    let query: Vec<String> = settings
-                        .iter()
-                        .map(|setting| format!("set {setting}"))
-                        .collect();
-                    let query = query.join(";");
-
-                    ctrl::query(
-                        ctrl,
-                        Query {
-                            expr: query,
-                            opts: None,
-                        },
-                    )
-                    .await
-                }
+                self.inject_pid(pid).await
             }
             _ => Ok(()),
         }
@@ -4,6 +4,7 @@ use anyhow::{Context, Result};
 use clap::Subcommand;
 
 use crate::cli::ctrl::ProbeEndpoint;
+use crate::cli::error::CliError;
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum McpCommand {
@@ -46,11 +47,10 @@ async fn mcp_base_url(ctrl: &ProbeEndpoint) -> Result<String> {
             )?;
             Ok(normalize_listen_addr(addr.trim()))
         }
-        ProbeEndpoint::Launch { .. } => {
-            anyhow::bail!(
-                "MCP is served by the probing HTTP server; use `-t <pid>` or `-t host:port`"
-            )
-        }
+        ProbeEndpoint::Launch { .. } => Err(CliError::Usage(
+            "MCP is served by the probing HTTP server; use `-t <pid>` or `-t host:port`".into(),
+        )
+        .into()),
     }
 }
 
@@ -2,17 +2,33 @@ use anyhow::Result;
 use clap::Parser;
 use probing_proto::prelude::Query;
 
+pub mod analyze;
 pub mod bench;
 pub mod cluster;
 pub mod commands;
+pub mod completions;
+pub mod config;
 pub mod ctrl;
+pub mod detach;
+pub mod dump_trace;
+pub mod error;
+pub mod eval;
 pub mod fanout;
+pub mod flamegraph;
+pub mod gc;
 pub mod help;
 pub mod mcp;
+pub mod pprof;
 pub mod repl;
+pub mod serve_snapshot;
 pub mod skill;
+pub mod stacks;
 
 pub mod store;
+pub mod top;
+pub mod trace;
+pub mod watch;
+pub mod watchdog;
 
 #[cfg(target_os = "linux")]
 pub mod inject;
@@ -20,6 +36,15 @@ pub mod inject;
 #[cfg(target_os = "linux")]
 pub mod process_monitor;
 
+#[cfg(target_os = "linux")]
+pub mod ps;
+
+#[cfg(target_os = "linux")]
+pub mod ranks;
+
+#[cfg(target_os = "linux")]
+pub mod select;
+
 #[cfg(target_os = "linux")]
 use process_monitor::ProcessMonitor;
 
@@ -27,7 +52,8 @@ mod ptree;
 
 use crate::cli::ctrl::ProbeEndpoint;
 use crate::table::OutputFormat;
-use commands::{Commands, FlamegraphKind};
+use commands::Commands;
+use error::ErrorFormat;
 use once_cell::sync::Lazy;
 
 fn get_build_info() -> String {
@@ -49,6 +75,7 @@ static BUILD_INFO: Lazy<String> = Lazy::new(get_build_info);
 /// Probing CLI - A performance and stability diagnostic tool for AI applications
 #[derive(Parser, Debug)]
 #[command(version = BUILD_INFO.as_str(), arg_required_else_help = true)]
+#[command(group(clap::ArgGroup::new("selector").args(["name", "rank"])))]
 pub struct Cli {
     /// Enable verbose mode
     #[arg(short, long, global = true)]
@@ -58,6 +85,51 @@ pub struct Cli {
     #[arg(short, long)]
     target: Option<String>,
 
+    /// Remote probing server, e.g. `http://10.0.0.5:8080` (instead of `--target`)
+    #[arg(long, value_name = "URL", conflicts_with = "target")]
+    endpoint: Option<String>,
+
+    /// Target the Python process whose command line contains SUBSTRING (Linux)
+    #[arg(long, value_name = "SUBSTRING", conflicts_with_all = ["target", "endpoint"])]
+    name: Option<String>,
+
+    /// Target the Python process with this `LOCAL_RANK` (or `RANK`) in its environment (Linux)
+    #[arg(long, value_name = "N", conflicts_with_all = ["target", "endpoint"])]
+    rank: Option<u32>,
+
+    /// With `--name` / `--rank`: run the command in every matching process
+    #[arg(long, requires = "selector")]
+    all: bool,
+
+    /// Token for a server with `server.auth_token` set, sent as `Authorization: Bearer`
+    #[arg(long, env = "PROBING_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Seconds to wait for the target to connect and answer a request (0: no limit)
+    #[arg(long, global = true, value_name = "SECS", default_value_t = ctrl::DEFAULT_TIMEOUT.as_secs())]
+    timeout: u64,
+
+    /// Retry GET requests N times, with exponential backoff, when the target stalls
+    #[arg(long, global = true, value_name = "N", default_value_t = 0)]
+    retries: u32,
+
+    /// Output format for query results: `table`, `json` (array of row objects), `csv` or
+    /// `arrow` (an Arrow IPC stream; `query` writes it to `--output`)
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Cut table cells to N columns, marking the cut with `…`
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_col_width: Option<u64>,
+
+    /// Print each row as a `name | value` record when a table is wider than the terminal
+    #[arg(long, global = true)]
+    vertical: bool,
+
+    /// How failures are printed on stderr: `text`, or `json` with `kind` and `exit_code`
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -71,19 +143,38 @@ impl Cli {
         cmd
     }
 
+    pub fn error_format(&self) -> ErrorFormat {
+        self.error_format
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         // Handle external commands first to avoid target requirement
         if let Some(Commands::External(args)) = &self.command {
-            std::env::set_var("PROBING_ENDPOINT", self.target.clone().unwrap_or_default());
+            let endpoint = self.endpoint.clone().or(self.target.clone());
+            std::env::set_var("PROBING_ENDPOINT", endpoint.unwrap_or_default());
             return handle_external_command(args);
         }
 
+        ctrl::set_auth_token(self.token.as_deref());
+        ctrl::set_limits(ctrl::Limits {
+            timeout: (self.timeout > 0).then(|| std::time::Duration::from_secs(self.timeout)),
+            retries: self.retries,
+        });
+        crate::table::set_options(crate::table::TableOptions {
+            max_col_width: self.max_col_width.map(|n| n as usize),
+            vertical: self.vertical,
+        });
+
         // Handle commands that don't need a target
         match &self.command {
             Some(Commands::List { verbose, tree }) => {
                 return self.handle_list_command(*verbose, *tree).await;
             }
             #[cfg(target_os = "linux")]
+            Some(Commands::Ps(cmd)) => {
+                return cmd.run(self.format);
+            }
+            #[cfg(target_os = "linux")]
             Some(Commands::Launch { recursive, args }) => {
                 return ProcessMonitor::new(args, *recursive)?.monitor().await;
             }
@@ -93,6 +184,19 @@ impl Cli {
             Some(Commands::Bench(cmd)) => {
                 return cmd.run();
             }
+            Some(Commands::Analyze(cmd)) if cmd.is_offline() => {
+                return cmd.run_offline();
+            }
+            Some(Commands::ServeSnapshot(cmd)) => {
+                return cmd.run().await;
+            }
+            Some(Commands::Completions(cmd)) => {
+                return cmd.run();
+            }
+            Some(Commands::CompletePids) => {
+                completions::print_pids();
+                return Ok(());
+            }
             Some(Commands::Skill(skill::SkillCommand::List)) => {
                 return skill::list_skills_sync();
             }
@@ -106,12 +210,81 @@ impl Cli {
             _ => {}
         }
 
+        if let Some(selector) = self.selector() {
+            return self.run_selected(selector).await;
+        }
+
         // For other commands, we need a target
-        let target = self.target.clone().unwrap_or("0".to_string());
-        let ctrl: ProbeEndpoint = target.as_str().try_into()?;
+        let ctrl = match &self.endpoint {
+            Some(url) => ProbeEndpoint::from_url(url)?,
+            None => self.target.as_deref().unwrap_or("0").try_into()?,
+        };
+        if let Some(warning) = ctrl.cleartext_token_warning() {
+            eprintln!("{warning}");
+        }
         self.execute_command(ctrl).await
     }
 
+    #[cfg(target_os = "linux")]
+    fn selector(&self) -> Option<select::Selector> {
+        match (&self.name, self.rank) {
+            (Some(name), _) => Some(select::Selector::Name(name.clone())),
+            (None, Some(rank)) => Some(select::Selector::Rank(rank)),
+            (None, None) => None,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn selector(&self) -> Option<()> {
+        (self.name.is_some() || self.rank.is_some()).then_some(())
+    }
+
+    /// Run the command in the process(es) `--name` / `--rank` picks; under
+    /// `--all` once per process, each block headed by the process.
+    #[cfg(target_os = "linux")]
+    async fn run_selected(&self, selector: select::Selector) -> Result<()> {
+        let candidates = select::candidates()?;
+        let targets = select::resolve(&selector, &candidates, self.all)?;
+        if let [target] = targets[..] {
+            return self
+                .execute_command(ProbeEndpoint::Local { pid: target.pid })
+                .await;
+        }
+        let command = self.command.as_ref().expect("subcommand required");
+        if !select::fans_out(command) {
+            return Err(error::CliError::Usage(format!(
+                "this command runs in one process at a time; drop --all and narrow {selector}"
+            ))
+            .into());
+        }
+        let mut failed = Vec::new();
+        for (i, target) in targets.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("==> {target} <==");
+            let ctrl = ProbeEndpoint::Local { pid: target.pid };
+            if let Err(err) = self.execute_command(ctrl).await {
+                eprintln!("pid {}: {err:#}", target.pid);
+                failed.push(target.pid.to_string());
+            }
+        }
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "failed in {} of {} processes (pids {})",
+                failed.len(),
+                targets.len(),
+                failed.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn run_selected(&self, _: ()) -> Result<()> {
+        Err(error::CliError::Usage("--name and --rank read /proc and need Linux".into()).into())
+    }
+
     async fn handle_list_command(&self, verbose: bool, tree: bool) -> Result<()> {
         match ptree::collect_probe_processes().await {
             Ok(processes) => {
@@ -138,12 +311,7 @@ impl Cli {
         Ok(())
     }
 
-    async fn handle_tables_command(
-        &self,
-        ctrl: ProbeEndpoint,
-        all: bool,
-        format: OutputFormat,
-    ) -> Result<()> {
+    async fn handle_tables_command(&self, ctrl: ProbeEndpoint, all: bool) -> Result<()> {
         let expr = if all {
             "select table_catalog, table_schema, table_name, table_type \
              from information_schema.tables order by table_schema, table_name"
@@ -155,15 +323,10 @@ impl Cli {
              order by table_schema, table_name"
                 .to_string()
         };
-        ctrl::query_with_format(ctrl, Query::new(expr), format).await
+        ctrl::query_with_format(ctrl, Query::new(expr), self.format).await
     }
 
-    async fn handle_memory_command(
-        &self,
-        ctrl: ProbeEndpoint,
-        limit: usize,
-        format: OutputFormat,
-    ) -> Result<()> {
+    async fn handle_memory_command(&self, ctrl: ProbeEndpoint, limit: usize) -> Result<()> {
         let cpu_expr = format!(
             "select ts, comm, rss_kb, thread_count from cpu.utilization \
              where scope = 'process' order by ts desc limit {limit}"
@@ -172,7 +335,7 @@ impl Cli {
         match ctrl.query(Query::new(cpu_expr)).await {
             Ok(df) if df.cols.iter().any(|c| !c.is_empty()) => {
                 println!("Host memory (cpu.utilization):");
-                crate::table::render(&df, format);
+                crate::table::render(&df, self.format);
                 printed = true;
             }
             Ok(_) => {}
@@ -189,7 +352,7 @@ impl Cli {
                     println!();
                 }
                 println!("GPU memory (gpu.utilization):");
-                crate::table::render(&df, format);
+                crate::table::render(&df, self.format);
                 printed = true;
             }
             Ok(_) => {}
@@ -204,28 +367,6 @@ impl Cli {
         Ok(())
     }
 
-    async fn handle_flamegraph_command(
-        &self,
-        ctrl: ProbeEndpoint,
-        kind: FlamegraphKind,
-        output: Option<String>,
-        json: bool,
-    ) -> Result<()> {
-        let bytes = ctrl.flamegraph(kind.as_str(), json).await?;
-        match output {
-            Some(path) => {
-                std::fs::write(&path, &bytes)?;
-                eprintln!("flamegraph ({}) written to {path}", kind.as_str());
-            }
-            None => {
-                use std::io::Write;
-                std::io::stdout().write_all(&bytes)?;
-                std::io::stdout().flush()?;
-            }
-        }
-        Ok(())
-    }
-
     async fn execute_command(&self, ctrl: ProbeEndpoint) -> Result<()> {
         let command = self
             .command
@@ -234,7 +375,15 @@ impl Cli {
         match command {
             #[cfg(target_os = "linux")]
             Commands::Inject(cmd) => cmd.run(ctrl).await,
-            Commands::Config { options, setting } => {
+            Commands::Config {
+                action: Some(action),
+                ..
+            } => config::run(ctrl, action.clone(), self.format).await,
+            Commands::Config {
+                action: None,
+                options,
+                setting,
+            } => {
                 let options_cfg = options.to_cfg();
 
                 let query_expr = match (setting, options_cfg) {
@@ -262,44 +411,105 @@ impl Cli {
                     }
                 };
 
-                ctrl::query(
+                ctrl::query_with_format(
                     ctrl,
                     Query {
                         expr: query_expr,
                         opts: None,
                     },
+                    self.format,
                 )
                 .await
             }
             Commands::Backtrace { tid } => ctrl.backtrace(*tid).await,
+            Commands::Stacks(cmd) => cmd.run(ctrl).await,
             Commands::Rdma { hca_name } => {
                 let hca_name = hca_name.clone().unwrap_or_default();
                 ctrl.rdma(hca_name).await
             }
-            Commands::Eval { code } => ctrl.eval(code.clone()).await,
-            Commands::Query { query, format } => {
-                ctrl::query_with_format(ctrl, Query::new(query.clone()), *format).await
+            Commands::Eval(cmd) => {
+                if cmd.run(ctrl).await? == eval::EvalExit::Raised {
+                    std::process::exit(eval::EXIT_RAISED);
+                }
+                Ok(())
+            }
+            Commands::Detach => {
+                if detach::run(ctrl).await? == detach::DetachExit::Partial {
+                    std::process::exit(detach::EXIT_PARTIAL);
+                }
+                Ok(())
+            }
+            Commands::Gc { generation } => gc::run(ctrl, *generation).await,
+            Commands::Query {
+                cancel: Some(id), ..
+            } => {
+                ctrl.cancel_query(id).await?;
+                println!("cancelled query {id}");
+                Ok(())
+            }
+            Commands::Query { query: None, .. } => {
+                Err(error::CliError::Usage("missing SQL query".to_string()).into())
             }
-            Commands::Tables { all, format } => {
-                self.handle_tables_command(ctrl, *all, *format).await
+            Commands::Query { watch: Some(_), .. } if self.format == OutputFormat::Arrow => {
+                Err(error::CliError::Usage(
+                    "--watch redraws the result; use --format table, json or csv".to_string(),
+                )
+                .into())
             }
-            Commands::Memory { limit, format } => {
-                self.handle_memory_command(ctrl, *limit, *format).await
+            Commands::Query {
+                query: Some(query),
+                id,
+                output,
+                ..
+            } if self.format == OutputFormat::Arrow => {
+                ctrl::query_arrow(ctrl, Query::new(query.clone()), id.as_deref(), output).await
             }
-            Commands::Flamegraph { kind, output, json } => {
-                self.handle_flamegraph_command(ctrl, *kind, output.clone(), *json)
-                    .await
+            Commands::Query {
+                query: Some(query),
+                watch: Some(every),
+                ..
+            } => watch::run(ctrl, Query::new(query.clone()), *every, self.format).await,
+            Commands::Query {
+                query: Some(query),
+                id: Some(id),
+                ..
+            } => ctrl::query_as(ctrl, Query::new(query.clone()), id, self.format).await,
+            Commands::Query {
+                query: Some(query), ..
+            } => ctrl::query_with_format(ctrl, Query::new(query.clone()), self.format).await,
+            Commands::Tables { all } => self.handle_tables_command(ctrl, *all).await,
+            Commands::Memory { limit } => self.handle_memory_command(ctrl, *limit).await,
+            Commands::Top(cmd) => cmd.run(ctrl).await,
+            Commands::Flamegraph(cmd) => {
+                if cmd.run(ctrl).await? == flamegraph::FlamegraphExit::ProfilerDisabled {
+                    std::process::exit(flamegraph::EXIT_PROFILER_DISABLED);
+                }
+                Ok(())
             }
-            Commands::Cluster(cmd) => cluster::run(ctrl, cmd.clone()).await,
+            Commands::Cluster(cmd) => cluster::run(ctrl, cmd.clone(), self.format).await,
             Commands::Skill(cmd) => skill::run(ctrl, cmd.clone()).await,
             Commands::Mcp(cmd) => mcp::run(ctrl, cmd.clone()).await,
+            Commands::Pprof(cmd) => pprof::run(ctrl, cmd.clone()).await,
+            Commands::Analyze(cmd) => cmd.run(ctrl).await,
+            Commands::DumpTrace(cmd) => cmd.run(ctrl).await,
+            Commands::Trace(cmd) => trace::run(ctrl, cmd.clone(), self.format).await,
+            Commands::Watchdog(cmd) => {
+                if cmd.run(ctrl).await? == watchdog::WatchdogExit::TargetGone {
+                    std::process::exit(watchdog::EXIT_TARGET_GONE);
+                }
+                Ok(())
+            }
             Commands::Repl => repl::start_repl(ctrl).await,
             // These commands are handled in run() method and don't need a target
             #[cfg(target_os = "linux")]
             Commands::Launch { .. }
+            | Commands::Ps(..)
             | Commands::List { .. }
             | Commands::Store(..)
             | Commands::Bench(..)
+            | Commands::ServeSnapshot(..)
+            | Commands::Completions(..)
+            | Commands::CompletePids
             | Commands::External(..) => {
                 unreachable!("These commands should be handled in run() method")
             }
@@ -307,6 +517,9 @@ impl Cli {
             Commands::List { .. }
             | Commands::Store(..)
             | Commands::Bench(..)
+            | Commands::ServeSnapshot(..)
+            | Commands::Completions(..)
+            | Commands::CompletePids
             | Commands::External(..) => {
                 unreachable!("These commands should be handled in run() method")
             }
@@ -0,0 +1,440 @@
+//! `probing pprof serve`: a localhost endpoint speaking the Go `net/http/pprof`
+//! contract, so `go tool pprof -http=:8081 http://127.0.0.1:6060/debug/pprof/profile`
+//! works against a probed process.
+//!
+//! A CPU profile request snapshots the target's aggregated SIGPROF buckets
+//! (`/apis/pprofextension/flamegraph/folded/json`) before and after the
+//! requested window and encodes the delta as an (uncompressed) `profile.proto`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use clap::Subcommand;
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use probing_proto::protocol::pprof::{encode_cpu_profile, parse_folded};
+
+use crate::cli::ctrl::{request, ProbeEndpoint};
+
+/// Matches the target's default `probing.pprof.sample_freq`.
+const DEFAULT_SAMPLE_FREQ: u64 = 100;
+/// Upper bound on a single capture window, in seconds.
+const MAX_SECONDS: u64 = 3600;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PprofCommand {
+    /// Serve `/debug/pprof/profile` for `go tool pprof` on a local port
+    Serve {
+        /// Address to listen on (keep it on loopback; there is no auth)
+        #[arg(long, default_value = "127.0.0.1:6060")]
+        listen: String,
+
+        /// Capture window used when the request has no `?seconds=` parameter
+        #[arg(long, default_value_t = 30)]
+        seconds: u64,
+    },
+}
+
+pub async fn run(ctrl: ProbeEndpoint, cmd: PprofCommand) -> Result<()> {
+    match cmd {
+        PprofCommand::Serve { listen, seconds } => {
+            anyhow::ensure!(
+                (1..=MAX_SECONDS).contains(&seconds),
+                "--seconds must be between 1 and {MAX_SECONDS}"
+            );
+            let listener = tokio::net::TcpListener::bind(&listen)
+                .await
+                .with_context(|| format!("failed to bind {listen}"))?;
+            let addr = listener.local_addr()?;
+            eprintln!(
+                "serving pprof for {} on http://{addr}/debug/pprof/",
+                String::from(ctrl.clone())
+            );
+            eprintln!(
+                "  go tool pprof -http=:8081 http://{addr}/debug/pprof/profile?seconds={seconds}"
+            );
+            PprofServer::new(ctrl, seconds).serve(listener).await
+        }
+    }
+}
+
+/// Where CPU samples come from; the probed process in practice.
+#[async_trait]
+pub trait ProfileSource: Send + Sync + 'static {
+    /// Cumulative folded stacks (`"frame;frame;… count"`, root first).
+    async fn folded_lines(&self) -> Result<Vec<String>>;
+
+    /// Sampling frequency in Hz, if the target reports one.
+    async fn sample_freq(&self) -> Option<u64>;
+}
+
+#[async_trait]
+impl ProfileSource for ProbeEndpoint {
+    async fn folded_lines(&self) -> Result<Vec<String>> {
+        #[derive(serde::Deserialize)]
+        struct Folded {
+            lines: Vec<String>,
+        }
+        let body = request(
+            self.clone(),
+            "/apis/pprofextension/flamegraph/folded/json",
+            None,
+        )
+        .await?;
+        serde_json::from_slice::<Folded>(&body)
+            .map(|f| f.lines)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "target returned no CPU stacks: {}",
+                    String::from_utf8_lossy(&body).trim()
+                )
+            })
+    }
+
+    async fn sample_freq(&self) -> Option<u64> {
+        self.get("/config/probing.pprof.sample_freq")
+            .await
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|f| *f > 0)
+    }
+}
+
+pub struct PprofServer<S> {
+    source: Arc<S>,
+    default_seconds: u64,
+    /// Length of one requested "second"; shortened in tests.
+    second: Duration,
+}
+
+impl<S> Clone for PprofServer<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            default_seconds: self.default_seconds,
+            second: self.second,
+        }
+    }
+}
+
+impl<S: ProfileSource> PprofServer<S> {
+    pub fn new(source: S, default_seconds: u64) -> Self {
+        Self {
+            source: Arc::new(source),
+            default_seconds,
+            second: Duration::from_secs(1),
+        }
+    }
+
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req: Request<Incoming>| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(req.uri()).await) }
+                });
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log::debug!("pprof connection error: {e}");
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, uri: &hyper::Uri) -> Response<Full<Bytes>> {
+        let query = parse_query(uri.query().unwrap_or(""));
+        match uri.path().trim_end_matches('/') {
+            "/debug/pprof" => text_response(
+                StatusCode::OK,
+                "probing pprof bridge\n\n\
+                 /debug/pprof/profile?seconds=N  CPU profile (SIGPROF samples)\n",
+            ),
+            "/debug/pprof/profile" => {
+                let seconds = match query.get("seconds") {
+                    None => self.default_seconds,
+                    Some(v) => match v.parse::<u64>() {
+                        Ok(s) if (1..=MAX_SECONDS).contains(&s) => s,
+                        _ => {
+                            return pprof_error(
+                                StatusCode::BAD_REQUEST,
+                                &format!("invalid seconds {v:?} (expected 1..={MAX_SECONDS})"),
+                            )
+                        }
+                    },
+                };
+                match self.cpu_profile(seconds).await {
+                    Ok(body) => profile_response(body),
+                    Err(e) => pprof_error(StatusCode::INTERNAL_SERVER_ERROR, &format!("{e:#}")),
+                }
+            }
+            "/debug/pprof/heap" | "/debug/pprof/allocs" => pprof_error(
+                StatusCode::NOT_FOUND,
+                "heap profiles are not available from probing targets; \
+                 query memory tables with `probing memory` instead",
+            ),
+            other => pprof_error(StatusCode::NOT_FOUND, &format!("unknown profile: {other}")),
+        }
+    }
+
+    async fn cpu_profile(&self, seconds: u64) -> Result<Vec<u8>> {
+        let start = SystemTime::now();
+        let before_lines = self.source.folded_lines().await?;
+        tokio::time::sleep(self.second * seconds as u32).await;
+        let after_lines = self.source.folded_lines().await?;
+
+        let before: HashMap<Vec<&str>, i64> = parse_folded(&before_lines).into_iter().collect();
+        let delta: Vec<(Vec<&str>, i64)> = parse_folded(&after_lines)
+            .into_iter()
+            .filter_map(|(stack, count)| {
+                let prev = before.get(&stack).copied().unwrap_or(0);
+                (count > prev).then_some((stack, count - prev))
+            })
+            .collect();
+        anyhow::ensure!(
+            !delta.is_empty(),
+            "no CPU samples captured in {seconds}s; enable sampling on the target with \
+             `probing -t <pid> config probing.pprof.sample_freq=100`"
+        );
+
+        let freq = self
+            .source
+            .sample_freq()
+            .await
+            .unwrap_or(DEFAULT_SAMPLE_FREQ);
+        let time_nanos = start
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as i64);
+        Ok(encode_cpu_profile(
+            &delta,
+            freq,
+            time_nanos,
+            (self.second * seconds as u32).as_nanos() as i64,
+            &[],
+        ))
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<&str, &str> {
+    query
+        .split('&')
+        .filter_map(|kv| kv.split_once('=').or(Some((kv, ""))))
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
+}
+
+fn text_response(status: StatusCode, body: &str) -> Response<Full<Bytes>> {
+    let mut res = Response::new(Full::new(Bytes::from(body.to_string())));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    res
+}
+
+/// Error shape used by Go's `net/http/pprof`, which `go tool pprof` prints verbatim.
+fn pprof_error(status: StatusCode, msg: &str) -> Response<Full<Bytes>> {
+    let mut res = text_response(status, &format!("{msg}\n"));
+    res.headers_mut()
+        .insert("X-Go-Pprof", HeaderValue::from_static("1"));
+    res
+}
+
+fn profile_response(body: Vec<u8>) -> Response<Full<Bytes>> {
+    let mut res = Response::new(Full::new(Bytes::from(body)));
+    let headers = res.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    headers.insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"profile\""),
+    );
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    /// Each fetch adds 5 samples to `train_step` and none to `idle`.
+    struct GrowingSource {
+        calls: AtomicI64,
+    }
+
+    #[async_trait]
+    impl ProfileSource for GrowingSource {
+        async fn folded_lines(&self) -> Result<Vec<String>> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![
+                format!(
+                    "thread-1 (main);[py] <module> (train.py:3);[py] train_step (train.py:42) {}",
+                    10 + 5 * n
+                ),
+                "thread-1 (main);[py] idle (train.py:9) 7".to_string(),
+            ])
+        }
+
+        async fn sample_freq(&self) -> Option<u64> {
+            Some(200)
+        }
+    }
+
+    struct IdleSource;
+
+    #[async_trait]
+    impl ProfileSource for IdleSource {
+        async fn folded_lines(&self) -> Result<Vec<String>> {
+            Ok(vec!["thread-1 (main);[py] idle (train.py:9) 7".to_string()])
+        }
+
+        async fn sample_freq(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    async fn spawn<S: ProfileSource>(source: S) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut server = PprofServer::new(source, 30);
+        server.second = Duration::from_millis(1);
+        tokio::spawn(server.serve(listener));
+        addr
+    }
+
+    async fn fetch(addr: std::net::SocketAddr, path: &str) -> Response<Bytes> {
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+        let req = Request::get(path)
+            .header("host", addr.to_string())
+            .body(Full::<Bytes>::default())
+            .unwrap();
+        let res = sender.send_request(req).await.unwrap();
+        let (parts, body) = res.into_parts();
+        Response::from_parts(parts, body.collect().await.unwrap().to_bytes())
+    }
+
+    fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+        let (mut v, mut shift) = (0u64, 0);
+        loop {
+            let b = buf[*pos];
+            *pos += 1;
+            v |= u64::from(b & 0x7f) << shift;
+            if b < 0x80 {
+                return v;
+            }
+            shift += 7;
+        }
+    }
+
+    /// Top-level `(field, payload)` pairs; varint fields carry their value as LE bytes.
+    fn top_level_fields(buf: &[u8]) -> Vec<(u64, Vec<u8>)> {
+        let mut pos = 0;
+        let mut fields = Vec::new();
+        while pos < buf.len() {
+            let key = read_varint(buf, &mut pos);
+            match key & 7 {
+                0 => {
+                    let v = read_varint(buf, &mut pos);
+                    fields.push((key >> 3, v.to_le_bytes().to_vec()));
+                }
+                2 => {
+                    let len = read_varint(buf, &mut pos) as usize;
+                    fields.push((key >> 3, buf[pos..pos + len].to_vec()));
+                    pos += len;
+                }
+                wire => panic!("unexpected wire type {wire}"),
+            }
+        }
+        fields
+    }
+
+    #[tokio::test]
+    async fn profile_endpoint_returns_delta_as_pprof_protobuf() {
+        let addr = spawn(GrowingSource {
+            calls: AtomicI64::new(0),
+        })
+        .await;
+        let res = fetch(addr, "/debug/pprof/profile?seconds=2").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[CONTENT_TYPE], "application/octet-stream");
+
+        let body = res.body();
+        // First field is `sample_type` (field 1, length-delimited).
+        assert_eq!(body[0], 0x0a);
+        let fields = top_level_fields(body);
+        let strings: Vec<String> = fields
+            .iter()
+            .filter(|(f, _)| *f == 6)
+            .map(|(_, b)| String::from_utf8(b.clone()).unwrap())
+            .collect();
+        assert_eq!(strings[0], "");
+        for s in [
+            "samples",
+            "count",
+            "cpu",
+            "nanoseconds",
+            "[py] train_step",
+            "train.py",
+        ] {
+            assert!(
+                strings.iter().any(|x| x == s),
+                "missing {s:?} in {strings:?}"
+            );
+        }
+        assert!(
+            !strings.iter().any(|x| x.contains("idle")),
+            "idle had no new samples"
+        );
+
+        let samples: Vec<&Vec<u8>> = fields
+            .iter()
+            .filter(|(f, _)| *f == 2)
+            .map(|(_, b)| b)
+            .collect();
+        assert_eq!(samples.len(), 1);
+        let sample = top_level_fields(samples[0]);
+        let values = &sample.iter().find(|(f, _)| *f == 2).unwrap().1;
+        let mut pos = 0;
+        assert_eq!(read_varint(values, &mut pos), 5);
+        assert_eq!(read_varint(values, &mut pos), 5 * 5_000_000);
+        // Three frames: thread, <module>, train_step.
+        let ids = &sample.iter().find(|(f, _)| *f == 1).unwrap().1;
+        assert_eq!(ids.len(), 3);
+        assert_eq!(fields.iter().filter(|(f, _)| *f == 4).count(), 3);
+    }
+
+    #[tokio::test]
+    async fn capture_errors_use_pprof_error_shape() {
+        let addr = spawn(IdleSource).await;
+        let res = fetch(addr, "/debug/pprof/profile?seconds=1").await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()["X-Go-Pprof"], "1");
+        let text = String::from_utf8(res.body().to_vec()).unwrap();
+        assert!(text.contains("probing.pprof.sample_freq"), "{text}");
+
+        let res = fetch(addr, "/debug/pprof/profile?seconds=abc").await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let res = fetch(addr, "/debug/pprof/heap").await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,293 @@
+//! `probing ps`: Python processes on this host that probing can attach to.
+//!
+//! Only reads `/proc`, so no privileges are needed. For processes of other
+//! users `exe` and `maps` are usually unreadable: they are still listed when
+//! their command line names a Python interpreter, with the probing state
+//! `unknown` unless the probe socket gives it away.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::Args;
+use probing_proto::prelude::{DataFrame, Seq};
+
+use crate::table::{render, OutputFormat};
+
+#[derive(Args, Clone, Debug)]
+pub struct PsCommand {
+    /// Print a JSON array of processes, same as `--format json`
+    #[arg(long)]
+    json: bool,
+}
+
+/// Whether probing is loaded into a process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbingState {
+    Injected,
+    No,
+    /// `maps` unreadable and no probe socket.
+    Unknown,
+}
+
+impl ProbingState {
+    fn as_str(self) -> &'static str {
+        match self {
+            ProbingState::Injected => "injected",
+            ProbingState::No => "no",
+            ProbingState::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PythonProcess {
+    pub pid: i32,
+    pub user: String,
+    /// Local start time, `?` when `/proc/<pid>/stat` is unreadable.
+    pub started: String,
+    pub probing: ProbingState,
+    pub cmdline: String,
+}
+
+/// Libraries of interest among the file mappings of a process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct MappedLibs {
+    libpython: bool,
+    probing: bool,
+}
+
+fn mapped_libs(paths: &[PathBuf]) -> MappedLibs {
+    let mut libs = MappedLibs::default();
+    for path in paths {
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy()) else {
+            continue;
+        };
+        libs.libpython |= name.starts_with("libpython");
+        // Injected `libprobing.so`, or the `probing._core` extension of `import probing`.
+        let in_probing_pkg = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|d| d == "probing");
+        libs.probing |=
+            name.starts_with("libprobing") || (in_probing_pkg && name.starts_with("_core"));
+    }
+    libs
+}
+
+/// `python`, `python3`, `python3.11`, `python3.13t`, ... by file name.
+fn is_python_binary(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let Some(version) = name.strip_prefix("python") else {
+        return false;
+    };
+    version
+        .trim_end_matches(['d', 'm', 't'])
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '.')
+}
+
+fn looks_like_python(exe: Option<&Path>, cmdline: &[String]) -> bool {
+    exe.is_some_and(is_python_binary)
+        || cmdline
+            .first()
+            .is_some_and(|argv0| is_python_binary(Path::new(argv0)))
+}
+
+/// Login name of `uid` in `/etc/passwd` contents.
+fn user_name(passwd: &str, uid: u32) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let id = fields.nth(1)?.parse::<u32>().ok()?;
+        (id == uid).then(|| name.to_string())
+    })
+}
+
+fn to_dataframe(processes: &[PythonProcess]) -> DataFrame {
+    let text = |f: fn(&PythonProcess) -> String| Seq::SeqText(processes.iter().map(f).collect());
+    DataFrame::new(
+        ["pid", "user", "started", "probing", "cmdline"]
+            .map(String::from)
+            .to_vec(),
+        vec![
+            Seq::SeqI32(processes.iter().map(|p| p.pid).collect()),
+            text(|p| p.user.clone()),
+            text(|p| p.started.clone()),
+            text(|p| p.probing.as_str().to_string()),
+            text(|p| p.cmdline.clone()),
+        ],
+    )
+}
+
+fn start_time(start_ticks: u64, boot_secs: u64, ticks_per_sec: u64) -> Option<String> {
+    let secs = boot_secs + start_ticks / ticks_per_sec.max(1);
+    let local = chrono::DateTime::from_timestamp(secs as i64, 0)?.with_timezone(&chrono::Local);
+    Some(local.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Python processes of this host other than the CLI itself, by pid.
+pub fn scan() -> Result<Vec<PythonProcess>> {
+    use procfs::process::MMapPath;
+
+    let sockets: HashSet<i32> = super::ptree::find_probe_sockets()
+        .unwrap_or_else(|e| {
+            log::debug!("cannot list probe sockets: {e}");
+            Vec::new()
+        })
+        .into_iter()
+        .map(|(pid, _)| pid)
+        .collect();
+    let passwd = std::fs::read_to_string("/etc/passwd").unwrap_or_default();
+    let boot_secs = procfs::boot_time_secs().ok();
+    let ticks_per_sec = procfs::ticks_per_second();
+    let me = std::process::id() as i32;
+
+    let mut found = Vec::new();
+    for process in procfs::process::all_processes()?.filter_map(|p| p.ok()) {
+        let pid = process.pid();
+        // Gone since the listing, or a kernel thread (empty command line).
+        let Ok(cmdline) = process.cmdline() else {
+            continue;
+        };
+        if pid == me || cmdline.is_empty() {
+            continue;
+        }
+        // `exe` and `maps` need ptrace access; fine to miss for other users.
+        let exe = process.exe().ok();
+        let libs = process.maps().ok().map(|maps| {
+            let paths: Vec<PathBuf> = maps
+                .iter()
+                .filter_map(|m| match &m.pathname {
+                    MMapPath::Path(p) => Some(p.clone()),
+                    _ => None,
+                })
+                .collect();
+            mapped_libs(&paths)
+        });
+        if !looks_like_python(exe.as_deref(), &cmdline) && !libs.is_some_and(|l| l.libpython) {
+            continue;
+        }
+
+        let probing = match libs {
+            _ if sockets.contains(&pid) => ProbingState::Injected,
+            Some(libs) if libs.probing => ProbingState::Injected,
+            Some(_) => ProbingState::No,
+            None => ProbingState::Unknown,
+        };
+        let user = match process.uid() {
+            Ok(uid) => user_name(&passwd, uid).unwrap_or_else(|| uid.to_string()),
+            Err(_) => "?".to_string(),
+        };
+        let started = process
+            .stat()
+            .ok()
+            .zip(boot_secs)
+            .and_then(|(stat, boot)| start_time(stat.starttime, boot, ticks_per_sec))
+            .unwrap_or_else(|| "?".to_string());
+        found.push(PythonProcess {
+            pid,
+            user,
+            started,
+            probing,
+            cmdline: cmdline.join(" "),
+        });
+    }
+    found.sort_by_key(|p| p.pid);
+    Ok(found)
+}
+
+impl PsCommand {
+    pub fn run(&self, format: OutputFormat) -> Result<()> {
+        let format = if self.json {
+            OutputFormat::Json
+        } else {
+            format
+        };
+        let processes = scan()?;
+        if processes.is_empty() && format == OutputFormat::Table {
+            println!("No Python processes found.");
+            return Ok(());
+        }
+        render(&to_dataframe(&processes), format);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_python_interpreters() {
+        for name in [
+            "/usr/bin/python",
+            "python3",
+            "/opt/py/bin/python3.11",
+            "python3.13t",
+        ] {
+            assert!(is_python_binary(Path::new(name)), "{name}");
+        }
+        for name in [
+            "/usr/bin/python-config",
+            "pythonista",
+            "/bin/bash",
+            "ipython",
+        ] {
+            assert!(!is_python_binary(Path::new(name)), "{name}");
+        }
+        let argv = vec!["/usr/bin/python3".to_string(), "train.py".to_string()];
+        assert!(looks_like_python(None, &argv));
+        assert!(looks_like_python(
+            Some(Path::new("/usr/bin/python3.10")),
+            &["torchrun".to_string()]
+        ));
+        assert!(!looks_like_python(
+            Some(Path::new("/usr/bin/bash")),
+            &["bash".to_string()]
+        ));
+    }
+
+    #[test]
+    fn finds_libpython_and_probing_mappings() {
+        let paths = |list: &[&str]| list.iter().map(PathBuf::from).collect::<Vec<_>>();
+        let plain = mapped_libs(&paths(&[
+            "/usr/lib/libpython3.11.so.1.0",
+            "/usr/lib/libc.so.6",
+        ]));
+        assert_eq!(
+            plain,
+            MappedLibs {
+                libpython: true,
+                probing: false
+            }
+        );
+        assert!(mapped_libs(&paths(&["/opt/probing/libprobing.so"])).probing);
+        assert!(mapped_libs(&paths(&["/venv/site-packages/probing/_core.abi3.so"])).probing);
+        assert!(!mapped_libs(&paths(&["/venv/site-packages/numpy/_core/_multiarray.so"])).probing);
+    }
+
+    #[test]
+    fn resolves_user_names() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\n";
+        assert_eq!(user_name(passwd, 1000).as_deref(), Some("alice"));
+        assert_eq!(user_name(passwd, 0).as_deref(), Some("root"));
+        assert_eq!(user_name(passwd, 42), None);
+    }
+
+    #[test]
+    fn renders_one_row_per_process() {
+        let df = to_dataframe(&[PythonProcess {
+            pid: 7,
+            user: "alice".to_string(),
+            started: "2026-01-01 00:00:00".to_string(),
+            probing: ProbingState::Unknown,
+            cmdline: "python train.py".to_string(),
+        }]);
+        let json = crate::table::render_json(&df);
+        assert!(json.contains("\"pid\": 7"));
+        assert!(json.contains("\"probing\": \"unknown\""));
+    }
+}
@@ -57,7 +57,7 @@ pub async fn collect_probe_processes() -> Result<Vec<ProcessInfo>> {
 
 /// Find all probe-related sockets.
 #[cfg(target_os = "linux")]
-fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
+pub fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
     let mut result = Vec::new();
 
     // Read /proc/net/unix for abstract sockets
@@ -95,7 +95,7 @@ fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
 }
 
 #[cfg(target_os = "macos")]
-fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
+pub fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
     let mut result = Vec::new();
     let temp_dir = std::env::temp_dir();
 
@@ -124,7 +124,7 @@ fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
 }
 
 #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
+pub fn find_probe_sockets() -> Result<Vec<(i32, String)>, std::io::Error> {
     log::warn!("find_probe_sockets is not implemented for this OS. Returning empty list.");
     Ok(Vec::new())
 }
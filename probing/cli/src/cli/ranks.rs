@@ -0,0 +1,739 @@
+//! `probing inject --all-ranks`: inject every rank of a distributed job.
+//!
+//! Hosts come from `--hosts` or from the launcher's rendezvous environment
+//! (`SLURM_JOB_NODELIST`, read from `-t <launcher pid>` or the CLI's own
+//! environment). Each host runs `probing inject --local-ranks` over the
+//! configured ssh command; that finds the host's rank processes, injects them
+//! and prints one [`REPORT_PREFIX`] line per rank. The lines are merged into
+//! one table, and ranks without a successful report are listed as un-probed.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use probing_proto::prelude::{DataFrame, Seq};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::error::CliError;
+
+/// Prefix of the per-rank report lines printed by `inject --local-ranks`.
+pub const REPORT_PREFIX: &str = "PROBING_RANK ";
+
+const NODELIST_VARS: [&str; 2] = ["SLURM_JOB_NODELIST", "SLURM_NODELIST"];
+const WORLD_SIZE_VARS: [&str; 3] = ["WORLD_SIZE", "SLURM_NTASKS", "SLURM_NPROCS"];
+const JOB_ID_VARS: [&str; 2] = ["SLURM_JOB_ID", "TORCHELASTIC_RUN_ID"];
+
+/// How `--local-ranks` finds the rank processes on a host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PidSource {
+    /// Processes whose environment has a launcher rank (`RANK`, `SLURM_PROCID`)
+    #[default]
+    Env,
+    /// Processes already running probing (as shown by `probing list`)
+    Registry,
+}
+
+impl PidSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            PidSource::Env => "env",
+            PidSource::Registry => "registry",
+        }
+    }
+}
+
+/// What the launcher environment says about the job.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Rendezvous {
+    pub hosts: Vec<String>,
+    pub world_size: Option<u32>,
+    /// `VAR=VALUE` that every rank of the job carries, e.g. `SLURM_JOB_ID=42`.
+    pub job: Option<String>,
+}
+
+impl Rendezvous {
+    pub fn from_env(env: &HashMap<String, String>) -> Result<Self> {
+        let hosts = match NODELIST_VARS.iter().find_map(|k| env.get(*k)) {
+            Some(list) => expand_hostlist(list)?,
+            None => Vec::new(),
+        };
+        let world_size = WORLD_SIZE_VARS
+            .iter()
+            .find_map(|k| env.get(*k)?.parse().ok());
+        let job = JOB_ID_VARS
+            .iter()
+            .find_map(|k| env.get(*k).map(|v| format!("{k}={v}")));
+        Ok(Self {
+            hosts,
+            world_size,
+            job,
+        })
+    }
+}
+
+/// Environment of `pid`, or of this process for pid 0.
+pub fn process_env(pid: i32) -> Result<HashMap<String, String>> {
+    if pid == 0 {
+        return Ok(std::env::vars().collect());
+    }
+    let environ = procfs::process::Process::new(pid)
+        .and_then(|p| p.environ())
+        .with_context(|| {
+            CliError::TargetNotFound(format!("failed to read the environment of pid {pid}"))
+        })?;
+    Ok(environ
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                k.to_string_lossy().into_owned(),
+                v.to_string_lossy().into_owned(),
+            )
+        })
+        .collect())
+}
+
+/// Expands a SLURM host list such as `node[01-03,07],login1`.
+pub fn expand_hostlist(list: &str) -> Result<Vec<String>> {
+    let mut hosts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                expand_item(&list[start..i], &mut hosts)?;
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    expand_item(&list[start..], &mut hosts)?;
+    Ok(hosts)
+}
+
+fn expand_item(item: &str, out: &mut Vec<String>) -> Result<()> {
+    let item = item.trim();
+    let Some(open) = item.find('[') else {
+        if !item.is_empty() {
+            out.push(item.to_string());
+        }
+        return Ok(());
+    };
+    let close = item[open..]
+        .find(']')
+        .map(|i| open + i)
+        .ok_or_else(|| CliError::Usage(format!("unbalanced '[' in host list: {item}")))?;
+    let (prefix, body, rest) = (&item[..open], &item[open + 1..close], &item[close + 1..]);
+    for part in body.split(',') {
+        match part.split_once('-') {
+            Some((lo, hi)) => {
+                let width = lo.len();
+                let bad_range =
+                    || CliError::Usage(format!("bad range '{part}' in host list: {item}"));
+                let parse = |s: &str| s.parse::<u64>().map_err(|_| bad_range());
+                let (lo, hi) = (parse(lo)?, parse(hi)?);
+                if lo > hi {
+                    return Err(bad_range().into());
+                }
+                for n in lo..=hi {
+                    expand_item(&format!("{prefix}{n:0width$}{rest}"), out)?;
+                }
+            }
+            None => expand_item(&format!("{prefix}{part}{rest}"), out)?,
+        }
+    }
+    Ok(())
+}
+
+/// One rank process found on this host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalRank {
+    pub pid: i32,
+    pub rank: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    pid: i32,
+    ppid: i32,
+    rank: Option<u32>,
+    /// Rank came from `RANK` rather than the `SLURM_PROCID` fallback.
+    explicit: bool,
+}
+
+/// Rank processes on this host, ordered by rank.
+pub fn local_ranks(source: PidSource, job: Option<&str>) -> Result<Vec<LocalRank>> {
+    let job = match job {
+        Some(job) => Some(
+            job.split_once('=')
+                .ok_or_else(|| CliError::Usage(format!("--job expects VAR=VALUE, got '{job}'")))?,
+        ),
+        None => None,
+    };
+    let pids: Vec<i32> = match source {
+        PidSource::Env => procfs::process::all_processes()?
+            .filter_map(|p| p.ok().map(|p| p.pid()))
+            .collect(),
+        PidSource::Registry => super::ptree::find_probe_sockets()?
+            .into_iter()
+            .map(|(pid, _)| pid)
+            .collect(),
+    };
+    let me = std::process::id() as i32;
+    let mut candidates = Vec::new();
+    for pid in pids.into_iter().filter(|pid| *pid != me) {
+        // Processes may exit or be unreadable (other users); skip them.
+        let Ok(process) = procfs::process::Process::new(pid) else {
+            continue;
+        };
+        let (Ok(stat), Ok(environ)) = (process.stat(), process.environ()) else {
+            continue;
+        };
+        let var = |k: &str| {
+            environ
+                .get(std::ffi::OsStr::new(k))
+                .map(|v| v.to_string_lossy().into_owned())
+        };
+        if let Some((key, value)) = job {
+            if var(key).as_deref() != Some(value) {
+                continue;
+            }
+        }
+        let explicit = var("RANK").and_then(|r| r.parse().ok());
+        let rank = explicit.or_else(|| var("SLURM_PROCID").and_then(|r| r.parse().ok()));
+        if rank.is_none() && source == PidSource::Env {
+            continue;
+        }
+        candidates.push(Candidate {
+            pid,
+            ppid: stat.ppid,
+            rank,
+            explicit: explicit.is_some(),
+        });
+    }
+    Ok(topmost(candidates))
+}
+
+/// Drops helpers that inherit a rank's environment: children of a process
+/// with the same rank (dataloader workers, compile workers) and launchers
+/// such as `torchrun` under `srun` whose only rank is `SLURM_PROCID`.
+fn topmost(candidates: Vec<Candidate>) -> Vec<LocalRank> {
+    let by_pid: HashMap<i32, &Candidate> = candidates.iter().map(|c| (c.pid, c)).collect();
+    let mut ranks: Vec<LocalRank> = candidates
+        .iter()
+        .filter(|c| {
+            let forked = by_pid
+                .get(&c.ppid)
+                .is_some_and(|parent| c.rank.is_some() && parent.rank == c.rank);
+            let launcher = !c.explicit
+                && candidates
+                    .iter()
+                    .any(|child| child.explicit && child.ppid == c.pid);
+            !forked && !launcher
+        })
+        .map(|c| LocalRank {
+            pid: c.pid,
+            rank: c.rank,
+        })
+        .collect();
+    ranks.sort_by_key(|r| (r.rank.is_none(), r.rank, r.pid));
+    ranks
+}
+
+/// Result line printed by `inject --local-ranks` for one rank.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankReport {
+    pub rank: Option<u32>,
+    pub pid: i32,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RankReport {
+    pub fn line(&self) -> String {
+        format!(
+            "{REPORT_PREFIX}{}",
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
+
+/// Command line each host runs to inject its local ranks.
+pub fn remote_args(
+    program: &str,
+    source: PidSource,
+    job: Option<&str>,
+    settings: &[String],
+) -> Vec<String> {
+    let mut args = vec![
+        program.to_string(),
+        "inject".to_string(),
+        "--local-ranks".to_string(),
+        "--pids-from".to_string(),
+        source.as_str().to_string(),
+    ];
+    if let Some(job) = job {
+        args.extend(["--job".to_string(), job.to_string()]);
+    }
+    if !settings.is_empty() {
+        args.push("-D".to_string());
+        args.extend(settings.iter().cloned());
+    }
+    args
+}
+
+/// Captured output of one host's command.
+#[derive(Debug, Clone, Default)]
+pub struct HostOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs a command on a host; [`SshRunner`] in production, mocked in tests.
+pub trait HostRunner: Sync {
+    fn run(&self, host: &str, args: &[String]) -> io::Result<HostOutput>;
+}
+
+/// Runs commands as `<ssh command> <host> '<args>'`.
+pub struct SshRunner {
+    ssh: Vec<String>,
+}
+
+impl SshRunner {
+    pub fn new(ssh: &str) -> Result<Self> {
+        let ssh: Vec<String> = ssh.split_whitespace().map(str::to_string).collect();
+        if ssh.is_empty() {
+            return Err(CliError::Usage("--ssh must not be empty".into()).into());
+        }
+        Ok(Self { ssh })
+    }
+}
+
+impl HostRunner for SshRunner {
+    fn run(&self, host: &str, args: &[String]) -> io::Result<HostOutput> {
+        let remote = args
+            .iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let output = Command::new(&self.ssh[0])
+            .args(&self.ssh[1..])
+            .arg(host)
+            .arg(remote)
+            .output()?;
+        Ok(HostOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankStatus {
+    Injected,
+    Failed,
+    /// The host could not be reached or reported no ranks.
+    HostError,
+}
+
+impl RankStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RankStatus::Injected => "injected",
+            RankStatus::Failed => "failed",
+            RankStatus::HostError => "host-error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankRow {
+    pub host: String,
+    pub rank: Option<u32>,
+    pub pid: Option<i32>,
+    pub status: RankStatus,
+    pub detail: String,
+}
+
+impl RankRow {
+    fn host_error(host: &str, detail: String) -> Self {
+        Self {
+            host: host.to_string(),
+            rank: None,
+            pid: None,
+            status: RankStatus::HostError,
+            detail,
+        }
+    }
+}
+
+/// Runs `args` on every host in parallel and collects one row per rank.
+pub fn inject_hosts(runner: &dyn HostRunner, hosts: &[String], args: &[String]) -> Vec<RankRow> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = hosts
+            .iter()
+            .map(|host| (host, scope.spawn(move || runner.run(host, args))))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|(host, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(io::Error::other("runner panicked")));
+                host_rows(host, result)
+            })
+            .collect()
+    })
+}
+
+fn host_rows(host: &str, result: io::Result<HostOutput>) -> Vec<RankRow> {
+    let output = match result {
+        Ok(output) => output,
+        Err(err) => return vec![RankRow::host_error(host, format!("failed to run: {err}"))],
+    };
+    let rows: Vec<RankRow> = output
+        .stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix(REPORT_PREFIX))
+        .filter_map(|json| serde_json::from_str::<RankReport>(json).ok())
+        .map(|report| RankRow {
+            host: host.to_string(),
+            rank: report.rank,
+            pid: Some(report.pid),
+            status: if report.ok {
+                RankStatus::Injected
+            } else {
+                RankStatus::Failed
+            },
+            detail: report.error.unwrap_or_default(),
+        })
+        .collect();
+    if !rows.is_empty() {
+        return rows;
+    }
+    let detail = if output.success {
+        "no rank processes found".to_string()
+    } else {
+        output
+            .stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("remote command failed")
+            .trim()
+            .to_string()
+    };
+    vec![RankRow::host_error(host, detail)]
+}
+
+/// Merged result of an `--all-ranks` run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub rows: Vec<RankRow>,
+    /// Ranks known not to be probed: missing from `0..world_size` when the
+    /// world size is known, otherwise the ones that reported a failure.
+    pub unprobed: Vec<u32>,
+    /// Hosts whose ranks are unknown.
+    pub failed_hosts: Vec<String>,
+}
+
+pub fn summarize(rows: Vec<RankRow>, world_size: Option<u32>) -> Outcome {
+    let injected: BTreeSet<u32> = rows
+        .iter()
+        .filter(|r| r.status == RankStatus::Injected)
+        .filter_map(|r| r.rank)
+        .collect();
+    let unprobed = match world_size {
+        Some(n) => (0..n).filter(|r| !injected.contains(r)).collect(),
+        None => rows
+            .iter()
+            .filter(|r| r.status == RankStatus::Failed)
+            .filter_map(|r| r.rank)
+            .filter(|r| !injected.contains(r))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    };
+    let failed_hosts = rows
+        .iter()
+        .filter(|r| r.status == RankStatus::HostError)
+        .map(|r| r.host.clone())
+        .collect();
+    Outcome {
+        rows,
+        unprobed,
+        failed_hosts,
+    }
+}
+
+impl Outcome {
+    pub fn to_dataframe(&self) -> DataFrame {
+        let text = |f: &dyn Fn(&RankRow) -> String| -> Seq {
+            Seq::SeqText(self.rows.iter().map(f).collect())
+        };
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        DataFrame::new(
+            ["host", "rank", "pid", "status", "detail"]
+                .map(String::from)
+                .to_vec(),
+            vec![
+                text(&|r| r.host.clone()),
+                text(&|r| opt(r.rank.map(|v| v.to_string()))),
+                text(&|r| opt(r.pid.map(|v| v.to_string()))),
+                text(&|r| r.status.as_str().to_string()),
+                text(&|r| r.detail.clone()),
+            ],
+        )
+    }
+
+    /// Ok when every rank was injected; otherwise an error naming what is left.
+    pub fn into_result(self) -> Result<()> {
+        let failed_ranks = self.rows.iter().any(|r| r.status == RankStatus::Failed);
+        if self.unprobed.is_empty() && self.failed_hosts.is_empty() && !failed_ranks {
+            return Ok(());
+        }
+        let mut problems = Vec::new();
+        if !self.unprobed.is_empty() {
+            problems.push(format!(
+                "ranks {} remain un-probed",
+                format_ranks(&self.unprobed)
+            ));
+        }
+        if !self.failed_hosts.is_empty() {
+            problems.push(format!(
+                "hosts {} did not report their ranks",
+                self.failed_hosts.join(",")
+            ));
+        }
+        if problems.is_empty() {
+            problems.push("some rank processes failed to inject".to_string());
+        }
+        bail!("partial injection: {}", problems.join("; "))
+    }
+}
+
+/// `[0, 1, 2, 5]` -> `0-2,5`.
+fn format_ranks(ranks: &[u32]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut iter = ranks.iter().copied().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end += 1;
+            iter.next();
+        }
+        parts.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        });
+    }
+    parts.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn expands_slurm_host_lists() {
+        assert_eq!(
+            expand_hostlist("node[01-03,07],login1").unwrap(),
+            ["node01", "node02", "node03", "node07", "login1"]
+        );
+        assert_eq!(
+            expand_hostlist("r[1-2]n[8-9]").unwrap(),
+            ["r1n8", "r1n9", "r2n8", "r2n9"]
+        );
+        assert!(expand_hostlist("node[3-1]").is_err());
+        assert!(expand_hostlist("node[1-2").is_err());
+    }
+
+    #[test]
+    fn reads_rendezvous_from_launcher_env() {
+        let env: HashMap<String, String> = [
+            ("SLURM_JOB_NODELIST", "gpu[1-2]"),
+            ("SLURM_NTASKS", "16"),
+            ("SLURM_JOB_ID", "4242"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let rdzv = Rendezvous::from_env(&env).unwrap();
+        assert_eq!(rdzv.hosts, ["gpu1", "gpu2"]);
+        assert_eq!(rdzv.world_size, Some(16));
+        assert_eq!(rdzv.job.as_deref(), Some("SLURM_JOB_ID=4242"));
+        assert_eq!(
+            Rendezvous::from_env(&HashMap::new()).unwrap(),
+            Rendezvous::default()
+        );
+    }
+
+    #[test]
+    fn keeps_only_topmost_rank_processes() {
+        let c = |pid, ppid, rank, explicit| Candidate {
+            pid,
+            ppid,
+            rank: Some(rank),
+            explicit,
+        };
+        let ranks = topmost(vec![
+            // torchrun under srun: rank only from SLURM_PROCID.
+            c(10, 1, 0, false),
+            c(12, 10, 1, true),
+            c(11, 10, 0, true),
+            // dataloader worker of rank 1.
+            c(13, 12, 1, true),
+        ]);
+        assert_eq!(
+            ranks,
+            [
+                LocalRank {
+                    pid: 11,
+                    rank: Some(0)
+                },
+                LocalRank {
+                    pid: 12,
+                    rank: Some(1)
+                },
+            ]
+        );
+    }
+
+    struct MockRunner {
+        calls: Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    impl HostRunner for MockRunner {
+        fn run(&self, host: &str, args: &[String]) -> io::Result<HostOutput> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((host.to_string(), args.to_vec()));
+            let report = |rank, pid, error: Option<&str>| {
+                RankReport {
+                    rank: Some(rank),
+                    pid,
+                    ok: error.is_none(),
+                    error: error.map(str::to_string),
+                }
+                .line()
+            };
+            match host {
+                "a" => Ok(HostOutput {
+                    success: true,
+                    stdout: format!(
+                        "Injecting libprobing.so into 100\n{}\n{}\n",
+                        report(0, 100, None),
+                        report(1, 101, None)
+                    ),
+                    stderr: String::new(),
+                }),
+                "b" => Ok(HostOutput {
+                    success: true,
+                    stdout: format!(
+                        "{}\n{}\n",
+                        report(2, 200, None),
+                        report(3, 201, Some("ptrace: permission denied"))
+                    ),
+                    stderr: String::new(),
+                }),
+                "c" => Ok(HostOutput {
+                    success: false,
+                    stdout: String::new(),
+                    stderr: "ssh: connect to host c port 22: Connection refused\n".into(),
+                }),
+                _ => Err(io::Error::new(io::ErrorKind::NotFound, "ssh not found")),
+            }
+        }
+    }
+
+    #[test]
+    fn aggregates_hosts_and_reports_unprobed_ranks() {
+        let runner = MockRunner {
+            calls: Mutex::new(Vec::new()),
+        };
+        let hosts: Vec<String> = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let args = remote_args("probing", PidSource::Env, Some("SLURM_JOB_ID=7"), &[]);
+        let rows = inject_hosts(&runner, &hosts, &args);
+
+        let calls = runner.calls.lock().unwrap();
+        assert_eq!(calls.len(), 4);
+        assert!(calls.iter().all(|(_, a)| a == &args));
+        assert_eq!(
+            args,
+            [
+                "probing",
+                "inject",
+                "--local-ranks",
+                "--pids-from",
+                "env",
+                "--job",
+                "SLURM_JOB_ID=7"
+            ]
+        );
+
+        let outcome = summarize(rows, Some(8));
+        let statuses: Vec<_> = outcome
+            .rows
+            .iter()
+            .map(|r| (r.host.as_str(), r.rank, r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("a", Some(0), RankStatus::Injected),
+                ("a", Some(1), RankStatus::Injected),
+                ("b", Some(2), RankStatus::Injected),
+                ("b", Some(3), RankStatus::Failed),
+                ("c", None, RankStatus::HostError),
+                ("d", None, RankStatus::HostError),
+            ]
+        );
+        assert_eq!(
+            outcome.rows[4].detail,
+            "ssh: connect to host c port 22: Connection refused"
+        );
+        assert_eq!(outcome.unprobed, [3, 4, 5, 6, 7]);
+        assert_eq!(outcome.failed_hosts, ["c", "d"]);
+        assert_eq!(outcome.to_dataframe().names.len(), 5);
+
+        let err = outcome.into_result().unwrap_err().to_string();
+        assert!(err.contains("ranks 3-7 remain un-probed"), "{err}");
+        assert!(err.contains("hosts c,d"), "{err}");
+    }
+
+    #[test]
+    fn complete_run_succeeds_and_quotes_remote_args() {
+        let rows = vec![RankRow {
+            host: "a".into(),
+            rank: Some(0),
+            pid: Some(1),
+            status: RankStatus::Injected,
+            detail: String::new(),
+        }];
+        assert!(summarize(rows, Some(1)).into_result().is_ok());
+        assert_eq!(shell_quote("probing.a=1"), "probing.a=1");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(format_ranks(&[0, 1, 2, 5, 7, 8]), "0-2,5,7-8");
+    }
+}
@@ -9,11 +9,15 @@ use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::UnixStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request as WsRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, AUTHORIZATION};
 use tokio_tungstenite::tungstenite::Error as WsError;
 use tokio_tungstenite::{client_async, connect_async};
 use tokio_tungstenite::{tungstenite::Message as WsMessage, WebSocketStream as WsStream};
 
-use super::ctrl::ProbeEndpoint;
+use super::ctrl::{auth_token, within, ProbeEndpoint};
+use super::error::CliError;
 
 pub async fn start_repl(ctrl: ProbeEndpoint) -> Result<()> {
     println!("Connecting to REPL server...");
@@ -149,18 +153,21 @@ pub async fn start_repl(ctrl: ProbeEndpoint) -> Result<()> {
 }
 
 async fn connect_websocket(ctrl: &ProbeEndpoint) -> Result<WsConnection> {
-    match ctrl {
-        ProbeEndpoint::Local { pid } => connect_unix_websocket(*pid).await,
-        ProbeEndpoint::Remote { addr } => connect_tcp_websocket(addr).await,
-        _ => anyhow::bail!("Unsupported endpoint type for REPL"),
-    }
+    within(ctrl, "the REPL handshake", async {
+        match ctrl {
+            ProbeEndpoint::Local { pid } => connect_unix_websocket(*pid).await,
+            ProbeEndpoint::Remote { addr } => connect_tcp_websocket(addr).await,
+            _ => Err(CliError::Usage("Unsupported endpoint type for REPL".into()).into()),
+        }
+    })
+    .await
 }
 
 async fn connect_tcp_websocket(addr: &str) -> Result<WsConnection> {
     let url = format!("ws://{}/ws", addr);
-    let (ws_stream, _) = connect_async(&url)
-        .await
-        .context("WebSocket connection failed")?;
+    let (ws_stream, _) = connect_async(ws_request(&url)?).await.with_context(|| {
+        CliError::TargetNotFound(format!("WebSocket connection to {addr} failed"))
+    })?;
 
     Ok(boxed_connection(ws_stream))
 }
@@ -174,24 +181,35 @@ async fn connect_unix_websocket(pid: i32) -> Result<WsConnection> {
         temp_dir.join(format!("probing-{}.sock", pid))
     };
 
-    let stream: UnixStream = {
-        #[cfg(target_os = "linux")]
-        {
-            UnixStream::connect(path.as_str()).await?
-        }
-        #[cfg(not(target_os = "linux"))]
-        {
-            UnixStream::connect(&path).await?
-        }
-    };
-
-    let (ws_stream, _) = client_async("ws://localhost/ws", stream)
+    #[cfg(target_os = "linux")]
+    let stream = UnixStream::connect(path.as_str()).await;
+    #[cfg(not(target_os = "linux"))]
+    let stream = UnixStream::connect(&path).await;
+    let stream = stream.with_context(|| {
+        CliError::TargetNotFound(format!(
+            "cannot reach probing in pid {pid}: is the process running with probing enabled?"
+        ))
+    })?;
+
+    let (ws_stream, _) = client_async(ws_request("ws://localhost/ws")?, stream)
         .await
         .context("WebSocket connection failed")?;
 
     Ok(boxed_connection(ws_stream))
 }
 
+/// Handshake request carrying the same `Authorization` as HTTP requests.
+fn ws_request(url: &str) -> Result<WsRequest> {
+    let mut request = url.into_client_request()?;
+    if let Some(token) = auth_token() {
+        request.headers_mut().insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}"))?,
+        );
+    }
+    Ok(request)
+}
+
 type DynWsSink = Pin<Box<dyn Sink<WsMessage, Error = WsError> + Send>>;
 type DynWsStream = Pin<Box<dyn Stream<Item = Result<WsMessage, WsError>> + Send>>;
 
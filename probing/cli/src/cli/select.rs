@@ -0,0 +1,247 @@
+//! `--name` / `--rank`: pick the target among this host's Python processes
+//! instead of looking up its pid.
+//!
+//! `--name` matches a substring of the command line, `--rank` the
+//! `LOCAL_RANK` of the process (or `RANK` when it has none) read from
+//! `/proc/<pid>/environ`. Helpers that inherit a match from their parent,
+//! such as dataloader workers with the parent's command line and
+//! environment, are dropped, and so are launchers whose children carry the
+//! ranks. More than one match is an error unless `--all` is given; then
+//! commands that [`fans_out`] run once per process. Failures list the
+//! candidates considered.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+
+use anyhow::Result;
+
+use super::commands::Commands;
+use super::config::ConfigCommand;
+use super::error::CliError;
+use super::ps;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    Name(String),
+    Rank(u32),
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selector::Name(name) => write!(f, "--name {name}"),
+            Selector::Rank(rank) => write!(f, "--rank {rank}"),
+        }
+    }
+}
+
+impl Selector {
+    fn matches(&self, candidate: &Candidate) -> bool {
+        match self {
+            Selector::Name(name) => candidate.cmdline.contains(name.as_str()),
+            Selector::Rank(rank) => candidate.rank == Some(*rank),
+        }
+    }
+}
+
+/// A Python process of this host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub pid: i32,
+    pub ppid: i32,
+    /// `LOCAL_RANK`, else `RANK`; `None` when unset or unreadable.
+    pub rank: Option<u32>,
+    pub cmdline: String,
+}
+
+impl std::fmt::Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pid {}", self.pid)?;
+        if let Some(rank) = self.rank {
+            write!(f, " (rank {rank})")?;
+        }
+        write!(f, ": {}", self.cmdline)
+    }
+}
+
+/// This host's Python processes, see [`ps::scan`].
+pub fn candidates() -> Result<Vec<Candidate>> {
+    Ok(ps::scan()?
+        .into_iter()
+        .map(|python| {
+            // Environments of other users' processes are unreadable.
+            let process = procfs::process::Process::new(python.pid).ok();
+            let ppid = process
+                .as_ref()
+                .and_then(|process| process.stat().ok())
+                .map_or(0, |stat| stat.ppid);
+            let rank = process
+                .and_then(|process| process.environ().ok())
+                .and_then(|env| rank_of(&env));
+            Candidate {
+                pid: python.pid,
+                ppid,
+                rank,
+                cmdline: python.cmdline,
+            }
+        })
+        .collect())
+}
+
+fn rank_of(env: &HashMap<OsString, OsString>) -> Option<u32> {
+    ["LOCAL_RANK", "RANK"]
+        .iter()
+        .find_map(|key| env.get(OsStr::new(key))?.to_str()?.trim().parse().ok())
+}
+
+/// The candidates `selector` picks; exactly one unless `all`.
+pub fn resolve<'a>(
+    selector: &Selector,
+    candidates: &'a [Candidate],
+    all: bool,
+) -> Result<Vec<&'a Candidate>> {
+    let matched: Vec<&Candidate> = candidates.iter().filter(|c| selector.matches(c)).collect();
+    let picked: Vec<&Candidate> = matched
+        .iter()
+        .copied()
+        .filter(|c| {
+            let inherited = matched
+                .iter()
+                .any(|parent| parent.pid == c.ppid && parent.rank == c.rank);
+            let launcher = c.rank.is_none()
+                && matched
+                    .iter()
+                    .any(|child| child.ppid == c.pid && child.rank.is_some());
+            !inherited && !launcher
+        })
+        .collect();
+    match picked.len() {
+        0 if candidates.is_empty() => Err(CliError::TargetNotFound(format!(
+            "no Python process matches {selector}: there are no Python processes on this host"
+        ))
+        .into()),
+        0 => Err(CliError::TargetNotFound(format!(
+            "no Python process matches {selector}; candidates:\n{}",
+            listing(candidates.iter())
+        ))
+        .into()),
+        1 => Ok(picked),
+        n if all => {
+            log::debug!("{selector} matches {n} processes");
+            Ok(picked)
+        }
+        n => Err(CliError::Usage(format!(
+            "{selector} matches {n} processes; narrow it down or pass --all to run in each:\n{}",
+            listing(picked.iter().copied())
+        ))
+        .into()),
+    }
+}
+
+fn listing<'a>(candidates: impl Iterator<Item = &'a Candidate>) -> String {
+    candidates
+        .map(|c| format!("  {c}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether `command` can run once per process under `--all`: one-shot
+/// commands whose output is printed per pid, not streams or sessions.
+pub fn fans_out(command: &Commands) -> bool {
+    match command {
+        Commands::Query { watch, cancel, .. } => watch.is_none() && cancel.is_none(),
+        Commands::Config { action, .. } => !matches!(action, Some(ConfigCommand::Watch(_))),
+        Commands::Inject(_)
+        | Commands::Tables { .. }
+        | Commands::Memory { .. }
+        | Commands::Backtrace { .. }
+        | Commands::Stacks(_)
+        | Commands::Gc { .. } => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::error::{exit_code, EXIT_TARGET_NOT_FOUND, EXIT_USAGE};
+
+    fn candidate(pid: i32, ppid: i32, rank: Option<u32>, cmdline: &str) -> Candidate {
+        Candidate {
+            pid,
+            ppid,
+            rank,
+            cmdline: cmdline.into(),
+        }
+    }
+
+    /// torchrun with two ranks, a dataloader worker of rank 1, and a notebook.
+    fn host() -> Vec<Candidate> {
+        vec![
+            candidate(100, 1, None, "python -m torch.distributed.run train.py"),
+            candidate(101, 100, Some(0), "python -u train.py"),
+            candidate(102, 100, Some(1), "python -u train.py"),
+            candidate(103, 102, Some(1), "python -u train.py"),
+            candidate(200, 1, None, "python -m jupyter lab"),
+        ]
+    }
+
+    fn pids(picked: Vec<&Candidate>) -> Vec<i32> {
+        picked.into_iter().map(|c| c.pid).collect()
+    }
+
+    #[test]
+    fn rank_picks_the_rank_not_its_workers() {
+        let host = host();
+        let picked = resolve(&Selector::Rank(1), &host, false).unwrap();
+        assert_eq!(pids(picked), [102]);
+        let picked = resolve(&Selector::Name("jupyter".into()), &host, false).unwrap();
+        assert_eq!(pids(picked), [200]);
+    }
+
+    #[test]
+    fn ambiguous_names_need_all() {
+        let host = host();
+        let err = resolve(&Selector::Name("train.py".into()), &host, false).unwrap_err();
+        assert_eq!(exit_code(&err), EXIT_USAGE);
+        let msg = err.to_string();
+        assert!(msg.contains("--name train.py matches 2 processes"), "{msg}");
+        assert!(
+            msg.contains("pid 101 (rank 0): python -u train.py"),
+            "{msg}"
+        );
+        assert!(!msg.contains("pid 103"), "{msg}");
+
+        let picked = resolve(&Selector::Name("train.py".into()), &host, true).unwrap();
+        assert_eq!(pids(picked), [101, 102]);
+    }
+
+    #[test]
+    fn no_match_lists_the_candidates() {
+        let host = host();
+        let err = resolve(&Selector::Rank(7), &host, true).unwrap_err();
+        assert_eq!(exit_code(&err), EXIT_TARGET_NOT_FOUND);
+        let msg = err.to_string();
+        assert!(msg.starts_with("no Python process matches --rank 7; candidates:"));
+        assert!(msg.contains("  pid 200: python -m jupyter lab"), "{msg}");
+
+        let err = resolve(&Selector::Rank(0), &[], false).unwrap_err();
+        assert!(err.to_string().contains("no Python processes on this host"));
+    }
+
+    #[test]
+    fn local_rank_wins_over_rank() {
+        let env = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+                .collect::<HashMap<_, _>>()
+        };
+        assert_eq!(
+            rank_of(&env(&[("RANK", "9"), ("LOCAL_RANK", "1")])),
+            Some(1)
+        );
+        assert_eq!(rank_of(&env(&[("RANK", "9")])), Some(9));
+        assert_eq!(rank_of(&env(&[("PATH", "/bin")])), None);
+    }
+}
@@ -0,0 +1,52 @@
+//! `probing serve-snapshot`: browse a trace archive in the web UI, no target
+//! needed.
+//!
+//! Takes what `analyze --dump` and `watchdog` write (or an autosave
+//! directory) and serves it read-only: queries run against the archived
+//! tables, while `SET`, eval and profiler controls answer 403. The server
+//! lives in another crate, so the embedding binary registers it with
+//! [`register_snapshot_server`].
+
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use futures_util::future::BoxFuture;
+use probing_proto::prelude::TraceArchive;
+
+use crate::cli::analyze::read_archive;
+
+/// Serves `(archive, source path, listen address)` until the server exits.
+pub type SnapshotServer = fn(TraceArchive, String, String) -> BoxFuture<'static, Result<()>>;
+
+static SNAPSHOT_SERVER: OnceLock<SnapshotServer> = OnceLock::new();
+
+/// Install the server `serve-snapshot` runs; later calls are ignored.
+pub fn register_snapshot_server(server: SnapshotServer) {
+    let _ = SNAPSHOT_SERVER.set(server);
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeSnapshotCommand {
+    /// Trace archive FILE (`analyze --dump`, `watchdog`) or autosave directory
+    #[arg(value_name = "ARCHIVE")]
+    pub archive: String,
+
+    /// Address the read-only dashboard listens on
+    #[arg(long, default_value = "127.0.0.1:9090")]
+    pub listen: String,
+}
+
+impl ServeSnapshotCommand {
+    pub async fn run(&self) -> Result<()> {
+        let server = SNAPSHOT_SERVER
+            .get()
+            .context("serve-snapshot is not available in this build")?;
+        let (_, archive) = read_archive(&self.archive)?;
+        eprintln!(
+            "serving {} read-only on http://{}",
+            self.archive, self.listen
+        );
+        server(archive, self.archive.clone(), self.listen.clone()).await
+    }
+}
@@ -16,6 +16,7 @@ use anyhow::Result;
 use clap::Subcommand;
 
 use crate::cli::ctrl::ProbeEndpoint;
+use crate::cli::error::CliError;
 use crate::table::OutputFormat;
 
 #[derive(Subcommand, Debug, Clone)]
@@ -111,7 +112,7 @@ fn parse_params(params: &[String]) -> Result<HashMap<String, String>> {
     let mut out = HashMap::new();
     for p in params {
         let Some((k, v)) = p.split_once('=') else {
-            anyhow::bail!("invalid --set {p:?}, expected key=value");
+            return Err(CliError::Usage(format!("invalid --set {p:?}, expected key=value")).into());
         };
         out.insert(k.to_string(), v.to_string());
     }
@@ -0,0 +1,246 @@
+//! `probing stacks`: dump the call stack of every thread of the target, in a
+//! layout close to `py-spy dump`.
+//!
+//! Each thread gets a heading with its id, name and scheduler state, then its
+//! frames as `function (file:line)`, in the order the server captured them.
+//! Python frames only by default; `--native` asks the stack tracer for
+//! interleaved native frames, tagged `[native]`. A thread with no Python frame
+//! (e.g. blocked in a C extension or a syscall) shows its state and why no
+//! frames were captured rather than an empty section.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use clap::Args;
+use probing_proto::prelude::{CallFrame, ThreadStack};
+use serde::Deserialize;
+
+use super::ctrl::{request, ProbeEndpoint};
+use super::error::CliError;
+
+#[derive(Args, Debug, Clone)]
+pub struct StacksCommand {
+    /// Only this thread (OS thread id)
+    #[arg(long, value_name = "TID")]
+    tid: Option<u64>,
+
+    /// Interleave native frames where the stack tracer supports it
+    #[arg(long)]
+    native: bool,
+
+    /// Write the dump to a file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// Body of `/apis/pythonext/callstack?group=thread`.
+#[derive(Debug, Deserialize)]
+struct Threads {
+    #[serde(default)]
+    threads: Vec<ThreadStack>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl StacksCommand {
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        let target = String::from(ctrl.clone());
+        let url = format!(
+            "/apis/pythonext/callstack?group=thread&native={}",
+            self.native
+        );
+        let body = request(ctrl, &url, None).await?;
+        let reply: Threads =
+            serde_json::from_slice(&body).context("unexpected callstack response")?;
+        if let Some(err) = reply.error.filter(|_| reply.threads.is_empty()) {
+            anyhow::bail!("{target} could not list its threads: {err}");
+        }
+        let threads = select(reply.threads, self.tid)?;
+
+        let mut dump = format!(
+            "Stacks of {target} at {}\n\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        dump.push_str(&format_threads(&threads));
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &dump)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                eprintln!("{} thread(s) written to {}", threads.len(), path.display());
+            }
+            None => print!("{dump}"),
+        }
+        Ok(())
+    }
+}
+
+/// `threads`, or just `tid` when given.
+fn select(threads: Vec<ThreadStack>, tid: Option<u64>) -> Result<Vec<ThreadStack>> {
+    let Some(tid) = tid else {
+        return Ok(threads);
+    };
+    let known: Vec<String> = threads.iter().map(|t| t.tid.to_string()).collect();
+    let selected: Vec<ThreadStack> = threads.into_iter().filter(|t| t.tid == tid).collect();
+    if selected.is_empty() {
+        return Err(CliError::TargetNotFound(format!(
+            "thread {tid} is not a Python thread of the target (threads: {})",
+            known.join(", ")
+        ))
+        .into());
+    }
+    Ok(selected)
+}
+
+/// `Thread 7 (main, S (sleeping)): "MainThread"`.
+fn heading(thread: &ThreadStack) -> String {
+    let mut tags = Vec::new();
+    if thread.main {
+        tags.push("main");
+    }
+    if let Some(state) = thread.state.as_deref() {
+        tags.push(state);
+    }
+    let mut line = format!("Thread {}", thread.tid);
+    if !tags.is_empty() {
+        let _ = write!(line, " ({})", tags.join(", "));
+    }
+    if let Some(name) = thread.name.as_deref().filter(|n| !n.is_empty()) {
+        let _ = write!(line, ": \"{name}\"");
+    }
+    line
+}
+
+fn frame_line(frame: &CallFrame) -> String {
+    match frame {
+        CallFrame::PyFrame {
+            file, func, lineno, ..
+        } => format!("{func} ({file}:{lineno})"),
+        CallFrame::CFrame {
+            ip,
+            file,
+            func,
+            lineno,
+            ..
+        } => {
+            let func = if func.is_empty() { ip } else { func };
+            if file.is_empty() {
+                format!("{func} [native]")
+            } else {
+                format!("{func} ({file}:{lineno}) [native]")
+            }
+        }
+    }
+}
+
+fn format_threads(threads: &[ThreadStack]) -> String {
+    let mut out = String::new();
+    for thread in threads {
+        let _ = writeln!(out, "{}", heading(thread));
+        for frame in &thread.frames {
+            let _ = writeln!(out, "    {}", frame_line(frame));
+        }
+        let python = thread
+            .frames
+            .iter()
+            .any(|f| matches!(f, CallFrame::PyFrame { .. }));
+        if !python {
+            let reason = thread
+                .error
+                .as_deref()
+                .unwrap_or("likely blocked in native code");
+            let _ = match thread.state.as_deref() {
+                Some(state) => writeln!(out, "    (no Python frames: {reason}; thread is {state})"),
+                None => writeln!(out, "    (no Python frames: {reason})"),
+            };
+        }
+        out.push('\n');
+    }
+    if threads.is_empty() {
+        out.push_str("(no Python threads registered)\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn py(func: &str, lineno: i64) -> CallFrame {
+        CallFrame::PyFrame {
+            file: "train.py".into(),
+            func: func.into(),
+            lineno,
+            locals: Default::default(),
+        }
+    }
+
+    fn threads() -> Vec<ThreadStack> {
+        vec![
+            ThreadStack {
+                tid: 7,
+                name: Some("MainThread".into()),
+                main: true,
+                frames: vec![
+                    CallFrame::CFrame {
+                        ip: "0x1".into(),
+                        file: String::new(),
+                        func: "epoll_wait".into(),
+                        lineno: 0,
+                        lang: None,
+                    },
+                    py("step", 42),
+                    py("<module>", 3),
+                ],
+                state: Some("S (sleeping)".into()),
+                ..Default::default()
+            },
+            ThreadStack {
+                tid: 9,
+                name: Some("loader".into()),
+                state: Some("D (disk sleep) in nfs_wait_on_request".into()),
+                ..Default::default()
+            },
+            ThreadStack {
+                tid: 11,
+                error: Some("callstack capture busy".into()),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn formats_like_py_spy_dump() {
+        assert_eq!(
+            format_threads(&threads()),
+            "Thread 7 (main, S (sleeping)): \"MainThread\"\n    \
+             epoll_wait [native]\n    step (train.py:42)\n    <module> (train.py:3)\n\n\
+             Thread 9 (D (disk sleep) in nfs_wait_on_request): \"loader\"\n    \
+             (no Python frames: likely blocked in native code; \
+             thread is D (disk sleep) in nfs_wait_on_request)\n\n\
+             Thread 11\n    (no Python frames: callstack capture busy)\n\n"
+        );
+        assert_eq!(format_threads(&[]), "(no Python threads registered)\n");
+    }
+
+    #[test]
+    fn selects_one_thread() {
+        assert_eq!(select(threads(), None).unwrap().len(), 3);
+        let one = select(threads(), Some(9)).unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].name.as_deref(), Some("loader"));
+        let err = select(threads(), Some(5)).unwrap_err().to_string();
+        assert!(err.contains("threads: 7, 9, 11"), "{err}");
+    }
+
+    #[test]
+    fn parses_the_grouped_response() {
+        let reply: Threads = serde_json::from_str(
+            r#"{"threads": [{"tid": 7, "main": true, "frames": [], "state": "R (running)"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(reply.threads[0].state.as_deref(), Some("R (running)"));
+        assert!(reply.error.is_none());
+    }
+}
@@ -0,0 +1,440 @@
+//! `probing top`: a live view of the target's resource usage, like `htop`
+//! scoped to one process.
+//!
+//! Every `--interval` the newest rows of `cpu.utilization` (CPU%, RSS, I/O
+//! rates, threads) and `gpu.utilization` are fetched over one connection and
+//! redrawn. On a terminal the view takes the alternate screen in raw mode:
+//! `q` (or Ctrl-C) quits and `p` pauses. The terminal is restored on every
+//! exit path, panics included. Piped output gets one frame per interval.
+
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, terminal};
+use probing_proto::prelude::{DataFrame, Ele, Query};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+
+use super::bench::metrics::human_bytes;
+use super::ctrl::{ProbeEndpoint, Session};
+use super::watchdog::parse_interval;
+use crate::table::format_table;
+
+/// Busiest threads shown below the summary.
+const TOP_THREADS: usize = 10;
+
+const PROCESS_SQL: &str =
+    "SELECT * FROM cpu.utilization WHERE scope = 'process' ORDER BY ts DESC LIMIT 1";
+
+#[derive(Args, Debug, Clone)]
+pub struct TopCommand {
+    /// Refresh interval, e.g. `2s`, `500ms` or `2`
+    #[arg(short, long, default_value = "2s", value_parser = parse_interval)]
+    interval: Duration,
+}
+
+/// Newest process row of `cpu.utilization`.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ProcessStats {
+    ts_us: i64,
+    cpu_pct: f64,
+    user_pct: f64,
+    sys_pct: f64,
+    rss_kb: i64,
+    threads: i64,
+    /// Bytes per second; `None` for targets without the I/O columns.
+    read_rate: Option<f64>,
+    write_rate: Option<f64>,
+}
+
+impl ProcessStats {
+    fn from_row(df: &DataFrame) -> Option<Self> {
+        let ts_us = int(df, "ts")?;
+        let secs = float(df, "wall_ns").filter(|ns| *ns > 0.0)? / 1e9;
+        let rate = |name: &str| float(df, name).map(|bytes| bytes / secs);
+        Some(Self {
+            ts_us,
+            cpu_pct: float(df, "cpu_total_pct").unwrap_or_default(),
+            user_pct: float(df, "cpu_user_pct").unwrap_or_default(),
+            sys_pct: float(df, "cpu_sys_pct").unwrap_or_default(),
+            rss_kb: int(df, "rss_kb").unwrap_or_default(),
+            threads: int(df, "thread_count").unwrap_or_default(),
+            read_rate: rate("delta_read_bytes"),
+            write_rate: rate("delta_write_bytes"),
+        })
+    }
+}
+
+/// One refresh of the view.
+#[derive(Debug, Default)]
+struct Frame {
+    process: Option<ProcessStats>,
+    threads: Option<DataFrame>,
+    gpus: Option<DataFrame>,
+    /// Why the last refresh failed; the previous numbers stay on screen.
+    error: Option<String>,
+}
+
+fn cell(df: &DataFrame, name: &str) -> Option<Ele> {
+    let col = df.cols.get(df.col_index(name)?)?;
+    (!col.is_empty()).then(|| col.get(0))
+}
+
+fn int(df: &DataFrame, name: &str) -> Option<i64> {
+    match cell(df, name)? {
+        Ele::I32(x) => Some(x as i64),
+        Ele::I64(x) | Ele::DateTime(x) => Some(x),
+        Ele::DataTime(x) => Some(x as i64),
+        _ => None,
+    }
+}
+
+fn float(df: &DataFrame, name: &str) -> Option<f64> {
+    match cell(df, name)? {
+        Ele::F32(x) => Some(x as f64),
+        Ele::F64(x) => Some(x),
+        _ => int(df, name).map(|x| x as f64),
+    }
+}
+
+fn non_empty(df: DataFrame) -> Option<DataFrame> {
+    (!df.is_empty()).then_some(df)
+}
+
+async fn fetch(session: &mut Session) -> Result<Frame> {
+    let process = session.query(Query::new(PROCESS_SQL.to_string())).await?;
+    let threads = session
+        .query(Query::new(format!(
+            "SELECT tid, comm, state, cpu_total_pct AS cpu_pct, wchan FROM cpu.utilization \
+             WHERE scope = 'thread' AND ts = (SELECT max(ts) FROM cpu.utilization \
+             WHERE scope = 'thread') ORDER BY cpu_total_pct DESC LIMIT {TOP_THREADS}"
+        )))
+        .await?;
+    // Hosts without GPUs have no such table.
+    let gpus = session
+        .query(Query::new(
+            "SELECT device_id, name, used_bytes, total_bytes, mem_used_pct, gpu_util_pct \
+             FROM gpu.utilization WHERE ts = (SELECT max(ts) FROM gpu.utilization) \
+             ORDER BY device_id"
+                .to_string(),
+        ))
+        .await
+        .ok()
+        .and_then(non_empty);
+    Ok(Frame {
+        process: ProcessStats::from_row(&process),
+        threads: non_empty(threads),
+        gpus,
+        error: None,
+    })
+}
+
+fn rate(bytes_per_sec: Option<f64>) -> String {
+    match bytes_per_sec {
+        Some(rate) => format!("{}/s", human_bytes(rate.max(0.0) as u64)),
+        None => "n/a".to_string(),
+    }
+}
+
+/// The view as lines of at most `width` columns.
+fn render(
+    frame: &Frame,
+    target: &str,
+    every: Duration,
+    paused: bool,
+    keys: bool,
+    width: usize,
+) -> Vec<String> {
+    let mut title = format!("probing top - {target} - every {every:?}");
+    if paused {
+        title.push_str(" - PAUSED");
+    }
+    if keys {
+        title.push_str("    (q quit, p pause)");
+    }
+    let mut lines = vec![title, String::new()];
+    match &frame.process {
+        Some(p) => {
+            let at = DateTime::from_timestamp_micros(p.ts_us)
+                .map(|t| t.with_timezone(&Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            lines.push(format!(
+                "CPU     {:6.1}%   user {:.1}%  sys {:.1}%      sampled {at}",
+                p.cpu_pct, p.user_pct, p.sys_pct
+            ));
+            lines.push(format!(
+                "RSS     {}   threads {}",
+                human_bytes(p.rss_kb.max(0) as u64 * 1024),
+                p.threads
+            ));
+            lines.push(format!(
+                "I/O     read {}   write {}",
+                rate(p.read_rate),
+                rate(p.write_rate)
+            ));
+        }
+        None => lines.push(
+            "No CPU samples yet: the target samples cpu.utilization every second unless \
+             PROBING_CPU=off (interval: PROBING_CPU_SAMPLE_MS)."
+                .to_string(),
+        ),
+    }
+    if let Some(gpus) = &frame.gpus {
+        lines.push(String::new());
+        lines.push("GPU memory".to_string());
+        lines.extend(format_table(gpus, width).lines().map(String::from));
+    }
+    if let Some(threads) = &frame.threads {
+        lines.push(String::new());
+        lines.push(format!("Busiest threads (top {TOP_THREADS})"));
+        lines.extend(format_table(threads, width).lines().map(String::from));
+    }
+    if let Some(err) = &frame.error {
+        lines.push(String::new());
+        lines.push(format!("refresh failed: {err}"));
+    }
+    lines
+        .into_iter()
+        .map(|line| line.chars().take(width).collect())
+        .collect()
+}
+
+/// Raw mode on the alternate screen, undone on drop and on panic.
+struct Screen;
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            Screen::restore();
+            previous(info);
+        }));
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(Screen)
+    }
+
+    fn restore() {
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+
+    fn draw(&self, lines: &[String]) -> Result<()> {
+        let (_, rows) = terminal::size().unwrap_or((80, 24));
+        let mut out = std::io::stdout().lock();
+        crossterm::queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        // Raw mode does not turn `\n` into a carriage return.
+        let shown: Vec<&str> = lines
+            .iter()
+            .take(rows as usize)
+            .map(String::as_str)
+            .collect();
+        write!(out, "{}", shown.join("\r\n"))?;
+        Ok(out.flush()?)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        Screen::restore();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Quit,
+    Pause,
+}
+
+fn key(event: KeyEvent) -> Option<Key> {
+    if event.kind != KeyEventKind::Press {
+        return None;
+    }
+    match event.code {
+        KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => Some(Key::Quit),
+        KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => Some(Key::Quit),
+        KeyCode::Char('p') | KeyCode::Char('P') | KeyCode::Char(' ') => Some(Key::Pause),
+        _ => None,
+    }
+}
+
+/// Keys read on a thread of their own; it stops once `keys` is dropped.
+fn read_keys() -> mpsc::UnboundedReceiver<Key> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !tx.is_closed() {
+            match event::poll(Duration::from_millis(100)) {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(_) => break,
+            }
+            if let Ok(Event::Key(event)) = event::read() {
+                if let Some(k) = key(event) {
+                    let _ = tx.send(k);
+                }
+            }
+        }
+    });
+    rx
+}
+
+impl TopCommand {
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<()> {
+        let target = String::from(ctrl.clone());
+        let mut session = Session::new(ctrl);
+        let interactive = std::io::stdout().is_terminal() && std::io::stdin().is_terminal();
+        // Connection problems show up before the screen is taken.
+        let mut frame = fetch(&mut session).await?;
+        if !interactive {
+            return self.run_piped(&mut session, &target, frame).await;
+        }
+
+        let screen = Screen::enter()?;
+        let mut keys = read_keys();
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
+        let mut paused = false;
+        loop {
+            let width = terminal::size().map_or(80, |(cols, _)| cols as usize);
+            screen.draw(&render(&frame, &target, self.interval, paused, true, width))?;
+            tokio::select! {
+                _ = ticker.tick(), if !paused => {
+                    match fetch(&mut session).await {
+                        Ok(next) => frame = next,
+                        Err(err) => frame.error = Some(format!("{err:#}")),
+                    }
+                }
+                pressed = keys.recv() => match pressed {
+                    Some(Key::Pause) => paused = !paused,
+                    Some(Key::Quit) | None => break,
+                },
+            }
+        }
+        drop(screen);
+        Ok(())
+    }
+
+    async fn run_piped(&self, session: &mut Session, target: &str, first: Frame) -> Result<()> {
+        let mut frame = first;
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        loop {
+            for line in render(&frame, target, self.interval, false, false, usize::MAX) {
+                println!("{line}");
+            }
+            println!();
+            std::io::stdout().flush()?;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = &mut ctrl_c => break,
+            }
+            frame = match fetch(session).await {
+                Ok(next) => next,
+                Err(err) => Frame {
+                    error: Some(format!("{err:#}")),
+                    ..frame
+                },
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use probing_proto::prelude::Seq;
+
+    use super::*;
+
+    fn process_row(io: bool) -> DataFrame {
+        let mut names = vec!["ts", "wall_ns", "cpu_total_pct", "rss_kb", "thread_count"];
+        let mut cols = vec![
+            Seq::SeqI64(vec![1_700_000_000_000_000]),
+            Seq::SeqI64(vec![2_000_000_000]),
+            Seq::SeqF32(vec![150.0]),
+            Seq::SeqI64(vec![2048]),
+            Seq::SeqI32(vec![12]),
+        ];
+        if io {
+            names.extend(["delta_read_bytes", "delta_write_bytes"]);
+            cols.extend([Seq::SeqI64(vec![4 << 20]), Seq::SeqI64(vec![0])]);
+        }
+        DataFrame::new(names.into_iter().map(String::from).collect(), cols)
+    }
+
+    #[test]
+    fn reads_rates_from_the_newest_process_row() {
+        let stats = ProcessStats::from_row(&process_row(true)).unwrap();
+        assert_eq!(stats.cpu_pct, 150.0);
+        assert_eq!(stats.threads, 12);
+        assert_eq!(stats.read_rate, Some(2.0 * 1024.0 * 1024.0));
+        assert_eq!(stats.write_rate, Some(0.0));
+
+        let old_target = ProcessStats::from_row(&process_row(false)).unwrap();
+        assert_eq!(old_target.read_rate, None);
+        assert_eq!(ProcessStats::from_row(&DataFrame::default()), None);
+    }
+
+    #[test]
+    fn renders_summary_within_width() {
+        let frame = Frame {
+            process: ProcessStats::from_row(&process_row(true)),
+            error: Some("timed out".to_string()),
+            ..Default::default()
+        };
+        let lines = render(&frame, "1234", Duration::from_secs(2), true, true, 40);
+        assert!(lines[0].starts_with("probing top - 1234 - every 2s - PAUSED"));
+        assert!(lines.iter().all(|l| l.chars().count() <= 40));
+        assert!(lines.iter().any(|l| l.starts_with("RSS     2.00 MiB")));
+        assert!(lines.iter().any(|l| l.contains("read 2.00 MiB/s")));
+        assert_eq!(lines.last().unwrap(), "refresh failed: timed out");
+
+        let empty = render(
+            &Frame::default(),
+            "1234",
+            Duration::from_secs(2),
+            false,
+            false,
+            200,
+        );
+        assert!(empty[2].starts_with("No CPU samples yet"));
+    }
+
+    #[test]
+    fn maps_keys() {
+        let press = |code, modifiers| key(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            press(KeyCode::Char('q'), KeyModifiers::NONE),
+            Some(Key::Quit)
+        );
+        assert_eq!(
+            press(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(Key::Quit)
+        );
+        assert_eq!(
+            press(KeyCode::Char('p'), KeyModifiers::NONE),
+            Some(Key::Pause)
+        );
+        assert_eq!(press(KeyCode::Char('c'), KeyModifiers::NONE), None);
+    }
+}
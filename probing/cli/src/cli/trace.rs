@@ -0,0 +1,919 @@
+//! `probing trace start|stop|status`: manage function traces from the
+//! command line, through the same `pythonext` endpoints as the web UI.
+//! `probing trace watch <function>`: live view of watched-variable records.
+//! `probing trace flush` writes out lines buffered by the trace file sink.
+//!
+//! A trace started with print-to-terminal off only writes its records to
+//! `python.trace_variables`. `watch` polls that table for rows of one
+//! function newer than the last one it printed (the server has no push
+//! channel for them) and prints one line per traced call: local time, call
+//! id, and the variables that changed. `--values-only` drops the prefix,
+//! `--jsonl` prints one JSON object per call, and `--stats` replaces the
+//! records with a rolling count/mean of numeric variables every
+//! `--stats-every`.
+//!
+//! Watching starts at the newest record present when it attaches. A failed
+//! poll keeps the cursor, so once the target answers again the watcher
+//! resumes from the last timestamp it saw; rows sharing that timestamp are
+//! de-duplicated.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use clap::{Args, Subcommand};
+use probing_proto::prelude::{DataFrame, Ele, Query, Seq};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::cli::ctrl::ProbeEndpoint;
+use crate::cli::error::CliError;
+use crate::cli::watchdog::parse_interval;
+use crate::table::{render, OutputFormat};
+
+const TABLE: &str = "python.trace_variables";
+/// Rows fetched per poll; the rest arrive on the next one.
+const BATCH_ROWS: usize = 1000;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TraceCommand {
+    /// Start tracing a function, or change the variables it watches
+    Start(StartArgs),
+
+    /// Stop tracing a function
+    Stop {
+        /// Traced function, e.g. `__main__.train_step`
+        function: String,
+    },
+
+    /// List active traces with their calls, overhead and recorded rows
+    Status,
+
+    /// Print watched-variable records of a traced function as they arrive
+    Watch(WatchArgs),
+
+    /// Write buffered `probing.trace.file_sink` lines to disk
+    Flush,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StartArgs {
+    /// Function to trace, e.g. `__main__.train_step`
+    pub function: String,
+
+    /// Variables to record, comma separated (e.g. `loss,lr`)
+    #[arg(long, value_delimiter = ',')]
+    pub watch: Vec<String>,
+
+    /// Also print watched changes on the target's terminal
+    #[arg(long)]
+    pub print: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchArgs {
+    /// Traced function, as recorded in `function_name` (e.g. `__main__.train_step`)
+    pub function: String,
+
+    /// Print only `name=value` pairs, without time and call id
+    #[arg(long, conflicts_with_all = ["jsonl", "stats"])]
+    pub values_only: bool,
+
+    /// Print one JSON object per call
+    #[arg(long, conflicts_with = "stats")]
+    pub jsonl: bool,
+
+    /// Print rolling count/mean of numeric variables instead of records
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Time between `--stats` reports, e.g. `5s`, `1m`
+    #[arg(long, default_value = "5s", value_parser = parse_interval)]
+    pub stats_every: Duration,
+
+    /// Time between polls
+    #[arg(long, default_value = "500ms", value_parser = parse_interval)]
+    pub poll: Duration,
+
+    /// Disable colors (also off when stdout is not a terminal or NO_COLOR is set)
+    #[arg(long)]
+    pub no_color: bool,
+}
+
+pub async fn run(ctrl: ProbeEndpoint, cmd: TraceCommand, format: OutputFormat) -> Result<()> {
+    match cmd {
+        TraceCommand::Start(args) => start(ctrl, args).await,
+        TraceCommand::Stop { function } => stop(ctrl, &function).await,
+        TraceCommand::Status => status(ctrl, format).await,
+        TraceCommand::Watch(args) => watch(ctrl, args).await,
+        TraceCommand::Flush => flush(ctrl).await,
+    }
+}
+
+/// Dotted Python names only, so they go into the URL unescaped.
+fn check_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        return Err(CliError::Usage(format!(
+            "invalid {kind} `{name}`: use letters, digits, `_` and `.`"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn start_url(args: &StartArgs) -> Result<String> {
+    check_name("function name", &args.function)?;
+    let mut url = format!("/apis/pythonext/trace/start?function={}", args.function);
+    if !args.watch.is_empty() {
+        for var in &args.watch {
+            check_name("variable name", var)?;
+        }
+        url.push_str(&format!("&watch={}", args.watch.join(",")));
+    }
+    if args.print {
+        url.push_str("&print_to_terminal=true");
+    }
+    Ok(url)
+}
+
+/// Reply of `trace/start` and `trace/stop`.
+#[derive(Debug, Deserialize)]
+struct TraceReply {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// The reply's message, or its error (with suggestions for unknown names).
+fn trace_reply(body: &str) -> Result<String> {
+    let reply: TraceReply = serde_json::from_str(body)
+        .map_err(|_| anyhow::anyhow!("unexpected reply: {}", body.trim()))?;
+    if !reply.success {
+        anyhow::bail!(reply.error.unwrap_or_else(|| body.trim().to_string()));
+    }
+    Ok(reply.message.unwrap_or_default())
+}
+
+async fn start(ctrl: ProbeEndpoint, args: StartArgs) -> Result<()> {
+    let reply = ctrl.get(&start_url(&args)?).await?;
+    println!("{}", trace_reply(&reply)?);
+    if !args.watch.is_empty() && !args.print {
+        eprintln!(
+            "records go to {TABLE}; follow them with `probing trace watch {}`",
+            args.function
+        );
+    }
+    Ok(())
+}
+
+async fn stop(ctrl: ProbeEndpoint, function: &str) -> Result<()> {
+    check_name("function name", function)?;
+    let reply = ctrl
+        .get(&format!("/apis/pythonext/trace/stop?function={function}"))
+        .await?;
+    println!("{}", trace_reply(&reply)?);
+    Ok(())
+}
+
+/// Entry of `trace/show`.
+#[derive(Debug, Default, Deserialize)]
+struct ActiveTrace {
+    function: String,
+    #[serde(default)]
+    backend: String,
+    #[serde(default)]
+    calls: u64,
+    #[serde(default)]
+    overhead_us: Option<f64>,
+    #[serde(default)]
+    records: u64,
+    #[serde(default)]
+    records_dropped: u64,
+    /// Epoch seconds.
+    #[serde(default)]
+    last_record_ts: Option<f64>,
+}
+
+fn parse_active(body: &str) -> Result<Vec<ActiveTrace>> {
+    serde_json::from_str(body).map_err(|_| {
+        let error = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v.get("error")?.as_str().map(String::from));
+        anyhow::anyhow!(error.unwrap_or_else(|| format!("unexpected reply: {}", body.trim())))
+    })
+}
+
+fn variable_rows_sql() -> String {
+    format!("SELECT function_name, count(*) AS n FROM {TABLE} GROUP BY function_name")
+}
+
+/// Rows in the variables table per function.
+fn variable_rows(df: &DataFrame) -> HashMap<String, i64> {
+    let (Some(name), Some(n)) = (df.col_index("function_name"), df.col_index("n")) else {
+        return HashMap::new();
+    };
+    df.iter()
+        .filter_map(|row| Some((row[name].to_string(), int(&row[n])?)))
+        .collect()
+}
+
+fn status_frame(traces: &[ActiveTrace], rows: &HashMap<String, i64>) -> DataFrame {
+    let int_col =
+        |f: fn(&ActiveTrace) -> u64| Seq::SeqI64(traces.iter().map(|t| f(t) as i64).collect());
+    DataFrame::new(
+        [
+            "function",
+            "backend",
+            "calls",
+            "overhead_us",
+            "records",
+            "dropped",
+            "variable_rows",
+            "last_record",
+        ]
+        .map(String::from)
+        .to_vec(),
+        vec![
+            Seq::SeqText(traces.iter().map(|t| t.function.clone()).collect()),
+            Seq::SeqText(traces.iter().map(|t| t.backend.clone()).collect()),
+            int_col(|t| t.calls),
+            Seq::SeqText(
+                traces
+                    .iter()
+                    .map(|t| {
+                        t.overhead_us
+                            .map(|us| format!("{us:.1}"))
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            ),
+            int_col(|t| t.records),
+            int_col(|t| t.records_dropped),
+            Seq::SeqI64(
+                traces
+                    .iter()
+                    .map(|t| rows.get(&t.function).copied().unwrap_or(0))
+                    .collect(),
+            ),
+            Seq::SeqText(
+                traces
+                    .iter()
+                    .map(|t| {
+                        t.last_record_ts
+                            .map(|secs| local_time((secs * 1e6) as i64))
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            ),
+        ],
+    )
+}
+
+async fn status(ctrl: ProbeEndpoint, format: OutputFormat) -> Result<()> {
+    let traces = parse_active(&ctrl.get("/apis/pythonext/trace/show").await?)?;
+    if traces.is_empty() && format == OutputFormat::Table {
+        println!("No active traces.");
+        return Ok(());
+    }
+    // Missing until the first watched variable is recorded.
+    let rows = match ctrl.query(Query::new(variable_rows_sql())).await {
+        Ok(df) => variable_rows(&df),
+        Err(err) => {
+            log::debug!("no variable row counts: {err}");
+            HashMap::new()
+        }
+    };
+    render(&status_frame(&traces, &rows), format);
+    Ok(())
+}
+
+async fn flush(ctrl: ProbeEndpoint) -> Result<()> {
+    let reply = ctrl.get("/apis/traceextension/file_sink/flush").await?;
+    let value: serde_json::Value = serde_json::from_str(&reply)
+        .map_err(|_| anyhow::anyhow!("flush failed: {}", reply.trim()))?;
+    match value.get("file").and_then(|f| f.as_str()) {
+        Some(file) => println!("flushed {file}"),
+        None => println!("no file sink configured (set probing.trace.file_sink)"),
+    }
+    Ok(())
+}
+
+async fn watch(ctrl: ProbeEndpoint, args: WatchArgs) -> Result<()> {
+    let color =
+        !args.no_color && std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let mode = if args.stats {
+        Mode::Stats
+    } else if args.jsonl {
+        Mode::Jsonl
+    } else if args.values_only {
+        Mode::ValuesOnly
+    } else {
+        Mode::Lines
+    };
+    let format = Formatter {
+        mode,
+        color,
+        function: args.function.clone(),
+    };
+    eprintln!(
+        "watching {} in {} (Ctrl+C to stop)",
+        args.function,
+        String::from(ctrl.clone())
+    );
+
+    let mut cursor = Cursor::default();
+    let mut stats = RollingStats::default();
+    let mut outage: Option<String> = None;
+    let mut next_report = Instant::now() + args.stats_every;
+    let mut stdout = std::io::stdout();
+    loop {
+        match poll(&ctrl, &args.function, &mut cursor).await {
+            Ok(records) => {
+                if outage.take().is_some() {
+                    eprintln!("reconnected; resuming after {}", cursor.describe());
+                }
+                if mode == Mode::Stats {
+                    records.iter().for_each(|r| stats.observe(r));
+                } else {
+                    for call in group_calls(&records) {
+                        writeln!(stdout, "{}", format.call(&call))?;
+                    }
+                    stdout.flush()?;
+                }
+            }
+            Err(err) => {
+                let msg = format!("{err:#}");
+                if outage.as_deref() != Some(msg.as_str()) {
+                    eprintln!("poll failed ({msg}); retrying every {:?}", args.poll);
+                    outage = Some(msg);
+                }
+            }
+        }
+        if mode == Mode::Stats && Instant::now() >= next_report {
+            for line in format.report(&stats.report(), Local::now()) {
+                writeln!(stdout, "{line}")?;
+            }
+            stdout.flush()?;
+            next_report = Instant::now() + args.stats_every;
+        }
+        tokio::time::sleep(args.poll).await;
+    }
+}
+
+/// One poll: attach on first success, then fetch rows past the cursor.
+async fn poll(ctrl: &ProbeEndpoint, function: &str, cursor: &mut Cursor) -> Result<Vec<Record>> {
+    if !cursor.attached {
+        let df = ctrl.query(Query::new(latest_sql(function))).await?;
+        cursor.attach(df.scalar_i64("ts", 0));
+    }
+    let df = ctrl.query(Query::new(cursor.sql(function))).await?;
+    Ok(cursor.accept(parse_records(&df)))
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn latest_sql(function: &str) -> String {
+    format!(
+        "SELECT max(timestamp) AS ts FROM {TABLE} WHERE function_name = {}",
+        quote(function)
+    )
+}
+
+/// One row of `python.trace_variables`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// µs since epoch.
+    pub timestamp: i64,
+    pub call_id: i64,
+    pub name: String,
+    pub value: String,
+    pub value_type: String,
+}
+
+fn parse_records(df: &DataFrame) -> Vec<Record> {
+    let col = |name| df.col_index(name);
+    let (Some(ts), Some(call), Some(name), Some(value), Some(ty)) = (
+        col("timestamp"),
+        col("call_id"),
+        col("variable_name"),
+        col("value"),
+        col("value_type"),
+    ) else {
+        return vec![];
+    };
+    let text = |ele: Ele| match ele {
+        Ele::Nil => String::new(),
+        other => other.to_string(),
+    };
+    df.iter()
+        .filter_map(|row| {
+            Some(Record {
+                timestamp: int(&row[ts])?,
+                call_id: int(&row[call]).unwrap_or(-1),
+                name: text(row[name].clone()),
+                value: text(row[value].clone()),
+                value_type: text(row[ty].clone()),
+            })
+        })
+        .collect()
+}
+
+fn int(ele: &Ele) -> Option<i64> {
+    match ele {
+        Ele::I64(x) | Ele::DateTime(x) => Some(*x),
+        Ele::I32(x) => Some(*x as i64),
+        Ele::DataTime(x) => Some(*x as i64),
+        _ => None,
+    }
+}
+
+/// Position in the record stream: the newest timestamp printed and the rows
+/// already printed at exactly that timestamp.
+#[derive(Debug, Default)]
+struct Cursor {
+    attached: bool,
+    last_us: Option<i64>,
+    seen_at_last: HashSet<(i64, String, String)>,
+    /// Rows at `last_us` that predate attaching; skipped without being listed.
+    skip_at_last: bool,
+}
+
+impl Cursor {
+    /// Start after `latest`, the newest existing record (`None`: no rows yet).
+    fn attach(&mut self, latest: Option<i64>) {
+        self.attached = true;
+        self.last_us = latest;
+        self.skip_at_last = latest.is_some();
+    }
+
+    fn sql(&self, function: &str) -> String {
+        let since = self
+            .last_us
+            .map(|ts| format!(" AND timestamp >= {ts}"))
+            .unwrap_or_default();
+        format!(
+            "SELECT timestamp, call_id, variable_name, value, value_type FROM {TABLE} \
+             WHERE function_name = {}{since} ORDER BY timestamp, call_id LIMIT {BATCH_ROWS}",
+            quote(function)
+        )
+    }
+
+    /// Drop rows already returned by an earlier poll and advance past the rest.
+    fn accept(&mut self, mut records: Vec<Record>) -> Vec<Record> {
+        records.sort_by_key(|r| (r.timestamp, r.call_id));
+        let mut fresh = Vec::with_capacity(records.len());
+        for record in records {
+            if self.last_us.is_some_and(|last| record.timestamp < last) {
+                continue;
+            }
+            if Some(record.timestamp) == self.last_us {
+                if self.skip_at_last {
+                    continue;
+                }
+            } else {
+                self.last_us = Some(record.timestamp);
+                self.seen_at_last.clear();
+                self.skip_at_last = false;
+            }
+            let key = (record.call_id, record.name.clone(), record.value.clone());
+            if self.seen_at_last.insert(key) {
+                fresh.push(record);
+            }
+        }
+        fresh
+    }
+
+    fn describe(&self) -> String {
+        match self.last_us {
+            Some(ts) => local_time(ts),
+            None => "the start of the table".to_string(),
+        }
+    }
+}
+
+/// Records of one traced call, in arrival order.
+#[derive(Debug, Clone, PartialEq)]
+struct Call<'a> {
+    call_id: i64,
+    records: Vec<&'a Record>,
+}
+
+/// Consecutive records of the same call form one [`Call`].
+fn group_calls(records: &[Record]) -> Vec<Call<'_>> {
+    let mut calls: Vec<Call> = Vec::new();
+    for record in records {
+        match calls.last_mut() {
+            Some(call) if call.call_id == record.call_id => call.records.push(record),
+            _ => calls.push(Call {
+                call_id: record.call_id,
+                records: vec![record],
+            }),
+        }
+    }
+    calls
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Lines,
+    ValuesOnly,
+    Jsonl,
+    Stats,
+}
+
+struct Formatter {
+    mode: Mode,
+    color: bool,
+    function: String,
+}
+
+impl Formatter {
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn call(&self, call: &Call) -> String {
+        let first = call.records[0];
+        if self.mode == Mode::Jsonl {
+            let values: serde_json::Map<_, _> = call
+                .records
+                .iter()
+                .map(|r| (r.name.clone(), json!(r.value)))
+                .collect();
+            let types: serde_json::Map<_, _> = call
+                .records
+                .iter()
+                .map(|r| (r.name.clone(), json!(r.value_type)))
+                .collect();
+            return json!({
+                "timestamp_us": first.timestamp,
+                "function": self.function,
+                "call_id": call.call_id,
+                "values": values,
+                "types": types,
+            })
+            .to_string();
+        }
+        let pairs: Vec<String> = call
+            .records
+            .iter()
+            .map(|r| format!("{}={}", self.paint("1", &r.name), r.value))
+            .collect();
+        let pairs = pairs.join(" ");
+        if self.mode == Mode::ValuesOnly {
+            return pairs;
+        }
+        format!(
+            "{} {} {pairs}",
+            self.paint("2", &local_time(first.timestamp)),
+            self.paint("36", &format!("#{}", call.call_id)),
+        )
+    }
+
+    fn report(&self, rows: &[StatsRow], now: DateTime<Local>) -> Vec<String> {
+        let header = self.paint("2", &now.format("%H:%M:%S").to_string());
+        if rows.is_empty() {
+            return vec![format!("{header} no numeric records yet")];
+        }
+        let width = rows.iter().map(|r| r.name.len()).max().unwrap_or(0);
+        let mut lines = vec![header];
+        for row in rows {
+            let mean = row
+                .mean
+                .map(|m| format!("{m:.6}"))
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(format!(
+                "  {} n={} mean={mean} (total {})",
+                self.paint("1", &format!("{:<width$}", row.name)),
+                row.count,
+                row.total
+            ));
+        }
+        lines
+    }
+}
+
+fn local_time(ts_us: i64) -> String {
+    DateTime::from_timestamp_micros(ts_us)
+        .map(|t| t.with_timezone(&Local).format("%H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| ts_us.to_string())
+}
+
+/// Numeric reading of a recorded value: plain numbers, booleans, and 0-d
+/// tensors (`tensor(0.25, device='cuda:0')`).
+fn numeric(value: &str) -> Option<f64> {
+    let value = value.trim();
+    match value {
+        "True" => return Some(1.0),
+        "False" => return Some(0.0),
+        _ => {}
+    }
+    let inner = value
+        .strip_prefix("tensor(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .map(|rest| rest.split(',').next().unwrap_or(rest))
+        .unwrap_or(value);
+    inner.trim().parse().ok()
+}
+
+#[derive(Debug, Default)]
+struct RollingStats {
+    /// Per variable: (count, sum) since the last report, and total count.
+    vars: BTreeMap<String, (u64, f64, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StatsRow {
+    name: String,
+    count: u64,
+    mean: Option<f64>,
+    total: u64,
+}
+
+impl RollingStats {
+    fn observe(&mut self, record: &Record) {
+        if let Some(x) = numeric(&record.value) {
+            let entry = self.vars.entry(record.name.clone()).or_default();
+            entry.0 += 1;
+            entry.1 += x;
+            entry.2 += 1;
+        }
+    }
+
+    /// Count/mean since the previous report, then start a new window.
+    fn report(&mut self) -> Vec<StatsRow> {
+        self.vars
+            .iter_mut()
+            .map(|(name, (count, sum, total))| {
+                let row = StatsRow {
+                    name: name.clone(),
+                    count: *count,
+                    mean: (*count > 0).then(|| *sum / *count as f64),
+                    total: *total,
+                };
+                (*count, *sum) = (0, 0.0);
+                row
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    fn record(ts: i64, call_id: i64, name: &str, value: &str) -> Record {
+        Record {
+            timestamp: ts,
+            call_id,
+            name: name.to_string(),
+            value: value.to_string(),
+            value_type: "float".to_string(),
+        }
+    }
+
+    /// A recorded stream as the table returns it.
+    fn frame(records: &[Record]) -> DataFrame {
+        DataFrame::new(
+            [
+                "timestamp",
+                "call_id",
+                "variable_name",
+                "value",
+                "value_type",
+            ]
+            .map(String::from)
+            .to_vec(),
+            vec![
+                Seq::SeqI64(records.iter().map(|r| r.timestamp).collect()),
+                Seq::SeqI64(records.iter().map(|r| r.call_id).collect()),
+                Seq::SeqText(records.iter().map(|r| r.name.clone()).collect()),
+                Seq::SeqText(records.iter().map(|r| r.value.clone()).collect()),
+                Seq::SeqText(records.iter().map(|r| r.value_type.clone()).collect()),
+            ],
+        )
+    }
+
+    /// What a poll at `cursor` would return from the full `stream`.
+    fn serve(stream: &[Record], cursor: &Cursor, limit: usize) -> DataFrame {
+        let rows: Vec<Record> = stream
+            .iter()
+            .filter(|r| cursor.last_us.is_none_or(|last| r.timestamp >= last))
+            .take(limit)
+            .cloned()
+            .collect();
+        frame(&rows)
+    }
+
+    fn plain(mode: Mode) -> Formatter {
+        Formatter {
+            mode,
+            color: false,
+            function: "__main__.step".to_string(),
+        }
+    }
+
+    #[test]
+    fn start_url_carries_watch_and_print() {
+        let args = |watch: &[&str], print| StartArgs {
+            function: "__main__.train_step".to_string(),
+            watch: watch.iter().map(|w| w.to_string()).collect(),
+            print,
+        };
+        assert_eq!(
+            start_url(&args(&[], false)).unwrap(),
+            "/apis/pythonext/trace/start?function=__main__.train_step"
+        );
+        assert_eq!(
+            start_url(&args(&["loss", "lr"], true)).unwrap(),
+            "/apis/pythonext/trace/start?function=__main__.train_step&watch=loss,lr&print_to_terminal=true"
+        );
+        assert!(start_url(&args(&["a&b"], false)).is_err());
+    }
+
+    #[test]
+    fn trace_replies_surface_errors() {
+        assert_eq!(
+            trace_reply(r#"{"success": true, "message": "Started tracing f"}"#).unwrap(),
+            "Started tracing f"
+        );
+        let err = trace_reply(
+            r#"{"success": false, "error": "Could not trace m.trian; see logs. Did you mean: m.train?"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Did you mean: m.train?"));
+        assert!(trace_reply("<html>").is_err());
+        assert_eq!(
+            parse_active(r#"{"error": "boom"}"#)
+                .unwrap_err()
+                .to_string(),
+            "boom"
+        );
+    }
+
+    #[test]
+    fn status_joins_variable_row_counts() {
+        let traces = parse_active(
+            r#"[{"function": "m.step", "backend": "settrace", "calls": 3,
+                 "overhead_us": 12.345, "records": 6, "records_dropped": 0,
+                 "last_record_ts": null},
+                {"function": "m.eval", "calls": 1}]"#,
+        )
+        .unwrap();
+        let counts = DataFrame::new(
+            vec!["function_name".to_string(), "n".to_string()],
+            vec![
+                Seq::SeqText(vec!["m.step".to_string()]),
+                Seq::SeqI64(vec![6]),
+            ],
+        );
+        let df = status_frame(&traces, &variable_rows(&counts));
+        let json: serde_json::Value =
+            serde_json::from_str(&crate::table::render_json(&df)).unwrap();
+        assert_eq!(json[0]["overhead_us"], "12.3");
+        assert_eq!(json[0]["variable_rows"], 6);
+        assert_eq!(json[1]["variable_rows"], 0);
+        assert_eq!(json[1]["last_record"], "");
+    }
+
+    #[test]
+    fn formats_one_line_per_call() {
+        let records = [
+            record(1_000, 7, "loss", "0.5"),
+            record(1_010, 7, "lr", "0.01"),
+            record(2_000, 8, "loss", "0.25"),
+        ];
+        let calls = group_calls(&records);
+        assert_eq!(calls.len(), 2);
+
+        let line = plain(Mode::Lines).call(&calls[0]);
+        assert!(line.ends_with(" #7 loss=0.5 lr=0.01"), "{line}");
+        assert_eq!(line, format!("{} #7 loss=0.5 lr=0.01", local_time(1_000)));
+        assert_eq!(plain(Mode::ValuesOnly).call(&calls[1]), "loss=0.25");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&plain(Mode::Jsonl).call(&calls[0])).unwrap();
+        assert_eq!(json["call_id"], 7);
+        assert_eq!(json["timestamp_us"], 1_000);
+        assert_eq!(json["function"], "__main__.step");
+        assert_eq!(json["values"]["lr"], "0.01");
+        assert_eq!(json["types"]["loss"], "float");
+
+        let colored = Formatter {
+            color: true,
+            ..plain(Mode::ValuesOnly)
+        };
+        assert_eq!(colored.call(&calls[1]), "\x1b[1mloss\x1b[0m=0.25");
+    }
+
+    #[test]
+    fn attaches_after_existing_records_and_resumes_without_gaps_or_repeats() {
+        let stream = vec![
+            record(100, 1, "loss", "0.9"),
+            record(200, 2, "loss", "0.8"),
+            record(200, 2, "lr", "0.1"),
+            record(300, 3, "loss", "0.7"),
+            record(300, 4, "loss", "0.6"),
+            record(400, 5, "loss", "0.5"),
+        ];
+        // Attached when the newest record was at 100: only later ones print.
+        let mut cursor = Cursor::default();
+        cursor.attach(Some(100));
+        assert!(cursor.sql("f").contains("timestamp >= 100"));
+        let mut printed = Vec::new();
+        // Small batches split the rows sharing a timestamp across polls; the
+        // repeated polls stand in for a reconnect re-reading from the cursor.
+        for _ in 0..6 {
+            let batch = parse_records(&serve(&stream, &cursor, 3));
+            printed.extend(cursor.accept(batch));
+        }
+        let seen: Vec<_> = printed
+            .iter()
+            .map(|r| (r.timestamp, r.call_id, r.name.as_str()))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                (200, 2, "loss"),
+                (200, 2, "lr"),
+                (300, 3, "loss"),
+                (300, 4, "loss"),
+                (400, 5, "loss"),
+            ]
+        );
+        assert_eq!(cursor.last_us, Some(400));
+        assert!(cursor.sql("it's").contains("function_name = 'it''s'"));
+    }
+
+    #[test]
+    fn attaching_to_an_empty_table_prints_everything() {
+        let stream = vec![record(10, 1, "x", "1"), record(10, 1, "y", "2")];
+        let mut cursor = Cursor::default();
+        cursor.attach(None);
+        assert!(!cursor.sql("f").contains("timestamp >="));
+        let first = cursor.accept(parse_records(&serve(&stream, &cursor, 10)));
+        assert_eq!(first.len(), 2);
+        assert!(cursor
+            .accept(parse_records(&serve(&stream, &cursor, 10)))
+            .is_empty());
+    }
+
+    #[test]
+    fn rolling_stats_average_numeric_values_per_window() {
+        let mut stats = RollingStats::default();
+        for r in [
+            record(1, 1, "loss", "0.5"),
+            record(2, 2, "loss", "tensor(1.5, device='cuda:0')"),
+            record(3, 2, "done", "True"),
+            record(4, 2, "name", "resnet"),
+        ] {
+            stats.observe(&r);
+        }
+        let rows = stats.report();
+        assert_eq!(
+            rows,
+            [
+                StatsRow {
+                    name: "done".into(),
+                    count: 1,
+                    mean: Some(1.0),
+                    total: 1
+                },
+                StatsRow {
+                    name: "loss".into(),
+                    count: 2,
+                    mean: Some(1.0),
+                    total: 2
+                },
+            ]
+        );
+        stats.observe(&record(5, 3, "loss", "3"));
+        let rows = stats.report();
+        assert_eq!((rows[0].count, rows[0].mean, rows[0].total), (0, None, 1));
+        assert_eq!(
+            (rows[1].count, rows[1].mean, rows[1].total),
+            (1, Some(3.0), 3)
+        );
+
+        let now = Local::now();
+        let lines = plain(Mode::Stats).report(&rows, now);
+        assert_eq!(lines[2], "  loss n=1 mean=3.000000 (total 3)");
+        assert!(plain(Mode::Stats).report(&[], now)[0].ends_with("no numeric records yet"));
+    }
+}
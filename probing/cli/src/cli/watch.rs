@@ -0,0 +1,84 @@
+//! `query --watch`: re-run a statement on an interval and redraw its result,
+//! like `watch(1)` but over a single connection to the target.
+
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use probing_proto::prelude::Query;
+use tokio::time::MissedTickBehavior;
+
+use super::ctrl::{ProbeEndpoint, Session};
+use crate::table::{render, OutputFormat};
+
+/// Clear the screen and move the cursor home.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+pub async fn run(
+    ctrl: ProbeEndpoint,
+    query: Query,
+    every: Duration,
+    format: OutputFormat,
+) -> Result<()> {
+    // Piped output keeps every iteration, separated by the header lines.
+    let clear = std::io::stdout().is_terminal();
+    let mut session = Session::new(ctrl);
+    let mut ticker = tokio::time::interval(every);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    for iteration in 1.. {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut ctrl_c => break,
+        }
+        let result = tokio::select! {
+            result = session.query(query.clone()) => result,
+            _ = &mut ctrl_c => break,
+        };
+        if clear {
+            print!("{CLEAR}");
+        }
+        println!("{}", header(&query.expr, every, Local::now(), iteration));
+        println!();
+        match result {
+            Ok(df) => render(&df, format),
+            // Shown in place of the result; the next tick retries.
+            Err(err) => println!("query failed: {err:#}"),
+        }
+        std::io::stdout().flush()?;
+    }
+    Ok(())
+}
+
+/// `Every 2s: <sql>    12:00:05 #3`, with the statement on one line.
+fn header(expr: &str, every: Duration, at: DateTime<Local>, iteration: u64) -> String {
+    let expr = expr.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(
+        "Every {every:?}: {expr}    {} #{iteration}",
+        at.format("%H:%M:%S")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn header_names_interval_time_and_iteration() {
+        let at = Local.with_ymd_and_hms(2026, 1, 2, 12, 0, 5).unwrap();
+        assert_eq!(
+            header(
+                "SELECT *\n  FROM cpu.utilization",
+                Duration::from_millis(500),
+                at,
+                3
+            ),
+            "Every 500ms: SELECT * FROM cpu.utilization    12:00:05 #3"
+        );
+    }
+}
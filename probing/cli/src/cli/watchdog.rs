@@ -0,0 +1,396 @@
+//! `probing watchdog`: periodic trace-archive snapshots for post-mortems.
+//!
+//! Every `--interval` the target's trace archive (as `analyze --dump` writes
+//! it) is saved to `--out` as `snapshot-<UTC time>.bin`, and only the newest
+//! `--keep` are kept. A failed snapshot is logged and retried on the next tick.
+//! The target counts as gone once a local pid no longer exists or after
+//! `--max-misses` failures in a row; the watchdog then writes
+//! [`GONE_MARKER`] with the last known state and exits with
+//! [`EXIT_TARGET_GONE`] so a wrapper script can alert. Activity is appended to
+//! `<out>/watchdog.log`.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde::Serialize;
+
+use crate::cli::analyze::dump_archive;
+use crate::cli::ctrl::ProbeEndpoint;
+
+/// Process exit code when the target disappeared.
+pub const EXIT_TARGET_GONE: i32 = 3;
+/// Marker written to `--out` when the target disappeared.
+pub const GONE_MARKER: &str = "TARGET_GONE.json";
+const LOG_FILE: &str = "watchdog.log";
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const SNAPSHOT_SUFFIX: &str = ".bin";
+
+#[derive(Args, Debug, Clone)]
+pub struct WatchdogCommand {
+    /// Time between snapshots, e.g. `30s`, `5m`, `1h`
+    #[arg(long, default_value = "5m", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    /// Snapshots to keep; older ones are deleted
+    #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u64).range(1..))]
+    pub keep: u64,
+
+    /// Directory for snapshots, the activity log and the exit marker
+    #[arg(long, value_name = "DIR")]
+    pub out: String,
+
+    /// Failed snapshots in a row after which the target counts as gone
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_misses: u32,
+
+    /// Stop after this many attempts (default: until the target goes away)
+    #[arg(long)]
+    pub count: Option<u64>,
+}
+
+/// How [`WatchdogCommand::run`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogExit {
+    /// `--count` attempts were made.
+    Finished,
+    /// The target disappeared; [`GONE_MARKER`] was written.
+    TargetGone,
+}
+
+/// Contents of [`GONE_MARKER`].
+#[derive(Debug, Default, Serialize)]
+struct LastKnown {
+    target: String,
+    reason: String,
+    detected_at: String,
+    snapshots_taken: u64,
+    last_snapshot: Option<String>,
+    last_snapshot_bytes: Option<u64>,
+    last_success_at: Option<String>,
+    misses: u32,
+    last_error: Option<String>,
+}
+
+impl WatchdogCommand {
+    pub async fn run(&self, ctrl: ProbeEndpoint) -> Result<WatchdogExit> {
+        let out = Path::new(&self.out);
+        std::fs::create_dir_all(out).with_context(|| format!("failed to create {}", self.out))?;
+        let mut log = ActivityLog::open(&out.join(LOG_FILE))?;
+        let mut state = LastKnown {
+            target: String::from(ctrl.clone()),
+            ..Default::default()
+        };
+        log.line(format!(
+            "watching {} every {:?}, keeping {} snapshots in {}",
+            state.target, self.interval, self.keep, self.out
+        ));
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match snapshot(ctrl.clone(), out).await {
+                Ok((name, bytes)) => {
+                    log.line(format!("snapshot {name} ({bytes} bytes)"));
+                    state.snapshots_taken += 1;
+                    state.last_snapshot = Some(name);
+                    state.last_snapshot_bytes = Some(bytes);
+                    state.last_success_at = Some(timestamp(Utc::now()));
+                    state.misses = 0;
+                    match rotate(out, self.keep as usize) {
+                        Ok(removed) => {
+                            for name in removed {
+                                log.line(format!("removed {name}"));
+                            }
+                        }
+                        Err(err) => log.line(format!("rotation failed: {err}")),
+                    }
+                }
+                Err(err) => {
+                    state.misses += 1;
+                    log.line(format!(
+                        "snapshot failed ({}/{}): {err:#}",
+                        state.misses, self.max_misses
+                    ));
+                    state.last_error = Some(format!("{err:#}"));
+                    if let Some(reason) = gone_reason(&ctrl, state.misses, self.max_misses) {
+                        state.reason = reason;
+                        state.detected_at = timestamp(Utc::now());
+                        let marker = out.join(GONE_MARKER);
+                        std::fs::write(&marker, serde_json::to_vec_pretty(&state)?)
+                            .with_context(|| format!("failed to write {}", marker.display()))?;
+                        log.line(format!(
+                            "target gone: {}; wrote {GONE_MARKER}",
+                            state.reason
+                        ));
+                        return Ok(WatchdogExit::TargetGone);
+                    }
+                }
+            }
+            if self.count.is_some_and(|n| attempts >= n) {
+                log.line(format!("stopping after {attempts} attempts"));
+                return Ok(WatchdogExit::Finished);
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+/// Dump one archive into `out`; written to a `.part` file first so a crash
+/// mid-transfer never leaves a truncated snapshot behind.
+async fn snapshot(ctrl: ProbeEndpoint, out: &Path) -> Result<(String, u64)> {
+    let name = format!(
+        "{SNAPSHOT_PREFIX}{}{SNAPSHOT_SUFFIX}",
+        Utc::now().format("%Y%m%dT%H%M%S%.6fZ")
+    );
+    let path = out.join(&name);
+    let part = path.with_extension("part");
+    match dump_archive(ctrl, &part.to_string_lossy()).await {
+        Ok(bytes) => {
+            std::fs::rename(&part, &path)
+                .with_context(|| format!("failed to move snapshot to {}", path.display()))?;
+            Ok((name, bytes))
+        }
+        Err(err) => {
+            let _ = std::fs::remove_file(&part);
+            Err(err)
+        }
+    }
+}
+
+/// Delete all but the newest `keep` snapshots; returns the removed names.
+fn rotate(out: &Path, keep: usize) -> std::io::Result<Vec<String>> {
+    let mut names: Vec<String> = std::fs::read_dir(out)?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(SNAPSHOT_SUFFIX))
+        .collect();
+    // UTC timestamps in the name sort chronologically.
+    names.sort();
+    let excess = names.len().saturating_sub(keep);
+    let mut removed = Vec::with_capacity(excess);
+    for name in names.into_iter().take(excess) {
+        std::fs::remove_file(out.join(&name))?;
+        removed.push(name);
+    }
+    Ok(removed)
+}
+
+/// Why the target counts as gone after `misses` failures in a row, if it does.
+fn gone_reason(ctrl: &ProbeEndpoint, misses: u32, max_misses: u32) -> Option<String> {
+    if let ProbeEndpoint::Local { pid } | ProbeEndpoint::Ptrace { pid } = ctrl {
+        if !pid_alive(*pid) {
+            return Some(format!("process {pid} no longer exists"));
+        }
+    }
+    (misses >= max_misses).then(|| format!("{misses} snapshots failed in a row"))
+}
+
+fn pid_alive(pid: i32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    // Signal 0 only checks existence; EPERM still means the pid exists.
+    !matches!(kill(Pid::from_raw(pid), None), Err(Errno::ESRCH))
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// `30s`, `5m`, `2h`, or plain seconds.
+pub(crate) fn parse_interval(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid interval `{value}`"))?;
+    let interval = match unit {
+        "ms" => Duration::from_millis(n),
+        "s" => Duration::from_secs(n),
+        "m" => Duration::from_secs(n * 60),
+        "h" => Duration::from_secs(n * 3600),
+        _ => return Err(format!("unknown unit in `{value}` (use ms, s, m or h)")),
+    };
+    if interval.is_zero() {
+        return Err("interval must be positive".to_string());
+    }
+    Ok(interval)
+}
+
+/// Timestamped lines appended to `<out>/watchdog.log`, mirrored to `log`.
+struct ActivityLog {
+    file: std::fs::File,
+}
+
+impl ActivityLog {
+    fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    fn line(&mut self, msg: String) {
+        log::info!("watchdog: {msg}");
+        let _ = writeln!(self.file, "{} {msg}", timestamp(Utc::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+    use hyper::{Response, StatusCode};
+    use hyper_util::rt::TokioIo;
+    use probing_proto::prelude::TraceArchive;
+
+    use super::*;
+
+    /// Serves one scripted reply per connection (`true` = archive, `false` =
+    /// 503), then closes the listener so later connects are refused.
+    async fn mock_target(script: Vec<bool>) -> ProbeEndpoint {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let archive = Bytes::from(TraceArchive::default().encode().unwrap());
+        tokio::spawn(async move {
+            for ok in script {
+                let (stream, _) = listener.accept().await.unwrap();
+                let body = archive.clone();
+                let svc = hyper::service::service_fn(move |_req| {
+                    let reply = if ok {
+                        Response::new(Full::new(body.clone()))
+                    } else {
+                        let mut res = Response::new(Full::new(Bytes::from("engine not ready")));
+                        *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                        res
+                    };
+                    async move { Ok::<_, Infallible>(reply) }
+                });
+                tokio::spawn(
+                    hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), svc),
+                );
+            }
+        });
+        addr.as_str().try_into().unwrap()
+    }
+
+    fn out_dir(label: &str) -> String {
+        static SEQ: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "probing-watchdog-{label}-{}-{}",
+            std::process::id(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn command(out: &str, keep: u64, count: Option<u64>) -> WatchdogCommand {
+        WatchdogCommand {
+            interval: Duration::from_millis(5),
+            keep,
+            out: out.to_string(),
+            max_misses: 2,
+            count,
+        }
+    }
+
+    fn snapshots(out: &str) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(out)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .filter(|n| n.starts_with(SNAPSHOT_PREFIX))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn rotates_to_newest_snapshots_and_survives_transient_failures() {
+        let out = out_dir("rotate");
+        let ctrl = mock_target(vec![true, false, true, true, true]).await;
+        let exit = command(&out, 2, Some(5)).run(ctrl).await.unwrap();
+        assert_eq!(exit, WatchdogExit::Finished);
+
+        let kept = snapshots(&out);
+        assert_eq!(kept.len(), 2, "{kept:?}");
+        assert!(
+            kept.iter().all(|n| n.ends_with(SNAPSHOT_SUFFIX)),
+            "{kept:?}"
+        );
+        assert!(!Path::new(&out).join(GONE_MARKER).exists());
+
+        let log = std::fs::read_to_string(Path::new(&out).join(LOG_FILE)).unwrap();
+        assert_eq!(log.matches(" snapshot snapshot-").count(), 4, "{log}");
+        assert_eq!(log.matches(" removed ").count(), 2, "{log}");
+        assert!(log.contains("snapshot failed (1/2)"), "{log}");
+        // The removed names are older than everything kept.
+        let first_removed = log
+            .lines()
+            .find_map(|l| l.split(" removed ").nth(1))
+            .unwrap();
+        assert!(first_removed < kept[0].as_str());
+        let _ = std::fs::remove_dir_all(&out);
+    }
+
+    #[tokio::test]
+    async fn writes_marker_when_target_disappears() {
+        let out = out_dir("gone");
+        let ctrl = mock_target(vec![true, true]).await;
+        let exit = command(&out, 5, None).run(ctrl).await.unwrap();
+        assert_eq!(exit, WatchdogExit::TargetGone);
+
+        let kept = snapshots(&out);
+        assert_eq!(kept.len(), 2, "{kept:?}");
+        let marker: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(Path::new(&out).join(GONE_MARKER)).unwrap())
+                .unwrap();
+        assert_eq!(marker["snapshots_taken"], 2);
+        assert_eq!(marker["misses"], 2);
+        assert_eq!(marker["last_snapshot"], kept[1].as_str());
+        assert_eq!(marker["reason"], "2 snapshots failed in a row");
+        assert!(marker["last_error"].as_str().is_some());
+        let log = std::fs::read_to_string(Path::new(&out).join(LOG_FILE)).unwrap();
+        assert!(log.contains("target gone"), "{log}");
+        let _ = std::fs::remove_dir_all(&out);
+    }
+
+    #[test]
+    fn dead_local_pid_is_gone_without_waiting_for_misses() {
+        let ctrl = ProbeEndpoint::Local { pid: i32::MAX };
+        assert_eq!(
+            gone_reason(&ctrl, 1, 3).as_deref(),
+            Some("process 2147483647 no longer exists")
+        );
+        let me = ProbeEndpoint::Local {
+            pid: std::process::id() as i32,
+        };
+        assert_eq!(gone_reason(&me, 1, 3), None);
+    }
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(parse_interval("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("0ms").is_err());
+        assert!(parse_interval("5d").is_err());
+        assert!(parse_interval("m").is_err());
+    }
+}
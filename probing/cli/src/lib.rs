@@ -10,9 +10,8 @@ pub mod inject;
 use anyhow::Result;
 use clap::error::ErrorKind;
 use clap::FromArgMatches;
-use env_logger::Env;
 
-const ENV_PROBING_LOGLEVEL: &str = "PROBING_LOGLEVEL";
+use cli::error::ErrorFormat;
 
 fn is_help_or_version(err: &clap::Error) -> bool {
     matches!(
@@ -43,14 +42,26 @@ fn parse_cli(args: Vec<String>) -> Result<Option<cli::Cli>> {
     }
 }
 
-/// Main entry point for the CLI, can be called from Python or as a binary
+/// Main entry point for the CLI, can be called from Python or as a binary.
+///
+/// A failure is printed on stderr in the `--error-format` and ends the
+/// process with its exit code (see [`cli::error`]).
 #[tokio::main]
-pub async fn cli_main(args: Vec<String>) -> Result<()> {
-    let _ = env_logger::try_init_from_env(Env::new().filter(ENV_PROBING_LOGLEVEL));
-    let Some(mut cli) = parse_cli(args)? else {
-        return Ok(());
+pub async fn cli_main(args: Vec<String>) {
+    probing_logging::init();
+    // Until clap has parsed the flag, e.g. for a usage error.
+    let mut format = ErrorFormat::from_args(&args);
+    let result = match parse_cli(args) {
+        Ok(Some(mut cli)) => {
+            format = cli.error_format();
+            cli.run().await
+        }
+        Ok(None) => Ok(()),
+        Err(err) => Err(err),
     };
-    cli.run().await
+    if let Err(err) = result {
+        std::process::exit(cli::error::report(&err, format));
+    }
 }
 
 #[cfg(test)]
@@ -71,6 +82,12 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
 
+    #[test]
+    fn usage_errors_exit_with_code_2() {
+        let err = parse_cli(vec!["probing".into(), "--no-such-flag".into()]).unwrap_err();
+        assert_eq!(cli::error::exit_code(&err), cli::error::EXIT_USAGE);
+    }
+
     #[test]
     fn version_flag_exits_cleanly_without_error() {
         let result = parse_cli(vec!["probing".into(), "--version".into()]);
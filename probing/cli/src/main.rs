@@ -1,8 +1,8 @@
-use anyhow::Result;
 use probing_cli::cli_main;
 
-fn main() -> Result<()> {
+fn main() {
     let args: Vec<String> = std::env::args().collect();
-    // cli_main already uses #[tokio::main], so it handles async execution internally
+    // cli_main already uses #[tokio::main], so it handles async execution internally;
+    // on failure it exits with the error's code.
     cli_main(args)
 }
@@ -10,10 +10,7 @@ pub fn cli_main(py: Python, args: Vec<String>) -> PyResult<()> {
     if let Ok(exe) = py.import("sys")?.getattr("executable")?.extract::<String>() {
         std::env::set_var("PROBING_PYTHON", exe);
     }
-    if let Err(e) = cli_main_impl(args) {
-        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-            e.to_string(),
-        ));
-    }
+    // Failures end the process with their exit code (`probing_cli::cli::error`).
+    cli_main_impl(args);
     Ok(())
 }
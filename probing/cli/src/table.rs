@@ -4,64 +4,42 @@ use nix::ioctl_read;
 use nix::libc;
 #[cfg(unix)]
 use std::os::fd::{AsFd, AsRawFd};
-use tabled::builder::Builder;
-use tabled::grid::config::Position;
-use tabled::grid::records::{
-    vec_records::{Text, VecRecords},
-    ExactRecords, Records,
-};
-use tabled::settings::{
-    object::Segment,
-    peaker::{PriorityMax, PriorityMin},
-    Alignment, Settings, Style, Width,
-};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use std::io::Write;
 
 use probing_proto::prelude::{DataFrame, Ele};
+use probing_proto::types::arrow_convert;
+use probing_proto::types::basic::format_datetime;
 
-pub struct Table {
-    data: VecRecords<Text<String>>,
+/// `--max-col-width` and `--vertical`, see [`set_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TableOptions {
+    /// Cut longer cell lines to this many columns, ending them with `…`.
+    pub max_col_width: Option<usize>,
+    /// Print one `name | value` record per row when the table is wider than
+    /// the terminal.
+    pub vertical: bool,
 }
 
-impl Table {
-    pub fn new(ncol: usize, nrow: usize) -> Self {
-        Self {
-            data: VecRecords::new(vec![vec![Text::default(); ncol]; nrow + 1]),
-        }
-    }
-
-    pub fn count_rows(&self) -> usize {
-        self.data.count_rows()
-    }
-
-    pub fn count_columns(&self) -> usize {
-        self.data.count_columns()
-    }
-
-    pub fn put(&mut self, pos: Position, text: String) {
-        self.data[pos.row][pos.col] = Text::new(text)
-    }
-
-    pub fn draw(self, termwidth: usize) -> Option<String> {
-        if self.count_columns() == 0 || self.count_rows() == 0 {
-            return Some(Default::default());
-        }
+static OPTIONS: std::sync::RwLock<TableOptions> = std::sync::RwLock::new(TableOptions {
+    max_col_width: None,
+    vertical: false,
+});
 
-        let data: Vec<Vec<_>> = self.data.into();
-        let mut table = Builder::from(data).build();
-        table.with(Style::sharp());
-        table.modify(
-            Segment::all(),
-            Settings::new(Alignment::left(), Alignment::top()),
-        );
+/// Apply `options` to every following table.
+pub fn set_options(options: TableOptions) {
+    *OPTIONS.write().unwrap_or_else(|e| e.into_inner()) = options;
+}
 
-        table.with((
-            Width::wrap(termwidth).priority(PriorityMax::default()),
-            Width::increase(termwidth).priority(PriorityMin::default()),
-        ));
-        Some(table.to_string())
-    }
+fn options() -> TableOptions {
+    *OPTIONS.read().unwrap_or_else(|e| e.into_inner())
 }
 
+/// Columns are not narrowed below this when fitting a table, so that a
+/// wide character still fits on a line.
+const MIN_FIT_WIDTH: usize = 2;
+
 /// Output format for rendering query results.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
@@ -72,11 +50,15 @@ pub enum OutputFormat {
     Json,
     /// Comma-separated values with a header row.
     Csv,
+    /// Arrow IPC stream; `query` writes it to `--output`, other commands to stdout.
+    Arrow,
 }
 
+/// Text of a cell; `Nil` is empty, like a missing cell (`null` in JSON).
+/// Datetimes print as RFC 3339 in UTC.
 fn ele_to_string(ele: &Ele) -> String {
     match ele {
-        Ele::Nil => "nil".to_string(),
+        Ele::Nil => String::new(),
         Ele::BOOL(x) => x.to_string(),
         Ele::I32(x) => x.to_string(),
         Ele::I64(x) => x.to_string(),
@@ -85,6 +67,7 @@ fn ele_to_string(ele: &Ele) -> String {
         Ele::Text(x) => x.to_string(),
         Ele::Url(x) => x.to_string(),
         Ele::DataTime(x) => x.to_string(),
+        Ele::DateTime(x) => format_datetime(*x),
     }
 }
 
@@ -94,11 +77,30 @@ pub fn render(df: &DataFrame, format: OutputFormat) {
         OutputFormat::Table => render_dataframe(df),
         OutputFormat::Json => println!("{}", render_json(df)),
         OutputFormat::Csv => print!("{}", render_csv(df)),
+        OutputFormat::Arrow => match render_arrow(df) {
+            Ok(bytes) => {
+                let _ = std::io::stdout().write_all(&bytes);
+            }
+            Err(err) => eprintln!("error: cannot encode the result as Arrow: {err}"),
+        },
     }
 }
 
+/// Serialize a [`DataFrame`] into an Arrow IPC stream of one record batch.
+pub fn render_arrow(df: &DataFrame) -> anyhow::Result<Vec<u8>> {
+    let batch = df.to_record_batch()?;
+    Ok(arrow_convert::write_ipc_stream(&batch.schema(), &[batch])?)
+}
+
 /// Serialize a [`DataFrame`] into a JSON array of row objects.
 pub fn render_json(df: &DataFrame) -> String {
+    serde_json::to_string_pretty(&serde_json::Value::Array(json_rows(df)))
+        .unwrap_or_else(|_| "[]".to_string())
+}
+
+/// One JSON object per row, keyed by column name. Datetimes stay numbers
+/// (nanoseconds since the epoch) so scripts can compute with them.
+pub fn json_rows(df: &DataFrame) -> Vec<serde_json::Value> {
     let nrow = df.cols.iter().map(|col| col.len()).max().unwrap_or(0);
     let mut rows = Vec::with_capacity(nrow);
     for row in 0..nrow {
@@ -114,7 +116,7 @@ pub fn render_json(df: &DataFrame) -> String {
                 Some(Ele::Nil) | None => serde_json::Value::Null,
                 Some(Ele::BOOL(x)) => serde_json::Value::Bool(x),
                 Some(Ele::I32(x)) => serde_json::Value::from(x),
-                Some(Ele::I64(x)) => serde_json::Value::from(x),
+                Some(Ele::I64(x) | Ele::DateTime(x)) => serde_json::Value::from(x),
                 Some(Ele::F32(x)) => serde_json::Value::from(x),
                 Some(Ele::F64(x)) => serde_json::Value::from(x),
                 Some(other) => serde_json::Value::String(ele_to_string(&other)),
@@ -123,8 +125,7 @@ pub fn render_json(df: &DataFrame) -> String {
         }
         rows.push(serde_json::Value::Object(obj));
     }
-    serde_json::to_string_pretty(&serde_json::Value::Array(rows))
-        .unwrap_or_else(|_| "[]".to_string())
+    rows
 }
 
 fn csv_escape(field: &str) -> String {
@@ -166,35 +167,259 @@ pub fn render_csv(df: &DataFrame) -> String {
 }
 
 pub fn render_dataframe(df: &DataFrame) {
-    let ncol = df.names.len();
+    let rendered = format_table(df, terminal_width().unwrap_or(80) as usize);
+    println!("{rendered}");
+}
+
+/// Draw a [`DataFrame`] as a table fitted to `termwidth` columns, or as
+/// records with `--vertical`, see [`TableOptions`].
+pub fn format_table(df: &DataFrame, termwidth: usize) -> String {
+    format_table_with(df, termwidth, options())
+}
+
+fn format_table_with(df: &DataFrame, termwidth: usize, options: TableOptions) -> String {
+    if df.names.is_empty() {
+        return String::new();
+    }
+    let mut rows = rows_of(df);
+    if let Some(max) = options.max_col_width {
+        for line in rows.iter_mut().flatten().flatten() {
+            *line = truncate(line, max);
+        }
+    }
+    let widths = column_widths(&rows);
+    if options.vertical && rows.len() > 1 && table_width(&widths) > termwidth {
+        return draw_records(&rows, termwidth);
+    }
+    draw_grid(&rows, &fit(widths, termwidth))
+}
+
+/// The header and then each row, every cell split into its lines.
+fn rows_of(df: &DataFrame) -> Vec<Vec<Vec<String>>> {
     let nrow = df.cols.iter().map(|col| col.len()).max().unwrap_or(0);
+    let mut rows = Vec::with_capacity(nrow + 1);
+    rows.push(df.names.iter().map(|name| cell_lines(name)).collect());
+    for row in 0..nrow {
+        rows.push(
+            (0..df.names.len())
+                .map(|col| {
+                    let text = df
+                        .cols
+                        .get(col)
+                        .filter(|c| row < c.len())
+                        .map(|c| ele_to_string(&c.get(row)))
+                        .unwrap_or_default();
+                    cell_lines(&text)
+                })
+                .collect(),
+        );
+    }
+    rows
+}
 
-    let mut table = Table::new(ncol, nrow);
+/// Lines of a cell as printed: `\r` is dropped, a tab becomes a space and
+/// other control characters, which would move the cursor, become `�`.
+fn cell_lines(text: &str) -> Vec<String> {
+    text.split('\n')
+        .map(|line| {
+            line.chars()
+                .filter(|c| *c != '\r')
+                .map(|c| match c {
+                    '\t' => ' ',
+                    c if c.is_control() => '\u{fffd}',
+                    c => c,
+                })
+                .collect()
+        })
+        .collect()
+}
 
-    for (col, name) in df.names.iter().enumerate() {
-        table.put((0_usize, col).into(), name.clone());
+/// `line` in the pieces a terminal draws as one: a character with the
+/// zero-width marks after it, and characters joined by a ZWJ.
+fn clusters(line: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    for (i, c) in line.char_indices() {
+        let zero_width = c.width() == Some(0);
+        if i > start && !joined && !zero_width {
+            pieces.push(&line[start..i]);
+            start = i;
+        }
+        joined = c == '\u{200d}';
     }
+    if start < line.len() {
+        pieces.push(&line[start..]);
+    }
+    pieces
+}
 
-    for (col, col_data) in df.cols.iter().enumerate() {
-        for row in 0..col_data.len() {
-            let value = match col_data.get(row) {
-                Ele::Nil => "nil".to_string(),
-                Ele::BOOL(x) => x.to_string(),
-                Ele::I32(x) => x.to_string(),
-                Ele::I64(x) => x.to_string(),
-                Ele::F32(x) => x.to_string(),
-                Ele::F64(x) => x.to_string(),
-                Ele::Text(x) => x.to_string(),
-                Ele::Url(x) => x.to_string(),
-                Ele::DataTime(x) => x.to_string(),
-            };
-            table.put((row + 1, col).into(), value);
+/// Terminal columns taken by `line`.
+fn display_width(line: &str) -> usize {
+    clusters(line).into_iter().map(UnicodeWidthStr::width).sum()
+}
+
+/// `line` cut to `width` columns, ending with `…` when something was cut.
+fn truncate(line: &str, width: usize) -> String {
+    if display_width(line) <= width {
+        return line.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for piece in clusters(line) {
+        let w = piece.width();
+        if used + w + 1 > width {
+            break;
         }
+        out.push_str(piece);
+        used += w;
     }
-    let rendered = table
-        .draw(terminal_width().unwrap_or(80) as usize)
-        .unwrap_or_else(|| "(table render failed)".to_string());
-    println!("{rendered}");
+    out.push('…');
+    out
+}
+
+/// `line` broken into lines of at most `width` columns; a character wider
+/// than `width` gets a line of its own.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut used = 0;
+    for piece in clusters(line) {
+        let w = piece.width();
+        if used > 0 && used + w > width {
+            lines.push(String::new());
+            used = 0;
+        }
+        if let Some(last) = lines.last_mut() {
+            last.push_str(piece);
+        }
+        used += w;
+    }
+    lines
+}
+
+fn column_widths(rows: &[Vec<Vec<String>>]) -> Vec<usize> {
+    let ncol = rows.first().map_or(0, Vec::len);
+    (0..ncol)
+        .map(|col| {
+            rows.iter()
+                .flat_map(|row| &row[col])
+                .map(|line| display_width(line))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Width of a table with these columns, borders and padding included.
+fn table_width(widths: &[usize]) -> usize {
+    widths.iter().sum::<usize>() + 3 * widths.len() + 1
+}
+
+/// Narrow the widest column until the table fits in `termwidth`, down to
+/// [`MIN_FIT_WIDTH`] per column; cells wrap to the narrowed widths.
+fn fit(mut widths: Vec<usize>, termwidth: usize) -> Vec<usize> {
+    while table_width(&widths) > termwidth {
+        let Some(widest) = (0..widths.len()).max_by_key(|&col| widths[col]) else {
+            break;
+        };
+        if widths[widest] <= MIN_FIT_WIDTH {
+            break;
+        }
+        widths[widest] -= 1;
+    }
+    widths
+}
+
+/// `text` followed by spaces up to `width` columns.
+fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{text}{}",
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
+}
+
+/// Box-drawn table with a rule under the header, cells aligned left.
+fn draw_grid(rows: &[Vec<Vec<String>>], widths: &[usize]) -> String {
+    let rule = |left: char, mid: char, right: char| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{left}{}{right}", segments.join(&mid.to_string()))
+    };
+    let mut out = vec![rule('┌', '┬', '┐')];
+    for (i, row) in rows.iter().enumerate() {
+        if i == 1 {
+            out.push(rule('├', '┼', '┤'));
+        }
+        let cells: Vec<Vec<String>> = row
+            .iter()
+            .zip(widths)
+            .map(|(lines, &w)| lines.iter().flat_map(|line| wrap(line, w)).collect())
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+        for n in 0..height {
+            let line: Vec<String> = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, &w)| pad(cell.get(n).map_or("", String::as_str), w))
+                .collect();
+            out.push(format!("│ {} │", line.join(" │ ")));
+        }
+    }
+    out.push(rule('└', '┴', '┘'));
+    out.join("\n")
+}
+
+/// `--vertical`: a `-[ RECORD n ]-` block per row with one `name | value`
+/// line per column, values wrapped to what is left of `termwidth`.
+fn draw_records(rows: &[Vec<Vec<String>>], termwidth: usize) -> String {
+    let Some((header, records)) = rows.split_first() else {
+        return String::new();
+    };
+    let names: Vec<String> = header.iter().map(|lines| lines.join(" ")).collect();
+    let name_width = names
+        .iter()
+        .map(|name| display_width(name))
+        .max()
+        .unwrap_or(0);
+    let value_width = termwidth.saturating_sub(name_width + 3).max(MIN_FIT_WIDTH);
+    let values: Vec<Vec<Vec<String>>> = records
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .flat_map(|line| wrap(line, value_width))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    let widest = values
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|line| display_width(line))
+        .max()
+        .unwrap_or(0);
+    let rule_width = (name_width + 3 + widest).min(termwidth);
+
+    let mut out = Vec::new();
+    for (i, record) in values.iter().enumerate() {
+        let label = format!("-[ RECORD {} ]", i + 1);
+        let dashes = "-".repeat(rule_width.saturating_sub(display_width(&label)));
+        out.push(format!("{label}{dashes}"));
+        for (name, lines) in names.iter().zip(record) {
+            for (n, line) in lines.iter().enumerate() {
+                let name = if n == 0 { name.as_str() } else { "" };
+                out.push(
+                    format!("{} | {line}", pad(name, name_width))
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+        }
+    }
+    out.join("\n")
 }
 
 fn terminal_width() -> Option<u32> {
@@ -236,3 +461,229 @@ fn terminal_size_of<Fd: AsFd>(fd: Fd) -> Option<u32> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use probing_proto::prelude::Seq;
+
+    use super::*;
+
+    fn sample() -> DataFrame {
+        DataFrame::new(
+            vec!["id".to_string(), "note".to_string(), "score".to_string()],
+            vec![
+                Seq::SeqI64(vec![1, 2, 3]),
+                Seq::SeqText(vec![
+                    "a, b".to_string(),
+                    "say \"hi\"".to_string(),
+                    "two\nlines".to_string(),
+                ]),
+                Seq::SeqF64(vec![0.5, 1.0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn json_keys_rows_by_column() {
+        let rows: serde_json::Value = serde_json::from_str(&render_json(&sample())).unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([
+                {"id": 1, "note": "a, b", "score": 0.5},
+                {"id": 2, "note": "say \"hi\"", "score": 1.0},
+                {"id": 3, "note": "two\nlines", "score": null},
+            ])
+        );
+    }
+
+    #[test]
+    fn nil_columns_are_null_or_empty() {
+        let df = DataFrame::new(
+            vec!["x".to_string(), "y".to_string()],
+            vec![Seq::SeqI64(vec![1]), Seq::Nil],
+        );
+        let rows: serde_json::Value = serde_json::from_str(&render_json(&df)).unwrap();
+        assert_eq!(rows, serde_json::json!([{"x": 1, "y": null}]));
+        assert_eq!(render_csv(&df), "x,y\n1,\n");
+        assert!(!format_table(&df, 80).contains("nil"));
+    }
+
+    #[test]
+    fn csv_quotes_special_characters() {
+        assert_eq!(
+            render_csv(&sample()),
+            "id,note,score\n1,\"a, b\",0.5\n2,\"say \"\"hi\"\"\",1\n3,\"two\nlines\",\n"
+        );
+    }
+
+    #[test]
+    fn table_lists_every_cell() {
+        let table = format_table(&sample(), 120);
+        for cell in [
+            "id",
+            "note",
+            "score",
+            "a, b",
+            "say \"hi\"",
+            "two",
+            "lines",
+            "0.5",
+        ] {
+            assert!(table.contains(cell), "{cell} missing from\n{table}");
+        }
+        assert_eq!(format_table(&DataFrame::default(), 80), "");
+    }
+
+    fn text(cells: &[&str]) -> Seq {
+        Seq::SeqText(cells.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn frame(names: &[&str], cols: Vec<Seq>) -> DataFrame {
+        DataFrame::new(names.iter().map(|n| n.to_string()).collect(), cols)
+    }
+
+    #[test]
+    fn wide_characters_keep_columns_aligned() {
+        let df = frame(
+            &["name", "value"],
+            vec![
+                text(&["中文", "🚀 go", "cafe\u{301}"]),
+                Seq::SeqI64(vec![1, 22, 333]),
+            ],
+        );
+        assert_eq!(
+            format_table_with(&df, 80, TableOptions::default()),
+            "\
+┌───────┬───────┐
+│ name  │ value │
+├───────┼───────┤
+│ 中文  │ 1     │
+│ 🚀 go │ 22    │
+│ cafe\u{301}  │ 333   │
+└───────┴───────┘"
+        );
+    }
+
+    #[test]
+    fn embedded_newlines_make_taller_rows() {
+        let df = frame(
+            &["id", "note"],
+            vec![Seq::SeqI64(vec![1, 2]), text(&["two\nlines", "x\r\n\ty"])],
+        );
+        assert_eq!(
+            format_table_with(&df, 80, TableOptions::default()),
+            "\
+┌────┬───────┐
+│ id │ note  │
+├────┼───────┤
+│ 1  │ two   │
+│    │ lines │
+│ 2  │ x     │
+│    │  y    │
+└────┴───────┘"
+        );
+    }
+
+    #[test]
+    fn max_col_width_truncates_with_ellipsis() {
+        let df = frame(
+            &["path", "n"],
+            vec![
+                text(&["/usr/lib/中文.so", "中文字"]),
+                Seq::SeqI64(vec![7, 8]),
+            ],
+        );
+        let options = TableOptions {
+            max_col_width: Some(4),
+            vertical: false,
+        };
+        assert_eq!(
+            format_table_with(&df, 80, options),
+            "\
+┌──────┬───┐
+│ path │ n │
+├──────┼───┤
+│ /us… │ 7 │
+│ 中…  │ 8 │
+└──────┴───┘"
+        );
+    }
+
+    #[test]
+    fn wide_tables_wrap_to_the_terminal() {
+        let df = frame(
+            &["k", "v"],
+            vec![text(&["a", "b"]), text(&["abcdefghij", "ab中文"])],
+        );
+        let table = format_table_with(&df, 12, TableOptions::default());
+        assert_eq!(
+            table,
+            "\
+┌───┬──────┐
+│ k │ v    │
+├───┼──────┤
+│ a │ abcd │
+│   │ efgh │
+│   │ ij   │
+│ b │ ab中 │
+│   │ 文   │
+└───┴──────┘"
+        );
+        assert!(table.lines().all(|line| display_width(line) <= 12));
+    }
+
+    #[test]
+    fn vertical_prints_records_when_too_wide() {
+        let df = frame(
+            &["id", "note"],
+            vec![
+                Seq::SeqI64(vec![1, 2]),
+                text(&["abcdefghijklmnopq", "two\nlines"]),
+            ],
+        );
+        let options = TableOptions {
+            max_col_width: None,
+            vertical: true,
+        };
+        assert_eq!(
+            format_table_with(&df, 20, options),
+            "\
+-[ RECORD 1 ]-------
+id   | 1
+note | abcdefghijklm
+     | nopq
+-[ RECORD 2 ]-------
+id   | 2
+note | two
+     | lines"
+        );
+        assert!(format_table_with(&df, 80, options).starts_with('┌'));
+    }
+
+    #[test]
+    fn arrow_output_reads_back_as_the_same_frame() {
+        let mut df = sample();
+        // Arrow columns all have the same length.
+        assert!(render_arrow(&df).is_err());
+        df.cols[2] = Seq::SeqF64(vec![0.5, 1.0, 1.5]);
+
+        let bytes = render_arrow(&df).unwrap();
+        let (schema, batches) = arrow_convert::read_ipc_stream(&bytes).unwrap();
+        assert_eq!(DataFrame::from_record_batches(&schema, &batches), df);
+    }
+
+    #[test]
+    fn datetimes_are_readable_in_tables_and_numeric_in_json() {
+        let df = frame(
+            &["time"],
+            vec![Seq::SeqTimestamp(vec![1_700_000_000_500_000_000])],
+        );
+        assert!(format_table(&df, 80).contains("2023-11-14T22:13:20.500Z"));
+        assert_eq!(render_csv(&df), "time\n2023-11-14T22:13:20.500Z\n");
+        let rows: serde_json::Value = serde_json::from_str(&render_json(&df)).unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([{"time": 1_700_000_000_500_000_000i64}])
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use clap_complete::Shell;
+use probing_cli::cli::completions::generate;
+
+fn script(shell: Shell) -> String {
+    let mut out = Vec::new();
+    generate(shell, &mut out).expect("generate completions");
+    String::from_utf8(out).expect("utf8 script")
+}
+
+#[test]
+fn bash_script_completes_subcommands_and_pids() {
+    let bash = script(Shell::Bash);
+    for name in [
+        "query",
+        "tables",
+        "eval",
+        "backtrace",
+        "flamegraph",
+        "config",
+        "trace",
+        "completions",
+    ] {
+        assert!(bash.contains(name), "bash completion lacks `{name}`");
+    }
+    assert!(bash.contains("probing complete-pids"));
+    assert!(bash.contains("complete -F _probing_with_pids"));
+}
+
+#[test]
+fn zsh_and_fish_complete_target_pids() {
+    let zsh = script(Shell::Zsh);
+    assert!(zsh.starts_with("#compdef probing\n"));
+    assert!(zsh.contains(":TARGET:_probing_pids"));
+    assert!(!zsh.contains(":TARGET:_default"));
+
+    let fish = script(Shell::Fish);
+    assert!(fish.contains("-l target -x -a '(probing complete-pids 2>/dev/null)'"));
+}
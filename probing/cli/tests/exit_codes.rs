@@ -0,0 +1,87 @@
+//! Exit codes of typed failures, against local servers that refuse a token,
+//! fail a query or never answer, and a pid nothing listens for.
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::Router;
+use probing_cli::cli::ctrl::{request, set_auth_token, set_limits, Limits, ProbeEndpoint};
+use probing_cli::cli::error::{
+    exit_code, render, ErrorFormat, EXIT_AUTH, EXIT_QUERY, EXIT_TARGET_NOT_FOUND, EXIT_TIMEOUT,
+    EXIT_USAGE,
+};
+use probing_proto::prelude::{ErrorCode, Message, Query, QueryDataFormat, QueryError};
+
+async fn unauthorized() -> (StatusCode, &'static str) {
+    (StatusCode::UNAUTHORIZED, "Unauthorized")
+}
+
+async fn failed_query() -> String {
+    serde_json::to_string(&Message::new(QueryDataFormat::Error(QueryError {
+        code: ErrorCode::ExecutionError,
+        message: "table 'nope' not found".into(),
+        details: None,
+    })))
+    .unwrap()
+}
+
+async fn stall() -> &'static str {
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    "late"
+}
+
+async fn spawn_server() -> ProbeEndpoint {
+    let app = Router::new()
+        .route("/apis/overview", get(unauthorized))
+        .route("/query", post(failed_query))
+        .route("/apis/stall", get(stall));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    ProbeEndpoint::Remote {
+        addr: addr.to_string(),
+    }
+}
+
+// One test: the token and limits are process-wide state.
+#[tokio::test]
+async fn failures_map_to_their_exit_codes() {
+    std::env::remove_var("PROBING_AUTH_TOKEN");
+    set_auth_token(None);
+    set_limits(Limits {
+        timeout: Some(Duration::from_millis(200)),
+        retries: 0,
+    });
+    let endpoint = spawn_server().await;
+
+    let err = request(endpoint.clone(), "/apis/overview", None)
+        .await
+        .unwrap_err();
+    assert_eq!(exit_code(&err), EXIT_AUTH, "{err:#}");
+    let json: serde_json::Value = serde_json::from_str(&render(&err, ErrorFormat::Json)).unwrap();
+    assert_eq!(json["kind"], "auth");
+    assert_eq!(json["exit_code"], EXIT_AUTH);
+
+    let err = endpoint
+        .query(Query::new("select * from nope".into()))
+        .await
+        .unwrap_err();
+    assert_eq!(exit_code(&err), EXIT_QUERY, "{err:#}");
+    assert!(err.to_string().contains("'nope' not found"), "{err}");
+
+    let err = request(endpoint, "/apis/stall", None).await.unwrap_err();
+    assert_eq!(exit_code(&err), EXIT_TIMEOUT, "{err:#}");
+
+    let gone = ProbeEndpoint::Local { pid: i32::MAX };
+    let err = request(gone, "/apis/overview", None).await.unwrap_err();
+    assert_eq!(exit_code(&err), EXIT_TARGET_NOT_FOUND, "{err:#}");
+}
+
+#[test]
+fn malformed_targets_are_usage_errors() {
+    let err = ProbeEndpoint::try_from("node1").err().unwrap();
+    assert_eq!(exit_code(&err), EXIT_USAGE, "{err:#}");
+    let err = ProbeEndpoint::from_url("https://node1:443").err().unwrap();
+    assert_eq!(exit_code(&err), EXIT_USAGE, "{err:#}");
+}
@@ -0,0 +1,91 @@
+//! `--endpoint` / `--token` against a local server that requires a Bearer
+//! token, as `probing/server/src/auth.rs` does.
+
+use axum::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use probing_cli::cli::ctrl::{request, set_auth_token, ProbeEndpoint};
+
+const TOKEN: &str = "s3cret";
+
+async fn overview(headers: HeaderMap) -> (StatusCode, &'static str) {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if bearer == Some(TOKEN) {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::UNAUTHORIZED, "Unauthorized")
+    }
+}
+
+async fn spawn_server() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route("/apis/overview", get(overview));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn fetch(endpoint: &ProbeEndpoint) -> Result<String, String> {
+    request(endpoint.clone(), "/apis/overview", None)
+        .await
+        .map(|body| String::from_utf8_lossy(&body).into_owned())
+        .map_err(|err| format!("{err:#}"))
+}
+
+// One test: the token is process-wide state.
+#[tokio::test]
+async fn token_is_sent_and_failures_are_explained() {
+    std::env::remove_var("PROBING_AUTH_TOKEN");
+    let endpoint = ProbeEndpoint::from_url(&spawn_server().await).unwrap();
+
+    set_auth_token(None);
+    let err = fetch(&endpoint).await.unwrap_err();
+    assert!(err.contains("requires authentication"), "{err}");
+    assert!(err.contains("--token"), "{err}");
+
+    set_auth_token(Some("wrong"));
+    let err = fetch(&endpoint).await.unwrap_err();
+    assert!(err.contains("rejected the token"), "{err}");
+
+    set_auth_token(Some(TOKEN));
+    assert_eq!(fetch(&endpoint).await.unwrap(), "ok");
+    // Loopback: no cleartext warning.
+    assert_eq!(endpoint.cleartext_token_warning(), None);
+    let remote = ProbeEndpoint::from_url("http://10.1.2.3:8080").unwrap();
+    assert!(remote
+        .cleartext_token_warning()
+        .is_some_and(|w| w.contains("plain HTTP")));
+
+    let closed = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    let err = fetch(&ProbeEndpoint::from_url(&format!("http://{closed}")).unwrap())
+        .await
+        .unwrap_err();
+    assert!(
+        err.contains("cannot connect to the probing server"),
+        "{err}"
+    );
+}
+
+#[test]
+fn parses_endpoint_urls() {
+    let addr = |url: &str| match ProbeEndpoint::from_url(url).unwrap() {
+        ProbeEndpoint::Remote { addr } => addr,
+        _ => panic!("{url}: not a remote endpoint"),
+    };
+    assert_eq!(addr("http://10.0.0.5:8080/"), "10.0.0.5:8080");
+    assert_eq!(addr("node1:9700"), "node1:9700");
+    assert_eq!(addr("http://node1"), "node1:80");
+    assert_eq!(addr("http://[::1]"), "[::1]:80");
+    assert_eq!(addr("http://[::1]:8080"), "[::1]:8080");
+
+    let err = ProbeEndpoint::from_url("https://node1:443").unwrap_err();
+    assert!(err.to_string().contains("ssh -L"), "{err}");
+    assert!(ProbeEndpoint::from_url("http://node1:80/apis").is_err());
+    assert!(ProbeEndpoint::from_url("ftp://node1").is_err());
+}
@@ -0,0 +1,55 @@
+//! `--timeout` / `--retries` against a local server that never answers.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::routing::{get, post};
+use axum::Router;
+use probing_cli::cli::ctrl::{request, set_limits, Limits, ProbeEndpoint};
+
+async fn spawn_stalled_server(hits: Arc<AtomicU32>) -> ProbeEndpoint {
+    let stall = move || {
+        let hits = hits.clone();
+        async move {
+            hits.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            "late"
+        }
+    };
+    let app = Router::new()
+        .route("/apis/overview", get(stall.clone()))
+        .route("/query", post(stall));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    ProbeEndpoint::Remote {
+        addr: addr.to_string(),
+    }
+}
+
+// One test: the limits are process-wide state.
+#[tokio::test]
+async fn stalled_requests_time_out_and_gets_are_retried() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let endpoint = spawn_stalled_server(hits.clone()).await;
+    set_limits(Limits {
+        timeout: Some(Duration::from_millis(200)),
+        retries: 2,
+    });
+
+    let err = request(endpoint.clone(), "/apis/overview", None)
+        .await
+        .unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(msg.contains("did not answer /apis/overview"), "{msg}");
+    assert!(msg.contains("GIL"), "{msg}");
+    assert_eq!(hits.swap(0, Ordering::SeqCst), 3, "one try and two retries");
+
+    // POST bodies are not replayed.
+    let err = request(endpoint, "/query", Some("{}".to_string()))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("did not answer /query"));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
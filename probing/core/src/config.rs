@@ -1,8 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
 
 use once_cell::sync::Lazy;
-use probing_proto::prelude::{Ele, EleExt};
-use tokio::sync::RwLock;
+use probing_proto::prelude::{ConfigChange, Ele, EleExt};
+use tokio::sync::{broadcast, RwLock};
 
 use crate::core::{EngineError, ProbeExtensionManager};
 use crate::ENGINE;
@@ -11,6 +16,92 @@ use crate::ENGINE;
 pub static CONFIG_STORE: Lazy<RwLock<BTreeMap<String, Ele>>> =
     Lazy::new(|| RwLock::new(BTreeMap::new()));
 
+/// Config list description of keys the overhead governor has lowered.
+pub const GOVERNED_HELP: &str = "governed: lowered by the overhead governor \
+     (probing.overhead_budget_pct); the manual value is restored when overhead subsides";
+
+/// Keys lowered by the overhead governor, with the value to restore (`None`:
+/// the key was unset).
+static GOVERNED: Lazy<RwLock<BTreeMap<String, Option<String>>>> =
+    Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Changes buffered per subscriber before it starts missing them.
+const CHANGE_CAPACITY: usize = 256;
+
+static CHANGES: Lazy<broadcast::Sender<ConfigChange>> =
+    Lazy::new(|| broadcast::channel(CHANGE_CAPACITY).0);
+
+tokio::task_local! {
+    static CHANGE_SOURCE: String;
+}
+
+/// Subscribe to changes of keys starting with `prefix` (`probing.` optional,
+/// empty for all): sets, including those that only land in the store, and
+/// removals whose value differs. Dropping the [`Subscription`] unsubscribes.
+pub fn subscribe(prefix: &str) -> Subscription {
+    Subscription {
+        prefix: prefix.to_string(),
+        changes: CHANGES.subscribe(),
+    }
+}
+
+/// Live [`Subscription`]s; changes are only built while there is one.
+pub fn subscriber_count() -> usize {
+    CHANGES.receiver_count()
+}
+
+/// Config changes under one prefix, see [`subscribe`].
+pub struct Subscription {
+    prefix: String,
+    changes: broadcast::Receiver<ConfigChange>,
+}
+
+impl Subscription {
+    /// The next matching change. `Lagged` when this subscriber fell more than
+    /// a buffer of changes behind and missed some; re-read the keys it cares
+    /// about and go on.
+    pub async fn recv(&mut self) -> Result<ConfigChange, broadcast::error::RecvError> {
+        loop {
+            let change = self.changes.recv().await?;
+            if change.matches_prefix(&self.prefix) {
+                return Ok(change);
+            }
+        }
+    }
+
+    /// [`recv`](Self::recv) without waiting: `Empty` when no matching change
+    /// is pending.
+    pub fn try_recv(&mut self) -> Result<ConfigChange, broadcast::error::TryRecvError> {
+        loop {
+            let change = self.changes.try_recv()?;
+            if change.matches_prefix(&self.prefix) {
+                return Ok(change);
+            }
+        }
+    }
+}
+
+/// Run `fut` with `source` recorded on the config changes it makes.
+pub async fn with_change_source<F: Future>(source: String, fut: F) -> F::Output {
+    CHANGE_SOURCE.scope(source, fut).await
+}
+
+fn notify(key: &str, old: Option<String>, new: Option<String>) {
+    if old == new || CHANGES.receiver_count() == 0 {
+        return;
+    }
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let _ = CHANGES.send(ConfigChange {
+        timestamp_ms,
+        key: key.to_string(),
+        old,
+        new,
+        source: CHANGE_SOURCE.try_with(Clone::clone).ok(),
+    });
+}
+
 /// Get a configuration value.
 pub async fn get(key: &str) -> Option<Ele> {
     CONFIG_STORE.read().await.get(key).cloned()
@@ -18,10 +109,14 @@ pub async fn get(key: &str) -> Option<Ele> {
 
 /// Set a configuration value.
 pub async fn set<T: Into<Ele>>(key: &str, value: T) {
-    CONFIG_STORE
-        .write()
-        .await
-        .insert(key.to_string(), value.into());
+    let value = value.into();
+    let new = value.to_string_lossy();
+    let old = insert(key, value).await;
+    notify(key, old.map(|o| o.to_string_lossy()), Some(new));
+}
+
+async fn insert(key: &str, value: Ele) -> Option<Ele> {
+    CONFIG_STORE.write().await.insert(key.to_string(), value)
 }
 
 /// Get a configuration value as string.
@@ -31,7 +126,9 @@ pub async fn get_str(key: &str) -> Option<String> {
 
 /// Remove a configuration value.
 pub async fn remove(key: &str) -> Option<Ele> {
-    CONFIG_STORE.write().await.remove(key)
+    let old = CONFIG_STORE.write().await.remove(key);
+    notify(key, old.as_ref().map(|o| o.to_string_lossy()), None);
+    old
 }
 
 /// Check if a key exists.
@@ -47,6 +144,8 @@ pub async fn keys() -> Vec<String> {
 /// Clear all configuration.
 pub async fn clear() {
     CONFIG_STORE.write().await.clear();
+    GOVERNED.write().await.clear();
+    PERSISTED.write().await.clear();
 }
 
 /// Get the number of configuration entries.
@@ -59,10 +158,31 @@ pub async fn is_empty() -> bool {
     CONFIG_STORE.read().await.is_empty()
 }
 
+/// Mark `key` as governed, remembering `manual` as the value to restore. A
+/// key already governed keeps its first manual value.
+pub async fn govern(key: &str, manual: Option<String>) {
+    GOVERNED
+        .write()
+        .await
+        .entry(key.to_string())
+        .or_insert(manual);
+}
+
+/// Stop governing `key`; the manual value when it was governed.
+pub async fn release(key: &str) -> Option<Option<String>> {
+    GOVERNED.write().await.remove(key)
+}
+
+/// Governed keys and their manual values.
+pub async fn governed() -> BTreeMap<String, Option<String>> {
+    GOVERNED.read().await.clone()
+}
+
 /// Set a configuration option through the engine extension system.
 ///
 /// If the key starts with "probing", it will attempt to update the engine's
 /// extension configuration first. Otherwise, it directly updates the configuration store.
+/// When [`persist_path`] names a file, the change is also merged into it.
 ///
 /// # Examples
 /// ```rust
@@ -72,6 +192,20 @@ pub async fn is_empty() -> bool {
 /// # Ok::<(), probing_core::core::EngineError>(())
 /// ```
 pub async fn write(key: &str, value: &str) -> Result<(), EngineError> {
+    apply(key, value).await?;
+    persist(key, value).await;
+    Ok(())
+}
+
+/// [`write`] without persisting, for values that only hold for this process
+/// such as the bound address, a token or a detach.
+pub async fn write_transient(key: &str, value: &str) -> Result<(), EngineError> {
+    apply(key, value).await.map(|_| ())
+}
+
+/// Set `key` on its extension, else in the store; `true` when an extension
+/// took it.
+async fn apply(key: &str, value: &str) -> Result<bool, EngineError> {
     if key.starts_with("probing") {
         let engine_guard = ENGINE.write().await;
         let mut state = engine_guard.context.state();
@@ -88,12 +222,19 @@ pub async fn write(key: &str, value: &str) -> Result<(), EngineError> {
                 key
             };
 
+            // The extension's current value, for keys never written before.
+            let previous = match get_str(key).await {
+                Some(v) => Some(v),
+                None => eem.get_option(extension_key).await.ok(),
+            };
+
             // Attempt to set the option on an extension.
             match eem.set_option(extension_key, value).await {
                 Ok(_) => {
                     // If successful, also update the global config store.
-                    set(key, value).await;
-                    return Ok(());
+                    insert(key, value.into()).await;
+                    notify(key, previous, Some(value.to_string()));
+                    return Ok(true);
                 }
                 Err(EngineError::UnsupportedOption(_)) => {
                     // If unsupported by any extension, just write to the config store.
@@ -109,7 +250,203 @@ pub async fn write(key: &str, value: &str) -> Result<(), EngineError> {
 
     // For non-"probing" keys or unsupported "probing" keys, write to the store.
     set(key, value).await;
-    Ok(())
+    Ok(false)
+}
+
+/// Config key naming the TOML file [`write`] merges changes into.
+pub const PERSIST_PATH_KEY: &str = "probing.config.persist_path";
+
+/// Environment variable read for [`PERSIST_PATH_KEY`] when it is unset.
+pub const PERSIST_PATH_ENV: &str = "PROBING_CONFIG_PERSIST_PATH";
+
+/// Keys applied from the persisted file at startup.
+static PERSISTED: Lazy<RwLock<BTreeSet<String>>> = Lazy::new(|| RwLock::new(BTreeSet::new()));
+
+/// The file changes are persisted to: [`PERSIST_PATH_KEY`], else
+/// [`PERSIST_PATH_ENV`]. Setting the key to an empty value turns it off.
+pub async fn persist_path() -> Option<PathBuf> {
+    get_str(PERSIST_PATH_KEY)
+        .await
+        .or_else(|| std::env::var(PERSIST_PATH_ENV).ok())
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Whether `key` was applied from the persisted file. `_` and `.` match
+/// each other, as environment settings spell both with `_`.
+pub async fn is_persisted(key: &str) -> bool {
+    let key = key.replace('_', ".");
+    PERSISTED
+        .read()
+        .await
+        .iter()
+        .any(|persisted| persisted.replace('_', ".") == key)
+}
+
+/// Secrets stay off disk: keys whose last segment names a token, password
+/// or secret.
+pub(crate) fn is_secret(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key).to_ascii_lowercase();
+    ["token", "password", "secret"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+/// Merge a successful [`write`] into the persisted file. Governed keys hold
+/// the governor's value, not the operator's, and are skipped; a failure is
+/// logged, the change itself already applied.
+async fn persist(key: &str, value: &str) {
+    if key == PERSIST_PATH_KEY || is_secret(key) || GOVERNED.read().await.contains_key(key) {
+        return;
+    }
+    let Some(path) = persist_path().await else {
+        return;
+    };
+    let (file, entry, value) = (path.clone(), key.to_string(), value.to_string());
+    if let Err(err) = blocking(move || persist_to(&file, &entry, &value)).await {
+        log::warn!("failed to persist {key} to {}: {err}", path.display());
+    }
+}
+
+/// Load the persisted file, if [`persist_path`] names one, and apply its
+/// keys. Runs once the engine is built: environment settings synced later
+/// skip the keys it set (see [`is_persisted`]) and runtime changes override
+/// them. Keys that fail to apply, e.g. because their extension is not
+/// present, are logged and kept in the store and the file.
+pub async fn load_persisted() {
+    let Some(path) = persist_path().await else {
+        return;
+    };
+    if !contains_key(PERSIST_PATH_KEY).await {
+        set(PERSIST_PATH_KEY, path.display().to_string()).await;
+    }
+    let file = path.clone();
+    match blocking(move || read_persisted(&file)).await {
+        Ok(entries) => {
+            log::info!(
+                "applying {} persisted config keys from {}",
+                entries.len(),
+                path.display()
+            );
+            apply_persisted(entries).await;
+        }
+        Err(err) => log::error!("failed to load persisted config {}: {err}", path.display()),
+    }
+}
+
+async fn apply_persisted(entries: BTreeMap<String, String>) {
+    for (key, value) in entries {
+        if key == PERSIST_PATH_KEY {
+            continue;
+        }
+        match apply(&key, &value).await {
+            Ok(true) => {}
+            Ok(false) if key.starts_with("probing.") => {
+                log::info!("persisted {key} kept in the store: no extension handles it");
+            }
+            Ok(false) => {}
+            Err(err) => {
+                log::warn!("persisted {key} = {value} kept but not applied: {err}");
+                set(&key, value).await;
+            }
+        }
+        PERSISTED.write().await.insert(key);
+    }
+}
+
+/// Run blocking file work off the async workers.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> io::Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|err| Err(io::Error::other(err)))
+}
+
+/// The keys of the TOML file at `path`, empty when it does not exist.
+/// Values are strings as written by [`persist_to`]; hand-written numbers and
+/// booleans, and nested tables as dotted keys, are read too.
+pub fn read_persisted(path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err),
+    };
+    let table: toml::Table = text
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut entries = BTreeMap::new();
+    flatten("", table, &mut entries);
+    Ok(entries)
+}
+
+fn flatten(prefix: &str, table: toml::Table, entries: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, entries),
+            toml::Value::String(value) => {
+                entries.insert(key, value);
+            }
+            value => {
+                entries.insert(key, value.to_string());
+            }
+        }
+    }
+}
+
+/// Merge `key = value` into the TOML file at `path`.
+///
+/// Writers, in this process or others sharing the file, take turns on an
+/// exclusive `flock` of `<path>.lock`; each writes a temporary file and
+/// renames it over `path`, so readers never see a partial file. A file that
+/// does not parse is left alone and reported.
+pub fn persist_to(path: &Path, key: &str, value: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _lock = lock(&sibling(path, ".lock"))?;
+    let mut entries = read_persisted(path)?;
+    entries.insert(key.to_string(), value.to_string());
+    let text =
+        toml::to_string(&entries).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let tmp = sibling(path, &format!(".{}.tmp", std::process::id()));
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Hold an exclusive `flock` on `path` until the returned file is dropped.
+/// The lock belongs to the open file, so threads of one process exclude
+/// each other too.
+fn lock(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    loop {
+        // SAFETY: `file` owns the descriptor for the duration of the call.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(file);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -169,6 +506,7 @@ mod tests {
                 key: "option".to_string(),
                 value: Some(self.test_option.clone()),
                 help: "Test option",
+                ..Default::default()
             }]
         }
     }
@@ -245,6 +583,79 @@ mod tests {
         teardown_test().await;
     }
 
+    /// Held by tests that count subscribers.
+    static SUBSCRIBERS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    #[tokio::test]
+    async fn test_changes_carry_old_value_and_source() {
+        let _subscribers = SUBSCRIBERS.lock().await;
+        let mut changes = subscribe("watch.demo");
+
+        set("watch.demo", "1").await;
+        with_change_source("req:abc".to_string(), set("watch.demo", "2")).await;
+        // Writing the same value again is not a change.
+        set("watch.demo", "2").await;
+        remove("watch.demo").await;
+
+        let mut seen = Vec::new();
+        while let Ok(change) = changes.try_recv() {
+            if change.key == "watch.demo" {
+                seen.push((change.old, change.new, change.source));
+            }
+        }
+        assert_eq!(
+            seen,
+            [
+                (None, Some("1".to_string()), None),
+                (
+                    Some("1".to_string()),
+                    Some("2".to_string()),
+                    Some("req:abc".to_string())
+                ),
+                (Some("2".to_string()), None, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_see_their_prefix_until_dropped() {
+        let _subscribers = SUBSCRIBERS.lock().await;
+        let before = subscriber_count();
+        let mut server = subscribe("probing.notify_test.server");
+        let mut also_server = subscribe("notify_test.server.");
+        let mut all = subscribe("notify_test.");
+        assert_eq!(subscriber_count(), before + 3);
+
+        set("notify_test.server.auth_token", "t1").await;
+        set("notify_test.cpu.interval", "10").await;
+
+        for changes in [&mut server, &mut also_server] {
+            let change = changes.try_recv().unwrap();
+            assert_eq!(change.key, "notify_test.server.auth_token");
+            assert_eq!(change.old, None);
+            assert_eq!(change.new.as_deref(), Some("t1"));
+            assert!(changes.try_recv().is_err());
+        }
+        let keys: Vec<_> = std::iter::from_fn(|| all.try_recv().ok())
+            .map(|change| change.key)
+            .collect();
+        assert_eq!(
+            keys,
+            ["notify_test.server.auth_token", "notify_test.cpu.interval"]
+        );
+
+        drop(server);
+        drop(also_server);
+        assert_eq!(subscriber_count(), before + 1);
+        set("notify_test.server.auth_token", "t2").await;
+        assert_eq!(all.recv().await.unwrap().old.as_deref(), Some("t1"));
+        drop(all);
+        assert_eq!(subscriber_count(), before);
+
+        remove("notify_test.server.auth_token").await;
+        remove("notify_test.cpu.interval").await;
+    }
+
     #[tokio::test]
     async fn test_config_set_engine_not_initialized() {
         setup_test().await;
@@ -253,4 +664,88 @@ mod tests {
 
         teardown_test().await;
     }
+
+    #[tokio::test]
+    async fn persisted_values_sit_between_env_and_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("probing.toml");
+        std::fs::write(
+            &path,
+            "\"probing.persist_order.level\" = \"file\"\n\
+             [probing.persist_absent]\nknob = 3\n",
+        )
+        .unwrap();
+
+        apply_persisted(read_persisted(&path).unwrap()).await;
+        assert_eq!(
+            get_str("probing.persist_order.level").await.as_deref(),
+            Some("file")
+        );
+        // No extension handles it: kept in the store and in the file.
+        assert_eq!(
+            get_str("probing.persist_absent.knob").await.as_deref(),
+            Some("3")
+        );
+
+        // The env sync skips keys the file set, spelled as env names map them.
+        assert!(is_persisted("probing.persist.order.level").await);
+        assert!(!is_persisted("probing.persist_order.other").await);
+
+        // A runtime change overrides the file and is merged into it.
+        write_transient("probing.persist_order.level", "runtime")
+            .await
+            .unwrap();
+        persist_to(&path, "probing.persist_order.level", "runtime").unwrap();
+        assert_eq!(
+            get_str("probing.persist_order.level").await.as_deref(),
+            Some("runtime")
+        );
+        let entries = read_persisted(&path).unwrap();
+        assert_eq!(entries["probing.persist_order.level"], "runtime");
+        assert_eq!(entries["probing.persist_absent.knob"], "3");
+    }
+
+    #[test]
+    fn concurrent_writers_do_not_corrupt_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("probing.toml");
+        let writers: Vec<_> = (0..8)
+            .map(|w| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        persist_to(&path, &format!("probing.w{w}.k{i}"), &format!("{w}-{i}"))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let entries = read_persisted(&path).unwrap();
+        assert_eq!(entries.len(), 200);
+        assert_eq!(entries["probing.w3.k7"], "3-7");
+        let mut names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["probing.toml", "probing.toml.lock"]);
+    }
+
+    #[test]
+    fn unparsable_files_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("probing.toml");
+        std::fs::write(&path, "level = [unterminated").unwrap();
+        assert!(persist_to(&path, "probing.x.y", "1").is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "level = [unterminated"
+        );
+        assert!(is_secret("probing.server.auth_token"));
+        assert!(!is_secret("probing.pprof.sample_freq"));
+    }
 }
@@ -1,58 +1,16 @@
 //! Arrow array to Seq conversion utilities
 //!
-//! This module provides unified conversion functions from Arrow arrays to Seq,
-//! replacing hardcoded type conversion logic throughout the codebase.
+//! Thin wrapper over [`probing_proto::types::arrow_convert`], which also
+//! converts whole data frames and reads and writes Arrow IPC streams.
 
 use arrow::array::ArrayRef;
-use arrow::array::*;
-use arrow::datatypes::DataType;
 use probing_proto::prelude::Seq;
+use probing_proto::types::arrow_convert;
 
 /// Convert Arrow ArrayRef to Seq
 ///
-/// This function provides a unified way to convert Arrow arrays to Seq,
-/// replacing hardcoded type conversion logic throughout the codebase.
+/// Timestamps become `SeqI64` in their own unit; types without a matching
+/// [`Seq`] become `Seq::Nil`.
 pub fn arrow_array_to_seq(array: &ArrayRef) -> Seq {
-    if let Some(arr) = array.as_any().downcast_ref::<Int32Array>() {
-        Seq::SeqI32(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<Int64Array>() {
-        Seq::SeqI64(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<Float32Array>() {
-        Seq::SeqF32(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<Float64Array>() {
-        Seq::SeqF64(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<StringArray>() {
-        Seq::SeqText((0..array.len()).map(|i| arr.value(i).to_string()).collect())
-    } else if let Some(arr) = array.as_any().downcast_ref::<BooleanArray>() {
-        Seq::SeqBOOL((0..array.len()).map(|i| arr.value(i)).collect())
-    } else if let Some(arr) = array.as_any().downcast_ref::<TimestampMicrosecondArray>() {
-        // Convert timestamp to i64 (microseconds)
-        Seq::SeqI64(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<TimestampNanosecondArray>() {
-        // Convert nanosecond timestamp to i64 (nanoseconds)
-        Seq::SeqI64(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<TimestampMillisecondArray>() {
-        // Convert millisecond timestamp to i64 (milliseconds)
-        Seq::SeqI64(arr.values().to_vec())
-    } else if let Some(arr) = array.as_any().downcast_ref::<TimestampSecondArray>() {
-        // Convert second timestamp to i64 (seconds)
-        Seq::SeqI64(arr.values().to_vec())
-    } else {
-        // Fallback: return Nil for unsupported types
-        Seq::Nil
-    }
-}
-
-/// Empty column matching an Arrow type (for zero-row query results).
-pub fn empty_seq_for_data_type(data_type: &DataType) -> Seq {
-    match data_type {
-        DataType::Int32 => Seq::SeqI32(vec![]),
-        DataType::Int64 => Seq::SeqI64(vec![]),
-        DataType::Float32 => Seq::SeqF32(vec![]),
-        DataType::Float64 => Seq::SeqF64(vec![]),
-        DataType::Utf8 | DataType::LargeUtf8 => Seq::SeqText(vec![]),
-        DataType::Boolean => Seq::SeqBOOL(vec![]),
-        DataType::Timestamp(_, _) => Seq::SeqI64(vec![]),
-        _ => Seq::Nil,
-    }
+    arrow_convert::array_to_seq(array.as_ref())
 }
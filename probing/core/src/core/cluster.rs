@@ -63,12 +63,16 @@ fn now_micros() -> u64 {
         .unwrap_or(0)
 }
 
-fn stale_threshold_micros() -> u64 {
+/// Heartbeat age after which a node is marked ``dead`` (`PROBING_CLUSTER_STALE_SEC`).
+pub fn stale_threshold_secs() -> u64 {
     std::env::var("PROBING_CLUSTER_STALE_SEC")
         .ok()
         .and_then(|v| v.trim().parse().ok())
         .unwrap_or(25)
-        * 1_000_000
+}
+
+fn stale_threshold_micros() -> u64 {
+    stale_threshold_secs() * 1_000_000
 }
 
 /// Override local listen address(es) at runtime (e.g. when the HTTP server binds).
@@ -0,0 +1,380 @@
+//! `probing.config` — the configuration as a table.
+//!
+//! One row per option of each registered extension, then the entries of the
+//! config store that no option covers (values set by `SET` that only Python
+//! or the server read, e.g. `probing.overhead_budget_pct`). Keys are listed
+//! without the `probing.` prefix, and values are read when the table is
+//! scanned:
+//!
+//! ```sql
+//! SELECT key, value, help FROM probing.config WHERE key LIKE 'torch.%'
+//! ```
+//!
+//! Extension options also list what their values parse into
+//! (`option_type`, `choices` of an enum) and their numeric bounds
+//! (`min`, `max`), so clients can offer a matching input.
+//!
+//! Secret values (tokens, passwords) are shown as `<redacted>`.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{BooleanBuilder, Float64Builder, RecordBatch, StringBuilder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::catalog::{SchemaProvider, Session};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::Result;
+use datafusion::execution::SessionState;
+use datafusion::logical_expr::TableProviderFilterPushDown;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use probing_proto::prelude::EleExt;
+
+use super::data_source::{ProbeDataSource, ProbeDataSourceKind};
+use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use super::probe_extension::PROBE_EXTENSIONS;
+use super::semantic_catalog::DOCS_SCHEMA;
+use crate::config;
+
+pub const CONFIG_SCHEMA: &str = DOCS_SCHEMA;
+pub const CONFIG_TABLE: &str = "config";
+
+const REDACTED: &str = "<redacted>";
+
+/// One row of `probing.config`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigRow {
+    /// Option key without the `probing.` prefix, e.g. `torch.profiling`.
+    pub key: String,
+    pub value: Option<String>,
+    pub help: String,
+    /// Extension owning the option; `None` for plain store entries.
+    pub extension: Option<String>,
+    /// Whether an extension applies `SET`s of the key; plain store entries
+    /// are only recorded for whoever reads them.
+    pub writable: bool,
+    /// [`OptionType::name`](super::OptionType::name); `None` for plain store
+    /// entries.
+    pub option_type: Option<String>,
+    /// Accepted names of an enum option, `|`-separated.
+    pub choices: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+fn config_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, true),
+        Field::new("help", DataType::Utf8, false),
+        Field::new("extension", DataType::Utf8, true),
+        Field::new("writable", DataType::Boolean, false),
+        Field::new("option_type", DataType::Utf8, true),
+        Field::new("choices", DataType::Utf8, true),
+        Field::new("min", DataType::Float64, true),
+        Field::new("max", DataType::Float64, true),
+    ]))
+}
+
+/// The rows of `probing.config` as of now, extension options first.
+pub async fn config_rows() -> Vec<ConfigRow> {
+    let governed = config::governed().await;
+    let help_of = |key: &str, help: &str| {
+        if governed.contains_key(&format!("probing.{key}")) || governed.contains_key(key) {
+            config::GOVERNED_HELP.to_string()
+        } else {
+            help.to_string()
+        }
+    };
+    let shown = |key: &str, value: Option<String>| {
+        value.map(|value| {
+            if config::is_secret(key) && !value.is_empty() {
+                REDACTED.to_string()
+            } else {
+                value
+            }
+        })
+    };
+
+    let extensions: Vec<_> = {
+        let extensions = PROBE_EXTENSIONS.read().await;
+        extensions
+            .iter()
+            .map(|(name, ext)| (name.clone(), ext.clone()))
+            .collect()
+    };
+    let mut rows = Vec::new();
+    let mut seen = BTreeSet::new();
+    for (name, extension) in extensions {
+        let options = extension.lock().await.options();
+        for option in options {
+            if !seen.insert(option.key.clone()) {
+                continue;
+            }
+            rows.push(ConfigRow {
+                help: help_of(&option.key, option.help),
+                value: shown(&option.key, option.value),
+                key: option.key,
+                extension: Some(name.clone()),
+                writable: true,
+                option_type: Some(option.option_type.name().to_string()),
+                choices: option.option_type.choices().map(|names| names.join("|")),
+                min: option.min,
+                max: option.max,
+            });
+        }
+    }
+
+    let store: Vec<_> = config::CONFIG_STORE
+        .read()
+        .await
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_string_lossy()))
+        .collect();
+    for (key, value) in store {
+        let key = key.strip_prefix("probing.").unwrap_or(&key).to_string();
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        rows.push(ConfigRow {
+            help: help_of(&key, ""),
+            value: shown(&key, Some(value)),
+            key,
+            extension: None,
+            writable: false,
+            option_type: None,
+            choices: None,
+            min: None,
+            max: None,
+        });
+    }
+    rows
+}
+
+fn config_batch(rows: &[ConfigRow]) -> Result<RecordBatch> {
+    let mut key = StringBuilder::new();
+    let mut value = StringBuilder::new();
+    let mut help = StringBuilder::new();
+    let mut extension = StringBuilder::new();
+    let mut writable = BooleanBuilder::new();
+    let mut option_type = StringBuilder::new();
+    let mut choices = StringBuilder::new();
+    let mut min = Float64Builder::new();
+    let mut max = Float64Builder::new();
+    for row in rows {
+        key.append_value(&row.key);
+        value.append_option(row.value.as_deref());
+        help.append_value(&row.help);
+        extension.append_option(row.extension.as_deref());
+        writable.append_value(row.writable);
+        option_type.append_option(row.option_type.as_deref());
+        choices.append_option(row.choices.as_deref());
+        min.append_option(row.min);
+        max.append_option(row.max);
+    }
+    Ok(RecordBatch::try_new(
+        config_schema(),
+        vec![
+            Arc::new(key.finish()),
+            Arc::new(value.finish()),
+            Arc::new(help.finish()),
+            Arc::new(extension.finish()),
+            Arc::new(writable.finish()),
+            Arc::new(option_type.finish()),
+            Arc::new(choices.finish()),
+            Arc::new(min.finish()),
+            Arc::new(max.finish()),
+        ],
+    )?)
+}
+
+#[derive(Debug)]
+struct ConfigTable {
+    schema: SchemaRef,
+}
+
+#[async_trait]
+impl TableProvider for ConfigTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        supports_filters_pushdown_for_schema(&self.schema, filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let batch = config_batch(&config_rows().await)?;
+        scan_memory_partitions(
+            state,
+            Arc::clone(&self.schema),
+            &[vec![batch]],
+            projection,
+            filters,
+            limit,
+        )
+        .await
+    }
+}
+
+/// Registers `probing.config`; built into every engine.
+#[derive(Debug, Default)]
+pub struct ConfigProbeDataSource;
+
+impl ProbeDataSource for ConfigProbeDataSource {
+    fn name(&self) -> String {
+        CONFIG_TABLE.to_string()
+    }
+
+    fn kind(&self) -> ProbeDataSourceKind {
+        ProbeDataSourceKind::Table
+    }
+
+    fn namespace(&self) -> String {
+        CONFIG_SCHEMA.to_string()
+    }
+
+    fn register_table(&self, schema: Arc<dyn SchemaProvider>, _state: &SessionState) -> Result<()> {
+        schema.register_table(
+            CONFIG_TABLE.to_string(),
+            Arc::new(ConfigTable {
+                schema: config_schema(),
+            }),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        Engine, EngineError, OptionType, ProbeExtension, ProbeExtensionCall, ProbeExtensionManager,
+        ProbeExtensionOption,
+    };
+    use probing_proto::prelude::Ele;
+
+    #[derive(Debug)]
+    struct TestExtension {
+        level: String,
+    }
+
+    impl ProbeExtensionCall for TestExtension {}
+
+    impl ProbeExtension for TestExtension {
+        fn name(&self) -> String {
+            "config_table_test".to_string()
+        }
+
+        fn set(&mut self, key: &str, value: &str) -> Result<String, EngineError> {
+            match key {
+                "level" => Ok(std::mem::replace(&mut self.level, value.into())),
+                _ => Err(EngineError::UnsupportedOption(key.to_string())),
+            }
+        }
+
+        fn get(&self, key: &str) -> Result<String, EngineError> {
+            match key {
+                "level" => Ok(self.level.clone()),
+                _ => Err(EngineError::UnsupportedOption(key.to_string())),
+            }
+        }
+
+        fn options(&self) -> Vec<ProbeExtensionOption> {
+            vec![ProbeExtensionOption {
+                key: "config_table_test.level".to_string(),
+                value: Some(self.level.clone()),
+                help: "Test level",
+                option_type: OptionType::Enum(&["info", "debug"]),
+                min: None,
+                max: None,
+            }]
+        }
+    }
+
+    async fn rows(engine: &Engine, sql: &str) -> Vec<Vec<Ele>> {
+        let df = engine.async_query(sql).await.unwrap().unwrap();
+        (0..df.len())
+            .map(|i| df.cols.iter().map(|col| col.get(i)).collect())
+            .collect()
+    }
+
+    fn text(s: &str) -> Ele {
+        Ele::Text(s.to_string())
+    }
+
+    #[tokio::test]
+    async fn lists_live_option_values_and_store_entries() {
+        let engine = Engine::builder()
+            .with_extension(TestExtension {
+                level: "info".into(),
+            })
+            .build()
+            .await
+            .unwrap();
+        config::set("probing.config_table_test.extra", "7").await;
+        config::set("config_table_test.token", "s3cret").await;
+
+        let sql = "SELECT key, value, help, coalesce(extension, '-'), writable \
+                   FROM probing.config WHERE key LIKE 'config_table_test.%' ORDER BY key";
+        assert_eq!(
+            rows(&engine, sql).await,
+            [
+                vec![
+                    text("config_table_test.extra"),
+                    text("7"),
+                    text(""),
+                    text("-"),
+                    Ele::BOOL(false)
+                ],
+                vec![
+                    text("config_table_test.level"),
+                    text("info"),
+                    text("Test level"),
+                    text("config_table_test"),
+                    Ele::BOOL(true)
+                ],
+                vec![
+                    text("config_table_test.token"),
+                    text(REDACTED),
+                    text(""),
+                    text("-"),
+                    Ele::BOOL(false)
+                ],
+            ]
+        );
+
+        // Changed after the engine was built: the next scan sees it.
+        ProbeExtensionManager
+            .set_option("config_table_test.level", "debug")
+            .await
+            .unwrap();
+        let sql = "SELECT value FROM probing.config WHERE key = 'config_table_test.level'";
+        assert_eq!(rows(&engine, sql).await, [vec![text("debug")]]);
+
+        let sql = "SELECT option_type, choices, coalesce(min, -1) FROM probing.config \
+                   WHERE key LIKE 'config_table_test.%' AND option_type IS NOT NULL";
+        assert_eq!(
+            rows(&engine, sql).await,
+            [vec![text("enum"), text("info|debug"), Ele::F64(-1.0)]]
+        );
+
+        config::remove("probing.config_table_test.extra").await;
+        config::remove("config_table_test.token").await;
+        PROBE_EXTENSIONS.write().await.remove("config_table_test");
+    }
+}
@@ -63,6 +63,7 @@ use datafusion::physical_plan::ExecutionPlan;
 use datafusion::prelude::Expr;
 
 use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use super::scan_stats::{self, ScanMetrics, ScanMetricsExec};
 
 /// Trait defining a custom table with static/dynamic schema and data
 ///
@@ -153,16 +154,36 @@ impl<T: CustomTable + Default + Debug + Send + Sync + 'static> ProbeDataSource
         schema: std::sync::Arc<dyn datafusion::catalog::SchemaProvider>,
         _state: &datafusion::execution::SessionState,
     ) -> datafusion::error::Result<()> {
-        schema.register_table(self.name(), Arc::new(TableDataSource::<T>::default()))?;
+        let label = format!("{}.{}", self.namespace, self.name);
+        schema.register_table(self.name(), Arc::new(TableDataSource::<T>::new(label)))?;
         Ok(())
     }
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct TableDataSource<T: CustomTable> {
+    /// `namespace.table` under which scans are reported in `probe.scan_stats`.
+    label: String,
     data: PhantomData<T>,
 }
 
+impl<T: CustomTable> TableDataSource<T> {
+    pub fn new<S: Into<String>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            data: PhantomData,
+        }
+    }
+
+    fn label(&self) -> &str {
+        if self.label.is_empty() {
+            T::name()
+        } else {
+            &self.label
+        }
+    }
+}
+
 #[async_trait]
 impl<T: CustomTable + Default + Debug + Send + Sync + 'static> TableProvider
     for TableDataSource<T>
@@ -198,9 +219,12 @@ impl<T: CustomTable + Default + Debug + Send + Sync + 'static> TableProvider
         filters: &[Expr],
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let batches = T::data();
+        let (batches, metrics) = scan_stats::measure(self.label(), T::data);
         let partitions = vec![batches];
-        scan_memory_partitions(state, T::schema(), &partitions, projection, filters, limit).await
+        let plan =
+            scan_memory_partitions(state, T::schema(), &partitions, projection, filters, limit)
+                .await?;
+        Ok(Arc::new(ScanMetricsExec::new(plan, self.label(), &metrics)))
     }
 }
 
@@ -213,6 +237,8 @@ pub struct LazyTableSource {
     pub name: String,
     pub schema: Option<SchemaRef>,
     pub data: Vec<RecordBatch>,
+    /// Cost of producing [`Self::data`], reported by `EXPLAIN ANALYZE`.
+    pub metrics: Option<ScanMetrics>,
 }
 
 #[async_trait]
@@ -266,7 +292,12 @@ impl TableProvider for LazyTableSource {
         }
         let schema = data[0].schema();
         let partitions = vec![self.data.clone()];
-        scan_memory_partitions(state, schema, &partitions, projection, filters, limit).await
+        let plan =
+            scan_memory_partitions(state, schema, &partitions, projection, filters, limit).await?;
+        Ok(match &self.metrics {
+            Some(metrics) => Arc::new(ScanMetricsExec::new(plan, &self.name, metrics)),
+            None => plan,
+        })
     }
 }
 
@@ -292,12 +323,14 @@ pub trait CustomNamespace: Sync + Send {
     where
         Self: Sized,
     {
-        let data = Self::data(expr);
+        let label = format!("{}.{expr}", Self::name());
+        let (data, metrics) = scan_stats::measure(&label, || Self::data(expr));
         let schema = data.first().map(|batch| batch.schema());
         Arc::new(LazyTableSource {
-            name: expr.to_string(),
+            name: label,
             schema,
             data,
+            metrics: Some(metrics),
         })
     }
 
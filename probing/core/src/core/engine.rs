@@ -2,23 +2,33 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use arrow::compute::concat_batches;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
 use datafusion::catalog::MemoryCatalogProvider;
 use datafusion::catalog::MemorySchemaProvider;
 use datafusion::config::ConfigExtension;
+use datafusion::datasource::{MemTable, TableProvider};
 use datafusion::error::DataFusionError;
 use datafusion::error::Result;
 use datafusion::execution::SessionState;
 use datafusion::prelude::{DataFrame, SessionConfig, SessionContext};
+use futures::StreamExt;
 
-use super::arrow_convert::{arrow_array_to_seq, empty_seq_for_data_type};
 use super::probe_extension::ProbeExtension;
 use super::probe_extension::ProbeExtensionManager;
 
+use super::config_table;
 use super::data_source::{ProbeDataSource, ProbeDataSourceKind};
+use super::event_attrs;
 use super::federation;
 use super::metadata_rewrite;
+use super::probe_events;
+use super::query_cancel;
+use super::scan_stats;
 use super::semantic_catalog;
+use super::span_stats;
+use super::table_resolution;
+use super::trace_spans::{with_span_rows, TRACE_EVENT_TABLE};
 
 /// Core query engine for the Probing system
 ///
@@ -111,52 +121,142 @@ impl Engine {
         self.context.sql(query).await
     }
 
+    /// Run `query` and materialize its result.
+    ///
+    /// The query stops with [`EngineError::QueryTimeout`](super::EngineError::QueryTimeout)
+    /// once it runs past `probing.query.timeout`, see
+    /// [`query_cancel::with_timeout`].
     pub async fn async_query<T: Into<String>>(
         &self,
         query: T,
     ) -> Result<Option<probing_proto::prelude::DataFrame>> {
-        let original: String = query.into();
+        let df = match self.timed_query(query.into()).await? {
+            QueryOutput::Frame(df) => df,
+            QueryOutput::Batches(schema, batches) => {
+                probing_proto::prelude::DataFrame::from_record_batches(&schema, &batches)
+            }
+        };
+        Ok(Some(df))
+    }
+
+    /// [`Self::async_query`] keeping the result as Arrow record batches, for
+    /// clients that take an Arrow IPC stream.
+    pub async fn async_query_batches<T: Into<String>>(
+        &self,
+        query: T,
+    ) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+        match self.timed_query(query.into()).await? {
+            QueryOutput::Frame(df) => Ok((df.arrow_schema(), vec![df.to_record_batch()?])),
+            QueryOutput::Batches(schema, batches) => Ok((schema, batches)),
+        }
+    }
+
+    async fn timed_query(&self, original: String) -> Result<QueryOutput> {
+        query_cancel::with_timeout(query_cancel::timeout(), self.run_query(original)).await
+    }
+
+    async fn run_query(&self, original: String) -> Result<QueryOutput> {
         let capped = federation::ensure_global_scan_limit(&original);
         if let Some(df) = federation::try_execute_aggregate_pushdown(self, &capped).await? {
             federation::check_fanout_strict()?;
-            return Ok(Some(df));
+            return Ok(QueryOutput::Frame(df));
         }
         let default_schema = self.default_namespace();
+        let resolution = table_resolution::resolve_tables(&self.context, &capped)?;
+        let capped = resolution.sql.clone().unwrap_or(capped);
         let query: String =
             metadata_rewrite::prepare_metadata_query(&capped, &default_schema).unwrap_or(capped);
         let query: String = federation::prepare_global_query(&query);
-        let df = self.sql(query.as_str()).await?;
-        let schema = df.schema().clone();
-        let batches = df.collect().await?;
+        let df = self
+            .sql(query.as_str())
+            .await
+            .map_err(|e| resolution.explain(&self.context, e))?;
+        // Lazy tables are scanned while planning; one that gave up past the
+        // deadline only left an error batch behind.
+        query_cancel::check()?;
+        let schema = df.schema().inner().clone();
+        let batches = collect_cancellable(df).await?;
         federation::check_fanout_strict()?;
-        if batches.is_empty() {
-            let names = schema
-                .fields()
-                .iter()
-                .map(|f| f.name().clone())
-                .collect::<Vec<_>>();
-            let columns = schema
-                .fields()
-                .iter()
-                .map(|f| empty_seq_for_data_type(f.data_type()))
-                .collect::<Vec<_>>();
-            return Ok(Some(probing_proto::prelude::DataFrame::new(names, columns)));
+        let Some(first) = batches.first() else {
+            return Ok(QueryOutput::Batches(schema, batches));
+        };
+        let schema = first.schema();
+        let rows = batches.iter().map(RecordBatch::num_rows).sum();
+        federation::cap_materialized_rows(&original, rows)?;
+        Ok(QueryOutput::Batches(schema, batches))
+    }
+
+    /// Register in-memory table snapshots as `<catalog>.<schema>.<table>`.
+    ///
+    /// `tables` are `("schema.table", dataframe)` pairs. A table of the same
+    /// name is replaced, so re-importing an archive is idempotent. The live
+    /// `probe` catalog and the federated `global` catalog cannot be targeted.
+    pub fn register_snapshot(
+        &self,
+        catalog: &str,
+        tables: Vec<(String, probing_proto::prelude::DataFrame)>,
+    ) -> Result<Vec<String>> {
+        if catalog == "probe" || catalog == federation::GLOBAL_CATALOG {
+            return Err(DataFusionError::Plan(format!(
+                "catalog `{catalog}` is reserved"
+            )));
         }
-        let batch = concat_batches(&batches[0].schema(), batches.iter())?;
-        federation::cap_materialized_rows(&original, batch.num_rows())?;
+        self.register_tables(catalog, tables, false)
+    }
 
-        let names = batch
-            .schema()
-            .fields()
-            .iter()
-            .map(|x| x.name().clone())
-            .collect::<Vec<_>>();
-        let columns = batch
-            .columns()
-            .iter()
-            .map(arrow_array_to_seq)
-            .collect::<Vec<_>>();
-        Ok(Some(probing_proto::prelude::DataFrame::new(names, columns)))
+    /// Register archived tables in place of live ones, under the `probe`
+    /// catalog, for an engine that serves a snapshot instead of a process
+    /// (`probing serve-snapshot`). Unlike [`Self::register_snapshot`],
+    /// `python.trace_event` also gets its paired `span` rows, so span views
+    /// behave as they do live.
+    pub fn register_live_snapshot(
+        &self,
+        tables: Vec<(String, probing_proto::prelude::DataFrame)>,
+    ) -> Result<Vec<String>> {
+        self.register_tables("probe", tables, true)
+    }
+
+    fn register_tables(
+        &self,
+        catalog: &str,
+        tables: Vec<(String, probing_proto::prelude::DataFrame)>,
+        span_rows: bool,
+    ) -> Result<Vec<String>> {
+        let provider = match self.context.catalog(catalog) {
+            Some(provider) => provider,
+            None => {
+                self.context
+                    .register_catalog(catalog, Arc::new(MemoryCatalogProvider::new()));
+                self.context
+                    .catalog(catalog)
+                    .ok_or_else(|| DataFusionError::Internal(format!("no catalog `{catalog}`")))?
+            }
+        };
+
+        let mut registered = Vec::with_capacity(tables.len());
+        for (qualified, df) in tables {
+            let Some((schema_name, table_name)) = qualified.split_once('.') else {
+                return Err(DataFusionError::Plan(format!(
+                    "snapshot table `{qualified}` must be `schema.table`"
+                )));
+            };
+            if provider.schema(schema_name).is_none() {
+                provider.register_schema(schema_name, Arc::new(MemorySchemaProvider::new()))?;
+            }
+            let schema = provider.schema(schema_name).ok_or_else(|| {
+                DataFusionError::Internal(format!("namespace `{schema_name}` not found"))
+            })?;
+            let batch = federation::proto_dataframe_to_record_batch(&df)?;
+            let mut table: Arc<dyn TableProvider> =
+                Arc::new(MemTable::try_new(batch.schema(), vec![vec![batch]])?);
+            if span_rows && qualified == format!("python.{TRACE_EVENT_TABLE}") {
+                table = with_span_rows(table);
+            }
+            schema.deregister_table(table_name)?;
+            schema.register_table(table_name.to_string(), table)?;
+            registered.push(format!("{catalog}.{qualified}"));
+        }
+        Ok(registered)
     }
 
     /// Get default namespace from configuration
@@ -212,6 +312,36 @@ impl Engine {
     }
 }
 
+/// Result of [`Engine::run_query`]: aggregate pushdown answers with a merged
+/// frame, everything else with DataFusion's record batches.
+enum QueryOutput {
+    Frame(probing_proto::prelude::DataFrame),
+    Batches(SchemaRef, Vec<RecordBatch>),
+}
+
+/// Collect `df`, stopping between record batches once the current query is
+/// cancelled or times out (see [`query_cancel::scope`]).
+async fn collect_cancellable(df: DataFrame) -> Result<Vec<RecordBatch>> {
+    if query_cancel::current().is_none() {
+        return df.collect().await;
+    }
+    let stopped = query_cancel::stopped();
+    tokio::pin!(stopped);
+    let mut stream = df.execute_stream().await?;
+    let mut batches = Vec::new();
+    loop {
+        let next = tokio::select! {
+            biased;
+            err = &mut stopped => return Err(err.into()),
+            next = stream.next() => next,
+        };
+        match next {
+            Some(batch) => batches.push(batch?),
+            None => return Ok(batches),
+        }
+    }
+}
+
 // Define the EngineBuilder struct
 pub struct EngineBuilder {
     config: SessionConfig,
@@ -280,7 +410,14 @@ impl EngineBuilder {
         for data_source in self.data_sources {
             engine.enable(data_source).await?;
         }
+        engine
+            .enable(Arc::new(config_table::ConfigProbeDataSource))
+            .await?;
         semantic_catalog::install_semantic_catalog(&engine.context)?;
+        event_attrs::install_event_attrs(&engine.context);
+        scan_stats::install_scan_stats(&engine.context)?;
+        span_stats::install_span_stats(&engine.context)?;
+        probe_events::install_probe_events(&engine.context)?;
         federation::install_global_catalog(&engine.context)?;
 
         Ok(engine)
@@ -295,7 +432,9 @@ impl Default for EngineBuilder {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::{ProbeExtension, ProbeExtensionCall};
+    use crate::core::{
+        CustomNamespace, EngineError, NamespaceProbeDataSource, ProbeExtension, ProbeExtensionCall,
+    };
 
     use super::*;
     use arrow::array::{Int32Array, StringArray};
@@ -448,6 +587,61 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_snapshot_round_trip_through_trace_archive() -> Result<()> {
+        use probing_proto::prelude::{ArchivedTable, TraceArchive};
+
+        let source = Engine::builder().build().await?;
+        source
+            .enable(Arc::new(TestTableProbeDataSource::default()))
+            .await?;
+        let queries = [
+            "SELECT * FROM {t} ORDER BY id",
+            "SELECT name, id * 2 AS twice FROM {t} WHERE id > 1 ORDER BY id",
+            "SELECT count(*) AS n, sum(id) AS total FROM {t}",
+        ];
+        let snapshot = source
+            .async_query("SELECT * FROM test_namespace.test_table")
+            .await?
+            .unwrap();
+
+        let archive = TraceArchive {
+            tables: vec![ArchivedTable {
+                table: "test_namespace.test_table".into(),
+                dataframe: snapshot,
+                dropped_columns: vec![],
+            }],
+            ..Default::default()
+        };
+        let archive = TraceArchive::decode(&archive.encode().unwrap()).unwrap();
+
+        let replay = Engine::builder().build().await?;
+        let tables = archive
+            .tables
+            .into_iter()
+            .map(|t| (t.table, t.dataframe))
+            .collect();
+        let registered = replay.register_snapshot("replay", tables)?;
+        assert_eq!(registered, vec!["replay.test_namespace.test_table"]);
+
+        for q in queries {
+            let expected = source
+                .async_query(q.replace("{t}", "test_namespace.test_table"))
+                .await?;
+            let actual = replay
+                .async_query(q.replace("{t}", "replay.test_namespace.test_table"))
+                .await?;
+            assert_eq!(actual, expected, "{q}");
+        }
+        // Imported rows stay out of the live catalog.
+        assert!(replay
+            .async_query("SELECT * FROM test_namespace.test_table")
+            .await
+            .is_err());
+        assert!(replay.register_snapshot("probe", vec![]).is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_extension_registration() {
         #[derive(Debug)]
@@ -1074,4 +1268,96 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn cancelled_queries_stop_with_a_cancellation_error() {
+        let engine = Engine::builder().build().await.unwrap();
+        let handle = query_cancel::register("engine-cancel-test").unwrap();
+        assert!(query_cancel::cancel(handle.id()));
+        let err = query_cancel::scope(handle.token(), engine.async_query("SELECT 1"))
+            .await
+            .unwrap_err();
+        assert!(EngineError::is_cancellation(&err));
+
+        // The same query outside the cancelled scope still runs.
+        assert!(engine.async_query("SELECT 1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn batches_and_frames_hold_the_same_result() {
+        let engine = Engine::builder().build().await.unwrap();
+        let sql = "SELECT * FROM (VALUES (1, 'a'), (2, 'b')) AS t(n, s)";
+        let frame = engine.async_query(sql).await.unwrap().unwrap();
+        let (schema, batches) = engine.async_query_batches(sql).await.unwrap();
+        assert_eq!(
+            probing_proto::prelude::DataFrame::from_record_batches(&schema, &batches),
+            frame
+        );
+
+        let (schema, batches) = engine
+            .async_query_batches("SELECT 1 AS n WHERE false")
+            .await
+            .unwrap();
+        assert_eq!(schema.field(0).name(), "n");
+        assert!(batches.iter().all(|b| b.num_rows() == 0));
+    }
+
+    /// Stands in for a Python table that yields its rows in slow chunks.
+    #[derive(Debug, Default)]
+    struct SlowNamespace;
+
+    impl SlowNamespace {
+        const CHUNKS: usize = 50;
+        const CHUNK_DELAY: std::time::Duration = std::time::Duration::from_millis(20);
+    }
+
+    impl CustomNamespace for SlowNamespace {
+        fn name() -> &'static str {
+            "slow_test"
+        }
+
+        fn list() -> Vec<String> {
+            vec!["chunks".to_string()]
+        }
+
+        fn data(_expr: &str) -> Vec<RecordBatch> {
+            let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, false)]));
+            let mut batches = Vec::new();
+            for i in 0..Self::CHUNKS {
+                if query_cancel::check().is_err() {
+                    break;
+                }
+                std::thread::sleep(Self::CHUNK_DELAY);
+                let column = Arc::new(Int32Array::from(vec![i as i32]));
+                batches.push(RecordBatch::try_new(schema.clone(), vec![column]).unwrap());
+            }
+            batches
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_scans_stop_at_the_query_timeout() {
+        let engine = Engine::builder()
+            .with_data_source(NamespaceProbeDataSource::<SlowNamespace>::create(
+                "slow_test",
+            ))
+            .build()
+            .await
+            .unwrap();
+        let limit = std::time::Duration::from_millis(100);
+        let started = std::time::Instant::now();
+        let err = query_cancel::with_timeout(
+            Some(limit),
+            engine.async_query("SELECT count(*) FROM slow_test.chunks"),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            EngineError::stop_reason(&err),
+            Some(EngineError::QueryTimeout(l)) if *l == limit
+        ));
+        let full_scan = SlowNamespace::CHUNK_DELAY * SlowNamespace::CHUNKS as u32;
+        assert!(started.elapsed() < full_scan / 2);
+    }
 }
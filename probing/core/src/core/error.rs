@@ -51,6 +51,14 @@ pub enum EngineError {
     #[error("Internal engine error: {0}")]
     InternalError(String),
 
+    /// The query was cancelled (see [`crate::core::query_cancel`]).
+    #[error("Query cancelled")]
+    Cancelled,
+
+    /// The query ran past `probing.query.timeout`.
+    #[error("Query exceeded probing.query.timeout ({}s)", .0.as_secs_f64())]
+    QueryTimeout(std::time::Duration),
+
     /// Error during external API call.
     #[error("API call error: {0}")]
     CallError(String),
@@ -159,6 +167,25 @@ impl EngineError {
     pub fn invalid_option(option: impl Into<String>, detail: impl std::fmt::Display) -> Self {
         Self::InvalidOptionValue(option.into(), detail.to_string())
     }
+
+    /// A [`EngineError::QueryTimeout`] after `limit`.
+    pub fn timeout(limit: std::time::Duration) -> Self {
+        Self::QueryTimeout(limit)
+    }
+
+    /// Whether `err` or any error in its source chain is
+    /// [`EngineError::Cancelled`], e.g. once wrapped in a [`DataFusionError`].
+    pub fn is_cancellation(err: &(dyn std::error::Error + 'static)) -> bool {
+        matches!(Self::stop_reason(err), Some(EngineError::Cancelled))
+    }
+
+    /// The [`EngineError::Cancelled`] or [`EngineError::QueryTimeout`] in
+    /// `err`'s source chain, if the query was stopped rather than failed.
+    pub fn stop_reason<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a EngineError> {
+        std::iter::successors(Some(err), |e| e.source())
+            .filter_map(|e| e.downcast_ref::<EngineError>())
+            .find(|e| matches!(e, EngineError::Cancelled | EngineError::QueryTimeout(_)))
+    }
 }
 
 // Generic lock poison error conversion.
@@ -188,6 +215,7 @@ impl From<EngineError> for DataFusionError {
 mod tests {
     use super::*;
     use crate::runtime::RuntimeError;
+    use std::time::Duration;
 
     #[test]
     fn constructors_preserve_variant() {
@@ -209,6 +237,27 @@ mod tests {
         assert!(df.to_string().contains("boom"));
     }
 
+    #[test]
+    fn cancellation_is_found_through_wrappers() {
+        let df: DataFusionError = EngineError::Cancelled.into();
+        assert!(EngineError::is_cancellation(&df));
+        let wrapped = anyhow::Error::from(df).context("SELECT failed");
+        assert!(EngineError::is_cancellation(&*wrapped));
+        let other = EngineError::internal("boom");
+        assert!(!EngineError::is_cancellation(&other));
+    }
+
+    #[test]
+    fn timeouts_are_a_stop_reason_but_not_a_cancellation() {
+        let df: DataFusionError = EngineError::timeout(Duration::from_millis(1500)).into();
+        assert!(!EngineError::is_cancellation(&df));
+        let reason = EngineError::stop_reason(&df).unwrap();
+        assert_eq!(
+            reason.to_string(),
+            "Query exceeded probing.query.timeout (1.5s)"
+        );
+    }
+
     #[test]
     fn invalid_option_formats_name_and_detail() {
         let err = EngineError::invalid_option("sample_rate", "must be <= 1");
@@ -0,0 +1,372 @@
+//! `event_attrs()` — typed view of span event attributes.
+//!
+//! Event rows in `python.trace_event` keep their fields as a JSON object in
+//! `event_attributes`. This table function explodes them into one row per
+//! (event, key) with the value in a column of its own type, so numeric fields
+//! can be aggregated directly:
+//!
+//! ```sql
+//! SELECT sum(int_value) FROM event_attrs('tokens') WHERE name = 'prefill'
+//! ```
+//!
+//! `float_value` is also filled for integers, for mixed int/float fields.
+//! Arrays and objects are kept as JSON text in `string_value` with
+//! `value_type = 'json'`. The optional argument restricts rows to one key.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{
+    Array, AsArray, BooleanBuilder, Float64Builder, Int64Array, Int64Builder, RecordBatch,
+    StringBuilder,
+};
+use datafusion::arrow::compute::{cast, concat_batches};
+use datafusion::arrow::datatypes::{DataType, Field, Int64Type, Schema, SchemaRef};
+use datafusion::catalog::{Session, TableFunctionImpl};
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result as DfResult};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::{col, lit, Expr, TableProviderFilterPushDown};
+use datafusion::physical_plan::{collect, ExecutionPlan};
+use datafusion::prelude::SessionContext;
+use datafusion::scalar::ScalarValue;
+use serde_json::Value;
+
+use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use super::trace_spans::TRACE_EVENT_TABLE;
+
+pub const EVENT_ATTRS_FUNCTION: &str = "event_attrs";
+
+const SOURCE_SCHEMA: &str = "python";
+const ID_COLUMNS: [&str; 4] = ["trace_id", "span_id", "thread_id", "time"];
+
+/// Register `event_attrs()` on `ctx`.
+pub fn install_event_attrs(ctx: &SessionContext) {
+    ctx.register_udtf(EVENT_ATTRS_FUNCTION, Arc::new(EventAttrsFunction));
+}
+
+fn event_attrs_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("trace_id", DataType::Int64, true),
+        Field::new("span_id", DataType::Int64, true),
+        Field::new("thread_id", DataType::Int64, true),
+        Field::new("time", DataType::Int64, true),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("key", DataType::Utf8, false),
+        Field::new("value_type", DataType::Utf8, false),
+        Field::new("int_value", DataType::Int64, true),
+        Field::new("float_value", DataType::Float64, true),
+        Field::new("bool_value", DataType::Boolean, true),
+        Field::new("string_value", DataType::Utf8, true),
+    ]))
+}
+
+#[derive(Debug)]
+struct EventAttrsFunction;
+
+impl TableFunctionImpl for EventAttrsFunction {
+    fn call(&self, args: &[Expr]) -> DfResult<Arc<dyn TableProvider>> {
+        let key = match args {
+            [] => None,
+            [Expr::Literal(ScalarValue::Utf8(Some(key)), _)]
+            | [Expr::Literal(ScalarValue::Utf8View(Some(key)), _)]
+            | [Expr::Literal(ScalarValue::LargeUtf8(Some(key)), _)] => Some(key.clone()),
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "{EVENT_ATTRS_FUNCTION}() takes an optional attribute name string"
+                )))
+            }
+        };
+        Ok(Arc::new(EventAttrsTable {
+            key,
+            schema: event_attrs_schema(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct EventAttrsTable {
+    key: Option<String>,
+    schema: SchemaRef,
+}
+
+#[async_trait]
+impl TableProvider for EventAttrsTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> DfResult<Vec<TableProviderFilterPushDown>> {
+        supports_filters_pushdown_for_schema(&self.schema, filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        let batch = match source_table(state).await? {
+            Some(source) => {
+                // Lets the span pairing wrapper skip synthesizing span rows.
+                let only_events = col("record_type").eq(lit("event"));
+                let pushdown = source.supports_filters_pushdown(&[&only_events])?;
+                let inner_filters = if pushdown[0] == TableProviderFilterPushDown::Unsupported {
+                    vec![]
+                } else {
+                    vec![only_events]
+                };
+                let plan = source.scan(state, None, &inner_filters, None).await?;
+                let batches = collect(plan, state.task_ctx()).await?;
+                let raw = concat_batches(&source.schema(), &batches)?;
+                explode(&self.schema, &raw, self.key.as_deref())?
+            }
+            None => RecordBatch::new_empty(Arc::clone(&self.schema)),
+        };
+        scan_memory_partitions(
+            state,
+            Arc::clone(&self.schema),
+            &[vec![batch]],
+            projection,
+            filters,
+            limit,
+        )
+        .await
+    }
+}
+
+/// `python.trace_event` in the session's default catalog, if present.
+pub(super) async fn source_table(state: &dyn Session) -> DfResult<Option<Arc<dyn TableProvider>>> {
+    let Some(state) = state.as_any().downcast_ref::<SessionState>() else {
+        return Ok(None);
+    };
+    let Ok(schema) = state.schema_for_ref(format!("{SOURCE_SCHEMA}.{TRACE_EVENT_TABLE}")) else {
+        return Ok(None);
+    };
+    schema.table(TRACE_EVENT_TABLE).await
+}
+
+fn column_as(raw: &RecordBatch, name: &str, to: &DataType) -> DfResult<Option<Arc<dyn Array>>> {
+    raw.column_by_name(name)
+        .map(|c| cast(c, to).map_err(DataFusionError::from))
+        .transpose()
+}
+
+/// One output row per attribute of each `event` row.
+fn explode(schema: &SchemaRef, raw: &RecordBatch, only_key: Option<&str>) -> DfResult<RecordBatch> {
+    let (Some(record_type), Some(attrs)) = (
+        column_as(raw, "record_type", &DataType::Utf8)?,
+        column_as(raw, "event_attributes", &DataType::Utf8)?,
+    ) else {
+        return Ok(RecordBatch::new_empty(Arc::clone(schema)));
+    };
+    let record_type = record_type.as_string::<i32>();
+    let attrs = attrs.as_string::<i32>();
+    let name = column_as(raw, "name", &DataType::Utf8)?;
+    let name = name.as_ref().map(|n| n.as_string::<i32>());
+    let ids = ID_COLUMNS
+        .iter()
+        .map(|c| column_as(raw, c, &DataType::Int64))
+        .collect::<DfResult<Vec<_>>>()?;
+    let ids: Vec<Option<&Int64Array>> = ids
+        .iter()
+        .map(|c| c.as_ref().map(|c| c.as_primitive::<Int64Type>()))
+        .collect();
+
+    let mut id_out: Vec<Int64Builder> = ids.iter().map(|_| Int64Builder::new()).collect();
+    let mut name_out = StringBuilder::new();
+    let mut key_out = StringBuilder::new();
+    let mut type_out = StringBuilder::new();
+    let mut int_out = Int64Builder::new();
+    let mut float_out = Float64Builder::new();
+    let mut bool_out = BooleanBuilder::new();
+    let mut string_out = StringBuilder::new();
+
+    for row in 0..raw.num_rows() {
+        if record_type.is_null(row) || record_type.value(row) != "event" || attrs.is_null(row) {
+            continue;
+        }
+        let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(attrs.value(row)) else {
+            continue;
+        };
+        for (key, value) in fields {
+            if only_key.is_some_and(|k| k != key) {
+                continue;
+            }
+            for (out, id) in id_out.iter_mut().zip(ids.iter().copied()) {
+                out.append_option(id.filter(|a| a.is_valid(row)).map(|a| a.value(row)));
+            }
+            name_out.append_option(name.filter(|n| n.is_valid(row)).map(|n| n.value(row)));
+            key_out.append_value(&key);
+            let (value_type, int, float, boolean, text) = match &value {
+                Value::Bool(b) => ("bool", None, None, Some(*b), None),
+                Value::Number(n) => match n.as_i64() {
+                    Some(i) => ("int", Some(i), Some(i as f64), None, None),
+                    None => ("float", None, n.as_f64(), None, None),
+                },
+                Value::String(s) => ("string", None, None, None, Some(s.clone())),
+                Value::Null => ("null", None, None, None, None),
+                other => ("json", None, None, None, Some(other.to_string())),
+            };
+            type_out.append_value(value_type);
+            int_out.append_option(int);
+            float_out.append_option(float);
+            bool_out.append_option(boolean);
+            string_out.append_option(text);
+        }
+    }
+
+    let mut columns: Vec<Arc<dyn Array>> = id_out
+        .iter_mut()
+        .map(|b| Arc::new(b.finish()) as Arc<dyn Array>)
+        .collect();
+    columns.push(Arc::new(name_out.finish()));
+    columns.push(Arc::new(key_out.finish()));
+    columns.push(Arc::new(type_out.finish()));
+    columns.push(Arc::new(int_out.finish()));
+    columns.push(Arc::new(float_out.finish()));
+    columns.push(Arc::new(bool_out.finish()));
+    columns.push(Arc::new(string_out.finish()));
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PluginAdvancedTable;
+    use datafusion::arrow::array::StringArray;
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::catalog::{MemorySchemaProvider, SchemaProvider};
+
+    async fn context(rows: &[(&str, &str, &str)]) -> SessionContext {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("trace_id", DataType::Int64, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+            Field::new("event_attributes", DataType::Utf8, true),
+        ]));
+        let n = rows.len();
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from(vec![1; n])),
+                Arc::new(Int64Array::from_iter_values(0..n as i64)),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(Int64Array::from_iter_values((0..n as i64).map(|t| t * 10))),
+                Arc::new(Int64Array::from(vec![7; n])),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.2))),
+            ],
+        )
+        .unwrap();
+        let table =
+            PluginAdvancedTable::try_new("python.trace_event", schema, vec![batch]).unwrap();
+
+        let ctx = SessionContext::new();
+        install_event_attrs(&ctx);
+        let python = Arc::new(MemorySchemaProvider::new());
+        python
+            .register_table(TRACE_EVENT_TABLE.into(), Arc::new(table))
+            .unwrap();
+        ctx.catalog("datafusion")
+            .unwrap()
+            .register_schema(SOURCE_SCHEMA, python)
+            .unwrap();
+        ctx
+    }
+
+    #[tokio::test]
+    async fn aggregates_numeric_event_field() {
+        let ctx = context(&[
+            ("event", "prefill", r#"{"tokens": 4096, "cache_hit": true}"#),
+            (
+                "event",
+                "prefill",
+                r#"{"tokens": 1024, "cache_hit": false}"#,
+            ),
+            (
+                "event",
+                "decode",
+                r#"{"tokens": 1, "ratio": 0.5, "tag": "x"}"#,
+            ),
+            ("span_start", "step", r#"{"tokens": 99}"#),
+            ("event", "bad", "not json"),
+        ])
+        .await;
+
+        let batches = ctx
+            .sql(
+                "SELECT sum(int_value), count(*) FROM event_attrs('tokens') \
+                 WHERE name = 'prefill'",
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            batches[0].column(0).as_primitive::<Int64Type>().value(0),
+            5120
+        );
+        assert_eq!(batches[0].column(1).as_primitive::<Int64Type>().value(0), 2);
+
+        let batches = ctx
+            .sql("SELECT key, value_type, float_value FROM event_attrs() ORDER BY time, key")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        let keys = batch.column(0).as_string::<i32>();
+        let types = batch.column(1).as_string::<i32>();
+        let floats = batch.column(2).as_primitive::<Float64Type>();
+        let rows: Vec<(&str, &str, Option<f64>)> = (0..batch.num_rows())
+            .map(|r| {
+                (
+                    keys.value(r),
+                    types.value(r),
+                    floats.is_valid(r).then(|| floats.value(r)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("cache_hit", "bool", None),
+                ("tokens", "int", Some(4096.0)),
+                ("cache_hit", "bool", None),
+                ("tokens", "int", Some(1024.0)),
+                ("ratio", "float", Some(0.5)),
+                ("tag", "string", None),
+                ("tokens", "int", Some(1.0)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_without_trace_table_and_rejects_bad_args() {
+        let ctx = SessionContext::new();
+        install_event_attrs(&ctx);
+        let batches = ctx
+            .sql("SELECT * FROM event_attrs()")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+        assert!(ctx.sql("SELECT * FROM event_attrs(1)").await.is_err());
+    }
+}
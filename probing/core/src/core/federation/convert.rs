@@ -8,6 +8,7 @@ use arrow::array::{
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
 use datafusion::error::{DataFusionError, Result};
 use probing_proto::prelude::{DataFrame, Seq};
+use probing_proto::types::arrow_convert;
 
 pub const PROBE_HOST_COL: &str = "_host";
 pub const PROBE_ADDR_COL: &str = "_addr";
@@ -188,7 +189,7 @@ pub fn proto_dataframe_to_record_batch(df: &DataFrame) -> Result<RecordBatch> {
     let mut columns = Vec::with_capacity(df.cols.len());
     let mut fields = Vec::with_capacity(df.names.len());
     for (name, col) in df.names.iter().zip(df.cols.iter()) {
-        fields.push(Field::new(name, array_data_type(col), true));
+        fields.push(seq_field(name, col));
         columns.push(seq_to_array(col)?);
     }
     record_batch(
@@ -217,6 +218,7 @@ fn seq_to_array(seq: &Seq) -> Result<ArrayRef> {
         Seq::SeqDateTime(values) => Ok(Arc::new(Int64Array::from(
             values.iter().map(|v| *v as i64).collect::<Vec<_>>(),
         ))),
+        Seq::SeqTimestamp(values) => Ok(Arc::new(Int64Array::from(values.clone()))),
         Seq::Nil => Ok(Arc::new(StringArray::from(Vec::<String>::new()))),
     }
 }
@@ -239,7 +241,7 @@ pub fn dataframe_to_record_batch(
     let mut fields = Vec::with_capacity(df.names.len() + FEDERATION_TAG_COLUMNS.len());
 
     for (name, col) in df.names.iter().zip(df.cols.iter()) {
-        fields.push(Field::new(name, array_data_type(col), true));
+        fields.push(seq_field(name, col));
         columns.push(seq_to_array(col)?);
     }
 
@@ -344,10 +346,20 @@ pub fn align_batch_to_schema(batch: RecordBatch, schema: &Schema) -> Result<Reco
     record_batch(schema.clone(), columns, "align batch failed")
 }
 
+/// Timestamps stay `Int64` nanoseconds, as in the local tables, and keep
+/// their metadata so merged results read back as timestamps.
+fn seq_field(name: &str, seq: &Seq) -> Field {
+    let field = Field::new(name, array_data_type(seq), true);
+    match seq {
+        Seq::SeqTimestamp(_) => arrow_convert::with_timestamp_ns_metadata(field),
+        _ => field,
+    }
+}
+
 fn array_data_type(seq: &Seq) -> DataType {
     match seq {
         Seq::SeqI32(_) => DataType::Int32,
-        Seq::SeqI64(_) | Seq::SeqDateTime(_) => DataType::Int64,
+        Seq::SeqI64(_) | Seq::SeqDateTime(_) | Seq::SeqTimestamp(_) => DataType::Int64,
         Seq::SeqF32(_) => DataType::Float32,
         Seq::SeqF64(_) => DataType::Float64,
         Seq::SeqText(_) | Seq::Nil => DataType::Utf8,
@@ -23,9 +23,9 @@ pub use cluster_executor::{
 pub use convert::{
     cluster_local_rank_for_endpoint, cluster_node_rank_for_endpoint, cluster_rank_for_endpoint,
     cluster_role_for_endpoint, federated_output_schema, federation_tags_for_endpoint,
-    is_federation_tag_column, tag_proto_dataframe, FederationEndpointTags, FEDERATION_TAG_COLUMNS,
-    PROBE_ADDR_COL, PROBE_HOST_COL, PROBE_LOCAL_RANK_COL, PROBE_NODE_RANK_COL, PROBE_RANK_COL,
-    PROBE_ROLE_COL,
+    is_federation_tag_column, proto_dataframe_to_record_batch, tag_proto_dataframe,
+    FederationEndpointTags, FEDERATION_TAG_COLUMNS, PROBE_ADDR_COL, PROBE_HOST_COL,
+    PROBE_LOCAL_RANK_COL, PROBE_NODE_RANK_COL, PROBE_RANK_COL, PROBE_ROLE_COL,
 };
 pub use fanout_scope::{
     current_fanout_scope, hierarchical_fanout_enabled, is_local0_from_env, resolve_fanout_scope,
@@ -25,6 +25,12 @@
 //! - When the table has a designated timestamp column, chunks whose
 //!   `[min_ts, max_ts]` range cannot satisfy the query's time predicates are
 //!   **pruned** before materialisation ([`RingMmapTable`]).
+//! - Scans advertise ascending order on the timestamp column when the
+//!   materialised rows are verified sorted (per tier for [`HotColdTable`]),
+//!   so time-ordered window functions avoid a re-sort; see
+//!   [`timestamp_ordering`](super::plugin_advanced::timestamp_ordering).
+//! - An `Int64` `step` column reads negative values ([`NO_STEP`], written
+//!   before a step provider reports a step) as NULL.
 
 use std::collections::{BTreeSet, HashSet};
 use std::panic::AssertUnwindSafe;
@@ -62,10 +68,12 @@ use probing_memtable::{
 };
 
 use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use super::trace_spans::{with_span_rows, TRACE_EVENT_TABLE};
 use super::{
     EngineError, Maybe, PluginAdvancedTable, ProbeDataSource, ProbeDataSourceKind, ProbeExtension,
     ProbeExtensionCall, ProbeExtensionOption,
 };
+use crate::trace::{NO_STEP, STEP_COLUMN};
 use probing_macros::ProbeExtension as ProbeExtensionDerive;
 
 /// SQL schema used for mmap files whose basename contains no `.`.
@@ -329,6 +337,7 @@ fn chunk_to_recordbatch(
         log::debug!("memtable chunk {chunk} recycled mid-read; dropping");
     })
     .ok()?;
+    let arrays = null_missing_steps(arrow_schema, arrays);
 
     if view.chunk_generation(chunk) != generation_before {
         log::debug!("memtable chunk {chunk} recycled during materialisation; dropping");
@@ -635,9 +644,30 @@ fn cold_segment_paths(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
             }
         }
     }
+    // `<writer>-<seq>.memc`: name order is write order, which keeps the cold
+    // partition time-sorted so the scan can still advertise ts ordering.
+    out.sort();
     out
 }
 
+/// Samplers store [`NO_STEP`] in the `step` column until a step is known;
+/// SQL sees those rows as NULL.
+fn null_missing_steps(schema: &SchemaRef, mut arrays: Vec<ArrayRef>) -> Vec<ArrayRef> {
+    let Ok(idx) = schema.index_of(STEP_COLUMN) else {
+        return arrays;
+    };
+    if let Some(steps) = arrays
+        .get(idx)
+        .and_then(|a| a.as_any().downcast_ref::<Int64Array>())
+    {
+        if steps.values().iter().any(|v| *v <= NO_STEP) {
+            let nulled: Int64Array = steps.iter().map(|v| v.filter(|v| *v > NO_STEP)).collect();
+            arrays[idx] = Arc::new(nulled);
+        }
+    }
+    arrays
+}
+
 /// One decoded cold column → an Arrow array (schema order is preserved).
 fn cold_column_to_array(col: ColumnData) -> ArrayRef {
     match col {
@@ -693,6 +723,7 @@ fn cold_scan(
                 Ok(cols) => {
                     let arrays: Vec<ArrayRef> =
                         cols.into_iter().map(cold_column_to_array).collect();
+                    let arrays = null_missing_steps(schema, arrays);
                     match RecordBatch::try_new(Arc::clone(schema), arrays) {
                         Ok(b) if b.num_rows() > 0 => out.push(b),
                         Ok(_) => {}
@@ -913,21 +944,9 @@ impl MmapFileSchemaProvider {
             inner,
         }
     }
-}
 
-#[async_trait]
-impl SchemaProvider for MmapFileSchemaProvider {
-    fn table_names(&self) -> Vec<String> {
-        let mut names = tables_in_schema(&self.schema);
-        if let Some(inner) = &self.inner {
-            names.extend(inner.table_names());
-        }
-        names.sort();
-        names.dedup();
-        names
-    }
-
-    async fn table(&self, name: &str) -> DfResult<Option<Arc<dyn TableProvider>>> {
+    /// Lookup without the `python.trace_event` span pairing layer.
+    async fn resolve_table(&self, name: &str) -> DfResult<Option<Arc<dyn TableProvider>>> {
         if mmap_table_exists(&self.schema, name) {
             let basename = mmap_filename_for(&self.schema, name);
             let path = self_dir().join(&basename);
@@ -959,6 +978,27 @@ impl SchemaProvider for MmapFileSchemaProvider {
             None => Ok(None),
         }
     }
+}
+
+#[async_trait]
+impl SchemaProvider for MmapFileSchemaProvider {
+    fn table_names(&self) -> Vec<String> {
+        let mut names = tables_in_schema(&self.schema);
+        if let Some(inner) = &self.inner {
+            names.extend(inner.table_names());
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    async fn table(&self, name: &str) -> DfResult<Option<Arc<dyn TableProvider>>> {
+        let table = self.resolve_table(name).await?;
+        if self.schema == "python" && name == TRACE_EVENT_TABLE {
+            return Ok(table.map(with_span_rows));
+        }
+        Ok(table)
+    }
 
     fn register_table(
         &self,
@@ -1432,6 +1472,25 @@ mod tests {
         assert_eq!(tags.value(1), "world");
     }
 
+    #[test]
+    fn step_column_reads_missing_steps_as_null() {
+        let schema = MtSchema::new()
+            .col("ts", DType::I64)
+            .col(STEP_COLUMN, DType::I64);
+        let mut t = MemTable::new(&schema, 4096, 2);
+        for (ts, step) in [(1, NO_STEP), (2, 0), (3, 1)] {
+            t.push_row(&[Value::I64(ts), Value::I64(step)]);
+        }
+
+        let batches = view_to_recordbatches(&t.view());
+        let steps = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(steps.iter().collect::<Vec<_>>(), [None, Some(0), Some(1)]);
+    }
+
     #[test]
     fn recordbatches_multiple_chunks_in_logical_order() {
         let schema = MtSchema::new().col("v", DType::I64);
@@ -1,15 +1,24 @@
 mod arrow_convert;
 pub mod cluster;
 pub mod cluster_model;
+pub mod config_table;
 mod data_source;
 mod engine;
 mod error;
+mod event_attrs;
 pub mod federation;
 pub mod memtable_sql;
 mod metadata_rewrite;
+pub mod option_value;
 mod plugin_advanced;
+pub mod probe_events;
 pub mod probe_extension;
+pub mod query_cancel;
+pub mod scan_stats;
 mod semantic_catalog;
+mod span_stats;
+mod table_resolution;
+mod trace_spans;
 
 pub use data_source::ProbeDataSource;
 pub use data_source::ProbeDataSourceKind;
@@ -32,6 +41,9 @@ pub use plugin_advanced::PluginAdvancedTable;
 pub use memtable_sql::MemTableProbeExtension;
 pub use memtable_sql::UnifiedMemtableProbeDataSource;
 
+pub use probe_extension::ExtensionStream;
+pub use probe_extension::{channel_stream, single_chunk};
+pub use option_value::{OptionDuration, OptionType, OptionValue};
 pub use probe_extension::Maybe;
 pub use probe_extension::ProbeExtension;
 pub use probe_extension::ProbeExtensionCall;
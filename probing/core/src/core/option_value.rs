@@ -0,0 +1,295 @@
+//! Typed values of extension options.
+//!
+//! `#[derive(ProbeExtension)]` parses a `SET` value into the type the
+//! option's `set_<field>` method takes, through [`OptionValue`], and checks
+//! numbers against the `#[option(min = .., max = ..)]` bounds before the
+//! setter runs. A value that does not parse is rejected with the expected
+//! type instead of reaching the setter; an empty value clears a [`Maybe`].
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::probe_extension::Maybe;
+
+/// What an option holds, listed with the option so clients can offer a
+/// matching input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OptionType {
+    #[default]
+    String,
+    Integer,
+    Float,
+    /// `true`/`false`, also `on`/`off`, `yes`/`no` and `1`/`0`.
+    Bool,
+    /// A number and a unit: `ms`, `s`, `m` or `h`, e.g. `500ms` or `1.5s`.
+    Duration,
+    /// One of these names, matched ignoring case.
+    Enum(&'static [&'static str]),
+}
+
+impl OptionType {
+    /// `string`, `integer`, `number`, `bool`, `duration` or `enum`, as in
+    /// the `option_type` column of `probing.config`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptionType::String => "string",
+            OptionType::Integer => "integer",
+            OptionType::Float => "number",
+            OptionType::Bool => "bool",
+            OptionType::Duration => "duration",
+            OptionType::Enum(_) => "enum",
+        }
+    }
+
+    /// The names an [`OptionType::Enum`] accepts.
+    pub fn choices(&self) -> Option<&'static [&'static str]> {
+        match self {
+            OptionType::Enum(names) => Some(names),
+            _ => None,
+        }
+    }
+}
+
+impl Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionType::Enum(names) => write!(f, "one of {}", names.join("|")),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// A type the values of an option are parsed into.
+///
+/// Enums implement it by hand with [`OptionType::Enum`] and a `Display`
+/// printing the same names, see [`parse_variant`].
+pub trait OptionValue: Sized {
+    fn option_type() -> OptionType;
+
+    /// `value` as `Self`, `None` when it is not one.
+    fn parse_option(value: &str) -> Option<Self>;
+
+    /// The value compared with `min` and `max`; `None` is always in range.
+    fn as_number(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Index of `value` in `names`, ignoring case and surrounding spaces.
+pub fn parse_variant(value: &str, names: &[&str]) -> Option<usize> {
+    let value = value.trim();
+    names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+}
+
+macro_rules! integer_option {
+    ($($ty:ty),*) => {$(
+        impl OptionValue for $ty {
+            fn option_type() -> OptionType {
+                OptionType::Integer
+            }
+
+            fn parse_option(value: &str) -> Option<Self> {
+                value.trim().parse().ok()
+            }
+
+            fn as_number(&self) -> Option<f64> {
+                Some(*self as f64)
+            }
+        }
+    )*};
+}
+
+integer_option!(i32, i64, u32, u64, usize);
+
+impl OptionValue for f64 {
+    fn option_type() -> OptionType {
+        OptionType::Float
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        value.trim().parse().ok().filter(|v: &f64| v.is_finite())
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        Some(*self)
+    }
+}
+
+impl OptionValue for bool {
+    fn option_type() -> OptionType {
+        OptionType::Bool
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "on" | "yes" | "1" => Some(true),
+            "false" | "off" | "no" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl OptionValue for String {
+    fn option_type() -> OptionType {
+        OptionType::String
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+impl<T: OptionValue> OptionValue for Maybe<T> {
+    fn option_type() -> OptionType {
+        T::option_type()
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            Some(Maybe::Nothing)
+        } else {
+            T::parse_option(value).map(Maybe::Just)
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Maybe::Just(value) => value.as_number(),
+            Maybe::Nothing => None,
+        }
+    }
+}
+
+/// A [`Duration`] option, written as a number and a unit (`ms`, `s`, `m` or
+/// `h`); `min` and `max` bound it in seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OptionDuration(pub Duration);
+
+impl FromStr for OptionDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration `{s}`"))?;
+        let secs = match unit.trim() {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return Err(format!("duration `{s}` needs a unit: ms, s, m or h")),
+        };
+        Duration::try_from_secs_f64(secs)
+            .map(OptionDuration)
+            .map_err(|_| format!("invalid duration `{s}`"))
+    }
+}
+
+impl Display for OptionDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.subsec_nanos();
+        if nanos == 0 {
+            write!(f, "{}s", self.0.as_secs())
+        } else if nanos % 1_000_000 == 0 {
+            write!(f, "{}ms", self.0.as_millis())
+        } else {
+            write!(f, "{}s", self.0.as_secs_f64())
+        }
+    }
+}
+
+impl OptionValue for OptionDuration {
+    fn option_type() -> OptionType {
+        OptionType::Duration
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        Some(self.0.as_secs_f64())
+    }
+}
+
+/// `integer in [1, 1000]`, `integer >= 0`, `one of a|b`: the type and
+/// bounds of an option as shown in listings and errors.
+pub fn describe(option_type: OptionType, min: Option<f64>, max: Option<f64>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{option_type} in [{min}, {max}]"),
+        (Some(min), None) => format!("{option_type} >= {min}"),
+        (None, Some(max)) => format!("{option_type} <= {max}"),
+        (None, None) => option_type.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_and_flags_parse_strictly() {
+        assert_eq!(i32::parse_option(" 99 "), Some(99));
+        assert_eq!(i32::parse_option("abc"), None);
+        assert_eq!(u32::parse_option("-1"), None);
+        assert_eq!(f64::parse_option("inf"), None);
+        assert_eq!(bool::parse_option("ON"), Some(true));
+        assert_eq!(bool::parse_option("0"), Some(false));
+        assert_eq!(bool::parse_option("maybe"), None);
+    }
+
+    #[test]
+    fn empty_clears_a_maybe() {
+        assert!(matches!(
+            Maybe::<i64>::parse_option(""),
+            Some(Maybe::Nothing)
+        ));
+        assert!(matches!(
+            Maybe::<i64>::parse_option("7"),
+            Some(Maybe::Just(7))
+        ));
+        assert!(Maybe::<i64>::parse_option("seven").is_none());
+        assert_eq!(Maybe::<i64>::option_type(), OptionType::Integer);
+    }
+
+    #[test]
+    fn durations_need_a_unit() {
+        let parse = |s: &str| s.parse::<OptionDuration>().map(|d| d.0);
+        assert_eq!(parse("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse("30").unwrap_err().contains("needs a unit"));
+        assert!(parse("fast").is_err());
+
+        assert_eq!(OptionDuration(Duration::from_secs(3)).to_string(), "3s");
+        assert_eq!(
+            OptionDuration(Duration::from_millis(1500)).to_string(),
+            "1500ms"
+        );
+    }
+
+    #[test]
+    fn describes_type_and_bounds() {
+        assert_eq!(
+            describe(OptionType::Integer, Some(1.0), Some(1000.0)),
+            "integer in [1, 1000]"
+        );
+        assert_eq!(
+            describe(OptionType::Float, Some(0.5), None),
+            "number >= 0.5"
+        );
+        assert_eq!(
+            describe(OptionType::Enum(&["info", "warn"]), None, None),
+            "one of info|warn"
+        );
+        assert_eq!(parse_variant(" WARN", &["info", "warn"]), Some(1));
+    }
+}
@@ -4,6 +4,10 @@
 //! limit / stats behaviour is reused by [`super::plugin::TableDataSource`](super::plugin::TableDataSource)
 //! and [`super::plugin::LazyTableSource`](super::plugin::LazyTableSource) via [`scan_memory_partitions`]
 //! and [`supports_filters_pushdown_for_schema`].
+//!
+//! Scans also declare **ascending timestamp order** when the data proves it (see
+//! [`timestamp_ordering`]), so `ORDER BY ts` and window functions such as
+//! `avg(v) OVER (ORDER BY ts ROWS …)` / `lag` / `lead` run without a `SortExec`.
 
 use std::collections::HashSet;
 use std::fmt::Debug;
@@ -12,7 +16,12 @@ use std::sync::Arc;
 use async_trait::async_trait;
 #[cfg(test)]
 use datafusion::arrow::array::Int64Array;
-use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::array::{Array, ArrayRef, ArrowPrimitiveType, AsArray};
+use datafusion::arrow::compute::SortOptions;
+use datafusion::arrow::datatypes::{
+    DataType, Field, Int64Type, Schema, SchemaRef, TimeUnit, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::catalog::Session;
 use datafusion::common::tree_node::TreeNode;
@@ -22,7 +31,9 @@ use datafusion::datasource::memory::{DataSourceExec, MemorySourceConfig};
 use datafusion::datasource::{TableProvider, TableType};
 use datafusion::error::{DataFusionError, Result};
 use datafusion::logical_expr::{Expr, TableProviderFilterPushDown};
+use datafusion::physical_expr::expressions::Column;
 use datafusion::physical_expr::utils::conjunction;
+use datafusion::physical_expr::{LexOrdering, PhysicalSortExpr};
 use datafusion::physical_plan::common::compute_record_batch_statistics;
 use datafusion::physical_plan::filter::FilterExecBuilder;
 use datafusion::physical_plan::ExecutionPlan;
@@ -183,7 +194,79 @@ pub(crate) fn supports_filters_pushdown_for_schema(
         .collect())
 }
 
+/// Column names treated as the designated timestamp, in priority order (same
+/// convention as memtable rings).
+pub const TIMESTAMP_COLUMN_NAMES: [&str; 2] = ["timestamp", "ts"];
+
+/// `true` when every partition is non-null and non-decreasing in the column
+/// (within and across its batches).
+fn partitions_non_decreasing<T>(partitions: &[Vec<RecordBatch>], idx: usize) -> bool
+where
+    T: ArrowPrimitiveType,
+    T::Native: PartialOrd,
+{
+    partitions.iter().all(|batches| {
+        let mut last: Option<T::Native> = None;
+        batches.iter().all(|batch| {
+            let column: &ArrayRef = batch.column(idx);
+            if column.null_count() > 0 {
+                return false;
+            }
+            column.as_primitive::<T>().values().iter().all(|&v| {
+                let ok = last.is_none_or(|prev| prev <= v);
+                last = Some(v);
+                ok
+            })
+        })
+    })
+}
+
+/// Ascending ordering on the designated timestamp column, if the data is
+/// actually sorted by it.
+///
+/// Producers append in time order, but a multi-writer table (or a ring whose
+/// clock stepped back) may not be; declaring a false ordering would make
+/// windows and merges silently wrong, so each scan verifies with one linear
+/// pass over the column instead of trusting the source.
+pub fn timestamp_ordering(
+    schema: &SchemaRef,
+    partitions: &[Vec<RecordBatch>],
+) -> Option<LexOrdering> {
+    let (idx, field) = TIMESTAMP_COLUMN_NAMES
+        .iter()
+        .find_map(|name| schema.column_with_name(name))?;
+    let sorted = match field.data_type() {
+        DataType::Int64 => partitions_non_decreasing::<Int64Type>(partitions, idx),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            partitions_non_decreasing::<TimestampSecondType>(partitions, idx)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            partitions_non_decreasing::<TimestampMillisecondType>(partitions, idx)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            partitions_non_decreasing::<TimestampMicrosecondType>(partitions, idx)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            partitions_non_decreasing::<TimestampNanosecondType>(partitions, idx)
+        }
+        _ => false,
+    };
+    if !sorted {
+        return None;
+    }
+    LexOrdering::new([PhysicalSortExpr::new(
+        Arc::new(Column::new(field.name(), idx)),
+        SortOptions {
+            descending: false,
+            nulls_first: false,
+        },
+    )])
+}
+
 /// Build a scan plan over in-memory partitions with optional filter + limit pushdown.
+///
+/// The source advertises [`timestamp_ordering`] when it holds; `FilterExec` and
+/// projection preserve it.
 pub(crate) async fn scan_memory_partitions(
     state: &dyn Session,
     schema: SchemaRef,
@@ -193,11 +276,15 @@ pub(crate) async fn scan_memory_partitions(
     limit: Option<usize>,
 ) -> Result<Arc<dyn ExecutionPlan>> {
     let show_sizes = state.config_options().explain.show_sizes;
+    let sort_information: Vec<LexOrdering> = timestamp_ordering(&schema, partitions)
+        .into_iter()
+        .collect();
 
     let plan: Arc<dyn ExecutionPlan> = if filters.is_empty() {
         let mem = MemorySourceConfig::try_new(partitions, schema.clone(), projection.cloned())?
             .with_show_sizes(show_sizes)
-            .with_limit(limit);
+            .with_limit(limit)
+            .try_with_sort_information(sort_information)?;
         DataSourceExec::from_data_source(mem)
     } else {
         // Predicates are compiled against the FULL table schema, so the
@@ -213,7 +300,8 @@ pub(crate) async fn scan_memory_partitions(
         let predicate = conjunction(phys);
 
         let mem = MemorySourceConfig::try_new(partitions, schema.clone(), None)?
-            .with_show_sizes(show_sizes);
+            .with_show_sizes(show_sizes)
+            .try_with_sort_information(sort_information)?;
         let input: Arc<dyn ExecutionPlan> = DataSourceExec::from_data_source(mem);
         let filt = FilterExecBuilder::new(predicate, input)
             .apply_projection(projection.cloned())?
@@ -541,6 +629,76 @@ mod tests {
         Ok(())
     }
 
+    // --- timestamp ordering ---
+
+    fn ts_batch(ts: Vec<i64>) -> Result<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ts", DataType::Int64, false),
+            Field::new("v", DataType::Int32, false),
+        ]));
+        let v: Vec<i32> = (0..ts.len() as i32).collect();
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(ts)),
+                Arc::new(Int32Array::from(v)),
+            ],
+        )
+        .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))
+    }
+
+    #[test]
+    fn timestamp_ordering_requires_sorted_data() -> Result<()> {
+        let sorted = vec![ts_batch(vec![1, 2, 2])?, ts_batch(vec![3, 5])?];
+        let schema = sorted[0].schema();
+        let ordering = timestamp_ordering(&schema, &[sorted]).expect("sorted");
+        assert_eq!(ordering.to_string(), "ts@0 ASC NULLS LAST");
+
+        // Out of order across a batch boundary.
+        let unsorted = vec![ts_batch(vec![1, 4])?, ts_batch(vec![3])?];
+        assert!(timestamp_ordering(&schema, &[unsorted]).is_none());
+
+        // Each partition is checked independently.
+        let parts = vec![vec![ts_batch(vec![5, 6])?], vec![ts_batch(vec![1, 2])?]];
+        assert!(timestamp_ordering(&schema, &parts).is_some());
+
+        assert!(timestamp_ordering(&test_schema_id(), &[]).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn window_over_sorted_scan_skips_sort() -> Result<()> {
+        let batch = ts_batch(vec![10, 20, 30, 40])?;
+        let schema = batch.schema();
+        let table = Arc::new(PluginAdvancedTable::try_new("t", schema, vec![batch])?);
+        let ctx = SessionContext::new();
+        ctx.register_table("t", table)?;
+        let df = ctx
+            .sql(
+                "SELECT ts, avg(v) OVER (ORDER BY ts ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) AS ma \
+                 FROM t WHERE ts > 10 ORDER BY ts",
+            )
+            .await?;
+        let plan = df.clone().create_physical_plan().await?;
+        let shown = datafusion::physical_plan::displayable(plan.as_ref())
+            .indent(true)
+            .to_string();
+        assert!(!shown.contains("SortExec"), "unexpected sort:\n{shown}");
+
+        let batches = df.collect().await?;
+        let ma: Vec<f64> = batches
+            .iter()
+            .flat_map(|b| {
+                b.column(1)
+                    .as_primitive::<datafusion::arrow::datatypes::Float64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(ma, vec![1.0, 1.5, 2.5]);
+        Ok(())
+    }
+
     // --- TableProvider ---
 
     #[test]
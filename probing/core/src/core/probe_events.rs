@@ -0,0 +1,142 @@
+//! Operator actions taken against this process.
+//!
+//! Commands that change the target (a forced garbage collection, …) call
+//! [`record_probe_event`] so the action shows up in `probe.events` next to
+//! the data it may have affected. Only the newest [`MAX_EVENTS`] are kept.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use datafusion::arrow::array::{Int64Builder, RecordBatch, StringBuilder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::error::Result;
+use datafusion::prelude::SessionContext;
+
+use super::data_source::{CustomTable, TableDataSource};
+use super::scan_stats::{probe_schema, SCAN_STATS_SCHEMA};
+
+pub const PROBE_EVENTS_TABLE: &str = "events";
+
+/// Events kept in memory; older ones are dropped first.
+pub const MAX_EVENTS: usize = 1024;
+
+/// One recorded action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeEvent {
+    /// Wall-clock nanoseconds since the Unix epoch.
+    pub time: i64,
+    /// Short action name, e.g. `gc`.
+    pub kind: String,
+    /// Action-specific details, usually a JSON object.
+    pub detail: String,
+}
+
+static EVENTS: LazyLock<Mutex<VecDeque<ProbeEvent>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(MAX_EVENTS)));
+
+/// Append an event stamped with the current time.
+pub fn record_probe_event(kind: impl Into<String>, detail: impl Into<String>) {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX))
+        .unwrap_or_default();
+    let event = ProbeEvent {
+        time,
+        kind: kind.into(),
+        detail: detail.into(),
+    };
+    let mut events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    if events.len() == MAX_EVENTS {
+        events.pop_front();
+    }
+    events.push_back(event);
+}
+
+/// Recorded events, oldest first.
+pub fn probe_events() -> Vec<ProbeEvent> {
+    EVENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// `probe.events`: one row per recorded action.
+#[derive(Debug, Default)]
+pub struct ProbeEventsTable;
+
+impl CustomTable for ProbeEventsTable {
+    fn name() -> &'static str {
+        PROBE_EVENTS_TABLE
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("time", DataType::Int64, false),
+            Field::new("kind", DataType::Utf8, false),
+            Field::new("detail", DataType::Utf8, false),
+        ]))
+    }
+
+    fn data() -> Vec<RecordBatch> {
+        let mut time = Int64Builder::new();
+        let mut kind = StringBuilder::new();
+        let mut detail = StringBuilder::new();
+        for event in probe_events() {
+            time.append_value(event.time);
+            kind.append_value(&event.kind);
+            detail.append_value(&event.detail);
+        }
+        match RecordBatch::try_new(
+            Self::schema(),
+            vec![
+                Arc::new(time.finish()),
+                Arc::new(kind.finish()),
+                Arc::new(detail.finish()),
+            ],
+        ) {
+            Ok(batch) => vec![batch],
+            Err(e) => {
+                log::warn!("probe.events: {e}");
+                vec![]
+            }
+        }
+    }
+}
+
+/// Register `probe.events` on `ctx`.
+pub fn install_probe_events(ctx: &SessionContext) -> Result<()> {
+    probe_schema(ctx)?.register_table(
+        PROBE_EVENTS_TABLE.to_string(),
+        Arc::new(TableDataSource::<ProbeEventsTable>::new(format!(
+            "{SCAN_STATS_SCHEMA}.{PROBE_EVENTS_TABLE}"
+        ))),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Engine;
+    use probing_proto::prelude::Ele;
+
+    #[tokio::test]
+    async fn recorded_events_are_queryable() {
+        record_probe_event("probe_events_test", r#"{"n":1}"#);
+        let engine = Engine::builder().build().await.unwrap();
+        let df = engine
+            .async_query(
+                "SELECT kind, detail, time > 0 FROM probe.events \
+                 WHERE kind = 'probe_events_test'",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(df.len(), 1, "{df:?}");
+        assert_eq!(df.cols[1].get(0), Ele::Text(r#"{"n":1}"#.to_string()));
+        assert_eq!(df.cols[2].get(0), Ele::BOOL(true));
+    }
+}
@@ -8,15 +8,33 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use datafusion::config::{ConfigExtension, ExtensionOptions};
+use futures::stream::BoxStream;
 use once_cell::sync::Lazy;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 
 use super::error::EngineError;
+use super::option_value::{describe, OptionType, OptionValue};
 use crate::config;
 
 /// Shared probe extension instances keyed by extension name.
 pub type ProbeExtensionMap = BTreeMap<String, Arc<Mutex<dyn ProbeExtension + Send + Sync>>>;
 
+/// Response body produced chunk by chunk by [`ProbeExtensionCall::call_stream`].
+pub type ExtensionStream = BoxStream<'static, Result<Vec<u8>, EngineError>>;
+
+/// A whole response as a one-chunk [`ExtensionStream`].
+pub fn single_chunk(bytes: Vec<u8>) -> ExtensionStream {
+    Box::pin(futures::stream::once(async move { Ok(bytes) }))
+}
+
+/// Adapt a channel filled by a blocking producer into an [`ExtensionStream`];
+/// the stream ends when every sender is dropped.
+pub fn channel_stream(rx: mpsc::Receiver<Result<Vec<u8>, EngineError>>) -> ExtensionStream {
+    Box::pin(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
 /// Global probe extension registry.
 ///
 /// Shared storage for [`ProbeExtension`] instances; [`ProbeExtensionManager`] operates on this map.
@@ -78,10 +96,56 @@ impl<T: Display> From<Maybe<T>> for String {
 /// * `key` - The unique identifier for this option
 /// * `value` - The current value of the option, if set
 /// * `help` - Static help text describing the purpose and usage of this option
+/// * `option_type` - What values parse into, see [`OptionValue`]
+/// * `min` / `max` - Inclusive bounds of numeric values
+#[derive(Clone, Debug, Default)]
 pub struct ProbeExtensionOption {
     pub key: String,
     pub value: Option<String>,
     pub help: &'static str,
+    pub option_type: OptionType,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ProbeExtensionOption {
+    /// Parse `value` of option `key` into the type its setter takes, within
+    /// `min..=max`; the error names the expected type and bounds.
+    pub fn parse<T: OptionValue>(
+        key: &str,
+        value: &str,
+        min: Option<f64>,
+        max: Option<f64>,
+    ) -> Result<T, EngineError> {
+        let invalid = || {
+            EngineError::InvalidOptionValue(
+                key.to_string(),
+                format!(
+                    "{value} (expected {})",
+                    describe(T::option_type(), min, max)
+                ),
+            )
+        };
+        let parsed = T::parse_option(value).ok_or_else(invalid)?;
+        match parsed.as_number() {
+            Some(n) if min.is_some_and(|min| n < min) || max.is_some_and(|max| n > max) => {
+                Err(invalid())
+            }
+            _ => Ok(parsed),
+        }
+    }
+
+    /// [`OptionType`] of the values `setter` takes.
+    pub fn type_of<S, T: OptionValue>(
+        _setter: fn(&mut S, T) -> Result<(), EngineError>,
+    ) -> OptionType {
+        T::option_type()
+    }
+
+    /// `integer in [1, 1000]` and the like, see [`describe`].
+    pub fn spec(&self) -> String {
+        describe(self.option_type, self.min, self.max)
+    }
 }
 
 /// Extension trait for handling HTTP API calls
@@ -106,6 +170,18 @@ pub trait ProbeExtensionCall: Debug + Send + Sync {
     ) -> Result<Vec<u8>, EngineError> {
         Err(EngineError::UnsupportedCall)
     }
+
+    /// Streaming variant of [`call`](Self::call) for responses too large to
+    /// buffer. The first chunk should carry any error payload, since the HTTP
+    /// status is derived from it. Defaults to `call` as a single chunk.
+    async fn call_stream(
+        &self,
+        path: &str,
+        params: &HashMap<String, String>,
+        body: &[u8],
+    ) -> Result<ExtensionStream, EngineError> {
+        self.call(path, params, body).await.map(single_chunk)
+    }
 }
 
 /// Configurable Probing extension: HTTP calls, SET options, and runtime side effects.
@@ -181,7 +257,8 @@ pub trait ProbeExtension: Debug + Send + Sync + ProbeExtensionCall {
 ///             ProbeExtensionOption {
 ///                 key: "some_option".to_string(), // Local option key
 ///                 value: Some(self.some_option.clone()),
-///                 help: "An example option"
+///                 help: "An example option",
+///                 ..Default::default()
 ///             }
 ///         ]
 ///     }
@@ -357,28 +434,15 @@ impl ProbeExtensionManager {
         for extension in extensions_clone {
             let ext = extension.lock().await;
             let name = ext.name();
-            let expected_prefix = format!("/{name}/");
-
-            // Also check without leading slash for flexibility
-            let expected_prefix_no_slash = format!("{name}/");
-
-            let (matched, local_path) = if path.starts_with(&expected_prefix) {
-                (true, path[expected_prefix.len()..].to_string())
-            } else if path.starts_with(&expected_prefix_no_slash) {
-                (true, path[expected_prefix_no_slash.len()..].to_string())
-            } else {
-                (false, String::new())
-            };
-
-            if !matched {
+            let Some(local_path) = extension_local_path(&name, path) else {
                 continue;
-            }
+            };
 
             log::debug!("checking extension [{name}]:{path}");
             log::debug!("Extension [{name}] matched, local_path: {}", local_path);
 
             // Call the extension's async call method
-            match ext.call(&local_path, params, body).await {
+            match ext.call(local_path, params, body).await {
                 Ok(value) => return Ok(value),
                 Err(EngineError::UnsupportedCall) => {
                     log::debug!(
@@ -400,6 +464,48 @@ impl ProbeExtensionManager {
         log::error!("No extension matched path: {}", path);
         Err(EngineError::CallError(format!("API call error: {}", path)))
     }
+
+    /// Like [`call`](Self::call), but hands back the extension's chunk stream
+    /// (see [`ProbeExtensionCall::call_stream`]); the extension lock is not
+    /// held while the stream is consumed.
+    pub async fn call_stream(
+        &self,
+        path: &str,
+        params: &HashMap<String, String>,
+        body: &[u8],
+    ) -> Result<ExtensionStream, EngineError> {
+        let extensions_clone: Vec<_> = {
+            let extensions = PROBE_EXTENSIONS.read().await;
+            extensions.values().cloned().collect()
+        };
+
+        for extension in extensions_clone {
+            let ext = extension.lock().await;
+            let name = ext.name();
+            let Some(local_path) = extension_local_path(&name, path) else {
+                continue;
+            };
+            match ext.call_stream(local_path, params, body).await {
+                Err(EngineError::UnsupportedCall) => continue,
+                Err(e) => {
+                    log::error!("Extension [{name}] call failed for path '{local_path}': {e}");
+                    return Err(e);
+                }
+                ok => return ok,
+            }
+        }
+        log::error!("No extension matched path: {}", path);
+        Err(EngineError::CallError(format!("API call error: {}", path)))
+    }
+}
+
+/// Path below `/{name}/` when `path` addresses extension `name` (the leading
+/// slash is optional).
+fn extension_local_path<'a>(name: &str, path: &'a str) -> Option<&'a str> {
+    path.strip_prefix('/')
+        .unwrap_or(path)
+        .strip_prefix(name)?
+        .strip_prefix('/')
 }
 
 impl ConfigExtension for ProbeExtensionManager {
@@ -434,15 +540,36 @@ impl ExtensionOptions for ProbeExtensionManager {
 
     fn entries(&self) -> Vec<datafusion::config::ConfigEntry> {
         let fut = async {
-            self.options()
+            let governed = crate::config::governed().await;
+            let mut entries: Vec<_> = self
+                .options()
                 .await
                 .iter()
-                .map(|option| datafusion::config::ConfigEntry {
-                    key: format!("{}.{}", Self::PREFIX, option.key),
-                    value: option.value.clone(),
-                    description: option.help,
+                .map(|option| {
+                    let key = format!("{}.{}", Self::PREFIX, option.key);
+                    let description = if governed.contains_key(&key) {
+                        crate::config::GOVERNED_HELP
+                    } else {
+                        option.help
+                    };
+                    datafusion::config::ConfigEntry {
+                        key,
+                        value: option.value.clone(),
+                        description,
+                    }
                 })
-                .collect()
+                .collect();
+            // Governed keys no extension owns are listed with their stored value.
+            for key in governed.keys() {
+                if !entries.iter().any(|e| &e.key == key) {
+                    entries.push(datafusion::config::ConfigEntry {
+                        key: key.clone(),
+                        value: crate::config::get_str(key).await,
+                        description: crate::config::GOVERNED_HELP,
+                    });
+                }
+            }
+            entries
         };
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             tokio::task::block_in_place(|| handle.block_on(fut))
@@ -516,6 +643,7 @@ mod tests {
                 key: "option".to_string(),
                 value: Some(self.test_option.clone()),
                 help: "Test option",
+                ..Default::default()
             }]
         }
     }
@@ -608,4 +736,76 @@ mod tests {
 
         teardown_test().await;
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_governed_keys_are_marked_in_entries() {
+        setup_test().await;
+
+        let mut manager = ProbeExtensionManager;
+        let extension = Arc::new(Mutex::new(TestExtension::default()));
+        manager.register("test".to_string(), extension).await;
+        config::set("probing.torch.gpu_streams.interval_ms", "2000").await;
+        config::govern("probing.test.option", Some("default".into())).await;
+        config::govern("probing.torch.gpu_streams.interval_ms", None).await;
+
+        let entries = manager.entries();
+        let find = |key: &str| entries.iter().find(|e| e.key == key).unwrap();
+        assert_eq!(
+            find("probing.test.option").description,
+            config::GOVERNED_HELP
+        );
+        let interval = find("probing.torch.gpu_streams.interval_ms");
+        assert_eq!(interval.value.as_deref(), Some("2000"));
+        assert_eq!(interval.description, config::GOVERNED_HELP);
+
+        assert_eq!(
+            config::release("probing.test.option").await,
+            Some(Some("default".into()))
+        );
+        let entries = manager.entries();
+        let option = entries.iter().find(|e| e.key == "probing.test.option");
+        assert_eq!(option.unwrap().description, "Test option");
+
+        teardown_test().await;
+    }
+
+    #[test]
+    fn test_extension_local_path() {
+        assert_eq!(
+            extension_local_path("pythonext", "/pythonext/a/b"),
+            Some("a/b")
+        );
+        assert_eq!(extension_local_path("pythonext", "pythonext/a"), Some("a"));
+        assert_eq!(extension_local_path("python", "/pythonext/a"), None);
+        assert_eq!(extension_local_path("pythonext", "/pythonext"), None);
+    }
+
+    #[derive(Debug)]
+    struct EchoExtension;
+
+    #[async_trait]
+    impl ProbeExtensionCall for EchoExtension {
+        async fn call(
+            &self,
+            path: &str,
+            _params: &HashMap<String, String>,
+            body: &[u8],
+        ) -> Result<Vec<u8>, EngineError> {
+            Ok([path.as_bytes(), body].concat())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_call_stream_is_single_chunk() {
+        use futures::StreamExt;
+
+        let chunks: Vec<_> = EchoExtension
+            .call_stream("a", &HashMap::new(), b"!")
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_ref().unwrap(), b"a!");
+    }
 }
@@ -0,0 +1,273 @@
+//! Cancellation and timeouts of running queries.
+//!
+//! The server registers each query under an id ([`register`]) and runs it
+//! inside [`scope`]; `/query/cancel?id=` calls [`cancel`]. Every query also
+//! gets a deadline from `probing.query.timeout` ([`with_timeout`]). The
+//! engine stops pulling record batches once the token fires or the deadline
+//! passes and returns [`EngineError::Cancelled`] or
+//! [`EngineError::QueryTimeout`]. Table providers that fetch data in chunks
+//! (Python Arrow streams) call [`check`] between chunks to stop early.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+pub use tokio_util::sync::CancellationToken;
+
+use super::{EngineError, Maybe, ProbeExtension, ProbeExtensionCall, ProbeExtensionOption};
+use probing_macros::ProbeExtension as ProbeExtensionDerive;
+
+static RUNNING: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `probing.query.timeout` in milliseconds; 0 disables it.
+static TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// What stops the current query.
+#[derive(Clone, Debug)]
+struct Limits {
+    token: CancellationToken,
+    /// When the query times out, and the limit it was given.
+    deadline: Option<(Instant, Duration)>,
+}
+
+tokio::task_local! {
+    static CURRENT: Limits;
+}
+
+fn running() -> std::sync::MutexGuard<'static, HashMap<String, CancellationToken>> {
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Debug)]
+pub struct QueryHandle {
+    id: String,
+    token: CancellationToken,
+}
+
+impl QueryHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for QueryHandle {
+    fn drop(&mut self) {
+        running().remove(&self.id);
+    }
+}
+
+/// Register a query under `id`; `None` when a query with that id is
+/// already running.
+pub fn register(id: impl Into<String>) -> Option<QueryHandle> {
+    let id = id.into();
+    let mut running = running();
+    if running.contains_key(&id) {
+        return None;
+    }
+    let token = CancellationToken::new();
+    running.insert(id.clone(), token.clone());
+    Some(QueryHandle { id, token })
+}
+
+/// Signal the query registered under `id`; false when none is running.
+pub fn cancel(id: &str) -> bool {
+    match running().get(id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Ids of the queries running now.
+pub fn running_ids() -> Vec<String> {
+    let mut ids: Vec<_> = running().keys().cloned().collect();
+    ids.sort();
+    ids
+}
+
+/// Run `fut` with `token` as the current query's cancellation token.
+pub async fn scope<F: Future>(token: CancellationToken, fut: F) -> F::Output {
+    let deadline = CURRENT.try_with(|l| l.deadline).ok().flatten();
+    CURRENT.scope(Limits { token, deadline }, fut).await
+}
+
+/// Run `fut` with a deadline `limit` from now; an earlier deadline of the
+/// enclosing scope is kept. `None` adds none.
+pub async fn with_timeout<F: Future>(limit: Option<Duration>, fut: F) -> F::Output {
+    let current = CURRENT.try_with(Clone::clone).ok();
+    let Some(limit) = limit else {
+        return fut.await;
+    };
+    let deadline = (Instant::now() + limit, limit);
+    let limits = match current {
+        Some(Limits {
+            token,
+            deadline: Some(outer),
+        }) if outer.0 <= deadline.0 => Limits {
+            token,
+            deadline: Some(outer),
+        },
+        current => Limits {
+            token: current.map(|l| l.token).unwrap_or_default(),
+            deadline: Some(deadline),
+        },
+    };
+    CURRENT.scope(limits, fut).await
+}
+
+/// Token of the enclosing [`scope`], if any.
+///
+/// Like other task-locals it does not cross `spawn` / `spawn_blocking`.
+pub fn current() -> Option<CancellationToken> {
+    CURRENT.try_with(|l| l.token.clone()).ok()
+}
+
+/// Whether the enclosing query has been cancelled; false outside [`scope`].
+pub fn is_cancelled() -> bool {
+    CURRENT
+        .try_with(|l| l.token.is_cancelled())
+        .unwrap_or(false)
+}
+
+/// Why the enclosing query should stop now, if it should.
+pub fn check() -> Result<(), EngineError> {
+    CURRENT
+        .try_with(|l| {
+            if l.token.is_cancelled() {
+                return Err(EngineError::Cancelled);
+            }
+            match l.deadline {
+                Some((at, limit)) if Instant::now() >= at => Err(EngineError::timeout(limit)),
+                _ => Ok(()),
+            }
+        })
+        .unwrap_or(Ok(()))
+}
+
+/// Resolves once the current query is cancelled or times out, with the
+/// reason; never outside a scope.
+pub fn stopped() -> impl Future<Output = EngineError> + Send + 'static {
+    let limits = CURRENT.try_with(Clone::clone).ok();
+    async move {
+        let Some(Limits { token, deadline }) = limits else {
+            return std::future::pending().await;
+        };
+        let expired = async move {
+            match deadline {
+                Some((at, limit)) => {
+                    tokio::time::sleep_until(at.into()).await;
+                    limit
+                }
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            () = token.cancelled() => EngineError::Cancelled,
+            limit = expired => EngineError::timeout(limit),
+        }
+    }
+}
+
+/// The configured `probing.query.timeout`, `None` when disabled.
+pub fn timeout() -> Option<Duration> {
+    match TIMEOUT_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// Set the query timeout; `None` or zero disables it.
+pub fn set_timeout(limit: Option<Duration>) {
+    let ms = limit.map_or(0, |limit| limit.as_millis().max(1) as u64);
+    TIMEOUT_MS.store(ms, Ordering::Relaxed);
+}
+
+// --- `probing.query.*` options ---
+
+#[derive(Debug, Default, ProbeExtensionDerive)]
+pub struct QueryProbeExtension {
+    /// Stop queries running longer than this many seconds with a timeout error (0 or unset: no limit)
+    #[option(min = 0)]
+    timeout: Maybe<f64>,
+}
+
+impl QueryProbeExtension {
+    fn set_timeout(&mut self, timeout: Maybe<f64>) -> Result<(), EngineError> {
+        let limit = match timeout {
+            Maybe::Just(secs) if secs > 0.0 => Some(
+                Duration::try_from_secs_f64(secs)
+                    .map_err(|e| EngineError::invalid_option(Self::OPTION_TIMEOUT, e))?,
+            ),
+            _ => None,
+        };
+        set_timeout(limit);
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+impl ProbeExtensionCall for QueryProbeExtension {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancel_reaches_the_scoped_query_until_it_is_dropped() {
+        let handle = register("query-cancel-test").unwrap();
+        assert!(register("query-cancel-test").is_none());
+        assert!(running_ids().contains(&"query-cancel-test".to_string()));
+
+        assert!(!is_cancelled());
+        assert!(!scope(handle.token(), async { is_cancelled() }).await);
+        assert!(cancel("query-cancel-test"));
+        assert!(scope(handle.token(), async { is_cancelled() }).await);
+
+        drop(handle);
+        assert!(!cancel("query-cancel-test"));
+        assert!(register("query-cancel-test").is_some());
+    }
+
+    #[tokio::test]
+    async fn the_earlier_deadline_wins_and_stops_the_query() {
+        assert!(check().is_ok());
+        let limit = Duration::from_millis(20);
+        let err = with_timeout(Some(limit), async {
+            with_timeout(Some(Duration::from_secs(60)), async { stopped().await }).await
+        })
+        .await;
+        assert!(matches!(err, EngineError::QueryTimeout(l) if l == limit));
+
+        let token = CancellationToken::new();
+        let err = with_timeout(Some(Duration::from_secs(60)), async {
+            scope(token.clone(), async {
+                token.cancel();
+                check().unwrap_err()
+            })
+            .await
+        })
+        .await;
+        assert!(matches!(err, EngineError::Cancelled));
+    }
+
+    #[test]
+    fn timeout_option_is_in_seconds_and_zero_disables_it() {
+        let mut ext = QueryProbeExtension::default();
+        assert!(ext.set("timeout", "2.5").is_ok());
+        assert_eq!(timeout(), Some(Duration::from_millis(2500)));
+        assert!(ext.set("timeout", "-1").is_err());
+        assert!(ext.set("timeout", "0").is_ok());
+        assert_eq!(timeout(), None);
+    }
+}
@@ -0,0 +1,480 @@
+//! Per-table scan metrics for plugin tables.
+//!
+//! Plugin tables materialize their rows while the scan is planned
+//! ([`super::CustomTable::data`], [`super::CustomNamespace::data`]). [`measure`]
+//! wraps that call and records rows, bytes, wall time and, for tables that
+//! call back into Python, the time spent holding the GIL (via
+//! [`gil_section`]). The numbers are attached to the plan as
+//! [`ScanMetricsExec`] metrics, shown by `EXPLAIN ANALYZE`, and added to the
+//! per-table totals served by `probe.scan_stats`.
+//!
+//! Outside of a [`measure`] call [`gil_section`] does nothing, and a measured
+//! scan only updates a handful of counters.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use datafusion::arrow::array::{Int64Builder, RecordBatch, StringBuilder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::catalog::{
+    CatalogProvider, MemoryCatalogProvider, MemorySchemaProvider, SchemaProvider,
+};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties, SendableRecordBatchStream,
+};
+use datafusion::prelude::SessionContext;
+
+use super::data_source::{CustomTable, TableDataSource};
+
+pub const SCAN_STATS_SCHEMA: &str = "probe";
+pub const SCAN_STATS_TABLE: &str = "scan_stats";
+
+/// Cost of materializing one table scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanMetrics {
+    pub rows: u64,
+    /// In-memory size of the produced batches.
+    pub bytes: u64,
+    pub wall_time: Duration,
+    /// Time spent inside [`gil_section`]s.
+    pub gil_time: Duration,
+    pub gil_acquisitions: u64,
+}
+
+/// Running totals for one table, as served by `probe.scan_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanTotals {
+    pub scans: u64,
+    pub metrics: ScanMetrics,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<ScanMetrics>> = const { RefCell::new(None) };
+}
+
+static TOTALS: LazyLock<Mutex<HashMap<String, ScanTotals>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Restores the enclosing scan's accumulator, also when the scan panics.
+struct ActiveScope(Option<ScanMetrics>);
+
+impl Drop for ActiveScope {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        ACTIVE.with(|a| *a.borrow_mut() = outer);
+    }
+}
+
+/// Run `scan` and record its cost under `table`.
+pub fn measure<F>(table: &str, scan: F) -> (Vec<RecordBatch>, ScanMetrics)
+where
+    F: FnOnce() -> Vec<RecordBatch>,
+{
+    let scope = ActiveScope(ACTIVE.with(|a| a.replace(Some(ScanMetrics::default()))));
+    let start = Instant::now();
+    let batches = scan();
+    let wall_time = start.elapsed();
+    let mut metrics = ACTIVE.with(|a| a.borrow_mut().take()).unwrap_or_default();
+    drop(scope);
+
+    metrics.wall_time = wall_time;
+    for batch in &batches {
+        metrics.rows += batch.num_rows() as u64;
+        metrics.bytes += batch.get_array_memory_size() as u64;
+    }
+    if let Ok(mut totals) = TOTALS.lock() {
+        let entry = totals.entry(table.to_string()).or_default();
+        entry.scans += 1;
+        entry.metrics.rows += metrics.rows;
+        entry.metrics.bytes += metrics.bytes;
+        entry.metrics.wall_time += metrics.wall_time;
+        entry.metrics.gil_time += metrics.gil_time;
+        entry.metrics.gil_acquisitions += metrics.gil_acquisitions;
+    }
+    (batches, metrics)
+}
+
+/// Guard returned by [`gil_section`].
+pub struct GilSection {
+    start: Option<Instant>,
+}
+
+impl Drop for GilSection {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        ACTIVE.with(|a| {
+            if let Some(metrics) = a.borrow_mut().as_mut() {
+                metrics.gil_time += elapsed;
+                metrics.gil_acquisitions += 1;
+            }
+        });
+    }
+}
+
+/// Mark the current scope as holding the GIL; call it right after acquiring.
+pub fn gil_section() -> GilSection {
+    let active = ACTIVE.with(|a| a.borrow().is_some());
+    GilSection {
+        start: active.then(Instant::now),
+    }
+}
+
+/// Per-table totals since process start, sorted by table name.
+pub fn scan_totals() -> Vec<(String, ScanTotals)> {
+    let mut rows: Vec<_> = TOTALS
+        .lock()
+        .map(|t| t.iter().map(|(k, v)| (k.clone(), *v)).collect())
+        .unwrap_or_default();
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+    rows
+}
+
+/// Pass-through plan carrying the [`ScanMetrics`] of the scan below it.
+///
+/// `gil_time` / `gil_acquisitions` are only reported for scans that entered
+/// a [`gil_section`].
+#[derive(Debug)]
+pub struct ScanMetricsExec {
+    input: Arc<dyn ExecutionPlan>,
+    table: String,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl ScanMetricsExec {
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        table: impl Into<String>,
+        scan: &ScanMetrics,
+    ) -> Self {
+        let metrics = ExecutionPlanMetricsSet::new();
+        MetricBuilder::new(&metrics)
+            .global_counter("scan_rows")
+            .add(scan.rows as usize);
+        MetricBuilder::new(&metrics)
+            .global_counter("scan_bytes")
+            .add(scan.bytes as usize);
+        MetricBuilder::new(&metrics)
+            .subset_time("scan_time", 0)
+            .add_duration(scan.wall_time);
+        if scan.gil_acquisitions > 0 {
+            MetricBuilder::new(&metrics)
+                .subset_time("gil_time", 0)
+                .add_duration(scan.gil_time);
+            MetricBuilder::new(&metrics)
+                .global_counter("gil_acquisitions")
+                .add(scan.gil_acquisitions as usize);
+        }
+        Self {
+            input,
+            table: table.into(),
+            metrics,
+        }
+    }
+}
+
+impl DisplayAs for ScanMetricsExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScanMetricsExec: table={}", self.table)
+    }
+}
+
+impl ExecutionPlan for ScanMetricsExec {
+    fn name(&self) -> &str {
+        "ScanMetricsExec"
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        self.input.properties()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = children.into_iter().next().ok_or_else(|| {
+            DataFusionError::Internal("ScanMetricsExec expects exactly one child".into())
+        })?;
+        Ok(Arc::new(ScanMetricsExec {
+            input,
+            table: self.table.clone(),
+            metrics: self.metrics.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        self.input.execute(partition, context)
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+/// `probe.scan_stats`: one row per table scanned since process start.
+#[derive(Debug, Default)]
+pub struct ScanStatsTable;
+
+impl CustomTable for ScanStatsTable {
+    fn name() -> &'static str {
+        SCAN_STATS_TABLE
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("table_name", DataType::Utf8, false),
+            Field::new("scans", DataType::Int64, false),
+            Field::new("rows", DataType::Int64, false),
+            Field::new("bytes", DataType::Int64, false),
+            Field::new("wall_time_ns", DataType::Int64, false),
+            // NULL for tables that never entered the Python interpreter.
+            Field::new("gil_time_ns", DataType::Int64, true),
+            Field::new("gil_acquisitions", DataType::Int64, true),
+        ]))
+    }
+
+    fn data() -> Vec<RecordBatch> {
+        let totals = scan_totals();
+        let mut table = StringBuilder::new();
+        let mut scans = Int64Builder::new();
+        let mut rows = Int64Builder::new();
+        let mut bytes = Int64Builder::new();
+        let mut wall = Int64Builder::new();
+        let mut gil = Int64Builder::new();
+        let mut acquisitions = Int64Builder::new();
+        let int = |n: u64| i64::try_from(n).unwrap_or(i64::MAX);
+        let nanos = |d: Duration| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX);
+        for (name, t) in &totals {
+            table.append_value(name);
+            scans.append_value(int(t.scans));
+            rows.append_value(int(t.metrics.rows));
+            bytes.append_value(int(t.metrics.bytes));
+            wall.append_value(nanos(t.metrics.wall_time));
+            if t.metrics.gil_acquisitions > 0 {
+                gil.append_value(nanos(t.metrics.gil_time));
+                acquisitions.append_value(int(t.metrics.gil_acquisitions));
+            } else {
+                gil.append_null();
+                acquisitions.append_null();
+            }
+        }
+        match RecordBatch::try_new(
+            Self::schema(),
+            vec![
+                Arc::new(table.finish()),
+                Arc::new(scans.finish()),
+                Arc::new(rows.finish()),
+                Arc::new(bytes.finish()),
+                Arc::new(wall.finish()),
+                Arc::new(gil.finish()),
+                Arc::new(acquisitions.finish()),
+            ],
+        ) {
+            Ok(batch) => vec![batch],
+            Err(e) => {
+                log::warn!("probe.scan_stats: {e}");
+                vec![]
+            }
+        }
+    }
+}
+
+/// The `probe.probe` schema, created on first use.
+pub(crate) fn probe_schema(ctx: &SessionContext) -> Result<Arc<dyn SchemaProvider>> {
+    let catalog: Arc<dyn CatalogProvider> = if let Some(catalog) = ctx.catalog("probe") {
+        catalog
+    } else {
+        let c: Arc<dyn CatalogProvider> = Arc::new(MemoryCatalogProvider::new());
+        ctx.register_catalog("probe", Arc::clone(&c));
+        c
+    };
+    if catalog.schema(SCAN_STATS_SCHEMA).is_none() {
+        catalog.register_schema(SCAN_STATS_SCHEMA, Arc::new(MemorySchemaProvider::new()))?;
+    }
+    catalog
+        .schema(SCAN_STATS_SCHEMA)
+        .ok_or_else(|| DataFusionError::Internal(format!("schema `{SCAN_STATS_SCHEMA}` not found")))
+}
+
+/// Register `probe.scan_stats` on `ctx`.
+pub fn install_scan_stats(ctx: &SessionContext) -> Result<()> {
+    probe_schema(ctx)?.register_table(
+        SCAN_STATS_TABLE.to_string(),
+        Arc::new(TableDataSource::<ScanStatsTable>::new(format!(
+            "{SCAN_STATS_SCHEMA}.{SCAN_STATS_TABLE}"
+        ))),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{CustomNamespace, Engine, NamespaceProbeDataSource, TableProbeDataSource};
+    use datafusion::arrow::array::Int64Array;
+    use probing_proto::prelude::{Ele, Seq};
+
+    fn int_batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    /// Stands in for the Python namespace: rows are built inside a GIL section.
+    #[derive(Debug, Default)]
+    struct GilNamespace;
+
+    impl CustomNamespace for GilNamespace {
+        fn name() -> &'static str {
+            "scan_stats_gil"
+        }
+
+        fn list() -> Vec<String> {
+            vec!["items".to_string()]
+        }
+
+        fn data(_expr: &str) -> Vec<RecordBatch> {
+            let _gil = gil_section();
+            vec![int_batch(vec![1, 2, 3])]
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct NativeTable;
+
+    impl CustomTable for NativeTable {
+        fn name() -> &'static str {
+            "scan_stats_native"
+        }
+
+        fn schema() -> SchemaRef {
+            int_batch(vec![]).schema()
+        }
+
+        fn data() -> Vec<RecordBatch> {
+            vec![int_batch(vec![4, 5])]
+        }
+    }
+
+    async fn engine() -> Engine {
+        Engine::builder()
+            .with_data_source(NamespaceProbeDataSource::<GilNamespace>::create(
+                "scan_stats_gil",
+            ))
+            .with_data_source(TableProbeDataSource::<NativeTable>::create(
+                "scan_stats_rs",
+                "native",
+            ))
+            .build()
+            .await
+            .unwrap()
+    }
+
+    async fn analyze(engine: &Engine, sql: &str) -> String {
+        let df = engine
+            .async_query(format!("EXPLAIN ANALYZE {sql}"))
+            .await
+            .unwrap()
+            .unwrap();
+        let Seq::SeqText(plans) = &df.cols[df.names.len() - 1] else {
+            panic!("expected text plan, got {:?}", df.cols);
+        };
+        plans.join("\n")
+    }
+
+    #[test]
+    fn gil_section_outside_a_scan_is_a_no_op() {
+        drop(gil_section());
+        let (_, metrics) = measure("scan_stats_test.outside", || vec![int_batch(vec![7])]);
+        assert_eq!(metrics.rows, 1);
+        assert_eq!(metrics.gil_acquisitions, 0);
+        assert!(metrics.bytes > 0);
+    }
+
+    #[tokio::test]
+    async fn explain_analyze_reports_gil_time_only_for_python_tables() {
+        let engine = engine().await;
+
+        let plan = analyze(&engine, "SELECT v FROM scan_stats_gil.items").await;
+        assert!(
+            plan.contains("ScanMetricsExec: table=scan_stats_gil.items"),
+            "{plan}"
+        );
+        assert!(plan.contains("scan_rows=3"), "{plan}");
+        assert!(plan.contains("gil_time="), "{plan}");
+        assert!(plan.contains("gil_acquisitions=1"), "{plan}");
+
+        let plan = analyze(&engine, "SELECT v FROM scan_stats_rs.native").await;
+        assert!(
+            plan.contains("ScanMetricsExec: table=scan_stats_rs.native"),
+            "{plan}"
+        );
+        assert!(plan.contains("scan_rows=2"), "{plan}");
+        assert!(!plan.contains("gil_time"), "{plan}");
+    }
+
+    #[tokio::test]
+    async fn scan_stats_table_accumulates_per_table() {
+        let engine = engine().await;
+        for _ in 0..2 {
+            engine
+                .async_query("SELECT v FROM scan_stats_gil.items")
+                .await
+                .unwrap();
+        }
+        engine
+            .async_query("SELECT v FROM scan_stats_rs.native")
+            .await
+            .unwrap();
+
+        let df = engine
+            .async_query(
+                "SELECT table_name, rows, coalesce(gil_acquisitions, -1) FROM probe.scan_stats \
+                 WHERE table_name LIKE 'scan_stats_%' ORDER BY table_name",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let row = |name: &str| {
+            (0..df.len())
+                .find(|&i| df.cols[0].get(i) == Ele::Text(name.to_string()))
+                .map(|i| (df.cols[1].get(i), df.cols[2].get(i)))
+                .unwrap_or_else(|| panic!("no scan_stats row for {name}: {df:?}"))
+        };
+        // Other tests may scan the same tables concurrently, so only lower
+        // bounds hold.
+        let (rows, acquisitions) = row("scan_stats_gil.items");
+        assert!(matches!(rows, Ele::I64(n) if n >= 6), "{rows:?}");
+        assert!(
+            matches!(acquisitions, Ele::I64(n) if n >= 2),
+            "{acquisitions:?}"
+        );
+        let (rows, acquisitions) = row("scan_stats_rs.native");
+        assert!(matches!(rows, Ele::I64(n) if n >= 2), "{rows:?}");
+        // NULL: the native table never held the GIL.
+        assert_eq!(acquisitions, Ele::I64(-1));
+    }
+}
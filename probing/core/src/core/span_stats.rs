@@ -0,0 +1,286 @@
+//! `probing.span_stats` — duration statistics of closed spans, by name.
+//!
+//! One row per span name over the paired `span` rows of `python.trace_event`
+//! ([`super::trace_spans`]); spans still open (NULL `duration`) are left out.
+//! All durations are in nanoseconds:
+//!
+//! ```sql
+//! SELECT name, count, p95_ns FROM probing.span_stats WHERE name LIKE 'step_%'
+//! ```
+//!
+//! The table is planned as a streaming aggregate at scan time, so no span
+//! rows are buffered beyond what the source scan produces: percentiles come
+//! from `approx_percentile_cont` (one t-digest per name) rather than from
+//! sorting every duration. Filters on `name` are applied before aggregating.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::RecordBatch;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::catalog::{
+    CatalogProvider, MemoryCatalogProvider, MemorySchemaProvider, SchemaProvider, Session,
+};
+use datafusion::common::Column;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result as DfResult};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::utils::{conjunction, unnormalize_col};
+use datafusion::logical_expr::{Expr, LogicalPlanBuilder, TableProviderFilterPushDown};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::SessionContext;
+
+use super::event_attrs::source_table;
+use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use super::semantic_catalog::DOCS_SCHEMA;
+use super::trace_spans::{DURATION_COLUMN, SPAN_RECORD_TYPE, TRACE_EVENT_TABLE};
+
+pub const SPAN_STATS_SCHEMA: &str = DOCS_SCHEMA;
+pub const SPAN_STATS_TABLE: &str = "span_stats";
+
+fn span_stats_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, true),
+        Field::new("count", DataType::Int64, false),
+        Field::new("min_ns", DataType::Int64, true),
+        Field::new("p50_ns", DataType::Int64, true),
+        Field::new("p95_ns", DataType::Int64, true),
+        Field::new("p99_ns", DataType::Int64, true),
+        Field::new("max_ns", DataType::Int64, true),
+        Field::new("total_ns", DataType::Int64, true),
+    ]))
+}
+
+/// Aggregation over closed spans; column order matches [`span_stats_schema`].
+fn span_stats_sql() -> String {
+    let percentile = |p: f64| {
+        format!(
+            "CAST(approx_percentile_cont({p}) WITHIN GROUP (ORDER BY {DURATION_COLUMN}) \
+             AS BIGINT)"
+        )
+    };
+    format!(
+        "SELECT name, count(*) AS \"count\", \
+         CAST(min({DURATION_COLUMN}) AS BIGINT) AS min_ns, \
+         {} AS p50_ns, {} AS p95_ns, {} AS p99_ns, \
+         CAST(max({DURATION_COLUMN}) AS BIGINT) AS max_ns, \
+         CAST(sum({DURATION_COLUMN}) AS BIGINT) AS total_ns \
+         FROM python.{TRACE_EVENT_TABLE} \
+         WHERE record_type = '{SPAN_RECORD_TYPE}' AND {DURATION_COLUMN} IS NOT NULL \
+         GROUP BY name",
+        percentile(0.5),
+        percentile(0.95),
+        percentile(0.99),
+    )
+}
+
+#[derive(Debug)]
+struct SpanStatsTable {
+    schema: SchemaRef,
+}
+
+#[async_trait]
+impl TableProvider for SpanStatsTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> DfResult<Vec<TableProviderFilterPushDown>> {
+        supports_filters_pushdown_for_schema(&self.schema, filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        let session = state.as_any().downcast_ref::<SessionState>();
+        let (Some(session), Some(_)) = (session, source_table(state).await?) else {
+            let empty = RecordBatch::new_empty(Arc::clone(&self.schema));
+            return scan_memory_partitions(
+                state,
+                Arc::clone(&self.schema),
+                &[vec![empty]],
+                projection,
+                filters,
+                limit,
+            )
+            .await;
+        };
+
+        let mut plan =
+            LogicalPlanBuilder::from(session.create_logical_plan(&span_stats_sql()).await?);
+        // Filters arrive qualified with this table's name; the optimizer moves
+        // those on `name` below the aggregate.
+        if let Some(predicate) = conjunction(filters.iter().cloned().map(unnormalize_col)) {
+            plan = plan.filter(predicate)?;
+        }
+        if let Some(projection) = projection {
+            let fields = self.schema.fields();
+            plan = plan.project(
+                projection
+                    .iter()
+                    .map(|&i| Expr::Column(Column::new_unqualified(fields[i].name()))),
+            )?;
+        }
+        if limit.is_some() {
+            plan = plan.limit(0, limit)?;
+        }
+        session.create_physical_plan(&plan.build()?).await
+    }
+}
+
+/// The `probe.probing` schema, created on first use.
+fn probing_schema(ctx: &SessionContext) -> DfResult<Arc<dyn SchemaProvider>> {
+    let catalog: Arc<dyn CatalogProvider> = if let Some(catalog) = ctx.catalog("probe") {
+        catalog
+    } else {
+        let c: Arc<dyn CatalogProvider> = Arc::new(MemoryCatalogProvider::new());
+        ctx.register_catalog("probe", Arc::clone(&c));
+        c
+    };
+    if catalog.schema(SPAN_STATS_SCHEMA).is_none() {
+        catalog.register_schema(SPAN_STATS_SCHEMA, Arc::new(MemorySchemaProvider::new()))?;
+    }
+    catalog
+        .schema(SPAN_STATS_SCHEMA)
+        .ok_or_else(|| DataFusionError::Internal(format!("schema `{SPAN_STATS_SCHEMA}` not found")))
+}
+
+/// Register `probing.span_stats` on `ctx`.
+pub fn install_span_stats(ctx: &SessionContext) -> DfResult<()> {
+    probing_schema(ctx)?.register_table(
+        SPAN_STATS_TABLE.to_string(),
+        Arc::new(SpanStatsTable {
+            schema: span_stats_schema(),
+        }),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PluginAdvancedTable;
+    use datafusion::arrow::array::{AsArray, Int64Array, StringArray};
+    use datafusion::arrow::compute::concat_batches;
+    use datafusion::arrow::datatypes::Int64Type;
+
+    /// `python.trace_event` holding already-paired `span` rows.
+    fn context(spans: &[(&str, Option<i64>)]) -> SessionContext {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new(DURATION_COLUMN, DataType::Int64, true),
+        ]));
+        let mut record_types = vec![SPAN_RECORD_TYPE; spans.len()];
+        let mut names: Vec<&str> = spans.iter().map(|s| s.0).collect();
+        let mut durations: Vec<Option<i64>> = spans.iter().map(|s| s.1).collect();
+        // Raw rows never count, whatever their duration.
+        record_types.push("span_end");
+        names.push("step_fwd");
+        durations.push(Some(1));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(record_types)),
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int64Array::from(durations)),
+            ],
+        )
+        .unwrap();
+        let table =
+            PluginAdvancedTable::try_new("python.trace_event", schema, vec![batch]).unwrap();
+
+        let ctx = SessionContext::new();
+        install_span_stats(&ctx).unwrap();
+        let python = Arc::new(MemorySchemaProvider::new());
+        python
+            .register_table(TRACE_EVENT_TABLE.into(), Arc::new(table))
+            .unwrap();
+        ctx.catalog("datafusion")
+            .unwrap()
+            .register_schema("python", python)
+            .unwrap();
+        ctx
+    }
+
+    async fn rows(ctx: &SessionContext, sql: &str) -> Vec<Vec<Option<i64>>> {
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        (0..batch.num_rows())
+            .map(|r| {
+                batch
+                    .columns()
+                    .iter()
+                    .map(|c| {
+                        let c = c.as_primitive::<Int64Type>();
+                        c.is_valid(r).then(|| c.value(r))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn aggregates_closed_spans_by_name() {
+        let mut spans: Vec<(&str, Option<i64>)> =
+            (1..=100).map(|d| ("step_fwd", Some(d * 1000))).collect();
+        spans.push(("step_fwd", None));
+        spans.push(("step_bwd", Some(7)));
+        spans.push(("data_load", Some(5)));
+        let ctx = context(&spans);
+
+        let stats = rows(
+            &ctx,
+            "SELECT \"count\", min_ns, max_ns, total_ns FROM probe.probing.span_stats \
+             WHERE name LIKE 'step_%' ORDER BY name",
+        )
+        .await;
+        assert_eq!(
+            stats,
+            vec![
+                vec![Some(1), Some(7), Some(7), Some(7)],
+                vec![Some(100), Some(1000), Some(100_000), Some(5_050_000)],
+            ]
+        );
+
+        let percentiles = rows(
+            &ctx,
+            "SELECT p50_ns, p95_ns, p99_ns FROM probe.probing.span_stats \
+             WHERE name = 'step_fwd'",
+        )
+        .await;
+        let [p50, p95, p99] = percentiles[0][..] else {
+            panic!("{percentiles:?}");
+        };
+        let near = |v: Option<i64>, want: i64| v.is_some_and(|v| (v - want).abs() <= 1000);
+        assert!(near(p50, 50_500), "{p50:?}");
+        assert!(near(p95, 95_000), "{p95:?}");
+        assert!(near(p99, 99_000), "{p99:?}");
+        assert!(p50 <= p95 && p95 <= p99);
+    }
+
+    #[tokio::test]
+    async fn empty_without_trace_table() {
+        let ctx = SessionContext::new();
+        install_span_stats(&ctx).unwrap();
+        let batches = ctx
+            .sql("SELECT * FROM probe.probing.span_stats")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+    }
+}
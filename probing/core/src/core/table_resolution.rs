@@ -0,0 +1,567 @@
+//! Forgiving table-name resolution for user SQL.
+//!
+//! DataFusion only lowercases unquoted identifiers, so `"Python".trace_event`,
+//! a mixed-case table typed in lower case or a bare `trace_event` (which looks
+//! in the default `probe` schema) all fail with "table not found". Before
+//! planning, [`resolve_tables`] rewrites each relation that does not resolve
+//! as written:
+//!
+//! * schema and table names are matched case-insensitively against the live
+//!   catalog;
+//! * an unqualified name missing from the default schema is qualified with
+//!   the one schema that has it, or rejected as ambiguous when several do.
+//!
+//! Names with no match are left alone; if planning then fails,
+//! [`TableResolution::explain`] names up to [`MAX_SUGGESTIONS`] close
+//! candidates. The message format (`table `x` not found. Did you mean `a`,
+//! `b`?`) is parsed by the web SQL editor to offer one-click replacements.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use datafusion::catalog::{CatalogProvider, SchemaProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::prelude::SessionContext;
+use datafusion::sql::sqlparser::ast::{
+    visit_relations_mut, Ident, ObjectName, ObjectNamePart, Query, Visit, Visitor,
+};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+
+use super::federation::GLOBAL_CATALOG;
+
+/// Candidates listed when a table cannot be found.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+const INFORMATION_SCHEMA: &str = "information_schema";
+
+/// Outcome of [`resolve_tables`].
+#[derive(Debug, Default)]
+pub struct TableResolution {
+    /// Rewritten SQL, when any relation name changed.
+    pub sql: Option<String>,
+    /// Relations (as typed) that matched nothing in the catalog. Some may
+    /// still resolve at planning time (e.g. `python."sys.path"` expressions).
+    unresolved: Vec<Relation>,
+}
+
+#[derive(Debug, Clone)]
+struct Relation {
+    typed: String,
+    /// Normalized `[catalog.]schema.table` parts.
+    parts: Vec<String>,
+}
+
+impl TableResolution {
+    /// Turn a planning failure on an unresolved relation into a "did you
+    /// mean" error; other errors are returned unchanged.
+    pub fn explain(&self, ctx: &SessionContext, err: DataFusionError) -> DataFusionError {
+        let message = err.to_string();
+        if !(message.contains("not found") || message.contains("failed to resolve schema")) {
+            return err;
+        }
+        let Some(relation) = self
+            .unresolved
+            .iter()
+            .find(|r| r.parts.last().is_some_and(|t| message.contains(t.as_str())))
+            .or_else(|| self.unresolved.first())
+        else {
+            return err;
+        };
+        let candidates = suggestions(ctx, &relation.parts);
+        if candidates.is_empty() {
+            return err;
+        }
+        DataFusionError::Plan(format!(
+            "table `{}` not found. Did you mean {}?",
+            relation.typed,
+            backticked(&candidates)
+        ))
+    }
+}
+
+/// Resolve every relation in `sql` against the catalogs of `ctx`.
+///
+/// Unparseable SQL is left to DataFusion. Fails only when an unqualified or
+/// case-mismatched name matches more than one table.
+pub fn resolve_tables(ctx: &SessionContext, sql: &str) -> Result<TableResolution> {
+    let dialect = GenericDialect {};
+    let Ok(mut stmts) = Parser::parse_sql(&dialect, sql) else {
+        return Ok(TableResolution::default());
+    };
+    let options = ctx.state().config().options().catalog.clone();
+    let resolver = Resolver {
+        ctx,
+        default_catalog: &options.default_catalog,
+        default_schema: &options.default_schema,
+    };
+
+    let mut ctes = CteNames::default();
+    for stmt in &stmts {
+        let _ = stmt.visit(&mut ctes);
+    }
+
+    let mut resolution = TableResolution::default();
+    let mut changed = false;
+    for stmt in &mut stmts {
+        let flow = visit_relations_mut(stmt, |name| {
+            let Some(parts) = ident_parts(name) else {
+                return ControlFlow::Continue(());
+            };
+            let normalized: Vec<String> = parts.iter().map(normalize).collect();
+            if normalized.len() == 1 && ctes.0.contains(&normalized[0]) {
+                return ControlFlow::Continue(());
+            }
+            match resolver.resolve(&normalized) {
+                Lookup::Found => {}
+                Lookup::Rewrite(resolved) => {
+                    *name = ObjectName::from(
+                        resolved
+                            .iter()
+                            .map(String::as_str)
+                            .map(ident)
+                            .collect::<Vec<_>>(),
+                    );
+                    changed = true;
+                }
+                Lookup::Missing => resolution.unresolved.push(Relation {
+                    typed: name.to_string(),
+                    parts: normalized,
+                }),
+                Lookup::Ambiguous(candidates) => {
+                    return ControlFlow::Break(DataFusionError::Plan(format!(
+                        "table `{name}` is ambiguous: {}. Qualify it with a schema.",
+                        backticked(&candidates)
+                    )));
+                }
+            }
+            ControlFlow::Continue(())
+        });
+        if let ControlFlow::Break(err) = flow {
+            return Err(err);
+        }
+    }
+    if changed {
+        resolution.sql = Some(
+            stmts
+                .iter()
+                .map(|stmt| stmt.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        );
+    }
+    Ok(resolution)
+}
+
+#[derive(Default)]
+struct CteNames(HashSet<String>);
+
+impl Visitor for CteNames {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.0.insert(normalize(&cte.alias.name));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[derive(Debug)]
+enum Lookup {
+    Found,
+    Rewrite(Vec<String>),
+    Missing,
+    Ambiguous(Vec<String>),
+}
+
+struct Resolver<'a> {
+    ctx: &'a SessionContext,
+    default_catalog: &'a str,
+    default_schema: &'a str,
+}
+
+impl Resolver<'_> {
+    fn resolve(&self, parts: &[String]) -> Lookup {
+        match parts {
+            [table] => self.resolve_unqualified(table),
+            [schema, table] => {
+                let Some(catalog) = self.ctx.catalog(self.default_catalog) else {
+                    return Lookup::Found;
+                };
+                self.resolve_in_catalog(catalog.as_ref(), schema, table)
+                    .map_or(Lookup::Missing, |(s, t)| rewrite(parts, vec![s, t]))
+            }
+            [catalog, schema, table] => {
+                let Some(name) = find_name(&self.ctx.catalog_names(), catalog) else {
+                    return Lookup::Missing;
+                };
+                if name == GLOBAL_CATALOG {
+                    // Federated schemas live on peers; leave them to fan-out.
+                    return Lookup::Found;
+                }
+                let Some(provider) = self.ctx.catalog(&name) else {
+                    return Lookup::Missing;
+                };
+                self.resolve_in_catalog(provider.as_ref(), schema, table)
+                    .map_or(Lookup::Missing, |(s, t)| rewrite(parts, vec![name, s, t]))
+            }
+            _ => Lookup::Found,
+        }
+    }
+
+    fn resolve_unqualified(&self, table: &str) -> Lookup {
+        let Some(catalog) = self.ctx.catalog(self.default_catalog) else {
+            return Lookup::Found;
+        };
+        if let Some(schema) = catalog.schema(self.default_schema) {
+            if has_table(schema.as_ref(), table) {
+                return Lookup::Found;
+            }
+            if let Some(name) = unique_match(&schema.table_names(), table) {
+                return Lookup::Rewrite(vec![name]);
+            }
+        }
+        let mut matches: Vec<(String, String)> = Vec::new();
+        for schema_name in catalog.schema_names() {
+            let Some(schema) = catalog.schema(&schema_name) else {
+                continue;
+            };
+            let names = schema.table_names();
+            if names.iter().any(|n| n == table) {
+                matches.push((schema_name, table.to_string()));
+            } else {
+                matches.extend(
+                    names
+                        .into_iter()
+                        .filter(|n| n.eq_ignore_ascii_case(table))
+                        .map(|n| (schema_name.clone(), n)),
+                );
+            }
+        }
+        matches.sort();
+        match matches.as_slice() {
+            [] => Lookup::Missing,
+            [(schema, table)] => Lookup::Rewrite(vec![schema.clone(), table.clone()]),
+            _ => Lookup::Ambiguous(matches.iter().map(|(s, t)| format!("{s}.{t}")).collect()),
+        }
+    }
+
+    /// `(schema, table)` as registered, or `None` when nothing matches.
+    fn resolve_in_catalog(
+        &self,
+        catalog: &dyn CatalogProvider,
+        schema: &str,
+        table: &str,
+    ) -> Option<(String, String)> {
+        if schema.eq_ignore_ascii_case(INFORMATION_SCHEMA) {
+            return Some((INFORMATION_SCHEMA.to_string(), table.to_string()));
+        }
+        let schema_name = if catalog.schema(schema).is_some() {
+            schema.to_string()
+        } else {
+            find_name(&catalog.schema_names(), schema)?
+        };
+        let provider = catalog.schema(&schema_name)?;
+        if has_table(provider.as_ref(), table) {
+            return Some((schema_name, table.to_string()));
+        }
+        match unique_match(&provider.table_names(), table) {
+            Some(table_name) => Some((schema_name, table_name)),
+            // Namespace schemas accept names they do not list (Python
+            // expressions); only the schema spelling can be fixed here.
+            None if schema_name != schema => Some((schema_name, table.to_string())),
+            None => None,
+        }
+    }
+}
+
+fn rewrite(typed: &[String], resolved: Vec<String>) -> Lookup {
+    if typed == resolved.as_slice() {
+        Lookup::Found
+    } else {
+        Lookup::Rewrite(resolved)
+    }
+}
+
+fn has_table(schema: &dyn SchemaProvider, table: &str) -> bool {
+    schema.table_exist(table) || schema.table_names().iter().any(|n| n == table)
+}
+
+/// `wanted` itself when listed, else [`unique_match`].
+fn find_name(names: &[String], wanted: &str) -> Option<String> {
+    if names.iter().any(|n| n == wanted) {
+        return Some(wanted.to_string());
+    }
+    unique_match(names, wanted)
+}
+
+/// The single name equal to `wanted` ignoring ASCII case.
+fn unique_match(names: &[String], wanted: &str) -> Option<String> {
+    let mut found = names.iter().filter(|n| n.eq_ignore_ascii_case(wanted));
+    match (found.next(), found.next()) {
+        (Some(name), None) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Up to [`MAX_SUGGESTIONS`] `schema.table` names closest to `parts`.
+fn suggestions(ctx: &SessionContext, parts: &[String]) -> Vec<String> {
+    let options = ctx.state().config().options().catalog.clone();
+    let (catalog, schema, table) = match parts {
+        [table] => (options.default_catalog.clone(), None, table),
+        [schema, table] => (options.default_catalog.clone(), Some(schema), table),
+        [catalog, schema, table] => (catalog.clone(), Some(schema), table),
+        _ => return vec![],
+    };
+    let Some(provider) = ctx.catalog(&catalog) else {
+        return vec![];
+    };
+    let live = live_tables(provider);
+    let table = table.to_ascii_lowercase();
+    let limit = (table.len() / 3).max(2);
+    let mut scored: Vec<(usize, String)> = live
+        .into_iter()
+        .filter_map(|(s, t)| {
+            let mut distance = edit_distance(&table, &t.to_ascii_lowercase());
+            if let Some(schema) = schema {
+                distance += edit_distance(&schema.to_ascii_lowercase(), &s.to_ascii_lowercase());
+            }
+            if t.eq_ignore_ascii_case(&table) {
+                // Right table, wrong schema.
+                distance = distance.min(1);
+            }
+            (distance <= limit).then(|| (distance, format!("{s}.{t}")))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .map(|(_, name)| name)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+fn live_tables(catalog: Arc<dyn CatalogProvider>) -> Vec<(String, String)> {
+    catalog
+        .schema_names()
+        .into_iter()
+        .filter_map(|s| catalog.schema(&s).map(|provider| (s, provider)))
+        .flat_map(|(s, provider)| {
+            provider
+                .table_names()
+                .into_iter()
+                .map(move |t| (s.clone(), t))
+        })
+        .collect()
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+fn ident_parts(name: &ObjectName) -> Option<Vec<&Ident>> {
+    name.0.iter().map(ObjectNamePart::as_ident).collect()
+}
+
+/// The name DataFusion looks up: unquoted identifiers are lowercased.
+fn normalize(ident: &Ident) -> String {
+    if ident.quote_style.is_some() {
+        ident.value.clone()
+    } else {
+        ident.value.to_lowercase()
+    }
+}
+
+/// Quote only names that would not survive normalization unquoted.
+fn ident(name: &str) -> Ident {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain {
+        Ident::new(name)
+    } else {
+        Ident::with_quote('"', name)
+    }
+}
+
+fn backticked(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|n| format!("`{n}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Engine;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::catalog::MemorySchemaProvider;
+    use datafusion::datasource::MemTable;
+
+    async fn engine() -> Engine {
+        let engine = Engine::builder().build().await.unwrap();
+        let catalog = engine.context.catalog("probe").unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, true)]));
+        for (schema_name, tables) in [
+            ("python", &["trace_event", "trace_stats", "Mixed_Case"][..]),
+            ("cpu", &["utilization", "tasks"][..]),
+            ("gpu", &["tasks"][..]),
+        ] {
+            let provider = Arc::new(MemorySchemaProvider::new());
+            for table in tables {
+                let t = MemTable::try_new(schema.clone(), vec![vec![]]).unwrap();
+                provider
+                    .register_table(table.to_string(), Arc::new(t))
+                    .unwrap();
+            }
+            catalog.register_schema(schema_name, provider).unwrap();
+        }
+        engine
+    }
+
+    async fn runs(engine: &Engine, sql: &str) -> Result<()> {
+        engine.async_query(sql).await.map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn case_variants_resolve() {
+        let engine = engine().await;
+        for sql in [
+            "SELECT * FROM Python.trace_event",
+            r#"SELECT * FROM "Python"."TRACE_EVENT""#,
+            "SELECT * FROM python.mixed_case",
+            r#"SELECT * FROM "PROBE"."python".trace_stats"#,
+            "SELECT * FROM information_schema.tables",
+        ] {
+            runs(&engine, sql)
+                .await
+                .unwrap_or_else(|e| panic!("{sql}: {e}"));
+        }
+        let resolution =
+            resolve_tables(&engine.context, "SELECT * FROM python.mixed_case").unwrap();
+        assert_eq!(
+            resolution.sql.as_deref(),
+            Some(r#"SELECT * FROM python."Mixed_Case""#)
+        );
+    }
+
+    #[tokio::test]
+    async fn unqualified_names_use_the_unique_schema() {
+        let engine = engine().await;
+        runs(&engine, "SELECT * FROM trace_event").await.unwrap();
+        runs(
+            &engine,
+            "SELECT * FROM Utilization u JOIN cpu.tasks t ON u.v = t.v",
+        )
+        .await
+        .unwrap();
+        // The default schema wins, and CTE names are never requalified.
+        runs(&engine, "SELECT * FROM events").await.unwrap();
+        runs(
+            &engine,
+            "WITH trace_stats AS (SELECT 1 AS v) SELECT * FROM trace_stats",
+        )
+        .await
+        .unwrap();
+        let resolution = resolve_tables(&engine.context, "SELECT 1").unwrap();
+        assert!(resolution.sql.is_none());
+    }
+
+    #[tokio::test]
+    async fn ambiguous_names_are_rejected() {
+        let engine = engine().await;
+        let err = runs(&engine, "SELECT * FROM tasks").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: table `tasks` is ambiguous: `cpu.tasks`, `gpu.tasks`. \
+             Qualify it with a schema."
+        );
+        runs(&engine, "SELECT * FROM gpu.tasks").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_tables_suggest_close_names() {
+        let engine = engine().await;
+        let err = runs(&engine, "SELECT * FROM python.trace_evnt")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.ends_with(
+                "table `python.trace_evnt` not found. Did you mean `python.trace_event`?"
+            ),
+            "{err}"
+        );
+
+        let err = runs(&engine, "SELECT * FROM trace_stat")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("table `trace_stat` not found. Did you mean `python.trace_stats`"),
+            "{err}"
+        );
+
+        let err = runs(&engine, "SELECT * FROM pyhton.utilization")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Did you mean `cpu.utilization`?"), "{err}");
+
+        // Nothing close: DataFusion's own error is kept.
+        let err = runs(&engine, "SELECT * FROM zzzzzzzz")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(!err.contains("Did you mean"), "{err}");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("trace_evnt", "trace_event"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[tokio::test]
+    async fn at_most_three_suggestions() {
+        let engine = engine().await;
+        let catalog = engine.context.catalog("probe").unwrap();
+        let provider = catalog.schema("python").unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int64, true)]));
+        for table in ["trace_a", "trace_b", "trace_c", "trace_d"] {
+            let t = MemTable::try_new(schema.clone(), vec![vec![]]).unwrap();
+            provider
+                .register_table(table.to_string(), Arc::new(t))
+                .unwrap();
+        }
+        let found = suggestions(&engine.context, &["python".into(), "trace_x".into()]);
+        assert_eq!(found.len(), MAX_SUGGESTIONS, "{found:?}");
+        assert!(
+            found.iter().all(|n| n.starts_with("python.trace_")),
+            "{found:?}"
+        );
+    }
+}
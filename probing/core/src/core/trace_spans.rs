@@ -0,0 +1,968 @@
+//! Paired span rows for `python.trace_event`.
+//!
+//! The tracer writes one `span_start` and one `span_end` row per span, so a
+//! duration used to need a self-join — easy to get wrong, because span ids are
+//! only unique per thread. [`SpanPairingTable`] wraps the raw table and adds:
+//!
+//! - two nullable columns, `end_time` and `duration` (ns), NULL on raw rows;
+//! - one synthesized row per `span_start` with `record_type = 'span'`, carrying
+//!   the start row's columns plus its end time and duration.
+//!
+//! Values known only at span end (`cpu_time_ns`, `ctx_switches`, `links`) are
+//! written on the `span_end` row with `-1` / `''` elsewhere; `span` rows take
+//! them from the end row, with NULL when unfinished or not recorded. Tables
+//! written before these columns existed get them as all-NULL columns, so the
+//! same queries run against old and new rows.
+//!
+//! ```sql
+//! SELECT name, duration FROM python.trace_event WHERE record_type = 'span'
+//! ```
+//!
+//! Starts and ends pair on `(thread_id, span_id)` (end rows carry `trace_id`
+//! 0); in `time` order an end closes the most recent open start with the same
+//! key. Unfinished spans keep NULL `end_time`/`duration`; an end without a
+//! start only shows up as its raw row.
+//!
+//! `time` and `end_time` carry the timestamp field metadata, so result frames
+//! read them as `Ele::DateTime` while SQL still sees `Int64` nanoseconds.
+//!
+//! Rows of traces evicted by the retention policy
+//! ([`crate::trace::retention`]) are dropped before pairing, all from one
+//! snapshot, so a trace disappears whole.
+//!
+//! Cost: a scan that may return `span` rows reads every row of the inner
+//! table (only the columns it needs), since a start can only be paired once
+//! its end has been seen; other filters and `LIMIT` apply after pairing. The
+//! scanned batches are kept as they are (not concatenated); pairing adds a
+//! 16-byte index entry per row plus the synthesized rows. Filtering on
+//! `record_type` so that no `span` row can match (`= 'event'`,
+//! `<> 'span'`, ...) skips pairing entirely and passes filters and `LIMIT`
+//! to the inner scan.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::arrow::array::{
+    new_null_array, Array, ArrayRef, AsArray, BooleanArray, Int64Array, RecordBatch, StringArray,
+};
+use datafusion::arrow::compute::{cast, filter_record_batch, interleave, nullif};
+use datafusion::arrow::datatypes::{DataType, Field, Int64Type, Schema, SchemaRef};
+use datafusion::catalog::Session;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result as DfResult};
+use datafusion::logical_expr::{Expr, Operator, TableProviderFilterPushDown};
+use datafusion::physical_plan::{collect, ExecutionPlan};
+use datafusion::scalar::ScalarValue;
+use probing_proto::types::arrow_convert::with_timestamp_ns_metadata;
+
+use super::plugin_advanced::{scan_memory_partitions, supports_filters_pushdown_for_schema};
+use crate::trace::{eviction_state, EvictedTraces, EvictionState};
+
+/// Table name (in the `python` schema) that gets paired span rows.
+pub const TRACE_EVENT_TABLE: &str = "trace_event";
+/// `record_type` of synthesized rows.
+pub const SPAN_RECORD_TYPE: &str = "span";
+pub const END_TIME_COLUMN: &str = "end_time";
+pub const DURATION_COLUMN: &str = "duration";
+
+const RECORD_TYPE_COLUMN: &str = "record_type";
+const TRACE_ID_COLUMN: &str = "trace_id";
+const TIME_COLUMN: &str = "time";
+const KEY_COLUMNS: [&str; 2] = ["thread_id", "span_id"];
+/// Columns `span` rows take from the end row (negative or empty = unset),
+/// with the type they get when an older table lacks them.
+const END_ROW_COLUMNS: [(&str, DataType); 3] = [
+    ("cpu_time_ns", DataType::Int64),
+    ("ctx_switches", DataType::Int64),
+    ("links", DataType::Utf8),
+];
+
+fn is_end_row_column(name: &str) -> bool {
+    END_ROW_COLUMNS.iter().any(|(c, _)| *c == name)
+}
+
+/// Wrap `inner` in a [`SpanPairingTable`] when its schema allows it.
+pub fn with_span_rows(inner: Arc<dyn TableProvider>) -> Arc<dyn TableProvider> {
+    match SpanPairingTable::try_new(Arc::clone(&inner)) {
+        Some(table) => Arc::new(table),
+        None => inner,
+    }
+}
+
+/// Raw trace rows plus synthesized `span` rows; see the module docs, which
+/// also cover what a pairing scan costs.
+#[derive(Debug)]
+pub struct SpanPairingTable {
+    inner: Arc<dyn TableProvider>,
+    /// Inner columns plus any missing end-row columns.
+    raw_schema: SchemaRef,
+    schema: SchemaRef,
+    evictions: &'static EvictionState,
+}
+
+impl SpanPairingTable {
+    /// `None` when `inner` lacks a pairing column or already has a derived one.
+    pub fn try_new(inner: Arc<dyn TableProvider>) -> Option<Self> {
+        let base = inner.schema();
+        let has = |name: &str| base.column_with_name(name).is_some();
+        if !has(RECORD_TYPE_COLUMN)
+            || !has(TIME_COLUMN)
+            || !KEY_COLUMNS.iter().all(|c| has(c))
+            || has(END_TIME_COLUMN)
+            || has(DURATION_COLUMN)
+        {
+            return None;
+        }
+        let mut fields: Vec<Field> = base
+            .fields()
+            .iter()
+            .map(|f| {
+                let nullable = f.is_nullable() || is_end_row_column(f.name());
+                let field = f.as_ref().clone().with_nullable(nullable);
+                if f.name() == TIME_COLUMN && f.data_type() == &DataType::Int64 {
+                    with_timestamp_ns_metadata(field)
+                } else {
+                    field
+                }
+            })
+            .collect();
+        for (name, data_type) in &END_ROW_COLUMNS {
+            if !has(name) {
+                fields.push(Field::new(*name, data_type.clone(), true));
+            }
+        }
+        let raw_schema = Arc::new(Schema::new_with_metadata(
+            fields.clone(),
+            base.metadata().clone(),
+        ));
+        fields.push(with_timestamp_ns_metadata(Field::new(
+            END_TIME_COLUMN,
+            DataType::Int64,
+            true,
+        )));
+        fields.push(Field::new(DURATION_COLUMN, DataType::Int64, true));
+        let schema = Arc::new(Schema::new_with_metadata(fields, base.metadata().clone()));
+        Some(Self {
+            inner,
+            raw_schema,
+            schema,
+            evictions: eviction_state(),
+        })
+    }
+
+    /// Filter against `evictions` instead of the process-wide state.
+    pub fn with_evictions(mut self, evictions: &'static EvictionState) -> Self {
+        self.evictions = evictions;
+        self
+    }
+}
+
+#[async_trait]
+impl TableProvider for SpanPairingTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> DfResult<Vec<TableProviderFilterPushDown>> {
+        supports_filters_pushdown_for_schema(&self.schema, filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        // Pairing needs every start and end, so filters only reach the raw
+        // scan when they already rule out span rows, and never when they
+        // name a column the inner table lacks.
+        let raw_only = filters.iter().any(excludes_span_rows);
+        let inner_schema = self.inner.schema();
+        let mut exact = 0;
+        let inner_filters = if raw_only {
+            let refs: Vec<&Expr> = filters
+                .iter()
+                .filter(|f| {
+                    f.column_refs()
+                        .iter()
+                        .all(|c| inner_schema.column_with_name(c.name()).is_some())
+                })
+                .collect();
+            let support = self.inner.supports_filters_pushdown(&refs)?;
+            exact = support
+                .iter()
+                .filter(|s| **s == TableProviderFilterPushDown::Exact)
+                .count();
+            refs.into_iter()
+                .zip(support)
+                .filter(|(_, s)| *s != TableProviderFilterPushDown::Unsupported)
+                .map(|(f, _)| f.clone())
+                .collect()
+        } else {
+            vec![]
+        };
+        let evicted = self.evictions.snapshot();
+
+        // Read only the columns the query, its filters, pairing and eviction
+        // use; the derived pair always closes the scanned schema.
+        let mut needed: Vec<usize> = match projection {
+            Some(p) => p.clone(),
+            None => (0..self.schema.fields().len()).collect(),
+        };
+        let mut need = |name: &str| needed.extend(self.schema.index_of(name).ok());
+        for column in filters.iter().flat_map(Expr::column_refs) {
+            need(column.name());
+        }
+        if !raw_only || !evicted.is_empty() {
+            need(RECORD_TYPE_COLUMN);
+            KEY_COLUMNS.into_iter().for_each(&mut need);
+        }
+        if !raw_only {
+            need(TIME_COLUMN);
+        }
+        if !evicted.is_empty() {
+            need(TRACE_ID_COLUMN);
+        }
+        need(END_TIME_COLUMN);
+        need(DURATION_COLUMN);
+        needed.sort_unstable();
+        needed.dedup();
+        let schema = Arc::new(self.schema.project(&needed)?);
+        let raw_schema = Arc::new(self.raw_schema.project(&needed[..needed.len() - 2])?);
+        let inner_projection: Vec<usize> = raw_schema
+            .fields()
+            .iter()
+            .filter_map(|f| inner_schema.index_of(f.name()).ok())
+            .collect();
+        // Rows dropped after the inner scan (eviction, filters it cannot
+        // apply exactly) would make an inner limit return too few.
+        let inner_limit =
+            limit.filter(|_| raw_only && evicted.is_empty() && exact == filters.len());
+
+        let plan = self
+            .inner
+            .scan(state, Some(&inner_projection), &inner_filters, inner_limit)
+            .await?;
+        let mut seen = HashSet::new();
+        let mut raw = Vec::new();
+        for batch in collect(plan, state.task_ctx()).await? {
+            let batch = with_missing_columns(&raw_schema, &batch)?;
+            if evicted.is_empty() {
+                raw.push(batch);
+            } else {
+                let (kept, ids) = without_evicted(&batch, &evicted)?;
+                seen.extend(ids);
+                raw.push(kept);
+            }
+        }
+        // Only a scan of every row can tell which ids are gone for good.
+        if !evicted.is_empty() && inner_filters.is_empty() {
+            self.evictions.forget_absent(&evicted, &seen);
+        }
+
+        let spans = if raw_only {
+            None
+        } else {
+            Some(span_rows(&schema, &raw)?)
+        };
+        let raw = raw
+            .iter()
+            .map(|batch| with_null_derived(&schema, batch))
+            .collect::<DfResult<Vec<_>>>()?;
+        let mut partitions = vec![raw];
+        partitions.extend(spans.map(|spans| vec![spans]));
+        let projection: Option<Vec<usize>> = projection.map(|p| {
+            p.iter()
+                .map(|i| needed.partition_point(|n| n < i))
+                .collect()
+        });
+        scan_memory_partitions(
+            state,
+            schema,
+            &partitions,
+            projection.as_ref(),
+            filters,
+            limit,
+        )
+        .await
+    }
+}
+
+fn is_record_type(expr: &Expr) -> bool {
+    matches!(expr, Expr::Column(c) if c.name() == RECORD_TYPE_COLUMN)
+}
+
+fn string_literal(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(s)), _)
+        | Expr::Literal(ScalarValue::LargeUtf8(Some(s)), _)
+        | Expr::Literal(ScalarValue::Utf8View(Some(s)), _) => Some(s),
+        _ => None,
+    }
+}
+
+/// `record_type = 'x'`, `record_type IN (...)` without `'span'`, or
+/// `record_type <> 'span'`.
+fn excludes_span_rows(expr: &Expr) -> bool {
+    match expr {
+        Expr::BinaryExpr(b) => {
+            let lit = if is_record_type(&b.left) {
+                string_literal(&b.right)
+            } else if is_record_type(&b.right) {
+                string_literal(&b.left)
+            } else {
+                None
+            };
+            match (b.op, lit) {
+                (Operator::Eq, Some(v)) => v != SPAN_RECORD_TYPE,
+                (Operator::NotEq, Some(v)) => v == SPAN_RECORD_TYPE,
+                (Operator::And, _) => excludes_span_rows(&b.left) || excludes_span_rows(&b.right),
+                _ => false,
+            }
+        }
+        Expr::InList(list) => {
+            !list.negated
+                && is_record_type(&list.expr)
+                && list
+                    .list
+                    .iter()
+                    .all(|e| string_literal(e).is_some_and(|v| v != SPAN_RECORD_TYPE))
+        }
+        _ => false,
+    }
+}
+
+/// `raw` without rows of evicted traces, plus the evicted ids it contained.
+fn without_evicted(
+    raw: &RecordBatch,
+    evicted: &EvictedTraces,
+) -> DfResult<(RecordBatch, HashSet<i64>)> {
+    let record_type = column_as(raw, RECORD_TYPE_COLUMN, &DataType::Utf8)?;
+    let record_type = record_type.as_string::<i32>();
+    let trace_id = match raw.column_by_name(TRACE_ID_COLUMN) {
+        Some(col) => cast(col, &DataType::Int64)?,
+        None => new_null_array(&DataType::Int64, raw.num_rows()),
+    };
+    let trace_id = trace_id.as_primitive::<Int64Type>();
+    let keys = KEY_COLUMNS
+        .iter()
+        .map(|c| column_as(raw, c, &DataType::Int64))
+        .collect::<DfResult<Vec<_>>>()?;
+    let keys: Vec<&Int64Array> = keys.iter().map(|k| k.as_primitive::<Int64Type>()).collect();
+
+    let mut seen = HashSet::new();
+    let keep: BooleanArray = (0..raw.num_rows())
+        .map(|row| {
+            let hit = evicted.trace_of(
+                if record_type.is_null(row) {
+                    ""
+                } else {
+                    record_type.value(row)
+                },
+                int_at(trace_id, row).unwrap_or(0),
+                int_at(keys[0], row).unwrap_or(0),
+                int_at(keys[1], row).unwrap_or(0),
+            );
+            if let Some(id) = hit {
+                seen.insert(id);
+            }
+            Some(hit.is_none())
+        })
+        .collect();
+    Ok((filter_record_batch(raw, &keep)?, seen))
+}
+
+fn column_as(raw: &RecordBatch, name: &str, to: &DataType) -> DfResult<ArrayRef> {
+    let col = raw.column_by_name(name).ok_or_else(|| {
+        DataFusionError::Internal(format!("{TRACE_EVENT_TABLE} scan lacks column {name}"))
+    })?;
+    Ok(cast(col, to)?)
+}
+
+fn int_at(arr: &Int64Array, row: usize) -> Option<i64> {
+    arr.is_valid(row).then(|| arr.value(row))
+}
+
+/// `raw` in `schema`, with all-NULL columns for those it lacks.
+fn with_missing_columns(schema: &SchemaRef, raw: &RecordBatch) -> DfResult<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|f| match raw.column_by_name(f.name()) {
+            Some(column) => Arc::clone(column),
+            None => new_null_array(f.data_type(), raw.num_rows()),
+        })
+        .collect();
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// NULL for unset end-row values: negative numbers and empty strings.
+fn unset_to_null(values: &ArrayRef) -> DfResult<ArrayRef> {
+    Ok(match values.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
+            let text = cast(values, &DataType::Utf8)?;
+            let set: StringArray = text
+                .as_string::<i32>()
+                .iter()
+                .map(|v| v.filter(|v| !v.is_empty()))
+                .collect();
+            cast(&set, values.data_type())?
+        }
+        _ => {
+            let ints = cast(values, &DataType::Int64)?;
+            let set: Int64Array = ints
+                .as_primitive::<Int64Type>()
+                .iter()
+                .map(|v| v.filter(|v| *v >= 0))
+                .collect();
+            cast(&set, values.data_type())?
+        }
+    })
+}
+
+/// Raw rows with NULL `end_time` / `duration`.
+fn with_null_derived(schema: &SchemaRef, raw: &RecordBatch) -> DfResult<RecordBatch> {
+    let mut columns = raw.columns().to_vec();
+    columns.push(new_null_array(&DataType::Int64, raw.num_rows()));
+    columns.push(new_null_array(&DataType::Int64, raw.num_rows()));
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// One `span` row per `span_start` across `raw`, in start-time order.
+///
+/// Rows are addressed as `(batch, row)` so the batches never need to be
+/// concatenated.
+fn span_rows(schema: &SchemaRef, raw: &[RecordBatch]) -> DfResult<RecordBatch> {
+    let mut record_types = Vec::with_capacity(raw.len());
+    let mut times = Vec::with_capacity(raw.len());
+    let mut keys = Vec::with_capacity(raw.len());
+    for batch in raw {
+        record_types.push(column_as(batch, RECORD_TYPE_COLUMN, &DataType::Utf8)?);
+        times.push(column_as(batch, TIME_COLUMN, &DataType::Int64)?);
+        keys.push(
+            KEY_COLUMNS
+                .iter()
+                .map(|c| column_as(batch, c, &DataType::Int64))
+                .collect::<DfResult<Vec<_>>>()?,
+        );
+    }
+    let record_type = |(b, row): (usize, usize)| {
+        let arr = record_types[b].as_string::<i32>();
+        arr.is_valid(row).then(|| arr.value(row))
+    };
+    let time = |(b, row): (usize, usize)| int_at(times[b].as_primitive::<Int64Type>(), row);
+    let key = |(b, row): (usize, usize)| {
+        [0, 1].map(|i: usize| int_at(keys[b][i].as_primitive::<Int64Type>(), row))
+    };
+
+    // Stable: rows written at the same instant keep their write order, so a
+    // zero-length span still starts before it ends.
+    let mut order: Vec<(usize, usize)> = raw
+        .iter()
+        .enumerate()
+        .flat_map(|(b, batch)| (0..batch.num_rows()).map(move |row| (b, row)))
+        .collect();
+    order.sort_by_key(|&at| time(at).unwrap_or(i64::MIN));
+
+    let mut starts: Vec<(usize, usize)> = vec![];
+    let mut ends: Vec<Option<(usize, usize)>> = vec![];
+    let mut open: HashMap<[Option<i64>; 2], Vec<usize>> = HashMap::new();
+    for at in order {
+        match record_type(at) {
+            Some("span_start") => {
+                open.entry(key(at)).or_default().push(starts.len());
+                starts.push(at);
+                ends.push(None);
+            }
+            Some("span_end") => {
+                if let Some(slot) = open.get_mut(&key(at)).and_then(Vec::pop) {
+                    ends[slot] = Some(at);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if starts.is_empty() {
+        return Ok(RecordBatch::new_empty(Arc::clone(schema)));
+    }
+    // Unfinished spans read their start row for end-row columns, then get
+    // NULL there.
+    let end_rows: Vec<(usize, usize)> = starts
+        .iter()
+        .zip(&ends)
+        .map(|(&start, end)| end.unwrap_or(start))
+        .collect();
+    let unfinished: BooleanArray = ends.iter().map(|end| Some(end.is_none())).collect();
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    let raw_fields = schema.fields().len() - 2;
+    for (i, field) in schema.fields()[..raw_fields].iter().enumerate() {
+        let values: Vec<&dyn Array> = raw.iter().map(|b| b.column(i).as_ref()).collect();
+        if field.name() == RECORD_TYPE_COLUMN {
+            let span = StringArray::from(vec![SPAN_RECORD_TYPE; starts.len()]);
+            columns.push(cast(&span, field.data_type())?);
+        } else if is_end_row_column(field.name()) {
+            let values = nullif(&interleave(&values, &end_rows)?, &unfinished)?;
+            columns.push(unset_to_null(&values)?);
+        } else {
+            columns.push(interleave(&values, &starts)?);
+        }
+    }
+    let end_time: Int64Array = ends.iter().map(|end| end.and_then(time)).collect();
+    let duration: Int64Array = starts
+        .iter()
+        .zip(&end_time)
+        .map(|(&start, end)| Some(end? - time(start)?))
+        .collect();
+    columns.push(Arc::new(end_time));
+    columns.push(Arc::new(duration));
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PluginAdvancedTable;
+    use datafusion::arrow::compute::concat_batches;
+    use datafusion::prelude::SessionContext;
+
+    fn trace_table(rows: &[(&str, i64, i64, &str, i64)]) -> Arc<dyn TableProvider> {
+        with_span_rows(raw_trace_table(rows))
+    }
+
+    fn raw_trace_table(rows: &[(&str, i64, i64, &str, i64)]) -> Arc<dyn TableProvider> {
+        raw_trace_batches(&[rows])
+    }
+
+    fn raw_trace_batches(batches: &[&[(&str, i64, i64, &str, i64)]]) -> Arc<dyn TableProvider> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("trace_id", DataType::Int64, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+        ]));
+        let batches = batches
+            .iter()
+            .map(|rows| {
+                RecordBatch::try_new(
+                    Arc::clone(&schema),
+                    vec![
+                        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                        Arc::new(Int64Array::from(vec![1; rows.len()])),
+                        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.3))),
+                        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.2))),
+                        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.4))),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        let raw = PluginAdvancedTable::try_new("python.trace_event", schema, batches).unwrap();
+        Arc::new(raw)
+    }
+
+    async fn spans(table: Arc<dyn TableProvider>, sql: &str) -> Vec<(String, Option<i64>)> {
+        let ctx = SessionContext::new();
+        ctx.register_table("trace_event", table).unwrap();
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let mut out = vec![];
+        for b in &batches {
+            let names = b.column(0).as_string::<i32>();
+            let durations = b.column(1).as_primitive::<Int64Type>();
+            for row in 0..b.num_rows() {
+                out.push((names.value(row).to_string(), int_at(durations, row)));
+            }
+        }
+        out
+    }
+
+    const SPANS: &str = "SELECT name, duration FROM trace_event \
+                         WHERE record_type = 'span' ORDER BY time, name";
+
+    #[tokio::test]
+    async fn pairs_by_thread_when_span_ids_repeat() {
+        // Span id 7 is reused by two threads with interleaved lifetimes; a
+        // span_id-only join would produce four rows with crossed durations.
+        let table = trace_table(&[
+            ("span_start", 7, 100, "a", 1),
+            ("span_start", 7, 110, "b", 2),
+            ("span_end", 7, 130, "b", 2),
+            ("span_end", 7, 170, "a", 1),
+        ]);
+        assert_eq!(
+            spans(table, SPANS).await,
+            vec![("a".into(), Some(70)), ("b".into(), Some(20))]
+        );
+    }
+
+    #[tokio::test]
+    async fn pairs_across_scanned_batches() {
+        let rows = [
+            ("span_start", 1, 100, "outer", 1),
+            ("span_start", 2, 120, "inner", 1),
+            ("span_end", 2, 140, "inner", 1),
+            ("span_end", 1, 190, "outer", 1),
+        ];
+        // Ends land in an earlier batch than their starts.
+        let table = with_span_rows(raw_trace_batches(&[&rows[2..], &rows[..2]]));
+        assert_eq!(
+            spans(table, SPANS).await,
+            vec![("outer".into(), Some(90)), ("inner".into(), Some(20))]
+        );
+    }
+
+    #[tokio::test]
+    async fn unfinished_spans_are_null_and_unmatched_ends_ignored() {
+        let table = trace_table(&[
+            ("span_end", 3, 50, "orphan", 1),
+            ("span_start", 4, 100, "open", 1),
+            ("span_start", 5, 120, "done", 1),
+            ("span_end", 5, 120, "done", 1),
+            ("event", 5, 120, "tick", 1),
+        ]);
+        assert_eq!(
+            spans(Arc::clone(&table), SPANS).await,
+            vec![("open".into(), None), ("done".into(), Some(0))]
+        );
+        // Raw rows stay as written, with NULL derived columns.
+        let raw = spans(
+            table,
+            "SELECT record_type, duration FROM trace_event \
+             WHERE record_type <> 'span' ORDER BY time, record_type",
+        )
+        .await;
+        assert_eq!(raw.len(), 5);
+        assert!(raw.iter().all(|(_, duration)| duration.is_none()));
+    }
+
+    #[tokio::test]
+    async fn span_rows_take_cpu_counters_from_the_end_row() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+            Field::new("cpu_time_ns", DataType::Int64, false),
+        ]));
+        let rows = [
+            ("span_start", 1, "busy", 100, -1),
+            ("span_start", 2, "unsampled", 110, -1),
+            ("span_end", 2, "", 120, -1),
+            ("span_end", 1, "", 200, 60),
+            ("span_start", 3, "open", 210, -1),
+        ];
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.2))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.3))),
+                Arc::new(Int64Array::from(vec![1; rows.len()])),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.4))),
+            ],
+        )
+        .unwrap();
+        let raw = PluginAdvancedTable::try_new("python.trace_event", schema, vec![batch]).unwrap();
+        let table = with_span_rows(Arc::new(raw));
+        assert_eq!(
+            spans(
+                table,
+                "SELECT name, cpu_time_ns FROM trace_event \
+                 WHERE record_type = 'span' ORDER BY time",
+            )
+            .await,
+            vec![
+                ("busy".into(), Some(60)),
+                ("unsampled".into(), None),
+                ("open".into(), None)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn older_tables_get_null_end_row_columns() {
+        // `trace_table` predates cpu sampling and links.
+        let table = trace_table(&[("span_start", 1, 100, "a", 1), ("span_end", 1, 150, "a", 1)]);
+        for column in ["cpu_time_ns", "ctx_switches", "links"] {
+            assert!(table
+                .schema()
+                .field_with_name(column)
+                .unwrap()
+                .is_nullable());
+        }
+        let ctx = SessionContext::new();
+        ctx.register_table("trace_event", table).unwrap();
+        let batches = ctx
+            .sql(
+                "SELECT links, cpu_time_ns, duration FROM trace_event \
+                 WHERE record_type = 'span' OR links IS NOT NULL",
+            )
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let b = concat_batches(&batches[0].schema(), &batches).unwrap();
+        assert_eq!(b.num_rows(), 1);
+        assert!(b.column(0).is_null(0) && b.column(1).is_null(0));
+        assert_eq!(b.column(2).as_primitive::<Int64Type>().value(0), 50);
+
+        // A raw-only filter on a padded column stays out of the inner scan.
+        let raw = ctx
+            .sql("SELECT name FROM trace_event WHERE record_type = 'span_end' AND links = ''")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(raw.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+    }
+
+    #[tokio::test]
+    async fn span_rows_take_links_from_the_end_row() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+            Field::new("links", DataType::Utf8, false),
+        ]));
+        let link = r#"[{"trace_id":1,"span_id":9}]"#;
+        let rows = [
+            ("span_start", 1, 100, ""),
+            ("span_start", 2, 110, ""),
+            ("span_end", 2, 120, ""),
+            ("span_end", 1, 130, link),
+        ];
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.2))),
+                Arc::new(Int64Array::from(vec![1; rows.len()])),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.3))),
+            ],
+        )
+        .unwrap();
+        let raw = PluginAdvancedTable::try_new("python.trace_event", schema, vec![batch]).unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_table("trace_event", with_span_rows(Arc::new(raw)))
+            .unwrap();
+        let batches = ctx
+            .sql("SELECT links FROM trace_event WHERE record_type = 'span' ORDER BY time")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        let links = batch.column(0).as_string::<i32>();
+        assert_eq!(links.value(0), link);
+        assert!(links.is_null(1));
+    }
+
+    fn two_trace_table(
+        rows: &[(&str, i64, i64, i64)],
+        evictions: &'static EvictionState,
+    ) -> Arc<dyn TableProvider> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("record_type", DataType::Utf8, false),
+            Field::new("trace_id", DataType::Int64, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("time", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.2))),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| format!("s{}", r.2)),
+                )),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.3))),
+                Arc::new(Int64Array::from(vec![1; rows.len()])),
+            ],
+        )
+        .unwrap();
+        let raw = PluginAdvancedTable::try_new("python.trace_event", schema, vec![batch]).unwrap();
+        Arc::new(
+            SpanPairingTable::try_new(Arc::new(raw))
+                .unwrap()
+                .with_evictions(evictions),
+        )
+    }
+
+    #[tokio::test]
+    async fn evicted_traces_disappear_whole() {
+        use crate::trace::TraceExtent;
+
+        let evictions: &'static EvictionState = Box::leak(Box::default());
+        let rows = [
+            ("span_start", 1, 10, 100),
+            ("event", 1, 10, 120),
+            ("span_end", 0, 10, 150),
+            ("span_start", 2, 20, 200),
+            ("span_end", 0, 20, 250),
+        ];
+        evictions.evict(&[TraceExtent {
+            trace_id: 1,
+            last_ns: 150,
+            open: false,
+            spans: vec![(1, 10)],
+        }]);
+
+        let table = two_trace_table(&rows, evictions);
+        assert_eq!(
+            spans(Arc::clone(&table), SPANS).await,
+            vec![("s20".into(), Some(50))]
+        );
+        // End rows carry no trace id; a raw-only scan still drops them.
+        let ends = spans(
+            table,
+            "SELECT name, time FROM trace_event WHERE record_type = 'span_end'",
+        )
+        .await;
+        assert_eq!(ends, vec![("s20".into(), Some(250))]);
+        assert_eq!(evictions.snapshot().len(), 1, "rows still stored");
+
+        // Once storage no longer holds its rows the id is forgotten.
+        let table = two_trace_table(&rows[3..], evictions);
+        assert_eq!(spans(table, SPANS).await.len(), 1);
+        assert!(evictions.snapshot().is_empty());
+        assert_eq!(evictions.evicted_count(), 1);
+    }
+
+    /// Passes scans through, remembering the projection and limit asked for.
+    #[derive(Debug)]
+    struct RecordingTable {
+        inner: Arc<dyn TableProvider>,
+        scans: std::sync::Mutex<Vec<(Option<Vec<usize>>, Option<usize>)>>,
+    }
+
+    #[async_trait]
+    impl TableProvider for RecordingTable {
+        fn schema(&self) -> SchemaRef {
+            self.inner.schema()
+        }
+
+        fn table_type(&self) -> TableType {
+            TableType::Base
+        }
+
+        fn supports_filters_pushdown(
+            &self,
+            filters: &[&Expr],
+        ) -> DfResult<Vec<TableProviderFilterPushDown>> {
+            self.inner.supports_filters_pushdown(filters)
+        }
+
+        async fn scan(
+            &self,
+            state: &dyn Session,
+            projection: Option<&Vec<usize>>,
+            filters: &[Expr],
+            limit: Option<usize>,
+        ) -> DfResult<Arc<dyn ExecutionPlan>> {
+            self.scans
+                .lock()
+                .unwrap()
+                .push((projection.cloned(), limit));
+            self.inner.scan(state, projection, filters, limit).await
+        }
+    }
+
+    #[tokio::test]
+    async fn projection_and_limit_reach_the_raw_scan() {
+        let recording = Arc::new(RecordingTable {
+            inner: raw_trace_table(&[
+                ("span_start", 1, 100, "a", 1),
+                ("event", 1, 110, "tick", 1),
+                ("event", 1, 120, "tock", 1),
+                ("span_end", 1, 150, "a", 1),
+            ]),
+            scans: Default::default(),
+        });
+        let table = || {
+            let inner = Arc::clone(&recording) as Arc<dyn TableProvider>;
+            let table = SpanPairingTable::try_new(inner).unwrap();
+            Arc::new(table.with_evictions(Box::leak(Box::default()))) as Arc<dyn TableProvider>
+        };
+        let last_scan = || recording.scans.lock().unwrap().pop().unwrap();
+
+        // Raw rows only: just `record_type` and `name`, and the limit.
+        let ctx = SessionContext::new();
+        ctx.register_table("trace_event", table()).unwrap();
+        let batches = ctx
+            .sql("SELECT name FROM trace_event WHERE record_type = 'event' LIMIT 1")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+        assert_eq!(last_scan(), (Some(vec![0, 3]), Some(1)));
+
+        // Pairing reads its own columns, but still not `trace_id`.
+        assert_eq!(spans(table(), SPANS).await, vec![("a".into(), Some(50))]);
+        assert_eq!(last_scan(), (Some(vec![0, 2, 3, 4, 5]), None));
+    }
+
+    #[test]
+    fn record_type_filters_that_skip_pairing() {
+        use datafusion::prelude::{col, lit};
+        let rt = || col(RECORD_TYPE_COLUMN);
+        assert!(excludes_span_rows(&rt().eq(lit("span_start"))));
+        assert!(excludes_span_rows(&rt().not_eq(lit("span"))));
+        assert!(excludes_span_rows(
+            &rt().in_list(vec![lit("span_end"), lit("event")], false)
+        ));
+        assert!(!excludes_span_rows(&rt().eq(lit("span"))));
+        assert!(!excludes_span_rows(&rt().in_list(vec![lit("span")], false)));
+        assert!(!excludes_span_rows(&col("name").eq(lit("span_start"))));
+    }
+
+    #[tokio::test]
+    async fn times_read_back_as_datetimes_but_stay_integers_in_sql() {
+        use probing_proto::prelude::{DataFrame, Seq};
+
+        let table = trace_table(&[("span_start", 1, 100, "a", 1), ("span_end", 1, 170, "a", 1)]);
+        let ctx = SessionContext::new();
+        ctx.register_table("trace_event", table).unwrap();
+        let df = ctx
+            .sql(
+                "SELECT time, end_time, end_time - time AS d FROM trace_event \
+                 WHERE record_type = 'span'",
+            )
+            .await
+            .unwrap();
+        let schema = Arc::clone(df.schema().inner());
+        let batches = df.collect().await.unwrap();
+        let frame = DataFrame::from_record_batches(&schema, &batches);
+        assert_eq!(
+            frame.cols,
+            vec![
+                Seq::SeqTimestamp(vec![100]),
+                Seq::SeqTimestamp(vec![170]),
+                Seq::SeqI64(vec![70]),
+            ]
+        );
+    }
+}
@@ -3,6 +3,7 @@
 pub mod config;
 pub mod core;
 pub mod diagnostics;
+pub mod proc_access;
 pub mod runtime;
 pub mod signal;
 pub mod storage;
@@ -36,5 +37,6 @@ pub async fn initialize_engine(builder: EngineBuilder) -> Result<()> {
         .inspect_err(|e| log::error!("Error creating engine: {e}"))?;
 
     *ENGINE.write().await = engine;
+    config::load_persisted().await;
     Ok(())
 }
@@ -0,0 +1,222 @@
+//! What this process can read from `/proc`.
+//!
+//! Inside some containers the injected library cannot read parts of `/proc`
+//! (masked `task/` or `fd/`, `maps` denied by the LSM, no `/proc` mount at
+//! all). Features that depend on it consult [`proc_capabilities`] and degrade
+//! instead of failing: thread listings fall back to the calling thread and
+//! tables come back empty. The probes take the proc root as a parameter so
+//! tests can point them at a fake tree.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use serde::Serialize;
+
+pub const DEFAULT_PROC_ROOT: &str = "/proc";
+
+/// A `/proc/self` entry some feature depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcFeature {
+    /// `task/`: thread enumeration (Stacks thread list, per-thread CPU).
+    ThreadList,
+    /// `fd/`: open file descriptors.
+    FdList,
+    /// `maps`: memory mappings (injection checks, library lookup).
+    MemoryMaps,
+    /// `stat`: process CPU time and RSS.
+    Stat,
+    /// `status`: RSS high-water mark and context switches.
+    Status,
+}
+
+impl ProcFeature {
+    pub const ALL: [ProcFeature; 5] = [
+        ProcFeature::ThreadList,
+        ProcFeature::FdList,
+        ProcFeature::MemoryMaps,
+        ProcFeature::Stat,
+        ProcFeature::Status,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProcFeature::ThreadList => "proc.thread_list",
+            ProcFeature::FdList => "proc.fd_list",
+            ProcFeature::MemoryMaps => "proc.memory_maps",
+            ProcFeature::Stat => "proc.stat",
+            ProcFeature::Status => "proc.status",
+        }
+    }
+
+    fn entry(self) -> &'static str {
+        match self {
+            ProcFeature::ThreadList => "self/task",
+            ProcFeature::FdList => "self/fd",
+            ProcFeature::MemoryMaps => "self/maps",
+            ProcFeature::Stat => "self/stat",
+            ProcFeature::Status => "self/status",
+        }
+    }
+
+    /// What still works when the entry is unreadable.
+    fn fallback(self) -> &'static str {
+        match self {
+            ProcFeature::ThreadList => "only the current thread is listed; cpu.tasks stays empty",
+            ProcFeature::FdList => "file descriptor listings are empty",
+            ProcFeature::MemoryMaps => "library checks assume probing is not loaded",
+            ProcFeature::Stat => "process CPU samples are skipped",
+            ProcFeature::Status => "memory figures fall back to getrusage",
+        }
+    }
+
+    /// Read the entry once; directories must also be listable.
+    fn probe(self, root: &Path) -> io::Result<()> {
+        let path = root.join(self.entry());
+        match self {
+            ProcFeature::ThreadList | ProcFeature::FdList => {
+                std::fs::read_dir(&path)?.next().transpose()?;
+            }
+            _ => {
+                std::fs::read(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One row of the features endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Capability {
+    pub name: &'static str,
+    pub available: bool,
+    /// Why the entry could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Degraded behavior while unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcCapabilities {
+    pub root: PathBuf,
+    pub capabilities: Vec<Capability>,
+}
+
+impl ProcCapabilities {
+    pub fn detect(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let capabilities = ProcFeature::ALL
+            .into_iter()
+            .map(|feature| match feature.probe(&root) {
+                Ok(()) => Capability {
+                    name: feature.name(),
+                    available: true,
+                    reason: None,
+                    fallback: None,
+                },
+                Err(e) => Capability {
+                    name: feature.name(),
+                    available: false,
+                    reason: Some(format!("{}: {e}", root.join(feature.entry()).display())),
+                    fallback: Some(feature.fallback()),
+                },
+            })
+            .collect();
+        Self { root, capabilities }
+    }
+
+    pub fn available(&self, feature: ProcFeature) -> bool {
+        self.capabilities
+            .iter()
+            .any(|c| c.name == feature.name() && c.available)
+    }
+
+    /// Thread ids of this process, or just the calling thread when `task/` is
+    /// unreadable.
+    pub fn thread_ids(&self) -> Vec<u64> {
+        if self.available(ProcFeature::ThreadList) {
+            if let Ok(tids) = list_thread_ids(&self.root, None) {
+                if !tids.is_empty() {
+                    return tids;
+                }
+            }
+        }
+        vec![current_tid()]
+    }
+}
+
+/// Capabilities of the real `/proc`, probed once.
+pub fn proc_capabilities() -> &'static ProcCapabilities {
+    static CAPS: LazyLock<ProcCapabilities> =
+        LazyLock::new(|| ProcCapabilities::detect(DEFAULT_PROC_ROOT));
+    &CAPS
+}
+
+/// Entries of `<root>/<pid>/task` (`self` when `pid` is `None`).
+pub fn list_thread_ids(root: &Path, pid: Option<i32>) -> io::Result<Vec<u64>> {
+    let dir = match pid {
+        Some(pid) => root.join(pid.to_string()).join("task"),
+        None => root.join("self/task"),
+    };
+    let mut tids: Vec<u64> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    tids.sort_unstable();
+    Ok(tids)
+}
+
+#[cfg(target_os = "linux")]
+fn current_tid() -> u64 {
+    unsafe { libc::syscall(libc::SYS_gettid) as u64 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_tid() -> u64 {
+    std::process::id() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_proc() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for tid in ["12", "7"] {
+            std::fs::create_dir_all(dir.path().join("self/task").join(tid)).unwrap();
+        }
+        std::fs::create_dir_all(dir.path().join("self/fd")).unwrap();
+        std::fs::write(dir.path().join("self/fd/0"), "").unwrap();
+        std::fs::write(dir.path().join("self/stat"), "1 (python) S").unwrap();
+        dir
+    }
+
+    #[test]
+    fn missing_entries_become_unavailable_with_reasons() {
+        let root = fake_proc();
+        let caps = ProcCapabilities::detect(root.path());
+        assert!(caps.available(ProcFeature::ThreadList));
+        assert!(caps.available(ProcFeature::FdList));
+        assert!(caps.available(ProcFeature::Stat));
+        assert!(!caps.available(ProcFeature::MemoryMaps));
+        let maps = caps
+            .capabilities
+            .iter()
+            .find(|c| c.name == "proc.memory_maps")
+            .unwrap();
+        assert!(maps.reason.as_deref().unwrap().contains("self/maps"));
+        assert!(maps.fallback.is_some());
+        assert_eq!(caps.thread_ids(), [7, 12]);
+    }
+
+    #[test]
+    fn no_proc_mount_falls_back_to_current_thread() {
+        let root = tempfile::tempdir().unwrap();
+        let caps = ProcCapabilities::detect(root.path().join("missing"));
+        assert!(caps.capabilities.iter().all(|c| !c.available));
+        assert_eq!(caps.thread_ids(), [current_tid()]);
+        assert!(list_thread_ids(&caps.root, Some(1)).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Periodic export of trace rows to a directory.
+//!
+//! `probing.trace.autosave=60s,/path/dir` makes the server write the
+//! `python.trace_event` rows recorded since the previous export every 60s,
+//! so a process killed outright still leaves its trace on disk. Optional
+//! `segments=N` and `size=512M` items bound how many segments and how many
+//! bytes the directory keeps; the oldest segments are deleted first.
+//!
+//! This module only holds the parsed setting and the export watermark; the
+//! writer lives in the server. While autosave is on, retention
+//! ([`super::retention`]) holds back traces that ended after the last
+//! successful export, so nothing is evicted before it has been written.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Segments kept unless `segments=` says otherwise.
+pub const DEFAULT_MAX_SEGMENTS: usize = 100;
+/// Bytes kept unless `size=` says otherwise.
+pub const DEFAULT_MAX_BYTES: u64 = 1 << 30;
+
+/// No hold on retention (autosave off).
+const NO_HOLD: i64 = i64::MAX;
+
+static CONFIG: RwLock<Option<AutosaveConfig>> = RwLock::new(None);
+static EXPORTED_THROUGH: AtomicI64 = AtomicI64::new(NO_HOLD);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutosaveConfig {
+    pub interval: Duration,
+    pub dir: PathBuf,
+    pub max_segments: usize,
+    pub max_bytes: u64,
+}
+
+impl AutosaveConfig {
+    /// `<interval>,<dir>[,segments=N][,size=BYTES]`, e.g. `60s,/tmp/trace`
+    /// or `5m,/data/trace,segments=20,size=256M`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut items = spec.split(',').map(str::trim);
+        let interval = parse_interval(items.next().unwrap_or_default())?;
+        let dir = items
+            .next()
+            .filter(|d| !d.is_empty())
+            .ok_or_else(|| format!("missing directory in `{spec}` (expected `60s,/path/dir`)"))?;
+        let mut config = AutosaveConfig {
+            interval,
+            dir: PathBuf::from(dir),
+            max_segments: DEFAULT_MAX_SEGMENTS,
+            max_bytes: DEFAULT_MAX_BYTES,
+        };
+        for item in items {
+            match item.split_once('=') {
+                Some(("segments", n)) => {
+                    config.max_segments = n
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid segment count `{n}`"))?;
+                }
+                Some(("size", size)) => config.max_bytes = parse_size(size)?,
+                _ => return Err(format!("unknown autosave item `{item}`")),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// `30s`, `5m`, `2h`, or plain seconds.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let (digits, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid interval `{value}`"))?;
+    let interval = match unit {
+        "s" => Duration::from_secs(n),
+        "m" => Duration::from_secs(n * 60),
+        "h" => Duration::from_secs(n * 3600),
+        _ => return Err(format!("unknown unit in `{value}` (use s, m or h)")),
+    };
+    if interval.is_zero() {
+        return Err("interval must be positive".to_string());
+    }
+    Ok(interval)
+}
+
+/// Bytes, optionally with a `K`, `M` or `G` suffix (powers of 1024).
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (digits, shift) = match value.strip_suffix(['K', 'k']) {
+        Some(d) => (d, 10),
+        None => match value.strip_suffix(['M', 'm']) {
+            Some(d) => (d, 20),
+            None => match value.strip_suffix(['G', 'g']) {
+                Some(d) => (d, 30),
+                None => (value, 0),
+            },
+        },
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("invalid size `{value}`"))
+}
+
+/// Turn autosave on or off. Turning it on holds retention until the first
+/// export completes.
+pub fn set_autosave(config: Option<AutosaveConfig>) {
+    EXPORTED_THROUGH.store(
+        if config.is_some() { i64::MIN } else { NO_HOLD },
+        Ordering::Relaxed,
+    );
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+pub fn autosave_config() -> Option<AutosaveConfig> {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Record that every row stamped at or before `ns` is on disk.
+pub fn mark_exported_through(ns: i64) {
+    if autosave_config().is_some() {
+        EXPORTED_THROUGH.fetch_max(ns, Ordering::Relaxed);
+    }
+}
+
+/// Newest time retention may evict up to; `None` while autosave is off.
+pub fn exported_through() -> Option<i64> {
+    match EXPORTED_THROUGH.load(Ordering::Relaxed) {
+        NO_HOLD => None,
+        ns => Some(ns),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_interval_directory_and_limits() {
+        assert_eq!(
+            AutosaveConfig::parse("60s,/tmp/trace").unwrap(),
+            AutosaveConfig {
+                interval: Duration::from_secs(60),
+                dir: PathBuf::from("/tmp/trace"),
+                max_segments: DEFAULT_MAX_SEGMENTS,
+                max_bytes: DEFAULT_MAX_BYTES,
+            }
+        );
+        let limited = AutosaveConfig::parse(" 5m , /data/t , segments=20, size=256M").unwrap();
+        assert_eq!(limited.interval, Duration::from_secs(300));
+        assert_eq!(limited.dir, PathBuf::from("/data/t"));
+        assert_eq!((limited.max_segments, limited.max_bytes), (20, 256 << 20));
+
+        for bad in [
+            "60s",
+            "60s,",
+            "0s,/tmp",
+            "1d,/tmp",
+            "60s,/tmp,segments=0",
+            "60s,/tmp,size=lots",
+            "60s,/tmp,keep=3",
+        ] {
+            assert!(AutosaveConfig::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-span thread CPU time and context switches.
+//!
+//! With `probing.trace.cpu_time=on`, a [`Span`](super::Span) reads the calling
+//! thread's `getrusage(RUSAGE_THREAD)` when it starts and when it finishes and
+//! keeps the deltas as `cpu_time_ns` (user + system) and `ctx_switches`
+//! (voluntary + involuntary). One call returns both counters, so the cost is
+//! two syscalls per span; CPU time has the microsecond resolution of `rusage`.
+//! A span finished on another thread, or on a platform without
+//! `RUSAGE_THREAD`, gets no figures. Off by default.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_cpu_time_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn cpu_time_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Cumulative CPU counters of one thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadCpu {
+    pub cpu_time_ns: u64,
+    pub ctx_switches: u64,
+}
+
+impl ThreadCpu {
+    /// Counters of the calling thread; `None` where `RUSAGE_THREAD` is missing.
+    #[cfg(target_os = "linux")]
+    pub fn sample() -> Option<Self> {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+        if unsafe { libc::getrusage(libc::RUSAGE_THREAD, usage.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        let usage = unsafe { usage.assume_init() };
+        let micros = |tv: libc::timeval| tv.tv_sec as u64 * 1_000_000 + tv.tv_usec as u64;
+        Some(ThreadCpu {
+            cpu_time_ns: (micros(usage.ru_utime) + micros(usage.ru_stime)) * 1_000,
+            ctx_switches: (usage.ru_nvcsw + usage.ru_nivcsw) as u64,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn sample() -> Option<Self> {
+        None
+    }
+
+    /// Counters accumulated since `start`; `None` if any went backwards, i.e.
+    /// the samples come from different threads.
+    pub fn since(self, start: ThreadCpu) -> Option<ThreadCpu> {
+        Some(ThreadCpu {
+            cpu_time_ns: self.cpu_time_ns.checked_sub(start.cpu_time_ns)?,
+            ctx_switches: self.ctx_switches.checked_sub(start.ctx_switches)?,
+        })
+    }
+}
+
+/// Start sample for a new span, or `None` while disabled.
+pub(super) fn start_sample() -> Option<ThreadCpu> {
+    if cpu_time_enabled() {
+        ThreadCpu::sample()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::Span;
+    use std::sync::Mutex;
+
+    /// Serializes tests that flip the global switch.
+    static SWITCH: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn deltas_subtract_and_reject_other_threads() {
+        let start = ThreadCpu {
+            cpu_time_ns: 5_000,
+            ctx_switches: 3,
+        };
+        let end = ThreadCpu {
+            cpu_time_ns: 12_000,
+            ctx_switches: 10,
+        };
+        assert_eq!(
+            end.since(start),
+            Some(ThreadCpu {
+                cpu_time_ns: 7_000,
+                ctx_switches: 7
+            })
+        );
+        assert_eq!(start.since(end), None);
+    }
+
+    #[test]
+    fn spans_carry_no_cpu_figures_by_default() {
+        let _switch = SWITCH.lock().unwrap();
+        assert!(!cpu_time_enabled());
+        let mut span = Span::new_root("idle", None, None);
+        span.finish();
+        assert_eq!((span.cpu_time_ns, span.ctx_switches), (None, None));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn enabled_spans_record_thread_cpu_time() {
+        let _switch = SWITCH.lock().unwrap();
+        set_cpu_time_enabled(true);
+        let mut span = Span::new_root("busy", None, None);
+        set_cpu_time_enabled(false);
+        let spin = std::time::Instant::now();
+        let mut x = 0u64;
+        while spin.elapsed() < std::time::Duration::from_millis(20) {
+            x = std::hint::black_box(x.wrapping_add(1));
+        }
+        span.finish();
+        let cpu = span.cpu_time_ns.expect("sampled at start");
+        let wall = span.duration().unwrap().as_nanos() as u64;
+        assert!(
+            cpu > 0 && cpu <= wall + 10_000_000,
+            "cpu {cpu}ns, wall {wall}ns"
+        );
+        assert!(span.ctx_switches.is_some());
+    }
+}
@@ -0,0 +1,372 @@
+//! Newline-delimited JSON export of raw trace events.
+//!
+//! `probing.trace.file_sink=/path/prefix` appends one JSON object per line to
+//! `/path/prefix.jsonl` for every span start, span end and span event:
+//!
+//! ```text
+//! {"type":"span_start","time":…,"trace_id":…,"span_id":…,"parent_id":…,"name":"step",…}
+//! {"type":"event","time":…,"trace_id":…,"span_id":…,"name":"loss","attrs":{"value":0.5}}
+//! {"type":"span_end","time":…,"trace_id":…,"span_id":…,"name":"step","duration_ns":…,"status":"ok",…}
+//! ```
+//!
+//! Times are nanoseconds since the Unix epoch. When a write would take the
+//! file past `probing.trace.file_max_bytes` it is renamed to
+//! `prefix.jsonl.1` (older files shift to `.2`, `.3`, …) and a new one is
+//! started; `probing.trace.file_max_files` rotated files are kept.
+//!
+//! Lines are written on the traced thread into a buffer, so a line reaches
+//! the file once the buffer fills, on rotation, on [`flush_file_sink`]
+//! (called at process exit and by `probing <pid> trace flush`), or when the
+//! sink is reconfigured. A write error disables the sink.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde_json::{json, Map, Value};
+
+use super::span::{Attribute, Ele, Event, Location, Span, SpanStatus};
+
+/// File size that triggers rotation unless `file_max_bytes` says otherwise.
+pub const DEFAULT_MAX_BYTES: u64 = 64 << 20;
+/// Rotated files kept unless `file_max_files` says otherwise.
+pub const DEFAULT_MAX_FILES: usize = 5;
+const BUFFER_BYTES: usize = 64 << 10;
+
+static SINK: Mutex<Option<FileSink>> = Mutex::new(None);
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSinkConfig {
+    /// Files are `<prefix>.jsonl` and `<prefix>.jsonl.<n>`.
+    pub prefix: PathBuf,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl FileSinkConfig {
+    pub fn new(prefix: impl Into<PathBuf>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+
+    /// The file being written.
+    pub fn current_path(&self) -> PathBuf {
+        self.rotated_path(0)
+    }
+
+    /// `0` is the current file, `n` the n-th most recent rotated one.
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.prefix.clone().into_os_string();
+        name.push(".jsonl");
+        if n > 0 {
+            name.push(format!(".{n}"));
+        }
+        PathBuf::from(name)
+    }
+}
+
+struct FileSink {
+    config: FileSinkConfig,
+    writer: BufWriter<File>,
+    /// Bytes in the current file, including what is still buffered.
+    written: u64,
+}
+
+impl FileSink {
+    fn open(config: FileSinkConfig) -> io::Result<Self> {
+        if let Some(dir) = config.prefix.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = open_append(&config.current_path())?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            config,
+            writer: BufWriter::with_capacity(BUFFER_BYTES, file),
+            written,
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.written > 0 && self.written + len > self.config.max_bytes {
+            self.rotate()?;
+        }
+        self.writer.write_all(line)?;
+        self.writer.write_all(b"\n")?;
+        self.written += len;
+        Ok(())
+    }
+
+    /// Shift `.n` to `.n+1` (dropping the oldest), move the current file to
+    /// `.1` and start an empty one.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        let config = &self.config;
+        let current = config.current_path();
+        if config.max_files == 0 {
+            remove_if_exists(&current)?;
+        } else {
+            remove_if_exists(&config.rotated_path(config.max_files))?;
+            for n in (1..config.max_files).rev() {
+                let from = config.rotated_path(n);
+                if from.exists() {
+                    std::fs::rename(&from, config.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&current, config.rotated_path(1))?;
+        }
+        self.writer = BufWriter::with_capacity(BUFFER_BYTES, open_append(&current)?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<FileSink>> {
+    SINK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start writing to `config.prefix`, or stop with `None`. The previous sink,
+/// if any, is flushed first.
+pub fn configure_file_sink(config: Option<FileSinkConfig>) -> io::Result<()> {
+    let mut sink = lock();
+    if let Some(mut previous) = sink.take() {
+        ENABLED.store(false, Ordering::Release);
+        if let Err(e) = previous.writer.flush() {
+            log::warn!("trace file sink: final flush failed: {e}");
+        }
+    }
+    if let Some(config) = config {
+        *sink = Some(FileSink::open(config)?);
+        ENABLED.store(true, Ordering::Release);
+    }
+    Ok(())
+}
+
+/// Write buffered lines to disk. Returns the current file, or `None` when no
+/// sink is configured.
+pub fn flush_file_sink() -> io::Result<Option<PathBuf>> {
+    let mut sink = lock();
+    match sink.as_mut() {
+        Some(s) => {
+            s.writer.flush()?;
+            Ok(Some(s.config.current_path()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn write(line: Value) {
+    let mut sink = lock();
+    let Some(s) = sink.as_mut() else {
+        return;
+    };
+    let bytes = line.to_string();
+    if let Err(e) = s.write_line(bytes.as_bytes()) {
+        log::warn!(
+            "trace file sink {} disabled after write error: {e}",
+            s.config.current_path().display()
+        );
+        ENABLED.store(false, Ordering::Release);
+        *sink = None;
+    }
+}
+
+pub(crate) fn record_span_start(span: &Span) {
+    if !ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+    let location = match &span.loc {
+        Some(Location::UnknownLocation(path)) => json!(path),
+        Some(Location::KnownLocation(id)) => json!(id),
+        None => Value::Null,
+    };
+    write(json!({
+        "type": "span_start",
+        "time": span.start.0 as u64,
+        "trace_id": span.trace_id,
+        "span_id": span.span_id,
+        "parent_id": span.parent_id,
+        "name": span.name,
+        "phase": span.phase,
+        "location": location,
+        "thread_id": span.thread_id,
+        "thread_name": span.thread_name,
+    }));
+}
+
+pub(crate) fn record_event(span: &Span, event: &Event) {
+    if !ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+    write(json!({
+        "type": "event",
+        "time": event.timestamp.0 as u64,
+        "trace_id": span.trace_id,
+        "span_id": span.span_id,
+        "name": event.name,
+        "attrs": attrs_json(&event.attributes),
+    }));
+}
+
+pub(crate) fn record_span_end(span: &Span) {
+    if !ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+    let Some(end) = span.end else {
+        return;
+    };
+    let (status, error) = match span.status() {
+        SpanStatus::Error(msg) => ("error", Some(msg)),
+        _ => ("ok", None),
+    };
+    let links: Vec<Value> = span
+        .links
+        .iter()
+        .map(|l| json!({"trace_id": l.trace_id, "span_id": l.span_id}))
+        .collect();
+    write(json!({
+        "type": "span_end",
+        "time": end.0 as u64,
+        "trace_id": span.trace_id,
+        "span_id": span.span_id,
+        "name": span.name,
+        "duration_ns": end.duration_since(span.start).as_nanos() as u64,
+        "status": status,
+        "error": error,
+        "attrs": attrs_json(&span.attrs),
+        "links": links,
+        "cpu_time_ns": span.cpu_time_ns,
+        "ctx_switches": span.ctx_switches,
+    }));
+}
+
+/// Span or event attributes as a JSON object keyed by attribute name.
+pub fn attrs_json(attrs: &[Attribute]) -> Value {
+    let map: Map<String, Value> = attrs
+        .iter()
+        .map(|a| (a.key().to_string(), ele_json(a.value())))
+        .collect();
+    Value::Object(map)
+}
+
+fn ele_json(value: &Ele) -> Value {
+    match value {
+        Ele::Nil => Value::Null,
+        Ele::BOOL(b) => json!(b),
+        Ele::I32(x) => json!(x),
+        Ele::I64(x) => json!(x),
+        // Non-finite floats have no JSON form and become null.
+        Ele::F32(x) => json!(x),
+        Ele::F64(x) => json!(x),
+        Ele::Text(s) | Ele::Url(s) => json!(s),
+        Ele::DataTime(t) => json!(t),
+        Ele::DateTime(t) => json!(t),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink(dir: &Path, max_bytes: u64, max_files: usize) -> FileSink {
+        FileSink::open(FileSinkConfig {
+            prefix: dir.join("sub/trace"),
+            max_bytes,
+            max_files,
+        })
+        .unwrap()
+    }
+
+    fn lines(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut s = sink(dir.path(), 20, 2);
+        for i in 0..5 {
+            // 10 bytes per line with the newline: two lines per file.
+            s.write_line(format!("line-{i:04}").as_bytes()).unwrap();
+        }
+        s.writer.flush().unwrap();
+        let config = s.config.clone();
+        assert_eq!(lines(&config.current_path()), ["line-0004"]);
+        assert_eq!(lines(&config.rotated_path(1)), ["line-0002", "line-0003"]);
+        assert_eq!(lines(&config.rotated_path(2)), ["line-0000", "line-0001"]);
+        assert!(!config.rotated_path(3).exists());
+
+        s.write_line(b"line-0005").unwrap();
+        s.write_line(b"line-0006").unwrap();
+        s.writer.flush().unwrap();
+        assert_eq!(lines(&config.rotated_path(2)), ["line-0002", "line-0003"]);
+        assert!(!config.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn reopening_appends_and_counts_existing_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut s = sink(dir.path(), 1 << 20, 1);
+        s.write_line(b"first").unwrap();
+        s.writer.flush().unwrap();
+        drop(s);
+        let mut s = sink(dir.path(), 1 << 20, 1);
+        assert_eq!(s.written, 6);
+        s.write_line(b"second").unwrap();
+        s.writer.flush().unwrap();
+        assert_eq!(lines(&s.config.current_path()), ["first", "second"]);
+    }
+
+    #[test]
+    fn spans_and_events_are_written_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FileSinkConfig::new(dir.path().join("spans"));
+        let path = config.current_path();
+        configure_file_sink(Some(config)).unwrap();
+
+        let mut span = Span::new_root("file_sink_test", Some("fwd"), None);
+        span.add_event("loss", Some(vec![crate::trace::attr("value", 0.5)]))
+            .unwrap();
+        span.add_attr("ok", true).unwrap();
+        span.finish();
+        assert_eq!(flush_file_sink().unwrap(), Some(path.clone()));
+        configure_file_sink(None).unwrap();
+        assert_eq!(flush_file_sink().unwrap(), None);
+
+        // Spans from concurrently running tests may be interleaved.
+        let ours: Vec<Value> = lines(&path)
+            .iter()
+            .map(|l| serde_json::from_str::<Value>(l).unwrap())
+            .filter(|v| v["span_id"] == json!(span.span_id))
+            .collect();
+        let types: Vec<&str> = ours.iter().map(|v| v["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["span_start", "event", "span_end"]);
+        assert_eq!(ours[0]["phase"], "fwd");
+        assert_eq!(ours[0]["parent_id"], Value::Null);
+        assert_eq!(ours[1]["attrs"]["value"], 0.5);
+        assert_eq!(ours[2]["status"], "ok");
+        assert_eq!(ours[2]["attrs"]["ok"], true);
+        assert!(ours[2]["duration_ns"].as_u64().is_some());
+    }
+}
@@ -0,0 +1,174 @@
+//! Span trees folded into flamegraph stacks.
+//!
+//! [`fold_span_tree`] turns span trees into folded `"root;child;leaf ns"`
+//! lines: one line per distinct stack of span names, valued by the summed
+//! self time of the spans on that stack. Self time is a span's duration minus
+//! the part covered by its children, with children clipped to the span and
+//! overlapping children (work run concurrently) counted once, so it is never
+//! negative. Unfinished spans have no duration and contribute no self time;
+//! their finished children are still folded under them.
+
+use std::collections::BTreeMap;
+
+use super::tree::SpanNode;
+
+/// Folded stack lines, sorted by stack; stacks with zero self time are left out.
+pub fn fold_span_tree(roots: &[SpanNode]) -> Vec<String> {
+    let mut stacks = BTreeMap::new();
+    let mut path = Vec::new();
+    for root in roots {
+        fold_span(root, &mut path, &mut stacks);
+    }
+    stacks
+        .into_iter()
+        .filter(|(_, ns)| *ns > 0)
+        .map(|(stack, ns)| format!("{stack} {ns}"))
+        .collect()
+}
+
+fn fold_span(span: &SpanNode, path: &mut Vec<String>, stacks: &mut BTreeMap<String, u64>) {
+    path.push(frame_name(&span.name));
+    *stacks.entry(path.join(";")).or_default() += self_time(span);
+    for child in &span.children {
+        fold_span(child, path, stacks);
+    }
+    path.pop();
+}
+
+/// `;` separates frames and the last space the count, so neither may stay
+/// in a name.
+fn frame_name(name: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        return "(unnamed)".to_string();
+    }
+    name.replace(';', ":").replace(char::is_whitespace, "_")
+}
+
+/// Duration of `span` not covered by any of its children, in ns.
+pub fn self_time(span: &SpanNode) -> u64 {
+    let (start, Some(end)) = (span.start_timestamp, span.end_timestamp) else {
+        return 0;
+    };
+    if end <= start {
+        return 0;
+    }
+    let mut covered: Vec<(i64, i64)> = span
+        .children
+        .iter()
+        .filter_map(|c| {
+            let (s, e) = (c.start_timestamp.max(start), c.end_timestamp?.min(end));
+            (s < e).then_some((s, e))
+        })
+        .collect();
+    covered.sort_unstable();
+    let mut busy = 0i64;
+    let mut reach = start;
+    for (s, e) in covered {
+        if e > reach {
+            busy += e - s.max(reach);
+            reach = e;
+        }
+    }
+    (end - start - busy) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(name: &str, start: i64, end: Option<i64>, children: Vec<SpanNode>) -> SpanNode {
+        SpanNode {
+            span_id: start,
+            trace_id: 1,
+            parent_id: None,
+            name: name.to_string(),
+            start_timestamp: start,
+            end_timestamp: end,
+            thread_id: 1,
+            phase: None,
+            location: None,
+            attributes: None,
+            cpu_time_ns: None,
+            ctx_switches: None,
+            links: None,
+            children,
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn folds_self_time_per_stack() {
+        // step [0, 100): forward [10, 40), backward [50, 90) with a nested
+        // allreduce [60, 70); a second step [200, 230) with one forward.
+        let tree = vec![
+            span(
+                "step",
+                0,
+                Some(100),
+                vec![
+                    span("forward", 10, Some(40), vec![]),
+                    span(
+                        "backward",
+                        50,
+                        Some(90),
+                        vec![span("allreduce", 60, Some(70), vec![])],
+                    ),
+                ],
+            ),
+            span(
+                "step",
+                200,
+                Some(230),
+                vec![span("forward", 205, Some(225), vec![])],
+            ),
+        ];
+        assert_eq!(
+            fold_span_tree(&tree),
+            [
+                "step 40",
+                "step;backward 30",
+                "step;backward;allreduce 10",
+                "step;forward 50",
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_children_are_counted_once() {
+        // Two concurrent loads cover [10, 80) together; `late` sticks out of
+        // the parent and is clipped to [90, 100), leaving [0, 10) and [80, 90).
+        let parent = span(
+            "fetch",
+            0,
+            Some(100),
+            vec![
+                span("load", 10, Some(60), vec![]),
+                span("load", 30, Some(80), vec![]),
+                span("late", 90, Some(150), vec![]),
+            ],
+        );
+        assert_eq!(self_time(&parent), 20);
+        let mut nested = parent.clone();
+        nested.children = vec![span("all", -50, Some(500), vec![])];
+        assert_eq!(self_time(&nested), 0);
+        assert_eq!(
+            fold_span_tree(&[parent]),
+            ["fetch 20", "fetch;late 60", "fetch;load 100"]
+        );
+    }
+
+    #[test]
+    fn unfinished_spans_and_odd_names() {
+        let tree = vec![span(
+            "serve batch",
+            0,
+            None,
+            vec![
+                span("a;b", 10, Some(15), vec![]),
+                span("", 20, None, vec![]),
+            ],
+        )];
+        assert_eq!(fold_span_tree(&tree), ["serve_batch;a:b 5"]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Scoped spans that cannot leak open.
+//!
+//! [`Span::enter`] starts a span and returns a [`SpanGuard`] that ends it when
+//! dropped. Each thread keeps a stack of its entered spans, so a span entered
+//! while another guard is alive becomes its child; with no guard alive it
+//! starts a new trace. A guard dropped while its thread is unwinding ends the
+//! span with [`SpanStatus::Error`].
+
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+use super::span::{Span, SpanStatus};
+
+/// Error message recorded on spans closed by a panic.
+pub const PANIC_MESSAGE: &str = "panicked";
+
+thread_local! {
+    /// `(trace_id, span_id)` of the spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<(u64, u64)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// `(trace_id, span_id)` of the innermost span entered on this thread.
+pub fn current_span_ids() -> Option<(u64, u64)> {
+    ENTERED.with(|stack| stack.borrow().last().copied())
+}
+
+impl Span {
+    /// Starts a span under the innermost entered span of this thread (or as a
+    /// new trace) and returns a guard that ends it on drop.
+    pub fn enter<N: Into<String>>(
+        name: N,
+        phase: Option<&str>,
+        location: Option<&str>,
+    ) -> SpanGuard {
+        let span = match current_span_ids() {
+            Some((trace_id, parent_id)) => {
+                Span::start(trace_id, Some(parent_id), name, phase, location)
+            }
+            None => Span::new_root(name, phase, location),
+        };
+        ENTERED.with(|stack| stack.borrow_mut().push((span.trace_id, span.span_id)));
+        SpanGuard { span: Some(span) }
+    }
+}
+
+/// Ends its span when dropped; see the module docs.
+#[derive(Debug)]
+pub struct SpanGuard {
+    span: Option<Span>,
+}
+
+impl SpanGuard {
+    /// Ends the span with `status` and returns it. `Active` is treated as
+    /// `Completed`.
+    pub fn end_with_status(mut self, status: SpanStatus) -> Span {
+        let mut span = self.span.take().expect("span guard already ended");
+        close(&mut span, status);
+        span
+    }
+
+    /// Ends the span successfully and returns it.
+    pub fn end(self) -> Span {
+        self.end_with_status(SpanStatus::Completed)
+    }
+}
+
+impl Deref for SpanGuard {
+    type Target = Span;
+
+    fn deref(&self) -> &Span {
+        self.span.as_ref().expect("span guard already ended")
+    }
+}
+
+impl DerefMut for SpanGuard {
+    fn deref_mut(&mut self) -> &mut Span {
+        self.span.as_mut().expect("span guard already ended")
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if let Some(mut span) = self.span.take() {
+            let status = if std::thread::panicking() {
+                SpanStatus::Error(PANIC_MESSAGE.to_string())
+            } else {
+                SpanStatus::Completed
+            };
+            close(&mut span, status);
+        }
+    }
+}
+
+fn close(span: &mut Span, status: SpanStatus) {
+    // Guards usually drop innermost first, but one moved out of its scope may
+    // not; remove this span wherever it sits.
+    ENTERED.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if let Some(pos) = stack.iter().rposition(|&(_, id)| id == span.span_id) {
+            stack.remove(pos);
+        }
+    });
+    match status {
+        SpanStatus::Error(msg) => span.end_error(Some(msg)),
+        SpanStatus::Active | SpanStatus::Completed => span.finish(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::span_ring;
+
+    fn closed_span(span_id: u64) -> Span {
+        span_ring()
+            .snapshot()
+            .into_iter()
+            .find(|s| s.span_id == span_id)
+            .expect("closed span in ring")
+    }
+
+    #[test]
+    fn nested_guards_set_parents_and_unwind_the_stack() {
+        assert_eq!(current_span_ids(), None);
+        let outer = Span::enter("step", Some("train"), None);
+        let (inner_id, leaf_id) = {
+            let inner = Span::enter("forward", Some("forward"), None);
+            assert_eq!(inner.parent_id, Some(outer.span_id));
+            assert_eq!(inner.trace_id, outer.trace_id);
+            let leaf = Span::enter("matmul", None, None).end();
+            assert_eq!(leaf.parent_id, Some(inner.span_id));
+            assert!(leaf.is_ended());
+            assert_eq!(
+                current_span_ids(),
+                Some((inner.trace_id, inner.span_id)),
+                "ending a guard pops it"
+            );
+            (inner.span_id, leaf.span_id)
+        };
+        assert_eq!(current_span_ids(), Some((outer.trace_id, outer.span_id)));
+        assert_eq!(closed_span(inner_id).status(), SpanStatus::Completed);
+        assert_eq!(closed_span(leaf_id).parent_id, Some(inner_id));
+
+        let sibling = Span::enter("backward", None, None);
+        assert_eq!(sibling.parent_id, Some(outer.span_id));
+        drop(sibling);
+        let outer = outer.end_with_status(SpanStatus::Error("nan loss".into()));
+        assert_eq!(outer.status(), SpanStatus::Error("nan loss".into()));
+        assert_eq!(current_span_ids(), None);
+
+        let next = Span::enter("step", None, None);
+        assert_eq!(next.parent_id, None);
+        assert_ne!(
+            next.trace_id, outer.trace_id,
+            "no guard alive starts a new trace"
+        );
+    }
+
+    #[test]
+    fn panic_inside_guard_closes_span_with_error() {
+        let mut span_ids = (0, 0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let outer = Span::enter("outer", None, None);
+            let mut inner = Span::enter("inner", None, None);
+            inner.add_attr("batch", 3i64).unwrap();
+            span_ids = (outer.span_id, inner.span_id);
+            panic!("boom");
+        }));
+        assert!(result.is_err());
+        assert_eq!(current_span_ids(), None, "unwinding pops both guards");
+
+        for span_id in [span_ids.0, span_ids.1] {
+            let span = closed_span(span_id);
+            assert!(span.is_ended());
+            assert_eq!(span.status(), SpanStatus::Error(PANIC_MESSAGE.into()));
+        }
+        assert_eq!(closed_span(span_ids.1).parent_id, Some(span_ids.0));
+    }
+}
@@ -0,0 +1,49 @@
+//! Per-span caps on events and attributes.
+//!
+//! `probing.trace.max_events_per_span` and
+//! `probing.trace.max_attributes_per_span` bound how much one span can hold,
+//! so a noisy loop cannot grow a single span without limit. Past a cap,
+//! [`Span::add_event`](super::Span::add_event) and
+//! [`Span::add_attr`](super::Span::add_attr) drop the new entry and count it
+//! in a `probing.dropped_events` / `probing.dropped_attributes` attribute,
+//! appended on the first drop and kept outside the cap. `0` means no limit.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const DEFAULT_MAX_EVENTS_PER_SPAN: usize = 1024;
+pub const DEFAULT_MAX_ATTRIBUTES_PER_SPAN: usize = 128;
+
+/// Attribute counting events dropped by the per-span cap.
+pub const DROPPED_EVENTS_ATTR: &str = "probing.dropped_events";
+/// Attribute counting attributes dropped by the per-span cap.
+pub const DROPPED_ATTRIBUTES_ATTR: &str = "probing.dropped_attributes";
+
+static MAX_EVENTS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_EVENTS_PER_SPAN);
+static MAX_ATTRIBUTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ATTRIBUTES_PER_SPAN);
+
+pub fn set_max_events_per_span(max: usize) {
+    MAX_EVENTS.store(max, Ordering::Relaxed);
+}
+
+pub fn set_max_attributes_per_span(max: usize) {
+    MAX_ATTRIBUTES.store(max, Ordering::Relaxed);
+}
+
+/// Current event cap; `None` when unlimited.
+pub fn max_events_per_span() -> Option<usize> {
+    limit(MAX_EVENTS.load(Ordering::Relaxed))
+}
+
+/// Current attribute cap; `None` when unlimited.
+pub fn max_attributes_per_span() -> Option<usize> {
+    limit(MAX_ATTRIBUTES.load(Ordering::Relaxed))
+}
+
+fn limit(max: usize) -> Option<usize> {
+    (max > 0).then_some(max)
+}
+
+/// Whether `key` is one of the drop counters (not subject to the cap).
+pub fn is_drop_counter(key: &str) -> bool {
+    key == DROPPED_EVENTS_ATTR || key == DROPPED_ATTRIBUTES_ATTR
+}
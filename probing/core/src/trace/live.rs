@@ -0,0 +1,57 @@
+//! Finished spans pushed to live subscribers (`/apis/trace/stream`).
+//!
+//! [`Span::finish`] publishes every finished span on a broadcast channel of
+//! [`LIVE_CAPACITY`] spans. Publishing never waits: without subscribers the
+//! span is not even cloned, and a subscriber that falls more than the
+//! capacity behind loses the oldest spans, reported to it as
+//! [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged).
+
+use std::sync::{Arc, LazyLock};
+
+use tokio::sync::broadcast;
+
+use super::span::Span;
+
+/// Spans a subscriber may fall behind before it starts losing them.
+pub const LIVE_CAPACITY: usize = 1024;
+
+static LIVE: LazyLock<broadcast::Sender<Arc<Span>>> =
+    LazyLock::new(|| broadcast::channel(LIVE_CAPACITY).0);
+
+/// Subscribe to spans finished from now on.
+pub fn subscribe_finished_spans() -> broadcast::Receiver<Arc<Span>> {
+    LIVE.subscribe()
+}
+
+/// Hand a finished span to the live subscribers, if any.
+pub(crate) fn publish_finished_span(span: &Span) {
+    if LIVE.receiver_count() == 0 {
+        return;
+    }
+    // Fails only when the last subscriber left meanwhile.
+    let _ = LIVE.send(Arc::new(span.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_receive_spans_finished_after_subscribing() {
+        let mut before = Span::new_root("live_before", None, None);
+        before.finish();
+
+        let mut spans = subscribe_finished_spans();
+        let mut span = Span::new_root("live_after", None, None);
+        span.finish();
+        // Finishing twice publishes once.
+        span.finish();
+
+        let mut names = Vec::new();
+        while let Ok(span) = spans.try_recv() {
+            names.push(span.name.clone());
+        }
+        let live: Vec<_> = names.iter().filter(|n| n.starts_with("live_")).collect();
+        assert_eq!(live, ["live_after"]);
+    }
+}
@@ -1,11 +1,45 @@
+pub mod autosave;
+pub mod cpu;
+pub mod file_sink;
+mod fold;
+mod guard;
+pub mod limits;
+pub mod live;
+pub mod otlp;
+pub mod propagation;
+pub mod retention;
+pub mod ring;
 mod span;
 mod step;
+mod tree;
 
-pub use span::{attr, Attribute, Ele, Event, Location, Span, SpanStatus, Timestamp};
+pub use autosave::{autosave_config, mark_exported_through, AutosaveConfig};
+pub use file_sink::{attrs_json, configure_file_sink, flush_file_sink, FileSinkConfig};
+pub use fold::{fold_span_tree, self_time};
+pub use guard::{current_span_ids, SpanGuard};
+pub use limits::{
+    max_attributes_per_span, max_events_per_span, set_max_attributes_per_span,
+    set_max_events_per_span, DROPPED_ATTRIBUTES_ATTR, DROPPED_EVENTS_ATTR,
+};
+pub use live::{subscribe_finished_spans, LIVE_CAPACITY};
+pub use otlp::{configure_otlp_export, otlp_dropped, TraceProbeExtension};
+pub use propagation::{
+    clear_remote_span_recorder, current_traceparent, record_remote_span,
+    register_remote_span_recorder, RemoteSpanRecorder, TraceParent, TRACEPARENT_HEADER,
+};
+pub use retention::{
+    evict, evicted_trace_count, eviction_state, retention_policy, select_evictions, EvictedTraces,
+    EvictionState, RetentionPolicy, TraceExtent,
+};
+pub use ring::{span_ring, RingStats, SpanRing};
+pub use span::{attr, Attribute, Ele, Event, Link, Location, Span, SpanStatus, Timestamp};
 pub use step::{
-    advance_micro_step, crash_atomic_step, crash_step_snapshot, current_micro_step,
-    set_micro_batches, step_snapshot, sync_micro_step, StepSnapshot,
+    advance_micro_step, clear_step_provider, crash_atomic_step, crash_step_snapshot,
+    current_micro_step, register_step_provider, sampled_step, sampled_step_value,
+    set_micro_batches, step_provider_active, step_snapshot, sync_micro_step,
+    training_step_provider, StepProvider, StepSnapshot, NO_STEP, STEP_COLUMN,
 };
+pub use tree::{build_span_tree, SpanEventNode, SpanNode, SpanRecord};
 
 // --- Custom Error Type ---
 
@@ -0,0 +1,981 @@
+//! OTLP/HTTP export of finished spans.
+//!
+//! Enabled with `SET probing.trace.otlp_endpoint=http://collector:4318` (or
+//! `PROBING_TRACE_OTLP_ENDPOINT`). [`Span::finish`] hands a copy of the span to
+//! a bounded queue; a background thread batches queued spans, encodes them as
+//! an `ExportTraceServiceRequest` protobuf and POSTs it to
+//! `<endpoint>/v1/traces`, retrying transient failures with backoff. The traced
+//! thread never waits on the network: when the queue is full the span is
+//! dropped and counted.
+//!
+//! Field mapping:
+//! - `trace_id` (16 bytes): a per-process salt followed by the big-endian
+//!   [`Span::trace_id`], so traces from different ranks do not collide;
+//! - `span_id` / `parent_span_id` (8 bytes): big-endian [`Span::span_id`] /
+//!   [`Span::parent_id`];
+//! - `status`: [`SpanStatus::Completed`] is `OK`, [`SpanStatus::Error`] (a span
+//!   ended through [`Span::end_error`]) is `ERROR` with the message;
+//!   unfinished spans are `UNSET`;
+//! - [`Attribute`]s and [`Event`]s keep their keys; `phase`, location and
+//!   thread id/name become `probing.phase`, `probing.location`, `thread.id`
+//!   and `thread.name`; CPU figures (see [`super::cpu`]) become
+//!   `probing.cpu_time_ns` and `probing.ctx_switches`;
+//! - [`Link`]s become span links, their trace ids salted like the span's own.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{LazyLock, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::autosave::AutosaveConfig;
+use super::file_sink::{configure_file_sink, flush_file_sink, FileSinkConfig};
+use super::span::{Attribute, Ele, Event, Link, Location, Span, SpanStatus};
+use crate::core::{EngineError, Maybe, ProbeExtension, ProbeExtensionCall, ProbeExtensionOption};
+
+const TRACES_PATH: &str = "/v1/traces";
+const SCOPE_NAME: &str = "probing";
+const SPAN_KIND_INTERNAL: u64 = 1;
+const STATUS_CODE_OK: u64 = 1;
+const STATUS_CODE_ERROR: u64 = 2;
+const ERROR_MESSAGE_ATTR: &str = "error.message";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExportError {
+    /// Worth retrying: connection failures, timeouts, HTTP 429 / 502 / 503 / 504.
+    #[error("transient export failure: {0}")]
+    Transient(String),
+    #[error("export rejected: {0}")]
+    Rejected(String),
+}
+
+/// Exporter settings; [`OtlpConfig::new`] fills in the defaults.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfig {
+    /// Collector base URL (`http://collector:4318`) or the full traces URL.
+    pub endpoint: String,
+    /// Spans per request.
+    pub batch_size: usize,
+    /// Longest time a queued span waits before its batch is sent.
+    pub flush_interval: Duration,
+    /// Spans buffered between the traced threads and the exporter thread.
+    pub queue_capacity: usize,
+    /// Retries after the first failed attempt of a batch.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after each attempt.
+    pub retry_backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl OtlpConfig {
+    pub fn new<S: Into<String>>(endpoint: S) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            batch_size: 512,
+            flush_interval: Duration::from_secs(2),
+            queue_capacity: 4096,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// `<endpoint>/v1/traces`, unless the endpoint already names that path.
+    pub fn traces_url(&self) -> String {
+        let base = self.endpoint.trim_end_matches('/');
+        if base.ends_with(TRACES_PATH) {
+            base.to_string()
+        } else {
+            format!("{base}{TRACES_PATH}")
+        }
+    }
+}
+
+/// Delivers one encoded `ExportTraceServiceRequest`.
+pub trait OtlpTransport: Send + 'static {
+    fn send(&self, body: &[u8]) -> Result<(), ExportError>;
+}
+
+/// Protobuf over HTTP POST.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    url: String,
+    timeout: Duration,
+}
+
+impl HttpTransport {
+    pub fn new(config: &OtlpConfig) -> Self {
+        Self {
+            url: config.traces_url(),
+            timeout: config.timeout,
+        }
+    }
+}
+
+impl OtlpTransport for HttpTransport {
+    fn send(&self, body: &[u8]) -> Result<(), ExportError> {
+        let result = ureq::post(&self.url)
+            .header("Content-Type", "application/x-protobuf")
+            .config()
+            .timeout_global(Some(self.timeout))
+            .build()
+            .send(body);
+        match result {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::StatusCode(code @ (429 | 502 | 503 | 504))) => {
+                Err(ExportError::Transient(format!("HTTP {code}")))
+            }
+            Err(ureq::Error::StatusCode(code)) => {
+                Err(ExportError::Rejected(format!("HTTP {code}")))
+            }
+            Err(e) => Err(ExportError::Transient(e.to_string())),
+        }
+    }
+}
+
+/// Background batching exporter. Dropping it stops accepting spans; the
+/// worker sends what is still queued and exits.
+pub struct OtlpExporter {
+    tx: Option<SyncSender<Span>>,
+    worker: Option<JoinHandle<()>>,
+    dropped: AtomicU64,
+}
+
+impl OtlpExporter {
+    /// Export over HTTP to [`OtlpConfig::endpoint`].
+    pub fn start(config: OtlpConfig) -> std::io::Result<Self> {
+        let transport = HttpTransport::new(&config);
+        Self::with_transport(config, transport)
+    }
+
+    pub fn with_transport<T: OtlpTransport>(
+        config: OtlpConfig,
+        transport: T,
+    ) -> std::io::Result<Self> {
+        let (tx, rx) = mpsc::sync_channel(config.queue_capacity.max(1));
+        let resource = resource_attributes();
+        let worker = std::thread::Builder::new()
+            .name("probing-otlp".into())
+            .spawn(move || {
+                let mut batch: Vec<Span> = Vec::with_capacity(config.batch_size);
+                let mut deadline = Instant::now() + config.flush_interval;
+                loop {
+                    let wait = deadline.saturating_duration_since(Instant::now());
+                    let done = match rx.recv_timeout(wait) {
+                        Ok(span) => {
+                            batch.push(span);
+                            if batch.len() < config.batch_size.max(1) {
+                                continue;
+                            }
+                            false
+                        }
+                        Err(RecvTimeoutError::Timeout) => false,
+                        Err(RecvTimeoutError::Disconnected) => true,
+                    };
+                    if !batch.is_empty() {
+                        let body = encode_export_request(&resource, &batch);
+                        send_with_retry(&transport, &body, &config, batch.len());
+                        batch.clear();
+                    }
+                    if done {
+                        break;
+                    }
+                    deadline = Instant::now() + config.flush_interval;
+                }
+            })?;
+        Ok(Self {
+            tx: Some(tx),
+            worker: Some(worker),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Queue a finished span; returns `false` (and counts it as dropped) when
+    /// the queue is full. Never blocks.
+    pub fn export(&self, span: Span) -> bool {
+        let Some(tx) = &self.tx else {
+            return false;
+        };
+        match tx.try_send(span) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Spans rejected because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Send everything still queued and wait for the worker to finish.
+    pub fn shutdown(mut self) {
+        self.tx.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn send_with_retry<T: OtlpTransport>(
+    transport: &T,
+    body: &[u8],
+    config: &OtlpConfig,
+    spans: usize,
+) {
+    let mut backoff = config.retry_backoff;
+    for attempt in 0..=config.max_retries {
+        match transport.send(body) {
+            Ok(()) => return,
+            Err(ExportError::Transient(e)) if attempt < config.max_retries => {
+                log::debug!("OTLP export attempt {} failed: {e}", attempt + 1);
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+            }
+            Err(e) => {
+                log::warn!("OTLP export dropped {spans} spans: {e}");
+                return;
+            }
+        }
+    }
+}
+
+static EXPORTER: LazyLock<RwLock<Option<OtlpExporter>>> = LazyLock::new(|| RwLock::new(None));
+static EXPORT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Start exporting finished spans to `endpoint`, or stop with `None`.
+pub fn configure_otlp_export(endpoint: Option<&str>) -> std::io::Result<()> {
+    let exporter = endpoint
+        .map(|endpoint| OtlpExporter::start(OtlpConfig::new(endpoint)))
+        .transpose()?;
+    EXPORT_ENABLED.store(exporter.is_some(), Ordering::Release);
+    let previous = match EXPORTER.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, exporter),
+        Err(poisoned) => std::mem::replace(&mut *poisoned.into_inner(), exporter),
+    };
+    // The old worker drains its queue on its own thread.
+    drop(previous);
+    Ok(())
+}
+
+/// Spans the configured exporter dropped on a full queue (0 without one).
+pub fn otlp_dropped() -> u64 {
+    EXPORTER
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(OtlpExporter::dropped))
+        .unwrap_or(0)
+}
+
+/// Hand a finished span to the configured exporter, if any.
+pub(crate) fn export_finished_span(span: &Span) {
+    if !EXPORT_ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+    if let Ok(guard) = EXPORTER.read() {
+        if let Some(exporter) = guard.as_ref() {
+            exporter.export(span.clone());
+        }
+    }
+}
+
+// --- Protobuf encoding (opentelemetry/proto/collector/trace/v1) ---
+
+#[derive(Default)]
+struct ProtoWriter(Vec<u8>);
+
+impl ProtoWriter {
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    fn tag(&mut self, field: u32, wire_type: u8) {
+        self.varint((u64::from(field) << 3) | u64::from(wire_type));
+    }
+
+    fn uint64(&mut self, field: u32, v: u64) {
+        self.tag(field, 0);
+        self.varint(v);
+    }
+
+    fn fixed64(&mut self, field: u32, v: u64) {
+        self.tag(field, 1);
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, field: u32, v: &[u8]) {
+        self.tag(field, 2);
+        self.varint(v.len() as u64);
+        self.0.extend_from_slice(v);
+    }
+
+    fn message(&mut self, field: u32, build: impl FnOnce(&mut ProtoWriter)) {
+        let mut inner = ProtoWriter::default();
+        build(&mut inner);
+        self.bytes(field, &inner.0);
+    }
+}
+
+/// `KeyValue { key = 1; AnyValue value = 2 }`; NULL values are sent as an
+/// empty `AnyValue`.
+fn write_attribute(w: &mut ProtoWriter, field: u32, key: &str, value: &Ele) {
+    w.message(field, |kv| {
+        kv.bytes(1, key.as_bytes());
+        kv.message(2, |any| match value {
+            Ele::Nil => {}
+            Ele::BOOL(b) => any.uint64(2, u64::from(*b)),
+            Ele::I32(x) => any.uint64(3, i64::from(*x) as u64),
+            Ele::I64(x) => any.uint64(3, *x as u64),
+            Ele::F32(x) => any.fixed64(4, f64::from(*x).to_bits()),
+            Ele::F64(x) => any.fixed64(4, x.to_bits()),
+            Ele::Text(s) | Ele::Url(s) => any.bytes(1, s.as_bytes()),
+            Ele::DataTime(t) => any.uint64(3, *t),
+            Ele::DateTime(t) => any.uint64(3, *t as u64),
+        });
+    });
+}
+
+fn process_salt() -> u64 {
+    static SALT: LazyLock<u64> = LazyLock::new(|| {
+        let nanos = super::Timestamp::now().0 as u64;
+        // splitmix64 over pid and start time.
+        let mut z = nanos ^ (u64::from(std::process::id()) << 32);
+        z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    });
+    *SALT
+}
+
+fn otlp_trace_id(trace_id: u64) -> [u8; 16] {
+    let mut id = [0u8; 16];
+    id[..8].copy_from_slice(&process_salt().to_be_bytes());
+    id[8..].copy_from_slice(&trace_id.to_be_bytes());
+    id
+}
+
+/// OTLP status code and message for a span.
+fn otlp_status(span: &Span) -> (u64, Option<&str>) {
+    if span.status() == SpanStatus::Active {
+        return (0, None);
+    }
+    let error = span.attrs.iter().find(|a| a.key() == ERROR_MESSAGE_ATTR);
+    match error.map(Attribute::value) {
+        Some(Ele::Text(msg)) => (STATUS_CODE_ERROR, Some(msg.as_str())),
+        Some(_) => (STATUS_CODE_ERROR, None),
+        None => (STATUS_CODE_OK, None),
+    }
+}
+
+fn write_event(w: &mut ProtoWriter, event: &Event) {
+    w.message(11, |e| {
+        e.fixed64(1, event.timestamp.0 as u64);
+        e.bytes(2, event.name.as_bytes());
+        for attr in &event.attributes {
+            write_attribute(e, 3, attr.key(), attr.value());
+        }
+    });
+}
+
+fn write_link(w: &mut ProtoWriter, link: &Link) {
+    w.message(13, |l| {
+        l.bytes(1, &otlp_trace_id(link.trace_id));
+        l.bytes(2, &link.span_id.to_be_bytes());
+        for attr in &link.attributes {
+            write_attribute(l, 4, attr.key(), attr.value());
+        }
+    });
+}
+
+fn write_span(w: &mut ProtoWriter, span: &Span) {
+    w.message(2, |s| {
+        s.bytes(1, &otlp_trace_id(span.trace_id));
+        s.bytes(2, &span.span_id.to_be_bytes());
+        if let Some(parent) = span.parent_id {
+            s.bytes(4, &parent.to_be_bytes());
+        }
+        s.bytes(5, span.name.as_bytes());
+        s.uint64(6, SPAN_KIND_INTERNAL);
+        s.fixed64(7, span.start.0 as u64);
+        if let Some(end) = span.end {
+            s.fixed64(8, end.0 as u64);
+        }
+        for attr in &span.attrs {
+            write_attribute(s, 9, attr.key(), attr.value());
+        }
+        if let Some(phase) = &span.phase {
+            write_attribute(s, 9, "probing.phase", &Ele::Text(phase.clone()));
+        }
+        let location = match &span.loc {
+            Some(Location::UnknownLocation(path)) => Some(Ele::Text(path.clone())),
+            Some(Location::KnownLocation(id)) => Some(Ele::I64(*id as i64)),
+            None => None,
+        };
+        if let Some(location) = location {
+            write_attribute(s, 9, "probing.location", &location);
+        }
+        write_attribute(s, 9, "thread.id", &Ele::I64(span.thread_id as i64));
+        if let Some(thread_name) = &span.thread_name {
+            write_attribute(s, 9, "thread.name", &Ele::Text(thread_name.clone()));
+        }
+        if let Some(cpu_time_ns) = span.cpu_time_ns {
+            write_attribute(s, 9, "probing.cpu_time_ns", &Ele::I64(cpu_time_ns as i64));
+        }
+        if let Some(ctx_switches) = span.ctx_switches {
+            write_attribute(s, 9, "probing.ctx_switches", &Ele::I64(ctx_switches as i64));
+        }
+        for event in &span.events {
+            write_event(s, event);
+        }
+        for link in &span.links {
+            write_link(s, link);
+        }
+        let (code, message) = otlp_status(span);
+        if code != 0 {
+            s.message(15, |st| {
+                if let Some(message) = message {
+                    st.bytes(2, message.as_bytes());
+                }
+                st.uint64(3, code);
+            });
+        }
+    });
+}
+
+/// `service.name` (from `OTEL_SERVICE_NAME`, default `probing`), `process.pid`
+/// and, when launched by torchrun / SLURM, `probing.rank`.
+fn resource_attributes() -> Vec<Attribute> {
+    let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| SCOPE_NAME.to_string());
+    let mut attrs = vec![
+        super::attr("service.name", service),
+        super::attr("process.pid", i64::from(std::process::id())),
+    ];
+    let rank = ["RANK", "SLURM_PROCID"]
+        .iter()
+        .find_map(|k| std::env::var(k).ok()?.parse::<i64>().ok());
+    if let Some(rank) = rank {
+        attrs.push(super::attr("probing.rank", rank));
+    }
+    attrs
+}
+
+/// One `ExportTraceServiceRequest` holding a single resource and scope.
+pub fn encode_export_request(resource: &[Attribute], spans: &[Span]) -> Vec<u8> {
+    let mut w = ProtoWriter::default();
+    w.message(1, |rs| {
+        rs.message(1, |r| {
+            for attr in resource {
+                write_attribute(r, 1, attr.key(), attr.value());
+            }
+        });
+        rs.message(2, |ss| {
+            ss.message(1, |scope| {
+                scope.bytes(1, SCOPE_NAME.as_bytes());
+                scope.bytes(2, env!("CARGO_PKG_VERSION").as_bytes());
+            });
+            for span in spans {
+                write_span(ss, span);
+            }
+        });
+    });
+    w.0
+}
+
+// --- `probing.trace.*` options ---
+
+#[derive(Debug, Default, ProbeExtension)]
+pub struct TraceProbeExtension {
+    /// OTLP/HTTP collector for finished spans, e.g. http://collector:4318 (empty disables export)
+    #[option(aliases = ["otlp.endpoint"])]
+    otlp_endpoint: Maybe<String>,
+    /// Events kept in the closed-span ring (oldest spans dropped first; 0 disables it)
+    #[option(aliases = ["max.events"], min = 0)]
+    max_events: Maybe<i64>,
+    /// Events kept per span; later ones are counted in probing.dropped_events (default 1024, 0: no limit)
+    #[option(aliases = ["max.events.per.span"], min = 0)]
+    max_events_per_span: Maybe<i64>,
+    /// Attributes kept per span; later ones are counted in probing.dropped_attributes (default 128, 0: no limit)
+    #[option(aliases = ["max.attributes.per.span"], min = 0)]
+    max_attributes_per_span: Maybe<i64>,
+    /// Record per-span thread CPU time and context switches: "on" or "off" (default)
+    #[option(aliases = ["cpu.time"])]
+    cpu_time: Maybe<bool>,
+    /// Evict finished traces whose last span ended this many seconds ago (0 or unset keeps them)
+    #[option(aliases = ["retention.seconds"], min = 0)]
+    retention_seconds: Maybe<i64>,
+    /// Keep at most this many finished traces, evicting the oldest (0 or unset: no limit)
+    #[option(aliases = ["max.traces"], min = 0)]
+    max_traces: Maybe<i64>,
+    /// Export new trace rows periodically, e.g. "60s,/path/dir[,segments=N][,size=512M]" (unset disables)
+    #[option]
+    autosave: Maybe<String>,
+    /// Append span starts, ends and events as JSON lines to <prefix>.jsonl (unset disables)
+    #[option]
+    file_sink: Maybe<String>,
+    /// Rotate the file sink once its file would exceed this many bytes (default 64 MiB)
+    #[option(min = 1)]
+    file_max_bytes: Maybe<i64>,
+    /// Rotated file-sink files to keep (default 5)
+    #[option(min = 0)]
+    file_max_files: Maybe<i64>,
+}
+
+impl TraceProbeExtension {
+    fn set_otlp_endpoint(&mut self, endpoint: Maybe<String>) -> Result<(), EngineError> {
+        let invalid = |reason: String| {
+            EngineError::InvalidOptionValue(Self::OPTION_OTLP_ENDPOINT.to_string(), reason)
+        };
+        let target = match &endpoint {
+            Maybe::Just(url) => {
+                let parsed = url::Url::parse(url).map_err(|e| invalid(e.to_string()))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(invalid(format!("unsupported scheme `{}`", parsed.scheme())));
+                }
+                Some(url.as_str())
+            }
+            Maybe::Nothing => None,
+        };
+        configure_otlp_export(target).map_err(|e| invalid(e.to_string()))?;
+        self.otlp_endpoint = endpoint;
+        Ok(())
+    }
+
+    fn set_max_events(&mut self, max_events: Maybe<i64>) -> Result<(), EngineError> {
+        let capacity = match max_events {
+            Maybe::Just(n) => n as usize,
+            Maybe::Nothing => super::ring::DEFAULT_MAX_EVENTS,
+        };
+        super::ring::span_ring().set_capacity(capacity);
+        self.max_events = max_events;
+        Ok(())
+    }
+
+    fn set_max_events_per_span(&mut self, max: Maybe<i64>) -> Result<(), EngineError> {
+        let value = match max {
+            Maybe::Just(n) => n as usize,
+            Maybe::Nothing => super::limits::DEFAULT_MAX_EVENTS_PER_SPAN,
+        };
+        super::limits::set_max_events_per_span(value);
+        self.max_events_per_span = max;
+        Ok(())
+    }
+
+    fn set_max_attributes_per_span(&mut self, max: Maybe<i64>) -> Result<(), EngineError> {
+        let value = match max {
+            Maybe::Just(n) => n as usize,
+            Maybe::Nothing => super::limits::DEFAULT_MAX_ATTRIBUTES_PER_SPAN,
+        };
+        super::limits::set_max_attributes_per_span(value);
+        self.max_attributes_per_span = max;
+        Ok(())
+    }
+
+    fn set_cpu_time(&mut self, cpu_time: Maybe<bool>) -> Result<(), EngineError> {
+        super::cpu::set_cpu_time_enabled(matches!(cpu_time, Maybe::Just(true)));
+        self.cpu_time = cpu_time;
+        Ok(())
+    }
+
+    /// Integer option value, at least 0 by its `min`; unset means 0.
+    fn or_zero(value: &Maybe<i64>) -> u64 {
+        match value {
+            Maybe::Just(n) => *n as u64,
+            Maybe::Nothing => 0,
+        }
+    }
+
+    fn set_retention_seconds(&mut self, secs: Maybe<i64>) -> Result<(), EngineError> {
+        let value = Self::or_zero(&secs);
+        super::retention::set_retention_seconds(value);
+        self.retention_seconds = secs;
+        Ok(())
+    }
+
+    fn set_max_traces(&mut self, max_traces: Maybe<i64>) -> Result<(), EngineError> {
+        let value = Self::or_zero(&max_traces);
+        super::retention::set_max_traces(value);
+        self.max_traces = max_traces;
+        Ok(())
+    }
+
+    fn set_autosave(&mut self, autosave: Maybe<String>) -> Result<(), EngineError> {
+        let config = match &autosave {
+            Maybe::Just(spec) => Some(AutosaveConfig::parse(spec).map_err(|reason| {
+                EngineError::InvalidOptionValue(Self::OPTION_AUTOSAVE.to_string(), reason)
+            })?),
+            Maybe::Nothing => None,
+        };
+        super::autosave::set_autosave(config);
+        self.autosave = autosave;
+        Ok(())
+    }
+
+    fn set_file_sink(&mut self, file_sink: Maybe<String>) -> Result<(), EngineError> {
+        if matches!(&file_sink, Maybe::Just(p) if p.trim().is_empty()) {
+            return Err(EngineError::InvalidOptionValue(
+                Self::OPTION_FILE_SINK.to_string(),
+                "empty path prefix".to_string(),
+            ));
+        }
+        self.file_sink = file_sink;
+        self.apply_file_sink(Self::OPTION_FILE_SINK)
+    }
+
+    fn set_file_max_bytes(&mut self, max_bytes: Maybe<i64>) -> Result<(), EngineError> {
+        self.file_max_bytes = max_bytes;
+        self.apply_file_sink(Self::OPTION_FILE_MAX_BYTES)
+    }
+
+    fn set_file_max_files(&mut self, max_files: Maybe<i64>) -> Result<(), EngineError> {
+        self.file_max_files = max_files;
+        self.apply_file_sink(Self::OPTION_FILE_MAX_FILES)
+    }
+
+    /// (Re)open the file sink from the three `file_*` options. If the file
+    /// cannot be opened the sink stays off and `file_sink` is unset.
+    fn apply_file_sink(&mut self, option: &str) -> Result<(), EngineError> {
+        let config = match &self.file_sink {
+            Maybe::Just(prefix) => {
+                let mut config = FileSinkConfig::new(prefix.trim());
+                if let Maybe::Just(n) = self.file_max_bytes {
+                    config.max_bytes = n as u64;
+                }
+                if let Maybe::Just(n) = self.file_max_files {
+                    config.max_files = n as usize;
+                }
+                Some(config)
+            }
+            Maybe::Nothing => None,
+        };
+        configure_file_sink(config).map_err(|e| {
+            self.file_sink = Maybe::Nothing;
+            EngineError::InvalidOptionValue(option.to_string(), e.to_string())
+        })
+    }
+}
+
+impl ProbeExtensionCall for TraceProbeExtension {
+    /// `file_sink/flush`: write buffered file-sink lines to disk.
+    async fn call(
+        &self,
+        path: &str,
+        _params: &HashMap<String, String>,
+        _body: &[u8],
+    ) -> Result<Vec<u8>, EngineError> {
+        match path.trim_start_matches('/') {
+            "file_sink/flush" => {
+                let file = flush_file_sink().map_err(|e| EngineError::CallError(e.to_string()))?;
+                let reply = serde_json::json!({
+                    "flushed": file.is_some(),
+                    "file": file.map(|p| p.display().to_string()),
+                });
+                Ok(reply.to_string().into_bytes())
+            }
+            _ => Err(EngineError::UnsupportedCall),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Minimal protobuf reader: (field, value) pairs of one message.
+    #[derive(Debug, Clone, PartialEq)]
+    enum Value {
+        Varint(u64),
+        Fixed64(u64),
+        Bytes(Vec<u8>),
+    }
+
+    fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+        let mut v = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = buf[*pos];
+            *pos += 1;
+            v |= u64::from(b & 0x7f) << shift;
+            if b < 0x80 {
+                return v;
+            }
+            shift += 7;
+        }
+    }
+
+    fn fields(buf: &[u8]) -> Vec<(u32, Value)> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while pos < buf.len() {
+            let tag = read_varint(buf, &mut pos);
+            let value = match tag & 7 {
+                0 => Value::Varint(read_varint(buf, &mut pos)),
+                1 => {
+                    let v = u64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+                    pos += 8;
+                    Value::Fixed64(v)
+                }
+                2 => {
+                    let len = read_varint(buf, &mut pos) as usize;
+                    pos += len;
+                    Value::Bytes(buf[pos - len..pos].to_vec())
+                }
+                other => panic!("unexpected wire type {other}"),
+            };
+            out.push(((tag >> 3) as u32, value));
+        }
+        out
+    }
+
+    fn get(msg: &[(u32, Value)], field: u32) -> Vec<Value> {
+        msg.iter()
+            .filter(|(f, _)| *f == field)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    fn sub(msg: &[(u32, Value)], field: u32) -> Vec<Vec<(u32, Value)>> {
+        get(msg, field)
+            .into_iter()
+            .map(|v| match v {
+                Value::Bytes(b) => fields(&b),
+                other => panic!("field {field} is not a message: {other:?}"),
+            })
+            .collect()
+    }
+
+    /// key -> AnyValue fields of the `KeyValue`s in `field`.
+    fn attrs(msg: &[(u32, Value)], field: u32) -> Vec<(String, Vec<(u32, Value)>)> {
+        sub(msg, field)
+            .into_iter()
+            .map(|kv| {
+                let Value::Bytes(key) = &get(&kv, 1)[0] else {
+                    panic!("key is not a string");
+                };
+                (
+                    String::from_utf8(key.clone()).unwrap(),
+                    sub(&kv, 2).remove(0),
+                )
+            })
+            .collect()
+    }
+
+    fn encoded_spans(spans: &[Span]) -> Vec<Vec<(u32, Value)>> {
+        let request = fields(&encode_export_request(&resource_attributes(), spans));
+        let resource_spans = sub(&request, 1).remove(0);
+        let scope_spans = sub(&resource_spans, 2).remove(0);
+        sub(&scope_spans, 2)
+    }
+
+    #[test]
+    fn maps_ids_parent_and_status() {
+        let mut root = Span::new_root("step", Some("forward"), Some("train.py:10"));
+        root.thread_name = Some("MainThread".into());
+        let mut child = Span::new_child(&root, "attn", None, None);
+        child.end_error(Some("oom".into()));
+        root.finish();
+
+        let spans = encoded_spans(&[root.clone(), child.clone()]);
+        let [root_pb, child_pb] = [&spans[0], &spans[1]];
+
+        let Value::Bytes(trace_id) = &get(root_pb, 1)[0] else {
+            panic!("trace_id");
+        };
+        assert_eq!(trace_id.len(), 16);
+        assert_eq!(&trace_id[8..], &root.trace_id.to_be_bytes());
+        assert_eq!(get(child_pb, 1), get(root_pb, 1), "same trace");
+        assert_eq!(
+            get(child_pb, 2),
+            vec![Value::Bytes(child.span_id.to_be_bytes().to_vec())]
+        );
+        assert_eq!(
+            get(child_pb, 4),
+            vec![Value::Bytes(root.span_id.to_be_bytes().to_vec())]
+        );
+        assert!(get(root_pb, 4).is_empty(), "root has no parent");
+        assert_eq!(
+            get(root_pb, 8),
+            vec![Value::Fixed64(root.end.unwrap().0 as u64)]
+        );
+
+        let root_status = sub(root_pb, 15).remove(0);
+        assert_eq!(get(&root_status, 3), vec![Value::Varint(STATUS_CODE_OK)]);
+        let child_status = sub(child_pb, 15).remove(0);
+        assert_eq!(
+            get(&child_status, 3),
+            vec![Value::Varint(STATUS_CODE_ERROR)]
+        );
+        assert_eq!(get(&child_status, 2), vec![Value::Bytes(b"oom".to_vec())]);
+
+        let phase = attrs(root_pb, 9)
+            .into_iter()
+            .find(|(k, _)| k == "probing.phase")
+            .expect("phase attribute");
+        assert_eq!(get(&phase.1, 1), vec![Value::Bytes(b"forward".to_vec())]);
+        let thread_name = attrs(root_pb, 9)
+            .into_iter()
+            .find(|(k, _)| k == "thread.name")
+            .expect("thread name attribute");
+        assert_eq!(
+            get(&thread_name.1, 1),
+            vec![Value::Bytes(b"MainThread".to_vec())]
+        );
+    }
+
+    #[test]
+    fn maps_attribute_types_and_events() {
+        let mut span = Span::new_root("decode", None, None);
+        span.add_attr("tokens", 42i64).unwrap();
+        span.add_attr("ratio", 0.5f64).unwrap();
+        span.add_attr("cached", true).unwrap();
+        span.add_attr("model", "llama").unwrap();
+        span.add_event("prefill", Some(vec![super::super::attr("batch", -3i64)]))
+            .unwrap();
+        span.add_link(7, 99, Some(vec![super::super::attr("kind", "step")]))
+            .unwrap();
+        span.finish();
+
+        let spans = encoded_spans(&[span]);
+        let span_attrs = attrs(&spans[0], 9);
+        let value = |key: &str| {
+            span_attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| panic!("missing {key}"))
+        };
+        assert_eq!(value("tokens"), vec![(3, Value::Varint(42))]);
+        assert_eq!(value("ratio"), vec![(4, Value::Fixed64(0.5f64.to_bits()))]);
+        assert_eq!(value("cached"), vec![(2, Value::Varint(1))]);
+        assert_eq!(value("model"), vec![(1, Value::Bytes(b"llama".to_vec()))]);
+
+        let event = sub(&spans[0], 11).remove(0);
+        assert_eq!(get(&event, 2), vec![Value::Bytes(b"prefill".to_vec())]);
+        let event_attrs = attrs(&event, 3);
+        assert_eq!(event_attrs[0].0, "batch");
+        assert_eq!(event_attrs[0].1, vec![(3, Value::Varint(-3i64 as u64))]);
+
+        let link = sub(&spans[0], 13).remove(0);
+        assert_eq!(get(&link, 1), vec![Value::Bytes(otlp_trace_id(7).to_vec())]);
+        assert_eq!(
+            get(&link, 2),
+            vec![Value::Bytes(99u64.to_be_bytes().to_vec())]
+        );
+        assert_eq!(attrs(&link, 4)[0].0, "kind");
+    }
+
+    /// Records request sizes; fails the first `failures` sends.
+    #[derive(Clone, Default)]
+    struct MockTransport {
+        sent: Arc<Mutex<Vec<usize>>>,
+        attempts: Arc<AtomicU64>,
+        failures: u64,
+        gate: Option<Arc<Mutex<()>>>,
+    }
+
+    impl OtlpTransport for MockTransport {
+        fn send(&self, body: &[u8]) -> Result<(), ExportError> {
+            let _gate = self.gate.as_ref().map(|g| g.lock().unwrap());
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(ExportError::Transient("collector unavailable".into()));
+            }
+            let spans = sub(&sub(&fields(body), 1)[0], 2)[0]
+                .iter()
+                .filter(|(f, _)| *f == 2)
+                .count();
+            self.sent.lock().unwrap().push(spans);
+            Ok(())
+        }
+    }
+
+    fn test_config() -> OtlpConfig {
+        OtlpConfig {
+            batch_size: 2,
+            flush_interval: Duration::from_secs(60),
+            retry_backoff: Duration::ZERO,
+            ..OtlpConfig::new("http://collector:4318")
+        }
+    }
+
+    fn finished(name: &str) -> Span {
+        let mut span = Span::new_root(name, None, None);
+        span.finish();
+        span
+    }
+
+    #[test]
+    fn batches_and_retries_transient_failures() {
+        let transport = MockTransport {
+            failures: 2,
+            ..Default::default()
+        };
+        let exporter = OtlpExporter::with_transport(test_config(), transport.clone()).unwrap();
+        for i in 0..5 {
+            assert!(exporter.export(finished(&format!("s{i}"))));
+        }
+        exporter.shutdown();
+        // Two full batches, then the remainder on shutdown.
+        assert_eq!(*transport.sent.lock().unwrap(), vec![2, 2, 1]);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn full_queue_drops_instead_of_blocking() {
+        let gate = Arc::new(Mutex::new(()));
+        let transport = MockTransport {
+            gate: Some(gate.clone()),
+            ..Default::default()
+        };
+        let config = OtlpConfig {
+            batch_size: 1,
+            queue_capacity: 1,
+            ..test_config()
+        };
+        let held = gate.lock().unwrap();
+        let exporter = OtlpExporter::with_transport(config, transport.clone()).unwrap();
+        let started = Instant::now();
+        let accepted = (0..10)
+            .filter(|i| exporter.export(finished(&format!("s{i}"))))
+            .count();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(accepted < 10);
+        assert_eq!(exporter.dropped(), (10 - accepted) as u64);
+        drop(held);
+        exporter.shutdown();
+        assert_eq!(transport.sent.lock().unwrap().len(), accepted);
+    }
+
+    #[test]
+    fn traces_url_appends_path_once() {
+        assert_eq!(
+            OtlpConfig::new("http://collector:4318/").traces_url(),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            OtlpConfig::new("http://collector:4318/v1/traces").traces_url(),
+            "http://collector:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn extension_rejects_non_http_endpoints() {
+        let mut ext = TraceProbeExtension::default();
+        assert!(ext.set("otlp_endpoint", "ftp://collector").is_err());
+        assert_eq!(ext.get("otlp_endpoint").unwrap(), "");
+        assert!(ext.set("otlp_endpoint", "").is_ok());
+        assert!(ext.set("max_events", "-1").is_err());
+        assert!(ext.set("max_events_per_span", "-1").is_err());
+        assert!(ext.set("max_attributes_per_span", "-5").is_err());
+        assert!(ext.set("cpu_time", "sometimes").is_err());
+        assert!(ext.set("file_max_bytes", "0").is_err());
+        assert!(ext.set("file_max_files", "-1").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! W3C Trace Context (`traceparent`) propagation between processes.
+//!
+//! A process calling another probed process sends its current span as
+//! `traceparent: 00-<trace id>-<span id>-01` ([`Span::traceparent`],
+//! [`current_traceparent`]); the callee starts its span under that parent
+//! ([`Span::from_traceparent`]), so both sides record the same `trace_id`.
+//! A missing or malformed header starts a fresh trace instead.
+//!
+//! Probing ids are below `2^63` (see [`Span::next_id`]) and are written
+//! zero-padded, so they round-trip. Wider ids from other tracers are folded
+//! (high ^ low half, top bit cleared): stable per trace, but not reversible.
+//!
+//! Spans started from a header end up in the ring, OTLP and the file sink
+//! like any other; [`record_remote_span`] additionally hands them to the
+//! recorder the Python extension registers, which writes them to
+//! `python.trace_event`.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::guard::current_span_ids;
+use super::span::Span;
+
+/// Request header carrying the caller's span.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+const VERSION: &str = "00";
+const FLAG_SAMPLED: u8 = 0x01;
+/// Probing ids fit a positive `i64`.
+const ID_MASK: u64 = i64::MAX as u64;
+
+/// Parsed `traceparent` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub trace_id: u64,
+    /// The caller's span, parent of the span started here.
+    pub parent_id: u64,
+    pub sampled: bool,
+}
+
+impl TraceParent {
+    pub fn new(trace_id: u64, parent_id: u64) -> Self {
+        Self {
+            trace_id,
+            parent_id,
+            sampled: true,
+        }
+    }
+
+    /// Parses a header value; `None` when it is not a valid `traceparent`
+    /// (wrong field sizes, upper-case or non-hex digits, version `ff`,
+    /// all-zero ids, or trailing fields on version `00`).
+    pub fn parse(value: &str) -> Option<Self> {
+        let (version, rest) = value.trim().split_once('-')?;
+        if version.len() != 2 || !is_lower_hex(version) || version == "ff" {
+            return None;
+        }
+        let mut fields = rest.splitn(4, '-');
+        let (trace, parent, flags) = (fields.next()?, fields.next()?, fields.next()?);
+        // Later versions may append fields; version 00 has exactly four.
+        if version == VERSION && fields.next().is_some() {
+            return None;
+        }
+        if trace.len() != 32
+            || parent.len() != 16
+            || flags.len() != 2
+            || ![trace, parent, flags].into_iter().all(is_lower_hex)
+        {
+            return None;
+        }
+        let trace = u128::from_str_radix(trace, 16).ok()?;
+        let parent = u64::from_str_radix(parent, 16).ok()?;
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        let trace_id = (((trace >> 64) as u64) ^ (trace as u64)) & ID_MASK;
+        let parent_id = parent & ID_MASK;
+        if trace_id == 0 || parent_id == 0 {
+            return None;
+        }
+        Some(Self {
+            trace_id,
+            parent_id,
+            sampled: flags & FLAG_SAMPLED != 0,
+        })
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = if self.sampled { FLAG_SAMPLED } else { 0 };
+        write!(
+            f,
+            "{VERSION}-{:032x}-{:016x}-{flags:02x}",
+            self.trace_id, self.parent_id
+        )
+    }
+}
+
+fn is_lower_hex(s: &str) -> bool {
+    s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// `traceparent` value for the innermost span entered on this thread
+/// ([`Span::enter`]), if any.
+pub fn current_traceparent() -> Option<String> {
+    current_span_ids().map(|(trace_id, span_id)| TraceParent::new(trace_id, span_id).to_string())
+}
+
+impl Span {
+    /// `traceparent` value naming this span as the parent of remote work.
+    pub fn traceparent(&self) -> String {
+        TraceParent::new(self.trace_id, self.span_id).to_string()
+    }
+
+    /// Starts a span continuing the caller's trace from a `traceparent`
+    /// header value, or a new root span when `header` is missing or
+    /// malformed. The span is not entered on this thread.
+    pub fn from_traceparent<N: Into<String>>(
+        header: Option<&str>,
+        name: N,
+        phase: Option<&str>,
+        location: Option<&str>,
+    ) -> Self {
+        match header.and_then(TraceParent::parse) {
+            Some(parent) => Span::start(
+                parent.trace_id,
+                Some(parent.parent_id),
+                name,
+                phase,
+                location,
+            ),
+            None => Span::new_root(name, phase, location),
+        }
+    }
+}
+
+/// Receives ended spans started from a `traceparent` header.
+pub type RemoteSpanRecorder = Arc<dyn Fn(&Span) + Send + Sync>;
+
+static REMOTE_SPAN_RECORDER: RwLock<Option<RemoteSpanRecorder>> = RwLock::new(None);
+
+/// Make `recorder` the destination of [`record_remote_span`], replacing any
+/// other.
+pub fn register_remote_span_recorder(recorder: RemoteSpanRecorder) {
+    *REMOTE_SPAN_RECORDER
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = Some(recorder);
+}
+
+pub fn clear_remote_span_recorder() {
+    *REMOTE_SPAN_RECORDER
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Hand an ended span to the registered recorder; a no-op without one.
+pub fn record_remote_span(span: &Span) {
+    let recorder = REMOTE_SPAN_RECORDER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(recorder) = recorder {
+        recorder(span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probing_ids_round_trip() {
+        let caller = Span::new_root("client", None, None);
+        let header = caller.traceparent();
+        assert_eq!(header.len(), 55);
+        assert!(header.starts_with("00-0000000000000000"));
+        assert!(header.ends_with("-01"));
+
+        let server = Span::from_traceparent(Some(&header), "GET /apis/x", None, None);
+        assert_eq!(server.trace_id, caller.trace_id);
+        assert_eq!(server.parent_id, Some(caller.span_id));
+        assert_ne!(server.span_id, caller.span_id);
+    }
+
+    #[test]
+    fn foreign_ids_fold_into_positive_i64() {
+        let parsed =
+            TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+        assert_eq!(
+            parsed.trace_id,
+            (0x4bf92f3577b34da6 ^ 0xa3ce929d0e0e4736) & ID_MASK
+        );
+        assert_eq!(parsed.parent_id, 0x00f067aa0ba902b7);
+        assert!(!parsed.sampled);
+        // Later versions may carry extra fields.
+        assert!(TraceParent::parse(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra"
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn malformed_headers_start_a_fresh_trace() {
+        let valid = TraceParent::new(7, 9).to_string();
+        assert_eq!(TraceParent::parse(&valid), Some(TraceParent::new(7, 9)));
+        for header in [
+            "",
+            "garbage",
+            "00-0000000000000000000000000000007-0000000000000009-01",
+            "00-00000000000000000000000000000007-000000000000009-01",
+            "00-00000000000000000000000000000007-0000000000000009-1",
+            "00-0000000000000000000000000000000G-0000000000000009-01",
+            "00-000000000000000000000000000000AB-0000000000000009-01",
+            "00-00000000000000000000000000000000-0000000000000009-01",
+            "00-00000000000000000000000000000007-0000000000000000-01",
+            "00-00000000000000000000000000000007-0000000000000009-01-extra",
+            "ff-00000000000000000000000000000007-0000000000000009-01",
+            "0-00000000000000000000000000000007-0000000000000009-01",
+        ] {
+            assert_eq!(TraceParent::parse(header), None, "{header:?}");
+            let span = Span::from_traceparent(Some(header), "GET /", None, None);
+            assert_eq!(span.parent_id, None, "{header:?}");
+            assert_ne!(span.trace_id, 7, "{header:?}");
+        }
+        let span = Span::from_traceparent(None, "GET /", None, None);
+        assert_eq!(span.parent_id, None);
+    }
+
+    #[test]
+    fn entered_span_is_the_current_traceparent() {
+        assert_eq!(current_traceparent(), None);
+        let outer = Span::enter("request", None, None);
+        assert_eq!(current_traceparent(), Some(outer.traceparent()));
+        drop(outer);
+        assert_eq!(current_traceparent(), None);
+    }
+
+    #[test]
+    fn remote_spans_reach_the_registered_recorder() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        register_remote_span_recorder(Arc::new(move |span: &Span| {
+            sink.lock().unwrap().push((span.trace_id, span.parent_id));
+        }));
+        let header = TraceParent::new(11, 13).to_string();
+        let mut span = Span::from_traceparent(Some(&header), "GET /", None, None);
+        span.finish();
+        record_remote_span(&span);
+        clear_remote_span_recorder();
+        record_remote_span(&span);
+        assert_eq!(*seen.lock().unwrap(), vec![(11, Some(13))]);
+    }
+}
@@ -0,0 +1,387 @@
+//! Retention of finished traces.
+//!
+//! `probing.trace.retention_seconds` and `probing.trace.max_traces` bound how
+//! long and how many finished traces stay visible. A background sweep reads
+//! the paired span rows, picks whole traces with [`select_evictions`] and
+//! hands them to [`evict`]:
+//!
+//! - traces whose last span ended more than `retention_seconds` ago go first;
+//! - of the rest, only the newest `max_traces` are kept.
+//!
+//! Traces with a span still open are never evicted, nor, while autosave
+//! ([`super::autosave`]) is on, traces that ended after the last export.
+//! Trace rows live in
+//! append-only memtables, so eviction publishes a new [`EvictedTraces`]
+//! snapshot that `python.trace_event` scans filter against; the snapshot is
+//! swapped in one step, so a scan sees all of a trace's rows or none of them
+//! (including its `span_end` rows, which carry `trace_id` 0 and are matched
+//! by `(thread_id, span_id)`). The closed-span [`span_ring`] drops the spans
+//! outright. Ids are forgotten again once a full scan no longer finds their
+//! rows, i.e. once the storage rings have overwritten them. Evictions are
+//! counted in `python.trace_stats`.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use super::ring::span_ring;
+use super::tree::SpanRecord;
+
+/// Time between sweeps of the background task.
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+static RETENTION_SECS: AtomicU64 = AtomicU64::new(0);
+static MAX_TRACES: AtomicU64 = AtomicU64::new(0);
+
+/// Limits on finished traces; `None` means unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub retention: Option<Duration>,
+    pub max_traces: Option<usize>,
+    /// Traces ending after this time (ns) are not yet autosaved and stay.
+    pub exported_through: Option<i64>,
+}
+
+impl RetentionPolicy {
+    pub fn is_active(&self) -> bool {
+        self.retention.is_some() || self.max_traces.is_some()
+    }
+}
+
+/// `0` disables the age limit.
+pub fn set_retention_seconds(secs: u64) {
+    RETENTION_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// `0` disables the count limit.
+pub fn set_max_traces(max: u64) {
+    MAX_TRACES.store(max, Ordering::Relaxed);
+}
+
+pub fn retention_policy() -> RetentionPolicy {
+    let secs = RETENTION_SECS.load(Ordering::Relaxed);
+    let max = MAX_TRACES.load(Ordering::Relaxed);
+    RetentionPolicy {
+        retention: (secs > 0).then(|| Duration::from_secs(secs)),
+        max_traces: (max > 0).then_some(max as usize),
+        exported_through: super::autosave::exported_through(),
+    }
+}
+
+/// One trace as the sweep sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceExtent {
+    pub trace_id: i64,
+    /// Latest start or end time of its spans (ns).
+    pub last_ns: i64,
+    /// Whether any span is still open.
+    pub open: bool,
+    /// `(thread_id, span_id)` of its spans.
+    pub spans: Vec<(i64, i64)>,
+}
+
+impl TraceExtent {
+    /// Group paired `span` rows by trace; other record types are ignored.
+    pub fn from_records(records: &[SpanRecord]) -> Vec<TraceExtent> {
+        let mut by_trace: HashMap<i64, TraceExtent> = HashMap::new();
+        for r in records.iter().filter(|r| r.record_type == "span") {
+            let extent = by_trace.entry(r.trace_id).or_insert_with(|| TraceExtent {
+                trace_id: r.trace_id,
+                last_ns: i64::MIN,
+                open: false,
+                spans: vec![],
+            });
+            extent.last_ns = extent.last_ns.max(r.end_time.unwrap_or(r.time));
+            extent.open |= r.end_time.is_none();
+            extent.spans.push((r.thread_id, r.span_id));
+        }
+        let mut extents: Vec<_> = by_trace.into_values().collect();
+        extents.sort_by_key(|e| e.trace_id);
+        extents
+    }
+}
+
+/// Trace ids to evict under `policy` at `now_ns`; see the module docs.
+pub fn select_evictions(
+    extents: &[TraceExtent],
+    now_ns: i64,
+    policy: &RetentionPolicy,
+) -> Vec<i64> {
+    let mut finished: Vec<&TraceExtent> = extents.iter().filter(|e| !e.open).collect();
+    // Newest first, so the count limit keeps the head.
+    finished.sort_by_key(|e| std::cmp::Reverse((e.last_ns, e.trace_id)));
+    let cutoff = policy
+        .retention
+        .map(|r| now_ns.saturating_sub(i64::try_from(r.as_nanos()).unwrap_or(i64::MAX)));
+    let mut kept = 0usize;
+    let mut evicted = vec![];
+    for extent in finished {
+        let expired = cutoff.is_some_and(|cutoff| extent.last_ns < cutoff);
+        let over = policy.max_traces.is_some_and(|max| kept >= max);
+        let exported = policy
+            .exported_through
+            .is_none_or(|through| extent.last_ns <= through);
+        if (expired || over) && exported {
+            evicted.push(extent.trace_id);
+        } else {
+            kept += 1;
+        }
+    }
+    evicted
+}
+
+/// Traces hidden from `python.trace_event`.
+#[derive(Debug, Clone, Default)]
+pub struct EvictedTraces {
+    traces: HashMap<i64, Vec<(i64, i64)>>,
+    spans: HashMap<(i64, i64), i64>,
+}
+
+impl EvictedTraces {
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+
+    /// The evicted trace a row belongs to, if any. `span_end` rows carry no
+    /// trace id and are matched by their span key.
+    pub fn trace_of(
+        &self,
+        record_type: &str,
+        trace_id: i64,
+        thread_id: i64,
+        span_id: i64,
+    ) -> Option<i64> {
+        if record_type == "span_end" {
+            self.spans.get(&(thread_id, span_id)).copied()
+        } else {
+            self.traces.contains_key(&trace_id).then_some(trace_id)
+        }
+    }
+}
+
+/// Published [`EvictedTraces`] snapshots plus the eviction counter.
+#[derive(Debug, Default)]
+pub struct EvictionState {
+    current: RwLock<Arc<EvictedTraces>>,
+    evicted: AtomicU64,
+}
+
+impl EvictionState {
+    /// The current snapshot; hold it for the whole scan.
+    pub fn snapshot(&self) -> Arc<EvictedTraces> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Traces evicted so far.
+    pub fn evicted_count(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
+    /// Publish a snapshot that also hides `extents`; returns how many were new.
+    pub fn evict(&self, extents: &[TraceExtent]) -> usize {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let mut next = EvictedTraces::clone(&current);
+        let mut added = 0;
+        for extent in extents {
+            if next.traces.contains_key(&extent.trace_id) {
+                continue;
+            }
+            for key in &extent.spans {
+                next.spans.insert(*key, extent.trace_id);
+            }
+            next.traces.insert(extent.trace_id, extent.spans.clone());
+            added += 1;
+        }
+        if added > 0 {
+            *current = Arc::new(next);
+            self.evicted.fetch_add(added as u64, Ordering::Relaxed);
+        }
+        added
+    }
+
+    /// Forget ids of `snapshot` that a full scan no longer found (`seen` holds
+    /// the ones it did); their rows are gone from storage.
+    pub fn forget_absent(&self, snapshot: &EvictedTraces, seen: &HashSet<i64>) {
+        let gone: Vec<i64> = snapshot
+            .traces
+            .keys()
+            .filter(|id| !seen.contains(id))
+            .copied()
+            .collect();
+        if gone.is_empty() {
+            return;
+        }
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let mut next = EvictedTraces::clone(&current);
+        for id in gone {
+            if let Some(spans) = next.traces.remove(&id) {
+                for key in spans {
+                    next.spans.remove(&key);
+                }
+            }
+        }
+        *current = Arc::new(next);
+    }
+}
+
+static EVICTIONS: LazyLock<EvictionState> = LazyLock::new(EvictionState::default);
+
+/// The process-wide state `python.trace_event` filters against.
+pub fn eviction_state() -> &'static EvictionState {
+    &EVICTIONS
+}
+
+/// Traces evicted since the process started.
+pub fn evicted_trace_count() -> u64 {
+    EVICTIONS.evicted_count()
+}
+
+/// Hide `extents` from trace queries and drop them from the span ring.
+/// Returns how many traces were newly evicted.
+pub fn evict(extents: &[TraceExtent]) -> usize {
+    if extents.is_empty() {
+        return 0;
+    }
+    let added = EVICTIONS.evict(extents);
+    let ids: HashSet<u64> = extents.iter().map(|e| e.trace_id as u64).collect();
+    span_ring().remove_traces(&ids);
+    if added > 0 {
+        log::info!(
+            "trace retention: evicted {added} traces ({} total)",
+            evicted_trace_count()
+        );
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: i64 = 1_000_000_000;
+
+    fn extent(trace_id: i64, last_s: i64, open: bool) -> TraceExtent {
+        TraceExtent {
+            trace_id,
+            last_ns: last_s * SEC,
+            open,
+            spans: vec![(1, trace_id * 10)],
+        }
+    }
+
+    fn span(trace_id: i64, span_id: i64, time: i64, end_time: Option<i64>) -> SpanRecord {
+        SpanRecord {
+            record_type: "span".into(),
+            trace_id,
+            span_id,
+            parent_id: None,
+            name: "s".into(),
+            time,
+            end_time,
+            thread_id: 7,
+            phase: None,
+            location: None,
+            attributes: None,
+            event_attributes: None,
+            cpu_time_ns: None,
+            ctx_switches: None,
+            links: None,
+        }
+    }
+
+    #[test]
+    fn extents_group_spans_and_flag_open_traces() {
+        let records = [
+            span(1, 10, 100, Some(400)),
+            span(1, 11, 200, Some(300)),
+            span(2, 20, 500, None),
+        ];
+        let extents = TraceExtent::from_records(&records);
+        assert_eq!(
+            extents,
+            [
+                TraceExtent {
+                    trace_id: 1,
+                    last_ns: 400,
+                    open: false,
+                    spans: vec![(7, 10), (7, 11)],
+                },
+                TraceExtent {
+                    trace_id: 2,
+                    last_ns: 500,
+                    open: true,
+                    spans: vec![(7, 20)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn evicts_expired_then_oldest_beyond_the_cap() {
+        let extents = [
+            extent(1, 10, false),
+            extent(2, 50, false),
+            extent(3, 80, false),
+            extent(4, 90, false),
+            extent(5, 5, true),
+        ];
+        let by_age = RetentionPolicy {
+            retention: Some(Duration::from_secs(30)),
+            max_traces: None,
+            ..Default::default()
+        };
+        assert_eq!(select_evictions(&extents, 100 * SEC, &by_age), [2, 1]);
+
+        let by_count = RetentionPolicy {
+            retention: None,
+            max_traces: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(select_evictions(&extents, 100 * SEC, &by_count), [2, 1]);
+
+        let both = RetentionPolicy {
+            retention: Some(Duration::from_secs(15)),
+            max_traces: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(select_evictions(&extents, 100 * SEC, &both), [3, 2, 1]);
+        // Autosave has only written rows up to 60s: trace 3 (ended at 80s) waits.
+        let held = RetentionPolicy {
+            exported_through: Some(60 * SEC),
+            ..both
+        };
+        assert_eq!(select_evictions(&extents, 100 * SEC, &held), [2, 1]);
+        assert!(select_evictions(&extents, 100 * SEC, &RetentionPolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn eviction_swaps_snapshots_and_forgets_overwritten_traces() {
+        let state = EvictionState::default();
+        let before = state.snapshot();
+        assert_eq!(
+            state.evict(&[extent(41, 1, false), extent(42, 2, false)]),
+            2
+        );
+        assert_eq!(state.evict(&[extent(42, 2, false)]), 0, "already evicted");
+        assert_eq!(state.evicted_count(), 2);
+
+        assert!(before.is_empty(), "held snapshots never change");
+        let now = state.snapshot();
+        assert_eq!(now.trace_of("span_start", 41, 1, 410), Some(41));
+        assert_eq!(now.trace_of("span_end", 0, 1, 420), Some(42));
+        assert_eq!(now.trace_of("span_end", 0, 2, 420), None);
+        assert_eq!(now.trace_of("event", 43, 1, 430), None);
+
+        state.forget_absent(&now, &HashSet::from([42]));
+        let after = state.snapshot();
+        assert_eq!(after.len(), 1);
+        assert_eq!(after.trace_of("span_end", 0, 1, 410), None);
+        assert_eq!(after.trace_of("span_end", 0, 1, 420), Some(42));
+        assert_eq!(state.evicted_count(), 2, "forgetting is not evicting");
+    }
+}
@@ -0,0 +1,244 @@
+//! Bounded in-memory history of closed spans.
+//!
+//! [`Span::finish`] appends a copy of every finished span to the process-wide
+//! [`span_ring`]. The ring is bounded by *events*: a span counts as one event
+//! plus one per [`Span::events`] entry. When appending would exceed the
+//! capacity, the oldest closed spans are evicted first and counted in
+//! [`RingStats::dropped_spans`] / [`RingStats::dropped_events`]. Open spans are
+//! never in the ring, so they cannot be evicted.
+//!
+//! The capacity is `probing.trace.max_events` (default
+//! [`DEFAULT_MAX_EVENTS`]); `0` disables the ring. Counters are served as
+//! `python.trace_stats`.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use super::span::Span;
+
+pub const DEFAULT_MAX_EVENTS: usize = 65_536;
+
+/// Counters of a [`SpanRing`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RingStats {
+    pub capacity: usize,
+    /// Spans currently held.
+    pub spans: usize,
+    /// Events currently held (see the module docs).
+    pub events: usize,
+    /// Spans ever appended, including dropped ones.
+    pub recorded_spans: u64,
+    pub dropped_spans: u64,
+    pub dropped_events: u64,
+}
+
+#[derive(Debug, Default)]
+struct RingState {
+    spans: VecDeque<Span>,
+    stats: RingStats,
+}
+
+impl RingState {
+    /// Evict oldest spans until `incoming` more events fit.
+    fn make_room(&mut self, incoming: usize) {
+        while self.stats.events + incoming > self.stats.capacity {
+            let Some(oldest) = self.spans.pop_front() else {
+                break;
+            };
+            let weight = span_events(&oldest);
+            self.stats.events -= weight;
+            self.stats.dropped_spans += 1;
+            self.stats.dropped_events += weight as u64;
+        }
+        self.stats.spans = self.spans.len();
+    }
+}
+
+/// Events a span occupies: the span itself plus its events.
+pub fn span_events(span: &Span) -> usize {
+    1 + span.events.len()
+}
+
+#[derive(Debug)]
+pub struct SpanRing {
+    state: Mutex<RingState>,
+}
+
+impl SpanRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(RingState {
+                spans: VecDeque::new(),
+                stats: RingStats {
+                    capacity,
+                    ..Default::default()
+                },
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RingState> {
+        crate::sync::lock_mutex(&self.state, "span ring")
+    }
+
+    /// Append a closed span, evicting the oldest ones if needed. A span larger
+    /// than the whole capacity is dropped right away.
+    pub fn push(&self, span: &Span) {
+        let weight = span_events(span);
+        let mut state = self.lock();
+        state.stats.recorded_spans += 1;
+        if weight > state.stats.capacity {
+            state.stats.dropped_spans += 1;
+            state.stats.dropped_events += weight as u64;
+            return;
+        }
+        state.make_room(weight);
+        state.spans.push_back(span.clone());
+        state.stats.events += weight;
+        state.stats.spans = state.spans.len();
+    }
+
+    /// Change the capacity; shrinking evicts the oldest spans immediately.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.lock();
+        state.stats.capacity = capacity;
+        state.make_room(0);
+    }
+
+    pub fn stats(&self) -> RingStats {
+        self.lock().stats
+    }
+
+    /// Held spans, oldest first.
+    pub fn snapshot(&self) -> Vec<Span> {
+        self.lock().spans.iter().cloned().collect()
+    }
+
+    /// Drop every held span of the given traces; returns how many went.
+    /// Unlike capacity evictions these are not counted as dropped.
+    pub fn remove_traces(&self, trace_ids: &HashSet<u64>) -> usize {
+        let mut state = self.lock();
+        let before = state.spans.len();
+        let mut freed = 0;
+        state.spans.retain(|span| {
+            let keep = !trace_ids.contains(&span.trace_id);
+            if !keep {
+                freed += span_events(span);
+            }
+            keep
+        });
+        state.stats.events -= freed;
+        state.stats.spans = state.spans.len();
+        before - state.spans.len()
+    }
+}
+
+static SPAN_RING: LazyLock<SpanRing> = LazyLock::new(|| SpanRing::new(DEFAULT_MAX_EVENTS));
+
+/// The process-wide ring fed by [`Span::finish`].
+pub fn span_ring() -> &'static SpanRing {
+    &SPAN_RING
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn closed(name: &str, events: usize) -> Span {
+        let mut span = Span::new_root(name, None, None);
+        for i in 0..events {
+            span.add_event(format!("e{i}"), None).unwrap();
+        }
+        span.end = Some(super::super::Timestamp::now());
+        span
+    }
+
+    #[test]
+    fn wraps_around_dropping_oldest_first() {
+        let ring = SpanRing::new(5);
+        for i in 0..4 {
+            ring.push(&closed(&format!("s{i}"), 0));
+        }
+        // `big` weighs two events; evicting s0 makes room.
+        ring.push(&closed("big", 1));
+        let names: Vec<_> = ring.snapshot().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["s1", "s2", "s3", "big"].map(String::from));
+        let stats = ring.stats();
+        assert_eq!((stats.spans, stats.events), (4, 5));
+        assert_eq!((stats.dropped_spans, stats.dropped_events), (1, 1));
+
+        ring.push(&closed("s4", 0));
+        ring.push(&closed("s5", 0));
+        let names: Vec<_> = ring.snapshot().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["s3", "big", "s4", "s5"].map(String::from));
+        assert_eq!(ring.stats().dropped_spans, 3);
+        assert_eq!(ring.stats().recorded_spans, 7);
+    }
+
+    #[test]
+    fn counts_evicted_events_and_oversized_spans() {
+        let ring = SpanRing::new(4);
+        ring.push(&closed("a", 2));
+        ring.push(&closed("b", 1));
+        // Evicting `a` frees three events.
+        let stats = ring.stats();
+        assert_eq!((stats.spans, stats.events), (1, 2));
+        assert_eq!((stats.dropped_spans, stats.dropped_events), (1, 3));
+
+        ring.push(&closed("huge", 9));
+        let stats = ring.stats();
+        assert_eq!(stats.spans, 1, "oversized span does not evict others");
+        assert_eq!((stats.dropped_spans, stats.dropped_events), (2, 13));
+
+        ring.set_capacity(0);
+        let stats = ring.stats();
+        assert_eq!((stats.spans, stats.events), (0, 0));
+        assert_eq!((stats.dropped_spans, stats.dropped_events), (3, 15));
+    }
+
+    #[test]
+    fn removes_whole_traces() {
+        let ring = SpanRing::new(10);
+        let root = closed("step", 1);
+        let mut child = Span::new_child(&root, "fwd", None, None);
+        child.end = Some(super::super::Timestamp::now());
+        let other = closed("other", 0);
+        for span in [&root, &child, &other] {
+            ring.push(span);
+        }
+        assert_eq!(ring.remove_traces(&HashSet::from([root.trace_id])), 2);
+        let names: Vec<_> = ring.snapshot().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["other"]);
+        let stats = ring.stats();
+        assert_eq!((stats.spans, stats.events, stats.dropped_spans), (1, 1, 0));
+    }
+
+    #[test]
+    fn concurrent_writers_stay_within_capacity() {
+        let ring = Arc::new(SpanRing::new(100));
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let ring = Arc::clone(&ring);
+                std::thread::spawn(move || {
+                    for i in 0..500 {
+                        ring.push(&closed(&format!("t{t}-{i}"), i % 3));
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let stats = ring.stats();
+        let total_events: u64 = (0..500u64).map(|i| 1 + i % 3).sum::<u64>() * 8;
+        assert_eq!(stats.recorded_spans, 4000);
+        assert!(stats.events <= 100);
+        assert_eq!(stats.events as u64 + stats.dropped_events, total_events);
+        assert_eq!(stats.spans as u64 + stats.dropped_spans, 4000);
+        assert_eq!(
+            ring.snapshot().iter().map(span_events).sum::<usize>(),
+            stats.events
+        );
+    }
+}
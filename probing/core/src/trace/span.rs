@@ -1,11 +1,59 @@
+use std::cell::Cell;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
 use std::time::{Duration, SystemTime};
 
 pub use probing_proto::types::Ele;
 
-// Global atomic counters for generating unique IDs.
-static NEXT_TRACE_ID: AtomicU64 = AtomicU64::new(1);
-static NEXT_SPAN_ID: AtomicU64 = AtomicU64::new(1);
+use super::cpu::ThreadCpu;
+
+// --- Id generation ---
+//
+// Trace and span ids are snowflake-style: bit 63 is always clear so an id is a
+// positive `i64` in SQL and JSON, then a per-process random epoch, the low bits
+// of the creating thread's id, and a process-wide counter.
+//
+//   63 | 62 ........ 47 | 46 ...... 35 | 34 ............ 0
+//    0 |  epoch (16)    |  thread (12) |  counter (35)
+//
+// The counter alone keeps ids unique within a process (2^35 ids before it
+// wraps). The epoch only makes cross-process clashes unlikely for a few
+// processes: with 16 bits, two of 300 ranks share one about half the time,
+// so merged traces must still be keyed by rank. Ids from one thread increase.
+const COUNTER_BITS: u32 = 35;
+const THREAD_BITS: u32 = 12;
+const EPOCH_BITS: u32 = 16;
+const COUNTER_MASK: u64 = (1 << COUNTER_BITS) - 1;
+const THREAD_MASK: u64 = (1 << THREAD_BITS) - 1;
+const EPOCH_MASK: u64 = (1 << EPOCH_BITS) - 1;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Random per-process epoch, already shifted into place.
+static ID_EPOCH: LazyLock<u64> = LazyLock::new(|| {
+    use std::hash::{BuildHasher, Hasher};
+    // `RandomState` is seeded randomly per process; mixing in the pid and the
+    // clock keeps forked workers apart as well.
+    let mut h = std::collections::hash_map::RandomState::new().build_hasher();
+    h.write_u32(std::process::id());
+    h.write_u128(Timestamp::now().0);
+    (h.finish() & EPOCH_MASK) << (COUNTER_BITS + THREAD_BITS)
+});
+
+thread_local! {
+    /// Thread bits of this thread's ids, shifted into place; computed once.
+    static ID_THREAD_BITS: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+fn id_thread_bits() -> u64 {
+    ID_THREAD_BITS.with(|bits| {
+        bits.get().unwrap_or_else(|| {
+            let value = (current_thread_id() & THREAD_MASK) << COUNTER_BITS;
+            bits.set(Some(value));
+            value
+        })
+    })
+}
 
 /// Obtain a numeric thread identifier using platform facilities where possible.
 ///
@@ -34,6 +82,24 @@ fn current_thread_id() -> u64 {
     }
 }
 
+/// Name of the current OS thread (`pthread_getname_np`), falling back to the
+/// Rust thread name. `None` for unnamed threads.
+fn current_thread_name() -> Option<String> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let mut buf = [0 as libc::c_char; 64];
+        let rc =
+            unsafe { libc::pthread_getname_np(libc::pthread_self(), buf.as_mut_ptr(), buf.len()) };
+        if rc == 0 {
+            let name = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+            if !name.to_bytes().is_empty() {
+                return Some(name.to_string_lossy().into_owned());
+            }
+        }
+    }
+    std::thread::current().name().map(String::from)
+}
+
 // --- Timestamp ---
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Timestamp(pub u128);
@@ -88,16 +154,27 @@ pub struct Event {
     pub attributes: Vec<Attribute>,
 }
 
+/// A reference from one span to another, possibly in a different trace
+/// (e.g. the checkpoint upload that persists a training step).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub trace_id: u64,
+    pub span_id: u64,
+    pub attributes: Vec<Attribute>,
+}
+
 // --- Span Status ---
 /// Represents the status of a span.
 ///
 /// The status is determined by whether the span has been ended:
 /// - `Active`: The span is still running (end_time is None)
 /// - `Completed`: The span has been ended (end_time is Some)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// - `Error`: The span has been ended with an `error.message` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SpanStatus {
-    Active,    // The span is currently active (end_time is None).
-    Completed, // The span has been completed (end_time is Some).
+    Active,        // The span is currently active (end_time is None).
+    Completed,     // The span has been completed (end_time is Some).
+    Error(String), // The span has been completed with an error message.
 }
 
 impl SpanStatus {
@@ -118,6 +195,7 @@ pub struct Span {
     pub span_id: u64,
     pub parent_id: Option<u64>,
     pub thread_id: u64, // stable numeric id for the originating thread
+    pub thread_name: Option<String>, // originating thread name, if it has one
 
     // === 基本信息 ===
     pub name: String,
@@ -133,29 +211,34 @@ pub struct Span {
     // === 扩展数据 ===
     pub attrs: Vec<Attribute>,
     pub events: Vec<Event>,
+    pub links: Vec<Link>,
+
+    // === CPU 统计（probing.trace.cpu_time，见 super::cpu） ===
+    /// Thread CPU time (ns) spent between start and end.
+    pub cpu_time_ns: Option<u64>,
+    /// Voluntary + involuntary context switches between start and end.
+    pub ctx_switches: Option<u64>,
+    cpu_start: Option<ThreadCpu>,
 }
 
 impl Span {
+    /// A fresh trace or span id: unique within the process, nonzero, and
+    /// below `2^63` so it round-trips through `i64` columns.
+    pub fn next_id() -> u64 {
+        let counter = NEXT_ID.fetch_add(1, Ordering::Relaxed) & COUNTER_MASK;
+        // After a wrap the counter restarts at 1, never 0.
+        let counter = if counter == 0 {
+            NEXT_ID.fetch_add(1, Ordering::Relaxed) & COUNTER_MASK
+        } else {
+            counter
+        };
+        *ID_EPOCH | id_thread_bits() | counter
+    }
+
     /// Creates a new root span (starts a new trace).
     pub fn new_root<N: Into<String>>(name: N, phase: Option<&str>, location: Option<&str>) -> Self {
-        let trace_id = NEXT_TRACE_ID.fetch_add(1, Ordering::Relaxed);
-        let span_id = NEXT_SPAN_ID.fetch_add(1, Ordering::Relaxed);
-        let location = location.map(|loc_val| Location::UnknownLocation(loc_val.into()));
-        let thread_id = current_thread_id();
-
-        Span {
-            trace_id,
-            span_id,
-            parent_id: None,
-            thread_id,
-            name: name.into(),
-            start: Timestamp::now(),
-            end: None,
-            phase: phase.map(|p| p.to_string()),
-            loc: location,
-            attrs: vec![],
-            events: vec![],
-        }
+        let trace_id = Span::next_id();
+        Self::start(trace_id, None, name, phase, location)
     }
 
     /// Creates a new child span within an existing trace.
@@ -165,15 +248,27 @@ impl Span {
         phase: Option<&str>,
         location: Option<&str>,
     ) -> Self {
-        let span_id = NEXT_SPAN_ID.fetch_add(1, Ordering::Relaxed);
+        Self::start(parent.trace_id, Some(parent.span_id), name, phase, location)
+    }
+
+    /// Starts a span with a fresh span id on the current thread.
+    pub(super) fn start<N: Into<String>>(
+        trace_id: u64,
+        parent_id: Option<u64>,
+        name: N,
+        phase: Option<&str>,
+        location: Option<&str>,
+    ) -> Self {
+        let span_id = Span::next_id();
         let location = location.map(|loc_val| Location::UnknownLocation(loc_val.into()));
-        let thread_id = current_thread_id(); // child bound to the current executing thread
+        let thread_id = current_thread_id(); // bound to the current executing thread
 
-        Span {
-            trace_id: parent.trace_id,
+        let span = Span {
+            trace_id,
             span_id,
-            parent_id: Some(parent.span_id),
+            parent_id,
             thread_id,
+            thread_name: current_thread_name(),
             name: name.into(),
             start: Timestamp::now(),
             end: None,
@@ -181,21 +276,49 @@ impl Span {
             loc: location,
             attrs: vec![],
             events: vec![],
-        }
+            links: vec![],
+            cpu_time_ns: None,
+            ctx_switches: None,
+            cpu_start: super::cpu::start_sample(),
+        };
+        super::file_sink::record_span_start(&span);
+        span
     }
 
-    /// Adds an attribute to this span.
+    /// Adds an attribute to this span. Past
+    /// `probing.trace.max_attributes_per_span` the attribute is dropped and
+    /// counted in `probing.dropped_attributes`.
     ///
     /// Returns an error if the span has already been ended.
     pub fn add_attr<V: Into<Ele>>(&mut self, key: &str, value: V) -> Result<(), super::TraceError> {
         if self.end.is_some() {
             return Err(super::TraceError::SpanAlreadyClosed);
         }
+        if let Some(max) = super::limits::max_attributes_per_span() {
+            let counters = self
+                .attrs
+                .iter()
+                .filter(|a| super::limits::is_drop_counter(a.key()))
+                .count();
+            if self.attrs.len() - counters >= max {
+                self.count_dropped(super::limits::DROPPED_ATTRIBUTES_ATTR);
+                return Ok(());
+            }
+        }
         self.attrs.push(attr(key, value));
         Ok(())
     }
 
-    /// Adds an event to this span.
+    /// Bumps the drop counter attribute `key`, appending it on first use.
+    fn count_dropped(&mut self, key: &str) {
+        match self.attrs.iter_mut().find(|a| a.key() == key) {
+            Some(Attribute(_, Ele::I64(n))) => *n += 1,
+            _ => self.attrs.push(attr(key, 1i64)),
+        }
+    }
+
+    /// Adds an event to this span. Past `probing.trace.max_events_per_span`
+    /// the event is dropped and counted in `probing.dropped_events`.
     ///
     /// Returns an error if the span has already been ended.
     pub fn add_event<S: Into<String>>(
@@ -206,6 +329,10 @@ impl Span {
         if self.end.is_some() {
             return Err(super::TraceError::SpanAlreadyClosed);
         }
+        if super::limits::max_events_per_span().is_some_and(|max| self.events.len() >= max) {
+            self.count_dropped(super::limits::DROPPED_EVENTS_ATTR);
+            return Ok(());
+        }
 
         self.events.push(Event {
             name: name.into(),
@@ -213,13 +340,54 @@ impl Span {
             timestamp: Timestamp::now(),
             attributes: attributes.unwrap_or_default(),
         });
+        if let Some(event) = self.events.last() {
+            super::file_sink::record_event(self, event);
+        }
 
         Ok(())
     }
 
-    /// Ends this span.
+    /// Links this span to span `span_id` of trace `trace_id`.
+    ///
+    /// Returns an error if the span has already been ended.
+    pub fn add_link(
+        &mut self,
+        trace_id: u64,
+        span_id: u64,
+        attributes: Option<Vec<Attribute>>,
+    ) -> Result<(), super::TraceError> {
+        if self.end.is_some() {
+            return Err(super::TraceError::SpanAlreadyClosed);
+        }
+        self.links.push(Link {
+            trace_id,
+            span_id,
+            attributes: attributes.unwrap_or_default(),
+        });
+        Ok(())
+    }
+
+    /// Ends this span. The first call also hands the span to the OTLP
+    /// exporter and the file sink when they are configured (see
+    /// [`super::otlp`] and [`super::file_sink`]), and to live subscribers
+    /// ([`super::live`]).
     pub fn finish(&mut self) {
+        let first = self.end.is_none();
         self.end = Some(Timestamp::now());
+        if let Some(start) = self.cpu_start.take() {
+            if self.thread_id == current_thread_id() {
+                if let Some(delta) = ThreadCpu::sample().and_then(|end| end.since(start)) {
+                    self.cpu_time_ns = Some(delta.cpu_time_ns);
+                    self.ctx_switches = Some(delta.ctx_switches);
+                }
+            }
+        }
+        if first {
+            super::ring::span_ring().push(self);
+            super::otlp::export_finished_span(self);
+            super::file_sink::record_span_end(self);
+            super::live::publish_finished_span(self);
+        }
     }
 
     /// Ends this span (alias for `finish()`).
@@ -235,14 +403,23 @@ impl Span {
     /// Ends this span and optionally records an error message as an attribute.
     pub fn end_error(&mut self, error_message: Option<String>) {
         if let Some(msg) = error_message {
-            // Record error message as an attribute
-            let _ = self.add_attr("error.message", msg);
+            // Recorded past the attribute cap: it decides the span's status.
+            if self.end.is_none() {
+                self.attrs.push(attr("error.message", msg));
+            }
         }
         self.finish();
     }
 
-    /// Returns the status of this span.
+    /// Returns the status of this span; an ended span carrying a text
+    /// `error.message` attribute is [`SpanStatus::Error`].
     pub fn status(&self) -> SpanStatus {
+        if self.end.is_some() {
+            let error = self.attrs.iter().find(|a| a.key() == "error.message");
+            if let Some(Ele::Text(msg)) = error.map(Attribute::value) {
+                return SpanStatus::Error(msg.clone());
+            }
+        }
         SpanStatus::from_end_time(self.end)
     }
 
@@ -290,6 +467,17 @@ mod tests {
         assert!(!span.is_ended(), "New span should not be ended");
     }
 
+    #[test]
+    fn test_span_captures_thread_name() {
+        let span = std::thread::Builder::new()
+            .name("data-loader".into())
+            .spawn(|| Span::new_root("load", None, None))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(span.thread_name.as_deref(), Some("data-loader"));
+    }
+
     #[test]
     fn test_new_child_span() {
         let parent = Span::new_root("root_operation", None, None);
@@ -342,7 +530,7 @@ mod tests {
 
         span.end_error(Some(error_message.clone()));
         assert!(span.is_ended(), "Span should be ended");
-        assert_eq!(span.status(), SpanStatus::Completed);
+        assert_eq!(span.status(), SpanStatus::Error(error_message.clone()));
         // Verify error message was recorded as an attribute
         assert!(
             span.attrs.iter().any(|attr| {
@@ -429,9 +617,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_events_and_attributes_past_cap_are_dropped() {
+        use super::super::limits::{
+            DEFAULT_MAX_ATTRIBUTES_PER_SPAN, DEFAULT_MAX_EVENTS_PER_SPAN, DROPPED_ATTRIBUTES_ATTR,
+            DROPPED_EVENTS_ATTR,
+        };
+        let dropped = |span: &Span, key: &str| {
+            span.attrs
+                .iter()
+                .find(|a| a.key() == key)
+                .map(|a| a.1.clone())
+        };
+
+        let mut span = Span::new_root("noisy_loop", None, None);
+        for i in 0..DEFAULT_MAX_EVENTS_PER_SPAN + 3 {
+            span.add_event(format!("tick_{i}"), None).unwrap();
+        }
+        assert_eq!(span.events.len(), DEFAULT_MAX_EVENTS_PER_SPAN);
+        assert_eq!(
+            span.events.last().unwrap().name,
+            format!("tick_{}", DEFAULT_MAX_EVENTS_PER_SPAN - 1)
+        );
+        assert_eq!(dropped(&span, DROPPED_EVENTS_ATTR), Some(Ele::I64(3)));
+
+        for i in 0..DEFAULT_MAX_ATTRIBUTES_PER_SPAN + 2 {
+            span.add_attr(&format!("k{i}"), i as i64).unwrap();
+        }
+        // The events counter sits outside the attribute cap.
+        assert_eq!(span.attrs.len(), DEFAULT_MAX_ATTRIBUTES_PER_SPAN + 2);
+        assert_eq!(dropped(&span, DROPPED_ATTRIBUTES_ATTR), Some(Ele::I64(2)));
+
+        span.end_error(Some("boom".to_string()));
+        assert!(span.is_ended());
+        assert_eq!(span.status(), SpanStatus::Error("boom".to_string()));
+        assert!(span.add_event("after_close", None).is_err());
+    }
+
+    #[test]
+    fn test_add_link_across_traces() {
+        let step = Span::new_root("step", Some("train"), None);
+        let mut upload = Span::new_root("upload_checkpoint", None, None);
+        upload
+            .add_link(
+                step.trace_id,
+                step.span_id,
+                Some(vec![attr("link.kind", "persists")]),
+            )
+            .unwrap();
+        assert_eq!(
+            upload.links,
+            vec![Link {
+                trace_id: step.trace_id,
+                span_id: step.span_id,
+                attributes: vec![attr("link.kind", "persists")],
+            }]
+        );
+
+        upload.end();
+        assert_eq!(
+            upload.add_link(step.trace_id, step.span_id, None),
+            Err(super::super::TraceError::SpanAlreadyClosed)
+        );
+        assert_eq!(upload.links.len(), 1);
+    }
+
     #[test]
     fn test_trace_id_generation() {
-        // First trace - should get a trace_id from atomic counter
+        // First trace - should get a fresh trace_id
         let span1 = Span::new_root("span1", None, None);
         let trace_id1 = span1.trace_id;
         assert!(trace_id1 > 0, "Trace ID should be positive");
@@ -460,4 +713,46 @@ mod tests {
         let span_id2 = span2.span_id;
         assert!(span_id2 > span_id1, "Span ID should increment");
     }
+
+    #[test]
+    fn ids_fit_in_i64_and_carry_the_process_epoch() {
+        let id = Span::next_id();
+        assert!(i64::try_from(id).is_ok_and(|v| v > 0));
+        assert_eq!(
+            id >> (COUNTER_BITS + THREAD_BITS),
+            *ID_EPOCH >> (COUNTER_BITS + THREAD_BITS)
+        );
+        assert_eq!(id & (THREAD_MASK << COUNTER_BITS), id_thread_bits());
+    }
+
+    #[test]
+    fn concurrent_ids_are_unique() {
+        const THREADS: usize = 16;
+        const PER_THREAD: usize = 250_000;
+        let barrier = std::sync::Barrier::new(THREADS);
+        let per_thread: Vec<Vec<u64>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        (0..PER_THREAD).map(|_| Span::next_id()).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let mut seen = std::collections::HashSet::with_capacity(THREADS * PER_THREAD);
+        for ids in &per_thread {
+            assert!(
+                ids.windows(2).all(|w| w[0] < w[1]),
+                "ids increase per thread"
+            );
+            for &id in ids {
+                assert!(id > 0 && id <= i64::MAX as u64);
+                assert!(seen.insert(id), "duplicate id {id:#x}");
+            }
+        }
+        assert_eq!(seen.len(), THREADS * PER_THREAD);
+    }
 }
@@ -1,7 +1,10 @@
 use std::cell::RefCell;
-use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
 static GLOBAL_MICRO_STEP: AtomicU64 = AtomicU64::new(0);
+/// Set once any thread reports a step.
+static STEP_REPORTED: AtomicBool = AtomicBool::new(false);
 static GLOBAL_MICRO_BATCHES: AtomicU64 = AtomicU64::new(1);
 static CACHED_RANK: AtomicI64 = AtomicI64::new(0);
 static CACHED_WORLD_SIZE: AtomicI64 = AtomicI64::new(1);
@@ -80,6 +83,68 @@ impl StepContext {
 fn publish_global(micro_step: u64, micro_batches: u64) {
     GLOBAL_MICRO_STEP.fetch_max(micro_step, Ordering::Relaxed);
     GLOBAL_MICRO_BATCHES.store(micro_batches.max(1), Ordering::Relaxed);
+    if !STEP_REPORTED.swap(true, Ordering::Relaxed) && !step_provider_active() {
+        register_step_provider(training_step_provider());
+    }
+}
+
+/// Column the time-series samplers (`cpu.utilization`, `cpu.tasks`,
+/// `gpu.utilization`, `python.inference_engine_metric`) stamp with the step
+/// at sample time.
+pub const STEP_COLUMN: &str = "step";
+
+/// Stored in [`STEP_COLUMN`] when no step is known; SQL reads it as NULL.
+pub const NO_STEP: i64 = -1;
+
+/// Current step for a sample, or `None` before the first step.
+pub type StepProvider = Arc<dyn Fn() -> Option<u64> + Send + Sync>;
+
+static STEP_PROVIDER: RwLock<Option<StepProvider>> = RwLock::new(None);
+
+/// Make `provider` the source of [`sampled_step`], replacing any other.
+///
+/// The first step reported through `train.step` / `probing.step()` registers
+/// [`training_step_provider`] unless a provider is already registered.
+pub fn register_step_provider(provider: StepProvider) {
+    *STEP_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = Some(provider);
+}
+
+pub fn clear_step_provider() {
+    *STEP_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn step_provider_active() -> bool {
+    STEP_PROVIDER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some()
+}
+
+/// Provider backed by the process-wide training step (the high-water
+/// `global_step` of all threads); `None` until some thread reports a step.
+pub fn training_step_provider() -> StepProvider {
+    Arc::new(|| {
+        STEP_REPORTED
+            .load(Ordering::Relaxed)
+            .then(|| atomic_step_snapshot().global_step)
+    })
+}
+
+/// Step of a sample taken now: `None` without a provider or before its first
+/// step.
+pub fn sampled_step() -> Option<u64> {
+    let provider = STEP_PROVIDER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()?;
+    provider()
+}
+
+/// [`sampled_step`] as stored in [`STEP_COLUMN`].
+pub fn sampled_step_value() -> i64 {
+    sampled_step()
+        .and_then(|step| i64::try_from(step).ok())
+        .unwrap_or(NO_STEP)
 }
 
 /// Best-effort step coordinates for crash reporting (prefers thread-local, falls
@@ -192,6 +257,35 @@ mod tests {
         assert_eq!(snap.global_step, 99);
     }
 
+    #[test]
+    fn step_provider_follows_a_counter_advancing_between_samples() {
+        // Report a step first so no later first report swaps in the training
+        // provider while this test runs.
+        let _ = sync_micro_step(0);
+        let counter = Arc::new(AtomicU64::new(0));
+        let started = Arc::new(AtomicBool::new(false));
+        let (c, s) = (counter.clone(), started.clone());
+        register_step_provider(Arc::new(move || {
+            s.load(Ordering::Relaxed).then(|| c.load(Ordering::Relaxed))
+        }));
+
+        assert_eq!(sampled_step(), None, "no step before the first one");
+        assert_eq!(sampled_step_value(), NO_STEP);
+        started.store(true, Ordering::Relaxed);
+        let samples: Vec<i64> = (0..3)
+            .map(|_| {
+                let value = sampled_step_value();
+                counter.fetch_add(1, Ordering::Relaxed);
+                value
+            })
+            .collect();
+        assert_eq!(samples, [0, 1, 2]);
+
+        clear_step_provider();
+        assert_eq!(sampled_step(), None);
+        assert!(!step_provider_active());
+    }
+
     #[test]
     fn micro_batches_groups_training_steps() {
         set_micro_batches(10);
@@ -0,0 +1,372 @@
+//! Span trees assembled from `python.trace_event` rows.
+//!
+//! [`build_span_tree`] accepts paired `span` rows (with `end_time`) as well as
+//! raw `span_start` / `span_end` rows, plus `event` rows. Rows are keyed on
+//! `(thread_id, span_id)` like the pairing table, since span ids are only
+//! unique per thread. `parent_id` of `-1` and NULL both mean "root". A span
+//! whose parent is not among the rows (e.g. cut off by a `LIMIT`) becomes a
+//! root but keeps its `parent_id`; a span without an end keeps
+//! `end_timestamp: None`.
+
+use std::collections::HashMap;
+
+use probing_proto::prelude::{DataFrame, Ele};
+use serde::{Deserialize, Serialize};
+
+/// One `python.trace_event` row, as far as the tree needs it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpanRecord {
+    pub record_type: String,
+    pub trace_id: i64,
+    pub span_id: i64,
+    pub parent_id: Option<i64>,
+    pub name: String,
+    /// Event time (ns).
+    pub time: i64,
+    /// End time of a paired `span` row; `None` while unfinished.
+    pub end_time: Option<i64>,
+    pub thread_id: i64,
+    pub phase: Option<String>,
+    pub location: Option<String>,
+    pub attributes: Option<String>,
+    pub event_attributes: Option<String>,
+    /// Thread CPU time (ns) of a paired `span` or `span_end` row, if sampled.
+    pub cpu_time_ns: Option<i64>,
+    pub ctx_switches: Option<i64>,
+    /// JSON list of linked spans, from a paired `span` or `span_end` row.
+    pub links: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanNode {
+    pub span_id: i64,
+    pub trace_id: i64,
+    pub parent_id: Option<i64>,
+    pub name: String,
+    pub start_timestamp: i64,
+    pub end_timestamp: Option<i64>,
+    pub thread_id: i64,
+    pub phase: Option<String>,
+    pub location: Option<String>,
+    pub attributes: Option<String>,
+    /// See [`crate::trace::cpu`]; `None` unless `probing.trace.cpu_time` was on.
+    #[serde(default)]
+    pub cpu_time_ns: Option<i64>,
+    #[serde(default)]
+    pub ctx_switches: Option<i64>,
+    /// JSON list of `{trace_id, span_id, attributes}` this span links to.
+    #[serde(default)]
+    pub links: Option<String>,
+    pub children: Vec<SpanNode>,
+    pub events: Vec<SpanEventNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanEventNode {
+    pub name: String,
+    pub timestamp: i64,
+    pub attributes: Option<String>,
+}
+
+impl SpanRecord {
+    /// Rows of a `python.trace_event` query; columns are matched by name and
+    /// `time` may also be selected as `timestamp`.
+    pub fn from_dataframe(df: &DataFrame) -> Vec<SpanRecord> {
+        let col = |name: &str| df.names.iter().position(|n| n == name);
+        let (record_type, trace_id, span_id, parent_id) = (
+            col("record_type"),
+            col("trace_id"),
+            col("span_id"),
+            col("parent_id"),
+        );
+        let (name, time, end_time, thread_id) = (
+            col("name"),
+            col("time").or_else(|| col("timestamp")),
+            col("end_time"),
+            col("thread_id"),
+        );
+        let (phase, location, attributes, event_attributes) = (
+            col("phase"),
+            col("location"),
+            col("attributes"),
+            col("event_attributes"),
+        );
+        let (cpu_time_ns, ctx_switches, links) =
+            (col("cpu_time_ns"), col("ctx_switches"), col("links"));
+        let nrows = df.cols.iter().map(|c| c.len()).max().unwrap_or(0);
+        (0..nrows)
+            .map(|row| {
+                let ele = |idx: Option<usize>| match idx.and_then(|i| df.cols.get(i)) {
+                    Some(c) if row < c.len() => c.get(row),
+                    _ => Ele::Nil,
+                };
+                let int = |idx| as_i64(ele(idx));
+                // Negative counters mean "not sampled".
+                let counter = |idx| int(idx).filter(|v| *v >= 0);
+                let text = |idx| match ele(idx) {
+                    Ele::Text(s) | Ele::Url(s) if !s.is_empty() => Some(s),
+                    _ => None,
+                };
+                SpanRecord {
+                    record_type: text(record_type).unwrap_or_default(),
+                    trace_id: int(trace_id).unwrap_or(0),
+                    span_id: int(span_id).unwrap_or(0),
+                    parent_id: int(parent_id),
+                    name: text(name).unwrap_or_default(),
+                    time: int(time).unwrap_or(0),
+                    end_time: int(end_time),
+                    thread_id: int(thread_id).unwrap_or(0),
+                    phase: text(phase),
+                    location: text(location),
+                    attributes: text(attributes),
+                    event_attributes: text(event_attributes),
+                    cpu_time_ns: counter(cpu_time_ns),
+                    ctx_switches: counter(ctx_switches),
+                    links: text(links),
+                }
+            })
+            .collect()
+    }
+}
+
+fn as_i64(ele: Ele) -> Option<i64> {
+    match ele {
+        Ele::I32(x) => Some(x as i64),
+        Ele::I64(x) | Ele::DateTime(x) => Some(x),
+        Ele::F32(x) => Some(x as i64),
+        Ele::F64(x) => Some(x as i64),
+        Ele::DataTime(x) => i64::try_from(x).ok(),
+        Ele::Text(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Order within one timestamp: spans open before their events, which come
+/// before span ends.
+fn record_order(record_type: &str) -> u8 {
+    match record_type {
+        "span" | "span_start" => 0,
+        "event" => 1,
+        _ => 2,
+    }
+}
+
+/// Root spans ordered by start time, each with its children and events.
+pub fn build_span_tree(mut records: Vec<SpanRecord>) -> Vec<SpanNode> {
+    records.sort_by_key(|r| (r.time, record_order(&r.record_type)));
+
+    let mut nodes: Vec<SpanNode> = Vec::new();
+    // Latest span per (thread_id, span_id), so a reused id pairs with its
+    // most recent start.
+    let mut latest: HashMap<(i64, i64), usize> = HashMap::new();
+    for r in records {
+        let key = (r.thread_id, r.span_id);
+        match r.record_type.as_str() {
+            "span" | "span_start" => {
+                // The same span as both a paired and a raw start row.
+                if let Some(&i) = latest.get(&key) {
+                    if nodes[i].start_timestamp == r.time {
+                        let node = &mut nodes[i];
+                        node.end_timestamp = node.end_timestamp.or(r.end_time);
+                        node.cpu_time_ns = node.cpu_time_ns.or(r.cpu_time_ns);
+                        node.ctx_switches = node.ctx_switches.or(r.ctx_switches);
+                        node.links = node.links.take().or(r.links);
+                        continue;
+                    }
+                }
+                latest.insert(key, nodes.len());
+                nodes.push(SpanNode {
+                    span_id: r.span_id,
+                    trace_id: r.trace_id,
+                    parent_id: r.parent_id.filter(|p| *p != -1),
+                    name: r.name,
+                    start_timestamp: r.time,
+                    end_timestamp: r.end_time,
+                    thread_id: r.thread_id,
+                    phase: r.phase,
+                    location: r.location,
+                    attributes: r.attributes,
+                    cpu_time_ns: r.cpu_time_ns,
+                    ctx_switches: r.ctx_switches,
+                    links: r.links,
+                    children: Vec::new(),
+                    events: Vec::new(),
+                });
+            }
+            "span_end" => {
+                if let Some(&i) = latest.get(&key) {
+                    let node = &mut nodes[i];
+                    node.end_timestamp.get_or_insert(r.time);
+                    node.cpu_time_ns = node.cpu_time_ns.or(r.cpu_time_ns);
+                    node.ctx_switches = node.ctx_switches.or(r.ctx_switches);
+                    node.links = node.links.take().or(r.links);
+                }
+            }
+            "event" => {
+                if let Some(&i) = latest.get(&key) {
+                    nodes[i].events.push(SpanEventNode {
+                        name: r.name,
+                        timestamp: r.time,
+                        attributes: r.event_attributes,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Parents on the same thread first; otherwise any span with that id, as
+    // long as it is unambiguous.
+    let mut by_span: HashMap<i64, Option<usize>> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        by_span
+            .entry(node.span_id)
+            .and_modify(|slot| *slot = None)
+            .or_insert(Some(i));
+    }
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    let mut roots = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let parent = node.parent_id.and_then(|p| {
+            latest
+                .get(&(node.thread_id, p))
+                .copied()
+                .or_else(|| by_span.get(&p).copied().flatten())
+        });
+        match parent {
+            Some(p) if p != i => children[p].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    let mut slots: Vec<Option<SpanNode>> = nodes.into_iter().map(Some).collect();
+    let mut tree: Vec<SpanNode> = Vec::new();
+    // Spans caught in a parent cycle are never reached from a root; they are
+    // picked up by the second pass.
+    for i in roots.into_iter().chain(0..slots.len()) {
+        if let Some(node) = take_subtree(i, &mut slots, &children) {
+            tree.push(node);
+        }
+    }
+    tree.sort_by_key(|s| s.start_timestamp);
+    tree
+}
+
+fn take_subtree(
+    i: usize,
+    slots: &mut [Option<SpanNode>],
+    children: &[Vec<usize>],
+) -> Option<SpanNode> {
+    let mut node = slots[i].take()?;
+    for &child in &children[i] {
+        if let Some(child) = take_subtree(child, slots, children) {
+            node.children.push(child);
+        }
+    }
+    node.children.sort_by_key(|s| s.start_timestamp);
+    Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(record_type: &str, span_id: i64, parent_id: Option<i64>, time: i64) -> SpanRecord {
+        SpanRecord {
+            record_type: record_type.into(),
+            trace_id: 1,
+            span_id,
+            parent_id,
+            name: format!("s{span_id}"),
+            time,
+            thread_id: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_nested_tree_from_raw_rows() {
+        let mut event = row("event", 2, Some(1), 25);
+        event.name = "prefill".into();
+        let mut end = row("span_end", 2, Some(-1), 30);
+        end.cpu_time_ns = Some(4);
+        end.links = Some(r#"[{"trace_id":9,"span_id":4}]"#.into());
+        let tree = build_span_tree(vec![
+            end,
+            row("span_start", 1, Some(-1), 10),
+            row("span_start", 2, Some(1), 20),
+            event,
+            row("span_start", 3, None, 40),
+        ]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].span_id, 1);
+        assert_eq!(tree[0].parent_id, None);
+        assert_eq!(tree[0].end_timestamp, None);
+        let child = &tree[0].children[0];
+        assert_eq!((child.span_id, child.end_timestamp), (2, Some(30)));
+        assert_eq!((child.cpu_time_ns, tree[0].cpu_time_ns), (Some(4), None));
+        assert!(child.links.as_deref().unwrap().contains("\"span_id\":4"));
+        assert_eq!(child.events[0].name, "prefill");
+        // NULL parent is a root, same as -1.
+        assert_eq!((tree[1].span_id, tree[1].parent_id), (3, None));
+    }
+
+    #[test]
+    fn keeps_orphans_and_paired_rows() {
+        let mut paired = row("span", 5, Some(4), 50);
+        paired.end_time = Some(90);
+        let mut other_thread = row("span", 6, Some(5), 60);
+        other_thread.thread_id = 8;
+        let tree = build_span_tree(vec![
+            paired,
+            other_thread,
+            row("span_start", 5, Some(4), 50),
+        ]);
+        assert_eq!(tree.len(), 1);
+        // Parent 4 was filtered out: the span is a root that keeps parent_id.
+        assert_eq!(tree[0].parent_id, Some(4));
+        assert_eq!(tree[0].end_timestamp, Some(90));
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(tree[0].children[0].span_id, 6);
+    }
+
+    #[test]
+    fn parent_cycles_do_not_lose_spans() {
+        let tree = build_span_tree(vec![row("span", 1, Some(2), 1), row("span", 2, Some(1), 2)]);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].children.len(), 1);
+    }
+
+    #[test]
+    fn reads_records_from_dataframe() {
+        use probing_proto::prelude::Seq;
+        let df = DataFrame::new(
+            [
+                "record_type",
+                "span_id",
+                "parent_id",
+                "time",
+                "end_time",
+                "phase",
+                "cpu_time_ns",
+            ]
+            .map(String::from)
+            .to_vec(),
+            vec![
+                Seq::SeqText(vec!["span".into()]),
+                Seq::SeqI64(vec![3]),
+                Seq::SeqI64(vec![-1]),
+                Seq::SeqI64(vec![100]),
+                Seq::Nil,
+                Seq::SeqText(vec![String::new()]),
+                Seq::SeqI64(vec![-1]),
+            ],
+        );
+        let records = SpanRecord::from_dataframe(&df);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].parent_id, Some(-1));
+        assert_eq!(records[0].end_time, None);
+        assert_eq!(records[0].phase, None);
+        assert_eq!(records[0].cpu_time_ns, None, "-1 is not sampled");
+    }
+}
@@ -0,0 +1,165 @@
+//! `PROBING_LOG_FORMAT=json` line encoder.
+
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_log::NormalizedMetadata;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// One JSON object per event: `{"ts","level","target","msg","fields"}`.
+///
+/// `fields` holds the event's own fields plus those of every enclosing span
+/// (outermost first, inner spans win on key collisions). Span fields must be
+/// recorded with [`tracing_subscriber::fmt::format::JsonFields`]. The
+/// [`current_request_id`](crate::current_request_id) is added as `request_id`
+/// when no span carries one, so it survives filters that disable the
+/// request span.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let normalized = event.normalized_metadata();
+        let meta = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut fields = Map::new();
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(recorded) = extensions.get::<FormattedFields<N>>() else {
+                    continue;
+                };
+                if let Ok(Value::Object(map)) = serde_json::from_str::<Value>(recorded.as_str()) {
+                    fields.extend(map);
+                }
+            }
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        fields.extend(visitor.fields);
+        if let Some(id) = crate::current_request_id() {
+            fields
+                .entry("request_id")
+                .or_insert_with(|| Value::String(id));
+        }
+
+        let line = json!({
+            "ts": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            "level": meta.level().as_str(),
+            "target": meta.target(),
+            "msg": visitor.message.unwrap_or_default(),
+            "fields": fields,
+        });
+        writeln!(writer, "{line}")
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let name = field.name();
+        // `log.target` / `log.file` / … are bridge bookkeeping, already folded
+        // into the normalized metadata.
+        if name.starts_with("log.") {
+            return;
+        }
+        if name == "message" {
+            self.message = Some(match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(name.to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::EnvFilter;
+
+    use crate::testing::CaptureWriter;
+    use crate::{build_subscriber, with_request_id, LogFormat};
+
+    #[test]
+    fn emits_span_and_event_fields() {
+        let capture = CaptureWriter::default();
+        let subscriber = build_subscriber(LogFormat::Json, EnvFilter::new("info"), capture.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc123");
+            let _guard = span.enter();
+            tracing::warn!(rows = 3, "query failed");
+        });
+
+        let line: serde_json::Value = serde_json::from_str(capture.contents().trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["msg"], "query failed");
+        assert_eq!(line["fields"]["rows"], 3);
+        assert_eq!(line["fields"]["request_id"], "abc123");
+        assert!(line["ts"].as_str().is_some());
+        assert_eq!(line["target"], module_path!());
+    }
+
+    #[tokio::test]
+    async fn request_id_survives_the_default_filter() {
+        let capture = CaptureWriter::default();
+        let subscriber =
+            build_subscriber(LogFormat::Json, EnvFilter::new("error"), capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+        with_request_id("abc123".to_string(), async {
+            let span = tracing::info_span!("request", request_id = "abc123");
+            let _entered = span.enter();
+            tracing::error!("query failed");
+        })
+        .await;
+
+        let line: serde_json::Value = serde_json::from_str(capture.contents().trim()).unwrap();
+        assert_eq!(line["level"], "ERROR");
+        assert_eq!(line["fields"]["request_id"], "abc123");
+    }
+}
@@ -0,0 +1,270 @@
+//! Process-wide log pipeline for probing binaries and the injected library.
+//!
+//! Both `log::` macros (bridged through [`tracing_log::LogTracer`]) and native
+//! `tracing` events end up in one `tracing-subscriber` pipeline:
+//!
+//! - `PROBING_LOGLEVEL` — filter directives (`info`, `probing_server=debug`, …);
+//!   defaults to `error`, matching the previous `env_logger` behaviour.
+//! - `PROBING_LOG_FORMAT=json` — one JSON object per line with `ts`, `level`,
+//!   `target`, `msg` and `fields` (event fields merged with enclosing span fields,
+//!   e.g. the HTTP `request_id`). Any other value keeps the human-readable format.
+//!   Both formats, and [`RecentLogs`], tag events logged while serving a request
+//!   with its [`current_request_id`], whatever level the request span has.
+//!
+//! The filter can be swapped at runtime with [`set_filter`] (the `probing.log.*`
+//! options), and every record that passes it is also kept in the bounded
+//! [`RecentLogs`] ring for remote retrieval.
+
+mod json;
+pub mod recent;
+pub mod request_id;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+mod text;
+
+use std::sync::{Once, OnceLock};
+
+use tracing::Subscriber;
+use tracing_log::AsLog;
+use tracing_subscriber::fmt::format::JsonFields;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+pub use json::JsonLines;
+pub use recent::{LogRecord, RecentLogs};
+pub use request_id::{
+    accept_or_new, current_request_id, new_request_id, with_request_id, REQUEST_ID_HEADER,
+};
+pub use text::TextLines;
+
+pub const ENV_PROBING_LOGLEVEL: &str = "PROBING_LOGLEVEL";
+pub const ENV_PROBING_LOG_FORMAT: &str = "PROBING_LOG_FORMAT";
+
+const DEFAULT_FILTER: &str = "error";
+
+/// Output encoding for log lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Self {
+        if value.trim().eq_ignore_ascii_case("json") {
+            Self::Json
+        } else {
+            Self::Text
+        }
+    }
+
+    pub fn from_env() -> Self {
+        std::env::var(ENV_PROBING_LOG_FORMAT)
+            .map(|v| Self::parse(&v))
+            .unwrap_or_default()
+    }
+}
+
+/// Filter from `PROBING_LOGLEVEL`; invalid directives fall back to the default.
+pub fn env_filter() -> EnvFilter {
+    match std::env::var(ENV_PROBING_LOGLEVEL) {
+        Ok(spec) if !spec.trim().is_empty() => {
+            EnvFilter::try_new(spec.trim()).unwrap_or_else(|e| {
+                eprintln!("probing: ignoring invalid {ENV_PROBING_LOGLEVEL}={spec:?}: {e}");
+                EnvFilter::new(DEFAULT_FILTER)
+            })
+        }
+        _ => EnvFilter::new(DEFAULT_FILTER),
+    }
+}
+
+/// Handle for swapping the filter of a subscriber built by
+/// [`build_reloadable_subscriber`].
+pub type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+static FILTER_HANDLE: OnceLock<FilterHandle> = OnceLock::new();
+
+/// Build a subscriber writing to `writer`. Exposed so tests can capture output.
+pub fn build_subscriber<W>(
+    format: LogFormat,
+    filter: EnvFilter,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    build_reloadable_subscriber(format, filter, writer, None).0
+}
+
+/// Like [`build_subscriber`], but the filter can be replaced later through the
+/// returned handle, and records passing it are copied into `recent` if given.
+pub fn build_reloadable_subscriber<W>(
+    format: LogFormat,
+    filter: EnvFilter,
+    writer: W,
+    recent: Option<RecentLogs>,
+) -> (Box<dyn Subscriber + Send + Sync>, FilterHandle)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let (filter, handle) = reload::Layer::new(filter);
+    let registry = tracing_subscriber::registry().with(filter).with(recent);
+    let subscriber: Box<dyn Subscriber + Send + Sync> = match format {
+        LogFormat::Text => Box::new(
+            registry.with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(TextLines::default())
+                    .with_writer(writer),
+            ),
+        ),
+        LogFormat::Json => Box::new(
+            registry.with(
+                tracing_subscriber::fmt::layer()
+                    .fmt_fields(JsonFields::new())
+                    .event_format(JsonLines)
+                    .with_writer(writer),
+            ),
+        ),
+    };
+    (subscriber, handle)
+}
+
+/// Filter directives for a base `level` plus comma-separated per-target
+/// overrides (`probing_core::trace=debug,probing_server=warn`). Without a
+/// level the `PROBING_LOGLEVEL` directives (or the default) stay in effect.
+pub fn filter_spec(level: Option<&str>, targets: Option<&str>) -> String {
+    let base = match level.map(str::trim).filter(|l| !l.is_empty()) {
+        Some(level) => level.to_string(),
+        None => std::env::var(ENV_PROBING_LOGLEVEL)
+            .ok()
+            .map(|spec| spec.trim().to_string())
+            .filter(|spec| !spec.is_empty())
+            .unwrap_or_else(|| DEFAULT_FILTER.to_string()),
+    };
+    match targets.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(targets) => format!("{base},{targets}"),
+        None => base,
+    }
+}
+
+fn parse_filter(spec: &str) -> Result<EnvFilter, String> {
+    EnvFilter::try_new(spec).map_err(|e| format!("invalid filter {spec:?}: {e}"))
+}
+
+/// Validate filter directives without applying them.
+pub fn check_filter(spec: &str) -> Result<(), String> {
+    parse_filter(spec).map(drop)
+}
+
+/// Replace the filter behind `handle` with `spec`; returns the applied spec.
+pub fn reload_filter(handle: &FilterHandle, spec: &str) -> Result<String, String> {
+    let filter = parse_filter(spec)?;
+    // The `log` bridge drops records above `log::max_level()` before they reach
+    // the filter, so widen (or narrow) it along with the new directives.
+    let max_level = filter
+        .max_level_hint()
+        .map_or(log::LevelFilter::Trace, |level| level.as_log());
+    handle.reload(filter).map_err(|e| e.to_string())?;
+    log::set_max_level(max_level);
+    Ok(spec.to_string())
+}
+
+/// Apply [`filter_spec`]`(level, targets)` to the global pipeline without a
+/// restart. Fails if [`init`] did not install the process-wide subscriber.
+pub fn set_filter(level: Option<&str>, targets: Option<&str>) -> Result<String, String> {
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "probing log pipeline is not installed in this process".to_string())?;
+    reload_filter(handle, &filter_spec(level, targets))
+}
+
+/// Bridge `log` records up to `max_level` into `tracing` (idempotent).
+pub fn init_log_bridge(max_level: log::LevelFilter) {
+    // Fails only when another `log` logger is already installed; keep that one.
+    let _ = tracing_log::LogTracer::builder()
+        .with_max_level(max_level)
+        .init();
+}
+
+/// Install the global stderr pipeline once per process. Later calls are no-ops,
+/// so the `#[ctor]` hook and the Python module init can both call it.
+pub fn init() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let filter = env_filter();
+        // Skip formatting `log` records the filter would drop anyway.
+        let max_level = filter
+            .max_level_hint()
+            .map_or(log::LevelFilter::Trace, |level| level.as_log());
+        init_log_bridge(max_level);
+        let (subscriber, handle) = build_reloadable_subscriber(
+            LogFormat::from_env(),
+            filter,
+            std::io::stderr,
+            Some(RecentLogs::global().clone()),
+        );
+        // Another global subscriber (e.g. an embedding application) wins.
+        if tracing::subscriber::set_global_default(subscriber).is_ok() {
+            let _ = FILTER_HANDLE.set(handle);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_format_case_insensitively() {
+        assert_eq!(LogFormat::parse("JSON"), LogFormat::Json);
+        assert_eq!(LogFormat::parse(" json "), LogFormat::Json);
+        assert_eq!(LogFormat::parse("text"), LogFormat::Text);
+        assert_eq!(LogFormat::parse(""), LogFormat::Text);
+    }
+
+    #[test]
+    fn filter_spec_appends_target_overrides() {
+        assert_eq!(
+            filter_spec(
+                Some("info"),
+                Some("probing_core::trace=debug,probing_server=warn")
+            ),
+            "info,probing_core::trace=debug,probing_server=warn"
+        );
+        assert_eq!(filter_spec(Some("warn"), Some(" ")), "warn");
+    }
+
+    #[test]
+    fn reload_filter_applies_without_rebuilding() {
+        let capture = testing::CaptureWriter::default();
+        let ring = RecentLogs::new(16);
+        let (subscriber, handle) = build_reloadable_subscriber(
+            LogFormat::Text,
+            EnvFilter::new("error"),
+            capture.clone(),
+            Some(ring.clone()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "probing_core::trace", "hidden");
+            reload_filter(
+                &handle,
+                &filter_spec(Some("warn"), Some("probing_core::trace=debug")),
+            )
+            .unwrap();
+            tracing::debug!(target: "probing_core::trace", "traced");
+            tracing::debug!(target: "probing_server", "still hidden");
+            tracing::warn!(target: "probing_server", "server warning");
+        });
+
+        let msgs: Vec<String> = ring
+            .recent(None, None, 16)
+            .into_iter()
+            .map(|r| r.msg)
+            .collect();
+        assert_eq!(msgs, ["traced", "server warning"]);
+        assert!(!capture.contents().contains("hidden"));
+        assert!(reload_filter(&handle, "probing_core=nonsense").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Bounded in-memory copy of probing's own log records, so diagnostics can be
+//! pulled remotely (`GET /apis/logs/recent`) instead of scraped from the
+//! user's terminal.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use serde_json::{json, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_log::NormalizedMetadata;
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Records kept by the process-wide ring installed by [`crate::init`].
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub ts_us: i64,
+    pub level: Level,
+    pub target: String,
+    /// Message followed by `key=value` for any extra event fields.
+    pub msg: String,
+}
+
+impl LogRecord {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "ts_us": self.ts_us,
+            "level": self.level.as_str(),
+            "target": self.target,
+            "msg": self.msg,
+        })
+    }
+}
+
+/// Ring-buffer layer; clones share the same buffer.
+#[derive(Debug, Clone)]
+pub struct RecentLogs {
+    capacity: usize,
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+}
+
+impl RecentLogs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity.max(1)))),
+        }
+    }
+
+    /// The ring fed by the global pipeline (empty until [`crate::init`] runs).
+    pub fn global() -> &'static RecentLogs {
+        static GLOBAL: OnceLock<RecentLogs> = OnceLock::new();
+        GLOBAL.get_or_init(|| RecentLogs::new(DEFAULT_CAPACITY))
+    }
+
+    /// Up to `limit` newest records (oldest first) at `min_level` or more severe
+    /// whose target starts with `target`.
+    pub fn recent(
+        &self,
+        min_level: Option<Level>,
+        target: Option<&str>,
+        limit: usize,
+    ) -> Vec<LogRecord> {
+        let records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out: Vec<LogRecord> = records
+            .iter()
+            .rev()
+            .filter(|r| min_level.is_none_or(|min| r.level <= min))
+            .filter(|r| target.is_none_or(|t| r.target.starts_with(t)))
+            .take(limit)
+            .cloned()
+            .collect();
+        out.reverse();
+        out
+    }
+
+    fn push(&self, record: LogRecord) {
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let normalized = event.normalized_metadata();
+        let meta = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut msg = visitor.finish();
+        if let Some(id) = crate::current_request_id() {
+            if !msg.contains("request_id=") {
+                let _ = write!(msg, " request_id={id}");
+            }
+        }
+        self.push(LogRecord {
+            ts_us: chrono::Utc::now().timestamp_micros(),
+            level: *meta.level(),
+            target: meta.target().to_string(),
+            msg,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(mut self) -> String {
+        if !self.fields.is_empty() {
+            if !self.message.is_empty() {
+                self.message.push(' ');
+            }
+            self.message.push_str(self.fields.trim_start());
+        }
+        self.message
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.message, "{value:?}");
+            }
+            // `log` bridge bookkeeping, already folded into the metadata.
+            name if name.starts_with("log.") => {}
+            name => {
+                let _ = write!(self.fields, " {name}={value:?}");
+            }
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            name if name.starts_with("log.") => {}
+            name => {
+                let _ = write!(self.fields, " {name}={value}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn keeps_newest_records_and_filters() {
+        let ring = RecentLogs::new(3);
+        let subscriber = tracing_subscriber::registry().with(ring.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "probing_core::trace", "one");
+            tracing::warn!(target: "probing_server", rows = 2, "two");
+            tracing::debug!(target: "probing_core::trace", "three");
+            tracing::error!(target: "probing_core::engine", "four");
+        });
+
+        let all = ring.recent(None, None, 10);
+        let msgs: Vec<&str> = all.iter().map(|r| r.msg.as_str()).collect();
+        assert_eq!(msgs, ["two rows=2", "three", "four"]);
+
+        let warn: Vec<String> = ring
+            .recent(Some(Level::WARN), None, 10)
+            .into_iter()
+            .map(|r| r.msg)
+            .collect();
+        assert_eq!(warn, ["two rows=2", "four"]);
+
+        let core = ring.recent(None, Some("probing_core"), 1);
+        assert_eq!(core.len(), 1);
+        assert_eq!(core[0].msg, "four");
+        assert_eq!(core[0].to_json()["level"], "ERROR");
+    }
+
+    #[tokio::test]
+    async fn records_carry_the_current_request_id() {
+        let ring = RecentLogs::new(4);
+        let subscriber = tracing_subscriber::registry().with(ring.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+        crate::with_request_id("req-9".to_string(), async {
+            tracing::error!(rows = 1, "failed");
+        })
+        .await;
+        tracing::error!("later");
+
+        let msgs: Vec<String> = ring
+            .recent(None, None, 10)
+            .into_iter()
+            .map(|r| r.msg)
+            .collect();
+        assert_eq!(msgs, ["failed rows=1 request_id=req-9", "later"]);
+    }
+}
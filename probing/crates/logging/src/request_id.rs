@@ -0,0 +1,73 @@
+//! Per-request correlation id carried through async work and into Python.
+//!
+//! The HTTP layer assigns an id, runs the handler inside [`with_request_id`]
+//! and echoes it back as `X-Request-Id`. Code further down (engine, extension
+//! calls, Python handlers) reads it with [`current_request_id`] without any
+//! signature changes.
+
+use std::future::Future;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Client-supplied ids longer than this (or with other characters than
+/// `[A-Za-z0-9._-]`) are replaced, so they cannot inject into log lines.
+const MAX_CLIENT_ID_LEN: usize = 64;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Fresh random id (32 lowercase hex chars).
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Reuse a well-formed client id (e.g. from a proxy) or mint a new one.
+pub fn accept_or_new(client_id: Option<&str>) -> String {
+    match client_id.map(str::trim) {
+        Some(id)
+            if !id.is_empty()
+                && id.len() <= MAX_CLIENT_ID_LEN
+                && id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-')) =>
+        {
+            id.to_string()
+        }
+        _ => new_request_id(),
+    }
+}
+
+/// Run `fut` with `id` as the current request id.
+pub async fn with_request_id<F: Future>(id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(id, fut).await
+}
+
+/// Request id of the enclosing [`with_request_id`] scope, if any.
+///
+/// Task-locals do not cross `spawn` / `spawn_blocking`; capture the value
+/// before handing work to another task or thread.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_malformed_client_ids() {
+        assert_eq!(accept_or_new(Some("trace-42.a_b")), "trace-42.a_b");
+        assert_ne!(accept_or_new(Some("bad id\n")), "bad id\n");
+        assert_eq!(accept_or_new(Some(&"x".repeat(65))).len(), 32);
+        assert_eq!(accept_or_new(None).len(), 32);
+    }
+
+    #[tokio::test]
+    async fn scoped_id_is_visible_only_inside() {
+        assert_eq!(current_request_id(), None);
+        let seen = with_request_id("req-1".to_string(), async { current_request_id() }).await;
+        assert_eq!(seen.as_deref(), Some("req-1"));
+        assert_eq!(current_request_id(), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! Human-readable line encoder (the default `PROBING_LOG_FORMAT`).
+
+use std::fmt;
+
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::{Format, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// `tracing_subscriber`'s full text format with targets, plus
+/// ` request_id=<id>` for events inside a request whose span is not shown
+/// (the default `error` filter disables the INFO request span).
+#[derive(Debug)]
+pub struct TextLines(Format);
+
+impl Default for TextLines {
+    fn default() -> Self {
+        Self(Format::default().with_target(true))
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for TextLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let Some(id) = crate::current_request_id() else {
+            return self.0.format_event(ctx, writer, event);
+        };
+        // Buffered to add the id before the newline; such lines lose ANSI
+        // colors, which a plain `Writer` cannot carry.
+        let mut line = String::new();
+        self.0.format_event(ctx, Writer::new(&mut line), event)?;
+        let line = line.trim_end_matches('\n');
+        if line.contains(&format!("request_id={id}")) {
+            writeln!(writer, "{line}")
+        } else {
+            writeln!(writer, "{line} request_id={id}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::EnvFilter;
+
+    use crate::testing::CaptureWriter;
+    use crate::{build_subscriber, with_request_id, LogFormat};
+
+    #[tokio::test]
+    async fn request_id_is_appended_when_the_span_is_filtered_out() {
+        let capture = CaptureWriter::default();
+        let subscriber =
+            build_subscriber(LogFormat::Text, EnvFilter::new("error"), capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+        with_request_id("req-7".to_string(), async {
+            let span = tracing::info_span!("request", request_id = "req-7");
+            let _entered = span.enter();
+            tracing::error!("query failed");
+        })
+        .await;
+        tracing::error!("outside");
+
+        let logs = capture.contents();
+        let lines: Vec<&str> = logs.lines().collect();
+        assert_eq!(lines.len(), 2, "{logs}");
+        assert!(
+            lines[0].ends_with("query failed request_id=req-7"),
+            "{logs}"
+        );
+        assert_eq!(lines[0].matches("request_id=").count(), 1);
+        assert!(!lines[1].contains("request_id"), "{logs}");
+    }
+}
@@ -322,7 +322,7 @@ fn ele_f64(ele: &Ele) -> Option<f64> {
     match ele {
         Ele::F64(x) => Some(*x),
         Ele::F32(x) => Some(*x as f64),
-        Ele::I64(x) => Some(*x as f64),
+        Ele::I64(x) | Ele::DateTime(x) => Some(*x as f64),
         Ele::I32(x) => Some(*x as f64),
         Ele::Text(s) => s.parse().ok(),
         _ => None,
@@ -340,6 +340,7 @@ fn ele_str(ele: &Ele) -> String {
         Ele::F64(x) => x.to_string(),
         Ele::Url(u) => u.clone(),
         Ele::DataTime(t) => t.to_string(),
+        Ele::DateTime(_) => ele.to_string(),
     }
 }
 
@@ -9,6 +9,7 @@ use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
 use probing_core::sync::lock_mutex;
+use probing_core::trace::{sampled_step_value, STEP_COLUMN};
 use probing_memtable::discover::ExposedTable;
 use probing_memtable::{DType, Schema, Value};
 use thiserror::Error;
@@ -63,6 +64,11 @@ pub fn start_cpu_sampling(interval_ms: u64, thread_top_n: usize) -> Result<(), C
     }
 }
 
+/// Stop CPU sampling and join the sampler thread; `Ok` when it is not running.
+pub fn stop_cpu_sampling() -> Result<(), CollectorError> {
+    CpuCollector::instance().stop()
+}
+
 /// Start CPU sampling from env (default on). Call once after engine init.
 pub fn start_cpu_sampling_from_env() {
     let Some(interval_ms) = autostart_interval_ms() else {
@@ -100,6 +106,9 @@ fn utilization_schema() -> Schema {
         .col("delta_invol_ctxt", DType::I64)
         .col("state", DType::Str)
         .col("wchan", DType::Str)
+        .col(STEP_COLUMN, DType::I64)
+        .col("delta_read_bytes", DType::I64)
+        .col("delta_write_bytes", DType::I64)
 }
 
 fn tasks_schema() -> Schema {
@@ -114,6 +123,7 @@ fn tasks_schema() -> Schema {
         .col("delta_user_ns", DType::I64)
         .col("delta_sys_ns", DType::I64)
         .col("delta_total_ns", DType::I64)
+        .col(STEP_COLUMN, DType::I64)
 }
 
 #[derive(Debug, Clone)]
@@ -147,6 +157,10 @@ struct SampleState {
     last_wall: Instant,
     last_process: Option<ProcessSample>,
     last_threads: HashMap<i32, ThreadSample>,
+    /// Set after the first failure of each sampler, so a masked `/proc`
+    /// warns once instead of every interval.
+    process_failed: bool,
+    threads_failed: bool,
 }
 
 impl SampleState {
@@ -155,10 +169,20 @@ impl SampleState {
             last_wall: Instant::now(),
             last_process: None,
             last_threads: HashMap::new(),
+            process_failed: false,
+            threads_failed: false,
         }
     }
 }
 
+fn warn_once(seen: &mut bool, what: &str, err: &std::io::Error) {
+    if std::mem::replace(seen, true) {
+        log::debug!("{what} failed: {err}");
+    } else {
+        log::warn!("{what} failed: {err} (further failures logged at debug)");
+    }
+}
+
 fn pct(delta_ns: u64, wall_ns: u64) -> f32 {
     if wall_ns == 0 {
         return 0.0;
@@ -177,6 +201,7 @@ fn ts_micros() -> i64 {
 fn push_utilization_row(
     table: &mut ExposedTable,
     ts: i64,
+    step: i64,
     platform: &str,
     scope: &str,
     tid: i32,
@@ -190,6 +215,8 @@ fn push_utilization_row(
     thread_count: i32,
     delta_vol_ctxt: i64,
     delta_invol_ctxt: i64,
+    delta_read_bytes: i64,
+    delta_write_bytes: i64,
     state: &str,
     wchan: &str,
 ) {
@@ -215,6 +242,9 @@ fn push_utilization_row(
         Value::I64(delta_invol_ctxt),
         Value::Str(state),
         Value::Str(wchan),
+        Value::I64(step),
+        Value::I64(delta_read_bytes),
+        Value::I64(delta_write_bytes),
     ]) {
         log::warn!("cpu collector: push_row failed for cpu.processes");
     }
@@ -223,6 +253,7 @@ fn push_utilization_row(
 fn push_tasks_row(
     table: &mut ExposedTable,
     ts: i64,
+    step: i64,
     platform: &str,
     thread: &ThreadSample,
     wall_ns: u64,
@@ -243,6 +274,7 @@ fn push_tasks_row(
         Value::I64(delta_user_ns as i64),
         Value::I64(delta_sys_ns as i64),
         Value::I64(delta_total as i64),
+        Value::I64(step),
     ]) {
         log::warn!("cpu collector: push_row failed for cpu.tasks");
     }
@@ -345,6 +377,7 @@ impl CpuCollector {
                     let now = Instant::now();
                     let wall_ns = now.duration_since(state.last_wall).as_nanos() as u64;
                     let ts = ts_micros();
+                    let step = sampled_step_value();
 
                     match sampler.sample_process() {
                         Ok(curr) => {
@@ -358,9 +391,14 @@ impl CpuCollector {
                                         curr.vol_ctxt.saturating_sub(prev.vol_ctxt) as i64;
                                     let delta_invol =
                                         curr.invol_ctxt.saturating_sub(prev.invol_ctxt) as i64;
+                                    let delta_read =
+                                        curr.read_bytes.saturating_sub(prev.read_bytes) as i64;
+                                    let delta_write =
+                                        curr.write_bytes.saturating_sub(prev.write_bytes) as i64;
                                     push_utilization_row(
                                         &mut lock_cpu_table(&tables.utilization),
                                         ts,
+                                        step,
                                         &platform,
                                         "process",
                                         0,
@@ -374,6 +412,8 @@ impl CpuCollector {
                                         curr.thread_count as i32,
                                         delta_vol,
                                         delta_invol,
+                                        delta_read,
+                                        delta_write,
                                         "",
                                         "",
                                     );
@@ -381,7 +421,7 @@ impl CpuCollector {
                             }
                             state.last_process = Some(curr);
                         }
-                        Err(e) => log::warn!("cpu process sample failed: {e}"),
+                        Err(e) => warn_once(&mut state.process_failed, "cpu process sample", &e),
                     }
 
                     match sampler.sample_threads(config.thread_top_n) {
@@ -399,6 +439,7 @@ impl CpuCollector {
                                     push_utilization_row(
                                         &mut lock_cpu_table(&tables.utilization),
                                         ts,
+                                        step,
                                         &platform,
                                         "thread",
                                         thread.tid,
@@ -412,12 +453,15 @@ impl CpuCollector {
                                         0,
                                         0,
                                         0,
+                                        0,
+                                        0,
                                         thread.state.as_deref().unwrap_or(""),
                                         thread.wchan.as_deref().unwrap_or(""),
                                     );
                                     push_tasks_row(
                                         &mut lock_cpu_table(&tables.tasks),
                                         ts,
+                                        step,
                                         &platform,
                                         thread,
                                         wall_ns,
@@ -428,7 +472,7 @@ impl CpuCollector {
                             }
                             state.last_threads = threads.into_iter().map(|t| (t.tid, t)).collect();
                         }
-                        Err(e) => log::warn!("cpu thread sample failed: {e}"),
+                        Err(e) => warn_once(&mut state.threads_failed, "cpu thread sample", &e),
                     }
 
                     state.last_wall = now;
@@ -448,7 +492,6 @@ impl CpuCollector {
         Ok(())
     }
 
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn stop(&self) -> Result<(), CollectorError> {
         if !self.running.swap(false, Ordering::SeqCst) {
             return Ok(());
@@ -544,4 +587,56 @@ mod tests {
             iterations - 1
         );
     }
+
+    #[test]
+    fn samples_carry_the_registered_step() {
+        use datafusion::arrow::array::{Array, Int64Array};
+        use probing_core::core::memtable_sql::view_to_recordbatches;
+        use probing_core::trace::{clear_step_provider, register_step_provider};
+        use std::sync::atomic::AtomicI64;
+
+        let _guard = ENV_TEST_LOCK.lock().unwrap();
+        let collector = CpuCollector::instance();
+        let _ = collector.stop();
+
+        // A simulated training loop: no step yet, then steps 0..=3.
+        let step = Arc::new(AtomicI64::new(-1));
+        let current = step.clone();
+        register_step_provider(Arc::new(move || {
+            u64::try_from(current.load(Ordering::Relaxed)).ok()
+        }));
+        collector
+            .start(CpuCollectorConfig {
+                interval: Duration::from_millis(5),
+                thread_top_n: 0,
+                iterations: None,
+            })
+            .expect("start collector");
+        for n in 0..=3 {
+            std::thread::sleep(Duration::from_millis(40));
+            step.store(n, Ordering::Relaxed);
+        }
+        std::thread::sleep(Duration::from_millis(40));
+        collector.stop().expect("stop collector");
+        clear_step_provider();
+
+        let tables = lock_cpu_collector(&collector.tables).clone().unwrap();
+        let batches = view_to_recordbatches(&lock_cpu_table(&tables.utilization).view());
+        let steps: Vec<i64> = batches
+            .iter()
+            .flat_map(|b| {
+                let col = b.column_by_name(STEP_COLUMN).unwrap();
+                let col = col.as_any().downcast_ref::<Int64Array>().unwrap();
+                (0..col.len())
+                    .filter(|&i| col.is_valid(i))
+                    .map(|i| col.value(i))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert!(steps.contains(&3), "last step sampled: {steps:?}");
+        assert!(
+            steps.windows(2).all(|w| w[0] <= w[1]),
+            "steps follow the counter: {steps:?}"
+        );
+    }
 }
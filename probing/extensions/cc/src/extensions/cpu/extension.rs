@@ -15,11 +15,11 @@ pub struct CpuProbeExtension {
         "taskstats_interval",
         "task_stats_interval",
         "task.stats.interval"
-    ])]
+    ], min = 0)]
     cpu_sample_interval_ms: Maybe<i64>,
 
     /// Max threads to record per sample (0 = process-level only).
-    #[option(aliases = ["thread_top_n"])]
+    #[option(aliases = ["thread_top_n"], min = 0)]
     cpu_thread_top_n: Maybe<i64>,
 }
 
@@ -37,13 +37,6 @@ impl CpuProbeExtension {
             ));
         };
 
-        if interval < 0 {
-            return Err(EngineError::InvalidOptionValue(
-                Self::OPTION_CPU_SAMPLE_INTERVAL_MS.to_string(),
-                cpu_sample_interval_ms.clone().into(),
-            ));
-        }
-
         if interval == 0 {
             self.cpu_sample_interval_ms = cpu_sample_interval_ms;
             return Ok(());
@@ -42,6 +42,11 @@ impl CpuHostSampler for LinuxSampler {
             })
             .unwrap_or((0, 0));
         let thread_count = proc.tasks().map(|tasks| tasks.count() as u32).unwrap_or(0);
+        // `/proc/self/io` is absent without CONFIG_TASK_IO_ACCOUNTING.
+        let (read_bytes, write_bytes) = proc
+            .io()
+            .map(|io| (io.read_bytes, io.write_bytes))
+            .unwrap_or((0, 0));
 
         Ok(ProcessSample {
             cputime_user_ns: self.ticks_to_ns(stat.utime),
@@ -50,6 +55,8 @@ impl CpuHostSampler for LinuxSampler {
             thread_count,
             vol_ctxt,
             invol_ctxt,
+            read_bytes,
+            write_bytes,
         })
     }
 
@@ -60,9 +67,12 @@ impl CpuHostSampler for LinuxSampler {
         let proc = Process::myself().map_err(io::Error::other)?;
         let mut threads = Vec::new();
 
-        for task in proc.tasks().map_err(io::Error::other)? {
-            let task = task.map_err(io::Error::other)?;
-            let stat = task.stat().map_err(io::Error::other)?;
+        // Threads exit between listing and reading, and some containers mask
+        // individual entries: skip those instead of dropping the whole sample.
+        for task in proc.tasks().map_err(io::Error::other)?.flatten() {
+            let Ok(stat) = task.stat() else {
+                continue;
+            };
             let wchan = std::fs::read_to_string(format!("/proc/self/task/{}/wchan", stat.pid))
                 .map(|s| s.trim().to_string())
                 .ok()
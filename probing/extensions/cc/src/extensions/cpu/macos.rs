@@ -235,6 +235,9 @@ impl CpuHostSampler for MacSampler {
             thread_count,
             vol_ctxt: ru.ru_nvcsw as u64,
             invol_ctxt: ru.ru_nivcsw as u64,
+            // rusage only counts blocks.
+            read_bytes: 0,
+            write_bytes: 0,
         })
     }
 
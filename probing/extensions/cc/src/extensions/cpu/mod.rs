@@ -9,7 +9,9 @@ mod sampler;
 #[cfg(not(any(target_os = "linux", target_os = "macos")))]
 mod unsupported;
 
-pub use collector::{autostart_interval_ms, start_cpu_sampling, start_cpu_sampling_from_env};
+pub use collector::{
+    autostart_interval_ms, start_cpu_sampling, start_cpu_sampling_from_env, stop_cpu_sampling,
+};
 pub use extension::CpuProbeExtension;
 #[cfg(target_os = "macos")]
 pub use probing_core::signal::send_sigusr2_to_thread_id;
@@ -9,6 +9,9 @@ pub struct ProcessSample {
     pub thread_count: u32,
     pub vol_ctxt: u64,
     pub invol_ctxt: u64,
+    /// Bytes fetched from and sent to storage; 0 where the platform has no counter.
+    pub read_bytes: u64,
+    pub write_bytes: u64,
 }
 
 impl ProcessSample {
@@ -3,6 +3,7 @@ pub use cpu::autostart_interval_ms;
 #[cfg(target_os = "macos")]
 pub use cpu::send_sigusr2_to_thread_id;
 pub use cpu::start_cpu_sampling_from_env;
+pub use cpu::stop_cpu_sampling;
 pub use cpu::CpuProbeExtension;
 
 pub mod cluster;
@@ -125,7 +125,7 @@ pub type RdmaProbeDataSource = TableProbeDataSource<RdmaTable>;
 
 #[derive(Debug, Default, ProbeExtension)]
 pub struct RdmaProbeExtension {
-    #[option(aliases=["sample.rate"])]
+    #[option(aliases=["sample.rate"], min = 0, max = 20)]
     sample_rate: Maybe<f64>,
 
     #[option(aliases=["hca.name"])]
@@ -211,13 +211,6 @@ fn format_rdma_snapshot(hca_name: &str) -> Result<String, EngineError> {
 impl RdmaProbeExtension {
     fn set_sample_rate(&mut self, sample_rate: Maybe<f64>) -> Result<(), EngineError> {
         if let Maybe::Just(rate) = sample_rate {
-            if !(0.0..=20.0).contains(&rate) {
-                return Err(EngineError::InvalidOptionValue(
-                    Self::OPTION_SAMPLE_RATE.to_string(),
-                    rate.to_string(),
-                ));
-            }
-
             *lock_sample_rate() = rate;
         }
 
@@ -8,6 +8,7 @@ use std::time::{Duration, Instant};
 
 use once_cell::sync::Lazy;
 use probing_core::sync::lock_mutex;
+use probing_core::trace::{sampled_step_value, STEP_COLUMN};
 use probing_memtable::discover::ExposedTable;
 use probing_memtable::{DType, Schema, Value};
 use thiserror::Error;
@@ -74,6 +75,11 @@ pub fn start_gpu_sampling(interval_ms: u64) -> Result<(), CollectorError> {
     }
 }
 
+/// Stop GPU sampling and join the sampler thread; `Ok` when it is not running.
+pub fn stop_gpu_sampling() -> Result<(), CollectorError> {
+    GpuCollector::instance().stop()
+}
+
 /// Start GPU sampling from env. Call once after engine init.
 pub fn start_gpu_sampling_from_env() {
     let Some(interval_ms) = autostart_interval_ms() else {
@@ -114,6 +120,7 @@ fn utilization_schema() -> Schema {
         .col("tiler_util_pct", DType::F32)
         .col("driver_mem_bytes", DType::I64)
         .col("wall_ns", DType::I64)
+        .col(STEP_COLUMN, DType::I64)
 }
 
 #[derive(Debug, Clone)]
@@ -141,7 +148,13 @@ fn ts_micros() -> i64 {
         .as_micros() as i64
 }
 
-fn push_utilization_row(table: &mut ExposedTable, ts: i64, wall_ns: u64, sample: &GpuMemorySample) {
+fn push_utilization_row(
+    table: &mut ExposedTable,
+    ts: i64,
+    step: i64,
+    wall_ns: u64,
+    sample: &GpuMemorySample,
+) {
     let used = sample.used_bytes();
     if !table.push_row(&[
         Value::I64(ts),
@@ -160,6 +173,7 @@ fn push_utilization_row(table: &mut ExposedTable, ts: i64, wall_ns: u64, sample:
         Value::F32(opt_f32(sample.tiler_util_pct)),
         Value::I64(sample.driver_mem_bytes.unwrap_or(0) as i64),
         Value::I64(wall_ns as i64),
+        Value::I64(step),
     ]) {
         log::warn!("gpu collector: push_row failed for gpu.utilization");
     }
@@ -267,12 +281,13 @@ impl GpuCollector {
 
                     let wall_start = Instant::now();
                     let ts = ts_micros();
+                    let step = sampled_step_value();
                     let samples = sample_all(&backends);
                     let wall_ns = wall_start.elapsed().as_nanos() as u64;
 
                     let mut exposed = lock_gpu_table(&table);
                     for sample in &samples {
-                        push_utilization_row(&mut exposed, ts, wall_ns, sample);
+                        push_utilization_row(&mut exposed, ts, step, wall_ns, sample);
                     }
 
                     thread::sleep(config.interval);
@@ -291,7 +306,6 @@ impl GpuCollector {
         Ok(())
     }
 
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn stop(&self) -> Result<(), CollectorError> {
         if !self.running.swap(false, Ordering::SeqCst) {
             return Ok(());
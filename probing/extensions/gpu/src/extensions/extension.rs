@@ -9,7 +9,7 @@ use super::collector::start_gpu_sampling;
 #[derive(Debug, Default, ProbeExtension)]
 pub struct GpuProbeExtension {
     /// GPU memory sampling interval in milliseconds (0 disables collection).
-    #[option(aliases = ["sample_interval", "interval", "gpu.interval"])]
+    #[option(aliases = ["sample_interval", "interval", "gpu.interval"], min = 0)]
     gpu_sample_interval_ms: Maybe<i64>,
 
     /// Backend filter: `auto`, `cuda`, `rocm`, `metal`, or comma-separated list.
@@ -31,13 +31,6 @@ impl GpuProbeExtension {
             ));
         };
 
-        if interval < 0 {
-            return Err(EngineError::InvalidOptionValue(
-                Self::OPTION_GPU_SAMPLE_INTERVAL_MS.to_string(),
-                gpu_sample_interval_ms.clone().into(),
-            ));
-        }
-
         if interval == 0 {
             self.gpu_sample_interval_ms = gpu_sample_interval_ms;
             return Ok(());
@@ -4,6 +4,8 @@ mod devices;
 mod extension;
 
 pub use backend::{GpuBackend, GpuBackendKind, GpuDeviceInfo, GpuMemoryModel, GpuMemorySample};
-pub use collector::{autostart_interval_ms, start_gpu_sampling, start_gpu_sampling_from_env};
+pub use collector::{
+    autostart_interval_ms, start_gpu_sampling, start_gpu_sampling_from_env, stop_gpu_sampling,
+};
 pub use devices::GpuDevicesProbeDataSource;
 pub use extension::GpuProbeExtension;
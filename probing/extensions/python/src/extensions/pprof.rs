@@ -9,8 +9,8 @@ use probing_core::core::ProbeExtensionOption;
 
 #[derive(Debug, Default, ProbeExtension)]
 pub struct PprofProbeExtension {
-    /// CPU profiling sample frequency in Hz (higher values increase overhead)
-    #[option(aliases=["sample.freq"])]
+    /// CPU profiling sample frequency in Hz (higher values increase overhead; 0 disables)
+    #[option(aliases=["sample.freq"], min = 0, max = 100000)]
     sample_freq: Maybe<i32>,
 }
 
@@ -52,8 +52,8 @@ impl ProbeExtensionCall for PprofProbeExtension {
 
 impl PprofProbeExtension {
     fn set_sample_freq(&mut self, pprof_sample_freq: Maybe<i32>) -> Result<(), EngineError> {
-        // Clearing the option (`set probing.pprof.sample_freq=;`) or a value < 1
-        // disables sampling and tears the sampler down.
+        // Clearing the option (`set probing.pprof.sample_freq=;`) or 0 disables
+        // sampling and tears the sampler down.
         let freq = match pprof_sample_freq {
             Maybe::Just(freq) if freq >= 1 => freq,
             _ => {
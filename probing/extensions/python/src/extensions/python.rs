@@ -1,18 +1,22 @@
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 
 use probing_core::core::EngineError;
 use probing_core::core::Maybe;
+use probing_core::core::OptionType;
+use probing_core::core::OptionValue;
 use probing_core::core::ProbeExtension;
 use probing_core::core::ProbeExtensionCall;
 use probing_core::core::ProbeExtensionOption;
 use probing_core::core::Result as EngineResult;
+use probing_core::core::{channel_stream, single_chunk, ExtensionStream};
 use probing_core::run_on_native_thread;
 use probing_proto::prelude::CallFrame;
 use pyo3::prelude::*;
-use pyo3::types::{PyAnyMethods, PyString};
+use pyo3::types::{PyAnyMethods, PyBytes, PyIterator, PyString};
 use pyo3::Python;
 
 pub use exttbls::PyExternalTableConfig;
@@ -23,6 +27,7 @@ use crate::features::stacktrace::{SignalTracer, StackTracer};
 use crate::python::enable_crash_handler;
 use crate::python::enable_monitoring;
 
+mod arrow_bridge;
 mod exttbls;
 mod profile_sql;
 mod tbls;
@@ -48,16 +53,37 @@ impl Display for PyExtList {
     }
 }
 
+/// A Python import path or extension statement; blank values do not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PyStatement(String);
+
+impl OptionValue for PyStatement {
+    fn option_type() -> OptionType {
+        OptionType::String
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        let value = value.trim();
+        (!value.is_empty()).then(|| Self(value.to_string()))
+    }
+}
+
+impl Display for PyStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Python integration with the probing system
 #[derive(Debug, Default, ProbeExtension)]
 pub struct PythonExt {
     /// Enable the crash handler module (`probing.crash`). Aliases: `crash.handler`.
     #[option(aliases = ["crash.handler", "crash.enabled"])]
-    crash_handler: Maybe<String>,
+    crash_handler: Maybe<bool>,
 
     /// Path to Python monitoring handler script
     #[option()]
-    monitoring: Maybe<String>,
+    monitoring: Maybe<PyStatement>,
 
     /// Enable Python extensions by setting `python.enabled=<extension_statement>`
     #[option()]
@@ -65,7 +91,7 @@ pub struct PythonExt {
 
     /// Disable Python extension by setting `python.disabled=<extension_statement>`
     #[option()]
-    disabled: Maybe<String>,
+    disabled: Maybe<PyStatement>,
 }
 
 #[async_trait]
@@ -90,98 +116,85 @@ impl ProbeExtensionCall for PythonExt {
         }
         call_python_handler(normalized_path, params, body).await
     }
+
+    async fn call_stream(
+        &self,
+        path: &str,
+        params: &HashMap<String, String>,
+        body: &[u8],
+    ) -> EngineResult<ExtensionStream> {
+        let normalized_path = path.trim_start_matches('/');
+        if normalized_path.starts_with("crash/") {
+            return self.call(path, params, body).await.map(single_chunk);
+        }
+        stream_python_handler(normalized_path, params, body).await
+    }
 }
 
 impl PythonExt {
-    /// Set up a Python crash handler
-    fn set_crash_handler(&mut self, crash_handler: Maybe<String>) -> EngineResult<()> {
-        match self.crash_handler {
-            Maybe::Just(_) => Err(EngineError::ReadOnlyOption(
+    /// Set up a Python crash handler; set once, `false` leaves it off.
+    fn set_crash_handler(&mut self, enabled: bool) -> EngineResult<()> {
+        if let Maybe::Just(_) = self.crash_handler {
+            return Err(EngineError::ReadOnlyOption(
                 Self::OPTION_CRASH_HANDLER.to_string(),
-            )),
-            Maybe::Nothing => match &crash_handler {
-                Maybe::Nothing => Err(EngineError::InvalidOptionValue(
+            ));
+        }
+        self.crash_handler = Maybe::Just(enabled);
+        if !enabled {
+            log::info!("Python crash handler disabled");
+            return Ok(());
+        }
+        match enable_crash_handler() {
+            Ok(_) => {
+                log::info!("Python crash handler enabled");
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Failed to enable crash handler: {e}");
+                Err(EngineError::InvalidOptionValue(
                     Self::OPTION_CRASH_HANDLER.to_string(),
-                    crash_handler.clone().into(),
-                )),
-                Maybe::Just(handler) => {
-                    self.crash_handler = crash_handler.clone();
-                    let lowered = handler.trim().to_ascii_lowercase();
-                    if lowered == "off" || lowered == "0" || lowered == "false" {
-                        log::info!("Python crash handler disabled");
-                        return Ok(());
-                    }
-                    match enable_crash_handler() {
-                        Ok(_) => {
-                            log::info!("Python crash handler enabled: {handler}");
-                            Ok(())
-                        }
-                        Err(e) => {
-                            log::error!("Failed to enable crash handler '{handler}': {e}");
-                            Err(EngineError::InvalidOptionValue(
-                                Self::OPTION_CRASH_HANDLER.to_string(),
-                                handler.to_string(),
-                            ))
-                        }
-                    }
-                }
-            },
+                    enabled.to_string(),
+                ))
+            }
         }
     }
 
     /// Set up Python monitoring
-    fn set_monitoring(&mut self, monitoring: Maybe<String>) -> EngineResult<()> {
+    fn set_monitoring(&mut self, monitoring: PyStatement) -> EngineResult<()> {
         log::debug!("Setting Python monitoring: {monitoring}");
-        match self.monitoring {
-            Maybe::Just(_) => Err(EngineError::ReadOnlyOption(
+        if let Maybe::Just(_) = self.monitoring {
+            return Err(EngineError::ReadOnlyOption(
                 Self::OPTION_MONITORING.to_string(),
-            )),
-            Maybe::Nothing => match &monitoring {
-                Maybe::Nothing => Err(EngineError::InvalidOptionValue(
+            ));
+        }
+        let handler = monitoring.to_string();
+        self.monitoring = Maybe::Just(monitoring);
+        match enable_monitoring(&handler) {
+            Ok(_) => {
+                log::info!("Python monitoring enabled: {handler}");
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Failed to enable monitoring '{handler}': {e}");
+                Err(EngineError::InvalidOptionValue(
                     Self::OPTION_MONITORING.to_string(),
-                    monitoring.clone().into(),
-                )),
-                Maybe::Just(handler) => {
-                    self.monitoring = monitoring.clone();
-                    match enable_monitoring(handler) {
-                        Ok(_) => {
-                            log::info!("Python monitoring enabled: {handler}");
-                            Ok(())
-                        }
-                        Err(e) => {
-                            log::error!("Failed to enable monitoring '{handler}': {e}");
-                            Err(EngineError::InvalidOptionValue(
-                                Self::OPTION_MONITORING.to_string(),
-                                handler.to_string(),
-                            ))
-                        }
-                    }
-                }
-            },
+                    handler,
+                ))
+            }
         }
     }
 
     /// Enable a Python extension from code string
-    fn set_enabled(&mut self, enabled: Maybe<String>) -> EngineResult<()> {
-        let ext = match &enabled {
-            Maybe::Nothing => {
-                return Err(EngineError::InvalidOptionValue(
-                    Self::OPTION_ENABLED.to_string(),
-                    enabled.clone().into(),
-                ));
-            }
-            Maybe::Just(e) => e,
-        };
-
-        if self.enabled.0.contains_key(ext) {
+    fn set_enabled(&mut self, enabled: PyStatement) -> EngineResult<()> {
+        let ext = enabled.0;
+        if self.enabled.0.contains_key(&ext) {
             return Err(EngineError::plugin(format!(
                 "Python extension '{ext}' is already enabled"
             )));
         }
 
-        let pyext = execute_python_code(ext)
+        let pyext = execute_python_code(&ext)
             .map_err(|e| EngineError::invalid_option(Self::OPTION_ENABLED, e))?;
-
         self.enabled.0.insert(ext.clone(), pyext);
         log::info!("Python extension enabled: {ext}");
         log::debug!("Current enabled extensions: {}", self.enabled);
@@ -190,17 +203,8 @@ impl PythonExt {
     }
 
     /// Disable a previously enabled Python extension
-    fn set_disabled(&mut self, disabled: Maybe<String>) -> EngineResult<()> {
-        let ext = match &disabled {
-            Maybe::Nothing => {
-                return Err(EngineError::InvalidOptionValue(
-                    Self::OPTION_DISABLED.to_string(),
-                    disabled.clone().into(),
-                ));
-            }
-            Maybe::Just(e) => e,
-        };
-
+    fn set_disabled(&mut self, disabled: PyStatement) -> EngineResult<()> {
+        let ext = &disabled.0;
         if let Some(pyext) = self.enabled.0.remove(ext) {
             log::info!("Disabling Python extension: {ext}");
             let ext_name = ext.clone();
@@ -289,59 +293,178 @@ async fn call_python_handler(
     let path = path.to_string();
     let params = params.clone();
     let body = body.to_vec();
-    tokio::task::spawn_blocking(move || call_python_handler_blocking(path, params, body))
-        .await
-        .map_err(|e| EngineError::plugin(format!("python handler task join failed: {e}")))?
+    // Task-locals don't follow spawn_blocking; hand the id over explicitly.
+    let request_id = probing_logging::current_request_id();
+    tokio::task::spawn_blocking(move || {
+        call_python_handler_blocking(path, params, body, request_id)
+    })
+    .await
+    .map_err(|e| EngineError::plugin(format!("python handler task join failed: {e}")))?
 }
 
 fn call_python_handler_blocking(
     path: String,
     params: HashMap<String, String>,
     body: Vec<u8>,
+    request_id: Option<String>,
 ) -> EngineResult<Vec<u8>> {
     run_on_native_thread(move || {
         Python::attach(|py| {
-            let router_module = py
-                .import("probing.handlers.router")
-                .py_context("Failed to import router module")?;
-
-            let handle_func = router_module
-                .getattr("handle_request")
-                .py_context("Failed to get handle_request function")?;
-
-            let params_dict = pyo3::types::PyDict::new(py);
-            for (key, value) in &params {
-                params_dict
-                    .set_item(key.as_str(), str_to_py(py, value))
-                    .py_context_with(|| format!("Failed to set param '{key}'"))?;
+            let result = invoke_python_handler(py, &path, &params, &body, request_id.as_deref())?;
+            match handler_reply(result)? {
+                HandlerReply::Body(bytes) => Ok(bytes),
+                // Buffered callers (MCP, fan-out) still get the whole document.
+                HandlerReply::Chunks(chunks) => {
+                    let mut out = Vec::new();
+                    for chunk in chunks.bind(py).clone() {
+                        let chunk = chunk.py_context("Streaming handler failed")?;
+                        out.extend(chunk_bytes(&chunk)?);
+                    }
+                    Ok(out)
+                }
             }
-
-            let body_arg = if body.is_empty() {
-                py.None()
-            } else {
-                let body_str = std::str::from_utf8(&body).map_err(|e| {
-                    EngineError::plugin(format!("Request body is not valid UTF-8: {e}"))
-                })?;
-                str_to_py(py, body_str)
-            };
-
-            let result = handle_func
-                .call1((str_to_py(py, &path), params_dict, body_arg))
-                .py_context("Failed to call handle_request")?;
-
-            let result_str: String = match result.extract() {
-                Ok(s) => s,
-                Err(_) => result
-                    .extract::<Vec<u8>>()
-                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
-                    .py_context("Failed to extract handler result")?,
-            };
-
-            Ok(result_str.into_bytes())
         })
     })
 }
 
+/// Chunks buffered between a streaming handler and the HTTP body; a slow
+/// client stalls the handler instead of growing server memory.
+const STREAM_CHANNEL_CHUNKS: usize = 4;
+
+/// Streaming counterpart of [`call_python_handler`]: handlers that return an
+/// iterator of chunks are pulled one chunk per GIL acquisition, so the GIL and
+/// the native bridge are never held across a slow client.
+async fn stream_python_handler(
+    path: &str,
+    params: &HashMap<String, String>,
+    body: &[u8],
+) -> EngineResult<ExtensionStream> {
+    let path = path.to_string();
+    let params = params.clone();
+    let body = body.to_vec();
+    let request_id = probing_logging::current_request_id();
+    let (tx, rx) = tokio::sync::mpsc::channel(STREAM_CHANNEL_CHUNKS);
+    tokio::task::spawn_blocking(move || pump_python_handler(path, params, body, request_id, tx));
+    Ok(channel_stream(rx))
+}
+
+fn pump_python_handler(
+    path: String,
+    params: HashMap<String, String>,
+    body: Vec<u8>,
+    request_id: Option<String>,
+    tx: tokio::sync::mpsc::Sender<EngineResult<Vec<u8>>>,
+) {
+    let reply = run_on_native_thread(move || {
+        Python::attach(|py| {
+            let result = invoke_python_handler(py, &path, &params, &body, request_id.as_deref())?;
+            handler_reply(result)
+        })
+    });
+    let chunks = match reply {
+        Ok(HandlerReply::Chunks(chunks)) => Arc::new(chunks),
+        Ok(HandlerReply::Body(bytes)) => {
+            let _ = tx.blocking_send(Ok(bytes));
+            return;
+        }
+        Err(e) => {
+            let _ = tx.blocking_send(Err(e));
+            return;
+        }
+    };
+    loop {
+        let iter = Arc::clone(&chunks);
+        let next = run_on_native_thread(move || {
+            Python::attach(|py| match iter.bind(py).clone().next() {
+                None => Ok(None),
+                Some(chunk) => {
+                    let chunk = chunk.py_context("Streaming handler failed")?;
+                    chunk_bytes(&chunk).map(Some)
+                }
+            })
+        });
+        let item = match next {
+            Ok(Some(bytes)) => Ok(bytes),
+            Ok(None) => break,
+            Err(e) => Err(e),
+        };
+        let failed = item.is_err();
+        // A send error means the client went away; stop pulling chunks.
+        if tx.blocking_send(item).is_err() || failed {
+            break;
+        }
+    }
+    // Release the iterator (and close the generator) with the GIL held.
+    run_on_native_thread(move || Python::attach(|_py| drop(chunks)));
+}
+
+/// What a router handler returned: a whole body, or an iterator of chunks.
+enum HandlerReply {
+    Body(Vec<u8>),
+    Chunks(Py<PyIterator>),
+}
+
+fn invoke_python_handler<'py>(
+    py: Python<'py>,
+    path: &str,
+    params: &HashMap<String, String>,
+    body: &[u8],
+    request_id: Option<&str>,
+) -> EngineResult<Bound<'py, PyAny>> {
+    let router_module = py
+        .import("probing.handlers.router")
+        .py_context("Failed to import router module")?;
+
+    let handle_func = router_module
+        .getattr("handle_request")
+        .py_context("Failed to get handle_request function")?;
+
+    let params_dict = pyo3::types::PyDict::new(py);
+    for (key, value) in params {
+        params_dict
+            .set_item(key.as_str(), str_to_py(py, value))
+            .py_context_with(|| format!("Failed to set param '{key}'"))?;
+    }
+
+    let body_arg = if body.is_empty() {
+        py.None()
+    } else {
+        let body_str = std::str::from_utf8(body)
+            .map_err(|e| EngineError::plugin(format!("Request body is not valid UTF-8: {e}")))?;
+        str_to_py(py, body_str)
+    };
+
+    let kwargs = pyo3::types::PyDict::new(py);
+    if let Some(id) = request_id {
+        kwargs
+            .set_item("request_id", str_to_py(py, id))
+            .py_context("Failed to set request_id")?;
+    }
+
+    handle_func
+        .call((str_to_py(py, path), params_dict, body_arg), Some(&kwargs))
+        .py_context("Failed to call handle_request")
+}
+
+fn handler_reply(result: Bound<'_, PyAny>) -> EngineResult<HandlerReply> {
+    if result.is_instance_of::<PyString>() || result.is_instance_of::<PyBytes>() {
+        return chunk_bytes(&result).map(HandlerReply::Body);
+    }
+    let chunks = result
+        .try_iter()
+        .py_context("Failed to extract handler result")?;
+    Ok(HandlerReply::Chunks(chunks.unbind()))
+}
+
+fn chunk_bytes(chunk: &Bound<'_, PyAny>) -> EngineResult<Vec<u8>> {
+    match chunk.extract::<String>() {
+        Ok(s) => Ok(s.into_bytes()),
+        Err(_) => chunk
+            .extract::<Vec<u8>>()
+            .py_context("Failed to extract handler result"),
+    }
+}
+
 fn str_to_py(py: Python, s: &str) -> Py<PyAny> {
     PyString::new(py, s).to_owned().unbind().into()
 }
@@ -367,6 +490,33 @@ mod tests {
         assert!(display.contains("ext1") || display.contains("ext2"));
     }
 
+    #[test]
+    fn test_options_are_typed() {
+        let mut ext = PythonExt::default();
+        assert!(matches!(
+            ext.set("crash_handler", "maybe"),
+            Err(EngineError::InvalidOptionValue(key, reason))
+                if key == PythonExt::OPTION_CRASH_HANDLER && reason == "maybe (expected bool)"
+        ));
+        assert!(matches!(
+            ext.set("enabled", " "),
+            Err(EngineError::InvalidOptionValue(..))
+        ));
+        assert!(ext.set("crash_handler", "off").is_ok());
+        assert_eq!(ext.get("crash_handler").unwrap(), "false");
+        assert!(matches!(
+            ext.set("crash_handler", "on"),
+            Err(EngineError::ReadOnlyOption(_))
+        ));
+
+        let options = ext.options();
+        let crash = options
+            .iter()
+            .find(|opt| opt.key == PythonExt::OPTION_CRASH_HANDLER)
+            .unwrap();
+        assert_eq!(crash.option_type, OptionType::Bool);
+    }
+
     #[test]
     fn test_str_to_py() {
         Python::attach(|py| {
@@ -0,0 +1,504 @@
+//! Columnar hand-off from Python values to Arrow without per-element PyO3 calls.
+//!
+//! Three shapes take the fast path:
+//! - objects exporting `__arrow_c_stream__` (pyarrow tables/readers, polars,
+//!   pandas >= 2.2), imported through the Arrow C stream interface;
+//! - pandas DataFrames, one `to_numpy()` view per column;
+//! - dicts mapping column names to equal-length numpy arrays or lists.
+//!
+//! Read-only numeric numpy columns are wrapped in place: the Arrow buffer
+//! points at the numpy data and holds a reference to the array. Writable
+//! arrays are copied once first, so Python code mutating them later cannot
+//! change what a query reads.
+//! Anything else (object/string dtypes, plain lists) is converted element by
+//! element by [`sequence_column`], which is also the reference the fast path
+//! is tested against.
+
+use std::mem::size_of;
+use std::panic::AssertUnwindSafe;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use arrow::alloc::Allocation;
+use arrow::array::{new_empty_array, BooleanArray, PrimitiveArray};
+use arrow::buffer::{Buffer, ScalarBuffer};
+use arrow::datatypes::{
+    ArrowPrimitiveType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use probing_core::core::query_cancel;
+use probing_core::core::{
+    ArrayRef, DataType, Field, Float64Array, Int64Array, RecordBatch, Schema, SchemaRef,
+    StringArray,
+};
+use pyo3::types::{
+    PyAnyMethods, PyBool, PyCapsule, PyCapsuleMethods, PyDict, PyDictMethods, PyFloat, PyInt,
+    PyList, PyString, PyTypeMethods,
+};
+use pyo3::{Bound, PyAny};
+
+use super::tbls::{try_record_batch, PythonTableError, TableResult};
+
+/// Convert `obj` to record batches if it has a columnar shape; `None` leaves
+/// it to the row-oriented converters in `tbls`.
+pub(crate) fn columnar_batches(obj: &Bound<'_, PyAny>) -> TableResult<Option<Vec<RecordBatch>>> {
+    if is_pandas_dataframe(obj) {
+        return dataframe_batches(obj).map(Some);
+    }
+    if obj.hasattr("__arrow_c_stream__")? {
+        return arrow_stream_batches(obj).map(Some);
+    }
+    if let Ok(dict) = obj.cast::<PyDict>() {
+        return dict_of_columns(dict);
+    }
+    Ok(None)
+}
+
+fn is_pandas_dataframe(obj: &Bound<'_, PyAny>) -> bool {
+    let ty = obj.get_type();
+    let module = ty.module().map(|m| m.to_string()).unwrap_or_default();
+    let name = ty.name().map(|n| n.to_string()).unwrap_or_default();
+    module.starts_with("pandas") && name == "DataFrame"
+}
+
+fn arrow_stream_batches(obj: &Bound<'_, PyAny>) -> TableResult<Vec<RecordBatch>> {
+    let capsule = obj.call_method0("__arrow_c_stream__")?;
+    let capsule = capsule.cast::<PyCapsule>()?;
+    let ptr = capsule.pointer_checked(Some(c"arrow_array_stream"))?;
+    // Move the stream out of the capsule; the empty struct left behind has no
+    // release callback, so the capsule destructor becomes a no-op.
+    let stream = unsafe {
+        std::ptr::replace(
+            ptr.as_ptr() as *mut FFI_ArrowArrayStream,
+            FFI_ArrowArrayStream::empty(),
+        )
+    };
+    let mut reader = ArrowArrayStreamReader::try_new(stream)
+        .map_err(|e| PythonTableError::BatchBuild(e.to_string()))?;
+    // Long streams (lazy readers over files or remote scans) stop early when
+    // the query is cancelled or times out between chunks.
+    let mut batches = Vec::new();
+    loop {
+        query_cancel::check().map_err(PythonTableError::Stopped)?;
+        match reader.next() {
+            Some(batch) => {
+                batches.push(batch.map_err(|e| PythonTableError::BatchBuild(e.to_string()))?)
+            }
+            None => return Ok(batches),
+        }
+    }
+}
+
+fn dataframe_batches(df: &Bound<'_, PyAny>) -> TableResult<Vec<RecordBatch>> {
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for name in df.getattr("columns")?.try_iter()? {
+        let name = name?;
+        let values = df.get_item(&name)?.call_method0("to_numpy")?;
+        let array = column(&values)?;
+        fields.push(Field::new(
+            name.str()?.to_string(),
+            array.data_type().clone(),
+            true,
+        ));
+        columns.push(array);
+    }
+    let schema = SchemaRef::new(Schema::new(fields));
+    Ok(vec![try_record_batch(schema, columns)?])
+}
+
+/// `{"name": ndarray | list, ...}` with at least one numpy column and equal
+/// lengths. Dicts of scalars keep their existing single-row meaning.
+fn dict_of_columns(dict: &Bound<'_, PyDict>) -> TableResult<Option<Vec<RecordBatch>>> {
+    let mut has_numpy = false;
+    let mut len = None;
+    for (_, value) in dict.iter() {
+        let is_numpy = value.hasattr("__array_interface__")?;
+        if !is_numpy && !value.is_instance_of::<PyList>() {
+            return Ok(None);
+        }
+        let n = value.len()?;
+        if len.is_some_and(|len| len != n) {
+            return Ok(None);
+        }
+        len = Some(n);
+        has_numpy |= is_numpy;
+    }
+    if !has_numpy {
+        return Ok(None);
+    }
+
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for (key, value) in dict.iter() {
+        let array = column(&value)?;
+        fields.push(Field::new(
+            key.extract::<String>()?,
+            array.data_type().clone(),
+            true,
+        ));
+        columns.push(array);
+    }
+    let schema = SchemaRef::new(Schema::new(fields));
+    Ok(Some(vec![try_record_batch(schema, columns)?]))
+}
+
+/// One column: zero-copy for numeric numpy arrays, element-wise otherwise.
+fn column(values: &Bound<'_, PyAny>) -> TableResult<ArrayRef> {
+    if let Some(array) = numpy_column(values)? {
+        return Ok(array);
+    }
+    if values.hasattr("tolist")? {
+        return sequence_column(&values.call_method0("tolist")?);
+    }
+    sequence_column(values)
+}
+
+/// Wrap a 1-D numeric numpy array, sharing its data when it is read-only.
+///
+/// Writable arrays, strided views and misaligned data are copied once by
+/// numpy into a contiguous array that only the Arrow buffer refers to;
+/// non-numeric dtypes return `None`.
+pub(crate) fn numpy_column(values: &Bound<'_, PyAny>) -> TableResult<Option<ArrayRef>> {
+    numpy_column_inner(values, false)
+}
+
+/// `owned` marks the private copy made by the first call.
+fn numpy_column_inner(values: &Bound<'_, PyAny>, owned: bool) -> TableResult<Option<ArrayRef>> {
+    let Ok(iface) = values.getattr("__array_interface__") else {
+        return Ok(None);
+    };
+    let iface = iface.cast::<PyDict>()?;
+    let item = |key: &str| interface_item(iface, key);
+
+    let shape: Vec<usize> = item("shape")?.extract()?;
+    let [len] = shape[..] else {
+        return Ok(None);
+    };
+    let typestr: String = item("typestr")?.extract()?;
+    let Some(kind) = numpy_kind(&typestr) else {
+        return Ok(None);
+    };
+    let data = item("data")?;
+    if data.is_none() {
+        return Ok(None);
+    }
+    let (addr, readonly): (usize, bool) = data.extract()?;
+    let strides: Option<Vec<isize>> = iface
+        .get_item("strides")?
+        .filter(|s| !s.is_none())
+        .map(|s| s.extract())
+        .transpose()?;
+
+    if len == 0 {
+        return Ok(Some(new_empty_array(&kind.data_type())));
+    }
+    let width = kind.width();
+    let contiguous = strides.is_none_or(|s| s == [width as isize]);
+    let aligned = addr % width == 0;
+    // numpy bools are bytes, Arrow's are bits: packing them copies anyway.
+    let shareable = readonly || owned || kind == NumpyKind::Bool;
+    if !contiguous || !aligned || !shareable {
+        if owned {
+            return Ok(None);
+        }
+        let numpy = values.py().import("numpy")?;
+        let kwargs = PyDict::new(values.py());
+        kwargs.set_item("copy", true)?;
+        let copy = numpy.call_method("array", (values,), Some(&kwargs))?;
+        return numpy_column_inner(&copy, true);
+    }
+    let Some(ptr) = NonNull::new(addr as *mut u8) else {
+        return Ok(None);
+    };
+
+    if kind == NumpyKind::Bool {
+        // One pass, no PyO3 calls.
+        let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), len) };
+        let array = BooleanArray::from_iter(bytes.iter().map(|b| Some(*b != 0)));
+        return Ok(Some(Arc::new(array)));
+    }
+
+    let owner: Arc<dyn Allocation> = Arc::new(AssertUnwindSafe(values.clone().unbind()));
+    // SAFETY: the numpy array owns `len * width` contiguous, aligned bytes at
+    // `ptr` that Python cannot write to (read-only, or our private copy), and
+    // `owner` keeps it alive for as long as the buffer is.
+    let buffer = unsafe { Buffer::from_custom_allocation(ptr, len * width, owner) };
+    let array = match kind {
+        NumpyKind::I8 => primitive::<Int8Type>(buffer, len),
+        NumpyKind::I16 => primitive::<Int16Type>(buffer, len),
+        NumpyKind::I32 => primitive::<Int32Type>(buffer, len),
+        NumpyKind::I64 => primitive::<Int64Type>(buffer, len),
+        NumpyKind::U8 => primitive::<UInt8Type>(buffer, len),
+        NumpyKind::U16 => primitive::<UInt16Type>(buffer, len),
+        NumpyKind::U32 => primitive::<UInt32Type>(buffer, len),
+        NumpyKind::U64 => primitive::<UInt64Type>(buffer, len),
+        NumpyKind::F32 => primitive::<Float32Type>(buffer, len),
+        NumpyKind::F64 => primitive::<Float64Type>(buffer, len),
+        NumpyKind::Bool => unreachable!("handled above"),
+    };
+    Ok(Some(array))
+}
+
+fn interface_item<'py>(iface: &Bound<'py, PyDict>, key: &str) -> TableResult<Bound<'py, PyAny>> {
+    iface
+        .get_item(key)?
+        .ok_or_else(|| PythonTableError::BatchBuild(format!("__array_interface__ lacks {key}")))
+}
+
+fn primitive<T: ArrowPrimitiveType>(buffer: Buffer, len: usize) -> ArrayRef {
+    debug_assert_eq!(buffer.len(), len * size_of::<T::Native>());
+    Arc::new(PrimitiveArray::<T>::new(
+        ScalarBuffer::new(buffer, 0, len),
+        None,
+    ))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NumpyKind {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+}
+
+impl NumpyKind {
+    fn width(self) -> usize {
+        match self {
+            Self::Bool | Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Bool => DataType::Boolean,
+            Self::I8 => DataType::Int8,
+            Self::I16 => DataType::Int16,
+            Self::I32 => DataType::Int32,
+            Self::I64 => DataType::Int64,
+            Self::U8 => DataType::UInt8,
+            Self::U16 => DataType::UInt16,
+            Self::U32 => DataType::UInt32,
+            Self::U64 => DataType::UInt64,
+            Self::F32 => DataType::Float32,
+            Self::F64 => DataType::Float64,
+        }
+    }
+}
+
+/// Parse an `__array_interface__` typestr (`"<i8"`, `"|b1"`, ...). Only
+/// native byte order is wrapped; anything else goes element-wise.
+fn numpy_kind(typestr: &str) -> Option<NumpyKind> {
+    let (order, code) = typestr.split_at_checked(1)?;
+    let native = match order {
+        "|" | "=" => true,
+        "<" => cfg!(target_endian = "little"),
+        ">" => cfg!(target_endian = "big"),
+        _ => false,
+    };
+    if !native {
+        return None;
+    }
+    Some(match code {
+        "b1" => NumpyKind::Bool,
+        "i1" => NumpyKind::I8,
+        "i2" => NumpyKind::I16,
+        "i4" => NumpyKind::I32,
+        "i8" => NumpyKind::I64,
+        "u1" => NumpyKind::U8,
+        "u2" => NumpyKind::U16,
+        "u4" => NumpyKind::U32,
+        "u8" => NumpyKind::U64,
+        "f4" => NumpyKind::F32,
+        "f8" => NumpyKind::F64,
+        _ => return None,
+    })
+}
+
+/// Element-wise conversion of a Python iterable: Boolean, Int64 or Float64
+/// when every non-`None` value fits, Utf8 (`str()`) otherwise.
+pub(crate) fn sequence_column(values: &Bound<'_, PyAny>) -> TableResult<ArrayRef> {
+    let items: Vec<Bound<'_, PyAny>> = values.try_iter()?.collect::<Result<_, _>>()?;
+
+    let present = || items.iter().filter(|v| !v.is_none());
+    if present().all(|v| v.is_instance_of::<PyBool>()) && present().next().is_some() {
+        let array: BooleanArray = items
+            .iter()
+            .map(|v| (!v.is_none()).then(|| v.extract::<bool>()).transpose())
+            .collect::<Result<_, _>>()?;
+        return Ok(Arc::new(array));
+    }
+    let numeric =
+        |v: &Bound<'_, PyAny>| !v.is_instance_of::<PyBool>() && v.is_instance_of::<PyInt>();
+    if present().all(numeric) {
+        let array: Int64Array = items
+            .iter()
+            .map(|v| (!v.is_none()).then(|| v.extract::<i64>()).transpose())
+            .collect::<Result<_, _>>()?;
+        return Ok(Arc::new(array));
+    }
+    if present().all(|v| numeric(v) || v.is_instance_of::<PyFloat>()) {
+        let array: Float64Array = items
+            .iter()
+            .map(|v| (!v.is_none()).then(|| v.extract::<f64>()).transpose())
+            .collect::<Result<_, _>>()?;
+        return Ok(Arc::new(array));
+    }
+    let array: StringArray = items
+        .iter()
+        .map(|v| {
+            if v.is_none() {
+                Ok(None)
+            } else if v.is_instance_of::<PyString>() {
+                v.extract::<String>().map(Some)
+            } else {
+                Ok(Some(v.to_string()))
+            }
+        })
+        .collect::<Result<_, pyo3::PyErr>>()?;
+    Ok(Arc::new(array))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::Python;
+    use std::ffi::CString;
+    use std::time::Instant;
+
+    const ROWS: usize = 1_000_000;
+
+    fn eval<'py>(py: Python<'py>, code: &str) -> Bound<'py, PyAny> {
+        let numpy = py
+            .import("numpy")
+            .expect("numpy is required by these tests");
+        let locals = PyDict::new(py);
+        locals.set_item("np", numpy).unwrap();
+        py.eval(&CString::new(code).unwrap(), None, Some(&locals))
+            .unwrap()
+    }
+
+    fn buffer_addr(array: &ArrayRef) -> usize {
+        array.to_data().buffers()[0].as_ptr() as usize
+    }
+
+    fn data_addr(values: &Bound<'_, PyAny>) -> usize {
+        let iface = values.getattr("__array_interface__").unwrap();
+        let (addr, _): (usize, bool) = iface.get_item("data").unwrap().extract().unwrap();
+        addr
+    }
+
+    #[test]
+    fn numpy_kind_accepts_native_numeric_dtypes_only() {
+        assert_eq!(numpy_kind("|b1"), Some(NumpyKind::Bool));
+        assert_eq!(numpy_kind("<i8"), Some(NumpyKind::I64));
+        assert_eq!(numpy_kind("=f4"), Some(NumpyKind::F32));
+        assert_eq!(numpy_kind("|O"), None);
+        assert_eq!(numpy_kind("<U5"), None);
+        assert_eq!(numpy_kind("<M8[ns]"), None);
+        assert_eq!(numpy_kind(""), None);
+    }
+
+    #[test]
+    fn dict_of_numpy_columns_matches_slow_path() {
+        Python::initialize();
+        Python::attach(|py| {
+            let obj = eval(
+                py,
+                "{'i': np.arange(5, dtype=np.int64), 'f': np.linspace(0, 1, 10)[::2], \
+                 'b': np.array([1, 0, 1, 1, 0], dtype=bool), 's': np.array(list('abcde'))}",
+            );
+            let batches = columnar_batches(&obj).unwrap().expect("columnar dict");
+            let batch = &batches[0];
+            assert_eq!(batch.num_rows(), 5);
+            assert_eq!(batch.schema().field(0).data_type(), &DataType::Int64);
+            assert_eq!(batch.schema().field(1).data_type(), &DataType::Float64);
+            assert_eq!(batch.schema().field(2).data_type(), &DataType::Boolean);
+            assert_eq!(batch.schema().field(3).data_type(), &DataType::Utf8);
+
+            let dict = obj.cast::<PyDict>().unwrap();
+            for (i, (_, value)) in dict.iter().enumerate() {
+                let slow = sequence_column(&value.call_method0("tolist").unwrap()).unwrap();
+                assert_eq!(batch.column(i).as_ref(), slow.as_ref(), "column {i}");
+            }
+
+            // Dicts of scalars keep the single-row converter.
+            let scalars = eval(py, "{'a': 1, 'b': 'x'}");
+            assert!(columnar_batches(&scalars).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn only_read_only_arrays_are_shared() {
+        Python::initialize();
+        Python::attach(|py| {
+            let writable = eval(py, "np.arange(4, dtype=np.int64)");
+            let column = numpy_column(&writable).unwrap().expect("numeric column");
+            assert_ne!(buffer_addr(&column), data_addr(&writable));
+            writable.set_item(0, 100).unwrap();
+            assert_eq!(column.as_ref(), &Int64Array::from(vec![0, 1, 2, 3]));
+
+            let frozen = eval(py, "np.arange(4, dtype=np.int64)");
+            frozen
+                .getattr("flags")
+                .unwrap()
+                .setattr("writeable", false)
+                .unwrap();
+            let column = numpy_column(&frozen).unwrap().expect("numeric column");
+            assert_eq!(buffer_addr(&column), data_addr(&frozen));
+        });
+    }
+
+    #[test]
+    fn numpy_fast_path_matches_slow_path() {
+        Python::initialize();
+        Python::attach(|py| {
+            let values = eval(py, &format!("np.arange({ROWS}, dtype=np.float64) * 0.5"));
+            let fast = numpy_column(&values).unwrap().expect("numeric column");
+            let slow = sequence_column(&values.call_method0("tolist").unwrap()).unwrap();
+            assert_eq!(fast.len(), ROWS);
+            assert_eq!(fast.as_ref(), slow.as_ref());
+        });
+    }
+
+    /// Timing-sensitive; run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn numpy_fast_path_is_an_order_of_magnitude_faster() {
+        Python::initialize();
+        Python::attach(|py| {
+            let values = eval(py, &format!("np.arange({ROWS}, dtype=np.float64) * 0.5"));
+            values
+                .getattr("flags")
+                .unwrap()
+                .setattr("writeable", false)
+                .unwrap();
+
+            let start = Instant::now();
+            let fast = numpy_column(&values).unwrap().expect("numeric column");
+            let fast_elapsed = start.elapsed();
+
+            let start = Instant::now();
+            let slow = sequence_column(&values.call_method0("tolist").unwrap()).unwrap();
+            let slow_elapsed = start.elapsed();
+
+            assert_eq!(fast.len(), ROWS);
+            assert_eq!(fast.as_ref(), slow.as_ref());
+            assert!(
+                fast_elapsed * 10 <= slow_elapsed,
+                "fast path {fast_elapsed:?} vs element-wise {slow_elapsed:?}"
+            );
+        });
+    }
+}
@@ -216,6 +216,7 @@ fn ele_dtype(e: &Ele) -> DType {
         Ele::F64(_) => DType::F64,
         Ele::BOOL(_) => DType::U8,
         Ele::DataTime(_) => DType::U64,
+        Ele::DateTime(_) => DType::I64,
         Ele::Text(_) | Ele::Url(_) | Ele::Nil => DType::Str,
     }
 }
@@ -240,6 +241,7 @@ fn ele_to_owned(e: &Ele, dt: DType) -> OwnedVal {
         Ele::F64(v) => *v,
         Ele::BOOL(v) => *v as u8 as f64,
         Ele::DataTime(v) => *v as f64,
+        Ele::DateTime(v) => *v as f64,
         _ => 0.0,
     };
     match dt {
@@ -248,7 +250,11 @@ fn ele_to_owned(e: &Ele, dt: DType) -> OwnedVal {
             other => as_f64(other) as u8,
         }),
         DType::I32 => OwnedVal::I32(as_f64(e) as i32),
-        DType::I64 => OwnedVal::I64(as_f64(e) as i64),
+        // Through f64 a nanosecond timestamp would lose its low digits.
+        DType::I64 => OwnedVal::I64(match e {
+            Ele::I64(v) | Ele::DateTime(v) => *v,
+            other => as_f64(other) as i64,
+        }),
         DType::F32 => OwnedVal::F32(as_f64(e) as f32),
         DType::F64 => OwnedVal::F64(as_f64(e)),
         DType::U64 => OwnedVal::U64(as_f64(e) as u64),
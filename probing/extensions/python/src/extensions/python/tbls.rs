@@ -3,6 +3,7 @@ use std::ffi::CString;
 use std::sync::Arc;
 
 use log::{debug, error};
+use probing_core::core::scan_stats;
 use probing_core::core::{
     ArrayRef, CustomNamespace, DataType, Field, Float64Array, Int64Array, NamespaceProbeDataSource,
     RecordBatch, Schema, SchemaRef, StringArray,
@@ -28,6 +29,9 @@ pub enum PythonTableError {
     MissingColumn(String),
     #[error("record batch build failed: {0}")]
     BatchBuild(String),
+    /// Cancelled or past `probing.query.timeout`.
+    #[error(transparent)]
+    Stopped(probing_core::core::EngineError),
     #[error("backtrace capture failed")]
     Backtrace(#[source] anyhow::Error),
     #[error(transparent)]
@@ -81,6 +85,71 @@ fn error_batch(message: &str) -> Vec<RecordBatch> {
 pub struct PythonNamespace {}
 
 impl PythonNamespace {
+    /// One row of closed-span ring counters (`probing_core::trace::span_ring`)
+    /// plus traces evicted by the retention policy.
+    fn trace_stats_data() -> TableResult<Vec<RecordBatch>> {
+        let stats = probing_core::trace::span_ring().stats();
+        let columns = [
+            ("capacity", stats.capacity as i64),
+            ("spans", stats.spans as i64),
+            ("events", stats.events as i64),
+            ("recorded_spans", stats.recorded_spans as i64),
+            ("dropped_spans", stats.dropped_spans as i64),
+            ("dropped_events", stats.dropped_events as i64),
+            (
+                "evicted_traces",
+                probing_core::trace::evicted_trace_count() as i64,
+            ),
+        ];
+        let schema = SchemaRef::new(Schema::new(
+            columns
+                .iter()
+                .map(|(name, _)| Field::new(*name, DataType::Int64, false))
+                .collect::<Vec<_>>(),
+        ));
+        let arrays = columns
+            .iter()
+            .map(|(_, v)| Arc::new(Int64Array::from(vec![*v])) as ArrayRef)
+            .collect();
+        Ok(vec![try_record_batch(schema, arrays)?])
+    }
+
+    /// Python spans open at scan time, read from the live span registry
+    /// (`features::python::tracing::active_spans`) rather than the ring.
+    fn active_spans_data() -> TableResult<Vec<RecordBatch>> {
+        let spans = crate::features::python::tracing::active_spans();
+        let schema = SchemaRef::new(Schema::new(vec![
+            Field::new("trace_id", DataType::Int64, false),
+            Field::new("span_id", DataType::Int64, false),
+            Field::new("parent_id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("start_timestamp", DataType::Int64, false),
+            Field::new("thread_id", DataType::Int64, false),
+            Field::new("depth", DataType::Int64, false),
+            Field::new("attributes", DataType::Utf8, false),
+        ]));
+        let ints = |f: &dyn Fn(&crate::features::python::tracing::ActiveSpan) -> i64| {
+            Arc::new(Int64Array::from_iter_values(spans.iter().map(f))) as ArrayRef
+        };
+        let columns: Vec<ArrayRef> = vec![
+            ints(&|s| s.trace_id as i64),
+            ints(&|s| s.span_id as i64),
+            Arc::new(Int64Array::from_iter(
+                spans.iter().map(|s| s.parent_id.map(|id| id as i64)),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                spans.iter().map(|s| s.name.as_str()),
+            )),
+            ints(&|s| i64::try_from(s.start_timestamp).unwrap_or(i64::MAX)),
+            ints(&|s| s.thread_id as i64),
+            ints(&|s| s.depth as i64),
+            Arc::new(StringArray::from_iter_values(
+                spans.iter().map(|s| s.attributes.as_str()),
+            )),
+        ];
+        Ok(vec![try_record_batch(schema, columns)?])
+    }
+
     fn get_backtrace_data() -> TableResult<Vec<RecordBatch>> {
         let frames =
             crate::extensions::python::backtrace(None).map_err(PythonTableError::Backtrace)?;
@@ -159,6 +228,7 @@ impl PythonNamespace {
 
     fn data_from_python(expr: &str) -> TableResult<Vec<RecordBatch>> {
         Python::attach(|py| {
+            let _gil = scan_stats::gil_section();
             let import_path = expr.split(['(', '[']).next().unwrap_or(expr);
 
             let parts: Vec<&str> = import_path
@@ -197,6 +267,12 @@ impl PythonNamespace {
 
             let result = py.eval(&expr, None, Some(&locals))?;
 
+            // Columnar values (numpy, pandas, Arrow streams) skip the
+            // per-element conversion below.
+            if let Some(batches) = super::arrow_bridge::columnar_batches(&result)? {
+                return Ok(batches);
+            }
+
             // Handle different Python types
             if let Ok(list) = result.cast::<PyList>() {
                 return Self::list_to_recordbatch(list);
@@ -222,14 +298,24 @@ impl CustomNamespace for PythonNamespace {
         // by the mmap SQL catalog (`probing_core::core::memtable_sql`), not
         // by this namespace.
         vec![
+            "active_spans".to_string(),
             "backtrace".to_string(),
             "profile_capture".to_string(),
             "profile_hotspot".to_string(),
+            "trace_stats".to_string(),
         ]
     }
 
     fn data(expr: &str) -> Vec<RecordBatch> {
-        if expr == "backtrace" {
+        if expr == "active_spans" {
+            match Self::active_spans_data() {
+                Ok(batches) => batches,
+                Err(e) => {
+                    error!("python.active_spans: {e:?}");
+                    error_batch(&e.to_string())
+                }
+            }
+        } else if expr == "backtrace" {
             match Self::get_backtrace_data() {
                 Ok(batches) => batches,
                 Err(PythonTableError::Backtrace(ref source))
@@ -259,6 +345,14 @@ impl CustomNamespace for PythonNamespace {
                     error_batch(&e.to_string())
                 }
             }
+        } else if expr == "trace_stats" {
+            match Self::trace_stats_data() {
+                Ok(batches) => batches,
+                Err(e) => {
+                    error!("python.trace_stats: {e:?}");
+                    error_batch(&e.to_string())
+                }
+            }
         } else if !expr.contains('.') {
             // Extern mmap tables (`comm_collective`, `torch_trace`, …) — not Python imports.
             debug!("python.{expr}: no live data (mmap empty or not created yet)");
@@ -268,6 +362,10 @@ impl CustomNamespace for PythonNamespace {
         } else {
             match Self::data_from_python(expr) {
                 Ok(batches) => batches,
+                Err(e @ PythonTableError::Stopped(_)) => {
+                    debug!("python.{expr}: {e}");
+                    error_batch(&e.to_string())
+                }
                 Err(e) => {
                     error!("Python dynamic expr {expr}: {e:?}");
                     error_batch(&e.to_string())
@@ -493,4 +591,86 @@ mod tests {
         assert_eq!(batches.len(), 1);
         assert_eq!(batches[0].schema().field(0).name(), "_error");
     }
+
+    #[test]
+    fn test_python_expression_scan_reports_gil_time() {
+        pyo3::Python::initialize();
+        let lazy = PythonNamespace::make_lazy("sys.path");
+        let metrics = lazy.metrics.expect("python scans are measured");
+        assert_eq!(lazy.name, "python.sys.path");
+        assert!(metrics.gil_acquisitions >= 1, "{metrics:?}");
+        assert!(metrics.rows > 0, "{metrics:?}");
+    }
+
+    #[test]
+    fn test_trace_stats_reports_ring_counters() {
+        let mut span = probing_core::trace::Span::new_root("stats", None, None);
+        span.finish();
+        let batches = PythonNamespace::data("trace_stats");
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 1);
+        let col = |name: &str| {
+            batch
+                .column_by_name(name)
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .map(|c| c.value(0))
+                .expect(name)
+        };
+        assert!(col("recorded_spans") >= 1);
+        assert!(col("events") <= col("capacity"));
+        assert!(col("dropped_events") >= col("dropped_spans"));
+        assert!(col("evicted_traces") >= 0);
+    }
+
+    #[test]
+    fn test_active_spans_lists_only_open_spans() {
+        use crate::features::python::tracing::Span;
+        use probing_core::trace::Span as RawSpan;
+
+        pyo3::Python::initialize();
+        Python::attach(|py| {
+            let mut root_raw = RawSpan::new_root("active_root", None, None);
+            root_raw.add_attr("rank", 3i64).unwrap();
+            let child_raw = RawSpan::new_child(&root_raw, "active_child", None, None);
+            let root_id = root_raw.span_id;
+            let child_id = child_raw.span_id;
+            let _root = Span::from_raw(py, root_raw);
+            let _child = Span::from_raw(py, child_raw);
+
+            let dropped = RawSpan::new_root("active_dropped", None, None);
+            drop(Span::from_raw(py, dropped));
+            let mut finished = RawSpan::new_root("active_finished", None, None);
+            finished.finish();
+            let _finished = Span::from_raw(py, finished);
+
+            let batches = PythonNamespace::data("active_spans");
+            let batch = &batches[0];
+            let ids = batch
+                .column_by_name("span_id")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .expect("span_id");
+            let names = batch
+                .column_by_name("name")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .expect("name");
+            let depths = batch
+                .column_by_name("depth")
+                .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+                .expect("depth");
+            let attrs = batch
+                .column_by_name("attributes")
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .expect("attributes");
+            let row = |id: u64| (0..batch.num_rows()).find(|&i| ids.value(i) == id as i64);
+
+            let root = row(root_id).expect("open root listed");
+            let child = row(child_id).expect("open child listed");
+            assert_eq!(depths.value(root), 0);
+            assert_eq!(depths.value(child), 1);
+            assert_eq!(attrs.value(root), r#"{"rank":3}"#);
+            let listed: Vec<&str> = (0..batch.num_rows()).map(|i| names.value(i)).collect();
+            assert!(!listed.contains(&"active_dropped"), "{listed:?}");
+            assert!(!listed.contains(&"active_finished"), "{listed:?}");
+        });
+    }
 }
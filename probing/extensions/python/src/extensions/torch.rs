@@ -29,6 +29,10 @@ impl ProbeExtensionCall for TorchProbeExtension {
                 let metric = params.get("metric").map(|s| s.as_str());
                 Ok(crate::features::torch::flamegraph_json(metric).into_bytes())
             }
+            "flamegraph/folded/json" => {
+                let metric = params.get("metric").map(|s| s.as_str());
+                Ok(crate::features::torch::folded_lines_json(metric).into_bytes())
+            }
             _ => Err(EngineError::UnsupportedCall),
         }
     }
@@ -1,6 +1,7 @@
 //! PyO3 functions registered on the `probing._core` module
-//! (config, SQL query, callstack, eval, enable flags).
+//! (config, SQL query, callstack, eval, enable flags, probe events).
 
+use std::collections::BTreeMap;
 use std::panic::{catch_unwind, AssertUnwindSafe};
 
 use probing_core::config;
@@ -72,6 +73,26 @@ pub fn api_callstack(tid: Option<i32>) -> PyResult<String> {
     })
 }
 
+/// HTTP `GET /apis/pythonext/callstack?group=thread` backend: one entry per
+/// registered Python thread, Python frames only unless `native`.
+#[pyfunction]
+#[pyo3(signature = (native=false))]
+pub fn api_callstack_threads(native: bool) -> PyResult<String> {
+    let payload = catch_unwind(AssertUnwindSafe(|| {
+        let threads = SignalTracer.trace_threads(native);
+        serde_json::to_string(&serde_json::json!({ "threads": threads })).unwrap_or_else(|e| {
+            serde_json::json!({
+                "error": format!("failed to encode callstack: {e}"),
+                "threads": [],
+            })
+            .to_string()
+        })
+    }));
+    Ok(payload.unwrap_or_else(|_| {
+        serde_json::json!({"error": "callstack capture panicked", "threads": []}).to_string()
+    }))
+}
+
 /// HTTP `POST /apis/pythonext/eval` backend.
 #[pyfunction]
 pub fn api_eval(code: &str) -> PyResult<String> {
@@ -84,6 +105,18 @@ pub fn api_eval(code: &str) -> PyResult<String> {
     }
 }
 
+/// Record an operator action in `probe.events`; `detail` is usually JSON.
+#[pyfunction]
+pub fn record_probe_event(kind: &str, detail: &str) {
+    probing_core::core::probe_events::record_probe_event(kind, detail);
+}
+
+/// Spans the OTLP exporter dropped on a full queue, for the overhead governor.
+#[pyfunction]
+pub fn trace_export_dropped() -> u64 {
+    probing_core::trace::otlp_dropped()
+}
+
 /// Get a configuration value.
 ///
 /// Returns None if the key doesn't exist, otherwise returns the value
@@ -176,6 +209,30 @@ fn is_empty(_py: Python) -> PyResult<bool> {
     with_detached_native(|| block_on(config::is_empty()).map_err(runtime_err))
 }
 
+/// Mark a key as lowered by the overhead governor; `manual` is restored later.
+#[pyfunction(name = "config_govern")]
+#[pyo3(signature = (key, manual=None))]
+fn govern(_py: Python, key: String, manual: Option<String>) -> PyResult<()> {
+    with_detached_native(move || {
+        block_on(async move { config::govern(&key, manual).await }).map_err(runtime_err)
+    })
+}
+
+/// Stop governing a key; returns `(True, manual value)` when it was governed.
+#[pyfunction(name = "config_release")]
+fn release(_py: Python, key: String) -> PyResult<(bool, Option<String>)> {
+    with_detached_native(move || {
+        let released = block_on(async move { config::release(&key).await }).map_err(runtime_err)?;
+        Ok((released.is_some(), released.flatten()))
+    })
+}
+
+/// Governed keys and the manual values they will be restored to.
+#[pyfunction(name = "config_governed")]
+fn governed(_py: Python) -> PyResult<BTreeMap<String, Option<String>>> {
+    with_detached_native(|| block_on(config::governed()).map_err(runtime_err))
+}
+
 /// Register the config functions directly to the probing Python module.
 pub fn register_config_functions(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_function(wrap_pyfunction!(get, module)?)?;
@@ -188,6 +245,9 @@ pub fn register_config_functions(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_function(wrap_pyfunction!(clear, module)?)?;
     module.add_function(wrap_pyfunction!(len, module)?)?;
     module.add_function(wrap_pyfunction!(is_empty, module)?)?;
+    module.add_function(wrap_pyfunction!(govern, module)?)?;
+    module.add_function(wrap_pyfunction!(release, module)?)?;
+    module.add_function(wrap_pyfunction!(governed, module)?)?;
 
     Ok(())
 }
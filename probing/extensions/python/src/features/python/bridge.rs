@@ -32,7 +32,7 @@ pub fn ele_to_python(py: Python, ele: &Ele) -> PyResult<Py<PyAny>> {
         Ele::Nil => py.None(),
         Ele::BOOL(b) => PyBool::new(py, *b).to_owned().unbind().into(),
         Ele::I32(i) => PyInt::new(py, *i as i64).to_owned().unbind().into(),
-        Ele::I64(i) => PyInt::new(py, *i).to_owned().unbind().into(),
+        Ele::I64(i) | Ele::DateTime(i) => PyInt::new(py, *i).to_owned().unbind().into(),
         Ele::F32(f) => PyFloat::new(py, *f as f64).to_owned().unbind().into(),
         Ele::F64(f) => PyFloat::new(py, *f).to_owned().unbind().into(),
         Ele::Text(s) => PyString::new(py, s).to_owned().unbind().into(),
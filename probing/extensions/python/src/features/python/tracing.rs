@@ -2,13 +2,16 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList, PyModule};
 use pyo3::IntoPyObjectExt;
 use std::cell::RefCell;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, Weak};
 
 use probing_core::sync::lock_mutex;
 use probing_core::trace::Span as RawSpan;
 use probing_core::trace::{
-    advance_micro_step, attr, set_micro_batches, step_snapshot, sync_micro_step, Attribute,
-    Event as RawEvent, SpanStatus, StepSnapshot, Timestamp,
+    advance_micro_step, attr, attrs_json, clear_remote_span_recorder, clear_step_provider,
+    current_traceparent, register_remote_span_recorder, register_step_provider, sampled_step,
+    set_micro_batches, step_snapshot, sync_micro_step, Attribute, Event as RawEvent, SpanStatus,
+    StepSnapshot, Timestamp,
 };
 
 use crate::features::python::bridge::{ele_to_python, python_to_ele};
@@ -145,6 +148,104 @@ fn pop_span_hint(span_id: u64) {
     });
 }
 
+/// Spans created from Python that may still be open, keyed by span id.
+///
+/// Entries are weak so the registry never keeps a span alive; dropped and
+/// finished spans are pruned as new spans register and whenever
+/// [`active_spans`] takes a snapshot.
+static OPEN_SPANS: LazyLock<Mutex<OpenSpans>> = LazyLock::new(Default::default);
+
+#[derive(Default)]
+struct OpenSpans {
+    spans: HashMap<u64, Weak<Mutex<RawSpan>>>,
+    /// Size at which the next insert sweeps out dropped spans.
+    prune_at: usize,
+}
+
+const MIN_PRUNE_AT: usize = 256;
+
+fn register_open_span(span_id: u64, span: &Arc<Mutex<RawSpan>>) {
+    let mut open = lock_mutex(&OPEN_SPANS, "open spans");
+    if open.spans.len() >= open.prune_at {
+        open.spans.retain(|_, w| w.strong_count() > 0);
+        open.prune_at = (open.spans.len() * 2).max(MIN_PRUNE_AT);
+    }
+    open.spans.insert(span_id, Arc::downgrade(span));
+}
+
+/// A span that had not ended when [`active_spans`] ran.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveSpan {
+    pub trace_id: u64,
+    pub span_id: u64,
+    pub parent_id: Option<u64>,
+    pub name: String,
+    /// Start time, nanoseconds since the Unix epoch.
+    pub start_timestamp: u128,
+    pub thread_id: u64,
+    /// Number of open ancestors above this span.
+    pub depth: usize,
+    /// Attributes as a JSON object.
+    pub attributes: String,
+}
+
+/// Snapshot of the Python spans that are open right now, oldest first.
+///
+/// Each span is checked under its own lock, so a span that ends while the
+/// snapshot is taken is either reported as still open or left out, never
+/// half-read.
+pub fn active_spans() -> Vec<ActiveSpan> {
+    let live: Vec<Arc<Mutex<RawSpan>>> = {
+        let mut open = lock_mutex(&OPEN_SPANS, "open spans");
+        open.spans.retain(|_, w| w.strong_count() > 0);
+        open.spans.values().filter_map(Weak::upgrade).collect()
+    };
+
+    let mut spans = Vec::with_capacity(live.len());
+    let mut ended = Vec::new();
+    for span in &live {
+        let s = lock_span(span);
+        if s.is_ended() {
+            ended.push(s.span_id);
+            continue;
+        }
+        spans.push(ActiveSpan {
+            trace_id: s.trace_id,
+            span_id: s.span_id,
+            parent_id: s.parent_id,
+            name: s.name.clone(),
+            start_timestamp: s.start.0,
+            thread_id: s.thread_id,
+            depth: 0,
+            attributes: attrs_json(&s.attrs).to_string(),
+        });
+    }
+    if !ended.is_empty() {
+        let mut open = lock_mutex(&OPEN_SPANS, "open spans");
+        for id in ended {
+            open.spans.remove(&id);
+        }
+    }
+
+    let parents: HashMap<u64, Option<u64>> =
+        spans.iter().map(|s| (s.span_id, s.parent_id)).collect();
+    for span in &mut spans {
+        let mut parent = span.parent_id;
+        while let Some(id) = parent {
+            match parents.get(&id) {
+                // Bounded by the number of open spans in case of a cycle.
+                Some(next) if span.depth < parents.len() => {
+                    span.depth += 1;
+                    parent = *next;
+                }
+                _ => break,
+            }
+        }
+    }
+    spans.sort_by_key(|s| (s.start_timestamp, s.span_id));
+    spans
+}
+
 /// Python binding for Span
 #[pyclass(from_py_object)]
 #[derive(Clone)]
@@ -153,6 +254,18 @@ pub struct Span {
 }
 
 impl Span {
+    pub(crate) fn from_raw(py: Python, mut span: RawSpan) -> Self {
+        // Python thread names ("MainThread", "Thread-3 (worker)") are more
+        // useful than the OS name, which Python does not set.
+        if let Some(name) = python_thread_name(py) {
+            span.thread_name = Some(name);
+        }
+        let span_id = span.span_id;
+        let inner = Arc::new(Mutex::new(span));
+        register_open_span(span_id, &inner);
+        Span { inner }
+    }
+
     fn with_inner<R>(&self, f: impl FnOnce(&RawSpan) -> R) -> R {
         f(&lock_span(&self.inner))
     }
@@ -167,17 +280,16 @@ impl Span {
     /// Creates a new root span (starts a new trace).
     #[new]
     #[pyo3(signature = (name, *, phase=None, location=None))]
-    fn new(name: String, phase: Option<String>, location: Option<String>) -> Self {
+    fn new(py: Python, name: String, phase: Option<String>, location: Option<String>) -> Self {
         let span = RawSpan::new_root(name, phase.as_deref(), location.as_deref());
-        Span {
-            inner: Arc::new(Mutex::new(span)),
-        }
+        Span::from_raw(py, span)
     }
 
     /// Creates a new child span from a parent span.
     #[staticmethod]
     #[pyo3(signature = (parent, name, *, phase=None, location=None))]
     fn new_child(
+        py: Python,
         parent: &Bound<'_, Span>,
         name: String,
         phase: Option<String>,
@@ -186,9 +298,7 @@ impl Span {
         let span = parent.borrow().with_inner(|parent_span| {
             RawSpan::new_child(parent_span, name, phase.as_deref(), location.as_deref())
         });
-        Span {
-            inner: Arc::new(Mutex::new(span)),
-        }
+        Span::from_raw(py, span)
     }
 
     /// Gets the trace ID.
@@ -215,6 +325,24 @@ impl Span {
         self.with_inner(|s| s.thread_id)
     }
 
+    /// Gets the originating thread name (Python name when created from Python).
+    #[getter]
+    fn thread_name(&self) -> Option<String> {
+        self.with_inner(|s| s.thread_name.clone())
+    }
+
+    /// Thread CPU time (ns) between start and end, when `probing.trace.cpu_time` is on.
+    #[getter]
+    fn cpu_time_ns(&self) -> Option<u64> {
+        self.with_inner(|s| s.cpu_time_ns)
+    }
+
+    /// Context switches between start and end, when `probing.trace.cpu_time` is on.
+    #[getter]
+    fn ctx_switches(&self) -> Option<u64> {
+        self.with_inner(|s| s.ctx_switches)
+    }
+
     /// Gets the span name.
     #[getter]
     fn name(&self) -> String {
@@ -233,6 +361,7 @@ impl Span {
         match self.with_inner(|s| s.status()) {
             SpanStatus::Active => "Active".to_string(),
             SpanStatus::Completed => "Completed".to_string(),
+            SpanStatus::Error(_) => "Error".to_string(),
         }
     }
 
@@ -260,6 +389,12 @@ impl Span {
         self.with_inner(|s| s.end.map(|t| t.0))
     }
 
+    /// W3C `traceparent` header value naming this span as the parent of a
+    /// call to another process.
+    fn traceparent(&self) -> String {
+        self.with_inner(RawSpan::traceparent)
+    }
+
     /// Gets the location from location if available.
     #[getter]
     fn location(&self) -> Option<String> {
@@ -306,6 +441,34 @@ impl Span {
         Ok(())
     }
 
+    /// Links the span to `target`: another `Span`, or a `(trace_id, span_id)`
+    /// pair for one in a different process or an earlier run.
+    #[pyo3(signature = (target, *, attributes=None))]
+    fn add_link(
+        &mut self,
+        target: &Bound<'_, PyAny>,
+        attributes: Option<Vec<Py<PyAny>>>,
+        py: Python,
+    ) -> PyResult<()> {
+        let (trace_id, span_id) = match target.cast::<Span>() {
+            Ok(span) => span
+                .borrow()
+                .with_inner(|inner| (inner.trace_id, inner.span_id)),
+            Err(_) => target.extract::<(u64, u64)>().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                    "add_link expects a Span or a (trace_id, span_id) tuple",
+                )
+            })?,
+        };
+        let attrs = attributes
+            .map(|attrs| parse_py_attributes(py, attrs))
+            .transpose()?;
+
+        self.with_inner_mut(|inner| inner.add_link(trace_id, span_id, attrs))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
+        Ok(())
+    }
+
     /// Ends the span.
     fn end(&mut self) {
         self.with_inner_mut(|inner| inner.end());
@@ -340,6 +503,20 @@ impl Span {
         Ok(list.into())
     }
 
+    /// Gets all links as a list of `{trace_id, span_id, attributes}` dicts.
+    fn get_links(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let list = PyList::empty(py);
+        let inner = lock_span(&self.inner);
+        for link in &inner.links {
+            let link_dict = PyDict::new(py);
+            link_dict.set_item("trace_id", link.trace_id)?;
+            link_dict.set_item("span_id", link.span_id)?;
+            link_dict.set_item("attributes", attrs_to_dict(py, &link.attributes)?)?;
+            list.append(link_dict)?;
+        }
+        Ok(list.into())
+    }
+
     /// Gets an attribute by name (for dynamic attribute access like s.a, s.b).
     fn __getattr__(&self, name: &str, py: Python) -> PyResult<Py<PyAny>> {
         match name {
@@ -347,6 +524,9 @@ impl Span {
             "span_id" => return Ok(self.span_id().into_bound_py_any(py)?.into()),
             "parent_id" => return optional_into_py(py, self.parent_id()),
             "thread_id" => return Ok(self.thread_id().into_bound_py_any(py)?.into()),
+            "thread_name" => return optional_into_py(py, self.thread_name()),
+            "cpu_time_ns" => return optional_into_py(py, self.cpu_time_ns()),
+            "ctx_switches" => return optional_into_py(py, self.ctx_switches()),
             "name" => return Ok(self.name().into_bound_py_any(py)?.into()),
             "phase" => return optional_into_py(py, self.phase()),
             "status" => return Ok(self.status().into_bound_py_any(py)?.into()),
@@ -381,18 +561,26 @@ impl Span {
         Ok(slf)
     }
 
-    /// Context manager exit (for `with` statement support).
+    /// Context manager exit (for `with` statement support). A span left by
+    /// an exception ends with [`SpanStatus::Error`]; the exception is never
+    /// suppressed.
     fn __exit__(
         slf: PyRef<Self>,
         exc_type: Option<&Bound<'_, PyAny>>,
-        _exc_val: Option<&Bound<'_, PyAny>>,
+        exc_val: Option<&Bound<'_, PyAny>>,
         _exc_tb: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<bool> {
-        if exc_type.is_some_and(|t| !t.is_none()) {
+        let error = exc_type
+            .filter(|t| !t.is_none())
+            .map(|t| exception_summary(t, exc_val));
+        if error.is_some() {
             capture_span_snapshot_for_crash();
         }
         let self_id = slf.span_id();
-        lock_span(&slf.inner).end();
+        match error {
+            Some(message) => lock_span(&slf.inner).end_error(Some(message)),
+            None => lock_span(&slf.inner).end(),
+        }
         pop_span_hint(self_id);
 
         SPAN_STACK.with(|stack| {
@@ -435,12 +623,41 @@ impl Span {
                 match inner.status() {
                     SpanStatus::Active => "Active",
                     SpanStatus::Completed => "Completed",
+                    SpanStatus::Error(_) => "Error",
                 }
             )
         })
     }
 }
 
+/// `Type: message` (or just `Type`) for an exception leaving a span.
+fn exception_summary(exc_type: &Bound<'_, PyAny>, exc_val: Option<&Bound<'_, PyAny>>) -> String {
+    let type_name = exc_type
+        .getattr("__name__")
+        .and_then(|n| n.extract::<String>())
+        .unwrap_or_else(|_| "Exception".to_string());
+    let message = exc_val
+        .filter(|v| !v.is_none())
+        .and_then(|v| v.str().ok())
+        .map(|m| m.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if message.is_empty() {
+        type_name
+    } else {
+        format!("{type_name}: {message}")
+    }
+}
+
+/// `threading.current_thread().name`, if `threading` is importable.
+fn python_thread_name(py: Python) -> Option<String> {
+    let thread = py
+        .import("threading")
+        .ok()?
+        .call_method0("current_thread")
+        .ok()?;
+    thread.getattr("name").ok()?.extract().ok()
+}
+
 /// Gets the current active span.
 #[pyfunction]
 fn current_span(py: Python) -> PyResult<Option<Py<PyAny>>> {
@@ -569,6 +786,76 @@ fn py_current_micro_step() -> u64 {
     probing_core::trace::current_micro_step()
 }
 
+/// Make `provider()` (an int, or None before the first step) the step the
+/// samplers stamp on their rows; `None` unregisters it.
+#[pyfunction]
+#[pyo3(signature = (provider=None))]
+fn py_set_step_provider(provider: Option<Py<PyAny>>) {
+    let Some(provider) = provider else {
+        clear_step_provider();
+        return;
+    };
+    register_step_provider(Arc::new(move || {
+        Python::attach(|py| {
+            let step = provider.bind(py).call0().ok()?;
+            step.extract::<Option<u64>>().ok().flatten()
+        })
+    }));
+}
+
+/// `traceparent` value for the innermost active Python span, falling back
+/// to Rust spans entered on this thread; `None` outside any span.
+#[pyfunction]
+fn py_traceparent(py: Python) -> PyResult<Option<String>> {
+    if let Some(span) = active_span_for_events(py)? {
+        return Ok(Some(span.bind(py).cast::<Span>()?.borrow().traceparent()));
+    }
+    Ok(current_traceparent())
+}
+
+/// Make `recorder(fields)` receive spans started from an incoming
+/// `traceparent` header once they end (see `probing_core::trace::propagation`);
+/// `None` unregisters it.
+#[pyfunction]
+#[pyo3(signature = (recorder=None))]
+fn py_set_remote_span_recorder(recorder: Option<Py<PyAny>>) {
+    let Some(recorder) = recorder else {
+        clear_remote_span_recorder();
+        return;
+    };
+    register_remote_span_recorder(Arc::new(move |span: &RawSpan| {
+        Python::attach(|py| {
+            let recorded = remote_span_fields(py, span)
+                .and_then(|fields| recorder.bind(py).call1((fields,)).map(drop));
+            if let Err(err) = recorded {
+                log::debug!("remote span recorder failed: {err}");
+            }
+        })
+    }));
+}
+
+fn remote_span_fields<'py>(py: Python<'py>, span: &RawSpan) -> PyResult<Bound<'py, PyDict>> {
+    let fields = PyDict::new(py);
+    fields.set_item("trace_id", span.trace_id)?;
+    fields.set_item("span_id", span.span_id)?;
+    fields.set_item("parent_id", span.parent_id)?;
+    fields.set_item("name", &span.name)?;
+    fields.set_item("phase", span.phase.as_deref())?;
+    fields.set_item("thread_id", span.thread_id)?;
+    fields.set_item("thread_name", span.thread_name.as_deref())?;
+    fields.set_item("start_timestamp", span.start.0 as u64)?;
+    fields.set_item("end_timestamp", span.end.map(|t| t.0 as u64))?;
+    fields.set_item("cpu_time_ns", span.cpu_time_ns)?;
+    fields.set_item("ctx_switches", span.ctx_switches)?;
+    fields.set_item("attributes", attrs_json(&span.attrs).to_string())?;
+    Ok(fields)
+}
+
+#[pyfunction]
+fn py_sampled_step() -> Option<u64> {
+    sampled_step()
+}
+
 /// Internal function to create a span - called by Python wrapper.
 /// This is a low-level function that directly creates a span.
 #[pyfunction]
@@ -587,9 +874,9 @@ fn _span_raw(
     let span = if let Some(parent) = parent {
         let parent_obj = parent.bind(py);
         let parent_span = parent_obj.cast::<Span>()?;
-        Span::new_child(parent_span, name, phase, location)
+        Span::new_child(py, parent_span, name, phase, location)
     } else {
-        Span::new(name, phase, location)
+        Span::new(py, name, phase, location)
     };
 
     Ok(span)
@@ -662,6 +949,10 @@ pub fn register_tracing_functions(module: &Bound<'_, PyModule>) -> PyResult<()>
     module.add_function(wrap_pyfunction!(py_advance_micro_step, module)?)?;
     module.add_function(wrap_pyfunction!(py_set_micro_batches, module)?)?;
     module.add_function(wrap_pyfunction!(py_current_micro_step, module)?)?;
+    module.add_function(wrap_pyfunction!(py_set_step_provider, module)?)?;
+    module.add_function(wrap_pyfunction!(py_sampled_step, module)?)?;
+    module.add_function(wrap_pyfunction!(py_traceparent, module)?)?;
+    module.add_function(wrap_pyfunction!(py_set_remote_span_recorder, module)?)?;
 
     Ok(())
 }
@@ -272,6 +272,64 @@ fn thread_slot(tid: u64) -> Option<&'static ThreadSlot> {
     None
 }
 
+/// OS tids of every registered Python thread, including exited ones: slots
+/// are never released (see [`thread_alive`]).
+pub fn registered_tids() -> Vec<u64> {
+    REG_TABLE
+        .iter()
+        .map(|slot| slot.tid.load(Ordering::Acquire))
+        .filter(|&tid| tid != 0)
+        .collect()
+}
+
+/// False once `tid` has exited; always true where that cannot be checked.
+pub fn thread_alive(tid: u64) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        std::path::Path::new(&format!("/proc/self/task/{tid}")).exists()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = tid;
+        true
+    }
+}
+
+/// Scheduler state of a thread of this process as `S (sleeping)`, with the
+/// kernel function it waits in when `/proc` exposes it.
+pub fn thread_state(tid: u64) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/self/task/{tid}/stat")).ok()?;
+        // The command name may contain spaces and parentheses; the state follows its `)`.
+        let code = stat.rsplit_once(')')?.1.trim_start().chars().next()?;
+        let desc = match code {
+            'R' => "running",
+            'S' => "sleeping",
+            'D' => "disk sleep",
+            'T' => "stopped",
+            't' => "tracing stop",
+            'Z' => "zombie",
+            'X' => "dead",
+            'I' => "idle",
+            _ => "unknown",
+        };
+        let wchan = std::fs::read_to_string(format!("/proc/self/task/{tid}/wchan"))
+            .ok()
+            .map(|w| w.trim().to_string())
+            .filter(|w| !w.is_empty() && w != "0");
+        Some(match wchan {
+            Some(wchan) => format!("{code} ({desc}) in {wchan}"),
+            None => format!("{code} ({desc})"),
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = tid;
+        None
+    }
+}
+
 /// Copy the registered thread's Python stack (PYSTACKS) without delivering a signal.
 pub fn copy_registered_py_snapshot(tid: u64) -> Option<StackSnapshot> {
     let slot = thread_slot(tid)?;
@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use pyo3::Python;
 
-use probing_proto::prelude::CallFrame;
+use probing_proto::prelude::{CallFrame, ThreadStack};
 
 use probing_core::is_python_main_thread;
 
@@ -150,6 +150,59 @@ impl SignalTracer {
         ))
     }
 
+    /// Every live registered Python thread, main thread first. Python-only
+    /// stacks are copied from PYSTACKS without signals; `native` interleaves
+    /// native frames and may signal each thread. A thread whose capture fails
+    /// is listed with the error instead of frames.
+    pub fn trace_threads(&self, native: bool) -> Vec<ThreadStack> {
+        let main = capture::python_main_os_tid();
+        let mut tids = capture::registered_tids();
+        tids.retain(|&tid| capture::thread_alive(tid));
+        tids.sort_by_key(|&tid| (Some(tid) != main, tid));
+        tids.into_iter()
+            .map(|tid| {
+                let is_main = Some(tid) == main;
+                let result = if native {
+                    self.trace_native(tid, is_main)
+                } else {
+                    Self::trace_python_only(tid)
+                };
+                let (frames, error) = match result {
+                    Ok(frames) => (frames, None),
+                    Err(e) if is_backtrace_busy(&e) => {
+                        (Vec::new(), Some("callstack capture busy".to_string()))
+                    }
+                    Err(e) => (Vec::new(), Some(e.to_string())),
+                };
+                ThreadStack {
+                    tid,
+                    name: capture::thread_name(tid),
+                    main: is_main,
+                    frames,
+                    error,
+                    state: capture::thread_state(tid),
+                }
+            })
+            .collect()
+    }
+
+    fn trace_native(&self, tid: u64, is_main: bool) -> Result<Vec<CallFrame>> {
+        if is_main {
+            return Self::trace_main_thread_off_signal();
+        }
+        let tid = i32::try_from(tid)
+            .map_err(|_| anyhow::anyhow!("thread {tid} cannot be signalled for a native stack"))?;
+        self.trace(Some(tid))
+    }
+
+    fn trace_python_only(tid: u64) -> Result<Vec<CallFrame>> {
+        let snapshot = capture::copy_registered_py_snapshot(tid)
+            .ok_or_else(|| anyhow::anyhow!("thread {tid} is not registered"))?;
+        let mut frames = Self::merged_from_snapshot(&snapshot, 0);
+        frames.retain(|frame| matches!(frame, CallFrame::PyFrame { .. }));
+        Ok(frames)
+    }
+
     fn is_main_tid(tid: i32) -> bool {
         capture::python_main_os_tid().is_some_and(|main| main == tid as u64)
     }
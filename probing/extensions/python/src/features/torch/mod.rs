@@ -710,6 +710,18 @@ pub fn flamegraph_json(metric: Option<&str>) -> String {
     }
 }
 
+/// Folded module stacks (`GET /apis/torchextension/flamegraph/folded/json`),
+/// the lines behind [`flamegraph_json`] for the same `metric`.
+pub fn folded_lines_json(metric: Option<&str>) -> String {
+    let lines = query_profiling(TorchMetric::parse(metric))
+        .map(|result| result.lines)
+        .unwrap_or_else(|err| {
+            error!("Failed to query torch profiling data: {err}");
+            Vec::new()
+        });
+    json!({ "lines": lines }).to_string()
+}
+
 #[derive(Debug, Clone)]
 struct TorchTraceRow {
     rank: i64,
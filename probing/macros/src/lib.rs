@@ -11,6 +11,10 @@ struct OptionMetadata {
     aliases: Vec<String>,
     description: String,
     managed: bool,
+    /// `#[option(min = ..)]`, inclusive.
+    min: Option<f64>,
+    /// `#[option(max = ..)]`, inclusive.
+    max: Option<f64>,
 }
 
 #[proc_macro_derive(ProbeExtension, attributes(option))]
@@ -62,6 +66,9 @@ fn impl_probe_extension(ast: &DeriveInput) -> TokenStream {
     let set_matches = field_metadata.iter().map(|meta| {
         let field_ident = format_ident!("{}", meta.field);
         let set_field = format_ident!("set_{}", meta.field);
+        let option_name = format!("{}.{}", namespace.to_lowercase(), meta.name);
+        let min = bound(meta.min);
+        let max = bound(meta.max);
 
         let field_name = meta.name.to_string();
         let aliases = &meta.aliases;
@@ -71,9 +78,8 @@ fn impl_probe_extension(ast: &DeriveInput) -> TokenStream {
         quote! {
             #(#matchers)|* => {
                 let old = self.#field_ident.to_string();
-                let new = value.parse()
-                .map_err(|_| EngineError::InvalidOptionValue(key.to_string(), value.to_string()))?;
-                // self.#field_ident = new.clone();
+                // Parsed into the type `set_<field>` takes.
+                let new = ProbeExtensionOption::parse(#option_name, value, #min, #max)?;
                 self.#set_field(new)?;
                 Ok(old)
             }
@@ -89,12 +95,18 @@ fn impl_probe_extension(ast: &DeriveInput) -> TokenStream {
             name.to_string().to_uppercase().replace(".", "_")
         );
         let field_ident = format_ident!("{}", meta.field);
+        let set_field = format_ident!("set_{}", meta.field);
+        let min = bound(meta.min);
+        let max = bound(meta.max);
 
         quote! {
             ProbeExtensionOption {
                 key: #name.to_string(),
                 value: Some(self.#field_ident.to_string()),
                 help: #desc,
+                option_type: ProbeExtensionOption::type_of(Self::#set_field),
+                min: #min,
+                max: #max,
             }
         }
     });
@@ -156,6 +168,8 @@ fn parse_field_metadata(field: &Field) -> OptionMetadata {
         aliases: vec![],
         description: String::new(),
         managed: false,
+        min: None,
+        max: None,
     };
 
     let mut descriptions: Vec<String> = vec![];
@@ -171,6 +185,17 @@ fn parse_field_metadata(field: &Field) -> OptionMetadata {
                 {
                     if let Meta::NameValue(nv) = nested {
                         let name = nv.path.get_ident().unwrap().to_string();
+                        if name == "min" || name == "max" {
+                            let Some(number) = parse_number(&nv.value) else {
+                                panic!("`{name}` of option `{}` must be a number", metadata.field);
+                            };
+                            if name == "min" {
+                                metadata.min = Some(number);
+                            } else {
+                                metadata.max = Some(number);
+                            }
+                            continue;
+                        }
                         let value = match &nv.value {
                             syn::Expr::Lit(lit) => match &lit.lit {
                                 syn::Lit::Str(s) => s.value(),
@@ -236,3 +261,32 @@ fn parse_string_array(input: &str) -> Vec<String> {
         .map(|s| s.trim().trim_matches('"').to_string())
         .collect()
 }
+
+/// A numeric literal, possibly negated, as in `#[option(min = -1)]`.
+fn parse_number(expr: &syn::Expr) -> Option<f64> {
+    match expr {
+        syn::Expr::Lit(lit) => match &lit.lit {
+            syn::Lit::Int(int) => int.base10_parse().ok(),
+            syn::Lit::Float(float) => float.base10_parse().ok(),
+            _ => None,
+        },
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_number(expr).map(|n| -n),
+        _ => None,
+    }
+}
+
+/// `Some(<bound>f64)` or `None`, as an `Option<f64>` expression.
+fn bound(value: Option<f64>) -> proc_macro2::TokenStream {
+    match value {
+        Some(n) if n < 0.0 => {
+            let n = -n;
+            quote! { Some(-#n) }
+        }
+        Some(n) => quote! { Some(#n) },
+        None => quote! { None },
+    }
+}
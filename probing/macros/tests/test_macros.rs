@@ -1,42 +1,14 @@
-use std::convert::Infallible;
 use std::fmt::Display;
-use std::str::FromStr;
 
 use probing_core::core::EngineError;
+use probing_core::core::Maybe;
+use probing_core::core::OptionDuration;
+use probing_core::core::OptionType;
+use probing_core::core::OptionValue;
 use probing_core::core::ProbeExtension;
 use probing_core::core::ProbeExtensionCall;
 use probing_core::core::ProbeExtensionOption;
 
-#[derive(Debug)]
-enum Maybe<T> {
-    Just(T),
-    Nothing,
-}
-
-impl<T: FromStr> FromStr for Maybe<T> {
-    type Err = Infallible;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() {
-            Ok(Maybe::Nothing)
-        } else {
-            match s.parse() {
-                Ok(v) => Ok(Maybe::Just(v)),
-                Err(_) => Ok(Maybe::Nothing),
-            }
-        }
-    }
-}
-
-impl<T: Display> Display for Maybe<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self {
-            Maybe::Just(s) => write!(f, "{s}"),
-            Maybe::Nothing => write!(f, ""),
-        }
-    }
-}
-
 #[test]
 fn test_macro() {
     #[allow(unused)]
@@ -131,3 +103,118 @@ fn test_macro() {
     assert_eq!(opts[2].value, Some("B".to_string()));
     // assert_eq!(opts[2].help, "describe managed_field_name3");
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Fast,
+    Exact,
+}
+
+impl Mode {
+    const NAMES: &'static [&'static str] = &["fast", "exact"];
+}
+
+impl OptionValue for Mode {
+    fn option_type() -> OptionType {
+        OptionType::Enum(Self::NAMES)
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        match probing_core::core::option_value::parse_variant(value, Self::NAMES)? {
+            0 => Some(Mode::Fast),
+            _ => Some(Mode::Exact),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Self::NAMES[*self as usize])
+    }
+}
+
+#[test]
+fn test_typed_options() {
+    #[derive(Debug, Default, ProbeExtension)]
+    struct TypedExtension {
+        /// Samples per second
+        #[option(min = 1, max = 1000)]
+        freq: Maybe<i64>,
+
+        /// Enable the thing
+        #[option]
+        enabled: Maybe<bool>,
+
+        /// Flush interval
+        #[option(max = 60)]
+        interval: Maybe<OptionDuration>,
+
+        /// Accuracy
+        #[option]
+        mode: Maybe<Mode>,
+    }
+
+    impl ProbeExtensionCall for TypedExtension {}
+
+    impl TypedExtension {
+        fn set_freq(&mut self, value: Maybe<i64>) -> Result<(), EngineError> {
+            self.freq = value;
+            Ok(())
+        }
+
+        fn set_enabled(&mut self, value: Maybe<bool>) -> Result<(), EngineError> {
+            self.enabled = value;
+            Ok(())
+        }
+
+        fn set_interval(&mut self, value: Maybe<OptionDuration>) -> Result<(), EngineError> {
+            self.interval = value;
+            Ok(())
+        }
+
+        fn set_mode(&mut self, value: Maybe<Mode>) -> Result<(), EngineError> {
+            self.mode = value;
+            Ok(())
+        }
+    }
+
+    let mut ext = TypedExtension::default();
+    assert!(ext.set("freq", "99").is_ok());
+    assert_eq!(ext.get("freq").unwrap(), "99");
+
+    let err = ext.set("freq", "abc").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid option value: typed.freq=abc (expected integer in [1, 1000])"
+    );
+    assert!(ext.set("freq", "0").is_err());
+    assert!(ext.set("freq", "1001").is_err());
+    assert_eq!(ext.get("freq").unwrap(), "99");
+    assert!(ext.set("freq", "").is_ok());
+    assert_eq!(ext.get("freq").unwrap(), "");
+
+    assert!(ext.set("enabled", "on").is_ok());
+    assert_eq!(ext.get("enabled").unwrap(), "true");
+    assert!(ext.set("enabled", "sometimes").is_err());
+
+    assert!(ext.set("interval", "500ms").is_ok());
+    assert_eq!(ext.get("interval").unwrap(), "500ms");
+    assert!(ext.set("interval", "2m").is_err());
+    assert!(ext.set("interval", "30").is_err());
+
+    assert!(ext.set("mode", "EXACT").is_ok());
+    assert_eq!(ext.get("mode").unwrap(), "exact");
+    let err = ext.set("mode", "slow").unwrap_err();
+    assert!(
+        err.to_string().ends_with("(expected one of fast|exact)"),
+        "{err}"
+    );
+
+    let opts = ext.options();
+    assert_eq!(opts[0].option_type, OptionType::Integer);
+    assert_eq!((opts[0].min, opts[0].max), (Some(1.0), Some(1000.0)));
+    assert_eq!(opts[0].spec(), "integer in [1, 1000]");
+    assert_eq!(opts[1].option_type, OptionType::Bool);
+    assert_eq!(opts[2].spec(), "duration <= 60");
+    assert_eq!(opts[3].option_type, OptionType::Enum(Mode::NAMES));
+}
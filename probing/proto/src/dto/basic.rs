@@ -16,6 +16,7 @@ pub enum EleType {
     Text,
     Url,
     DataTime,
+    DateTime,
 }
 
 /// Element value enumeration for DTO
@@ -30,6 +31,8 @@ pub enum Ele {
     Text(String),
     Url(String),
     DataTime(u64),
+    /// Nanoseconds since the Unix epoch, UTC.
+    DateTime(i64),
 }
 
 impl Display for Ele {
@@ -48,6 +51,7 @@ impl Display for Ele {
                     (SystemTime::UNIX_EPOCH + Duration::from_micros(*x)).into();
                 f.write_fmt(format_args!("{}", datetime.to_rfc3339()))
             }
+            Ele::DateTime(x) => f.write_str(&crate::types::basic::format_datetime(*x)),
         }
     }
 }
@@ -64,6 +68,7 @@ pub enum Seq {
     SeqF64(Vec<f64>),
     SeqText(Vec<String>),
     SeqDateTime(Vec<u64>),
+    SeqTimestamp(Vec<i64>),
 }
 
 impl Seq {
@@ -76,6 +81,7 @@ impl Seq {
             Seq::SeqF64(vec) => vec.len(),
             Seq::SeqText(vec) => vec.len(),
             Seq::SeqDateTime(vec) => vec.len(),
+            Seq::SeqTimestamp(vec) => vec.len(),
             Seq::Nil => 0,
         }
     }
@@ -96,6 +102,7 @@ impl Seq {
             Seq::SeqF64(vec) => vec.get(idx).map(|x| Ele::F64(*x)),
             Seq::SeqText(vec) => vec.get(idx).map(|x| Ele::Text(x.clone())),
             Seq::SeqDateTime(vec) => vec.get(idx).map(|x| Ele::DataTime(*x)),
+            Seq::SeqTimestamp(vec) => vec.get(idx).map(|x| Ele::DateTime(*x)),
             Seq::Nil => None,
         }
         .unwrap_or(Ele::Nil)
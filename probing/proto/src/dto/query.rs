@@ -159,6 +159,7 @@ fn convert_ele(ele: crate::types::basic::Ele) -> super::basic::Ele {
         crate::types::basic::Ele::Text(x) => super::basic::Ele::Text(x),
         crate::types::basic::Ele::Url(x) => super::basic::Ele::Url(x),
         crate::types::basic::Ele::DataTime(x) => super::basic::Ele::DataTime(x),
+        crate::types::basic::Ele::DateTime(x) => super::basic::Ele::DateTime(x),
     }
 }
 
@@ -184,6 +185,9 @@ impl From<crate::protocol::query::Data> for QueryDataDto {
                         crate::types::Seq::SeqF64(vec) => super::basic::Seq::SeqF64(vec),
                         crate::types::Seq::SeqText(vec) => super::basic::Seq::SeqText(vec),
                         crate::types::Seq::SeqDateTime(vec) => super::basic::Seq::SeqDateTime(vec),
+                        crate::types::Seq::SeqTimestamp(vec) => {
+                            super::basic::Seq::SeqTimestamp(vec)
+                        }
                     })
                     .collect();
 
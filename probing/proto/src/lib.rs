@@ -4,14 +4,21 @@ pub mod types;
 
 pub mod prelude {
     // --- Protocol Structures ---
+    pub use crate::protocol::chart::{ChartQueryRequest, ChartQueryResponse};
     pub use crate::protocol::cluster::{
-        Cluster, Node, NodeListResponse, NodeReportRequest, NodeReportResponse,
+        Cluster, Node, NodeListResponse, NodeMetrics, NodeMetricsResponse, NodeMetricsSample,
+        NodeReportRequest, NodeReportResponse,
     };
+    pub use crate::protocol::config::ConfigChange;
     pub use crate::protocol::message::Message;
-    pub use crate::protocol::process::{CallFrame, Process};
+    pub use crate::protocol::process::{CallFrame, Process, ThreadStack};
 
     pub use crate::protocol::query::{Data as QueryDataFormat, Options as QueryOptions, Query};
-    pub use crate::protocol::query::{ErrorCode, QueryError};
+    pub use crate::protocol::query::{ErrorCode, QueryError, ARROW_STREAM_CONTENT_TYPE};
+    pub use crate::protocol::trace_archive::{
+        ArchivedTable, AutosaveManifest, AutosaveSegment, ClockAnchor, TraceArchive,
+        TraceImportSummary,
+    };
     pub use crate::protocol::version::ProtocolVersion;
 
     // --- Core Data Types ---
@@ -21,6 +28,7 @@ pub mod prelude {
     pub use crate::types::TimeSeries;
     pub use crate::types::Value;
     pub use crate::types::{DiscardStrategy, Series};
+    pub use crate::types::{DownsampleInfo, DownsampleMode};
 
     // --- Type Conversion ---
     pub use crate::types::{EleExt, FromEle, ToEle};
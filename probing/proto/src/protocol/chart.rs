@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{DataFrame, DownsampleInfo, DownsampleMode};
+
+/// Default target point count when a chart query omits `points`.
+pub const DEFAULT_CHART_POINTS: usize = 1000;
+/// Upper bound on `points`; a chart never needs more rows than pixels.
+pub const MAX_CHART_POINTS: usize = 10_000;
+
+/// `POST /apis/chart_query` body: either a raw `expr`, or `table` + `y`
+/// columns (plus an optional `[start, end]` range on `x`).
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ChartQueryRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    /// X axis column; defaults to `ts`, or the first column for `expr` queries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub y: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<usize>,
+    #[serde(default)]
+    pub mode: DownsampleMode,
+}
+
+impl ChartQueryRequest {
+    /// Query over `table`, plotting `y` against `ts`.
+    pub fn table(table: impl Into<String>, y: Vec<String>) -> Self {
+        Self {
+            table: Some(table.into()),
+            y,
+            ..Default::default()
+        }
+    }
+
+    /// Query over an arbitrary read-only `expr`.
+    pub fn expr(expr: impl Into<String>) -> Self {
+        Self {
+            expr: Some(expr.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_points(mut self, points: usize) -> Self {
+        self.points = Some(points);
+        self
+    }
+
+    /// Target point count after applying the default and the upper bound.
+    pub fn target_points(&self) -> usize {
+        self.points
+            .unwrap_or(DEFAULT_CHART_POINTS)
+            .clamp(3, MAX_CHART_POINTS)
+    }
+
+    /// X column name the server should downsample along, if known.
+    pub fn x_column(&self) -> Option<&str> {
+        match (&self.x, &self.expr) {
+            (Some(x), _) => Some(x.as_str()),
+            (None, Some(_)) => None,
+            (None, None) => Some("ts"),
+        }
+    }
+
+    /// SQL to run; table-form requests only accept plain identifiers.
+    pub fn to_sql(&self) -> Result<String, String> {
+        if let Some(expr) = self.expr.as_deref().map(str::trim) {
+            if expr.is_empty() {
+                return Err("expr must not be empty".into());
+            }
+            if self.table.is_some() {
+                return Err("specify either expr or table, not both".into());
+            }
+            return Ok(expr.to_string());
+        }
+
+        let table = self
+            .table
+            .as_deref()
+            .ok_or_else(|| "either expr or table is required".to_string())?;
+        if !is_ident(table, true) {
+            return Err(format!("invalid table name {table:?}"));
+        }
+        if self.y.is_empty() {
+            return Err("at least one y column is required".into());
+        }
+        let x = self.x.as_deref().unwrap_or("ts");
+        for col in std::iter::once(x).chain(self.y.iter().map(String::as_str)) {
+            if !is_ident(col, false) {
+                return Err(format!("invalid column name {col:?}"));
+            }
+        }
+
+        let mut sql = format!("SELECT {x}, {} FROM {table}", self.y.join(", "));
+        let mut conds = vec![];
+        if let Some(start) = self.start {
+            conds.push(format!("{x} >= {start}"));
+        }
+        if let Some(end) = self.end {
+            conds.push(format!("{x} <= {end}"));
+        }
+        if !conds.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conds.join(" AND "));
+        }
+        sql.push_str(&format!(" ORDER BY {x}"));
+        Ok(sql)
+    }
+}
+
+fn is_ident(s: &str, dotted: bool) -> bool {
+    !s.is_empty()
+        && !s.starts_with('.')
+        && !s.ends_with('.')
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || (dotted && b == b'.'))
+}
+
+/// `POST /apis/chart_query` response.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct ChartQueryResponse {
+    pub dataframe: DataFrame,
+    pub downsample: DownsampleInfo,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_form_builds_ranged_sql() {
+        let mut req = ChartQueryRequest::table("cpu.utilization", vec!["cpu_pct".into()]);
+        req.start = Some(10);
+        req.end = Some(20);
+        assert_eq!(
+            req.to_sql().unwrap(),
+            "SELECT ts, cpu_pct FROM cpu.utilization WHERE ts >= 10 AND ts <= 20 ORDER BY ts"
+        );
+        assert_eq!(req.x_column(), Some("ts"));
+        assert_eq!(req.target_points(), DEFAULT_CHART_POINTS);
+    }
+
+    #[test]
+    fn rejects_non_identifiers_and_ambiguous_requests() {
+        let req = ChartQueryRequest::table("t; drop table x", vec!["v".into()]);
+        assert!(req.to_sql().is_err());
+        let req = ChartQueryRequest::table("t", vec!["v - 1".into()]);
+        assert!(req.to_sql().is_err());
+        let req = ChartQueryRequest::table("t", vec![]);
+        assert!(req.to_sql().is_err());
+        let mut req = ChartQueryRequest::expr("select 1");
+        req.table = Some("t".into());
+        assert!(req.to_sql().is_err());
+        assert!(ChartQueryRequest::default().to_sql().is_err());
+    }
+
+    #[test]
+    fn expr_form_defaults_and_clamps() {
+        let req = ChartQueryRequest::expr(" select ts, v from t ").with_points(1_000_000);
+        assert_eq!(req.to_sql().unwrap(), "select ts, v from t");
+        assert_eq!(req.x_column(), None);
+        assert_eq!(req.target_points(), MAX_CHART_POINTS);
+
+        let json = r#"{"expr":"select 1","mode":"lttb","points":50}"#;
+        let req: ChartQueryRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.mode, DownsampleMode::Lttb);
+        assert_eq!(req.target_points(), 50);
+    }
+}
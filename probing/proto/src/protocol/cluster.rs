@@ -58,6 +58,42 @@ pub struct NodeListResponse {
     pub nodes: Vec<Node>,
 }
 
+/// One metrics poll of a cluster node (`GET /apis/nodes/metrics`).
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct NodeMetricsSample {
+    /// Poll time (µs since epoch).
+    pub timestamp: u64,
+    /// Process CPU from the node's `cpu.utilization`.
+    pub cpu_pct: Option<f64>,
+    pub rss_kb: Option<i64>,
+    /// Mean over the node's GPUs in the latest `gpu.utilization` sample.
+    pub gpu_util_pct: Option<f64>,
+}
+
+/// Heartbeat and polled metrics of one registered node.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct NodeMetrics {
+    pub host: String,
+    pub addr: String,
+    /// Timestamp of the node's last report (µs since epoch).
+    pub last_heartbeat: u64,
+    /// Successful polls, oldest first; the last one is the current value.
+    pub history: Vec<NodeMetricsSample>,
+    /// Why the last poll failed; `history` keeps the earlier samples.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// `GET /apis/nodes/metrics`
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct NodeMetricsResponse {
+    /// Poll interval; `0` when polling is disabled.
+    pub interval_secs: u64,
+    /// Heartbeat age after which a node is reported dead.
+    pub stale_after_secs: u64,
+    pub nodes: Vec<NodeMetrics>,
+}
+
 impl Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
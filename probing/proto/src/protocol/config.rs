@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// One config write, as streamed by `GET /apis/config/watch` (one JSON object
+/// per line).
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ConfigChange {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub key: String,
+    /// Previous value; `None` when the key was unset.
+    #[serde(default)]
+    pub old: Option<String>,
+    /// New value; `None` when the key was removed.
+    #[serde(default)]
+    pub new: Option<String>,
+    /// Who made the change: `token:<fingerprint> req:<request id>` for HTTP
+    /// writes, `None` for writes from inside the process.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl ConfigChange {
+    /// Whether `key` starts with `prefix`, ignoring a leading `probing.` on
+    /// either side.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        let strip = |s: &str| s.strip_prefix("probing.").unwrap_or(s).to_string();
+        self.key.starts_with(prefix) || strip(&self.key).starts_with(&strip(prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_ignores_probing_namespace() {
+        let change = ConfigChange {
+            key: "probing.trace.max_events".into(),
+            ..Default::default()
+        };
+        assert!(change.matches_prefix("trace."));
+        assert!(change.matches_prefix("probing.trace"));
+        assert!(!change.matches_prefix("torch."));
+    }
+}
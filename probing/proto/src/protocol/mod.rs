@@ -1,5 +1,9 @@
+pub mod chart;
 pub mod cluster;
+pub mod config;
 pub mod message;
+pub mod pprof;
 pub mod process;
 pub mod query;
+pub mod trace_archive;
 pub mod version;
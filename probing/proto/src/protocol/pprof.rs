@@ -0,0 +1,202 @@
+//! Encoding of folded CPU stacks as a pprof `profile.proto`
+//! (github.com/google/pprof/proto/profile.proto), readable by `go tool pprof`
+//! and Speedscope. Shared by `probing pprof serve` and the server's
+//! `/apis/pprof/profile.pb.gz` download.
+
+use std::collections::HashMap;
+
+/// `"a;b;c 12"` → (`["a","b","c"]`, 12); merges duplicate stacks.
+pub fn parse_folded(lines: &[String]) -> Vec<(Vec<&str>, i64)> {
+    let mut out: Vec<(Vec<&str>, i64)> = Vec::with_capacity(lines.len());
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for line in lines {
+        let Some((path, count)) = line.trim_end().rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse::<i64>() else {
+            continue;
+        };
+        match index.get(path) {
+            Some(&i) => out[i].1 += count,
+            None => {
+                index.insert(path, out.len());
+                out.push((path.split(';').collect(), count));
+            }
+        }
+    }
+    out
+}
+
+/// Split `"[py] step (train.py:42)"` into name, file and line.
+fn split_frame(frame: &str) -> (&str, &str, i64) {
+    if let Some(inner) = frame.strip_suffix(')') {
+        if let Some((name, loc)) = inner.rsplit_once(" (") {
+            if let Some((file, line)) = loc.rsplit_once(':') {
+                if let Ok(line) = line.parse::<i64>() {
+                    return (name, file, line);
+                }
+            }
+        }
+    }
+    (frame, "", 0)
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    index: HashMap<String, i64>,
+}
+
+impl StringTable {
+    fn new() -> Self {
+        let mut table = Self::default();
+        table.intern("");
+        table
+    }
+
+    fn intern(&mut self, s: &str) -> i64 {
+        if let Some(&i) = self.index.get(s) {
+            return i;
+        }
+        let i = self.strings.len() as i64;
+        self.strings.push(s.to_string());
+        self.index.insert(s.to_string(), i);
+        i
+    }
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn put_int(buf: &mut Vec<u8>, field: u32, v: i64) {
+    if v != 0 {
+        put_varint(buf, u64::from(field) << 3);
+        put_varint(buf, v as u64);
+    }
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    put_varint(buf, (u64::from(field) << 3) | 2);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_packed(buf: &mut Vec<u8>, field: u32, values: impl IntoIterator<Item = u64>) {
+    let mut packed = Vec::new();
+    for v in values {
+        put_varint(&mut packed, v);
+    }
+    put_bytes(buf, field, &packed);
+}
+
+fn value_type(strings: &mut StringTable, ty: &str, unit: &str) -> Vec<u8> {
+    let mut msg = Vec::new();
+    put_int(&mut msg, 1, strings.intern(ty));
+    put_int(&mut msg, 2, strings.intern(unit));
+    msg
+}
+
+/// Encode folded stacks (root first) as a CPU `Profile` with
+/// `samples/count` and `cpu/nanoseconds` values. `comments` (e.g. `pid: 42`)
+/// are shown by `go tool pprof -comments`.
+pub fn encode_cpu_profile(
+    stacks: &[(Vec<&str>, i64)],
+    sample_freq: u64,
+    time_nanos: i64,
+    duration_nanos: i64,
+    comments: &[String],
+) -> Vec<u8> {
+    let period = 1_000_000_000 / sample_freq.max(1) as i64;
+    let mut strings = StringTable::new();
+    let mut out = Vec::new();
+
+    let samples_type = value_type(&mut strings, "samples", "count");
+    let cpu_type = value_type(&mut strings, "cpu", "nanoseconds");
+    put_bytes(&mut out, 1, &samples_type);
+    put_bytes(&mut out, 1, &cpu_type);
+
+    // One Function per (name, file), one Location per (function, line).
+    let mut functions: HashMap<(&str, &str), u64> = HashMap::new();
+    let mut locations: HashMap<(u64, i64), u64> = HashMap::new();
+    let mut function_msgs = Vec::new();
+    let mut location_msgs = Vec::new();
+
+    for (stack, count) in stacks {
+        let mut ids = Vec::with_capacity(stack.len());
+        // pprof wants the leaf first.
+        for frame in stack.iter().rev() {
+            let (name, file, line) = split_frame(frame);
+            let next_fn = functions.len() as u64 + 1;
+            let function_id = *functions.entry((name, file)).or_insert_with(|| {
+                let mut msg = Vec::new();
+                put_int(&mut msg, 1, next_fn as i64);
+                let name_idx = strings.intern(name);
+                put_int(&mut msg, 2, name_idx);
+                put_int(&mut msg, 3, name_idx);
+                put_int(&mut msg, 4, strings.intern(file));
+                function_msgs.push(msg);
+                next_fn
+            });
+            let next_loc = locations.len() as u64 + 1;
+            let location_id = *locations.entry((function_id, line)).or_insert_with(|| {
+                let mut line_msg = Vec::new();
+                put_int(&mut line_msg, 1, function_id as i64);
+                put_int(&mut line_msg, 2, line);
+                let mut msg = Vec::new();
+                put_int(&mut msg, 1, next_loc as i64);
+                put_bytes(&mut msg, 4, &line_msg);
+                location_msgs.push(msg);
+                next_loc
+            });
+            ids.push(location_id);
+        }
+        let mut sample = Vec::new();
+        put_packed(&mut sample, 1, ids);
+        put_packed(
+            &mut sample,
+            2,
+            [*count as u64, (*count).saturating_mul(period) as u64],
+        );
+        put_bytes(&mut out, 2, &sample);
+    }
+
+    for msg in &location_msgs {
+        put_bytes(&mut out, 4, msg);
+    }
+    for msg in &function_msgs {
+        put_bytes(&mut out, 5, msg);
+    }
+    let period_type = value_type(&mut strings, "cpu", "nanoseconds");
+    let comment_ids: Vec<u64> = comments.iter().map(|c| strings.intern(c) as u64).collect();
+    for s in &strings.strings {
+        put_bytes(&mut out, 6, s.as_bytes());
+    }
+    put_int(&mut out, 9, time_nanos);
+    put_int(&mut out, 10, duration_nanos);
+    put_bytes(&mut out, 11, &period_type);
+    put_int(&mut out, 12, period);
+    if !comment_ids.is_empty() {
+        put_packed(&mut out, 13, comment_ids);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_frame_extracts_file_and_line() {
+        assert_eq!(
+            split_frame("[py] step (train.py:42)"),
+            ("[py] step", "train.py", 42)
+        );
+        assert_eq!(split_frame("thread-1 (main)"), ("thread-1 (main)", "", 0));
+        assert_eq!(split_frame("libc.so.6`read"), ("libc.so.6`read", "", 0));
+    }
+}
@@ -36,6 +36,26 @@ pub enum CallFrame {
     },
 }
 
+/// Stack of one thread in a per-thread callstack dump.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ThreadStack {
+    pub tid: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The Python main thread.
+    #[serde(default)]
+    pub main: bool,
+    /// Same order as the flat callstack response.
+    #[serde(default)]
+    pub frames: Vec<CallFrame>,
+    /// Why `frames` could not be captured; the thread is still listed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Kernel scheduler state, e.g. `S (sleeping) in futex_wait_queue`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
 impl Display for CallFrame {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
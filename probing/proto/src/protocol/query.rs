@@ -4,6 +4,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::types::{DataFrame, TimeSeries};
 
+/// `Accept` / `Content-Type` of a `/query` result sent as an Arrow IPC stream
+/// instead of a JSON [`Message`](crate::protocol::message::Message).
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
 #[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
 pub struct Options {
     pub limit: Option<usize>,
@@ -41,11 +45,14 @@ pub struct QueryError {
 pub enum ErrorCode {
     ParseError,
     ExecutionError,
+    /// Ran past `probing.query.timeout`.
     TimeoutError,
     ResourceExhausted,
     PermissionDenied,
     NotFound,
     Internal,
+    /// Stopped through `/query/cancel` before it finished.
+    Cancelled,
 }
 
 impl Display for QueryError {
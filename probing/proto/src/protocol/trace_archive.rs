@@ -0,0 +1,376 @@
+//! Versioned trace archive: spans, events and metrics tables captured from one
+//! process so they can be replayed in a fresh probing instance.
+//!
+//! Wire layout: `PRBTRACE` magic, little-endian `u16` version, JSON body.
+//!
+//! Autosave (`probing.trace.autosave`) writes a directory of zstd-compressed
+//! archives, each holding the rows recorded since the previous one, listed
+//! oldest first in an [`AutosaveManifest`]; [`TraceArchive::merge`] puts the
+//! segments back together.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{merge_dataframes, DataFrame, ProtoError};
+
+pub const TRACE_ARCHIVE_MAGIC: &[u8; 8] = b"PRBTRACE";
+/// Bump on any incompatible change to [`TraceArchive`].
+pub const TRACE_ARCHIVE_VERSION: u16 = 1;
+/// File listing the segments of an autosave directory.
+pub const AUTOSAVE_MANIFEST: &str = "manifest.json";
+/// Bump on any incompatible change to [`AutosaveManifest`].
+pub const AUTOSAVE_MANIFEST_VERSION: u16 = 1;
+/// Catalog imported archives are registered under unless one is given.
+pub const DEFAULT_REPLAY_NAMESPACE: &str = "replay";
+
+const HEADER_LEN: usize = TRACE_ARCHIVE_MAGIC.len() + 2;
+
+/// Wall-clock reading taken when the archive was written. Table timestamps
+/// keep their original units; `time_base` names them so a replay can re-anchor.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ClockAnchor {
+    pub wall_ns: i64,
+    pub time_base: String,
+}
+
+/// One table snapshot, addressed as `schema.table` in the source process.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct ArchivedTable {
+    pub table: String,
+    pub dataframe: DataFrame,
+    /// Columns whose type could not be carried and were left out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped_columns: Vec<String>,
+}
+
+impl ArchivedTable {
+    /// This table as an entry of an archive's table list (see
+    /// [`TraceArchive::encode_head`]), comma-led unless it is the `first`.
+    pub fn encode_entry(&self, first: bool) -> Result<Vec<u8>, ProtoError> {
+        let mut entry = if first { vec![] } else { vec![b','] };
+        serde_json::to_writer(&mut entry, self)
+            .map_err(|e| ProtoError::SerializationError(e.to_string()))?;
+        Ok(entry)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct TraceArchive {
+    pub clock: ClockAnchor,
+    /// Resource tags of the source process (host, pid, rank, ...).
+    #[serde(default)]
+    pub resource: BTreeMap<String, String>,
+    #[serde(default)]
+    pub tables: Vec<ArchivedTable>,
+}
+
+impl TraceArchive {
+    pub fn encode(&self) -> Result<Vec<u8>, ProtoError> {
+        let body =
+            serde_json::to_vec(self).map_err(|e| ProtoError::SerializationError(e.to_string()))?;
+        let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+        out.extend_from_slice(TRACE_ARCHIVE_MAGIC);
+        out.extend_from_slice(&TRACE_ARCHIVE_VERSION.to_le_bytes());
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+
+    /// Decode an archive, rejecting foreign data and other archive versions.
+    /// A table written as several entries (a streamed dump writes one per
+    /// record batch) comes back as one.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtoError> {
+        let version = archive_version(bytes)?;
+        if version != TRACE_ARCHIVE_VERSION {
+            return Err(ProtoError::VersionMismatch {
+                expected: TRACE_ARCHIVE_VERSION.to_string(),
+                got: version.to_string(),
+            });
+        }
+        let archive: Self = serde_json::from_slice(&bytes[HEADER_LEN..])
+            .map_err(|e| ProtoError::DeserializationError(e.to_string()))?;
+        Ok(archive.join_split_tables())
+    }
+
+    /// The bytes [`encode`](Self::encode) writes before the first table, for
+    /// writers that stream the tables themselves: each one as an
+    /// [`ArchivedTable::encode_entry`], then [`ENCODED_TAIL`](Self::ENCODED_TAIL).
+    pub fn encode_head(
+        clock: &ClockAnchor,
+        resource: &BTreeMap<String, String>,
+    ) -> Result<Vec<u8>, ProtoError> {
+        let mut head = Vec::with_capacity(HEADER_LEN + 256);
+        head.extend_from_slice(TRACE_ARCHIVE_MAGIC);
+        head.extend_from_slice(&TRACE_ARCHIVE_VERSION.to_le_bytes());
+        head.extend_from_slice(b"{\"clock\":");
+        serde_json::to_writer(&mut head, clock)
+            .map_err(|e| ProtoError::SerializationError(e.to_string()))?;
+        head.extend_from_slice(b",\"resource\":");
+        serde_json::to_writer(&mut head, resource)
+            .map_err(|e| ProtoError::SerializationError(e.to_string()))?;
+        head.extend_from_slice(b",\"tables\":[");
+        Ok(head)
+    }
+
+    /// Closes an archive opened with [`encode_head`](Self::encode_head).
+    pub const ENCODED_TAIL: &'static [u8] = b"]}";
+
+    fn join_split_tables(self) -> Self {
+        let distinct = {
+            let mut names = HashSet::new();
+            self.tables.iter().all(|t| names.insert(t.table.as_str()))
+        };
+        if distinct {
+            return self;
+        }
+        Self::merge(vec![self])
+    }
+
+    pub fn table(&self, name: &str) -> Option<&ArchivedTable> {
+        self.tables.iter().find(|t| t.table == name)
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.tables.iter().map(|t| t.dataframe.len()).sum()
+    }
+
+    /// Concatenate archives written one after another (autosave segments):
+    /// rows of same-named tables are appended in order with columns aligned
+    /// by name; clock and resource tags come from the last archive.
+    pub fn merge(parts: Vec<TraceArchive>) -> TraceArchive {
+        let mut merged = TraceArchive::default();
+        let mut frames: Vec<(String, Vec<DataFrame>, Vec<String>)> = vec![];
+        for part in parts {
+            merged.clock = part.clock;
+            merged.resource = part.resource;
+            for table in part.tables {
+                let pos = match frames.iter().position(|(name, ..)| *name == table.table) {
+                    Some(pos) => pos,
+                    None => {
+                        frames.push((table.table, vec![], vec![]));
+                        frames.len() - 1
+                    }
+                };
+                let (_, dfs, dropped) = &mut frames[pos];
+                dfs.push(table.dataframe);
+                for column in table.dropped_columns {
+                    if !dropped.contains(&column) {
+                        dropped.push(column);
+                    }
+                }
+            }
+        }
+        merged.tables = frames
+            .into_iter()
+            .map(|(table, dfs, dropped_columns)| ArchivedTable {
+                table,
+                dataframe: merge_dataframes(&dfs),
+                dropped_columns,
+            })
+            .collect();
+        merged
+    }
+}
+
+/// One autosave segment: a compressed [`TraceArchive`] in the same directory.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct AutosaveSegment {
+    pub file: String,
+    pub seq: u64,
+    pub rows: usize,
+    /// Size on disk.
+    pub bytes: u64,
+    /// Every row newer than the previous segment's `through_ns` and at or
+    /// before this one is in this segment or an earlier one.
+    pub through_ns: i64,
+    pub written_ns: i64,
+}
+
+/// `manifest.json` of an autosave directory; rewritten after each segment.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct AutosaveManifest {
+    pub version: u16,
+    /// Oldest first.
+    #[serde(default)]
+    pub segments: Vec<AutosaveSegment>,
+}
+
+impl AutosaveManifest {
+    pub fn new() -> Self {
+        AutosaveManifest {
+            version: AUTOSAVE_MANIFEST_VERSION,
+            segments: vec![],
+        }
+    }
+
+    pub fn next_seq(&self) -> u64 {
+        self.segments.last().map_or(1, |s| s.seq + 1)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.segments.iter().map(|s| s.bytes).sum()
+    }
+
+    /// Drop the oldest segments until at most `max_segments` remain and
+    /// their total size fits `max_bytes` (the newest is always kept);
+    /// returns the dropped entries so their files can be deleted.
+    pub fn rotate(&mut self, max_segments: usize, max_bytes: u64) -> Vec<AutosaveSegment> {
+        let mut excess = self.segments.len().saturating_sub(max_segments.max(1));
+        let mut total = self.total_bytes();
+        for segment in &self.segments[excess..] {
+            if total <= max_bytes || excess + 1 >= self.segments.len() {
+                break;
+            }
+            total -= segment.bytes;
+            excess += 1;
+        }
+        self.segments.drain(..excess).collect()
+    }
+}
+
+/// Archive version from the header, without decoding the body.
+pub fn archive_version(bytes: &[u8]) -> Result<u16, ProtoError> {
+    if bytes.len() < HEADER_LEN || &bytes[..TRACE_ARCHIVE_MAGIC.len()] != TRACE_ARCHIVE_MAGIC {
+        return Err(ProtoError::DeserializationError(
+            "not a probing trace archive".into(),
+        ));
+    }
+    let v = &bytes[TRACE_ARCHIVE_MAGIC.len()..HEADER_LEN];
+    Ok(u16::from_le_bytes([v[0], v[1]]))
+}
+
+/// Summary returned by `POST /apis/trace/import`.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct TraceImportSummary {
+    pub namespace: String,
+    pub version: u16,
+    /// Fully qualified names the tables are now queryable under.
+    pub tables: Vec<String>,
+    pub rows: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Seq;
+
+    fn sample() -> TraceArchive {
+        TraceArchive {
+            clock: ClockAnchor {
+                wall_ns: 1_700_000_000_000_000_000,
+                time_base: "unix_ns".into(),
+            },
+            resource: BTreeMap::from([("rank".into(), "3".into())]),
+            tables: vec![ArchivedTable {
+                table: "python.trace_event".into(),
+                dataframe: DataFrame::new(
+                    vec!["name".into(), "time".into()],
+                    vec![
+                        Seq::SeqText(vec!["step".into(), "fwd".into()]),
+                        Seq::SeqI64(vec![10, 20]),
+                    ],
+                ),
+                dropped_columns: vec!["attributes".into()],
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let archive = sample();
+        let bytes = archive.encode().unwrap();
+        assert_eq!(archive_version(&bytes).unwrap(), TRACE_ARCHIVE_VERSION);
+        let decoded = TraceArchive::decode(&bytes).unwrap();
+        assert_eq!(decoded, archive);
+        assert_eq!(decoded.row_count(), 2);
+        assert!(decoded.table("python.trace_event").is_some());
+    }
+
+    #[test]
+    fn streamed_encoding_matches_encode() {
+        let archive = sample();
+        let mut streamed = TraceArchive::encode_head(&archive.clock, &archive.resource).unwrap();
+        for (i, table) in archive.tables.iter().enumerate() {
+            streamed.extend(table.encode_entry(i == 0).unwrap());
+        }
+        streamed.extend_from_slice(TraceArchive::ENCODED_TAIL);
+        assert_eq!(streamed, archive.encode().unwrap());
+    }
+
+    #[test]
+    fn decode_joins_a_table_split_across_entries() {
+        let mut split = sample();
+        let mut rest = split.tables[0].clone();
+        rest.dataframe = DataFrame::new(
+            vec!["name".into(), "time".into()],
+            vec![Seq::SeqText(vec!["bwd".into()]), Seq::SeqI64(vec![30])],
+        );
+        split.tables.push(rest);
+        let decoded = TraceArchive::decode(&split.encode().unwrap()).unwrap();
+        assert_eq!(decoded.tables.len(), 1);
+        assert_eq!(decoded.row_count(), 3);
+        assert_eq!(decoded.tables[0].dropped_columns, ["attributes"]);
+    }
+
+    #[test]
+    fn merge_appends_segments_in_order() {
+        let first = sample();
+        let mut second = sample();
+        second.clock.wall_ns += 60;
+        second.tables[0].dataframe = DataFrame::new(
+            vec!["name".into(), "time".into()],
+            vec![Seq::SeqText(vec!["bwd".into()]), Seq::SeqI64(vec![30])],
+        );
+        second.tables.push(ArchivedTable {
+            table: "gpu.utilization".into(),
+            ..Default::default()
+        });
+        let merged = TraceArchive::merge(vec![first, second.clone()]);
+        assert_eq!(merged.clock, second.clock);
+        assert_eq!(merged.tables.len(), 2);
+        let table = merged.table("python.trace_event").unwrap();
+        assert_eq!(table.dropped_columns, ["attributes"]);
+        let df = &table.dataframe;
+        assert_eq!(df.len(), 3);
+        let time = df.col_index("time").unwrap();
+        let times: Vec<_> = df.iter().map(|row| row[time].clone()).collect();
+        assert_eq!(times, [10, 20, 30].map(crate::types::Ele::I64));
+    }
+
+    #[test]
+    fn rotation_drops_oldest_segments_by_count_then_size() {
+        let mut manifest = AutosaveManifest::new();
+        for seq in 1..=5 {
+            manifest.segments.push(AutosaveSegment {
+                file: format!("segment-{seq}"),
+                seq,
+                bytes: 100,
+                ..Default::default()
+            });
+        }
+        assert_eq!(manifest.next_seq(), 6);
+        let dropped = manifest.rotate(4, 1_000);
+        assert_eq!(dropped.iter().map(|s| s.seq).collect::<Vec<_>>(), [1]);
+        let dropped = manifest.rotate(10, 250);
+        assert_eq!(dropped.iter().map(|s| s.seq).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(manifest.total_bytes(), 200);
+        // The newest segment stays even when it alone is over the limit.
+        manifest.rotate(10, 1);
+        assert_eq!(manifest.segments.len(), 1);
+        assert_eq!(manifest.segments[0].seq, 5);
+        assert_eq!(manifest.next_seq(), 6);
+    }
+
+    #[test]
+    fn rejects_other_versions_and_foreign_bytes() {
+        let mut bytes = sample().encode().unwrap();
+        bytes[TRACE_ARCHIVE_MAGIC.len()..HEADER_LEN]
+            .copy_from_slice(&(TRACE_ARCHIVE_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            TraceArchive::decode(&bytes),
+            Err(ProtoError::VersionMismatch { .. })
+        ));
+        assert!(TraceArchive::decode(b"{\"tables\":[]}").is_err());
+        assert!(TraceArchive::decode(b"PRBTRACE").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Conversion between [`DataFrame`] and Arrow record batches.
+//!
+//! `/query` answers `Accept: application/vnd.apache.arrow.stream` with the
+//! engine's record batches as an Arrow IPC stream (see
+//! [`ARROW_STREAM_CONTENT_TYPE`](crate::protocol::query::ARROW_STREAM_CONTENT_TYPE)).
+//! These helpers let callers that still work with [`DataFrame`] read such a
+//! stream, or write one from a frame they already have.
+//!
+//! Each [`Seq`] maps to one Arrow type and back. `SeqTimestamp` is a UTC
+//! nanosecond `Timestamp`; Arrow timestamps of other units are scaled to
+//! nanoseconds on the way back. `SeqDateTime` is stored as `UInt64` with the
+//! [`DATETIME_METADATA`] field metadata so it round-trips, and an `Int64`
+//! column carrying [`TIMESTAMP_NS_METADATA`] (such as `trace_event.time`)
+//! reads as `SeqTimestamp` while staying an integer in SQL. A `Nil` column
+//! becomes a `Null` array as long as the other columns.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array, NullArray,
+    StringArray, TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+    TimestampSecondArray, UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+
+use super::{DataFrame, Seq};
+
+/// Field metadata marking a `UInt64` column as `SeqDateTime`.
+pub const DATETIME_METADATA: (&str, &str) = ("probing.type", "datetime");
+
+/// Field metadata marking an `Int64` column of nanoseconds since the epoch
+/// as `SeqTimestamp`.
+pub const TIMESTAMP_NS_METADATA: (&str, &str) = ("probing.type", "timestamp_ns");
+
+/// Arrow type of `SeqTimestamp` columns.
+pub fn timestamp_data_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+}
+
+/// Arrow type a [`Seq`] is stored as.
+pub fn seq_data_type(seq: &Seq) -> DataType {
+    match seq {
+        Seq::Nil => DataType::Null,
+        Seq::SeqBOOL(_) => DataType::Boolean,
+        Seq::SeqI32(_) => DataType::Int32,
+        Seq::SeqI64(_) => DataType::Int64,
+        Seq::SeqF32(_) => DataType::Float32,
+        Seq::SeqF64(_) => DataType::Float64,
+        Seq::SeqText(_) => DataType::Utf8,
+        Seq::SeqDateTime(_) => DataType::UInt64,
+        Seq::SeqTimestamp(_) => timestamp_data_type(),
+    }
+}
+
+/// Arrow array of `seq`; a `Nil` column holds `len` nulls.
+pub fn seq_to_array(seq: &Seq, len: usize) -> ArrayRef {
+    match seq {
+        Seq::Nil => Arc::new(NullArray::new(len)),
+        Seq::SeqBOOL(v) => Arc::new(BooleanArray::from(v.clone())),
+        Seq::SeqI32(v) => Arc::new(Int32Array::from(v.clone())),
+        Seq::SeqI64(v) => Arc::new(Int64Array::from(v.clone())),
+        Seq::SeqF32(v) => Arc::new(Float32Array::from(v.clone())),
+        Seq::SeqF64(v) => Arc::new(Float64Array::from(v.clone())),
+        Seq::SeqText(v) => Arc::new(StringArray::from(v.clone())),
+        Seq::SeqDateTime(v) => Arc::new(UInt64Array::from(v.clone())),
+        Seq::SeqTimestamp(v) => {
+            Arc::new(TimestampNanosecondArray::from(v.clone()).with_timezone("UTC"))
+        }
+    }
+}
+
+/// [`Seq`] of an Arrow array; timestamps become `SeqTimestamp` nanoseconds
+/// and types without a [`Seq`] become `Nil`. Null slots are not tracked.
+pub fn array_to_seq(array: &dyn Array) -> Seq {
+    let any = array.as_any();
+    if let Some(arr) = any.downcast_ref::<Int32Array>() {
+        Seq::SeqI32(arr.values().to_vec())
+    } else if let Some(arr) = any.downcast_ref::<Int64Array>() {
+        Seq::SeqI64(arr.values().to_vec())
+    } else if let Some(arr) = any.downcast_ref::<Float32Array>() {
+        Seq::SeqF32(arr.values().to_vec())
+    } else if let Some(arr) = any.downcast_ref::<Float64Array>() {
+        Seq::SeqF64(arr.values().to_vec())
+    } else if let Some(arr) = any.downcast_ref::<StringArray>() {
+        Seq::SeqText((0..arr.len()).map(|i| arr.value(i).to_string()).collect())
+    } else if let Some(arr) = any.downcast_ref::<BooleanArray>() {
+        Seq::SeqBOOL((0..arr.len()).map(|i| arr.value(i)).collect())
+    } else if let Some(arr) = any.downcast_ref::<TimestampNanosecondArray>() {
+        Seq::SeqTimestamp(arr.values().to_vec())
+    } else if let Some(arr) = any.downcast_ref::<TimestampMicrosecondArray>() {
+        Seq::SeqTimestamp(to_nanos(arr.values(), 1_000))
+    } else if let Some(arr) = any.downcast_ref::<TimestampMillisecondArray>() {
+        Seq::SeqTimestamp(to_nanos(arr.values(), 1_000_000))
+    } else if let Some(arr) = any.downcast_ref::<TimestampSecondArray>() {
+        Seq::SeqTimestamp(to_nanos(arr.values(), 1_000_000_000))
+    } else {
+        Seq::Nil
+    }
+}
+
+fn to_nanos(values: &[i64], per_unit: i64) -> Vec<i64> {
+    values.iter().map(|v| v.saturating_mul(per_unit)).collect()
+}
+
+/// Empty [`Seq`] for a column of `data_type` (zero-row results).
+pub fn empty_seq_for_data_type(data_type: &DataType) -> Seq {
+    match data_type {
+        DataType::Int32 => Seq::SeqI32(vec![]),
+        DataType::Int64 => Seq::SeqI64(vec![]),
+        DataType::Float32 => Seq::SeqF32(vec![]),
+        DataType::Float64 => Seq::SeqF64(vec![]),
+        DataType::Utf8 | DataType::LargeUtf8 => Seq::SeqText(vec![]),
+        DataType::Boolean => Seq::SeqBOOL(vec![]),
+        DataType::Timestamp(_, _) => Seq::SeqTimestamp(vec![]),
+        _ => Seq::Nil,
+    }
+}
+
+fn has_metadata(field: &Field, (key, value): (&str, &str)) -> bool {
+    field.metadata().get(key).map(String::as_str) == Some(value)
+}
+
+/// [`Seq`] of a column of a batch with `field`.
+fn column_to_seq(field: &Field, array: &dyn Array) -> Seq {
+    let any = array.as_any();
+    if has_metadata(field, DATETIME_METADATA) {
+        if let Some(arr) = any.downcast_ref::<UInt64Array>() {
+            return Seq::SeqDateTime(arr.values().to_vec());
+        }
+    }
+    if has_metadata(field, TIMESTAMP_NS_METADATA) {
+        if let Some(arr) = any.downcast_ref::<Int64Array>() {
+            return Seq::SeqTimestamp(arr.values().to_vec());
+        }
+    }
+    array_to_seq(array)
+}
+
+/// Empty [`Seq`] for a column with `field`.
+fn empty_column(field: &Field) -> Seq {
+    match field.data_type() {
+        DataType::UInt64 if has_metadata(field, DATETIME_METADATA) => Seq::SeqDateTime(vec![]),
+        DataType::Int64 if has_metadata(field, TIMESTAMP_NS_METADATA) => Seq::SeqTimestamp(vec![]),
+        data_type => empty_seq_for_data_type(data_type),
+    }
+}
+
+/// `field` marked as holding nanoseconds since the epoch, so result frames
+/// read it as `SeqTimestamp`.
+pub fn with_timestamp_ns_metadata(field: Field) -> Field {
+    let (key, value) = TIMESTAMP_NS_METADATA;
+    let mut metadata = field.metadata().clone();
+    metadata.insert(key.to_string(), value.to_string());
+    field.with_metadata(metadata)
+}
+
+/// [`Seq::append`]-style concatenation of the same column across batches.
+fn extend_seq(seq: &mut Seq, more: Seq) {
+    match (seq, more) {
+        (Seq::SeqBOOL(a), Seq::SeqBOOL(b)) => a.extend(b),
+        (Seq::SeqI32(a), Seq::SeqI32(b)) => a.extend(b),
+        (Seq::SeqI64(a), Seq::SeqI64(b)) => a.extend(b),
+        (Seq::SeqF32(a), Seq::SeqF32(b)) => a.extend(b),
+        (Seq::SeqF64(a), Seq::SeqF64(b)) => a.extend(b),
+        (Seq::SeqText(a), Seq::SeqText(b)) => a.extend(b),
+        (Seq::SeqDateTime(a), Seq::SeqDateTime(b)) => a.extend(b),
+        (Seq::SeqTimestamp(a), Seq::SeqTimestamp(b)) => a.extend(b),
+        _ => {}
+    }
+}
+
+impl DataFrame {
+    /// Arrow schema of this frame's columns.
+    pub fn arrow_schema(&self) -> SchemaRef {
+        let fields = self.names.iter().zip(&self.cols).map(|(name, col)| {
+            let field = Field::new(name, seq_data_type(col), matches!(col, Seq::Nil));
+            match col {
+                Seq::SeqDateTime(_) => {
+                    let (key, value) = DATETIME_METADATA;
+                    field.with_metadata(HashMap::from([(key.to_string(), value.to_string())]))
+                }
+                _ => field,
+            }
+        });
+        Arc::new(Schema::new(fields.collect::<Vec<_>>()))
+    }
+
+    /// This frame as one record batch; fails when columns differ in length.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let rows = self.row_count();
+        let columns = self.cols.iter().map(|col| seq_to_array(col, rows));
+        RecordBatch::try_new_with_options(
+            self.arrow_schema(),
+            columns.collect(),
+            &RecordBatchOptions::new().with_row_count(Some(rows)),
+        )
+    }
+
+    /// Frame holding `batches` of `schema` one after another.
+    pub fn from_record_batches(schema: &Schema, batches: &[RecordBatch]) -> DataFrame {
+        let names = schema.fields().iter().map(|f| f.name().clone()).collect();
+        let cols = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let mut parts = batches
+                    .iter()
+                    .map(|batch| column_to_seq(field, batch.column(i).as_ref()));
+                let Some(mut col) = parts.next() else {
+                    return empty_column(field);
+                };
+                parts.for_each(|part| extend_seq(&mut col, part));
+                col
+            })
+            .collect();
+        DataFrame::new(names, cols)
+    }
+}
+
+/// `batches` as an Arrow IPC stream with `schema`.
+pub fn write_ipc_stream(schema: &Schema, batches: &[RecordBatch]) -> Result<Vec<u8>, ArrowError> {
+    let mut writer = StreamWriter::try_new(Vec::new(), schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.into_inner()
+}
+
+/// Schema and record batches of an Arrow IPC stream.
+pub fn read_ipc_stream(bytes: &[u8]) -> Result<(SchemaRef, Vec<RecordBatch>), ArrowError> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    Ok((schema, batches))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Ele;
+
+    /// A three-row column of `ele`; `None` for element types no [`Seq`] holds.
+    fn column_of(ele: Ele) -> Option<Seq> {
+        let mut seq = Seq::Nil;
+        match ele {
+            // A `Nil` column stays empty whatever is appended.
+            Ele::Nil => return Some(Seq::Nil),
+            // Urls are rendered as text; no column stores them.
+            Ele::Url(_) => return None,
+            Ele::BOOL(_)
+            | Ele::I32(_)
+            | Ele::I64(_)
+            | Ele::F32(_)
+            | Ele::F64(_)
+            | Ele::Text(_)
+            | Ele::DataTime(_)
+            | Ele::DateTime(_) => {
+                for _ in 0..3 {
+                    seq.append(ele.clone()).unwrap();
+                }
+            }
+        }
+        Some(seq)
+    }
+
+    fn all_elements() -> Vec<Ele> {
+        vec![
+            Ele::Nil,
+            Ele::BOOL(true),
+            Ele::I32(-7),
+            Ele::I64(1 << 40),
+            Ele::F32(1.5),
+            Ele::F64(-2.25),
+            Ele::Text("héllo".to_string()),
+            Ele::Url("http://localhost".to_string()),
+            Ele::DataTime(1_700_000_000_000_000),
+            Ele::DateTime(1_700_000_000_123_456_789),
+        ]
+    }
+
+    fn frame() -> DataFrame {
+        let (names, cols): (Vec<_>, Vec<_>) = all_elements()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, ele)| Some((format!("c{i}"), column_of(ele)?)))
+            .unzip();
+        DataFrame::new(names, cols)
+    }
+
+    #[test]
+    fn every_column_type_round_trips_through_a_record_batch() {
+        let df = frame();
+        assert_eq!(df.cols.len(), all_elements().len() - 1);
+        let batch = df.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(
+            DataFrame::from_record_batches(&batch.schema(), &[batch]),
+            df
+        );
+    }
+
+    #[test]
+    fn every_column_type_round_trips_through_an_ipc_stream() {
+        let df = frame();
+        let batch = df.to_record_batch().unwrap();
+        let bytes = write_ipc_stream(&batch.schema(), &[batch.clone(), batch]).unwrap();
+
+        let (schema, batches) = read_ipc_stream(&bytes).unwrap();
+        assert_eq!(batches.len(), 2);
+        let back = DataFrame::from_record_batches(&schema, &batches);
+        assert_eq!(back.names, df.names);
+        assert_eq!(back.row_count(), 6);
+        for (col, orig) in back.cols.iter().zip(&df.cols) {
+            assert_eq!(col.get(5), orig.get(2));
+        }
+    }
+
+    #[test]
+    fn an_empty_stream_keeps_the_column_types() {
+        let df = frame();
+        let bytes = write_ipc_stream(&df.arrow_schema(), &[]).unwrap();
+        let (schema, batches) = read_ipc_stream(&bytes).unwrap();
+        let back = DataFrame::from_record_batches(&schema, &batches);
+        assert_eq!(back.names, df.names);
+        assert!(back.is_empty());
+        assert!(matches!(&back.cols[back.cols.len() - 2], Seq::SeqDateTime(v) if v.is_empty()));
+        assert!(matches!(back.cols.last(), Some(Seq::SeqTimestamp(v)) if v.is_empty()));
+    }
+
+    #[test]
+    fn timestamps_read_as_utc_nanoseconds() {
+        let micros = TimestampMicrosecondArray::from(vec![1_700_000_000_000_001]);
+        assert_eq!(
+            array_to_seq(&micros),
+            Seq::SeqTimestamp(vec![1_700_000_000_000_001_000])
+        );
+
+        let field = with_timestamp_ns_metadata(Field::new("time", DataType::Int64, false));
+        let schema = Arc::new(Schema::new(vec![field]));
+        let column: ArrayRef = Arc::new(Int64Array::from(vec![5, 6]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![column]).unwrap();
+        let df = DataFrame::from_record_batches(&schema, &[batch]);
+        assert_eq!(df.cols, vec![Seq::SeqTimestamp(vec![5, 6])]);
+        let df = DataFrame::from_record_batches(&schema, &[]);
+        assert_eq!(df.cols, vec![Seq::SeqTimestamp(vec![])]);
+    }
+}
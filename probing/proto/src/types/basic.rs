@@ -1,7 +1,7 @@
 use std::fmt::{Display, Formatter};
 use std::time::{Duration, SystemTime};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::types::error::ProtoError;
@@ -17,6 +17,7 @@ pub enum EleType {
     Text,
     Url,
     DataTime,
+    DateTime,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -30,6 +31,8 @@ pub enum Ele {
     Text(String),
     Url(String),
     DataTime(u64),
+    /// Nanoseconds since the Unix epoch, UTC.
+    DateTime(i64),
 }
 
 impl Display for Ele {
@@ -48,10 +51,16 @@ impl Display for Ele {
                     (SystemTime::UNIX_EPOCH + Duration::from_micros(*x)).into();
                 f.write_fmt(format_args!("{}", datetime.to_rfc3339()))
             }
+            Ele::DateTime(x) => f.write_str(&format_datetime(*x)),
         }
     }
 }
 
+/// RFC 3339 form (UTC) of a [`Ele::DateTime`] value.
+pub fn format_datetime(ns: i64) -> String {
+    DateTime::<Utc>::from_timestamp_nanos(ns).to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
 impl From<&str> for Ele {
     fn from(val: &str) -> Self {
         Ele::Text(val.to_string())
@@ -111,7 +120,7 @@ impl TryInto<i64> for Ele {
     fn try_into(self) -> Result<i64, ProtoError> {
         match self {
             Ele::I32(x) => Ok(x as i64),
-            Ele::I64(x) => Ok(x),
+            Ele::I64(x) | Ele::DateTime(x) => Ok(x),
             _ => Err(ProtoError::WrongElementType),
         }
     }
@@ -151,6 +160,8 @@ pub enum Seq {
     SeqF64(Vec<f64>),
     SeqText(Vec<String>),
     SeqDateTime(Vec<u64>),
+    /// [`Ele::DateTime`] values.
+    SeqTimestamp(Vec<i64>),
 }
 
 impl Seq {
@@ -163,6 +174,7 @@ impl Seq {
             Seq::SeqF64(vec) => vec.len(),
             Seq::SeqText(vec) => vec.len(),
             Seq::SeqDateTime(vec) => vec.len(),
+            Seq::SeqTimestamp(vec) => vec.len(),
             Seq::Nil => 0,
         }
     }
@@ -176,6 +188,7 @@ impl Seq {
             Seq::SeqF64(vec) => vec.len() * std::mem::size_of::<f64>(),
             Seq::SeqText(vec) => vec.iter().map(|x| x.len()).sum(),
             Seq::SeqDateTime(vec) => vec.len() * std::mem::size_of::<u64>(),
+            Seq::SeqTimestamp(vec) => vec.len() * std::mem::size_of::<i64>(),
             Seq::Nil => 0,
         }
     }
@@ -200,6 +213,7 @@ impl Seq {
                     (SystemTime::UNIX_EPOCH + Duration::from_micros(*x)).into();
                 datetime.to_rfc3339()
             }),
+            Seq::SeqTimestamp(vec) => vec.get(idx).map(|x| format_datetime(*x)),
             Seq::Nil => None,
         }
     }
@@ -213,6 +227,7 @@ impl Seq {
             Seq::SeqF64(vec) => vec.get(idx).map(|x| Ele::F64(*x)),
             Seq::SeqText(vec) => vec.get(idx).map(|x| Ele::Text(x.clone())),
             Seq::SeqDateTime(vec) => vec.get(idx).map(|x| Ele::DataTime(*x)),
+            Seq::SeqTimestamp(vec) => vec.get(idx).map(|x| Ele::DateTime(*x)),
             Seq::Nil => None,
         }
         .unwrap_or(Ele::Nil)
@@ -227,6 +242,7 @@ impl Seq {
             (Seq::Nil, Ele::F64(x)) => *self = Seq::SeqF64(vec![x]),
             (Seq::Nil, Ele::Text(x)) => *self = Seq::SeqText(vec![x]),
             (Seq::Nil, Ele::DataTime(x)) => *self = Seq::SeqDateTime(vec![x]),
+            (Seq::Nil, Ele::DateTime(x)) => *self = Seq::SeqTimestamp(vec![x]),
             (Seq::Nil, Ele::Nil) => {} // Nil值不改变Nil序列
             (Seq::SeqI32(vec), Ele::I32(x)) => vec.push(x),
             (Seq::SeqI64(vec), Ele::I64(x)) => vec.push(x),
@@ -234,6 +250,7 @@ impl Seq {
             (Seq::SeqF64(vec), Ele::F64(x)) => vec.push(x),
             (Seq::SeqText(vec), Ele::Text(x)) => vec.push(x),
             (Seq::SeqDateTime(vec), Ele::DataTime(x)) => vec.push(x),
+            (Seq::SeqTimestamp(vec), Ele::DateTime(x)) => vec.push(x),
             _ => return Err(ProtoError::WrongSequenceType),
         }
         Ok(())
@@ -309,4 +326,17 @@ mod tests {
         assert_eq!(seq.len(), 0);
         assert!(seq.is_empty());
     }
+
+    #[test]
+    fn datetime_cells_are_utc_nanoseconds() {
+        let mut seq = Seq::Nil;
+        assert!(seq.append(Ele::DateTime(1_700_000_000_123_456_789)).is_ok());
+        assert!(seq.append(Ele::I64(1)).is_err());
+        assert_eq!(seq, Seq::SeqTimestamp(vec![1_700_000_000_123_456_789]));
+        assert_eq!(
+            seq.get_str(0).as_deref(),
+            Some("2023-11-14T22:13:20.123456789Z")
+        );
+        assert_eq!(seq.get(0).to_string(), "2023-11-14T22:13:20.123456789Z");
+    }
 }
@@ -35,6 +35,7 @@ impl Compressable for Seq {
                 Ok((EleType::Text, data, cb))
             }
             Seq::SeqDateTime(vec) => Ok((EleType::DataTime, sample_compress(vec)?, None)),
+            Seq::SeqTimestamp(vec) => Ok((EleType::DateTime, sample_compress(vec)?, None)),
         }
     }
 }
@@ -113,6 +114,10 @@ impl Decompressable for Seq {
                 simple_decompress::<u64>(data)
                     .map_err(|e| ProtoError::CompressError(e.to_string()))?,
             ),
+            EleType::DateTime => Seq::SeqTimestamp(
+                simple_decompress::<i64>(data)
+                    .map_err(|e| ProtoError::CompressError(e.to_string()))?,
+            ),
         };
         Ok(seq)
     }
@@ -50,6 +50,7 @@ impl FromEle for String {
             Ele::F32(f) => Ok(f.to_string()),
             Ele::F64(f) => Ok(f.to_string()),
             Ele::DataTime(t) => Ok(t.to_string()),
+            Ele::DateTime(t) => Ok(super::basic::format_datetime(*t)),
             Ele::Nil => Ok("nil".to_string()),
         }
     }
@@ -75,7 +76,7 @@ impl FromEle for i64 {
     fn from_ele(ele: &Ele) -> Result<Self, ProtoError> {
         match ele {
             Ele::I32(x) => Ok(*x as i64),
-            Ele::I64(x) => Ok(*x),
+            Ele::I64(x) | Ele::DateTime(x) => Ok(*x),
             _ => Err(ProtoError::WrongElementType),
         }
     }
@@ -159,7 +160,7 @@ impl EleExt for Ele {
     fn as_i64(&self) -> Option<i64> {
         match self {
             Ele::I32(x) => Some(*x as i64),
-            Ele::I64(x) => Some(*x),
+            Ele::I64(x) | Ele::DateTime(x) => Some(*x),
             _ => None,
         }
     }
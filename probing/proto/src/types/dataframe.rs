@@ -70,6 +70,31 @@ impl DataFrame {
         self.row_count() == 0
     }
 
+    /// The given rows, in the given order; out-of-range indices are skipped.
+    pub fn take_rows(&self, rows: &[usize]) -> DataFrame {
+        fn pick<T: Clone>(v: &[T], rows: &[usize]) -> Vec<T> {
+            rows.iter().filter_map(|&r| v.get(r).cloned()).collect()
+        }
+        let cols = self
+            .cols
+            .iter()
+            .map(|col| match col {
+                Seq::Nil => Seq::Nil,
+                Seq::SeqBOOL(v) => Seq::SeqBOOL(pick(v, rows)),
+                Seq::SeqI32(v) => Seq::SeqI32(pick(v, rows)),
+                Seq::SeqI64(v) => Seq::SeqI64(pick(v, rows)),
+                Seq::SeqF32(v) => Seq::SeqF32(pick(v, rows)),
+                Seq::SeqF64(v) => Seq::SeqF64(pick(v, rows)),
+                Seq::SeqText(v) => Seq::SeqText(pick(v, rows)),
+                Seq::SeqDateTime(v) => Seq::SeqDateTime(pick(v, rows)),
+                Seq::SeqTimestamp(v) => Seq::SeqTimestamp(pick(v, rows)),
+            })
+            .collect();
+        let mut out = DataFrame::new(self.names.clone(), cols);
+        out.size = out.len() as u64;
+        out
+    }
+
     pub fn iter(&'_ self) -> DataFrameIterator<'_> {
         DataFrameIterator {
             df: self,
@@ -82,7 +107,7 @@ fn ele_f64(ele: &Ele) -> Option<f64> {
     match ele {
         Ele::F64(x) => Some(*x),
         Ele::F32(x) => Some(*x as f64),
-        Ele::I64(x) => Some(*x as f64),
+        Ele::I64(x) | Ele::DateTime(x) => Some(*x as f64),
         Ele::I32(x) => Some(*x as f64),
         Ele::Text(s) => s.parse().ok(),
         _ => None,
@@ -91,7 +116,7 @@ fn ele_f64(ele: &Ele) -> Option<f64> {
 
 fn ele_i64(ele: &Ele) -> Option<i64> {
     match ele {
-        Ele::I64(x) => Some(*x),
+        Ele::I64(x) | Ele::DateTime(x) => Some(*x),
         Ele::I32(x) => Some(*x as i64),
         Ele::F64(x) => Some(*x as i64),
         Ele::Text(s) => s.parse().ok(),
@@ -0,0 +1,312 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{DataFrame, Seq};
+
+/// Point-reduction strategy for chart queries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownsampleMode {
+    /// Keep the first/last row plus the min and max row of every y column in
+    /// each bucket, so spikes survive the reduction.
+    #[default]
+    MinMax,
+    /// Largest-Triangle-Three-Buckets on the first y column (visually faithful,
+    /// but a single-sample spike may be dropped).
+    Lttb,
+}
+
+/// What [`downsample`] did; `applied == false` means rows were passed through.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DownsampleInfo {
+    pub applied: bool,
+    pub mode: DownsampleMode,
+    pub input_rows: usize,
+    pub output_rows: usize,
+    pub target_points: usize,
+}
+
+/// Reduce `df` to roughly `target` rows ordered by column `x_col`.
+///
+/// Every numeric column other than `x_col` is treated as a y series. Frames
+/// already at or below `target` rows are returned unchanged (but sorted by x).
+pub fn downsample(
+    df: &DataFrame,
+    x_col: usize,
+    target: usize,
+    mode: DownsampleMode,
+) -> (DataFrame, DownsampleInfo) {
+    let rows = df.row_count();
+    let target = target.max(3);
+    let mut info = DownsampleInfo {
+        applied: false,
+        mode,
+        input_rows: rows,
+        output_rows: rows,
+        target_points: target,
+    };
+
+    let xs: Vec<f64> = match df.cols.get(x_col).and_then(numeric_values) {
+        Some(xs) => xs
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| x.unwrap_or(i as f64))
+            .collect(),
+        None => (0..rows).map(|i| i as f64).collect(),
+    };
+    let mut order: Vec<usize> = (0..rows).collect();
+    if xs.windows(2).any(|w| w[0] > w[1]) {
+        order.sort_by(|&a, &b| xs[a].total_cmp(&xs[b]));
+    }
+
+    if rows <= target {
+        let out = if order.iter().enumerate().all(|(i, &o)| i == o) {
+            df.clone()
+        } else {
+            df.take_rows(&order)
+        };
+        return (out, info);
+    }
+
+    let ys: Vec<Vec<Option<f64>>> = df
+        .cols
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != x_col)
+        .filter_map(|(_, col)| numeric_values(col))
+        .map(|values| order.iter().map(|&i| values[i]).collect())
+        .collect();
+    let xs: Vec<f64> = order.iter().map(|&i| xs[i]).collect();
+
+    let picked = match (mode, ys.first()) {
+        (_, None) => stride(rows, target),
+        (DownsampleMode::MinMax, Some(_)) => min_max(&ys, rows, target),
+        (DownsampleMode::Lttb, Some(y)) => lttb(&xs, y, target),
+    };
+    let rows_out: Vec<usize> = picked.into_iter().map(|p| order[p]).collect();
+
+    info.applied = true;
+    info.output_rows = rows_out.len();
+    (df.take_rows(&rows_out), info)
+}
+
+fn numeric_values(seq: &Seq) -> Option<Vec<Option<f64>>> {
+    let finite = |x: f64| x.is_finite().then_some(x);
+    Some(match seq {
+        Seq::SeqI32(v) => v.iter().map(|x| Some(*x as f64)).collect(),
+        Seq::SeqI64(v) => v.iter().map(|x| Some(*x as f64)).collect(),
+        Seq::SeqF32(v) => v.iter().map(|x| finite(*x as f64)).collect(),
+        Seq::SeqF64(v) => v.iter().map(|x| finite(*x)).collect(),
+        Seq::SeqDateTime(v) => v.iter().map(|x| Some(*x as f64)).collect(),
+        Seq::SeqTimestamp(v) => v.iter().map(|x| Some(*x as f64)).collect(),
+        _ => return None,
+    })
+}
+
+/// Positions (into the x-sorted order) kept by the min/max bucket pass.
+fn min_max(ys: &[Vec<Option<f64>>], rows: usize, target: usize) -> Vec<usize> {
+    let per_bucket = 2 * ys.len();
+    let buckets = ((target - 2) / per_bucket).max(1);
+    let interior = rows - 2;
+
+    let mut keep = BTreeSet::from([0, rows - 1]);
+    for b in 0..buckets {
+        let start = 1 + b * interior / buckets;
+        let end = 1 + (b + 1) * interior / buckets;
+        for y in ys {
+            let mut lo: Option<(usize, f64)> = None;
+            let mut hi: Option<(usize, f64)> = None;
+            for (p, v) in y.iter().enumerate().take(end).skip(start) {
+                let Some(v) = *v else { continue };
+                if lo.is_none_or(|(_, m)| v < m) {
+                    lo = Some((p, v));
+                }
+                if hi.is_none_or(|(_, m)| v > m) {
+                    hi = Some((p, v));
+                }
+            }
+            keep.extend(lo.map(|(p, _)| p));
+            keep.extend(hi.map(|(p, _)| p));
+        }
+    }
+    keep.into_iter().collect()
+}
+
+fn lttb(xs: &[f64], y: &[Option<f64>], target: usize) -> Vec<usize> {
+    let rows = xs.len();
+    let yv = |p: usize| y[p].unwrap_or(0.0);
+    let every = (rows - 2) as f64 / (target - 2) as f64;
+
+    let mut out = Vec::with_capacity(target);
+    let mut a = 0;
+    out.push(a);
+    for i in 0..target - 2 {
+        let start = (i as f64 * every) as usize + 1;
+        let end = ((i + 1) as f64 * every) as usize + 1;
+
+        // Average of the next bucket (the last row for the final bucket).
+        let next_end = (((i + 2) as f64 * every) as usize + 1).min(rows);
+        let span = (next_end - end) as f64;
+        let (sum_x, sum_y) =
+            (end..next_end).fold((0.0, 0.0), |(sx, sy), p| (sx + xs[p], sy + yv(p)));
+        let (avg_x, avg_y) = (sum_x / span, sum_y / span);
+
+        let mut best = start;
+        let mut best_area = f64::NEG_INFINITY;
+        for p in start..end {
+            let area =
+                ((xs[a] - avg_x) * (yv(p) - yv(a)) - (xs[a] - xs[p]) * (avg_y - yv(a))).abs();
+            if area > best_area {
+                best_area = area;
+                best = p;
+            }
+        }
+        out.push(best);
+        a = best;
+    }
+    out.push(rows - 1);
+    out
+}
+
+fn stride(rows: usize, target: usize) -> Vec<usize> {
+    let mut out: Vec<usize> = (0..target - 1)
+        .map(|i| i * (rows - 1) / (target - 1))
+        .collect();
+    out.push(rows - 1);
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smooth sine with isolated one-sample spikes every 997 rows.
+    fn spiky(rows: usize) -> DataFrame {
+        let ts: Vec<i64> = (0..rows as i64).map(|i| 1_000 + i * 10).collect();
+        let v: Vec<f64> = (0..rows)
+            .map(|i| match i % 997 {
+                500 => 100.0,
+                700 => -100.0,
+                _ => (i as f64 / 50.0).sin(),
+            })
+            .collect();
+        DataFrame::new(
+            vec!["ts".into(), "v".into()],
+            vec![Seq::SeqI64(ts), Seq::SeqF64(v)],
+        )
+    }
+
+    fn f64s(df: &DataFrame, col: usize) -> Vec<f64> {
+        match &df.cols[col] {
+            Seq::SeqF64(v) => v.clone(),
+            other => panic!("unexpected column {other:?}"),
+        }
+    }
+
+    fn i64s(df: &DataFrame, col: usize) -> Vec<i64> {
+        match &df.cols[col] {
+            Seq::SeqI64(v) => v.clone(),
+            other => panic!("unexpected column {other:?}"),
+        }
+    }
+
+    #[test]
+    fn min_max_keeps_every_spike_and_endpoints() {
+        let raw = spiky(20_000);
+        let (out, info) = downsample(&raw, 0, 200, DownsampleMode::MinMax);
+
+        assert!(info.applied);
+        assert_eq!(info.input_rows, 20_000);
+        assert_eq!(info.output_rows, out.len());
+        assert!(out.len() <= 200, "{} rows", out.len());
+
+        let raw_v = f64s(&raw, 1);
+        let v = f64s(&out, 1);
+        let spikes_raw = raw_v.iter().filter(|x| x.abs() == 100.0).count();
+        let spikes_out = v.iter().filter(|x| x.abs() == 100.0).count();
+        assert_eq!(spikes_out, spikes_raw);
+
+        let ts = i64s(&out, 0);
+        let raw_ts = i64s(&raw, 0);
+        assert_eq!(ts.first(), raw_ts.first());
+        assert_eq!(ts.last(), raw_ts.last());
+        assert!(ts.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn min_max_preserves_per_bucket_extrema_of_each_series() {
+        let rows = 5_000;
+        let ts: Vec<i64> = (0..rows).collect();
+        let a: Vec<f64> = (0..rows).map(|i| ((i * 7919) % 1000) as f64).collect();
+        let b: Vec<i32> = (0..rows).map(|i| -(((i * 104_729) % 333) as i32)).collect();
+        let raw = DataFrame::new(
+            vec!["ts".into(), "a".into(), "b".into()],
+            vec![
+                Seq::SeqI64(ts),
+                Seq::SeqF64(a.clone()),
+                Seq::SeqI32(b.clone()),
+            ],
+        );
+        let (out, info) = downsample(&raw, 0, 100, DownsampleMode::MinMax);
+        assert!(out.len() <= 100);
+
+        let out_ts = i64s(&out, 0);
+        let out_a = f64s(&out, 1);
+        let Seq::SeqI32(out_b) = &out.cols[2] else {
+            panic!("b column type changed");
+        };
+        assert_eq!(out_a.iter().cloned().fold(f64::MIN, f64::max), 999.0);
+        assert_eq!(out_b.iter().min(), b.iter().min());
+        assert_eq!(out_b.iter().max(), b.iter().max());
+
+        // Every kept row is an original row.
+        for (i, t) in out_ts.iter().enumerate() {
+            assert_eq!(out_a[i], a[*t as usize]);
+            assert_eq!(out_b[i], b[*t as usize]);
+        }
+        assert_eq!(info.output_rows, out_ts.len());
+    }
+
+    #[test]
+    fn lttb_hits_target_and_keeps_endpoints() {
+        let raw = spiky(10_000);
+        let (out, info) = downsample(&raw, 0, 300, DownsampleMode::Lttb);
+        assert!(info.applied);
+        assert_eq!(out.len(), 300);
+        let ts = i64s(&out, 0);
+        assert_eq!(ts[0], 1_000);
+        assert_eq!(*ts.last().unwrap(), 1_000 + 9_999 * 10);
+        assert!(ts.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn small_frames_pass_through_sorted() {
+        let raw = DataFrame::new(
+            vec!["ts".into(), "v".into()],
+            vec![Seq::SeqI64(vec![3, 1, 2]), Seq::SeqF64(vec![0.3, 0.1, 0.2])],
+        );
+        let (out, info) = downsample(&raw, 0, 100, DownsampleMode::MinMax);
+        assert!(!info.applied);
+        assert_eq!(info.output_rows, 3);
+        assert_eq!(i64s(&out, 0), vec![1, 2, 3]);
+        assert_eq!(f64s(&out, 1), vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn unsorted_input_is_reduced_in_x_order() {
+        let mut raw = spiky(4_000);
+        for col in &mut raw.cols {
+            match col {
+                Seq::SeqI64(v) => v.reverse(),
+                Seq::SeqF64(v) => v.reverse(),
+                _ => {}
+            }
+        }
+        let (out, _) = downsample(&raw, 0, 50, DownsampleMode::MinMax);
+        let ts = i64s(&out, 0);
+        assert!(ts.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ts[0], 1_000);
+    }
+}
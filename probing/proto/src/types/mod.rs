@@ -1,7 +1,10 @@
+#[cfg(feature = "arrow")]
+pub mod arrow_convert;
 pub mod basic;
 mod compress;
 pub mod convert;
 mod dataframe;
+mod downsample;
 mod error;
 mod merge;
 pub mod series;
@@ -16,6 +19,7 @@ pub use compress::Compressable;
 pub use compress::Decompressable;
 pub use convert::{EleExt, FromEle, ToEle};
 pub use dataframe::DataFrame;
+pub use downsample::{downsample, DownsampleInfo, DownsampleMode};
 pub use error::ProtoError;
 pub use merge::{append_dataframe, merge_dataframes};
 pub use series::{DiscardStrategy, Series};
@@ -1,5 +1,5 @@
 use std::env;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use axum::body::Body;
 use axum::http::{header, HeaderMap, StatusCode, Uri};
@@ -27,6 +27,36 @@ fn assets_root() -> Option<String> {
         .filter(|root| Path::new(root).join("index.html").is_file())
 }
 
+/// URL prefix of the bundled Perfetto UI; the trace viewer tries it before
+/// `ui.perfetto.dev`.
+pub const PERFETTO_UI_PREFIX: &str = "/perfetto/";
+
+/// A Perfetto UI build: `PROBING_PERFETTO_UI_DIR`, else `perfetto/` under the
+/// assets root. `None` unless it holds an `index.html`.
+fn perfetto_ui_root() -> Option<PathBuf> {
+    env::var("PROBING_PERFETTO_UI_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| assets_root().map(|root| Path::new(&root).join("perfetto")))
+        .filter(|root| root.join("index.html").is_file())
+}
+
+/// File under the Perfetto UI root for a request path; directories map to
+/// their `index.html` and anything leaving the root is refused.
+fn perfetto_ui_key(path: &str) -> Option<String> {
+    let key = normalize_asset_path(path.strip_prefix(PERFETTO_UI_PREFIX)?);
+    let key = if key.is_empty() || key.ends_with('/') {
+        format!("{key}index.html")
+    } else {
+        key
+    };
+    Path::new(&key)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then_some(key)
+}
+
 /// Normalize request paths such as `/./assets/foo.js` → `assets/foo.js`.
 fn normalize_asset_path(path: &str) -> String {
     let mut p = path.trim_start_matches('/').to_string();
@@ -124,6 +154,8 @@ fn get_content_type(path: &str) -> &'static str {
         p if p.ends_with(".jpg") || p.ends_with(".jpeg") => "image/jpeg",
         p if p.ends_with(".gif") => "image/gif",
         p if p.ends_with(".ico") => "image/x-icon",
+        p if p.ends_with(".woff2") => "font/woff2",
+        p if p.ends_with(".ttf") => "font/ttf",
         _ => "application/octet-stream",
     }
 }
@@ -227,11 +259,30 @@ pub async fn static_files(uri: Uri, headers: HeaderMap) -> Result<Response, Stat
         .unwrap_or_else(|_| Response::new(Body::empty())))
 }
 
+/// Handler for `/perfetto/...`: the bundled Perfetto UI, so traces open
+/// without reaching `ui.perfetto.dev`. `404` when no build is installed.
+pub async fn perfetto_ui(uri: Uri) -> Response {
+    let (Some(root), Some(key)) = (perfetto_ui_root(), perfetto_ui_key(uri.path())) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match tokio::fs::read(root.join(&key)).await {
+        Ok(data) => (
+            [
+                (header::CONTENT_TYPE, get_content_type(&key)),
+                (header::CACHE_CONTROL, cache_control(&key)),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         accepts_brotli, assets_root, cache_control, get, get_content_type, is_content_hashed,
-        normalize_asset_path,
+        normalize_asset_path, perfetto_ui_key,
     };
 
     #[test]
@@ -243,6 +294,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn perfetto_ui_paths_stay_under_its_root() {
+        assert_eq!(perfetto_ui_key("/perfetto/").as_deref(), Some("index.html"));
+        assert_eq!(
+            perfetto_ui_key("/perfetto/v50.0/frontend_bundle.js").as_deref(),
+            Some("v50.0/frontend_bundle.js")
+        );
+        assert_eq!(
+            perfetto_ui_key("/perfetto/v50.0/").as_deref(),
+            Some("v50.0/index.html")
+        );
+        assert_eq!(perfetto_ui_key("/perfetto/../index.html"), None);
+        assert_eq!(perfetto_ui_key("/perfetto/a/../../etc/passwd"), None);
+        assert_eq!(perfetto_ui_key("/assets/web.js"), None);
+    }
+
     #[test]
     fn accepts_brotli_encoding() {
         assert!(accepts_brotli("br"));
@@ -8,6 +8,9 @@ use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use once_cell::sync::Lazy;
 use probing_core::config;
 use std::env;
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::OnceCell;
 
 // Auth token environment variable name
 pub const AUTH_TOKEN_ENV: &str = "PROBING_AUTH_TOKEN";
@@ -31,14 +34,14 @@ pub async fn bootstrap_auth_from_env() {
     if token.is_empty() {
         return;
     }
-    if let Err(err) = config::write(AUTH_TOKEN_CONFIG_KEY, token).await {
+    if let Err(err) = config::write_transient(AUTH_TOKEN_CONFIG_KEY, token).await {
         log::error!("failed to bootstrap auth token from {AUTH_TOKEN_ENV}: {err}");
     }
 }
 
 /// Persist auth token to the config store (used by SET and extension options).
 pub async fn persist_auth_token(token: &str) -> Result<(), probing_core::core::EngineError> {
-    config::write(AUTH_TOKEN_CONFIG_KEY, token).await
+    config::write_transient(AUTH_TOKEN_CONFIG_KEY, token).await
 }
 
 /// Get the auth token from the request
@@ -82,6 +85,16 @@ pub fn get_token_from_request(headers: &HeaderMap) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Short, stable identifier of `token` (first 8 hex digits of its SHA-256),
+/// safe to log or show in place of the token.
+pub fn token_fingerprint(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(token.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 /// Create a response that prompts the browser to show a login dialog
 fn unauthorized_response() -> Response {
     let realm = format!("Basic realm=\"{}\"", AUTH_REALM.as_str());
@@ -103,12 +116,60 @@ fn unauthorized_response() -> Response {
         .into_response()
 }
 
-/// Authentication middleware
-pub async fn auth_middleware(request: Request, next: Next) -> Response {
-    // Get the configured token
-    let configured_token = config::get_str(AUTH_TOKEN_CONFIG_KEY)
+/// `server.auth_token` as last seen, with the subscription that keeps it
+/// current: requests apply pending changes instead of reading the store.
+struct TokenCache {
+    token: String,
+    changes: config::Subscription,
+}
+
+static TOKEN_CACHE: OnceCell<Mutex<TokenCache>> = OnceCell::const_new();
+
+impl TokenCache {
+    /// Apply pending changes; `false` when some were missed.
+    fn catch_up(&mut self) -> bool {
+        loop {
+            match self.changes.try_recv() {
+                Ok(change) if change.key == AUTH_TOKEN_CONFIG_KEY => {
+                    self.token = change.new.unwrap_or_default();
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Lagged(_)) => return false,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return true,
+            }
+        }
+    }
+}
+
+/// The configured token, empty when none is.
+async fn configured_token() -> String {
+    let cache = TOKEN_CACHE
+        .get_or_init(|| async {
+            // Subscribe first so a change racing the read is not lost.
+            let changes = config::subscribe(AUTH_TOKEN_CONFIG_KEY);
+            let token = config::get_str(AUTH_TOKEN_CONFIG_KEY)
+                .await
+                .unwrap_or_default();
+            Mutex::new(TokenCache { token, changes })
+        })
+        .await;
+    {
+        let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if cache.catch_up() {
+            return cache.token.clone();
+        }
+    }
+    // Fell behind: read the store once and go on from there.
+    let token = config::get_str(AUTH_TOKEN_CONFIG_KEY)
         .await
         .unwrap_or_default();
+    cache.lock().unwrap_or_else(PoisonError::into_inner).token = token.clone();
+    token
+}
+
+/// Authentication middleware
+pub async fn auth_middleware(request: Request, next: Next) -> Response {
+    let configured_token = configured_token().await;
     log::debug!("Auth token configured: {}", !configured_token.is_empty());
 
     if !configured_token.is_empty() {
@@ -124,12 +185,35 @@ pub async fn auth_middleware(request: Request, next: Next) -> Response {
     next.run(request).await
 }
 
+/// Gate for admin-only endpoints (e.g. trace import), which stay closed even on
+/// transports without the auth middleware: a token must be configured and the
+/// request must present it. `FORBIDDEN` when none is configured.
+pub async fn require_admin(headers: &HeaderMap) -> Result<(), StatusCode> {
+    let configured_token = configured_token().await;
+    check_admin(
+        &configured_token,
+        get_token_from_request(headers).as_deref(),
+    )
+}
+
+fn check_admin(configured: &str, provided: Option<&str>) -> Result<(), StatusCode> {
+    if configured.is_empty() {
+        Err(StatusCode::FORBIDDEN)
+    } else if provided != Some(configured) {
+        Err(StatusCode::UNAUTHORIZED)
+    } else {
+        Ok(())
+    }
+}
+
 /// Check if a path is public (doesn't require authentication)
 pub fn is_public_path(path: &str) -> bool {
     // Liveness/readiness for load balancers and K8s probes (remote server uses auth middleware).
     path == "/health"
         || path == "/ready"
+        || path == "/healthz"
         || path.starts_with("/static/")
+        || path.starts_with(crate::asset::PERFETTO_UI_PREFIX)
         || path == "/"
         || path == "/index.html"
         || path.starts_with("/favicon")
@@ -154,4 +238,26 @@ mod tests {
     fn auth_token_config_key_matches_middleware() {
         assert_eq!(AUTH_TOKEN_CONFIG_KEY, "server.auth_token");
     }
+
+    #[tokio::test]
+    async fn token_cache_follows_config_changes() {
+        config::set(AUTH_TOKEN_CONFIG_KEY, "first").await;
+        assert_eq!(configured_token().await, "first");
+        config::set(AUTH_TOKEN_CONFIG_KEY, "second").await;
+        assert_eq!(configured_token().await, "second");
+        config::remove(AUTH_TOKEN_CONFIG_KEY).await;
+        assert_eq!(configured_token().await, "");
+    }
+
+    #[test]
+    fn admin_requires_configured_and_matching_token() {
+        assert_eq!(check_admin("", None), Err(StatusCode::FORBIDDEN));
+        assert_eq!(check_admin("", Some("")), Err(StatusCode::FORBIDDEN));
+        assert_eq!(check_admin("s3cret", None), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(
+            check_admin("s3cret", Some("guess")),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(check_admin("s3cret", Some("s3cret")), Ok(()));
+    }
 }
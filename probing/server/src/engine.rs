@@ -11,11 +11,14 @@ use probing_python::extensions as py;
 
 use probing_core::config;
 
+use crate::server::config::is_admin_only_config_key;
 use crate::server::error::{ApiError, ApiResult};
 
 use probing_core::core::federation::{reset_fanout_stats, take_fanout_stats};
 use probing_core::core::UnifiedMemtableProbeDataSource;
+use probing_core::core::{query_cancel, EngineError};
 pub use probing_core::ENGINE;
+use probing_proto::types::arrow_convert;
 use probing_python::extensions::python::PythonProbeDataSource;
 
 /// Composition root: wires L2 collectors/extensions into the engine.
@@ -31,11 +34,14 @@ pub async fn initialize_engine() -> Result<()> {
         .with_extension(py::PprofProbeExtension::default())
         .with_extension(py::TorchProbeExtension::default())
         .with_extension(se::ServerProbeExtension::default())
+        .with_extension(se::LogProbeExtension::default())
         .with_extension(py::PythonExt::default())
         .with_data_source(PythonProbeDataSource::create("python"))
         .with_extension(crate::memtable_ext::MemTableProbeExtension::default())
         .with_data_source(Arc::new(UnifiedMemtableProbeDataSource))
-        .with_extension(cc::CpuProbeExtension::default());
+        .with_extension(cc::CpuProbeExtension::default())
+        .with_extension(probing_core::trace::TraceProbeExtension::default())
+        .with_extension(probing_core::core::query_cancel::QueryProbeExtension::default());
 
     #[cfg(feature = "gpu")]
     let builder = builder
@@ -56,6 +62,9 @@ pub async fn initialize_engine() -> Result<()> {
     crate::memtable_ext::start_cold_compaction_from_env();
     if result.is_ok() {
         cc::start_cpu_sampling_from_env();
+        crate::server::trace_retention::start_trace_retention();
+        crate::server::trace_autosave::start_trace_autosave();
+        crate::server::cluster_metrics::start_cluster_metrics();
         #[cfg(feature = "gpu")]
         gpu::start_gpu_sampling_from_env();
         crate::engine_lifecycle::mark_engine_ready();
@@ -88,7 +97,29 @@ fn parse_set_assignment(stmt: &str) -> Option<(&str, &str)> {
     Some((key, value))
 }
 
-fn is_set_expr(expr: &str) -> bool {
+/// Key of a `SET key = value` / `SET key TO value` statement.
+fn set_key(stmt: &str) -> Option<&str> {
+    let s = stmt.trim();
+    if s.len() < 3 || !s.as_bytes()[..3].eq_ignore_ascii_case(b"set") {
+        return None;
+    }
+    let rest = s[3..].trim_start();
+    let end = rest
+        .find(|c: char| c == '=' || c.is_whitespace())
+        .unwrap_or(rest.len());
+    Some(&rest[..end]).filter(|key| !key.is_empty())
+}
+
+/// Admin-only keys (see [`is_admin_only_config_key`]) set by `expr`.
+pub fn admin_only_set_keys(expr: &str) -> Vec<String> {
+    expr.split(';')
+        .filter_map(set_key)
+        .filter(|key| is_admin_only_config_key(key))
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) fn is_set_expr(expr: &str) -> bool {
     expr.split(';').any(|part| {
         let p = part.trim();
         p.len() >= 3 && p.as_bytes()[..3].eq_ignore_ascii_case(b"set")
@@ -131,6 +162,9 @@ pub async fn handle_query(request: Query) -> Result<QueryDataFormat> {
                     .map_err(Into::into)
             };
             outcome.with_context(|| format!("Failed SET query '{q}'"))?;
+            if set_key(q).is_some_and(is_admin_only_config_key) {
+                log::info!("config audit: {q}");
+            }
             log::debug!("Successfully executed SET statement: {q}");
         }
         return Ok(QueryDataFormat::Nil);
@@ -143,16 +177,45 @@ pub async fn handle_query(request: Query) -> Result<QueryDataFormat> {
         Ok(Some(dataframe)) => Ok(QueryDataFormat::DataFrame(dataframe)),
         Ok(None) => Ok(QueryDataFormat::Nil),
         Err(e) => {
-            if is_missing_table_error(&e) {
-                log::debug!("Optional table missing for SELECT '{expr}': {e}");
-            } else {
-                log::error!("Error executing SELECT query '{expr}': {e}");
-            }
+            log_select_error(&expr, &e);
+            Err(e.into())
+        }
+    }
+}
+
+/// [`handle_query`] answering a SELECT with an Arrow IPC stream of the
+/// engine's record batches; SET statements and errors keep the JSON payload.
+async fn handle_query_arrow(request: Query) -> Result<QueryReply> {
+    if crate::engine_lifecycle::engine_not_ready_message().is_some() || is_set_expr(&request.expr) {
+        return handle_query(request).await.map(QueryReply::Data);
+    }
+    let expr = request.expr;
+    reset_fanout_stats();
+    let engine = ENGINE.read().await;
+    log::debug!("Executing SELECT query as Arrow: {expr}");
+    match engine.async_query_batches(&expr).await {
+        Ok((schema, batches)) => Ok(QueryReply::Arrow(arrow_convert::write_ipc_stream(
+            &schema, &batches,
+        )?)),
+        Err(e) => {
+            log_select_error(&expr, &e);
             Err(e.into())
         }
     }
 }
 
+fn log_select_error(expr: &str, e: &probing_core::core::DataFusionError) {
+    if EngineError::is_cancellation(e) {
+        log::info!("SELECT query cancelled: {expr}");
+    } else if let Some(timeout) = EngineError::stop_reason(e) {
+        log::warn!("SELECT query stopped: {timeout}: {expr}");
+    } else if is_missing_table_error(e) {
+        log::debug!("Optional table missing for SELECT '{expr}': {e}");
+    } else {
+        log::error!("Error executing SELECT query '{expr}': {e}");
+    }
+}
+
 /// Extension tables (NCCL profiler, optional GPU, etc.) may be absent on single-process jobs.
 fn is_missing_table_error(err: &impl std::fmt::Display) -> bool {
     let msg = err.to_string().to_ascii_lowercase();
@@ -179,14 +242,92 @@ fn query_response_partial(stats: &probing_core::core::federation::FanoutStats) -
     !stats.nodes_failed.is_empty() || stats.peer_batches_dropped > 0
 }
 
+/// Response header carrying the id a `/query` ran under.
+pub const QUERY_ID_HEADER: &str = "x-probing-query-id";
+
+/// How `/query` sends a result, from the request's `Accept` header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultFormat {
+    /// A JSON [`Message`] with a [`QueryDataFormat`] payload.
+    #[default]
+    Json,
+    /// An Arrow IPC stream ([`ARROW_STREAM_CONTENT_TYPE`]) for SELECT results.
+    Arrow,
+}
+
+impl ResultFormat {
+    /// Arrow when the client names it with a non-zero q-value no lower than
+    /// JSON's (`application/json`, else `application/*` or `*/*`); `q=0`
+    /// refuses it.
+    pub fn from_accept(headers: &axum::http::HeaderMap) -> Self {
+        let mut arrow_q = 0.0f32;
+        let mut json_q = None::<f32>;
+        let mut wildcard_q = 0.0f32;
+        let ranges = headers
+            .get_all(axum::http::header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+        for range in ranges {
+            let mut parts = range.split(';');
+            let media = parts.next().unwrap_or_default().trim();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0)
+                .clamp(0.0, 1.0);
+            if media.eq_ignore_ascii_case(ARROW_STREAM_CONTENT_TYPE) {
+                arrow_q = arrow_q.max(q);
+            } else if media.eq_ignore_ascii_case("application/json") {
+                json_q = Some(json_q.unwrap_or(0.0).max(q));
+            } else if media == "*/*" || media.eq_ignore_ascii_case("application/*") {
+                wildcard_q = wildcard_q.max(q);
+            }
+        }
+        if arrow_q > 0.0 && arrow_q >= json_q.unwrap_or(wildcard_q) {
+            ResultFormat::Arrow
+        } else {
+            ResultFormat::Json
+        }
+    }
+}
+
+/// What a query produced before it is serialized.
+enum QueryReply {
+    Data(QueryDataFormat),
+    Arrow(Vec<u8>),
+}
+
 /// Serialized `/query` body plus whether federated fan-out was partial.
 pub struct QueryHttpEnvelope {
-    pub body: String,
+    pub body: Vec<u8>,
+    /// `application/json`, or [`ARROW_STREAM_CONTENT_TYPE`] for an Arrow result.
+    pub content_type: &'static str,
     pub partial: bool,
+    /// Id the query was registered under for `/query/cancel`.
+    pub query_id: String,
+}
+
+/// Id for a query: the caller's (`/query?id=`), so it can cancel a query
+/// still running, or a fresh one.
+fn accept_query_id(id: Option<&str>) -> ApiResult<String> {
+    match id {
+        Some(id) if probing_logging::accept_or_new(Some(id)) != id => Err(ApiError::bad_request(
+            format!("invalid query id '{id}': use up to 64 of [A-Za-z0-9._-]"),
+        )),
+        Some(id) => Ok(id.to_string()),
+        None => Ok(probing_logging::new_request_id()),
+    }
 }
 
 // 处理Web API查询请求
-pub async fn query(req: String) -> ApiResult<QueryHttpEnvelope> {
+pub async fn query(
+    req: String,
+    headers: &axum::http::HeaderMap,
+    query_id: Option<&str>,
+    format: ResultFormat,
+) -> ApiResult<QueryHttpEnvelope> {
+    let query_id = accept_query_id(query_id)?;
     let request = serde_json::from_str::<Message<Query>>(&req);
     let request = match request {
         Ok(request) => request.payload,
@@ -197,18 +338,56 @@ pub async fn query(req: String) -> ApiResult<QueryHttpEnvelope> {
             )));
         }
     };
+    let admin_keys = admin_only_set_keys(&request.expr);
+    if !admin_keys.is_empty() {
+        let keys = admin_keys.join(", ");
+        crate::auth::require_admin(headers)
+            .await
+            .map_err(|status| match status {
+                axum::http::StatusCode::FORBIDDEN => ApiError::new(
+                    status,
+                    format!("{keys} cannot be changed: configure server.auth_token to enable it"),
+                ),
+                _ => ApiError::new(status, format!("changing {keys} requires the admin token")),
+            })?;
+    }
 
-    // Await the async handle_query function
-    let reply_payload = match handle_query(request).await {
+    // Config writes made by this query are attributed to the caller.
+    let source = crate::server::config_watch::change_source(headers);
+    let running = query_cancel::register(query_id.clone()).ok_or_else(|| {
+        ApiError::new(
+            axum::http::StatusCode::CONFLICT,
+            format!("query '{query_id}' is already running"),
+        )
+    })?;
+    let run = async move {
+        match format {
+            ResultFormat::Json => handle_query(request).await.map(QueryReply::Data),
+            ResultFormat::Arrow => handle_query_arrow(request).await,
+        }
+    };
+    let reply = query_cancel::scope(running.token(), config::with_change_source(source, run)).await;
+    drop(running);
+    let reply = match reply {
         Ok(reply) => reply,
-        Err(err) => {
+        Err(err) => QueryReply::Data(match EngineError::stop_reason(&*err) {
+            Some(EngineError::Cancelled) => QueryDataFormat::Error(QueryError {
+                code: ErrorCode::Cancelled,
+                message: format!("query '{query_id}' cancelled"),
+                details: None,
+            }),
+            Some(timeout) => QueryDataFormat::Error(QueryError {
+                code: ErrorCode::TimeoutError,
+                message: format!("query '{query_id}': {timeout}"),
+                details: None,
+            }),
             // Error already logged in handle_query if it originated there
-            QueryDataFormat::Error(QueryError {
+            None => QueryDataFormat::Error(QueryError {
                 code: ErrorCode::Internal,
                 message: format!("{err:#}"),
                 details: None,
-            })
-        }
+            }),
+        }),
     };
 
     // Wrap the payload in a Message
@@ -222,12 +401,89 @@ pub async fn query(req: String) -> ApiResult<QueryHttpEnvelope> {
             stats.peer_batches_dropped,
         );
     }
+    let reply_payload = match reply {
+        // A partial Arrow result is only flagged by the 503 status.
+        QueryReply::Arrow(body) => {
+            return Ok(QueryHttpEnvelope {
+                body,
+                content_type: ARROW_STREAM_CONTENT_TYPE,
+                partial,
+                query_id,
+            })
+        }
+        QueryReply::Data(payload) => payload,
+    };
     let mut reply_message = Message::new(reply_payload);
     reply_message.meta = fanout_meta_from_stats(stats);
 
     // Serialize the response message
-    let body = serde_json::to_string(&reply_message)
+    let body = serde_json::to_vec(&reply_message)
         .inspect_err(|e| log::error!("Failed to serialize query response: {e}"))
         .map_err(|e| ApiError::internal(format!("Failed to create response: {e}")))?;
-    Ok(QueryHttpEnvelope { body, partial })
+    Ok(QueryHttpEnvelope {
+        body,
+        content_type: "application/json",
+        partial,
+        query_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_results_are_negotiated_through_accept() {
+        let format = |accept: &str| {
+            let mut headers = axum::http::HeaderMap::new();
+            headers.insert(axum::http::header::ACCEPT, accept.parse().unwrap());
+            ResultFormat::from_accept(&headers)
+        };
+        let none = axum::http::HeaderMap::new();
+        assert_eq!(ResultFormat::from_accept(&none), ResultFormat::Json);
+        assert_eq!(format(ARROW_STREAM_CONTENT_TYPE), ResultFormat::Arrow);
+        assert_eq!(format("*/*"), ResultFormat::Json);
+        assert_eq!(
+            format("application/json, application/vnd.apache.arrow.stream;q=0.9"),
+            ResultFormat::Json
+        );
+        assert_eq!(
+            format("application/json;q=0.5, application/vnd.apache.arrow.stream"),
+            ResultFormat::Arrow
+        );
+        assert_eq!(
+            format("application/json, application/vnd.apache.arrow.stream"),
+            ResultFormat::Arrow
+        );
+        assert_eq!(
+            format("application/vnd.apache.arrow.stream;q=0"),
+            ResultFormat::Json
+        );
+        assert_eq!(
+            format("*/*;q=0.1, application/vnd.apache.arrow.stream;q=0.8"),
+            ResultFormat::Arrow
+        );
+    }
+
+    #[test]
+    fn query_ids_are_the_callers_or_fresh() {
+        assert_eq!(accept_query_id(Some("ui-42.a_b")).unwrap(), "ui-42.a_b");
+        assert_eq!(accept_query_id(None).unwrap().len(), 32);
+        let err = accept_query_id(Some("no spaces")).err().unwrap();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn finds_admin_only_keys_in_set_batches() {
+        assert_eq!(
+            admin_only_set_keys("set server.file_dirs='/var/log'"),
+            ["server.file_dirs"]
+        );
+        assert_eq!(
+            admin_only_set_keys("SET probing.server.file.dirs TO '/x'; set torch.profiling=on"),
+            ["probing.server.file.dirs"]
+        );
+        assert!(admin_only_set_keys("set server.file_dirs_other=1").is_empty());
+        assert!(admin_only_set_keys("SELECT 'set server.file_dirs=1'").is_empty());
+    }
 }
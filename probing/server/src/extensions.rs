@@ -1,5 +1,7 @@
+use probing_core::core::option_value::parse_variant;
 use probing_core::core::{
-    EngineError, Maybe, ProbeExtension, ProbeExtensionCall, ProbeExtensionOption,
+    EngineError, Maybe, OptionType, OptionValue, ProbeExtension, ProbeExtensionCall,
+    ProbeExtensionOption,
 };
 
 use crate::{start_remote, start_report_worker};
@@ -24,7 +26,7 @@ pub struct ServerProbeExtension {
     auth_token: Maybe<String>,
 
     /// Maximum number of connections allowed
-    #[option(aliases=["max_conns"])]
+    #[option(aliases=["max_conns"], min = 1)]
     max_connections: Maybe<u32>,
 
     /// Connection timeout in seconds
@@ -37,15 +39,113 @@ pub struct ServerProbeExtension {
 
     /// Log level (trace, debug, info, warn, error)
     #[option(aliases=["loglevel"])]
-    log_level: Maybe<String>,
+    log_level: Maybe<LogLevel>,
 
     /// Root path for assets used by the probing UI dashboard
     #[option(aliases=["assets.root"])]
     assets_root: Maybe<String>,
+
+    /// Comma-separated directories the file API may read; empty disables it.
+    /// Changing it at runtime requires the admin token.
+    #[option(aliases=["file.dirs"])]
+    file_dirs: Maybe<String>,
 }
 
 impl ProbeExtensionCall for ServerProbeExtension {}
 
+/// `server.log_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    const NAMES: &'static [&'static str] = &["trace", "debug", "info", "warn", "error"];
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+}
+
+impl OptionValue for LogLevel {
+    fn option_type() -> OptionType {
+        OptionType::Enum(Self::NAMES)
+    }
+
+    fn parse_option(value: &str) -> Option<Self> {
+        parse_variant(value, Self::NAMES).map(|i| Self::ALL[i])
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(Self::NAMES[*self as usize])
+    }
+}
+
+/// Runtime filter for probing's own log records; changes apply without a restart.
+#[derive(Debug, Default, ProbeExtension)]
+pub struct LogProbeExtension {
+    /// Base log level for probing's own records (trace, debug, info, warn, error, off)
+    #[option]
+    level: Maybe<String>,
+
+    /// Per-target overrides, e.g. `probing_core::trace=debug,probing_server=warn`
+    #[option]
+    targets: Maybe<String>,
+}
+
+impl ProbeExtensionCall for LogProbeExtension {}
+
+impl LogProbeExtension {
+    fn apply(&self, level: &Maybe<String>, targets: &Maybe<String>) -> Result<(), EngineError> {
+        let level: Option<String> = level.clone().into();
+        let targets: Option<String> = targets.clone().into();
+        probing_logging::set_filter(level.as_deref(), targets.as_deref())
+            .map(|spec| log::info!("probing log filter set to {spec:?}"))
+            .map_err(EngineError::InternalError)
+    }
+
+    fn set_level(&mut self, level: Maybe<String>) -> Result<(), EngineError> {
+        if let Maybe::Just(ref value) = level {
+            if value
+                .trim()
+                .parse::<tracing::level_filters::LevelFilter>()
+                .is_err()
+            {
+                return Err(EngineError::InvalidOptionValue(
+                    Self::OPTION_LEVEL.to_string(),
+                    value.clone(),
+                ));
+            }
+        }
+        self.apply(&level, &self.targets)?;
+        self.level = level;
+        Ok(())
+    }
+
+    fn set_targets(&mut self, targets: Maybe<String>) -> Result<(), EngineError> {
+        if let Maybe::Just(ref value) = targets {
+            if let Err(e) = probing_logging::check_filter(value.trim()) {
+                return Err(EngineError::InvalidOptionValue(
+                    Self::OPTION_TARGETS.to_string(),
+                    e,
+                ));
+            }
+        }
+        self.apply(&self.level, &targets)?;
+        self.targets = targets;
+        Ok(())
+    }
+}
+
 impl Default for ServerProbeExtension {
     fn default() -> Self {
         Self {
@@ -56,12 +156,20 @@ impl Default for ServerProbeExtension {
             max_connections: Maybe::Just(128),
             timeout: Maybe::Just(30),  // Default timeout of 30 seconds
             debug: Maybe::Just(false), // Debug mode off by default
-            log_level: Maybe::Just("info".to_string()), // Default log level
+            log_level: Maybe::Just(LogLevel::Info), // Default log level
             assets_root: Maybe::Nothing,
+            file_dirs: Maybe::Just(default_file_dirs_label()),
         }
     }
 }
 
+/// What the file API allows before `server.file_dirs` is set.
+fn default_file_dirs_label() -> String {
+    let mut dirs = crate::server::config::DEFAULT_FILE_DIRS.to_vec();
+    dirs.extend(["~", "."]);
+    dirs.join(",")
+}
+
 impl ServerProbeExtension {
     fn set_address(&mut self, address: Maybe<String>) -> Result<(), EngineError> {
         let address_string: String = address.clone().into();
@@ -104,14 +212,6 @@ impl ServerProbeExtension {
     }
 
     fn set_max_connections(&mut self, max_connections: Maybe<u32>) -> Result<(), EngineError> {
-        if let Maybe::Just(count) = max_connections {
-            if count == 0 {
-                return Err(EngineError::InvalidOptionValue(
-                    "max_connections".to_string(),
-                    count.to_string(),
-                ));
-            }
-        }
         self.max_connections = max_connections;
         Ok(())
     }
@@ -126,22 +226,9 @@ impl ServerProbeExtension {
         Ok(())
     }
 
-    fn set_log_level(&mut self, log_level: Maybe<String>) -> Result<(), EngineError> {
-        if let Maybe::Just(ref level_str) = log_level {
-            match level_str.to_lowercase().as_str() {
-                "trace" | "debug" | "info" | "warn" | "error" => {
-                    self.log_level = log_level;
-                    Ok(())
-                }
-                _ => Err(EngineError::InvalidOptionValue(
-                    "log_level".to_string(),
-                    level_str.clone(),
-                )),
-            }
-        } else {
-            self.log_level = log_level;
-            Ok(())
-        }
+    fn set_log_level(&mut self, log_level: Maybe<LogLevel>) -> Result<(), EngineError> {
+        self.log_level = log_level;
+        Ok(())
     }
 
     fn set_assets_root(&mut self, assets_root: Maybe<String>) -> Result<(), EngineError> {
@@ -151,13 +238,22 @@ impl ServerProbeExtension {
         self.assets_root = assets_root;
         Ok(())
     }
+
+    fn set_file_dirs(&mut self, file_dirs: Maybe<String>) -> Result<(), EngineError> {
+        let raw: String = file_dirs.clone().into();
+        let dirs = crate::server::config::parse_file_dirs(&raw)
+            .map_err(|e| EngineError::invalid_option(Self::OPTION_FILE_DIRS, e))?;
+        crate::server::config::set_file_dirs(Some(dirs));
+        self.file_dirs = file_dirs;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use probing_core::core::ProbeExtension;
+    use probing_core::core::{EngineError, OptionType, ProbeExtension};
 
-    use crate::extensions::ServerProbeExtension;
+    use crate::extensions::{LogProbeExtension, ServerProbeExtension};
 
     #[test]
     fn test_server_extension() {
@@ -190,7 +286,14 @@ mod test {
         // Test log level
         assert!(ext.set("log_level", "debug").is_ok());
         assert_eq!(ext.get("log_level").unwrap(), "debug");
-        assert!(ext.set("log_level", "invalid").is_err());
+        assert!(matches!(
+            ext.set("log_level", "invalid"),
+            Err(EngineError::InvalidOptionValue(key, reason))
+                if key == "server.log_level"
+                    && reason == "invalid (expected one of trace|debug|info|warn|error)"
+        ));
+        assert!(ext.set("max_connections", "many").is_err());
+        assert_eq!(ext.get("max_connections").unwrap(), "200");
 
         // Test auth token
         assert!(ext.set("auth_token", "secret123").is_ok());
@@ -204,9 +307,19 @@ mod test {
         assert!(ext.set("invalid.key", "value").is_err());
         assert!(ext.get("invalid.key").is_err());
 
+        // File dirs must exist; a rejected value leaves the allow-list alone.
+        assert!(matches!(
+            ext.set("file_dirs", "/nonexistent/probing-logs"),
+            Err(EngineError::InvalidOptionValue(key, _)) if key == "server.file_dirs"
+        ));
+        assert_eq!(
+            ext.get("file_dirs").unwrap(),
+            "./logs,./data,./config,/tmp,~,."
+        );
+
         // Test options list
         let options = ext.options();
-        assert_eq!(options.len(), 9); // Updated count to include all options
+        assert_eq!(options.len(), 10); // Updated count to include all options
         assert!(options.iter().any(|opt| opt.key == "server.address"));
         assert!(options.iter().any(|opt| opt.key == "server.unix_socket"));
         assert!(options.iter().any(|opt| opt.key == "server.report_addr"));
@@ -217,5 +330,28 @@ mod test {
         assert!(options.iter().any(|opt| opt.key == "server.timeout"));
         assert!(options.iter().any(|opt| opt.key == "server.debug"));
         assert!(options.iter().any(|opt| opt.key == "server.log_level"));
+        assert!(options.iter().any(|opt| opt.key == "server.file_dirs"));
+        let max_connections = options
+            .iter()
+            .find(|opt| opt.key == "server.max_connections")
+            .unwrap();
+        assert_eq!(max_connections.option_type, OptionType::Integer);
+        assert_eq!(max_connections.spec(), "integer >= 1");
+    }
+
+    #[test]
+    fn test_log_extension_rejects_bad_filters() {
+        let mut ext = LogProbeExtension::default();
+        assert_eq!(ext.name(), "logextension");
+
+        assert!(matches!(
+            ext.set("level", "chatty"),
+            Err(EngineError::InvalidOptionValue(key, _)) if key == "log.level"
+        ));
+        assert!(ext.set("targets", "probing_core=nonsense").is_err());
+        assert_eq!(ext.get("level").unwrap(), "");
+
+        let keys: Vec<String> = ext.options().into_iter().map(|o| o.key).collect();
+        assert_eq!(keys, ["log.level", "log.targets"]);
     }
 }
@@ -19,6 +19,7 @@ mod vars;
 pub use self::engine::initialize_engine;
 pub use self::engine_lifecycle::{engine_init_state, engine_is_ready};
 pub use self::report::start_report_worker;
+pub use self::server::snapshot::serve_snapshot;
 pub use self::server::start_local;
 pub use self::server::start_remote;
 pub use self::server::sync_env_settings;
@@ -59,6 +59,11 @@ pub fn validate_config_key(key: &str) -> Result<(), String> {
     if key.contains('=') || key.contains(';') || key.contains('\'') || key.contains('"') {
         return Err("config key contains invalid characters".to_string());
     }
+    if crate::server::config::is_admin_only_config_key(key) {
+        return Err(format!(
+            "{key} can only be changed over HTTP with the admin token"
+        ));
+    }
     Ok(())
 }
 
@@ -189,6 +194,7 @@ pub fn ele_to_json(ele: &Ele) -> serde_json::Value {
         Ele::F64(v) => serde_json::json!(v),
         Ele::Text(v) | Ele::Url(v) => serde_json::Value::String(v.clone()),
         Ele::DataTime(v) => serde_json::json!(v),
+        Ele::DateTime(v) => serde_json::json!(v),
     }
 }
 #[cfg(test)]
@@ -220,6 +226,15 @@ mod tests {
         assert!(ensure_read_only_sql("SELECT 1; SELECT 2").is_ok());
     }
 
+    #[test]
+    fn config_key_validation_rejects_admin_only_keys() {
+        assert!(validate_config_key("torch.profiling").is_ok());
+        assert!(validate_config_key("a=b").is_err());
+        assert!(validate_config_key("probing.server.file_dirs")
+            .unwrap_err()
+            .contains("admin token"));
+    }
+
     #[test]
     fn tool_error_from_preserves_anyhow_chain() {
         let err = anyhow::anyhow!("root cause")
@@ -76,7 +76,10 @@ async fn report_worker(report_addr: String, local_addr: String) {
             backoff.record(outcome);
             backoff.sleep_duration()
         };
-        tokio::time::sleep(sleep_for).await;
+        tokio::select! {
+            _ = tokio::time::sleep(sleep_for) => {}
+            _ = crate::server::detach::detached() => break,
+        }
     }
 }
 
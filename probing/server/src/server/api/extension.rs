@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
 use axum::{
+    body::Body,
     http::{HeaderMap, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
 };
+use futures_util::{stream, StreamExt};
 use http_body_util::BodyExt;
 
-use probing_core::core::ProbeExtensionManager;
+use probing_core::core::{ExtensionStream, ProbeExtensionManager};
 
 use crate::engine::ENGINE;
 use crate::server::api::response;
@@ -30,6 +32,17 @@ pub async fn handle(req: axum::extract::Request) -> ApiResult<Response> {
                 route.method
             )));
         }
+        if route.admin {
+            crate::auth::require_admin(&parts.headers)
+                .await
+                .map_err(|status| match status {
+                    StatusCode::FORBIDDEN => ApiError::new(
+                        status,
+                        format!("{path} is disabled: configure server.auth_token to enable it"),
+                    ),
+                    _ => ApiError::new(status, format!("{path} requires the admin token")),
+                })?;
+        }
     }
 
     let params: HashMap<String, String> = match parts.uri.query() {
@@ -45,24 +58,15 @@ pub async fn handle(req: axum::extract::Request) -> ApiResult<Response> {
         body_bytes.len()
     );
 
-    let eem = {
-        let engine = ENGINE.read().await;
-        engine
-            .context
-            .state()
-            .config()
-            .options()
-            .extensions
-            .get::<ProbeExtensionManager>()
-            .cloned()
-    };
-
-    let Some(eem) = eem else {
+    let Some(eem) = extension_manager().await else {
         return Ok((StatusCode::NOT_FOUND, "Extension manager not available").into_response());
     };
 
-    match eem.call(path, &params, &body_bytes).await {
-        Ok(response_bytes) => Ok(extension_response(path, response_bytes).into_response()),
+    match eem.call_stream(path, &params, &body_bytes).await {
+        Ok(chunks) => {
+            let meta = response::lookup_for(path, params.get("format").map(String::as_str));
+            extension_stream_response_with(meta, path, chunks).await
+        }
         Err(e) => {
             log::error!("Extension call failed for path '{path}': {e}");
             Err(ApiError::from_engine(e))
@@ -70,17 +74,64 @@ pub async fn handle(req: axum::extract::Request) -> ApiResult<Response> {
     }
 }
 
+/// The engine's extension manager, `None` before one is registered.
+pub(crate) async fn extension_manager() -> Option<ProbeExtensionManager> {
+    let engine = ENGINE.read().await;
+    engine
+        .context
+        .state()
+        .config()
+        .options()
+        .extensions
+        .get::<ProbeExtensionManager>()
+        .cloned()
+}
+
 /// Strip the `/apis` mount prefix so extensions match on `/{name}/…`.
 pub fn api_path(full_path: &str) -> &str {
     full_path.strip_prefix("/apis").unwrap_or(full_path)
 }
 
-fn extension_response(path: &str, body: Vec<u8>) -> (StatusCode, HeaderMap, Vec<u8>) {
-    let meta = response::lookup(path);
-    let status = response::status_for_extension_body(meta.content_type, &body);
+/// Turn an extension's chunks into a response.
+///
+/// The first chunk decides the status (handlers report errors as a JSON
+/// object before streaming starts). A reply that fits in one chunk is sent
+/// with `Content-Length`; anything longer goes out with chunked transfer
+/// encoding as the chunks arrive, so large exports are never buffered here.
+pub async fn extension_stream_response(path: &str, chunks: ExtensionStream) -> ApiResult<Response> {
+    extension_stream_response_with(response::lookup(path), path, chunks).await
+}
+
+/// [`extension_stream_response`] with metadata already resolved for the
+/// request (e.g. `?format=proto`).
+pub async fn extension_stream_response_with(
+    meta: response::ResponseMeta,
+    path: &str,
+    mut chunks: ExtensionStream,
+) -> ApiResult<Response> {
+    let first = match chunks.next().await {
+        Some(chunk) => chunk.map_err(ApiError::from_engine)?,
+        None => vec![],
+    };
+    let meta = response::meta_for_body(meta, &first);
+    let status = response::status_for_extension_body(meta.content_type, &first);
     let mut headers = HeaderMap::new();
     response::apply_response_headers(meta, &mut headers);
-    (status, headers, body)
+
+    let second = match chunks.next().await {
+        None => return Ok((status, headers, first).into_response()),
+        Some(chunk) => chunk.map_err(ApiError::from_engine)?,
+    };
+    let path = path.to_string();
+    let rest = chunks.map(move |chunk| {
+        // Headers are gone; all we can do is log and abort the body.
+        chunk.map_err(|e| {
+            log::error!("Extension stream for '{path}' failed: {e}");
+            std::io::Error::other(e.to_string())
+        })
+    });
+    let body = Body::from_stream(stream::iter([Ok(first), Ok(second)]).chain(rest));
+    Ok((status, headers, body).into_response())
 }
 
 fn cors_preflight() -> (StatusCode, HeaderMap, &'static str) {
@@ -122,6 +173,21 @@ mod tests {
         assert_eq!(route.method, "POST");
     }
 
+    #[tokio::test]
+    async fn admin_route_rejects_requests_without_token() {
+        let req = axum::http::Request::get("/apis/pythonext/gc")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let err = super::handle(req).await.unwrap_err();
+        assert!(
+            matches!(
+                err.status(),
+                StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED
+            ),
+            "{err:?}"
+        );
+    }
+
     #[test]
     fn response_lookup_follows_spec_not_path_heuristics() {
         assert_eq!(
@@ -129,6 +195,7 @@ mod tests {
             ResponseMeta {
                 content_type: "application/json",
                 cors: false,
+                proto: false,
             }
         );
     }
@@ -156,4 +223,41 @@ mod tests {
             StatusCode::NOT_FOUND
         );
     }
+
+    #[tokio::test]
+    async fn single_chunk_keeps_content_length_and_error_status() {
+        use super::extension_stream_response;
+        use axum::body::HttpBody;
+        use probing_core::core::single_chunk;
+
+        let resp = extension_stream_response(
+            "/pythonext/trace/chrome-tracing",
+            single_chunk(br#"{"error":"bad limit"}"#.to_vec()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(resp.body().size_hint().exact(), Some(21));
+    }
+
+    #[tokio::test]
+    async fn multi_chunk_reply_is_streamed_in_order() {
+        use super::extension_stream_response;
+        use axum::body::HttpBody;
+        use futures_util::stream;
+        use http_body_util::BodyExt;
+
+        let chunks = ["{\"traceEvents\": [", "1,", "2", "]}"].map(|c| Ok(c.as_bytes().to_vec()));
+        let resp = extension_stream_response(
+            "/pythonext/trace/chrome-tracing",
+            Box::pin(stream::iter(chunks)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.body().size_hint().exact(), None);
+        assert_eq!(resp.headers()["access-control-allow-origin"], "*");
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"traceEvents": [1,2]}"#);
+    }
 }
@@ -6,25 +6,48 @@ pub mod extension;
 pub mod response;
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 
-use super::{cluster, cluster_query, file_api, local_query, system, training};
+use super::{
+    chart_query, cluster, cluster_metrics, cluster_query, config_watch, detach, file_api,
+    local_query, logs, pprof_download, profile_diff, snapshot, system, trace_archive,
+    trace_download, trace_flamegraph, trace_source, trace_stream, trace_tree, training,
+};
 
 /// Canonical public `/apis` routes (method, path suffix under `/apis`).
 /// Keep in sync with `tests/regression/spec/api_spec.json` — verified by `spec_tests`.
 pub const PUBLIC_API_ROUTES: &[(&str, &str)] = &[
     ("GET", "/overview"),
+    ("GET", "/features"),
     ("GET", "/files"),
     ("GET", "/nodes"),
     ("PUT", "/nodes"),
+    ("GET", "/nodes/metrics"),
     ("GET", "/training/step_matrix"),
     ("GET", "/training/distributed_flamegraph/json"),
     ("GET", "/training/distributed_stack_flamegraph/json"),
     ("POST", "/cluster/query"),
     ("GET", "/processes/local"),
     ("POST", "/query/local-pid"),
+    ("GET", "/logs/recent"),
+    ("POST", "/chart_query"),
+    ("GET", "/trace/dump"),
+    ("POST", "/trace/import"),
+    ("GET", "/trace/span_tree"),
+    ("GET", "/trace/source"),
+    ("GET", "/trace/chrome-tracing/download"),
+    ("GET", "/trace/stream"),
+    ("GET", "/trace/flamegraph"),
+    ("GET", "/trace/flamegraph/json"),
+    ("POST", "/profile/diff"),
+    ("POST", "/profile/svg"),
+    ("GET", "/pprof/profile.pb.gz"),
+    ("GET", "/config/watch"),
+    ("GET", "/snapshot"),
+    ("POST", "/detach"),
 ];
 
 /// Build the `/apis` router mounted by the root application.
@@ -38,6 +61,7 @@ fn public_routes() -> Router {
         .route("/overview", get(system::get_overview_json))
         .route("/files", get(file_api::read_file))
         .route("/nodes", get(cluster::get_nodes).put(cluster::put_node))
+        .route("/nodes/metrics", get(cluster_metrics::get_node_metrics))
         .route("/training/step_matrix", get(training::get_step_matrix))
         .route(
             "/training/distributed_flamegraph/json",
@@ -50,6 +74,39 @@ fn public_routes() -> Router {
         .route("/cluster/query", post(cluster_query::post_cluster_query))
         .route("/processes/local", get(system::get_local_processes_json))
         .route("/query/local-pid", post(local_query::query_local_pid))
+        .route("/logs/recent", get(logs::get_recent_logs))
+        .route("/chart_query", post(chart_query::post_chart_query))
+        .route("/trace/dump", get(trace_archive::get_trace_dump))
+        // Archives are bounded by the global request size limit instead.
+        .route(
+            "/trace/import",
+            post(trace_archive::post_trace_import).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/trace/span_tree", get(trace_tree::get_span_tree))
+        .route("/trace/source", get(trace_source::get_span_source))
+        .route(
+            "/trace/chrome-tracing/download",
+            get(trace_download::get_chrome_tracing_download),
+        )
+        .route("/trace/stream", get(trace_stream::stream_spans))
+        .route(
+            "/trace/flamegraph",
+            get(trace_flamegraph::get_span_flamegraph),
+        )
+        .route(
+            "/trace/flamegraph/json",
+            get(trace_flamegraph::get_span_flamegraph_json),
+        )
+        .route("/profile/diff", post(profile_diff::post_profile_diff))
+        .route("/profile/svg", post(profile_diff::post_profile_svg))
+        .route(
+            "/pprof/profile.pb.gz",
+            get(pprof_download::get_pprof_profile),
+        )
+        .route("/features", get(system::get_features_json))
+        .route("/config/watch", get(config_watch::watch_config))
+        .route("/snapshot", get(snapshot::get_snapshot))
+        .route("/detach", post(detach::post_detach))
 }
 
 #[cfg(test)]
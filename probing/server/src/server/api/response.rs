@@ -10,13 +10,19 @@ use once_cell::sync::Lazy;
 pub struct ResponseMeta {
     pub content_type: &'static str,
     pub cors: bool,
+    /// Route also serves `?format=proto` as [`PROTOBUF`].
+    pub proto: bool,
 }
 
+/// Content type of binary Perfetto traces (`?format=proto`).
+pub const PROTOBUF: &str = "application/x-protobuf";
+
 impl Default for ResponseMeta {
     fn default() -> Self {
         Self {
             content_type: "text/plain",
             cors: false,
+            proto: false,
         }
     }
 }
@@ -26,6 +32,8 @@ impl Default for ResponseMeta {
 pub struct ExtensionRouteSpec {
     pub method: &'static str,
     pub response: ResponseMeta,
+    /// Route changes the target process; requires the admin token.
+    pub admin: bool,
 }
 
 static ROUTE_MAP: Lazy<HashMap<String, ExtensionRouteSpec>> = Lazy::new(|| {
@@ -67,6 +75,10 @@ fn build_route_map() -> Result<HashMap<String, ExtensionRouteSpec>, String> {
             ExtensionRouteSpec {
                 method: parse_method(method),
                 response: parse_response_meta(response, &defaults),
+                admin: handler
+                    .get("admin")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
             },
         );
     }
@@ -93,6 +105,10 @@ fn build_route_map() -> Result<HashMap<String, ExtensionRouteSpec>, String> {
             ExtensionRouteSpec {
                 method: parse_method(method),
                 response: parse_response_meta(response, &defaults),
+                admin: entry
+                    .get("admin")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
             },
         );
     }
@@ -124,6 +140,10 @@ fn parse_response_meta(response: &serde_json::Value, defaults: &serde_json::Valu
         .or_else(|| defaults.get("cors"))
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let proto = response
+        .get("formats")
+        .and_then(|v| v.as_array())
+        .is_some_and(|formats| formats.iter().any(|f| f.as_str() == Some("proto")));
 
     ResponseMeta {
         content_type: match content_type {
@@ -139,6 +159,7 @@ fn parse_response_meta(response: &serde_json::Value, defaults: &serde_json::Valu
             }
         },
         cors,
+        proto,
     }
 }
 
@@ -161,6 +182,37 @@ pub fn lookup(path: &str) -> ResponseMeta {
         .unwrap_or_default()
 }
 
+/// Response metadata for a request's `format` query parameter.
+///
+/// `format=proto` switches routes that declare it to [`PROTOBUF`]; any other
+/// value keeps the spec content type (the handler rejects unknown formats).
+pub fn lookup_for(path: &str, format: Option<&str>) -> ResponseMeta {
+    let meta = lookup(path);
+    if meta.proto && format == Some("proto") {
+        ResponseMeta {
+            content_type: PROTOBUF,
+            ..meta
+        }
+    } else {
+        meta
+    }
+}
+
+/// Metadata for a reply whose first chunk is `first`.
+///
+/// A Perfetto trace starts with a `packet` field tag (`0x0a`), so a protobuf
+/// reply starting with `{` is the handler's JSON error object.
+pub fn meta_for_body(meta: ResponseMeta, first: &[u8]) -> ResponseMeta {
+    if meta.content_type == PROTOBUF && first.first() == Some(&b'{') {
+        ResponseMeta {
+            content_type: "application/json",
+            ..meta
+        }
+    } else {
+        meta
+    }
+}
+
 /// HTTP status for an extension response body (Python router JSON errors → 4xx).
 pub fn status_for_extension_body(content_type: &str, body: &[u8]) -> StatusCode {
     if content_type != "application/json" {
@@ -235,9 +287,16 @@ mod tests {
                 route.response.content_type,
                 handler["response"]["content_type"].as_str().unwrap()
             );
+            assert_eq!(route.admin, handler["admin"].as_bool().unwrap_or(false));
         }
     }
 
+    #[test]
+    fn gc_route_requires_admin() {
+        assert!(route_spec("/pythonext/gc").expect("gc route").admin);
+        assert!(!route_spec("/pythonext/callstack").expect("callstack").admin);
+    }
+
     #[test]
     fn json_handler_error_returns_bad_request() {
         let body = br#"{"error":"Missing required parameter: function"}"#;
@@ -264,6 +323,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn proto_format_switches_content_type() {
+        let path = "/pythonext/trace/chrome-tracing";
+        assert_eq!(lookup_for(path, None).content_type, "application/json");
+        assert_eq!(
+            lookup_for(path, Some("json")).content_type,
+            "application/json"
+        );
+        let proto = lookup_for(path, Some("proto"));
+        assert_eq!(proto.content_type, PROTOBUF);
+        assert!(proto.cors);
+        assert_eq!(meta_for_body(proto, b"\x0a\x02").content_type, PROTOBUF);
+        let err = meta_for_body(proto, br#"{"error":"bad"}"#);
+        assert_eq!(
+            status_for_extension_body(err.content_type, br#"{"error":"bad"}"#),
+            StatusCode::BAD_REQUEST
+        );
+        // Routes without a proto format ignore the parameter.
+        assert_eq!(
+            lookup_for("/pythonext/callstack", Some("proto")).content_type,
+            "application/json"
+        );
+    }
+
     #[test]
     fn lookup_unknown_path_uses_defaults_without_panic() {
         let meta = lookup("/pythonext/does-not-exist");
@@ -0,0 +1,93 @@
+//! Chart-oriented SQL: run a read-only query and reduce it to a target point
+//! count server-side, so plots never ship (or render) every raw sample.
+
+use axum::Json;
+use probing_proto::prelude::{
+    ChartQueryRequest, ChartQueryResponse, DataFrame, Query, QueryDataFormat,
+};
+use probing_proto::types::downsample;
+
+use super::error::{ApiError, ApiResult};
+use super::sql_guard::ensure_read_only_sql;
+use crate::engine::handle_query;
+
+/// `POST /apis/chart_query` — query, then min/max (default) or LTTB downsample.
+pub async fn post_chart_query(
+    Json(req): Json<ChartQueryRequest>,
+) -> ApiResult<Json<ChartQueryResponse>> {
+    let sql = req.to_sql().map_err(ApiError::bad_request)?;
+    ensure_read_only_sql(&sql).map_err(ApiError::bad_request)?;
+    if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
+        return Err(ApiError::service_unavailable(msg));
+    }
+
+    let df = match handle_query(Query::new(sql)).await? {
+        QueryDataFormat::DataFrame(df) => df,
+        QueryDataFormat::Error(err) => return Err(ApiError::bad_request(err.message)),
+        _ => DataFrame::default(),
+    };
+    Ok(Json(reduce(&req, &df)?))
+}
+
+fn reduce(req: &ChartQueryRequest, df: &DataFrame) -> ApiResult<ChartQueryResponse> {
+    let x_col = match req.x_column() {
+        Some(x) if !df.names.is_empty() => df
+            .col_index(x)
+            .ok_or_else(|| ApiError::bad_request(format!("x column {x:?} not in result")))?,
+        _ => 0,
+    };
+    let (dataframe, info) = downsample(df, x_col, req.target_points(), req.mode);
+    Ok(ChartQueryResponse {
+        dataframe,
+        downsample: info,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use probing_proto::prelude::{DownsampleMode, Seq};
+
+    use super::*;
+
+    fn series(rows: i64) -> DataFrame {
+        DataFrame::new(
+            vec!["v".into(), "ts".into()],
+            vec![
+                Seq::SeqF64((0..rows).map(|i| (i % 17) as f64).collect()),
+                Seq::SeqI64((0..rows).collect()),
+            ],
+        )
+    }
+
+    #[test]
+    fn reduces_along_named_x_column() {
+        let mut req = ChartQueryRequest::expr("select v, ts from t").with_points(40);
+        req.x = Some("ts".into());
+        let resp = reduce(&req, &series(5_000)).unwrap();
+        assert!(resp.downsample.applied);
+        assert_eq!(resp.downsample.mode, DownsampleMode::MinMax);
+        assert_eq!(resp.downsample.input_rows, 5_000);
+        assert!(resp.dataframe.len() <= 40);
+        assert_eq!(resp.dataframe.scalar_f64("v", 0), Some(0.0));
+        assert_eq!(
+            resp.dataframe.scalar_i64("ts", resp.dataframe.len() - 1),
+            Some(4_999)
+        );
+
+        req.x = Some("missing".into());
+        let err = reduce(&req, &series(10)).unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn rejects_writes_and_bad_identifiers() {
+        let err = post_chart_query(Json(ChartQueryRequest::expr("delete from t")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+        let err = post_chart_query(Json(ChartQueryRequest::table("t x", vec!["v".into()])))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,279 @@
+//! Background poller behind `GET /apis/nodes/metrics`: every
+//! `PROBING_CLUSTER_METRICS_INTERVAL_SEC` (default 10, `0` disables) it asks
+//! each registered node for its latest process CPU, RSS and GPU utilization
+//! and keeps a short history per node for the Cluster page sparklines.
+//!
+//! Nodes are polled concurrently and independently: an unreachable node
+//! records its error and keeps its earlier samples, the others are unaffected.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use probing_core::core::cluster::{get_nodes, local_listen_addrs, stale_threshold_secs};
+use probing_core::core::federation::remote_fanout_concurrency;
+use probing_proto::prelude::{
+    DataFrame, Ele, Node, NodeMetrics, NodeMetricsResponse, NodeMetricsSample,
+};
+
+use super::cluster_fanout::{query_local_df, remote_query_df};
+use super::SERVER_RUNTIME;
+
+const INTERVAL_ENV: &str = "PROBING_CLUSTER_METRICS_INTERVAL_SEC";
+const DEFAULT_INTERVAL_SECS: u64 = 10;
+/// Samples kept per node (10 minutes at the default interval).
+const HISTORY_LEN: usize = 60;
+
+const CPU_SQL: &str = "SELECT cpu_total_pct, rss_kb FROM cpu.utilization \
+                       WHERE scope = 'process' ORDER BY ts DESC LIMIT 1";
+const GPU_SQL: &str = "SELECT avg(gpu_util_pct) AS gpu_util_pct FROM gpu.utilization \
+                       WHERE ts = (SELECT MAX(ts) FROM gpu.utilization)";
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static STORE: LazyLock<Mutex<MetricsStore>> = LazyLock::new(Default::default);
+
+fn poll_interval_secs() -> u64 {
+    std::env::var(INTERVAL_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS)
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+/// Per-node metrics keyed by `addr`.
+#[derive(Default)]
+struct MetricsStore {
+    nodes: HashMap<String, NodeMetrics>,
+}
+
+impl MetricsStore {
+    fn record(&mut self, node: &Node, result: Result<NodeMetricsSample, String>) {
+        let entry = self
+            .nodes
+            .entry(node.addr.clone())
+            .or_insert_with(|| NodeMetrics {
+                host: node.host.clone(),
+                addr: node.addr.clone(),
+                ..Default::default()
+            });
+        entry.host = node.host.clone();
+        entry.last_heartbeat = node.timestamp;
+        match result {
+            Ok(sample) => {
+                entry.error = None;
+                entry.history.push(sample);
+                let excess = entry.history.len().saturating_sub(HISTORY_LEN);
+                entry.history.drain(..excess);
+            }
+            Err(err) => entry.error = Some(err),
+        }
+    }
+
+    /// Forget nodes that left the cluster.
+    fn retain(&mut self, nodes: &[Node]) {
+        let addrs: HashSet<&str> = nodes.iter().map(|n| n.addr.as_str()).collect();
+        self.nodes.retain(|addr, _| addrs.contains(addr.as_str()));
+    }
+
+    /// One entry per node in `nodes` order; nodes not polled yet have no
+    /// history, and heartbeats come from `nodes` so they are never stale.
+    fn snapshot(&self, nodes: &[Node]) -> Vec<NodeMetrics> {
+        nodes
+            .iter()
+            .map(|node| {
+                let mut metrics = self.nodes.get(&node.addr).cloned().unwrap_or_default();
+                metrics.host = node.host.clone();
+                metrics.addr = node.addr.clone();
+                metrics.last_heartbeat = node.timestamp;
+                metrics
+            })
+            .collect()
+    }
+}
+
+fn first_f64(df: &DataFrame, name: &str) -> Option<f64> {
+    let idx = df.names.iter().position(|n| n == name)?;
+    let value = match df.cols.get(idx)?.get(0) {
+        Ele::F64(v) => v,
+        Ele::F32(v) => f64::from(v),
+        Ele::I64(v) => v as f64,
+        Ele::I32(v) => f64::from(v),
+        _ => return None,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// A missing sampler table means "no data", not a failed poll.
+fn is_table_missing(err: &anyhow::Error) -> bool {
+    let msg = err.to_string();
+    msg.contains("not found") || msg.contains("doesn't exist")
+}
+
+async fn query(addr: &str, local: bool, sql: &str) -> anyhow::Result<DataFrame> {
+    if local {
+        query_local_df(sql).await
+    } else {
+        remote_query_df(addr, sql).await
+    }
+}
+
+/// Latest sample of one node; `Err` only when the node could not be queried.
+async fn poll_node(addr: &str, local: bool) -> Result<NodeMetricsSample, String> {
+    let mut sample = NodeMetricsSample {
+        timestamp: now_micros(),
+        ..Default::default()
+    };
+    match query(addr, local, CPU_SQL).await {
+        Ok(df) => {
+            sample.cpu_pct = first_f64(&df, "cpu_total_pct");
+            sample.rss_kb = first_f64(&df, "rss_kb").map(|kb| kb as i64);
+        }
+        Err(err) if is_table_missing(&err) => {}
+        Err(err) => return Err(err.to_string()),
+    }
+    match query(addr, local, GPU_SQL).await {
+        Ok(df) => sample.gpu_util_pct = first_f64(&df, "gpu_util_pct"),
+        Err(err) => log::debug!("cluster metrics: no GPU utilization from {addr}: {err}"),
+    }
+    Ok(sample)
+}
+
+/// Poll every registered node once and record the outcomes.
+pub async fn poll_once() {
+    use futures_util::stream::{self, StreamExt};
+
+    let nodes = get_nodes();
+    let local = local_listen_addrs();
+    let results: Vec<(Node, Result<NodeMetricsSample, String>)> = stream::iter(nodes.clone())
+        .map(|node| {
+            let is_local = local.contains(&node.addr);
+            async move {
+                let result = poll_node(&node.addr, is_local).await;
+                (node, result)
+            }
+        })
+        .buffer_unordered(remote_fanout_concurrency())
+        .collect()
+        .await;
+
+    let mut store = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    store.retain(&nodes);
+    for (node, result) in results {
+        store.record(&node, result);
+    }
+}
+
+/// Start the poll loop once per process; it idles while no node is registered.
+pub fn start_cluster_metrics() {
+    let secs = poll_interval_secs();
+    if secs == 0 || STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    SERVER_RUNTIME.spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(secs));
+        // A round can outlast the interval when nodes time out; do not burst.
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = super::detach::detached() => break,
+            }
+            poll_once().await;
+        }
+    });
+}
+
+/// `GET /apis/nodes/metrics` — heartbeat, latest metrics and history per node.
+pub async fn get_node_metrics() -> axum::Json<NodeMetricsResponse> {
+    let nodes = get_nodes();
+    let metrics = STORE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .snapshot(&nodes);
+    axum::Json(NodeMetricsResponse {
+        interval_secs: poll_interval_secs(),
+        stale_after_secs: stale_threshold_secs(),
+        nodes: metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use probing_proto::prelude::Seq;
+
+    use super::*;
+
+    fn node(addr: &str, timestamp: u64) -> Node {
+        Node {
+            host: "host".to_string(),
+            addr: addr.to_string(),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn sample(timestamp: u64, cpu: f64) -> NodeMetricsSample {
+        NodeMetricsSample {
+            timestamp,
+            cpu_pct: Some(cpu),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn failed_poll_keeps_history_and_other_nodes() {
+        let (a, b) = (node("10.0.0.1:8080", 5), node("10.0.0.2:8080", 6));
+        let mut store = MetricsStore::default();
+        store.record(&a, Ok(sample(1, 10.0)));
+        store.record(&b, Ok(sample(1, 20.0)));
+        store.record(&a, Err("connection refused".to_string()));
+        store.record(&b, Ok(sample(2, 30.0)));
+
+        let snapshot = store.snapshot(&[a.clone(), b.clone(), node("10.0.0.3:8080", 7)]);
+        assert_eq!(snapshot[0].error.as_deref(), Some("connection refused"));
+        assert_eq!(snapshot[0].history, vec![sample(1, 10.0)]);
+        assert_eq!(snapshot[1].error, None);
+        assert_eq!(snapshot[1].history.len(), 2);
+        // Registered but not polled yet.
+        assert_eq!(snapshot[2].last_heartbeat, 7);
+        assert!(snapshot[2].history.is_empty());
+
+        store.record(&a, Ok(sample(3, 15.0)));
+        assert_eq!(store.snapshot(&[a.clone()])[0].error, None);
+
+        store.retain(&[b]);
+        assert!(!store.nodes.contains_key(&a.addr));
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let a = node("a:1", 1);
+        let mut store = MetricsStore::default();
+        for t in 0..(HISTORY_LEN as u64 + 5) {
+            store.record(&a, Ok(sample(t, 1.0)));
+        }
+        let history = &store.nodes["a:1"].history;
+        assert_eq!(history.len(), HISTORY_LEN);
+        assert_eq!(history[0].timestamp, 5);
+    }
+
+    #[test]
+    fn reads_first_numeric_cell_by_name() {
+        let df = DataFrame::new(
+            vec!["cpu_total_pct".to_string(), "rss_kb".to_string()],
+            vec![Seq::SeqF32(vec![12.5]), Seq::SeqI64(vec![2048])],
+        );
+        assert_eq!(first_f64(&df, "cpu_total_pct"), Some(12.5));
+        assert_eq!(first_f64(&df, "rss_kb"), Some(2048.0));
+        assert_eq!(first_f64(&df, "gpu_util_pct"), None);
+        let empty = DataFrame::new(vec!["rss_kb".to_string()], vec![Seq::SeqI64(vec![])]);
+        assert_eq!(first_f64(&empty, "rss_kb"), None);
+    }
+}
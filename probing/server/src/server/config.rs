@@ -1,11 +1,23 @@
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+
 /// Maximum request body size allowed (5MB)
 pub const MAX_REQUEST_BODY_SIZE: usize = 5 * 1024 * 1024;
 
 /// Maximum file size allowed for file API reading (10MB)
 pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
-/// Static allowed base directories (relative paths resolved at runtime).
-pub const ALLOWED_FILE_DIRS: &[&str] = &["./logs", "./data", "./config", "/tmp"];
+/// Base directories of the file read API before `server.file_dirs` is set
+/// (relative paths resolved at runtime).
+pub const DEFAULT_FILE_DIRS: &[&str] = &["./logs", "./data", "./config", "/tmp"];
+
+/// Config keys only an admin token may change at runtime; see
+/// [`crate::auth::require_admin`].
+pub const ADMIN_ONLY_CONFIG_KEYS: &[&str] = &["server.file_dirs", "server.file.dirs"];
+
+/// `server.file_dirs` once set; `None` keeps the defaults of
+/// [`allowed_file_base_dirs`]. An empty list disables the file API.
+static FILE_DIRS: LazyLock<RwLock<Option<Vec<PathBuf>>>> = LazyLock::new(|| RwLock::new(None));
 
 /// Get maximum request body size from environment or use default
 pub fn get_max_request_body_size() -> usize {
@@ -35,11 +47,54 @@ pub fn get_max_file_size() -> u64 {
         .unwrap_or(MAX_FILE_SIZE)
 }
 
+/// Whether `key` (with or without the `probing.` prefix) is admin-only.
+pub fn is_admin_only_config_key(key: &str) -> bool {
+    let key = key.trim().to_ascii_lowercase();
+    let key = key.strip_prefix("probing.").unwrap_or(&key);
+    ADMIN_ONLY_CONFIG_KEYS.contains(&key)
+}
+
+/// Parse a `server.file_dirs` value: comma-separated directories, each
+/// absolute or resolvable from the current directory (`~` is `$HOME`), and
+/// each must exist. Returns the canonical paths.
+pub fn parse_file_dirs(raw: &str) -> Result<Vec<PathBuf>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let path = match part.strip_prefix('~') {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                    let home =
+                        std::env::var("HOME").map_err(|_| format!("{part}: HOME is not set"))?;
+                    PathBuf::from(format!("{home}{rest}"))
+                }
+                _ => PathBuf::from(part),
+            };
+            let canonical = path.canonicalize().map_err(|e| format!("{part}: {e}"))?;
+            if !canonical.is_dir() {
+                return Err(format!("{part}: not a directory"));
+            }
+            Ok(canonical)
+        })
+        .collect()
+}
+
+/// Replace the file API base directories; `None` restores the defaults.
+pub fn set_file_dirs(dirs: Option<Vec<PathBuf>>) {
+    *FILE_DIRS.write().unwrap_or_else(|e| e.into_inner()) = dirs;
+}
+
 /// Runtime base directories for the file read API (stack traces, workspace sources).
-pub fn allowed_file_base_dirs() -> Vec<std::path::PathBuf> {
-    use std::path::PathBuf;
+///
+/// `server.file_dirs` when set (empty: the file API is disabled); otherwise
+/// [`DEFAULT_FILE_DIRS`], `$HOME`, the current directory and
+/// `PROBING_ALLOWED_FILE_DIRS`.
+pub fn allowed_file_base_dirs() -> Vec<PathBuf> {
+    if let Some(dirs) = FILE_DIRS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return dirs.clone();
+    }
 
-    let mut bases: Vec<PathBuf> = ALLOWED_FILE_DIRS.iter().map(PathBuf::from).collect();
+    let mut bases: Vec<PathBuf> = DEFAULT_FILE_DIRS.iter().map(PathBuf::from).collect();
 
     if let Ok(home) = std::env::var("HOME") {
         if !home.is_empty() {
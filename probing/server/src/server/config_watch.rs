@@ -0,0 +1,121 @@
+//! `GET /apis/config/watch`: config changes streamed as they happen, one JSON
+//! [`ConfigChange`] per line, so people sharing a target during an incident
+//! can see who changed what.
+//!
+//! Writes made through `/query` carry their source (see [`change_source`]);
+//! the auth token's value itself is never streamed.
+
+use std::convert::Infallible;
+
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use futures_util::Stream;
+use probing_core::config;
+use probing_proto::prelude::ConfigChange;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::auth::{get_token_from_request, token_fingerprint, AUTH_TOKEN_CONFIG_KEY};
+
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigWatchParams {
+    /// Only keys starting with this prefix (`probing.` optional).
+    pub filter: Option<String>,
+}
+
+/// `token:<fingerprint> req:<request id>` for the request being served; either
+/// part is left out when absent.
+pub fn change_source(headers: &HeaderMap) -> String {
+    let token = get_token_from_request(headers).map(|t| format!("token:{}", token_fingerprint(&t)));
+    let request = probing_logging::current_request_id().map(|id| format!("req:{id}"));
+    [token, request]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn redact(mut change: ConfigChange) -> ConfigChange {
+    let key = change.key.strip_prefix("probing.").unwrap_or(&change.key);
+    if key == AUTH_TOKEN_CONFIG_KEY {
+        change.old = change.old.map(|_| REDACTED.to_string());
+        change.new = change.new.map(|_| REDACTED.to_string());
+    }
+    change
+}
+
+/// NDJSON lines for the changes `changes` receives.
+fn change_lines(changes: config::Subscription) -> impl Stream<Item = Result<String, Infallible>> {
+    futures_util::stream::unfold(changes, move |mut changes| async move {
+        loop {
+            match changes.recv().await {
+                Ok(change) => {
+                    let mut line = serde_json::to_string(&redact(change)).ok()?;
+                    line.push('\n');
+                    return Some((Ok(line), changes));
+                }
+                Err(RecvError::Lagged(n)) => {
+                    log::warn!("config watch: subscriber fell behind, {n} changes dropped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// `GET /apis/config/watch?filter=` — streams until the client disconnects.
+pub async fn watch_config(Query(params): Query<ConfigWatchParams>) -> Response {
+    let filter = params.filter.unwrap_or_default();
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(change_lines(config::subscribe(filter.trim()))),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn streams_matching_changes_with_their_source() {
+        let lines = change_lines(config::subscribe("watch_test."));
+        tokio::pin!(lines);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Probing-Token", "secret".parse().unwrap());
+        let source =
+            probing_logging::with_request_id("r1".into(), async { change_source(&headers) }).await;
+        assert_eq!(
+            source,
+            format!("token:{} req:r1", token_fingerprint("secret"))
+        );
+
+        config::set("other.key", "x").await;
+        config::with_change_source(source.clone(), config::set("watch_test.level", "debug")).await;
+
+        let line = lines.next().await.unwrap().unwrap();
+        let change: ConfigChange = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(change.key, "watch_test.level");
+        assert_eq!(change.old, None);
+        assert_eq!(change.new.as_deref(), Some("debug"));
+        assert_eq!(change.source, Some(source));
+    }
+
+    #[test]
+    fn auth_token_values_are_redacted() {
+        let change = redact(ConfigChange {
+            key: "probing.server.auth_token".into(),
+            old: None,
+            new: Some("hunter2".into()),
+            ..Default::default()
+        });
+        assert_eq!(change.new.as_deref(), Some(REDACTED));
+        assert_eq!(change.old, None);
+    }
+}
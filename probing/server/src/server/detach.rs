@@ -0,0 +1,221 @@
+//! `POST /apis/detach` — admin only: switch probing off in this process and
+//! leave the process itself running.
+//!
+//! Every part is torn down on its own and reported as a [`Component`], so a
+//! hook that cannot be removed does not keep the rest alive: the pprof and
+//! torch profilers are disabled through their options, the CPU (and GPU)
+//! samplers are stopped, the Python side removes its hooks
+//! (`/pythonext/detach`), and the background loops of this crate (trace
+//! retention and autosave, cluster metrics and reporting) end. Last, both
+//! HTTP listeners stop accepting: the reply to this request is the final one
+//! served on a new connection.
+//!
+//! The library stays loaded; memtables and the engine are kept, so data
+//! recorded so far is not freed.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use super::api::extension::extension_manager;
+use super::error::{ApiError, ApiResult};
+
+/// Extension path of the Python teardown.
+const PYTHON_DETACH_PATH: &str = "/pythonext/detach";
+
+static DETACHED: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+/// Whether the process has been detached.
+pub fn is_detached() -> bool {
+    *DETACHED.borrow()
+}
+
+/// Resolves once the process is detached; the shutdown signal of the HTTP
+/// listeners and background loops.
+pub async fn detached() {
+    let mut rx = DETACHED.subscribe();
+    // The sender is static and never dropped.
+    let _ = rx.wait_for(|detached| *detached).await;
+}
+
+/// Outcome of tearing down one part of probing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Component {
+    pub name: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Component {
+    fn from_result(name: &str, result: Result<String, String>) -> Self {
+        let (ok, detail, error) = match result {
+            Ok(detail) => (true, Some(detail), None),
+            Err(error) => (false, None, Some(error)),
+        };
+        Self {
+            name: name.to_string(),
+            ok,
+            detail,
+            error,
+        }
+    }
+}
+
+/// Body of `POST /apis/detach`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetachReport {
+    pub pid: u32,
+    /// TCP address the remote listener had, if it was started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub components: Vec<Component>,
+}
+
+/// `POST /apis/detach`
+pub async fn post_detach(headers: HeaderMap) -> ApiResult<Json<DetachReport>> {
+    crate::auth::require_admin(&headers)
+        .await
+        .map_err(|status| match status {
+            StatusCode::FORBIDDEN => ApiError::new(
+                status,
+                "detach is disabled: configure server.auth_token to enable it",
+            ),
+            _ => ApiError::new(status, "detach requires the admin token"),
+        })?;
+    if is_detached() {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "probing is already detached from this process",
+        ));
+    }
+
+    let mut components = vec![
+        Component::from_result(
+            "pprof profiler",
+            disable_option("probing.pprof.sample_freq", "0", pprof_enabled).await,
+        ),
+        Component::from_result(
+            "torch profiler",
+            disable_option("probing.torch.profiling", "off", torch_enabled).await,
+        ),
+    ];
+    components.extend(python_components().await);
+    components.push(Component::from_result(
+        "cpu sampler",
+        stop_sampler(probing_cc::extensions::stop_cpu_sampling).await,
+    ));
+    #[cfg(feature = "gpu")]
+    components.push(Component::from_result(
+        "gpu sampler",
+        stop_sampler(probing_gpu::extensions::stop_gpu_sampling).await,
+    ));
+
+    DETACHED.send_replace(true);
+    components.push(Component::from_result(
+        "background tasks",
+        Ok("trace retention, trace autosave, cluster metrics and reporting stopped".into()),
+    ));
+    components.push(Component::from_result(
+        "http listeners",
+        crate::cleanup()
+            .map(|()| "closing after this reply".to_string())
+            .map_err(|e| format!("listeners closing, but the control file remains: {e}")),
+    ));
+    log::warn!("probing detached: no further requests will be served");
+    let address = Some(crate::vars::read_probing_address().clone()).filter(|a| !a.is_empty());
+    Ok(Json(DetachReport {
+        pid: std::process::id(),
+        address,
+        components,
+    }))
+}
+
+fn pprof_enabled(value: &str) -> bool {
+    value.trim().parse::<i32>().is_ok_and(|freq| freq >= 1)
+}
+
+fn torch_enabled(value: &str) -> bool {
+    let value = value.trim().to_ascii_lowercase();
+    !value.is_empty() && !matches!(value.as_str(), "off" | "false" | "0")
+}
+
+/// Set `key` to `off` when its current value leaves the profiler running.
+async fn disable_option(key: &str, off: &str, enabled: fn(&str) -> bool) -> Result<String, String> {
+    let current = probing_core::config::get_str(key).await;
+    if !current.as_deref().is_some_and(enabled) {
+        return Ok("not active".to_string());
+    }
+    // Off for this process only; a persisted config keeps the operator's value.
+    probing_core::config::write_transient(key, off)
+        .await
+        .map(|()| format!("{key} set to {off}"))
+        .map_err(|e| format!("failed to set {key}={off}: {e}"))
+}
+
+/// Stop a sampler thread; joining may wait out one sampling interval.
+async fn stop_sampler<E: std::fmt::Display + Send + 'static>(
+    stop: fn() -> Result<(), E>,
+) -> Result<String, String> {
+    match tokio::task::spawn_blocking(stop).await {
+        Ok(Ok(())) => Ok("stopped".to_string()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(format!("stop task failed: {e}")),
+    }
+}
+
+/// Components reported by the Python teardown, or one failed `python hooks`
+/// entry when it could not run.
+async fn python_components() -> Vec<Component> {
+    #[derive(Deserialize)]
+    struct Reply {
+        components: Vec<Component>,
+    }
+    let failed = |error: String| vec![Component::from_result("python hooks", Err(error))];
+    let Some(eem) = extension_manager().await else {
+        return failed("extension manager not available".into());
+    };
+    match eem.call(PYTHON_DETACH_PATH, &HashMap::new(), &[]).await {
+        Ok(body) => match serde_json::from_slice::<Reply>(&body) {
+            Ok(reply) => reply.components,
+            Err(_) => failed(String::from_utf8_lossy(&body).trim().to_string()),
+        },
+        Err(e) => failed(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_running_profilers() {
+        assert!(pprof_enabled("100"));
+        assert!(!pprof_enabled("0"));
+        assert!(!pprof_enabled(""));
+        assert!(torch_enabled("on"));
+        assert!(torch_enabled("random:0.1"));
+        assert!(!torch_enabled("OFF"));
+        assert!(!torch_enabled(" "));
+    }
+
+    #[test]
+    fn python_components_deserialize() {
+        let parsed: Vec<Component> = serde_json::from_str(
+            r#"[{"name": "import hook", "ok": true, "detail": "not active"},
+                {"name": "torch module hooks", "ok": false, "error": "boom"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed[1],
+            Component::from_result("torch module hooks", Err("boom".into()))
+        );
+        assert_eq!(parsed[0].detail.as_deref(), Some("not active"));
+    }
+}
@@ -1,11 +1,24 @@
 use super::config::{allowed_file_base_dirs, get_max_file_size};
 use crate::server::error::{ApiError, ApiResult};
+use axum::http::StatusCode;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Message for requests while `server.file_dirs` is empty.
+pub(crate) const FILE_API_DISABLED: &str = "File API is disabled: server.file_dirs is empty";
+
 /// Validate that the requested path is safe and within allowed directories
+/// (`server.file_dirs`, see [`allowed_file_base_dirs`]).
 /// Made public for integration tests
 pub fn validate_path(path: &str) -> Result<PathBuf, String> {
+    validate_path_in(path, &allowed_file_base_dirs())
+}
+
+pub(crate) fn validate_path_in(path: &str, base_dirs: &[PathBuf]) -> Result<PathBuf, String> {
+    if base_dirs.is_empty() {
+        return Err(FILE_API_DISABLED.to_string());
+    }
+
     // Reject empty paths
     if path.is_empty() {
         return Err("Path cannot be empty".to_string());
@@ -25,7 +38,7 @@ pub fn validate_path(path: &str) -> Result<PathBuf, String> {
 
     // Check if the canonical path is within any allowed base directory
     let mut is_allowed = false;
-    for base_dir in allowed_file_base_dirs() {
+    for base_dir in base_dirs {
         let base_path = match base_dir.canonicalize() {
             Ok(path) => path,
             Err(_) => continue,
@@ -48,6 +61,9 @@ pub fn validate_path(path: &str) -> Result<PathBuf, String> {
 pub async fn read_file(
     axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
 ) -> ApiResult<String> {
+    if allowed_file_base_dirs().is_empty() {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, FILE_API_DISABLED));
+    }
     let path = params
         .get("path")
         .ok_or_else(|| ApiError::bad_request("Missing 'path' parameter"))?;
@@ -106,6 +122,44 @@ mod tests {
         assert!(result.unwrap_err().contains("Invalid or non-existent"));
     }
 
+    #[test]
+    fn test_validate_path_follows_configured_dirs() {
+        let allowed = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join("job.log");
+        std::fs::write(&file, "loss=0.1").unwrap();
+        let file = file.to_str().unwrap();
+
+        let dirs =
+            crate::server::config::parse_file_dirs(allowed.path().to_str().unwrap()).unwrap();
+        assert!(validate_path_in(file, &dirs)
+            .unwrap_err()
+            .contains("Access denied"));
+
+        // Adding the directory at runtime opens it up.
+        let joined = format!("{},{}", allowed.path().display(), other.path().display());
+        let dirs = crate::server::config::parse_file_dirs(&joined).unwrap();
+        assert!(validate_path_in(file, &dirs).is_ok());
+    }
+
+    #[test]
+    fn test_validate_path_disabled_by_empty_dirs() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let err = validate_path_in(tmp.path().to_str().unwrap(), &[]).unwrap_err();
+        assert_eq!(err, FILE_API_DISABLED);
+        assert!(crate::server::config::parse_file_dirs(" , ")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_file_dirs_requires_existing_dirs() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        assert!(crate::server::config::parse_file_dirs("/nonexistent/probing").is_err());
+        let err = crate::server::config::parse_file_dirs(tmp.path().to_str().unwrap()).unwrap_err();
+        assert!(err.contains("not a directory"));
+    }
+
     // Note: Lengthy tests (requiring temporary directories, files, etc.) have been moved to tests/file_api_complex_tests.rs
 
     #[tokio::test]
@@ -19,6 +19,20 @@ struct ReadinessResponse {
     reason: Option<String>,
 }
 
+#[derive(Serialize)]
+struct HealthStage {
+    name: &'static str,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HealthzResponse {
+    status: &'static str,
+    stages: Vec<HealthStage>,
+}
+
 /// Process is up and the HTTP server is accepting connections.
 pub async fn liveness() -> impl IntoResponse {
     (StatusCode::OK, Json(LivenessResponse { status: "ok" }))
@@ -54,6 +68,42 @@ pub async fn readiness() -> impl IntoResponse {
     }
 }
 
+/// Combined liveness + per-stage readiness for dashboards. Always 200 while the
+/// server answers, so pollers can tell "degraded" from "unreachable".
+pub async fn healthz() -> impl IntoResponse {
+    let engine = match engine_init_state() {
+        EngineInitState::Ready => HealthStage {
+            name: "engine",
+            status: "ok",
+            detail: None,
+        },
+        EngineInitState::Uninitialized => HealthStage {
+            name: "engine",
+            status: "starting",
+            detail: Some("engine not initialized yet".into()),
+        },
+        EngineInitState::Failed(reason) => HealthStage {
+            name: "engine",
+            status: "failed",
+            detail: Some(reason),
+        },
+    };
+    let stages = vec![
+        HealthStage {
+            name: "http",
+            status: "ok",
+            detail: None,
+        },
+        engine,
+    ];
+    let status = if stages.iter().all(|s| s.status == "ok") {
+        "ok"
+    } else {
+        "degraded"
+    };
+    (StatusCode::OK, Json(HealthzResponse { status, stages }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +119,20 @@ mod tests {
         let resp = readiness().await.into_response();
         assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
     }
+
+    #[tokio::test]
+    async fn healthz_reports_stages_without_failing_status() {
+        mark_engine_failed("boom");
+        let resp = healthz().await.into_response();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["stages"][0]["name"], "http");
+        assert_eq!(json["stages"][1]["name"], "engine");
+        // Engine state is process-global; other tests may flip it meanwhile.
+        let engine_ok = json["stages"][1]["status"] == "ok";
+        assert_eq!(json["status"], if engine_ok { "ok" } else { "degraded" });
+    }
 }
@@ -0,0 +1,83 @@
+//! Probing's own recent log records, read from the in-memory ring so runtime
+//! diagnostics can be pulled remotely without raising the terminal log level.
+
+use axum::extract::Query;
+use axum::Json;
+use probing_logging::recent::DEFAULT_CAPACITY;
+use probing_logging::{LogRecord, RecentLogs};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::error::{ApiError, ApiResult};
+
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RecentLogsParams {
+    /// Minimum severity (`error`, `warn`, `info`, `debug`, `trace`).
+    pub level: Option<String>,
+    /// Target prefix, e.g. `probing_core::trace`.
+    pub target: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// `GET /apis/logs/recent?level=&target=&limit=` — newest records, oldest first.
+pub async fn get_recent_logs(Query(params): Query<RecentLogsParams>) -> ApiResult<Json<Value>> {
+    let level = match params.level.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(level) => Some(level.parse::<tracing::Level>().map_err(|_| {
+            ApiError::bad_request(format!(
+                "invalid level {level:?} (expected error, warn, info, debug or trace)"
+            ))
+        })?),
+    };
+    let target = params
+        .target
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(DEFAULT_CAPACITY);
+
+    let records = RecentLogs::global().recent(level, target, limit);
+    Ok(Json(json!({
+        "records": records.iter().map(LogRecord::to_json).collect::<Vec<_>>(),
+        "capacity": DEFAULT_CAPACITY,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn filters_global_ring_by_level_and_target() {
+        let subscriber = tracing_subscriber::registry().with(RecentLogs::global().clone());
+        {
+            let _guard = tracing::subscriber::set_default(subscriber);
+            tracing::info!(target: "probing_logs_test::a", "info from a");
+            tracing::warn!(target: "probing_logs_test::b", "warn from b");
+        }
+
+        let Json(body) = get_recent_logs(Query(RecentLogsParams {
+            level: Some("warn".into()),
+            target: Some("probing_logs_test".into()),
+            limit: None,
+        }))
+        .await
+        .unwrap();
+        let records = body["records"].as_array().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["msg"], "warn from b");
+        assert_eq!(records[0]["target"], "probing_logs_test::b");
+
+        let err = get_recent_logs(Query(RecentLogsParams {
+            level: Some("loud".into()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+        assert_eq!(err.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+}
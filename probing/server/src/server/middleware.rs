@@ -2,14 +2,17 @@ use super::config::get_max_request_body_size;
 use axum::{
     body::Body,
     extract::Request,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use bytes::Bytes;
 use http_body_util::BodyExt;
+use probing_core::trace::{record_remote_span, Span, TRACEPARENT_HEADER};
+use probing_logging::REQUEST_ID_HEADER;
 use std::sync::{Arc, LazyLock};
 use tokio::sync::Semaphore;
+use tracing::Instrument;
 
 static CONNECTION_SEMAPHORE: LazyLock<Arc<Semaphore>> = LazyLock::new(|| {
     Arc::new(Semaphore::new(
@@ -100,6 +103,59 @@ async fn collect_body_with_limit(body: Body, limit: usize) -> Result<Bytes, Stri
     Ok(bytes)
 }
 
+/// Assign each request an id (reusing a well-formed incoming `X-Request-Id`),
+/// scope it over the handler so logs emitted while serving carry it, and echo
+/// it back in the response header.
+pub async fn request_id_middleware(request: Request, next: Next) -> Response {
+    let request_id = probing_logging::accept_or_new(
+        request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+    );
+    let span = tracing::info_span!("request", request_id = %request_id);
+
+    let mut response = probing_logging::with_request_id(request_id.clone(), next.run(request))
+        .instrument(span)
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// For requests carrying a `traceparent` header, run the handler inside a
+/// server span continuing the caller's trace (a fresh trace when the header
+/// is malformed) and record it once the response is ready. Requests without
+/// the header are not traced.
+pub async fn trace_context_middleware(request: Request, next: Next) -> Response {
+    let Some(header) = request.headers().get(TRACEPARENT_HEADER) else {
+        return next.run(request).await;
+    };
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let mut span =
+        Span::from_traceparent(header.to_str().ok(), format!("{method} {path}"), None, None);
+    let _ = span.add_attr("http.method", method.as_str());
+    let _ = span.add_attr("url.path", path.as_str());
+    if let Some(request_id) = probing_logging::current_request_id() {
+        let _ = span.add_attr("request_id", request_id);
+    }
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let _ = span.add_attr("http.status_code", status.as_u16() as i64);
+    if status.is_server_error() {
+        span.end_error(Some(status.to_string()));
+    } else {
+        span.finish();
+    }
+    record_remote_span(&span);
+    response
+}
+
 /// Middleware for logging requests (optional - for debugging)
 pub async fn request_logging_middleware(request: Request, next: Next) -> Response {
     let method = request.method().to_string();
@@ -7,16 +7,32 @@ pub mod sql_guard;
 
 pub use runtime::SERVER_RUNTIME;
 
+pub mod chart_query;
 pub mod cluster;
 pub mod cluster_fanout;
+pub mod cluster_metrics;
 pub mod cluster_query;
 pub mod config;
+pub mod config_watch;
+pub mod detach;
 pub mod error;
 pub mod file_api;
 pub mod health;
 pub mod local_query;
+pub mod logs;
 pub mod middleware;
+pub mod pprof_download;
+pub mod profile_diff;
+pub mod snapshot;
 pub mod system;
+pub mod trace_archive;
+pub mod trace_autosave;
+pub mod trace_download;
+pub mod trace_flamegraph;
+pub mod trace_retention;
+pub mod trace_source;
+pub mod trace_stream;
+pub mod trace_tree;
 pub mod training;
 
 use crate::server::error::ApiError;
@@ -27,7 +43,8 @@ use log::error;
 
 use crate::engine::{handle_query, initialize_engine};
 use crate::server::middleware::{
-    connection_limit_middleware, request_logging_middleware, request_size_limit_middleware,
+    connection_limit_middleware, request_id_middleware, request_logging_middleware,
+    request_size_limit_middleware, trace_context_middleware,
 };
 use crate::server::repl::ws_handler;
 use probing_proto::prelude::Query;
@@ -36,8 +53,10 @@ use probing_proto::prelude::Query;
 pub const TOP_LEVEL_ROUTES: &[(&str, &str)] = &[
     ("GET", "/health"),
     ("GET", "/ready"),
+    ("GET", "/healthz"),
     ("POST", "/query"),
     ("POST", "/query/dto"),
+    ("POST", "/query/cancel"),
     ("GET", "/config/{config_key}"),
     ("GET", "/ws"),
     ("POST", "/mcp"),
@@ -56,8 +75,10 @@ fn build_app(auth: bool) -> axum::Router {
     let mut app = spa::routes()
         .route("/health", axum::routing::get(health::liveness))
         .route("/ready", axum::routing::get(health::readiness))
+        .route("/healthz", axum::routing::get(health::healthz))
         .route("/query", axum::routing::post(query))
         .route("/query/dto", axum::routing::post(query_dto::query_dto))
+        .route("/query/cancel", axum::routing::post(cancel_query))
         .route(
             "/config/{config_key}",
             axum::routing::get(get_config_value_handler),
@@ -80,25 +101,65 @@ fn build_app(auth: bool) -> axum::Router {
     app.layer(axum::middleware::from_fn(request_size_limit_middleware))
         .layer(axum::middleware::from_fn(request_logging_middleware))
         .layer(axum::middleware::from_fn(connection_limit_middleware))
+        .layer(axum::middleware::from_fn(trace_context_middleware))
+        // Outermost, so rejections from the layers above also carry an id.
+        .layer(axum::middleware::from_fn(request_id_middleware))
 }
 
-async fn query(body: String) -> impl IntoResponse {
+/// `?id=` of `/query` and `/query/cancel`.
+#[derive(Debug, serde::Deserialize)]
+struct QueryIdParams {
+    id: Option<String>,
+}
+
+async fn query(
+    axum::extract::Query(params): axum::extract::Query<QueryIdParams>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> impl IntoResponse {
     if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
         return ApiError::service_unavailable(msg).into_response();
     }
-    match crate::engine::query(body).await {
+    let format = crate::engine::ResultFormat::from_accept(&headers);
+    match crate::engine::query(body, &headers, params.id.as_deref(), format).await {
         Ok(envelope) => {
             let status = if envelope.partial {
                 StatusCode::SERVICE_UNAVAILABLE
             } else {
                 StatusCode::OK
             };
-            (status, envelope.body).into_response()
+            (
+                status,
+                [
+                    (crate::engine::QUERY_ID_HEADER, envelope.query_id),
+                    (
+                        axum::http::header::CONTENT_TYPE.as_str(),
+                        envelope.content_type.to_string(),
+                    ),
+                ],
+                envelope.body,
+            )
+                .into_response()
         }
         Err(api_error) => api_error.into_response(),
     }
 }
 
+/// Cancel the running `/query` registered under `?id=`.
+async fn cancel_query(
+    axum::extract::Query(params): axum::extract::Query<QueryIdParams>,
+) -> impl IntoResponse {
+    let Some(id) = params.id.filter(|id| !id.is_empty()) else {
+        return ApiError::bad_request("missing query id: /query/cancel?id=<id>").into_response();
+    };
+    if probing_core::core::query_cancel::cancel(&id) {
+        log::info!("query '{id}' cancelled on request");
+        axum::Json(serde_json::json!({ "id": id, "cancelled": true })).into_response()
+    } else {
+        ApiError::not_found(format!("no running query with id '{id}'")).into_response()
+    }
+}
+
 pub async fn local_server() -> Result<()> {
     #[cfg(target_os = "linux")]
     let socket_path = format!("\0probing-{}", std::process::id());
@@ -119,7 +180,10 @@ pub async fn local_server() -> Result<()> {
     );
 
     let app = build_app(false);
-    axum::serve(tokio::net::UnixListener::bind(socket_path)?, app).await?;
+    axum::serve(tokio::net::UnixListener::bind(socket_path)?, app)
+        .with_graceful_shutdown(detach::detached())
+        .await?;
+    log::info!("local server closed");
     Ok(())
 }
 
@@ -180,13 +244,16 @@ pub async fn remote_server(addr: Option<String>) -> Result<()> {
             }
             probing_core::core::cluster::set_local_listen_addrs(vec![addr.to_string()]);
             log::info!("probing server is available on: {addr}");
-            probing_core::config::write("server.address", &addr.to_string()).await?;
+            probing_core::config::write_transient("server.address", &addr.to_string()).await?;
         }
         Err(err) => {
             log::error!("error getting server address: {err}");
         }
     }
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(detach::detached())
+        .await?;
+    log::info!("probing server closed");
 
     Ok(())
 }
@@ -220,6 +287,7 @@ pub fn sync_env_settings() {
                     "PROBING_AUTH_TOKEN",
                     "PROBING_BASE_PATH",
                     "PROBING_ORIGINAL",
+                    probing_core::config::PERSIST_PATH_ENV,
                 ]
                 .contains(&k.as_str())
         })
@@ -228,6 +296,11 @@ pub fn sync_env_settings() {
     SERVER_RUNTIME.spawn(async move {
         for (k, v) in env_vars {
             let k = k.replace("_", ".").to_lowercase();
+            // The persisted config, loaded with the engine, wins over env.
+            if probing_core::config::is_persisted(&k).await {
+                log::debug!("Skipped env setting {k}: set by the persisted config");
+                continue;
+            }
             let setting = format!("set {k}={v}");
             match handle_query(Query {
                 expr: setting,
@@ -276,3 +349,71 @@ mod spec_tests {
         assert_eq!(actual, expected);
     }
 }
+
+#[cfg(test)]
+mod request_id_tests {
+    use probing_logging::testing::CaptureWriter;
+    use probing_proto::prelude::{Message, Query};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tracing_subscriber::EnvFilter;
+
+    async fn raw_post(addr: std::net::SocketAddr, path: &str, body: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn failing_query_logs_and_returns_request_id() {
+        let capture = CaptureWriter::default();
+        probing_logging::init_log_bridge(log::LevelFilter::Trace);
+        let _guard = tracing::subscriber::set_default(probing_logging::build_subscriber(
+            probing_logging::LogFormat::Json,
+            // The default filter: the INFO request span is disabled.
+            EnvFilter::new("error"),
+            capture.clone(),
+        ));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // current-thread runtime: the server task shares this thread's subscriber.
+        tokio::spawn(async move { axum::serve(listener, super::build_app(false)).await });
+
+        crate::engine_lifecycle::mark_engine_ready();
+        let query = Message::new(Query::new("SELECT no_such_function(1)".into()));
+        let query = serde_json::to_string(&query).unwrap();
+        let response = raw_post(addr, "/query", &query).await;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        // `/query` reports engine errors in the payload, not the status.
+        assert!(body.contains("no_such_function"), "{body}");
+        let request_id = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("x-request-id")
+                    .then(|| value.trim().to_string())
+            })
+            .expect("x-request-id header");
+        assert_eq!(request_id.len(), 32);
+
+        let logs = capture.contents();
+        let tagged = logs
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .find(|line| line["fields"]["request_id"] == request_id.as_str())
+            .unwrap_or_else(|| panic!("no log line tagged {request_id}: {logs}"));
+        assert_eq!(tagged["level"], "ERROR");
+        assert!(
+            tagged["msg"]
+                .as_str()
+                .is_some_and(|msg| msg.starts_with("Error executing SELECT query")),
+            "{tagged}"
+        );
+    }
+}
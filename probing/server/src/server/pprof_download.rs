@@ -0,0 +1,227 @@
+//! `GET /apis/pprof/profile.pb.gz`: the CPU samples behind the pprof
+//! flamegraph as a gzip-compressed `profile.proto`, for `go tool pprof`,
+//! Speedscope and other offline tools.
+//!
+//! The profile holds the cumulative SIGPROF buckets since sampling started
+//! (the `probing pprof serve` bridge captures a time window instead), with
+//! `samples/count` and `cpu/nanoseconds` values and the target's pid and
+//! command line as profile comments.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use probing_proto::protocol::pprof::{encode_cpu_profile, parse_folded};
+use probing_python::features::stacktrace::tracers::pprof::folded_lines_snapshot;
+
+use super::error::{ApiError, ApiResult};
+
+/// Matches the target's default `probing.pprof.sample_freq`.
+const DEFAULT_SAMPLE_FREQ: u64 = 100;
+
+/// `cpu-<pid>-<unix seconds>.pb.gz`
+fn download_filename(pid: u32, unix_secs: u64) -> String {
+    format!("cpu-{pid}-{unix_secs}.pb.gz")
+}
+
+/// Gzipped `profile.proto` of folded `lines`; `None` when no line holds a
+/// valid stack.
+fn encode_profile(
+    lines: &[String],
+    sample_freq: u64,
+    time_nanos: i64,
+    comments: &[String],
+) -> std::io::Result<Option<Vec<u8>>> {
+    let stacks = parse_folded(lines);
+    if stacks.is_empty() {
+        return Ok(None);
+    }
+    let profile = encode_cpu_profile(&stacks, sample_freq, time_nanos, 0, comments);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&profile)?;
+    encoder.finish().map(Some)
+}
+
+fn process_comments() -> Vec<String> {
+    let cmdline = std::env::args().collect::<Vec<_>>().join(" ");
+    vec![
+        format!("pid: {}", std::process::id()),
+        format!("cmdline: {cmdline}"),
+    ]
+}
+
+/// `GET /apis/pprof/profile.pb.gz`
+pub async fn get_pprof_profile() -> ApiResult<Response> {
+    let sample_freq = probing_core::config::get_str("probing.pprof.sample_freq")
+        .await
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|f| *f > 0)
+        .unwrap_or(DEFAULT_SAMPLE_FREQ);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let lines = folded_lines_snapshot();
+    let body = encode_profile(
+        &lines,
+        sample_freq,
+        now.as_nanos() as i64,
+        &process_comments(),
+    )
+    .map_err(|e| ApiError::internal(format!("failed to compress profile: {e}")))?
+    .ok_or_else(|| {
+        ApiError::not_found(
+            "no CPU samples collected yet; enable sampling with probing.pprof.sample_freq",
+        )
+    })?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        download_filename(std::process::id(), now.as_secs())
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+        let (mut v, mut shift) = (0u64, 0);
+        loop {
+            let b = buf[*pos];
+            *pos += 1;
+            v |= u64::from(b & 0x7f) << shift;
+            if b < 0x80 {
+                return v;
+            }
+            shift += 7;
+        }
+    }
+
+    /// `(field, payload)` pairs; varint fields carry their value as LE bytes.
+    fn fields(buf: &[u8]) -> Vec<(u64, Vec<u8>)> {
+        let mut pos = 0;
+        let mut out = Vec::new();
+        while pos < buf.len() {
+            let key = read_varint(buf, &mut pos);
+            match key & 7 {
+                0 => {
+                    let v = read_varint(buf, &mut pos);
+                    out.push((key >> 3, v.to_le_bytes().to_vec()));
+                }
+                2 => {
+                    let len = read_varint(buf, &mut pos) as usize;
+                    out.push((key >> 3, buf[pos..pos + len].to_vec()));
+                    pos += len;
+                }
+                wire => panic!("unexpected wire type {wire}"),
+            }
+        }
+        out
+    }
+
+    fn packed(buf: &[u8]) -> Vec<u64> {
+        let mut pos = 0;
+        let mut out = Vec::new();
+        while pos < buf.len() {
+            out.push(read_varint(buf, &mut pos));
+        }
+        out
+    }
+
+    #[test]
+    fn profile_round_trips_sample_counts() {
+        let lines: Vec<String> = [
+            "thread-1 (main);[py] <module> (train.py:3);[py] step (train.py:42) 30",
+            "thread-1 (main);[py] <module> (train.py:3);[py] load (data.py:7) 12",
+            "thread-1 (main);[py] <module> (train.py:3);[py] step (train.py:42) 8",
+            "thread-2;libc.so.6`read 5",
+        ]
+        .map(String::from)
+        .to_vec();
+        let comments = [
+            "pid: 42".to_string(),
+            "cmdline: python train.py".to_string(),
+        ];
+        let gz = encode_profile(&lines, 200, 1, &comments).unwrap().unwrap();
+
+        let mut proto = Vec::new();
+        GzDecoder::new(gz.as_slice())
+            .read_to_end(&mut proto)
+            .unwrap();
+        let top = fields(&proto);
+        let strings: Vec<String> = top
+            .iter()
+            .filter(|(f, _)| *f == 6)
+            .map(|(_, b)| String::from_utf8(b.clone()).unwrap())
+            .collect();
+
+        // Sample types: samples/count, then cpu/nanoseconds.
+        let types: Vec<(String, String)> = top
+            .iter()
+            .filter(|(f, _)| *f == 1)
+            .map(|(_, b)| {
+                let vt = fields(b);
+                let idx = |n| {
+                    let (_, v) = vt.iter().find(|(f, _)| *f == n).unwrap();
+                    strings[u64::from_le_bytes(v[..8].try_into().unwrap()) as usize].clone()
+                };
+                (idx(1), idx(2))
+            })
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ("samples".to_string(), "count".to_string()),
+                ("cpu".to_string(), "nanoseconds".to_string())
+            ]
+        );
+
+        // One sample per distinct stack; counts match the folded input.
+        let values: Vec<Vec<u64>> = top
+            .iter()
+            .filter(|(f, _)| *f == 2)
+            .map(|(_, b)| {
+                let (_, v) = fields(b).into_iter().find(|(f, _)| *f == 2).unwrap();
+                packed(&v)
+            })
+            .collect();
+        assert_eq!(values.len(), 3);
+        let total: u64 = values.iter().map(|v| v[0]).sum();
+        assert_eq!(total, 30 + 12 + 8 + 5);
+        assert!(values.contains(&vec![38, 38 * 5_000_000]));
+
+        // Locations and functions for every distinct frame.
+        assert_eq!(top.iter().filter(|(f, _)| *f == 5).count(), 6);
+        assert!(strings.iter().any(|s| s == "[py] step"));
+        assert!(strings.iter().any(|s| s == "train.py"));
+
+        let (_, comment_ids) = top.iter().find(|(f, _)| *f == 13).unwrap();
+        let comments: Vec<&str> = packed(comment_ids)
+            .into_iter()
+            .map(|i| strings[i as usize].as_str())
+            .collect();
+        assert_eq!(comments, ["pid: 42", "cmdline: python train.py"]);
+    }
+
+    #[test]
+    fn no_stacks_means_no_profile() {
+        let lines = vec!["garbage".to_string(), String::new()];
+        assert!(encode_profile(&lines, 100, 0, &[]).unwrap().is_none());
+        assert_eq!(download_filename(7, 9), "cpu-7-9.pb.gz");
+    }
+}
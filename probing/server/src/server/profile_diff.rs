@@ -0,0 +1,543 @@
+//! `POST /apis/profile/diff`: a differential flamegraph of two folded-stack
+//! profiles, after Brendan Gregg's `difffolded.pl`.
+//!
+//! Frames are sized by the current profile and colored by the change from
+//! the baseline: red where a frame grew, blue where it shrank, deeper the
+//! larger the change relative to the biggest one. Stacks only in the
+//! baseline have no width; swap the inputs to see what disappeared. With
+//! `normalize`, baseline counts are scaled to the current total first, so
+//! captures of different lengths compare by share instead of raw samples.
+//!
+//! Like `flamegraph.pl` output the SVG is interactive when loaded as a
+//! document (the web UI uses an `<iframe srcdoc>`): click a frame to zoom,
+//! click the root or "Reset Zoom" to go back, and "Search" (or `Ctrl+F`)
+//! highlights frames matching a regex with the matched share of samples.
+//!
+//! `POST /apis/profile/svg` renders a single profile the same way, with
+//! frames colored by name as in `flamegraph.pl`; `probing flamegraph --svg`
+//! uses it to write SVG files.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+
+use super::error::{ApiError, ApiResult};
+
+const WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const PAD: f64 = 10.0;
+const TITLE_HEIGHT: f64 = 36.0;
+/// Bottom row for the legend and the search's "Matched" share.
+const DETAILS_HEIGHT: f64 = 18.0;
+/// Frames narrower than this are not drawn.
+const MIN_FRAME_PX: f64 = 0.1;
+const CHAR_PX: f64 = 7.0;
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileDiffRequest {
+    /// Folded stacks (`frame;frame;frame count`) of the earlier profile.
+    pub baseline: Vec<String>,
+    /// Folded stacks of the later profile.
+    pub current: Vec<String>,
+    #[serde(default)]
+    pub normalize: bool,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// `POST /apis/profile/diff` — differential flamegraph SVG.
+pub async fn post_profile_diff(Json(req): Json<ProfileDiffRequest>) -> ApiResult<Response> {
+    let baseline = parse_folded(&req.baseline);
+    let current = parse_folded(&req.current);
+    if current.is_empty() {
+        return Err(ApiError::bad_request(
+            "current profile has no folded stacks",
+        ));
+    }
+    let tree = diff_tree(&baseline, &current, req.normalize);
+    let title = req.title.as_deref().unwrap_or("Differential flamegraph");
+    let body = render_svg(&tree, title);
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], body).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileSvgRequest {
+    /// Folded stacks of the profile.
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// `POST /apis/profile/svg` — flamegraph SVG of one profile.
+pub async fn post_profile_svg(Json(req): Json<ProfileSvgRequest>) -> ApiResult<Response> {
+    let stacks = parse_folded(&req.lines);
+    if stacks.is_empty() {
+        return Err(ApiError::bad_request("profile has no folded stacks"));
+    }
+    let tree = diff_tree(&BTreeMap::new(), &stacks, false);
+    let title = req.title.as_deref().unwrap_or("Flamegraph");
+    let body = render_plain_svg(&tree, title);
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], body).into_response())
+}
+
+/// Folded lines summed per stack; malformed lines and zero counts are skipped.
+pub fn parse_folded(lines: &[String]) -> BTreeMap<String, u64> {
+    let mut stacks = BTreeMap::new();
+    for line in lines {
+        let Some((stack, count)) = line.trim().rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse::<u64>() else {
+            continue;
+        };
+        let stack = stack.trim().trim_matches(';');
+        if count > 0 && !stack.is_empty() {
+            *stacks.entry(stack.to_string()).or_insert(0) += count;
+        }
+    }
+    stacks
+}
+
+/// One frame with inclusive baseline and current values.
+#[derive(Debug, Default, PartialEq)]
+pub struct DiffNode {
+    pub name: String,
+    pub before: f64,
+    pub after: f64,
+    pub children: BTreeMap<String, DiffNode>,
+}
+
+impl DiffNode {
+    fn insert(&mut self, frames: &[&str], before: f64, after: f64) {
+        self.before += before;
+        self.after += after;
+        if let Some((first, rest)) = frames.split_first() {
+            self.children
+                .entry((*first).to_string())
+                .or_insert_with(|| DiffNode {
+                    name: (*first).to_string(),
+                    ..Default::default()
+                })
+                .insert(rest, before, after);
+        }
+    }
+
+    fn max_abs_delta(&self) -> f64 {
+        self.children
+            .values()
+            .map(DiffNode::max_abs_delta)
+            .fold((self.after - self.before).abs(), f64::max)
+    }
+}
+
+/// The merged call tree under an `all` root.
+pub fn diff_tree(
+    baseline: &BTreeMap<String, u64>,
+    current: &BTreeMap<String, u64>,
+    normalize: bool,
+) -> DiffNode {
+    let before_total: u64 = baseline.values().sum();
+    let after_total: u64 = current.values().sum();
+    let scale = if normalize && before_total > 0 {
+        after_total as f64 / before_total as f64
+    } else {
+        1.0
+    };
+    let mut root = DiffNode {
+        name: "all".to_string(),
+        ..Default::default()
+    };
+    for (stack, &count) in baseline {
+        let frames: Vec<&str> = stack.split(';').filter(|f| !f.is_empty()).collect();
+        root.insert(&frames, count as f64 * scale, 0.0);
+    }
+    for (stack, &count) in current {
+        let frames: Vec<&str> = stack.split(';').filter(|f| !f.is_empty()).collect();
+        root.insert(&frames, 0.0, count as f64);
+    }
+    root
+}
+
+/// Fill for a frame whose value changed by `delta`: red for growth, blue for
+/// reduction, light gray when unchanged.
+pub fn diff_color(delta: f64, max_abs: f64) -> String {
+    if delta == 0.0 || max_abs <= 0.0 {
+        return "rgb(230,230,230)".to_string();
+    }
+    let shade = (220.0 - 180.0 * (delta.abs() / max_abs).min(1.0)).round() as u8;
+    if delta > 0.0 {
+        format!("rgb(255,{shade},{shade})")
+    } else {
+        format!("rgb({shade},{shade},255)")
+    }
+}
+
+/// `flamegraph.pl`'s "hot" palette, seeded by the frame name so a frame
+/// keeps its color from one render to the next.
+pub fn name_color(name: &str) -> String {
+    let hash = name
+        .bytes()
+        .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(u32::from(b)));
+    let v = |shift: u32| f64::from((hash >> shift) & 0xff) / 255.0;
+    format!(
+        "rgb({:.0},{:.0},{:.0})",
+        205.0 + 50.0 * v(0),
+        230.0 * v(8),
+        55.0 * v(16)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn depth(node: &DiffNode) -> usize {
+    node.children
+        .values()
+        .map(|c| 1 + depth(c))
+        .max()
+        .unwrap_or(0)
+}
+
+fn frame_tooltip(node: &DiffNode) -> String {
+    let delta = node.after - node.before;
+    let change = if node.before > 0.0 {
+        format!("{:+.1}%", delta / node.before * 100.0)
+    } else {
+        "new".to_string()
+    };
+    format!(
+        "{} ({:.0} → {:.0}, {change})",
+        node.name, node.before, node.after
+    )
+}
+
+/// `name` truncated with `..` to fit `width` px; empty when under 3 chars fit.
+fn frame_label(name: &str, width: f64) -> String {
+    let fits = ((width - 6.0) / CHAR_PX).floor().max(0.0) as usize;
+    if fits < 3 {
+        String::new()
+    } else if name.chars().count() > fits {
+        let mut short: String = name.chars().take(fits - 2).collect();
+        short.push_str("..");
+        short
+    } else {
+        name.to_string()
+    }
+}
+
+/// Zoom, reset and regex search over the `g.f` frames; the labels are
+/// re-fitted with the same rule as [`frame_label`].
+const INTERACTIVE_JS: &str = r##"
+var svg = document.querySelector("svg");
+var frames = [].slice.call(svg.querySelectorAll("g.f"));
+var resetBtn = document.getElementById("fg-reset");
+var searchBtn = document.getElementById("fg-search");
+var matchedText = document.getElementById("fg-matched");
+var term = "";
+function num(g, k) { return +g.getAttribute("data-" + k); }
+function place(g, x, w) {
+  var r = g.querySelector("rect"), t = g.querySelector("text"), n = g.getAttribute("data-n");
+  var fits = Math.max(0, Math.floor((w - 6) / CW));
+  g.style.display = "";
+  r.setAttribute("x", x.toFixed(2));
+  r.setAttribute("width", w.toFixed(2));
+  t.setAttribute("x", (x + 3).toFixed(2));
+  t.textContent = fits < 3 ? "" : (n.length > fits ? n.slice(0, fits - 2) + ".." : n);
+}
+function zoom(z) {
+  var zx = num(z, "x"), zw = num(z, "w"), zl = num(z, "l"), s = (W - 2 * P) / zw, e = 1e-6;
+  frames.forEach(function (g) {
+    var x = num(g, "x"), w = num(g, "w"), l = num(g, "l");
+    if (l >= zl && x >= zx - e && x + w <= zx + zw + e) place(g, P + (x - zx) * s, w * s);
+    else if (l < zl && x <= zx + e && x + w >= zx + zw - e) place(g, P, W - 2 * P);
+    else g.style.display = "none";
+  });
+  resetBtn.style.opacity = 1;
+}
+function unzoom() {
+  frames.forEach(function (g) { place(g, P + num(g, "x"), num(g, "w")); });
+  resetBtn.style.opacity = 0;
+}
+function search(q) {
+  term = q || "";
+  var re = null;
+  if (term) { try { re = new RegExp(term, "i"); } catch (err) { re = null; } }
+  var spans = [];
+  frames.forEach(function (g) {
+    var r = g.querySelector("rect"), n = g.getAttribute("data-n");
+    if (!r.hasAttribute("data-fill")) r.setAttribute("data-fill", r.getAttribute("fill"));
+    var hit = term !== "" && num(g, "l") > 0 &&
+      (re ? re.test(n) : n.toLowerCase().indexOf(term.toLowerCase()) >= 0);
+    r.setAttribute("fill", hit ? "rgb(230,0,230)" : r.getAttribute("data-fill"));
+    if (hit) spans.push([num(g, "x"), num(g, "x") + num(g, "w")]);
+  });
+  spans.sort(function (a, b) { return a[0] - b[0]; });
+  var covered = 0, end = -1;
+  spans.forEach(function (v) {
+    if (v[1] <= end) return;
+    covered += v[1] - Math.max(v[0], end);
+    end = v[1];
+  });
+  var total = frames.length ? num(frames[0], "w") : 0;
+  matchedText.textContent = term ? "Matched: " + (total > 0 ? (100 * covered / total).toFixed(1) : "0.0") + "%" : "";
+  searchBtn.textContent = term ? "Clear search" : "Search";
+}
+function promptSearch() {
+  if (term) { search(""); return; }
+  var q = window.prompt("Search frames (regex, case-insensitive)", "");
+  if (q) search(q);
+}
+svg.addEventListener("click", function (ev) {
+  var target = ev.target;
+  if (target.closest("#fg-reset")) { unzoom(); return; }
+  if (target.closest("#fg-search")) { promptSearch(); return; }
+  var g = target.closest("g.f");
+  if (!g) return;
+  if (num(g, "l") === 0) unzoom(); else zoom(g);
+});
+document.addEventListener("keydown", function (ev) {
+  if ((ev.ctrlKey || ev.metaKey) && ev.key === "f") { ev.preventDefault(); promptSearch(); }
+  else if (ev.key === "Escape") { search(""); unzoom(); }
+});
+"##;
+
+/// How frames are colored and described.
+enum Palette {
+    /// By change from the baseline, against the largest change.
+    Diff { max_abs: f64 },
+    /// By name; tooltips give the share of `total`.
+    Plain { total: f64 },
+}
+
+struct Canvas {
+    out: String,
+    px_per_unit: f64,
+    palette: Palette,
+    /// y of the root row; children are drawn above it.
+    base_y: f64,
+}
+
+impl Canvas {
+    fn frame(&mut self, node: &DiffNode, x: f64, level: usize) {
+        let width = node.after * self.px_per_unit;
+        if width < MIN_FRAME_PX {
+            return;
+        }
+        let y = self.base_y - level as f64 * FRAME_HEIGHT;
+        let (fill, tooltip) = match self.palette {
+            Palette::Diff { max_abs } => (
+                diff_color(node.after - node.before, max_abs),
+                frame_tooltip(node),
+            ),
+            Palette::Plain { total } => (
+                name_color(&node.name),
+                format!(
+                    "{} ({:.0} samples, {:.1}%)",
+                    node.name,
+                    node.after,
+                    node.after / total * 100.0
+                ),
+            ),
+        };
+        // `data-*` keep the unzoomed geometry for the embedded script.
+        let _ = write!(
+            self.out,
+            "<g class=\"f\" data-n=\"{}\" data-x=\"{:.2}\" data-w=\"{width:.2}\" \
+             data-l=\"{level}\"><title>{}</title><rect x=\"{x:.2}\" y=\"{y:.2}\" \
+             width=\"{width:.2}\" height=\"{:.1}\" fill=\"{fill}\" rx=\"2\"/>\
+             <text x=\"{:.2}\" y=\"{:.2}\">{}</text></g>",
+            escape_xml(&node.name),
+            x - PAD,
+            escape_xml(&tooltip),
+            FRAME_HEIGHT - 1.0,
+            x + 3.0,
+            y + FRAME_HEIGHT - 4.5,
+            escape_xml(&frame_label(&node.name, width)),
+        );
+        let mut child_x = x;
+        for child in node.children.values() {
+            self.frame(child, child_x, level + 1);
+            child_x += child.after * self.px_per_unit;
+        }
+    }
+}
+
+/// Standalone SVG, root at the bottom, one `<title>` tooltip per frame.
+pub fn render_svg(root: &DiffNode, title: &str) -> String {
+    let max_abs = root
+        .children
+        .values()
+        .map(DiffNode::max_abs_delta)
+        .fold(0.0, f64::max);
+    render(root, title, Palette::Diff { max_abs })
+}
+
+/// [`render_svg`] of the current profile alone, colored by frame name.
+pub fn render_plain_svg(root: &DiffNode, title: &str) -> String {
+    render(root, title, Palette::Plain { total: root.after })
+}
+
+fn render(root: &DiffNode, title: &str, palette: Palette) -> String {
+    let legend = match palette {
+        Palette::Diff { .. } => "red: grew · blue: shrank".to_string(),
+        Palette::Plain { total } => format!("{total:.0} samples"),
+    };
+    let levels = depth(root) + 1;
+    let height = TITLE_HEIGHT + levels as f64 * FRAME_HEIGHT + PAD + DETAILS_HEIGHT;
+    let mut canvas = Canvas {
+        out: String::new(),
+        px_per_unit: if root.after > 0.0 {
+            (WIDTH - 2.0 * PAD) / root.after
+        } else {
+            0.0
+        },
+        palette,
+        base_y: height - PAD - DETAILS_HEIGHT - FRAME_HEIGHT,
+    };
+    let _ = write!(
+        canvas.out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+         viewBox=\"0 0 {WIDTH} {height}\" font-family=\"Verdana,sans-serif\" font-size=\"11\">\
+         <style>g.f, #fg-reset, #fg-search {{ cursor: pointer }} g.f:hover rect {{ stroke: #000; stroke-width: 0.5 }}</style>\
+         <rect width=\"100%\" height=\"100%\" fill=\"#fafafa\"/>\
+         <text x=\"{:.1}\" y=\"22\" text-anchor=\"middle\" font-size=\"15\">{}</text>\
+         <text id=\"fg-reset\" x=\"{PAD}\" y=\"22\" opacity=\"0\">Reset Zoom</text>\
+         <text id=\"fg-search\" x=\"{:.1}\" y=\"22\" text-anchor=\"end\">Search</text>\
+         <text x=\"{PAD}\" y=\"{:.1}\" fill=\"#666\">{}</text>\
+         <text id=\"fg-matched\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\"></text>",
+        WIDTH / 2.0,
+        escape_xml(title),
+        WIDTH - PAD,
+        height - 6.0,
+        escape_xml(&legend),
+        WIDTH - PAD,
+        height - 6.0,
+    );
+    canvas.frame(root, PAD, 0);
+    let _ = write!(
+        canvas.out,
+        "<script type=\"text/ecmascript\"><![CDATA[var W = {WIDTH}, P = {PAD}, CW = {CHAR_PX};{INTERACTIVE_JS}]]></script></svg>"
+    );
+    canvas.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded(lines: &[&str]) -> BTreeMap<String, u64> {
+        parse_folded(&lines.iter().map(|l| l.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parse_sums_stacks_and_skips_bad_lines() {
+        let stacks = folded(&[
+            "main;a 3",
+            "main;a 2",
+            "main;b x",
+            "",
+            "main;c 0",
+            ";main;d; 1",
+        ]);
+        assert_eq!(stacks.get("main;a"), Some(&5));
+        assert_eq!(stacks.get("main;d"), Some(&1));
+        assert_eq!(stacks.len(), 2);
+    }
+
+    #[test]
+    fn tree_holds_inclusive_values_of_both_sides() {
+        let before = folded(&["main;a 10", "main;b 10"]);
+        let after = folded(&["main;a 30", "main;c 10"]);
+        let root = diff_tree(&before, &after, false);
+        assert_eq!((root.before, root.after), (20.0, 40.0));
+        let main = &root.children["main"];
+        assert_eq!(
+            (main.children["a"].before, main.children["a"].after),
+            (10.0, 30.0)
+        );
+        assert_eq!(
+            (main.children["b"].before, main.children["b"].after),
+            (10.0, 0.0)
+        );
+        assert_eq!(
+            (main.children["c"].before, main.children["c"].after),
+            (0.0, 10.0)
+        );
+
+        // Normalized, the baseline is scaled to 40 samples: `a` grew 20 → 30.
+        let root = diff_tree(&before, &after, true);
+        assert_eq!(root.children["main"].children["a"].before, 20.0);
+        assert_eq!(root.before, root.after);
+    }
+
+    #[test]
+    fn growth_is_red_and_reduction_is_blue() {
+        assert_eq!(diff_color(10.0, 10.0), "rgb(255,40,40)");
+        assert_eq!(diff_color(-5.0, 10.0), "rgb(130,130,255)");
+        assert_eq!(diff_color(0.0, 10.0), "rgb(230,230,230)");
+        assert_eq!(diff_color(3.0, 0.0), "rgb(230,230,230)");
+    }
+
+    #[test]
+    fn svg_sizes_frames_by_current_profile() {
+        let before = folded(&["main;a 10", "main;b 10"]);
+        let after = folded(&["main;a 30", "main;<c> 10"]);
+        let svg = render_svg(&diff_tree(&before, &after, false), "a & b");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">a &amp; b</text>"));
+        // `all` spans the full width; `a` has 3/4 of it and grew the most.
+        assert!(svg.contains("width=\"1180.00\""));
+        assert!(svg.contains("width=\"885.00\" height=\"15.0\" fill=\"rgb(255,40,40)\""));
+        assert!(svg.contains("<title>a (10 → 30, +200.0%)</title>"));
+        assert!(svg.contains("<title>&lt;c&gt; (0 → 10, new)</title>"));
+        // `b` is gone from the current profile, so it is not drawn.
+        assert!(!svg.contains("<title>b ("));
+        // Geometry for the zoom script; `a` follows the 295 px wide `<c>`.
+        assert!(svg.contains(
+            "<g class=\"f\" data-n=\"a\" data-x=\"295.00\" data-w=\"885.00\" data-l=\"2\">"
+        ));
+        assert!(svg.contains("data-n=\"&lt;c&gt;\""));
+        assert!(svg.contains("id=\"fg-search\""));
+        assert!(svg.ends_with("]]></script></svg>"));
+    }
+
+    #[test]
+    fn plain_svg_colors_by_name_and_shows_shares() {
+        let root = diff_tree(
+            &BTreeMap::new(),
+            &folded(&["main;a 30", "main;b 10"]),
+            false,
+        );
+        let svg = render_plain_svg(&root, "CPU");
+        assert!(svg.contains("<title>a (30 samples, 75.0%)</title>"));
+        assert!(svg.contains(&format!("fill=\"{}\"", name_color("a"))));
+        assert!(svg.contains(">40 samples</text>"));
+        assert!(!svg.contains("grew"));
+        // Stable per name, within the warm range.
+        assert_eq!(name_color("forward"), name_color("forward"));
+        assert!(name_color("forward").starts_with("rgb(2"));
+    }
+
+    #[test]
+    fn labels_are_truncated_to_fit() {
+        assert_eq!(frame_label("forward", 100.0), "forward");
+        // (40 - 6) / 7 = 4 characters fit.
+        assert_eq!(frame_label("forward", 40.0), "fo..");
+        assert_eq!(frame_label("forward", 20.0), "");
+    }
+}
@@ -3,28 +3,31 @@
 //! This module contains all the functions related to handling query DTOs,
 //! separated from the main server module for better organization.
 
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use probing_proto::protocol::message::Message;
 use probing_proto::protocol::query::{Data as ProtoData, Query as ProtoQuery};
 use serde_json;
 
+use crate::engine::ResultFormat;
 use crate::server::error::ApiError;
 
 /// HTTP handler wrapper for query endpoint with DTO interface
 /// This provides a stable external API while keeping the internal implementation unchanged
 #[axum::debug_handler]
 pub async fn query_dto(
+    headers: HeaderMap,
     axum::extract::Json(request_dto): axum::extract::Json<
         probing_proto::dto::query::QueryRequestDto,
     >,
 ) -> impl IntoResponse {
-    handle_query_dto(request_dto).await
+    handle_query_dto(request_dto, &headers).await
 }
 
 /// Handle query DTO processing and convert to internal format
 async fn handle_query_dto(
     request_dto: probing_proto::dto::query::QueryRequestDto,
+    headers: &HeaderMap,
 ) -> impl IntoResponse {
     if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
         return convert_engine_error_to_dto(ApiError::service_unavailable(msg)).await;
@@ -38,7 +41,7 @@ async fn handle_query_dto(
 
     // Serialize to JSON string for existing engine interface
     match serde_json::to_string(&message) {
-        Ok(json_request) => process_engine_query(json_request).await,
+        Ok(json_request) => process_engine_query(json_request, headers).await,
         Err(e) => (
             StatusCode::BAD_REQUEST,
             format!("Failed to serialize request: {}", e),
@@ -48,20 +51,23 @@ async fn handle_query_dto(
 }
 
 /// Process the engine query and convert response to DTO format
-async fn process_engine_query(json_request: String) -> axum::response::Response {
-    match crate::engine::query(json_request).await {
-        Ok(envelope) => convert_engine_response_to_dto(envelope.body, envelope.partial).await,
+async fn process_engine_query(
+    json_request: String,
+    headers: &HeaderMap,
+) -> axum::response::Response {
+    match crate::engine::query(json_request, headers, None, ResultFormat::Json).await {
+        Ok(envelope) => convert_engine_response_to_dto(&envelope.body, envelope.partial).await,
         Err(api_error) => convert_engine_error_to_dto(api_error).await,
     }
 }
 
 /// Convert engine response to DTO format
 async fn convert_engine_response_to_dto(
-    response_json: String,
+    response_json: &[u8],
     partial: bool,
 ) -> axum::response::Response {
     // Parse the response to convert to DTO format
-    match serde_json::from_str::<Message<ProtoData>>(&response_json) {
+    match serde_json::from_slice::<Message<ProtoData>>(response_json) {
         Ok(message_response) => {
             if let ProtoData::Error(err) = &message_response.payload {
                 let status = engine_error_status(err);
@@ -121,11 +127,18 @@ fn engine_error_status(err: &probing_proto::protocol::query::QueryError) -> Stat
     match err.code {
         ErrorCode::NotFound => StatusCode::NOT_FOUND,
         ErrorCode::ParseError | ErrorCode::PermissionDenied => StatusCode::BAD_REQUEST,
-        ErrorCode::TimeoutError | ErrorCode::ResourceExhausted => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::TimeoutError => StatusCode::GATEWAY_TIMEOUT,
+        ErrorCode::ResourceExhausted => StatusCode::SERVICE_UNAVAILABLE,
         ErrorCode::ExecutionError | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::Cancelled => cancelled_status(),
     }
 }
 
+/// nginx's 499 "client closed request": the query was stopped on request.
+fn cancelled_status() -> StatusCode {
+    StatusCode::from_u16(499).expect("499 is a valid status code")
+}
+
 /// Convert engine error to DTO error response
 async fn convert_engine_error_to_dto(api_error: ApiError) -> axum::response::Response {
     let status = api_error.status();
@@ -149,8 +162,10 @@ fn api_error_code(status: StatusCode) -> &'static str {
         StatusCode::NOT_FOUND => "NOT_FOUND",
         StatusCode::SERVICE_UNAVAILABLE => "SERVICE_UNAVAILABLE",
         StatusCode::BAD_GATEWAY => "BAD_GATEWAY",
+        StatusCode::GATEWAY_TIMEOUT => "GATEWAY_TIMEOUT",
         StatusCode::METHOD_NOT_ALLOWED => "METHOD_NOT_ALLOWED",
         StatusCode::PAYLOAD_TOO_LARGE => "PAYLOAD_TOO_LARGE",
+        status if status == cancelled_status() => "CANCELLED",
         _ => "INTERNAL_ERROR",
     }
 }
@@ -191,4 +206,25 @@ mod tests {
         };
         assert_eq!(engine_error_status(&err), StatusCode::NOT_FOUND);
     }
+
+    #[test]
+    fn cancelled_queries_have_their_own_code() {
+        let err = QueryError {
+            code: ErrorCode::Cancelled,
+            message: "Query cancelled".into(),
+            details: None,
+        };
+        assert_eq!(api_error_code(engine_error_status(&err)), "CANCELLED");
+    }
+
+    #[test]
+    fn timed_out_queries_are_gateway_timeouts() {
+        let err = QueryError {
+            code: ErrorCode::TimeoutError,
+            message: "Query exceeded probing.query.timeout (30s)".into(),
+            details: None,
+        };
+        assert_eq!(engine_error_status(&err), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(api_error_code(engine_error_status(&err)), "GATEWAY_TIMEOUT");
+    }
 }
@@ -0,0 +1,342 @@
+//! Snapshot mode (`probing serve-snapshot`): a read-only dashboard over a
+//! trace archive instead of a live process.
+//!
+//! The archived tables ([`DUMP_TABLES`] plus `archive.meta`) are registered
+//! in place of the live ones, so the web UI and `/query` see them under their
+//! usual names. Everything that would change or drive a process is answered
+//! with `403`: `SET`, the REPL socket, extension routes (eval, profiler
+//! controls) and any other write. `GET /apis/snapshot` tells the UI which
+//! archive it is looking at.
+
+use std::collections::BTreeMap;
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use probing_core::core::Engine;
+use probing_proto::dto::query::QueryRequestDto;
+use probing_proto::prelude::{DataFrame, Message, Query, TraceArchive};
+use serde::Serialize;
+
+use super::api::PUBLIC_API_ROUTES;
+use super::config::get_max_request_body_size;
+use super::error::ApiError;
+use super::trace_archive::{meta_frame, DUMP_TABLES, META_TABLE};
+
+/// Queries that may be posted in snapshot mode; `SET` is still refused.
+const QUERY_ROUTES: &[&str] = &["/query", "/query/dto", "/apis/chart_query"];
+
+/// The archive being served.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotInfo {
+    /// Path the archive was read from.
+    pub source: String,
+    /// Wall clock of the capture (Unix ns).
+    pub captured_ns: i64,
+    pub resource: BTreeMap<String, String>,
+    pub tables: Vec<String>,
+    pub rows: usize,
+}
+
+/// `GET /apis/snapshot` body; `snapshot: false` on a live server.
+#[derive(Debug, Serialize)]
+pub struct SnapshotStatus {
+    pub snapshot: bool,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub info: Option<SnapshotInfo>,
+}
+
+/// `GET /apis/snapshot`
+pub async fn get_snapshot(info: Option<Extension<SnapshotInfo>>) -> Json<SnapshotStatus> {
+    Json(SnapshotStatus {
+        snapshot: info.is_some(),
+        info: info.map(|Extension(info)| info),
+    })
+}
+
+/// An engine answering queries from `archive` alone.
+pub async fn snapshot_engine(
+    archive: TraceArchive,
+    source: &str,
+) -> anyhow::Result<(Engine, SnapshotInfo)> {
+    let engine = probing_core::create_engine().build().await?;
+    let mut tables: Vec<(String, DataFrame)> = archive
+        .tables
+        .into_iter()
+        .filter(|t| DUMP_TABLES.contains(&t.table.as_str()))
+        .map(|t| (t.table, t.dataframe))
+        .collect();
+    let rows = tables.iter().map(|(_, df)| df.len()).sum();
+    tables.push((
+        META_TABLE.to_string(),
+        meta_frame(&archive.clock, &archive.resource),
+    ));
+    let mut registered = engine.register_live_snapshot(tables)?;
+    registered.retain(|t| !t.ends_with(META_TABLE));
+    let info = SnapshotInfo {
+        source: source.to_string(),
+        captured_ns: archive.clock.wall_ns,
+        resource: archive.resource,
+        tables: registered,
+        rows,
+    };
+    Ok((engine, info))
+}
+
+/// The regular app, read-only, describing `info` at `/apis/snapshot`.
+pub(super) fn snapshot_app(info: SnapshotInfo) -> axum::Router {
+    super::build_app(false)
+        .layer(axum::middleware::from_fn(read_only_middleware))
+        .layer(Extension(info))
+}
+
+fn disabled(what: &str) -> Response {
+    ApiError::new(
+        StatusCode::FORBIDDEN,
+        format!("{what} is disabled in snapshot mode"),
+    )
+    .into_response()
+}
+
+/// Extension routes live under `/apis` without a public route of their own.
+fn is_extension_route(path: &str) -> bool {
+    path.strip_prefix("/apis/")
+        .is_some_and(|rest| !PUBLIC_API_ROUTES.iter().any(|(_, p)| &p[1..] == rest))
+}
+
+/// Expression of a `/query` or `/query/dto` body, if it parses.
+fn query_expr(path: &str, body: &[u8]) -> Option<String> {
+    match path {
+        "/query" => serde_json::from_slice::<Message<Query>>(body)
+            .ok()
+            .map(|m| m.payload.expr),
+        "/query/dto" => serde_json::from_slice::<QueryRequestDto>(body)
+            .ok()
+            .map(|dto| dto.expr),
+        _ => None,
+    }
+}
+
+async fn read_only_middleware(request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    if path == "/ws" {
+        return disabled("the REPL");
+    }
+    if is_extension_route(&path) {
+        return disabled(&path);
+    }
+    let method = request.method().clone();
+    if method == Method::GET || method == Method::HEAD {
+        return next.run(request).await;
+    }
+    if method != Method::POST || !QUERY_ROUTES.contains(&path.as_str()) {
+        return disabled(&format!("{method} {path}"));
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, get_max_request_body_size()).await {
+        Ok(bytes) => bytes,
+        Err(e) => return ApiError::payload_too_large(e.to_string()).into_response(),
+    };
+    if query_expr(&path, &bytes).is_some_and(|expr| crate::engine::is_set_expr(&expr)) {
+        return disabled("SET");
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// Serve `archive` read-only on `addr` until the server exits.
+pub async fn serve_snapshot(
+    archive: TraceArchive,
+    source: String,
+    addr: String,
+) -> anyhow::Result<()> {
+    let (engine, info) = snapshot_engine(archive, &source).await?;
+    log::info!(
+        "serving snapshot {source}: {} rows in {}",
+        info.rows,
+        info.tables.join(", ")
+    );
+    *probing_core::ENGINE.write().await = engine;
+    crate::engine_lifecycle::mark_engine_ready();
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    log::info!(
+        "snapshot dashboard is available on: {}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, snapshot_app(info)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::{ArchivedTable, ClockAnchor, Seq};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Two closed spans, as dumped from a traced process.
+    fn fixture() -> TraceArchive {
+        let events = DataFrame::new(
+            vec![
+                "record_type".into(),
+                "trace_id".into(),
+                "span_id".into(),
+                "name".into(),
+                "time".into(),
+                "thread_id".into(),
+            ],
+            vec![
+                Seq::SeqText(
+                    ["span_start", "span_end", "span_start", "span_end"]
+                        .map(String::from)
+                        .to_vec(),
+                ),
+                Seq::SeqI64(vec![1, 1, 1, 1]),
+                Seq::SeqI64(vec![10, 10, 11, 11]),
+                Seq::SeqText(["fwd", "fwd", "bwd", "bwd"].map(String::from).to_vec()),
+                Seq::SeqI64(vec![100, 150, 150, 400]),
+                Seq::SeqI64(vec![7, 7, 7, 7]),
+            ],
+        );
+        TraceArchive {
+            clock: ClockAnchor {
+                wall_ns: 1_700_000_000_000_000_000,
+                time_base: "unix_ns".into(),
+            },
+            resource: BTreeMap::from([("rank".into(), "3".into())]),
+            tables: vec![
+                ArchivedTable {
+                    table: "python.trace_event".into(),
+                    dataframe: events,
+                    dropped_columns: vec![],
+                },
+                // Not a dump table: left out.
+                ArchivedTable {
+                    table: "process.envs".into(),
+                    dataframe: DataFrame::new(
+                        vec!["name".into()],
+                        vec![Seq::SeqText(vec!["HOME".into()])],
+                    ),
+                    dropped_columns: vec![],
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn archived_tables_answer_queries_under_live_names() {
+        let archive = TraceArchive::decode(&fixture().encode().unwrap()).unwrap();
+        let (engine, info) = snapshot_engine(archive, "/tmp/run.bin").await.unwrap();
+        assert_eq!(info.tables, vec!["probe.python.trace_event"]);
+        assert_eq!(info.rows, 4);
+        assert_eq!(info.captured_ns, 1_700_000_000_000_000_000);
+
+        let raw = engine
+            .async_query(
+                "SELECT count(*) AS n FROM python.trace_event WHERE record_type = 'span_end'",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(raw.cols[0], Seq::SeqI64(vec![2]));
+        // Span rows are paired as they are live.
+        let spans = engine
+            .async_query(
+                "SELECT name, duration FROM python.trace_event \
+                 WHERE record_type = 'span' ORDER BY name",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(spans.cols[1], Seq::SeqI64(vec![250, 50]));
+        let rank = engine
+            .async_query("SELECT value FROM archive.meta WHERE key = 'resource.rank'")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rank.cols[0], Seq::SeqText(vec!["3".into()]));
+        assert!(engine
+            .async_query("SELECT * FROM process.envs")
+            .await
+            .is_err());
+    }
+
+    async fn request(addr: std::net::SocketAddr, method: &str, path: &str, body: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn status(response: &str) -> &str {
+        response.split(' ').nth(1).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn controls_are_disabled() {
+        let info = SnapshotInfo {
+            source: "/tmp/run.bin".into(),
+            captured_ns: 42,
+            ..Default::default()
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, snapshot_app(info)).await });
+
+        let set = serde_json::to_string(&Message::new(Query {
+            expr: "SET probing.torch.profiling = on".into(),
+            opts: None,
+        }))
+        .unwrap();
+        let dto = r#"{"expr": "set probing.sample_rate = 1"}"#;
+        for (method, path, body) in [
+            ("POST", "/query", set.as_str()),
+            ("POST", "/query/dto", dto),
+            ("GET", "/ws", ""),
+            ("POST", "/apis/pythonext/eval", "{}"),
+            ("GET", "/apis/pythonext/torch/profile", ""),
+            ("PUT", "/apis/nodes", "{}"),
+            ("POST", "/apis/trace/import", ""),
+        ] {
+            let response = request(addr, method, path, body).await;
+            assert_eq!(status(&response), "403", "{method} {path}: {response}");
+            assert!(response.contains("snapshot mode"), "{response}");
+        }
+
+        let response = request(addr, "GET", "/apis/snapshot", "").await;
+        assert_eq!(status(&response), "200", "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["snapshot"], true);
+        assert_eq!(body["captured_ns"], 42);
+        assert_eq!(body["source"], "/tmp/run.bin");
+
+        // Reads reach their handlers.
+        let select = serde_json::to_string(&Message::new(Query {
+            expr: "SELECT 1".into(),
+            opts: None,
+        }))
+        .unwrap();
+        let response = request(addr, "POST", "/query", &select).await;
+        assert_ne!(status(&response), "403", "{response}");
+    }
+
+    #[test]
+    fn only_unrouted_apis_are_extension_routes() {
+        assert!(is_extension_route("/apis/pythonext/eval"));
+        assert!(is_extension_route("/apis/torch/profile"));
+        assert!(!is_extension_route("/apis/trace/span_tree"));
+        assert!(!is_extension_route("/apis/snapshot"));
+        assert!(!is_extension_route("/query"));
+    }
+}
@@ -4,7 +4,7 @@ use axum::http::{HeaderMap, StatusCode, Uri};
 use axum::response::{IntoResponse, Response};
 use axum::{routing::get, Router};
 
-use crate::asset::{contains, index, static_files};
+use crate::asset::{contains, index, perfetto_ui, static_files, PERFETTO_UI_PREFIX};
 
 /// Static files that must not fall back to the SPA shell (avoids serving HTML as CSS/JS).
 fn is_static_asset_path(path: &str) -> bool {
@@ -21,6 +21,7 @@ fn is_static_asset_path(path: &str) -> bool {
 pub fn is_api_path(path: &str) -> bool {
     path == "/query"
         || path == "/query/dto"
+        || path == "/query/cancel"
         || path.starts_with("/apis/")
         || path.starts_with("/config/")
         || path.starts_with("/mcp")
@@ -32,6 +33,8 @@ pub fn routes() -> Router {
     Router::new()
         .route("/", get(index))
         .route("/index.html", get(index))
+        .route(PERFETTO_UI_PREFIX, get(perfetto_ui))
+        .route(&format!("{PERFETTO_UI_PREFIX}{{*path}}"), get(perfetto_ui))
 }
 
 /// SPA fallback: static asset if it exists, otherwise `index.html` for client routing.
@@ -63,6 +66,7 @@ mod tests {
     fn api_paths_are_not_spa() {
         assert!(is_api_path("/query"));
         assert!(is_api_path("/query/dto"));
+        assert!(is_api_path("/query/cancel"));
         assert!(is_api_path("/apis/nodes"));
         assert!(is_api_path("/config/server.address"));
         assert!(is_api_path("/ws"));
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
+use probing_core::proc_access::{proc_capabilities, ProcCapabilities};
 use probing_proto::prelude::*;
 
 use super::error::ApiResult;
@@ -28,14 +29,10 @@ fn public_env_vars() -> HashMap<String, String> {
 pub fn get_overview() -> Result<Process> {
     let myself = std::process::id() as i32;
 
+    // Only the calling thread when `/proc/self/task` is unreadable; see
+    // `/apis/features`.
     #[cfg(target_os = "linux")]
-    let threads = {
-        let current = procfs::process::Process::new(myself)?;
-        current
-            .tasks()
-            .map(|iter| iter.map(|r| r.map(|p| p.tid as u64).unwrap_or(0)).collect())
-            .unwrap_or_default()
-    };
+    let threads = proc_capabilities().thread_ids();
 
     #[cfg(target_os = "macos")]
     let threads = vec![];
@@ -66,6 +63,10 @@ pub async fn get_overview_json() -> ApiResult<axum::Json<Process>> {
     Ok(axum::Json(overview))
 }
 
+/// `/proc` capabilities with the degraded behavior of each missing one.
+pub async fn get_features_json() -> axum::Json<&'static ProcCapabilities> {
+    axum::Json(proc_capabilities())
+}
 
 /// Get local processes that currently expose probing memtables.
 pub fn get_local_processes() -> Result<Vec<Process>> {
@@ -142,12 +143,8 @@ fn read_proc_link(_pid: i32, _name: &str) -> String {
 
 #[cfg(target_os = "linux")]
 fn read_proc_threads(pid: i32) -> Vec<u64> {
-    std::fs::read_dir(format!("/proc/{pid}/task"))
-        .ok()
-        .into_iter()
-        .flat_map(|entries| entries.flatten())
-        .filter_map(|entry| entry.file_name().to_string_lossy().parse::<u64>().ok())
-        .collect()
+    probing_core::proc_access::list_thread_ids(&proc_capabilities().root, Some(pid))
+        .unwrap_or_default()
 }
 
 #[cfg(not(target_os = "linux"))]
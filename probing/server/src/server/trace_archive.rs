@@ -0,0 +1,350 @@
+//! Trace export/import: `GET /apis/trace/dump` snapshots span, event and metric
+//! tables into a versioned [`TraceArchive`]; `POST /apis/trace/import` replays
+//! one under its own catalog (default `replay`) so it never mixes with live data.
+
+use std::collections::BTreeMap;
+
+use axum::body::{Body, Bytes};
+use axum::extract::Query;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use datafusion::arrow::record_batch::RecordBatch;
+use futures_util::future;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use probing_core::core::Engine;
+use probing_proto::prelude::{
+    ArchivedTable, ClockAnchor, DataFrame, Seq, TraceArchive, TraceImportSummary,
+};
+use probing_proto::protocol::trace_archive::{DEFAULT_REPLAY_NAMESPACE, TRACE_ARCHIVE_VERSION};
+use serde::Deserialize;
+
+use super::error::{ApiError, ApiResult};
+use crate::engine::ENGINE;
+
+/// Tables captured by a dump: spans/events first, then metrics.
+pub const DUMP_TABLES: &[&str] = &[
+    "python.trace_event",
+    "python.torch_step_timing",
+    "cpu.utilization",
+    "gpu.utilization",
+];
+
+/// Archive metadata (resource tags, clock anchor, version) is replayed as
+/// `<namespace>.archive.meta` with `key`/`value` columns.
+pub(super) const META_TABLE: &str = "archive.meta";
+
+const RESOURCE_ENV: &[(&str, &str)] = &[
+    ("rank", "RANK"),
+    ("local_rank", "LOCAL_RANK"),
+    ("world_size", "WORLD_SIZE"),
+    ("node_rank", "NODE_RANK"),
+    ("role", "PROBING_NODE_ROLE"),
+];
+
+/// `GET /apis/trace/dump` — binary archive, streamed record batch by record
+/// batch; tables that are absent or empty in this process are left out.
+pub async fn get_trace_dump() -> ApiResult<Response> {
+    if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
+        return Err(ApiError::service_unavailable(msg));
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"probing-trace.bin\"",
+            ),
+        ],
+        trace_dump_body(),
+    )
+        .into_response())
+}
+
+/// Body of [`get_trace_dump`]. Each table is pulled from the engine as a
+/// record batch stream and written as one archive entry per batch, so neither
+/// the rows nor the encoded archive are held in full; decoding joins the
+/// entries of a table again.
+pub fn trace_dump_body() -> Body {
+    let clock = ClockAnchor {
+        wall_ns: wall_clock_ns(),
+        time_base: "unix_ns".into(),
+    };
+    let head = TraceArchive::encode_head(&clock, &resource_tags());
+    let mut first = true;
+    let entries = stream::iter(DUMP_TABLES.iter().copied())
+        .then(table_batches)
+        .flatten()
+        .filter_map(move |(table, batch)| {
+            let df = DataFrame::from_record_batches(&batch.schema(), &[batch]);
+            let entry = archived_table(table, df).map(|entry| {
+                let bytes = entry.encode_entry(first);
+                first = false;
+                bytes
+            });
+            future::ready(entry)
+        });
+    let chunks = stream::once(future::ready(head))
+        .chain(entries)
+        .chain(stream::once(future::ready(Ok(
+            TraceArchive::ENCODED_TAIL.to_vec()
+        ))))
+        .map(|chunk| {
+            chunk.map_err(|e| {
+                log::error!("trace dump: encoding failed: {e}");
+                std::io::Error::other(e.to_string())
+            })
+        });
+    Body::from_stream(chunks)
+}
+
+/// Record batches of `table` as the engine produces them; none when the table
+/// is missing, and the rest of the table is dropped after a failing batch.
+async fn table_batches(table: &'static str) -> BoxStream<'static, (&'static str, RecordBatch)> {
+    let planned = ENGINE
+        .read()
+        .await
+        .sql(&format!("SELECT * FROM {table}"))
+        .await;
+    let batches = match planned {
+        Ok(df) => df.execute_stream().await,
+        Err(err) => Err(err),
+    };
+    match batches {
+        Ok(batches) => batches
+            .take_while(move |batch| {
+                if let Err(err) = batch {
+                    log::warn!("trace dump: {table} cut short: {err}");
+                }
+                future::ready(batch.is_ok())
+            })
+            .filter_map(move |batch| future::ready(batch.ok().map(|batch| (table, batch))))
+            .boxed(),
+        Err(err) => {
+            log::debug!("trace dump: skipping {table}: {err}");
+            stream::empty().boxed()
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TraceImportParams {
+    /// Catalog to load into; defaults to `replay`.
+    pub namespace: Option<String>,
+}
+
+/// `POST /apis/trace/import?namespace=` — admin only (see `auth::require_admin`).
+pub async fn post_trace_import(
+    headers: HeaderMap,
+    Query(params): Query<TraceImportParams>,
+    body: Bytes,
+) -> ApiResult<Json<TraceImportSummary>> {
+    crate::auth::require_admin(&headers)
+        .await
+        .map_err(|status| match status {
+            StatusCode::FORBIDDEN => ApiError::new(
+                status,
+                "trace import is disabled: configure server.auth_token to enable it",
+            ),
+            _ => ApiError::new(status, "trace import requires the admin token"),
+        })?;
+    if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
+        return Err(ApiError::service_unavailable(msg));
+    }
+    let namespace = params
+        .namespace
+        .as_deref()
+        .map(str::trim)
+        .filter(|ns| !ns.is_empty())
+        .unwrap_or(DEFAULT_REPLAY_NAMESPACE);
+    let archive = TraceArchive::decode(&body)
+        .map_err(|e| ApiError::bad_request(format!("invalid trace archive: {e}")))?;
+    let engine = ENGINE.read().await;
+    Ok(Json(import_archive(&engine, namespace, archive)?))
+}
+
+fn import_archive(
+    engine: &Engine,
+    namespace: &str,
+    archive: TraceArchive,
+) -> ApiResult<TraceImportSummary> {
+    validate_namespace(namespace)?;
+    let mut tables: Vec<(String, DataFrame)> = archive
+        .tables
+        .into_iter()
+        .filter(|t| DUMP_TABLES.contains(&t.table.as_str()))
+        .map(|t| (t.table, t.dataframe))
+        .collect();
+    let rows = tables.iter().map(|(_, df)| df.len()).sum();
+    tables.push((
+        META_TABLE.to_string(),
+        meta_frame(&archive.clock, &archive.resource),
+    ));
+    let mut tables = engine
+        .register_snapshot(namespace, tables)
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    tables.retain(|t| !t.ends_with(META_TABLE));
+    Ok(TraceImportSummary {
+        namespace: namespace.to_string(),
+        version: TRACE_ARCHIVE_VERSION,
+        tables,
+        rows,
+    })
+}
+
+fn validate_namespace(ns: &str) -> ApiResult<()> {
+    let valid = ns.starts_with(|c: char| c.is_ascii_lowercase())
+        && ns
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if !valid {
+        return Err(ApiError::bad_request(format!(
+            "invalid namespace {ns:?} (lowercase letters, digits and `_`)"
+        )));
+    }
+    if ns == "probe" || ns == "global" {
+        return Err(ApiError::bad_request(format!(
+            "namespace {ns:?} is reserved for live data"
+        )));
+    }
+    Ok(())
+}
+
+/// Drop columns the engine could not convert (`Seq::Nil`) so the frame stays
+/// rectangular; `None` for empty tables.
+pub(super) fn archived_table(table: &str, df: DataFrame) -> Option<ArchivedTable> {
+    if df.is_empty() {
+        return None;
+    }
+    let mut kept = DataFrame::default();
+    let mut dropped_columns = vec![];
+    for (name, col) in df.names.into_iter().zip(df.cols) {
+        if matches!(col, Seq::Nil) {
+            dropped_columns.push(name);
+        } else {
+            kept.names.push(name);
+            kept.cols.push(col);
+        }
+    }
+    Some(ArchivedTable {
+        table: table.to_string(),
+        dataframe: kept,
+        dropped_columns,
+    })
+}
+
+pub(super) fn resource_tags() -> BTreeMap<String, String> {
+    let mut tags = BTreeMap::new();
+    if let Ok(host) = crate::report::get_hostname() {
+        tags.insert("host".to_string(), host);
+    }
+    tags.insert("pid".to_string(), std::process::id().to_string());
+    tags.insert(
+        "probing_version".to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    );
+    for (tag, var) in RESOURCE_ENV {
+        if let Some(value) = std::env::var(var).ok().filter(|v| !v.trim().is_empty()) {
+            tags.insert(tag.to_string(), value);
+        }
+    }
+    tags
+}
+
+pub(super) fn wall_clock_ns() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or_default()
+}
+
+pub(super) fn meta_frame(clock: &ClockAnchor, resource: &BTreeMap<String, String>) -> DataFrame {
+    let mut keys = vec![
+        "archive.version".to_string(),
+        "clock.wall_ns".to_string(),
+        "clock.time_base".to_string(),
+    ];
+    let mut values = vec![
+        TRACE_ARCHIVE_VERSION.to_string(),
+        clock.wall_ns.to_string(),
+        clock.time_base.clone(),
+    ];
+    for (key, value) in resource {
+        keys.push(format!("resource.{key}"));
+        values.push(value.clone());
+    }
+    DataFrame::new(
+        vec!["key".into(), "value".into()],
+        vec![Seq::SeqText(keys), Seq::SeqText(values)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events() -> DataFrame {
+        DataFrame::new(
+            vec!["name".into(), "time".into(), "attributes".into()],
+            vec![
+                Seq::SeqText(vec!["step".into(), "forward".into(), "backward".into()]),
+                Seq::SeqI64(vec![100, 110, 150]),
+                Seq::Nil,
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn imported_archive_answers_queries_like_the_source() {
+        let table = archived_table("python.trace_event", events()).unwrap();
+        assert_eq!(table.dropped_columns, vec!["attributes"]);
+        let archive = TraceArchive {
+            resource: BTreeMap::from([("rank".into(), "1".into())]),
+            tables: vec![table.clone()],
+            ..Default::default()
+        };
+        let archive = TraceArchive::decode(&archive.encode().unwrap()).unwrap();
+
+        let engine = Engine::builder().build().await.unwrap();
+        let summary = import_archive(&engine, "replay", archive).unwrap();
+        assert_eq!(summary.tables, vec!["replay.python.trace_event"]);
+        assert_eq!(summary.rows, 3);
+
+        let replayed = engine
+            .async_query("SELECT name, time FROM replay.python.trace_event")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replayed, table.dataframe);
+        let meta = engine
+            .async_query("SELECT value FROM replay.archive.meta WHERE key = 'resource.rank'")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.cols[0], Seq::SeqText(vec!["1".into()]));
+    }
+
+    #[tokio::test]
+    async fn rejects_reserved_or_malformed_namespaces() {
+        let engine = Engine::builder().build().await.unwrap();
+        for ns in ["probe", "global", "Replay", "re play", "1x"] {
+            let err = import_archive(&engine, ns, TraceArchive::default()).unwrap_err();
+            assert_eq!(err.status(), StatusCode::BAD_REQUEST, "{ns}");
+        }
+    }
+
+    #[tokio::test]
+    async fn import_requires_admin_token() {
+        let err = post_trace_import(
+            HeaderMap::new(),
+            Query(TraceImportParams::default()),
+            Bytes::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.status(),
+            StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED
+        ));
+    }
+}
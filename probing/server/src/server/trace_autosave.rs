@@ -0,0 +1,422 @@
+//! Background autosave of `python.trace_event` to a directory
+//! (`probing.trace.autosave`, see [`probing_core::trace::autosave`]).
+//!
+//! Each round reads the rows stamped since the previous round, writes them as
+//! one zstd-compressed [`TraceArchive`] segment (`segment-000042.prbtrace.zst`)
+//! and rewrites `manifest.json`. Both go through a temporary file and a
+//! rename, so a process killed mid-write leaves the previous state intact;
+//! `probing analyze --import DIR` reads the directory back.
+//!
+//! A row's stamp is its `time`, or `end_time` for paired `span` rows, which
+//! are only saved once closed. Rows stamped within [`GRACE`] of now wait for
+//! the next round, so appends racing the query are not split. Rows can also
+//! land late — a span recorded whole when it closes writes its start row with
+//! the start time — so every round re-reads [`LATE_WINDOW`] before the
+//! previous cut and skips the rows it already wrote, matched by content. Rows
+//! that show up further behind than that are not saved.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::Context;
+use probing_core::trace::{autosave_config, mark_exported_through, AutosaveConfig, Timestamp};
+use probing_proto::prelude::{
+    AutosaveManifest, AutosaveSegment, ClockAnchor, DataFrame, Ele, TraceArchive,
+};
+use probing_proto::protocol::trace_archive::AUTOSAVE_MANIFEST;
+
+use super::trace_archive::{archived_table, resource_tags, wall_clock_ns};
+use super::SERVER_RUNTIME;
+use crate::engine::ENGINE;
+
+const TABLE: &str = "python.trace_event";
+/// Rows this fresh are left for the next round.
+pub const GRACE: Duration = Duration::from_secs(2);
+/// How far before the previous cut each round looks for late rows.
+pub const LATE_WINDOW: Duration = Duration::from_secs(600);
+/// Poll period while autosave is off.
+const IDLE_POLL: Duration = Duration::from_secs(5);
+const ZSTD_LEVEL: i32 = 3;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Start the autosave loop once per process; it idles while the option is unset.
+pub fn start_trace_autosave() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    SERVER_RUNTIME.spawn(async {
+        let mut saver: Option<Autosaver> = None;
+        loop {
+            // A detach wakes the loop for one last round.
+            if super::detach::is_detached() {
+                break;
+            }
+            let wait = autosave_config().map_or(IDLE_POLL, |c| c.interval);
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = super::detach::detached() => {}
+            }
+            let Some(config) = autosave_config() else {
+                saver = None;
+                continue;
+            };
+            // A new directory starts over with a full segment.
+            if saver.as_ref().is_none_or(|s| s.dir.path != config.dir) {
+                match SegmentDir::open(&config.dir) {
+                    Ok(dir) => {
+                        saver = Some(Autosaver {
+                            cursor: ExportCursor::default(),
+                            dir,
+                        })
+                    }
+                    Err(err) => {
+                        log::warn!("trace autosave: {err:#}");
+                        continue;
+                    }
+                }
+            }
+            if let Some(saver) = saver.as_mut() {
+                match saver.save_once(&config).await {
+                    Ok(Some(segment)) => log::debug!(
+                        "trace autosave: wrote {} ({} rows)",
+                        segment.file,
+                        segment.rows
+                    ),
+                    Ok(None) => {}
+                    Err(err) => log::warn!("trace autosave failed: {err:#}"),
+                }
+            }
+        }
+    });
+}
+
+struct Autosaver {
+    cursor: ExportCursor,
+    dir: SegmentDir,
+}
+
+impl Autosaver {
+    /// One round; `None` when there was nothing new to write.
+    async fn save_once(
+        &mut self,
+        config: &AutosaveConfig,
+    ) -> anyhow::Result<Option<AutosaveSegment>> {
+        if crate::engine_lifecycle::engine_not_ready_message().is_some() {
+            return Ok(None);
+        }
+        let cut = Timestamp::now().0 as i64 - GRACE.as_nanos() as i64;
+        let sql = export_sql(self.cursor.floor());
+        let df = ENGINE
+            .read()
+            .await
+            .async_query(sql)
+            .await?
+            .unwrap_or_default();
+        let rows = stamped_rows(&df);
+        let picked = self.cursor.select(&rows, cut);
+        let segment = match archived_table(TABLE, df.take_rows(&picked)) {
+            Some(table) => {
+                let archive = TraceArchive {
+                    clock: ClockAnchor {
+                        wall_ns: wall_clock_ns(),
+                        time_base: "unix_ns".into(),
+                    },
+                    resource: resource_tags(),
+                    tables: vec![table],
+                };
+                Some(self.dir.write(&archive, cut, config)?)
+            }
+            None => None,
+        };
+        self.cursor.commit(&rows, &picked, cut);
+        mark_exported_through(cut);
+        Ok(segment)
+    }
+}
+
+/// Raw rows and closed `span` rows stamped after `floor` (all of them on the
+/// first round).
+fn export_sql(floor: Option<i64>) -> String {
+    match floor {
+        Some(floor) => format!(
+            "SELECT * FROM {TABLE} WHERE (record_type <> 'span' AND time > {floor}) \
+             OR (record_type = 'span' AND end_time > {floor})"
+        ),
+        None => {
+            format!("SELECT * FROM {TABLE} WHERE record_type <> 'span' OR end_time IS NOT NULL")
+        }
+    }
+}
+
+/// `(stamp, key)` per row of `df`. Identical rows get distinct keys by
+/// occurrence, so they are saved as often as they were recorded.
+fn stamped_rows(df: &DataFrame) -> Vec<(i64, u64)> {
+    let record_type = df.col_index("record_type");
+    let mut occurrences: HashMap<u64, u64> = HashMap::new();
+    df.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let is_span =
+                record_type.is_some_and(|c| matches!(&row[c], Ele::Text(t) if t == "span"));
+            let column = if is_span { "end_time" } else { "time" };
+            let stamp = df.scalar_i64(column, i).unwrap_or_default();
+            let mut hasher = DefaultHasher::new();
+            row.iter().for_each(|ele| hash_ele(ele, &mut hasher));
+            let content = hasher.finish();
+            let n = occurrences.entry(content).or_default();
+            *n += 1;
+            let mut hasher = DefaultHasher::new();
+            (content, *n).hash(&mut hasher);
+            (stamp, hasher.finish())
+        })
+        .collect()
+}
+
+fn hash_ele(ele: &Ele, state: &mut impl Hasher) {
+    match ele {
+        Ele::Nil => 0u8.hash(state),
+        Ele::BOOL(x) => (1u8, x).hash(state),
+        Ele::I32(x) => (2u8, x).hash(state),
+        Ele::I64(x) => (3u8, x).hash(state),
+        Ele::F32(x) => (4u8, x.to_bits()).hash(state),
+        Ele::F64(x) => (5u8, x.to_bits()).hash(state),
+        Ele::Text(x) => (6u8, x).hash(state),
+        Ele::Url(x) => (7u8, x).hash(state),
+        Ele::DataTime(x) => (8u8, x).hash(state),
+        Ele::DateTime(x) => (9u8, x).hash(state),
+    }
+}
+
+/// Position of the autosave stream: the cut of the last written round and
+/// the keys written within [`LATE_WINDOW`] of it.
+#[derive(Debug, Default)]
+struct ExportCursor {
+    through: Option<i64>,
+    seen: HashMap<u64, i64>,
+}
+
+impl ExportCursor {
+    /// Rows stamped at or before this were settled by earlier rounds.
+    fn floor(&self) -> Option<i64> {
+        self.through
+            .map(|t| t.saturating_sub(LATE_WINDOW.as_nanos() as i64))
+    }
+
+    /// Indices of `rows` that belong in the segment cut at `cut`.
+    fn select(&self, rows: &[(i64, u64)], cut: i64) -> Vec<usize> {
+        let floor = self.floor();
+        let mut batch = HashSet::new();
+        rows.iter()
+            .enumerate()
+            .filter(|(_, (stamp, key))| {
+                *stamp <= cut
+                    && floor.is_none_or(|floor| *stamp > floor)
+                    && !self.seen.contains_key(key)
+                    && batch.insert(*key)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Advance past a round whose `picked` rows are on disk.
+    fn commit(&mut self, rows: &[(i64, u64)], picked: &[usize], cut: i64) {
+        for &i in picked {
+            let (stamp, key) = rows[i];
+            self.seen.insert(key, stamp);
+        }
+        self.through = Some(self.through.map_or(cut, |t| t.max(cut)));
+        if let Some(floor) = self.floor() {
+            self.seen.retain(|_, stamp| *stamp > floor);
+        }
+    }
+}
+
+/// An autosave directory and its manifest.
+struct SegmentDir {
+    path: PathBuf,
+    manifest: AutosaveManifest,
+}
+
+impl SegmentDir {
+    /// Create `path` if needed; segments of an earlier run are kept and
+    /// numbering continues after them.
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(path)
+            .with_context(|| format!("cannot create {}", path.display()))?;
+        let manifest_path = path.join(AUTOSAVE_MANIFEST);
+        let manifest = match std::fs::read(&manifest_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+                log::warn!(
+                    "trace autosave: ignoring unreadable {}: {err}",
+                    manifest_path.display()
+                );
+                AutosaveManifest::new()
+            }),
+            Err(_) => AutosaveManifest::new(),
+        };
+        Ok(SegmentDir {
+            path: path.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// Write one segment, then the manifest, then delete rotated-out files.
+    fn write(
+        &mut self,
+        archive: &TraceArchive,
+        through_ns: i64,
+        config: &AutosaveConfig,
+    ) -> anyhow::Result<AutosaveSegment> {
+        let seq = self.manifest.next_seq();
+        let file = format!("segment-{seq:06}.prbtrace.zst");
+        let bytes = zstd::encode_all(archive.encode()?.as_slice(), ZSTD_LEVEL)?;
+        write_atomic(&self.path.join(&file), &bytes)?;
+        let segment = AutosaveSegment {
+            file,
+            seq,
+            rows: archive.row_count(),
+            bytes: bytes.len() as u64,
+            through_ns,
+            written_ns: wall_clock_ns(),
+        };
+        self.manifest.segments.push(segment.clone());
+        let dropped = self.manifest.rotate(config.max_segments, config.max_bytes);
+        write_atomic(
+            &self.path.join(AUTOSAVE_MANIFEST),
+            &serde_json::to_vec_pretty(&self.manifest)?,
+        )?;
+        for old in dropped {
+            let _ = std::fs::remove_file(self.path.join(old.file));
+        }
+        Ok(segment)
+    }
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, bytes).with_context(|| format!("cannot write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("cannot rename to {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    const SEC: i64 = 1_000_000_000;
+
+    /// Raw rows (`record_type`, `time`, `name`) as the table returns them.
+    fn frame(rows: &[(&str, i64, &str)]) -> DataFrame {
+        DataFrame::new(
+            vec!["record_type".into(), "time".into(), "name".into()],
+            vec![
+                Seq::SeqText(rows.iter().map(|r| r.0.to_string()).collect()),
+                Seq::SeqI64(rows.iter().map(|r| r.1).collect()),
+                Seq::SeqText(rows.iter().map(|r| r.2.to_string()).collect()),
+            ],
+        )
+    }
+
+    fn names(df: &DataFrame) -> Vec<String> {
+        let name = df.col_index("name").unwrap();
+        df.iter().map(|row| row[name].to_string()).collect()
+    }
+
+    #[test]
+    fn rounds_save_every_row_once_including_late_and_repeated_rows() {
+        // (written at, row): the table as it grows over time.
+        let arrivals = [
+            (1, ("event", SEC, "a")),
+            (2, ("event", 2 * SEC, "b")),
+            (3, ("event", 2 * SEC, "b")), // an identical second record
+            (9, ("event", 9 * SEC, "c")),
+            (11, ("event", 10 * SEC, "d")), // within GRACE of the cut at 11s
+            (25, ("span_start", 4 * SEC, "e")), // recorded on close, 21s late
+            (26, ("event", 26 * SEC, "f")),
+            (700, ("span_start", 30 * SEC, "g")), // behind the window of the 650s round
+            (900, ("event", 899 * SEC, "h")),
+        ];
+        let mut cursor = ExportCursor::default();
+        let mut saved = vec![];
+        for now in [11, 12, 20, 30, 30, 650, 800, 1000] {
+            let floor = cursor.floor();
+            let table: Vec<_> = arrivals
+                .iter()
+                .filter(|(at, (_, time, _))| *at <= now && floor.is_none_or(|f| *time > f))
+                .map(|(_, row)| *row)
+                .collect();
+            let df = frame(&table);
+            let rows = stamped_rows(&df);
+            let cut = now * SEC - GRACE.as_nanos() as i64;
+            let picked = cursor.select(&rows, cut);
+            saved.extend(names(&df.take_rows(&picked)));
+            cursor.commit(&rows, &picked, cut);
+        }
+        assert_eq!(saved, ["a", "b", "b", "c", "d", "e", "f", "h"]);
+        assert!(
+            cursor.seen.len() <= 1,
+            "keys outside the window are dropped"
+        );
+    }
+
+    #[test]
+    fn closed_spans_are_stamped_by_their_end() {
+        let df = DataFrame::new(
+            vec!["record_type".into(), "time".into(), "end_time".into()],
+            vec![
+                Seq::SeqText(vec!["span_start".into(), "span".into()]),
+                Seq::SeqI64(vec![5, 5]),
+                Seq::SeqI64(vec![0, 90]),
+            ],
+        );
+        let stamps: Vec<i64> = stamped_rows(&df).iter().map(|r| r.0).collect();
+        assert_eq!(stamps, [5, 90]);
+        assert!(export_sql(None).contains("end_time IS NOT NULL"));
+        assert!(export_sql(Some(7)).contains("time > 7"));
+    }
+
+    #[test]
+    fn segments_are_written_rotated_and_listed_in_the_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("autosave");
+        let config = AutosaveConfig {
+            interval: Duration::from_secs(60),
+            dir: path.clone(),
+            max_segments: 2,
+            max_bytes: u64::MAX,
+        };
+        let mut dir = SegmentDir::open(&path).unwrap();
+        for (i, name) in ["a", "b", "c"].into_iter().enumerate() {
+            let archive = TraceArchive {
+                tables: vec![archived_table(TABLE, frame(&[("event", i as i64, name)])).unwrap()],
+                ..Default::default()
+            };
+            dir.write(&archive, i as i64, &config).unwrap();
+        }
+
+        // A restart picks the manifest up again.
+        let manifest = SegmentDir::open(&path).unwrap().manifest;
+        let files: Vec<_> = manifest.segments.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(
+            files,
+            ["segment-000002.prbtrace.zst", "segment-000003.prbtrace.zst"]
+        );
+        assert!(!path.join("segment-000001.prbtrace.zst").exists());
+
+        let parts: Vec<TraceArchive> = manifest
+            .segments
+            .iter()
+            .map(|s| {
+                let bytes = zstd::decode_all(&std::fs::read(path.join(&s.file)).unwrap()[..]);
+                TraceArchive::decode(&bytes.unwrap()).unwrap()
+            })
+            .collect();
+        let merged = TraceArchive::merge(parts);
+        assert_eq!(names(&merged.table(TABLE).unwrap().dataframe), ["b", "c"]);
+    }
+}
@@ -0,0 +1,208 @@
+//! `GET /apis/trace/chrome-tracing/download` — the chrome-tracing export of
+//! `/apis/pythonext/trace/chrome-tracing` as a file to save and share.
+//!
+//! Query parameters pass through to the export (`limit`, `trace_id`, span
+//! filters); `gzip=1` compresses the body on the fly (`Content-Encoding:
+//! gzip`). Chunks are forwarded as the export yields them, so a large
+//! trace is never held here in full.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+
+use super::api::extension::extension_manager;
+use super::api::response::status_for_extension_body;
+use super::error::{ApiError, ApiResult};
+
+const CHROME_TRACING_PATH: &str = "/pythonext/trace/chrome-tracing";
+
+type ByteStream = BoxStream<'static, std::io::Result<Vec<u8>>>;
+
+/// `trace-<pid>-<unix seconds>.json`
+fn download_filename(pid: u32, unix_secs: u64) -> String {
+    format!("trace-{pid}-{unix_secs}.json")
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// Gzip `chunks` as they arrive: each output chunk is what the encoder has
+/// produced so far, the gzip trailer comes last.
+fn gzip_chunks(chunks: ByteStream) -> ByteStream {
+    let encoder = GzEncoder::new(Vec::new(), Compression::fast());
+    stream::unfold(
+        (chunks, Some(encoder)),
+        |(mut chunks, encoder)| async move {
+            let mut encoder = encoder?;
+            loop {
+                match chunks.next().await {
+                    Some(Ok(chunk)) => {
+                        if let Err(e) = encoder.write_all(&chunk) {
+                            return Some((Err(e), (chunks, None)));
+                        }
+                        let out = std::mem::take(encoder.get_mut());
+                        if !out.is_empty() {
+                            return Some((Ok(out), (chunks, Some(encoder))));
+                        }
+                    }
+                    Some(Err(e)) => return Some((Err(e), (chunks, None))),
+                    None => return Some((encoder.finish(), (chunks, None))),
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+/// An attachment response streaming `chunks` (a chrome-tracing document).
+fn attachment(chunks: ByteStream, filename: &str, gzip: bool) -> Response {
+    let disposition = format!("attachment; filename=\"{filename}\"");
+    let mut response = if gzip {
+        Body::from_stream(gzip_chunks(chunks))
+    } else {
+        Body::from_stream(chunks)
+    }
+    .into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    if let Ok(value) = header::HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    if gzip {
+        headers.insert(
+            header::CONTENT_ENCODING,
+            header::HeaderValue::from_static("gzip"),
+        );
+    }
+    response
+}
+
+/// `GET /apis/trace/chrome-tracing/download?limit=&trace_id=&gzip=`
+pub async fn get_chrome_tracing_download(
+    Query(mut params): Query<HashMap<String, String>>,
+) -> ApiResult<Response> {
+    let gzip = params.remove("gzip").is_some_and(|v| is_truthy(&v));
+    // Always the JSON document: the file is named `.json`.
+    params.remove("format");
+    let Some(eem) = extension_manager().await else {
+        return Err(ApiError::not_found("Extension manager not available"));
+    };
+    let mut chunks = eem
+        .call_stream(CHROME_TRACING_PATH, &params, &[])
+        .await
+        .map_err(ApiError::from_engine)?;
+
+    // Errors come back as one JSON object before any trace data: answer them
+    // as such instead of as a file.
+    let first = match chunks.next().await {
+        Some(chunk) => chunk.map_err(ApiError::from_engine)?,
+        None => vec![],
+    };
+    let status = status_for_extension_body("application/json", &first);
+    if status != StatusCode::OK {
+        return Ok((status, [(header::CONTENT_TYPE, "application/json")], first).into_response());
+    }
+
+    let rest = chunks.map(|chunk| {
+        chunk.map_err(|e| {
+            log::error!("chrome-tracing download failed: {e}");
+            std::io::Error::other(e.to_string())
+        })
+    });
+    let unix_secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let filename = download_filename(std::process::id(), unix_secs);
+    Ok(attachment(
+        stream::iter([Ok(first)]).chain(rest).boxed(),
+        &filename,
+        gzip,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use http_body_util::BodyExt;
+    use std::io::Read;
+
+    fn document_chunks() -> ByteStream {
+        let mut chunks = vec![br#"{"displayTimeUnit": "ms", "traceEvents": ["#.to_vec()];
+        for i in 0..500 {
+            let sep = if i == 0 { "\n" } else { ",\n" };
+            chunks.push(
+                format!(r#"{sep}{{"name":"step","ph":"X","ts":{i},"dur":1,"pid":1,"tid":7}}"#)
+                    .into_bytes(),
+            );
+        }
+        chunks.push(b"\n]}".to_vec());
+        stream::iter(chunks.into_iter().map(Ok)).boxed()
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn gzip_download_round_trips() {
+        let response = attachment(document_chunks(), "trace-1-2.json", true);
+        let headers = response.headers();
+        assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        assert_eq!(
+            headers[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"trace-1-2.json\""
+        );
+
+        let compressed = body_bytes(response).await;
+        let mut json = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut json)
+            .unwrap();
+        assert!(compressed.len() < json.len());
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let events = doc["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 500);
+        assert_eq!(events[499]["ts"], 499);
+    }
+
+    #[tokio::test]
+    async fn plain_download_is_the_document() {
+        let response = attachment(document_chunks(), "trace-1-2.json", false);
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        let doc: serde_json::Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(doc["traceEvents"].as_array().unwrap().len(), 500);
+    }
+
+    #[test]
+    fn filename_and_flag() {
+        assert_eq!(
+            download_filename(42, 1_700_000_000),
+            "trace-42-1700000000.json"
+        );
+        assert!(is_truthy("1") && is_truthy("true") && !is_truthy("0"));
+    }
+}
@@ -0,0 +1,128 @@
+//! `GET /apis/trace/flamegraph[/json]`: the recorded spans folded into a
+//! flamegraph of self time per stack of span names.
+//!
+//! Spans are selected with the parameters of `/apis/trace/span_tree`
+//! (`trace_id`, `name`, `phase`, `thread_id`, `start_ts`/`end_ts`,
+//! `limit`), folded by [`fold_span_tree`] and rendered by the flamegraph
+//! used for pprof: interactive HTML, or the JSON payload of the Web UI
+//! `FlamegraphView`. Values are nanoseconds of self time.
+
+use axum::extract::Query;
+use axum::http::header;
+use axum::response::{Html, IntoResponse, Response};
+use probing_core::trace::{fold_span_tree, SpanNode};
+use probing_python::features::flamegraph::{Flamegraph, FlamegraphKind, FlamegraphOptions};
+use serde_json::json;
+
+use super::error::{ApiError, ApiResult};
+use super::trace_tree::{query_span_tree, SpanTreeParams};
+
+const PROFILE: &str = "spans";
+const TITLE: &str = "Span self time";
+
+fn options(trace_id: Option<i64>) -> FlamegraphOptions {
+    FlamegraphOptions {
+        title: TITLE.to_string(),
+        count_name: "ns".to_string(),
+        kind: FlamegraphKind::Classic,
+        subtitle: match trace_id {
+            Some(id) => format!("trace {id} · span duration minus child spans"),
+            None => "recent spans · span duration minus child spans".to_string(),
+        },
+        metric: None,
+        profile: Some(PROFILE.to_string()),
+    }
+}
+
+fn span_flamegraph(roots: &[SpanNode]) -> Option<Flamegraph> {
+    Flamegraph::from_folded_lines(&fold_span_tree(roots))
+}
+
+/// Flamegraph JSON for `roots`; an empty payload with `emptyMessage` when
+/// no span has self time.
+fn span_flamegraph_json(roots: &[SpanNode], trace_id: Option<i64>) -> String {
+    let options = options(trace_id);
+    match span_flamegraph(roots) {
+        Some(fg) => fg.json_payload(&options),
+        None => json!({
+            "profile": PROFILE,
+            "title": TITLE,
+            "subtitle": options.subtitle,
+            "countName": options.count_name,
+            "total": 0,
+            "width": 1200.0,
+            "frameHeight": 18.0,
+            "frames": [],
+            "emptyMessage": "no finished spans; record some with probing.tracing.span()",
+        })
+        .to_string(),
+    }
+}
+
+/// `GET /apis/trace/flamegraph/json?trace_id=&…` — payload for the Web UI.
+pub async fn get_span_flamegraph_json(Query(params): Query<SpanTreeParams>) -> ApiResult<Response> {
+    let roots = query_span_tree(&params).await?;
+    let body = span_flamegraph_json(&roots, params.trace_id);
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// `GET /apis/trace/flamegraph?trace_id=&…` — interactive HTML.
+pub async fn get_span_flamegraph(Query(params): Query<SpanTreeParams>) -> ApiResult<Html<String>> {
+    let roots = query_span_tree(&params).await?;
+    span_flamegraph(&roots)
+        .map(|fg| Html(fg.render_html(&options(params.trace_id))))
+        .ok_or_else(|| ApiError::not_found("no finished spans to fold"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_core::trace::{build_span_tree, SpanRecord};
+
+    fn row(span_id: i64, parent_id: i64, name: &str, time: i64, end: i64) -> SpanRecord {
+        SpanRecord {
+            record_type: "span".into(),
+            trace_id: 1,
+            span_id,
+            parent_id: Some(parent_id),
+            name: name.into(),
+            time,
+            end_time: Some(end),
+            thread_id: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn payload_frames_carry_self_time() {
+        let roots = build_span_tree(vec![
+            row(1, -1, "step", 0, 1_000),
+            row(2, 1, "forward", 100, 400),
+            row(3, 1, "backward", 500, 900),
+        ]);
+        let payload: serde_json::Value =
+            serde_json::from_str(&span_flamegraph_json(&roots, Some(1))).unwrap();
+        assert_eq!(payload["profile"], "spans");
+        assert_eq!(payload["countName"], "ns");
+        assert_eq!(payload["total"], 1_000);
+        let value = |name: &str| {
+            payload["frames"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|f| f["name"] == name)
+                .map(|f| f["value"].as_u64().unwrap())
+        };
+        assert_eq!(value("step"), Some(1_000));
+        assert_eq!(value("forward"), Some(300));
+        assert_eq!(value("backward"), Some(400));
+    }
+
+    #[test]
+    fn no_spans_is_an_empty_payload() {
+        let payload: serde_json::Value =
+            serde_json::from_str(&span_flamegraph_json(&[], None)).unwrap();
+        assert_eq!(payload["total"], 0);
+        assert!(payload["emptyMessage"].is_string());
+    }
+}
@@ -0,0 +1,65 @@
+//! Background sweep enforcing `probing.trace.retention_seconds` and
+//! `probing.trace.max_traces` on `python.trace_event`; the policy itself is
+//! in [`probing_core::trace::retention`].
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use probing_core::trace::retention::SWEEP_INTERVAL;
+use probing_core::trace::{
+    evict, retention_policy, select_evictions, SpanRecord, Timestamp, TraceExtent,
+};
+
+use super::SERVER_RUNTIME;
+use crate::engine::ENGINE;
+
+/// Span rows the sweep needs: ids, start and end.
+const EXTENT_SQL: &str = "SELECT trace_id, thread_id, span_id, time, end_time, record_type \
+                          FROM python.trace_event WHERE record_type = 'span'";
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Start the sweep loop once per process; it idles while no limit is set.
+pub fn start_trace_retention() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    SERVER_RUNTIME.spawn(async {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = super::detach::detached() => break,
+            }
+            if let Err(err) = sweep_once().await {
+                log::debug!("trace retention sweep failed: {err}");
+            }
+        }
+    });
+}
+
+/// Evict what the current policy rules out; returns how many traces went.
+pub async fn sweep_once() -> anyhow::Result<usize> {
+    let policy = retention_policy();
+    if !policy.is_active() || crate::engine_lifecycle::engine_not_ready_message().is_some() {
+        return Ok(0);
+    }
+    let df = ENGINE.read().await.async_query(EXTENT_SQL).await?;
+    let records = df
+        .as_ref()
+        .map(SpanRecord::from_dataframe)
+        .unwrap_or_default();
+    let extents = TraceExtent::from_records(&records);
+    let now = Timestamp::now().0 as i64;
+    let doomed: HashSet<i64> = select_evictions(&extents, now, &policy)
+        .into_iter()
+        .collect();
+    if doomed.is_empty() {
+        return Ok(0);
+    }
+    let doomed: Vec<TraceExtent> = extents
+        .into_iter()
+        .filter(|e| doomed.contains(&e.trace_id))
+        .collect();
+    Ok(evict(&doomed))
+}
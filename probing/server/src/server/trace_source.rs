@@ -0,0 +1,314 @@
+//! `GET /apis/trace/source`: the source lines around a span's recorded
+//! location, so the code can be read without access to the machine.
+//!
+//! Files go through the file API path rules ([`super::file_api`]); Python
+//! sources under `sys.path` entries are readable here as well, so spans
+//! opened inside installed packages resolve too. Anything that cannot be
+//! shown comes back as `available: false` with a `reason`, not an error.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use axum::extract::Query;
+use axum::Json;
+use probing_proto::prelude::{DataFrame, Ele};
+use serde::{Deserialize, Serialize};
+
+use super::config::{allowed_file_base_dirs, get_max_file_size};
+use super::error::{ApiError, ApiResult};
+use super::file_api::{validate_path_in, FILE_API_DISABLED};
+use crate::engine::ENGINE;
+
+/// Lines shown on each side of the span's line.
+pub const SOURCE_CONTEXT_LINES: usize = 10;
+/// Files kept in the `(path, mtime)` cache.
+const MAX_CACHED_FILES: usize = 64;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SpanSourceParams {
+    pub span_id: Option<i64>,
+}
+
+/// Snippet of the file a span was opened in; `lines` start at `start_line`.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct SpanSource {
+    pub span_id: i64,
+    pub available: bool,
+    /// Why the source cannot be shown (`available: false`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub location: Option<String>,
+    pub path: Option<String>,
+    pub function: Option<String>,
+    /// The span's line (1-based), to highlight.
+    pub line: Option<usize>,
+    pub start_line: usize,
+    pub lines: Vec<String>,
+    pub total_lines: usize,
+}
+
+impl SpanSource {
+    fn unavailable(mut self, reason: impl Into<String>) -> Self {
+        self.available = false;
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// `path:function:line` (as recorded by `probing.span`) or `path:line`.
+fn parse_location(location: &str) -> Option<(&str, Option<&str>, usize)> {
+    let (rest, line) = location.rsplit_once(':')?;
+    let line = line.trim().parse().ok().filter(|l| *l > 0)?;
+    match rest.rsplit_once(':') {
+        // A Windows drive prefix (`C:\…`) is not a function name.
+        Some((path, function)) if !path.is_empty() && !function.contains(['/', '\\']) => {
+            Some((path, Some(function), line))
+        }
+        _ => Some((rest, None, line)),
+    }
+}
+
+/// The file API rules, plus Python sources under `sys.path` (read-only).
+fn validate_source_path(
+    path: &str,
+    file_dirs: &[PathBuf],
+    sys_path: &[PathBuf],
+) -> Result<PathBuf, String> {
+    if file_dirs.is_empty() {
+        return Err(FILE_API_DISABLED.to_string());
+    }
+    match validate_path_in(path, file_dirs) {
+        Ok(path) => Ok(path),
+        Err(err) if is_python_source(path) && !sys_path.is_empty() => {
+            validate_path_in(path, sys_path).map_err(|_| err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn is_python_source(path: &str) -> bool {
+    matches!(
+        Path::new(path).extension().and_then(|e| e.to_str()),
+        Some("py" | "pyi")
+    )
+}
+
+struct CachedSource {
+    mtime: SystemTime,
+    lines: Arc<Vec<String>>,
+}
+
+static SOURCE_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedSource>>> =
+    LazyLock::new(Default::default);
+
+/// Lines of `path`, cached by `(path, mtime)`.
+fn cached_lines(path: &Path, max_size: u64) -> Result<Arc<Vec<String>>, String> {
+    let metadata = std::fs::metadata(path).map_err(|_| "Cannot access file".to_string())?;
+    if metadata.len() > max_size {
+        return Err(format!("File too large (max {max_size} bytes allowed)"));
+    }
+    let mtime = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut cache = SOURCE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.get(path).filter(|c| c.mtime == mtime) {
+        return Ok(cached.lines.clone());
+    }
+    let text = std::fs::read_to_string(path).map_err(|_| "Cannot read file".to_string())?;
+    let lines = Arc::new(text.lines().map(str::to_string).collect::<Vec<_>>());
+    if cache.len() >= MAX_CACHED_FILES && !cache.contains_key(path) {
+        cache.clear();
+    }
+    cache.insert(
+        path.to_path_buf(),
+        CachedSource {
+            mtime,
+            lines: lines.clone(),
+        },
+    );
+    Ok(lines)
+}
+
+/// Fill `source` with the lines around `line` of the (validated) `path`.
+fn read_snippet(mut source: SpanSource, path: &Path, line: usize, max_size: u64) -> SpanSource {
+    let lines = match cached_lines(path, max_size) {
+        Ok(lines) => lines,
+        Err(reason) => return source.unavailable(reason),
+    };
+    if line > lines.len() {
+        return source.unavailable(format!(
+            "line {line} is past the end of the file ({} lines)",
+            lines.len()
+        ));
+    }
+    let start = line.saturating_sub(SOURCE_CONTEXT_LINES).max(1);
+    let end = (line + SOURCE_CONTEXT_LINES).min(lines.len());
+    source.available = true;
+    source.start_line = start;
+    source.lines = lines[start - 1..end].to_vec();
+    source.total_lines = lines.len();
+    source
+}
+
+fn text_column(df: &DataFrame) -> Vec<String> {
+    let Some(col) = df.cols.first() else {
+        return vec![];
+    };
+    (0..col.len())
+        .filter_map(|row| match col.get(row) {
+            Ele::Text(s) | Ele::Url(s) if !s.is_empty() => Some(s),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn query_texts(sql: String) -> Result<Vec<String>, String> {
+    let df = ENGINE
+        .read()
+        .await
+        .async_query(sql)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(df.as_ref().map(text_column).unwrap_or_default())
+}
+
+/// Existing directories on the target's `sys.path`.
+async fn sys_path_dirs() -> Vec<PathBuf> {
+    query_texts("SELECT value FROM python.\"sys.path\"".to_string())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+/// `GET /apis/trace/source?span_id=` — ±[`SOURCE_CONTEXT_LINES`] lines around
+/// the span's location.
+pub async fn get_span_source(
+    Query(params): Query<SpanSourceParams>,
+) -> ApiResult<Json<SpanSource>> {
+    if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
+        return Err(ApiError::service_unavailable(msg));
+    }
+    let span_id = params
+        .span_id
+        .ok_or_else(|| ApiError::bad_request("Missing 'span_id' parameter"))?;
+    let source = SpanSource {
+        span_id,
+        ..Default::default()
+    };
+    let locations = query_texts(format!(
+        "SELECT location FROM python.trace_event \
+         WHERE span_id = {span_id} AND location IS NOT NULL LIMIT 1"
+    ))
+    .await
+    .map_err(|e| ApiError::internal(format!("span location query failed: {e}")))?;
+    let Some(location) = locations.into_iter().next() else {
+        return Ok(Json(source.unavailable("span has no recorded location")));
+    };
+    let source = SpanSource {
+        location: Some(location.clone()),
+        ..source
+    };
+    let Some((path, function, line)) = parse_location(&location) else {
+        return Ok(Json(source.unavailable("location has no line number")));
+    };
+    let source = SpanSource {
+        path: Some(path.to_string()),
+        function: function.map(str::to_string),
+        line: Some(line),
+        ..source
+    };
+
+    let safe_path =
+        match validate_source_path(path, &allowed_file_base_dirs(), &sys_path_dirs().await) {
+            Ok(p) => p,
+            Err(reason) => return Ok(Json(source.unavailable(reason))),
+        };
+    let max_size = get_max_file_size();
+    let source =
+        tokio::task::spawn_blocking(move || read_snippet(source, &safe_path, line, max_size))
+            .await
+            .map_err(|e| ApiError::internal(format!("source read failed: {e}")))?;
+    Ok(Json(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_recorded_locations() {
+        assert_eq!(
+            parse_location("/job/train.py:step:42"),
+            Some(("/job/train.py", Some("step"), 42))
+        );
+        assert_eq!(
+            parse_location("/job/train.py:7"),
+            Some(("/job/train.py", None, 7))
+        );
+        assert_eq!(
+            parse_location(r"C:\job\train.py:7"),
+            Some((r"C:\job\train.py", None, 7))
+        );
+        assert_eq!(parse_location("/job/train.py:step"), None);
+        assert_eq!(parse_location("/job/train.py:0"), None);
+    }
+
+    #[test]
+    fn sys_path_only_opens_python_sources() {
+        let allowed = tempfile::tempdir().unwrap();
+        let site = tempfile::tempdir().unwrap();
+        let module = site.path().join("pkg.py");
+        let data = site.path().join("weights.bin");
+        std::fs::write(&module, "x = 1\n").unwrap();
+        std::fs::write(&data, "bin").unwrap();
+        let file_dirs = vec![allowed.path().to_path_buf()];
+        let sys_path = vec![site.path().to_path_buf()];
+
+        let module = module.to_str().unwrap();
+        assert!(validate_source_path(module, &file_dirs, &sys_path).is_ok());
+        assert!(validate_source_path(module, &file_dirs, &[])
+            .unwrap_err()
+            .contains("Access denied"));
+        assert!(validate_source_path(data.to_str().unwrap(), &file_dirs, &sys_path).is_err());
+        assert_eq!(
+            validate_source_path(module, &[], &sys_path).unwrap_err(),
+            FILE_API_DISABLED
+        );
+    }
+
+    #[test]
+    fn snippet_clamps_to_file_and_follows_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("train.py");
+        let text: Vec<String> = (1..=30).map(|i| format!("line {i}")).collect();
+        std::fs::write(&file, text.join("\n")).unwrap();
+
+        let source = read_snippet(SpanSource::default(), &file, 5, 1 << 20);
+        assert!(source.available);
+        assert_eq!(source.start_line, 1);
+        assert_eq!(source.lines.first().unwrap(), "line 1");
+        assert_eq!(source.lines.last().unwrap(), "line 15");
+        assert_eq!(source.total_lines, 30);
+
+        let past = read_snippet(SpanSource::default(), &file, 31, 1 << 20);
+        assert!(!past.available);
+        assert!(past.reason.unwrap().contains("past the end"));
+
+        // A rewritten file (new mtime) is read again rather than served stale.
+        std::fs::write(&file, "short\n").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(cached_lines(&file, 1 << 20).unwrap().as_slice(), ["short"]);
+
+        let big = read_snippet(SpanSource::default(), &file, 1, 1);
+        assert!(big.reason.unwrap().contains("too large"));
+    }
+}
@@ -0,0 +1,202 @@
+//! `GET /apis/trace/stream`: finished spans pushed over a WebSocket as they
+//! close, for the live view of the Spans page.
+//!
+//! Every text message is one JSON object, either
+//! `{"type": "span", "span": <span tree node>}` (no children; the node shape
+//! of `/apis/trace/span_tree`) or, after the client fell behind and spans
+//! were skipped, `{"type": "lagged", "dropped": n, "total_dropped": m}`.
+//! Recording never waits for a client: spans go through the bounded
+//! broadcast channel of [`probing_core::trace::live`], and a client that
+//! cannot keep up loses the oldest ones instead.
+
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use futures_util::{SinkExt, StreamExt};
+use probing_core::trace::{
+    attrs_json, subscribe_finished_spans, Attribute, Location, Span, SpanEventNode, SpanNode,
+};
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage {
+    Span { span: SpanNode },
+    Lagged { dropped: u64, total_dropped: u64 },
+}
+
+fn attrs_text(attrs: &[Attribute]) -> Option<String> {
+    (!attrs.is_empty()).then(|| attrs_json(attrs).to_string())
+}
+
+/// `span` as a childless span tree node.
+fn span_node(span: &Span) -> SpanNode {
+    let links: Vec<_> = span
+        .links
+        .iter()
+        .map(|l| {
+            json!({
+                "trace_id": l.trace_id,
+                "span_id": l.span_id,
+                "attributes": attrs_json(&l.attributes),
+            })
+        })
+        .collect();
+    SpanNode {
+        span_id: span.span_id as i64,
+        trace_id: span.trace_id as i64,
+        parent_id: span.parent_id.map(|id| id as i64),
+        name: span.name.clone(),
+        start_timestamp: span.start.0 as i64,
+        end_timestamp: span.end.map(|end| end.0 as i64),
+        thread_id: span.thread_id as i64,
+        phase: span.phase.clone(),
+        location: span.loc.as_ref().map(|loc| match loc {
+            Location::KnownLocation(id) => id.to_string(),
+            Location::UnknownLocation(path) => path.clone(),
+        }),
+        attributes: attrs_text(&span.attrs),
+        cpu_time_ns: span.cpu_time_ns.map(|ns| ns as i64),
+        ctx_switches: span.ctx_switches.map(|n| n as i64),
+        links: (!links.is_empty()).then(|| serde_json::Value::from(links).to_string()),
+        children: Vec::new(),
+        events: span
+            .events
+            .iter()
+            .map(|event| SpanEventNode {
+                name: event.name.clone(),
+                timestamp: event.timestamp.0 as i64,
+                attributes: attrs_text(&event.attributes),
+            })
+            .collect(),
+    }
+}
+
+/// The next message for a subscriber; `None` once the channel is closed.
+async fn next_message(
+    spans: &mut Receiver<Arc<Span>>,
+    total_dropped: &mut u64,
+) -> Option<StreamMessage> {
+    match spans.recv().await {
+        Ok(span) => Some(StreamMessage::Span {
+            span: span_node(&span),
+        }),
+        Err(RecvError::Lagged(dropped)) => {
+            *total_dropped += dropped;
+            Some(StreamMessage::Lagged {
+                dropped,
+                total_dropped: *total_dropped,
+            })
+        }
+        Err(RecvError::Closed) => None,
+    }
+}
+
+/// `GET /apis/trace/stream` — pushes spans until the client disconnects.
+pub async fn stream_spans(ws: WebSocketUpgrade) -> Response {
+    // Subscribed before the upgrade, so spans finished meanwhile are kept.
+    let spans = subscribe_finished_spans();
+    ws.on_upgrade(move |socket| forward_spans(socket, spans))
+}
+
+async fn forward_spans(socket: WebSocket, mut spans: Receiver<Arc<Span>>) {
+    let (mut write, mut read) = socket.split();
+    let forward = tokio::spawn(async move {
+        let mut total_dropped = 0;
+        while let Some(message) = next_message(&mut spans, &mut total_dropped).await {
+            let Ok(text) = serde_json::to_string(&message) else {
+                continue;
+            };
+            if write.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+    // Nothing is expected from the client; reading notices it leaving.
+    while let Some(Ok(message)) = read.next().await {
+        if matches!(message, Message::Close(_)) {
+            break;
+        }
+    }
+    forward.abort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_core::trace::{attr, Timestamp};
+    use tokio::sync::broadcast;
+
+    fn finished(name: &str) -> Arc<Span> {
+        let mut span = Span::new_root(name, Some("forward"), Some("train.py:step:12"));
+        span.add_attr("rank", 3i64).unwrap();
+        span.add_event("prefill", Some(vec![attr("tokens", 16i64)]))
+            .unwrap();
+        span.end = Some(Timestamp(span.start.0 + 1_000));
+        Arc::new(span)
+    }
+
+    #[test]
+    fn spans_become_childless_tree_nodes() {
+        let span = finished("step");
+        let message = serde_json::to_value(StreamMessage::Span {
+            span: span_node(&span),
+        })
+        .unwrap();
+        assert_eq!(message["type"], "span");
+        let node = &message["span"];
+        assert_eq!(node["name"], "step");
+        assert_eq!(node["phase"], "forward");
+        assert_eq!(node["location"], "train.py:step:12");
+        assert_eq!(node["attributes"], r#"{"rank":3}"#);
+        assert_eq!(
+            node["end_timestamp"].as_i64().unwrap() - node["start_timestamp"].as_i64().unwrap(),
+            1_000
+        );
+        assert_eq!(node["links"], serde_json::Value::Null);
+        assert_eq!(node["children"], json!([]));
+        assert_eq!(node["events"][0]["name"], "prefill");
+        assert_eq!(node["events"][0]["attributes"], r#"{"tokens":16}"#);
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_are_told_how_many_spans_they_lost() {
+        let (sender, mut spans) = broadcast::channel(2);
+        for name in ["a", "b", "c", "d"] {
+            sender.send(finished(name)).unwrap();
+        }
+        let mut total = 0;
+        let lagged =
+            serde_json::to_value(next_message(&mut spans, &mut total).await.unwrap()).unwrap();
+        assert_eq!(
+            lagged,
+            json!({"type": "lagged", "dropped": 2, "total_dropped": 2})
+        );
+        for name in ["c", "d"] {
+            match next_message(&mut spans, &mut total).await {
+                Some(StreamMessage::Span { span }) => assert_eq!(span.name, name),
+                other => panic!("expected span {name}, got {other:?}"),
+            }
+        }
+
+        for name in ["e", "f", "g"] {
+            sender.send(finished(name)).unwrap();
+        }
+        let lagged =
+            serde_json::to_value(next_message(&mut spans, &mut total).await.unwrap()).unwrap();
+        assert_eq!(lagged["total_dropped"], 3);
+        drop(sender);
+        let rest: Vec<_> = [
+            next_message(&mut spans, &mut total).await,
+            next_message(&mut spans, &mut total).await,
+            next_message(&mut spans, &mut total).await,
+        ]
+        .into_iter()
+        .map(|m| m.is_some())
+        .collect();
+        assert_eq!(rest, [true, true, false]);
+    }
+}
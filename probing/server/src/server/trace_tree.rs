@@ -0,0 +1,191 @@
+//! `GET /apis/trace/span_tree`: parent/child span trees built server-side
+//! from the paired span rows of `python.trace_event`.
+//!
+//! `name`, `phase` and `thread_id` take comma-separated values and are pushed
+//! into the query: the first two select span rows (events follow their span),
+//! `thread_id` selects every row. `start_ts`/`end_ts` (ns, inclusive) keep
+//! events inside the window and spans overlapping it, so a span that started
+//! before the window but ends inside it is still returned whole.
+
+use axum::extract::Query;
+use axum::Json;
+use probing_core::trace::{build_span_tree, SpanNode, SpanRecord};
+use serde::Deserialize;
+
+use super::error::{ApiError, ApiResult};
+use crate::engine::ENGINE;
+
+const DEFAULT_LIMIT: usize = 1000;
+const MAX_LIMIT: usize = 100_000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SpanTreeParams {
+    /// Most recent span and event rows to include (default 1000).
+    pub limit: Option<usize>,
+    pub trace_id: Option<i64>,
+    /// Span names, comma-separated.
+    pub name: Option<String>,
+    /// Span phases, comma-separated.
+    pub phase: Option<String>,
+    /// Thread ids, comma-separated.
+    pub thread_id: Option<String>,
+    /// Window start, ns since epoch.
+    pub start_ts: Option<i64>,
+    /// Window end, ns since epoch.
+    pub end_ts: Option<i64>,
+}
+
+/// Non-empty comma-separated values.
+fn split_list(raw: Option<&str>) -> Vec<&str> {
+    raw.map(|s| {
+        s.split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn sql_text_list(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl SpanTreeParams {
+    /// Extra `WHERE` conditions for the pushdown filters.
+    fn pushdown_sql(&self) -> Result<String, String> {
+        let mut sql = String::new();
+        if let Some(id) = self.trace_id {
+            sql.push_str(&format!(" AND trace_id = {id}"));
+        }
+        let threads = split_list(self.thread_id.as_deref())
+            .into_iter()
+            .map(|t| {
+                t.parse::<i64>()
+                    .map(|t| t.to_string())
+                    .map_err(|_| format!("invalid thread_id `{t}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !threads.is_empty() {
+            sql.push_str(&format!(" AND thread_id IN ({})", threads.join(", ")));
+        }
+        for (column, raw) in [("name", &self.name), ("phase", &self.phase)] {
+            let values = split_list(raw.as_deref());
+            if !values.is_empty() {
+                sql.push_str(&format!(
+                    " AND (record_type = 'event' OR {column} IN ({}))",
+                    sql_text_list(&values)
+                ));
+            }
+        }
+        if let Some(end) = self.end_ts {
+            sql.push_str(&format!(" AND time <= {end}"));
+        }
+        if let Some(start) = self.start_ts {
+            sql.push_str(&format!(
+                " AND (time >= {start} OR (record_type = 'span' AND \
+                 (end_time IS NULL OR end_time >= {start})))"
+            ));
+        }
+        Ok(sql)
+    }
+}
+
+/// Span (`record_type = 'span'`, already paired with its end) and event rows,
+/// newest first.
+fn span_tree_sql(limit: usize, pushdown: &str) -> String {
+    format!(
+        "SELECT record_type, trace_id, span_id, parent_id, name, time, end_time, thread_id, \
+         phase, location, attributes, event_attributes, cpu_time_ns, ctx_switches, links \
+         FROM python.trace_event \
+         WHERE record_type IN ('span', 'event'){pushdown} \
+         ORDER BY time DESC LIMIT {limit}"
+    )
+}
+
+/// `GET /apis/trace/span_tree?limit=&trace_id=&name=&phase=&thread_id=&start_ts=&end_ts=` — root
+/// spans ordered by start time; see [`build_span_tree`] for orphan and
+/// unfinished span handling.
+pub async fn get_span_tree(Query(params): Query<SpanTreeParams>) -> ApiResult<Json<Vec<SpanNode>>> {
+    query_span_tree(&params).await.map(Json)
+}
+
+/// Span trees selected by `params`, shared with the span flamegraph.
+pub(super) async fn query_span_tree(params: &SpanTreeParams) -> ApiResult<Vec<SpanNode>> {
+    if let Some(msg) = crate::engine_lifecycle::engine_not_ready_message() {
+        return Err(ApiError::service_unavailable(msg));
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let pushdown = params.pushdown_sql().map_err(ApiError::bad_request)?;
+    let sql = span_tree_sql(limit, &pushdown);
+    let df = ENGINE
+        .read()
+        .await
+        .async_query(sql)
+        .await
+        .map_err(|e| ApiError::internal(format!("span tree query failed: {e}")))?;
+    let records = df
+        .as_ref()
+        .map(SpanRecord::from_dataframe)
+        .unwrap_or_default();
+    Ok(build_span_tree(records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sql_filters_trace_and_caps_rows() {
+        let params = SpanTreeParams {
+            trace_id: Some(7),
+            ..Default::default()
+        };
+        let sql = span_tree_sql(50, &params.pushdown_sql().unwrap());
+        assert!(sql.contains("record_type IN ('span', 'event') AND trace_id = 7"));
+        assert!(sql.ends_with("LIMIT 50"));
+        let unfiltered = SpanTreeParams::default().pushdown_sql().unwrap();
+        assert!(!span_tree_sql(50, &unfiltered).contains("trace_id ="));
+    }
+
+    #[test]
+    fn pushdown_keeps_events_and_quotes_names() {
+        let params = SpanTreeParams {
+            name: Some("step, it's".into()),
+            phase: Some("forward".into()),
+            thread_id: Some("7, 8".into()),
+            ..Default::default()
+        };
+        let sql = params.pushdown_sql().unwrap();
+        assert!(sql.contains("thread_id IN (7, 8)"));
+        assert!(sql.contains("(record_type = 'event' OR name IN ('step', 'it''s'))"));
+        assert!(sql.contains("(record_type = 'event' OR phase IN ('forward'))"));
+
+        let bad = SpanTreeParams {
+            thread_id: Some("7; DROP".into()),
+            ..Default::default()
+        };
+        assert!(bad.pushdown_sql().is_err());
+    }
+
+    #[test]
+    fn time_window_keeps_spans_open_at_its_start() {
+        let params = SpanTreeParams {
+            start_ts: Some(100),
+            end_ts: Some(200),
+            ..Default::default()
+        };
+        let sql = params.pushdown_sql().unwrap();
+        assert!(sql.contains(" AND time <= 200"));
+        assert!(sql.contains(
+            "(time >= 100 OR (record_type = 'span' AND (end_time IS NULL OR end_time >= 100)))"
+        ));
+        assert!(!SpanTreeParams::default()
+            .pushdown_sql()
+            .unwrap()
+            .contains("time"));
+    }
+}
@@ -15,15 +15,13 @@ use super::error::ApiError;
 
 const STEP_MATRIX_SQL: &str = r#"
 SELECT
-    s.attributes,
-    s.name,
-    s.time AS start_time,
-    CAST((CAST(e.time AS BIGINT) - CAST(s.time AS BIGINT)) / 1000 AS DOUBLE) AS duration_us
-FROM python.trace_event s
-JOIN python.trace_event e
-  ON s.span_id = e.span_id AND e.record_type = 'span_end'
-WHERE s.record_type = 'span_start' AND s.name = 'train.step'
-ORDER BY s.time ASC
+    attributes,
+    name,
+    time AS start_time,
+    CAST(duration / 1000 AS DOUBLE) AS duration_us
+FROM python.trace_event
+WHERE record_type = 'span' AND name = 'train.step' AND duration IS NOT NULL
+ORDER BY time ASC
 LIMIT 10000
 "#;
 
@@ -316,7 +314,7 @@ fn ele_as_str(v: Option<Ele>) -> String {
 
 fn ele_as_i64(v: Option<Ele>) -> i64 {
     match v {
-        Some(Ele::I64(n)) => n,
+        Some(Ele::I64(n) | Ele::DateTime(n)) => n,
         Some(Ele::I32(n)) => n as i64,
         Some(Ele::F64(n)) => n as i64,
         Some(Ele::F32(n)) => n as i64,
@@ -0,0 +1,218 @@
+//! Memory tests of streamed exports: multi-hundred-MB replies are pulled
+//! while a counting allocator tracks peak heap use, which must stay flat (a
+//! few chunks or record batches), not grow with the reply size.
+//!
+//! `GET /apis/trace/dump` runs for real over a generated `python.trace_event`
+//! table. Replies of Python handlers are covered from the server side: a
+//! synthetic chrome trace goes through `extension_stream_response`; the
+//! handler's own paging is measured in
+//! `tests/unit/probing/handlers/test_python_handlers.py`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Int64Array, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::catalog::streaming::StreamingTable;
+use datafusion::catalog::MemorySchemaProvider;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use http_body_util::BodyExt;
+use probing_core::core::channel_stream;
+use probing_server::server::api::extension::extension_stream_response;
+use probing_server::server::trace_archive::trace_dump_body;
+
+struct CountingAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn track_alloc(size: usize) {
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+            track_alloc(new_size);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// One measurement at a time: the counters are process-wide.
+static MEASURING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Heap growth since `baseline` at its peak.
+fn peak_since(baseline: usize) -> usize {
+    PEAK.load(Ordering::Relaxed).saturating_sub(baseline)
+}
+
+/// Resets the peak and returns the live heap to measure from.
+fn start_measuring() -> usize {
+    let baseline = LIVE.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    baseline
+}
+
+const EVENTS_PER_CHUNK: usize = 1000;
+const CHUNKS: usize = 2200;
+const PEAK_BUDGET: usize = 32 << 20;
+
+/// One chunk of chrome trace events, roughly 160 KB.
+fn event_chunk(index: usize) -> Vec<u8> {
+    let mut out = String::with_capacity(EVENTS_PER_CHUNK * 170);
+    for i in 0..EVENTS_PER_CHUNK {
+        let ts = index * EVENTS_PER_CHUNK + i;
+        if index > 0 || i > 0 {
+            out.push_str(",\n");
+        }
+        out.push_str(&format!(
+            r#"{{"name": "synthetic_span_with_a_long_descriptive_name", "cat": "forward", "ph": "B", "ts": {ts}, "pid": 1, "tid": 7, "args": {{"location": "model.py:{i}"}}}}"#
+        ));
+    }
+    out.into_bytes()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn streamed_extension_reply_keeps_memory_flat() {
+    let _measuring = MEASURING.lock().await;
+    // Producer mirrors the Python handler pump: a blocking thread feeding a
+    // small bounded channel, one chunk at a time.
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    let producer = tokio::task::spawn_blocking(move || {
+        let _ = tx.blocking_send(Ok(br#"{"displayTimeUnit": "ms", "traceEvents": ["#.to_vec()));
+        for index in 0..CHUNKS {
+            if tx.blocking_send(Ok(event_chunk(index))).is_err() {
+                return;
+            }
+        }
+        let _ = tx.blocking_send(Ok(b"\n]}".to_vec()));
+    });
+
+    let baseline = start_measuring();
+
+    let resp = extension_stream_response("/pythonext/trace/chrome-tracing", channel_stream(rx))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let mut body = resp.into_body();
+    let mut streamed = 0usize;
+    let mut tail = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let data = frame.unwrap().into_data().unwrap();
+        streamed += data.len();
+        tail = data.to_vec();
+    }
+    producer.await.unwrap();
+
+    let peak = peak_since(baseline);
+    assert!(streamed > 300 << 20, "only {streamed} bytes streamed");
+    assert_eq!(tail, b"\n]}");
+    assert!(
+        peak < PEAK_BUDGET,
+        "peak heap grew by {peak} bytes while streaming {streamed} bytes"
+    );
+}
+
+const DUMP_ROWS: usize = 1_500_000;
+const DUMP_BATCH_ROWS: usize = 8192;
+
+/// `python.trace_event` rows made batch by batch as the scan pulls them,
+/// ~220 archive bytes each.
+#[derive(Debug)]
+struct GeneratedEvents(SchemaRef);
+
+impl PartitionStream for GeneratedEvents {
+    fn schema(&self) -> &SchemaRef {
+        &self.0
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let schema = Arc::clone(&self.0);
+        let batches = (0..DUMP_ROWS).step_by(DUMP_BATCH_ROWS).map(move |start| {
+            let rows = start as i64..(start + DUMP_BATCH_ROWS).min(DUMP_ROWS) as i64;
+            let names = rows
+                .clone()
+                .map(|i| format!("synthetic_span_with_a_long_descriptive_name_{i:0>160}"));
+            RecordBatch::try_new(
+                Arc::clone(&schema),
+                vec![
+                    Arc::new(Int64Array::from_iter_values(rows)),
+                    Arc::new(StringArray::from_iter_values(names)),
+                ],
+            )
+            .map_err(DataFusionError::from)
+        });
+        Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.0),
+            futures_util::stream::iter(batches),
+        ))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trace_dump_keeps_memory_flat() {
+    let _measuring = MEASURING.lock().await;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("time", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    {
+        let engine = probing_core::ENGINE.read().await;
+        let catalog = engine.context.catalog("probe").unwrap();
+        catalog
+            .register_schema("python", Arc::new(MemorySchemaProvider::new()))
+            .unwrap();
+        let source: Arc<dyn PartitionStream> = Arc::new(GeneratedEvents(Arc::clone(&schema)));
+        let table = StreamingTable::try_new(schema, vec![source]).unwrap();
+        engine
+            .context
+            .register_table("python.trace_event", Arc::new(table))
+            .unwrap();
+    }
+
+    let baseline = start_measuring();
+
+    let mut body = trace_dump_body();
+    let mut streamed = 0usize;
+    let mut tail = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let data = frame.unwrap().into_data().unwrap();
+        streamed += data.len();
+        tail = data.to_vec();
+    }
+
+    let peak = peak_since(baseline);
+    assert!(streamed > 300 << 20, "only {streamed} bytes streamed");
+    assert_eq!(tail, b"]}");
+    assert!(
+        peak < PEAK_BUDGET,
+        "peak heap grew by {peak} bytes while dumping {streamed} bytes"
+    );
+}
@@ -86,6 +86,25 @@ else:
     except Exception:
         pass
 
+    try:
+        from probing._entrypoint import should_activate_probing
+        from probing.inspect import threads as _threads
+
+        if should_activate_probing() and _threads.tracking_enabled():
+            _threads.install()
+    except Exception:
+        pass
+
+    try:
+        from probing._entrypoint import should_activate_probing
+
+        if should_activate_probing():
+            from probing.profiling.governor import maybe_start_governor
+
+            maybe_start_governor()
+    except Exception:
+        pass
+
     try:
         from probing.hooks.import_hook import install_and_run_pending
 
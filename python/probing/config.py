@@ -42,3 +42,19 @@ def len():
 
 def is_empty():
     return _core.config_is_empty()
+
+
+def govern(key, manual=None):
+    """Mark ``key`` as lowered by the overhead governor; ``manual`` is the
+    value to restore (``None``: the key was unset)."""
+    return _core.config_govern(key, manual)
+
+
+def release(key):
+    """Stop governing ``key``; ``(was_governed, manual value)``."""
+    return _core.config_release(key)
+
+
+def governed():
+    """Governed keys and their manual values."""
+    return _core.config_governed()
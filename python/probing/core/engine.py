@@ -9,7 +9,10 @@ interface between Python code and the underlying Rust implementation.
 from __future__ import annotations
 
 import json
-from typing import Any
+from typing import Any, Iterator
+
+# Rows per page of :func:`query_pages`.
+PAGE_ROWS = 10_000
 
 
 def _col_values(column: Any) -> list[Any]:
@@ -51,6 +54,44 @@ def query(sql: str) -> "DataFrame":  # noqa: F821
         return ret
 
 
+def query_pages(
+    sql: str, key: str, page_rows: int = 0
+) -> Iterator["DataFrame"]:  # noqa: F821
+    """Run ``sql`` and yield its result a page of ``page_rows`` rows (default
+    :data:`PAGE_ROWS`) at a time, oldest ``key`` first, so a large result is
+    never held in full.
+
+    ``key`` names an integer column of the result without NULLs. Each page is
+    a query over ``sql`` for the rows past the previous page's last key. A
+    full page stops before its last key, whose rows are read by one more
+    query, so rows sharing a key are neither skipped nor repeated. ``sql``
+    runs once per page; a ``LIMIT`` inside it still applies to the whole
+    result.
+    """
+    size = page_rows or PAGE_ROWS
+    column = f'"{key}"'
+    cursor = ""
+    while True:
+        page = query(
+            f"SELECT * FROM ({sql}) AS page{cursor} ORDER BY {column} LIMIT {size}"
+        )
+        if page is None or len(page) < size:
+            if page is not None and len(page):
+                yield page
+            return
+        boundary = int(page[key].iloc[-1])
+        # Only the page being consumed stays referenced while the next runs.
+        head = page[page[key] < boundary]
+        del page
+        if len(head):
+            yield head
+        del head
+        ties = query(f"SELECT * FROM ({sql}) AS page WHERE {column} = {boundary}")
+        if ties is not None and len(ties):
+            yield ties
+        cursor = f" WHERE {column} > {boundary}"
+
+
 def load_extension(statement: str):
     """Load a Rust extension into the probing library."""
     import importlib
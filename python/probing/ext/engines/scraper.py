@@ -15,6 +15,10 @@ from probing.ext.engines.registry import (
     update_scrape_result,
 )
 from probing.ext.engines.sglang import fetch_sglang_metrics, flatten_samples_for_storage
+from probing.tracing._bindings import sampled_step
+
+# Stored while no step is known; SQL reads it as NULL.
+NO_STEP = -1
 
 
 @table("inference_engine_metric")
@@ -26,6 +30,7 @@ class InferenceEngineMetric:
     metric_name: str
     metric_value: float
     labels: str
+    step: int = NO_STEP
 
 
 _scraper_lock = threading.Lock()
@@ -62,6 +67,8 @@ def scrape_engine(registration: EngineRegistration) -> dict:
 
     if snapshot.raw_samples:
         timestamp_ns = time.time_ns()
+        step = sampled_step()
+        step = NO_STEP if step is None else int(step)
         rows = flatten_samples_for_storage(
             registration.engine_id,
             registration.engine_type,
@@ -69,7 +76,7 @@ def scrape_engine(registration: EngineRegistration) -> dict:
             list(snapshot.raw_samples),
         )
         for row in rows:
-            InferenceEngineMetric(*row).save()
+            InferenceEngineMetric(*row, step=step).save()
         for metric_name, metric_value in snapshot.normalized.items():
             InferenceEngineMetric(
                 timestamp_ns=timestamp_ns,
@@ -78,6 +85,7 @@ def scrape_engine(registration: EngineRegistration) -> dict:
                 metric_name=f"normalized.{metric_name}",
                 metric_value=metric_value,
                 labels="normalized=1",
+                step=step,
             ).save()
 
     return snapshot.to_dict()
@@ -85,6 +85,17 @@ def collective_hook():
     maybe_start_collective_tracing()
 
 
+def gpu_streams_hook():
+    """Start kernel-launch / stream counters when ``probing.torch.gpu_streams``
+    or ``probing.torch.count_launches`` is on (no-op without CUDA)."""
+    try:
+        from probing.profiling.cuda_streams import maybe_start_stream_capture
+
+        maybe_start_stream_capture()
+    except Exception as exc:
+        logging.getLogger(__name__).debug("GPU stream capture not started: %s", exc)
+
+
 def megatron_hook():
     """Autostart Megatron role/step sync when Megatron loads before torch hooks."""
     try:
@@ -119,6 +130,7 @@ def init():
     register_optimizer_step_post_hook(optimizer_step_post_hook)
 
     collective_hook()
+    gpu_streams_hook()
     megatron_hook()
     vllm_hook()
     try:
@@ -130,6 +142,8 @@ def init():
 
 
 def deinit():
+    from probing.profiling.cuda_streams import stop_stream_capture
     from probing.profiling.torch import uninstall_hooks
 
     uninstall_hooks()
+    stop_stream_capture()
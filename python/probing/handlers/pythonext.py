@@ -4,24 +4,58 @@ This module contains handler functions for various API endpoints
 that were previously embedded as Python code strings in Rust.
 """
 
+import heapq
 import io
+import itertools
 import json
 import logging
+import math
+import numbers
+import os
 import sys
 import traceback
-from typing import Dict, List, Optional
+from typing import Dict, Iterator, List, Optional, Union
 
+from probing.handlers.perfetto import trace_packet_chunks
 from probing.handlers.router import ext_handler, handle_request
+from probing.handlers.streaming import json_array_chunks
 
 log = logging.getLogger(__name__)
 
+# Process-level samples of the CPU/memory (taskstats) collector; `ts` is µs.
+DEFAULT_COUNTER_SQL = "SELECT * FROM cpu.utilization WHERE scope = 'process'"
+DEFAULT_COUNTER_COLUMNS = "cpu_total_pct,rss_kb,thread_count"
+# Event recorded on a span left by an exception (probing.tracing.span).
+EXCEPTION_EVENT = "exception"
+ERROR_CATEGORY = "error"
+# Parent levels looked up for spans whose ancestors the filters dropped.
+MAX_ANCESTOR_DEPTH = 64
+# First pid of the PyTorch profiler lanes in a combined trace, clear of the
+# (sequential) trace ids and the counter lane 0 used by probing lanes.
+PYTORCH_PID_BASE = 1 << 30
+
 
 @ext_handler("pythonext", "callstack")
-def get_callstack(tid: Optional[int] = None, mode: Optional[str] = None) -> str:
-    """Return merged native/Python call stack as JSON."""
+def get_callstack(
+    tid: Optional[int] = None,
+    mode: Optional[str] = None,
+    group: Optional[str] = None,
+    native: bool = False,
+) -> str:
+    """Return merged native/Python call stack as JSON.
+
+    ``group=thread`` returns ``{"threads": [...]}`` instead, one entry per
+    Python thread with ``tid``, ``name``, ``main``, ``frames`` and ``error``;
+    its frames are Python-only unless ``native`` is set.
+    """
     import probing._core as core
 
     _ = mode  # reserved for future py/cpp/mixed filtering
+    if group == "thread":
+        try:
+            return core.api_callstack_threads(native)
+        except Exception as e:
+            return json.dumps({"error": str(e), "threads": []})
     try:
         return core.api_callstack(tid)
     except Exception as e:
@@ -36,6 +70,23 @@ def eval_code(code: str) -> str:
     return core.api_eval(code)
 
 
+@ext_handler("pythonext", "complete")
+def complete_code(code: str, cursor: Optional[int] = None) -> str:
+    """Tab completions for ``code`` at ``cursor`` in the REPL namespace.
+
+    Empty ``code`` answers without starting the REPL kernel, so the web
+    console can probe whether the REPL routes are reachable.
+    """
+    if not code:
+        return json.dumps({"matches": [], "start": 0, "end": 0})
+    try:
+        from probing.repl import get_debug_console
+
+        return json.dumps(get_debug_console().complete(code, cursor))
+    except Exception as e:
+        return json.dumps({"error": str(e)})
+
+
 @ext_handler("pythonext", "ray/timeline/chrome")
 def get_ray_timeline_chrome_format(
     task_filter: Optional[str] = None,
@@ -106,25 +157,183 @@ def get_ray_timeline(
         return json.dumps({"error": error_msg, "traceback": error_trace})
 
 
+def _split_list(raw: Optional[str]) -> List[str]:
+    """Non-empty values of a comma-separated query parameter."""
+    return [v.strip() for v in (raw or "").split(",") if v.strip()]
+
+
+def _glob_to_like(glob: str) -> str:
+    """SQL ``LIKE`` pattern (``\\`` escapes) for a name glob, quoted for a
+    string literal: ``*`` matches any run, ``?`` one character, and ``\\``
+    makes the next character literal. ``%`` and ``_`` are literal."""
+    out = []
+    chars = iter(glob)
+    for ch in chars:
+        if ch == "\\":
+            ch = next(chars, "\\")
+        elif ch == "*":
+            out.append("%")
+            continue
+        elif ch == "?":
+            out.append("_")
+            continue
+        out.append("\\" + ch if ch in "%_\\" else ch)
+    return "".join(out).replace("'", "''")
+
+
+def _chrome_tracing_filters(
+    name: Optional[str],
+    phase: Optional[str],
+    thread_id: Optional[str],
+    span_record: str = "span_start",
+    trace_id: Optional[int] = None,
+    name_glob: Optional[str] = None,
+) -> str:
+    """Extra ``WHERE`` conditions for the chrome-tracing pushdown filters.
+
+    ``span_end`` rows carry no name, phase or trace id, so name/phase/glob
+    only select ``span_record`` rows, and ends are kept for ``trace_id`` when
+    their start belongs to the trace; the ends and events of dropped spans
+    are discarded while converting.
+    """
+    sql = ""
+    threads = [int(t) for t in _split_list(thread_id)]
+    if threads:
+        sql += f" AND thread_id IN ({', '.join(map(str, threads))})"
+    for column, raw in (("name", name), ("phase", phase)):
+        values = _split_list(raw)
+        if values:
+            quoted = ", ".join("'" + v.replace("'", "''") + "'" for v in values)
+            sql += f" AND (record_type <> '{span_record}' OR {column} IN ({quoted}))"
+    if name_glob:
+        pattern = _glob_to_like(name_glob)
+        sql += f" AND (record_type <> '{span_record}' OR name LIKE '{pattern}')"
+    if trace_id is not None:
+        trace = int(trace_id)
+        if span_record == "span":
+            sql += f" AND trace_id = {trace}"
+        else:
+            sql += (
+                f" AND (trace_id = {trace} OR (record_type = 'span_end' AND span_id IN"
+                " (SELECT span_id FROM python.trace_event"
+                f" WHERE record_type = 'span_start' AND trace_id = {trace})))"
+            )
+    return sql
+
+
 @ext_handler("pythonext", "trace/chrome-tracing")
-def get_chrome_tracing(limit: int = 1000) -> str:
+def get_chrome_tracing(
+    limit: int = 1000,
+    name: Optional[str] = None,
+    phase: Optional[str] = None,
+    thread_id: Optional[str] = None,
+    start_ts: Optional[int] = None,
+    end_ts: Optional[int] = None,
+    trace_id: Optional[int] = None,
+    name_glob: Optional[str] = None,
+    include_counters: bool = False,
+    counter_sql: Optional[str] = None,
+    counter_columns: Optional[str] = None,
+    format: str = "json",
+    source: str = "trace",
+) -> Union[str, bytes, Iterator[str], Iterator[bytes]]:
     """Convert trace events to Chrome tracing format.
 
+    The document is streamed in chunks of ``streaming.FLUSH_EVERY`` events, so
+    large exports (``limit=0``) are never built as one string. The rows are
+    read from the engine a page at a time (``engine.query_pages``), once per
+    pass of the conversion; besides a page, only a small tuple per open,
+    failed or linked span is kept. Rows recorded while the export runs may
+    show up in the later passes only.
+
+    With ``format=proto`` the same events are written as a binary Perfetto
+    trace (``TracePacket`` track descriptors and track events, see
+    :mod:`probing.handlers.perfetto`) instead of JSON. Errors are still
+    returned as a JSON object.
+
+    With ``start_ts``/``end_ts`` only rows inside the window are converted and
+    timestamps are relative to the window's earliest row. A span that started
+    before the window but is still open at its start begins at ``start_ts``,
+    so its end inside the window closes a complete slice.
+
+    All filters, ``trace_id`` and ``name_glob`` included, apply before
+    ``limit``. When a span filter (``name``, ``phase``, ``name_glob``) keeps a
+    span but not its parent, the missing ancestors are added as well (not
+    counted against ``limit``), so the kept spans still nest under them.
+
+    With ``include_counters``, samples of ``counter_sql`` within the time range
+    of the trace rows become counter events (``ph: "C"``, one track per column
+    under pid 0) on the same timebase, interleaved with the spans. The source
+    must return a ``ts`` column in µs since epoch; requested columns it lacks,
+    and NULL or non-numeric values, are skipped, and a failing source only
+    drops the counters.
+
+    With ``source=combined`` the PyTorch profiler's last timeline is appended
+    (JSON only): its events are moved from the profiler's own epoch onto the
+    probing timebase, and each profiler process gets a lane of its own from
+    ``PYTORCH_PID_BASE`` up, named ``pytorch ...``. The filters and ``limit``
+    apply to probing rows only. When just one of the sources has events, the
+    trace is that source alone.
+
     Args:
         limit: Maximum number of events to process (0 for no limit)
+        name: Comma-separated span names to keep
+        phase: Comma-separated span phases to keep
+        thread_id: Comma-separated thread ids to keep
+        start_ts: Window start (ns since epoch, inclusive)
+        end_ts: Window end (ns since epoch, inclusive)
+        trace_id: Only rows of this trace
+        name_glob: Span name pattern (``*``, ``?``; ``\\`` escapes)
+        include_counters: Add counter tracks from ``counter_sql``
+        counter_sql: Counter source query (default: process rows of
+            ``cpu.utilization``)
+        counter_columns: Comma-separated counter columns (default:
+            ``cpu_total_pct,rss_kb,thread_count``)
+        format: ``json`` (default) or ``proto``
+        source: ``trace`` (default, probing spans) or ``combined`` (probing
+            spans and the PyTorch profiler timeline)
 
     Returns:
-        JSON string containing Chrome tracing format data
+        Chrome tracing JSON chunks, Perfetto protobuf chunks, or a JSON
+        error string
     """
     import probing.core.engine as engine
 
+    if format not in ("json", "proto"):
+        return json.dumps(
+            {"error": f"format must be json or proto, got {format!r}", "traceEvents": []}
+        )
+    if source not in ("trace", "combined"):
+        return json.dumps(
+            {
+                "error": f"source must be trace or combined, got {source!r}",
+                "traceEvents": [],
+            }
+        )
+    if source == "combined" and format != "json":
+        return json.dumps(
+            {"error": "source=combined is only exported as json", "traceEvents": []}
+        )
     try:
         # Query trace events from the database
         # IMPORTANT: Order by timestamp ASC to process events in chronological order
         # This ensures span_start events are processed before their corresponding span_end events
         if limit is None:
             limit = 1000
-        limit_clause = f" LIMIT {limit}" if limit > 0 else ""
+        if start_ts is not None and end_ts is not None and start_ts > end_ts:
+            if format == "proto":
+                return b"".join(trace_packet_chunks(()))
+            return '{"displayTimeUnit": "ms", "traceEvents": []}'
+        filters = _chrome_tracing_filters(
+            name, phase, thread_id, trace_id=trace_id, name_glob=name_glob
+        )
+        window = ""
+        if start_ts is not None:
+            window += f" AND time >= {int(start_ts)}"
+        if end_ts is not None:
+            window += f" AND time <= {int(end_ts)}"
+        # Without a limit the pages alone order the rows: no full sort per page.
+        top = f"ORDER BY timestamp ASC LIMIT {limit}" if limit > 0 else ""
         query = f"""
             SELECT
                 record_type,
@@ -137,172 +346,598 @@ def get_chrome_tracing(limit: int = 1000) -> str:
                 phase,
                 location,
                 attributes,
-                event_attributes
+                event_attributes,
+                thread_name,
+                links
             FROM python.trace_event
-            ORDER BY timestamp ASC
-            {limit_clause}
+            WHERE record_type <> 'span'{filters}{window}
+            {top}
         """
 
-        df = engine.query(query)
+        # Spans open at the window start are few; they start at ``start_ts``,
+        # before every row of the window.
+        open_spans = None
+        if start_ts is not None:
+            open_spans = engine.query(
+                _open_spans_query(
+                    int(start_ts), name, phase, thread_id, trace_id, name_glob
+                )
+            )
+        # Run the first page now, so a failing query is reported as a JSON
+        # error rather than cutting the stream short.
+        next(engine.query_pages(query, "timestamp"), None)
 
-        # Convert DataFrame to Chrome tracing format
-        trace_events = []
-        # Check if DataFrame is not None and not empty
-        # Use df is not None and not df.empty instead of if df (ambiguous truth value)
-        if df is not None and not df.empty:
-            # Convert DataFrame to list of dictionaries for iteration
-            df_list = df.to_dict("records") if hasattr(df, "to_dict") else []
-            # Find minimum timestamp
-            timestamps = [
-                row.get("timestamp", 0) for row in df_list if "timestamp" in row
-            ]
-            min_timestamp = min(timestamps) if timestamps else 0
-
-            # Track span starts by (span_id, thread_id) to handle multiple threads
-            # Also track trace_id for span_end events (which may have trace_id=0)
-            span_starts = {}
-
-            # First pass: collect all span_start events to build a lookup table
-            # This helps match span_end events even if trace_id is 0 in span_end
-            span_start_lookup = {}
-            for row in df_list:
-                if row.get("record_type") == "span_start":
-                    span_id = row.get("span_id", 0)
-                    thread_id = row.get("thread_id", 0)
-                    trace_id = row.get("trace_id", 0)
-                    name = row.get("name", "unknown")
-                    phase = row.get("phase", "")
-                    # Use (span_id, thread_id) as key to handle multiple threads
-                    key = (span_id, thread_id)
-                    span_start_lookup[key] = {
-                        "trace_id": trace_id,
-                        "name": name,
-                        "phase": phase,
-                        "timestamp": row.get("timestamp", 0),
+        def frame_rows():
+            return _frame_rows(
+                itertools.chain([open_spans], engine.query_pages(query, "timestamp"))
+            )
+
+        ancestors = []
+        if name or phase or name_glob:
+            ancestors = _ancestor_rows(engine, frame_rows, start_ts, end_ts)
+    except Exception as e:
+        return json.dumps(
+            {"error": str(e), "trace": traceback.format_exc(), "traceEvents": []}
+        )
+
+    def rows():
+        if not ancestors:
+            return frame_rows()
+        return heapq.merge(ancestors, frame_rows(), key=_row_timestamp)
+
+    counters = []
+    if include_counters and any(True for _ in rows()):
+        counters = _counter_samples(
+            engine,
+            counter_sql or DEFAULT_COUNTER_SQL,
+            _split_list(counter_columns or DEFAULT_COUNTER_COLUMNS),
+            *_timestamp_range(rows),
+        )
+
+    spans_filtered = bool(name or phase or name_glob or trace_id is not None)
+    events = _chrome_trace_events(
+        rows, spans_filtered=spans_filtered, counters=counters
+    )
+    if source == "combined":
+        events = _combined_events(events, rows, _pytorch_trace())
+    if format == "proto":
+        return trace_packet_chunks(events)
+    return json_array_chunks(
+        events,
+        head='{"displayTimeUnit": "ms", "traceEvents": [\n',
+        tail="\n]}",
+    )
+
+
+def _open_spans_query(
+    start_ts: int,
+    name: Optional[str],
+    phase: Optional[str],
+    thread_id: Optional[str],
+    trace_id: Optional[int] = None,
+    name_glob: Optional[str] = None,
+) -> str:
+    """``span_start`` rows, clamped to ``start_ts``, of the spans still open at
+    the window start."""
+    filters = _chrome_tracing_filters(
+        name, phase, thread_id, "span", trace_id=trace_id, name_glob=name_glob
+    )
+    return f"""
+        SELECT
+            'span_start' as record_type,
+            trace_id,
+            span_id,
+            COALESCE(parent_id, -1) as parent_id,
+            name,
+            CAST({start_ts} AS BIGINT) as timestamp,
+            COALESCE(thread_id, 0) as thread_id,
+            phase,
+            location,
+            attributes,
+            event_attributes,
+            thread_name,
+            '' as links
+        FROM python.trace_event
+        WHERE record_type = 'span'
+            AND time < {start_ts}
+            AND (end_time IS NULL OR end_time >= {start_ts}){filters}
+        ORDER BY time ASC
+    """
+
+
+def _frame_rows(frames) -> Iterator[dict]:
+    """Rows of the DataFrames in ``frames`` as dicts, one at a time."""
+    for df in frames:
+        if df is None or df.empty:
+            continue
+        columns = list(df.columns)
+        for values in df.itertuples(index=False, name=None):
+            yield dict(zip(columns, values))
+
+
+def _row_timestamp(row: dict) -> int:
+    return row.get("timestamp") or 0
+
+
+def _ancestor_rows(
+    engine, frame_rows, start_ts: Optional[int], end_ts: Optional[int]
+) -> List[dict]:
+    """``span_start``/``span_end`` rows, oldest first, of the ancestors that
+    the span filters left out of ``frame_rows()``.
+
+    Ancestors come from the paired ``span`` rows, one query per level. Within
+    a window their starts are clamped to ``start_ts`` and ends after
+    ``end_ts`` are left out, as for spans open at the window start. Ends
+    already among the rows are not repeated.
+    """
+    kept = set()
+    ended = set()
+    wanted = set()
+    for row in frame_rows():
+        record_type = row.get("record_type")
+        if record_type == "span_start":
+            kept.add(int(row["span_id"]))
+            parent = row.get("parent_id")
+            if parent is not None and parent >= 0:
+                wanted.add(int(parent))
+        elif record_type == "span_end":
+            ended.add(int(row["span_id"]))
+
+    rows = []
+    for _ in range(MAX_ANCESTOR_DEPTH):
+        missing = wanted - kept
+        if not missing:
+            break
+        kept |= missing
+        wanted = set()
+        df = engine.query(
+            f"""
+            SELECT
+                trace_id,
+                span_id,
+                COALESCE(parent_id, -1) as parent_id,
+                name,
+                time,
+                end_time,
+                COALESCE(thread_id, 0) as thread_id,
+                phase,
+                location,
+                thread_name
+            FROM python.trace_event
+            WHERE record_type = 'span'
+                AND span_id IN ({', '.join(map(str, sorted(missing)))})
+            """
+        )
+        if df is None or df.empty:
+            break
+        for span in df.to_dict("records"):
+            start = int(span.pop("time"))
+            end = span.pop("end_time")
+            if start_ts is not None:
+                start = max(start, int(start_ts))
+            rows.append({**span, "record_type": "span_start", "timestamp": start})
+            # NULL end times come back as None or NaN.
+            if (
+                end is not None
+                and end == end
+                and (end_ts is None or end <= end_ts)
+                and span["span_id"] not in ended
+            ):
+                rows.append(
+                    {
+                        "record_type": "span_end",
+                        "span_id": span["span_id"],
+                        "thread_id": span["thread_id"],
+                        "timestamp": int(end),
                     }
+                )
+            if span["parent_id"] >= 0:
+                wanted.add(int(span["parent_id"]))
+    rows.sort(key=_row_timestamp)
+    return rows
+
+
+def _timestamp_range(rows) -> tuple:
+    """``(min, max)`` row timestamp, ``(0, 0)`` without rows."""
+    lo = hi = None
+    for row in rows():
+        timestamp = row.get("timestamp")
+        if timestamp is None:
+            continue
+        lo = timestamp if lo is None else min(lo, timestamp)
+        hi = timestamp if hi is None else max(hi, timestamp)
+    return (lo or 0, hi or 0)
+
+
+def _counter_samples(engine, sql: str, columns: List[str], lo: int, hi: int) -> list:
+    """``(timestamp_ns, column, value)`` of the counter samples between the
+    ``lo`` and ``hi`` ns timestamps, oldest first."""
+    query = (
+        f"SELECT * FROM ({sql}) AS counters "
+        f"WHERE ts >= {lo // 1000} AND ts <= {hi // 1000} ORDER BY ts"
+    )
+    try:
+        df = engine.query(query)
+    except Exception as e:
+        log.debug("chrome-tracing: counter source failed: %s", e)
+        return []
+    if df is None or df.empty or "ts" not in df.columns:
+        return []
+    present = [c for c in columns if c in df.columns]
+    samples = []
+    for ts, *values in df[["ts", *present]].itertuples(index=False, name=None):
+        for column, value in zip(present, values):
+            if isinstance(value, bool) or not isinstance(value, numbers.Real):
+                continue
+            if math.isnan(value):
+                continue
+            samples.append((int(ts) * 1000, column, value))
+    return samples
 
-            # Second pass: convert events to Chrome tracing format
-            for row in df_list:
-                record_type = row.get("record_type", "")
-                timestamp = row.get("timestamp", 0)
-                name = row.get("name", "unknown")
-                trace_id = row.get("trace_id", 0)
-                span_id = row.get("span_id", 0)
-                thread_id = row.get("thread_id", 0)
-                phase = row.get("phase", "")
-
-                # Convert nanoseconds to microseconds
-                ts_micros = (timestamp - min_timestamp) // 1000
-                # Use trace_id from span_start if available, otherwise use current trace_id
-                pid = trace_id
-                tid = thread_id
-
-                if record_type == "span_start":
-                    # Store span start information with trace_id for matching
-                    key = (span_id, thread_id)
-                    span_starts[key] = (ts_micros, name, phase, pid)
-                    chrome_event = {
-                        "name": name,
-                        "cat": phase if phase else "span",
-                        "ph": "B",
-                        "ts": ts_micros,
-                        "pid": pid,
-                        "tid": tid,
+
+def _process_label() -> str:
+    script = os.path.basename(sys.argv[0]) if sys.argv and sys.argv[0] else ""
+    return f"{script or 'python'} [{os.getpid()}]"
+
+
+def _chrome_trace_events(
+    rows, spans_filtered: bool = False, counters: Optional[list] = None
+) -> Iterator[dict]:
+    """Yield Chrome tracing events for ``python.trace_event`` rows.
+
+    ``rows`` is called once per pass and must return a fresh row iterator.
+    With ``spans_filtered``, span ends and events whose ``span_start`` is not
+    among the rows are skipped instead of emitted standalone.
+
+    Lanes whose rows carry a ``thread_name`` are preceded by ``process_name``
+    and ``thread_name`` metadata events (``ph: "M"``), so Perfetto shows names
+    instead of bare ids. Each trace is its own process lane.
+
+    Span links become flow events: an arrow (``ph: "s"`` / ``"f"``) from the
+    start of the linked span to the start of the linking one, drawn when both
+    spans are among the rows.
+
+    ``counters`` holds ``(timestamp_ns, column, value)`` samples, oldest
+    first; they are emitted as counter events merged into the span stream
+    by timestamp.
+    """
+    thread_names = {}
+    for row in rows():
+        name = row.get("thread_name")
+        if isinstance(name, str) and name:
+            thread_names.setdefault(row.get("thread_id", 0), name)
+    min_timestamp = _timestamp_range(rows)[0]
+
+    process_label = _process_label()
+    counter_events = iter(
+        {
+            "name": column,
+            "ph": "C",
+            "ts": (timestamp - min_timestamp) // 1000,
+            "pid": 0,
+            "tid": 0,
+            "args": {column: value},
+        }
+        for timestamp, column, value in counters or ()
+    )
+    pending = next(counter_events, None)
+    if pending is not None:
+        yield {
+            "name": "process_name",
+            "ph": "M",
+            "pid": 0,
+            "tid": 0,
+            "args": {"name": f"{process_label} counters"},
+        }
+    named_pids = set()
+    named_lanes = set()
+    for event in _chrome_span_events(rows, spans_filtered, min_timestamp):
+        while pending is not None and pending["ts"] <= event["ts"]:
+            yield pending
+            pending = next(counter_events, None)
+        pid, tid = event["pid"], event["tid"]
+        thread_name = thread_names.get(tid)
+        if thread_name and (pid, tid) not in named_lanes:
+            named_lanes.add((pid, tid))
+            if pid not in named_pids:
+                named_pids.add(pid)
+                yield {
+                    "name": "process_name",
+                    "ph": "M",
+                    "pid": pid,
+                    "tid": 0,
+                    "args": {"name": f"{process_label} trace {pid}"},
+                }
+            yield {
+                "name": "thread_name",
+                "ph": "M",
+                "pid": pid,
+                "tid": tid,
+                "args": {"name": thread_name},
+            }
+        yield event
+    while pending is not None:
+        yield pending
+        pending = next(counter_events, None)
+
+
+def _pytorch_trace() -> Optional[dict]:
+    """The PyTorch profiler's last chrome trace, ``None`` without one."""
+    try:
+        import __main__
+        from probing.profiling.torch_profiler import ProfilerController, get_controller
+
+        state = getattr(__main__, "__probing__", None) or {}
+        controller = state.get("global_profiler") or get_controller()
+        if not isinstance(controller, ProfilerController):
+            return None
+        timeline = controller.export_timeline()
+        return json.loads(timeline) if timeline else None
+    except Exception as e:
+        log.debug("chrome-tracing: no PyTorch timeline: %s", e)
+        return None
+
+
+def _combined_events(events: Iterator[dict], rows, torch_trace) -> Iterator[dict]:
+    """``events`` (probing rows) followed by the ``torch_trace`` events, both
+    relative to the earlier of the two starts.
+
+    PyTorch timestamps are µs after ``baseTimeNanoseconds`` when the trace
+    carries it, µs since epoch otherwise; probing rows are ns since epoch.
+    """
+    torch_events = (torch_trace or {}).get("traceEvents") or []
+    base_us = ((torch_trace or {}).get("baseTimeNanoseconds") or 0) / 1000
+    torch_start = min(
+        (
+            event["ts"]
+            for event in torch_events
+            if event.get("ph") != "M" and isinstance(event.get("ts"), numbers.Real)
+        ),
+        default=None,
+    )
+    if torch_start is None:
+        yield from events
+        return
+    origin = torch_start + base_us
+    if any(True for _ in rows()):
+        probing_start = _timestamp_range(rows)[0] / 1000
+        origin = min(origin, probing_start)
+        shift = probing_start - origin
+        for event in events:
+            if shift and "ts" in event:
+                event["ts"] = round(event["ts"] + shift, 3)
+            yield event
+    yield from _pytorch_lane_events(torch_events, base_us - origin)
+
+
+def _pytorch_lane_events(events: List[dict], offset_us: float) -> Iterator[dict]:
+    """PyTorch profiler ``events`` moved by ``offset_us``, each profiler pid on
+    its own lane from ``PYTORCH_PID_BASE`` up, named after the profiler's
+    ``process_name`` (or pid) with a ``pytorch`` prefix."""
+    names = {}
+    for event in events:
+        if event.get("ph") == "M" and event.get("name") == "process_name":
+            names.setdefault(event.get("pid"), (event.get("args") or {}).get("name"))
+    lanes = {}
+    for event in events:
+        pid = event.get("pid")
+        if pid not in lanes:
+            lanes[pid] = PYTORCH_PID_BASE + len(lanes)
+            yield {
+                "name": "process_name",
+                "ph": "M",
+                "pid": lanes[pid],
+                "tid": 0,
+                "args": {"name": f"pytorch {names.get(pid) or pid}"},
+            }
+        if event.get("ph") == "M" and event.get("name") == "process_name":
+            continue
+        event = dict(event, pid=lanes[pid])
+        if event.get("ph") != "M" and isinstance(event.get("ts"), numbers.Real):
+            event["ts"] = round(event["ts"] + offset_us, 3)
+        yield event
+
+
+def _span_category(phase: str, failed: bool) -> str:
+    """Chrome ``cat`` of a span slice; spans that raised are ``error``."""
+    if failed:
+        return ERROR_CATEGORY
+    return phase if phase else "span"
+
+
+def _chrome_span_events(
+    rows, spans_filtered: bool, min_timestamp: int
+) -> Iterator[dict]:
+    """Span/event conversion behind :func:`_chrome_trace_events`; timestamps
+    become µs after ``min_timestamp``."""
+
+    # (timestamp, name, phase, trace_id) of each open span by (span_id,
+    # thread_id), so span_end rows (which may carry trace_id=0) match their
+    # start on any thread. Rows are in time order: a start always comes
+    # before its end, which closes it, so only open spans are held while
+    # the events are yielded as they are converted.
+    span_starts = {}
+    # Spans that recorded an ``exception`` event get their own category.
+    failed = set()
+    # Link targets are addressed by (trace_id, span_id); only the starts of
+    # spans some row links to are kept.
+    linked = set()
+    for row in rows():
+        if row.get("record_type") == "event" and row.get("name") == EXCEPTION_EVENT:
+            failed.add((row.get("span_id", 0), row.get("thread_id", 0)))
+        linked.update(_link_keys(row.get("links")))
+    link_targets = {}
+    if linked:
+        for row in rows():
+            target = (row.get("trace_id", 0), row.get("span_id", 0))
+            if row.get("record_type") == "span_start" and target in linked:
+                link_targets[target] = (
+                    row.get("timestamp", 0),
+                    row.get("thread_id", 0),
+                )
+
+    # Second pass: convert events to Chrome tracing format
+    flow_id = 0
+    for row in rows():
+        record_type = row.get("record_type", "")
+        timestamp = row.get("timestamp", 0)
+        name = row.get("name", "unknown")
+        trace_id = row.get("trace_id", 0)
+        span_id = row.get("span_id", 0)
+        thread_id = row.get("thread_id", 0)
+        phase = row.get("phase", "")
+
+        # Convert nanoseconds to microseconds
+        ts_micros = (timestamp - min_timestamp) // 1000
+        pid = trace_id
+        tid = thread_id
+        key = (span_id, thread_id)
+
+        if record_type == "span_start":
+            # A reused key pairs with its latest start.
+            span_starts[key] = (timestamp, name, phase, pid)
+            chrome_event = {
+                "name": name,
+                "cat": _span_category(phase, key in failed),
+                "ph": "B",
+                "ts": ts_micros,
+                "pid": pid,
+                "tid": tid,
+            }
+            if row.get("location"):
+                chrome_event["args"] = {"location": row.get("location")}
+            yield chrome_event
+        elif record_type == "span_end":
+            start_info = span_starts.pop(key, None)
+            if start_info:
+                start_time, start_name, start_phase, start_pid = start_info
+                start_ts = (start_time - min_timestamp) // 1000
+                # B/E pairs must agree on name, cat, pid and tid.
+                chrome_event = {
+                    "name": start_name,
+                    "cat": _span_category(start_phase, key in failed),
+                    "ph": "E",
+                    "ts": ts_micros,
+                    "pid": start_pid,
+                    "tid": tid,
+                }
+                # Chrome tracing B/E events don't need dur; kept for debugging
+                dur = ts_micros - start_ts
+                if dur > 0:
+                    chrome_event["dur"] = dur
+                yield chrome_event
+                for target_pid, (target_ts, target_tid) in _link_targets(
+                    row.get("links"), link_targets
+                ):
+                    flow_id += 1
+                    flow = {"name": "link", "cat": "link", "id": flow_id}
+                    yield {
+                        **flow,
+                        "ph": "s",
+                        "ts": (target_ts - min_timestamp) // 1000,
+                        "pid": target_pid,
+                        "tid": target_tid,
                     }
-                    if row.get("location"):
-                        chrome_event["args"] = {"location": row.get("location")}
-                    trace_events.append(chrome_event)
-                elif record_type == "span_end":
-                    # Try to find matching span_start
-                    key = (span_id, thread_id)
-                    start_info = span_starts.get(key)
-
-                    if start_info:
-                        # Found matching span_start that was already processed
-                        start_ts, start_name, start_phase, start_pid = start_info
-                        # Use the pid from span_start to ensure matching
-                        chrome_event = {
-                            "name": start_name,  # Must match span_start name
-                            "cat": (
-                                start_phase if start_phase else "span"
-                            ),  # Must match span_start cat
-                            "ph": "E",
-                            "ts": ts_micros,
-                            "pid": start_pid,  # Use pid from span_start
-                            "tid": tid,  # Must match span_start tid
-                        }
-                        # Note: Chrome tracing B/E events don't need dur, but we can add it for debugging
-                        dur = ts_micros - start_ts
-                        if dur > 0:
-                            chrome_event["dur"] = dur
-                        trace_events.append(chrome_event)
-                        # Remove from span_starts to avoid duplicate matches
-                        del span_starts[key]
-                    else:
-                        # No matching span_start found in processed events, try lookup
-                        lookup_info = span_start_lookup.get(key)
-                        if lookup_info:
-                            # Use trace_id and other info from span_start
-                            start_pid = lookup_info["trace_id"]
-                            start_ts = (
-                                lookup_info["timestamp"] - min_timestamp
-                            ) // 1000
-                            start_name = lookup_info["name"]
-                            start_phase = lookup_info["phase"]
-                            chrome_event = {
-                                "name": start_name,  # Must match span_start name
-                                "cat": (
-                                    start_phase if start_phase else "span"
-                                ),  # Must match span_start cat
-                                "ph": "E",
-                                "ts": ts_micros,
-                                "pid": start_pid,  # Use pid from span_start
-                                "tid": tid,  # Must match span_start tid
-                            }
-                            dur = ts_micros - start_ts
-                            if dur > 0:
-                                chrome_event["dur"] = dur
-                            trace_events.append(chrome_event)
-                        else:
-                            # No matching span_start found at all
-                            # This might happen if span_start was filtered out by limit
-                            # Create a standalone end event with warning
-                            chrome_event = {
-                                "name": name if name else "unknown_span",
-                                "cat": "span",
-                                "ph": "E",
-                                "ts": ts_micros,
-                                "pid": (
-                                    pid if pid > 0 else 1
-                                ),  # Use current pid or default
-                                "tid": tid,
-                            }
-                            trace_events.append(chrome_event)
-                elif record_type == "event":
-                    chrome_event = {
-                        "name": name,
-                        "cat": "event",
-                        "ph": "i",
-                        "ts": ts_micros,
-                        "pid": pid,
+                    yield {
+                        **flow,
+                        "ph": "f",
+                        "bp": "e",
+                        "ts": start_ts,
+                        "pid": start_pid,
                         "tid": tid,
-                        "s": "t",
                     }
-                    if row.get("event_attributes"):
-                        try:
-                            chrome_event["args"] = json.loads(
-                                row.get("event_attributes")
-                            )
-                        except (json.JSONDecodeError, TypeError, ValueError):
-                            pass
-                    trace_events.append(chrome_event)
-
-        chrome_trace = {"traceEvents": trace_events, "displayTimeUnit": "ms"}
-        return json.dumps(chrome_trace, indent=2)
-    except Exception as e:
+            elif not spans_filtered:
+                # span_start was filtered out by the limit: standalone end event
+                yield {
+                    "name": name if name else "unknown_span",
+                    "cat": "span",
+                    "ph": "E",
+                    "ts": ts_micros,
+                    "pid": pid if pid > 0 else 1,
+                    "tid": tid,
+                }
+        elif record_type == "event":
+            if spans_filtered and key not in span_starts:
+                continue
+            chrome_event = {
+                "name": name,
+                "cat": "event",
+                "ph": "i",
+                "ts": ts_micros,
+                "pid": pid,
+                "tid": tid,
+                "s": "t",
+            }
+            if row.get("event_attributes"):
+                # Typed JSON values pass through as native args (numbers stay
+                # numbers, so Perfetto can aggregate them).
+                try:
+                    args = json.loads(row.get("event_attributes"))
+                except (json.JSONDecodeError, TypeError, ValueError):
+                    args = None
+                if isinstance(args, dict) and args:
+                    chrome_event["args"] = args
+            yield chrome_event
+
+
+def _link_keys(links) -> Iterator[tuple]:
+    """``(trace_id, span_id)`` of each span in a ``links`` JSON list."""
+    if not isinstance(links, str) or not links:
+        return
+    try:
+        parsed = json.loads(links)
+    except (json.JSONDecodeError, ValueError):
+        return
+    for link in parsed if isinstance(parsed, list) else ():
+        if isinstance(link, dict):
+            yield (link.get("trace_id"), link.get("span_id"))
+
+
+def _link_targets(links, targets) -> Iterator[tuple]:
+    """``(trace_id, (timestamp, thread_id))`` of each linked span in ``targets``."""
+    for key in _link_keys(links):
+        if key in targets:
+            yield key[0], targets[key]
+
+
+@ext_handler("pythonext", "trace/summary")
+def get_trace_summary(
+    start_us: Optional[int] = None,
+    end_us: Optional[int] = None,
+    baseline_start_us: Optional[int] = None,
+    baseline_end_us: Optional[int] = None,
+) -> str:
+    """Per-span-name p50/p95 over a time window, optionally compared to a baseline.
+
+    Args:
+        start_us: Current window start (µs since epoch, inclusive)
+        end_us: Current window end (µs since epoch, exclusive)
+        baseline_start_us: Baseline window start; enables comparison mode
+        baseline_end_us: Baseline window end
+
+    Returns:
+        JSON with ``spans`` (summary) or ``deltas`` sorted by absolute regression
+    """
+    from probing.tracing import compare
+
+    try:
+        current = compare.query_window(start_us, end_us)
+        if baseline_start_us is None and baseline_end_us is None:
+            return json.dumps(
+                {"mode": "summary", "spans": compare.stats_to_dict(current)}
+            )
+        baseline = compare.query_window(baseline_start_us, baseline_end_us)
+        deltas = compare.compare(baseline, current)
         return json.dumps(
-            {"error": str(e), "trace": traceback.format_exc(), "traceEvents": []}
+            {
+                "mode": "compare",
+                "min_samples": compare.MIN_SAMPLES,
+                "rel_threshold": compare.REL_THRESHOLD,
+                "deltas": compare.deltas_to_dict(deltas),
+            }
         )
+    except Exception as e:
+        return json.dumps({"error": str(e)})
 
 
 @ext_handler("pythonext", "pytorch/timeline")
@@ -498,16 +1133,17 @@ def list_trace(prefix: Optional[str] = None) -> str:
 
 @ext_handler("pythonext", "trace/show")
 def show_trace() -> str:
-    """Show current trace configuration.
+    """List active traces with their backend, overhead and record counts.
 
     Returns:
-        JSON string containing trace configuration
+        JSON list of objects with ``function``, ``backend``, ``calls``,
+        ``overhead_us`` (moving average of microseconds added per call),
+        ``records``, ``records_dropped`` and ``last_record_ts`` (epoch seconds)
     """
     try:
-        from probing.inspect.trace import show_trace as _show_trace
+        from probing.inspect.trace import trace_stats
 
-        result = _show_trace()
-        return result if result else "[]"
+        return json.dumps(trace_stats())
     except Exception as e:
         return json.dumps({"error": str(e)})
 
@@ -533,7 +1169,7 @@ def start_trace(
         JSON string with success status
     """
     try:
-        from probing.inspect.trace import trace
+        from probing.inspect.trace import suggest_traceable, trace
 
         # Determine whether to use watch or silent_watch based on print_to_terminal
         # This matches the original Rust logic
@@ -545,10 +1181,22 @@ def start_trace(
             silent_watch_list = watch or []
 
         depth_val = 1 if depth is None else depth
-        trace(
+        status = trace(
             function, watch=watch_list, silent_watch=silent_watch_list, depth=depth_val
         )
-        return json.dumps({"success": True, "message": f"Started tracing {function}"})
+        if status is None:
+            error = f"Could not trace {function}; see logs"
+            suggestions = suggest_traceable(function)
+            if suggestions:
+                error += f". Did you mean: {', '.join(suggestions)}?"
+            return json.dumps(
+                {"success": False, "error": error, "suggestions": suggestions}
+            )
+        if status == "updated":
+            message = f"Updated trace config for {function}"
+        else:
+            message = f"Started tracing {function}"
+        return json.dumps({"success": True, "message": message})
     except Exception as e:
         return json.dumps({"success": False, "error": str(e)})
 
@@ -566,12 +1214,37 @@ def stop_trace(function: str) -> str:
     try:
         from probing.inspect.trace import untrace
 
-        untrace(function)
+        if not untrace(function):
+            return json.dumps(
+                {"success": False, "error": f"{function} is not being traced"}
+            )
         return json.dumps({"success": True, "message": f"Stopped tracing {function}"})
     except Exception as e:
         return json.dumps({"success": False, "error": str(e)})
 
 
+@ext_handler("pythonext", "trace/reset")
+def reset_trace() -> str:
+    """Restore every traced function (recovery when trace state is stale).
+
+    Returns:
+        JSON string with success status and the names that were traced
+    """
+    try:
+        from probing.inspect.trace import reset_traces
+
+        restored = reset_traces()
+        return json.dumps(
+            {
+                "success": True,
+                "restored": restored,
+                "message": f"Restored {len(restored)} traced function(s)",
+            }
+        )
+    except Exception as e:
+        return json.dumps({"success": False, "error": str(e)})
+
+
 @ext_handler("pythonext", "magics")
 def get_magics_list() -> str:
     """Get magic commands as JSON for UI quick actions.
@@ -785,6 +1458,25 @@ def crash_release() -> str:
     return '{"ok":true,"released":true}'
 
 
+@ext_handler("pythonext", "gc")
+def run_gc(generation: Optional[int] = None) -> str:
+    """Run garbage collection and report collected objects, timing and RSS."""
+    from probing.inspect.gc import collect
+
+    try:
+        return json.dumps(collect(generation))
+    except ValueError as e:
+        return json.dumps({"error": str(e)})
+
+
+@ext_handler("pythonext", "detach")
+def run_detach() -> str:
+    """Remove probing's Python hooks; part of ``POST /apis/detach``."""
+    from probing.inspect.detach import detach
+
+    return json.dumps(detach())
+
+
 @ext_handler("pythonext", "trace/variables")
 def get_trace_variables(function: Optional[str] = None, limit: int = 100) -> str:
     """Get trace variables from database.
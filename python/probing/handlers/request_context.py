@@ -0,0 +1,60 @@
+"""Per-request correlation id for Python handlers.
+
+The Rust server assigns every HTTP request an id (echoed as ``X-Request-Id``)
+and passes it to :func:`probing.handlers.router.handle_request`. While a handler
+runs, the id is available via :func:`current_request_id` and every
+``logging.LogRecord`` created on that thread gets a ``request_id`` attribute
+(``None`` outside a request), so formatters can use ``%(request_id)s``.
+"""
+
+from __future__ import annotations
+
+import contextlib
+import contextvars
+import logging
+from typing import Iterator, Optional
+
+_request_id: contextvars.ContextVar[Optional[str]] = contextvars.ContextVar(
+    "probing_request_id", default=None
+)
+
+_installed = False
+
+
+def current_request_id() -> Optional[str]:
+    """Id of the HTTP request currently being served, if any."""
+    return _request_id.get()
+
+
+@contextlib.contextmanager
+def request_scope(request_id: Optional[str]) -> Iterator[None]:
+    """Bind ``request_id`` for the duration of the block."""
+    token = _request_id.set(request_id)
+    try:
+        yield
+    finally:
+        _request_id.reset(token)
+
+
+def install_log_record_factory() -> None:
+    """Stamp ``record.request_id`` on every log record (idempotent).
+
+    Wraps the current factory instead of replacing it so application-installed
+    factories keep working.
+    """
+    global _installed
+    if _installed:
+        return
+    previous = logging.getLogRecordFactory()
+
+    def factory(*args, **kwargs):
+        record = previous(*args, **kwargs)
+        if not hasattr(record, "request_id"):
+            record.request_id = _request_id.get()
+        return record
+
+    logging.setLogRecordFactory(factory)
+    _installed = True
+
+
+__all__ = ["current_request_id", "request_scope", "install_log_record_factory"]
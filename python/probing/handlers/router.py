@@ -6,12 +6,19 @@ with automatic parameter parsing and validation.
 
 import inspect
 import json
+import logging
 import traceback
-from typing import Any, Callable, Dict, List, Optional, Tuple, Union
+from typing import Any, Callable, Dict, Iterator, List, Optional, Tuple, Union
+
+from probing.handlers.request_context import install_log_record_factory, request_scope
+
+logger = logging.getLogger(__name__)
 
 # Global router state
 _handlers: Dict[str, Dict[str, Any]] = {}
 
+install_log_record_factory()
+
 
 def _normalize_path(path: str) -> str:
     """Normalize path by removing leading slashes."""
@@ -147,17 +154,24 @@ def _parse_params(
 
 
 def handle_request(
-    path: str, params: Dict[str, str], body: Optional[str] = None
-) -> str:
+    path: str,
+    params: Dict[str, str],
+    body: Optional[str] = None,
+    request_id: Optional[str] = None,
+) -> Union[str, bytes, Iterator[str], Iterator[bytes]]:
     """Handle a request using the global router.
 
     Args:
         path: Request path
         params: Query parameters as string dictionary
         body: Optional POST body (for ``uses_body`` handlers)
+        request_id: Server-assigned ``X-Request-Id``; bound for the handler's
+            duration so its log records carry it (see ``request_context``)
 
     Returns:
-        JSON string response
+        JSON string response, or an iterator of ``str`` chunks for handlers
+        that stream (see ``probing.handlers.streaming``); binary handlers
+        return ``bytes`` or ``bytes`` chunks instead
 
     Example:
         >>> # Clean up and register a test handler
@@ -185,6 +199,36 @@ def handle_request(
         >>> "error" in result
         True
     """
+    with request_scope(request_id):
+        result = _dispatch(path, params, body)
+    if isinstance(result, (str, bytes)):
+        return result
+    return _scoped_chunks(result, path, request_id)
+
+
+def _scoped_chunks(
+    chunks: Iterator[str], path: str, request_id: Optional[str]
+) -> Iterator[str]:
+    """Re-bind the request id while each chunk is produced.
+
+    An error here cannot become an error response anymore (the status is
+    already sent); it is logged and re-raised so the server aborts the body.
+    """
+    while True:
+        with request_scope(request_id):
+            try:
+                chunk = next(chunks)
+            except StopIteration:
+                return
+            except Exception as e:
+                logger.warning("streaming handler %s failed: %s", path, e)
+                raise
+        yield chunk
+
+
+def _dispatch(
+    path: str, params: Dict[str, str], body: Optional[str]
+) -> Union[str, bytes, Iterator[str], Iterator[bytes]]:
     try:
         normalized_path = _normalize_path(path)
 
@@ -209,8 +253,11 @@ def handle_request(
                     return json.dumps({"error": error})
 
                 result = handler_info["function"](**parsed_params)
-            return result if isinstance(result, str) else json.dumps(result)
+            if isinstance(result, (str, bytes, Iterator)):
+                return result
+            return json.dumps(result)
         except Exception as e:
+            logger.warning("handler %s failed: %s", normalized_path, e)
             return json.dumps(
                 {
                     "error": str(e),
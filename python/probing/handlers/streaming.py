@@ -0,0 +1,63 @@
+"""Chunked JSON writers for large handler responses.
+
+A handler may return an iterator of ``str`` chunks instead of one string; the
+server then streams it with chunked transfer encoding, pulling one chunk at a
+time, so neither side holds the whole document (see ``router.handle_request``).
+Report errors *before* the first chunk by returning a plain JSON string: once
+streaming has started the HTTP status is already sent.
+"""
+
+from __future__ import annotations
+
+import json
+from typing import Any, Iterable, Iterator
+
+# Events per chunk; one chunk is roughly FLUSH_EVERY * ~150 bytes.
+FLUSH_EVERY = 1000
+# Compact items, one per line: no indentation or padding to pay for on
+# exports of hundreds of thousands of events.
+_SEPARATORS = (",", ":")
+
+
+def _scalar(obj: Any) -> Any:
+    # numpy / pandas scalars (rows from ``DataFrame.itertuples``).
+    item = getattr(obj, "item", None)
+    if callable(item):
+        return item()
+    raise TypeError(f"{type(obj).__name__} is not JSON serializable")
+
+
+def json_array_chunks(
+    items: Iterable[Any],
+    head: str = "[",
+    tail: str = "]",
+    flush_every: int = FLUSH_EVERY,
+) -> Iterator[str]:
+    """Encode ``items`` as a JSON array wrapped in ``head``/``tail``.
+
+    ``head``/``tail`` let the array sit inside an object, e.g.
+    ``head='{"traceEvents": ['`` and ``tail="]}"``.
+
+    >>> "".join(json_array_chunks(range(3), flush_every=2))
+    '[0,\\n1,\\n2]'
+    >>> json.loads("".join(json_array_chunks([], head='{"a": [', tail="]}")))
+    {'a': []}
+    """
+    buf = [head]
+    pending = 0
+    first = True
+    for item in items:
+        if not first:
+            buf.append(",\n")
+        buf.append(json.dumps(item, separators=_SEPARATORS, default=_scalar))
+        first = False
+        pending += 1
+        if pending >= flush_every:
+            yield "".join(buf)
+            buf = []
+            pending = 0
+    buf.append(tail)
+    yield "".join(buf)
+
+
+__all__ = ["FLUSH_EVERY", "json_array_chunks"]
@@ -0,0 +1,109 @@
+"""Remove probing's Python-side hooks (``probing <pid> detach``).
+
+``detach()`` undoes what probing installed in the interpreter: traced
+functions, PyTorch module and training-phase hooks, the ``Thread.start``
+wrapper, the import hook, the overhead governor and GPU stream capture. Each
+is a component reported on its own, so one that cannot be removed does not
+keep the others in place. Modules that were never imported had nothing
+installed and are not imported now.
+"""
+
+from __future__ import annotations
+
+import sys
+from typing import Any, Callable, Dict, List, Optional
+
+Component = Dict[str, Any]
+
+
+def _loaded(name: str):
+    """``sys.modules[name]``, or ``None`` when probing never imported it."""
+    return sys.modules.get(name)
+
+
+def _function_traces() -> Optional[str]:
+    trace = _loaded("probing.inspect.trace")
+    if trace is None:
+        return None
+    restored = trace.reset_traces()
+    return f"restored {len(restored)} traced function(s)"
+
+
+def _torch_module_hooks() -> Optional[str]:
+    torch_hooks = _loaded("probing.profiling.torch")
+    if torch_hooks is None:
+        return None
+    count = len(torch_hooks.HOOK_CACHE)
+    torch_hooks.uninstall_hooks()
+    return f"removed hooks from {count} module(s) and optimizer(s)"
+
+
+def _training_phase_hooks() -> Optional[str]:
+    phases = _loaded("probing.tracing.hooks")
+    if phases is None:
+        return None
+    trackers = list(phases._REGISTRY.values())
+    phases._REGISTRY.clear()
+    for tracker in trackers:
+        tracker.uninstall()
+    return f"removed {len(trackers)} model/optimizer tracker(s)"
+
+
+def _thread_tracking() -> Optional[str]:
+    threads = _loaded("probing.inspect.threads")
+    if threads is None or not threads.is_installed():
+        return None
+    threads.uninstall()
+    return "restored threading.Thread.start"
+
+
+def _import_hook() -> Optional[str]:
+    import_hook = _loaded("probing.hooks.import_hook")
+    if import_hook is None:
+        return None
+    finders = [f for f in sys.meta_path if isinstance(f, import_hook.ProbingFinder)]
+    for finder in finders:
+        sys.meta_path.remove(finder)
+    return f"removed {len(finders)} finder(s) from sys.meta_path" if finders else None
+
+
+def _overhead_governor() -> Optional[str]:
+    governor = _loaded("probing.profiling.governor")
+    if governor is None or governor._thread is None:
+        return None
+    governor.stop_governor()
+    return "stopped; governed options restored"
+
+
+def _gpu_stream_capture() -> Optional[str]:
+    streams = _loaded("probing.profiling.cuda_streams")
+    if streams is None or streams._capture is None:
+        return None
+    streams.stop_stream_capture()
+    return "stopped"
+
+
+COMPONENTS: List[tuple[str, Callable[[], Optional[str]]]] = [
+    ("function traces", _function_traces),
+    ("torch module hooks", _torch_module_hooks),
+    ("training phase hooks", _training_phase_hooks),
+    ("thread tracking", _thread_tracking),
+    ("import hook", _import_hook),
+    ("overhead governor", _overhead_governor),
+    ("gpu stream capture", _gpu_stream_capture),
+]
+
+
+def detach() -> Dict[str, List[Component]]:
+    """Run every component's teardown; failures are reported, not raised."""
+    components = []
+    for name, teardown in COMPONENTS:
+        try:
+            detail = teardown()
+        except Exception as e:
+            components.append({"name": name, "ok": False, "error": str(e)})
+            continue
+        components.append(
+            {"name": name, "ok": True, "detail": detail or "not active"}
+        )
+    return {"components": components}
@@ -0,0 +1,73 @@
+"""On-demand garbage collection (``probing <pid> gc``).
+
+``collect()`` runs :func:`gc.collect` and reports what it did: objects
+collected and found uncollectable (from the per-generation counters in
+:func:`gc.get_stats`), how long the collection took, and the process RSS
+before and after. Each run is also recorded in ``probe.events``::
+
+    SELECT * FROM probe.events WHERE kind = 'gc'
+"""
+
+from __future__ import annotations
+
+import gc
+import json
+import os
+import time
+from typing import Any, Dict, List, Optional
+
+EVENT_KIND = "gc"
+
+
+def _rss_bytes() -> Optional[int]:
+    """Resident set size from ``/proc/self/statm``; ``None`` where unavailable."""
+    try:
+        with open("/proc/self/statm") as f:
+            pages = int(f.read().split()[1])
+    except (OSError, ValueError, IndexError):
+        return None
+    return pages * os.sysconf("SC_PAGE_SIZE")
+
+
+def _totals(stats: List[Dict[str, int]]) -> Dict[str, int]:
+    return {
+        key: sum(s.get(key, 0) for s in stats)
+        for key in ("collections", "collected", "uncollectable")
+    }
+
+
+def collect(generation: Optional[int] = None) -> Dict[str, Any]:
+    """Run a collection of ``generation`` (0-2; all generations when None)."""
+    if generation is not None and not 0 <= generation <= 2:
+        raise ValueError(f"generation must be 0, 1 or 2, got {generation}")
+    target = 2 if generation is None else generation
+
+    rss_before = _rss_bytes()
+    before = _totals(gc.get_stats())
+    start = time.perf_counter()
+    unreachable = gc.collect(target)
+    duration = time.perf_counter() - start
+    after = _totals(gc.get_stats())
+    rss_after = _rss_bytes()
+
+    result = {
+        "generation": target,
+        "unreachable": unreachable,
+        "collected": after["collected"] - before["collected"],
+        "uncollectable": after["uncollectable"] - before["uncollectable"],
+        "garbage": len(gc.garbage),
+        "duration_ms": duration * 1000.0,
+        "rss_before": rss_before,
+        "rss_after": rss_after,
+    }
+    _record(result)
+    return result
+
+
+def _record(result: Dict[str, Any]) -> None:
+    try:
+        import probing._core as core
+
+        core.record_probe_event(EVENT_KIND, json.dumps(result))
+    except (ImportError, AttributeError):
+        pass
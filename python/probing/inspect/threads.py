@@ -0,0 +1,251 @@
+"""Thread lifecycle tracking (``python.threads``).
+
+``install()`` wraps :meth:`threading.Thread.start` so every thread started
+afterwards records a ``start`` row when its ``run`` begins and an ``end`` row
+(with ``lifetime_ns``) when it returns or raises. The creator's native tid is
+captured at ``start()`` time, which gives each thread an ancestry chain.
+
+SQL::
+
+    SELECT * FROM python.threads                        -- lifecycle rows
+    SELECT * FROM python.`probing.inspect.threads.alive_threads()`
+    SELECT * FROM python.`probing.inspect.threads.ancestry(12345)`
+
+``uninstall()`` restores ``Thread.start``; threads already started keep their
+(harmless) wrapper until they exit. Set ``PROBING_THREAD_TRACKING=0`` to keep
+it from being installed when probing activates.
+"""
+
+from __future__ import annotations
+
+import os
+import sys
+import threading
+import time
+from collections import OrderedDict
+from dataclasses import dataclass, field
+from typing import Any, Optional
+
+from probing.core.table import table
+from probing.util.env import parse_bool_flag
+
+EVENT_START = "start"
+EVENT_END = "end"
+
+# Threads remembered for ancestry after they exit (bounded; oldest evicted).
+_MAX_KNOWN = 4096
+
+_LOCK = threading.Lock()
+_ORIGINAL_START: Optional[Any] = None
+_UNTRACKED_START = threading.Thread.start
+# native tid -> ThreadInfo for alive and recently ended threads.
+_KNOWN: "OrderedDict[int, ThreadInfo]" = OrderedDict()
+
+
+@table("threads")
+@dataclass
+class Threads:
+    """Python thread creation/termination events."""
+
+    event: str = field(metadata={"doc": "start | end"})
+    time: int = field(metadata={"doc": "Event wall time (ns since epoch)"})
+    tid: int = field(metadata={"doc": "Native thread id (matches Stacks / CPU tid)"})
+    name: str = field(default="", metadata={"doc": "Thread name"})
+    target: str = field(
+        default="", metadata={"doc": "Target callable, or Class.run for subclasses"}
+    )
+    daemon: int = field(default=0, metadata={"doc": "1 for daemon threads"})
+    creator_tid: int = field(
+        default=-1, metadata={"doc": "Native tid of the thread that called start()"}
+    )
+    creator_name: str = field(default="", metadata={"doc": "Creator thread name"})
+    lifetime_ns: int = field(
+        default=0, metadata={"doc": "Run duration; set on end rows only"}
+    )
+
+
+@dataclass
+class ThreadInfo:
+    tid: int
+    name: str
+    target: str
+    daemon: bool
+    creator_tid: int
+    creator_name: str
+    started_ns: int
+    ended_ns: Optional[int] = None
+
+
+def tracking_enabled() -> bool:
+    explicit = parse_bool_flag(os.environ.get("PROBING_THREAD_TRACKING"))
+    return True if explicit is None else explicit
+
+
+def is_installed() -> bool:
+    return _ORIGINAL_START is not None
+
+
+def install() -> None:
+    """Wrap ``Thread.start``; idempotent."""
+    global _ORIGINAL_START
+    with _LOCK:
+        if _ORIGINAL_START is not None:
+            return
+        _ORIGINAL_START = threading.Thread.start
+        threading.Thread.start = _tracked_start
+
+
+def uninstall() -> None:
+    """Restore the original ``Thread.start``; idempotent."""
+    global _ORIGINAL_START
+    with _LOCK:
+        if _ORIGINAL_START is None:
+            return
+        threading.Thread.start = _ORIGINAL_START
+        _ORIGINAL_START = None
+
+
+def _target_name(thread: threading.Thread) -> str:
+    target = getattr(thread, "_target", None)
+    if target is None:
+        cls = type(thread)
+        return f"{cls.__qualname__}.run" if cls.__module__ != "threading" else ""
+    module = getattr(target, "__module__", None) or ""
+    qualname = getattr(target, "__qualname__", None) or repr(target)
+    return f"{module}.{qualname}" if module else qualname
+
+
+def _tracked_start(self: threading.Thread) -> None:
+    # Fall back to the import-time original when racing ``uninstall()``.
+    original = _ORIGINAL_START or _UNTRACKED_START
+    if "run" not in self.__dict__:
+        creator = threading.current_thread()
+        self.run = _wrap_run(self, self.run, threading.get_native_id(), creator.name)
+    original(self)
+
+
+def _wrap_run(thread: threading.Thread, run, creator_tid: int, creator_name: str):
+    def run_tracked():
+        info = None
+        try:
+            info = ThreadInfo(
+                tid=threading.get_native_id(),
+                name=thread.name,
+                target=_target_name(thread),
+                daemon=thread.daemon,
+                creator_tid=creator_tid,
+                creator_name=creator_name,
+                started_ns=time.time_ns(),
+            )
+            _remember(info)
+            _emit(EVENT_START, info)
+        except Exception:
+            pass
+        try:
+            run()
+        finally:
+            # A daemon thread can still be running while the interpreter shuts
+            # down; never let bookkeeping raise or touch a finalizing runtime.
+            if info is not None and not sys.is_finalizing():
+                try:
+                    info.ended_ns = time.time_ns()
+                    _emit(EVENT_END, info)
+                except Exception:
+                    pass
+
+    return run_tracked
+
+
+def _remember(info: ThreadInfo) -> None:
+    with _LOCK:
+        _KNOWN[info.tid] = info
+        _KNOWN.move_to_end(info.tid)
+        while len(_KNOWN) > _MAX_KNOWN:
+            _KNOWN.popitem(last=False)
+
+
+def _emit(event: str, info: ThreadInfo) -> None:
+    now = info.ended_ns if event == EVENT_END else info.started_ns
+    Threads(
+        event=event,
+        time=now,
+        tid=info.tid,
+        name=info.name,
+        target=info.target,
+        daemon=int(info.daemon),
+        creator_tid=info.creator_tid,
+        creator_name=info.creator_name,
+        lifetime_ns=(now - info.started_ns) if event == EVENT_END else 0,
+    ).save()
+
+
+def alive_threads() -> list[dict]:
+    """Currently alive threads with age; untracked ones have ``age_s`` -1."""
+    now = time.time_ns()
+    with _LOCK:
+        known = dict(_KNOWN)
+    rows = []
+    for thread in threading.enumerate():
+        tid = thread.native_id
+        if tid is None:
+            continue
+        info = known.get(tid)
+        tracked = info is not None and info.ended_ns is None
+        rows.append(
+            {
+                "tid": tid,
+                "name": thread.name,
+                "target": info.target if tracked else _target_name(thread),
+                "daemon": int(thread.daemon),
+                "creator_tid": info.creator_tid if tracked else -1,
+                "creator_name": info.creator_name if tracked else "",
+                "started_ns": info.started_ns if tracked else -1,
+                "age_s": (now - info.started_ns) / 1e9 if tracked else -1.0,
+            }
+        )
+    return rows
+
+
+def ancestry(tid: int) -> list[dict]:
+    """Creator chain for ``tid``, starting with the thread itself.
+
+    Stops at a thread that was not started while tracking was installed
+    (e.g. the main thread), which is still listed as the root.
+    """
+    with _LOCK:
+        known = dict(_KNOWN)
+    names = {t.native_id: t.name for t in threading.enumerate()}
+    chain: list[dict] = []
+    seen: set[int] = set()
+    current: Optional[int] = int(tid)
+    while current is not None and current >= 0 and current not in seen:
+        seen.add(current)
+        info = known.get(current)
+        chain.append(
+            {
+                "depth": len(chain),
+                "tid": current,
+                "name": info.name if info else names.get(current, ""),
+                "target": info.target if info else "",
+                "alive": int(current in names),
+            }
+        )
+        current = info.creator_tid if info else None
+    return chain
+
+
+def reset_for_tests() -> None:
+    uninstall()
+    with _LOCK:
+        _KNOWN.clear()
+
+
+__all__ = [
+    "Threads",
+    "alive_threads",
+    "ancestry",
+    "install",
+    "is_installed",
+    "tracking_enabled",
+    "uninstall",
+]
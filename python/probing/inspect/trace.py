@@ -1,9 +1,11 @@
 import logging
 import ctypes
 import contextlib
+import difflib
 import fnmatch
 import functools
 import inspect
+import itertools
 import json
 import os
 import re
@@ -12,7 +14,7 @@ import threading
 import time
 import types
 import warnings
-from dataclasses import dataclass
+from dataclasses import asdict, dataclass, field
 from types import FrameType, FunctionType, ModuleType
 from typing import Any, AnyStr, Callable, Dict, List, Set, Optional
 
@@ -45,6 +47,7 @@ def _trace_warn(message: str) -> None:
 thread_global = threading.local()
 internal_directories = os.path.dirname((lambda: 0).__code__.co_filename)
 
+# Traced name -> _Instrumentation (several names may alias one function).
 traced_functions = {}
 # Global dictionary to store probe attributes for functions
 # Key: function code object id, Value: dict with __probe_func__, __probe_watch__, __probe_depth__
@@ -82,6 +85,12 @@ TRACE_BLOCKLIST_TOPLEVEL = frozenset(
         "errno",
     }
 )
+# Smoothing factor of the per-call overhead moving average; the newest call
+# weighs this much, older calls fade geometrically.
+OVERHEAD_EMA_ALPHA = 0.2
+# Only hook the tracer knows; reported so clients can tell it apart from a
+# future sys.monitoring backend.
+TRACE_BACKEND = "settrace"
 # Pattern: only allow dotted identifiers (letters, digits, underscore, dot)
 TRACE_NAME_PATTERN = re.compile(r"^[a-zA-Z_][a-zA-Z0-9_.]*$")
 
@@ -142,6 +151,9 @@ class Variable:
         String representation of the variable value.
     value_type : str
         Type name of the variable value.
+    call_id : int
+        Id of the traced call the change belongs to; changes recorded during
+        the same call share it.
     """
 
     function_name: str
@@ -150,6 +162,11 @@ class Variable:
     variable_name: str
     value: str
     value_type: str
+    call_id: int
+
+
+# One id per traced call (one ProbingTracer each), shared by its records.
+_call_ids = itertools.count(1)
 
 
 class _TraceableCollector:
@@ -709,10 +726,18 @@ def probe(func, watch=None, silent_watch=None, depth=1):
             probe_depth,
             attrs.get("__probe_watch__", []),
             attrs.get("__probe_silent_watch__", []),
+            stats=attrs.get("__probe_stats__"),
         )
         with tracer:
             return _func(*args, **kwargs)
 
+    # Attributes are keyed by code id, and the nested ``wrapper`` code object is
+    # shared by every probe() call; give each wrapper its own copy.
+    if hasattr(wrapper.__code__, "replace"):  # Python 3.8+
+        wrapper.__code__ = wrapper.__code__.replace(
+            co_name=getattr(func, "__name__", "wrapper")
+        )
+
     # Store attributes in global dict keyed by code object id
     code_id = id(wrapper.__code__)
     _probe_attrs[code_id] = {
@@ -758,8 +783,42 @@ def probe(func, watch=None, silent_watch=None, depth=1):
     return wrapper
 
 
+def _ema(previous: Optional[float], sample: float, alpha=OVERHEAD_EMA_ALPHA):
+    """Exponential moving average; the first sample seeds it."""
+    if previous is None:
+        return float(sample)
+    return alpha * sample + (1.0 - alpha) * previous
+
+
+@dataclass
+class _TraceStats:
+    """What tracing one function costs and produced, measured by the hook.
+
+    ``overhead_us`` is a moving average of the microseconds each traced call
+    spent inside the trace callback; ``records_dropped`` counts variable
+    changes that could not be written to ``trace_variables``.
+    """
+
+    calls: int = 0
+    overhead_us: Optional[float] = None
+    records: int = 0
+    records_dropped: int = 0
+    last_record_ts: Optional[float] = None
+
+    def observe_call(self, overhead_ns: int) -> None:
+        self.calls += 1
+        self.overhead_us = _ema(self.overhead_us, overhead_ns / 1000.0)
+
+    def observe_record(self, saved: bool) -> None:
+        if saved:
+            self.records += 1
+            self.last_record_ts = time.time()
+        else:
+            self.records_dropped += 1
+
+
 class ProbingTracer:
-    def __init__(self, depth=1, watch=None, silent_watch=None):
+    def __init__(self, depth=1, watch=None, silent_watch=None, stats=None):
         self.depth = depth
         self.count_calls = 0
         self.count_returns = 0
@@ -769,6 +828,9 @@ class ProbingTracer:
         self.silent_watch = list(silent_watch)
         self.all_watch = list(set(self.watch + self.silent_watch))
         self.watch_impl = {}
+        self.call_id = next(_call_ids)
+        self.stats = stats
+        self.overhead_ns = 0
 
     def on_call(self):
         self.count_calls += 1
@@ -795,8 +857,21 @@ class ProbingTracer:
     def __exit__(self, exc_type, exc_val, exc_tb):
         tracer_stack = thread_global.tracer_stack
         sys.settrace(tracer_stack.pop())
+        if self.stats is not None:
+            self.stats.observe_call(self.overhead_ns)
 
     def trace(self, frame: FrameType, event: AnyStr, arg: Any):
+        started = time.perf_counter_ns()
+        try:
+            return self._trace(frame, event, arg)
+        finally:
+            self.overhead_ns += time.perf_counter_ns() - started
+
+    def _record(self, saved: bool) -> None:
+        if self.stats is not None:
+            self.stats.observe_record(saved)
+
+    def _trace(self, frame: FrameType, event: AnyStr, arg: Any):
         import torch
 
         # print(
@@ -877,8 +952,11 @@ class ProbingTracer:
                         variable_name=k,
                         value=value_str,
                         value_type=value_type,
+                        call_id=self.call_id,
                     ).save()
+                    self._record(True)
                 except Exception as e:
+                    self._record(False)
                     # Log error but don't disrupt the tracing process
                     _trace_warn(
                         f"Warning: Failed to save variable change to trace_variables table: {e}"
@@ -1004,202 +1082,231 @@ def getname(obj):
     return _TraceableCollector.get_object_name(obj)
 
 
-def trace(
-    func_or_name,
-    watch=None,
-    silent_watch=None,
-    depth=1,
-    callback=None,
-):
-    def get_func(name):
-        names = name.split(".")
-        parent = sys.modules.get(names[0], None)
+@dataclass
+class _Instrumentation:
+    """One instrumented function, keyed in ``_instrumented`` by its original code.
+
+    The function object is patched in place (its ``__code__`` points at a
+    per-function probe wrapper), so every alias keeps seeing the same object;
+    restoring puts back the exact original code and defaults objects.
+    """
+
+    func: FunctionType
+    original_code: types.CodeType
+    original_defaults: Optional[tuple]
+    original_kwdefaults: Optional[dict]
+    probe_code: types.CodeType
+    stats: _TraceStats = field(default_factory=_TraceStats)
+
+
+# id(original __code__) -> live instrumentation. The record holds the code
+# object, so the id stays valid for as long as the entry exists.
+_instrumented: Dict[int, _Instrumentation] = {}
+_instrument_lock = threading.RLock()
+
+
+def _resolve_function(name):
+    names = name.split(".")
+    parent = sys.modules.get(names[0], None)
+    if parent is None:
+        raise ValueError(f"module {names[0]} is not imported")
+    names = names[1:]
+    while len(names) > 0:
+        if not hasattr(parent, names[0]):
+            raise ValueError(f"{names[0]} not found in {parent}.")
+        parent = getattr(parent, names[0])
         names = names[1:]
-        while parent is not None and len(names) > 0:
-            if hasattr(parent, names[0]):
-                if len(names) == 1:
-                    return getattr(parent, names[0])
-                parent = getattr(parent, names[0])
-                names = names[1:]
-            else:
-                raise ValueError(f"{names[0]} not found in {parent}.")
+    return parent
 
-    if watch is None:
-        watch = []
-    if silent_watch is None:
-        silent_watch = []
 
-    if isinstance(func_or_name, str):
-        _validate_trace_name(func_or_name)
-        if depth is None:
-            depth = 1
-        depth = max(0, min(int(depth), MAX_TRACE_DEPTH))
-        if func_or_name in traced_functions:
-            _trace_warn(f"Function {func_or_name} is already being traced.")
-            return
-        try:
-            func = get_func(func_or_name)
-            if not isinstance(func, FunctionType):
-                _trace_warn(f"Error: {func_or_name} is not a function")
-                return
+def suggest_traceable(name: str, limit: int = 5) -> List[str]:
+    """Names close to ``name`` for "could not trace" errors.
 
-            # Store original attributes for restoration
-            original_attrs = {
-                "__code__": func.__code__,
-                "__defaults__": func.__defaults__,
-                "__kwdefaults__": (
-                    func.__kwdefaults__.copy() if func.__kwdefaults__ else None
-                ),
-                "__closure__": func.__closure__,
-                "__code_id__": id(func.__code__),  # Store original code_id for cleanup
-            }
-            traced_functions[func_or_name] = original_attrs
-
-            # Create a copy of the original function to avoid recursion
-            # When we replace func.__code__, the func object itself is modified
-            # So we need to store a copy that won't be affected
-            original_func = types.FunctionType(
-                func.__code__,
-                func.__globals__,
-                func.__name__,
-                func.__defaults__,
-                func.__closure__,
-            )
-            original_func.__kwdefaults__ = (
-                func.__kwdefaults__.copy() if func.__kwdefaults__ else None
-            )
-            original_func.__annotations__ = getattr(func, "__annotations__", None)
-            original_func.__doc__ = func.__doc__
-            original_func.__module__ = getattr(func, "__module__", None)
+    Walks ``name`` as far as it resolves. When it resolves fully (e.g. a
+    class) the functions defined on it are suggested; otherwise the
+    attributes of the last object reached that best match the next part,
+    or imported top-level modules when not even the first part exists.
+    """
+    parts = name.split(".")
+    parent = sys.modules.get(parts[0])
+    if parent is None:
+        modules = [m for m in sys.modules if "." not in m and not m.startswith("_")]
+        return difflib.get_close_matches(parts[0], modules, n=limit)
+    depth = 1
+    while depth < len(parts) and hasattr(parent, parts[depth]):
+        parent = getattr(parent, parts[depth])
+        depth += 1
+    base = ".".join(parts[:depth])
+
+    def attrs(kinds):
+        for attr in dir(parent):
+            if attr.startswith("__"):
+                continue
+            try:
+                value = getattr(parent, attr)
+            except Exception:
+                continue
+            if isinstance(value, kinds):
+                yield attr
 
-            # Create wrapped function using probe with the original function copy
-            wrapped_func = probe(
-                original_func, watch=watch, silent_watch=silent_watch, depth=depth
-            )
+    if depth == len(parts):
+        names = sorted(attrs(FunctionType))[:limit]
+    else:
+        candidates = list(attrs((FunctionType, ModuleType, type)))
+        names = difflib.get_close_matches(parts[depth], candidates, n=limit)
+    return [f"{base}.{attr}" for attr in names]
 
-            # Create a new function object using types.FunctionType to ensure proper validation
-            wrapper_globals = wrapped_func.__globals__.copy()
-            wrapper_globals["_probe_attrs"] = _probe_attrs
-            wrapper_globals["_probe_modules"] = _probe_modules
-            new_func = types.FunctionType(
-                wrapped_func.__code__,
-                wrapper_globals,
-                func.__name__,
-                wrapped_func.__defaults__,
-                wrapped_func.__closure__,
-            )
 
-            # Copy additional attributes
-            new_func.__kwdefaults__ = wrapped_func.__kwdefaults__
-            new_func.__annotations__ = getattr(func, "__annotations__", None)
-            new_func.__doc__ = func.__doc__
-            new_func.__module__ = getattr(func, "__module__", None)
+def _probe_config(watch, silent_watch, depth):
+    return {
+        "__probe_watch__": list(watch or []),
+        "__probe_silent_watch__": list(silent_watch or []),
+        "__probe_depth__": depth,
+    }
 
-            # Update _probe_attrs before replacing __code__ (new code object will have different id)
-            wrapped_code_id = id(wrapped_func.__code__)
-            new_code_id = id(new_func.__code__)
-            if wrapped_code_id in _probe_attrs:
-                _probe_attrs[new_code_id] = _probe_attrs[wrapped_code_id].copy()
 
-            # Replace function attributes
-            try:
-                func.__code__ = new_func.__code__
-                func.__defaults__ = new_func.__defaults__
-                func.__kwdefaults__ = new_func.__kwdefaults__
-                func.__closure__ = new_func.__closure__
-            except (AttributeError, TypeError):
-                # If direct assignment fails (readonly attribute), use object.__setattr__
-                object.__setattr__(func, "__code__", new_func.__code__)
-                object.__setattr__(func, "__defaults__", new_func.__defaults__)
-                object.__setattr__(func, "__kwdefaults__", new_func.__kwdefaults__)
-                object.__setattr__(func, "__closure__", new_func.__closure__)
-
-            # Ensure final code_id is in _probe_attrs
-            final_code_id = id(func.__code__)
-            if final_code_id not in _probe_attrs and new_code_id in _probe_attrs:
-                _probe_attrs[final_code_id] = _probe_attrs[new_code_id].copy()
-
-            # Store all code_ids associated with this function for cleanup in untrace
-            # Collect all code_ids that might be in _probe_attrs
-            all_traced_code_ids = []
-            if wrapped_code_id in _probe_attrs:
-                all_traced_code_ids.append(wrapped_code_id)
-            if new_code_id in _probe_attrs:
-                all_traced_code_ids.append(new_code_id)
-            if final_code_id in _probe_attrs:
-                all_traced_code_ids.append(final_code_id)
-            # Also include the code_id from probe function (wrapper's code_id before functools.wraps)
-            # This is stored in probe function, we need to get it from wrapped_func
-            # Actually, wrapped_code_id should already be the one from probe
-            traced_functions[func_or_name]["__traced_code_ids__"] = list(
-                set(all_traced_code_ids)
-            )
+def _find_instrumentation(func) -> Optional[_Instrumentation]:
+    for inst in _instrumented.values():
+        if inst.func is func:
+            return inst
+    return None
 
-        except Exception as e:
-            _trace_warn(f"Function {func_or_name} not found: {e}")
-            return
-    else:
+
+def _restore(inst: _Instrumentation) -> None:
+    """Put the original code/defaults back and drop every trace of ``inst``."""
+    func = inst.func
+    func.__code__ = inst.original_code
+    func.__defaults__ = inst.original_defaults
+    func.__kwdefaults__ = inst.original_kwdefaults
+    _probe_attrs.pop(id(inst.probe_code), None)
+    _instrumented.pop(id(inst.original_code), None)
+    for alias in [name for name, other in traced_functions.items() if other is inst]:
+        del traced_functions[alias]
+
+
+def trace(
+    func_or_name,
+    watch=None,
+    silent_watch=None,
+    depth=1,
+    callback=None,
+):
+    """Instrument the function named ``func_or_name``.
+
+    Tracing a function that is already instrumented (under this or another
+    name) only replaces its watch/depth configuration; it is never wrapped
+    twice.
+
+    Returns:
+        ``"started"``, ``"updated"``, or ``None`` when the function could not
+        be traced (a warning is logged).
+    """
+    if not isinstance(func_or_name, str):
         raise NotImplementedError("Only string names are supported for tracing.")
+    _validate_trace_name(func_or_name)
+    depth = 1 if depth is None else max(0, min(int(depth), MAX_TRACE_DEPTH))
+    config = _probe_config(watch, silent_watch, depth)
+
+    try:
+        func = _resolve_function(func_or_name)
+    except Exception as e:
+        _trace_warn(f"Function {func_or_name} not found: {e}")
+        return None
+    if not isinstance(func, FunctionType):
+        _trace_warn(f"Error: {func_or_name} is not a function")
+        return None
 
+    with _instrument_lock:
+        inst = _find_instrumentation(func)
+        if inst is not None:
+            _probe_attrs[id(inst.probe_code)].update(config)
+            traced_functions[func_or_name] = inst
+            return "updated"
+
+        # Calls go through the probe wrapper to this copy, which keeps the
+        # original code, so the wrapper never recurses into itself.
+        original_func = types.FunctionType(
+            func.__code__,
+            func.__globals__,
+            func.__name__,
+            func.__defaults__,
+            func.__closure__,
+        )
+        original_func.__kwdefaults__ = func.__kwdefaults__
+        original_func.__annotations__ = getattr(func, "__annotations__", None)
+        original_func.__doc__ = func.__doc__
+        original_func.__module__ = getattr(func, "__module__", None)
+
+        wrapper = probe(original_func, depth=depth)
+        probe_code = wrapper.__code__
+        _probe_attrs[id(probe_code)].update(config)
+        inst = _Instrumentation(
+            func=func,
+            original_code=func.__code__,
+            original_defaults=func.__defaults__,
+            original_kwdefaults=func.__kwdefaults__,
+            probe_code=probe_code,
+        )
+        _probe_attrs[id(probe_code)]["__probe_stats__"] = inst.stats
+        try:
+            # Fails for closures (free variable count mismatch); nothing has
+            # been modified yet in that case.
+            func.__code__ = probe_code
+        except ValueError as e:
+            _probe_attrs.pop(id(probe_code), None)
+            _trace_warn(f"Cannot trace {func_or_name}: {e}")
+            return None
+        func.__defaults__ = wrapper.__defaults__
+        func.__kwdefaults__ = wrapper.__kwdefaults__
+        _instrumented[id(inst.original_code)] = inst
+        traced_functions[func_or_name] = inst
+        return "started"
 
-def untrace(func_or_name):
-    def get_func(name):
-        names = name.split(".")
-        parent = sys.modules.get(names[0], None)
-        names = names[1:]
-        while parent is not None and len(names) > 0:
-            if hasattr(parent, names[0]):
-                if len(names) == 1:
-                    return getattr(parent, names[0])
-                parent = getattr(parent, names[0])
-                names = names[1:]
-            else:
-                raise ValueError(f"{names[0]} not found in {parent}.")
 
-    if isinstance(func_or_name, str):
-        _validate_trace_name(func_or_name)
-        if func_or_name not in traced_functions:
+def untrace(func_or_name):
+    """Restore the function traced as ``func_or_name`` (and all its aliases)."""
+    if not isinstance(func_or_name, str):
+        raise NotImplementedError("Only string names are supported for tracing.")
+    _validate_trace_name(func_or_name)
+    with _instrument_lock:
+        inst = traced_functions.get(func_or_name)
+        if inst is None:
             _trace_warn(f"Function {func_or_name} is not being traced.")
-            return
-        try:
-            func = get_func(func_or_name)
-            if not isinstance(func, FunctionType):
-                _trace_warn(f"Error: {func_or_name} is not a function")
-                return
+            return False
+        _restore(inst)
+        return True
 
-            # Get original attributes
-            original_attrs = traced_functions.pop(func_or_name)
 
-            # Clean up _probe_attrs entries for all code_ids associated with this function
-            traced_code_ids = original_attrs.get("__traced_code_ids__", [])
-            current_code_id = id(func.__code__)
-            # Also check current code_id in case it's different
-            all_code_ids = set(traced_code_ids + [current_code_id])
-            for code_id in all_code_ids:
-                if code_id in _probe_attrs:
-                    del _probe_attrs[code_id]
+def reset_traces() -> List[str]:
+    """Restore every instrumented function; returns the names that were traced.
 
-            # Restore function's attributes
+    Recovery path for when trace state and live functions disagree. Probes
+    created directly with :func:`probe` are left alone.
+    """
+    with _instrument_lock:
+        names = sorted(traced_functions)
+        for inst in list(_instrumented.values()):
             try:
-                func.__code__ = original_attrs["__code__"]
-                func.__defaults__ = original_attrs["__defaults__"]
-                func.__kwdefaults__ = original_attrs["__kwdefaults__"]
-                func.__closure__ = original_attrs["__closure__"]
-            except (AttributeError, TypeError):
-                # If direct assignment fails (readonly attribute), use object.__setattr__
-                object.__setattr__(func, "__code__", original_attrs["__code__"])
-                object.__setattr__(func, "__defaults__", original_attrs["__defaults__"])
-                object.__setattr__(
-                    func, "__kwdefaults__", original_attrs["__kwdefaults__"]
-                )
-                object.__setattr__(func, "__closure__", original_attrs["__closure__"])
-
-        except Exception as e:
-            _trace_warn(f"Function {func_or_name} not found: {e}")
-            return
-    else:
-        raise NotImplementedError("Only string names are supported for tracing.")
+                _restore(inst)
+            except Exception as e:
+                _trace_warn(f"Failed to restore {inst.func.__qualname__}: {e}")
+                _probe_attrs.pop(id(inst.probe_code), None)
+        _instrumented.clear()
+        traced_functions.clear()
+        return names
 
 
 def show_trace():
     return json.dumps([x for x in traced_functions.keys()], indent=2)
+
+
+def trace_stats() -> List[Dict[str, Any]]:
+    """One entry per traced name: backend, overhead estimate and record counts.
+
+    Aliases of one function share its figures.
+    """
+    with _instrument_lock:
+        return [
+            {"function": name, "backend": TRACE_BACKEND, **asdict(inst.stats)}
+            for name, inst in traced_functions.items()
+        ]
@@ -0,0 +1,371 @@
+"""CUDA kernel-launch counts and per-stream activity, without the profiler.
+
+Torch profiler traces are too heavy to leave on; this keeps continuous,
+coarse visibility instead. With ``probing.torch.gpu_streams=on`` every
+interval (``probing.torch.gpu_streams.interval_ms``, default 1000) writes one
+``gpu.streams`` row per CUDA stream that saw work:
+
+- ``launches``: kernels launched on the stream during the interval;
+- ``busy_us`` / ``busy_pct``: approximate busy time. The stream is polled
+  (``cudaStreamQuery``) every ``probing.torch.gpu_streams.sample_ms``
+  (default 5); a poll that finds pending work counts the whole tick as busy.
+
+``probing.torch.count_launches=on`` (implies the above) also stamps each
+closing span with ``cuda.launches``: kernels launched while it or one of its
+children was open.
+
+Launches come from a hook layer. The built-in one is a torch dispatch mode on
+the thread that starts capture (the training loop), counting one launch per
+CUDA op that is not a view; CUPTI-backed layers can be put ahead of it with
+:func:`register_launch_hooks`. Without torch or CUDA nothing is installed.
+"""
+
+from __future__ import annotations
+
+import logging
+import threading
+import time
+from dataclasses import dataclass
+from typing import Any, Callable, Optional, Protocol
+
+import probing
+from probing.core import table
+from probing.tracing._bindings import active_span_for_events, sampled_step
+from probing.util.env import parse_bool_flag
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_INTERVAL_MS = 1000
+DEFAULT_SAMPLE_MS = 5
+INTERVAL_KEY = "probing.torch.gpu_streams.interval_ms"
+SAMPLE_KEY = "probing.torch.gpu_streams.sample_ms"
+LAUNCHES_ATTR = "cuda.launches"
+NO_STEP = -1
+
+# Called with (device index, stream handle, stream object or None).
+OnLaunch = Callable[[int, int, Any], None]
+
+
+@table("gpu.streams")
+@dataclass
+class GpuStream:
+    """Kernel launches and approximate busy time per CUDA stream and interval."""
+
+    ts: int = 0
+    step: int = NO_STEP
+    device_id: int = -1
+    stream: int = 0
+    interval_ms: float = 0.0
+    launches: int = 0
+    busy_us: int = 0
+    busy_pct: float = 0.0
+
+
+class LaunchHooks(Protocol):
+    name: str
+
+    def install(self, on_launch: OnLaunch) -> bool:
+        """Start reporting launches; ``False`` when unavailable here."""
+        ...
+
+    def uninstall(self) -> None: ...
+
+
+class DispatchLaunchHooks:
+    """One launch per non-view op dispatched on a CUDA tensor (ops that launch
+    several kernels count once), on the thread that installs it."""
+
+    name = "dispatch"
+
+    def __init__(self) -> None:
+        self._mode: Any = None
+
+    def install(self, on_launch: OnLaunch) -> bool:
+        try:
+            import torch
+            from torch.utils._python_dispatch import TorchDispatchMode
+        except ImportError:
+            return False
+        if not torch.cuda.is_available():
+            return False
+
+        class _LaunchCounter(TorchDispatchMode):
+            def __torch_dispatch__(self, func, types, args=(), kwargs=None):
+                out = func(*args, **(kwargs or {}))
+                if not getattr(func, "is_view", False):
+                    device = _cuda_device(out, args)
+                    if device is not None:
+                        stream = torch.cuda.current_stream(device)
+                        on_launch(device, int(stream.cuda_stream), stream)
+                return out
+
+        self._mode = _LaunchCounter()
+        self._mode.__enter__()
+        return True
+
+    def uninstall(self) -> None:
+        if self._mode is not None:
+            self._mode.__exit__(None, None, None)
+            self._mode = None
+
+
+def _cuda_device(out: Any, args: tuple) -> Optional[int]:
+    """Device index of the first CUDA tensor in the result or arguments."""
+    import torch
+
+    for value in (out, *args):
+        candidates = value if isinstance(value, (list, tuple)) else (value,)
+        for item in candidates:
+            if isinstance(item, torch.Tensor) and item.is_cuda:
+                return item.device.index or 0
+    return None
+
+
+_HOOK_FACTORIES: list[Callable[[], LaunchHooks]] = [DispatchLaunchHooks]
+
+
+def register_launch_hooks(factory: Callable[[], LaunchHooks]) -> None:
+    """Try ``factory()`` before the built-in layers (e.g. CUPTI callbacks)."""
+    if factory not in _HOOK_FACTORIES:
+        _HOOK_FACTORIES.insert(0, factory)
+
+
+@dataclass
+class _StreamState:
+    device: int
+    stream: Any
+    launches: int = 0
+    busy_ns: int = 0
+
+
+class StreamActivity:
+    """Per-stream counters between flushes, plus per-span launch counts."""
+
+    def __init__(self, *, count_launches: bool = False) -> None:
+        self.count_launches = count_launches
+        self._lock = threading.Lock()
+        self._streams: dict[tuple[int, int], _StreamState] = {}
+        self._span_launches: dict[int, int] = {}
+        self._last_flush_ns = time.monotonic_ns()
+
+    def record_launch(self, device: int, stream_id: int, stream: Any = None) -> None:
+        span_id = None
+        if self.count_launches:
+            span = active_span_for_events()
+            span_id = int(span.span_id) if span is not None else None
+        with self._lock:
+            state = self._streams.get((device, stream_id))
+            if state is None:
+                state = _StreamState(device, stream)
+                self._streams[(device, stream_id)] = state
+            state.launches += 1
+            if span_id is not None:
+                self._span_launches[span_id] = self._span_launches.get(span_id, 0) + 1
+
+    def sample(self, elapsed_ns: int, is_busy: Callable[[Any], bool]) -> None:
+        """Count ``elapsed_ns`` as busy for streams with pending work."""
+        with self._lock:
+            states = list(self._streams.values())
+        for state in states:
+            if state.stream is None:
+                continue
+            try:
+                busy = is_busy(state.stream)
+            except Exception:
+                continue
+            if busy:
+                with self._lock:
+                    state.busy_ns += elapsed_ns
+
+    def flush(self, now_ns: Optional[int] = None) -> list[GpuStream]:
+        """Persist one row per active stream since the last flush and reset."""
+        now_ns = time.monotonic_ns() if now_ns is None else now_ns
+        interval_ns = max(now_ns - self._last_flush_ns, 1)
+        self._last_flush_ns = now_ns
+        with self._lock:
+            active = [
+                ((device, stream_id), state)
+                for (device, stream_id), state in self._streams.items()
+                if state.launches or state.busy_ns
+            ]
+            counts = [
+                (state.launches, min(state.busy_ns, interval_ns)) for _, state in active
+            ]
+            for _, state in active:
+                state.launches = 0
+                state.busy_ns = 0
+        step = sampled_step()
+        ts = time.time_ns() // 1000
+        rows = [
+            GpuStream(
+                ts=ts,
+                step=NO_STEP if step is None else int(step),
+                device_id=device,
+                stream=stream_id,
+                interval_ms=interval_ns / 1e6,
+                launches=launches,
+                busy_us=busy_ns // 1000,
+                busy_pct=round(100.0 * busy_ns / interval_ns, 2),
+            )
+            for ((device, stream_id), _), (launches, busy_ns) in zip(active, counts)
+        ]
+        if rows:
+            GpuStream.append_many(rows)
+        return rows
+
+    def span_close_attrs(self, span: Any) -> dict:
+        """``cuda.launches`` for a closing span; its count rolls into the
+        parent, so a span includes its children's launches."""
+        span_id = int(span.span_id)
+        parent_id = getattr(span, "parent_id", None)
+        with self._lock:
+            launches = self._span_launches.pop(span_id, 0)
+            if launches and parent_id is not None:
+                parent_id = int(parent_id)
+                self._span_launches[parent_id] = (
+                    self._span_launches.get(parent_id, 0) + launches
+                )
+        return {LAUNCHES_ATTR: launches} if launches else {}
+
+
+def _stream_busy(stream: Any) -> bool:
+    return not stream.query()
+
+
+class _Sampler(threading.Thread):
+    def __init__(self, activity: StreamActivity, interval_ms: int, sample_ms: int):
+        super().__init__(name="probing-gpu-streams", daemon=True)
+        self.activity = activity
+        self.base_interval_ms = interval_ms
+        self.base_sample_ms = sample_ms
+        self.interval_ns = interval_ms * 1_000_000
+        self.sample_s = sample_ms / 1000.0
+        self.stopped = threading.Event()
+
+    def reload(self) -> None:
+        """Pick up interval changes made while running (e.g. by the overhead
+        governor); an unset key falls back to the starting value."""
+        interval_ms = _positive_int(INTERVAL_KEY, self.base_interval_ms)
+        sample_ms = _positive_int(SAMPLE_KEY, self.base_sample_ms)
+        self.interval_ns = interval_ms * 1_000_000
+        self.sample_s = sample_ms / 1000.0
+
+    def run(self) -> None:
+        last = last_flush = time.monotonic_ns()
+        while not self.stopped.wait(self.sample_s):
+            now = time.monotonic_ns()
+            self.activity.sample(now - last, _stream_busy)
+            last = now
+            if now - last_flush >= self.interval_ns:
+                self.activity.flush(now)
+                last_flush = now
+                self.reload()
+
+
+@dataclass(frozen=True)
+class GpuStreamsConfig:
+    enabled: bool = False
+    count_launches: bool = False
+    interval_ms: int = DEFAULT_INTERVAL_MS
+    sample_ms: int = DEFAULT_SAMPLE_MS
+
+
+def _positive_int(key: str, default: int) -> int:
+    raw = probing.config.get_str(key)
+    try:
+        value = int(str(raw).strip()) if raw is not None else default
+    except ValueError:
+        return default
+    return value if value > 0 else default
+
+
+def gpu_streams_config() -> GpuStreamsConfig:
+    count_launches = bool(
+        parse_bool_flag(probing.config.get_str("probing.torch.count_launches"))
+    )
+    enabled = (
+        bool(parse_bool_flag(probing.config.get_str("probing.torch.gpu_streams")))
+        or count_launches
+    )
+    return GpuStreamsConfig(
+        enabled=enabled,
+        count_launches=count_launches,
+        interval_ms=_positive_int(INTERVAL_KEY, DEFAULT_INTERVAL_MS),
+        sample_ms=_positive_int(SAMPLE_KEY, DEFAULT_SAMPLE_MS),
+    )
+
+
+class StreamCapture:
+    """Installed hook layer, counters and sampler thread."""
+
+    def __init__(
+        self, hooks: LaunchHooks, activity: StreamActivity, sampler: _Sampler
+    ) -> None:
+        self.hooks = hooks
+        self.activity = activity
+        self.sampler = sampler
+
+    def stop(self) -> None:
+        from probing.tracing.span import remove_span_close_attribute_provider
+
+        self.hooks.uninstall()
+        self.sampler.stopped.set()
+        remove_span_close_attribute_provider(self.activity.span_close_attrs)
+        self.activity.flush()
+
+
+_capture: Optional[StreamCapture] = None
+
+
+def start_stream_capture(
+    config: GpuStreamsConfig,
+    hook_factories: Optional[list[Callable[[], LaunchHooks]]] = None,
+) -> Optional[StreamCapture]:
+    """Install the first available hook layer; ``None`` when none applies
+    (no torch, no CUDA) or capture is already running."""
+    global _capture
+    if _capture is not None:
+        return None
+    activity = StreamActivity(count_launches=config.count_launches)
+    for factory in hook_factories or _HOOK_FACTORIES:
+        hooks = factory()
+        try:
+            installed = hooks.install(activity.record_launch)
+        except Exception as exc:
+            logger.debug("launch hooks %s failed to install: %s", hooks.name, exc)
+            installed = False
+        if installed:
+            break
+    else:
+        return None
+
+    if config.count_launches:
+        from probing.tracing.span import add_span_close_attribute_provider
+
+        add_span_close_attribute_provider(activity.span_close_attrs)
+    sampler = _Sampler(activity, config.interval_ms, config.sample_ms)
+    sampler.start()
+    _capture = StreamCapture(hooks, activity, sampler)
+    logger.info(
+        "GPU stream capture enabled (hooks=%s, interval_ms=%s, count_launches=%s)",
+        hooks.name,
+        config.interval_ms,
+        config.count_launches,
+    )
+    return _capture
+
+
+def stop_stream_capture() -> None:
+    global _capture
+    if _capture is not None:
+        _capture.stop()
+        _capture = None
+
+
+def maybe_start_stream_capture() -> Optional[StreamCapture]:
+    """Start capture when ``probing.torch.gpu_streams`` / ``count_launches``
+    is on."""
+    config = gpu_streams_config()
+    if not config.enabled:
+        return None
+    return start_stream_capture(config)
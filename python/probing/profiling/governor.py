@@ -0,0 +1,410 @@
+"""Adaptive overhead governor: keeps probing's own cost under a budget.
+
+With ``probing.overhead_budget_pct=2`` (or ``PROBING_OVERHEAD_BUDGET_PCT``) set
+at startup, a background thread estimates, every
+``probing.overhead_governor.interval_ms`` (default 5000), how much probing
+costs the process:
+
+- hook self-time: the TorchProbe dispatch overhead measured against shadow
+  (unhooked) steps, as a percentage of step time;
+- sampler CPU: CPU time of probing's own threads (``probing-*``) as a
+  percentage of the process's CPU time;
+- writer queue pressure: spans dropped by the OTLP export queue.
+
+When hook plus sampler overhead exceeds the budget, or the export queue
+drops spans, the governor moves one level down: trace step sampling
+(``rate=`` of ``probing.torch.profiling``) and ``probing.pprof.sample_freq``
+are halved, the ``gpu.streams`` intervals doubled. After
+:data:`RESTORE_TICKS` calm ticks (overhead below half the budget) it moves
+one level back up. Each level is computed from the manual value, so level 0
+puts back exactly what was set before; a key that was unset is unset again.
+
+Lowered keys are marked governed in the config list
+(``information_schema.df_settings``). Setting a governed key by hand hands it
+back to the operator: the governor releases it and leaves it alone until it
+next dials down. Every adjustment is recorded in ``probe.events`` with kind
+``overhead_governor``.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+import re
+import threading
+from dataclasses import dataclass, field
+from typing import Callable, Optional
+
+import probing
+from probing.profiling import cuda_streams
+
+logger = logging.getLogger(__name__)
+
+BUDGET_KEY = "probing.overhead_budget_pct"
+BUDGET_ENV = "PROBING_OVERHEAD_BUDGET_PCT"
+INTERVAL_KEY = "probing.overhead_governor.interval_ms"
+EVENT_KIND = "overhead_governor"
+
+DEFAULT_BUDGET_PCT = 2.0
+DEFAULT_INTERVAL_MS = 5000
+# Levels below the manual settings; each halves rates and doubles intervals.
+MAX_LEVEL = 4
+# Consecutive calm ticks before one level is given back.
+RESTORE_TICKS = 3
+# Calm means overhead below this fraction of the budget.
+RESTORE_FRACTION = 0.5
+# Sampling rate never goes below this when dialed down.
+MIN_TRACE_RATE = 0.001
+THREAD_PREFIX = "probing-"
+
+
+@dataclass(frozen=True)
+class OverheadSignals:
+    """One estimate of probing's overhead."""
+
+    # Hook self-time, % of step time.
+    hook_pct: float = 0.0
+    # Probing threads' CPU, % of the process's CPU.
+    sampler_cpu_pct: float = 0.0
+    # Spans dropped by export queues since the last estimate.
+    queue_drops: int = 0
+
+    @property
+    def overhead_pct(self) -> float:
+        return self.hook_pct + self.sampler_cpu_pct
+
+    def exceeds(self, budget_pct: float) -> bool:
+        return self.overhead_pct > budget_pct or self.queue_drops > 0
+
+    def calm(self, budget_pct: float) -> bool:
+        calm_pct = budget_pct * RESTORE_FRACTION
+        return self.overhead_pct < calm_pct and not self.queue_drops
+
+    def as_dict(self) -> dict:
+        return {
+            "overhead_pct": round(self.overhead_pct, 3),
+            "hook_pct": round(self.hook_pct, 3),
+            "sampler_cpu_pct": round(self.sampler_cpu_pct, 3),
+            "queue_drops": self.queue_drops,
+        }
+
+
+@dataclass(frozen=True)
+class Dial:
+    """A config key the governor may lower.
+
+    ``scale(base, level)`` is the value at ``level`` (> 0) computed from the
+    manual value, or from ``default`` when the key is unset; ``None`` leaves
+    the key alone (e.g. the feature is off).
+    """
+
+    key: str
+    scale: Callable[[str, int], Optional[str]]
+    default: Optional[str] = None
+
+
+def _scale_torch_rate(spec: str, level: int) -> Optional[str]:
+    from probing.profiling.torch_probe import TorchProbeConfig
+
+    config = TorchProbeConfig.parse(spec)
+    if not config.enabled:
+        return None
+    rate = max(config.rate / 2**level, MIN_TRACE_RATE)
+    # A trailing ``rate=`` wins over the rate-spec form (``0.1:0.3``).
+    tokens = [t for t in spec.split(",") if not re.match(r"\s*rate\s*=", t)]
+    return ",".join([*tokens, f"rate={rate:g}"])
+
+
+def _scale_frequency(value: str, level: int) -> Optional[str]:
+    try:
+        freq = int(value)
+    except ValueError:
+        return None
+    if freq < 1:
+        return None
+    return str(max(1, freq >> level))
+
+
+def _scale_interval(value: str, level: int) -> Optional[str]:
+    try:
+        interval = int(value)
+    except ValueError:
+        return None
+    if interval < 1:
+        return None
+    return str(interval << level)
+
+
+def _gpu_streams_on() -> bool:
+    return cuda_streams._capture is not None
+
+
+def _scale_streams_interval(value: str, level: int) -> Optional[str]:
+    return _scale_interval(value, level) if _gpu_streams_on() else None
+
+
+def _streams_dial(key: str, default: int) -> Dial:
+    return Dial(key, _scale_streams_interval, str(default))
+
+
+DIALS: list[Dial] = [
+    Dial("probing.torch.profiling", _scale_torch_rate),
+    Dial("probing.pprof.sample_freq", _scale_frequency),
+    _streams_dial(cuda_streams.INTERVAL_KEY, cuda_streams.DEFAULT_INTERVAL_MS),
+    _streams_dial(cuda_streams.SAMPLE_KEY, cuda_streams.DEFAULT_SAMPLE_MS),
+]
+
+
+def _record(detail: dict) -> None:
+    try:
+        import probing._core as core
+
+        core.record_probe_event(EVENT_KIND, json.dumps(detail))
+    except (ImportError, AttributeError):
+        pass
+
+
+@dataclass
+class OverheadGovernor:
+    """Levels up and down over :data:`DIALS` as signals come in."""
+
+    budget_pct: float = DEFAULT_BUDGET_PCT
+    dials: list[Dial] = field(default_factory=lambda: list(DIALS))
+    level: int = 0
+    _calm_ticks: int = 0
+    # Value last written per governed key, to notice manual changes.
+    _written: dict[str, str] = field(default_factory=dict)
+
+    def tick(self, signals: OverheadSignals) -> None:
+        self._release_overridden()
+        if signals.exceeds(self.budget_pct):
+            self._calm_ticks = 0
+            if self.level < MAX_LEVEL:
+                self._move(self.level + 1, "dial_down", signals)
+        elif signals.calm(self.budget_pct) and self.level > 0:
+            self._calm_ticks += 1
+            if self._calm_ticks >= RESTORE_TICKS:
+                self._calm_ticks = 0
+                self._move(self.level - 1, "restore", signals)
+        else:
+            self._calm_ticks = 0
+
+    def stop(self) -> None:
+        """Put every governed key back to its manual value."""
+        if self.level:
+            self._move(0, "restore", OverheadSignals())
+
+    def _release_overridden(self) -> None:
+        for key, written in list(self._written.items()):
+            current = probing.config.get_str(key)
+            if current == written:
+                continue
+            probing.config.release(key)
+            del self._written[key]
+            logger.info("overhead governor: %s set by hand, released", key)
+            _record(
+                {
+                    "action": "manual_override",
+                    "level": self.level,
+                    "key": key,
+                    "value": current,
+                }
+            )
+
+    def _move(self, level: int, action: str, signals: OverheadSignals) -> None:
+        changes = []
+        for dial in self.dials:
+            change = self._apply(dial, level)
+            if change is not None:
+                changes.append(change)
+        previous, self.level = self.level, level
+        logger.info(
+            "overhead governor: %s to level %d (overhead %.2f%%, budget %.2f%%)",
+            action,
+            level,
+            signals.overhead_pct,
+            self.budget_pct,
+        )
+        _record(
+            {
+                "action": action,
+                "from_level": previous,
+                "level": level,
+                "budget_pct": self.budget_pct,
+                **signals.as_dict(),
+                "changes": changes,
+            }
+        )
+
+    def _apply(self, dial: Dial, level: int) -> Optional[dict]:
+        current = probing.config.get_str(dial.key)
+        governed = dial.key in self._written
+        if level == 0 or (governed and self._target(dial, level) is None):
+            if not governed:
+                return None
+            _, manual = probing.config.release(dial.key)
+            del self._written[dial.key]
+            if manual is None:
+                probing.config.remove(dial.key)
+            else:
+                probing.config.write(dial.key, manual)
+            return {"key": dial.key, "from": current, "to": manual}
+
+        target = self._target(dial, level)
+        if target is None or target == current:
+            return None
+        if not governed:
+            probing.config.govern(dial.key, current)
+        probing.config.write(dial.key, target)
+        self._written[dial.key] = target
+        return {"key": dial.key, "from": current, "to": target}
+
+    def _target(self, dial: Dial, level: int) -> Optional[str]:
+        if dial.key in self._written:
+            manual = probing.config.governed().get(dial.key)
+        else:
+            manual = probing.config.get_str(dial.key)
+        base = manual if manual is not None else dial.default
+        return None if base is None else dial.scale(base, level)
+
+
+def _thread_cpu_ticks() -> tuple[int, int]:
+    """(probing threads, whole process) CPU in clock ticks, from ``/proc``."""
+    own = total = 0
+    try:
+        tids = os.listdir("/proc/self/task")
+    except OSError:
+        return 0, 0
+    for tid in tids:
+        try:
+            with open(f"/proc/self/task/{tid}/stat") as f:
+                stat = f.read()
+        except OSError:
+            continue
+        comm_end = stat.rfind(")")
+        comm = stat[stat.find("(") + 1 : comm_end]
+        fields = stat[comm_end + 2 :].split()
+        ticks = int(fields[11]) + int(fields[12])
+        total += ticks
+        if comm.startswith(THREAD_PREFIX):
+            own += ticks
+    return own, total
+
+
+def _hook_overhead_pct() -> float:
+    try:
+        from probing.profiling.torch_probe import dispatch_overhead_pct
+    except ImportError:
+        return 0.0
+    return max(dispatch_overhead_pct() or 0.0, 0.0)
+
+
+class ProcessSignals:
+    """Signals of this process, as deltas since the previous read."""
+
+    def __init__(self) -> None:
+        self._cpu = _thread_cpu_ticks()
+        self._drops = self._export_drops()
+
+    @staticmethod
+    def _export_drops() -> int:
+        try:
+            import probing._core as core
+
+            return int(core.trace_export_dropped())
+        except (ImportError, AttributeError):
+            return 0
+
+    def read(self) -> OverheadSignals:
+        own, total = _thread_cpu_ticks()
+        d_own, d_total = own - self._cpu[0], total - self._cpu[1]
+        self._cpu = (own, total)
+        drops = self._export_drops()
+        # The counter restarts with a new exporter.
+        queue_drops = max(drops - self._drops, 0)
+        self._drops = drops
+        return OverheadSignals(
+            hook_pct=_hook_overhead_pct(),
+            sampler_cpu_pct=100.0 * d_own / d_total if d_total > 0 else 0.0,
+            queue_drops=queue_drops,
+        )
+
+
+def _budget_pct() -> Optional[float]:
+    """The configured budget; ``None`` when the governor is off."""
+    raw = probing.config.get_str(BUDGET_KEY)
+    if raw is None:
+        raw = os.environ.get(BUDGET_ENV)
+    if raw is None or not str(raw).strip():
+        return None
+    raw = str(raw).strip().lower()
+    if raw in ("on", "true", "yes"):
+        return DEFAULT_BUDGET_PCT
+    try:
+        budget = float(raw)
+    except ValueError:
+        logger.warning("ignoring %s=%r: not a percentage", BUDGET_KEY, raw)
+        return None
+    return budget if budget > 0 else None
+
+
+def _interval_ms() -> int:
+    raw = probing.config.get_str(INTERVAL_KEY)
+    try:
+        value = int(str(raw).strip()) if raw is not None else DEFAULT_INTERVAL_MS
+    except ValueError:
+        return DEFAULT_INTERVAL_MS
+    return value if value > 0 else DEFAULT_INTERVAL_MS
+
+
+class _GovernorThread(threading.Thread):
+    def __init__(self, governor: OverheadGovernor, signals: ProcessSignals) -> None:
+        super().__init__(name="probing-governor", daemon=True)
+        self.governor = governor
+        self.signals = signals
+        self.stopped = threading.Event()
+
+    def run(self) -> None:
+        while not self.stopped.wait(_interval_ms() / 1000.0):
+            budget = _budget_pct()
+            if budget is None:
+                break
+            self.governor.budget_pct = budget
+            try:
+                self.governor.tick(self.signals.read())
+            except Exception as exc:
+                logger.debug("overhead governor tick failed: %s", exc)
+        self.governor.stop()
+
+
+_thread: Optional[_GovernorThread] = None
+
+
+def start_governor(budget_pct: float) -> Optional[OverheadGovernor]:
+    """Start the governor thread; ``None`` when it is already running."""
+    global _thread
+    if _thread is not None and _thread.is_alive():
+        return None
+    governor = OverheadGovernor(budget_pct=budget_pct)
+    _thread = _GovernorThread(governor, ProcessSignals())
+    _thread.start()
+    logger.info("overhead governor enabled (budget %.2f%%)", budget_pct)
+    return governor
+
+
+def stop_governor() -> None:
+    """Stop the thread, restoring every governed key."""
+    global _thread
+    if _thread is not None:
+        _thread.stopped.set()
+        _thread.join(timeout=5.0)
+        _thread = None
+
+
+def maybe_start_governor() -> Optional[OverheadGovernor]:
+    """Start the governor when ``probing.overhead_budget_pct`` is set."""
+    budget = _budget_pct()
+    if budget is None:
+        return None
+    return start_governor(budget)
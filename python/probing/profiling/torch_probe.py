@@ -3,6 +3,7 @@ import logging
 import os
 import statistics
 import time
+import weakref
 from collections import deque
 from dataclasses import dataclass
 from typing import Any, Optional
@@ -83,6 +84,7 @@ class _AdaptiveRateController:
         self._target_pct = target_pct
         self._high_pct = high_pct
         self._window: deque[tuple[float, bool, bool]] = deque(maxlen=_ADAPTIVE_WINDOW)
+        _CONTROLLERS.add(self)
 
     def record(self, duration_sec: float, *, is_shadow: bool, sampled: bool) -> None:
         self._window.append((duration_sec, is_shadow, sampled))
@@ -121,6 +123,16 @@ class _AdaptiveRateController:
         )
 
 
+_CONTROLLERS: "weakref.WeakSet[_AdaptiveRateController]" = weakref.WeakSet()
+
+
+def dispatch_overhead_pct() -> Optional[float]:
+    """Highest hook dispatch overhead (%) among live tracers, measured against
+    shadow steps; ``None`` until enough steps were timed."""
+    pcts = [c._dispatch_overhead_pct() for c in list(_CONTROLLERS)]
+    return max((p for p in pcts if p is not None), default=None)
+
+
 def shadow_step_in_cycle(
     cycle_index: int,
     shadow_normal: int = DEFAULT_SHADOW_NORMAL,
@@ -306,6 +306,27 @@ class CodeExecutor:
 
 
 import code
+import re
+
+# Completions returned per request.
+MAX_COMPLETIONS = 200
+
+
+def _rlcomplete(text: str) -> List[str]:
+    """:mod:`rlcompleter` matches for ``text`` over ``__main__``."""
+    import __main__
+    import rlcompleter
+
+    if not text:
+        return []
+    completer = rlcompleter.Completer(vars(__main__))
+    matches = []
+    while len(matches) < MAX_COMPLETIONS:
+        match = completer.complete(text, len(matches))
+        if match is None:
+            break
+        matches.append(match.rstrip("("))
+    return matches
 
 
 class DebugConsole(code.InteractiveConsole):
@@ -362,6 +383,26 @@ class DebugConsole(code.InteractiveConsole):
         self.resetbuffer()
         return retval
 
+    def complete(self, source: str, cursor: Optional[int] = None) -> dict:
+        """Completions for ``source`` at ``cursor`` (default: its end).
+
+        Returns ``{"matches": [...], "start": int, "end": int}``; a match
+        replaces ``source[start:end]``. Uses the kernel's IPython completer,
+        which also knows magics, or :mod:`rlcompleter` over ``__main__``
+        when the kernel is unavailable.
+        """
+        if cursor is None or not 0 <= cursor <= len(source):
+            cursor = len(source)
+        line = source[:cursor].rsplit("\n", 1)[-1]
+        if self.code_executor is not None:
+            shell = self.code_executor.km.kernel.shell
+            text, matches = shell.complete("", line, len(line))
+        else:
+            text = re.search(r"[\w.]*$", line).group()
+            matches = _rlcomplete(text)
+        matches = list(dict.fromkeys(matches))[:MAX_COMPLETIONS]
+        return {"matches": matches, "start": cursor - len(text), "end": cursor}
+
     def push(self, code: str):
         """Pushes code to the executor and executes it.
 
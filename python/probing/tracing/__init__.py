@@ -27,6 +27,7 @@ from probing.tracing.phases import (
     phase,
     reset_phase,
 )
+from probing.tracing.propagation import TRACEPARENT_HEADER, traceparent
 from probing.tracing.span import add_span_attribute_provider, event, record_span, span
 from probing.tracing.table import SPANS_SQL, TraceEvent
 
@@ -37,6 +38,8 @@ __all__ = [
     "event",
     "record_span",
     "current_span",
+    "traceparent",
+    "TRACEPARENT_HEADER",
     "step",
     "step_fields",
     "row_fields",
@@ -15,6 +15,10 @@ try:
     advance_micro_step = _core.py_advance_micro_step
     set_micro_batches = _core.py_set_micro_batches
     current_micro_step = _core.py_current_micro_step
+    set_step_provider = _core.py_set_step_provider
+    sampled_step = _core.py_sampled_step
+    traceparent = _core.py_traceparent
+    set_remote_span_recorder = _core.py_set_remote_span_recorder
 except AttributeError:
     Span = None
 
@@ -44,3 +48,15 @@ except AttributeError:
 
     def current_micro_step() -> int:
         return 0
+
+    def set_step_provider(_provider=None):
+        return None
+
+    def sampled_step():
+        return None
+
+    def traceparent():
+        return None
+
+    def set_remote_span_recorder(_recorder=None):
+        return None
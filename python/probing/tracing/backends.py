@@ -20,6 +20,7 @@ from __future__ import annotations
 
 import json
 import logging
+import math
 import os
 import sys
 import time
@@ -50,6 +51,7 @@ class SpanStartRecord:
     thread_id: int
     location: str
     attributes_json: str
+    thread_name: str = ""
 
 
 @dataclass(frozen=True)
@@ -57,6 +59,11 @@ class SpanEndRecord:
     span_id: int
     time_ns: int
     thread_id: int
+    # -1 unless ``probing.trace.cpu_time`` sampled the span.
+    cpu_time_ns: int = -1
+    ctx_switches: int = -1
+    # JSON list of ``{trace_id, span_id, attributes}``; empty without links.
+    links_json: str = ""
 
 
 @dataclass(frozen=True)
@@ -76,6 +83,7 @@ class SpanEventRecord:
     time_ns: int
     thread_id: int
     event_attributes_json: str
+    thread_name: str = ""
 
 
 @runtime_checkable
@@ -233,6 +241,7 @@ class MemtableBackend:
             location=record.location,
             attributes=record.attributes_json,
             event_attributes="",
+            thread_name=record.thread_name,
         )
 
     def _end_row(self, record: SpanEndRecord):
@@ -248,6 +257,9 @@ class MemtableBackend:
             location="",
             attributes="",
             event_attributes="",
+            cpu_time_ns=record.cpu_time_ns,
+            ctx_switches=record.ctx_switches,
+            links=record.links_json,
         )
 
     def on_span_start(self, record: SpanStartRecord) -> None:
@@ -274,6 +286,7 @@ class MemtableBackend:
             location=record.location,
             attributes="",
             event_attributes=record.event_attributes_json,
+            thread_name=record.thread_name,
         ).save()
 
     def shutdown(self) -> None:
@@ -414,6 +427,8 @@ class OtelBackend:
             otel_span.set_attribute("probing.phase", record.phase)
         if record.location:
             otel_span.set_attribute("probing.location", record.location)
+        if record.thread_name:
+            otel_span.set_attribute("thread.name", record.thread_name)
 
         self._spans[record.span_id] = otel_span
         self._parents[record.span_id] = record.parent_id
@@ -423,6 +438,12 @@ class OtelBackend:
         self._parents.pop(record.span_id, None)
         if otel_span is None:
             return
+        if record.cpu_time_ns >= 0:
+            otel_span.set_attribute("probing.cpu_time_ns", record.cpu_time_ns)
+            otel_span.set_attribute("probing.ctx_switches", record.ctx_switches)
+        if record.links_json:
+            # OTel only takes links at span start; keep them as an attribute.
+            otel_span.set_attribute("probing.links", record.links_json)
         otel_span.end(end_time=record.time_ns)
 
     def on_event(self, record: SpanEventRecord) -> None:
@@ -467,6 +488,33 @@ def _thread_id(span: Any) -> int:
     return int(getattr(span, "thread_id", 0))
 
 
+def _thread_name(span: Any) -> str:
+    return str(getattr(span, "thread_name", None) or "")
+
+
+def _links_json(span: Any) -> str:
+    get_links = getattr(span, "get_links", None)
+    links = get_links() if get_links is not None else []
+    if not links:
+        return ""
+    return json.dumps(links, default=str)
+
+
+def _end_record(span: Any, time_ns: int) -> SpanEndRecord:
+    def counter(attr: str) -> int:
+        value = getattr(span, attr, None)
+        return int(value) if value is not None else -1
+
+    return SpanEndRecord(
+        span_id=int(span.span_id),
+        time_ns=int(time_ns),
+        thread_id=_thread_id(span),
+        cpu_time_ns=counter("cpu_time_ns"),
+        ctx_switches=counter("ctx_switches"),
+        links_json=_links_json(span),
+    )
+
+
 class SpanRecorder:
     """Fan-out span lifecycle records to all enabled backends."""
 
@@ -491,12 +539,7 @@ class SpanRecorder:
         if not self.enabled:
             return
         end_ts = span.end_timestamp or int(time.time_ns())
-        record = SpanEndRecord(
-            span_id=int(span.span_id),
-            time_ns=int(end_ts),
-            thread_id=_thread_id(span),
-        )
-        self._dispatch("on_span_end", record)
+        self._dispatch("on_span_end", _end_record(span, end_ts))
 
     def record_closed_span(
         self,
@@ -520,13 +563,9 @@ class SpanRecorder:
             thread_id=_thread_id(span),
             location=_span_location(span),
             attributes_json=attributes_json,
+            thread_name=_thread_name(span),
         )
-        end = SpanEndRecord(
-            span_id=int(span.span_id),
-            time_ns=int(end_ns),
-            thread_id=_thread_id(span),
-        )
-        self._dispatch_closed(start, end)
+        self._dispatch_closed(start, _end_record(span, end_ns))
 
     def record_event(
         self, span: Any, event_name: str, event_attributes: Optional[list] = None
@@ -575,9 +614,33 @@ def _span_start_record(span: Any, attrs: dict) -> SpanStartRecord:
         thread_id=_thread_id(span),
         location=_span_location(span),
         attributes_json=json.dumps(attrs) if attrs else "",
+        thread_name=_thread_name(span),
     )
 
 
+def _typed_attr(value: Any) -> Any:
+    """JSON-native event value: scalars keep their type, the rest become str.
+
+    numpy/torch scalars are unwrapped via ``.item()``; NaN/inf are stringified
+    because they are not valid JSON.
+    """
+    if isinstance(value, (list, tuple)):
+        return [_typed_attr(v) for v in value]
+    if isinstance(value, dict):
+        return {str(k): _typed_attr(v) for k, v in value.items()}
+    if not isinstance(value, (type(None), bool, int, float, str)):
+        item = getattr(value, "item", None)
+        try:
+            value = item() if callable(item) else value
+        except (TypeError, ValueError):
+            pass
+    if isinstance(value, float) and not math.isfinite(value):
+        return str(value)
+    if isinstance(value, (type(None), bool, int, float, str)):
+        return value
+    return str(value)
+
+
 def _event_record(
     span: Any, event_name: str, event_attributes: Optional[list]
 ) -> SpanEventRecord:
@@ -585,9 +648,11 @@ def _event_record(
     if event_attributes:
         for attr_item in event_attributes:
             if isinstance(attr_item, dict):
-                attrs_dict.update(attr_item)
+                attrs_dict.update(
+                    {str(k): _typed_attr(v) for k, v in attr_item.items()}
+                )
             elif isinstance(attr_item, (list, tuple)) and len(attr_item) == 2:
-                attrs_dict[attr_item[0]] = attr_item[1]
+                attrs_dict[str(attr_item[0])] = _typed_attr(attr_item[1])
     return SpanEventRecord(
         trace_id=int(span.trace_id),
         span_id=int(span.span_id),
@@ -598,6 +663,7 @@ def _event_record(
         time_ns=int(time.time_ns()),
         thread_id=_thread_id(span),
         event_attributes_json=json.dumps(attrs_dict) if attrs_dict else "",
+        thread_name=_thread_name(span),
     )
 
 
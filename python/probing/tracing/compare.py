@@ -0,0 +1,233 @@
+"""Per-span-name duration summaries and window-over-window regression comparison.
+
+Rows come from the materialized spans view (``SPANS_SQL``); both windows are
+summarized with the same percentile rule so deltas are comparable.
+"""
+
+from __future__ import annotations
+
+import math
+from dataclasses import asdict, dataclass
+from typing import Dict, Iterable, List, Mapping, Optional, Tuple
+
+from probing.tracing.table import SPANS_SQL
+
+# Below this many samples in either window a delta is reported but never flagged.
+MIN_SAMPLES = 5
+# Relative p50 change that counts as a regression / improvement candidate.
+REL_THRESHOLD = 0.05
+
+
+@dataclass(frozen=True)
+class SpanStats:
+    name: str
+    count: int
+    p50_us: float
+    p95_us: float
+    mean_us: float
+    # Over spans sampled with ``probing.trace.cpu_time``; None when none were.
+    mean_cpu_us: Optional[float] = None
+    mean_ctx_switches: Optional[float] = None
+
+
+@dataclass(frozen=True)
+class SpanDelta:
+    name: str
+    baseline_count: int
+    current_count: int
+    baseline_p50_us: Optional[float]
+    current_p50_us: Optional[float]
+    baseline_p95_us: Optional[float]
+    current_p95_us: Optional[float]
+    delta_p50_us: Optional[float]
+    delta_p95_us: Optional[float]
+    delta_p50_pct: Optional[float]
+    significant: bool
+    verdict: str
+
+
+def percentile(sorted_values: List[float], q: float) -> float:
+    """Linear-interpolated percentile of an ascending list (``q`` in ``[0, 1]``).
+
+    >>> percentile([1.0, 2.0, 3.0, 4.0], 0.5)
+    2.5
+    >>> percentile([10.0], 0.95)
+    10.0
+    """
+    if not sorted_values:
+        return 0.0
+    if len(sorted_values) == 1:
+        return float(sorted_values[0])
+    pos = q * (len(sorted_values) - 1)
+    lo = math.floor(pos)
+    hi = math.ceil(pos)
+    frac = pos - lo
+    return float(sorted_values[lo] + (sorted_values[hi] - sorted_values[lo]) * frac)
+
+
+def _sampled(value) -> bool:
+    # NULL arrives as None or, through pandas, as NaN.
+    return value is not None and value == value
+
+
+def summarize(rows: Iterable[Tuple]) -> Dict[str, SpanStats]:
+    """Group ``(name, duration_us)`` pairs into per-name p50/p95/mean.
+
+    Rows may carry ``cpu_time_us`` and ``ctx_switches`` as third and fourth
+    items; sampled ones are averaged into ``mean_cpu_us`` /
+    ``mean_ctx_switches``.
+    """
+    grouped: Dict[str, List[float]] = {}
+    cpu: Dict[str, List[Tuple[float, float]]] = {}
+    for row in rows:
+        name, duration = row[0], row[1]
+        if duration is None:
+            continue
+        grouped.setdefault(str(name), []).append(float(duration))
+        if len(row) >= 4 and _sampled(row[2]) and _sampled(row[3]):
+            cpu.setdefault(str(name), []).append((float(row[2]), float(row[3])))
+    out: Dict[str, SpanStats] = {}
+    for name, values in grouped.items():
+        values.sort()
+        samples = cpu.get(name, [])
+        out[name] = SpanStats(
+            name=name,
+            count=len(values),
+            p50_us=percentile(values, 0.5),
+            p95_us=percentile(values, 0.95),
+            mean_us=sum(values) / len(values),
+            mean_cpu_us=(
+                sum(c for c, _ in samples) / len(samples) if samples else None
+            ),
+            mean_ctx_switches=(
+                sum(s for _, s in samples) / len(samples) if samples else None
+            ),
+        )
+    return out
+
+
+def _is_significant(base: SpanStats, cur: SpanStats, rel_threshold: float) -> bool:
+    """Heuristic: enough samples, and the p50 shift beats both the relative
+    threshold and the baseline tail spread scaled by sample size."""
+    if base.count < MIN_SAMPLES or cur.count < MIN_SAMPLES:
+        return False
+    delta = abs(cur.p50_us - base.p50_us)
+    spread = max(base.p95_us - base.p50_us, 0.0)
+    noise = spread / math.sqrt(min(base.count, cur.count))
+    return delta > max(rel_threshold * base.p50_us, noise)
+
+
+def compare(
+    baseline: Mapping[str, SpanStats],
+    current: Mapping[str, SpanStats],
+    *,
+    rel_threshold: float = REL_THRESHOLD,
+) -> List[SpanDelta]:
+    """Per-name deltas sorted by the size of the p50 change in µs, slowdowns
+    and speedups alike (largest first, unmatched names at the end)."""
+    out: List[SpanDelta] = []
+    for name in sorted(set(baseline) | set(current)):
+        base = baseline.get(name)
+        cur = current.get(name)
+        if base is None or cur is None:
+            out.append(
+                SpanDelta(
+                    name=name,
+                    baseline_count=base.count if base else 0,
+                    current_count=cur.count if cur else 0,
+                    baseline_p50_us=base.p50_us if base else None,
+                    current_p50_us=cur.p50_us if cur else None,
+                    baseline_p95_us=base.p95_us if base else None,
+                    current_p95_us=cur.p95_us if cur else None,
+                    delta_p50_us=None,
+                    delta_p95_us=None,
+                    delta_p50_pct=None,
+                    significant=False,
+                    verdict="new" if base is None else "gone",
+                )
+            )
+            continue
+        delta_p50 = cur.p50_us - base.p50_us
+        significant = _is_significant(base, cur, rel_threshold)
+        if not significant:
+            verdict = "unchanged"
+        elif delta_p50 > 0:
+            verdict = "regression"
+        else:
+            verdict = "improvement"
+        out.append(
+            SpanDelta(
+                name=name,
+                baseline_count=base.count,
+                current_count=cur.count,
+                baseline_p50_us=base.p50_us,
+                current_p50_us=cur.p50_us,
+                baseline_p95_us=base.p95_us,
+                current_p95_us=cur.p95_us,
+                delta_p50_us=delta_p50,
+                delta_p95_us=cur.p95_us - base.p95_us,
+                delta_p50_pct=(
+                    delta_p50 / base.p50_us * 100.0 if base.p50_us else None
+                ),
+                significant=significant,
+                verdict=verdict,
+            )
+        )
+    out.sort(
+        key=lambda d: (d.delta_p50_us is None, -abs(d.delta_p50_us or 0.0), d.name)
+    )
+    return out
+
+
+def window_sql(start_us: Optional[int], end_us: Optional[int]) -> str:
+    """Span durations whose start falls in ``[start_us, end_us)``."""
+    clauses = ["end_us IS NOT NULL"]
+    if start_us is not None:
+        clauses.append(f"start_us >= {int(start_us)}")
+    if end_us is not None:
+        clauses.append(f"start_us < {int(end_us)}")
+    where = " AND ".join(clauses)
+    return (
+        "SELECT name, duration_us, cpu_time_us, ctx_switches "
+        f"FROM ({SPANS_SQL}) spans WHERE {where}"
+    )
+
+
+def query_window(
+    start_us: Optional[int], end_us: Optional[int]
+) -> Dict[str, SpanStats]:
+    """Summarize one window from ``python.trace_event`` via the engine."""
+    import probing.core.engine as engine
+
+    df = engine.query(window_sql(start_us, end_us))
+    if df is None or df.empty:
+        return {}
+    return summarize(
+        zip(
+            df["name"].tolist(),
+            df["duration_us"].tolist(),
+            df["cpu_time_us"].tolist(),
+            df["ctx_switches"].tolist(),
+        )
+    )
+
+
+def stats_to_dict(stats: Mapping[str, SpanStats]) -> List[dict]:
+    return [asdict(s) for s in sorted(stats.values(), key=lambda s: -s.p50_us)]
+
+
+def deltas_to_dict(deltas: List[SpanDelta]) -> List[dict]:
+    return [asdict(d) for d in deltas]
+
+
+__all__ = [
+    "SpanStats",
+    "SpanDelta",
+    "percentile",
+    "summarize",
+    "compare",
+    "window_sql",
+    "query_window",
+    "stats_to_dict",
+    "deltas_to_dict",
+]
@@ -2,11 +2,13 @@
 
 from __future__ import annotations
 
-from typing import Any, Optional
+from typing import Any, Callable, Optional
 
 from probing.tracing._bindings import (
     advance_micro_step,
+    sampled_step,
     set_micro_batches,
+    set_step_provider,
     step_snapshot,
     sync_micro_step,
 )
@@ -59,6 +61,21 @@ class Step:
     def snapshot(self) -> Any:
         return step_snapshot()
 
+    def set_provider(self, provider: Optional[Callable[[], Optional[int]]]) -> None:
+        """Use ``provider()`` as the step stamped on sampled rows (``step``
+        column of ``cpu.*`` / ``gpu.utilization``); ``None`` unregisters it.
+
+        Without a registered provider, the first ``probing.step()`` activates
+        one backed by ``global_step``. ``provider`` runs on sampler threads;
+        returning ``None`` (or raising) leaves the row's step NULL.
+        """
+        set_step_provider(provider)
+
+    @property
+    def sampled(self) -> Optional[int]:
+        """Step the samplers stamp right now; ``None`` before the first step."""
+        return sampled_step()
+
 
 step = Step()
 
@@ -0,0 +1,48 @@
+"""W3C ``traceparent`` propagation to and from other probed processes.
+
+Outgoing: send ``traceparent()`` with HTTP calls to another probed process
+(parameter server, data service)::
+
+    headers = {"traceparent": probing.tracing.traceparent()}
+
+Incoming: the probing server starts a span continuing the caller's trace
+for requests carrying the header; those spans are recorded here, so both
+sides show the same ``trace_id`` in ``python.trace_event``.
+"""
+
+from __future__ import annotations
+
+from types import SimpleNamespace
+from typing import Optional
+
+from probing.tracing._bindings import set_remote_span_recorder
+from probing.tracing._bindings import traceparent as _traceparent
+
+TRACEPARENT_HEADER = "traceparent"
+
+
+def traceparent() -> Optional[str]:
+    """``traceparent`` value for the current span; ``None`` outside any span."""
+    return _traceparent()
+
+
+def _record_remote_span(fields: dict) -> None:
+    """Persist a server span started from an incoming ``traceparent``."""
+    from probing.tracing.backends import get_recorder
+
+    recorder = get_recorder()
+    if not recorder.enabled:
+        return
+    span = SimpleNamespace(location=None, **fields)
+    attributes = fields.get("attributes") or ""
+    recorder.record_closed_span(
+        span,
+        name=str(span.name),
+        phase=str(span.phase or ""),
+        start_ns=int(span.start_timestamp),
+        end_ns=int(span.end_timestamp or span.start_timestamp),
+        attributes_json="" if attributes == "{}" else attributes,
+    )
+
+
+set_remote_span_recorder(_record_remote_span)
@@ -6,7 +6,9 @@ import functools
 import inspect
 import json
 import os
+import sys
 import time
+import traceback
 import warnings
 from dataclasses import dataclass
 from typing import Callable, Optional
@@ -21,10 +23,17 @@ from probing.tracing.coordinates import span_attrs, step
 from probing.tracing.phases import OPTIMIZER, resolve_span
 
 _LOCATION_ENV = frozenset({"1", "true", "yes", "on"})
+EXCEPTION_EVENT = "exception"
+# ``file:line`` a decorated function was called from; shows the untraced
+# frames between a span and its parent.
+CALLER_ATTR = "caller"
+# Tail of the formatted traceback kept on the event (innermost frames last).
+MAX_TRACEBACK_CHARS = 4096
 
 # Rust Span cannot hold arbitrary Python attrs; track deferred persistence by id.
 _DEFERRED: dict[int, "_DeferredState"] = {}
 _span_attribute_providers: list[Callable[[], dict]] = []
+_span_close_attribute_providers: list[Callable[[Span], dict]] = []
 
 
 @dataclass
@@ -39,6 +48,29 @@ def add_span_attribute_provider(provider: Callable[[], dict]) -> None:
         _span_attribute_providers.append(provider)
 
 
+def add_span_close_attribute_provider(provider: Callable[[Span], dict]) -> None:
+    """Register a provider for attributes attached to each span as it closes."""
+    if provider not in _span_close_attribute_providers:
+        _span_close_attribute_providers.append(provider)
+
+
+def remove_span_close_attribute_provider(provider: Callable[[Span], dict]) -> None:
+    if provider in _span_close_attribute_providers:
+        _span_close_attribute_providers.remove(provider)
+
+
+def _close_attrs(span_obj: Span) -> dict:
+    merged: dict = {}
+    for provider in list(_span_close_attribute_providers):
+        try:
+            provided = provider(span_obj)
+            if provided:
+                merged.update(provided)
+        except Exception as exc:
+            warnings.warn(f"Span close attribute provider failed: {exc}")
+    return merged
+
+
 def _provider_attrs() -> dict:
     merged: dict = {}
     for provider in list(_span_attribute_providers):
@@ -66,8 +98,10 @@ def _persistence_enabled() -> bool:
 def _spawn_span(
     name: str, phase: Optional[str], *, location: Optional[str] = None
 ) -> Span:
-    parent = current_span()
-    if parent:
+    # The innermost span still open on this thread; one ended without leaving
+    # its block is skipped rather than adopted as the parent.
+    parent = active_span_for_events()
+    if parent is not None:
         return Span.new_child(parent, name, phase=phase, location=location)
     return Span(name, phase=phase, location=location)
 
@@ -140,6 +174,14 @@ class _RecordedSpan:
         if self._span is None or self._reentrant:
             return False
 
+        if exc_type is not None and not issubclass(exc_type, GeneratorExit):
+            _record_exception(self._span, exc_type, exc_val, exc_tb)
+        closing = _close_attrs(self._span) if _span_close_attribute_providers else {}
+        if closing:
+            _attach_attrs(self._span, closing)
+            deferred = _DEFERRED.get(int(self._span.span_id))
+            if deferred is not None:
+                deferred.merged.update(closing)
         result = self._span.__exit__(exc_type, exc_val, exc_tb)
         state = _DEFERRED.pop(int(self._span.span_id), None)
         if state is not None:
@@ -149,6 +191,27 @@ class _RecordedSpan:
         return result
 
 
+def _record_exception(span_obj: Span, exc_type, exc_val, exc_tb) -> None:
+    """Add an ``exception`` event to the span; the span itself ends as an
+    error in ``Span.__exit__``."""
+    stacktrace = "".join(traceback.format_exception(exc_type, exc_val, exc_tb))
+    if len(stacktrace) > MAX_TRACEBACK_CHARS:
+        stacktrace = "...\n" + stacktrace[-MAX_TRACEBACK_CHARS:]
+    module = getattr(exc_type, "__module__", "builtins")
+    type_name = exc_type.__qualname__
+    attrs = {
+        "exception.type": (
+            type_name if module == "builtins" else f"{module}.{type_name}"
+        ),
+        "exception.message": str(exc_val) if exc_val is not None else "",
+        "exception.stacktrace": stacktrace,
+    }
+    try:
+        span_obj.add_event(EXCEPTION_EVENT, attributes=[attrs])
+    except Exception as exc:
+        warnings.warn(f"Failed to record span exception: {exc}")
+
+
 class _SpanHandle:
     """Deferred ``probing.span()`` entry (context manager or decorator)."""
 
@@ -169,12 +232,13 @@ class _SpanHandle:
         self._auto_location = auto_location
         self._inner: Optional[_RecordedSpan] = None
 
-    def _make_cm(self) -> _RecordedSpan:
+    def _make_cm(self, caller: Optional[str] = None) -> _RecordedSpan:
+        attrs = {**self._attrs, CALLER_ATTR: caller} if caller else self._attrs
         return _RecordedSpan(
             self._name,
             phase=self._phase,
             location=self._location,
-            attrs=self._attrs,
+            attrs=attrs,
             source=self._source,
             auto_location=self._auto_location,
         )
@@ -182,7 +246,7 @@ class _SpanHandle:
     def __call__(self, func: Callable) -> Callable:
         @functools.wraps(func)
         def wrapper(*args, **kwargs):
-            with self._make_cm():
+            with self._make_cm(_call_site(sys._getframe(1))):
                 return func(*args, **kwargs)
 
         return wrapper
@@ -202,6 +266,16 @@ class _SpanHandle:
         raise AttributeError(attr)
 
 
+def _call_site(frame) -> Optional[str]:
+    """``file:line`` of ``frame``, past wrappers from this module (stacked
+    decorators)."""
+    while frame is not None and frame.f_code.co_filename == __file__:
+        frame = frame.f_back
+    if frame is None:
+        return None
+    return f"{frame.f_code.co_filename}:{frame.f_lineno}"
+
+
 def _caller_location() -> Optional[str]:
     """First stack frame outside ``probing/tracing``."""
     try:
@@ -287,12 +361,18 @@ def span(*args, **kwargs):
     return decorator
 
 
-def event(name: str, *, attributes: Optional[list] = None) -> None:
-    """Add a point event on the active span."""
+def event(name: str, *, attributes: Optional[list] = None, **fields) -> None:
+    """Add a point event on the active span.
+
+    Keyword ``fields`` keep their types (int/float/bool/str) through to
+    ``event_attrs()`` in SQL and the Chrome export's ``args``::
+
+        probing.event("prefill", tokens=4096, cache_hit=True)
+    """
     current = active_span_for_events() or current_span()
     if current is None or getattr(current, "is_ended", False):
         raise RuntimeError("No active span in current context. Cannot add event.")
-    current.add_event(name, attributes=attributes)
+    current.add_event(name, attributes=attributes, **fields)
 
 
 def record_span(
@@ -378,8 +458,20 @@ def _persist_event(
 if Span:
     _rust_add_event = Span.add_event
 
-    def _add_event_persist(self, name, attributes=None):
+    def _add_event_persist(self, name, attributes=None, **fields):
+        if fields:
+            attributes = [*(attributes or []), fields]
         _rust_add_event(self, name, attributes=attributes)
         _persist_event(self, name, attributes)
 
     Span.add_event = _add_event_persist
+    Span.event = _add_event_persist
+
+    _rust_add_link = Span.add_link
+
+    def _add_link(self, target, attributes=None, **fields):
+        if fields:
+            attributes = [*(attributes or []), fields]
+        _rust_add_link(self, target, attributes=attributes)
+
+    Span.add_link = _add_link
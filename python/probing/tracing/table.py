@@ -7,25 +7,32 @@ from typing import Optional
 
 from probing.core.table import table
 
-# Materialized span rows derived from ``python.trace_event`` (start/end join).
+# Finished spans from ``python.trace_event``. The datasource pairs
+# span_start/span_end rows per thread into ``record_type = 'span'`` rows with
+# ``end_time`` and ``duration`` (ns); unfinished spans have NULL ``duration``.
 # Use span ``time`` (ns since epoch), not the memtable ingestion ``timestamp``.
+# ``cpu_time_us`` / ``ctx_switches`` are NULL unless ``probing.trace.cpu_time``
+# was on; ``duration_us`` far above ``cpu_time_us`` means the thread waited.
+# ``links`` is a JSON list of ``{trace_id, span_id, attributes}`` or NULL.
 SPANS_SQL = """
 SELECT
-    s.trace_id,
-    s.span_id,
-    COALESCE(s.parent_id, -1) AS parent_span_id,
-    s.name,
-    s.phase,
-    CAST(CAST(s.time AS BIGINT) / 1000 AS BIGINT) AS start_us,
-    CAST(CAST(e.time AS BIGINT) / 1000 AS BIGINT) AS end_us,
-    CAST((CAST(e.time AS BIGINT) - CAST(s.time AS BIGINT)) / 1000 AS BIGINT) AS duration_us,
-    s.thread_id,
-    s.location,
-    s.attributes
-FROM python.trace_event s
-JOIN python.trace_event e
-  ON s.span_id = e.span_id AND e.record_type = 'span_end'
-WHERE s.record_type = 'span_start'
+    trace_id,
+    span_id,
+    COALESCE(parent_id, -1) AS parent_span_id,
+    name,
+    phase,
+    CAST(CAST(time AS BIGINT) / 1000 AS BIGINT) AS start_us,
+    CAST(end_time / 1000 AS BIGINT) AS end_us,
+    CAST(duration / 1000 AS BIGINT) AS duration_us,
+    CAST(cpu_time_ns / 1000 AS BIGINT) AS cpu_time_us,
+    ctx_switches,
+    thread_id,
+    thread_name,
+    location,
+    attributes,
+    links
+FROM python.trace_event
+WHERE record_type = 'span' AND duration IS NOT NULL
 """
 
 
@@ -35,6 +42,9 @@ class TraceEvent:
     """Row model for trace records.
 
     Each saved instance is one of: span_start, span_end, event.
+    ``cpu_time_ns`` / ``ctx_switches`` are set on span_end rows of sampled
+    spans and -1 otherwise; ``links`` (JSON) is set on span_end rows of spans
+    with links and empty otherwise.
     """
 
     record_type: str
@@ -48,3 +58,7 @@ class TraceEvent:
     location: Optional[str] = ""
     attributes: Optional[str] = ""
     event_attributes: Optional[str] = ""
+    thread_name: Optional[str] = ""
+    cpu_time_ns: int = -1
+    ctx_switches: int = -1
+    links: Optional[str] = ""
@@ -0,0 +1,38 @@
+#!/usr/bin/env bash
+# Package a pinned Perfetto UI build into DEST, served by probing at /perfetto/
+# so the trace viewer never needs ui.perfetto.dev.
+#
+#   PERFETTO_VERSION   Perfetto release tag to build (default: v50.1)
+#   PERFETTO_UI_DIST   prebuilt UI to copy instead (the out/ui/ui/dist of
+#                      Perfetto's ui/build), e.g. on a build host without
+#                      internet access
+#
+# Builds are cached under $CARGO_TARGET_DIR/perfetto-ui/<version>.
+set -euo pipefail
+
+DEST="${1:-python/probing/bundled_web/public/perfetto}"
+VERSION="${PERFETTO_VERSION:-v50.1}"
+CACHE="${CARGO_TARGET_DIR:-target}/perfetto-ui/$VERSION"
+
+if [[ -n "${PERFETTO_UI_DIST:-}" ]]; then
+  dist="$PERFETTO_UI_DIST"
+else
+  dist="$CACHE/dist"
+  if [[ ! -f "$dist/index.html" ]]; then
+    src="$CACHE/src"
+    rm -rf "$src" "$dist"
+    git clone --depth 1 --branch "$VERSION" https://github.com/google/perfetto.git "$src"
+    (cd "$src" && tools/install-build-deps --ui && ui/build)
+    cp -R "$src/out/ui/ui/dist" "$dist"
+    rm -rf "$src"
+  fi
+fi
+
+if [[ ! -f "$dist/index.html" ]]; then
+  echo "error: no Perfetto UI build (index.html) in $dist" >&2
+  exit 1
+fi
+rm -rf "$DEST"
+mkdir -p "$(dirname "$DEST")"
+cp -R "$dist" "$DEST"
+echo "$DEST (Perfetto UI from $dist, $(du -sh "$DEST" | cut -f1))"
@@ -17,6 +17,8 @@ REQUIRED_PATHS = (
     "probing/profiling/torch_probe.py",
     "probing/bundled_skills/catalog.yaml",
     "probing/bundled_web/public/index.html",
+    # Served at /perfetto/ so traces open without ui.perfetto.dev.
+    "probing/bundled_web/public/perfetto/index.html",
 )
 
 
@@ -4,6 +4,7 @@ extern crate ctor;
 use anyhow::Result;
 use pyo3::prelude::*;
 
+use probing_cli::cli::serve_snapshot::register_snapshot_server;
 use probing_cli::pyo3::cli_main;
 use probing_core::{install_panic_hook, register_python_main_thread};
 use probing_python::extensions::python::{register_table_docs, ExternalTable};
@@ -18,7 +19,6 @@ use probing_skills::pyo3::register_skills_bindings;
 
 use probing_python::pkg::TCPStore;
 
-const ENV_PROBING_LOGLEVEL: &str = "PROBING_LOGLEVEL";
 const ENV_PROBING_PORT: &str = "PROBING_PORT";
 
 #[cfg(feature = "use-mimalloc")]
@@ -166,8 +166,8 @@ fn setup() {
 
     let pid = std::process::id();
 
-    // Initialize logging (try_init to avoid conflicts)
-    let _ = env_logger::try_init_from_env(env_logger::Env::new().filter(ENV_PROBING_LOGLEVEL));
+    // Initialize logging (idempotent; honors PROBING_LOGLEVEL / PROBING_LOG_FORMAT)
+    probing_logging::init();
     log::info!("Initializing probing module for process {pid} ...");
 
     // Initialize probing server (local Unix domain socket)
@@ -186,6 +186,9 @@ fn cleanup() {
         return;
     }
 
+    if let Err(e) = probing_core::trace::flush_file_sink() {
+        log::error!("Failed to flush trace file sink: {e}");
+    }
     if let Err(e) = probing_server::cleanup() {
         log::error!("Failed to cleanup unix socket: {e}");
     }
@@ -205,8 +208,8 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     register_python_main_thread();
     probing_python::features::stacktrace::capture::register_main_os_tid();
 
-    // Initialize logging (try_init to avoid conflicts if already initialized via #[ctor])
-    let _ = env_logger::try_init_from_env(env_logger::Env::new().filter(ENV_PROBING_LOGLEVEL));
+    // Initialize logging (no-op if already initialized via #[ctor])
+    probing_logging::init();
 
     // Initialize globals and tracer if needed
     if initialize_globals() {
@@ -231,14 +234,30 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(_get_python_stacks, m)?)?;
     m.add_function(wrap_pyfunction!(_get_python_frames, m)?)?;
     m.add_function(wrap_pyfunction!(cli_main, m)?)?;
+    // `probing serve-snapshot` runs in the CLI, which does not link the server.
+    register_snapshot_server(|archive, source, listen| {
+        Box::pin(probing_server::serve_snapshot(archive, source, listen))
+    });
     m.add_function(wrap_pyfunction!(
         probing_python::features::python::bindings::api_callstack,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(
+        probing_python::features::python::bindings::api_callstack_threads,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(
         probing_python::features::python::bindings::api_eval,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(
+        probing_python::features::python::bindings::record_probe_event,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        probing_python::features::python::bindings::trace_export_dropped,
+        m
+    )?)?;
     register_skills_bindings(m)?;
 
     // Add is_enabled function to help tests check state
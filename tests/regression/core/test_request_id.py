@@ -0,0 +1,131 @@
+"""Request ids reach engine and Python handler logs of a live probing process."""
+
+from __future__ import annotations
+
+import json
+import os
+import subprocess
+import sys
+import textwrap
+
+_SCRIPT = textwrap.dedent(
+    """
+    import json
+    import logging
+    import os
+    import socket
+    import sys
+    import tempfile
+    import time
+
+    os.environ["PROBING"] = "1"
+    os.environ["PROBING_LOG_FORMAT"] = "json"
+    os.environ["PROBING_DATA_DIR"] = tempfile.mkdtemp(prefix="probing_reqid_")
+
+    import probing
+    from probing.handlers.router import ext_handler
+
+    logging.basicConfig(
+        stream=sys.stderr,
+        level=logging.INFO,
+        format="python-handler %(request_id)s %(message)s",
+    )
+
+    @ext_handler("pythonext", "test/request-id")
+    def request_id_probe() -> str:
+        logging.getLogger("request_id_probe").info("handler ran")
+        return json.dumps({"ok": True})
+
+    def http(method, path, body=""):
+        data = body.encode()
+        sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        sock.connect(f"\\0probing-{os.getpid()}")
+        sock.sendall(
+            f"{method} {path} HTTP/1.1\\r\\nHost: localhost\\r\\n"
+            f"Connection: close\\r\\nContent-Length: {len(data)}\\r\\n\\r\\n".encode()
+            + data
+        )
+        chunks = []
+        while chunk := sock.recv(65536):
+            chunks.append(chunk)
+        sock.close()
+        head, _, body = b"".join(chunks).decode().partition("\\r\\n\\r\\n")
+        headers = {}
+        for line in head.split("\\r\\n")[1:]:
+            name, _, value = line.partition(":")
+            headers[name.strip().lower()] = value.strip()
+        return headers, body
+
+    deadline = time.monotonic() + 30
+    while True:
+        try:
+            probing.query("SELECT 1")
+            break
+        except Exception:
+            if time.monotonic() > deadline:
+                raise
+            time.sleep(0.2)
+
+    message = {
+        "version": {"major": 0, "minor": 1, "patch": 0},
+        "message_id": None,
+        "timestamp": 0,
+        "payload": {"expr": "SELECT no_such_function(1)", "opts": None},
+    }
+    query_headers, query_body = http("POST", "/query", json.dumps(message))
+    handler_headers, handler_body = http("GET", "/apis/pythonext/test/request-id")
+    sys.stderr.flush()
+    print(
+        json.dumps(
+            {
+                "query_id": query_headers["x-request-id"],
+                "query_body": query_body,
+                "handler_id": handler_headers["x-request-id"],
+                "handler_body": handler_body,
+            }
+        )
+    )
+    """
+)
+
+
+def test_failing_query_and_handler_logs_carry_request_id():
+    env = os.environ.copy()
+    for name in ("PROBING", "PROBING_ORIGINAL", "PROBING_CLI_MODE", "PROBING_LOGLEVEL"):
+        env.pop(name, None)
+    result = subprocess.run(
+        [sys.executable, "-c", _SCRIPT],
+        env=env,
+        capture_output=True,
+        text=True,
+        timeout=60,
+    )
+    assert result.returncode == 0, result.stderr
+    seen = json.loads(result.stdout.strip().splitlines()[-1])
+    assert "no_such_function" in seen["query_body"]
+    assert json.loads(seen["handler_body"]) == {"ok": True}
+    assert seen["query_id"] != seen["handler_id"]
+
+    engine_errors = [
+        line
+        for line in map(_json_line, result.stderr.splitlines())
+        if line and line["msg"].startswith("Error executing SELECT query")
+    ]
+    tagged = [line["fields"].get("request_id") for line in engine_errors]
+    assert tagged == [seen["query_id"]], result.stderr
+    assert engine_errors[0]["level"] == "ERROR"
+
+    handler_lines = [
+        line for line in result.stderr.splitlines() if line.endswith(" handler ran")
+    ]
+    expected = f"python-handler {seen['handler_id']} handler ran"
+    assert handler_lines == [expected], result.stderr
+
+
+def _json_line(line: str) -> dict | None:
+    if not line.startswith("{"):
+        return None
+    try:
+        return json.loads(line)
+    except ValueError:
+        return None
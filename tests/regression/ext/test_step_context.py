@@ -55,3 +55,24 @@ def test_nested_optimizer_span_is_reentrant():
     assert probing.step.micro_step == 4
     assert probing.step.local_step == 4
     assert probing.step.global_step == 4
+
+
+def test_step_provider_feeds_sampled_step():
+    current = {"step": None}
+    probing.step.set_provider(lambda: current["step"])
+    try:
+        assert probing.step.sampled is None, "NULL before the first step"
+        seen = []
+        for n in range(3):
+            current["step"] = n
+            seen.append(probing.step.sampled)
+        assert seen == [0, 1, 2]
+
+        def broken():
+            raise RuntimeError("no trainer")
+
+        probing.step.set_provider(broken)
+        assert probing.step.sampled is None
+    finally:
+        probing.step.set_provider(None)
+    assert probing.step.sampled is None
@@ -1,4 +1,5 @@
 import dataclasses
+import json
 import time
 
 import pytest
@@ -157,6 +158,48 @@ def test_nested_spans_persist_parent_links():
     assert child_start["parent_id"] == parent_id
 
 
+def test_untraced_frames_keep_parent_chain_and_record_caller():
+    """main (traced) -> helper (untraced) -> add_data (traced)."""
+    import inspect
+
+    from probing.tracing import current_span
+
+    spans = {}
+
+    @probing.span("add_data")
+    def add_data():
+        spans["add_data"] = current_span()
+
+    def helper():
+        add_data()
+
+    @probing.span("main")
+    def main():
+        spans["main"] = current_span()
+        helper()
+
+    main()
+    main_span, child = spans["main"], spans["add_data"]
+    assert main_span.parent_id is None
+    assert child.parent_id == main_span.span_id
+    assert child.trace_id == main_span.trace_id
+    call_line = inspect.getsourcelines(helper)[1] + 1
+    assert dict(child.get_attributes())["caller"] == f"{__file__}:{call_line}"
+
+    rows = _trace_event_rows()
+    starts = {r["name"]: r for r in rows if r.get("record_type") == "span_start"}
+    assert starts["add_data"]["parent_id"] == starts["main"]["span_id"]
+
+
+def test_ended_span_left_on_stack_is_not_a_parent():
+    with probing.span("main") as main_span:
+        stale = probing.span("stale")
+        stale.__enter__().end()
+        with probing.span("add_data") as child:
+            assert child.parent_id == main_span.span_id
+        stale.__exit__(None, None, None)
+
+
 def test_decorator_persists_trace_event_rows():
     @probing.span("decor_persist")
     def work():
@@ -242,6 +285,35 @@ def test_add_event_module_function():
     assert {r["name"] for r in persisted} == {"event1", "event2"}
 
 
+def test_add_link_records_links_on_span_end():
+    from probing.tracing import Span
+
+    with probing.span("train_step") as step:
+        pass
+    with probing.span("upload_checkpoint") as upload:
+        upload.add_link(step, kind="persists")
+        upload.add_link((99, 7))
+        links = upload.get_links()
+        span_id = upload.span_id
+    assert links[0] == {
+        "trace_id": step.trace_id,
+        "span_id": step.span_id,
+        "attributes": {"kind": "persists"},
+    }
+    assert (links[1]["trace_id"], links[1]["span_id"]) == (99, 7)
+    with pytest.raises(RuntimeError):
+        upload.add_link(step)
+    with pytest.raises(TypeError):
+        Span.new_root("x").add_link("not a span")
+
+    ends = [
+        r
+        for r in _trace_event_rows()
+        if r.get("record_type") == "span_end" and r.get("span_id") == span_id
+    ]
+    assert json.loads(ends[-1]["links"])[0]["span_id"] == step.span_id
+
+
 def test_access_nonexistent_attribute_raises():
     with probing.span("attr") as s:
         with pytest.raises(AttributeError):
@@ -329,5 +329,59 @@ class TestFilterByPrefix:
         assert result == sorted(result, key=lambda x: x["name"])
 
 
+
+class TestSuggestTraceable:
+    """Test suggest_traceable for names that could not be traced."""
+
+    @pytest.fixture
+    def fake_module(self, monkeypatch):
+        module = ModuleType("suggest_mod")
+
+        def train_step():
+            pass
+
+        def evaluate():
+            pass
+
+        class Trainer:
+            def fit(self):
+                pass
+
+            def step(self):
+                pass
+
+        module.train_step = train_step
+        module.evaluate = evaluate
+        module.Trainer = Trainer
+        monkeypatch.setitem(sys.modules, "suggest_mod", module)
+        return module
+
+    def test_misspelled_function(self, fake_module):
+        from probing.inspect.trace import suggest_traceable
+
+        assert suggest_traceable("suggest_mod.trian_step") == ["suggest_mod.train_step"]
+
+    def test_class_lists_its_functions(self, fake_module):
+        from probing.inspect.trace import suggest_traceable
+
+        assert suggest_traceable("suggest_mod.Trainer") == [
+            "suggest_mod.Trainer.fit",
+            "suggest_mod.Trainer.step",
+        ]
+        assert "suggest_mod.Trainer.step" in suggest_traceable(
+            "suggest_mod.Trainer.stpe"
+        )
+
+    def test_misspelled_module(self, fake_module):
+        from probing.inspect.trace import suggest_traceable
+
+        assert "suggest_mod" in suggest_traceable("sugest_mod.train_step")
+
+    def test_limit(self, fake_module):
+        from probing.inspect.trace import suggest_traceable
+
+        assert len(suggest_traceable("suggest_mod.Trainer", limit=1)) == 1
+
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
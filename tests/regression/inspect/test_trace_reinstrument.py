@@ -0,0 +1,110 @@
+"""Start/stop cycles on one function must not stack wrappers or leak state."""
+
+import sys
+import types
+
+import pytest
+
+import probing.inspect.trace as trace_mod
+
+MODULE = "probing_reinstrument_target"
+
+SOURCE = """
+def step(x):
+    y = x + 1
+    return y
+
+
+def other(x):
+    z = x * 2
+    return z
+"""
+
+
+@pytest.fixture
+def target(monkeypatch):
+    if "torch" not in sys.modules:
+        # ProbingTracer only needs ``torch.Tensor`` for isinstance checks.
+        stub = types.ModuleType("torch")
+        stub.Tensor = type("Tensor", (), {})
+        monkeypatch.setitem(sys.modules, "torch", stub)
+    module = types.ModuleType(MODULE)
+    exec(SOURCE, module.__dict__)
+    monkeypatch.setitem(sys.modules, MODULE, module)
+
+    records = []
+    monkeypatch.setattr(
+        trace_mod.Variable, "save", lambda self: records.append(self), raising=False
+    )
+    yield module, records
+    trace_mod.reset_traces()
+
+
+def _records_for(records, variable):
+    return [r for r in records if r.variable_name == variable]
+
+
+def test_start_stop_cycles_restore_identity_and_record_once(target):
+    module, records = target
+    func = module.step
+    code, defaults = func.__code__, func.__defaults__
+    name = f"{MODULE}.step"
+
+    for cycle in range(10):
+        assert trace_mod.trace(name, silent_watch=["y"]) == "started"
+        assert module.step(cycle) == cycle + 1
+        assert len(_records_for(records, "y")) == cycle + 1
+
+        assert trace_mod.untrace(name)
+        assert module.step is func
+        assert func.__code__ is code
+        assert func.__defaults__ is defaults
+        # Untraced calls record nothing.
+        module.step(0)
+        assert len(_records_for(records, "y")) == cycle + 1
+
+    assert trace_mod.traced_functions == {}
+    assert trace_mod._instrumented == {}
+
+
+def test_retrace_updates_config_instead_of_rewrapping(target):
+    module, records = target
+    name = f"{MODULE}.step"
+
+    assert trace_mod.trace(name, silent_watch=["y"]) == "started"
+    probe_code = module.step.__code__
+    for _ in range(3):
+        assert trace_mod.trace(name, silent_watch=["x", "y"], depth=2) == "updated"
+    assert module.step.__code__ is probe_code
+    assert trace_mod._probe_attrs[id(probe_code)]["__probe_silent_watch__"] == [
+        "x",
+        "y",
+    ]
+
+    module.step(1)
+    assert len(_records_for(records, "y")) == 1
+
+
+def test_functions_traced_together_keep_their_own_probe(target):
+    module, _ = target
+    assert trace_mod.trace(f"{MODULE}.step") == "started"
+    assert trace_mod.trace(f"{MODULE}.other") == "started"
+    assert module.step.__code__ is not module.other.__code__
+    assert module.step(1) == 2
+    assert module.other(3) == 6
+
+    trace_mod.untrace(f"{MODULE}.step")
+    assert module.other(4) == 8
+
+
+def test_reset_restores_everything(target):
+    module, _ = target
+    step, other = module.step, module.other
+    codes = (step.__code__, other.__code__)
+    trace_mod.trace(f"{MODULE}.step")
+    trace_mod.trace(f"{MODULE}.other")
+
+    assert trace_mod.reset_traces() == [f"{MODULE}.other", f"{MODULE}.step"]
+    assert (step.__code__, other.__code__) == codes
+    assert trace_mod.traced_functions == {}
+    assert trace_mod.reset_traces() == []
@@ -0,0 +1,89 @@
+"""Self-measured overhead and record counts of active traces."""
+
+import sys
+import types
+
+import pytest
+
+import probing.inspect.trace as trace_mod
+
+MODULE = "probing_trace_stats_target"
+
+SOURCE = """
+def step(x):
+    y = x + 1
+    return y
+"""
+
+
+@pytest.fixture
+def target(monkeypatch):
+    if "torch" not in sys.modules:
+        # ProbingTracer only needs ``torch.Tensor`` for isinstance checks.
+        stub = types.ModuleType("torch")
+        stub.Tensor = type("Tensor", (), {})
+        monkeypatch.setitem(sys.modules, "torch", stub)
+    module = types.ModuleType(MODULE)
+    exec(SOURCE, module.__dict__)
+    monkeypatch.setitem(sys.modules, MODULE, module)
+    yield module
+    trace_mod.reset_traces()
+
+
+def test_ema_seeds_with_first_sample_then_smooths():
+    assert trace_mod._ema(None, 10.0) == 10.0
+    assert trace_mod._ema(10.0, 20.0, alpha=0.2) == pytest.approx(12.0)
+    value = None
+    for sample in [100.0] + [0.0] * 10:
+        value = trace_mod._ema(value, sample, alpha=0.5)
+    assert value == pytest.approx(100.0 / 2**10)
+
+
+def test_stats_track_calls_and_overhead_in_microseconds():
+    stats = trace_mod._TraceStats()
+    stats.observe_call(4_000)
+    assert (stats.calls, stats.overhead_us) == (1, pytest.approx(4.0))
+    stats.observe_call(9_000)
+    assert stats.calls == 2
+    assert stats.overhead_us == pytest.approx(
+        trace_mod.OVERHEAD_EMA_ALPHA * 9.0 + (1 - trace_mod.OVERHEAD_EMA_ALPHA) * 4.0
+    )
+
+
+def test_trace_stats_report_records_and_drops(target, monkeypatch):
+    name = f"{MODULE}.step"
+    saved = []
+    monkeypatch.setattr(
+        trace_mod.Variable, "save", lambda self: saved.append(self), raising=False
+    )
+    assert trace_mod.trace(name, silent_watch=["y"]) == "started"
+    [entry] = trace_mod.trace_stats()
+    assert entry == {
+        "function": name,
+        "backend": "settrace",
+        "calls": 0,
+        "overhead_us": None,
+        "records": 0,
+        "records_dropped": 0,
+        "last_record_ts": None,
+    }
+
+    target.step(1)
+    target.step(2)
+
+    def refuse(self):
+        raise RuntimeError("table full")
+
+    monkeypatch.setattr(trace_mod.Variable, "save", refuse, raising=False)
+    target.step(3)
+
+    [entry] = trace_mod.trace_stats()
+    assert entry["calls"] == 3
+    assert entry["records"] == len(saved) == 2
+    assert entry["records_dropped"] == 1
+    assert entry["overhead_us"] > 0
+    assert entry["last_record_ts"] is not None
+
+    # Retracing keeps the figures; they belong to the function.
+    assert trace_mod.trace(name, silent_watch=["x", "y"]) == "updated"
+    assert trace_mod.trace_stats()[0]["calls"] == 3
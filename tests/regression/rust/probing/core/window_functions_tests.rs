@@ -0,0 +1,192 @@
+//! Integration tests: window functions over sampled mmap tables.
+//!
+//! Builds `cpu.utilization`-shaped rings under a temp `PROBING_DATA_DIR`, queries
+//! them through the engine catalog, and checks both results and that the
+//! physical plan reuses the scan's timestamp ordering (no `SortExec`).
+
+use std::sync::Arc;
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::datatypes::{Float64Type, Int64Type};
+use probing_core::core::federation::explain_physical_plan;
+use probing_core::core::{Engine, ProbeDataSource, UnifiedMemtableProbeDataSource};
+use probing_memtable::discover::ExposedTable;
+use probing_memtable::{DType, Schema, Value};
+
+/// `PROBING_DATA_DIR` is process-global.
+static DATA_DIR_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+const SAMPLES: i64 = 120;
+
+struct Fixture {
+    _dir: tempfile::TempDir,
+    _tables: Vec<ExposedTable>,
+    engine: Engine,
+}
+
+/// `cpu.utilization` (process scope): ts every 1000µs, cpu = i % 10, rss = 1000 + i.
+async fn cpu_fixture() -> Fixture {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::env::set_var("PROBING_DATA_DIR", dir.path());
+
+    let schema = Schema::new()
+        .col("ts", DType::I64)
+        .col("scope", DType::Str)
+        .col("cpu_total_pct", DType::F32)
+        .col("rss_kb", DType::I64);
+    // Small chunks so the ring spans many chunks / batches.
+    let mut cpu = ExposedTable::create("cpu.utilization", &schema, 1024, 64).expect("create");
+    for i in 0..SAMPLES {
+        assert!(cpu.push_row(&[
+            Value::I64(1_000_000 + i * 1000),
+            Value::Str("process"),
+            Value::F32((i % 10) as f32),
+            Value::I64(1000 + i),
+        ]));
+    }
+
+    let engine = Engine::builder()
+        .with_default_namespace("probe")
+        .with_data_source(
+            Arc::new(UnifiedMemtableProbeDataSource) as Arc<dyn ProbeDataSource + Send + Sync>
+        )
+        .build()
+        .await
+        .expect("engine");
+    Fixture {
+        _dir: dir,
+        _tables: vec![cpu],
+        engine,
+    }
+}
+
+async fn run(engine: &Engine, sql: &str) -> Vec<RecordBatch> {
+    engine
+        .sql(sql)
+        .await
+        .unwrap_or_else(|e| panic!("plan {sql}: {e}"))
+        .collect()
+        .await
+        .unwrap_or_else(|e| panic!("exec {sql}: {e}"))
+}
+
+fn f64_col(batches: &[RecordBatch], idx: usize) -> Vec<Option<f64>> {
+    batches
+        .iter()
+        .flat_map(|b| {
+            b.column(idx)
+                .as_primitive::<Float64Type>()
+                .iter()
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn i64_col(batches: &[RecordBatch], idx: usize) -> Vec<Option<i64>> {
+    batches
+        .iter()
+        .flat_map(|b| {
+            b.column(idx)
+                .as_primitive::<Int64Type>()
+                .iter()
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn moving_average_matches_manual_window_without_resort() {
+    let _lock = DATA_DIR_LOCK.lock().await;
+    let fx = cpu_fixture().await;
+
+    let sql = "SELECT ts, avg(cpu_total_pct) OVER \
+               (ORDER BY ts ROWS BETWEEN 59 PRECEDING AND CURRENT ROW) AS cpu_ma \
+               FROM cpu.utilization ORDER BY ts";
+    let batches = run(&fx.engine, sql).await;
+    let ma = f64_col(&batches, 1);
+    assert_eq!(ma.len() as i64, SAMPLES);
+
+    let raw: Vec<f64> = (0..SAMPLES).map(|i| (i % 10) as f64).collect();
+    for (i, got) in ma.iter().enumerate() {
+        let lo = i.saturating_sub(59);
+        let window = &raw[lo..=i];
+        let want = window.iter().sum::<f64>() / window.len() as f64;
+        assert!(
+            (got.unwrap() - want).abs() < 1e-9,
+            "row {i}: {got:?} vs {want}"
+        );
+    }
+
+    let plan = explain_physical_plan(&fx.engine, sql)
+        .await
+        .expect("explain");
+    assert!(
+        !plan.contains("SortExec"),
+        "window over ts-ordered ring must not re-sort:\n{plan}"
+    );
+}
+
+#[tokio::test]
+async fn lag_lead_over_memory_column() {
+    let _lock = DATA_DIR_LOCK.lock().await;
+    let fx = cpu_fixture().await;
+
+    let sql = "SELECT rss_kb - lag(rss_kb) OVER (ORDER BY ts) AS rss_delta, \
+                      lead(rss_kb, 2) OVER (ORDER BY ts) AS rss_ahead \
+               FROM cpu.utilization WHERE ts >= 1010000 ORDER BY ts";
+    let batches = run(&fx.engine, sql).await;
+    let delta = i64_col(&batches, 0);
+    let ahead = i64_col(&batches, 1);
+
+    // Filter keeps samples 10..120 (110 rows).
+    assert_eq!(delta.len(), 110);
+    assert_eq!(
+        delta[0], None,
+        "lag has no predecessor inside the filtered window"
+    );
+    assert!(delta[1..].iter().all(|d| *d == Some(1)));
+    assert_eq!(ahead[0], Some(1012));
+    assert_eq!(ahead[107], Some(1119));
+    assert_eq!(&ahead[108..], &[None, None]);
+
+    let plan = explain_physical_plan(&fx.engine, sql)
+        .await
+        .expect("explain");
+    assert!(!plan.contains("SortExec"), "unexpected sort:\n{plan}");
+}
+
+#[tokio::test]
+async fn out_of_order_rows_are_sorted_not_trusted() {
+    let _lock = DATA_DIR_LOCK.lock().await;
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::env::set_var("PROBING_DATA_DIR", dir.path());
+
+    // Multi-writer style table: timestamps arrive out of order.
+    let schema = Schema::new().col("ts", DType::I64).col("v", DType::I64);
+    let mut t = ExposedTable::create("demo.jitter", &schema, 1024, 8).expect("create");
+    for ts in [30, 10, 20, 40] {
+        assert!(t.push_row(&[Value::I64(ts), Value::I64(ts / 10)]));
+    }
+    let engine = Engine::builder()
+        .with_data_source(
+            Arc::new(UnifiedMemtableProbeDataSource) as Arc<dyn ProbeDataSource + Send + Sync>
+        )
+        .build()
+        .await
+        .expect("engine");
+
+    let sql = "SELECT v, lag(v) OVER (ORDER BY ts) AS prev FROM demo.jitter ORDER BY ts";
+    let batches = run(&engine, sql).await;
+    assert_eq!(
+        i64_col(&batches, 0),
+        vec![Some(1), Some(2), Some(3), Some(4)]
+    );
+    assert_eq!(i64_col(&batches, 1), vec![None, Some(1), Some(2), Some(3)]);
+
+    let plan = explain_physical_plan(&engine, sql).await.expect("explain");
+    assert!(
+        plan.contains("SortExec"),
+        "unsorted ring must be sorted:\n{plan}"
+    );
+    drop(t);
+}
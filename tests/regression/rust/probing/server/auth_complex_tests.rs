@@ -72,6 +72,7 @@ fn test_is_public_path_favicon() {
 fn test_is_public_path_health_ready() {
     assert!(is_public_path("/health"));
     assert!(is_public_path("/ready"));
+    assert!(is_public_path("/healthz"));
 }
 
 #[test]
@@ -8,14 +8,19 @@ use tempfile::{NamedTempFile, TempDir};
 
 // Access server modules directly since tests can access private modules
 // Note: server module is private, but tests can access it
-use probing_server::server::config::get_max_file_size;
+use probing_server::server::config::{get_max_file_size, parse_file_dirs, set_file_dirs};
 use probing_server::server::error::ApiResult;
 use probing_server::server::file_api::{read_file, validate_path};
 
+/// Serializes tests that change the cwd or `server.file_dirs`, both process-wide.
+/// An async mutex, since the guard is held across `read_file(...).await`.
+static PROCESS_STATE: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 // ========== 路径验证复杂测试 ==========
 
 #[tokio::test]
 async fn test_validate_path_traversal_attack() {
+    let _state = PROCESS_STATE.lock().await;
     // Create a temporary directory structure
     let temp_dir = TempDir::new().unwrap();
     let allowed_dir = temp_dir.path().join("logs");
@@ -32,7 +37,8 @@ async fn test_validate_path_traversal_attack() {
 
 #[tokio::test]
 async fn test_validate_path_within_allowed_dir() {
-    // Create a temporary directory structure matching ALLOWED_FILE_DIRS
+    let _state = PROCESS_STATE.lock().await;
+    // Create a temporary directory structure matching DEFAULT_FILE_DIRS
     let temp_dir = TempDir::new().unwrap();
     let logs_dir = temp_dir.path().join("logs");
     fs::create_dir_all(&logs_dir).unwrap();
@@ -55,7 +61,8 @@ async fn test_validate_path_within_allowed_dir() {
 
 #[tokio::test]
 async fn test_validate_path_outside_allowed_dir() {
-    // Use a stable system path outside ALLOWED_FILE_DIRS / HOME / cwd — not a
+    let _state = PROCESS_STATE.lock().await;
+    // Use a stable system path outside DEFAULT_FILE_DIRS / HOME / cwd — not a
     // subdir of the process cwd (cwd is always allowed for dev convenience).
     let outside = std::path::Path::new("/etc/hosts");
     if !outside.is_file() {
@@ -69,6 +76,7 @@ async fn test_validate_path_outside_allowed_dir() {
 
 #[tokio::test]
 async fn test_validate_path_normalization() {
+    let _state = PROCESS_STATE.lock().await;
     // Create a temporary directory structure
     let temp_dir = TempDir::new().unwrap();
     let logs_dir = temp_dir.path().join("logs");
@@ -94,6 +102,7 @@ async fn test_validate_path_normalization() {
 
 #[tokio::test]
 async fn test_validate_path_double_encoding() {
+    let _state = PROCESS_STATE.lock().await;
     // Test path traversal with double encoding (....//....//)
     let temp_dir = TempDir::new().unwrap();
     let logs_dir = temp_dir.path().join("logs");
@@ -114,6 +123,7 @@ async fn test_validate_path_double_encoding() {
 
 #[tokio::test]
 async fn test_validate_path_symlink() {
+    let _state = PROCESS_STATE.lock().await;
     // Note: Symlink tests may not work on all platforms
     // This is a basic test that symlinks are handled
     let temp_dir = TempDir::new().unwrap();
@@ -140,6 +150,7 @@ async fn test_validate_path_symlink() {
 
 #[tokio::test]
 async fn test_read_file_success() {
+    let _state = PROCESS_STATE.lock().await;
     // Create a temporary file
     let temp_file = NamedTempFile::new().unwrap();
     let file_path = temp_file.path();
@@ -173,6 +184,7 @@ async fn test_read_file_success() {
 
 #[tokio::test]
 async fn test_read_file_size_limit() {
+    let _state = PROCESS_STATE.lock().await;
     // Create a temporary directory structure
     let temp_dir = TempDir::new().unwrap();
     let logs_dir = temp_dir.path().join("logs");
@@ -201,6 +213,7 @@ async fn test_read_file_size_limit() {
 
 #[tokio::test]
 async fn test_read_file_within_size_limit() {
+    let _state = PROCESS_STATE.lock().await;
     // Create a temporary directory structure
     let temp_dir = TempDir::new().unwrap();
     let logs_dir = temp_dir.path().join("logs");
@@ -225,3 +238,62 @@ async fn test_read_file_within_size_limit() {
     // Restore original directory
     std::env::set_current_dir(&original_dir).unwrap();
 }
+
+// ========== server.file_dirs 运行时配置 ==========
+
+fn read_params(path: &std::path::Path) -> Query<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    params.insert("path".to_string(), path.to_str().unwrap().to_string());
+    Query(params)
+}
+
+#[tokio::test]
+async fn test_file_dirs_changed_at_runtime() {
+    let _state = PROCESS_STATE.lock().await;
+    let job_dir = TempDir::new().unwrap();
+    let log = job_dir.path().join("train.log");
+    fs::write(&log, "step=1").unwrap();
+    let other = TempDir::new().unwrap();
+
+    set_file_dirs(Some(
+        parse_file_dirs(other.path().to_str().unwrap()).unwrap(),
+    ));
+    let denied = validate_path(log.to_str().unwrap());
+    assert!(denied.unwrap_err().contains("Access denied"));
+
+    let dirs = format!("{},{}", other.path().display(), job_dir.path().display());
+    set_file_dirs(Some(parse_file_dirs(&dirs).unwrap()));
+    let result: ApiResult<String> = read_file(read_params(&log)).await;
+    assert_eq!(result.unwrap(), "step=1");
+
+    // Only the configured list applies: defaults such as /tmp are gone.
+    let tmp_file = NamedTempFile::new_in("/tmp").unwrap();
+    assert!(validate_path(tmp_file.path().to_str().unwrap()).is_err());
+
+    set_file_dirs(None);
+}
+
+#[tokio::test]
+async fn test_empty_file_dirs_disable_file_api() {
+    let _state = PROCESS_STATE.lock().await;
+    let temp_file = NamedTempFile::new_in("/tmp").unwrap();
+    fs::write(temp_file.path(), "x").unwrap();
+
+    set_file_dirs(Some(parse_file_dirs("").unwrap()));
+    let result: ApiResult<String> = read_file(read_params(temp_file.path())).await;
+    let err = result.unwrap_err();
+    assert_eq!(err.status(), axum::http::StatusCode::FORBIDDEN);
+    assert!(err.to_string().contains("disabled"));
+    assert!(validate_path(temp_file.path().to_str().unwrap())
+        .unwrap_err()
+        .contains("disabled"));
+
+    set_file_dirs(None);
+    assert!(validate_path(temp_file.path().to_str().unwrap()).is_ok());
+}
+
+#[test]
+fn test_file_dirs_rejects_missing_directories() {
+    assert!(parse_file_dirs("/nonexistent/probing-job-logs").is_err());
+    assert!(parse_file_dirs("./does-not-exist-probing").is_err());
+}
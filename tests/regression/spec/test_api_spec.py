@@ -137,6 +137,14 @@ class TestExtensionResponseSpec:
             "ray/timeline/chrome",
         }
 
+    def test_admin_handlers_change_process_state(self, spec):
+        admin = {
+            h["local_path"]
+            for h in spec["pythonext_handlers"]
+            if h.get("admin", False)
+        }
+        assert admin == {"gc"}
+
     def test_eval_uses_plain_text_response(self, spec):
         by_path = {h["local_path"]: h for h in spec["pythonext_handlers"]}
         assert by_path["eval"]["response"]["content_type"] == "text/plain"
@@ -211,6 +219,12 @@ class TestEvalUsesBody:
         result = handle_request("callstack", {})
         assert "No handler found" not in result
 
+    def test_callstack_groups_by_thread(self):
+        result = handle_request("callstack", {"group": "thread", "native": "true"})
+        assert "No handler found" not in result
+        payload = json.loads(result)
+        assert "threads" in payload or "error" in payload
+
 
 class TestServerPublicRoutes:
     """Spec: server public routes in Rust match api_spec.json."""
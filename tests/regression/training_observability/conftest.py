@@ -27,15 +27,13 @@ import probing
 # Same shape as probing/server/src/server/training.rs STEP_MATRIX_SQL (local window).
 STEP_MATRIX_SQL = """
 SELECT
-    s.attributes,
-    s.name,
-    s.time AS start_time,
-    CAST((e.time - s.time) / 1000 AS DOUBLE) AS duration_us
-FROM python.trace_event s
-JOIN python.trace_event e
-  ON s.span_id = e.span_id AND e.record_type = 'span_end'
-WHERE s.record_type = 'span_start' AND s.name = 'train.step'
-ORDER BY s.time ASC
+    attributes,
+    name,
+    time AS start_time,
+    CAST(duration / 1000 AS DOUBLE) AS duration_us
+FROM python.trace_event
+WHERE record_type = 'span' AND name = 'train.step' AND duration IS NOT NULL
+ORDER BY time ASC
 """
 
 COMM_COLLECTIVE_RECENT_SQL = """
@@ -152,20 +150,25 @@ def train_step_samples_from_memtable(limit: int = 500) -> list[dict[str, Any]]:
     """Mirror ``STEP_MATRIX_SQL`` using memtable rows (agent-side E2E).
 
     The SQL engine path for ``python.trace_event`` is not stable in all dev
-    environments; this helper validates the same join semantics in-process.
+    environments; this helper validates the same pairing in-process (span ids
+    are unique per thread, so the key includes ``thread_id``; end rows carry
+    ``trace_id`` 0).
     """
     from probing.tracing import TraceEvent
 
+    def key(e: dict) -> tuple:
+        return (e.get("thread_id"), e["span_id"])
+
     events = table_rows(TraceEvent, limit)
     starts = {
-        e["span_id"]: e
+        key(e): e
         for e in events
         if e.get("record_type") == "span_start" and e.get("name") == "train.step"
     }
-    ends = {e["span_id"]: e for e in events if e.get("record_type") == "span_end"}
+    ends = {key(e): e for e in events if e.get("record_type") == "span_end"}
     out: list[dict[str, Any]] = []
-    for span_id, start in starts.items():
-        end = ends.get(span_id)
+    for span_key, start in starts.items():
+        end = ends.get(span_key)
         if end is None:
             continue
         attrs_raw = start.get("attributes") or "{}"
@@ -0,0 +1,67 @@
+"""Peak memory of the chrome-tracing export on 500k events: the streamed
+document against building the event list and one pretty-printed string."""
+
+import json
+import tracemalloc
+
+import pytest
+
+from probing.handlers import streaming
+from probing.handlers.pythonext import _chrome_trace_events
+
+pytestmark = pytest.mark.slow
+
+SPANS = 250_000
+NAMES = ("forward", "backward", "optimizer", "data_load", "allreduce")
+
+
+def _rows():
+    # Generated on demand, so the source rows are not part of the peak.
+    for i in range(SPANS):
+        for record_type, offset in (("span_start", 0), ("span_end", 1)):
+            yield {
+                "record_type": record_type,
+                "trace_id": 1,
+                "span_id": i,
+                "parent_id": -1,
+                "name": NAMES[i % len(NAMES)],
+                "timestamp": (2 * i + offset) * 1000,
+                "thread_id": 7,
+                "phase": "",
+            }
+
+
+def _peak(export) -> tuple:
+    """``(peak bytes, document bytes)`` while running ``export``."""
+    tracemalloc.start()
+    try:
+        size = export()
+        return tracemalloc.get_traced_memory()[1], size
+    finally:
+        tracemalloc.stop()
+
+
+def _materialized() -> int:
+    # The pre-streaming export: every event, then one pretty document.
+    events = list(_chrome_trace_events(_rows))
+    return len(json.dumps({"traceEvents": events}, indent=2))
+
+
+def _streamed() -> int:
+    chunks = streaming.json_array_chunks(
+        _chrome_trace_events(_rows), head='{"traceEvents": [\n', tail="\n]}"
+    )
+    return sum(len(chunk) for chunk in chunks)
+
+
+def test_streamed_export_peak_is_a_fraction_of_materialized():
+    before, pretty_size = _peak(_materialized)
+    after, compact_size = _peak(_streamed)
+    print(
+        f"\n{2 * SPANS} events: materialized peak {before >> 20} MiB "
+        f"({pretty_size >> 20} MiB document), streamed peak {after >> 20} MiB "
+        f"({compact_size >> 20} MiB document)"
+    )
+    assert compact_size < pretty_size
+    # What remains is the one-tuple-per-span start map, not the events.
+    assert after * 4 < before
@@ -0,0 +1,184 @@
+"""Tests for the Perfetto protobuf writer behind ``chrome-tracing?format=proto``."""
+
+import json
+import struct
+from collections import defaultdict
+
+import pytest
+
+from probing.handlers import perfetto
+
+
+def _fields(buf):
+    """``(field, value)`` pairs of one protobuf message."""
+    pos = 0
+    while pos < len(buf):
+        key, pos = _varint(buf, pos)
+        field, wire = key >> 3, key & 7
+        if wire == 0:
+            value, pos = _varint(buf, pos)
+        elif wire == 1:
+            value, pos = buf[pos : pos + 8], pos + 8
+        elif wire == 2:
+            size, pos = _varint(buf, pos)
+            value, pos = buf[pos : pos + size], pos + size
+        else:
+            raise AssertionError(f"unexpected wire type {wire}")
+        yield field, value
+
+
+def _varint(buf, pos):
+    result = shift = 0
+    while True:
+        byte = buf[pos]
+        pos += 1
+        result |= (byte & 0x7F) << shift
+        shift += 7
+        if not byte & 0x80:
+            return result, pos
+
+
+def _message(buf):
+    out = defaultdict(list)
+    for field, value in _fields(buf):
+        out[field].append(value)
+    return out
+
+
+def decode(blob):
+    """Track names and ``(kind, track, name, ts_ns, fields)`` events of a trace."""
+    tracks, events = {}, []
+    for field, packet in _fields(blob):
+        assert field == 1
+        packet = _message(packet)
+        assert packet[10] == [perfetto.SEQUENCE_ID]
+        for descriptor in map(_message, packet.get(60, [])):
+            name = descriptor[2][0].decode() if 2 in descriptor else None
+            if 3 in descriptor:
+                name = _message(descriptor[3][0])[6][0].decode()
+            tracks[descriptor[1][0]] = name
+        for event in map(_message, packet.get(11, [])):
+            name = event[23][0].decode() if 23 in event else None
+            events.append((event[9][0], event[11][0], name, packet[8][0], event))
+    return tracks, events
+
+
+def slices(tracks, events):
+    """``(track name, slice name, begin ns, end ns)`` by per-track stack."""
+    stacks, out = defaultdict(list), []
+    for kind, track, name, ts, _ in events:
+        if kind == perfetto.TYPE_SLICE_BEGIN:
+            stacks[track].append((name, ts))
+        elif kind == perfetto.TYPE_SLICE_END:
+            begin_name, begin = stacks[track].pop()
+            out.append((tracks[track], begin_name, begin, ts))
+    assert not any(stacks.values())
+    return sorted(out)
+
+
+def chrome_slices(events, lane_names):
+    stacks, out = defaultdict(list), []
+    for e in events:
+        lane = (e["pid"], e["tid"])
+        if e["ph"] == "B":
+            stacks[lane].append((e["name"], e["ts"]))
+        elif e["ph"] == "E" and stacks[lane]:
+            name, begin = stacks[lane].pop()
+            out.append((lane_names(lane), name, begin * 1000, e["ts"] * 1000))
+    return sorted(out)
+
+
+def test_varint_and_signed_values():
+    assert perfetto._varint(0) == b"\x00"
+    assert perfetto._varint(300) == b"\xac\x02"
+    assert _varint(perfetto._varint(-1), 0) == ((1 << 64) - 1, 10)
+
+
+def test_slices_instants_and_counters():
+    events = [
+        {"name": "process_name", "ph": "M", "pid": 3, "tid": 0, "args": {"name": "job"}},
+        {"name": "thread_name", "ph": "M", "pid": 3, "tid": 7, "args": {"name": "main"}},
+        {"name": "step", "cat": "span", "ph": "B", "ts": 1, "pid": 3, "tid": 7},
+        {
+            "name": "loss",
+            "cat": "event",
+            "ph": "i",
+            "ts": 2,
+            "pid": 3,
+            "tid": 7,
+            "s": "t",
+            "args": {"value": 0.5, "ok": True, "n": -2},
+        },
+        {"name": "step", "cat": "span", "ph": "E", "ts": 4, "pid": 3, "tid": 7},
+        {"name": "orphan", "cat": "span", "ph": "E", "ts": 5, "pid": 3, "tid": 7},
+        {"name": "rss_kb", "ph": "C", "ts": 5, "pid": 0, "tid": 0, "args": {"rss_kb": 10}},
+    ]
+    blob = b"".join(perfetto.trace_packet_chunks(events, flush_every=2))
+    tracks, decoded = decode(blob)
+
+    assert sorted(n for n in tracks.values() if n) == ["job", "main", "rss_kb", "trace 0"]
+    assert slices(tracks, decoded) == [("main", "step", 1000, 4000)]
+    # The orphan end is dropped rather than left unmatched.
+    assert [k for k, *_ in decoded].count(perfetto.TYPE_SLICE_END) == 1
+
+    instant = next(e for e in decoded if e[0] == perfetto.TYPE_INSTANT)
+    annotations = {
+        _message(a)[10][0].decode(): _message(a) for a in instant[4][4]
+    }
+    assert struct.unpack("<d", annotations["value"][5][0]) == (0.5,)
+    assert annotations["ok"][2] == [1]
+    assert _varint(perfetto._varint(-2), 0)[0] == annotations["n"][4][0]
+
+    counter = next(e for e in decoded if e[0] == perfetto.TYPE_COUNTER)
+    assert tracks[counter[1]] == "rss_kb"
+    assert struct.unpack("<d", counter[4][44][0]) == (10.0,)
+
+
+def test_chrome_tracing_proto_matches_json(monkeypatch):
+    pd = pytest.importorskip("pandas")
+    import probing.core.engine as engine
+    from probing.handlers import pythonext
+
+    def row(record_type, span_id, ts, thread_id, name="", thread_name=None):
+        return {
+            "record_type": record_type,
+            "trace_id": 1,
+            "span_id": span_id,
+            "parent_id": -1,
+            "name": name,
+            "timestamp": ts * 1000,
+            "thread_id": thread_id,
+            "phase": "",
+            "location": None,
+            "attributes": None,
+            "event_attributes": None,
+            "thread_name": thread_name,
+            "links": None,
+        }
+
+    rows = [
+        row("span_start", 1, 0, 7, "step", "MainThread"),
+        row("span_start", 2, 1, 7, "forward"),
+        row("event", 2, 2, 7, "loss"),
+        row("span_end", 2, 3, 7),
+        row("span_start", 3, 2, 8, "load", "loader"),
+        row("span_end", 1, 5, 7),
+        row("span_end", 3, 6, 8),
+    ]
+    monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+
+    doc = json.loads("".join(pythonext.get_chrome_tracing(limit=0)))
+    names = {
+        (e["pid"], e["tid"]): e["args"]["name"]
+        for e in doc["traceEvents"]
+        if e["ph"] == "M" and e["name"] == "thread_name"
+    }
+    expected = chrome_slices(doc["traceEvents"], names.get)
+    assert len(expected) == 3
+
+    blob = b"".join(pythonext.get_chrome_tracing(limit=0, format="proto"))
+    assert blob[:1] == b"\x0a"
+    assert slices(*decode(blob)) == expected
+
+    err = json.loads(pythonext.get_chrome_tracing(format="xml"))
+    assert "format must be" in err["error"]
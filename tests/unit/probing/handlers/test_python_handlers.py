@@ -1,6 +1,9 @@
 """Tests for Python extension handlers and router."""
 
 import json
+import types
+
+import pytest
 
 from probing.handlers.pythonext import handle_api_request
 from probing.handlers.router import (
@@ -93,6 +96,26 @@ class TestHandlerRouter:
         missing = json.loads(handle_request("test/eval", {}))
         assert "Missing request body" in missing["error"]
 
+    def test_request_id_bound_for_handler_logs(self, caplog):
+        """Server-assigned request ids reach handler code and its log records."""
+        import logging
+
+        from probing.handlers.request_context import current_request_id
+
+        @ext_handler("test", "test/fails")
+        def test_handler() -> str:
+            logging.getLogger("probing.test").warning("inside handler")
+            raise RuntimeError(f"boom {current_request_id()}")
+
+        with caplog.at_level(logging.WARNING):
+            result = json.loads(handle_request("test/fails", {}, request_id="req-42"))
+
+        assert result["error"] == "boom req-42"
+        ids = {r.getMessage(): r.request_id for r in caplog.records}
+        assert ids["inside handler"] == "req-42"
+        assert ids["handler test/fails failed: boom req-42"] == "req-42"
+        assert current_request_id() is None
+
     def test_optional_parameters(self):
         """Test optional parameter handling."""
 
@@ -144,6 +167,652 @@ class TestHandlerRouter:
         assert parsed["metrics_path"] == "/engine_metrics"
         assert parsed["count"] == 10
 
+    def test_streaming_handler_yields_chunks_lazily(self):
+        """Iterator results pass through unbuffered with the request id bound."""
+        from probing.handlers.request_context import current_request_id
+
+        produced = []
+
+        @ext_handler("test", "test/stream")
+        def test_handler(n: int):
+            for i in range(n):
+                produced.append(i)
+                yield f"{i}:{current_request_id()};"
+
+        chunks = handle_request("test/stream", {"n": "3"}, request_id="req-7")
+        assert not isinstance(chunks, str)
+        assert produced == []
+        assert next(chunks) == "0:req-7;"
+        assert produced == [0]
+        assert list(chunks) == ["1:req-7;", "2:req-7;"]
+
+    def test_chrome_tracing_streams_valid_document(self, monkeypatch):
+        """chrome-tracing emits one JSON document split into event chunks."""
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext, streaming
+
+        spans = 2500
+        rows = []
+        for i in range(spans):
+            for record_type, ts in (("span_start", 2 * i), ("span_end", 2 * i + 1)):
+                rows.append(
+                    {
+                        "record_type": record_type,
+                        "trace_id": 1,
+                        "span_id": i,
+                        "parent_id": -1,
+                        "name": f"span{i}",
+                        "timestamp": ts * 1000,
+                        "thread_id": 7,
+                        "phase": "",
+                        "location": None,
+                        "attributes": None,
+                        "event_attributes": None,
+                    }
+                )
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+
+        chunks = list(pythonext.get_chrome_tracing(limit=0))
+        assert len(chunks) == 2 * spans // streaming.FLUSH_EVERY + 1
+        doc = json.loads("".join(chunks))
+        events = doc["traceEvents"]
+        assert len(events) == 2 * spans
+        assert events[0] == {
+            "name": "span0",
+            "cat": "span",
+            "ph": "B",
+            "ts": 0,
+            "pid": 1,
+            "tid": 7,
+        }
+        assert events[1]["ph"] == "E" and events[1]["dur"] == 1
+
+    def test_query_pages_keeps_rows_sharing_a_key_together(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import re
+
+        import probing.core.engine as engine
+
+        keys = [1, 2, 2, 2, 2, 3, 4, 4, 5]
+        table = pd.DataFrame({"k": keys, "row": range(len(keys))})
+
+        def query(sql):
+            at = re.search(r'"k" = (\d+)', sql)
+            if at:
+                return table[table["k"] == int(at.group(1))]
+            after = re.search(r'"k" > (\d+)', sql)
+            rows = table[table["k"] > int(after.group(1))] if after else table
+            return rows.head(int(re.findall(r"LIMIT (\d+)", sql)[-1]))
+
+        monkeypatch.setattr(engine, "query", query)
+
+        pages = engine.query_pages("SELECT * FROM t", "k", page_rows=3)
+        assert [list(page["row"]) for page in pages] == [
+            [0],
+            [1, 2, 3, 4],
+            [5],
+            [6, 7],
+            [8],
+        ]
+
+    def test_chrome_tracing_memory_stays_flat(self, monkeypatch):
+        """The export holds a page of rows at a time, not the whole trace."""
+        pd = pytest.importorskip("pandas")
+        import re
+        import tracemalloc
+
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        # Many short spans (start, event, end), one row per µs; ~400 bytes
+        # per event. Holding every finished span would exceed the budget.
+        total = 120_000
+        note = "x" * 300
+        budget = 4 << 20
+
+        def record_type(t):
+            return ("span_start", "event", "span_end")[t % 3]
+
+        def page(lo, hi):
+            ts = range(lo, hi)
+            return pd.DataFrame(
+                {
+                    "record_type": [record_type(t) for t in ts],
+                    "trace_id": [1] * len(ts),
+                    "span_id": [t // 3 + 1 for t in ts],
+                    "parent_id": [-1] * len(ts),
+                    "name": ["tick" if t % 3 == 1 else "step" for t in ts],
+                    "timestamp": [t * 1000 for t in ts],
+                    "thread_id": [7] * len(ts),
+                    "phase": [""] * len(ts),
+                    "location": [None] * len(ts),
+                    "attributes": [None] * len(ts),
+                    "event_attributes": [
+                        json.dumps({"i": t, "note": note}) for t in ts
+                    ],
+                    "thread_name": [None] * len(ts),
+                    "links": [None] * len(ts),
+                }
+            )
+
+        def query(sql):
+            at = re.search(r'"timestamp" = (\d+)', sql)
+            if at:
+                t = int(at.group(1)) // 1000
+                return page(t, t + 1)
+            after = re.search(r'"timestamp" > (\d+)', sql)
+            lo = int(after.group(1)) // 1000 + 1 if after else 0
+            size = int(re.findall(r"LIMIT (\d+)", sql)[-1])
+            return page(lo, min(lo + size, total))
+
+        monkeypatch.setattr(engine, "query", query)
+        monkeypatch.setattr(engine, "PAGE_ROWS", 1000)
+
+        streamed = 0
+        tail = ""
+        tracemalloc.start()
+        try:
+            for chunk in pythonext.get_chrome_tracing(limit=0):
+                streamed += len(chunk)
+                tail = chunk[-3:]
+            _, peak = tracemalloc.get_traced_memory()
+        finally:
+            tracemalloc.stop()
+        assert tail == "\n]}"
+        assert streamed > 4 * budget, f"only {streamed} bytes streamed"
+        assert peak < budget, f"peak {peak} bytes while streaming {streamed} bytes"
+
+    def test_chrome_tracing_event_args_keep_native_types(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        row = {
+            "record_type": "event",
+            "trace_id": 1,
+            "span_id": 3,
+            "parent_id": -1,
+            "name": "prefill",
+            "timestamp": 5000,
+            "thread_id": 7,
+            "phase": "",
+            "location": None,
+            "attributes": None,
+            "event_attributes": json.dumps(
+                {"tokens": 4096, "cache_hit": True, "ratio": 0.5, "model": "m"}
+            ),
+        }
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame([row]))
+
+        doc = json.loads("".join(pythonext.get_chrome_tracing(limit=0)))
+        args = doc["traceEvents"][0]["args"]
+        assert args == {"tokens": 4096, "cache_hit": True, "ratio": 0.5, "model": "m"}
+        assert type(args["tokens"]) is int
+        assert type(args["cache_hit"]) is bool
+        assert type(args["ratio"]) is float
+
+    def test_chrome_tracing_marks_failed_spans(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, span_id, name, ts, phase=""):
+            return {
+                "record_type": record_type,
+                "trace_id": 1,
+                "span_id": span_id,
+                "parent_id": -1,
+                "name": name,
+                "timestamp": ts * 1000,
+                "thread_id": 7,
+                "phase": phase,
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        rows = [
+            row("span_start", 1, "ok", 0, phase="forward"),
+            row("span_end", 1, "", 1),
+            row("span_start", 2, "bad", 2, phase="forward"),
+            row("event", 2, "exception", 3),
+            row("span_end", 2, "", 4),
+        ]
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+
+        doc = json.loads("".join(pythonext.get_chrome_tracing(limit=0)))
+        cats = {
+            (e["name"], e["ph"]): e["cat"]
+            for e in doc["traceEvents"]
+            if e["ph"] in ("B", "E")
+        }
+        assert cats == {
+            ("ok", "B"): "forward",
+            ("ok", "E"): "forward",
+            ("bad", "B"): "error",
+            ("bad", "E"): "error",
+        }
+
+    def test_chrome_tracing_pushes_filters_into_query(self, monkeypatch):
+        """name filters select span starts; orphaned ends and events are skipped."""
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, span_id, name, ts):
+            return {
+                "record_type": record_type,
+                "trace_id": 1,
+                "span_id": span_id,
+                "parent_id": -1,
+                "name": name,
+                "timestamp": ts * 1000,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        queries = []
+        rows = [
+            row("span_start", 1, "step", 0),
+            row("event", 2, "prefill", 1),
+            row("span_end", 2, "", 2),
+            row("span_end", 1, "", 3),
+        ]
+
+        def query(sql):
+            queries.append(sql)
+            return pd.DataFrame(rows)
+
+        monkeypatch.setattr(engine, "query", query)
+
+        doc = json.loads(
+            "".join(
+                pythonext.get_chrome_tracing(limit=0, name="step,it's", thread_id="7")
+            )
+        )
+        assert "thread_id IN (7)" in queries[0]
+        assert "name IN ('step', 'it''s')" in queries[0]
+        assert [(e["name"], e["ph"]) for e in doc["traceEvents"]] == [
+            ("step", "B"),
+            ("step", "E"),
+        ]
+
+    def test_chrome_tracing_time_window_keeps_open_spans_whole(self, monkeypatch):
+        """A span open at the window start begins there; its end closes it."""
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, span_id, name, ts):
+            return {
+                "record_type": record_type,
+                "trace_id": 1,
+                "span_id": span_id,
+                "parent_id": -1,
+                "name": name,
+                "timestamp": ts,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        queries = []
+
+        def query(sql):
+            queries.append(sql)
+            if "record_type = 'span'" in sql:
+                return pd.DataFrame([row("span_start", 1, "step", 10_000)])
+            return pd.DataFrame(
+                [
+                    row("span_start", 2, "forward", 12_000),
+                    row("span_end", 2, "", 15_000),
+                    row("span_end", 1, "", 20_000),
+                ]
+            )
+
+        monkeypatch.setattr(engine, "query", query)
+
+        chunks = pythonext.get_chrome_tracing(limit=0, start_ts=10_000, end_ts=30_000)
+        doc = json.loads("".join(chunks))
+        assert "end_time >= 10000" in queries[0]
+        assert "time >= 10000 AND time <= 30000" in queries[1]
+        assert [(e["name"], e["ph"], e["ts"]) for e in doc["traceEvents"]] == [
+            ("step", "B", 0),
+            ("forward", "B", 2),
+            ("forward", "E", 5),
+            ("step", "E", 10),
+        ]
+
+        queries.clear()
+        empty = pythonext.get_chrome_tracing(start_ts=5, end_ts=1)
+        assert json.loads("".join(empty)) == {
+            "displayTimeUnit": "ms",
+            "traceEvents": [],
+        }
+        assert queries == []
+
+    def test_chrome_tracing_name_glob_escapes_like_wildcards(self):
+        from probing.handlers.pythonext import _chrome_tracing_filters, _glob_to_like
+
+        assert _glob_to_like("step_*") == "step\\_%"
+        assert _glob_to_like("it's ?%") == "it''s _\\%"
+        assert _glob_to_like("a\\*b\\\\") == "a*b\\\\"
+        assert _glob_to_like("trailing\\") == "trailing\\\\"
+        sql = _chrome_tracing_filters(None, None, None, name_glob="fwd_*")
+        assert sql == " AND (record_type <> 'span_start' OR name LIKE 'fwd\\_%')"
+
+    def test_chrome_tracing_filters_before_limit_and_keeps_parents(self, monkeypatch):
+        """A span kept by the glob brings its dropped ancestors along."""
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, span_id, parent_id, name, ts):
+            return {
+                "record_type": record_type,
+                "trace_id": 9 if record_type != "span_end" else 0,
+                "span_id": span_id,
+                "parent_id": parent_id,
+                "name": name,
+                "timestamp": ts * 1000,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        def span(span_id, parent_id, name, start, end):
+            return {
+                "trace_id": 9,
+                "span_id": span_id,
+                "parent_id": parent_id,
+                "name": name,
+                "time": start * 1000,
+                "end_time": end * 1000,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "thread_name": None,
+            }
+
+        queries = []
+
+        def query(sql):
+            queries.append(sql)
+            if "span_id IN (2)" in sql:
+                return pd.DataFrame([span(2, 1, "step", 1, 8)])
+            if "span_id IN (1)" in sql:
+                return pd.DataFrame([span(1, -1, "run", 0, 10)])
+            # The step's own end made it past the filters; the run's did not.
+            return pd.DataFrame(
+                [
+                    row("span_start", 3, 2, "step_fwd", 2),
+                    row("span_end", 3, -1, "", 4),
+                    row("span_end", 2, -1, "", 8),
+                ]
+            )
+
+        monkeypatch.setattr(engine, "query", query)
+
+        chunks = pythonext.get_chrome_tracing(limit=5, name_glob="step_*", trace_id=9)
+        doc = json.loads("".join(chunks))
+        main = queries[0]
+        assert main.index("name LIKE 'step\\_%'") < main.index("LIMIT 5")
+        assert main.index("trace_id = 9 OR (record_type = 'span_end'") < main.index(
+            "LIMIT 5"
+        )
+        assert sum("span_id IN" in q for q in queries) == 2
+        assert [(e["name"], e["ph"], e["ts"]) for e in doc["traceEvents"]] == [
+            ("run", "B", 0),
+            ("step", "B", 1),
+            ("step_fwd", "B", 2),
+            ("step_fwd", "E", 4),
+            ("step", "E", 8),
+            ("run", "E", 10),
+        ]
+
+    def test_chrome_tracing_interleaves_counter_samples(self, monkeypatch):
+        """Counter samples share the span timebase; missing columns are skipped."""
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, ts):
+            return {
+                "record_type": record_type,
+                "trace_id": 1,
+                "span_id": 1,
+                "parent_id": -1,
+                "name": "step",
+                "timestamp": ts,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        counter_queries = []
+
+        def query(sql):
+            if "cpu.utilization" in sql:
+                counter_queries.append(sql)
+                return pd.DataFrame(
+                    {
+                        "ts": [1000, 2000, 3000],
+                        "comm": ["python"] * 3,
+                        "cpu_total_pct": [50.0, float("nan"), 75.0],
+                        "rss_kb": [100, 200, 300],
+                    }
+                )
+            return pd.DataFrame(
+                [row("span_start", 1_000_000), row("span_end", 3_000_000)]
+            )
+
+        monkeypatch.setattr(engine, "query", query)
+
+        doc = json.loads(
+            "".join(pythonext.get_chrome_tracing(limit=0, include_counters=True))
+        )
+        assert "ts >= 1000 AND ts <= 3000" in counter_queries[0]
+        events = [e for e in doc["traceEvents"] if e["ph"] != "M"]
+        assert [(e["name"], e["ph"], e["ts"]) for e in events] == [
+            ("cpu_total_pct", "C", 0),
+            ("rss_kb", "C", 0),
+            ("step", "B", 0),
+            ("rss_kb", "C", 1000),
+            ("cpu_total_pct", "C", 2000),
+            ("rss_kb", "C", 2000),
+            ("step", "E", 2000),
+        ]
+        assert events[0]["pid"] == 0 and events[0]["args"] == {"cpu_total_pct": 50.0}
+
+        # A failing counter source leaves the spans intact.
+        def failing(sql):
+            if "cpu.utilization" in sql:
+                raise RuntimeError("table not found")
+            return query(sql)
+
+        monkeypatch.setattr(engine, "query", failing)
+        doc = json.loads(
+            "".join(pythonext.get_chrome_tracing(limit=0, include_counters=True))
+        )
+        assert [e["ph"] for e in doc["traceEvents"]] == ["B", "E"]
+
+    def test_chrome_tracing_names_processes_and_threads(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, span_id, ts, thread_id, thread_name):
+            return {
+                "record_type": record_type,
+                "trace_id": 3,
+                "span_id": span_id,
+                "parent_id": -1,
+                "name": "step" if record_type == "span_start" else "",
+                "timestamp": ts * 1000,
+                "thread_id": thread_id,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+                "thread_name": thread_name,
+            }
+
+        rows = [
+            row("span_start", 1, 0, 7, "MainThread"),
+            row("span_start", 2, 1, 8, "loader"),
+            row("span_end", 2, 2, 8, ""),
+            row("span_end", 1, 3, 7, ""),
+        ]
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+
+        events = json.loads("".join(pythonext.get_chrome_tracing(limit=0)))[
+            "traceEvents"
+        ]
+        meta = [e for e in events if e["ph"] == "M"]
+        assert [(e["name"], e["tid"], e["args"]["name"]) for e in meta[1:]] == [
+            ("thread_name", 7, "MainThread"),
+            ("thread_name", 8, "loader"),
+        ]
+        assert meta[0]["name"] == "process_name" and meta[0]["pid"] == 3
+        assert meta[0]["args"]["name"].endswith("trace 3")
+        assert events.index(meta[2]) < events.index(
+            next(e for e in events if e["tid"] == 8 and e["ph"] == "B")
+        )
+        assert len(events) - len(meta) == 4
+
+    def test_chrome_tracing_draws_span_links_as_flows(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, trace_id, span_id, ts, thread_id, links=None):
+            return {
+                "record_type": record_type,
+                "trace_id": trace_id,
+                "span_id": span_id,
+                "parent_id": -1,
+                "name": f"s{span_id}" if record_type == "span_start" else "",
+                "timestamp": ts * 1000,
+                "thread_id": thread_id,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+                "thread_name": None,
+                "links": links,
+            }
+
+        link = json.dumps(
+            [
+                {"trace_id": 1, "span_id": 1, "attributes": {}},
+                {"trace_id": 5, "span_id": 99, "attributes": {}},
+            ]
+        )
+        rows = [
+            row("span_start", 1, 1, 0, 7),
+            row("span_end", 0, 1, 4, 7, ""),
+            row("span_start", 2, 2, 6, 8),
+            row("span_end", 0, 2, 9, 8, link),
+        ]
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+
+        events = json.loads("".join(pythonext.get_chrome_tracing(limit=0)))[
+            "traceEvents"
+        ]
+        flows = [e for e in events if e.get("cat") == "link"]
+        # The link to a span outside the rows is dropped.
+        assert [(e["ph"], e["pid"], e["tid"], e["ts"]) for e in flows] == [
+            ("s", 1, 7, 0),
+            ("f", 2, 8, 6),
+        ]
+        assert flows[0]["id"] == flows[1]["id"] and flows[1]["bp"] == "e"
+
+    def test_chrome_tracing_combined_aligns_pytorch_events(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        def row(record_type, ts):
+            return {
+                "record_type": record_type,
+                "trace_id": 3,
+                "span_id": 1,
+                "parent_id": -1,
+                "name": "step",
+                "timestamp": ts,
+                "thread_id": 7,
+                "phase": "",
+                "location": None,
+                "attributes": None,
+                "event_attributes": None,
+            }
+
+        # Probing spans 1_000_010..1_000_050 µs; PyTorch starts 10 µs earlier.
+        rows = [row("span_start", 1_000_010_000), row("span_end", 1_000_050_000)]
+        torch_trace = {
+            "baseTimeNanoseconds": 1_000_000_000,
+            "traceEvents": [
+                {
+                    "ph": "M",
+                    "name": "process_name",
+                    "pid": 42,
+                    "args": {"name": "GPU 0"},
+                },
+                {"ph": "X", "name": "gemm", "pid": 42, "tid": 1, "ts": 0.5, "dur": 5},
+                {"ph": "X", "name": "aten::mm", "pid": 9, "tid": 9, "ts": 20, "dur": 1},
+            ],
+        }
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame(rows))
+        monkeypatch.setattr(pythonext, "_pytorch_trace", lambda: torch_trace)
+
+        events = json.loads(
+            "".join(pythonext.get_chrome_tracing(limit=0, source="combined"))
+        )["traceEvents"]
+        spans = [(e["ph"], e["pid"], e["ts"]) for e in events if e["pid"] == 3]
+        assert spans == [("B", 3, 9.5), ("E", 3, 49.5)]
+        base = pythonext.PYTORCH_PID_BASE
+        lanes = {
+            e["pid"]: e["args"]["name"] for e in events if e["name"] == "process_name"
+        }
+        assert lanes == {base: "pytorch GPU 0", base + 1: "pytorch 9"}
+        torch = [(e["name"], e["pid"], e["ts"]) for e in events if e["ph"] == "X"]
+        assert torch == [("gemm", base, 0), ("aten::mm", base + 1, 19.5)]
+
+    def test_chrome_tracing_combined_degrades_to_one_source(self, monkeypatch):
+        pd = pytest.importorskip("pandas")
+        import probing.core.engine as engine
+        from probing.handlers import pythonext
+
+        torch_trace = {
+            "traceEvents": [
+                {"ph": "X", "name": "gemm", "pid": 1, "tid": 1, "ts": 700, "dur": 5}
+            ]
+        }
+        monkeypatch.setattr(engine, "query", lambda _sql: pd.DataFrame())
+        monkeypatch.setattr(pythonext, "_pytorch_trace", lambda: torch_trace)
+        doc = json.loads("".join(pythonext.get_chrome_tracing(source="combined")))
+        metadata, gemm = doc["traceEvents"]
+        assert metadata["args"] == {"name": "pytorch 1"}
+        assert (gemm["pid"], gemm["ts"]) == (pythonext.PYTORCH_PID_BASE, 0)
+
+        monkeypatch.setattr(pythonext, "_pytorch_trace", lambda: None)
+        doc = json.loads("".join(pythonext.get_chrome_tracing(source="combined")))
+        assert doc["traceEvents"] == []
+
+        error = pythonext.get_chrome_tracing(source="combined", format="proto")
+        assert "json" in json.loads(error)["error"]
+
+
 
 class TestUnifiedEntryPoint:
     """Test the unified entry point."""
@@ -161,6 +830,86 @@ class TestUnifiedEntryPoint:
         parsed = json.loads(result)
         assert isinstance(parsed, (dict, list))
 
+    def test_trace_show_lists_stats_per_active_trace(self, monkeypatch):
+        import probing.inspect.trace as trace_mod
+
+        stats = trace_mod._TraceStats(
+            calls=4, overhead_us=12.5, records=9, records_dropped=1
+        )
+        inst = types.SimpleNamespace(stats=stats)
+        monkeypatch.setattr(
+            trace_mod, "traced_functions", {"train.step": inst, "step": inst}
+        )
+
+        parsed = json.loads(handle_api_request("trace/show", {}))
+        assert [t["function"] for t in parsed] == ["train.step", "step"]
+        assert parsed[0] == {
+            "function": "train.step",
+            "backend": "settrace",
+            "calls": 4,
+            "overhead_us": 12.5,
+            "records": 9,
+            "records_dropped": 1,
+            "last_record_ts": None,
+        }
+
+    def test_gc_reports_collected_cycles(self, monkeypatch):
+        import gc
+
+        import probing.inspect.gc as gc_mod
+
+        monkeypatch.setattr(gc_mod, "_record", lambda _result: None)
+
+        class Node:
+            pass
+
+        gc.disable()
+        try:
+            for _ in range(10):
+                a, b = Node(), Node()
+                a.peer, b.peer = b, a
+            del a, b
+        finally:
+            gc.enable()
+
+        parsed = json.loads(handle_api_request("gc", {}))
+        assert parsed["generation"] == 2
+        assert parsed["collected"] > 0
+
+        parsed = json.loads(handle_api_request("gc", {"generation": "5"}))
+        assert "generation must be" in parsed["error"]
+
+    def test_complete_uses_main_namespace_without_kernel(self, monkeypatch):
+        import __main__
+
+        import probing.repl as repl
+
+        console = repl.DebugConsole.__new__(repl.DebugConsole)
+        console.code_executor = None
+        monkeypatch.setattr(repl, "get_debug_console", lambda: console)
+        monkeypatch.setattr(
+            __main__,
+            "probing_console_ns",
+            types.SimpleNamespace(value=1, values=[]),
+            raising=False,
+        )
+
+        parsed = json.loads(
+            handle_api_request("complete", {"code": "x = 1\nprobing_console_n"})
+        )
+        assert parsed == {"matches": ["probing_console_ns"], "start": 6, "end": 23}
+
+        parsed = json.loads(
+            handle_api_request(
+                "complete", {"code": "probing_console_ns.val + 1", "cursor": "22"}
+            )
+        )
+        assert parsed["matches"] == [
+            "probing_console_ns.value",
+            "probing_console_ns.values",
+        ]
+        assert (parsed["start"], parsed["end"]) == (0, 22)
+
     def test_handle_api_request_invalid_path(self):
         result = handle_api_request("invalid/path", {})
         parsed = json.loads(result)
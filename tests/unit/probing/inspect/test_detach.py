@@ -0,0 +1,75 @@
+"""Python-side teardown (``probing.inspect.detach``) tests."""
+
+from __future__ import annotations
+
+import json
+import sys
+import threading
+
+import pytest
+
+from probing.inspect import detach as detach_mod
+from probing.inspect import threads
+
+
+@pytest.fixture(autouse=True)
+def restore_hooks(monkeypatch):
+    """Put back what a detach removed from the test process."""
+    meta_path = list(sys.meta_path)
+    monkeypatch.setattr(threading.Thread, "start", threading.Thread.start)
+    monkeypatch.setattr(threads, "_ORIGINAL_START", threads._ORIGINAL_START)
+    yield
+    sys.meta_path[:] = meta_path
+
+
+def _by_name(result):
+    return {c["name"]: c for c in result["components"]}
+
+
+def test_every_component_is_reported():
+    result = detach_mod.detach()
+    names = [c["name"] for c in result["components"]]
+    assert names == [name for name, _ in detach_mod.COMPONENTS]
+    assert all(c["ok"] for c in result["components"])
+    json.dumps(result)
+
+
+def test_thread_tracking_is_restored():
+    threads.uninstall()
+    original = threading.Thread.start
+    threads.install()
+    assert threading.Thread.start is not original
+
+    component = _by_name(detach_mod.detach())["thread tracking"]
+    assert component == {
+        "name": "thread tracking",
+        "ok": True,
+        "detail": "restored threading.Thread.start",
+    }
+    assert threading.Thread.start is original
+
+
+def test_import_hook_is_removed():
+    from probing.hooks import import_hook
+
+    finder = import_hook.install()
+    assert _by_name(detach_mod.detach())["import hook"]["ok"]
+    assert finder not in sys.meta_path
+
+
+def test_failing_component_does_not_stop_the_others(monkeypatch):
+    def broken():
+        raise RuntimeError("hook still referenced")
+
+    calls = []
+    monkeypatch.setattr(
+        detach_mod,
+        "COMPONENTS",
+        [("broken", broken), ("after", lambda: calls.append(1) or "done")],
+    )
+    result = detach_mod.detach()
+    assert result["components"] == [
+        {"name": "broken", "ok": False, "error": "hook still referenced"},
+        {"name": "after", "ok": True, "detail": "done"},
+    ]
+    assert calls == [1]
@@ -0,0 +1,68 @@
+"""On-demand garbage collection (``probing.inspect.gc``) tests."""
+
+from __future__ import annotations
+
+import gc
+import json
+
+import pytest
+
+from probing.inspect import gc as gc_mod
+
+
+class _Node:
+    pass
+
+
+def _make_cycles(n: int) -> None:
+    """Leave ``2 * n`` unreachable objects; automatic collection is paused so
+    none of them are freed before the test collects."""
+    gc.disable()
+    try:
+        for _ in range(n):
+            a, b = _Node(), _Node()
+            a.peer, b.peer = b, a
+    finally:
+        gc.enable()
+
+
+@pytest.fixture
+def recorded(monkeypatch):
+    events: list[dict] = []
+    monkeypatch.setattr(gc_mod, "_record", events.append)
+    return events
+
+
+def test_collect_counts_cyclic_garbage(recorded):
+    gc.collect()
+    _make_cycles(100)
+
+    result = gc_mod.collect()
+
+    assert result["generation"] == 2
+    assert result["collected"] >= 200
+    assert result["unreachable"] >= 200
+    assert result["uncollectable"] == 0
+    assert result["duration_ms"] >= 0
+    assert recorded == [result]
+    json.dumps(result)
+
+
+def test_collect_single_generation(recorded):
+    _make_cycles(10)
+    result = gc_mod.collect(0)
+    assert result["generation"] == 0
+    assert len(recorded) == 1
+
+
+def test_collect_rejects_unknown_generation(recorded):
+    with pytest.raises(ValueError):
+        gc_mod.collect(3)
+    assert recorded == []
+
+
+def test_rss_is_reported_where_proc_is_available(recorded):
+    result = gc_mod.collect()
+    if gc_mod._rss_bytes() is None:
+        pytest.skip("/proc/self/statm unavailable")
+    assert result["rss_before"] > 0 and result["rss_after"] > 0
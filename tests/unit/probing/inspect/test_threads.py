@@ -0,0 +1,112 @@
+"""Thread lifecycle tracking (``python.threads``) tests."""
+
+from __future__ import annotations
+
+import dataclasses
+import threading
+
+import pytest
+
+from probing.inspect import threads as thr
+
+
+@pytest.fixture(autouse=True)
+def _isolate_threads_table():
+    thr.reset_for_tests()
+    try:
+        thr.Threads.drop()
+    except Exception:
+        pass
+    thr.Threads.init_table()
+    yield
+    thr.reset_for_tests()
+
+
+def _rows(n: int = 100) -> list[dict]:
+    fields = [f.name for f in dataclasses.fields(thr.Threads)]
+    return [dict(zip(fields, data)) for _ts, data in thr.Threads.take(n)]
+
+
+def test_records_start_and_end_with_creator():
+    thr.install()
+    child_tid: list[int] = []
+    ancestry: list[list[dict]] = []
+
+    def grandchild():
+        ancestry.append(thr.ancestry(threading.get_native_id()))
+
+    def child():
+        child_tid.append(threading.get_native_id())
+        t = threading.Thread(target=grandchild, name="grandchild")
+        t.start()
+        t.join()
+
+    t = threading.Thread(target=child, name="child")
+    t.start()
+    t.join()
+
+    rows = _rows()
+    by_name = {(r["name"], r["event"]): r for r in rows}
+    assert set(by_name) == {
+        ("child", "start"),
+        ("child", "end"),
+        ("grandchild", "start"),
+        ("grandchild", "end"),
+    }
+    start, end = by_name[("grandchild", "start")], by_name[("grandchild", "end")]
+    assert start["tid"] == end["tid"]
+    assert start["creator_tid"] == child_tid[0]
+    assert start["creator_name"] == "child"
+    assert start["target"].endswith("grandchild")
+    assert start["lifetime_ns"] == 0
+    assert end["lifetime_ns"] == end["time"] - start["time"] >= 0
+    assert by_name[("child", "start")]["creator_tid"] == threading.get_native_id()
+
+    chain = [link["name"] for link in ancestry[0]]
+    assert chain == ["grandchild", "child", threading.current_thread().name]
+
+
+def test_end_row_written_when_run_raises_and_daemon_flag_kept():
+    thr.install()
+
+    class Boom(threading.Thread):
+        def run(self):
+            raise RuntimeError("boom")
+
+    t = Boom(name="boom", daemon=True)
+    previous_hook = threading.excepthook
+    threading.excepthook = lambda _args: None
+    try:
+        t.start()
+        t.join()
+    finally:
+        threading.excepthook = previous_hook
+
+    rows = [r for r in _rows() if r["name"] == "boom"]
+    assert [r["event"] for r in rows] == ["start", "end"]
+    assert all(r["daemon"] == 1 and r["target"].endswith("Boom.run") for r in rows)
+
+
+def test_alive_view_and_uninstall():
+    thr.install()
+    thr.install()
+    release = threading.Event()
+    t = threading.Thread(target=release.wait, name="waiter", daemon=True)
+    t.start()
+    try:
+        alive = {row["name"]: row for row in thr.alive_threads()}
+        assert alive["waiter"]["age_s"] >= 0
+        assert alive["waiter"]["creator_tid"] == threading.get_native_id()
+        assert alive[threading.current_thread().name]["age_s"] == -1.0
+    finally:
+        release.set()
+        t.join()
+
+    thr.uninstall()
+    assert not thr.is_installed()
+    assert "_tracked_start" not in repr(threading.Thread.start)
+    before = len(_rows())
+    t = threading.Thread(target=lambda: None)
+    t.start()
+    t.join()
+    assert len(_rows()) == before
@@ -0,0 +1,133 @@
+"""``gpu.streams`` counters and ``cuda.launches`` span attributes, with the
+launch hook layer mocked out (no CUDA needed)."""
+
+from __future__ import annotations
+
+import dataclasses
+
+import pytest
+
+import probing
+
+
+@pytest.fixture
+def streams(monkeypatch):
+    from probing.profiling import cuda_streams as cs
+
+    try:
+        cs.GpuStream.drop()
+    except Exception:
+        pass
+    cs.GpuStream.init_table()
+    monkeypatch.setattr(cs, "sampled_step", lambda: 12)
+    yield cs
+    cs.stop_stream_capture()
+
+
+def _rows(cs) -> list[dict]:
+    fields = [f.name for f in dataclasses.fields(cs.GpuStream)]
+    return [dict(zip(fields, data)) for _ts, data in cs.GpuStream.take(100)]
+
+
+class FakeStream:
+    def __init__(self, pending: bool) -> None:
+        self.pending = pending
+
+    def query(self) -> bool:
+        return not self.pending
+
+
+class FakeHooks:
+    name = "fake"
+
+    def __init__(self, available: bool = True) -> None:
+        self.available = available
+        self.on_launch = None
+        self.uninstalled = False
+
+    def install(self, on_launch) -> bool:
+        self.on_launch = on_launch
+        return self.available
+
+    def uninstall(self) -> None:
+        self.uninstalled = True
+
+
+def test_counters_aggregate_per_stream_and_interval(streams):
+    cs = streams
+    activity = cs.StreamActivity()
+    compute, copy = FakeStream(pending=True), FakeStream(pending=False)
+    for _ in range(3):
+        activity.record_launch(0, 7, compute)
+    activity.record_launch(0, 9, copy)
+    activity.record_launch(1, 7, None)
+
+    start = activity._last_flush_ns
+    for _ in range(4):
+        activity.sample(5_000_000, cs._stream_busy)
+    rows = activity.flush(start + 40_000_000)
+
+    by_stream = {(r.device_id, r.stream): r for r in rows}
+    assert {k: r.launches for k, r in by_stream.items()} == {
+        (0, 7): 3,
+        (0, 9): 1,
+        (1, 7): 1,
+    }
+    assert by_stream[(0, 7)].busy_us == 20_000
+    assert by_stream[(0, 7)].busy_pct == 50.0
+    assert by_stream[(0, 9)].busy_us == 0
+    assert all(r.step == 12 and r.interval_ms == 40.0 for r in rows)
+    assert len(_rows(cs)) == 3
+
+    # Counters reset; idle streams write no row.
+    compute.pending = False
+    activity.sample(5_000_000, cs._stream_busy)
+    assert activity.flush() == []
+
+
+def test_launches_roll_up_into_enclosing_spans(streams):
+    cs = streams
+    activity = cs.StreamActivity(count_launches=True)
+    with probing.span("step") as outer:
+        activity.record_launch(0, 7)
+        with probing.span("forward") as inner:
+            activity.record_launch(0, 7)
+            activity.record_launch(0, 7)
+            inner_attrs = activity.span_close_attrs(inner)
+        outer_attrs = activity.span_close_attrs(outer)
+    assert inner_attrs == {cs.LAUNCHES_ATTR: 2}
+    assert outer_attrs == {cs.LAUNCHES_ATTR: 3}
+    assert activity.span_close_attrs(outer) == {}
+
+
+def test_capture_installs_first_available_layer(streams):
+    cs = streams
+    config = cs.GpuStreamsConfig(enabled=True, count_launches=True, sample_ms=1000)
+    unavailable, fake = FakeHooks(available=False), FakeHooks()
+    capture = cs.start_stream_capture(config, [lambda: unavailable, lambda: fake])
+    assert capture is not None and capture.hooks is fake
+    assert cs.start_stream_capture(config, [FakeHooks]) is None
+
+    with probing.span("step") as span:
+        fake.on_launch(0, 3, None)
+        fake.on_launch(0, 3, None)
+    attrs = span.get_attributes()
+    assert attrs[cs.LAUNCHES_ATTR] == 2
+
+    cs.stop_stream_capture()
+    assert fake.uninstalled
+    assert [(r["stream"], r["launches"]) for r in _rows(cs)] == [(3, 2)]
+
+
+def test_noop_without_cuda(streams, monkeypatch):
+    cs = streams
+    monkeypatch.setattr(probing.config, "get_str", lambda key: None)
+    assert cs.gpu_streams_config() == cs.GpuStreamsConfig()
+    assert cs.maybe_start_stream_capture() is None
+
+    config = cs.GpuStreamsConfig(enabled=True)
+    assert cs.start_stream_capture(config, [lambda: FakeHooks(available=False)]) is None
+    # The built-in layer declines when torch or CUDA is missing.
+    torch = pytest.importorskip("torch")
+    if not torch.cuda.is_available():
+        assert cs.DispatchLaunchHooks().install(lambda *a: None) is False
@@ -0,0 +1,192 @@
+"""Overhead governor dial-down / restore cycle, with config and signals
+simulated (no sampler, profiler or exporter needed)."""
+
+from __future__ import annotations
+
+import pytest
+
+import probing
+
+
+class FakeConfig:
+    """Config store plus the governed registry kept by the core."""
+
+    def __init__(self, values: dict[str, str]) -> None:
+        self.values = dict(values)
+        self.manual: dict[str, object] = {}
+
+    def get_str(self, key):
+        return self.values.get(key)
+
+    def write(self, key, value):
+        self.values[key] = value
+
+    def remove(self, key):
+        return self.values.pop(key, None)
+
+    def govern(self, key, manual=None):
+        self.manual.setdefault(key, manual)
+
+    def release(self, key):
+        if key not in self.manual:
+            return (False, None)
+        return (True, self.manual.pop(key))
+
+    def governed(self):
+        return dict(self.manual)
+
+
+@pytest.fixture
+def gov(monkeypatch):
+    from probing.profiling import governor
+
+    monkeypatch.setattr(governor, "_gpu_streams_on", lambda: False)
+    return governor
+
+
+@pytest.fixture
+def events(gov, monkeypatch) -> list[dict]:
+    recorded: list[dict] = []
+    monkeypatch.setattr(gov, "_record", recorded.append)
+    return recorded
+
+
+def _use_config(monkeypatch, values: dict[str, str]) -> FakeConfig:
+    config = FakeConfig(values)
+    for name in ("get_str", "write", "remove", "govern", "release", "governed"):
+        monkeypatch.setattr(probing.config, name, getattr(config, name))
+    return config
+
+
+def _high(gov):
+    return gov.OverheadSignals(hook_pct=2.5, sampler_cpu_pct=1.0)
+
+
+def _calm(gov):
+    return gov.OverheadSignals(hook_pct=0.2, sampler_cpu_pct=0.1)
+
+
+def test_dials_down_then_restores_manual_values_exactly(gov, events, monkeypatch):
+    manual = {
+        "probing.pprof.sample_freq": "100",
+        "probing.torch.profiling": "0.2:0.5,tracepy=on",
+    }
+    config = _use_config(monkeypatch, manual)
+    governor = gov.OverheadGovernor(budget_pct=2.0)
+
+    governor.tick(_high(gov))
+    governor.tick(_high(gov))
+    assert governor.level == 2
+    assert config.values["probing.pprof.sample_freq"] == "25"
+    spec = config.values["probing.torch.profiling"]
+    assert spec == "0.2:0.5,tracepy=on,rate=0.05"
+    from probing.profiling.torch_probe import TorchProbeConfig
+
+    parsed = TorchProbeConfig.parse(spec)
+    assert (parsed.rate, parsed.layer_rate, parsed.tracepy) == (0.05, 0.5, True)
+    assert config.governed() == manual
+
+    # Overhead within budget but not calm: nothing moves.
+    governor.tick(gov.OverheadSignals(hook_pct=1.5))
+    for _ in range(gov.RESTORE_TICKS - 1):
+        governor.tick(_calm(gov))
+    assert governor.level == 2
+    governor.tick(_calm(gov))
+    assert governor.level == 1
+    assert config.values["probing.pprof.sample_freq"] == "50"
+
+    for _ in range(gov.RESTORE_TICKS):
+        governor.tick(_calm(gov))
+    assert governor.level == 0
+    assert config.values == manual
+    assert config.governed() == {}
+
+    actions = [(e["action"], e["level"]) for e in events]
+    assert actions == [
+        ("dial_down", 1),
+        ("dial_down", 2),
+        ("restore", 1),
+        ("restore", 0),
+    ]
+    first = events[0]
+    assert first["budget_pct"] == 2.0 and first["overhead_pct"] == 3.5
+    assert {c["key"]: (c["from"], c["to"]) for c in first["changes"]} == {
+        "probing.pprof.sample_freq": ("100", "50"),
+        "probing.torch.profiling": (
+            "0.2:0.5,tracepy=on",
+            "0.2:0.5,tracepy=on,rate=0.1",
+        ),
+    }
+
+
+def test_unset_keys_are_unset_again(gov, events, monkeypatch):
+    monkeypatch.setattr(gov, "_gpu_streams_on", lambda: True)
+    config = _use_config(monkeypatch, {})
+    governor = gov.OverheadGovernor(budget_pct=2.0)
+
+    governor.tick(gov.OverheadSignals(queue_drops=3))
+    assert config.values == {
+        "probing.torch.gpu_streams.interval_ms": "2000",
+        "probing.torch.gpu_streams.sample_ms": "10",
+    }
+    assert config.governed() == {
+        "probing.torch.gpu_streams.interval_ms": None,
+        "probing.torch.gpu_streams.sample_ms": None,
+    }
+    assert events[0]["queue_drops"] == 3
+
+    governor.stop()
+    assert config.values == {} and config.governed() == {}
+    assert events[-1]["action"] == "restore"
+
+
+def test_manual_change_releases_the_key(gov, events, monkeypatch):
+    config = _use_config(monkeypatch, {"probing.pprof.sample_freq": "100"})
+    governor = gov.OverheadGovernor(budget_pct=2.0)
+    governor.tick(_high(gov))
+    assert config.values["probing.pprof.sample_freq"] == "50"
+
+    config.values["probing.pprof.sample_freq"] = "80"
+    governor.tick(gov.OverheadSignals(hook_pct=1.5))
+    assert config.governed() == {}
+    assert events[-1] == {
+        "action": "manual_override",
+        "level": 1,
+        "key": "probing.pprof.sample_freq",
+        "value": "80",
+    }
+
+    for _ in range(gov.RESTORE_TICKS):
+        governor.tick(_calm(gov))
+    assert governor.level == 0
+    assert config.values["probing.pprof.sample_freq"] == "80"
+    assert events[-1]["changes"] == []
+
+
+def test_disabled_features_and_floors_are_left_alone(gov, events, monkeypatch):
+    config = _use_config(
+        monkeypatch,
+        {"probing.torch.profiling": "off", "probing.pprof.sample_freq": "2"},
+    )
+    governor = gov.OverheadGovernor(budget_pct=2.0)
+    for _ in range(gov.MAX_LEVEL + 2):
+        governor.tick(_high(gov))
+    assert governor.level == gov.MAX_LEVEL
+    assert config.values == {
+        "probing.torch.profiling": "off",
+        "probing.pprof.sample_freq": "1",
+    }
+    assert len(events) == gov.MAX_LEVEL
+
+
+def test_budget_from_config_or_env(gov, monkeypatch):
+    _use_config(monkeypatch, {gov.BUDGET_KEY: "on"})
+    assert gov._budget_pct() == gov.DEFAULT_BUDGET_PCT
+    _use_config(monkeypatch, {gov.BUDGET_KEY: "0"})
+    assert gov._budget_pct() is None
+    _use_config(monkeypatch, {})
+    monkeypatch.setenv(gov.BUDGET_ENV, "3.5")
+    assert gov._budget_pct() == 3.5
+    monkeypatch.delenv(gov.BUDGET_ENV)
+    assert gov._budget_pct() is None
+    assert gov.maybe_start_governor() is None
@@ -0,0 +1,74 @@
+"""``traceparent`` propagation: outgoing header and recorded server spans."""
+
+from __future__ import annotations
+
+import dataclasses
+import json
+
+import pytest
+
+import probing
+
+
+@pytest.fixture(autouse=True)
+def _isolate_trace_table(monkeypatch):
+    from probing.tracing import TraceEvent, bind_table, reset_backends
+
+    monkeypatch.delenv("PROBING_SPAN_BACKENDS", raising=False)
+    try:
+        TraceEvent.drop()
+    except Exception:
+        pass
+    TraceEvent.init_table()
+    reset_backends(clear_registered=True)
+    bind_table(TraceEvent)
+    yield
+    reset_backends(clear_registered=True)
+
+
+def _trace_rows(n: int = 50) -> list[dict]:
+    from probing.tracing import TraceEvent
+
+    fields = [f.name for f in dataclasses.fields(TraceEvent)]
+    return [dict(zip(fields, data)) for _ts, data in TraceEvent.take(n)]
+
+
+def test_traceparent_names_the_current_span():
+    from probing.tracing import traceparent
+
+    assert traceparent() is None
+    with probing.span("client") as span:
+        header = traceparent()
+    version, trace_id, parent_id, flags = header.split("-")
+    assert (version, flags) == ("00", "01")
+    assert int(trace_id, 16) == span.trace_id
+    assert int(parent_id, 16) == span.span_id
+
+
+def test_remote_span_lands_in_trace_event():
+    from probing.tracing.propagation import _record_remote_span
+
+    _record_remote_span(
+        {
+            "trace_id": 41,
+            "span_id": 43,
+            "parent_id": 42,
+            "name": "GET /apis/overview",
+            "phase": None,
+            "thread_id": 7,
+            "thread_name": "tokio-runtime-worker",
+            "start_timestamp": 1_000,
+            "end_timestamp": 3_000,
+            "cpu_time_ns": None,
+            "ctx_switches": None,
+            "attributes": json.dumps({"http.status_code": 200}),
+        }
+    )
+
+    rows = _trace_rows()
+    start = next(r for r in rows if r["record_type"] == "span_start")
+    assert (start["trace_id"], start["span_id"], start["parent_id"]) == (41, 43, 42)
+    assert start["name"] == "GET /apis/overview"
+    assert json.loads(start["attributes"]) == {"http.status_code": 200}
+    end = next(r for r in rows if r["record_type"] == "span_end")
+    assert (end["span_id"], end["time"]) == (43, 3_000)
@@ -222,6 +222,55 @@ def test_logger_backend_with_memtable(monkeypatch, capsys):
     )
 
 
+def test_event_keyword_fields_keep_types():
+    import json
+
+    class FakeScalar:
+        def item(self):
+            return 7
+
+    with probing.span("typed") as span:
+        probing.event("prefill", tokens=4096, cache_hit=True, ratio=0.5)
+        span.event("decode", attributes=[{"step": FakeScalar()}], loss=float("nan"))
+
+    events = {
+        r["name"]: json.loads(r["event_attributes"])
+        for r in _trace_rows()
+        if r.get("record_type") == "event"
+    }
+    assert events["prefill"] == {"tokens": 4096, "cache_hit": True, "ratio": 0.5}
+    assert type(events["prefill"]["cache_hit"]) is bool
+    assert events["decode"] == {"step": 7, "loss": "nan"}
+
+
+def test_exception_is_recorded_and_propagates():
+    import json
+
+    error = ValueError("bad batch")
+
+    @probing.span("failing_step")
+    def failing_step():
+        raise error
+
+    with pytest.raises(ValueError) as raised:
+        failing_step()
+    assert raised.value is error
+
+    with pytest.raises(KeyError):
+        with probing.span("lookup") as span:
+            raise KeyError("missing")
+    assert span.status == "Error"
+
+    events = [
+        json.loads(r["event_attributes"])
+        for r in _trace_rows()
+        if r.get("record_type") == "event" and r.get("name") == "exception"
+    ]
+    assert [e["exception.type"] for e in events] == ["ValueError", "KeyError"]
+    assert events[0]["exception.message"] == "bad batch"
+    assert "raise error" in events[0]["exception.stacktrace"]
+
+
 def test_logger_backend_only(monkeypatch, capsys):
     import logging
 
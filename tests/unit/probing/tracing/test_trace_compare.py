@@ -0,0 +1,101 @@
+"""Window-over-window span regression comparison (pure math, no engine)."""
+
+from __future__ import annotations
+
+import random
+
+from probing.tracing import compare
+
+
+def _rows(name: str, center: float, jitter: float, n: int, seed: int):
+    rng = random.Random(seed)
+    return [(name, center + rng.uniform(-jitter, jitter)) for _ in range(n)]
+
+
+def test_percentile_interpolates():
+    assert compare.percentile([1.0, 2.0, 3.0, 4.0], 0.5) == 2.5
+    assert compare.percentile([], 0.5) == 0.0
+    assert compare.percentile(list(map(float, range(101))), 0.95) == 95.0
+
+
+def test_summarize_groups_by_name():
+    stats = compare.summarize([("a", 10), ("a", 20), ("b", 5), ("a", None)])
+    assert stats["a"].count == 2
+    assert stats["a"].p50_us == 15.0
+    assert stats["b"].p95_us == 5.0
+    assert stats["a"].mean_cpu_us is None
+
+
+def test_summarize_averages_sampled_cpu_time():
+    nan = float("nan")
+    stats = compare.summarize(
+        [
+            ("step", 100, 40, 2),
+            ("step", 300, 20, 6),
+            ("step", 200, None, None),
+            ("step", 250, nan, nan),
+        ]
+    )
+    assert stats["step"].count == 4
+    assert stats["step"].mean_cpu_us == 30.0
+    assert stats["step"].mean_ctx_switches == 4.0
+
+
+def test_shifted_distribution_is_flagged_as_regression():
+    baseline = compare.summarize(_rows("forward", 1000, 50, 200, seed=1))
+    current = compare.summarize(_rows("forward", 1300, 50, 200, seed=2))
+
+    (delta,) = compare.compare(baseline, current)
+
+    assert delta.verdict == "regression"
+    assert delta.significant
+    assert 250 < delta.delta_p50_us < 350
+    assert 25 < delta.delta_p50_pct < 35
+
+
+def test_noise_within_spread_is_not_significant():
+    baseline = compare.summarize(_rows("backward", 1000, 400, 200, seed=3))
+    current = compare.summarize(_rows("backward", 1010, 400, 200, seed=4))
+
+    (delta,) = compare.compare(baseline, current)
+
+    assert not delta.significant
+    assert delta.verdict == "unchanged"
+
+
+def test_too_few_samples_never_flagged():
+    baseline = compare.summarize([("opt", 10.0)] * 2)
+    current = compare.summarize([("opt", 100.0)] * 2)
+
+    (delta,) = compare.compare(baseline, current)
+
+    assert delta.delta_p50_us == 90.0
+    assert not delta.significant
+
+
+def test_sorted_by_absolute_change_then_unmatched():
+    baseline = compare.summarize(
+        _rows("fwd", 100, 1, 50, seed=5)
+        + _rows("bwd", 200, 1, 50, seed=6)
+        + _rows("gone", 10, 1, 50, seed=7)
+    )
+    current = compare.summarize(
+        _rows("fwd", 120, 1, 50, seed=8)
+        + _rows("bwd", 150, 1, 50, seed=9)
+        + _rows("new", 10, 1, 50, seed=10)
+    )
+
+    deltas = compare.compare(baseline, current)
+
+    # The 50 µs speedup outranks the 20 µs slowdown.
+    assert [d.name for d in deltas] == ["bwd", "fwd", "gone", "new"]
+    assert deltas[0].verdict == "improvement"
+    assert deltas[1].verdict == "regression"
+    assert {deltas[2].verdict, deltas[3].verdict} == {"gone", "new"}
+
+
+def test_window_sql_bounds_start_time():
+    sql = compare.window_sql(1_000, 2_000)
+    assert "start_us >= 1000" in sql
+    assert "start_us < 2000" in sql
+    assert "python.trace_event" in sql
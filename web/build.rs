@@ -0,0 +1,36 @@
+//! Expose the probing release version (`[workspace.package] version` in the
+//! repository root manifest) as `PROBING_VERSION`; this crate is versioned
+//! separately and is not part of that workspace.
+
+use std::path::Path;
+
+fn main() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("../Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest.display());
+    println!("cargo:rerun-if-env-changed=PROBING_VERSION");
+
+    let version = std::env::var("PROBING_VERSION")
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string(&manifest)
+                .ok()
+                .and_then(|text| workspace_version(&text))
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=PROBING_VERSION={version}");
+}
+
+fn workspace_version(manifest: &str) -> Option<String> {
+    let mut in_workspace_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_workspace_package = line == "[workspace.package]";
+        } else if in_workspace_package {
+            if let Some(value) = line.strip_prefix("version") {
+                let value = value.trim_start().strip_prefix('=')?.trim();
+                return Some(value.trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
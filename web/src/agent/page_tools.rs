@@ -25,6 +25,7 @@ fn ele_str(ele: &Ele) -> String {
         Ele::Text(x) => x.clone(),
         Ele::Url(x) => x.clone(),
         Ele::DataTime(x) => x.to_string(),
+        Ele::DateTime(_) => ele.to_string(),
     }
 }
 
@@ -9,6 +9,21 @@ impl ApiClient {
         self.execute_query_at_path("/query", query).await
     }
 
+    /// Execute SQL query under `id`, which [`Self::cancel_query`] can stop
+    /// while it runs; a cancelled query fails with [`AppError::Cancelled`].
+    pub async fn execute_query_with_id(&self, id: &str, query: &str) -> Result<DataFrame> {
+        self.execute_query_at_path(&format!("/query?id={id}"), query)
+            .await
+    }
+
+    /// Cancel the query running under `id`; an error when none is (it
+    /// already finished).
+    pub async fn cancel_query(&self, id: &str) -> Result<()> {
+        self.post_request_with_body(&format!("/query/cancel?id={id}"), String::new())
+            .await
+            .map(|_| ())
+    }
+
     /// Execute SQL query against another local probing process via the current server.
     pub async fn execute_query_local_pid(&self, pid: i32, query: &str) -> Result<DataFrame> {
         self.execute_query_at_path(&format!("/apis/query/local-pid?pid={pid}"), query)
@@ -35,6 +50,10 @@ impl ApiClient {
                 cols: vec![],
                 size: 0,
             }),
+            QueryDataFormat::Error(QueryError {
+                code: ErrorCode::Cancelled,
+                ..
+            }) => Err(AppError::Cancelled),
             QueryDataFormat::Error(err) => Err(AppError::Api(err.message)),
             QueryDataFormat::TimeSeries(_) => {
                 Err(AppError::Api("TimeSeries format not supported".to_string()))
@@ -42,6 +61,17 @@ impl ApiClient {
         }
     }
 
+    /// Query for plotting: the server downsamples to `request.points` rows,
+    /// keeping per-bucket extrema unless LTTB is requested.
+    pub async fn chart_query(&self, request: &ChartQueryRequest) -> Result<ChartQueryResponse> {
+        let body = serde_json::to_string(request)
+            .map_err(|e| AppError::Api(format!("Failed to serialize request: {}", e)))?;
+        let response = self
+            .post_request_with_body("/apis/chart_query", body)
+            .await?;
+        Self::parse_json(&response)
+    }
+
     /// Preview query (with fallback): prioritize getting latest 10 rows by first column descending, fallback to limit 10 on failure
     pub async fn execute_preview_last10(&self, table: &str) -> Result<DataFrame> {
         let try_sqls = [
@@ -47,6 +47,12 @@ impl ApiClient {
         Ok(all)
     }
 
+    /// Heartbeat and polled CPU / RSS / GPU history of every registered node.
+    pub async fn get_node_metrics(&self) -> Result<NodeMetricsResponse> {
+        let response = self.get_request("/apis/nodes/metrics").await?;
+        Self::parse_json(&response)
+    }
+
     /// On-demand SQL fan-out across cluster nodes (`cluster=true`) or local only.
     pub async fn cluster_query(&self, expr: &str, cluster: bool) -> Result<ClusterQueryResponse> {
         let body = serde_json::to_string(&ClusterQueryRequest {
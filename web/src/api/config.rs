@@ -0,0 +1,76 @@
+use super::ApiClient;
+use crate::utils::error::{AppError, Result};
+
+/// Runtime `probing.*` options: read via `GET /config/{key}`, write via `SET`.
+impl ApiClient {
+    /// Current value of `key`, or `None` when the server has no such option set.
+    pub async fn get_config_option(&self, key: &str) -> Result<Option<String>> {
+        validate_config_key(key)?;
+        let url = Self::build_url(&format!("/config/{key}"))?;
+        let response = reqwest::get(&url).await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let message = Some(body).filter(|b| !b.trim().is_empty());
+            return Err(Self::http_error(status, message));
+        }
+        Ok(Some(body))
+    }
+
+    /// `SET key=value`; an empty `value` resets the option. Errors carry the
+    /// server's rejection message.
+    pub async fn set_config_option(&self, key: &str, value: &str) -> Result<()> {
+        let expr = config_set_expr(key, value)?;
+        self.execute_query(&expr).await.map(|_| ())
+    }
+}
+
+fn validate_config_key(key: &str) -> Result<()> {
+    let valid = key.starts_with("probing.")
+        && !key.ends_with('.')
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::Api(format!("invalid config key {key:?}")))
+    }
+}
+
+fn config_set_expr(key: &str, value: &str) -> Result<String> {
+    validate_config_key(key)?;
+    let value = value.trim();
+    if value.contains([';', '\'', '"', '\n', '\r']) {
+        return Err(AppError::Api(format!("invalid value for {key}: {value:?}")));
+    }
+    Ok(format!("set {key}={value};"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_set_and_reset_statements() {
+        assert_eq!(
+            config_set_expr("probing.pprof.sample_freq", "100").unwrap(),
+            "set probing.pprof.sample_freq=100;"
+        );
+        assert_eq!(
+            config_set_expr("probing.torch.profiling", "").unwrap(),
+            "set probing.torch.profiling=;"
+        );
+    }
+
+    #[test]
+    fn rejects_injection_and_foreign_keys() {
+        assert!(config_set_expr("probing.torch.profiling", "on; set x=1").is_err());
+        assert!(config_set_expr("datafusion.execution.batch_size", "1").is_err());
+        assert!(config_set_expr("probing.a b", "1").is_err());
+        assert!(validate_config_key("probing.").is_err());
+    }
+}
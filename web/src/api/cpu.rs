@@ -1,6 +1,6 @@
 use super::ApiClient;
 use crate::utils::error::{AppError, Result};
-use probing_proto::prelude::{DataFrame, Ele};
+use probing_proto::prelude::{ChartQueryRequest, DataFrame, Ele};
 
 /// Latest process-level CPU snapshot from `cpu.utilization`.
 #[derive(Clone, Debug, Default)]
@@ -20,6 +20,8 @@ pub struct CpuSnapshot {
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct CpuHistorySample {
+    /// Sample time (µs since epoch).
+    pub ts_us: i64,
     pub user_ms: f32,
     pub sys_ms: f32,
     pub total_ms: f32,
@@ -68,15 +70,21 @@ impl ApiClient {
         }
     }
 
-    pub async fn fetch_cpu_history(&self, limit: usize) -> Result<Vec<CpuHistorySample>> {
-        match self
-            .execute_query(&format!(
-                "SELECT delta_user_ns, delta_sys_ns, delta_total_ns \
-                 FROM cpu.utilization WHERE scope = 'process' ORDER BY ts DESC LIMIT {limit}"
-            ))
-            .await
-        {
-            Ok(df) => Ok(parse_cpu_history(&df)),
+    /// Last `window` process samples, reduced server-side to at most `points`
+    /// (per-bucket min/max, so short CPU spikes stay visible).
+    pub async fn fetch_cpu_history(
+        &self,
+        window: usize,
+        points: usize,
+    ) -> Result<Vec<CpuHistorySample>> {
+        let mut request = ChartQueryRequest::expr(format!(
+            "SELECT ts, delta_user_ns, delta_sys_ns, delta_total_ns \
+             FROM cpu.utilization WHERE scope = 'process' ORDER BY ts DESC LIMIT {window}"
+        ))
+        .with_points(points);
+        request.x = Some("ts".to_string());
+        match self.chart_query(&request).await {
+            Ok(resp) => Ok(parse_cpu_history(&resp.dataframe)),
             Err(e) if is_cpu_table_missing(&e) => Ok(vec![]),
             Err(e) => Err(e),
         }
@@ -190,6 +198,9 @@ fn parse_cpu_history(df: &DataFrame) -> Vec<CpuHistorySample> {
     let idx = |name| col_index(df, name);
     let mut out: Vec<CpuHistorySample> = (0..rows)
         .map(|r| CpuHistorySample {
+            ts_us: idx("ts")
+                .and_then(|c| cell(df, r, c).map(ele_i64))
+                .unwrap_or(0),
             user_ms: idx("delta_user_ns")
                 .and_then(|c| cell(df, r, c).map(ele_i64))
                 .map(ns_to_ms)
@@ -204,7 +215,7 @@ fn parse_cpu_history(df: &DataFrame) -> Vec<CpuHistorySample> {
                 .unwrap_or(0.0),
         })
         .collect();
-    out.reverse();
+    out.sort_by_key(|s| s.ts_us);
     out
 }
 
@@ -288,6 +299,23 @@ mod tests {
         assert!(is_cpu_table_missing(&err));
     }
 
+    #[test]
+    fn cpu_history_is_oldest_first() {
+        use probing_proto::prelude::Seq;
+
+        let df = DataFrame::new(
+            vec!["ts".into(), "delta_total_ns".into()],
+            vec![
+                Seq::SeqI64(vec![30, 10, 20]),
+                Seq::SeqI64(vec![3_000_000, 1_000_000, 2_000_000]),
+            ],
+        );
+        let samples = parse_cpu_history(&df);
+        let ts: Vec<i64> = samples.iter().map(|s| s.ts_us).collect();
+        assert_eq!(ts, vec![10, 20, 30]);
+        assert_eq!(samples[2].total_ms, 3.0);
+    }
+
     #[test]
     fn thread_display_name_prefers_comm() {
         assert_eq!(
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+use super::ApiClient;
+use crate::utils::error::Result;
+
+/// One readiness stage from `GET /healthz` (`http`, `engine`, …).
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct HealthStage {
+    pub name: String,
+    /// `ok`, `starting` or `failed`.
+    pub status: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct HealthReport {
+    /// `ok` when every stage is ok, `degraded` otherwise.
+    pub status: String,
+    #[serde(default)]
+    pub stages: Vec<HealthStage>,
+}
+
+/// `GET /apis/snapshot`: whether the server replays an archive (`probing
+/// serve-snapshot`) rather than watching a live process.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct SnapshotStatus {
+    pub snapshot: bool,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Capture wall clock (Unix ns).
+    #[serde(default)]
+    pub captured_ns: Option<i64>,
+}
+
+impl ApiClient {
+    /// Probe `/healthz`; returns the report and the round-trip latency in ms.
+    pub async fn fetch_health(&self) -> Result<(HealthReport, f64)> {
+        let started = js_sys::Date::now();
+        let response = self.get_request("/healthz").await?;
+        let latency_ms = js_sys::Date::now() - started;
+        Ok((Self::parse_json(&response)?, latency_ms))
+    }
+
+    pub async fn fetch_snapshot(&self) -> Result<SnapshotStatus> {
+        let response = self.get_request("/apis/snapshot").await?;
+        Self::parse_json(&response)
+    }
+}
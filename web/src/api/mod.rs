@@ -40,12 +40,10 @@ impl ApiClient {
         let body = response.text().await?;
 
         if !status.is_success() {
-            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&body) {
-                if let Some(err) = value.get("error").and_then(|v| v.as_str()) {
-                    return Err(AppError::Api(err.to_string()));
-                }
-            }
-            return Err(AppError::Api(format!("HTTP error: {status}")));
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|value| value.get("error")?.as_str().map(str::to_string));
+            return Err(Self::http_error(status, message));
         }
 
         Ok(body)
@@ -63,12 +61,26 @@ impl ApiClient {
             .await?;
 
         if !response.status().is_success() {
-            return Err(AppError::Api(format!("HTTP error: {}", response.status())));
+            return Err(Self::http_error(response.status(), None));
         }
 
         Ok(response.text().await?)
     }
 
+    /// [`AppError::Http`] for `status`, with the status reason when the
+    /// server sent no message.
+    fn http_error(status: reqwest::StatusCode, message: Option<String>) -> AppError {
+        AppError::Http {
+            status: status.as_u16(),
+            message: message.unwrap_or_else(|| {
+                status
+                    .canonical_reason()
+                    .unwrap_or("request failed")
+                    .to_string()
+            }),
+        }
+    }
+
     /// Send GET request (public wrapper for agent / extensions).
     pub async fn get_raw(&self, path: &str) -> Result<String> {
         self.get_request(path).await
@@ -84,10 +96,12 @@ impl ApiClient {
 // Export all API modules
 mod analytics;
 mod cluster;
+mod config;
 mod cpu;
 mod dashboard;
 mod files;
 mod gpu;
+mod health;
 mod overhead;
 mod profiling;
 mod pulsing;
@@ -96,6 +110,7 @@ mod repl;
 mod rl;
 mod skills;
 mod stack;
+mod threads;
 mod trace;
 mod traces;
 mod training;
@@ -111,6 +126,8 @@ pub use dashboard::*;
 #[allow(unused_imports)]
 pub use gpu::*;
 #[allow(unused_imports)]
+pub use health::*;
+#[allow(unused_imports)]
 pub use overhead::*;
 #[allow(unused_imports)]
 pub use profiling::*;
@@ -127,6 +144,8 @@ pub use skills::*;
 #[allow(unused_imports)]
 pub use stack::*;
 #[allow(unused_imports)]
+pub use threads::*;
+#[allow(unused_imports)]
 pub use trace::*;
 #[allow(unused_imports)]
 pub use traces::*;
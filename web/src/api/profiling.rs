@@ -57,4 +57,38 @@ impl ApiClient {
         };
         self.get_request(&path).await
     }
+
+    /// Span self-time flamegraph JSON, of one trace when `trace_id` is set.
+    pub async fn get_span_flamegraph_json(&self, trace_id: Option<i64>) -> Result<String> {
+        let path = match trace_id {
+            Some(id) => format!("/apis/trace/flamegraph/json?trace_id={id}"),
+            None => "/apis/trace/flamegraph/json".to_string(),
+        };
+        self.get_request(&path).await
+    }
+
+    /// Differential flamegraph SVG of two folded-stack profiles (red grew,
+    /// blue shrank), sized by `current`.
+    pub async fn get_profile_diff_svg(
+        &self,
+        baseline: Vec<String>,
+        current: Vec<String>,
+        normalize: bool,
+        title: Option<String>,
+    ) -> Result<String> {
+        let body = serde_json::json!({
+            "baseline": baseline,
+            "current": current,
+            "normalize": normalize,
+            "title": title,
+        });
+        self.post_request_with_body("/apis/profile/diff", body.to_string())
+            .await
+    }
+
+    /// Absolute URL of the CPU samples as a gzip-compressed pprof
+    /// `profile.proto` (`go tool pprof`, Speedscope).
+    pub fn pprof_download_url() -> Result<String> {
+        Self::build_url("/apis/pprof/profile.pb.gz")
+    }
 }
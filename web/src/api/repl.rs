@@ -28,6 +28,16 @@ pub struct EvalResponse {
     pub traceback: Vec<String>,
 }
 
+/// Tab completions from `pythonext/complete`: each match replaces the
+/// characters `start..end` (code points) of the completed code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    #[serde(default)]
+    pub matches: Vec<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
 /// REPL / Magic command API
 impl ApiClient {
     /// Get magic commands as structured list for UI quick actions.
@@ -57,4 +67,27 @@ impl ApiClient {
             traceback: vec![],
         })
     }
+
+    /// Completions for `code` at character offset `cursor`.
+    pub async fn complete(&self, code: &str, cursor: usize) -> Result<Completion> {
+        let path = format!(
+            "/apis/pythonext/complete?code={}&cursor={cursor}",
+            urlencoding::encode(code)
+        );
+        let text = self.get_request(&path).await?;
+        serde_json::from_str(&text)
+            .map_err(|e| crate::utils::error::AppError::Api(format!("JSON parse error: {e}")))
+    }
+
+    /// Whether the target serves the REPL routes: `false` when they are
+    /// gated by auth (401/403) or the Python extension is missing (404).
+    pub async fn repl_available(&self) -> bool {
+        let Ok(url) = Self::build_url("/apis/pythonext/complete?code=") else {
+            return false;
+        };
+        match reqwest::get(&url).await {
+            Ok(response) => !matches!(response.status().as_u16(), 401 | 403 | 404),
+            Err(_) => false,
+        }
+    }
 }
@@ -155,6 +155,7 @@ fn trace_events_query(limit: Option<usize>) -> String {
                 attributes,
                 event_attributes
             FROM python.trace_event
+            WHERE record_type <> 'span'
             ORDER BY time DESC
             {}
         "#,
@@ -214,7 +215,7 @@ fn trace_events_for_span_ids_query(span_ids: &[i64]) -> String {
                 attributes,
                 event_attributes
             FROM python.trace_event
-            WHERE span_id IN ({span_ids})
+            WHERE span_id IN ({span_ids}) AND record_type <> 'span'
             ORDER BY time DESC
         "#
     )
@@ -284,7 +285,7 @@ fn trace_events_from_df(df: DataFrame) -> Vec<TraceEvent> {
         let get_i64 = |idx: usize| -> i64 {
             match df.cols.get(idx).map(|col| col.get(row_idx)) {
                 Some(Ele::I32(x)) => x as i64,
-                Some(Ele::I64(x)) => x,
+                Some(Ele::I64(x) | Ele::DateTime(x)) => x,
                 Some(Ele::F32(x)) => x as i64,
                 Some(Ele::F64(x)) => x as i64,
                 Some(Ele::Text(s)) => s.parse().unwrap_or(0),
@@ -437,6 +438,9 @@ fn build_span_tree_from_events(mut events: Vec<TraceEvent>) -> Vec<SpanInfo> {
                 phase: event.phase.clone(),
                 location: event.location.clone(),
                 attributes: event.attributes.clone(),
+                cpu_time_ns: None,
+                ctx_switches: None,
+                links: None,
                 children: Vec::new(),
                 events: Vec::new(),
             };
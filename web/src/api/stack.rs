@@ -1,6 +1,16 @@
 use super::ApiClient;
-use crate::utils::error::Result;
+use crate::utils::error::{AppError, Result};
 use probing_proto::prelude::*;
+use serde::Deserialize;
+
+/// `GET /apis/pythonext/callstack?group=thread` response.
+#[derive(Debug, Deserialize)]
+struct ThreadStacksResponse {
+    #[serde(default)]
+    threads: Vec<ThreadStack>,
+    #[serde(default)]
+    error: Option<String>,
+}
 
 /// Activity analysis API
 impl ApiClient {
@@ -24,6 +34,18 @@ impl ApiClient {
         Self::parse_json(&response)
     }
 
+    /// Stacks of every Python thread, main thread first; Python frames only
+    /// unless `native`. Threads that could not be captured carry an error.
+    pub async fn get_thread_stacks(&self, native: bool) -> Result<Vec<ThreadStack>> {
+        let path = format!("/apis/pythonext/callstack?group=thread&native={native}");
+        let response = self.get_request(&path).await?;
+        let body: ThreadStacksResponse = Self::parse_json(&response)?;
+        match body.error {
+            Some(err) if body.threads.is_empty() => Err(AppError::Api(err)),
+            _ => Ok(body.threads),
+        }
+    }
+
     /// Distributed CPU stack flamegraph (`mode`: `mixed` | `py`).
     pub async fn get_distributed_stack_flamegraph_json(
         &self,
@@ -0,0 +1,238 @@
+use super::ApiClient;
+use crate::utils::error::{AppError, Result};
+use probing_proto::prelude::{DataFrame, Ele};
+
+/// A live Python thread from `probing.inspect.threads.alive_threads()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PythonThreadRow {
+    pub tid: i64,
+    pub name: String,
+    pub target: String,
+    pub daemon: bool,
+    pub creator_tid: i64,
+    pub creator_name: String,
+    /// Seconds since `run` began; `None` for threads started before tracking.
+    pub age_s: Option<f64>,
+}
+
+/// One link of a thread's creator chain; depth 0 is the thread itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadAncestor {
+    pub depth: i64,
+    pub tid: i64,
+    pub name: String,
+    pub target: String,
+    pub alive: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ThreadCountSample {
+    /// Event time (µs since epoch).
+    pub ts_us: i64,
+    pub alive: i64,
+}
+
+fn is_threads_table_missing(err: &AppError) -> bool {
+    matches!(err, AppError::Api(msg)
+        if msg.contains("threads") && msg.contains("not found"))
+}
+
+impl ApiClient {
+    pub async fn fetch_python_threads(&self) -> Result<Vec<PythonThreadRow>> {
+        let df = self
+            .execute_query("SELECT * FROM python.`probing.inspect.threads.alive_threads()`")
+            .await?;
+        Ok(parse_python_threads(&df))
+    }
+
+    pub async fn fetch_thread_ancestry(&self, tid: i64) -> Result<Vec<ThreadAncestor>> {
+        let df = self
+            .execute_query(&format!(
+                "SELECT * FROM python.`probing.inspect.threads.ancestry({tid})`"
+            ))
+            .await?;
+        Ok(parse_thread_ancestry(&df))
+    }
+
+    /// Alive thread count after each of the last `window` lifecycle events,
+    /// anchored so the newest point equals `alive_now`.
+    pub async fn fetch_thread_count_history(
+        &self,
+        window: usize,
+        alive_now: i64,
+    ) -> Result<Vec<ThreadCountSample>> {
+        match self
+            .execute_query(&format!(
+                "SELECT time, event FROM python.threads ORDER BY time DESC LIMIT {window}"
+            ))
+            .await
+        {
+            Ok(df) => Ok(thread_count_series(&df, alive_now)),
+            Err(e) if is_threads_table_missing(&e) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn col(df: &DataFrame, name: &str) -> Option<usize> {
+    df.names.iter().position(|n| n == name)
+}
+
+fn rows(df: &DataFrame) -> usize {
+    df.cols.first().map(|c| c.len()).unwrap_or(0)
+}
+
+fn int_at(df: &DataFrame, name: &str, row: usize) -> Option<i64> {
+    match df.cols.get(col(df, name)?)?.get(row) {
+        Ele::I64(v) => Some(v),
+        Ele::I32(v) => Some(v as i64),
+        Ele::F64(v) => Some(v as i64),
+        _ => None,
+    }
+}
+
+fn float_at(df: &DataFrame, name: &str, row: usize) -> Option<f64> {
+    match df.cols.get(col(df, name)?)?.get(row) {
+        Ele::F64(v) => Some(v),
+        Ele::F32(v) => Some(v as f64),
+        Ele::I64(v) => Some(v as f64),
+        Ele::I32(v) => Some(v as f64),
+        _ => None,
+    }
+}
+
+fn text_at(df: &DataFrame, name: &str, row: usize) -> String {
+    match col(df, name)
+        .and_then(|c| df.cols.get(c))
+        .map(|c| c.get(row))
+    {
+        Some(Ele::Text(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn parse_python_threads(df: &DataFrame) -> Vec<PythonThreadRow> {
+    let mut out: Vec<PythonThreadRow> = (0..rows(df))
+        .filter_map(|r| {
+            Some(PythonThreadRow {
+                tid: int_at(df, "tid", r)?,
+                name: text_at(df, "name", r),
+                target: text_at(df, "target", r),
+                daemon: int_at(df, "daemon", r).unwrap_or(0) != 0,
+                creator_tid: int_at(df, "creator_tid", r).unwrap_or(-1),
+                creator_name: text_at(df, "creator_name", r),
+                age_s: float_at(df, "age_s", r).filter(|age| *age >= 0.0),
+            })
+        })
+        .collect();
+    // Oldest tracked threads first; untracked (pre-existing) ones last.
+    out.sort_by(|a, b| b.age_s.unwrap_or(-1.0).total_cmp(&a.age_s.unwrap_or(-1.0)));
+    out
+}
+
+fn parse_thread_ancestry(df: &DataFrame) -> Vec<ThreadAncestor> {
+    let mut out: Vec<ThreadAncestor> = (0..rows(df))
+        .filter_map(|r| {
+            Some(ThreadAncestor {
+                depth: int_at(df, "depth", r)?,
+                tid: int_at(df, "tid", r)?,
+                name: text_at(df, "name", r),
+                target: text_at(df, "target", r),
+                alive: int_at(df, "alive", r).unwrap_or(0) != 0,
+            })
+        })
+        .collect();
+    out.sort_by_key(|a| a.depth);
+    out
+}
+
+/// Walk newest-first lifecycle events backwards from the current count and
+/// return the series oldest-first.
+fn thread_count_series(df: &DataFrame, alive_now: i64) -> Vec<ThreadCountSample> {
+    let mut events: Vec<(i64, i64)> = (0..rows(df))
+        .filter_map(|r| {
+            let delta = match text_at(df, "event", r).as_str() {
+                "start" => 1,
+                "end" => -1,
+                _ => return None,
+            };
+            Some((int_at(df, "time", r)?, delta))
+        })
+        .collect();
+    events.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    let mut alive = alive_now;
+    let mut out: Vec<ThreadCountSample> = events
+        .into_iter()
+        .map(|(time_ns, delta)| {
+            let sample = ThreadCountSample {
+                ts_us: time_ns / 1_000,
+                alive,
+            };
+            alive -= delta;
+            sample
+        })
+        .collect();
+    out.reverse();
+    out
+}
+
+pub fn format_thread_age(age_s: Option<f64>) -> String {
+    match age_s {
+        None => "—".to_string(),
+        Some(s) if s >= 3600.0 => format!("{:.1} h", s / 3600.0),
+        Some(s) if s >= 60.0 => format!("{:.1} min", s / 60.0),
+        Some(s) => format!("{s:.1} s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    #[test]
+    fn count_series_ends_at_current_alive_count() {
+        let df = DataFrame::new(
+            vec!["time".into(), "event".into()],
+            vec![
+                Seq::SeqI64(vec![4_000, 3_000, 2_000, 1_000]),
+                Seq::SeqText(vec![
+                    "end".into(),
+                    "start".into(),
+                    "start".into(),
+                    "start".into(),
+                ]),
+            ],
+        );
+        let series = thread_count_series(&df, 3);
+        let alive: Vec<i64> = series.iter().map(|s| s.alive).collect();
+        assert_eq!(alive, vec![2, 3, 4, 3]);
+        assert_eq!(series[0].ts_us, 1);
+    }
+
+    #[test]
+    fn python_threads_sort_oldest_tracked_first() {
+        let df = DataFrame::new(
+            vec!["tid".into(), "name".into(), "age_s".into()],
+            vec![
+                Seq::SeqI64(vec![1, 2, 3]),
+                Seq::SeqText(vec!["main".into(), "young".into(), "old".into()]),
+                Seq::SeqF64(vec![-1.0, 2.5, 90.0]),
+            ],
+        );
+        let names: Vec<String> = parse_python_threads(&df)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["old", "young", "main"]);
+        assert_eq!(format_thread_age(Some(90.0)), "1.5 min");
+        assert_eq!(format_thread_age(None), "—");
+    }
+
+    #[test]
+    fn detects_missing_threads_table_error() {
+        let err = AppError::Api("Error during planning: table 'python.threads' not found".into());
+        assert!(is_threads_table_missing(&err));
+    }
+}
@@ -1,6 +1,6 @@
 use super::ApiClient;
 use crate::utils::error::Result;
-use probing_proto::prelude::DataFrame;
+use probing_proto::prelude::{DataFrame, Ele};
 use serde::{Deserialize, Serialize};
 
 /// Trace API response structure
@@ -25,6 +25,38 @@ pub struct VariableRecord {
     pub timestamp: f64,
 }
 
+/// Row filters for [`ApiClient::get_variable_records`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariableRecordsQuery {
+    pub function: Option<String>,
+    pub variable: Option<String>,
+    /// Only records at or after this time (µs since epoch).
+    pub since_us: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl VariableRecordsQuery {
+    /// ` WHERE …` for the set filters, or empty; names are quote-escaped.
+    fn where_clause(&self) -> String {
+        let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let mut conditions = Vec::new();
+        if let Some(function) = &self.function {
+            conditions.push(format!("function_name = {}", quote(function)));
+        }
+        if let Some(variable) = &self.variable {
+            conditions.push(format!("variable_name = {}", quote(variable)));
+        }
+        if let Some(since) = self.since_us {
+            conditions.push(format!("timestamp >= {since}"));
+        }
+        if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        }
+    }
+}
+
 /// Traceable item (function or module)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TraceableItem {
@@ -35,6 +67,28 @@ pub struct TraceableItem {
     pub variables: Vec<String>,
 }
 
+/// One active trace with the figures its hook measures about itself.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActiveTrace {
+    pub function: String,
+    /// Interpreter hook in use (`settrace`).
+    #[serde(default)]
+    pub backend: String,
+    #[serde(default)]
+    pub calls: u64,
+    /// Moving average of the microseconds the hook adds per call.
+    #[serde(default)]
+    pub overhead_us: Option<f64>,
+    #[serde(default)]
+    pub records: u64,
+    /// Variable changes that could not be stored.
+    #[serde(default)]
+    pub records_dropped: u64,
+    /// Epoch seconds of the newest stored record.
+    #[serde(default)]
+    pub last_record_ts: Option<f64>,
+}
+
 /// Trace API
 impl ApiClient {
     /// Get list of traceable items (includes variable information when available).
@@ -50,12 +104,11 @@ impl ApiClient {
         Self::parse_json(&response)
     }
 
-    /// Get current trace status (returns list of traced function names)
-    pub async fn get_trace_info(&self) -> Result<Vec<String>> {
+    /// Get the active traces with their backend, overhead and record counts.
+    pub async fn get_trace_info(&self) -> Result<Vec<ActiveTrace>> {
         let path = "/apis/pythonext/trace/show";
         let response = self.get_request(path).await?;
-        let info: Vec<String> = Self::parse_json(&response)?;
-        Ok(info)
+        Self::parse_json(&response)
     }
 
     /// Start tracing a function
@@ -117,39 +170,54 @@ impl ApiClient {
         Self::parse_json(&response)
     }
 
-    /// Get variable change records (via SQL query)
+    /// Get variable change records (via SQL query), newest first.
     /// Returns DataFrame directly, uses SQL AS to control column name display
-    pub async fn get_variable_records(
-        &self,
-        function: Option<&str>,
-        limit: Option<usize>,
-    ) -> Result<DataFrame> {
-        // Build SQL query with column renaming via AS (SQL controls column names)
-        let limit_clause = limit.map(|l| format!(" LIMIT {}", l)).unwrap_or_default();
-        let where_clause = if let Some(func) = function {
-            // Escape single quotes in function name
-            let escaped_func = func.replace("'", "''");
-            format!(" WHERE function_name = '{}'", escaped_func)
-        } else {
-            String::new()
-        };
-
+    pub async fn get_variable_records(&self, query: &VariableRecordsQuery) -> Result<DataFrame> {
+        let where_clause = query.where_clause();
+        let limit_clause = query
+            .limit
+            .map(|l| format!(" LIMIT {}", l))
+            .unwrap_or_default();
         // Use snake_case column names (DataFusion lowercases unquoted aliases).
-        let queries = [
-            format!(
-                "SELECT function_name, filename, lineno, variable_name, value, value_type, timestamp FROM python.trace_variables{} ORDER BY timestamp DESC{}",
-                where_clause, limit_clause
-            ),
+        self.query_trace_variables(|table| {
             format!(
-                "SELECT function_name, filename, lineno, variable_name, value, value_type, timestamp FROM trace_variables{} ORDER BY timestamp DESC{}",
-                where_clause, limit_clause
-            ),
-        ];
+                "SELECT function_name, filename, lineno, variable_name, value, value_type, timestamp FROM {}{} ORDER BY timestamp DESC{}",
+                table, where_clause, limit_clause
+            )
+        })
+        .await
+    }
 
-        // Try each query until one succeeds
+    /// Names of the variables recorded for `function`, sorted.
+    pub async fn get_traced_variable_names(&self, function: &str) -> Result<Vec<String>> {
+        let where_clause = VariableRecordsQuery {
+            function: Some(function.to_string()),
+            ..Default::default()
+        }
+        .where_clause();
+        let df = self
+            .query_trace_variables(|table| {
+                format!(
+                    "SELECT DISTINCT variable_name FROM {}{} ORDER BY variable_name",
+                    table, where_clause
+                )
+            })
+            .await?;
+        Ok(df
+            .iter()
+            .filter_map(|row| match row.into_iter().next() {
+                Some(Ele::Text(name)) => Some(name),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Run the query built by `sql` against `python.trace_variables`, falling
+    /// back to the unqualified table name.
+    async fn query_trace_variables(&self, sql: impl Fn(&str) -> String) -> Result<DataFrame> {
         let mut last_err: Option<crate::utils::error::AppError> = None;
-        for query in queries.iter() {
-            match self.execute_query(query).await {
+        for table in ["python.trace_variables", "trace_variables"] {
+            match self.execute_query(&sql(table)).await {
                 Ok(df) => {
                     return Ok(df);
                 }
@@ -168,3 +236,41 @@ impl ApiClient {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_trace_parses_show_payload() {
+        let payload = r#"[
+            {"function": "train.step", "backend": "settrace", "calls": 12,
+             "overhead_us": 41.5, "records": 30, "records_dropped": 2,
+             "last_record_ts": 1760000000.25},
+            {"function": "train.eval", "backend": "settrace", "calls": 0,
+             "overhead_us": null, "records": 0, "records_dropped": 0,
+             "last_record_ts": null}
+        ]"#;
+        let traces: Vec<ActiveTrace> = serde_json::from_str(payload).unwrap();
+        assert_eq!(traces[0].overhead_us, Some(41.5));
+        assert_eq!(traces[0].records_dropped, 2);
+        assert_eq!(traces[1].last_record_ts, None);
+        assert_eq!(traces[1].backend, "settrace");
+    }
+
+    #[test]
+    fn records_query_combines_escaped_filters() {
+        assert_eq!(VariableRecordsQuery::default().where_clause(), "");
+        let query = VariableRecordsQuery {
+            function: Some("train.step".to_string()),
+            variable: Some("it's".to_string()),
+            since_us: Some(1_760_000_000_000_000),
+            limit: Some(50),
+        };
+        assert_eq!(
+            query.where_clause(),
+            " WHERE function_name = 'train.step' AND variable_name = 'it''s' \
+             AND timestamp >= 1760000000000000"
+        );
+    }
+}
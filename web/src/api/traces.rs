@@ -1,6 +1,5 @@
 use super::ApiClient;
 use crate::utils::error::Result;
-use probing_proto::prelude::Ele;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +29,14 @@ pub struct SpanInfo {
     pub phase: Option<String>,
     pub location: Option<String>,
     pub attributes: Option<String>,
+    /// Thread CPU time and context switches; set with `probing.trace.cpu_time=on`.
+    #[serde(default)]
+    pub cpu_time_ns: Option<i64>,
+    #[serde(default)]
+    pub ctx_switches: Option<i64>,
+    /// JSON list of `{trace_id, span_id, attributes}` this span links to.
+    #[serde(default)]
+    pub links: Option<String>,
     pub children: Vec<SpanInfo>,
     pub events: Vec<EventInfo>,
 }
@@ -41,262 +48,237 @@ pub struct EventInfo {
     pub attributes: Option<String>,
 }
 
-/// Tracing API
-impl ApiClient {
-    /// Get trace events, supports limiting count
-    pub async fn get_trace_events(&self, limit: Option<usize>) -> Result<Vec<TraceEvent>> {
-        let limit_clause = if let Some(limit) = limit {
-            format!("LIMIT {}", limit)
-        } else {
-            String::new()
-        };
+/// One message of the live span stream (`/apis/trace/stream`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpanStreamMessage {
+    /// A span that just finished, without children.
+    Span { span: SpanInfo },
+    /// The server skipped `dropped` spans because this client fell behind.
+    Lagged { dropped: u64, total_dropped: u64 },
+}
 
-        // Use logical event time (`time`, ns) — not memtable ingestion `timestamp` (µs).
-        // Matches training step_matrix / SPANS_SQL in probing.tracing.
-        let query = format!(
-            r#"
-            SELECT
-                record_type,
-                trace_id,
-                span_id,
-                COALESCE(parent_id, -1) as parent_id,
-                name,
-                time AS timestamp,
-                COALESCE(thread_id, 0) as thread_id,
-                phase,
-                location,
-                attributes,
-                event_attributes
-            FROM python.trace_event
-            ORDER BY time DESC
-            {}
-        "#,
-            limit_clause
-        );
+/// Source lines around a span's location (`/apis/trace/source`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanSource {
+    pub span_id: i64,
+    pub available: bool,
+    /// Why the source cannot be shown when `available` is false.
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Span line (1-based) to highlight.
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub start_line: usize,
+    #[serde(default)]
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub total_lines: usize,
+}
 
-        let df = self.execute_query(&query).await?;
+/// One span name's p50/p95 change between a baseline and a current window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanWindowDelta {
+    pub name: String,
+    pub baseline_count: u64,
+    pub current_count: u64,
+    pub baseline_p50_us: Option<f64>,
+    pub current_p50_us: Option<f64>,
+    pub baseline_p95_us: Option<f64>,
+    pub current_p95_us: Option<f64>,
+    pub delta_p50_us: Option<f64>,
+    pub delta_p95_us: Option<f64>,
+    pub delta_p50_pct: Option<f64>,
+    pub significant: bool,
+    /// `regression`, `improvement`, `unchanged`, `new`, or `gone`.
+    pub verdict: String,
+}
 
-        // Convert DataFrame to Vec<TraceEvent>
-        let mut events = Vec::new();
+#[derive(Debug, Clone, Deserialize)]
+struct TraceCompareResponse {
+    #[serde(default)]
+    deltas: Vec<SpanWindowDelta>,
+}
 
-        if df.names.is_empty() || df.cols.is_empty() {
-            return Ok(events);
-        }
+/// Inclusive-start / exclusive-end window in µs since epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceWindow {
+    pub start_us: i64,
+    pub end_us: i64,
+}
 
-        // Find column indices
-        let record_type_idx = df
-            .names
-            .iter()
-            .position(|c| c == "record_type")
-            .unwrap_or(0);
-        let trace_id_idx = df.names.iter().position(|c| c == "trace_id").unwrap_or(1);
-        let span_id_idx = df.names.iter().position(|c| c == "span_id").unwrap_or(2);
-        let parent_id_idx = df.names.iter().position(|c| c == "parent_id").unwrap_or(3);
-        let name_idx = df.names.iter().position(|c| c == "name").unwrap_or(4);
-        let timestamp_idx = df.names.iter().position(|c| c == "timestamp").unwrap_or(5);
-        let thread_id_idx = df.names.iter().position(|c| c == "thread_id").unwrap_or(6);
-        let phase_idx = df.names.iter().position(|c| c == "phase").unwrap_or(7);
-        let location_idx = df.names.iter().position(|c| c == "location").unwrap_or(8);
-        let attributes_idx = df.names.iter().position(|c| c == "attributes").unwrap_or(9);
-        let event_attributes_idx = df
-            .names
-            .iter()
-            .position(|c| c == "event_attributes")
-            .unwrap_or(10);
-
-        // Get number of rows
-        let nrows = df.cols.iter().map(|col| col.len()).max().unwrap_or(0);
-
-        for row_idx in 0..nrows {
-            let get_str = |idx: usize| -> String {
-                match df.cols.get(idx).map(|col| col.get(row_idx)) {
-                    Some(Ele::Text(s)) => s.clone(),
-                    Some(Ele::I32(x)) => x.to_string(),
-                    Some(Ele::I64(x)) => x.to_string(),
-                    Some(Ele::F32(x)) => x.to_string(),
-                    Some(Ele::F64(x)) => x.to_string(),
-                    _ => "".to_string(),
-                }
-            };
-
-            let get_i64 = |idx: usize| -> i64 {
-                match df.cols.get(idx).map(|col| col.get(row_idx)) {
-                    Some(Ele::I32(x)) => x as i64,
-                    Some(Ele::I64(x)) => x,
-                    Some(Ele::F32(x)) => x as i64,
-                    Some(Ele::F64(x)) => x as i64,
-                    Some(Ele::Text(s)) => s.parse().unwrap_or(0),
-                    _ => 0,
-                }
-            };
-
-            let get_opt_str = |idx: usize| -> Option<String> {
-                match df.cols.get(idx).map(|col| col.get(row_idx)) {
-                    Some(Ele::Text(s)) if !s.is_empty() => Some(s.clone()),
-                    _ => None,
-                }
-            };
-
-            let get_opt_i64 = |idx: usize| -> Option<i64> {
-                let val = get_i64(idx);
-                if val == -1 {
-                    None
-                } else {
-                    Some(val)
-                }
-            };
-
-            events.push(TraceEvent {
-                record_type: get_str(record_type_idx),
-                trace_id: get_i64(trace_id_idx),
-                span_id: get_i64(span_id_idx),
-                parent_id: get_opt_i64(parent_id_idx),
-                name: get_str(name_idx),
-                timestamp: get_i64(timestamp_idx),
-                thread_id: get_i64(thread_id_idx),
-                phase: get_opt_str(phase_idx),
-                location: get_opt_str(location_idx),
-                attributes: get_opt_str(attributes_idx),
-                event_attributes: get_opt_str(event_attributes_idx),
-            });
-        }
+/// Span filters the span tree and chrome-tracing endpoints apply in their query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceFilters {
+    pub names: Vec<String>,
+    pub phases: Vec<String>,
+    pub thread_ids: Vec<i64>,
+    /// Time window in ns since epoch (inclusive); spans overlapping it are kept.
+    pub start_ts: Option<i64>,
+    pub end_ts: Option<i64>,
+    pub trace_id: Option<i64>,
+    /// Span name pattern (`*`, `?`); chrome-tracing only, the span tree
+    /// ignores it.
+    pub name_glob: Option<String>,
+}
 
-        Ok(events)
-    }
+/// Events in a chrome-tracing export (`source=`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TraceSource {
+    /// Probing spans.
+    #[default]
+    Spans,
+    /// Probing spans and the PyTorch profiler timeline on one timebase
+    /// (JSON only).
+    Combined,
+}
 
-    /// Build span tree structure, supports limiting count
-    pub async fn get_span_tree(&self, limit: Option<usize>) -> Result<Vec<SpanInfo>> {
-        let events = self.get_trace_events(limit).await?;
-
-        // Build span map from span_start events
-        let mut span_map: std::collections::HashMap<i64, SpanInfo> =
-            std::collections::HashMap::new();
-        let mut root_spans: Vec<i64> = Vec::new();
-
-        for event in &events {
-            if event.record_type == "span_start" {
-                let span = SpanInfo {
-                    span_id: event.span_id,
-                    trace_id: event.trace_id,
-                    parent_id: event.parent_id,
-                    name: event.name.clone(),
-                    start_timestamp: event.timestamp,
-                    end_timestamp: None,
-                    thread_id: event.thread_id,
-                    phase: event.phase.clone(),
-                    location: event.location.clone(),
-                    attributes: event.attributes.clone(),
-                    children: Vec::new(),
-                    events: Vec::new(),
-                };
-
-                if event.parent_id.is_none() || event.parent_id == Some(-1) {
-                    root_spans.push(event.span_id);
-                }
-
-                span_map.insert(event.span_id, span);
-            } else if event.record_type == "span_end" {
-                if let Some(span) = span_map.get_mut(&event.span_id) {
-                    span.end_timestamp = Some(event.timestamp);
-                }
-            } else if event.record_type == "event" {
-                if let Some(span) = span_map.get_mut(&event.span_id) {
-                    span.events.push(EventInfo {
-                        name: event.name.clone(),
-                        timestamp: event.timestamp,
-                        attributes: event.event_attributes.clone(),
-                    });
-                }
-            }
+impl TraceSource {
+    fn query_param(self) -> &'static str {
+        match self {
+            TraceSource::Spans => "",
+            TraceSource::Combined => "&source=combined",
         }
+    }
+}
 
-        // Build tree structure - process from deepest to shallowest
-        // Calculate depth for each span using iterative approach
-        let mut depth_map: std::collections::HashMap<i64, usize> = std::collections::HashMap::new();
-
-        // Initialize all root spans to depth 0
-        for root_id in &root_spans {
-            depth_map.insert(*root_id, 0);
-        }
+impl TraceFilters {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+            && self.phases.is_empty()
+            && self.thread_ids.is_empty()
+            && self.start_ts.is_none()
+            && self.end_ts.is_none()
+            && self.trace_id.is_none()
+            && self.name_glob.is_none()
+    }
 
-        // Iteratively calculate depths until no changes
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for (span_id, span) in span_map.iter() {
-                if depth_map.contains_key(span_id) {
-                    continue; // Already calculated
-                }
-
-                if let Some(parent_id) = span.parent_id {
-                    if parent_id != -1 && depth_map.contains_key(&parent_id) {
-                        let parent_depth = depth_map[&parent_id];
-                        depth_map.insert(*span_id, parent_depth + 1);
-                        changed = true;
-                    }
-                } else {
-                    // Root span (should have been added already, but handle it)
-                    depth_map.insert(*span_id, 0);
-                    changed = true;
-                }
-            }
-        }
+    /// `&name=…&phase=…&thread_id=…&start_ts=…&end_ts=…&trace_id=…&name_glob=…`
+    /// (comma-separated values), empty when unfiltered.
+    pub fn query_params(&self) -> String {
+        let threads: Vec<String> = self.thread_ids.iter().map(i64::to_string).collect();
+        let num = |t: Option<i64>| t.map(|t| t.to_string()).unwrap_or_default();
+        [
+            ("name", self.names.join(",")),
+            ("phase", self.phases.join(",")),
+            ("thread_id", threads.join(",")),
+            ("start_ts", num(self.start_ts)),
+            ("end_ts", num(self.end_ts)),
+            ("trace_id", num(self.trace_id)),
+            ("name_glob", self.name_glob.clone().unwrap_or_default()),
+        ]
+        .into_iter()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(key, values)| format!("&{key}={}", urlencoding::encode(&values)))
+        .collect()
+    }
+}
 
-        // Sort spans by depth (deepest first) so we process children before parents
-        let mut spans_to_process: Vec<(i64, usize)> = span_map
-            .keys()
-            .map(|&id| (id, depth_map.get(&id).copied().unwrap_or(0)))
-            .collect();
-        spans_to_process.sort_by_key(|b| std::cmp::Reverse(b.1)); // Sort by depth descending
-
-        // Process spans from deepest to shallowest
-        // This ensures that when we add a child to its parent, the child's children
-        // have already been added to the child
-        for (span_id, _depth) in spans_to_process {
-            let parent_id = span_map
-                .get(&span_id)
-                .and_then(|span| span.parent_id)
-                .filter(|&pid| pid != -1);
-
-            if let Some(parent_id) = parent_id {
-                // Remove child from map and add to parent
-                if let Some(child) = span_map.remove(&span_id) {
-                    if let Some(parent) = span_map.get_mut(&parent_id) {
-                        parent.children.push(child);
-                    } else {
-                        // Parent not found (shouldn't happen if depth calculation is correct)
-                        // Put child back as orphan
-                        span_map.insert(span_id, child);
-                    }
-                }
-            }
-        }
+/// Tracing API
+impl ApiClient {
+    /// Span trees assembled by the server (`/apis/trace/span_tree`).
+    pub async fn get_span_tree(
+        &self,
+        limit: Option<usize>,
+        filters: &TraceFilters,
+    ) -> Result<Vec<SpanInfo>> {
+        let limit = limit.unwrap_or(1000);
+        let path = format!(
+            "/apis/trace/span_tree?limit={limit}{}",
+            filters.query_params()
+        );
+        let response = self.get_request(&path).await?;
+        Self::parse_json(&response)
+    }
 
-        // Collect root spans
-        let mut result = Vec::new();
-        for root_id in root_spans {
-            if let Some(span) = span_map.remove(&root_id) {
-                result.push(span);
-            }
-        }
+    /// Source snippet around a span's recorded location.
+    pub async fn get_span_source(&self, span_id: i64) -> Result<SpanSource> {
+        let response = self
+            .get_request(&format!("/apis/trace/source?span_id={span_id}"))
+            .await?;
+        Self::parse_json(&response)
+    }
 
-        // Add any remaining spans (orphans)
-        for (_, span) in span_map {
-            result.push(span);
+    /// Get JSON data in Chrome tracing format via the Python extension API.
+    /// `include_counters` adds the sampled CPU/memory series as counter tracks.
+    /// `filters` (trace id and name glob included) apply before `limit`;
+    /// ancestors of kept spans come along so the slices still nest.
+    /// `source` adds the PyTorch profiler timeline when combined.
+    pub async fn get_chrome_tracing_json(
+        &self,
+        limit: Option<usize>,
+        filters: &TraceFilters,
+        include_counters: bool,
+        source: TraceSource,
+    ) -> Result<String> {
+        let limit = limit.unwrap_or(1000);
+        let path = format!(
+            "/apis/pythonext/trace/chrome-tracing?limit={limit}&include_counters={include_counters}{}{}",
+            filters.query_params(),
+            source.query_param()
+        );
+        let response = self.get_request(&path).await?;
+        if let Some(error_obj) = chrome_tracing_error(&response) {
+            return Err(crate::utils::error::AppError::Api(format!(
+                "Backend error: {}",
+                error_obj
+            )));
         }
 
-        // Sort by start timestamp
-        result.sort_by_key(|s| s.start_timestamp);
+        Ok(response)
+    }
 
-        Ok(result)
+    /// Absolute URL of the same export as a binary Perfetto trace
+    /// (`format=proto`), for the viewer page to fetch itself.
+    pub fn chrome_tracing_proto_url(
+        limit: Option<usize>,
+        filters: &TraceFilters,
+        include_counters: bool,
+    ) -> Result<String> {
+        let limit = limit.unwrap_or(1000);
+        Self::build_url(&format!(
+            "/apis/pythonext/trace/chrome-tracing?limit={limit}&include_counters={include_counters}&format=proto{}",
+            filters.query_params()
+        ))
     }
 
-    /// Get JSON data in Chrome tracing format via the Python extension API.
-    pub async fn get_chrome_tracing_json(&self, limit: Option<usize>) -> Result<String> {
+    /// Absolute URL of the same export as a file to save
+    /// (`trace-<pid>-<ts>.json`); gzip-compressed in transit only.
+    pub fn chrome_tracing_download_url(
+        limit: Option<usize>,
+        filters: &TraceFilters,
+        include_counters: bool,
+        source: TraceSource,
+    ) -> Result<String> {
         let limit = limit.unwrap_or(1000);
-        let path = format!("/apis/pythonext/trace/chrome-tracing?limit={limit}");
-        let response = self.get_request(&path).await?;
+        Self::build_url(&format!(
+            "/apis/trace/chrome-tracing/download?limit={limit}&include_counters={include_counters}&gzip=1{}{}",
+            filters.query_params(),
+            source.query_param()
+        ))
+    }
 
+    /// WebSocket URL of the live span stream (`ws://` or `wss://` like the page).
+    pub fn span_stream_url() -> Result<String> {
+        Ok(websocket_url(&Self::build_url("/apis/trace/stream")?))
+    }
+
+    /// Compare per-span p50/p95 between two windows (server-side over the spans view).
+    pub async fn compare_trace_windows(
+        &self,
+        baseline: TraceWindow,
+        current: TraceWindow,
+    ) -> Result<Vec<SpanWindowDelta>> {
+        let path = format!(
+            "/apis/pythonext/trace/summary?start_us={}&end_us={}&baseline_start_us={}&baseline_end_us={}",
+            current.start_us, current.end_us, baseline.start_us, baseline.end_us
+        );
+        let response = self.get_request(&path).await?;
         let json_value: serde_json::Value = serde_json::from_str(&response)?;
         if let Some(error_obj) = json_value.get("error") {
             return Err(crate::utils::error::AppError::Api(format!(
@@ -304,8 +286,8 @@ impl ApiClient {
                 error_obj
             )));
         }
-
-        Ok(response)
+        let parsed: TraceCompareResponse = serde_json::from_value(json_value)?;
+        Ok(parsed.deltas)
     }
 
     /// Get Ray timeline in Chrome tracing format (for Perfetto UI)
@@ -352,3 +334,85 @@ impl ApiClient {
         Ok(response)
     }
 }
+
+/// `url` with its `http` scheme swapped for the matching WebSocket one.
+fn websocket_url(url: &str) -> String {
+    match url.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => url.to_string(),
+    }
+}
+
+/// The `error` of a failed chrome-tracing export. Errors come back as a small
+/// object led by `error`, so a trace (led by `displayTimeUnit`) is never
+/// parsed a second time just to look for one.
+fn chrome_tracing_error(body: &str) -> Option<serde_json::Value> {
+    if !body.trim_start().starts_with(r#"{"error""#) {
+        return None;
+    }
+    let mut value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value.get_mut("error").map(serde_json::Value::take)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_and_glob_filters_are_encoded() {
+        let filters = TraceFilters {
+            trace_id: Some(9),
+            name_glob: Some("step_*".into()),
+            ..Default::default()
+        };
+        assert!(!filters.is_empty());
+        assert_eq!(filters.query_params(), "&trace_id=9&name_glob=step_%2A");
+        assert_eq!(TraceFilters::default().query_params(), "");
+    }
+
+    #[test]
+    fn chrome_tracing_errors_are_detected_without_parsing_traces() {
+        let error = r#"{"error": "no table", "traceEvents": []}"#;
+        assert_eq!(
+            chrome_tracing_error(error),
+            Some(serde_json::Value::from("no table"))
+        );
+        let trace = r#"{"displayTimeUnit": "ms", "traceEvents": [
+{"name":"error","ph":"i","ts":0,"pid":1,"tid":1}
+]}"#;
+        assert_eq!(chrome_tracing_error(trace), None);
+    }
+
+    #[test]
+    fn span_stream_urls_and_messages() {
+        assert_eq!(
+            websocket_url("https://host:8080/base/apis/trace/stream"),
+            "wss://host:8080/base/apis/trace/stream"
+        );
+        assert_eq!(
+            websocket_url("http://host/apis/trace/stream"),
+            "ws://host/apis/trace/stream"
+        );
+
+        let lagged: SpanStreamMessage =
+            serde_json::from_str(r#"{"type":"lagged","dropped":2,"total_dropped":5}"#).unwrap();
+        assert_eq!(
+            lagged,
+            SpanStreamMessage::Lagged {
+                dropped: 2,
+                total_dropped: 5
+            }
+        );
+        let span = r#"{"type":"span","span":{"span_id":7,"trace_id":1,"parent_id":3,
+            "name":"fwd","start_timestamp":10,"end_timestamp":20,"thread_id":4,
+            "phase":null,"location":null,"attributes":null,"children":[],"events":[]}}"#;
+        match serde_json::from_str(span).unwrap() {
+            SpanStreamMessage::Span { span } => {
+                assert_eq!((span.span_id, span.parent_id), (7, Some(3)));
+                assert_eq!(span.end_timestamp, Some(20));
+            }
+            other => panic!("expected a span, got {other:?}"),
+        }
+    }
+}
@@ -24,6 +24,7 @@ use crate::pages::{
     training::Training,
 };
 use crate::state::profiling::normalize_profiling_view;
+use crate::state::profiling_url::apply_profiling_params_from_url;
 
 /// All routes. Each is rendered inside AppLayout by the corresponding page component below.
 #[derive(Routable, Clone, PartialEq)]
@@ -162,6 +163,8 @@ pub fn StackDistributedPyPage() -> Element {
 pub fn ProfilingRedirect() -> Element {
     let nav = dioxus_router::use_navigator();
     use_effect(move || {
+        // The redirect drops the query string; keep its settings.
+        apply_profiling_params_from_url();
         nav.replace(Route::ProfilingViewPage {
             view: "pprof".to_string(),
         });
@@ -230,6 +233,7 @@ fn StackDistributedRedirect() -> Element {
 fn ProfilingSlugRedirect(target: String) -> Element {
     let nav = dioxus_router::use_navigator();
     use_effect(move || {
+        apply_profiling_params_from_url();
         nav.replace(Route::ProfilingViewPage {
             view: target.clone(),
         });
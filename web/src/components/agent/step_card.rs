@@ -55,6 +55,7 @@ fn ele_display(ele: &Ele) -> String {
         Ele::Text(x) => x.clone(),
         Ele::Url(x) => x.clone(),
         Ele::DataTime(x) => x.to_string(),
+        Ele::DateTime(_) => ele.to_string(),
     }
 }
 
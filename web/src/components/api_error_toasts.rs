@@ -0,0 +1,50 @@
+//! Global stack of failed-request toasts (see [`crate::state::api_errors`]).
+
+use dioxus::prelude::*;
+
+use crate::components::icon::Icon;
+use crate::state::api_errors::{dismiss_api_error, API_ERROR_TOASTS};
+
+#[component]
+pub fn ApiErrorToasts() -> Element {
+    let toasts = API_ERROR_TOASTS.read().clone();
+    if toasts.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "fixed top-4 right-4 z-[9996] w-80 max-w-[calc(100vw-2rem)] flex flex-col gap-2",
+            role: "alert",
+            for toast in toasts {
+                div {
+                    key: "{toast.id}",
+                    class: "flex items-start gap-2 px-3 py-2.5 rounded-lg shadow-lg border border-red-200 bg-red-50 text-red-900 text-sm",
+                    Icon { icon: &icondata::AiCloseCircleOutlined, class: "w-4 h-4 shrink-0 mt-0.5" }
+                    div { class: "flex-1 min-w-0",
+                        p { class: "font-mono text-xs font-medium truncate", title: "{toast.endpoint}",
+                            "{toast.heading()}"
+                        }
+                        p { class: "mt-0.5 text-xs text-red-800 break-words", "{toast.message}" }
+                        if let Some(retry) = toast.retry.clone() {
+                            button {
+                                class: "mt-1.5 px-2 py-0.5 text-xs font-medium rounded border border-red-300 bg-white text-red-700 hover:bg-red-100",
+                                onclick: move |_| {
+                                    dismiss_api_error(toast.id);
+                                    retry();
+                                },
+                                "Retry"
+                            }
+                        }
+                    }
+                    button {
+                        class: "shrink-0 p-0.5 rounded opacity-60 hover:opacity-100",
+                        title: "Dismiss",
+                        onclick: move |_| dismiss_api_error(toast.id),
+                        Icon { icon: &icondata::AiCloseOutlined, class: "w-3.5 h-3.5" }
+                    }
+                }
+            }
+        }
+    }
+}
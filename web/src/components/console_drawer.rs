@@ -0,0 +1,321 @@
+//! Console drawer (`` ` `` to toggle): a mini REPL over `pythonext/eval`.
+//!
+//! Enter runs, Shift+Enter adds a line, Tab completes via `pythonext/complete`
+//! and ↑/↓ walk the per-tab history. Each run is a UI task, so it shows its
+//! elapsed time and can be cancelled; cancelling stops waiting for the reply
+//! (the target still finishes the statement).
+
+use dioxus::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::api::ApiClient;
+use crate::components::icon::Icon;
+use crate::state::console::{
+    apply_completion, char_to_utf16, history_step, push_console_entry, remember_console_input,
+    update_console_entry, utf16_to_char, ConsoleEntry, ConsoleStatus, CONSOLE_AVAILABLE,
+    CONSOLE_ENTRIES, CONSOLE_HISTORY, CONSOLE_INPUT, CONSOLE_OPEN,
+};
+use crate::state::ui_tasks::{cancel_ui_task, open_ui_task, UiTaskKind, UI_TASK_TICK};
+
+const INPUT_ID: &str = "probing-console-input";
+const MAX_INPUT_ROWS: usize = 8;
+
+fn input_element() -> Option<web_sys::HtmlTextAreaElement> {
+    web_sys::window()?
+        .document()?
+        .get_element_by_id(INPUT_ID)?
+        .dyn_into()
+        .ok()
+}
+
+/// Caret position in characters (end of input when unknown).
+fn caret(code: &str) -> usize {
+    input_element()
+        .and_then(|el| el.selection_start().ok().flatten())
+        .map_or(code.chars().count(), |offset| {
+            utf16_to_char(code, offset as usize)
+        })
+}
+
+/// Move the caret once the re-rendered value is in the DOM.
+fn set_caret(code: &str, index: usize) {
+    let offset = char_to_utf16(code, index) as u32;
+    gloo_timers::callback::Timeout::new(0, move || {
+        if let Some(el) = input_element() {
+            let _ = el.set_selection_range(offset, offset);
+        }
+    })
+    .forget();
+}
+
+fn run(code: String) {
+    remember_console_input(&code);
+    let task = open_ui_task(UiTaskKind::Eval, "Console eval", Some(first_line(&code)));
+    let task_id = task.id();
+    push_console_entry(ConsoleEntry {
+        task_id,
+        input: code.clone(),
+        output: String::new(),
+        traceback: vec![],
+        status: ConsoleStatus::Running,
+        started_ms: js_sys::Date::now() as u64,
+        finished_ms: None,
+    });
+    // Outlives the drawer (and the page) so navigating keeps the result.
+    spawn_forever(async move {
+        let result = ApiClient::new().eval(&code).await;
+        let finished_ms = Some(js_sys::Date::now() as u64);
+        if task.is_cancelled() {
+            task.finish();
+            return;
+        }
+        match result {
+            Ok(resp) => {
+                let failed = resp.status == "error" || !resp.traceback.is_empty();
+                update_console_entry(task_id, |e| {
+                    e.finished_ms = finished_ms;
+                    e.output = resp.output;
+                    e.traceback = resp.traceback;
+                    e.status = if failed {
+                        ConsoleStatus::Error
+                    } else {
+                        ConsoleStatus::Ok
+                    };
+                });
+                if failed {
+                    task.fail("evaluation raised");
+                } else {
+                    task.finish();
+                }
+            }
+            Err(err) => {
+                let message = err.display_message();
+                update_console_entry(task_id, |e| {
+                    e.finished_ms = finished_ms;
+                    e.traceback = vec![message.clone()];
+                    e.status = ConsoleStatus::Error;
+                });
+                task.fail(message);
+            }
+        }
+    });
+}
+
+fn first_line(code: &str) -> String {
+    let line = code.lines().next().unwrap_or_default();
+    if line.chars().count() > 60 {
+        format!("{}…", line.chars().take(60).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[component]
+pub fn ConsoleDrawer() -> Element {
+    let mut history_pos = use_signal(|| None::<usize>);
+    let mut draft = use_signal(String::new);
+    let mut candidates = use_signal(Vec::<String>::new);
+
+    use_effect(move || {
+        if !*CONSOLE_OPEN.read() || CONSOLE_AVAILABLE.peek().is_some() {
+            return;
+        }
+        spawn(async move {
+            let available = ApiClient::new().repl_available().await;
+            *CONSOLE_AVAILABLE.write() = Some(available);
+            if !available {
+                *CONSOLE_OPEN.write() = false;
+            }
+        });
+    });
+
+    if !*CONSOLE_OPEN.read() || *CONSOLE_AVAILABLE.read() == Some(false) {
+        return rsx! {};
+    }
+
+    let _tick = UI_TASK_TICK.read();
+    let now_ms = js_sys::Date::now() as u64;
+    let entries = CONSOLE_ENTRIES.read().clone();
+    let input = CONSOLE_INPUT.read().clone();
+    let rows = input.split('\n').count().clamp(1, MAX_INPUT_ROWS);
+
+    let on_keydown = move |e: KeyboardEvent| {
+        use dioxus::html::input_data::keyboard_types::Key;
+        let code = CONSOLE_INPUT.read().clone();
+        match e.key() {
+            Key::Enter if !e.modifiers().shift() => {
+                e.prevent_default();
+                if code.trim().is_empty() {
+                    return;
+                }
+                *CONSOLE_INPUT.write() = String::new();
+                history_pos.set(None);
+                candidates.set(vec![]);
+                run(code);
+            }
+            Key::Tab => {
+                e.prevent_default();
+                let cursor = caret(&code);
+                spawn(async move {
+                    let Ok(completion) = ApiClient::new().complete(&code, cursor).await else {
+                        return;
+                    };
+                    if let Some((completed, at)) = apply_completion(
+                        &code,
+                        completion.start,
+                        completion.end,
+                        &completion.matches,
+                    ) {
+                        *CONSOLE_INPUT.write() = completed.clone();
+                        set_caret(&completed, at);
+                    }
+                    let shown = if completion.matches.len() > 1 {
+                        completion.matches
+                    } else {
+                        vec![]
+                    };
+                    candidates.set(shown);
+                });
+            }
+            Key::ArrowUp | Key::ArrowDown => {
+                let up = e.key() == Key::ArrowUp;
+                let before: String = code.chars().take(caret(&code)).collect();
+                let on_edge = if up {
+                    !before.contains('\n')
+                } else {
+                    !code[before.len()..].contains('\n')
+                };
+                if !on_edge {
+                    return;
+                }
+                let history = CONSOLE_HISTORY.read().clone();
+                let pos = history_pos();
+                if pos.is_none() && up {
+                    draft.set(code);
+                }
+                let next = history_step(history.len(), pos, up);
+                if next == pos {
+                    return;
+                }
+                e.prevent_default();
+                history_pos.set(next);
+                let value = next.map_or_else(|| draft(), |i| history[i].clone());
+                let end = value.chars().count();
+                *CONSOLE_INPUT.write() = value.clone();
+                set_caret(&value, end);
+            }
+            Key::Escape => {
+                *CONSOLE_OPEN.write() = false;
+            }
+            _ => {}
+        }
+    };
+
+    rsx! {
+        div {
+            class: "fixed inset-x-0 bottom-0 z-[9995] h-[40vh] flex flex-col bg-gray-900 text-gray-100 border-t border-gray-700 shadow-2xl",
+            div { class: "flex items-center gap-3 px-4 py-2 border-b border-gray-700 text-xs",
+                Icon { icon: &icondata::AiCodeOutlined, class: "w-4 h-4 text-gray-400" }
+                span { class: "font-semibold text-gray-200", "Console" }
+                span { class: "text-gray-500 truncate",
+                    "Enter run · Shift+Enter newline · Tab complete · ↑↓ history · Esc close"
+                }
+                div { class: "ml-auto flex items-center gap-1",
+                    button {
+                        class: "px-2 py-1 rounded text-gray-400 hover:text-gray-100 hover:bg-gray-800 disabled:opacity-40",
+                        disabled: entries.is_empty(),
+                        onclick: move |_| CONSOLE_ENTRIES.write().retain(|e| e.status == ConsoleStatus::Running),
+                        "Clear"
+                    }
+                    button {
+                        class: "p-1 rounded text-gray-400 hover:text-gray-100 hover:bg-gray-800",
+                        title: "Close console (Esc)",
+                        onclick: move |_| *CONSOLE_OPEN.write() = false,
+                        Icon { icon: &icondata::AiCloseOutlined, class: "w-4 h-4" }
+                    }
+                }
+            }
+            div { class: "flex-1 min-h-0 overflow-y-auto px-4 py-2 space-y-3 font-mono text-xs",
+                if entries.is_empty() {
+                    p { class: "text-gray-500", "Evaluate Python in the target process. Definitions persist between runs." }
+                }
+                for entry in entries.iter() {
+                    ConsoleEntryView {
+                        key: "{entry.task_id}",
+                        entry: entry.clone(),
+                        now_ms,
+                    }
+                }
+            }
+            if !candidates().is_empty() {
+                div { class: "flex flex-wrap gap-1 px-4 py-1.5 border-t border-gray-800 max-h-20 overflow-y-auto",
+                    for m in candidates() {
+                        span { class: "px-1.5 py-0.5 rounded bg-gray-800 text-gray-300 font-mono text-[11px]", "{m}" }
+                    }
+                }
+            }
+            div { class: "flex items-start gap-2 px-4 py-2 border-t border-gray-700",
+                span { class: "pt-1 font-mono text-sm text-blue-300 select-none", "›" }
+                textarea {
+                    id: INPUT_ID,
+                    autofocus: true,
+                    rows: "{rows}",
+                    spellcheck: "false",
+                    class: "flex-1 resize-none bg-transparent font-mono text-sm text-gray-100 placeholder-gray-600 focus:outline-none",
+                    placeholder: "print(model) · %trace list · …",
+                    value: "{input}",
+                    oninput: move |e| {
+                        *CONSOLE_INPUT.write() = e.value();
+                        history_pos.set(None);
+                    },
+                    onkeydown: on_keydown,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ConsoleEntryView(entry: ConsoleEntry, now_ms: u64) -> Element {
+    let task_id = entry.task_id;
+    let elapsed = entry.elapsed_label(now_ms);
+    let traceback = entry.traceback.join("\n");
+    let (status_class, status_label) = match entry.status {
+        ConsoleStatus::Running => ("text-blue-300", "running"),
+        ConsoleStatus::Ok => ("text-gray-500", "ok"),
+        ConsoleStatus::Error => ("text-red-400", "error"),
+        ConsoleStatus::Cancelled => ("text-gray-500", "cancelled"),
+    };
+    rsx! {
+        div {
+            pre { class: "whitespace-pre-wrap text-blue-200", "› {entry.input}" }
+            if !entry.output.is_empty() {
+                pre { class: "whitespace-pre-wrap text-gray-100 mt-1", "{entry.output}" }
+            }
+            if !traceback.is_empty() {
+                pre { class: "whitespace-pre-wrap text-red-400 mt-1", "{traceback}" }
+            }
+            div { class: "flex items-center gap-2 mt-1 text-[11px] {status_class}",
+                if entry.status == ConsoleStatus::Running {
+                    span { class: "inline-block w-2.5 h-2.5 border-2 border-blue-300 border-t-transparent rounded-full animate-spin" }
+                }
+                span { "{status_label}" }
+                span { class: "tabular-nums", "· {elapsed}" }
+                if entry.status == ConsoleStatus::Running {
+                    button {
+                        class: "px-1.5 rounded text-gray-400 hover:text-red-300 hover:bg-gray-800",
+                        title: "Stop waiting for this evaluation",
+                        onclick: move |_| {
+                            cancel_ui_task(task_id);
+                            update_console_entry(task_id, |e| {
+                                e.status = ConsoleStatus::Cancelled;
+                                e.finished_ms = Some(js_sys::Date::now() as u64);
+                            });
+                        },
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,35 +1,350 @@
-use crate::components::table_view::TableView;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::components::table_view::{SortState, TableView};
+use crate::utils::export::{dataframe_to_csv, dataframe_to_json_records, download_file};
 use dioxus::prelude::*;
 use probing_proto::prelude::{DataFrame, Ele};
 
+/// Rows per page offered by the footer.
+pub const PAGE_SIZES: [usize; 3] = [50, 200, 1000];
+const DEFAULT_PAGE_SIZE: usize = 200;
+/// Pages longer than this are rendered windowed.
+const WINDOWED_ROWS: usize = 100;
+
 #[component]
 pub fn DataFrameView(
     df: DataFrame,
     #[props(optional)] on_row_click: Option<EventHandler<usize>>,
 ) -> Element {
-    let headers = use_memo(move || df.names.clone());
-
-    let data = use_memo(move || {
-        let nrows = df.cols.iter().map(|x| x.len()).max().unwrap_or(0);
-        (0..nrows)
-            .map(|i| {
-                df.cols
-                    .iter()
-                    .map(move |col| match col.get(i) {
-                        Ele::Nil => "nil".to_string(),
-                        Ele::BOOL(x) => x.to_string(),
-                        Ele::I32(x) => x.to_string(),
-                        Ele::I64(x) => x.to_string(),
-                        Ele::F32(x) => x.to_string(),
-                        Ele::F64(x) => x.to_string(),
-                        Ele::Text(x) => x.to_string(),
-                        Ele::Url(x) => x.to_string(),
-                        Ele::DataTime(x) => x.to_string(),
-                    })
-                    .collect()
-            })
-            .collect::<Vec<Vec<String>>>()
+    let mut sort = use_signal(|| None::<SortState>);
+    let mut page_size = use_signal(|| DEFAULT_PAGE_SIZE);
+    let mut page = use_signal(|| 0usize);
+    let headers = df.names.clone();
+
+    // Display order of the original rows; clicks map back through it.
+    let order = sorted_row_order(&df, sort());
+    let total = order.len();
+    let size = page_size();
+    let pages = page_count(total, size);
+    let current = page().min(pages - 1);
+    let (start, end) = page_range(total, size, current);
+    let cells = order[start..end]
+        .iter()
+        .map(|&i| df.cols.iter().map(|col| col.get(i)).collect())
+        .collect::<Vec<Vec<Ele>>>();
+    let titles = cells
+        .iter()
+        .map(|row| row.iter().map(cell_title).collect())
+        .collect::<Vec<Vec<String>>>();
+    let data = cells
+        .into_iter()
+        .map(|row| row.into_iter().map(cell_text).collect())
+        .collect::<Vec<Vec<String>>>();
+    let on_row_click = on_row_click.map(|cb| {
+        EventHandler::new(move |shown: usize| {
+            if let Some(&row) = order.get(start + shown) {
+                cb.call(row);
+            }
+        })
+    });
+    let (first_row, page_no) = (start + 1, current + 1);
+    let export_df = Rc::new(df);
+    let csv_df = export_df.clone();
+    let nav_class = "px-2 py-0.5 rounded border border-gray-300 bg-white hover:bg-gray-50 disabled:opacity-40 disabled:cursor-not-allowed";
+
+    rsx! {
+        TableView {
+            key: "{current}-{size}",
+            headers,
+            data,
+            titles: Some(titles),
+            on_row_click,
+            sort: sort(),
+            on_sort: move |column: usize| {
+                sort.set(next_sort(sort(), column));
+                page.set(0);
+            },
+            windowed: end - start > WINDOWED_ROWS,
+        }
+        div { class: "flex flex-wrap items-center gap-3 px-1 py-2 text-xs text-gray-600",
+            span {
+                if total == 0 {
+                    "0 rows"
+                } else {
+                    "Rows {first_row}–{end} of {total}"
+                }
+            }
+            if pages > 1 {
+                div { class: "flex items-center gap-1",
+                    button {
+                        class: nav_class,
+                        disabled: current == 0,
+                        onclick: move |_| page.set(current.saturating_sub(1)),
+                        "‹ Prev"
+                    }
+                    span { class: "px-1", "Page {page_no} / {pages}" }
+                    button {
+                        class: nav_class,
+                        disabled: current + 1 >= pages,
+                        onclick: move |_| page.set((current + 1).min(pages - 1)),
+                        "Next ›"
+                    }
+                }
+            }
+            div { class: "ml-auto flex items-center gap-1",
+                span { "Export" }
+                button {
+                    class: nav_class,
+                    title: "Download all rows as CSV",
+                    onclick: move |_| export(&dataframe_to_csv(&csv_df), "csv", "text/csv"),
+                    "CSV"
+                }
+                button {
+                    class: nav_class,
+                    title: "Download all rows as JSON records",
+                    onclick: move |_| {
+                        export(&dataframe_to_json_records(&export_df), "json", "application/json")
+                    },
+                    "JSON"
+                }
+            }
+            label { class: "flex items-center gap-1",
+                "Rows per page"
+                select {
+                    class: "border border-gray-300 rounded px-1 py-0.5 bg-white",
+                    value: "{size}",
+                    onchange: move |e| {
+                        if let Ok(size) = e.value().parse() {
+                            page_size.set(size);
+                            page.set(0);
+                        }
+                    },
+                    for size in PAGE_SIZES {
+                        option { value: "{size}", "{size}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn export(body: &str, extension: &str, mime: &str) {
+    let filename = format!("probing-query-{}.{extension}", js_sys::Date::now() as u64);
+    if let Err(e) = download_file(&filename, mime, body) {
+        log::warn!("query export failed: {e}");
+    }
+}
+
+/// Pages needed for `total` rows; an empty table still has one.
+fn page_count(total: usize, page_size: usize) -> usize {
+    total.div_ceil(page_size.max(1)).max(1)
+}
+
+/// Display positions `start..end` of `page`.
+fn page_range(total: usize, page_size: usize, page: usize) -> (usize, usize) {
+    let start = (page * page_size).min(total);
+    (start, (start + page_size).min(total))
+}
+
+fn cell_text(ele: Ele) -> String {
+    match ele {
+        Ele::Nil => "nil".to_string(),
+        Ele::BOOL(x) => x.to_string(),
+        Ele::I32(x) => x.to_string(),
+        Ele::I64(x) => x.to_string(),
+        Ele::F32(x) => x.to_string(),
+        Ele::F64(x) => x.to_string(),
+        Ele::Text(x) => x,
+        Ele::Url(x) => x,
+        Ele::DataTime(x) => x.to_string(),
+        Ele::DateTime(x) => local_time(x),
+    }
+}
+
+/// Datetimes show in local time, to the millisecond.
+fn local_time(ns: i64) -> String {
+    chrono::DateTime::from_timestamp_nanos(ns)
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S%.3f")
+        .to_string()
+}
+
+/// Hover text of a cell: the raw nanoseconds and UTC time of datetimes.
+fn cell_title(ele: &Ele) -> String {
+    match ele {
+        Ele::DateTime(x) => format!("{x} ns ({ele})"),
+        _ => String::new(),
+    }
+}
+
+/// Header clicks cycle a column through ascending, descending, unsorted.
+fn next_sort(current: Option<SortState>, column: usize) -> Option<SortState> {
+    match current {
+        Some(s) if s.column == column && s.ascending => Some(SortState {
+            column,
+            ascending: false,
+        }),
+        Some(s) if s.column == column => None,
+        _ => Some(SortState {
+            column,
+            ascending: true,
+        }),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CellKind {
+    Number,
+    Text,
+}
+
+fn cell_kind(ele: &Ele) -> Option<CellKind> {
+    match ele {
+        Ele::Nil => None,
+        Ele::Text(_) | Ele::Url(_) => Some(CellKind::Text),
+        _ => Some(CellKind::Number),
+    }
+}
+
+fn number(ele: &Ele) -> f64 {
+    match ele {
+        Ele::BOOL(x) => f64::from(u8::from(*x)),
+        Ele::I32(x) => f64::from(*x),
+        Ele::I64(x) | Ele::DateTime(x) => *x as f64,
+        Ele::F32(x) => f64::from(*x),
+        Ele::F64(x) => *x,
+        Ele::DataTime(x) => *x as f64,
+        _ => 0.0,
+    }
+}
+
+fn text(ele: &Ele) -> &str {
+    match ele {
+        Ele::Text(x) | Ele::Url(x) => x,
+        _ => "",
+    }
+}
+
+/// Same-kind comparison: numeric for numbers, lexical for text.
+fn compare_cells(a: &Ele, b: &Ele) -> Ordering {
+    match (cell_kind(a), cell_kind(b)) {
+        (Some(CellKind::Number), Some(CellKind::Number)) => number(a).total_cmp(&number(b)),
+        (Some(CellKind::Text), Some(CellKind::Text)) => text(a).cmp(text(b)),
+        (ka, kb) => (ka == Some(CellKind::Text)).cmp(&(kb == Some(CellKind::Text))),
+    }
+}
+
+/// Row indices of `df` in display order. The column's kind is that of its
+/// first non-nil cell; cells of another kind follow, then nils, whichever the
+/// direction. Ties keep server order.
+fn sorted_row_order(df: &DataFrame, sort: Option<SortState>) -> Vec<usize> {
+    let nrows = df.cols.iter().map(|x| x.len()).max().unwrap_or(0);
+    let mut order: Vec<usize> = (0..nrows).collect();
+    let Some(SortState { column, ascending }) = sort else {
+        return order;
+    };
+    let Some(col) = df.cols.get(column) else {
+        return order;
+    };
+    let cells: Vec<Ele> = (0..nrows).map(|i| col.get(i)).collect();
+    let kind = cells.iter().find_map(cell_kind);
+    let rank = |ele: &Ele| match cell_kind(ele) {
+        None => 2,
+        k if k == kind => 0,
+        _ => 1,
+    };
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&cells[a], &cells[b]);
+        rank(a).cmp(&rank(b)).then_with(|| {
+            let ord = compare_cells(a, b);
+            if ascending || rank(a) != 0 {
+                ord
+            } else {
+                ord.reverse()
+            }
+        })
     });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    fn frame() -> DataFrame {
+        DataFrame::new(
+            vec!["n".to_string(), "name".to_string()],
+            vec![
+                Seq::SeqI64(vec![10, 2, 33, 2]),
+                Seq::SeqText(vec!["b".into(), "a".into(), "c".into(), "B".into()]),
+            ],
+        )
+    }
+
+    fn sort(column: usize, ascending: bool) -> Option<SortState> {
+        Some(SortState { column, ascending })
+    }
+
+    #[test]
+    fn numbers_sort_numerically_and_ties_stay_in_order() {
+        let df = frame();
+        assert_eq!(sorted_row_order(&df, None), [0, 1, 2, 3]);
+        assert_eq!(sorted_row_order(&df, sort(0, true)), [1, 3, 0, 2]);
+        assert_eq!(sorted_row_order(&df, sort(0, false)), [2, 0, 1, 3]);
+        assert_eq!(sorted_row_order(&df, sort(1, true)), [3, 1, 0, 2]);
+        // An unknown column leaves server order.
+        assert_eq!(sorted_row_order(&df, sort(9, true)), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn mixed_and_nil_cells_sort_last_both_ways() {
+        let mut df = frame();
+        df.cols[0] = Seq::SeqI64(vec![5, 1, 3]);
+        // Row 3 has no value in column 0.
+        assert_eq!(sorted_row_order(&df, sort(0, true)), [1, 2, 0, 3]);
+        assert_eq!(sorted_row_order(&df, sort(0, false)), [0, 2, 1, 3]);
+
+        // Text among numbers sorts after them; numbers compare across widths.
+        assert_eq!(
+            compare_cells(&Ele::F64(2.5), &Ele::I32(1)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_cells(&Ele::I64(7), &Ele::Text("x".into())),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn datetimes_sort_by_instant_and_keep_the_raw_value_in_the_title() {
+        let mut df = frame();
+        df.cols[0] = Seq::SeqTimestamp(vec![30, 10, 20, 10]);
+        assert_eq!(sorted_row_order(&df, sort(0, true)), [1, 3, 2, 0]);
+        assert_eq!(
+            cell_title(&Ele::DateTime(1_700_000_000_000_000_001)),
+            "1700000000000000001 ns (2023-11-14T22:13:20.000000001Z)"
+        );
+        assert_eq!(cell_title(&Ele::I64(1)), "");
+    }
+
+    #[test]
+    fn pages_cover_every_row_once() {
+        assert_eq!(page_count(0, 50), 1);
+        assert_eq!(page_range(0, 50, 0), (0, 0));
+        assert_eq!(page_count(401, 200), 3);
+        assert_eq!(page_range(401, 200, 1), (200, 400));
+        assert_eq!(page_range(401, 200, 2), (400, 401));
+        assert_eq!(page_range(401, 200, 7), (401, 401));
+    }
 
-    rsx! { TableView { headers: headers.read().clone(), data: data.read().clone(), on_row_click } }
+    #[test]
+    fn header_clicks_cycle_direction() {
+        let asc = next_sort(None, 1);
+        assert_eq!(asc, sort(1, true));
+        assert_eq!(next_sort(asc, 1), sort(1, false));
+        assert_eq!(next_sort(sort(1, false), 1), None);
+        assert_eq!(next_sort(sort(1, false), 0), sort(0, true));
+    }
 }
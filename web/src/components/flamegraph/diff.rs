@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::logic::index_frames;
+use super::logic::{child_map, index_frames};
 use super::model::{FlameFrame, FlamegraphPayload};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -71,3 +71,75 @@ pub fn compute_frame_deltas(
     deltas.truncate(30);
     deltas
 }
+
+/// Folded stacks (`a;b;c self_value`) for `/apis/profile/diff`, one line per
+/// frame with self value; the root frame is left out of the stacks.
+pub fn folded_lines(payload: &FlamegraphPayload) -> Vec<String> {
+    let by_id = index_frames(&payload.frames);
+    let children = child_map(&payload.frames);
+    payload
+        .frames
+        .iter()
+        .filter(|f| f.depth > 0)
+        .filter_map(|f| {
+            let child_total: u64 = children
+                .get(&f.id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| by_id.get(id))
+                .map(|c| c.value)
+                .sum();
+            let own = f.value.saturating_sub(child_total);
+            let path = frame_path(f, &by_id);
+            let stack: Vec<&str> = path.split(" › ").skip(1).collect();
+            (own > 0).then(|| format!("{} {own}", stack.join(";")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: usize, parent: Option<usize>, name: &str, value: u64, depth: usize) -> FlameFrame {
+        FlameFrame {
+            id,
+            parent,
+            name: name.to_string(),
+            value,
+            x: 0.0,
+            y: 0.0,
+            w: 0.0,
+            depth,
+            phase: None,
+            module_path: None,
+            ranks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn folded_lines_carry_self_values_without_root() {
+        let payload = FlamegraphPayload {
+            profile: "pprof".to_string(),
+            title: "CPU".to_string(),
+            subtitle: String::new(),
+            count_name: "samples".to_string(),
+            metric: None,
+            total: 10,
+            width: 1200.0,
+            frame_height: 16.0,
+            frames: vec![
+                frame(0, None, "all", 10, 0),
+                frame(1, Some(0), "main", 10, 1),
+                frame(2, Some(1), "a", 6, 2),
+                frame(3, Some(1), "b", 4, 2),
+            ],
+            empty_message: None,
+            dropped: 0,
+            rank_count: None,
+        };
+        let mut lines = folded_lines(&payload);
+        lines.sort();
+        assert_eq!(lines, ["main;a 6", "main;b 4"]);
+    }
+}
@@ -5,8 +5,8 @@ use dioxus::prelude::*;
 use super::logic::{
     ancestor_ids, child_map, descendants, format_frame_value, format_pct, frame_fill_color,
     frame_matches_thread_tid, frame_visible_for_thread, index_frames, is_torch_profile,
-    label_for_frame, leaf_count_label, list_phases, matches_search, metric_value_label,
-    phase_label, search_placeholder, TORCH_METRICS,
+    label_for_frame, leaf_count_label, list_phases, matched_value, matches_search,
+    metric_value_label, phase_label, search_placeholder, TORCH_METRICS,
 };
 use super::model::{FlameFrame, FlamegraphPayload};
 use super::widgets::{
@@ -101,6 +101,7 @@ pub fn StackExplorerView(
     let scope = root.as_ref().map(|r| r.value).unwrap_or(0);
     let scope_label = format_frame_value(scope, &count_name);
     let scope_pct = format_pct(scope, total);
+    let matched_pct = any_search.then(|| format_pct(matched_value(&visible, &query), scope));
     let ancestor_chain = ancestor_ids(&by_id, *zoom_id.read());
     let phase_roots: HashMap<String, usize> = frames
         .iter()
@@ -129,6 +130,7 @@ pub fn StackExplorerView(
                 search_ph,
                 scope_label,
                 scope_pct,
+                matched_pct,
                 leaf_count,
                 leaf_label,
                 on_search: EventHandler::new(move |value: String| search_query.set(value)),
@@ -242,6 +244,7 @@ fn ExplorerToolbar(
     search_ph: &'static str,
     scope_label: String,
     scope_pct: String,
+    matched_pct: Option<String>,
     leaf_count: usize,
     leaf_label: &'static str,
     on_search: EventHandler<String>,
@@ -296,6 +299,9 @@ fn ExplorerToolbar(
             StatChipRow {
                 StatChip { label: "View", value: scope_label }
                 StatChip { label: "Share", value: format!("{scope_pct}%") }
+                if let Some(matched_pct) = matched_pct {
+                    StatChip { label: "Matched", value: format!("{matched_pct}%") }
+                }
                 StatChip { label: leaf_label, value: leaf_count.to_string() }
             }
         }
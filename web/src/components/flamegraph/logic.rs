@@ -128,6 +128,22 @@ pub fn matches_search(frame: &FlameFrame, query: &str) -> bool {
             .is_some_and(|p| p.to_lowercase().contains(&q))
 }
 
+/// Total value of the `frames` matching `query`, counting a match nested
+/// under another match in `frames` once (the inferno "Matched" share).
+pub fn matched_value(frames: &[FlameFrame], query: &str) -> u64 {
+    let matched: HashSet<usize> = frames
+        .iter()
+        .filter(|f| f.depth > 0 && matches_search(f, query))
+        .map(|f| f.id)
+        .collect();
+    frames
+        .iter()
+        .filter(|f| matched.contains(&f.id))
+        .filter(|f| f.parent.is_none_or(|p| !matched.contains(&p)))
+        .map(|f| f.value)
+        .sum()
+}
+
 pub fn format_frame_value(value: u64, count_name: &str) -> String {
     if count_name == "ns" {
         format_duration_ns(value)
@@ -251,6 +267,39 @@ pub fn frame_visible_for_thread(
 mod tests {
     use super::*;
 
+    fn frame(id: usize, parent: Option<usize>, name: &str, value: u64, depth: usize) -> FlameFrame {
+        FlameFrame {
+            id,
+            parent,
+            name: name.to_string(),
+            value,
+            x: 0.0,
+            y: 0.0,
+            w: 0.0,
+            depth,
+            phase: None,
+            module_path: None,
+            ranks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn matched_value_counts_nested_matches_once() {
+        let frames = [
+            frame(0, None, "all", 100, 0),
+            frame(1, Some(0), "train_step", 60, 1),
+            frame(2, Some(1), "train_loss", 20, 2),
+            frame(3, Some(0), "eval", 40, 1),
+            frame(4, Some(3), "train_metrics", 5, 2),
+        ];
+        // `train_loss` sits under `train_step`; only its ancestor counts.
+        assert_eq!(matched_value(&frames, "train"), 65);
+        assert_eq!(matched_value(&frames, "LOSS"), 20);
+        assert_eq!(matched_value(&frames, "missing"), 0);
+        // Zoomed into `train_step`: the nested match counts on its own.
+        assert_eq!(matched_value(&frames[2..3], "train"), 20);
+    }
+
     #[test]
     fn thread_tid_frame_names() {
         assert!(frame_matches_thread_tid("thread-42", 42));
@@ -7,12 +7,14 @@ use dioxus::prelude::*;
 
 use crate::api::{ApiClient, MagicGroup, MagicItem};
 use crate::components::colors::colors;
+use crate::components::health_indicator::HealthIndicator;
 use crate::hooks::use_api;
 use crate::state::agent::AGENT_PANEL_OPEN;
 use crate::state::commands::{
     Cell, EvalState, FloatingResult, COMMAND_INPUT, COMMAND_PANEL_OPEN, EVAL_HISTORY,
     SHORTCUTS_HELP_OPEN,
 };
+use crate::state::console::{toggle_console, CONSOLE_AVAILABLE, CONSOLE_OPEN};
 
 /// Flatten groups into searchable items
 fn flatten_magics(groups: &[MagicGroup]) -> Vec<(String, MagicItem)> {
@@ -287,6 +289,18 @@ pub fn CommandBar(on_execute_done: EventHandler<FloatingResult>) -> Element {
                 },
                 "Investigate"
             }
+            if *CONSOLE_AVAILABLE.read() != Some(false) {
+                button {
+                    class: if *CONSOLE_OPEN.read() {
+                        "shrink-0 px-2.5 py-2 rounded-lg text-sm font-medium bg-gray-900 text-gray-100 border border-gray-900"
+                    } else {
+                        "shrink-0 px-2.5 py-2 rounded-lg text-sm font-medium text-gray-600 hover:bg-gray-100 border border-gray-300"
+                    },
+                    title: "Python console (`)",
+                    onclick: move |_| toggle_console(),
+                    "Console"
+                }
+            }
             button {
                 class: "shrink-0 px-2.5 py-2 rounded-lg text-sm font-medium text-gray-600 hover:bg-gray-100 border border-gray-300",
                 title: "Keyboard shortcuts",
@@ -345,6 +359,7 @@ pub fn CommandBar(on_execute_done: EventHandler<FloatingResult>) -> Element {
                     }
                 }
             }
+            HealthIndicator {}
         }
     }
 }
@@ -0,0 +1,165 @@
+//! Header health pill: live `/healthz` status with a detail popover.
+
+use dioxus::prelude::*;
+
+use crate::api::ApiClient;
+use crate::hooks::{use_health_poll_tick, use_page_visible};
+use crate::state::health::{format_elapsed, HealthLevel, TARGET_HEALTH};
+
+const HEALTH_POLL_MS: u32 = 5_000;
+/// A probe still pending after this long counts as a failure.
+const HEALTH_TIMEOUT_MS: f64 = 8_000.0;
+
+fn pill_class(level: HealthLevel) -> &'static str {
+    match level {
+        HealthLevel::Unknown => "border-gray-300 bg-gray-50 text-gray-600",
+        HealthLevel::Ok => "border-green-300 bg-green-50 text-green-800",
+        HealthLevel::Degraded => "border-yellow-300 bg-yellow-50 text-yellow-800",
+        HealthLevel::Unreachable => "border-red-300 bg-red-50 text-red-800",
+    }
+}
+
+fn dot_class(level: HealthLevel) -> &'static str {
+    match level {
+        HealthLevel::Unknown => "bg-gray-400",
+        HealthLevel::Ok => "bg-green-500",
+        HealthLevel::Degraded => "bg-yellow-500",
+        HealthLevel::Unreachable => "bg-red-500",
+    }
+}
+
+fn stage_class(status: &str) -> &'static str {
+    match status {
+        "ok" => "text-green-700",
+        "starting" => "text-yellow-700",
+        _ => "text-red-700",
+    }
+}
+
+#[component]
+pub fn HealthIndicator() -> Element {
+    let visible = use_page_visible();
+    let tick = use_health_poll_tick(HEALTH_POLL_MS, Some(visible));
+    let mut in_flight = use_signal(|| None::<f64>);
+    // Bumped by reconnect so a hung probe's late answer is ignored.
+    let mut generation = use_signal(|| 0u32);
+    let mut open = use_signal(|| false);
+
+    let mut probe = move |force: bool| {
+        let now = js_sys::Date::now();
+        if force {
+            let bumped = *generation.peek() + 1;
+            generation.set(bumped);
+            in_flight.set(None);
+        }
+        if let Some(started) = *in_flight.peek() {
+            if now - started > HEALTH_TIMEOUT_MS {
+                let next = TARGET_HEALTH
+                    .peek()
+                    .on_failure(format!("no response for {}", format_elapsed(now - started)));
+                *TARGET_HEALTH.write() = next;
+            }
+            return;
+        }
+        in_flight.set(Some(now));
+        let this_generation = *generation.peek();
+        spawn(async move {
+            let result = ApiClient::new().fetch_health().await;
+            if *generation.peek() != this_generation {
+                return;
+            }
+            let next = match result {
+                Ok((report, latency_ms)) => {
+                    TARGET_HEALTH
+                        .peek()
+                        .on_success(report, latency_ms, js_sys::Date::now())
+                }
+                Err(e) => TARGET_HEALTH.peek().on_failure(e.display_message()),
+            };
+            *TARGET_HEALTH.write() = next;
+            in_flight.set(None);
+        });
+    };
+
+    use_effect(move || {
+        let _ = tick();
+        probe(false);
+    });
+
+    let health = TARGET_HEALTH.read().clone();
+    let now = js_sys::Date::now();
+    let summary = health.summary(now);
+    let last_ok = health
+        .last_ok_ms
+        .map(|at| format!("{} ago", format_elapsed(now - at)))
+        .unwrap_or_else(|| "never".to_string());
+    let latency = health
+        .latency_ms
+        .map(|ms| format!("{ms:.0} ms"))
+        .unwrap_or_else(|| "—".to_string());
+
+    rsx! {
+        div {
+            class: "relative shrink-0",
+            button {
+                class: format!(
+                    "inline-flex items-center gap-1.5 px-2.5 py-1.5 rounded-full border text-xs font-medium tabular-nums whitespace-nowrap {}",
+                    pill_class(health.level),
+                ),
+                title: "Target health (click for details)",
+                onclick: move |_| open.set(!open()),
+                span { class: format!("w-2 h-2 rounded-full {}", dot_class(health.level)) }
+                "{summary}"
+            }
+            if open() {
+                div {
+                    class: "fixed inset-0 z-[9996]",
+                    onclick: move |_| open.set(false),
+                }
+                div {
+                    class: "absolute top-full right-0 mt-1 w-72 p-3 bg-white border border-gray-200 rounded-lg shadow-lg z-[9997] text-xs space-y-2",
+                    div { class: "font-medium text-gray-800", "Target health" }
+                    div {
+                        class: "grid grid-cols-2 gap-x-2 gap-y-1 text-gray-600",
+                        span { "Last latency" }
+                        span { class: "tabular-nums text-gray-800", "{latency}" }
+                        span { "Last success" }
+                        span { class: "tabular-nums text-gray-800", "{last_ok}" }
+                    }
+                    if health.stages.is_empty() {
+                        div { class: "text-gray-500", "No readiness data yet" }
+                    } else {
+                        div {
+                            class: "border-t border-gray-100 pt-2 space-y-1",
+                            for stage in health.stages.iter() {
+                                div {
+                                    key: "{stage.name}",
+                                    class: "flex items-start justify-between gap-2",
+                                    span { class: "font-mono text-gray-700", "{stage.name}" }
+                                    span {
+                                        class: format!("text-right {}", stage_class(&stage.status)),
+                                        "{stage.status}"
+                                        if let Some(detail) = stage.detail.as_ref() {
+                                            span { class: "block text-gray-500", "{detail}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if let Some(error) = health.error.as_ref() {
+                        div { class: "text-red-700 break-words", "{error}" }
+                    }
+                    if health.level == HealthLevel::Unreachable {
+                        div { class: "text-gray-500", "Live panels are paused until the target responds." }
+                    }
+                    button {
+                        class: "w-full px-2 py-1.5 rounded-md border border-gray-300 text-gray-700 hover:bg-gray-50",
+                        onclick: move |_| probe(true),
+                        "Reconnect"
+                    }
+                }
+            }
+        }
+    }
+}
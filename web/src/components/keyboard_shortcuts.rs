@@ -1,4 +1,4 @@
-//! Global keyboard shortcuts: ⌘K command palette and ? help overlay.
+//! Global keyboard shortcuts: ⌘K command palette, ` console and ? help overlay.
 
 use dioxus::prelude::*;
 use std::cell::RefCell;
@@ -9,6 +9,7 @@ use wasm_bindgen::JsCast;
 use crate::components::icon::Icon;
 use crate::state::agent::AGENT_PANEL_OPEN;
 use crate::state::commands::{COMMAND_PANEL_OPEN, SHORTCUTS_HELP_OPEN};
+use crate::state::console::{toggle_console, CONSOLE_OPEN};
 use crate::state::source_viewer::{close_source_viewer, source_viewer_open};
 
 #[component]
@@ -62,6 +63,10 @@ fn handle_global_key(e: &web_sys::KeyboardEvent) -> bool {
             *COMMAND_PANEL_OPEN.write() = false;
             return true;
         }
+        if *CONSOLE_OPEN.read() {
+            *CONSOLE_OPEN.write() = false;
+            return true;
+        }
         if *AGENT_PANEL_OPEN.read() {
             *AGENT_PANEL_OPEN.write() = false;
             return true;
@@ -87,6 +92,12 @@ fn handle_global_key(e: &web_sys::KeyboardEvent) -> bool {
         return true;
     }
 
+    if e.key() == "`" && !mod_key && !text_input_focused() {
+        toggle_console();
+        *SHORTCUTS_HELP_OPEN.write() = false;
+        return true;
+    }
+
     if e.key() == "?" && !text_input_focused() {
         *SHORTCUTS_HELP_OPEN.write() = !*SHORTCUTS_HELP_OPEN.read();
         return true;
@@ -137,8 +148,18 @@ pub fn ShortcutsHelpOverlay() -> Element {
                         items: &[
                             ("⌘K / Ctrl+K", "Open command palette"),
                             ("⌘J / Ctrl+J", "Toggle Investigate overlay (diagnostic agent)"),
+                            ("`", "Toggle Python console"),
                             ("?", "Toggle this help"),
-                            ("Esc", "Close palette / investigate / help / source preview"),
+                            ("Esc", "Close palette / console / investigate / help / source preview"),
+                        ],
+                    }
+                    ShortcutSection {
+                        title: "Python console",
+                        items: &[
+                            ("Enter", "Run"),
+                            ("Shift+Enter", "New line"),
+                            ("Tab", "Complete"),
+                            ("↑ / ↓", "History"),
                         ],
                     }
                     ShortcutSection {
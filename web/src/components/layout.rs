@@ -6,6 +6,8 @@ use dioxus::prelude::*;
 
 use crate::api::ApiClient;
 use crate::components::agent::{AgentPanel, LlmSettingsOverlay};
+use crate::components::api_error_toasts::ApiErrorToasts;
+use crate::components::console_drawer::ConsoleDrawer;
 use crate::components::global_command_panel::{
     CommandBar, FloatingResultToast, GlobalCommandPanel,
 };
@@ -13,6 +15,7 @@ use crate::components::icon::Icon;
 use crate::components::keyboard_shortcuts::{GlobalShortcutInstaller, ShortcutsHelpOverlay};
 use crate::components::page_context_sync::PageContextSync;
 use crate::components::sidebar::Sidebar;
+use crate::components::snapshot_banner::SnapshotBanner;
 use crate::components::ui_task_runtime::UiTaskRuntime;
 use crate::state::agent::load_agent_panel_width;
 use crate::state::commands::{FloatingResult, COMMAND_PANEL_OPEN};
@@ -55,9 +58,13 @@ pub fn AppLayout(
         }
         ShortcutsHelpOverlay {}
         LlmSettingsOverlay {}
+        if !compact {
+            ConsoleDrawer {}
+        }
         FloatingResultToast {
             result: floating_result,
         }
+        ApiErrorToasts {}
 
         div {
             class: "flex h-screen bg-gray-50 overflow-hidden",
@@ -81,6 +88,7 @@ pub fn AppLayout(
             div {
                 class: "flex-1 flex flex-col min-w-0 min-h-0",
                 if !compact {
+                    SnapshotBanner {}
                     CommandBar {
                         on_execute_done: move |r| *floating_result.write() = Some(r),
                     }
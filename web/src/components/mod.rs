@@ -12,8 +12,17 @@
 //! - **collapsible_card** / **card_view** / **callstack_view** / **value_list** — Domain helpers.
 //! - **timeline_viewer** — Native Chrome trace timeline + Perfetto export.
 //! - **flamegraph** — Native flamegraph visualizations.
+//! - **trace_compare** — Spans page baseline-vs-current window comparison.
+//! - **trace_chips** — Spans page quick-filter chips from the loaded tree.
+//! - **profile_compare** — Profiling Compare mode, a differential flamegraph of two snapshots.
+//! - **span_detail** — Spans page side panel with a span's raw attributes.
+//! - **report_button** — Export the current page as a static HTML report.
+//! - **health_indicator** — Header pill for target health (`/healthz`).
+//! - **api_error_toasts** — Global toasts for failed API calls, with Retry for reads.
+//! - **snapshot_banner** — Read-only notice when serving an archive (`/apis/snapshot`).
 
 pub mod agent;
+pub mod api_error_toasts;
 pub mod app_overlays;
 pub mod callstack_view;
 pub mod card;
@@ -21,11 +30,13 @@ pub mod card_view;
 pub mod collapsible_card;
 pub mod colors;
 pub mod common;
+pub mod console_drawer;
 pub mod cpu_threads_table;
 pub mod data;
 pub mod dataframe_view;
 pub mod flamegraph;
 pub mod global_command_panel;
+pub mod health_indicator;
 pub mod icon;
 pub mod investigation_context_hint;
 pub mod keyboard_shortcuts;
@@ -36,16 +47,22 @@ pub mod overlay_shell;
 pub mod page;
 pub mod page_context_sync;
 pub mod poll_status;
+pub mod profile_compare;
 pub mod profile_snapshot_bar;
 pub mod profiling;
 pub mod profiling_sidebar_hint;
+pub mod report_button;
 pub mod rl;
 pub mod sidebar;
+pub mod snapshot_banner;
 pub mod source_viewer;
+pub mod span_detail;
 pub mod span_timeline;
 pub mod stat_card;
 pub mod table_view;
 pub mod timeline_viewer;
+pub mod trace_chips;
+pub mod trace_compare;
 pub mod ui_task_runtime;
 pub mod value_list;
 pub mod workspace;
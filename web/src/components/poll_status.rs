@@ -3,7 +3,10 @@
 use dioxus::prelude::*;
 
 use crate::components::icon::Icon;
-use crate::hooks::use_page_visible;
+use crate::hooks::{use_page_visible, AutoRefresh};
+use crate::state::auto_refresh::{
+    auto_refresh_label, set_auto_refresh_secs, AUTO_REFRESH_CHOICES, AUTO_REFRESH_SECS,
+};
 
 #[component]
 pub fn PollStatusBar(interval_secs: u32, poll_tick: u32) -> Element {
@@ -47,6 +50,58 @@ fn format_local_time() -> String {
     format!("{hours:02}:{minutes:02}:{seconds:02}")
 }
 
+/// Last refresh time, interval picker and a failure badge for pages driven
+/// by [`crate::hooks::use_auto_refresh`].
+#[component]
+pub fn AutoRefreshControl(refresh: AutoRefresh) -> Element {
+    let visible = use_page_visible();
+    let mut last_updated = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        let _ = refresh.tick();
+        last_updated.set(Some(format_local_time()));
+    });
+
+    let secs = AUTO_REFRESH_SECS();
+    let status = match last_updated.read().clone() {
+        Some(at) if secs > 0 && !visible() => format!("Updated {at} · paused in background"),
+        Some(at) => format!("Updated {at}"),
+        None => "Not loaded yet".to_string(),
+    };
+
+    rsx! {
+        div { class: "flex items-center gap-2",
+            if refresh.failed() {
+                span {
+                    class: "px-1.5 py-0.5 rounded text-[11px] font-medium bg-red-50 text-red-700 border border-red-200 whitespace-nowrap",
+                    title: "A request failed; auto refresh keeps retrying",
+                    "last refresh failed"
+                }
+            }
+            span { class: "text-[11px] text-gray-500 tabular-nums whitespace-nowrap",
+                "{status}"
+            }
+            label {
+                class: "flex items-center gap-1 text-[11px] text-gray-500",
+                title: "Auto refresh interval (saved in this browser)",
+                Icon { icon: &icondata::AiReloadOutlined, class: "w-3.5 h-3.5" }
+                select {
+                    class: "border border-gray-300 rounded px-1 py-0.5 bg-white text-gray-700",
+                    value: "{secs}",
+                    onchange: move |e| {
+                        if let Ok(secs) = e.value().parse() {
+                            set_auto_refresh_secs(secs);
+                        }
+                    },
+                    for choice in AUTO_REFRESH_CHOICES {
+                        option { value: "{choice}", {auto_refresh_label(choice)} }
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Status line for manually refreshed pages (Cluster, Spans, etc.).
 #[component]
 pub fn ManualRefreshStatus(refresh_tick: u32) -> Element {
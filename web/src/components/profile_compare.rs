@@ -0,0 +1,145 @@
+//! Compare mode of the Profiling page: a differential flamegraph of two
+//! captured snapshots, folded client-side and rendered by the server as an
+//! interactive SVG (click to zoom, Search / Ctrl+F to highlight frames).
+
+use dioxus::prelude::*;
+
+use crate::api::ApiClient;
+use crate::components::common::LoadingState;
+use crate::components::flamegraph::diff::folded_lines;
+use crate::components::profiling::{ProfilingErrorPanel, TimelinePlaceholder};
+use crate::hooks::use_app_resource;
+use crate::state::profile_snapshots::{snapshot_label, PROFILE_DIFF_BASELINE, PROFILE_SNAPSHOTS};
+
+const SELECT_CLASS: &str =
+    "min-w-0 max-w-xs px-2 py-1 text-xs rounded-md border border-gray-300 bg-white text-gray-700";
+
+#[component]
+pub fn ProfileCompare() -> Element {
+    // Newest snapshot against the chosen diff baseline, else the one before it.
+    let mut current = use_signal(|| PROFILE_SNAPSHOTS.peek().first().map(|s| s.id));
+    let mut baseline = use_signal(|| {
+        let snapshots = PROFILE_SNAPSHOTS.peek();
+        let newest = snapshots.first().map(|s| s.id);
+        PROFILE_DIFF_BASELINE
+            .peek()
+            .filter(|id| Some(*id) != newest && snapshots.iter().any(|s| s.id == *id))
+            .or_else(|| snapshots.get(1).map(|s| s.id))
+    });
+    let mut normalize = use_signal(|| true);
+
+    let svg = use_app_resource(move || {
+        let (baseline_id, current_id, normalize) = (baseline(), current(), normalize());
+        let pair = {
+            let snapshots = PROFILE_SNAPSHOTS.peek();
+            let find = |id: Option<u64>| id.and_then(|id| snapshots.iter().find(|s| s.id == id));
+            find(baseline_id).zip(find(current_id)).map(|(b, c)| {
+                (
+                    folded_lines(&b.payload),
+                    folded_lines(&c.payload),
+                    format!("{} → {}", snapshot_label(b), snapshot_label(c)),
+                )
+            })
+        };
+        async move {
+            let Some((before, after, title)) = pair else {
+                return Ok(None);
+            };
+            ApiClient::new()
+                .get_profile_diff_svg(before, after, normalize, Some(title))
+                .await
+                .map(Some)
+        }
+    });
+
+    let snapshots = PROFILE_SNAPSHOTS.read().clone();
+    if snapshots.len() < 2 {
+        return rsx! {
+            TimelinePlaceholder {
+                title: "Compare snapshots",
+                hint: "Capture at least two snapshots in a flamegraph view, e.g. before and after a change.".to_string(),
+            }
+        };
+    }
+
+    let options = |selected: Option<u64>| {
+        snapshots
+            .iter()
+            .map(|s| {
+                (
+                    s.id,
+                    format!("{} · {}", s.profiler, snapshot_label(s)),
+                    selected == Some(s.id),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let parse_id = |value: String| value.parse::<u64>().ok();
+
+    rsx! {
+        div { class: "flex flex-col flex-1 min-h-0 min-w-0",
+            div { class: "flex flex-wrap items-center gap-3 border-b border-gray-200 bg-gray-50/80 px-4 py-3 text-xs text-gray-600",
+                label { class: "flex items-center gap-1.5",
+                    "Baseline"
+                    select {
+                        class: SELECT_CLASS,
+                        onchange: move |e| baseline.set(parse_id(e.value())),
+                        for (id, label, selected) in options(baseline()) {
+                            option { value: "{id}", selected, "{label}" }
+                        }
+                    }
+                }
+                label { class: "flex items-center gap-1.5",
+                    "Current"
+                    select {
+                        class: SELECT_CLASS,
+                        onchange: move |e| current.set(parse_id(e.value())),
+                        for (id, label, selected) in options(current()) {
+                            option { value: "{id}", selected, "{label}" }
+                        }
+                    }
+                }
+                label {
+                    class: "flex items-center gap-1.5",
+                    title: "Scale the baseline to the current total so captures of different lengths compare by share",
+                    input {
+                        r#type: "checkbox",
+                        checked: normalize(),
+                        onchange: move |e| normalize.set(e.checked()),
+                    }
+                    "Normalize"
+                }
+                span { class: "ml-auto text-gray-400",
+                    "Widths follow the current snapshot · "
+                    span { class: "text-red-600", "red grew" }
+                    " · "
+                    span { class: "text-blue-600", "blue shrank" }
+                    " · click to zoom, Ctrl+F to search"
+                }
+            }
+            div { class: "flex-1 min-h-0 overflow-auto p-4",
+                match &*svg.read() {
+                    None => rsx! { LoadingState { message: Some("Rendering differential flamegraph…".to_string()) } },
+                    // An iframe so the SVG's own search / zoom script runs.
+                    Some(Ok(Some(svg))) => rsx! {
+                        iframe {
+                            class: "w-full h-full min-w-[1200px] min-h-[480px] border-0 bg-white",
+                            title: "Differential flamegraph",
+                            sandbox: "allow-scripts allow-modals",
+                            srcdoc: "{svg}",
+                        }
+                    },
+                    Some(Ok(None)) => rsx! {
+                        p { class: "text-sm text-gray-500", "Pick a baseline and a current snapshot." }
+                    },
+                    Some(Err(err)) => rsx! {
+                        ProfilingErrorPanel {
+                            title: "Diff Error".to_string(),
+                            error: err.display_message(),
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
@@ -2,9 +2,10 @@
 
 use dioxus::prelude::*;
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, TraceSource};
 use crate::components::timeline_viewer::TimelineViewer;
 use crate::hooks::use_app_resource;
+use crate::state::profiling::TRACE_SERVER_FILTERS;
 
 use super::sections::{ProfilingErrorPanel, TimelinePanel};
 
@@ -13,6 +14,7 @@ fn ChromeTimelineResource(
     timeline: Resource<Result<String, crate::utils::error::AppError>>,
     empty_message: String,
     error_title: String,
+    #[props(optional)] perfetto_url: Option<String>,
 ) -> Element {
     match timeline.suspend()?() {
         Ok(json) => rsx! {
@@ -20,6 +22,7 @@ fn ChromeTimelineResource(
                 TimelineViewer {
                     trace_json: json,
                     empty_message: Some(empty_message),
+                    perfetto_url,
                 }
             }
         },
@@ -33,11 +36,21 @@ fn ChromeTimelineResource(
 }
 
 #[component]
-pub fn TraceChromeTimelineLoader(reload_key: i32, limit: usize) -> Element {
+pub fn TraceChromeTimelineLoader(reload_key: i32, limit: usize, source: TraceSource) -> Element {
+    let filters = TRACE_SERVER_FILTERS.read().clone();
+    // The combined export has no protobuf form.
+    let perfetto_url = (source == TraceSource::Spans)
+        .then(|| ApiClient::chrome_tracing_proto_url(Some(limit), &filters, true).ok())
+        .flatten();
     let timeline = use_app_resource(move || {
         let _ = reload_key;
         let lim = limit;
-        async move { ApiClient::new().get_chrome_tracing_json(Some(lim)).await }
+        let filters = TRACE_SERVER_FILTERS.read().clone();
+        async move {
+            ApiClient::new()
+                .get_chrome_tracing_json(Some(lim), &filters, true, source)
+                .await
+        }
     });
 
     rsx! {
@@ -45,6 +58,7 @@ pub fn TraceChromeTimelineLoader(reload_key: i32, limit: usize) -> Element {
             timeline,
             empty_message: "Timeline data is empty. Make sure the profiler has been executed.".to_string(),
             error_title: "Load Timeline Error".to_string(),
+            perfetto_url,
         }
     }
 }
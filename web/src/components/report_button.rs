@@ -0,0 +1,38 @@
+//! "Export report" action: snapshot the current page into a static HTML file.
+
+use dioxus::prelude::*;
+
+use crate::components::icon::Icon;
+use crate::utils::report::{download_html, Report};
+
+/// Calls `build` on click and downloads the rendered report.
+#[component]
+pub fn ExportReportButton(build: Callback<(), Report>) -> Element {
+    let mut error = use_signal(|| None::<String>);
+
+    rsx! {
+        button {
+            class: if error.read().is_some() {
+                "inline-flex items-center gap-1 px-2 py-1.5 text-xs rounded-md border border-red-300 bg-red-50 text-red-700"
+            } else {
+                "inline-flex items-center gap-1 px-2 py-1.5 text-xs rounded-md border border-gray-300 bg-white hover:bg-gray-50"
+            },
+            title: error.read().clone().unwrap_or_else(|| {
+                "Download a self-contained HTML snapshot of this page (print it to PDF from the browser)"
+                    .to_string()
+            }),
+            onclick: move |_| {
+                let report = build.call(());
+                match download_html(&report.filename(), &report.to_html()) {
+                    Ok(()) => error.set(None),
+                    Err(e) => {
+                        log::warn!("report export failed: {e}");
+                        error.set(Some(e));
+                    }
+                }
+            },
+            Icon { icon: &icondata::AiExportOutlined, class: "w-3.5 h-3.5" }
+            "Export report"
+        }
+    }
+}
@@ -2,15 +2,18 @@
 
 use dioxus::prelude::*;
 
-use crate::api::{ApiClient, ProfileResponse};
+use crate::api::{ApiClient, ProfileResponse, TraceSource};
 use crate::components::colors::colors;
-use crate::hooks::use_api_simple;
+use crate::hooks::{use_api_simple, use_config_option};
 use crate::state::profiling::{
-    show_profiling_feedback, PROFILING_CHROME_LIMIT, PROFILING_PPROF_FREQ, PROFILING_PYTORCH_STEPS,
-    PROFILING_PYTORCH_TIMELINE_RELOAD, PROFILING_RAY_TIMELINE_RELOAD, PROFILING_TORCH_ENABLED,
-    PROFILING_TRACE_RELOAD,
+    is_enabled_value, show_profiling_feedback, PROFILING_CHROME_LIMIT, PROFILING_PPROF_FREQ,
+    PROFILING_PYTORCH_STEPS, PROFILING_PYTORCH_TIMELINE_RELOAD, PROFILING_RAY_TIMELINE_RELOAD,
+    PROFILING_TORCH_ENABLED, PROFILING_TRACE_RELOAD, TRACE_SERVER_FILTERS,
 };
 
+const PENDING_SPINNER: &str =
+    "inline-block w-2.5 h-2.5 border border-blue-400 border-t-transparent rounded-full animate-spin";
+
 const PPROF_FREQ_VALUES: [i32; 6] = [0, 10, 100, 1000, 10000, 100000];
 
 fn pprof_freq_index(freq: i32) -> usize {
@@ -26,9 +29,15 @@ fn pprof_freq_index(freq: i32) -> usize {
 
 #[component]
 pub fn PprofControls(control_title_class: String, control_value_class: String) -> Element {
-    let freq = *PROFILING_PPROF_FREQ.read();
+    let option = use_config_option("probing.pprof.sample_freq");
+    let freq = option
+        .value()
+        .map(|v| v.trim().parse::<i32>().unwrap_or(0))
+        .unwrap_or_else(|| *PROFILING_PPROF_FREQ.read());
     let current_idx = pprof_freq_index(freq);
     let label = PPROF_FREQ_VALUES[current_idx];
+    let pending = option.pending();
+    let download_url = ApiClient::pprof_download_url().ok();
 
     rsx! {
         div {
@@ -39,6 +48,9 @@ pub fn PprofControls(control_title_class: String, control_value_class: String) -
                 div {
                     class: "{control_value_class} flex items-center justify-between",
                     span { "{label} Hz" }
+                    if pending {
+                        span { class: PENDING_SPINNER }
+                    }
                 }
                 input {
                     r#type: "range",
@@ -46,32 +58,34 @@ pub fn PprofControls(control_title_class: String, control_value_class: String) -
                     max: "5",
                     step: "1",
                     value: "{current_idx}",
-                    class: "w-full accent-blue-500",
+                    disabled: pending,
+                    class: if pending { "w-full accent-blue-500 opacity-50 cursor-wait" } else { "w-full accent-blue-500" },
                     onchange: move |ev| {
-                        if let Ok(idx) = ev.value().parse::<usize>() {
-                            if idx < PPROF_FREQ_VALUES.len() {
-                                let mapped = PPROF_FREQ_VALUES[idx];
-                                let previous = *PROFILING_PPROF_FREQ.read();
-                                *PROFILING_PPROF_FREQ.write() = mapped;
-                                let expr = if mapped <= 0 {
-                                    "set probing.pprof.sample_freq=;".to_string()
-                                } else {
-                                    format!("set probing.pprof.sample_freq={mapped};")
-                                };
-                                spawn(async move {
-                                    match ApiClient::new().execute_query(&expr).await {
-                                        Ok(_) => show_profiling_feedback("Setting applied", false),
-                                        Err(err) => {
-                                            *PROFILING_PPROF_FREQ.write() = previous;
-                                            show_profiling_feedback(err.display_message(), true);
-                                        }
-                                    }
-                                });
-                            }
+                        if let Some(&mapped) = ev
+                            .value()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|idx| PPROF_FREQ_VALUES.get(idx))
+                        {
+                            option.set(if mapped <= 0 { String::new() } else { mapped.to_string() });
                         }
                     },
                 }
             }
+            if let Some(href) = download_url {
+                a {
+                    class: format!(
+                        "block w-full px-2 py-1.5 text-xs font-medium text-center rounded border border-{} text-{} hover:bg-{}",
+                        colors::SIDEBAR_INPUT_BORDER,
+                        colors::SIDEBAR_TEXT_SECONDARY,
+                        colors::SIDEBAR_HOVER_BG
+                    ),
+                    href: "{href}",
+                    download: "",
+                    title: "Save the CPU samples as a pprof profile for go tool pprof or Speedscope",
+                    "Download .pb.gz"
+                }
+            }
         }
     }
 }
@@ -83,37 +97,25 @@ pub fn TorchControls(
     toggle_disabled_class: String,
     toggle_label_class: String,
 ) -> Element {
-    let is_enabled = *PROFILING_TORCH_ENABLED.read();
+    let option = use_config_option("probing.torch.profiling");
+    let is_enabled = option
+        .value()
+        .map(|v| is_enabled_value(&v))
+        .unwrap_or_else(|| *PROFILING_TORCH_ENABLED.read());
+    let pending = option.pending();
 
     rsx! {
         div {
             class: "space-y-2",
             div { class: "{control_title_class}", "Torch Profiling" }
             label {
-                class: "flex items-center gap-2 cursor-pointer select-none",
+                class: if pending { "flex items-center gap-2 cursor-wait select-none opacity-60" } else { "flex items-center gap-2 cursor-pointer select-none" },
                 input {
                     r#type: "checkbox",
                     class: "sr-only",
                     checked: is_enabled,
-                    onchange: move |_| {
-                        let previous = *PROFILING_TORCH_ENABLED.read();
-                        let next = !previous;
-                        *PROFILING_TORCH_ENABLED.write() = next;
-                        let expr = if next {
-                            "set probing.torch.profiling=on;".to_string()
-                        } else {
-                            "set probing.torch.profiling=;".to_string()
-                        };
-                        spawn(async move {
-                            match ApiClient::new().execute_query(&expr).await {
-                                Ok(_) => show_profiling_feedback("Setting applied", false),
-                                Err(err) => {
-                                    *PROFILING_TORCH_ENABLED.write() = previous;
-                                    show_profiling_feedback(err.display_message(), true);
-                                }
-                            }
-                        });
-                    },
+                    disabled: pending,
+                    onchange: move |_| option.set(if is_enabled { "" } else { "on" }),
                 }
                 span {
                     class: if is_enabled { "{toggle_enabled_class}" } else { "{toggle_disabled_class}" },
@@ -128,6 +130,9 @@ pub fn TorchControls(
                 span { class: "{toggle_label_class}",
                     if is_enabled { "Enabled" } else { "Disabled" }
                 }
+                if pending {
+                    span { class: PENDING_SPINNER }
+                }
             }
         }
     }
@@ -138,8 +143,16 @@ pub fn TraceTimelineControls(
     control_title_class: String,
     control_value_class: String,
     input_class: String,
+    source: TraceSource,
 ) -> Element {
     let limit = *PROFILING_CHROME_LIMIT.read();
+    let download_url = ApiClient::chrome_tracing_download_url(
+        Some(limit),
+        &TRACE_SERVER_FILTERS.read(),
+        true,
+        source,
+    )
+    .ok();
 
     rsx! {
         div {
@@ -177,6 +190,20 @@ pub fn TraceTimelineControls(
                 },
                 "Reload Timeline"
             }
+            if let Some(href) = download_url {
+                a {
+                    class: format!(
+                        "block w-full px-2 py-1.5 text-xs font-medium text-center rounded border border-{} text-{} hover:bg-{}",
+                        colors::SIDEBAR_INPUT_BORDER,
+                        colors::SIDEBAR_TEXT_SECONDARY,
+                        colors::SIDEBAR_HOVER_BG
+                    ),
+                    href: "{href}",
+                    download: "",
+                    title: "Save the timeline as Chrome tracing JSON",
+                    "Download"
+                }
+            }
         }
     }
 }
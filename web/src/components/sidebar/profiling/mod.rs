@@ -4,6 +4,7 @@ use dioxus::prelude::*;
 use dioxus_router::{use_route, Link};
 use icondata::Icon as IconData;
 
+use crate::api::TraceSource;
 use crate::app::Route;
 use crate::components::colors::colors;
 use crate::components::icon::Icon;
@@ -20,9 +21,12 @@ fn profiling_view_icon(id: &str) -> &'static IconData {
     match id {
         "pprof" => &icondata::CgPerformance,
         "torch" => &icondata::AiFireOutlined,
+        "spanflame" => &icondata::AiApiOutlined,
         "trace" => &icondata::AiThunderboltOutlined,
+        "combined" => &icondata::AiMergeCellsOutlined,
         "pytorch" => &icondata::SiPytorch,
         "ray" => &icondata::AiClockCircleOutlined,
+        "compare" => &icondata::AiDiffOutlined,
         _ => &icondata::AiSearchOutlined,
     }
 }
@@ -144,6 +148,15 @@ fn ProfilingControlsPanel(current_view: String) -> Element {
                             control_title_class: control_title_class,
                             control_value_class: control_value_class,
                             input_class: input_class,
+                            source: TraceSource::Spans,
+                        }
+                    },
+                    "combined" => rsx! {
+                        TraceTimelineControls {
+                            control_title_class: control_title_class,
+                            control_value_class: control_value_class,
+                            input_class: input_class,
+                            source: TraceSource::Combined,
                         }
                     },
                     "pytorch" => rsx! {
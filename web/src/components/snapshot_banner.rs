@@ -0,0 +1,47 @@
+//! Banner shown when the server replays an archive (`probing serve-snapshot`)
+//! instead of a live process: data is frozen and controls are disabled.
+
+use dioxus::prelude::*;
+
+use crate::api::{ApiClient, SnapshotStatus};
+
+fn captured_at(ns: i64) -> String {
+    chrono::DateTime::from_timestamp_nanos(ns)
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+#[component]
+pub fn SnapshotBanner() -> Element {
+    let mut status = use_signal(SnapshotStatus::default);
+
+    use_effect(move || {
+        spawn(async move {
+            if let Ok(s) = ApiClient::new().fetch_snapshot().await {
+                status.set(s);
+            }
+        });
+    });
+
+    let status = status.read();
+    if !status.snapshot {
+        return rsx! {};
+    }
+    let captured = status
+        .captured_ns
+        .map(captured_at)
+        .unwrap_or_else(|| "unknown time".to_string());
+    let source = status.source.clone().unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "px-4 py-1.5 border-b border-amber-300 bg-amber-50 text-amber-900 text-xs flex items-center gap-2",
+            role: "status",
+            span { class: "font-semibold", "Snapshot mode (read-only)" }
+            span { "— captured {captured}" }
+            if !source.is_empty() {
+                span { class: "font-mono text-amber-700 truncate", title: "{source}", "{source}" }
+            }
+        }
+    }
+}
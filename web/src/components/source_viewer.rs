@@ -399,8 +399,9 @@ fn HighlightedSourceBlock(slice: SourceSlice, language: dioxus_code::Language) -
     }
 }
 
+/// Numbered lines of `slice`, with its highlight line marked.
 #[component]
-fn PlainSourceLines(slice: SourceSlice) -> Element {
+pub fn PlainSourceLines(slice: SourceSlice) -> Element {
     let highlight = slice.highlight_line;
     let rows: Vec<(usize, String)> = slice
         .text
@@ -0,0 +1,134 @@
+//! Side panel for the span clicked on the Spans page: timing, identifiers,
+//! raw attributes JSON and events.
+
+use dioxus::prelude::*;
+
+use crate::api::SpanInfo;
+use crate::components::icon::Icon;
+use crate::components::span_timeline::format_axis_label;
+
+/// `raw` pretty-printed when it is JSON, unchanged otherwise.
+pub fn pretty_json(raw: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(raw)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| raw.to_string())
+}
+
+#[component]
+pub fn SpanDetailPanel(span: SpanInfo, on_close: EventHandler<()>) -> Element {
+    let duration = span
+        .end_timestamp
+        .map(|end| format_axis_label((end - span.start_timestamp) as f64));
+    let attributes = span
+        .attributes
+        .as_deref()
+        .filter(|a| !a.trim().is_empty())
+        .map(pretty_json);
+    let parent = span
+        .parent_id
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "—".to_string());
+    let rows = [
+        ("trace", span.trace_id.to_string()),
+        ("span", span.span_id.to_string()),
+        ("parent", parent),
+        ("thread", span.thread_id.to_string()),
+        (
+            "phase",
+            span.phase.clone().unwrap_or_else(|| "—".to_string()),
+        ),
+        ("start", span.start_timestamp.to_string()),
+        (
+            "end",
+            span.end_timestamp
+                .map_or_else(|| "— (unfinished)".to_string(), |t| t.to_string()),
+        ),
+    ];
+
+    rsx! {
+        aside {
+            class: "fixed right-0 top-0 z-40 h-full w-full sm:w-[28rem] flex flex-col border-l border-gray-200 bg-white shadow-2xl",
+            aria_label: "Span details",
+            div { class: "flex items-center gap-2 px-4 py-3 border-b border-gray-200 bg-gray-50/80 shrink-0",
+                div { class: "flex-1 min-w-0",
+                    h2 { class: "text-sm font-semibold text-gray-900 truncate font-mono", "{span.name}" }
+                    p { class: "text-xs text-gray-500",
+                        if let Some(ref duration) = duration {
+                            "{duration}"
+                        } else {
+                            span { class: "text-amber-600", "unfinished" }
+                        }
+                        if let Some(ref location) = span.location {
+                            " · {location}"
+                        }
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "p-1.5 rounded-md border border-gray-200 text-gray-500 hover:bg-gray-100 hover:text-gray-800",
+                    title: "Close",
+                    onclick: move |_| on_close.call(()),
+                    Icon { icon: &icondata::AiCloseOutlined, class: "w-4 h-4" }
+                }
+            }
+            div { class: "flex-1 overflow-y-auto min-h-0 px-4 py-3 space-y-4 text-xs",
+                dl { class: "grid grid-cols-[5rem_1fr] gap-x-3 gap-y-1 font-mono",
+                    for (label, value) in rows {
+                        dt { class: "text-gray-500", "{label}" }
+                        dd { class: "text-gray-900 break-all", "{value}" }
+                    }
+                }
+                section {
+                    h3 { class: "mb-1 text-[11px] font-semibold uppercase tracking-wide text-gray-500",
+                        "Attributes"
+                    }
+                    if let Some(ref attributes) = attributes {
+                        pre { class: "p-2 rounded-md border border-gray-200 bg-slate-50 font-mono text-[11px] text-gray-800 whitespace-pre-wrap break-all",
+                            "{attributes}"
+                        }
+                    } else {
+                        p { class: "text-gray-400", "No attributes" }
+                    }
+                }
+                if !span.events.is_empty() {
+                    section {
+                        h3 { class: "mb-1 text-[11px] font-semibold uppercase tracking-wide text-gray-500",
+                            "Events ({span.events.len()})"
+                        }
+                        ul { class: "space-y-2",
+                            for event in span.events.iter() {
+                                li {
+                                    div { class: "flex items-baseline gap-2 font-mono",
+                                        span { class: "text-gray-900", "{event.name}" }
+                                        span { class: "text-gray-400",
+                                            "+{format_axis_label((event.timestamp - span.start_timestamp) as f64)}"
+                                        }
+                                    }
+                                    if let Some(ref attrs) = event.attributes {
+                                        if !attrs.trim().is_empty() {
+                                            pre { class: "mt-0.5 p-1.5 rounded bg-slate-50 font-mono text-[11px] text-gray-700 whitespace-pre-wrap break-all",
+                                                {pretty_json(attrs)}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_are_pretty_printed_when_json() {
+        assert_eq!(pretty_json(r#"{"a":1}"#), "{\n  \"a\": 1\n}");
+        assert_eq!(pretty_json("not json"), "not json");
+    }
+}
@@ -3,12 +3,17 @@
 //! Each visible tree row gets a timeline row on the left; expanding the tree
 //! grows the timeline stack so hierarchy and timing stay aligned.
 
+use std::fmt::Write as _;
+
 use dioxus::prelude::*;
 
 use crate::api::SpanInfo;
+use crate::utils::report::escape_html;
 
 const TIMELINE_LANE_PX: f64 = 148.0;
 const MIN_BAR_PX: f64 = 3.0;
+/// Attribute and event lines listed in a bar's tooltip before "…".
+const TOOLTIP_ITEMS: usize = 8;
 
 /// Nanosecond window covering all spans in the current tree.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -81,7 +86,11 @@ pub fn format_axis_label(duration_ns: f64) -> String {
 
 fn span_bar_style(phase: Option<&str>, active: bool) -> (&'static str, &'static str) {
     if active {
-        return ("bg-amber-200/80", "bg-amber-500");
+        // Open-ended: dashed, no rounded right edge, runs to the window end.
+        return (
+            "bg-amber-200/80",
+            "bg-amber-300/80 border border-r-0 border-dashed border-amber-600",
+        );
     }
     match phase {
         Some("forward") => ("bg-blue-200/70", "bg-blue-500"),
@@ -102,14 +111,55 @@ fn span_tooltip(span: &SpanInfo, window: TraceTimeWindow) -> String {
         .end_timestamp
         .map(|t| format_axis_label((t - span.start_timestamp) as f64))
         .unwrap_or_else(|| "active".to_string());
-    format!(
+    let mut tip = format!(
         "{}\nphase: {}\noffset: {} · end: {}\nduration: {}",
         span.name,
         span.phase.as_deref().unwrap_or("—"),
         start,
         end,
         dur,
-    )
+    );
+    let attributes = tooltip_attributes(span.attributes.as_deref().unwrap_or(""));
+    if !attributes.is_empty() {
+        tip.push_str("\nattributes:");
+        push_tooltip_items(&mut tip, attributes);
+    }
+    if !span.events.is_empty() {
+        let _ = write!(tip, "\nevents ({}):", span.events.len());
+        let events = span.events.iter().map(|e| {
+            let rel = format_axis_label((e.timestamp - span.start_timestamp) as f64);
+            format!("{} +{rel}", e.name)
+        });
+        push_tooltip_items(&mut tip, events.collect());
+    }
+    tip
+}
+
+/// `key = value` lines of a JSON attribute object; other text as one line.
+fn tooltip_attributes(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Vec::new();
+    }
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::Object(obj)) => obj
+            .iter()
+            .map(|(k, v)| match v {
+                serde_json::Value::String(s) => format!("{k} = {s}"),
+                _ => format!("{k} = {v}"),
+            })
+            .collect(),
+        _ => vec![raw.to_string()],
+    }
+}
+
+fn push_tooltip_items(tip: &mut String, items: Vec<String>) {
+    for item in items.iter().take(TOOLTIP_ITEMS) {
+        let _ = write!(tip, "\n  {item}");
+    }
+    if items.len() > TOOLTIP_ITEMS {
+        let _ = write!(tip, "\n  … {} more", items.len() - TOOLTIP_ITEMS);
+    }
 }
 
 #[component]
@@ -165,8 +215,8 @@ pub fn SpanTimelineLegend() -> Element {
                 span { "other" }
             }
             div { class: "inline-flex items-center gap-1",
-                span { class: "w-3 h-2 rounded-sm bg-amber-500 animate-pulse" }
-                span { "active" }
+                span { class: "w-3 h-2 rounded-l-sm bg-amber-300 border border-r-0 border-dashed border-amber-600 animate-pulse" }
+                span { "unfinished" }
             }
         }
     }
@@ -184,6 +234,11 @@ pub fn SpanTimelineBar(span: SpanInfo, window: TraceTimeWindow, depth: usize) ->
     let bar_left = left.min(lane_inner - MIN_BAR_PX).max(0.0);
     let bar_width = width.min(lane_inner - bar_left);
     let guide_left = indent.saturating_sub(6);
+    let (bar_shape, dot_bg) = if active {
+        ("rounded-l-sm", "bg-amber-500")
+    } else {
+        ("rounded-sm", bar_bg)
+    };
 
     rsx! {
         div {
@@ -203,11 +258,11 @@ pub fn SpanTimelineBar(span: SpanInfo, window: TraceTimeWindow, depth: usize) ->
             div { class: "relative h-[22px] flex-1 min-w-0 pr-1",
                 div { class: "absolute inset-y-[7px] inset-x-0 rounded-full {track_bg}" }
                 div {
-                    class: "absolute top-[5px] h-[12px] rounded-sm {bar_bg} shadow-sm",
+                    class: "absolute top-[5px] h-[12px] {bar_shape} {bar_bg} shadow-sm",
                     style: "left: {bar_left:.2}px; width: {bar_width:.2}px;",
                 }
                 div {
-                    class: "absolute top-[9px] w-1.5 h-1.5 rounded-full {bar_bg} ring-2 ring-white -translate-x-1/2",
+                    class: "absolute top-[9px] w-1.5 h-1.5 rounded-full {dot_bg} ring-2 ring-white -translate-x-1/2",
                     style: "left: {bar_left:.2}px;",
                 }
                 if active {
@@ -232,6 +287,67 @@ pub fn SpanTimelineSpacer() -> Element {
     }
 }
 
+/// Hex bar colors matching [`span_bar_style`], for exported SVG.
+fn span_bar_hex(phase: Option<&str>, active: bool) -> &'static str {
+    if active {
+        return "#f59e0b";
+    }
+    match phase {
+        Some("forward") => "#3b82f6",
+        Some("backward") => "#a855f7",
+        Some("optimizer") | Some("step") => "#f59e0b",
+        Some("idle") => "#9ca3af",
+        _ => "#10b981",
+    }
+}
+
+/// Offscreen render of the timeline lane as a standalone SVG: one labelled
+/// row per `(depth, span)`, bars positioned within `window`.
+pub fn timeline_svg(rows: &[(usize, &SpanInfo)], window: TraceTimeWindow) -> String {
+    const LABEL_W: f64 = 320.0;
+    const LANE_W: f64 = 640.0;
+    const ROW_H: f64 = 16.0;
+    const AXIS_H: f64 = 18.0;
+    let height = AXIS_H + ROW_H * rows.len().max(1) as f64;
+    let range = window.range_ns() as f64;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {height}\" \
+         font-family=\"ui-monospace,monospace\" font-size=\"10\">",
+        w = LABEL_W + LANE_W,
+    );
+    for (frac, anchor) in [(0.0, "start"), (0.5, "middle"), (1.0, "end")] {
+        let _ = write!(
+            out,
+            "<text x=\"{:.1}\" y=\"12\" text-anchor=\"{anchor}\" fill=\"#6b7280\">{}</text>",
+            LABEL_W + frac * LANE_W,
+            format_axis_label(range * frac),
+        );
+    }
+    for (i, (depth, span)) in rows.iter().enumerate() {
+        let y = AXIS_H + i as f64 * ROW_H;
+        let start = (span.start_timestamp - window.start_ns) as f64 / range;
+        let end = (span.end_timestamp.unwrap_or(window.end_ns) - window.start_ns) as f64 / range;
+        let x = LABEL_W + start.clamp(0.0, 1.0) * LANE_W;
+        let w = ((end - start).max(0.0) * LANE_W)
+            .max(1.0)
+            .min(LABEL_W + LANE_W - x);
+        let _ = write!(
+            out,
+            "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"#111827\">{}</text>\
+             <rect x=\"{x:.1}\" y=\"{:.1}\" width=\"{w:.1}\" height=\"{:.1}\" rx=\"2\" fill=\"{}\"/>",
+            4.0 + *depth as f64 * 10.0,
+            y + 11.0,
+            escape_html(&span.name),
+            y + 3.0,
+            ROW_H - 6.0,
+            span_bar_hex(span.phase.as_deref(), span.end_timestamp.is_none()),
+        );
+    }
+    out.push_str("</svg>");
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,6 +364,9 @@ mod tests {
             phase: None,
             location: None,
             attributes: None,
+            cpu_time_ns: None,
+            ctx_switches: None,
+            links: None,
             children: vec![],
             events: vec![],
         }
@@ -277,4 +396,49 @@ mod tests {
         assert!((w.width_px(0, Some(1000)) - TIMELINE_LANE_PX).abs() < 0.01);
         assert!(w.width_px(0, Some(1)) >= MIN_BAR_PX);
     }
+
+    #[test]
+    fn tooltip_lists_attributes_and_events() {
+        let w = TraceTimeWindow {
+            start_ns: 0,
+            end_ns: 1000,
+        };
+        let with_details = SpanInfo {
+            attributes: Some(r#"{"step": 3, "mode": "eval"}"#.into()),
+            events: (0..10)
+                .map(|i| crate::api::EventInfo {
+                    name: format!("e{i}"),
+                    timestamp: 100 + i,
+                    attributes: None,
+                })
+                .collect(),
+            ..span(100, None)
+        };
+        let tip = span_tooltip(&with_details, w);
+        assert!(tip.contains("duration: active"));
+        assert!(tip.contains("attributes:\n  mode = eval\n  step = 3"));
+        assert!(tip.contains("events (10):\n  e0 +0ns"));
+        assert!(tip.ends_with("  … 2 more"));
+
+        assert!(!span_tooltip(&span(0, Some(5)), w).contains("attributes"));
+        assert_eq!(tooltip_attributes("plain text"), ["plain text"]);
+    }
+
+    #[test]
+    fn timeline_svg_places_bars_in_lane() {
+        let w = TraceTimeWindow {
+            start_ns: 0,
+            end_ns: 1000,
+        };
+        let child = SpanInfo {
+            name: "<fwd>".into(),
+            phase: Some("forward".into()),
+            ..span(500, Some(1000))
+        };
+        let svg = timeline_svg(&[(0, &span(0, Some(500))), (1, &child)], w);
+        assert!(svg.contains("<rect x=\"320.0\" y=\"21.0\" width=\"320.0\""));
+        assert!(svg.contains("<rect x=\"640.0\" y=\"37.0\" width=\"320.0\""));
+        assert!(svg.contains(">&lt;fwd&gt;</text>"));
+        assert!(svg.contains("fill=\"#3b82f6\""));
+    }
 }
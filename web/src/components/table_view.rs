@@ -1,15 +1,64 @@
 use dioxus::prelude::*;
 // Tailwind classes inlined for table view.
 
+/// Column a table is sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortState {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+/// Height of one row when `windowed`; cells do not wrap then.
+pub const WINDOW_ROW_PX: f64 = 40.0;
+/// Height of the scroll area of a `windowed` table.
+pub const WINDOW_VIEWPORT_PX: f64 = 600.0;
+/// Rows rendered above and below the visible ones.
+const WINDOW_OVERSCAN: usize = 10;
+
+/// Rows `first..last` to render when the scroll area of `viewport` px is
+/// scrolled to `scroll_top` over `rows` rows of `row_px` each.
+pub fn visible_rows(scroll_top: f64, viewport: f64, row_px: f64, rows: usize) -> (usize, usize) {
+    let first = ((scroll_top.max(0.0) / row_px) as usize).min(rows);
+    let shown = (viewport / row_px).ceil() as usize;
+    (
+        first.saturating_sub(WINDOW_OVERSCAN),
+        (first + shown + WINDOW_OVERSCAN).min(rows),
+    )
+}
+
+/// Rows are shown as given; with `on_sort`, headers are clickable and the
+/// column in `sort` carries an arrow. A `windowed` table scrolls in a fixed
+/// height and only puts the rows in view (plus some overscan) in the DOM.
+/// `on_row_click` gets the index into `data`. `titles`, shaped like `data`,
+/// gives cells a hover tooltip; empty strings add none.
 #[component]
 pub fn TableView(
     headers: Vec<String>,
     data: Vec<Vec<String>>,
+    #[props(optional)] titles: Option<Vec<Vec<String>>>,
     #[props(optional)] on_row_click: Option<EventHandler<usize>>,
+    #[props(optional)] sort: Option<SortState>,
+    #[props(optional)] on_sort: Option<EventHandler<usize>>,
+    #[props(optional)] windowed: bool,
 ) -> Element {
+    let sortable = on_sort.is_some();
+    let mut scroll_top = use_signal(|| 0.0f64);
+    let (first, last) = if windowed {
+        visible_rows(scroll_top(), WINDOW_VIEWPORT_PX, WINDOW_ROW_PX, data.len())
+    } else {
+        (0, data.len())
+    };
+    let pad_top = first as f64 * WINDOW_ROW_PX;
+    let pad_bottom = (data.len() - last) as f64 * WINDOW_ROW_PX;
     rsx! {
         div {
-            class: "w-full overflow-x-auto border border-gray-200 rounded-lg",
+            class: if windowed { "w-full overflow-auto border border-gray-200 rounded-lg" } else { "w-full overflow-x-auto border border-gray-200 rounded-lg" },
+            style: if windowed { format!("max-height: {WINDOW_VIEWPORT_PX}px") } else { String::new() },
+            onscroll: move |e| {
+                if windowed {
+                    scroll_top.set(e.data().scroll_top());
+                }
+            },
 
             table {
                 class: "w-full border-collapse table-auto",
@@ -18,17 +67,39 @@ pub fn TableView(
                     tr { class: "bg-gray-50 border-b border-gray-200 sticky top-0 z-10",
                         for (col_idx, header) in headers.iter().enumerate() {
                             th {
-                                class: format!("px-4 py-2 text-left font-semibold text-gray-700 border-r border-gray-200 bg-gray-50 {} {}", if col_idx == 0 { "sticky left-0 z-10" } else { "" }, ""),
+                                class: format!("px-4 py-2 text-left font-semibold text-gray-700 border-r border-gray-200 bg-gray-50 {} {}", if col_idx == 0 { "sticky left-0 z-10" } else { "" }, if sortable { "cursor-pointer select-none hover:bg-gray-100" } else { "" }),
+                                title: if sortable { "Sort by this column" } else { "" },
+                                aria_sort: match sort {
+                                    Some(s) if s.column == col_idx && s.ascending => "ascending",
+                                    Some(s) if s.column == col_idx => "descending",
+                                    _ => "none",
+                                },
+                                onclick: move |_| {
+                                    if let Some(cb) = on_sort {
+                                        cb.call(col_idx);
+                                    }
+                                },
                                 {header.clone()}
+                                match sort {
+                                    Some(s) if s.column == col_idx => rsx! {
+                                        span { class: "ml-1 text-blue-600", if s.ascending { "▲" } else { "▼" } }
+                                    },
+                                    _ => rsx! {},
+                                }
                             }
                         }
                     }
                 }
 
                 tbody {
-                    for (row_idx, row) in data.iter().enumerate() {
+                    if pad_top > 0.0 {
+                        tr { style: "height: {pad_top}px" }
+                    }
+                    for (row_idx, row) in data.iter().enumerate().skip(first).take(last - first) {
                         tr {
+                            key: "{row_idx}",
                             class: if row_idx % 2 == 0 { "bg-white hover:bg-gray-50" } else { "bg-gray-50 hover:bg-gray-100" },
+                            style: if windowed { format!("height: {WINDOW_ROW_PX}px") } else { String::new() },
                             onclick: move |_| {
                                 if let Some(cb) = on_row_click {
                                     cb.call(row_idx);
@@ -36,14 +107,40 @@ pub fn TableView(
                             },
                             for (cell_idx, cell) in row.iter().enumerate() {
                                 td {
-                                    class: format!("px-4 py-2 text-gray-700 border-r border-gray-200 {} {}", if cell_idx == 0 { "sticky left-0 z-[1]" } else { "" }, if cell_idx == 0 && row_idx % 2 == 0 { "bg-white" } else if cell_idx == 0 { "bg-gray-50" } else { "" }),
+                                    class: format!("px-4 py-2 text-gray-700 border-r border-gray-200 {} {} {}", if cell_idx == 0 { "sticky left-0 z-[1]" } else { "" }, if cell_idx == 0 && row_idx % 2 == 0 { "bg-white" } else if cell_idx == 0 { "bg-gray-50" } else { "" }, if windowed { "whitespace-nowrap" } else { "" }),
+                                    title: cell_title(titles.as_deref(), row_idx, cell_idx),
                                     {cell.clone()}
                                 }
                             }
                         }
                     }
+                    if pad_bottom > 0.0 {
+                        tr { style: "height: {pad_bottom}px" }
+                    }
                 }
             }
         }
     }
 }
+
+fn cell_title(titles: Option<&[Vec<String>]>, row: usize, cell: usize) -> String {
+    titles
+        .and_then(|t| t.get(row)?.get(cell).cloned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_covers_the_viewport_plus_overscan() {
+        assert_eq!(visible_rows(0.0, 400.0, 40.0, 1000), (0, 20));
+        // Scrolled to row 100.
+        assert_eq!(visible_rows(4000.0, 400.0, 40.0, 1000), (90, 120));
+        // Near the end, and past it after the rows shrank.
+        assert_eq!(visible_rows(39_800.0, 400.0, 40.0, 1000), (985, 1000));
+        assert_eq!(visible_rows(39_800.0, 400.0, 40.0, 30), (20, 30));
+        assert_eq!(visible_rows(0.0, 400.0, 40.0, 0), (0, 0));
+    }
+}
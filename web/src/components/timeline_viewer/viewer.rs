@@ -110,6 +110,10 @@ struct Selection {
 pub fn TimelineViewer(
     trace_json: String,
     #[props(optional)] empty_message: Option<String>,
+    /// Binary Perfetto trace of the same events; the Perfetto button loads
+    /// it instead of re-posting `trace_json`.
+    #[props(optional)]
+    perfetto_url: Option<String>,
 ) -> Element {
     let filter = use_signal(String::new);
     let mut viewport = use_signal(Viewport::full);
@@ -202,7 +206,11 @@ pub fn TimelineViewer(
                         zoom_pct,
                         in_overview,
                         on_export: move |_| {
-                            if let Err(err) = tracing_viewer::open_perfetto_window(&export_json) {
+                            let opened = match &perfetto_url {
+                                Some(url) => tracing_viewer::open_perfetto_window_url(url),
+                                None => tracing_viewer::open_perfetto_window(&export_json),
+                            };
+                            if let Err(err) = opened {
                                 log::warn!("Perfetto export failed: {err}");
                             }
                         },
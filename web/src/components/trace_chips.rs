@@ -0,0 +1,286 @@
+//! Quick-filter chips on the Spans page: the most frequent span names, kinds
+//! (phases) and threads of the loaded tree. Active chips live in the
+//! investigation context (`?chip=` in the URL); chips of one kind are OR-ed,
+//! different kinds AND-ed. "Apply server-side" turns them into
+//! [`TraceFilters`] for a refetch.
+
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+
+use crate::api::{SpanInfo, TraceFilters};
+use crate::state::investigation::{toggle_span_chip, INVESTIGATION_CONTEXT};
+use crate::state::profiling::TRACE_SERVER_FILTERS;
+
+/// Chips shown per kind.
+pub const TOP_CHIPS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ChipKind {
+    Name,
+    Kind,
+    Thread,
+}
+
+impl ChipKind {
+    const ALL: [ChipKind; 3] = [ChipKind::Name, ChipKind::Kind, ChipKind::Thread];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            ChipKind::Name => "name",
+            ChipKind::Kind => "kind",
+            ChipKind::Thread => "thread",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ChipKind::Name => "Names",
+            ChipKind::Kind => "Kinds",
+            ChipKind::Thread => "Threads",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceChip {
+    pub kind: ChipKind,
+    pub value: String,
+}
+
+impl TraceChip {
+    /// `name:value` form stored in the investigation context and URL.
+    pub fn param(&self) -> String {
+        format!("{}:{}", self.kind.prefix(), self.value)
+    }
+
+    pub fn parse(param: &str) -> Option<Self> {
+        let (prefix, value) = param.split_once(':')?;
+        let kind = ChipKind::ALL.into_iter().find(|k| k.prefix() == prefix)?;
+        (!value.is_empty()).then(|| TraceChip {
+            kind,
+            value: value.to_string(),
+        })
+    }
+
+    fn matches(&self, span: &SpanInfo) -> bool {
+        match self.kind {
+            ChipKind::Name => span.name == self.value,
+            ChipKind::Kind => span.phase.as_deref() == Some(self.value.as_str()),
+            ChipKind::Thread => span.thread_id.to_string() == self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChipCount {
+    pub chip: TraceChip,
+    pub count: usize,
+}
+
+/// Top `n` names, kinds and threads by span count (ties by value), grouped by kind.
+pub fn top_chips(spans: &[SpanInfo], n: usize) -> Vec<ChipCount> {
+    fn walk(spans: &[SpanInfo], counts: &mut HashMap<TraceChip, usize>) {
+        for span in spans {
+            let mut bump = |kind, value: String| {
+                *counts.entry(TraceChip { kind, value }).or_default() += 1;
+            };
+            bump(ChipKind::Name, span.name.clone());
+            if let Some(phase) = span.phase.as_ref().filter(|p| !p.is_empty()) {
+                bump(ChipKind::Kind, phase.clone());
+            }
+            bump(ChipKind::Thread, span.thread_id.to_string());
+            walk(&span.children, counts);
+        }
+    }
+
+    let mut counts = HashMap::new();
+    walk(spans, &mut counts);
+    let mut all: Vec<ChipCount> = counts
+        .into_iter()
+        .map(|(chip, count)| ChipCount { chip, count })
+        .collect();
+    all.sort_by(|a, b| {
+        a.chip
+            .kind
+            .cmp(&b.chip.kind)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.chip.value.cmp(&b.chip.value))
+    });
+    ChipKind::ALL
+        .into_iter()
+        .flat_map(|kind| {
+            all.iter()
+                .filter(move |c| c.chip.kind == kind)
+                .take(n)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Whether `span` passes the active chips.
+pub fn span_matches_chips(span: &SpanInfo, chips: &[TraceChip]) -> bool {
+    ChipKind::ALL.into_iter().all(|kind| {
+        let mut of_kind = chips.iter().filter(|c| c.kind == kind).peekable();
+        of_kind.peek().is_none() || of_kind.any(|c| c.matches(span))
+    })
+}
+
+/// Active chips as pushdown filters for the span tree / chrome-tracing endpoints.
+pub fn chips_to_filters(chips: &[TraceChip]) -> TraceFilters {
+    let mut filters = TraceFilters::default();
+    for chip in chips {
+        match chip.kind {
+            ChipKind::Name => filters.names.push(chip.value.clone()),
+            ChipKind::Kind => filters.phases.push(chip.value.clone()),
+            ChipKind::Thread => filters.thread_ids.extend(chip.value.parse::<i64>().ok()),
+        }
+    }
+    filters
+}
+
+/// Active chips parsed from the investigation context.
+pub fn active_chips() -> Vec<TraceChip> {
+    INVESTIGATION_CONTEXT
+        .read()
+        .chips
+        .iter()
+        .filter_map(|c| TraceChip::parse(c))
+        .collect()
+}
+
+#[component]
+pub fn TraceChipBar(chips: Memo<Vec<ChipCount>>) -> Element {
+    let active = active_chips();
+    let server = TRACE_SERVER_FILTERS.read().clone();
+    let chips = chips.read();
+    if chips.is_empty() && server.is_empty() {
+        return rsx! {};
+    }
+    rsx! {
+        div { class: "border-b border-gray-200 px-4 py-2 flex flex-col gap-1 text-xs",
+            for kind in ChipKind::ALL {
+                if chips.iter().any(|c| c.chip.kind == kind) {
+                    div { class: "flex flex-wrap items-center gap-1",
+                        span { class: "w-14 shrink-0 text-gray-500", "{kind.label()}" }
+                        for c in chips.iter().filter(|c| c.chip.kind == kind) {
+                            {
+                                let param = c.chip.param();
+                                let toggled = param.clone();
+                                let selected = active.contains(&c.chip);
+                                rsx! {
+                                    button {
+                                        key: "{param}",
+                                        class: if selected {
+                                            "px-2 py-0.5 rounded-full border border-blue-300 bg-blue-50 text-blue-700"
+                                        } else {
+                                            "px-2 py-0.5 rounded-full border border-gray-200 bg-white text-gray-700 hover:bg-gray-50"
+                                        },
+                                        title: "Toggle filter {param}",
+                                        onclick: move |_| toggle_span_chip(&toggled),
+                                        span { class: "font-mono", "{c.chip.value}" }
+                                        span { class: "ml-1 text-gray-400", "{c.count}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "flex flex-wrap items-center gap-2",
+                if !active.is_empty() {
+                    button {
+                        class: "px-2 py-0.5 rounded-md border border-gray-300 bg-white hover:bg-gray-50 text-gray-700",
+                        title: "Refetch with the active chips applied in the server query",
+                        onclick: move |_| *TRACE_SERVER_FILTERS.write() = chips_to_filters(&active_chips()),
+                        "Apply server-side"
+                    }
+                }
+                if !server.is_empty() {
+                    span { class: "text-blue-700", "Server filter active" }
+                    button {
+                        class: "px-2 py-0.5 rounded-md border border-gray-300 bg-white hover:bg-gray-50 text-gray-700",
+                        title: "Refetch without server-side filters",
+                        onclick: move |_| *TRACE_SERVER_FILTERS.write() = TraceFilters::default(),
+                        "Clear server filter"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(name: &str, phase: Option<&str>, thread_id: i64, children: Vec<SpanInfo>) -> SpanInfo {
+        SpanInfo {
+            span_id: 0,
+            trace_id: 1,
+            parent_id: None,
+            name: name.to_string(),
+            start_timestamp: 0,
+            end_timestamp: None,
+            thread_id,
+            phase: phase.map(String::from),
+            location: None,
+            attributes: None,
+            cpu_time_ns: None,
+            ctx_switches: None,
+            links: None,
+            children,
+            events: vec![],
+        }
+    }
+
+    #[test]
+    fn counts_top_values_per_kind() {
+        let tree = vec![span(
+            "step",
+            None,
+            7,
+            vec![
+                span("fwd", Some("forward"), 7, vec![]),
+                span("fwd", Some("forward"), 8, vec![]),
+                span("bwd", Some("backward"), 8, vec![]),
+            ],
+        )];
+        let chips = top_chips(&tree, 2);
+        let names: Vec<_> = chips
+            .iter()
+            .filter(|c| c.chip.kind == ChipKind::Name)
+            .map(|c| (c.chip.value.as_str(), c.count))
+            .collect();
+        assert_eq!(names, [("fwd", 2), ("bwd", 1)]);
+        let kinds = chips
+            .iter()
+            .filter(|c| c.chip.kind == ChipKind::Kind)
+            .count();
+        assert_eq!(kinds, 2);
+        let threads: Vec<_> = chips
+            .iter()
+            .filter(|c| c.chip.kind == ChipKind::Thread)
+            .map(|c| (c.chip.value.as_str(), c.count))
+            .collect();
+        assert_eq!(threads, [("7", 2), ("8", 2)]);
+    }
+
+    #[test]
+    fn chips_or_within_kind_and_across_kinds() {
+        let chips: Vec<_> = ["name:fwd", "name:bwd", "thread:8", "bogus", "kind:"]
+            .iter()
+            .filter_map(|p| TraceChip::parse(p))
+            .collect();
+        assert_eq!(chips.len(), 3);
+        assert!(span_matches_chips(&span("bwd", None, 8, vec![]), &chips));
+        assert!(!span_matches_chips(&span("fwd", None, 7, vec![]), &chips));
+        assert!(!span_matches_chips(&span("step", None, 8, vec![]), &chips));
+        assert!(span_matches_chips(&span("step", None, 7, vec![]), &[]));
+
+        let filters = chips_to_filters(&chips);
+        assert_eq!(filters.thread_ids, [8]);
+        assert_eq!(filters.query_params(), "&name=fwd%2Cbwd&thread_id=8");
+    }
+}
@@ -0,0 +1,219 @@
+//! "Compare windows" panel on the Spans page: per-span p50/p95 deltas between a
+//! baseline and a current time range, computed server-side by `trace/summary`.
+
+use chrono::{Duration, Local, NaiveDateTime, TimeZone};
+use dioxus::prelude::*;
+
+use crate::api::{ApiClient, SpanWindowDelta, TraceWindow};
+use crate::components::common::{ErrorState, LoadingState};
+
+const INPUT_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+/// Parse a `datetime-local` input value (browser local time) into µs since epoch.
+pub fn parse_local_input_us(value: &str) -> Option<i64> {
+    let naive = NaiveDateTime::parse_from_str(value.trim(), INPUT_FORMAT).ok()?;
+    let local = Local.from_local_datetime(&naive).earliest()?;
+    Some(local.timestamp_micros())
+}
+
+fn format_local_input(hours_ago: i64) -> String {
+    (Local::now() - Duration::hours(hours_ago))
+        .format(INPUT_FORMAT)
+        .to_string()
+}
+
+fn window_from_inputs(start: &str, end: &str) -> Option<TraceWindow> {
+    let start_us = parse_local_input_us(start)?;
+    let end_us = parse_local_input_us(end)?;
+    (end_us > start_us).then_some(TraceWindow { start_us, end_us })
+}
+
+fn format_us(value: Option<f64>) -> String {
+    match value {
+        None => "—".to_string(),
+        Some(us) if us.abs() >= 1_000_000.0 => format!("{:.3}s", us / 1_000_000.0),
+        Some(us) if us.abs() >= 1_000.0 => format!("{:.2}ms", us / 1_000.0),
+        Some(us) => format!("{us:.0}us"),
+    }
+}
+
+fn format_delta(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v > 0.0 => format!("+{}", format_us(Some(v))),
+        other => format_us(other),
+    }
+}
+
+fn verdict_class(delta: &SpanWindowDelta) -> &'static str {
+    match delta.verdict.as_str() {
+        "regression" => "bg-red-50 text-red-800",
+        "improvement" => "bg-green-50 text-green-800",
+        _ => "text-gray-700",
+    }
+}
+
+#[component]
+pub fn TraceCompareCard() -> Element {
+    // Default: the last hour vs. the same hour one day earlier.
+    let mut baseline_start = use_signal(|| format_local_input(25));
+    let mut baseline_end = use_signal(|| format_local_input(24));
+    let mut current_start = use_signal(|| format_local_input(1));
+    let mut current_end = use_signal(|| format_local_input(0));
+    let mut result = use_signal(|| None::<Result<Vec<SpanWindowDelta>, String>>);
+    let mut loading = use_signal(|| false);
+
+    let run = move |_| {
+        let baseline = window_from_inputs(&baseline_start.read(), &baseline_end.read());
+        let current = window_from_inputs(&current_start.read(), &current_end.read());
+        let (Some(baseline), Some(current)) = (baseline, current) else {
+            result.set(Some(Err(
+                "Both windows need a valid start before their end.".to_string(),
+            )));
+            return;
+        };
+        loading.set(true);
+        spawn(async move {
+            let outcome = ApiClient::new()
+                .compare_trace_windows(baseline, current)
+                .await
+                .map_err(|e| e.to_string());
+            result.set(Some(outcome));
+            loading.set(false);
+        });
+    };
+
+    let input_class =
+        "px-2 py-1 text-xs rounded-md border border-gray-300 bg-white font-mono focus:outline-none focus:ring-2 focus:ring-blue-500/30";
+
+    rsx! {
+        div { class: "flex flex-col gap-3",
+            div { class: "flex flex-wrap items-end gap-4 text-xs text-gray-600",
+                div { class: "flex flex-col gap-1",
+                    span { class: "font-medium text-gray-800", "Baseline" }
+                    div { class: "flex items-center gap-1",
+                        input {
+                            r#type: "datetime-local",
+                            class: input_class,
+                            value: "{baseline_start}",
+                            oninput: move |ev| baseline_start.set(ev.value()),
+                        }
+                        span { "→" }
+                        input {
+                            r#type: "datetime-local",
+                            class: input_class,
+                            value: "{baseline_end}",
+                            oninput: move |ev| baseline_end.set(ev.value()),
+                        }
+                    }
+                }
+                div { class: "flex flex-col gap-1",
+                    span { class: "font-medium text-gray-800", "Current" }
+                    div { class: "flex items-center gap-1",
+                        input {
+                            r#type: "datetime-local",
+                            class: input_class,
+                            value: "{current_start}",
+                            oninput: move |ev| current_start.set(ev.value()),
+                        }
+                        span { "→" }
+                        input {
+                            r#type: "datetime-local",
+                            class: input_class,
+                            value: "{current_end}",
+                            oninput: move |ev| current_end.set(ev.value()),
+                        }
+                    }
+                }
+                button {
+                    class: "px-3 py-1.5 text-xs rounded-md border border-blue-300 bg-blue-50 text-blue-700 hover:bg-blue-100 disabled:opacity-50",
+                    disabled: loading(),
+                    onclick: run,
+                    "Compare"
+                }
+            }
+            match result.read().as_ref() {
+                None if loading() => rsx! { LoadingState { message: Some("Comparing windows…".to_string()) } },
+                None => rsx! {
+                    p { class: "text-xs text-gray-500",
+                        "Pick two windows and compare per-span p50/p95, largest p50 change first. Regressions are red, improvements green."
+                    }
+                },
+                Some(Err(err)) => rsx! { ErrorState { error: err.clone(), title: None } },
+                Some(Ok(deltas)) if deltas.is_empty() => rsx! {
+                    p { class: "text-xs text-gray-500", "No completed spans in either window." }
+                },
+                Some(Ok(deltas)) => rsx! { TraceCompareTable { deltas: deltas.clone() } },
+            }
+        }
+    }
+}
+
+#[component]
+fn TraceCompareTable(deltas: Vec<SpanWindowDelta>) -> Element {
+    rsx! {
+        div { class: "overflow-x-auto",
+            table { class: "min-w-full text-xs font-mono",
+                thead { class: "bg-gray-50 text-gray-600",
+                    tr {
+                        th { class: "px-2 py-1 text-left font-medium", "span" }
+                        th { class: "px-2 py-1 text-right font-medium", "n (base/cur)" }
+                        th { class: "px-2 py-1 text-right font-medium", "p50 base" }
+                        th { class: "px-2 py-1 text-right font-medium", "p50 cur" }
+                        th { class: "px-2 py-1 text-right font-medium", "Δp50" }
+                        th { class: "px-2 py-1 text-right font-medium", "Δp50 %" }
+                        th { class: "px-2 py-1 text-right font-medium", "Δp95" }
+                        th { class: "px-2 py-1 text-left font-medium", "verdict" }
+                    }
+                }
+                tbody {
+                    for delta in deltas.iter() {
+                        tr { key: "{delta.name}", class: "border-b border-gray-100 {verdict_class(delta)}",
+                            td { class: "px-2 py-1 text-left", "{delta.name}" }
+                            td { class: "px-2 py-1 text-right", "{delta.baseline_count}/{delta.current_count}" }
+                            td { class: "px-2 py-1 text-right", "{format_us(delta.baseline_p50_us)}" }
+                            td { class: "px-2 py-1 text-right", "{format_us(delta.current_p50_us)}" }
+                            td { class: "px-2 py-1 text-right", "{format_delta(delta.delta_p50_us)}" }
+                            td { class: "px-2 py-1 text-right",
+                                {delta.delta_p50_pct.map(|p| format!("{p:+.1}%")).unwrap_or_else(|| "—".to_string())}
+                            }
+                            td { class: "px-2 py-1 text-right", "{format_delta(delta.delta_p95_us)}" }
+                            td { class: "px-2 py-1 text-left",
+                                if delta.significant {
+                                    span { class: "font-semibold", "{delta.verdict}" }
+                                } else {
+                                    span { "{delta.verdict}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_datetime_local_input() {
+        let us = parse_local_input_us("2024-03-01T12:30").unwrap();
+        let back = Local.timestamp_micros(us).unwrap();
+        assert_eq!(back.format(INPUT_FORMAT).to_string(), "2024-03-01T12:30");
+        assert!(parse_local_input_us("not a date").is_none());
+    }
+
+    #[test]
+    fn rejects_inverted_window() {
+        assert!(window_from_inputs("2024-03-01T12:30", "2024-03-01T11:30").is_none());
+        assert!(window_from_inputs("2024-03-01T11:30", "2024-03-01T12:30").is_some());
+    }
+
+    #[test]
+    fn formats_signed_durations() {
+        assert_eq!(format_delta(Some(1500.0)), "+1.50ms");
+        assert_eq!(format_delta(Some(-20.0)), "-20us");
+        assert_eq!(format_delta(None), "—");
+    }
+}
@@ -0,0 +1,119 @@
+//! Timer-driven refetching at the user's [`AUTO_REFRESH_SECS`] interval.
+
+use std::future::Future;
+
+use dioxus::prelude::*;
+use gloo_timers::callback::Interval;
+
+use crate::state::auto_refresh::AUTO_REFRESH_SECS;
+use crate::utils::error::AppError;
+
+/// Returned by [`use_auto_refresh`]. `Copy`, so it can move into fetchers.
+///
+/// Fetchers read [`tick`](Self::tick) to re-run and wrap their requests in
+/// [`track`](Self::track); the timer skips a beat while any tracked request
+/// is still in flight, so slow endpoints do not pile up.
+#[derive(Clone, Copy, PartialEq)]
+pub struct AutoRefresh {
+    tick: Signal<u32>,
+    in_flight: Signal<u32>,
+    /// A request of the current refresh has failed so far.
+    round_failed: Signal<bool>,
+    /// Outcome of the last refresh whose requests all completed.
+    failed: Signal<bool>,
+}
+
+impl AutoRefresh {
+    /// Refresh counter; read it in a fetcher to re-run on every refresh.
+    pub fn tick(&self) -> u32 {
+        (self.tick)()
+    }
+
+    /// True when a request of the last completed refresh failed.
+    pub fn failed(&self) -> bool {
+        (self.failed)()
+    }
+
+    /// Refresh now (manual refresh buttons), whatever the interval.
+    pub fn refresh_now(&self) {
+        let mut this = *self;
+        this.round_failed.set(false);
+        this.tick += 1;
+    }
+
+    /// Await `request`, counting it as in flight and recording failures.
+    pub async fn track<T>(
+        self,
+        request: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        // Released on drop too, since a restarted resource drops its old
+        // future; `try_write` because the page may already be gone.
+        struct InFlight(AutoRefresh);
+        impl Drop for InFlight {
+            fn drop(&mut self) {
+                let AutoRefresh {
+                    mut in_flight,
+                    round_failed,
+                    mut failed,
+                    ..
+                } = self.0;
+                let Ok(mut n) = in_flight.try_write() else {
+                    return;
+                };
+                *n = n.saturating_sub(1);
+                if *n == 0 {
+                    if let (Ok(round), Ok(mut shown)) =
+                        (round_failed.try_read(), failed.try_write())
+                    {
+                        *shown = *round;
+                    }
+                }
+            }
+        }
+
+        let mut in_flight = self.in_flight;
+        in_flight += 1;
+        let _guard = InFlight(self);
+        let result = request.await;
+        if result.is_err() {
+            let mut round_failed = self.round_failed;
+            round_failed.set(true);
+        }
+        result
+    }
+}
+
+/// Refresh ticks every [`AUTO_REFRESH_SECS`] (never when `0`), skipped while
+/// `gate` is false, the target is unreachable or a tracked request is in
+/// flight. Errors do not stop the timer; see [`AutoRefresh::failed`].
+pub fn use_auto_refresh(gate: Option<Signal<bool>>) -> AutoRefresh {
+    let refresh = AutoRefresh {
+        tick: use_signal(|| 0u32),
+        in_flight: use_signal(|| 0u32),
+        round_failed: use_signal(|| false),
+        failed: use_signal(|| false),
+    };
+    let mut interval_slot = use_signal(|| None::<Interval>);
+
+    use_effect(move || {
+        let secs = AUTO_REFRESH_SECS();
+        if secs == 0 {
+            interval_slot.set(None);
+            return;
+        }
+        interval_slot.set(Some(Interval::new(secs * 1000, move || {
+            let allowed = gate.map(|g| g()).unwrap_or(true)
+                && !crate::state::health::target_unreachable()
+                && *refresh.in_flight.peek() == 0;
+            if allowed {
+                refresh.refresh_now();
+            }
+        })));
+    });
+
+    use_drop(move || {
+        interval_slot.set(None);
+    });
+
+    refresh
+}
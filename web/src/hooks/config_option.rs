@@ -0,0 +1,89 @@
+//! Optimistic read/write handle for one runtime `probing.*` option.
+
+use dioxus::prelude::*;
+
+use crate::api::ApiClient;
+use crate::state::profiling::{apply_profiler_option, show_profiling_feedback};
+
+/// Returned by [`use_config_option`]. `Copy`, so it can move into handlers.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ConfigOption {
+    key: &'static str,
+    value: Signal<Option<String>>,
+    pending: Signal<bool>,
+}
+
+impl ConfigOption {
+    /// Last value read from (or optimistically sent to) the server; `None`
+    /// until the first read completes or when the option is unset.
+    pub fn value(&self) -> Option<String> {
+        self.value.read().clone()
+    }
+
+    /// True while a write is in flight; controls should be disabled.
+    pub fn pending(&self) -> bool {
+        *self.pending.read()
+    }
+
+    /// Show `next` immediately and `SET` it. On failure the control rolls back
+    /// to the server's value and the rejection message is shown in a toast.
+    pub fn set(&self, next: impl Into<String>) {
+        if *self.pending.peek() {
+            return;
+        }
+        let next = next.into();
+        let Self {
+            key,
+            mut value,
+            mut pending,
+        } = *self;
+        let previous = value.peek().clone();
+
+        value.set(Some(next.clone()));
+        apply_profiler_option(key, &next);
+        pending.set(true);
+        spawn(async move {
+            let client = ApiClient::new();
+            match client.set_config_option(key, &next).await {
+                Ok(()) => show_profiling_feedback(format!("{key} applied"), false),
+                Err(err) => {
+                    let restored = client
+                        .get_config_option(key)
+                        .await
+                        .unwrap_or_else(|_| previous.clone());
+                    value.set(restored.clone());
+                    apply_profiler_option(key, restored.as_deref().unwrap_or(""));
+                    show_profiling_feedback(err.display_message(), true);
+                }
+            }
+            pending.set(false);
+        });
+    }
+}
+
+/// Read `key` via `GET /config/{key}` on mount; write with [`ConfigOption::set`].
+///
+/// Profiling keys are mirrored into `state::profiling` so pages gated on them
+/// follow the control, including rollbacks.
+pub fn use_config_option(key: &'static str) -> ConfigOption {
+    let mut value = use_signal(|| None::<String>);
+    let pending = use_signal(|| false);
+
+    use_hook(move || {
+        spawn(async move {
+            if let Ok(current) = ApiClient::new().get_config_option(key).await {
+                // A write started before the read returned wins.
+                if !*pending.peek() {
+                    apply_profiler_option(key, current.as_deref().unwrap_or(""));
+                    value.set(current);
+                }
+            }
+        });
+    });
+
+    ConfigOption {
+        key,
+        value,
+        pending,
+    }
+}
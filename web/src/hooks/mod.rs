@@ -3,6 +3,14 @@
 //! Prefer [`use_app_resource`] (auto-fetch) and Dioxus [`use_action`](dioxus::prelude::use_action)
 //! (user-triggered). [`use_api`] remains on a few pages (e.g. Pulsing) pending migration.
 
+mod auto_refresh;
+mod config_option;
+mod span_stream;
+
+pub use auto_refresh::{use_auto_refresh, AutoRefresh};
+pub use config_option::{use_config_option, ConfigOption};
+pub use span_stream::{use_span_stream, SpanStream, LIVE_SPANS_CAP};
+
 use crate::utils::error::AppError;
 use dioxus::prelude::*;
 use gloo_timers::callback::Interval;
@@ -103,8 +111,23 @@ where
 }
 
 /// Periodic tick signal for polling APIs (e.g. dashboard metrics).
-/// Use [`use_poll_tick_gated`] when the page can be hidden.
+/// Use [`use_poll_tick_gated`] when the page can be hidden. Ticks are also
+/// skipped while the target is unreachable (see [`crate::state::health`]).
 pub fn use_poll_tick_gated(interval_ms: u32, gate: Option<Signal<bool>>) -> Signal<u32> {
+    use_poll_tick(interval_ms, gate, true)
+}
+
+/// Like [`use_poll_tick_gated`] but keeps ticking while the target is
+/// unreachable; only the health probe itself should use this.
+pub fn use_health_poll_tick(interval_ms: u32, gate: Option<Signal<bool>>) -> Signal<u32> {
+    use_poll_tick(interval_ms, gate, false)
+}
+
+fn use_poll_tick(
+    interval_ms: u32,
+    gate: Option<Signal<bool>>,
+    pause_when_unreachable: bool,
+) -> Signal<u32> {
     let tick = use_signal(|| 0u32);
     let mut interval_slot = use_signal(|| None::<Interval>);
 
@@ -112,7 +135,8 @@ pub fn use_poll_tick_gated(interval_ms: u32, gate: Option<Signal<bool>>) -> Sign
         let mut tick = tick;
         let gate = gate;
         interval_slot.set(Some(Interval::new(interval_ms, move || {
-            let allowed = gate.map(|g| g()).unwrap_or(true);
+            let allowed = gate.map(|g| g()).unwrap_or(true)
+                && !(pause_when_unreachable && crate::state::health::target_unreachable());
             if allowed {
                 tick.set(tick() + 1);
             }
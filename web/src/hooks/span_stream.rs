@@ -0,0 +1,145 @@
+//! Live spans pushed by `/apis/trace/stream`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+use crate::api::{ApiClient, SpanInfo, SpanStreamMessage};
+
+/// Streamed spans kept by the Spans page.
+pub const LIVE_SPANS_CAP: usize = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct SpanStream {
+    /// Newest first, at most the cap.
+    pub spans: Signal<Vec<SpanInfo>>,
+    /// Spans the server skipped because this page fell behind.
+    pub dropped: Signal<u64>,
+    pub connected: Signal<bool>,
+}
+
+/// Put `span` in front of `spans`, keeping at most `cap` of them.
+pub fn push_live_span(spans: &mut Vec<SpanInfo>, span: SpanInfo, cap: usize) {
+    spans.insert(0, span);
+    spans.truncate(cap);
+}
+
+/// An open socket and the callbacks it holds; dropping it closes the socket.
+struct LiveSocket {
+    socket: web_sys::WebSocket,
+    _on_open: Closure<dyn FnMut(web_sys::Event)>,
+    _on_message: Closure<dyn FnMut(web_sys::MessageEvent)>,
+    _on_close: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Drop for LiveSocket {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+    }
+}
+
+fn open_socket(stream: SpanStream, cap: usize) -> Option<LiveSocket> {
+    let SpanStream {
+        mut spans,
+        mut dropped,
+        mut connected,
+    } = stream;
+    let url = ApiClient::span_stream_url().ok()?;
+    let socket = web_sys::WebSocket::new(&url).ok()?;
+
+    let on_open =
+        Closure::wrap(Box::new(move |_e: web_sys::Event| connected.set(true))
+            as Box<dyn FnMut(web_sys::Event)>);
+    let on_message = Closure::wrap(Box::new(move |e: web_sys::MessageEvent| {
+        let Some(text) = e.data().as_string() else {
+            return;
+        };
+        match serde_json::from_str(&text) {
+            Ok(SpanStreamMessage::Span { span }) => push_live_span(&mut spans.write(), span, cap),
+            Ok(SpanStreamMessage::Lagged { total_dropped, .. }) => dropped.set(total_dropped),
+            Err(err) => log::warn!("span stream: unreadable message: {err}"),
+        }
+    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+    let on_close =
+        Closure::wrap(Box::new(move |_e: web_sys::Event| connected.set(false))
+            as Box<dyn FnMut(web_sys::Event)>);
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    Some(LiveSocket {
+        socket,
+        _on_open: on_open,
+        _on_message: on_message,
+        _on_close: on_close,
+    })
+}
+
+/// Spans finished while `enabled` is on, newest first and at most `cap`.
+/// Turning it off closes the socket and keeps the spans received so far.
+pub fn use_span_stream(enabled: Signal<bool>, cap: usize) -> SpanStream {
+    let stream = SpanStream {
+        spans: use_signal(Vec::new),
+        dropped: use_signal(|| 0),
+        connected: use_signal(|| false),
+    };
+    let slot = use_hook(|| Rc::new(RefCell::new(None::<LiveSocket>)));
+
+    let slot_for_effect = slot.clone();
+    use_effect(move || {
+        let on = enabled();
+        let mut socket = slot_for_effect.borrow_mut();
+        *socket = None;
+        let mut connected = stream.connected;
+        connected.set(false);
+        if on {
+            *socket = open_socket(stream, cap);
+        }
+    });
+
+    use_drop(move || {
+        slot.borrow_mut().take();
+    });
+
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(span_id: i64) -> SpanInfo {
+        SpanInfo {
+            span_id,
+            trace_id: 1,
+            parent_id: None,
+            name: format!("s{span_id}"),
+            start_timestamp: span_id,
+            end_timestamp: Some(span_id + 1),
+            thread_id: 1,
+            phase: None,
+            location: None,
+            attributes: None,
+            cpu_time_ns: None,
+            ctx_switches: None,
+            links: None,
+            children: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn newest_spans_first_within_the_cap() {
+        let mut spans = Vec::new();
+        for id in 1..=4 {
+            push_live_span(&mut spans, span(id), 3);
+        }
+        let ids: Vec<_> = spans.iter().map(|s| s.span_id).collect();
+        assert_eq!(ids, [4, 3, 2]);
+    }
+}
@@ -69,10 +69,9 @@ pub fn recent_steps() -> String {
 }
 
 pub const TRAIN_STEP_MEDIAN: &str =
-    "SELECT round(median((CAST(e.time AS BIGINT) - CAST(s.time AS BIGINT)) / 1000000.0), 1) AS train_step_median_ms \
-     FROM python.trace_event s \
-     JOIN python.trace_event e ON s.span_id = e.span_id AND e.record_type = 'span_end' \
-     WHERE s.record_type = 'span_start' AND s.name = 'train.step'";
+    "SELECT round(median(duration / 1000000.0), 1) AS train_step_median_ms \
+     FROM python.trace_event \
+     WHERE record_type = 'span' AND name = 'train.step'";
 
 pub const NCCL_COUNTERS: &str = "SELECT coll_events, rows_written, pool_exhausted, write_errors \
      FROM nccl.profiler_counters ORDER BY ts DESC LIMIT 1";
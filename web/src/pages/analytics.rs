@@ -9,11 +9,22 @@ use crate::components::common::{query_result, AppErrorDisplay, AsyncBoundary, Lo
 use crate::components::dataframe_view::DataFrameView;
 use crate::components::icon::Icon;
 use crate::components::page::{PageContainer, PageTitle};
+use crate::components::rl::{ChartSeries, MetricsLineChart};
 use crate::hooks::use_app_resource;
+use crate::state::console::{apply_completion, char_to_utf16, utf16_to_char};
+use crate::state::health::format_elapsed;
+use crate::state::sql_history::{
+    clear_sql_history, forget_sql_query, record_sql_run, save_sql_query, SQL_DRAFT, SQL_LIBRARY,
+};
 use crate::utils::error::AppError;
-use probing_proto::prelude::{DataFrame, Ele};
+use crate::utils::sql_complete::{complete_sql, schema_sql, SqlCompletion, SqlSchema};
+use probing_proto::prelude::{ChartQueryRequest, DataFrame, DownsampleInfo, DownsampleMode, Ele};
+use wasm_bindgen::JsCast;
 
 const HIDDEN_SCHEMAS: &[&str] = &["information_schema"];
+const EDITOR_ID: &str = "probing-sql-editor";
+/// Completions shown under the editor.
+const MAX_SUGGESTIONS: usize = 8;
 
 #[derive(Clone, PartialEq, Eq)]
 struct TableEntry {
@@ -66,7 +77,10 @@ fn example_sql(global_mode: bool) -> &'static str {
 #[component]
 pub fn Analytics() -> Element {
     let global_mode = use_signal(|| false);
-    let mut sql = use_signal(String::new);
+    // Bumped to reload the catalog and the editor's completion schema.
+    let mut catalog_reload = use_signal(|| 0u32);
+    let mut sql = use_signal(|| SQL_DRAFT.peek().clone());
+    use_effect(move || *SQL_DRAFT.write() = sql());
     let mut selected_table = use_signal(|| None::<String>);
     let mut preview_title = use_signal(String::new);
     let mut preview_open = use_signal(|| false);
@@ -104,17 +118,26 @@ pub fn Analytics() -> Element {
                         title: "Catalog",
                         content_class: Some("p-0"),
                         header_right: Some(rsx! {
-                            GlobalModeToggle {
-                                global_mode,
-                                on_change: move |_| {
-                                    selected_table.set(None);
-                                },
+                            div { class: "flex items-center gap-2",
+                                button {
+                                    class: "p-1.5 rounded-md text-gray-500 hover:text-gray-800 hover:bg-gray-100 transition-colors",
+                                    title: "Reload tables and columns",
+                                    onclick: move |_| catalog_reload += 1,
+                                    Icon { icon: &icondata::AiReloadOutlined, class: "w-4 h-4" }
+                                }
+                                GlobalModeToggle {
+                                    global_mode,
+                                    on_change: move |_| {
+                                        selected_table.set(None);
+                                    },
+                                }
                             }
                         }),
                         AsyncBoundary {
                             message: Some("Loading tables...".to_string()),
                             TableCatalog {
                                 global_mode,
+                                catalog_reload,
                                 selected_table,
                                 on_select: select_for_query,
                                 on_preview: open_preview,
@@ -129,6 +152,7 @@ pub fn Analytics() -> Element {
                         content_class: Some("p-4"),
                         SqlEditorPanel {
                             global_mode,
+                            catalog_reload,
                             sql,
                             selected_table,
                             on_clear_selection: move |_| selected_table.set(None),
@@ -244,12 +268,14 @@ fn GlobalModeToggle(mut global_mode: Signal<bool>, on_change: EventHandler<()>)
 #[component]
 fn TableCatalog(
     global_mode: Signal<bool>,
+    catalog_reload: Signal<u32>,
     selected_table: Signal<Option<String>>,
     on_select: EventHandler<TableEntry>,
     on_preview: EventHandler<TableEntry>,
 ) -> Element {
     let mut filter = use_signal(String::new);
     let tables = use_app_resource(move || {
+        catalog_reload();
         let query = catalog_sql(global_mode());
         async move { ApiClient::new().execute_query(&query).await }
     });
@@ -354,19 +380,197 @@ fn TableCatalog(
     )
 }
 
+/// History and saved-query menus under the SQL editor. Picking an entry
+/// puts it in the editor and runs it through `on_run`.
+#[component]
+fn SqlHistoryBar(sql: Signal<String>, on_run: EventHandler<String>) -> Element {
+    let mut open = use_signal(|| None::<&'static str>);
+    let mut save_name = use_signal(String::new);
+    let library = SQL_LIBRARY.read().clone();
+    let now = js_sys::Date::now();
+    let menu_button = "inline-flex items-center gap-1 px-2 py-1 text-xs rounded-md border border-gray-300 bg-white text-gray-700 hover:bg-gray-50";
+    let mut toggle = move |menu: &'static str| {
+        let next = if open() == Some(menu) {
+            None
+        } else {
+            Some(menu)
+        };
+        open.set(next);
+    };
+    let mut run = move |query: String| {
+        open.set(None);
+        on_run.call(query);
+    };
+
+    rsx! {
+        div { class: "relative flex flex-wrap items-center gap-2 text-xs text-gray-600",
+            button {
+                class: menu_button,
+                onclick: move |_| toggle("history"),
+                Icon { icon: &icondata::AiHistoryOutlined, class: "w-3.5 h-3.5" }
+                "History ({library.history.len()})"
+            }
+            button {
+                class: menu_button,
+                onclick: move |_| toggle("saved"),
+                Icon { icon: &icondata::AiStarOutlined, class: "w-3.5 h-3.5" }
+                "Saved ({library.saved.len()})"
+            }
+            input {
+                class: "ml-auto w-40 px-2 py-1 rounded-md border border-gray-300 bg-white",
+                placeholder: "Name this query",
+                value: "{save_name}",
+                oninput: move |e| save_name.set(e.value()),
+            }
+            button {
+                class: menu_button,
+                disabled: save_name().trim().is_empty() || sql().trim().is_empty(),
+                onclick: move |_| {
+                    save_sql_query(&save_name(), &sql());
+                    save_name.set(String::new());
+                },
+                "Save"
+            }
+
+            if open() == Some("history") {
+                div { class: "absolute left-0 top-full mt-1 z-20 w-full max-h-72 overflow-y-auto rounded-md border border-gray-200 bg-white shadow-lg",
+                    if library.history.is_empty() {
+                        p { class: "px-3 py-2 text-gray-400", "No queries run yet." }
+                    } else {
+                        for entry in library.history {
+                            {
+                                let query = entry.sql.clone();
+                                let ago = format_elapsed(now - entry.at_ms);
+                                rsx! {
+                                    button {
+                                        class: "w-full flex items-center gap-2 px-3 py-1.5 text-left hover:bg-gray-50",
+                                        title: "Run again",
+                                        onclick: move |_| run(query.clone()),
+                                        span {
+                                            class: if entry.ok { "w-2 h-2 shrink-0 rounded-full bg-emerald-500" } else { "w-2 h-2 shrink-0 rounded-full bg-red-500" },
+                                            title: if entry.ok { "Succeeded" } else { "Failed" },
+                                        }
+                                        span { class: "flex-1 min-w-0 font-mono truncate text-gray-800", "{entry.sql}" }
+                                        span { class: "shrink-0 text-gray-400", "{ago} ago" }
+                                    }
+                                }
+                            }
+                        }
+                        div { class: "border-t border-gray-100 px-3 py-1.5 text-right",
+                            button {
+                                class: "text-red-600 hover:underline",
+                                onclick: move |_| {
+                                    clear_sql_history();
+                                    open.set(None);
+                                },
+                                "Clear history"
+                            }
+                        }
+                    }
+                }
+            }
+            if open() == Some("saved") {
+                div { class: "absolute left-0 top-full mt-1 z-20 w-full max-h-72 overflow-y-auto rounded-md border border-gray-200 bg-white shadow-lg",
+                    if library.saved.is_empty() {
+                        p { class: "px-3 py-2 text-gray-400", "Name the query in the editor and press Save." }
+                    } else {
+                        for saved in library.saved {
+                            {
+                                let query = saved.sql.clone();
+                                let name = saved.name.clone();
+                                rsx! {
+                                    div { class: "flex items-center gap-2 px-3 py-1.5 hover:bg-gray-50",
+                                        button {
+                                            class: "flex-1 min-w-0 text-left",
+                                            title: "Run",
+                                            onclick: move |_| run(query.clone()),
+                                            span { class: "font-medium text-gray-800", "{saved.name}" }
+                                            span { class: "ml-2 font-mono text-gray-500 truncate", "{saved.sql}" }
+                                        }
+                                        button {
+                                            class: "shrink-0 p-1 rounded text-gray-400 hover:text-red-600",
+                                            title: "Delete saved query",
+                                            onclick: move |_| forget_sql_query(&name),
+                                            Icon { icon: &icondata::AiDeleteOutlined, class: "w-3.5 h-3.5" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn SqlEditorPanel(
     global_mode: Signal<bool>,
+    catalog_reload: Signal<u32>,
     sql: Signal<String>,
     selected_table: Signal<Option<String>>,
     on_clear_selection: EventHandler<()>,
 ) -> Element {
-    let mut run_query = use_action(|query: String| async move {
+    // Id of the running query, for the Cancel button.
+    let mut running_id = use_signal(|| None::<String>);
+    // The last run was stopped by Cancel rather than failing.
+    let mut cancelled = use_signal(|| false);
+    let mut run_query = use_action(move |query: String| async move {
+        cancelled.set(false);
         if query.trim().is_empty() {
             return Err(AppError::Api("SQL query cannot be empty".to_string()));
         }
-        ApiClient::new().execute_query(&query).await
+        let id = new_query_id();
+        running_id.set(Some(id.clone()));
+        let result = ApiClient::new().execute_query_with_id(&id, &query).await;
+        running_id.set(None);
+        cancelled.set(result.as_ref().is_err_and(AppError::is_cancelled));
+        record_sql_run(&query, result.is_ok());
+        result
+    });
+    let cancel_query = move |_: MouseEvent| {
+        if let Some(id) = running_id() {
+            spawn(async move {
+                // Fails only when the query finished first.
+                let _ = ApiClient::new().cancel_query(&id).await;
+            });
+        }
+    };
+    // Tables and columns for completion; the editor works without them.
+    let schema = use_app_resource(move || {
+        catalog_reload();
+        let global = global_mode();
+        let query = schema_sql(global, HIDDEN_SCHEMAS);
+        async move {
+            let df = ApiClient::new().execute_query(&query).await?;
+            Ok(SqlSchema::from_columns(&df, global))
+        }
     });
+    let mut suggestions = use_signal(|| None::<SqlCompletion>);
+    let mut suggest = move |text: &str, cursor: usize| {
+        let found = match &*schema.read() {
+            Some(Ok(schema)) => complete_sql(text, cursor, schema, MAX_SUGGESTIONS),
+            _ => None,
+        };
+        suggestions.set(found);
+    };
+    // Replace the word being completed and keep suggesting from the new caret.
+    let mut complete = move |matches: &[String]| {
+        let Some(current) = suggestions() else {
+            return false;
+        };
+        let text = sql();
+        match apply_completion(&text, current.start, current.end, matches) {
+            Some((completed, at)) => {
+                sql.set(completed.clone());
+                set_editor_caret(&completed, at);
+                suggest(&completed, at);
+                true
+            }
+            None => false,
+        }
+    };
     let global = global_mode();
     let placeholder = if global {
         "SELECT * FROM global.schema.table LIMIT 10"
@@ -405,6 +609,7 @@ fn SqlEditorPanel(
                         "Clear"
                     }
                 }
+                SeriesBuilder { fqtn: fqtn.clone(), sql }
             }
 
             div { class: "flex flex-wrap items-center gap-2",
@@ -424,6 +629,14 @@ fn SqlEditorPanel(
                     Icon { icon: &icondata::AiPlayCircleOutlined, class: "w-4 h-4" }
                     if run_query.pending() { "Running…" } else { "Run" }
                 }
+                if run_query.pending() {
+                    button {
+                        class: "px-3 py-2 text-sm rounded-md border border-red-300 bg-white text-red-700 hover:bg-red-50 transition-colors",
+                        title: "Stop the running query",
+                        onclick: cancel_query,
+                        "Cancel"
+                    }
+                }
                 button {
                     class: format!(
                         "px-3 py-2 text-sm rounded-md border border-gray-300 bg-white text-gray-700 hover:bg-{} transition-colors",
@@ -447,24 +660,82 @@ fn SqlEditorPanel(
 
             div { class: "rounded-lg border border-gray-300 overflow-hidden focus-within:ring-2 focus-within:ring-blue-500/30 focus-within:border-blue-500",
                 textarea {
+                    id: EDITOR_ID,
                     class: "w-full min-h-[140px] max-h-[320px] font-mono text-sm p-3 bg-slate-50 text-gray-900 resize-y focus:outline-none",
                     placeholder: "{placeholder}",
                     value: "{sql}",
-                    oninput: move |ev| sql.set(ev.value()),
-                    onkeydown: move |e: KeyboardEvent| {
-                        if e.key() == Key::Enter && (e.modifiers().meta() || e.modifiers().ctrl()) {
+                    oninput: move |ev| {
+                        let text = ev.value();
+                        suggest(&text, editor_caret(&text));
+                        sql.set(text);
+                    },
+                    onkeydown: move |e: KeyboardEvent| match e.key() {
+                        Key::Enter if e.modifiers().meta() || e.modifiers().ctrl() => {
                             e.prevent_default();
+                            suggestions.set(None);
                             if !run_query.pending() {
                                 run_query.call(sql());
                             }
                         }
+                        Key::Tab if suggestions.read().is_some() => {
+                            e.prevent_default();
+                            let matches = suggestions().map(|c| c.matches).unwrap_or_default();
+                            // No common prefix to add: take the first suggestion.
+                            if !complete(&matches) {
+                                complete(&matches[..1]);
+                            }
+                        }
+                        Key::Escape if suggestions.read().is_some() => {
+                            e.prevent_default();
+                            suggestions.set(None);
+                        }
+                        _ => {}
                     },
+                    onblur: move |_| suggestions.set(None),
                 }
+                if let Some(current) = suggestions() {
+                    div { class: "flex flex-wrap items-center gap-1.5 px-3 py-2 border-t border-gray-200 bg-white text-xs",
+                        for candidate in current.matches {
+                            {
+                                let chosen = candidate.clone();
+                                rsx! {
+                                    button {
+                                        class: "font-mono px-2 py-0.5 rounded border border-blue-200 bg-blue-50 text-blue-700 hover:bg-blue-100 transition-colors",
+                                        // Keep the editor focused so blur does not hide the list first.
+                                        onmousedown: move |e| e.prevent_default(),
+                                        onclick: move |_| {
+                                            if complete(std::slice::from_ref(&chosen)) {
+                                                suggestions.set(None);
+                                            }
+                                        },
+                                        "{candidate}"
+                                    }
+                                }
+                            }
+                        }
+                        span { class: "ml-auto text-gray-400", "Tab complete · Esc dismiss" }
+                    }
+                }
+            }
+
+            SqlHistoryBar {
+                sql,
+                on_run: move |query: String| {
+                    sql.set(query.clone());
+                    if !run_query.pending() {
+                        run_query.call(query);
+                    }
+                },
             }
 
             div { class: "min-h-[4rem]",
                 if run_query.pending() {
                     LoadingState { message: Some("Running query…".to_string()) }
+                } else if cancelled() {
+                    div {
+                        class: "rounded-lg border border-gray-200 bg-gray-50 px-4 py-3 text-sm text-gray-600",
+                        "Query cancelled"
+                    }
                 } else if let Some(Ok(df_signal)) = run_query.value() {
                     {
                         let df = df_signal();
@@ -485,9 +756,38 @@ fn SqlEditorPanel(
                         }
                     }
                 } else if let Some(Err(err)) = run_query.value() {
-                    AppErrorDisplay {
-                        error: AppError::Api(err.to_string()),
-                        title: Some("Query failed".to_string()),
+                    {
+                        let message = err.to_string();
+                        let suggestions = table_suggestions(&message);
+                        rsx! {
+                            AppErrorDisplay {
+                                error: AppError::Api(message),
+                                title: Some("Query failed".to_string()),
+                            }
+                            if let Some((typed, candidates)) = suggestions {
+                                div { class: "mt-2 flex flex-wrap items-center gap-2 text-xs text-gray-600",
+                                    span { "Did you mean" }
+                                    for candidate in candidates {
+                                        {
+                                            let typed = typed.clone();
+                                            let replacement = candidate.clone();
+                                            rsx! {
+                                                button {
+                                                    class: "font-mono px-2 py-1 rounded-md border border-blue-200 bg-blue-50 text-blue-700 hover:bg-blue-100 transition-colors",
+                                                    title: "Replace {typed} and run again",
+                                                    onclick: move |_| {
+                                                        let query = replace_table(&sql(), &typed, &replacement);
+                                                        sql.set(query.clone());
+                                                        run_query.call(query);
+                                                    },
+                                                    "{candidate}"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
                     }
                 } else {
                     div {
@@ -500,6 +800,34 @@ fn SqlEditorPanel(
     }
 }
 
+fn editor_element() -> Option<web_sys::HtmlTextAreaElement> {
+    web_sys::window()?
+        .document()?
+        .get_element_by_id(EDITOR_ID)?
+        .dyn_into()
+        .ok()
+}
+
+/// Caret position in characters (end of text when unknown).
+fn editor_caret(text: &str) -> usize {
+    editor_element()
+        .and_then(|el| el.selection_start().ok().flatten())
+        .map_or(text.chars().count(), |offset| {
+            utf16_to_char(text, offset as usize)
+        })
+}
+
+/// Move the caret once the re-rendered value is in the DOM.
+fn set_editor_caret(text: &str, index: usize) {
+    let offset = char_to_utf16(text, index) as u32;
+    gloo_timers::callback::Timeout::new(0, move || {
+        if let Some(el) = editor_element() {
+            let _ = el.set_selection_range(offset, offset);
+        }
+    })
+    .forget();
+}
+
 #[component]
 fn PreviewModal(
     title: String,
@@ -594,6 +922,297 @@ fn PreviewModal(
     }
 }
 
+/// Moving-average window sizes (in samples) offered by the series builder.
+const SMOOTHING_WINDOWS: &[usize] = &[5, 10, 30, 60];
+
+/// Target point count for "Plot"; the server reduces larger series.
+const PLOT_POINTS: usize = 600;
+
+const PLOT_COLORS: &[&str] = &["#2563eb", "#dc2626", "#16a34a", "#9333ea"];
+
+/// Writes a time-series query for the selected table into the editor,
+/// optionally adding a trailing moving average of the value column, or plots
+/// it directly through the downsampling chart endpoint.
+#[component]
+fn SeriesBuilder(fqtn: String, sql: Signal<String>) -> Element {
+    let mut ts_col = use_signal(|| "ts".to_string());
+    let mut value_col = use_signal(String::new);
+    let mut smoothing = use_signal(|| 0usize);
+    let mut plot = use_action(|(query, x): (String, String)| async move {
+        let mut request = ChartQueryRequest::expr(query).with_points(PLOT_POINTS);
+        request.x = Some(x);
+        ApiClient::new().chart_query(&request).await
+    });
+    let generated = series_sql(&fqtn, &ts_col(), &value_col(), smoothing());
+    let can_generate = generated.is_some();
+    let generated_for_plot = generated.clone();
+    let input_class = "px-2 py-1 w-32 font-mono text-xs rounded border border-gray-300 bg-white focus:outline-none focus:border-blue-500";
+    let button_class = |enabled: bool| {
+        format!(
+            "px-2 py-1 rounded border border-gray-300 bg-white text-gray-700 hover:bg-{} transition-colors {}",
+            colors::BTN_SECONDARY_HOVER,
+            if enabled { "" } else { "opacity-50 cursor-not-allowed" }
+        )
+    };
+
+    rsx! {
+        div { class: "space-y-2",
+            div { class: "flex flex-wrap items-center gap-2 text-xs text-gray-600",
+                span { class: "font-medium text-gray-700", "Series:" }
+                input {
+                    class: input_class,
+                    placeholder: "value column",
+                    value: "{value_col}",
+                    oninput: move |ev| value_col.set(ev.value()),
+                }
+                span { "over" }
+                input {
+                    class: input_class,
+                    placeholder: "ts",
+                    value: "{ts_col}",
+                    oninput: move |ev| ts_col.set(ev.value()),
+                }
+                select {
+                    class: "px-2 py-1 text-xs rounded border border-gray-300 bg-white",
+                    onchange: move |ev| smoothing.set(ev.value().parse().unwrap_or(0)),
+                    option { value: "0", selected: smoothing() == 0, "No smoothing" }
+                    for n in SMOOTHING_WINDOWS.iter().copied() {
+                        option { value: "{n}", selected: smoothing() == n, "Moving avg · {n} samples" }
+                    }
+                }
+                button {
+                    class: button_class(can_generate),
+                    disabled: !can_generate,
+                    onclick: move |_| {
+                        if let Some(q) = generated.clone() {
+                            sql.set(q);
+                        }
+                    },
+                    "Generate SQL"
+                }
+                button {
+                    class: button_class(can_generate && !plot.pending()),
+                    disabled: !can_generate || plot.pending(),
+                    onclick: move |_| {
+                        if let Some(q) = generated_for_plot.clone() {
+                            plot.call((q, ts_col().trim().to_string()));
+                        }
+                    },
+                    if plot.pending() { "Plotting…" } else { "Plot" }
+                }
+            }
+            if let Some(Ok(resp)) = plot.value() {
+                {
+                    let resp = resp();
+                    let x = ts_col().trim().to_string();
+                    rsx! {
+                        if let Some(note) = downsample_note(&resp.downsample) {
+                            div { class: "text-[11px] text-gray-500", "{note}" }
+                        }
+                        MetricsLineChart {
+                            title: fqtn.clone(),
+                            series: chart_series(&resp.dataframe, &x),
+                        }
+                    }
+                }
+            } else if let Some(Err(err)) = plot.value() {
+                AppErrorDisplay {
+                    error: AppError::Api(err.to_string()),
+                    title: Some("Plot failed".to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// One line per numeric column other than `x_col`.
+fn chart_series(df: &DataFrame, x_col: &str) -> Vec<ChartSeries> {
+    let Some(xi) = df.col_index(x_col) else {
+        return vec![];
+    };
+    let xs: Vec<Option<f64>> = (0..df.len())
+        .map(|r| ele_f64(&df.cols[xi].get(r)))
+        .collect();
+    df.names
+        .iter()
+        .enumerate()
+        .filter(|(ci, _)| *ci != xi)
+        .filter_map(|(ci, name)| {
+            let points: Vec<(f64, f64)> = xs
+                .iter()
+                .enumerate()
+                .filter_map(|(r, x)| Some(((*x)?, ele_f64(&df.cols[ci].get(r))?)))
+                .collect();
+            (!points.is_empty()).then(|| (name.clone(), points))
+        })
+        .enumerate()
+        .map(|(i, (label, points))| ChartSeries {
+            label,
+            points,
+            color: PLOT_COLORS[i % PLOT_COLORS.len()],
+        })
+        .collect()
+}
+
+fn ele_f64(ele: &Ele) -> Option<f64> {
+    match ele {
+        Ele::I32(v) => Some(*v as f64),
+        Ele::I64(v) | Ele::DateTime(v) => Some(*v as f64),
+        Ele::F32(v) => Some(*v as f64),
+        Ele::F64(v) => Some(*v),
+        Ele::DataTime(v) => Some(*v as f64),
+        _ => None,
+    }
+}
+
+fn downsample_note(info: &DownsampleInfo) -> Option<String> {
+    info.applied.then(|| {
+        let mode = match info.mode {
+            DownsampleMode::MinMax => "min/max per bucket",
+            DownsampleMode::Lttb => "LTTB",
+        };
+        format!(
+            "Downsampled {} → {} points ({mode})",
+            info.input_rows, info.output_rows
+        )
+    })
+}
+
+/// `SELECT ts, value[, moving average] FROM fqtn ORDER BY ts`.
+///
+/// `window` is the moving-average width in samples; `0`/`1` selects the raw
+/// series. Returns `None` unless both column names are plain identifiers.
+fn series_sql(fqtn: &str, ts_col: &str, value_col: &str, window: usize) -> Option<String> {
+    let (ts, value) = (ts_col.trim(), value_col.trim());
+    if !is_identifier(ts) || !is_identifier(value) {
+        return None;
+    }
+    let smoothed = if window > 1 {
+        format!(
+            ", avg({value}) OVER (ORDER BY {ts} ROWS BETWEEN {} PRECEDING AND CURRENT ROW) AS {value}_ma{window}",
+            window - 1
+        )
+    } else {
+        String::new()
+    };
+    Some(format!(
+        "SELECT {ts}, {value}{smoothed}\nFROM {fqtn}\nORDER BY {ts}"
+    ))
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Typed name and candidates from an engine error such as
+/// ``table `trace_evnt` not found. Did you mean `python.trace_event`?``.
+fn table_suggestions(message: &str) -> Option<(String, Vec<String>)> {
+    let rest = &message[message.find("table `")? + "table `".len()..];
+    let (typed, rest) = rest.split_once('`')?;
+    let rest = rest.strip_prefix(" not found. Did you mean ")?;
+    let end = rest.find('?')?;
+    let candidates: Vec<String> = rest[..end]
+        .split(", ")
+        .filter_map(|c| c.strip_prefix('`')?.strip_suffix('`'))
+        .map(str::to_string)
+        .collect();
+    (!candidates.is_empty()).then(|| (typed.to_string(), candidates))
+}
+
+/// `sql` with the first occurrence of `typed` replaced by `replacement`.
+fn replace_table(sql: &str, typed: &str, replacement: &str) -> String {
+    sql.replacen(typed, replacement, 1)
+}
+
+/// Id for `/query?id=`, unique enough among one browser's queries.
+fn new_query_id() -> String {
+    let salt = (js_sys::Math::random() * f64::from(u32::MAX)) as u32;
+    format!("sql-{}-{salt:08x}", js_sys::Date::now() as u64)
+}
+
 fn dataframe_row_count(df: &DataFrame) -> usize {
     df.cols.iter().map(|c| c.len()).max().unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn series_sql_adds_trailing_moving_average() {
+        assert_eq!(
+            series_sql("cpu.utilization", "ts", "cpu_total_pct", 60).as_deref(),
+            Some(
+                "SELECT ts, cpu_total_pct, avg(cpu_total_pct) OVER (ORDER BY ts ROWS BETWEEN 59 PRECEDING AND CURRENT ROW) AS cpu_total_pct_ma60\nFROM cpu.utilization\nORDER BY ts"
+            )
+        );
+        assert_eq!(
+            series_sql("cpu.utilization", " ts ", "rss_kb", 0).as_deref(),
+            Some("SELECT ts, rss_kb\nFROM cpu.utilization\nORDER BY ts")
+        );
+    }
+
+    #[test]
+    fn chart_series_plots_numeric_columns_against_x() {
+        use probing_proto::prelude::Seq;
+
+        let df = DataFrame::new(
+            vec!["ts".into(), "v".into(), "comm".into()],
+            vec![
+                Seq::SeqI64(vec![1, 2]),
+                Seq::SeqF64(vec![0.5, 1.5]),
+                Seq::SeqText(vec!["a".into(), "b".into()]),
+            ],
+        );
+        let series = chart_series(&df, "ts");
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].label, "v");
+        assert_eq!(series[0].points, vec![(1.0, 0.5), (2.0, 1.5)]);
+        assert!(chart_series(&df, "missing").is_empty());
+    }
+
+    #[test]
+    fn downsample_note_only_when_applied() {
+        let mut info = DownsampleInfo {
+            input_rows: 20_000,
+            output_rows: 598,
+            target_points: 600,
+            ..Default::default()
+        };
+        assert_eq!(downsample_note(&info), None);
+        info.applied = true;
+        assert_eq!(
+            downsample_note(&info).as_deref(),
+            Some("Downsampled 20000 → 598 points (min/max per bucket)")
+        );
+    }
+
+    #[test]
+    fn table_suggestions_parse_engine_errors() {
+        let (typed, candidates) = table_suggestions(
+            "API error: Error during planning: table `trace_stat` not found. \
+             Did you mean `python.trace_stats`, `python.trace_event`?",
+        )
+        .unwrap();
+        assert_eq!(typed, "trace_stat");
+        assert_eq!(candidates, vec!["python.trace_stats", "python.trace_event"]);
+        assert_eq!(
+            replace_table("SELECT * FROM trace_stat LIMIT 1", &typed, &candidates[0]),
+            "SELECT * FROM python.trace_stats LIMIT 1"
+        );
+        assert_eq!(
+            table_suggestions("table `tasks` is ambiguous: `cpu.tasks`, `gpu.tasks`."),
+            None
+        );
+        assert_eq!(table_suggestions("table 'x' not found"), None);
+    }
+
+    #[test]
+    fn series_sql_rejects_non_identifiers() {
+        assert_eq!(series_sql("t.x", "ts", "", 5), None);
+        assert_eq!(series_sql("t.x", "ts", "a; DROP", 5), None);
+        assert_eq!(series_sql("t.x", "1ts", "v", 5), None);
+    }
+}
@@ -2,25 +2,31 @@ use std::time::{Duration, SystemTime};
 
 use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
-use probing_proto::prelude::Node;
+use probing_proto::prelude::{Node, NodeMetrics, NodeMetricsResponse, NodeMetricsSample};
 
-use crate::api::ApiClient;
+use crate::api::{format_bytes, ApiClient};
 use crate::components::card::Card;
 use crate::components::colors::colors;
 use crate::components::common::{AsyncBoundary, EmptyState, ErrorState};
 use crate::components::icon::Icon;
 use crate::components::page::{PageContainer, PageTitle};
-use crate::components::poll_status::{ManualRefreshStatus, RefreshButton};
+use crate::components::poll_status::{AutoRefreshControl, RefreshButton};
 use crate::components::stat_card::StatCard;
-use crate::hooks::use_app_resource;
+use crate::hooks::{use_app_resource, use_auto_refresh, use_page_visible, AutoRefresh};
+use crate::utils::error::AppError;
 
 #[component]
 pub fn Cluster() -> Element {
-    let mut refresh = use_signal(|| 0u32);
-    let refresh_tick = refresh();
+    let visible = use_page_visible();
+    let refresh = use_auto_refresh(Some(visible));
     let nodes = use_app_resource(move || {
-        let _ = refresh();
-        async move { ApiClient::new().get_nodes().await }
+        let _ = refresh.tick();
+        refresh.track(async move { ApiClient::new().get_nodes().await })
+    });
+    // Separate from the node list so a metrics failure never hides nodes.
+    let metrics = use_app_resource(move || {
+        let _ = refresh.tick();
+        refresh.track(async move { ApiClient::new().get_node_metrics().await })
     });
 
     rsx! {
@@ -30,15 +36,13 @@ pub fn Cluster() -> Element {
                 subtitle: Some("Distributed training nodes and health".to_string()),
                 icon: Some(&icondata::AiClusterOutlined),
                 header_right: Some(rsx! {
-                    ManualRefreshStatus { refresh_tick }
-                    RefreshButton {
-                        onclick: move |_| refresh.set(refresh() + 1),
-                    }
+                    AutoRefreshControl { refresh }
+                    RefreshButton { onclick: move |_| refresh.refresh_now() }
                 }),
             }
             AsyncBoundary {
                 message: Some("Loading cluster nodes…".to_string()),
-                ClusterBody { nodes: nodes(), refresh }
+                ClusterBody { nodes: nodes(), metrics: metrics(), refresh }
             }
         }
     }
@@ -46,8 +50,9 @@ pub fn Cluster() -> Element {
 
 #[component]
 fn ClusterBody(
-    nodes: Option<Result<Vec<Node>, crate::utils::error::AppError>>,
-    refresh: Signal<u32>,
+    nodes: Option<Result<Vec<Node>, AppError>>,
+    metrics: Option<Result<NodeMetricsResponse, AppError>>,
+    refresh: AutoRefresh,
 ) -> Element {
     let Some(result) = nodes else {
         return rsx! { div {} };
@@ -70,6 +75,11 @@ fn ClusterBody(
             }
         },
         Ok(nodes) => {
+            let (metrics, metrics_error) = match metrics {
+                Some(Ok(metrics)) => (Some(metrics), None),
+                Some(Err(err)) => (None, Some(err.display_message())),
+                None => (None, None),
+            };
             let total = nodes.len();
             let healthy = nodes
                 .iter()
@@ -112,15 +122,20 @@ fn ClusterBody(
                                 colors::CONTENT_ACCENT_BG,
                                 colors::BTN_SECONDARY_HOVER,
                             ),
-                            onclick: move |_| refresh.set(refresh() + 1),
+                            onclick: move |_| refresh.refresh_now(),
                             Icon { icon: &icondata::AiReloadOutlined, class: "w-3.5 h-3.5" }
                             "Refresh nodes"
                         }
                     }
+                    if let Some(err) = metrics_error {
+                        p { class: "text-xs text-amber-700",
+                            "Node metrics unavailable: {err}"
+                        }
+                    }
                     Card {
                         title: "Nodes",
                         content_class: Some("p-0"),
-                        ClusterTable { nodes }
+                        ClusterTable { nodes, metrics }
                     }
                 }
             }
@@ -144,8 +159,104 @@ fn node_status_tone(node: &Node) -> NodeTone {
     }
 }
 
+/// Heartbeat freshness: green while within the report cadence, yellow when
+/// late, red once past the server's stale threshold (or marked `dead`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HeartbeatTone {
+    Fresh,
+    Late,
+    Stale,
+}
+
+fn heartbeat_tone(node: &Node, now_us: u64, stale_after_secs: u64) -> HeartbeatTone {
+    if node.status.as_deref() == Some("dead") {
+        return HeartbeatTone::Stale;
+    }
+    let age_secs = now_us.saturating_sub(node.timestamp) / 1_000_000;
+    // Reporters back off to at most ~3/4 of the stale threshold.
+    if age_secs * 4 <= stale_after_secs * 3 {
+        HeartbeatTone::Fresh
+    } else if age_secs <= stale_after_secs {
+        HeartbeatTone::Late
+    } else {
+        HeartbeatTone::Stale
+    }
+}
+
+/// `4s ago`, `3m ago`, `2h ago`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s ago"),
+        60..=3599 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
+/// SVG polyline points for `values` scaled into `width` × `height`, with
+/// `max` at the top edge; `None` values are skipped.
+fn sparkline_points(values: &[Option<f64>], max: f64, width: f64, height: f64) -> String {
+    let step = if values.len() > 1 {
+        width / (values.len() - 1) as f64
+    } else {
+        0.0
+    };
+    values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            let y = height - (v?.clamp(0.0, max) / max) * height;
+            Some(format!("{:.1},{:.1}", i as f64 * step, y))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const SPARK_W: f64 = 80.0;
+const SPARK_H: f64 = 20.0;
+
+/// CPU% (blue) and GPU util (violet) over the polled history.
+#[component]
+fn NodeSparkline(history: Vec<NodeMetricsSample>) -> Element {
+    if history.len() < 2 {
+        return rsx! { span { class: "text-gray-300", "—" } };
+    }
+    let cpu: Vec<Option<f64>> = history.iter().map(|s| s.cpu_pct).collect();
+    let gpu: Vec<Option<f64>> = history.iter().map(|s| s.gpu_util_pct).collect();
+    // CPU% exceeds 100 on multi-core processes.
+    let max = cpu
+        .iter()
+        .chain(gpu.iter())
+        .flatten()
+        .fold(100.0f64, |a, b| a.max(*b));
+    let cpu_points = sparkline_points(&cpu, max, SPARK_W, SPARK_H);
+    let gpu_points = sparkline_points(&gpu, max, SPARK_W, SPARK_H);
+    rsx! {
+        svg {
+            width: "{SPARK_W}",
+            height: "{SPARK_H}",
+            view_box: "0 0 {SPARK_W} {SPARK_H}",
+            class: "overflow-visible",
+            polyline { points: "{cpu_points}", fill: "none", stroke: "#3b82f6", stroke_width: "1.5" }
+            if gpu.iter().any(Option::is_some) {
+                polyline { points: "{gpu_points}", fill: "none", stroke: "#8b5cf6", stroke_width: "1.5" }
+            }
+        }
+    }
+}
+
+fn format_opt_pct(v: Option<f64>) -> String {
+    v.map(|v| format!("{v:.0}%"))
+        .unwrap_or_else(|| "—".to_string())
+}
+
 #[component]
-fn ClusterTable(nodes: Vec<Node>) -> Element {
+fn ClusterTable(nodes: Vec<Node>, metrics: Option<NodeMetricsResponse>) -> Element {
+    let now_us = (js_sys::Date::now() * 1000.0) as u64;
+    let stale_after_secs = metrics.as_ref().map_or(25, |m| m.stale_after_secs.max(1));
+    let by_addr: std::collections::HashMap<String, NodeMetrics> = metrics
+        .map(|m| m.nodes.into_iter().map(|n| (n.addr.clone(), n)).collect())
+        .unwrap_or_default();
+
     rsx! {
         div { class: "overflow-x-auto",
             table { class: "w-full border-collapse text-sm",
@@ -157,6 +268,10 @@ fn ClusterTable(nodes: Vec<Node>) -> Element {
                         th { class: "px-4 py-2 font-medium", "Role" }
                         th { class: "px-4 py-2 font-medium", "Status" }
                         th { class: "px-4 py-2 font-medium", "Last seen" }
+                        th { class: "px-4 py-2 font-medium text-right", "CPU" }
+                        th { class: "px-4 py-2 font-medium text-right", "RSS" }
+                        th { class: "px-4 py-2 font-medium text-right", "GPU" }
+                        th { class: "px-4 py-2 font-medium", title: "CPU% (blue) and GPU util (violet)", "Trend" }
                     }
                 }
                 tbody {
@@ -166,6 +281,12 @@ fn ClusterTable(nodes: Vec<Node>) -> Element {
                                 + Duration::from_micros(node.timestamp))
                                 .into();
                             let timestamp_str = datetime.format("%H:%M:%S").to_string();
+                            let age = format_age(now_us.saturating_sub(node.timestamp) / 1_000_000);
+                            let (dot, age_class) = match heartbeat_tone(&node, now_us, stale_after_secs) {
+                                HeartbeatTone::Fresh => ("bg-emerald-500", "text-gray-500"),
+                                HeartbeatTone::Late => ("bg-amber-400", "text-amber-700"),
+                                HeartbeatTone::Stale => ("bg-red-500", "text-red-700"),
+                            };
                             let url = format!("http://{}", node.addr);
                             let tone = node_status_tone(&node);
                             let (badge_bg, badge_text) = match tone {
@@ -174,9 +295,15 @@ fn ClusterTable(nodes: Vec<Node>) -> Element {
                                 NodeTone::Unknown => ("bg-gray-100 text-gray-600 border-gray-200", "Unknown"),
                             };
                             let status_label = node.status.clone().unwrap_or_else(|| badge_text.to_string());
+                            let node_metrics = by_addr.get(&node.addr).cloned().unwrap_or_default();
+                            let latest = node_metrics.history.last().cloned();
+                            let poll_error = node_metrics.error.clone();
                             rsx! {
                                 tr { class: "border-b border-gray-100 hover:bg-gray-50/80",
-                                    td { class: "px-4 py-2.5 font-medium text-gray-900", "{node.host}" }
+                                    td { class: "px-4 py-2.5 font-medium text-gray-900",
+                                        span { class: "inline-block w-2 h-2 rounded-full mr-2 {dot}" }
+                                        "{node.host}"
+                                    }
                                     td { class: "px-4 py-2.5",
                                         a {
                                             href: "{url}",
@@ -200,7 +327,25 @@ fn ClusterTable(nodes: Vec<Node>) -> Element {
                                             "{status_label}"
                                         }
                                     }
-                                    td { class: "px-4 py-2.5 font-mono text-xs text-gray-500", "{timestamp_str}" }
+                                    td { class: "px-4 py-2.5 font-mono text-xs {age_class}", title: "{timestamp_str}", "{age}" }
+                                    if let Some(err) = poll_error {
+                                        td { class: "px-4 py-2.5 text-xs text-red-700", colspan: "3", title: "{err}",
+                                            "poll failed"
+                                        }
+                                    } else {
+                                        td { class: "px-4 py-2.5 font-mono text-xs text-right text-gray-700",
+                                            {format_opt_pct(latest.as_ref().and_then(|s| s.cpu_pct))}
+                                        }
+                                        td { class: "px-4 py-2.5 font-mono text-xs text-right text-gray-700",
+                                            {latest.as_ref().and_then(|s| s.rss_kb).map(|kb| format_bytes(kb * 1024)).unwrap_or_else(|| "—".to_string())}
+                                        }
+                                        td { class: "px-4 py-2.5 font-mono text-xs text-right text-gray-700",
+                                            {format_opt_pct(latest.as_ref().and_then(|s| s.gpu_util_pct))}
+                                        }
+                                    }
+                                    td { class: "px-4 py-2.5",
+                                        NodeSparkline { history: node_metrics.history.clone() }
+                                    }
                                 }
                             }
                         }
@@ -210,3 +355,47 @@ fn ClusterTable(nodes: Vec<Node>) -> Element {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_seen_at(timestamp: u64, status: Option<&str>) -> Node {
+        Node {
+            timestamp,
+            status: status.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn heartbeat_tone_follows_age_and_stale_threshold() {
+        let now = 100_000_000;
+        let at = |age_secs: u64| node_seen_at(now - age_secs * 1_000_000, Some("ok"));
+        assert_eq!(heartbeat_tone(&at(5), now, 25), HeartbeatTone::Fresh);
+        assert_eq!(heartbeat_tone(&at(18), now, 25), HeartbeatTone::Fresh);
+        assert_eq!(heartbeat_tone(&at(22), now, 25), HeartbeatTone::Late);
+        assert_eq!(heartbeat_tone(&at(40), now, 25), HeartbeatTone::Stale);
+        assert_eq!(
+            heartbeat_tone(&node_seen_at(now, Some("dead")), now, 25),
+            HeartbeatTone::Stale
+        );
+    }
+
+    #[test]
+    fn formats_heartbeat_age() {
+        assert_eq!(format_age(4), "4s ago");
+        assert_eq!(format_age(185), "3m ago");
+        assert_eq!(format_age(7_300), "2h ago");
+    }
+
+    #[test]
+    fn sparkline_scales_and_skips_gaps() {
+        let points = sparkline_points(&[Some(0.0), None, Some(100.0)], 100.0, 80.0, 20.0);
+        assert_eq!(points, "0.0,20.0 80.0,0.0");
+        assert_eq!(
+            sparkline_points(&[Some(250.0)], 100.0, 80.0, 20.0),
+            "0.0,0.0"
+        );
+    }
+}
@@ -3,15 +3,15 @@
 use std::collections::HashMap;
 
 use dioxus::prelude::*;
-use dioxus_router::use_navigator;
+use dioxus_router::{use_navigator, Link};
 use probing_proto::prelude::Process;
 
 use crate::app::Route;
 
 use crate::api::{
-    format_bytes, format_cpu_ms, format_opt_pct, format_pct, format_rss, gpu_device_label,
-    ApiClient, CpuHistorySample, CpuSnapshot, CpuThreadRow, GpuDeviceRow, GpuHistorySample,
-    GpuSnapshot,
+    format_bytes, format_cpu_ms, format_opt_pct, format_pct, format_rss, format_thread_age,
+    gpu_device_label, ApiClient, CpuHistorySample, CpuSnapshot, CpuThreadRow, GpuDeviceRow,
+    GpuHistorySample, GpuSnapshot, PythonThreadRow, ThreadCountSample,
 };
 use crate::components::card::Card;
 use crate::components::colors::colors;
@@ -19,16 +19,35 @@ use crate::components::common::{EmptyState, ErrorState, LoadingState};
 use crate::components::cpu_threads_table::CpuThreadsTable;
 use crate::components::data::KeyValueList;
 use crate::components::page::{PageContainer, PageTitle};
-use crate::components::poll_status::PollStatusBar;
+use crate::components::poll_status::AutoRefreshControl;
+use crate::components::report_button::ExportReportButton;
+use crate::components::rl::{ChartSeries, MetricsLineChart};
 use crate::components::stat_card::StatCard;
 use crate::hooks::{
-    use_api, use_api_with_options, use_page_visible, use_poll_tick_gated, ApiFetchOptions,
+    use_api, use_api_with_options, use_auto_refresh, use_page_visible, ApiFetchOptions,
 };
+use crate::state::auto_refresh::AUTO_REFRESH_SECS;
 use crate::state::investigation::sync_overview_process_context;
+use crate::utils::report::{stacked_bars_svg, Report, ReportBlock, ReportMeta};
 
-const CPU_POLL_MS: u32 = 2000;
 const ENV_VARS_PREVIEW: usize = 40;
 const THREADS_PREVIEW: usize = 80;
+const PY_THREAD_EVENTS: usize = 500;
+const PY_THREADS_OLDEST: usize = 8;
+
+// Hex equivalents of the sparkline Tailwind colors, for exported SVG.
+const CPU_USER_HEX: &str = "#3b82f6";
+const CPU_SYS_HEX: &str = "#f59e0b";
+const GPU_UTIL_HEX: &str = "#8b5cf6";
+const GPU_MEM_HEX: &str = "#10b981";
+
+/// Chart footnote for the current auto-refresh interval.
+fn refresh_hint() -> String {
+    match AUTO_REFRESH_SECS() {
+        0 => "Auto refresh off".to_string(),
+        secs => format!("Updates every {secs}s"),
+    }
+}
 
 fn refresh_options() -> ApiFetchOptions {
     ApiFetchOptions {
@@ -39,9 +58,8 @@ fn refresh_options() -> ApiFetchOptions {
 #[component]
 pub fn Dashboard() -> Element {
     let visible = use_page_visible();
-    let poll = use_poll_tick_gated(CPU_POLL_MS, Some(visible));
+    let auto = use_auto_refresh(Some(visible));
     let refresh = refresh_options();
-    let poll_tick = poll();
 
     let overview = use_api(|| {
         let client = ApiClient::new();
@@ -50,59 +68,93 @@ pub fn Dashboard() -> Element {
 
     let cpu_latest = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_cpu_latest().await }
+            auto.track(async move { client.fetch_cpu_latest().await })
         },
         refresh,
     );
 
     let cpu_history = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_cpu_history(60).await }
+            auto.track(async move { client.fetch_cpu_history(300, 60).await })
         },
         refresh,
     );
 
     let cpu_threads = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
+            let client = ApiClient::new();
+            auto.track(async move { client.fetch_cpu_top_threads(15).await })
+        },
+        refresh,
+    );
+
+    let python_threads = use_api_with_options(
+        move || {
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_cpu_top_threads(15).await }
+            auto.track(async move {
+                let alive = client.fetch_python_threads().await?;
+                let history = client
+                    .fetch_thread_count_history(PY_THREAD_EVENTS, alive.len() as i64)
+                    .await?;
+                Ok((alive, history))
+            })
         },
         refresh,
     );
 
     let gpu_devices = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_gpu_devices().await }
+            auto.track(async move { client.fetch_gpu_devices().await })
         },
         refresh,
     );
 
     let gpu_latest = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_gpu_latest().await }
+            auto.track(async move { client.fetch_gpu_latest().await })
         },
         refresh,
     );
 
     let gpu_history = use_api_with_options(
         move || {
-            let _ = poll();
+            let _ = auto.tick();
             let client = ApiClient::new();
-            async move { client.fetch_gpu_history(60).await }
+            auto.track(async move { client.fetch_gpu_history(60).await })
         },
         refresh,
     );
 
     let show_gpu = gpu_has_data(&gpu_devices, &gpu_latest);
+    let export_report = {
+        let (overview, cpu_latest, cpu_history, cpu_threads, gpu_latest, gpu_history) = (
+            overview.clone(),
+            cpu_latest.clone(),
+            cpu_history.clone(),
+            cpu_threads.clone(),
+            gpu_latest.clone(),
+            gpu_history.clone(),
+        );
+        move |_: ()| {
+            dashboard_report(
+                &overview,
+                &cpu_latest,
+                &cpu_history,
+                &cpu_threads,
+                show_gpu.then_some((&gpu_latest, &gpu_history)),
+            )
+        }
+    };
 
     rsx! {
         PageContainer {
@@ -115,13 +167,14 @@ pub fn Dashboard() -> Element {
                 }),
                 icon: Some(&icondata::AiLineChartOutlined),
                 header_right: Some(rsx! {
-                    PollStatusBar {
-                        interval_secs: CPU_POLL_MS / 1000,
-                        poll_tick,
+                    div { class: "flex items-center gap-2",
+                        AutoRefreshControl { refresh: auto }
+                        ExportReportButton { build: export_report }
                     }
                 }),
             }
             {cpu_section(&cpu_latest, &cpu_history, &cpu_threads)}
+            {python_threads_section(&python_threads)}
             if show_gpu {
                 {gpu_section(&gpu_devices, &gpu_latest, &gpu_history)}
             }
@@ -293,7 +346,8 @@ fn cpu_trend_panel(
             CpuTimeSparkline { samples: samples.clone() }
             div { class: "flex flex-wrap items-center justify-between gap-2",
                 p { class: "text-xs text-gray-400",
-                    "Updates every {CPU_POLL_MS / 1000}s · click the latest bar to open profile for this interval"
+                    {refresh_hint()}
+                    " · click the latest bar to open profile for this interval"
                 }
                 ProfileExemplarButton {}
             }
@@ -327,6 +381,107 @@ fn cpu_threads_panel(state: &crate::hooks::ApiState<Vec<CpuThreadRow>>) -> Eleme
     }
 }
 
+type PythonThreadsState = crate::hooks::ApiState<(Vec<PythonThreadRow>, Vec<ThreadCountSample>)>;
+
+/// Python thread lifecycle from `python.threads`: alive count over time and
+/// the longest-running threads.
+fn python_threads_section(state: &PythonThreadsState) -> Element {
+    let body = if state.is_loading() {
+        rsx! { LoadingState { message: Some("Loading Python threads…".to_string()) } }
+    } else {
+        match state.data.read().as_ref() {
+            Some(Ok((alive, history))) if !alive.is_empty() => python_threads_panel(alive, history),
+            Some(Err(e)) => rsx! {
+                ErrorState { error: e.display_message(), title: None }
+            },
+            _ => rsx! {
+                EmptyState { message: "No Python threads reported yet.".to_string() }
+            },
+        }
+    };
+    rsx! {
+        div { class: "mb-6",
+            Card {
+                title: "Python Threads",
+                content_class: Some("p-4"),
+                {body}
+            }
+        }
+    }
+}
+
+fn python_threads_panel(alive: &[PythonThreadRow], history: &[ThreadCountSample]) -> Element {
+    let tracked = alive.iter().filter(|t| t.age_s.is_some()).count();
+    let daemons = alive.iter().filter(|t| t.daemon).count();
+    let series = vec![ChartSeries {
+        label: "alive".to_string(),
+        points: history
+            .iter()
+            .map(|s| (s.ts_us as f64 / 1e6, s.alive as f64))
+            .collect(),
+        color: CPU_USER_HEX,
+    }];
+    let oldest: Vec<PythonThreadRow> = alive
+        .iter()
+        .filter(|t| t.age_s.is_some())
+        .take(PY_THREADS_OLDEST)
+        .cloned()
+        .collect();
+
+    rsx! {
+        div { class: "space-y-4",
+            div { class: "grid grid-cols-3 gap-4",
+                StatCard { label: "Alive", value: format!("{}", alive.len()) }
+                StatCard {
+                    label: "Tracked",
+                    value: format!("{tracked}"),
+                    hint: Some("started after probing loaded".to_string()),
+                }
+                StatCard { label: "Daemon", value: format!("{daemons}") }
+            }
+            MetricsLineChart {
+                title: "Alive threads (per start/end event)".to_string(),
+                series,
+                height: 200.0,
+            }
+            if !oldest.is_empty() {
+                table { class: "w-full text-sm",
+                    thead {
+                        tr { class: "text-left text-xs text-gray-500 border-b",
+                            th { class: "py-1 pr-3", "Thread" }
+                            th { class: "py-1 pr-3", "Target" }
+                            th { class: "py-1 pr-3", "Created by" }
+                            th { class: "py-1 text-right", "Age" }
+                        }
+                    }
+                    tbody {
+                        for t in oldest {
+                            tr { key: "{t.tid}", class: "border-b border-gray-100",
+                                td { class: "py-1 pr-3",
+                                    Link {
+                                        to: Route::StackWithTidPage { tid: t.tid.to_string() },
+                                        class: "text-blue-700 hover:underline",
+                                        "{t.name} ({t.tid})"
+                                    }
+                                }
+                                td { class: "py-1 pr-3 font-mono text-xs text-gray-600 truncate max-w-xs",
+                                    "{t.target}"
+                                }
+                                td { class: "py-1 pr-3 text-gray-600",
+                                    "{t.creator_name} ({t.creator_tid})"
+                                }
+                                td { class: "py-1 text-right tabular-nums",
+                                    {format_thread_age(t.age_s)}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn ProfileExemplarButton() -> Element {
     let nav = use_navigator();
@@ -794,7 +949,8 @@ fn gpu_trend_panel(
                 }
             }
             p { class: "text-xs text-gray-400",
-                "Updates every {CPU_POLL_MS / 1000}s · one series per device"
+                {refresh_hint()}
+                " · one series per device"
             }
         }
     }
@@ -1030,3 +1186,242 @@ fn EnvVars(env: std::collections::HashMap<String, String>) -> Element {
         }
     }
 }
+
+fn loaded<T: Clone + 'static>(state: &crate::hooks::ApiState<T>) -> Option<T> {
+    state
+        .data
+        .peek()
+        .as_ref()
+        .and_then(|r| r.as_ref().ok())
+        .cloned()
+}
+
+/// Static snapshot of the data currently shown on the Dashboard.
+fn dashboard_report(
+    overview: &crate::hooks::ApiState<Process>,
+    cpu_latest: &crate::hooks::ApiState<Option<CpuSnapshot>>,
+    cpu_history: &crate::hooks::ApiState<Vec<CpuHistorySample>>,
+    cpu_threads: &crate::hooks::ApiState<Vec<CpuThreadRow>>,
+    gpu: Option<(
+        &crate::hooks::ApiState<Vec<GpuSnapshot>>,
+        &crate::hooks::ApiState<HashMap<i32, Vec<GpuHistorySample>>>,
+    )>,
+) -> Report {
+    let history = loaded(cpu_history).unwrap_or_default();
+    let mut report =
+        Report::new(ReportMeta::now("Dashboard").with_time_range(cpu_time_range(&history)));
+
+    if let Some(Some(snap)) = loaded(cpu_latest) {
+        report.push(ReportBlock::KeyValues {
+            title: "CPU (latest sample)".to_string(),
+            items: vec![
+                (
+                    "User CPU".to_string(),
+                    format!(
+                        "{} · {}",
+                        format_cpu_ms(snap.delta_user_ns),
+                        format_pct(snap.cpu_user_pct)
+                    ),
+                ),
+                (
+                    "Kernel CPU".to_string(),
+                    format!(
+                        "{} · {}",
+                        format_cpu_ms(snap.delta_sys_ns),
+                        format_pct(snap.cpu_sys_pct)
+                    ),
+                ),
+                (
+                    "Total CPU".to_string(),
+                    format!(
+                        "{} · {}",
+                        format_cpu_ms(snap.delta_total_ns),
+                        format_pct(snap.cpu_total_pct)
+                    ),
+                ),
+                ("Memory (RSS)".to_string(), format_rss(snap.rss_kb)),
+                ("Threads".to_string(), snap.thread_count.to_string()),
+                (
+                    "Context switches".to_string(),
+                    format!(
+                        "vol {} · invol {}",
+                        snap.delta_vol_ctxt, snap.delta_invol_ctxt
+                    ),
+                ),
+                ("Platform".to_string(), snap.platform),
+            ],
+        });
+    }
+    if !history.is_empty() {
+        let bars: Vec<(f32, f32)> = history.iter().map(|s| (s.sys_ms, s.user_ms)).collect();
+        report.push(ReportBlock::Chart {
+            title: "CPU Time Trend (per sample)".to_string(),
+            svg: stacked_bars_svg(&bars, (CPU_SYS_HEX, CPU_USER_HEX), 80.0),
+            caption: Some(format!(
+                "{} samples · blue = user, amber = kernel",
+                history.len()
+            )),
+        });
+    }
+    if let Some(threads) = loaded(cpu_threads) {
+        report.push(ReportBlock::Table {
+            title: "Top CPU Threads".to_string(),
+            columns: [
+                "TID",
+                "Name",
+                "State",
+                "Wait channel",
+                "User",
+                "Kernel",
+                "Total",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows: threads
+                .iter()
+                .map(|t| {
+                    vec![
+                        t.tid.to_string(),
+                        t.name.clone(),
+                        t.state.clone(),
+                        t.wchan.clone().unwrap_or_default(),
+                        format_cpu_ms(t.delta_user_ns),
+                        format_cpu_ms(t.delta_sys_ns),
+                        format_cpu_ms(t.delta_total_ns),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    report.push(ReportBlock::Placeholder {
+        title: "CPU profile".to_string(),
+        widget: "pprof flamegraph".to_string(),
+        config: vec![
+            ("Page".to_string(), "Profiling → pprof".to_string()),
+            ("Window".to_string(), "latest sampling interval".to_string()),
+        ],
+    });
+
+    if let Some((gpu_latest, gpu_history)) = gpu {
+        let snapshots = loaded(gpu_latest).unwrap_or_default();
+        report.push(ReportBlock::Table {
+            title: "GPU".to_string(),
+            columns: [
+                "Device",
+                "Backend",
+                "GPU util",
+                "Memory",
+                "Used / total",
+                "Detail",
+            ]
+            .map(String::from)
+            .to_vec(),
+            rows: snapshots
+                .iter()
+                .map(|s| {
+                    vec![
+                        gpu_device_label(s.device_id, &s.name),
+                        s.backend.clone(),
+                        format_opt_pct(s.gpu_util_pct),
+                        format_pct(s.mem_used_pct),
+                        format!(
+                            "{} / {}",
+                            format_bytes(s.used_bytes),
+                            format_bytes(s.total_bytes)
+                        ),
+                        gpu_util_hint(s),
+                    ]
+                })
+                .collect(),
+        });
+        let history_map = loaded(gpu_history).unwrap_or_default();
+        let mut device_ids: Vec<i32> = history_map.keys().copied().collect();
+        device_ids.sort();
+        for id in device_ids {
+            let samples = &history_map[&id];
+            let label = snapshots
+                .iter()
+                .find(|s| s.device_id == id)
+                .map(|s| gpu_device_label(id, &s.name))
+                .unwrap_or_else(|| format!("GPU {id}"));
+            let bars: Vec<(f32, f32)> = samples
+                .iter()
+                .map(|s| (s.mem_used_pct, s.gpu_util_pct))
+                .collect();
+            report.push(ReportBlock::Chart {
+                title: format!("{label} utilization trend"),
+                svg: stacked_bars_svg(&bars, (GPU_MEM_HEX, GPU_UTIL_HEX), 80.0),
+                caption: Some(format!(
+                    "{} samples · violet = GPU util %, emerald = memory used %",
+                    samples.len()
+                )),
+            });
+        }
+    }
+
+    if let Some(process) = loaded(overview) {
+        report.push(ReportBlock::KeyValues {
+            title: "Process Information".to_string(),
+            items: vec![
+                ("Process ID (PID)".to_string(), process.pid.to_string()),
+                ("Executable Path".to_string(), process.exe.clone()),
+                ("Command Line".to_string(), process.cmd.clone()),
+                ("Working Directory".to_string(), process.cwd.clone()),
+                ("Threads".to_string(), process.threads.len().to_string()),
+            ],
+        });
+        // Values often hold tokens or credentials; a shareable artifact keeps names only.
+        let mut names: Vec<String> = process.env.keys().cloned().collect();
+        names.sort();
+        report.push(ReportBlock::Table {
+            title: format!(
+                "Environment Variables ({} names, values omitted)",
+                names.len()
+            ),
+            columns: vec!["Name".to_string()],
+            rows: names.into_iter().map(|n| vec![n]).collect(),
+        });
+    }
+    report
+}
+
+/// `first – last UTC (n samples)` from CPU history timestamps.
+fn cpu_time_range(history: &[CpuHistorySample]) -> Option<String> {
+    let stamps = history.iter().map(|s| s.ts_us).filter(|ts| *ts > 0);
+    let first = stamps.clone().min()?;
+    let last = stamps.max()?;
+    let fmt = |us: i64| {
+        chrono::DateTime::from_timestamp_micros(us)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| us.to_string())
+    };
+    Some(format!(
+        "{} – {} UTC ({} samples)",
+        fmt(first),
+        fmt(last),
+        history.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_time_range_skips_unstamped_samples() {
+        let sample = |ts_us| CpuHistorySample {
+            ts_us,
+            ..Default::default()
+        };
+        assert_eq!(cpu_time_range(&[sample(0)]), None);
+        assert_eq!(
+            cpu_time_range(&[
+                sample(0),
+                sample(1_700_000_000_000_000),
+                sample(1_700_000_060_000_000)
+            ])
+            .as_deref(),
+            Some("2023-11-14 22:13:20 – 2023-11-14 22:14:20 UTC (3 samples)")
+        );
+    }
+}
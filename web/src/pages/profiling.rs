@@ -1,9 +1,12 @@
+use std::rc::Rc;
+
 use dioxus::prelude::*;
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, TraceSource};
 use crate::components::common::AsyncBoundary;
 use crate::components::flamegraph::{FlamegraphPayload, FlamegraphView};
 use crate::components::page::PageTitle;
+use crate::components::profile_compare::ProfileCompare;
 use crate::components::profile_snapshot_bar::ProfileSnapshotBar;
 use crate::components::profiling::{
     ProfilerDisabledNotice, ProfilingContentPanel, ProfilingErrorPanel, ProfilingFeedbackToast,
@@ -12,14 +15,17 @@ use crate::components::profiling::{
 };
 use crate::components::profiling_sidebar_hint::ProfilingSidebarHint;
 use crate::hooks::use_app_resource;
+use crate::state::api_errors::{with_error_toast, RetryFn};
 use crate::state::investigation::{
     clear_profiling_thread_filter, INVESTIGATION_CONTEXT, PROFILING_THREAD_FILTER,
 };
 use crate::state::profiling::{
     apply_profiler_config, normalize_profiling_view, profiling_view_spec, PROFILING_CHROME_LIMIT,
-    PROFILING_CONFIG_LOADED, PROFILING_PPROF_FREQ, PROFILING_PYTORCH_TIMELINE_RELOAD,
-    PROFILING_RAY_TIMELINE_RELOAD, PROFILING_TORCH_ENABLED, PROFILING_TRACE_RELOAD,
+    PROFILING_CONFIG_LOADED, PROFILING_CONFIG_RELOAD, PROFILING_PPROF_FREQ,
+    PROFILING_PYTORCH_TIMELINE_RELOAD, PROFILING_RAY_TIMELINE_RELOAD, PROFILING_TORCH_ENABLED,
+    PROFILING_TORCH_METRIC, PROFILING_TRACE_RELOAD,
 };
+use crate::state::profiling_url::ProfilingUrlSync;
 
 #[component]
 pub fn Profiling(view: String) -> Element {
@@ -30,6 +36,7 @@ pub fn Profiling(view: String) -> Element {
 
     rsx! {
         ProfilingFeedbackToast {}
+        ProfilingUrlSync { view: current_view.clone() }
         div {
             class: "flex flex-col flex-1 min-h-0 h-full gap-4",
             PageTitle {
@@ -54,9 +61,12 @@ fn view_icon(view: &str) -> &'static icondata::Icon {
     match view {
         "pprof" => &icondata::CgPerformance,
         "torch" => &icondata::SiPytorch,
+        "spanflame" => &icondata::AiApiOutlined,
         "trace" => &icondata::AiThunderboltOutlined,
+        "combined" => &icondata::AiMergeCellsOutlined,
         "pytorch" => &icondata::SiPytorch,
         "ray" => &icondata::AiClockCircleOutlined,
+        "compare" => &icondata::AiDiffOutlined,
         _ => &icondata::AiSearchOutlined,
     }
 }
@@ -65,9 +75,12 @@ fn view_subtitle(view: &str) -> String {
     match view {
         "pprof" => "SIGPROF stack explorer · statistical sampling".to_string(),
         "torch" => "Module flamegraph from TorchProbe hooks".to_string(),
+        "spanflame" => "Span self time per stack of span names".to_string(),
         "trace" => "Chrome trace events from probing buffers — not distributed spans".to_string(),
+        "combined" => "Probing spans and PyTorch profiler events on one timeline".to_string(),
         "pytorch" => "PyTorch profiler chrome trace".to_string(),
         "ray" => "Ray task timeline".to_string(),
+        "compare" => "Differential flamegraph of two captured snapshots".to_string(),
         _ => "Profiling views".to_string(),
     }
 }
@@ -76,32 +89,47 @@ fn view_subtitle(view: &str) -> String {
 fn ProfilerConfigGate(view: String) -> Element {
     let trace_reload = *PROFILING_TRACE_RELOAD.read();
     let trace_limit = *PROFILING_CHROME_LIMIT.read();
+    let trace_source = if view == "combined" {
+        TraceSource::Combined
+    } else {
+        TraceSource::Spans
+    };
 
-    let _config = use_app_resource(|| async move {
-        let client = ApiClient::new();
-        let result = client.get_profiler_config().await;
-        match &result {
-            Ok(config) => apply_profiler_config(config),
-            Err(_) => *PROFILING_CONFIG_LOADED.write() = true,
+    let _config = use_app_resource(|| {
+        let _ = *PROFILING_CONFIG_RELOAD.read();
+        async move {
+            let retry: RetryFn = Rc::new(|| *PROFILING_CONFIG_RELOAD.write() += 1);
+            let result = with_error_toast(
+                "POST /query (profiler settings)",
+                Some(retry),
+                ApiClient::new().get_profiler_config(),
+            )
+            .await;
+            match &result {
+                Ok(config) => apply_profiler_config(config),
+                // Show the views anyway; the toast offers a retry.
+                Err(_) => *PROFILING_CONFIG_LOADED.write() = true,
+            }
+            result
         }
-        result
     });
     _config.suspend()?;
 
     match view.as_str() {
-        "pprof" | "torch" => rsx! {
+        "pprof" | "torch" | "spanflame" => rsx! {
             AsyncBoundary {
                 message: Some("Loading flamegraph…".to_string()),
                 FlamegraphLoader { key: "{view}", view: view.clone() }
             }
         },
-        "trace" => rsx! {
+        "trace" | "combined" => rsx! {
             AsyncBoundary {
                 message: Some("Loading trace data…".to_string()),
                 TraceChromeTimelineLoader {
                     key: "{view}-{trace_reload}-{trace_limit}",
                     reload_key: trace_reload,
                     limit: trace_limit,
+                    source: trace_source,
                 }
             }
         },
@@ -117,6 +145,9 @@ fn ProfilerConfigGate(view: String) -> Element {
                 RayTimelineLoader { key: "{view}" }
             }
         },
+        "compare" => rsx! {
+            ProfileCompare {}
+        },
         _ => rsx! { div {} },
     }
 }
@@ -125,11 +156,17 @@ fn ProfilerConfigGate(view: String) -> Element {
 fn FlamegraphLoader(view: String) -> Element {
     let pprof_enabled = *PROFILING_PPROF_FREQ.read() > 0;
     let torch_enabled = *PROFILING_TORCH_ENABLED.read();
-    let profiler_name = if view == "pprof" { "pprof" } else { "torch" };
+    let profiler_name = match view.as_str() {
+        "pprof" => "pprof",
+        "spanflame" => "spans",
+        _ => "torch",
+    };
 
+    // Spans are recorded whether or not a profiler runs.
     let profiler_active = match view.as_str() {
         "pprof" => pprof_enabled,
         "torch" => torch_enabled,
+        "spanflame" => true,
         _ => false,
     };
 
@@ -151,7 +188,7 @@ fn FlamegraphLoader(view: String) -> Element {
 fn FlamegraphData(profiler_name: String) -> Element {
     let is_torch = profiler_name == "torch";
     let is_pprof = profiler_name == "pprof";
-    let mut metric = use_signal(|| "duration".to_string());
+    let mut metric = use_signal(|| PROFILING_TORCH_METRIC.peek().clone());
     let fetch_name = profiler_name.clone();
     let thread_tid = if is_pprof {
         *PROFILING_THREAD_FILTER.read()
@@ -159,6 +196,11 @@ fn FlamegraphData(profiler_name: String) -> Element {
         None
     };
     let thread_label = INVESTIGATION_CONTEXT.read().label.clone();
+    let trace_id = if profiler_name == "spans" {
+        INVESTIGATION_CONTEXT.read().trace_id
+    } else {
+        None
+    };
 
     let payload = use_app_resource(move || {
         let name = fetch_name.clone();
@@ -169,6 +211,8 @@ fn FlamegraphData(profiler_name: String) -> Element {
                 client
                     .get_flamegraph_json_with_metric(&name, Some(&m))
                     .await?
+            } else if name == "spans" {
+                client.get_span_flamegraph_json(trace_id).await?
             } else {
                 client.get_flamegraph_json(&name).await?
             };
@@ -212,7 +256,10 @@ fn FlamegraphData(profiler_name: String) -> Element {
                     thread_tid,
                     torch_metric: if is_torch { Some(metric) } else { None },
                     on_torch_metric: if is_torch {
-                        Some(EventHandler::new(move |m: String| metric.set(m)))
+                        Some(EventHandler::new(move |m: String| {
+                            *PROFILING_TORCH_METRIC.write() = m.clone();
+                            metric.set(m);
+                        }))
                     } else {
                         None
                     },
@@ -2,13 +2,14 @@ use std::collections::HashSet;
 
 use dioxus::prelude::*;
 
-use crate::api::{ApiClient, VariableRecord};
+use crate::api::{ActiveTrace, ApiClient, VariableRecord};
 use crate::components::colors::colors;
-use crate::components::common::{query_result, AppErrorDisplay};
+use crate::components::common::query_result;
 use crate::hooks::use_app_resource;
+use crate::state::api_errors::with_error_toast;
 use crate::utils::error::AppError;
 
-use super::shared::PREVIEW_RECORD_LIMIT;
+use super::shared::{OVERHEAD_WARN_US, PREVIEW_RECORD_LIMIT};
 
 #[component]
 pub fn ActiveTracesPanel(
@@ -21,8 +22,15 @@ pub fn ActiveTracesPanel(
         let _ = refresh_key();
         async move { ApiClient::new().get_trace_info().await }
     });
+    // Stopping mutates the target, so the failure toast offers no retry.
     let mut stop_trace = use_action(move |func: String| async move {
-        ApiClient::new().stop_trace(&func).await?;
+        let client = ApiClient::new();
+        with_error_toast(
+            "GET /apis/pythonext/trace/stop",
+            None,
+            client.stop_trace(&func),
+        )
+        .await?;
         refresh_key.set(refresh_key() + 1);
         Ok::<(), AppError>(())
     });
@@ -36,10 +44,10 @@ pub fn ActiveTracesPanel(
         move |active| {
             rsx! {
                 div { class: "divide-y divide-gray-100",
-                    for func in active {
+                    for trace in active {
                         ActiveTraceRow {
-                            key: "{func}",
-                            function: func,
+                            key: "{trace.function}",
+                            trace,
                             poll,
                             refresh_key,
                             on_view_records,
@@ -52,13 +60,6 @@ pub fn ActiveTracesPanel(
                     div { class: "px-4 py-2 text-xs text-gray-500 border-t border-gray-100",
                         "Stopping trace…"
                     }
-                } else if let Some(Err(err)) = stop_trace.value() {
-                    div { class: "px-4 py-2 border-t border-gray-100",
-                        AppErrorDisplay {
-                            error: AppError::Api(err.to_string()),
-                            title: Some("Stop failed".to_string()),
-                        }
-                    }
                 }
             }
         },
@@ -67,13 +68,14 @@ pub fn ActiveTracesPanel(
 
 #[component]
 fn ActiveTraceRow(
-    function: String,
+    trace: ActiveTrace,
     poll: Signal<u32>,
     refresh_key: Signal<u32>,
     on_view_records: EventHandler<String>,
     stop_pending: bool,
     on_stop: EventHandler<String>,
 ) -> Element {
+    let function = trace.function.clone();
     let records = use_app_resource({
         let function = function.clone();
         move || {
@@ -100,6 +102,7 @@ fn ActiveTraceRow(
                         "tracing"
                     }
                     p { class: "font-mono text-sm text-gray-900 break-all", "{function}" }
+                    TraceCostLine { trace: trace.clone() }
                     if let Some(result) = snapshot.as_ref() {
                         match result {
                             Ok(_) if latest.is_empty() => rsx! {
@@ -117,8 +120,10 @@ fn ActiveTraceRow(
                                     }
                                 }
                             },
-                            Err(_) => rsx! {
-                                p { class: "mt-1.5 text-xs text-amber-700", "Preview unavailable" }
+                            Err(err) => rsx! {
+                                p { class: "mt-1.5 text-xs text-amber-700", title: err.display_message(),
+                                    "Preview unavailable"
+                                }
                             },
                         }
                     } else {
@@ -153,6 +158,55 @@ fn ActiveTraceRow(
     }
 }
 
+const TRACE_COST_HELP: &str =
+    "Backend: interpreter hook the trace uses (settrace runs on every line of the traced call).\n\
+Overhead: moving average of the time the hook itself adds to each traced call.\n\
+Records: variable changes stored in trace_variables; dropped ones could not be stored.\n\
+Last: age of the newest stored record.";
+
+#[component]
+fn TraceCostLine(trace: ActiveTrace) -> Element {
+    let heavy = trace.overhead_us.is_some_and(|us| us > OVERHEAD_WARN_US);
+    let overhead = match trace.overhead_us {
+        Some(us) => format!("~{us:.1} µs/call"),
+        None => "no calls yet".to_string(),
+    };
+    let last = trace
+        .last_record_ts
+        .map(|ts| format!("last {}", age_label(js_sys::Date::now() / 1000.0 - ts)));
+    let class = if heavy {
+        "mt-1 inline-flex flex-wrap items-center gap-x-2 text-[11px] tabular-nums text-amber-800 bg-amber-50 border border-amber-200 rounded px-1.5 py-0.5 cursor-help"
+    } else {
+        "mt-1 flex flex-wrap items-center gap-x-2 text-[11px] tabular-nums text-gray-500 cursor-help"
+    };
+
+    rsx! {
+        div { class: "{class}", title: TRACE_COST_HELP,
+            span { class: "font-mono", "{trace.backend}" }
+            span { "{overhead}" }
+            span { "{trace.records} records" }
+            if trace.records_dropped > 0 {
+                span { class: "text-amber-700", "{trace.records_dropped} dropped" }
+            }
+            if let Some(last) = last {
+                span { "{last}" }
+            }
+            if heavy {
+                span { class: "font-medium", "high overhead" }
+            }
+        }
+    }
+}
+
+fn age_label(secs: f64) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    match secs {
+        0..=59 => format!("{secs}s ago"),
+        60..=3599 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
 #[component]
 fn VariablePreviewChip(name: String, value: String, ty: String) -> Element {
     rsx! {
@@ -1,17 +1,25 @@
 use dioxus::html::events::KeyboardEvent;
 use dioxus::html::input_data::keyboard_types::Key;
 use dioxus::prelude::*;
+use probing_proto::prelude::DataFrame;
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, VariableRecordsQuery};
 use crate::components::colors::colors;
 use crate::components::common::{AppErrorDisplay, LoadingState};
 use crate::components::dataframe_view::DataFrameView;
 use crate::components::icon::Icon;
-use crate::hooks::use_app_resource;
+use crate::hooks::{use_app_resource, use_poll_tick_gated};
 use crate::utils::error::AppError;
 
+use super::records::{
+    merge_newer, newest_timestamp_us, window_start_us, DEFAULT_RECORD_LIMIT, RECORD_LIMITS,
+    RECORD_WINDOWS,
+};
 use super::shared::{StartTraceDraft, POLL_MS};
 
+const RECORDS_SELECT_CLASS: &str =
+    "max-w-[12rem] px-2 py-1 text-xs rounded-md border border-gray-300 bg-white text-gray-700";
+
 #[component]
 pub fn StartTraceDialog(
     draft: Signal<StartTraceDraft>,
@@ -140,22 +148,83 @@ pub fn StartTraceDialog(
 }
 
 #[component]
-pub fn RecordsModal(function: String, poll: Signal<u32>, on_close: EventHandler<()>) -> Element {
+pub fn RecordsModal(function: String, on_close: EventHandler<()>) -> Element {
     let function_label = function.clone();
-    let records = use_app_resource({
+    let mut variable = use_signal(|| None::<String>);
+    let mut limit = use_signal(|| DEFAULT_RECORD_LIMIT);
+    let mut window = use_signal(|| None::<i64>);
+    let mut follow = use_signal(|| true);
+    let mut reload = use_signal(|| 0u32);
+    // Rows shown: the last full load plus whatever follow polls appended.
+    let mut rows = use_signal(DataFrame::default);
+    // Bumped by every full load so a follow poll started before it is dropped.
+    let mut generation = use_signal(|| 0u64);
+
+    let query = {
+        let function = function.clone();
+        move |since_us: Option<i64>| VariableRecordsQuery {
+            function: Some(function.clone()),
+            variable: variable.peek().clone(),
+            since_us,
+            limit: Some(*limit.peek()),
+        }
+    };
+
+    let variables = use_app_resource({
         let function = function.clone();
         move || {
             let func = function.clone();
-            let _ = poll();
+            let _ = reload();
+            async move { ApiClient::new().get_traced_variable_names(&func).await }
+        }
+    });
+
+    let records = use_app_resource({
+        let query = query.clone();
+        move || {
+            let _ = (variable(), limit(), window(), reload());
+            let query = query(window_start_us(window(), js_sys::Date::now()));
             async move {
-                ApiClient::new()
-                    .get_variable_records(Some(&func), Some(100))
-                    .await
+                let df = ApiClient::new().get_variable_records(&query).await?;
+                generation += 1;
+                rows.set(df.clone());
+                Ok(df)
             }
         }
     });
-    let refreshing = records.pending();
+
+    // Owned by the modal, so closing it stops the timer; ticks only while following.
+    let follow_tick = use_poll_tick_gated(POLL_MS, Some(follow));
+    let mut tail_busy = use_signal(|| false);
+    use_effect(move || {
+        if follow_tick() == 0 || *tail_busy.peek() || !matches!(*records.peek(), Some(Ok(_))) {
+            return;
+        }
+        let since = newest_timestamp_us(&rows.peek());
+        let query = query(since.or_else(|| window_start_us(*window.peek(), js_sys::Date::now())));
+        let started = *generation.peek();
+        tail_busy.set(true);
+        spawn(async move {
+            let result = ApiClient::new().get_variable_records(&query).await;
+            tail_busy.set(false);
+            // A failed poll keeps the rows; the next tick retries.
+            if let (Ok(fresh), true) = (result, *generation.peek() == started) {
+                let oldest = window_start_us(*window.peek(), js_sys::Date::now());
+                let merged = merge_newer(&rows.peek(), &fresh, *limit.peek(), oldest);
+                rows.set(merged);
+            }
+        });
+    });
+
+    let refreshing = records.pending() || tail_busy();
     let snapshot = records.read();
+    let variable_names = variables
+        .read()
+        .as_ref()
+        .and_then(|r| r.as_ref().ok())
+        .cloned()
+        .unwrap_or_default();
+    let selected_variable = variable();
 
     rsx! {
         div {
@@ -181,9 +250,6 @@ pub fn RecordsModal(function: String, poll: Signal<u32>, on_close: EventHandler<
                         if refreshing && snapshot.as_ref().is_some() {
                             span { class: "text-[11px] text-gray-500", "Updating…" }
                         }
-                        span { class: "text-[11px] text-gray-500 tabular-nums",
-                            "Auto {POLL_MS / 1000}s"
-                        }
                         button {
                             class: format!(
                                 "px-3 py-1.5 text-sm rounded-md bg-{} hover:bg-{}",
@@ -195,12 +261,78 @@ pub fn RecordsModal(function: String, poll: Signal<u32>, on_close: EventHandler<
                         }
                     }
                 }
+                div { class: "flex flex-wrap items-center gap-3 px-4 py-2 border-b border-gray-200 text-xs text-gray-600",
+                    label { class: "flex items-center gap-1.5",
+                        "Variable"
+                        select {
+                            class: RECORDS_SELECT_CLASS,
+                            onchange: move |e| {
+                                let value = e.value();
+                                variable.set((!value.is_empty()).then_some(value));
+                            },
+                            option { value: "", selected: selected_variable.is_none(), "All variables" }
+                            for name in variable_names {
+                                option {
+                                    value: "{name}",
+                                    selected: selected_variable.as_deref() == Some(name.as_str()),
+                                    "{name}"
+                                }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-1.5",
+                        "Rows"
+                        select {
+                            class: RECORDS_SELECT_CLASS,
+                            onchange: move |e| {
+                                if let Ok(n) = e.value().parse() {
+                                    limit.set(n);
+                                }
+                            },
+                            for n in RECORD_LIMITS {
+                                option { value: "{n}", selected: limit() == n, "{n}" }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-1.5",
+                        "Window"
+                        select {
+                            class: RECORDS_SELECT_CLASS,
+                            onchange: move |e| window.set(e.value().parse().ok()),
+                            for (secs, label) in RECORD_WINDOWS {
+                                option {
+                                    value: secs.map(|s| s.to_string()).unwrap_or_default(),
+                                    selected: window() == secs,
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+                    label {
+                        class: "flex items-center gap-1.5",
+                        title: "Poll every {POLL_MS / 1000}s and add new records on top",
+                        input {
+                            r#type: "checkbox",
+                            checked: follow(),
+                            onchange: move |e| follow.set(e.checked()),
+                        }
+                        "Follow"
+                    }
+                    button {
+                        class: "ml-auto inline-flex items-center gap-1 px-2 py-1 rounded-md border border-gray-300 bg-white hover:bg-gray-50",
+                        onclick: move |_| reload += 1,
+                        "Reload"
+                    }
+                }
                 div { class: "flex-1 overflow-auto p-4",
                     if let Some(result) = snapshot.as_ref() {
                         match result {
-                            Ok(df) => rsx! {
+                            Ok(_) if rows.read().is_empty() => rsx! {
+                                p { class: "text-sm text-gray-500", "No records match these filters yet." }
+                            },
+                            Ok(_) => rsx! {
                                 div { class: "rounded-lg border border-gray-200 overflow-hidden",
-                                    DataFrameView { df: df.clone(), on_row_click: None }
+                                    DataFrameView { df: rows(), on_row_click: None }
                                 }
                             },
                             Err(err) => rsx! {
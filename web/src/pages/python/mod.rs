@@ -3,6 +3,7 @@
 mod active;
 mod catalog;
 mod dialogs;
+mod records;
 mod shared;
 
 use dioxus::prelude::*;
@@ -114,7 +115,6 @@ pub fn Python() -> Element {
             if *records_open.read() {
                 RecordsModal {
                     function: records_function(),
-                    poll,
                     on_close: move |_| records_open.set(false),
                 }
             }
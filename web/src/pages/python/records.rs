@@ -0,0 +1,168 @@
+//! Row bookkeeping for the records modal: the filter choices and the merge of
+//! follow-mode polls into the rows already shown.
+
+use std::collections::HashSet;
+
+use probing_proto::prelude::{DataFrame, Ele};
+use probing_proto::types::append_dataframe;
+
+pub const RECORD_LIMITS: [usize; 4] = [50, 100, 500, 1000];
+pub const DEFAULT_RECORD_LIMIT: usize = 100;
+
+/// Time window choices: seconds back from now, `None` for all records.
+pub const RECORD_WINDOWS: [(Option<i64>, &str); 5] = [
+    (None, "All time"),
+    (Some(60), "Last 1 min"),
+    (Some(300), "Last 5 min"),
+    (Some(900), "Last 15 min"),
+    (Some(3600), "Last 1 h"),
+];
+
+/// Oldest timestamp (µs since epoch) inside `window_secs` of `now_ms`.
+pub fn window_start_us(window_secs: Option<i64>, now_ms: f64) -> Option<i64> {
+    window_secs.map(|secs| (now_ms * 1000.0) as i64 - secs * 1_000_000)
+}
+
+/// `timestamp` of `row` in µs since epoch.
+fn timestamp_us(df: &DataFrame, row: usize) -> Option<i64> {
+    let col = df.cols.get(df.col_index("timestamp")?)?;
+    if row >= col.len() {
+        return None;
+    }
+    match col.get(row) {
+        Ele::DataTime(v) => Some(v as i64),
+        Ele::DateTime(v) => Some(v / 1_000),
+        Ele::I64(v) => Some(v),
+        Ele::I32(v) => Some(i64::from(v)),
+        Ele::F64(v) => Some(v as i64),
+        _ => None,
+    }
+}
+
+/// Newest timestamp in `df`; the next follow poll starts there.
+pub fn newest_timestamp_us(df: &DataFrame) -> Option<i64> {
+    (0..df.len()).filter_map(|row| timestamp_us(df, row)).max()
+}
+
+/// Identifies a record among those sharing its timestamp.
+fn row_key(df: &DataFrame, row: usize) -> String {
+    df.cols
+        .iter()
+        .map(|col| col.get(row).to_string())
+        .collect::<Vec<_>>()
+        .join("\u{1f}")
+}
+
+/// `fresh` (newest first, polled with `since` = the newest shown timestamp)
+/// in front of `rows`, without the rows both hold, dropping rows older than
+/// `oldest_us` and keeping at most `limit`.
+pub fn merge_newer(
+    rows: &DataFrame,
+    fresh: &DataFrame,
+    limit: usize,
+    oldest_us: Option<i64>,
+) -> DataFrame {
+    let newest = newest_timestamp_us(rows);
+    let seen: HashSet<String> = (0..rows.len())
+        .filter(|&row| newest.is_some() && timestamp_us(rows, row) == newest)
+        .map(|row| row_key(rows, row))
+        .collect();
+    let in_window = |df: &DataFrame, row: usize| match (oldest_us, timestamp_us(df, row)) {
+        (Some(oldest), Some(ts)) => ts >= oldest,
+        _ => true,
+    };
+
+    let new_rows: Vec<usize> = (0..fresh.len())
+        .filter(|&row| {
+            let ts = timestamp_us(fresh, row);
+            let is_new = match (ts, newest) {
+                (Some(ts), Some(newest)) if ts == newest => !seen.contains(&row_key(fresh, row)),
+                (Some(ts), Some(newest)) => ts > newest,
+                _ => true,
+            };
+            is_new && in_window(fresh, row)
+        })
+        .collect();
+    let kept: Vec<usize> = (0..rows.len())
+        .filter(|&row| in_window(rows, row))
+        .collect();
+
+    let mut out = fresh.take_rows(&new_rows);
+    append_dataframe(&mut out, &rows.take_rows(&kept));
+    if out.len() > limit {
+        out = out.take_rows(&(0..limit).collect::<Vec<_>>());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use probing_proto::prelude::Seq;
+
+    use super::*;
+
+    fn records(rows: &[(u64, &str, &str)]) -> DataFrame {
+        DataFrame::new(
+            vec![
+                "variable_name".to_string(),
+                "value".to_string(),
+                "timestamp".to_string(),
+            ],
+            vec![
+                Seq::SeqText(rows.iter().map(|r| r.1.to_string()).collect()),
+                Seq::SeqText(rows.iter().map(|r| r.2.to_string()).collect()),
+                Seq::SeqDateTime(rows.iter().map(|r| r.0).collect()),
+            ],
+        )
+    }
+
+    fn values(df: &DataFrame) -> Vec<String> {
+        (0..df.len())
+            .map(|row| df.cols[1].get(row).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn follow_poll_prepends_only_unseen_rows() {
+        let shown = records(&[(20, "loss", "0.5"), (20, "step", "2"), (10, "loss", "0.9")]);
+        // Polled with `timestamp >= 20`: repeats both rows at 20, adds one.
+        let fresh = records(&[(30, "loss", "0.4"), (20, "step", "2"), (20, "loss", "0.5")]);
+        let merged = merge_newer(&shown, &fresh, 100, None);
+        assert_eq!(values(&merged), ["0.4", "0.5", "2", "0.9"]);
+        assert_eq!(newest_timestamp_us(&merged), Some(30));
+
+        // A row written at the boundary after the previous poll is kept.
+        let late = records(&[(20, "lr", "0.01")]);
+        assert_eq!(values(&merge_newer(&shown, &late, 100, None))[0], "0.01");
+    }
+
+    #[test]
+    fn merge_applies_limit_and_window() {
+        let shown = records(&[(20, "loss", "0.5"), (10, "loss", "0.9")]);
+        let fresh = records(&[(40, "loss", "0.3"), (30, "loss", "0.4")]);
+        assert_eq!(
+            values(&merge_newer(&shown, &fresh, 3, None)),
+            ["0.3", "0.4", "0.5"]
+        );
+        assert_eq!(
+            values(&merge_newer(&shown, &fresh, 100, Some(20))),
+            ["0.3", "0.4", "0.5"]
+        );
+
+        let empty = DataFrame::default();
+        assert_eq!(
+            values(&merge_newer(&empty, &fresh, 100, None)),
+            ["0.3", "0.4"]
+        );
+        assert_eq!(
+            values(&merge_newer(&shown, &empty, 100, None)),
+            ["0.5", "0.9"]
+        );
+    }
+
+    #[test]
+    fn window_counts_back_from_now() {
+        assert_eq!(window_start_us(None, 5_000.0), None);
+        assert_eq!(window_start_us(Some(2), 5_000.0), Some(3_000_000));
+    }
+}
@@ -4,6 +4,8 @@ use crate::components::icon::Icon;
 
 pub const POLL_MS: u32 = 3000;
 pub const PREVIEW_RECORD_LIMIT: usize = 50;
+/// Per-call hook overhead (µs) above which an active trace is flagged.
+pub const OVERHEAD_WARN_US: f64 = 50.0;
 
 #[derive(Clone, PartialEq)]
 pub struct StartTraceDraft {
@@ -10,6 +10,7 @@ use crate::components::common::{EmptyState, ErrorState, LoadingState};
 use crate::components::rl::metrics_line_chart::{ChartSeries, MetricsLineChart};
 use crate::components::page::{PageContainer, PageTitle};
 use crate::hooks::use_api;
+use crate::state::health::target_unreachable;
 
 const REFRESH_MS: u32 = 5000;
 const METRICS_HISTORY_LIMIT: i64 = 500;
@@ -56,7 +57,9 @@ pub fn Inference() -> Element {
         spawn(async move {
             loop {
                 gloo_timers::future::TimeoutFuture::new(REFRESH_MS).await;
-                refresh_tick.set(refresh_tick() + 1);
+                if !target_unreachable() {
+                    refresh_tick.set(refresh_tick() + 1);
+                }
             }
         });
     });
@@ -367,7 +370,7 @@ fn col_idx(df: &DataFrame, name: &str) -> Option<usize> {
 
 fn ele_i64(ele: Option<&Ele>) -> Option<i64> {
     match ele? {
-        Ele::I64(v) => Some(*v),
+        Ele::I64(v) | Ele::DateTime(v) => Some(*v),
         Ele::I32(v) => Some(*v as i64),
         Ele::F64(v) => Some(*v as i64),
         Ele::F32(v) => Some(*v as i64),
@@ -381,7 +384,7 @@ fn ele_f64(ele: Option<&Ele>) -> Option<f64> {
     match ele? {
         Ele::F64(v) => Some(*v),
         Ele::F32(v) => Some(*v as f64),
-        Ele::I64(v) => Some(*v as f64),
+        Ele::I64(v) | Ele::DateTime(v) => Some(*v as f64),
         Ele::I32(v) => Some(*v as f64),
         Ele::Text(v) | Ele::Url(v) => v.parse().ok(),
         Ele::DataTime(v) => Some(*v as f64),
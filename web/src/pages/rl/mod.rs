@@ -7,7 +7,7 @@ use crate::components::colors::colors;
 use crate::components::page::{PageContainer, PageTitle};
 use crate::components::common::{LoadingState, ErrorState};
 use crate::hooks::use_api_simple;
-use crate::api::{ApiClient, SpanInfo, EventInfo, TraceProcessInfo};
+use crate::api::{ApiClient, SpanInfo, EventInfo, TraceFilters, TraceProcessInfo};
 use crate::rl_contract::{
     self, is_rollout_submit_parent_span,
     is_step_parent_span, is_train_timeline_span, is_rollout_worker_role, logical_step_key,
@@ -131,7 +131,9 @@ pub fn RlObservability(view: RlViewMode) -> Element {
 
                     let mut spans = if rollout_id.is_empty() {
                         let fetch_limit = expanded_trace_fetch_limit(limit_val);
-                        let mut spans = client.get_span_tree(Some(fetch_limit)).await?;
+                        let mut spans = client
+                            .get_span_tree(Some(fetch_limit), &TraceFilters::default())
+                            .await?;
                         let local_pids = collect_span_process_pids(&spans);
                         for process in processes {
                             if local_pids.contains(&process.pid) {
@@ -1,8 +1,8 @@
 use dioxus::prelude::*;
 use dioxus_router::Link;
-use probing_proto::prelude::CallFrame;
+use probing_proto::prelude::{CallFrame, ThreadStack};
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, ThreadAncestor};
 use crate::app::Route;
 use crate::components::callstack_view::CallStackView;
 use crate::components::common::{AsyncBoundary, EmptyState, ErrorState};
@@ -11,10 +11,10 @@ use crate::components::page::{PageContainer, PageTitle};
 use crate::components::profiling::{ProfilingContentPanel, ProfilingErrorPanel};
 use crate::hooks::use_app_resource;
 use crate::state::stack::{
-    stack_tid_label, StackSnapshot, STACK_DIST_CLUSTER, STACK_DIST_RELOAD, STACK_MODE,
-    STACK_REFRESH, STACK_SNAPSHOT,
+    bump_stack_refresh, stack_tid_label, StackSnapshot, STACK_DIST_CLUSTER, STACK_DIST_RELOAD,
+    STACK_MODE, STACK_NATIVE, STACK_REFRESH, STACK_SNAPSHOT,
 };
-use crate::utils::callframe::{count_by_kind, matches_mode};
+use crate::utils::callframe::{count_by_kind, matches_mode, thread_heading, threads_as_text};
 use crate::utils::error::AppError;
 
 #[component]
@@ -35,12 +35,22 @@ pub fn Stack(tid: Option<String>) -> Element {
                 icon: Some(&icondata::AiApartmentOutlined),
             }
 
-            AsyncBoundary {
-                message: Some("Loading call stack…".to_string()),
-                StackLoaded {
-                    tid: tid_for_api,
-                    tid_label: tid_label,
-                    refresh_tick: refresh_tick,
+            if let Some(tid) = tid.as_deref().and_then(|t| t.parse::<i64>().ok()) {
+                ThreadAncestry { key: "{tid}", tid }
+            }
+            if tid_for_api.is_some() {
+                AsyncBoundary {
+                    message: Some("Loading call stack…".to_string()),
+                    StackLoaded {
+                        tid: tid_for_api,
+                        tid_label: tid_label,
+                        refresh_tick: refresh_tick,
+                    }
+                }
+            } else {
+                AsyncBoundary {
+                    message: Some("Loading thread stacks…".to_string()),
+                    ThreadStacksLoaded { refresh_tick }
                 }
             }
         }
@@ -128,6 +138,181 @@ fn StackLoaded(tid: Option<String>, tid_label: String, refresh_tick: u32) -> Ele
     }
 }
 
+/// Every Python thread as a collapsible section, main thread first.
+#[component]
+fn ThreadStacksLoaded(refresh_tick: u32) -> Element {
+    let mode = STACK_MODE();
+    let native = STACK_NATIVE();
+    let mut copied = use_signal(|| false);
+    let stacks = use_app_resource(move || {
+        let _ = STACK_REFRESH();
+        let native = STACK_NATIVE();
+        async move { ApiClient::new().get_thread_stacks(native).await }
+    });
+
+    let stacks_peek = stacks.read().clone();
+    use_effect(use_reactive!(|(mode, stacks_peek)| {
+        let Some(result) = stacks_peek.as_ref() else {
+            return;
+        };
+        *STACK_SNAPSHOT.write() = threads_snapshot_for(result, &mode);
+    }));
+
+    let threads = match stacks.suspend()?().as_ref() {
+        Err(err) => {
+            return rsx! {
+                ErrorState {
+                    title: Some("Failed to load thread stacks".to_string()),
+                    error: err.display_message(),
+                }
+            };
+        }
+        Ok(threads) => threads.clone(),
+    };
+    let text = threads_as_text(&threads, &mode);
+
+    rsx! {
+        div { class: "mb-3 flex flex-wrap items-center gap-3 text-xs text-gray-600",
+            span { class: "tabular-nums", "{threads.len()} threads" }
+            label {
+                class: "flex items-center gap-1.5",
+                title: "Interleave native frames where they can be captured; may signal each thread",
+                input {
+                    r#type: "checkbox",
+                    checked: native,
+                    onchange: move |e| *STACK_NATIVE.write() = e.checked(),
+                }
+                "Native frames"
+            }
+            div { class: "ml-auto flex gap-2",
+                button {
+                    r#type: "button",
+                    class: "px-2.5 py-1 rounded-md border border-gray-300 bg-white hover:bg-gray-50",
+                    onclick: move |_| {
+                        copied.set(false);
+                        bump_stack_refresh();
+                    },
+                    "Refresh"
+                }
+                button {
+                    r#type: "button",
+                    class: "px-2.5 py-1 rounded-md border border-gray-300 bg-white hover:bg-gray-50",
+                    disabled: threads.is_empty(),
+                    onclick: move |_| {
+                        if let Some(window) = web_sys::window() {
+                            let _ = window.navigator().clipboard().write_text(&text);
+                            copied.set(true);
+                        }
+                    },
+                    if copied() { "Copied" } else { "Copy as text" }
+                }
+            }
+        }
+        if threads.is_empty() {
+            EmptyState { message: "No Python threads are registered yet.".to_string() }
+        }
+        div { class: "space-y-2",
+            for thread in threads {
+                ThreadStackSection {
+                    key: "{refresh_tick}-{thread.tid}",
+                    thread: thread.clone(),
+                    mode: mode.clone(),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ThreadStackSection(thread: ThreadStack, mode: String) -> Element {
+    let frames: Vec<CallFrame> = thread
+        .frames
+        .iter()
+        .filter(|cf| matches_mode(cf, &mode))
+        .cloned()
+        .collect();
+    let shown = frames.len();
+    let heading = thread_heading(&thread);
+    let filter_label = mode_label(&mode);
+    let summary = match &thread.error {
+        Some(_) => "unavailable".to_string(),
+        None if shown == thread.frames.len() => format!("{shown} frames"),
+        None => format!("{shown}/{} frames", thread.frames.len()),
+    };
+
+    rsx! {
+        details {
+            class: "rounded-lg border border-gray-200 bg-white overflow-hidden",
+            open: thread.main,
+            summary { class: "flex items-center gap-2 px-3 py-2 cursor-pointer select-none bg-gray-50/80 hover:bg-gray-100 text-sm",
+                span { class: "font-mono text-gray-900 truncate", "{heading}" }
+                if thread.main {
+                    span { class: "px-1.5 py-0.5 text-[10px] rounded border border-blue-200 bg-blue-50 text-blue-700", "main" }
+                }
+                span { class: "ml-auto text-xs text-gray-500 tabular-nums", "{summary}" }
+                Link {
+                    to: Route::StackWithTidPage { tid: thread.tid.to_string() },
+                    class: "text-xs text-blue-700 hover:underline",
+                    "Open"
+                }
+            }
+            div { class: "px-3 py-2",
+                if let Some(err) = &thread.error {
+                    p { class: "text-xs text-amber-700", "Stack could not be captured: {err}" }
+                } else if frames.is_empty() {
+                    p { class: "text-xs text-gray-500",
+                        if thread.frames.is_empty() {
+                            "No frames; the thread may be idle."
+                        } else {
+                            "No frames match the \"{filter_label}\" filter"
+                        }
+                    }
+                } else {
+                    for (idx, cf) in frames.iter().enumerate() {
+                        CallStackView {
+                            key: "{idx}",
+                            callstack: cf.clone(),
+                            index: idx,
+                            is_last: idx + 1 == shown,
+                            default_open: false,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Creator chain from `python.threads`; hidden when the thread was not
+/// started under tracking.
+#[component]
+fn ThreadAncestry(tid: i64) -> Element {
+    let chain =
+        use_app_resource(move || async move { ApiClient::new().fetch_thread_ancestry(tid).await });
+    let links: Vec<ThreadAncestor> = match chain.read().as_ref() {
+        Some(Ok(links)) if links.len() > 1 => links.clone(),
+        _ => return rsx! {},
+    };
+    let last = links.len() - 1;
+    rsx! {
+        div { class: "mb-3 flex flex-wrap items-center gap-1 text-xs text-gray-600",
+            span { class: "font-medium text-gray-500 mr-1", "Created by" }
+            for (idx, link) in links.into_iter().enumerate().skip(1) {
+                Link {
+                    key: "{link.tid}",
+                    to: Route::StackWithTidPage { tid: link.tid.to_string() },
+                    class: if link.alive { "text-blue-700 hover:underline" } else { "text-gray-400 line-through" },
+                    title: "{link.target}",
+                    "{link.name} ({link.tid})"
+                }
+                if idx < last {
+                    span { class: "text-gray-400", "←" }
+                }
+            }
+        }
+    }
+}
+
 fn stack_snapshot_for(
     tid_label: &str,
     result: &Result<Vec<CallFrame>, AppError>,
@@ -156,6 +341,23 @@ fn stack_snapshot_for(
     }
 }
 
+fn threads_snapshot_for(result: &Result<Vec<ThreadStack>, AppError>, mode: &str) -> StackSnapshot {
+    let Ok(threads) = result else {
+        return StackSnapshot::default();
+    };
+    let frames: Vec<CallFrame> = threads.iter().flat_map(|t| t.frames.clone()).collect();
+    let (py, rust, cpp) = count_by_kind(&frames);
+    StackSnapshot {
+        tid_label: "all threads".to_string(),
+        total: frames.len(),
+        py,
+        rust,
+        cpp,
+        shown: frames.iter().filter(|cf| matches_mode(cf, mode)).count(),
+        loaded: true,
+    }
+}
+
 fn mode_label(mode: &str) -> &'static str {
     match mode {
         "py" => "Python",
@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 use dioxus_router::Link;
 
-use crate::api::{ApiClient, EventInfo, SpanInfo};
+use crate::api::{ApiClient, EventInfo, SpanInfo, SpanSource, TraceFilters};
 use crate::app::Route;
 use crate::components::card::Card;
 use crate::components::colors::colors;
@@ -9,19 +9,31 @@ use crate::components::common::{query_result, AsyncBoundary, EmptyState};
 use crate::components::icon::Icon;
 use crate::components::page::{PageContainer, PageTitle};
 use crate::components::poll_status::{ManualRefreshStatus, RefreshButton};
+use crate::components::report_button::ExportReportButton;
+use crate::components::source_viewer::PlainSourceLines;
+use crate::components::span_detail::SpanDetailPanel;
 use crate::components::span_timeline::{
-    SpanTimelineBar, SpanTimelineHeader, SpanTimelineLegend, SpanTimelineSpacer, TraceTimeWindow,
+    format_axis_label, timeline_svg, SpanTimelineBar, SpanTimelineHeader, SpanTimelineLegend,
+    SpanTimelineSpacer, TraceTimeWindow,
 };
-use crate::hooks::use_app_resource;
+use crate::components::trace_chips::{
+    active_chips, span_matches_chips, top_chips, TraceChip, TraceChipBar, TOP_CHIPS,
+};
+use crate::components::trace_compare::TraceCompareCard;
+use crate::hooks::{use_app_resource, use_span_stream, SpanStream, LIVE_SPANS_CAP};
 use crate::state::investigation::{
     clear_spans_investigation_filters, investigation_context_key, set_trace_context,
     sync_spans_filters_to_context, InvestigationContext, INVESTIGATION_CONTEXT,
 };
-use crate::state::profiling::SPANS_TREE_LIMIT;
+use crate::state::profiling::{SPANS_TREE_LIMIT, TRACE_SERVER_FILTERS};
+use crate::utils::report::{Report, ReportBlock, ReportMeta};
+use crate::utils::source_ref::SourceSlice;
 
 const SPANS_LIMIT_MIN: usize = 100;
 const SPANS_LIMIT_MAX: usize = 5000;
 const SPANS_LIMIT_STEP: usize = 100;
+/// Spans drawn in an exported timeline; larger trees are only tabulated.
+const REPORT_TIMELINE_ROWS: usize = 500;
 
 #[component]
 pub fn Traces() -> Element {
@@ -37,6 +49,9 @@ pub fn Traces() -> Element {
     let mut show_advanced = use_signal(|| false);
     let mut last_applied_ctx = use_signal(String::new);
     let clear_filters_tick = use_signal(|| 0u32);
+    let mut live = use_signal(|| false);
+    let stream = use_span_stream(live, LIVE_SPANS_CAP);
+    let mut selected = use_signal(|| None::<SpanInfo>);
 
     use_effect(move || {
         let ctx = INVESTIGATION_CONTEXT.read().clone();
@@ -72,12 +87,36 @@ pub fn Traces() -> Element {
                 icon: Some(&icondata::AiApiOutlined),
                 header_right: Some(rsx! {
                     ManualRefreshStatus { refresh_tick }
+                    button {
+                        class: if live() {
+                            "inline-flex items-center gap-1 px-2 py-1.5 text-xs rounded-md border border-blue-300 bg-blue-50 text-blue-700"
+                        } else {
+                            "inline-flex items-center gap-1 px-2 py-1.5 text-xs rounded-md border border-gray-300 bg-white hover:bg-gray-50"
+                        },
+                        title: "Show spans as they finish",
+                        onclick: move |_| live.set(!live()),
+                        Icon { icon: &icondata::AiPlayCircleOutlined, class: "w-3.5 h-3.5" }
+                        if live() { "Live on" } else { "Live" }
+                    }
                     RefreshButton {
                         onclick: move |_| refresh.set(refresh() + 1),
                     }
                 }),
             }
 
+            if live() || !stream.spans.read().is_empty() {
+                div { class: "mb-4",
+                    Card {
+                        title: "Live spans",
+                        content_class: Some("p-0"),
+                        header_right: Some(rsx! {
+                            LiveStreamStatus { stream, live: live() }
+                        }),
+                        LiveSpanList { stream, expand_all, collapse_all, selected }
+                    }
+                }
+            }
+
             Card {
                 title: "Span Tree",
                 content_class: Some("p-0"),
@@ -106,6 +145,83 @@ pub fn Traces() -> Element {
                         active_only,
                         expand_all,
                         collapse_all,
+                        selected,
+                    }
+                }
+            }
+
+            if let Some(span) = selected() {
+                SpanDetailPanel {
+                    key: "{span.trace_id}-{span.span_id}",
+                    span,
+                    on_close: move |_| selected.set(None),
+                }
+            }
+
+            div { class: "mt-4",
+                Card { title: "Compare windows",
+                    TraceCompareCard {}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn LiveStreamStatus(stream: SpanStream, live: bool) -> Element {
+    let count = stream.spans.read().len();
+    let dropped = *stream.dropped.read();
+    let state = match (live, *stream.connected.read()) {
+        (false, _) => "paused",
+        (true, true) => "connected",
+        (true, false) => "connecting…",
+    };
+    rsx! {
+        div { class: "flex items-center gap-2 text-xs text-gray-600",
+            span { "{count} spans · newest first, last {LIVE_SPANS_CAP} kept" }
+            if dropped > 0 {
+                span {
+                    class: "text-amber-700",
+                    title: "Spans the server skipped because this page fell behind",
+                    "· {dropped} dropped"
+                }
+            }
+            span { "· {state}" }
+        }
+    }
+}
+
+#[component]
+fn LiveSpanList(
+    stream: SpanStream,
+    expand_all: Signal<u32>,
+    collapse_all: Signal<u32>,
+    selected: Signal<Option<SpanInfo>>,
+) -> Element {
+    let spans = stream.spans.read().clone();
+    if spans.is_empty() {
+        return rsx! {
+            div { class: "px-4 py-6",
+                EmptyState { message: "Waiting for spans to finish…".to_string() }
+            }
+        };
+    }
+    let highlight = SpanHighlight::from_context(&INVESTIGATION_CONTEXT.read());
+    let time_window = TraceTimeWindow::from_spans(&spans);
+    rsx! {
+        div { class: "max-h-80 overflow-y-auto font-mono text-xs leading-5",
+            SpanTimelineHeader { window: time_window }
+            div { class: "px-0 py-1",
+                for span in spans {
+                    SpanView {
+                        key: "{span.trace_id}-{span.span_id}",
+                        span: span.clone(),
+                        depth: 0,
+                        highlight: highlight.clone(),
+                        expand_all,
+                        collapse_all,
+                        selected,
+                        time_window,
                     }
                 }
             }
@@ -140,7 +256,9 @@ fn TraceToolbar(
                     || ctx.trace_id.is_some()
                     || ctx.span_name.is_some()
                     || ctx.local_step.is_some()
+                    || !ctx.chips.is_empty()
             }
+            || !TRACE_SERVER_FILTERS.read().is_empty()
     };
     rsx! {
         div { class: "flex flex-col gap-2 max-w-3xl w-full",
@@ -198,6 +316,7 @@ fn TraceToolbar(
                             active_only.set(false);
                             show_advanced.set(false);
                             clear_spans_investigation_filters();
+                            *TRACE_SERVER_FILTERS.write() = TraceFilters::default();
                             clear_filters_tick.set(clear_filters_tick() + 1);
                         },
                         "Clear filters"
@@ -297,11 +416,22 @@ fn TraceTreePanel(
     active_only: Signal<bool>,
     expand_all: Signal<u32>,
     collapse_all: Signal<u32>,
+    selected: Signal<Option<SpanInfo>>,
 ) -> Element {
     let spans = use_app_resource(move || {
         let _ = refresh();
         let limit_val = *SPANS_TREE_LIMIT.read();
-        async move { ApiClient::new().get_span_tree(Some(limit_val)).await }
+        let filters = TRACE_SERVER_FILTERS.read().clone();
+        async move {
+            ApiClient::new()
+                .get_span_tree(Some(limit_val), &filters)
+                .await
+        }
+    });
+    // Recomputed only when a fetch lands, not on every filter keystroke.
+    let chips = use_memo(move || match &*spans.read() {
+        Some(Ok(tree)) => top_chips(tree, TOP_CHIPS),
+        _ => Vec::new(),
     });
     let tree = spans.suspend()?();
 
@@ -318,6 +448,7 @@ fn TraceTreePanel(
                 min_duration_ms: min_ms_filter().trim().parse().ok(),
                 active_only: active_only(),
                 local_step: ctx.local_step,
+                chips: active_chips(),
             };
             let filtered = filter_span_tree(&spans, &filter(), &advanced);
             let total = count_spans(&spans);
@@ -325,7 +456,13 @@ fn TraceTreePanel(
             let shown = count_spans(&filtered);
             let limit_display = *SPANS_TREE_LIMIT.read();
             let filter_summary = active_filter_summary(&filter(), &advanced);
+            let export_report = {
+                let spans = filtered.clone();
+                let filters = filter_summary.clone();
+                move |_: ()| spans_report(&spans, &filters, limit_display)
+            };
             rsx! {
+                TraceChipBar { chips }
                 div { class: "border-b border-gray-200 px-4 py-2 bg-gray-50/80 flex flex-wrap items-center gap-x-3 gap-y-0.5 text-xs text-gray-600",
                     span { class: "font-medium text-gray-800", "{roots} roots" }
                     span { "·" }
@@ -344,6 +481,11 @@ fn TraceTreePanel(
                         span { "·" }
                         span { class: "text-blue-700", "{shown} matched · {filter_summary}" }
                     }
+                    if !filtered.is_empty() {
+                        span { class: "ml-auto",
+                            ExportReportButton { build: export_report }
+                        }
+                    }
                 }
                 if filtered.is_empty() {
                     div { class: "px-4 py-10",
@@ -371,6 +513,7 @@ fn TraceTreePanel(
                                             highlight: highlight.clone(),
                                             expand_all,
                                             collapse_all,
+                                            selected,
                                             time_window,
                                         }
                                     }
@@ -388,6 +531,102 @@ fn count_spans(spans: &[SpanInfo]) -> usize {
     spans.iter().map(|s| 1 + count_spans(&s.children)).sum()
 }
 
+/// Pre-order `(depth, span)` rows of the fully expanded tree.
+fn flatten_spans<'a>(spans: &'a [SpanInfo], depth: usize, out: &mut Vec<(usize, &'a SpanInfo)>) {
+    for span in spans {
+        out.push((depth, span));
+        flatten_spans(&span.children, depth + 1, out);
+    }
+}
+
+fn format_epoch_ns(ns: i64) -> String {
+    chrono::DateTime::from_timestamp(
+        ns.div_euclid(1_000_000_000),
+        ns.rem_euclid(1_000_000_000) as u32,
+    )
+    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+    .unwrap_or_else(|| ns.to_string())
+}
+
+/// Static snapshot of the filtered span tree, fully expanded.
+fn spans_report(spans: &[SpanInfo], filter_summary: &str, limit: usize) -> Report {
+    let window = TraceTimeWindow::from_spans(spans);
+    let mut rows = Vec::new();
+    flatten_spans(spans, 0, &mut rows);
+
+    let mut report = Report::new(ReportMeta::now("Spans").with_time_range(Some(format!(
+        "{} – {} UTC ({})",
+        format_epoch_ns(window.start_ns),
+        format_epoch_ns(window.end_ns),
+        format_axis_label(window.range_ns() as f64)
+    ))));
+    report.push(ReportBlock::KeyValues {
+        title: "Span tree".to_string(),
+        items: vec![
+            ("Roots".to_string(), spans.len().to_string()),
+            ("Spans".to_string(), rows.len().to_string()),
+            ("Row limit".to_string(), limit.to_string()),
+            (
+                "Filters".to_string(),
+                if filter_summary.is_empty() {
+                    "none".to_string()
+                } else {
+                    filter_summary.to_string()
+                },
+            ),
+        ],
+    });
+
+    let drawn = rows.len().min(REPORT_TIMELINE_ROWS);
+    report.push(ReportBlock::Chart {
+        title: "Timeline".to_string(),
+        svg: timeline_svg(&rows[..drawn], window),
+        caption: Some(if drawn < rows.len() {
+            format!(
+                "First {drawn} of {} spans; all spans are listed below.",
+                rows.len()
+            )
+        } else {
+            "blue = forward, purple = backward, amber = optimizer / active, green = other"
+                .to_string()
+        }),
+    });
+    report.push(ReportBlock::Table {
+        title: "Spans".to_string(),
+        columns: [
+            "Span", "Phase", "Thread", "Trace", "Start", "Duration", "Location",
+        ]
+        .map(String::from)
+        .to_vec(),
+        rows: rows
+            .iter()
+            .map(|(depth, span)| {
+                vec![
+                    // Non-breaking spaces keep the indentation in HTML.
+                    format!("{}{}", "\u{a0}\u{a0}".repeat(*depth), span.name),
+                    span.phase.clone().unwrap_or_default(),
+                    span.thread_id.to_string(),
+                    span.trace_id.to_string(),
+                    format!(
+                        "+{}",
+                        format_axis_label((span.start_timestamp - window.start_ns) as f64)
+                    ),
+                    span_duration_secs(span)
+                        .map(duration_label)
+                        .unwrap_or_else(|| "active".to_string()),
+                    span.location.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    });
+    report.push(ReportBlock::Placeholder {
+        title: "Compare windows".to_string(),
+        widget: "TraceCompareCard".to_string(),
+        config: vec![],
+    });
+    report
+}
+
 fn filter_span_tree(
     spans: &[SpanInfo],
     query: &str,
@@ -452,6 +691,11 @@ fn active_filter_summary(query: &str, advanced: &TraceAdvancedFilters) -> String
     if advanced.active_only {
         parts.push("active".to_string());
     }
+    parts.extend(advanced.chips.iter().map(TraceChip::param));
+    let server = TRACE_SERVER_FILTERS.read().query_params();
+    if !server.is_empty() {
+        parts.push(format!("server:{}", server.trim_start_matches('&')));
+    }
     parts.join(", ")
 }
 
@@ -461,6 +705,7 @@ struct TraceAdvancedFilters {
     min_duration_ms: Option<f64>,
     active_only: bool,
     local_step: Option<i64>,
+    chips: Vec<TraceChip>,
 }
 
 fn span_local_step(span: &SpanInfo) -> Option<i64> {
@@ -471,6 +716,26 @@ fn span_local_step(span: &SpanInfo) -> Option<i64> {
         .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|n| n as i64)))
 }
 
+/// `trace:span` of each span this one links to.
+fn span_link_targets(span: &SpanInfo) -> Vec<String> {
+    let Some(raw) = span.links.as_ref() else {
+        return vec![];
+    };
+    let Ok(serde_json::Value::Array(links)) = serde_json::from_str(raw) else {
+        return vec![];
+    };
+    links
+        .iter()
+        .filter_map(|link| {
+            Some(format!(
+                "{}:{}",
+                link.get("trace_id")?,
+                link.get("span_id")?
+            ))
+        })
+        .collect()
+}
+
 fn span_matches_advanced(span: &SpanInfo, filters: &TraceAdvancedFilters) -> bool {
     if let Some(trace_id) = filters.trace_id {
         if span.trace_id != trace_id {
@@ -488,6 +753,9 @@ fn span_matches_advanced(span: &SpanInfo, filters: &TraceAdvancedFilters) -> boo
             _ => return false,
         }
     }
+    if !span_matches_chips(span, &filters.chips) {
+        return false;
+    }
     if filters.active_only && span.end_timestamp.is_some() {
         return false;
     }
@@ -573,22 +841,30 @@ fn SpanView(
     highlight: SpanHighlight,
     expand_all: Signal<u32>,
     collapse_all: Signal<u32>,
+    mut selected: Signal<Option<SpanInfo>>,
     time_window: TraceTimeWindow,
 ) -> Element {
     let mut expanded = use_signal(|| depth < 2);
     let has_children = !span.children.is_empty();
     let has_events = !span.events.is_empty();
+    let link_targets = span_link_targets(&span);
     let has_attrs = span
         .attributes
         .as_ref()
         .is_some_and(|a| !a.trim().is_empty());
-    let has_details = has_children || has_events || has_attrs;
+    let has_location = span.location.as_ref().is_some_and(|l| !l.is_empty());
+    let has_details = has_children || has_events || has_attrs || has_location;
     let duration = span_duration_secs(&span);
     let indent = depth * 20;
     let trace_id = span.trace_id;
     let thread_id = span.thread_id as i32;
     let span_name = span.name.clone();
     let row_class = span_row_class(&span, &highlight);
+    let is_selected = selected
+        .read()
+        .as_ref()
+        .is_some_and(|s| s.trace_id == span.trace_id && s.span_id == span.span_id);
+    let clicked = span.clone();
 
     use_effect(move || {
         if expand_all() > 0 {
@@ -604,7 +880,11 @@ fn SpanView(
     rsx! {
         div { class: "min-w-0",
             div {
-                class: "flex items-stretch min-w-0 hover:bg-gray-50/50",
+                class: if is_selected {
+                    "flex items-stretch min-w-0 bg-blue-50/70 ring-1 ring-inset ring-blue-300"
+                } else {
+                    "flex items-stretch min-w-0 hover:bg-gray-50/50"
+                },
                 div {
                     class: "flex flex-1 min-w-0 items-stretch",
                     SpanTimelineBar {
@@ -615,8 +895,10 @@ fn SpanView(
                     div {
                         class: "{row_class} flex-1 min-w-0 border-b border-gray-50",
                         style: if indent > 0 { format!("padding-left: {indent}px") } else { String::new() },
+                        title: "Click for span details",
                         onclick: move |_| {
                             set_trace_context(trace_id, Some(&span_name), Some(thread_id));
+                            selected.set(Some(clicked.clone()));
                         },
                         if has_details {
                             button {
@@ -660,6 +942,23 @@ fn SpanView(
                         } else {
                             span { class: "text-amber-600 shrink-0", "active" }
                         }
+                        if let Some(cpu_ns) = span.cpu_time_ns {
+                            span {
+                                class: "text-gray-500 shrink-0",
+                                title: "Thread CPU time / context switches",
+                                "cpu {duration_label(cpu_ns as f64 / 1e9)}"
+                                if let Some(switches) = span.ctx_switches {
+                                    " · {switches}cs"
+                                }
+                            }
+                        }
+                        if !link_targets.is_empty() {
+                            span {
+                                class: "text-indigo-500 shrink-0",
+                                title: "Linked spans (trace:span): {link_targets.join(\", \")}",
+                                "{link_targets.len()}↗"
+                            }
+                        }
                         if has_events {
                             span { class: "text-gray-400 shrink-0", "{span.events.len()}evt" }
                         }
@@ -681,6 +980,16 @@ fn SpanView(
                         }
                     }
                 }
+                if has_location {
+                    div { class: "flex items-stretch min-w-0",
+                        SpanTimelineSpacer {}
+                        div {
+                            class: "flex-1 min-w-0 pb-1",
+                            style: format!("padding-left: {}px", indent + 20),
+                            SpanSourceSnippet { span_id: span.span_id }
+                        }
+                    }
+                }
                 if has_events {
                     for event in span.events.iter() {
                         div { class: "flex items-stretch min-w-0",
@@ -702,6 +1011,7 @@ fn SpanView(
                             highlight: highlight.clone(),
                             expand_all,
                             collapse_all,
+                            selected,
                             time_window,
                         }
                     }
@@ -711,6 +1021,73 @@ fn SpanView(
     }
 }
 
+/// Source around the span's location, fetched when the span is expanded.
+/// Unavailable files show the server's reason in place of the snippet.
+#[component]
+fn SpanSourceSnippet(span_id: i64) -> Element {
+    let source =
+        use_app_resource(move || async move { ApiClient::new().get_span_source(span_id).await });
+    let mut copied = use_signal(|| false);
+
+    let loaded = source.read().clone();
+    match loaded {
+        None => rsx! {
+            div { class: "py-0.5 text-gray-400", "Loading source…" }
+        },
+        Some(Err(e)) => rsx! {
+            div { class: "py-0.5 text-gray-400", "Source unavailable: {e.display_message()}" }
+        },
+        Some(Ok(source)) if !source.available => rsx! {
+            div { class: "py-0.5 text-gray-400",
+                "Source unavailable"
+                if let Some(reason) = source.reason {
+                    ": {reason}"
+                }
+            }
+        },
+        Some(Ok(source)) => {
+            let text = source.lines.join("\n");
+            let title = match (&source.path, &source.function) {
+                (Some(path), Some(function)) => format!("{path} · {function}"),
+                (Some(path), None) => path.clone(),
+                _ => String::new(),
+            };
+            rsx! {
+                div { class: "my-0.5 rounded border border-gray-200 bg-white overflow-hidden",
+                    div { class: "flex items-center gap-2 px-2 py-0.5 border-b border-gray-100 bg-gray-50 text-[10px] text-gray-500",
+                        span { class: "truncate", title: "{title}", "{title}" }
+                        button {
+                            class: "ml-auto shrink-0 px-1.5 rounded hover:bg-gray-200 hover:text-gray-800",
+                            title: "Copy snippet",
+                            onclick: move |e| {
+                                e.stop_propagation();
+                                if let Some(window) = web_sys::window() {
+                                    let _ = window.navigator().clipboard().write_text(&text);
+                                    copied.set(true);
+                                }
+                            },
+                            if copied() { "Copied" } else { "Copy" }
+                        }
+                    }
+                    div { class: "max-h-72 overflow-auto",
+                        PlainSourceLines { slice: span_source_slice(&source) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn span_source_slice(source: &SpanSource) -> SourceSlice {
+    SourceSlice {
+        text: source.lines.join("\n"),
+        start_line: source.start_line,
+        end_line: source.start_line + source.lines.len().saturating_sub(1),
+        total_lines: source.total_lines,
+        highlight_line: source.line.and_then(|l| u32::try_from(l).ok()),
+    }
+}
+
 #[component]
 fn AttributesInline(raw: String) -> Element {
     rsx! {
@@ -0,0 +1,216 @@
+//! Failed API calls shown as global toasts by
+//! [`ApiErrorToasts`](crate::components::api_error_toasts::ApiErrorToasts).
+//!
+//! Callers name the endpoint and, for idempotent reads only, pass a retry
+//! closure. Toast text goes through [`redact_secrets`] so auth tokens echoed
+//! by a proxy or URL never reach the screen.
+
+use std::future::Future;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::utils::error::AppError;
+
+/// Toasts beyond this many drop the oldest.
+const MAX_API_TOASTS: usize = 4;
+const API_TOAST_DISMISS_MS: u32 = 8_000;
+
+/// Re-issues a failed idempotent request. Runs after its page may be gone,
+/// so it should only touch global signals (or use `try_write`).
+pub type RetryFn = Rc<dyn Fn()>;
+
+#[derive(Clone)]
+pub struct ApiErrorToast {
+    pub id: u64,
+    /// e.g. `GET /apis/pythonext/trace/stop`, without query parameters.
+    pub endpoint: String,
+    pub status: Option<u16>,
+    pub message: String,
+    pub retry: Option<RetryFn>,
+}
+
+impl ApiErrorToast {
+    /// `404 · GET /apis/x` or just the endpoint when there was no response.
+    pub fn heading(&self) -> String {
+        match self.status {
+            Some(status) => format!("{status} · {}", self.endpoint),
+            None => self.endpoint.clone(),
+        }
+    }
+}
+
+pub static API_ERROR_TOASTS: GlobalSignal<Vec<ApiErrorToast>> = Signal::global(Vec::new);
+static NEXT_API_TOAST_ID: GlobalSignal<u64> = Signal::global(|| 0);
+
+/// Key names whose `key=value` / `key: value` values are masked.
+const SECRET_KEYS: [&str; 5] = ["token", "password", "secret", "authorization", "api_key"];
+
+/// `text` with bearer tokens and secret-looking `key=value` values masked.
+pub fn redact_secrets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut mask_next = false;
+    for (i, word) in text.split(' ').enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        if word.eq_ignore_ascii_case("bearer") {
+            mask_next = true;
+            out.push_str(word);
+            continue;
+        }
+        if mask_next && !word.is_empty() {
+            out.push_str("***");
+            mask_next = false;
+            continue;
+        }
+        out.push_str(&redact_pairs(word, &mut mask_next));
+    }
+    out
+}
+
+/// Masks the value of each secret `key=value` / `key:value` in `word`; a
+/// trailing `key:` or `key=` masks the next word instead.
+fn redact_pairs(word: &str, mask_next: &mut bool) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut rest = word;
+    while let Some(pos) = rest.find(['=', ':']) {
+        let key = rest[..pos]
+            .trim_end_matches(['"', '\''])
+            .rsplit(['&', '?', ';', ',', '"', '\'', '{', '('])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        out.push_str(&rest[..=pos]);
+        rest = &rest[pos + 1..];
+        if !SECRET_KEYS.iter().any(|k| key.ends_with(k)) {
+            continue;
+        }
+        let value = rest.trim_start_matches('"');
+        out.push_str(&rest[..rest.len() - value.len()]);
+        let end = value
+            .find(['&', ';', ',', '"', '\'', '}', ')'])
+            .unwrap_or(value.len());
+        if value.is_empty() {
+            *mask_next = true;
+        } else {
+            out.push_str("***");
+        }
+        rest = &value[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `GET /apis/x?token=…` → `GET /apis/x`: query strings may hold secrets.
+fn endpoint_label(endpoint: &str) -> String {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    redact_secrets(path.trim())
+}
+
+/// Show `err` from `endpoint` as a toast, with a Retry button when `retry`
+/// is given. Cancelled requests are ignored.
+pub fn notify_api_error(endpoint: &str, err: &AppError, retry: Option<RetryFn>) {
+    if err.is_cancelled() {
+        return;
+    }
+    let message = match err {
+        AppError::Http { message, .. } => message.clone(),
+        other => other.display_message(),
+    };
+    let id = {
+        let mut next = NEXT_API_TOAST_ID.write();
+        *next += 1;
+        *next
+    };
+    let toast = ApiErrorToast {
+        id,
+        endpoint: endpoint_label(endpoint),
+        status: err.http_status(),
+        message: redact_secrets(&message),
+        retry,
+    };
+    {
+        let mut toasts = API_ERROR_TOASTS.write();
+        toasts.retain(|t| t.endpoint != toast.endpoint);
+        toasts.push(toast);
+        let excess = toasts.len().saturating_sub(MAX_API_TOASTS);
+        toasts.drain(..excess);
+    }
+    spawn(async move {
+        gloo_timers::future::TimeoutFuture::new(API_TOAST_DISMISS_MS).await;
+        dismiss_api_error(id);
+    });
+}
+
+pub fn dismiss_api_error(id: u64) {
+    API_ERROR_TOASTS.write().retain(|t| t.id != id);
+}
+
+/// Await `request`, routing a failure into [`notify_api_error`]; the result
+/// is returned unchanged so callers can still render their own state.
+pub async fn with_error_toast<T>(
+    endpoint: &str,
+    retry: Option<RetryFn>,
+    request: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    let result = request.await;
+    if let Err(err) = &result {
+        notify_api_error(endpoint, err, retry);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bearer_and_query_tokens_are_masked() {
+        assert_eq!(
+            redact_secrets("rejected Authorization: Bearer abc.def"),
+            "rejected Authorization: Bearer ***"
+        );
+        assert_eq!(
+            redact_secrets("GET /apis/x?token=s3cr3t&limit=5 failed"),
+            "GET /apis/x?token=***&limit=5 failed"
+        );
+        assert_eq!(
+            redact_secrets(r#"{"auth_token":"xyz","ok":false}"#),
+            r#"{"auth_token":"***","ok":false}"#
+        );
+        assert_eq!(
+            redact_secrets("server.auth_token= hunter2 is invalid"),
+            "server.auth_token= *** is invalid"
+        );
+        assert_eq!(
+            redact_secrets("HTTP 404: no such table: python.trace_1"),
+            "HTTP 404: no such table: python.trace_1"
+        );
+    }
+
+    #[test]
+    fn endpoint_drops_query_string() {
+        assert_eq!(
+            endpoint_label("GET /apis/pythonext/trace/stop?function=f&token=t"),
+            "GET /apis/pythonext/trace/stop"
+        );
+    }
+
+    #[test]
+    fn heading_includes_status_when_known() {
+        let toast = ApiErrorToast {
+            id: 1,
+            endpoint: "POST /query".to_string(),
+            status: Some(502),
+            message: "Bad Gateway".to_string(),
+            retry: None,
+        };
+        assert_eq!(toast.heading(), "502 · POST /query");
+        let offline = ApiErrorToast {
+            status: None,
+            ..toast
+        };
+        assert_eq!(offline.heading(), "POST /query");
+    }
+}
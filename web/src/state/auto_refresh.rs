@@ -0,0 +1,61 @@
+//! Auto-refresh interval shared by the Dashboard and Cluster pages,
+//! persisted in browser localStorage next to the sidebar layout.
+
+use dioxus::prelude::*;
+
+const STORAGE_KEY: &str = "auto_refresh_secs";
+/// Intervals offered by the control; `0` is off.
+pub const AUTO_REFRESH_CHOICES: [u32; 4] = [0, 2, 5, 30];
+const DEFAULT_AUTO_REFRESH_SECS: u32 = 2;
+
+pub static AUTO_REFRESH_SECS: GlobalSignal<u32> = Signal::global(load_auto_refresh);
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// A stored interval, when it is one of [`AUTO_REFRESH_CHOICES`].
+fn parse_auto_refresh(raw: &str) -> Option<u32> {
+    raw.trim()
+        .parse()
+        .ok()
+        .filter(|secs| AUTO_REFRESH_CHOICES.contains(secs))
+}
+
+fn load_auto_refresh() -> u32 {
+    storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|raw| parse_auto_refresh(&raw))
+        .unwrap_or(DEFAULT_AUTO_REFRESH_SECS)
+}
+
+pub fn set_auto_refresh_secs(secs: u32) {
+    *AUTO_REFRESH_SECS.write() = secs;
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(STORAGE_KEY, &secs.to_string());
+    }
+}
+
+/// Control label for an interval.
+pub fn auto_refresh_label(secs: u32) -> String {
+    if secs == 0 {
+        "Off".to_string()
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_offered_intervals_are_restored() {
+        assert_eq!(parse_auto_refresh("5"), Some(5));
+        assert_eq!(parse_auto_refresh(" 0 "), Some(0));
+        assert_eq!(parse_auto_refresh("7"), None);
+        assert_eq!(parse_auto_refresh("fast"), None);
+        assert_eq!(auto_refresh_label(0), "Off");
+        assert_eq!(auto_refresh_label(30), "30s");
+    }
+}
@@ -0,0 +1,251 @@
+//! Console drawer state: transcript, input history and REPL availability.
+//!
+//! The drawer evaluates through `pythonext/eval`, whose namespace lives in the
+//! target process, so definitions survive navigation on their own. The input
+//! history is kept per browser tab (`sessionStorage`).
+
+use dioxus::prelude::*;
+
+const HISTORY_KEY: &str = "probing_console_history";
+const MAX_HISTORY: usize = 200;
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleStatus {
+    Running,
+    Ok,
+    Error,
+    Cancelled,
+}
+
+/// One evaluation: input, its output / traceback and the UI task timing it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleEntry {
+    pub task_id: u64,
+    pub input: String,
+    pub output: String,
+    pub traceback: Vec<String>,
+    pub status: ConsoleStatus,
+    pub started_ms: u64,
+    /// Set when the reply arrives or the run is cancelled.
+    pub finished_ms: Option<u64>,
+}
+
+impl ConsoleEntry {
+    pub fn elapsed_label(&self, now_ms: u64) -> String {
+        let ms = self
+            .finished_ms
+            .unwrap_or(now_ms)
+            .saturating_sub(self.started_ms);
+        if ms < 1_000 {
+            format!("{ms}ms")
+        } else {
+            format!("{:.1}s", ms as f64 / 1_000.0)
+        }
+    }
+}
+
+pub static CONSOLE_OPEN: GlobalSignal<bool> = Signal::global(|| false);
+pub static CONSOLE_INPUT: GlobalSignal<String> = Signal::global(String::new);
+pub static CONSOLE_ENTRIES: GlobalSignal<Vec<ConsoleEntry>> = Signal::global(Vec::new);
+pub static CONSOLE_HISTORY: GlobalSignal<Vec<String>> = Signal::global(load_history);
+/// `None` until probed; `Some(false)` hides the console (REPL routes gated
+/// by auth or the Python extension not loaded).
+pub static CONSOLE_AVAILABLE: GlobalSignal<Option<bool>> = Signal::global(|| None);
+
+/// Toggle the drawer unless the REPL is known to be unavailable.
+pub fn toggle_console() {
+    if *CONSOLE_AVAILABLE.read() == Some(false) {
+        *CONSOLE_OPEN.write() = false;
+        return;
+    }
+    let open = *CONSOLE_OPEN.read();
+    *CONSOLE_OPEN.write() = !open;
+}
+
+pub fn push_console_entry(entry: ConsoleEntry) {
+    let mut entries = CONSOLE_ENTRIES.write();
+    entries.push(entry);
+    let excess = entries.len().saturating_sub(MAX_ENTRIES);
+    entries.drain(..excess);
+}
+
+pub fn update_console_entry(task_id: u64, mutator: impl FnOnce(&mut ConsoleEntry)) {
+    if let Some(entry) = CONSOLE_ENTRIES
+        .write()
+        .iter_mut()
+        .find(|e| e.task_id == task_id)
+    {
+        mutator(entry);
+    }
+}
+
+/// Record `input` in the per-tab history.
+pub fn remember_console_input(input: &str) {
+    let mut history = CONSOLE_HISTORY.write();
+    push_history(&mut history, input);
+    save_history(&history);
+}
+
+fn load_history() -> Vec<String> {
+    let Some(storage) = web_sys::window().and_then(|w| w.session_storage().ok().flatten()) else {
+        return Vec::new();
+    };
+    storage
+        .get_item(HISTORY_KEY)
+        .ok()
+        .flatten()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_history(history: &[String]) {
+    let Some(storage) = web_sys::window().and_then(|w| w.session_storage().ok().flatten()) else {
+        return;
+    };
+    if let Ok(raw) = serde_json::to_string(history) {
+        let _ = storage.set_item(HISTORY_KEY, &raw);
+    }
+}
+
+/// Append `input` unless it repeats the last entry; keeps the newest
+/// [`MAX_HISTORY`].
+pub fn push_history(history: &mut Vec<String>, input: &str) {
+    if input.trim().is_empty() || history.last().is_some_and(|last| last == input) {
+        return;
+    }
+    history.push(input.to_string());
+    let excess = history.len().saturating_sub(MAX_HISTORY);
+    history.drain(..excess);
+}
+
+/// Next history position for ↑ (`older`) / ↓ from `pos` (`None` = the
+/// draft being typed). Returns `None` when ↓ walks past the newest entry.
+pub fn history_step(len: usize, pos: Option<usize>, older: bool) -> Option<usize> {
+    match (pos, older) {
+        (_, true) if len == 0 => None,
+        (None, true) => Some(len - 1),
+        (Some(i), true) => Some(i.saturating_sub(1)),
+        (None, false) => None,
+        (Some(i), false) if i + 1 < len => Some(i + 1),
+        (Some(_), false) => None,
+    }
+}
+
+/// Longest common prefix of `matches`.
+pub fn common_prefix(matches: &[String]) -> &str {
+    let Some(first) = matches.first() else {
+        return "";
+    };
+    let mut len = first.len();
+    for m in &matches[1..] {
+        len = first
+            .char_indices()
+            .zip(m.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(len);
+    }
+    &first[..len]
+}
+
+/// Apply completion `matches` for `code[start..end]` (character offsets):
+/// the single match, or the matches' common prefix when it extends the
+/// typed text. Returns the new code and cursor (characters), or `None`
+/// when nothing would change.
+pub fn apply_completion(
+    code: &str,
+    start: usize,
+    end: usize,
+    matches: &[String],
+) -> Option<(String, usize)> {
+    let start_byte = char_to_byte(code, start);
+    let end_byte = char_to_byte(code, end);
+    if start_byte > end_byte {
+        return None;
+    }
+    let typed = &code[start_byte..end_byte];
+    let replacement = match matches {
+        [only] => only.as_str(),
+        _ => common_prefix(matches),
+    };
+    if replacement.is_empty() || replacement == typed {
+        return None;
+    }
+    let completed = format!("{}{replacement}{}", &code[..start_byte], &code[end_byte..]);
+    Some((completed, start + replacement.chars().count()))
+}
+
+/// Byte offset of character `index` (clamped to the end).
+pub fn char_to_byte(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Character index of a UTF-16 offset (DOM `selectionStart`).
+pub fn utf16_to_char(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.chars().enumerate() {
+        if units >= offset {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    text.chars().count()
+}
+
+/// UTF-16 offset of character `index` (for `setSelectionRange`).
+pub fn char_to_utf16(text: &str, index: usize) -> usize {
+    text.chars().take(index).map(char::len_utf16).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_skips_repeats_and_walks_both_ways() {
+        let mut history = Vec::new();
+        for input in ["a", "a", " ", "b"] {
+            push_history(&mut history, input);
+        }
+        assert_eq!(history, ["a", "b"]);
+        assert_eq!(history_step(2, None, true), Some(1));
+        assert_eq!(history_step(2, Some(0), true), Some(0));
+        assert_eq!(history_step(2, Some(0), false), Some(1));
+        assert_eq!(history_step(2, Some(1), false), None);
+        assert_eq!(history_step(0, None, true), None);
+    }
+
+    #[test]
+    fn completion_uses_single_match_or_common_prefix() {
+        let m = |xs: &[&str]| xs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            apply_completion("x = os.pa", 4, 9, &m(&["os.path"])),
+            Some(("x = os.path".to_string(), 11))
+        );
+        assert_eq!(
+            apply_completion("ns.va + 1", 0, 5, &m(&["ns.value", "ns.values"])),
+            Some(("ns.value + 1".to_string(), 8))
+        );
+        assert_eq!(
+            apply_completion("ns.v", 0, 4, &m(&["ns.value", "ns.vx"])),
+            None
+        );
+        assert_eq!(
+            apply_completion("é.a", 2, 3, &m(&["abc"])),
+            Some(("é.abc".to_string(), 5))
+        );
+    }
+
+    #[test]
+    fn dom_offsets_map_to_characters() {
+        let text = "a😀b";
+        assert_eq!(utf16_to_char(text, 3), 2);
+        assert_eq!(char_to_utf16(text, 2), 3);
+        assert_eq!(char_to_byte(text, 2), 5);
+        assert_eq!(char_to_byte(text, 9), text.len());
+    }
+}
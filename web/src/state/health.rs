@@ -0,0 +1,163 @@
+//! Target health as seen by the header's `/healthz` poll.
+//!
+//! Polling hooks read [`target_unreachable`] and skip their ticks while the
+//! target is down, so a dead process does not turn every panel into an error
+//! storm; they resume on the first successful probe.
+
+use dioxus::prelude::*;
+
+use crate::api::{HealthReport, HealthStage};
+
+/// Probes slower than this are shown as degraded even when every stage is ok.
+pub const SLOW_PROBE_MS: f64 = 1_000.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HealthLevel {
+    /// No probe has completed yet.
+    #[default]
+    Unknown,
+    Ok,
+    /// Reachable, but slow or with a readiness stage not ok.
+    Degraded,
+    Unreachable,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TargetHealth {
+    pub level: HealthLevel,
+    /// Latency of the last successful probe.
+    pub latency_ms: Option<f64>,
+    /// Epoch ms of the last successful probe.
+    pub last_ok_ms: Option<f64>,
+    /// Stages from the last successful probe.
+    pub stages: Vec<HealthStage>,
+    /// Error of the last failed probe; cleared on success.
+    pub error: Option<String>,
+}
+
+pub static TARGET_HEALTH: GlobalSignal<TargetHealth> = Signal::global(TargetHealth::default);
+
+/// True while the last probe failed. Peeks, so interval callbacks calling
+/// this do not subscribe to health changes.
+pub fn target_unreachable() -> bool {
+    TARGET_HEALTH.peek().level == HealthLevel::Unreachable
+}
+
+impl TargetHealth {
+    pub fn on_success(&self, report: HealthReport, latency_ms: f64, now_ms: f64) -> Self {
+        let stages_ok = report.status == "ok" && report.stages.iter().all(|s| s.status == "ok");
+        let level = if stages_ok && latency_ms <= SLOW_PROBE_MS {
+            HealthLevel::Ok
+        } else {
+            HealthLevel::Degraded
+        };
+        Self {
+            level,
+            latency_ms: Some(latency_ms),
+            last_ok_ms: Some(now_ms),
+            stages: report.stages,
+            error: None,
+        }
+    }
+
+    /// Keeps the last known stages and success time for the detail popover.
+    pub fn on_failure(&self, error: String) -> Self {
+        Self {
+            level: HealthLevel::Unreachable,
+            error: Some(error),
+            ..self.clone()
+        }
+    }
+
+    /// Short pill text, e.g. `Healthy · 12 ms` or `Unreachable · 1m 5s ago`.
+    pub fn summary(&self, now_ms: f64) -> String {
+        match self.level {
+            HealthLevel::Unknown => "Connecting…".to_string(),
+            HealthLevel::Ok => format!("Healthy · {}", format_latency(self.latency_ms)),
+            HealthLevel::Degraded => {
+                let stage = self.stages.iter().find(|s| s.status != "ok");
+                match stage {
+                    Some(stage) => format!("{} {}", stage.name, stage.status),
+                    None => format!("Slow · {}", format_latency(self.latency_ms)),
+                }
+            }
+            HealthLevel::Unreachable => match self.last_ok_ms {
+                Some(at) => format!("Unreachable · {} ago", format_elapsed(now_ms - at)),
+                None => "Unreachable".to_string(),
+            },
+        }
+    }
+}
+
+fn format_latency(latency_ms: Option<f64>) -> String {
+    match latency_ms {
+        Some(ms) if ms >= 1_000.0 => format!("{:.1} s", ms / 1_000.0),
+        Some(ms) => format!("{ms:.0} ms"),
+        None => "—".to_string(),
+    }
+}
+
+pub fn format_elapsed(ms: f64) -> String {
+    let secs = (ms.max(0.0) / 1_000.0) as u64;
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(engine: &str) -> HealthReport {
+        HealthReport {
+            status: if engine == "ok" { "ok" } else { "degraded" }.into(),
+            stages: vec![
+                HealthStage {
+                    name: "http".into(),
+                    status: "ok".into(),
+                    detail: None,
+                },
+                HealthStage {
+                    name: "engine".into(),
+                    status: engine.into(),
+                    detail: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn classifies_ok_slow_and_degraded_probes() {
+        let base = TargetHealth::default();
+        let ok = base.on_success(report("ok"), 12.0, 1_000.0);
+        assert_eq!(ok.level, HealthLevel::Ok);
+        assert_eq!(ok.summary(1_000.0), "Healthy · 12 ms");
+
+        let slow = base.on_success(report("ok"), 1_500.0, 1_000.0);
+        assert_eq!(slow.level, HealthLevel::Degraded);
+        assert_eq!(slow.summary(1_000.0), "Slow · 1.5 s");
+
+        let starting = base.on_success(report("starting"), 5.0, 1_000.0);
+        assert_eq!(starting.level, HealthLevel::Degraded);
+        assert_eq!(starting.summary(1_000.0), "engine starting");
+    }
+
+    #[test]
+    fn failure_keeps_last_success_for_elapsed_time() {
+        let ok = TargetHealth::default().on_success(report("ok"), 8.0, 10_000.0);
+        let down = ok.on_failure("HTTP error: 502".into());
+        assert_eq!(down.level, HealthLevel::Unreachable);
+        assert_eq!(down.stages.len(), 2);
+        assert_eq!(down.summary(75_000.0), "Unreachable · 1m 5s ago");
+        assert_eq!(
+            TargetHealth::default().on_failure("x".into()).summary(0.0),
+            "Unreachable"
+        );
+
+        let recovered = down.on_success(report("ok"), 9.0, 80_000.0);
+        assert_eq!(recovered.level, HealthLevel::Ok);
+        assert!(recovered.error.is_none());
+    }
+}
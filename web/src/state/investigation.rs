@@ -9,6 +9,9 @@ pub struct InvestigationContext {
     pub span_name: Option<String>,
     /// Training coordinate from step matrix / heatmap (filters span attributes on Spans page).
     pub local_step: Option<i64>,
+    /// Active Spans page quick-filter chips (`name:…`, `kind:…`, `thread:…`).
+    #[serde(default)]
+    pub chips: Vec<String>,
     pub label: Option<String>,
 }
 
@@ -19,6 +22,7 @@ impl InvestigationContext {
             && self.trace_id.is_none()
             && self.span_name.is_none()
             && self.local_step.is_none()
+            && self.chips.is_empty()
             && self.label.is_none()
     }
 
@@ -39,6 +43,7 @@ impl InvestigationContext {
         if let Some(name) = &self.span_name {
             parts.push(name.clone());
         }
+        parts.extend(self.chips.iter().cloned());
         if parts.is_empty() {
             "No context".to_string()
         } else {
@@ -135,6 +140,22 @@ pub fn investigation_context_key(ctx: &InvestigationContext) -> String {
     )
 }
 
+/// Toggle a Spans page quick-filter chip (in `name:value` form) in global context (and URL).
+pub fn toggle_span_chip(chip: &str) {
+    update_investigation_context(|ctx| {
+        if let Some(i) = ctx.chips.iter().position(|c| c == chip) {
+            ctx.chips.remove(i);
+        } else {
+            ctx.chips.push(chip.to_string());
+        }
+        ctx.label = if ctx.is_empty() {
+            None
+        } else {
+            Some(ctx.summary())
+        };
+    });
+}
+
 /// Write Spans page filters back into global context (and URL).
 pub fn sync_spans_filters_to_context(
     name_filter: &str,
@@ -1,19 +1,24 @@
-//! Sync investigation context with URL query parameters (`?pid=&tid=&trace_id=`).
+//! Sync investigation context with URL query parameters (`?pid=&tid=&trace_id=&chip=`);
+//! other query parameters are left alone (see [`crate::state::url_query`]).
 
 use dioxus::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
-use wasm_bindgen::JsValue;
 
 use crate::state::investigation::{
     update_investigation_context, InvestigationContext, INVESTIGATION_CONTEXT,
 };
+use crate::state::url_query::{current_url_search, query_pairs, replace_url_query};
 
 const QUERY_PID: &str = "pid";
 const QUERY_TID: &str = "tid";
 const QUERY_TRACE_ID: &str = "trace_id";
 const QUERY_SPAN: &str = "span";
+/// Repeated once per active Spans quick-filter chip.
+const QUERY_CHIP: &str = "chip";
+const INVESTIGATION_QUERY_KEYS: &[&str] =
+    &[QUERY_PID, QUERY_TID, QUERY_TRACE_ID, QUERY_SPAN, QUERY_CHIP];
 
 pub fn parse_context_from_search(search: &str) -> InvestigationContext {
     let search = search.trim_start_matches('?');
@@ -22,21 +27,13 @@ pub fn parse_context_from_search(search: &str) -> InvestigationContext {
     }
 
     let mut ctx = InvestigationContext::default();
-    for pair in search.split('&') {
-        if pair.is_empty() {
-            continue;
-        }
-        let Some((key, raw)) = pair.split_once('=') else {
-            continue;
-        };
-        let value = urlencoding::decode(raw)
-            .map(|cow| cow.into_owned())
-            .unwrap_or_else(|_| raw.to_string());
-        match key {
+    for (key, value) in query_pairs(search) {
+        match key.as_str() {
             QUERY_PID => ctx.pid = value.parse().ok(),
             QUERY_TID => ctx.tid = value.parse().ok(),
             QUERY_TRACE_ID => ctx.trace_id = value.parse().ok(),
             QUERY_SPAN if !value.is_empty() => ctx.span_name = Some(value),
+            QUERY_CHIP if !value.is_empty() && !ctx.chips.contains(&value) => ctx.chips.push(value),
             _ => {}
         }
     }
@@ -67,15 +64,12 @@ pub fn context_to_search(ctx: &InvestigationContext) -> String {
             parts.push(format!("{QUERY_SPAN}={}", urlencoding::encode(span)));
         }
     }
+    for chip in &ctx.chips {
+        parts.push(format!("{QUERY_CHIP}={}", urlencoding::encode(chip)));
+    }
     parts.join("&")
 }
 
-pub fn current_url_search() -> String {
-    web_sys::window()
-        .and_then(|w| w.location().search().ok())
-        .unwrap_or_default()
-}
-
 pub fn apply_investigation_context_from_url() {
     let url_ctx = parse_context_from_search(&current_url_search());
     if url_ctx.is_empty() {
@@ -94,29 +88,16 @@ pub fn apply_investigation_context_from_url() {
         if url_ctx.span_name.is_some() {
             ctx.span_name = url_ctx.span_name.clone();
         }
+        if !url_ctx.chips.is_empty() {
+            ctx.chips = url_ctx.chips.clone();
+        }
         ctx.label = Some(ctx.summary());
     });
 }
 
 pub fn sync_investigation_context_to_url() {
-    let Some(window) = web_sys::window() else {
-        return;
-    };
-    let location = window.location();
-    let Ok(pathname) = location.pathname() else {
-        return;
-    };
-    let hash = location.hash().unwrap_or_default();
     let query = context_to_search(&INVESTIGATION_CONTEXT.read());
-    let new_url = if query.is_empty() {
-        format!("{pathname}{hash}")
-    } else {
-        format!("{pathname}?{query}{hash}")
-    };
-
-    if let Ok(history) = window.history() {
-        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&new_url));
-    }
+    replace_url_query(INVESTIGATION_QUERY_KEYS, &query);
 }
 
 /// Keep URL query in sync with global context; re-apply on browser back/forward.
@@ -124,11 +105,12 @@ pub fn sync_investigation_context_to_url() {
 pub fn InvestigationUrlSync() -> Element {
     let ctx = INVESTIGATION_CONTEXT.read().clone();
     let ctx_key = format!(
-        "{}:{}:{}:{}",
+        "{}:{}:{}:{}:{}",
         ctx.pid.unwrap_or(-1),
         ctx.tid.unwrap_or(-1),
         ctx.trace_id.unwrap_or(-1),
-        ctx.span_name.as_deref().unwrap_or("")
+        ctx.span_name.as_deref().unwrap_or(""),
+        ctx.chips.join(",")
     );
 
     use_effect(move || {
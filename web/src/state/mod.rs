@@ -1,5 +1,9 @@
 pub mod agent;
+pub mod api_errors;
+pub mod auto_refresh;
 pub mod commands;
+pub mod console;
+pub mod health;
 pub mod investigation;
 pub mod investigation_url;
 pub mod llm_config;
@@ -7,9 +11,12 @@ pub mod overlays;
 pub mod page_context;
 pub mod profile_snapshots;
 pub mod profiling;
+pub mod profiling_url;
 pub mod rl;
 pub mod scroll_lock;
 pub mod sidebar;
 pub mod source_viewer;
+pub mod sql_history;
 pub mod stack;
 pub mod ui_tasks;
+pub mod url_query;
@@ -36,7 +36,10 @@ pub fn push_profile_snapshot(profiler: &str, metric: Option<&str>, payload: Flam
         payload,
     };
     let mut list = PROFILE_SNAPSHOTS.read().clone();
-    list.retain(|s| !(s.profiler == snap.profiler && s.metric == snap.metric));
+    // Several captures per profiler can be compared; only repeats are dropped.
+    list.retain(|s| {
+        !(s.profiler == snap.profiler && s.metric == snap.metric && s.payload == snap.payload)
+    });
     list.insert(0, snap);
     list.truncate(MAX_SNAPSHOTS);
     *PROFILE_SNAPSHOTS.write() = list;
@@ -12,9 +12,12 @@ pub fn normalize_profiling_view(view: &str) -> &'static str {
     match view {
         "" | "pprof" => "pprof",
         "torch" => "torch",
+        "spanflame" | "span-flamegraph" => "spanflame",
         "trace" | "trace-timeline" => "trace",
+        "combined" | "combined-timeline" => "combined",
         "pytorch" | "pytorch-timeline" => "pytorch",
         "ray" | "ray-timeline" => "ray",
+        "compare" | "diff" => "compare",
         _ => "pprof",
     }
 }
@@ -29,31 +32,47 @@ pub fn apply_profiler_config(config: &[(String, String)]) {
     *PROFILING_TORCH_ENABLED.write() = false;
 
     for (name, value) in config {
-        match name.as_str() {
-            "probing.pprof.sample_freq" => {
-                if let Ok(v) = value.parse::<i32>() {
-                    *PROFILING_PPROF_FREQ.write() = v.max(0);
-                }
-            }
-            "probing.torch.profiling" => {
-                let lowered = value.trim().to_lowercase();
-                let disabled_values = ["", "0", "false", "off", "disable", "disabled"];
-                let enabled = !disabled_values.contains(&lowered.as_str());
-                *PROFILING_TORCH_ENABLED.write() = enabled;
-            }
-            _ => {}
-        }
+        apply_profiler_option(name, value);
     }
     *PROFILING_CONFIG_LOADED.write() = true;
 }
 
+/// Mirror one option into profiling UI state (other keys are ignored).
+pub fn apply_profiler_option(name: &str, value: &str) {
+    match name {
+        "probing.pprof.sample_freq" => {
+            *PROFILING_PPROF_FREQ.write() = value.trim().parse::<i32>().unwrap_or(0).max(0);
+        }
+        "probing.torch.profiling" => {
+            *PROFILING_TORCH_ENABLED.write() = is_enabled_value(value);
+        }
+        _ => {}
+    }
+}
+
+/// Truthiness of a switch-like option value (`""`/`off`/`false`/… are off).
+pub fn is_enabled_value(value: &str) -> bool {
+    let lowered = value.trim().to_lowercase();
+    !matches!(
+        lowered.as_str(),
+        "" | "0" | "false" | "off" | "disable" | "disabled"
+    )
+}
+
 pub static PROFILING_CHROME_LIMIT: GlobalSignal<usize> = Signal::global(|| 1000);
 /// Row cap for the Spans page tree (`python.trace_event`); independent of Profiling chrome trace.
 pub static SPANS_TREE_LIMIT: GlobalSignal<usize> = Signal::global(|| 1000);
+/// Filters pushed into the span tree and chrome trace queries ("apply server-side" on Spans).
+pub static TRACE_SERVER_FILTERS: GlobalSignal<crate::api::TraceFilters> =
+    Signal::global(crate::api::TraceFilters::default);
+/// Torch flamegraph metric (`duration`, `delta_mb`, `peak_mb`).
+pub static PROFILING_TORCH_METRIC: GlobalSignal<String> = Signal::global(|| "duration".to_string());
 pub static PROFILING_PYTORCH_STEPS: GlobalSignal<i32> = Signal::global(|| 5);
 pub static PROFILING_PYTORCH_TIMELINE_RELOAD: GlobalSignal<i32> = Signal::global(|| 0);
 pub static PROFILING_RAY_TIMELINE_RELOAD: GlobalSignal<i32> = Signal::global(|| 0);
 pub static PROFILING_TRACE_RELOAD: GlobalSignal<i32> = Signal::global(|| 0);
+/// Bumped to refetch the profiler settings (Retry on a failed load).
+pub static PROFILING_CONFIG_RELOAD: GlobalSignal<i32> = Signal::global(|| 0);
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ProfilingFeedback {
@@ -101,12 +120,24 @@ pub const PROFILING_VIEWS: &[ProfilingViewSpec] = &[
         sidebar_label: "Torch flamegraph",
         tooltip: "PyTorch module hook durations · statistical flamegraph (not the profiler timeline)",
     },
+    ProfilingViewSpec {
+        id: "spanflame",
+        label: "Span flamegraph",
+        sidebar_label: "Span flamegraph",
+        tooltip: "Self time of recorded spans per stack of span names (scoped to the investigation trace when set)",
+    },
     ProfilingViewSpec {
         id: "trace",
         label: "Chrome trace",
         sidebar_label: "Chrome trace",
         tooltip: "Chrome trace event timeline from probing trace buffers (not distributed spans on the Spans page)",
     },
+    ProfilingViewSpec {
+        id: "combined",
+        label: "Combined timeline",
+        sidebar_label: "Combined timeline",
+        tooltip: "Probing spans and PyTorch profiler events merged on one timebase",
+    },
     ProfilingViewSpec {
         id: "pytorch",
         label: "PyTorch profiler",
@@ -119,6 +150,12 @@ pub const PROFILING_VIEWS: &[ProfilingViewSpec] = &[
         sidebar_label: "Ray timeline",
         tooltip: "Ray task and actor timeline",
     },
+    ProfilingViewSpec {
+        id: "compare",
+        label: "Compare snapshots",
+        sidebar_label: "Compare snapshots",
+        tooltip: "Differential flamegraph of two captured snapshots (red grew, blue shrank)",
+    },
 ];
 
 pub fn profiling_view_spec(view: &str) -> &'static ProfilingViewSpec {
@@ -0,0 +1,143 @@
+//! Profiling view settings in the URL query (`/profiling/torch?metric=peak_mb`,
+//! `/profiling/trace?limit=2000`), so a shared link opens the same view with
+//! the same settings. The view itself is the route segment.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::components::flamegraph::logic::TORCH_METRICS;
+use crate::state::profiling::{
+    PROFILING_CHROME_LIMIT, PROFILING_PYTORCH_STEPS, PROFILING_TORCH_METRIC,
+};
+use crate::state::url_query::{current_url_search, query_pairs, replace_url_query};
+
+const QUERY_METRIC: &str = "metric";
+const QUERY_LIMIT: &str = "limit";
+const QUERY_STEPS: &str = "steps";
+const PROFILING_QUERY_KEYS: &[&str] = &[QUERY_METRIC, QUERY_LIMIT, QUERY_STEPS];
+
+/// Bounds of the sidebar controls; values outside them are ignored.
+const LIMIT_RANGE: std::ops::RangeInclusive<usize> = 100..=5000;
+const STEPS_RANGE: std::ops::RangeInclusive<i32> = 1..=100;
+
+/// Settings named in the URL; `None` keeps the current value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfilingUrlParams {
+    pub metric: Option<String>,
+    pub limit: Option<usize>,
+    pub steps: Option<i32>,
+}
+
+pub fn parse_profiling_params(search: &str) -> ProfilingUrlParams {
+    let mut params = ProfilingUrlParams::default();
+    for (key, value) in query_pairs(search) {
+        let value = value.trim();
+        match key.as_str() {
+            QUERY_METRIC => {
+                params.metric = TORCH_METRICS
+                    .iter()
+                    .find(|(id, _)| *id == value)
+                    .map(|(id, _)| id.to_string());
+            }
+            QUERY_LIMIT => {
+                params.limit = value.parse().ok().filter(|l| LIMIT_RANGE.contains(l));
+            }
+            QUERY_STEPS => {
+                params.steps = value.parse().ok().filter(|s| STEPS_RANGE.contains(s));
+            }
+            _ => {}
+        }
+    }
+    params
+}
+
+/// Encoded query of the named settings.
+pub fn profiling_params_to_search(params: &ProfilingUrlParams) -> String {
+    let mut parts = Vec::new();
+    if let Some(metric) = &params.metric {
+        parts.push(format!("{QUERY_METRIC}={}", urlencoding::encode(metric)));
+    }
+    if let Some(limit) = params.limit {
+        parts.push(format!("{QUERY_LIMIT}={limit}"));
+    }
+    if let Some(steps) = params.steps {
+        parts.push(format!("{QUERY_STEPS}={steps}"));
+    }
+    parts.join("&")
+}
+
+/// Current settings of `view` (a canonical view id); other views' settings
+/// stay out of its URL.
+pub fn profiling_params_for_view(view: &str) -> ProfilingUrlParams {
+    match view {
+        "torch" => ProfilingUrlParams {
+            metric: Some(PROFILING_TORCH_METRIC.read().clone()),
+            ..Default::default()
+        },
+        "trace" | "combined" => ProfilingUrlParams {
+            limit: Some(*PROFILING_CHROME_LIMIT.read()),
+            ..Default::default()
+        },
+        "pytorch" => ProfilingUrlParams {
+            steps: Some(*PROFILING_PYTORCH_STEPS.read()),
+            ..Default::default()
+        },
+        _ => ProfilingUrlParams::default(),
+    }
+}
+
+/// Copy valid settings from the current URL into the profiling signals.
+pub fn apply_profiling_params_from_url() {
+    let params = parse_profiling_params(&current_url_search());
+    if let Some(metric) = params.metric {
+        *PROFILING_TORCH_METRIC.write() = metric;
+    }
+    if let Some(limit) = params.limit {
+        *PROFILING_CHROME_LIMIT.write() = limit;
+    }
+    if let Some(steps) = params.steps {
+        *PROFILING_PYTORCH_STEPS.write() = steps;
+    }
+}
+
+/// Applies the URL once on mount, then mirrors `view`'s settings into it.
+#[component]
+pub fn ProfilingUrlSync(view: String) -> Element {
+    let applied = use_hook(|| Rc::new(Cell::new(false)));
+    use_effect(move || {
+        if !applied.replace(true) {
+            apply_profiling_params_from_url();
+        }
+        let query = profiling_params_to_search(&profiling_params_for_view(&view));
+        replace_url_query(PROFILING_QUERY_KEYS, &query);
+    });
+    rsx! {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_or_missing_params_are_ignored() {
+        let params = parse_profiling_params("?metric=peak_mb&limit=2000&steps=7&pid=3");
+        assert_eq!(
+            params,
+            ProfilingUrlParams {
+                metric: Some("peak_mb".to_string()),
+                limit: Some(2000),
+                steps: Some(7),
+            }
+        );
+        assert_eq!(
+            profiling_params_to_search(&params),
+            "metric=peak_mb&limit=2000&steps=7"
+        );
+        let params = parse_profiling_params("?metric=bogus&limit=99999&steps=-1");
+        assert_eq!(params, ProfilingUrlParams::default());
+        assert_eq!(parse_profiling_params(""), ProfilingUrlParams::default());
+        assert_eq!(profiling_params_to_search(&params), "");
+    }
+}
@@ -0,0 +1,152 @@
+//! SQL editor history and saved queries, persisted in browser localStorage.
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const STORAGE_KEY: &str = "probing_sql_history";
+/// Executed statements kept, newest first.
+pub const SQL_HISTORY_CAP: usize = 50;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SqlHistoryEntry {
+    pub sql: String,
+    /// Last run, ms since epoch.
+    pub at_ms: f64,
+    pub ok: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub sql: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SqlLibrary {
+    #[serde(default)]
+    pub history: Vec<SqlHistoryEntry>,
+    #[serde(default)]
+    pub saved: Vec<SavedQuery>,
+}
+
+impl SqlLibrary {
+    /// Put a run of `sql` first; an earlier run of the same statement
+    /// (ignoring surrounding whitespace) is replaced, and at most `cap` are kept.
+    pub fn record(&mut self, sql: &str, at_ms: f64, ok: bool, cap: usize) {
+        let sql = sql.trim();
+        if sql.is_empty() {
+            return;
+        }
+        self.history.retain(|e| e.sql != sql);
+        self.history.insert(
+            0,
+            SqlHistoryEntry {
+                sql: sql.to_string(),
+                at_ms,
+                ok,
+            },
+        );
+        self.history.truncate(cap);
+    }
+
+    /// Save `sql` as `name`, replacing a saved query of that name.
+    pub fn save(&mut self, name: &str, sql: &str) {
+        let (name, sql) = (name.trim(), sql.trim());
+        if name.is_empty() || sql.is_empty() {
+            return;
+        }
+        match self.saved.iter_mut().find(|q| q.name == name) {
+            Some(query) => query.sql = sql.to_string(),
+            None => self.saved.push(SavedQuery {
+                name: name.to_string(),
+                sql: sql.to_string(),
+            }),
+        }
+    }
+
+    pub fn forget(&mut self, name: &str) {
+        self.saved.retain(|q| q.name != name);
+    }
+}
+
+pub static SQL_LIBRARY: GlobalSignal<SqlLibrary> = Signal::global(load_sql_library);
+/// Editor text, kept across navigation within the app.
+pub static SQL_DRAFT: GlobalSignal<String> = Signal::global(String::new);
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+fn load_sql_library() -> SqlLibrary {
+    storage()
+        .and_then(|s| s.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn update_sql_library(change: impl FnOnce(&mut SqlLibrary)) {
+    let mut library = SQL_LIBRARY.write();
+    change(&mut library);
+    if let (Some(storage), Ok(raw)) = (storage(), serde_json::to_string(&*library)) {
+        let _ = storage.set_item(STORAGE_KEY, &raw);
+    }
+}
+
+pub fn record_sql_run(sql: &str, ok: bool) {
+    let now = js_sys::Date::now();
+    update_sql_library(|l| l.record(sql, now, ok, SQL_HISTORY_CAP));
+}
+
+pub fn save_sql_query(name: &str, sql: &str) {
+    update_sql_library(|l| l.save(name, sql));
+}
+
+pub fn forget_sql_query(name: &str) {
+    update_sql_library(|l| l.forget(name));
+}
+
+pub fn clear_sql_history() {
+    update_sql_library(|l| l.history.clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_deduplicated_newest_first_and_capped() {
+        let mut library = SqlLibrary::default();
+        library.record("SELECT 1", 1.0, true, 3);
+        library.record("SELECT 2", 2.0, false, 3);
+        library.record("  SELECT 1\n", 3.0, false, 3);
+        library.record("   ", 4.0, true, 3);
+        let runs: Vec<_> = library
+            .history
+            .iter()
+            .map(|e| (e.sql.as_str(), e.at_ms, e.ok))
+            .collect();
+        assert_eq!(runs, [("SELECT 1", 3.0, false), ("SELECT 2", 2.0, false)]);
+
+        library.record("SELECT 3", 5.0, true, 3);
+        library.record("SELECT 4", 6.0, true, 3);
+        let sqls: Vec<_> = library.history.iter().map(|e| e.sql.as_str()).collect();
+        assert_eq!(sqls, ["SELECT 4", "SELECT 3", "SELECT 1"]);
+    }
+
+    #[test]
+    fn saved_queries_are_replaced_by_name() {
+        let mut library = SqlLibrary::default();
+        library.save("steps", "SELECT * FROM python.step");
+        library.save(" steps ", "SELECT count(*) FROM python.step");
+        library.save("", "SELECT 1");
+        library.save("other", "SELECT 2");
+        assert_eq!(library.saved.len(), 2);
+        assert_eq!(library.saved[0].sql, "SELECT count(*) FROM python.step");
+        library.forget("steps");
+        assert_eq!(library.saved[0].name, "other");
+
+        // Older stored data without `saved` still loads.
+        let old: SqlLibrary = serde_json::from_str(r#"{"history": []}"#).unwrap();
+        assert!(old.saved.is_empty());
+    }
+}
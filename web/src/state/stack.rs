@@ -14,6 +14,8 @@ pub struct StackSnapshot {
 }
 
 pub static STACK_MODE: GlobalSignal<String> = Signal::global(|| String::from("mixed"));
+/// Interleave native frames in the per-thread view (may signal each thread).
+pub static STACK_NATIVE: GlobalSignal<bool> = Signal::global(|| false);
 pub static STACK_REFRESH: GlobalSignal<u32> = Signal::global(|| 0);
 pub static STACK_DIST_CLUSTER: GlobalSignal<bool> = Signal::global(|| true);
 pub static STACK_DIST_RELOAD: GlobalSignal<i32> = Signal::global(|| 0);
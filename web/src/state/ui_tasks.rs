@@ -13,6 +13,7 @@ pub enum UiTaskKind {
     Agent,
     Snapshot,
     Skill,
+    Eval,
     #[allow(dead_code)] // reserved for Command Panel / SQL tasks
     Query,
 }
@@ -23,6 +24,7 @@ impl UiTaskKind {
             UiTaskKind::Agent => "Agent",
             UiTaskKind::Snapshot => "Snapshot",
             UiTaskKind::Skill => "Skill",
+            UiTaskKind::Eval => "Eval",
             UiTaskKind::Query => "Query",
         }
     }
@@ -0,0 +1,96 @@
+//! The page URL's query string, shared by the state mirrored into it
+//! (investigation context, profiling view settings). Each writer owns a set
+//! of keys and keeps everyone else's pairs as they are.
+
+use wasm_bindgen::JsValue;
+
+pub fn current_url_search() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().search().ok())
+        .unwrap_or_default()
+}
+
+/// Decoded `key=value` pairs of `search` (leading `?` optional).
+pub fn query_pairs(search: &str) -> Vec<(String, String)> {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, raw)| {
+            let value = urlencoding::decode(raw)
+                .map(|cow| cow.into_owned())
+                .unwrap_or_else(|_| raw.to_string());
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+/// `search` with the pairs of `owned` keys replaced by `query` (already
+/// encoded, may be empty); other pairs keep their order and come first.
+pub fn merge_query(search: &str, owned: &[&str], query: &str) -> String {
+    let mut parts: Vec<&str> = search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map_or(*pair, |(k, _)| k);
+            !owned.contains(&key)
+        })
+        .collect();
+    if !query.is_empty() {
+        parts.push(query);
+    }
+    parts.join("&")
+}
+
+/// Rewrite the `owned` keys of the current URL with `history.replaceState`
+/// (no navigation, no history entry).
+pub fn replace_url_query(owned: &[&str], query: &str) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let location = window.location();
+    let Ok(pathname) = location.pathname() else {
+        return;
+    };
+    let hash = location.hash().unwrap_or_default();
+    let search = location.search().unwrap_or_default();
+    let merged = merge_query(&search, owned, query);
+    let new_url = if merged.is_empty() {
+        format!("{pathname}{hash}")
+    } else {
+        format!("{pathname}?{merged}{hash}")
+    };
+    if new_url == format!("{pathname}{search}{hash}") {
+        return;
+    }
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&new_url));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_pairs_of_other_owners() {
+        assert_eq!(
+            merge_query(
+                "?pid=1&metric=duration&chip=a",
+                &["metric"],
+                "metric=peak_mb"
+            ),
+            "pid=1&chip=a&metric=peak_mb"
+        );
+        assert_eq!(
+            merge_query("?pid=1&limit=500", &["metric", "limit"], ""),
+            "pid=1"
+        );
+        assert_eq!(merge_query("", &["pid"], "pid=2"), "pid=2");
+        assert_eq!(
+            query_pairs("?span=a%20b&x"),
+            [("span".to_string(), "a b".to_string())]
+        );
+    }
+}
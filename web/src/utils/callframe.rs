@@ -1,6 +1,6 @@
 //! Call-frame classification and display helpers for the Stacks page.
 
-use probing_proto::prelude::CallFrame;
+use probing_proto::prelude::{CallFrame, ThreadStack};
 
 /// Logical frame kind for UI styling and filtering.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -173,6 +173,44 @@ pub fn matches_mode(frame: &CallFrame, mode: &str) -> bool {
     }
 }
 
+/// `name (tid)`, or `Thread tid` for unnamed threads.
+pub fn thread_heading(thread: &ThreadStack) -> String {
+    match thread.name.as_deref().filter(|n| !n.is_empty()) {
+        Some(name) => format!("{name} ({})", thread.tid),
+        None => format!("Thread {}", thread.tid),
+    }
+}
+
+/// Plain-text dump of `threads` with frames filtered by `mode`, for pasting
+/// into issues and chats.
+pub fn threads_as_text(threads: &[ThreadStack], mode: &str) -> String {
+    let mut out = String::new();
+    for thread in threads {
+        out.push_str(&thread_heading(thread));
+        if thread.main {
+            out.push_str(" [main]");
+        }
+        out.push('\n');
+        if let Some(err) = &thread.error {
+            out.push_str(&format!("    (stack unavailable: {err})\n"));
+        }
+        for frame in thread.frames.iter().filter(|f| matches_mode(f, mode)) {
+            let tag = match classify_frame(frame) {
+                FrameKind::Python => "py",
+                FrameKind::Rust => "rust",
+                FrameKind::Cpp => "native",
+            };
+            out.push_str(&format!("    [{tag}] {}", frame_title(frame)));
+            if let Some((file, lineno)) = frame_location(frame) {
+                out.push_str(&format!(" ({file}:{lineno})"));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,4 +249,42 @@ mod tests {
         };
         assert_eq!(classify_frame(&frame), FrameKind::Python);
     }
+
+    #[test]
+    fn threads_dump_as_text() {
+        let threads = vec![
+            ThreadStack {
+                tid: 7,
+                name: Some("MainThread".into()),
+                main: true,
+                frames: vec![
+                    CallFrame::PyFrame {
+                        file: "train.py".into(),
+                        func: "step".into(),
+                        lineno: 42,
+                        locals: Default::default(),
+                    },
+                    CallFrame::CFrame {
+                        ip: "0x1".into(),
+                        file: String::new(),
+                        func: "epoll_wait".into(),
+                        lineno: 0,
+                        lang: None,
+                    },
+                ],
+                ..Default::default()
+            },
+            ThreadStack {
+                tid: 9,
+                error: Some("timed out".into()),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            threads_as_text(&threads, "mixed"),
+            "MainThread (7) [main]\n    [py] step (train.py:42)\n    [native] epoll_wait\n\n\
+             Thread 9\n    (stack unavailable: timed out)"
+        );
+        assert!(!threads_as_text(&threads, "py").contains("epoll_wait"));
+    }
 }
@@ -10,6 +10,11 @@ pub enum AppError {
 
     #[error("API error: {0}")]
     Api(String),
+
+    /// Non-success HTTP response; `message` is the server's `error` field or
+    /// the status reason.
+    #[error("HTTP {status}: {message}")]
+    Http { status: u16, message: String },
     #[error("Cancelled")]
     Cancelled,
 }
@@ -19,6 +24,14 @@ impl AppError {
         matches!(self, AppError::Cancelled)
     }
 
+    /// Status code of a failed HTTP response.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            AppError::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
     /// User-facing message for display in the UI (enables future i18n).
     pub fn display_message(&self) -> String {
         self.to_string()
@@ -0,0 +1,172 @@
+//! Query results as CSV or JSON records, and browser file downloads.
+//!
+//! Both formats walk the `DataFrame` row by row over the longest column;
+//! missing and `Nil` cells are empty in CSV and `null` in JSON.
+
+use probing_proto::prelude::{DataFrame, Ele};
+use serde_json::{Map, Number, Value};
+
+/// `field` as one CSV field (RFC 4180): quoted when it holds a comma, a
+/// quote or a line break, with quotes doubled.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_text(ele: &Ele) -> String {
+    match ele {
+        Ele::Nil => String::new(),
+        Ele::BOOL(x) => x.to_string(),
+        Ele::I32(x) => x.to_string(),
+        Ele::I64(x) => x.to_string(),
+        Ele::F32(x) => x.to_string(),
+        Ele::F64(x) => x.to_string(),
+        Ele::Text(x) | Ele::Url(x) => x.clone(),
+        Ele::DataTime(x) => x.to_string(),
+        Ele::DateTime(_) => ele.to_string(),
+    }
+}
+
+fn json_value(ele: Ele) -> Value {
+    match ele {
+        Ele::Nil => Value::Null,
+        Ele::BOOL(x) => Value::Bool(x),
+        Ele::I32(x) => Value::from(x),
+        Ele::I64(x) => Value::from(x),
+        // NaN and infinities have no JSON number.
+        Ele::F32(x) => Number::from_f64(f64::from(x)).map_or(Value::Null, Value::Number),
+        Ele::F64(x) => Number::from_f64(x).map_or(Value::Null, Value::Number),
+        Ele::Text(x) | Ele::Url(x) => Value::String(x),
+        Ele::DataTime(x) => Value::from(x),
+        Ele::DateTime(x) => Value::from(x),
+    }
+}
+
+/// Header line, then one line per row, each ending in `\r\n`.
+pub fn dataframe_to_csv(df: &DataFrame) -> String {
+    let mut out = String::new();
+    let mut push_line = |fields: Vec<String>| {
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    };
+    push_line(df.names.iter().map(|n| csv_field(n)).collect());
+    for i in 0..df.row_count() {
+        push_line(
+            df.cols
+                .iter()
+                .map(|col| csv_field(&csv_text(&col.get(i))))
+                .collect(),
+        );
+    }
+    out
+}
+
+/// A JSON array with one `{column: value}` object per row.
+pub fn dataframe_to_json_records(df: &DataFrame) -> String {
+    let records: Vec<Value> = (0..df.row_count())
+        .map(|i| {
+            let row: Map<String, Value> = df
+                .names
+                .iter()
+                .zip(&df.cols)
+                .map(|(name, col)| (name.clone(), json_value(col.get(i))))
+                .collect();
+            Value::Object(row)
+        })
+        .collect();
+    Value::Array(records).to_string()
+}
+
+/// Offer `body` to the browser as a file download of type `mime`.
+pub fn download_file(filename: &str, mime: &str, body: &str) -> Result<(), String> {
+    use js_sys::Array;
+    use wasm_bindgen::JsCast;
+    use web_sys::{Blob, BlobPropertyBag, HtmlElement, Url};
+
+    let window = web_sys::window().ok_or("No browser window")?;
+    let document = window.document().ok_or("No document")?;
+
+    let parts = Array::new();
+    parts.push(&wasm_bindgen::JsValue::from_str(body));
+    let bag = BlobPropertyBag::new();
+    bag.set_type(mime);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &bag)
+        .map_err(|_| "Failed to create download blob")?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(|_| "Failed to create object URL")?;
+
+    let anchor = document
+        .create_element("a")
+        .map_err(|_| "Failed to create download link")?;
+    let result = anchor
+        .set_attribute("href", &url)
+        .and_then(|_| anchor.set_attribute("download", filename))
+        .map_err(|_| "Failed to create download link".to_string())
+        .and_then(|_| {
+            anchor
+                .dyn_into::<HtmlElement>()
+                .map(|a| a.click())
+                .map_err(|_| "Failed to create download link".to_string())
+        });
+    // Revoking synchronously can cancel the download in some browsers.
+    gloo_timers::callback::Timeout::new(1_000, move || {
+        let _ = Url::revoke_object_url(&url);
+    })
+    .forget();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+        assert_eq!(csv_field("it's fine"), "it's fine");
+    }
+
+    fn frame() -> DataFrame {
+        DataFrame::new(
+            vec![
+                "rank".to_string(),
+                "name, full".to_string(),
+                "loss".to_string(),
+            ],
+            vec![
+                Seq::SeqI64(vec![0, 1]),
+                Seq::SeqText(vec!["a \"b\"".into(), "x\ny".into()]),
+                Seq::SeqF64(vec![0.5]),
+            ],
+        )
+    }
+
+    #[test]
+    fn csv_has_header_escaped_fields_and_empty_nils() {
+        assert_eq!(
+            dataframe_to_csv(&frame()),
+            "rank,\"name, full\",loss\r\n0,\"a \"\"b\"\"\",0.5\r\n1,\"x\ny\",\r\n"
+        );
+    }
+
+    #[test]
+    fn json_records_use_null_for_nil() {
+        let records: Value = serde_json::from_str(&dataframe_to_json_records(&frame())).unwrap();
+        assert_eq!(
+            records,
+            serde_json::json!([
+                {"rank": 0, "name, full": "a \"b\"", "loss": 0.5},
+                {"rank": 1, "name, full": "x\ny", "loss": null},
+            ])
+        );
+        assert_eq!(json_value(Ele::F64(f64::NAN)), Value::Null);
+    }
+}
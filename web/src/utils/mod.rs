@@ -1,6 +1,9 @@
 pub mod base_path;
 pub mod callframe;
 pub mod error;
+pub mod export;
 pub mod markdown;
+pub mod report;
 pub mod source_ref;
+pub mod sql_complete;
 pub mod tracing_viewer;
@@ -0,0 +1,358 @@
+//! Static report export for post-mortems (Dashboard, Spans).
+//!
+//! [`Report::to_html`] renders one self-contained document: inline CSS, charts
+//! as inline SVG, tables as plain HTML, no scripts and no external assets, so
+//! the file opens (and prints to PDF) offline. Interactive-only widgets are
+//! exported as [`ReportBlock::Placeholder`] with the settings needed to
+//! reproduce them in the live UI.
+
+use std::fmt::Write as _;
+
+/// Version of the probing release this UI bundle was built from.
+pub const PROBING_VERSION: &str = env!("PROBING_VERSION");
+
+/// Header facts printed at the top of every report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportMeta {
+    pub title: String,
+    /// `host:port` of the probed process's server.
+    pub target: String,
+    /// Human-readable data window, when the page knows it.
+    pub time_range: Option<String>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ReportMeta {
+    /// Metadata for a report generated now from the current browser tab.
+    pub fn now(title: impl Into<String>) -> Self {
+        let target = web_sys::window()
+            .and_then(|w| w.location().host().ok())
+            .unwrap_or_default();
+        Self {
+            title: title.into(),
+            target,
+            time_range: None,
+            generated_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn with_time_range(mut self, range: Option<String>) -> Self {
+        self.time_range = range;
+        self
+    }
+}
+
+/// One section of a report.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportBlock {
+    /// Pre-rendered standalone `<svg>` markup.
+    Chart {
+        title: String,
+        svg: String,
+        caption: Option<String>,
+    },
+    Table {
+        title: String,
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    KeyValues {
+        title: String,
+        items: Vec<(String, String)>,
+    },
+    /// Widget that only works in the live UI; `config` records how to reopen it.
+    Placeholder {
+        title: String,
+        widget: String,
+        config: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub meta: ReportMeta,
+    pub blocks: Vec<ReportBlock>,
+}
+
+impl Report {
+    pub fn new(meta: ReportMeta) -> Self {
+        Self {
+            meta,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, block: ReportBlock) {
+        self.blocks.push(block);
+    }
+
+    /// `probing-<title>-<utc timestamp>.html`
+    pub fn filename(&self) -> String {
+        let slug: String = self
+            .meta
+            .title
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let slug = slug.trim_matches('-');
+        format!(
+            "probing-{}-{}.html",
+            if slug.is_empty() { "report" } else { slug },
+            self.meta.generated_at.format("%Y%m%d-%H%M%S")
+        )
+    }
+
+    pub fn to_html(&self) -> String {
+        let meta = &self.meta;
+        let mut out = String::with_capacity(16 * 1024);
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{REPORT_CSS}</style>\n</head>\n<body>\n\
+             <h1>{title}</h1>\n<table class=\"meta\">",
+            title = escape_html(&meta.title),
+        );
+        let mut meta_row = |k: &str, v: &str| {
+            let _ = writeln!(out, "<tr><th>{k}</th><td>{}</td></tr>", escape_html(v));
+        };
+        meta_row("Target", &meta.target);
+        if let Some(range) = &meta.time_range {
+            meta_row("Time range", range);
+        }
+        meta_row("Probing version", PROBING_VERSION);
+        meta_row(
+            "Generated",
+            &meta
+                .generated_at
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+        );
+        out.push_str("</table>\n");
+
+        for block in &self.blocks {
+            write_block(&mut out, block);
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+fn write_block(out: &mut String, block: &ReportBlock) {
+    match block {
+        ReportBlock::Chart {
+            title,
+            svg,
+            caption,
+        } => {
+            let _ = writeln!(out, "<section><h2>{}</h2>\n{svg}", escape_html(title));
+            if let Some(caption) = caption {
+                let _ = writeln!(out, "<p class=\"caption\">{}</p>", escape_html(caption));
+            }
+            out.push_str("</section>\n");
+        }
+        ReportBlock::Table {
+            title,
+            columns,
+            rows,
+        } => {
+            let _ = writeln!(out, "<section><h2>{}</h2>", escape_html(title));
+            if rows.is_empty() {
+                out.push_str("<p class=\"caption\">No rows.</p>\n</section>\n");
+                return;
+            }
+            out.push_str("<table><thead><tr>");
+            for col in columns {
+                let _ = write!(out, "<th>{}</th>", escape_html(col));
+            }
+            out.push_str("</tr></thead>\n<tbody>\n");
+            for row in rows {
+                out.push_str("<tr>");
+                for cell in row {
+                    let _ = write!(out, "<td>{}</td>", escape_html(cell));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</tbody></table>\n</section>\n");
+        }
+        ReportBlock::KeyValues { title, items } => {
+            let _ = writeln!(
+                out,
+                "<section><h2>{}</h2>\n<table class=\"kv\">",
+                escape_html(title)
+            );
+            for (k, v) in items {
+                let _ = writeln!(
+                    out,
+                    "<tr><th>{}</th><td>{}</td></tr>",
+                    escape_html(k),
+                    escape_html(v)
+                );
+            }
+            out.push_str("</table>\n</section>\n");
+        }
+        ReportBlock::Placeholder {
+            title,
+            widget,
+            config,
+        } => {
+            let _ = writeln!(
+                out,
+                "<section class=\"placeholder\"><h2>{}</h2>\n\
+                 <p>Interactive widget <code>{}</code> is not exported; reopen it in the live UI with:</p>",
+                escape_html(title),
+                escape_html(widget)
+            );
+            if config.is_empty() {
+                out.push_str("<p class=\"caption\">Default settings.</p>\n");
+            } else {
+                out.push_str("<table class=\"kv\">\n");
+                for (k, v) in config {
+                    let _ = writeln!(
+                        out,
+                        "<tr><th>{}</th><td><code>{}</code></td></tr>",
+                        escape_html(k),
+                        escape_html(v)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+            out.push_str("</section>\n");
+        }
+    }
+}
+
+const REPORT_CSS: &str = "\
+body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;\
+color:#111827;margin:24px auto;max-width:960px;padding:0 16px;font-size:13px}\
+h1{font-size:20px;margin:0 0 12px}h2{font-size:15px;margin:0 0 8px}\
+section{margin:20px 0;page-break-inside:avoid}\
+table{border-collapse:collapse;width:100%}\
+th,td{border:1px solid #e5e7eb;padding:4px 8px;text-align:left;vertical-align:top}\
+thead th{background:#f3f4f6}\
+table.meta,table.kv{width:auto}table.meta th,table.kv th{background:#f9fafb;font-weight:600}\
+td{font-family:ui-monospace,SFMono-Regular,Menlo,monospace;word-break:break-all}\
+svg{width:100%;height:auto;border:1px solid #e5e7eb;border-radius:4px}\
+.caption{color:#6b7280;font-size:12px;margin:4px 0}\
+.placeholder{border:1px dashed #9ca3af;border-radius:6px;padding:12px;background:#f9fafb}\
+@media print{body{margin:0;max-width:none}}";
+
+/// Escape text for HTML element content and quoted attribute values.
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Offscreen render of a two-segment stacked bar chart (one bar per sample,
+/// `bottom` stacked under `top`), matching the Dashboard sparklines.
+pub fn stacked_bars_svg(bars: &[(f32, f32)], colors: (&str, &str), height: f64) -> String {
+    let width = 640.0;
+    let n = bars.len().max(1) as f64;
+    let slot = width / n;
+    let bar_w = (slot - 1.0).max(1.0);
+    let max = bars
+        .iter()
+        .map(|(bottom, top)| bottom + top)
+        .fold(0.0f32, f32::max)
+        .max(1.0) as f64;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+         preserveAspectRatio=\"none\">"
+    );
+    for (i, (bottom, top)) in bars.iter().enumerate() {
+        let x = i as f64 * slot;
+        let h_bottom = (*bottom as f64 / max * height).max(0.0);
+        let h_top = (*top as f64 / max * height).max(0.0);
+        let _ = write!(
+            out,
+            "<rect x=\"{x:.1}\" y=\"{:.1}\" width=\"{bar_w:.1}\" height=\"{h_bottom:.1}\" fill=\"{}\"/>\
+             <rect x=\"{x:.1}\" y=\"{:.1}\" width=\"{bar_w:.1}\" height=\"{h_top:.1}\" fill=\"{}\"/>",
+            height - h_bottom,
+            colors.0,
+            height - h_bottom - h_top,
+            colors.1,
+        );
+    }
+    out.push_str("</svg>");
+    out
+}
+
+/// Offer `html` to the browser as a file download.
+pub fn download_html(filename: &str, html: &str) -> Result<(), String> {
+    crate::utils::export::download_file(filename, "text/html", html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Report {
+        let mut report = Report::new(ReportMeta {
+            title: "Dashboard <prod>".to_string(),
+            target: "10.0.0.1:9700".to_string(),
+            time_range: Some("12:00:00 – 12:01:00 UTC".to_string()),
+            generated_at: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        });
+        report.push(ReportBlock::Chart {
+            title: "CPU".to_string(),
+            svg: stacked_bars_svg(&[(1.0, 2.0), (0.5, 0.5)], ("#f59e0b", "#3b82f6"), 80.0),
+            caption: None,
+        });
+        report.push(ReportBlock::Table {
+            title: "Threads".to_string(),
+            columns: vec!["tid".to_string(), "name".to_string()],
+            rows: vec![vec!["7".to_string(), "<script>".to_string()]],
+        });
+        report.push(ReportBlock::Placeholder {
+            title: "Compare windows".to_string(),
+            widget: "TraceCompareCard".to_string(),
+            config: vec![("baseline".to_string(), "1h".to_string())],
+        });
+        report
+    }
+
+    #[test]
+    fn html_is_escaped_and_self_contained() {
+        let html = sample().to_html();
+        assert!(html.contains("<title>Dashboard &lt;prod&gt;</title>"));
+        assert!(html.contains("<td>&lt;script&gt;</td>"));
+        assert!(html.contains("10.0.0.1:9700"));
+        assert!(html.contains(PROBING_VERSION));
+        assert!(html.contains("<svg xmlns="));
+        assert!(html.contains("TraceCompareCard"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+    }
+
+    #[test]
+    fn filename_is_slugged_and_timestamped() {
+        assert_eq!(
+            sample().filename(),
+            "probing-dashboard--prod-20231114-221320.html"
+        );
+    }
+
+    #[test]
+    fn stacked_bars_scale_to_tallest_sample() {
+        let svg = stacked_bars_svg(&[(1.0, 3.0)], ("a", "b"), 100.0);
+        assert!(svg.contains("y=\"75.0\" width=\"639.0\" height=\"25.0\" fill=\"a\""));
+        assert!(svg.contains("y=\"0.0\" width=\"639.0\" height=\"75.0\" fill=\"b\""));
+    }
+}
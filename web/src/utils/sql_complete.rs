@@ -0,0 +1,267 @@
+//! Schema-aware completion for the SQL editor.
+//!
+//! A token-prefix matcher, not a parser: the word under the cursor is
+//! completed from table names after `FROM` / `JOIN` and from the columns of
+//! the statement's tables after `SELECT`, `WHERE`, `BY`, `ON`, `AND`, `OR`
+//! and `HAVING`, whichever keyword comes last before the word.
+
+use std::collections::BTreeMap;
+
+use probing_proto::prelude::{DataFrame, Ele};
+
+/// Tables (`schema.table`, `global.schema.table` in cluster mode) and their
+/// columns in table order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SqlSchema {
+    pub tables: Vec<String>,
+    pub columns: BTreeMap<String, Vec<String>>,
+}
+
+/// `information_schema.columns` rows for the tables offered in the editor.
+pub fn schema_sql(global_mode: bool, hidden_schemas: &[&str]) -> String {
+    let catalog = if global_mode { "global" } else { "probe" };
+    let hidden = hidden_schemas
+        .iter()
+        .map(|s| format!("'{s}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "SELECT table_schema, table_name, column_name FROM information_schema.columns \
+         WHERE table_catalog = '{catalog}' AND table_schema NOT IN ({hidden}) \
+         ORDER BY table_schema, table_name, ordinal_position"
+    )
+}
+
+impl SqlSchema {
+    /// From [`schema_sql`] rows.
+    pub fn from_columns(df: &DataFrame, global_mode: bool) -> Self {
+        let text = |col: usize, row: usize| match df.cols.get(col).map(|c| c.get(row)) {
+            Some(Ele::Text(s)) => Some(s),
+            _ => None,
+        };
+        let mut schema = SqlSchema::default();
+        for row in 0..df.row_count() {
+            let (Some(db), Some(table), Some(column)) = (text(0, row), text(1, row), text(2, row))
+            else {
+                continue;
+            };
+            let name = if global_mode {
+                format!("global.{db}.{table}")
+            } else {
+                format!("{db}.{table}")
+            };
+            if !schema.columns.contains_key(&name) {
+                schema.tables.push(name.clone());
+            }
+            schema.columns.entry(name).or_default().push(column);
+        }
+        schema
+    }
+}
+
+/// Replace characters `start..end` of the statement with one of `matches`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlCompletion {
+    pub start: usize,
+    pub end: usize,
+    pub matches: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Table,
+    Column,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !is_word_char(c))
+        .filter(|w| !w.is_empty())
+}
+
+/// Keywords that end a completable clause without starting one.
+const OTHER_KEYWORDS: &[&str] = &["LIMIT", "OFFSET", "AS", "UNION", "GROUP", "ORDER"];
+
+fn expect_after(keyword: &str) -> Option<Expect> {
+    match keyword.to_ascii_uppercase().as_str() {
+        "FROM" | "JOIN" => Some(Expect::Table),
+        "SELECT" | "WHERE" | "BY" | "ON" | "AND" | "OR" | "HAVING" => Some(Expect::Column),
+        _ => None,
+    }
+}
+
+/// What the nearest keyword before the cursor asks for.
+fn expectation(before: &str) -> Option<Expect> {
+    let keyword = words(before)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .find(|w| {
+            expect_after(w).is_some() || OTHER_KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(w))
+        })?;
+    expect_after(keyword)
+}
+
+/// Schema tables named after `FROM` / `JOIN` anywhere in `sql`, matched
+/// case-insensitively on the full name or, when unambiguous, the bare table.
+fn referenced_tables<'a>(sql: &str, schema: &'a SqlSchema) -> Vec<&'a str> {
+    let tokens: Vec<&str> = words(sql).collect();
+    let mut found: Vec<&str> = Vec::new();
+    for pair in tokens.windows(2) {
+        if !matches!(expect_after(pair[0]), Some(Expect::Table)) {
+            continue;
+        }
+        let wanted = pair[1].to_lowercase();
+        let full = schema.tables.iter().find(|t| t.to_lowercase() == wanted);
+        let bare: Vec<_> = schema
+            .tables
+            .iter()
+            .filter(|t| {
+                t.rsplit('.')
+                    .next()
+                    .is_some_and(|n| n.to_lowercase() == wanted)
+            })
+            .collect();
+        let table = full.or_else(|| (bare.len() == 1).then(|| bare[0]));
+        if let Some(table) = table {
+            if !found.contains(&table.as_str()) {
+                found.push(table);
+            }
+        }
+    }
+    found
+}
+
+/// Completions for the word ending at `cursor` (characters), at most `limit`.
+pub fn complete_sql(
+    sql: &str,
+    cursor: usize,
+    schema: &SqlSchema,
+    limit: usize,
+) -> Option<SqlCompletion> {
+    let chars: Vec<char> = sql.chars().collect();
+    let end = cursor.min(chars.len());
+    let mut start = end;
+    while start > 0 && is_word_char(chars[start - 1]) {
+        start -= 1;
+    }
+    let before: String = chars[..start].iter().collect();
+    let word: String = chars[start..end].iter().collect();
+    let expect = expectation(&before)?;
+    let mut matches: Vec<String> = match expect {
+        Expect::Table => {
+            let typed = word.to_lowercase();
+            schema
+                .tables
+                .iter()
+                .filter(|t| {
+                    let t = t.to_lowercase();
+                    t.starts_with(&typed)
+                        || t.rsplit('.').next().is_some_and(|n| n.starts_with(&typed))
+                })
+                .cloned()
+                .collect()
+        }
+        Expect::Column => {
+            // `alias.col` completes the part after the last dot.
+            if let Some(dot) = word.rfind('.') {
+                start += word[..=dot].chars().count();
+            }
+            let typed = word.rsplit('.').next().unwrap_or_default().to_lowercase();
+            let mut columns: Vec<String> = referenced_tables(sql, schema)
+                .into_iter()
+                .flat_map(|t| schema.columns.get(t).into_iter().flatten())
+                .filter(|c| c.to_lowercase().starts_with(&typed))
+                .cloned()
+                .collect();
+            columns.sort();
+            columns.dedup();
+            columns
+        }
+    };
+    let typed: String = chars[start..end].iter().collect();
+    matches.retain(|m| *m != typed);
+    matches.truncate(limit);
+    (!matches.is_empty()).then_some(SqlCompletion {
+        start,
+        end,
+        matches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probing_proto::prelude::Seq;
+
+    fn schema() -> SqlSchema {
+        let rows = [
+            ("python", "trace_event", "span_id"),
+            ("python", "trace_event", "name"),
+            ("python", "torch_trace", "module"),
+            ("python", "torch_trace", "name"),
+            ("taskstats", "threads", "tid"),
+        ];
+        let df = DataFrame::new(
+            ["table_schema", "table_name", "column_name"]
+                .map(String::from)
+                .to_vec(),
+            vec![
+                Seq::SeqText(rows.iter().map(|r| r.0.to_string()).collect()),
+                Seq::SeqText(rows.iter().map(|r| r.1.to_string()).collect()),
+                Seq::SeqText(rows.iter().map(|r| r.2.to_string()).collect()),
+            ],
+        );
+        SqlSchema::from_columns(&df, false)
+    }
+
+    fn matches(sql: &str) -> Vec<String> {
+        complete_sql(sql, sql.chars().count(), &schema(), 10)
+            .map(|c| c.matches)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn tables_after_from_match_full_or_bare_names() {
+        assert_eq!(schema().tables.len(), 3);
+        assert_eq!(
+            matches("SELECT * FROM py"),
+            ["python.trace_event", "python.torch_trace"]
+        );
+        assert_eq!(matches("select * from tor"), ["python.torch_trace"]);
+        let c = complete_sql("SELECT * FROM thr LIMIT 5", 17, &schema(), 10).unwrap();
+        assert_eq!((c.start, c.end), (14, 17));
+        assert_eq!(c.matches, ["taskstats.threads"]);
+        // An exact name needs no completion.
+        assert!(matches("SELECT * FROM taskstats.threads").is_empty());
+    }
+
+    #[test]
+    fn columns_come_from_the_statement_tables() {
+        let sql = "SELECT na FROM python.trace_event JOIN torch_trace ON ";
+        let c = complete_sql(sql, 9, &schema(), 10).unwrap();
+        assert_eq!((c.start, c.end), (7, 9));
+        assert_eq!(c.matches, ["name"]);
+        assert_eq!(
+            matches("SELECT * FROM python.trace_event WHERE t.s"),
+            ["span_id"]
+        );
+        let c = complete_sql(
+            "SELECT * FROM python.trace_event WHERE t.s",
+            42,
+            &schema(),
+            10,
+        );
+        assert_eq!(c.unwrap().start, 41);
+        assert_eq!(
+            matches("SELECT * FROM taskstats.threads ORDER BY "),
+            ["tid"]
+        );
+        // After LIMIT, or before any keyword, nothing is offered.
+        assert!(matches("SELECT * FROM python.trace_event LIMIT 1").is_empty());
+        assert!(matches("nam").is_empty());
+    }
+}
@@ -2,14 +2,26 @@
 
 //! Open trace JSON in Perfetto UI in a new browser tab/window.
 pub fn open_perfetto_window(trace_json: &str) -> Result<(), String> {
+    open_viewer_html(&get_tracing_viewer_html(trace_json))
+}
+
+/// Open a binary Perfetto trace served at `trace_url` (e.g.
+/// `chrome-tracing?format=proto`) in Perfetto UI.
+///
+/// The tab opens right away and fetches the trace itself, so the click that
+/// triggered it is not lost to the pop-up blocker while the export streams.
+pub fn open_perfetto_window_url(trace_url: &str) -> Result<(), String> {
+    open_viewer_html(&get_tracing_viewer_html_for_url(trace_url))
+}
+
+fn open_viewer_html(html: &str) -> Result<(), String> {
     use js_sys::Array;
     use web_sys::{Blob, BlobPropertyBag, Url};
 
     let window = web_sys::window().ok_or("No browser window")?;
-    let html = get_tracing_viewer_html(trace_json);
 
     let parts = Array::new();
-    parts.push(&wasm_bindgen::JsValue::from_str(&html));
+    parts.push(&wasm_bindgen::JsValue::from_str(html));
     let bag = BlobPropertyBag::new();
     bag.set_type("text/html");
     let blob = Blob::new_with_str_sequence_and_options(&parts, &bag)
@@ -24,14 +36,81 @@ pub fn open_perfetto_window(trace_json: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn escape_template_literal(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace('$', "\\$")
+}
+
+/// Remote Perfetto UI, used when the probing server has no bundled copy.
+const REMOTE_UI: &str = "https://ui.perfetto.dev/";
+
+/// Absolute URL of the Perfetto UI bundled with the probing server (see
+/// `PROBING_PERFETTO_UI_DIR`).
+#[cfg(target_arch = "wasm32")]
+fn local_ui_url() -> String {
+    web_sys::window()
+        .and_then(|w| w.location().origin().ok())
+        .map(|origin| {
+            format!(
+                "{origin}{}",
+                crate::utils::base_path::with_base("/perfetto/")
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// No server origin outside the browser: the remote UI only.
+#[cfg(not(target_arch = "wasm32"))]
+fn local_ui_url() -> String {
+    String::new()
+}
+
 /// Generate HTML page containing Chrome tracing viewer.
 /// Embeds trace JSON and loads Perfetto UI via postMessage API.
 pub fn get_tracing_viewer_html(trace_json: &str) -> String {
-    let escaped_json = trace_json
-        .replace('\\', "\\\\")
-        .replace('`', "\\`")
-        .replace('$', "\\$");
+    let load_buffer = format!(
+        "Promise.resolve(new TextEncoder().encode(JSON.stringify(JSON.parse(`{}`), null, 2)).buffer)",
+        escape_template_literal(trace_json)
+    );
+    viewer_html(
+        &load_buffer,
+        "trace.json",
+        "application/json",
+        &local_ui_url(),
+    )
+}
 
+/// Viewer page that fetches a binary Perfetto trace from `trace_url` and
+/// posts its bytes as-is (no JSON validation).
+pub fn get_tracing_viewer_html_for_url(trace_url: &str) -> String {
+    let load_buffer = format!(
+        r#"fetch(`{}`).then(function(r) {{
+                    if (r.ok && r.headers.get('content-type') !== 'application/json') {{
+                        return r.arrayBuffer();
+                    }}
+                    return r.text().then(function(body) {{
+                        let message = body || ('HTTP ' + r.status);
+                        try {{ message = JSON.parse(body).error || message; }} catch (_) {{}}
+                        throw new Error(message);
+                    }});
+                }})"#,
+        escape_template_literal(trace_url)
+    );
+    viewer_html(
+        &load_buffer,
+        "trace.perfetto-trace",
+        "application/octet-stream",
+        &local_ui_url(),
+    )
+}
+
+/// `load_buffer` is a JS expression evaluating to a promise of the trace
+/// bytes (`ArrayBuffer`) handed to Perfetto. `local_ui` (may be empty) is
+/// used instead of [`REMOTE_UI`] when its `index.html` answers, so traces
+/// open without internet access.
+fn viewer_html(load_buffer: &str, file_name: &str, mime: &str, local_ui: &str) -> String {
+    let local_ui = escape_template_literal(local_ui);
     format!(
         r#"
 <!DOCTYPE html>
@@ -67,138 +146,138 @@ pub fn get_tracing_viewer_html(trace_json: &str) -> String {
     <script>
         (function() {{
             try {{
-                const traceData = JSON.parse(`{escaped_json}`);
+                const traceBuffer = {load_buffer};
 
                 const iframe = document.getElementById('perfetto-iframe');
                 const loading = document.getElementById('loading');
 
-                const perfettoUrl = 'https://ui.perfetto.dev/#!/';
-                iframe.src = perfettoUrl;
+                const REMOTE_UI = '{REMOTE_UI}';
+                const LOCAL_UI = `{local_ui}`;
+                const MAX_PINGS = 10;
 
                 let loaded = false;
                 let errorShown = false;
+                const listeners = [];
 
-                const messageHandler = function(event) {{
-                    if (event.origin === 'https://ui.perfetto.dev') {{
-                        if (event.data) {{
-                            const dataStr = typeof event.data === 'string' ? event.data : JSON.stringify(event.data);
-                            if (dataStr.includes('error') || dataStr.includes('Failed')) {{
-                                console.error('Perfetto UI error:', event.data);
-                                if (!loaded && !errorShown) {{
-                                    errorShown = true;
-                                    showError('Perfetto UI reported an error. Please check the trace data format.');
-                                    window.removeEventListener('message', messageHandler);
-                                }}
-                            }} else if (dataStr.includes('loaded') || dataStr.includes('ready')) {{
-                                if (!loaded) {{
-                                    loaded = true;
-                                    loading.style.display = 'none';
-                                    iframe.style.display = 'block';
-                                    window.removeEventListener('message', messageHandler);
-                                }}
-                            }}
-                        }}
+                const listen = function(handler) {{
+                    listeners.push(handler);
+                    window.addEventListener('message', handler);
+                }};
+                const unlistenAll = function() {{
+                    listeners.splice(0).forEach(function(handler) {{
+                        window.removeEventListener('message', handler);
+                    }});
+                }};
+                const reveal = function() {{
+                    if (!loaded && !errorShown) {{
+                        loaded = true;
+                        loading.style.display = 'none';
+                        iframe.style.display = 'block';
+                        unlistenAll();
                     }}
                 }};
-                window.addEventListener('message', messageHandler);
-
-                iframe.onload = function() {{
-                    let handshakeComplete = false;
-                    let retryCount = 0;
-                    const maxRetries = 10;
-
-                    const handshakeHandler = function(event) {{
-                        if (event.origin === 'https://ui.perfetto.dev' ||
-                            (event.source === iframe.contentWindow && event.data === 'PONG')) {{
-                            if (event.data && event.data === 'PONG') {{
-                                handshakeComplete = true;
-                                window.removeEventListener('message', handshakeHandler);
-
-                                try {{
-                                    const traceJson = JSON.stringify(traceData, null, 2);
-                                    const encoder = new TextEncoder();
-                                    const buffer = encoder.encode(traceJson).buffer;
-
-                                    iframe.contentWindow.postMessage({{
-                                        perfetto: {{
-                                            buffer: buffer,
-                                            title: 'Chrome Tracing Data',
-                                            fileName: 'trace.json',
-                                        }}
-                                    }}, 'https://ui.perfetto.dev');
-
-                                    setTimeout(() => {{
-                                        if (!loaded && !errorShown) {{
-                                            loaded = true;
-                                            loading.style.display = 'none';
-                                            iframe.style.display = 'block';
-                                            window.removeEventListener('message', messageHandler);
-                                        }}
-                                    }}, 2000);
-                                }} catch (e) {{
-                                    console.error('Error sending trace data:', e);
-                                    if (!errorShown) {{
-                                        errorShown = true;
-                                        showError('Failed to send trace data to Perfetto UI: ' + e.message);
-                                        window.removeEventListener('message', messageHandler);
-                                    }}
-                                }}
-                            }}
+                const fail = function(message) {{
+                    if (!loaded && !errorShown) {{
+                        errorShown = true;
+                        unlistenAll();
+                        showError(message);
+                    }}
+                }};
+
+                // The bundled UI when the server has one, else ui.perfetto.dev.
+                const pickUi = function() {{
+                    if (!LOCAL_UI) {{
+                        return Promise.resolve(REMOTE_UI);
+                    }}
+                    return fetch(LOCAL_UI + 'index.html', {{ method: 'HEAD', cache: 'no-store' }})
+                        .then(function(r) {{ return r.ok ? LOCAL_UI : REMOTE_UI; }})
+                        .catch(function() {{ return REMOTE_UI; }});
+                }};
+
+                // PING the UI until it answers PONG (ready for postMessage),
+                // then call onPong; onGiveUp after MAX_PINGS unanswered pings.
+                const handshake = function(uiOrigin, onPong, onGiveUp) {{
+                    let done = false;
+                    let pings = 0;
+                    const onMessage = function(event) {{
+                        if (done || event.source !== iframe.contentWindow || event.data !== 'PONG') {{
+                            return;
                         }}
+                        done = true;
+                        window.removeEventListener('message', onMessage);
+                        onPong();
                     }};
-                    window.addEventListener('message', handshakeHandler);
-
-                    const sendPing = function() {{
-                        if (!handshakeComplete && retryCount < maxRetries) {{
-                            try {{
-                                if (iframe.contentWindow) {{
-                                    iframe.contentWindow.postMessage('PING', 'https://ui.perfetto.dev');
-                                    retryCount++;
-                                    if (retryCount < maxRetries) {{
-                                        setTimeout(sendPing, 500);
-                                    }} else {{
-                                        console.warn('PING/PONG handshake failed, trying data URL fallback');
-                                        const traceJson = JSON.stringify(traceData, null, 2);
-                                        const base64Data = btoa(unescape(encodeURIComponent(traceJson)));
-                                        const dataUrl = 'data:application/json;base64,' + base64Data;
-                                        iframe.src = 'https://ui.perfetto.dev/#!/?url=' + encodeURIComponent(dataUrl);
-                                        window.removeEventListener('message', handshakeHandler);
-                                    }}
-                                }} else {{
-                                    if (retryCount < maxRetries) {{
-                                        retryCount++;
-                                        setTimeout(sendPing, 500);
-                                    }}
-                                }}
-                            }} catch (e) {{
-                                console.error('Error sending PING:', e);
-                                if (retryCount < maxRetries) {{
-                                    retryCount++;
-                                    setTimeout(sendPing, 500);
-                                }}
+                    listen(onMessage);
+                    const ping = function() {{
+                        if (done) {{
+                            return;
+                        }}
+                        if (pings++ >= MAX_PINGS) {{
+                            done = true;
+                            window.removeEventListener('message', onMessage);
+                            onGiveUp();
+                            return;
+                        }}
+                        try {{
+                            if (iframe.contentWindow) {{
+                                iframe.contentWindow.postMessage('PING', uiOrigin);
                             }}
+                        }} catch (e) {{
+                            console.error('Error sending PING:', e);
                         }}
+                        setTimeout(ping, 500);
                     }};
+                    setTimeout(ping, 1500);
+                }};
 
-                    setTimeout(sendPing, 1500);
+                pickUi().then(function(ui) {{
+                    const uiOrigin = new URL(ui).origin;
 
-                    setTimeout(() => {{
-                        if (!loaded && !errorShown) {{
-                            loaded = true;
-                            loading.style.display = 'none';
-                            iframe.style.display = 'block';
-                            window.removeEventListener('message', messageHandler);
-                            window.removeEventListener('message', handshakeHandler);
+                    listen(function(event) {{
+                        if (event.origin !== uiOrigin || !event.data) {{
+                            return;
                         }}
-                    }}, 10000);
-                }};
+                        const dataStr = typeof event.data === 'string' ? event.data : JSON.stringify(event.data);
+                        if (dataStr.includes('error') || dataStr.includes('Failed')) {{
+                            console.error('Perfetto UI error:', event.data);
+                            fail('Perfetto UI reported an error. Please check the trace data format.');
+                        }} else if (dataStr.includes('loaded') || dataStr.includes('ready')) {{
+                            reveal();
+                        }}
+                    }});
 
-                iframe.onerror = function() {{
-                    if (!loaded && !errorShown) {{
-                        errorShown = true;
-                        showError('Failed to load Perfetto UI');
-                    }}
-                }};
+                    iframe.onload = function() {{
+                        iframe.onload = null;
+                        handshake(uiOrigin, function() {{
+                            traceBuffer.then(function(buffer) {{
+                                iframe.contentWindow.postMessage({{
+                                    perfetto: {{
+                                        buffer: buffer,
+                                        title: 'Chrome Tracing Data',
+                                        fileName: '{file_name}',
+                                    }}
+                                }}, uiOrigin);
+                                setTimeout(reveal, 2000);
+                            }}).catch(function(e) {{
+                                console.error('Error sending trace data:', e);
+                                fail('Failed to send trace data to Perfetto UI: ' + e.message);
+                            }});
+                        }}, function() {{
+                            console.warn('PING/PONG handshake failed, trying data URL fallback');
+                            traceBuffer.then(function(buffer) {{
+                                let binary = '';
+                                new Uint8Array(buffer).forEach(function(b) {{ binary += String.fromCharCode(b); }});
+                                const dataUrl = 'data:{mime};base64,' + btoa(binary);
+                                iframe.src = ui + '#!/?url=' + encodeURIComponent(dataUrl);
+                            }});
+                        }});
+                        setTimeout(reveal, 10000);
+                    }};
+                    iframe.onerror = function() {{
+                        fail('Failed to load Perfetto UI');
+                    }};
+                    iframe.src = ui + '#!/';
+                }});
 
                 function showError(message) {{
                     loading.innerHTML = `
@@ -231,3 +310,36 @@ pub fn get_tracing_viewer_html(trace_json: &str) -> String {
     "#
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proto_viewer_fetches_bytes_without_json_validation() {
+        let html = get_tracing_viewer_html_for_url("http://h/apis/x?format=proto&name=`$a`");
+        assert!(html.contains(r"fetch(`http://h/apis/x?format=proto&name=\`\$a\``)"));
+        assert!(html.contains("r.arrayBuffer()"));
+        assert!(html.contains("fileName: 'trace.perfetto-trace'"));
+        assert!(!html.contains("JSON.stringify(JSON.parse("));
+
+        let html = get_tracing_viewer_html(r#"{"traceEvents": []}"#);
+        assert!(html.contains("JSON.stringify(JSON.parse(`{\"traceEvents\": []}`)"));
+        assert!(html.contains("fileName: 'trace.json'"));
+    }
+
+    #[test]
+    fn local_ui_is_tried_before_the_remote_one() {
+        let html = viewer_html("p", "t.json", "application/json", "http://h:8080/perfetto/");
+        assert!(html.contains("const LOCAL_UI = `http://h:8080/perfetto/`;"));
+        assert!(html.contains(&format!("const REMOTE_UI = '{REMOTE_UI}';")));
+        assert!(html.contains("fetch(LOCAL_UI + 'index.html'"));
+        // One handshake, aimed at whichever UI was picked.
+        assert_eq!(html.matches("postMessage('PING'").count(), 1);
+        assert!(html.contains("postMessage('PING', uiOrigin)"));
+        assert!(!html.contains("'https://ui.perfetto.dev')"));
+
+        let html = viewer_html("p", "t.json", "application/json", "");
+        assert!(html.contains("const LOCAL_UI = ``;"));
+    }
+}